@@ -0,0 +1,88 @@
+//! Editing operations on 5k-node flows; run with `cargo bench --bench flow_edit`.
+//!
+//! Each removal looks up the node's predecessors through the predecessor index, so splicing
+//! out every other node of a chain stays linear in the number of removals instead of
+//! rescanning the whole flow each time.
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use greentic_flow::{
+    builder::FlowBuilder,
+    flow_edit::{DeleteStrategy, MultiplePredecessors},
+    flow_ir::FlowIr,
+};
+use serde_json::json;
+
+const NODES: usize = 5_000;
+
+/// `n0 -> n1 -> ... -> n4999 -> out`, with every tenth node also routing to the last one.
+fn chain(count: usize) -> FlowIr {
+    let mut builder = FlowBuilder::new("chain").kind("messaging");
+    for index in 0..count {
+        builder = builder.node(format!("n{index}"), "emit", json!({}));
+        builder = if index + 1 == count {
+            builder.out()
+        } else if index % 10 == 0 && index + 2 < count {
+            builder
+                .route_on_status("skip", format!("n{}", count - 1))
+                .route_to(format!("n{}", index + 1))
+        } else {
+            builder.route_to(format!("n{}", index + 1))
+        };
+    }
+    builder.build().expect("chain builds")
+}
+
+fn flow_edit(c: &mut Criterion) {
+    let flow = chain(NODES);
+    let mut group = c.benchmark_group("flow_edit_5k");
+    group.sample_size(10);
+
+    group.bench_function("remove_middle_node", |b| {
+        b.iter_batched(
+            || flow.clone(),
+            |mut flow| {
+                flow.remove_node(
+                    &format!("n{}", NODES / 2),
+                    DeleteStrategy::Splice,
+                    MultiplePredecessors::Error,
+                )
+                .expect("node removes");
+                flow
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("splice_out_every_other_node", |b| {
+        b.iter_batched(
+            || flow.clone(),
+            |mut flow| {
+                for index in (1..NODES - 1).step_by(2) {
+                    flow.splice_out_node(&format!("n{index}"))
+                        .expect("node splices out");
+                }
+                flow
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("rename_every_hundredth_node", |b| {
+        b.iter_batched(
+            || flow.clone(),
+            |mut flow| {
+                for index in (0..NODES).step_by(100) {
+                    flow.rename_node(&format!("n{index}"), &format!("renamed_{index}"))
+                        .expect("node renames");
+                }
+                flow
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, flow_edit);
+criterion_main!(benches);
@@ -11,11 +11,13 @@ use std::{fs, path::Path};
 use crate::{
     component_catalog::ComponentCatalog,
     component_catalog::ManifestCatalog,
-    config_flow::run_config_flow,
+    config_flow::{resolve_param_defaults_in_yaml, run_config_flow},
     error::{FlowError, FlowErrorLocation, Result},
     flow_ir::{FlowIr, NodeIr, Route},
     loader::load_ygtc_from_str,
     model::FlowDoc,
+    source_map::SourceMap,
+    splice::NEXT_NODE_PLACEHOLDER,
 };
 
 use self::{
@@ -28,10 +30,15 @@ use self::{
 #[derive(Debug, Clone)]
 pub struct AddStepSpec {
     pub after: Option<String>,
+    /// Insert in front of this node instead, redirecting every route into it (and every
+    /// entrypoint targeting it) to the new node. Exclusive with `after`.
+    pub before: Option<String>,
     pub node_id_hint: Option<String>,
     pub node: Value,
     pub allow_cycles: bool,
     pub require_placeholder: bool,
+    /// Routing for the new node when neither the node nor its anchor provides any.
+    pub default_routing: Option<Vec<Route>>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +47,8 @@ pub struct AddStepPlan {
     pub new_node: NodeIr,
     pub anchor_old_routing: Vec<Route>,
     pub insert_before_entrypoint: bool,
+    /// `before` insert: `anchor` is the node the new one goes in front of.
+    pub insert_before: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -124,6 +133,18 @@ pub fn plan_add_step(
 ) -> std::result::Result<AddStepPlan, Vec<Diagnostic>> {
     let mut diags = Vec::new();
 
+    if let (Some(after), Some(before)) = (&spec.after, &spec.before) {
+        diags.push(Diagnostic {
+            code: "ADD_STEP_ANCHOR_CONFLICT",
+            message: format!("insert either after '{after}' or before '{before}', not both"),
+            location: Some("nodes".to_string()),
+        });
+        return Err(diags);
+    }
+    if let Some(before) = spec.before.clone() {
+        return plan_insert_before(flow, spec, &before);
+    }
+
     let anchor_source = match resolve_anchor(flow, spec.after.as_deref()) {
         Ok(anchor) => anchor,
         Err(msg) => {
@@ -187,7 +208,7 @@ pub fn plan_add_step(
         .or(Some(normalized.operation.as_str()));
     let new_node_id = generate_node_id(hint, &anchor, flow.nodes.keys().map(|k| k.as_str()));
 
-    let routing = rewrite_placeholder_routes(
+    let mut routing = rewrite_placeholder_routes(
         normalized.routing.clone(),
         &anchor_old_routing,
         spec.allow_cycles,
@@ -202,10 +223,15 @@ pub fn plan_add_step(
         }]
     })?;
 
+    if routing.is_empty()
+        && let Some(default_routing) = spec.default_routing
+    {
+        routing = default_routing;
+    }
     if routing.is_empty() {
         return Err(vec![Diagnostic {
             code: "ADD_STEP_ROUTING_MISSING",
-            message: "add-step requires at least one routing target; use --routing-* or include routing in config flow output".to_string(),
+            message: "add-step requires at least one routing target; use --routing-*, include routing in config flow output, or set default_routing in .greentic-flow.toml".to_string(),
             location: Some(format!("nodes.{new_node_id}.routing")),
         }]);
     }
@@ -217,6 +243,9 @@ pub fn plan_add_step(
         output: serde_json::Value::Object(Default::default()),
         routing,
         telemetry: normalized.telemetry.clone(),
+        limits: None,
+        feature: None,
+        doc: None,
     };
 
     Ok(AddStepPlan {
@@ -224,11 +253,91 @@ pub fn plan_add_step(
         new_node,
         anchor_old_routing,
         insert_before_entrypoint,
+        insert_before: false,
+    })
+}
+
+/// Plan for [`AddStepSpec::before`]: the new node's `NEXT_NODE_PLACEHOLDER` routes (or, without
+/// routing, a plain route) continue to `before`, keeping their status and docs.
+fn plan_insert_before(
+    flow: &FlowIr,
+    spec: AddStepSpec,
+    before: &str,
+) -> std::result::Result<AddStepPlan, Vec<Diagnostic>> {
+    if !flow.nodes.contains_key(before) {
+        return Err(vec![Diagnostic {
+            code: "ADD_STEP_ANCHOR_MISSING",
+            message: format!("anchor node '{before}' not found"),
+            location: Some("nodes".to_string()),
+        }]);
+    }
+    if let Some(hint) = spec.node_id_hint.as_deref()
+        && is_placeholder_value(hint)
+    {
+        return Err(vec![Diagnostic {
+            code: "ADD_STEP_NODE_ID_PLACEHOLDER",
+            message: format!(
+                "Config flow emitted placeholder node id '{hint}'; update greentic-component to emit the component name."
+            ),
+            location: Some("add_step.node_id".to_string()),
+        }]);
+    }
+    let normalized = normalize_node_map(spec.node).map_err(|e| {
+        vec![Diagnostic {
+            code: "ADD_STEP_NODE_INVALID",
+            message: e.to_string(),
+            location: Some("add_step.node".to_string()),
+        }]
+    })?;
+
+    let hint = spec
+        .node_id_hint
+        .as_deref()
+        .or(Some(normalized.operation.as_str()));
+    let new_node_id = generate_node_id(hint, before, flow.nodes.keys().map(|k| k.as_str()));
+    let mut replaced = false;
+    let mut routing = normalized.routing.clone();
+    for route in &mut routing {
+        if route.to.as_deref() == Some(NEXT_NODE_PLACEHOLDER) {
+            route.to = Some(before.to_string());
+            replaced = true;
+        }
+    }
+    if !replaced && spec.require_placeholder {
+        return Err(vec![Diagnostic {
+            code: "ADD_STEP_ROUTING_INVALID",
+            message: "Config flow output missing NEXT_NODE_PLACEHOLDER; cannot preserve anchor routing semantics.".to_string(),
+            location: Some(format!("nodes.{new_node_id}.routing")),
+        }]);
+    }
+    if routing.is_empty() {
+        routing = vec![Route {
+            to: Some(before.to_string()),
+            ..Route::default()
+        }];
+    }
+
+    Ok(AddStepPlan {
+        anchor: before.to_string(),
+        new_node: NodeIr {
+            id: new_node_id,
+            operation: normalized.operation,
+            payload: normalized.payload,
+            output: Value::Object(Default::default()),
+            routing,
+            telemetry: normalized.telemetry,
+            limits: None,
+            feature: None,
+            doc: None,
+        },
+        anchor_old_routing: Vec::new(),
+        insert_before_entrypoint: false,
+        insert_before: true,
     })
 }
 
 pub fn apply_plan(flow: &FlowIr, plan: AddStepPlan, allow_cycles: bool) -> Result<FlowIr> {
-    let mut nodes: IndexMap<String, NodeIr> = flow.nodes.clone();
+    let mut nodes: IndexMap<String, NodeIr> = flow.nodes.map.clone();
     if nodes.contains_key(&plan.new_node.id) {
         return Err(FlowError::Internal {
             message: format!("node '{}' already exists", plan.new_node.id),
@@ -251,7 +360,53 @@ pub fn apply_plan(flow: &FlowIr, plan: AddStepPlan, allow_cycles: bool) -> Resul
             schema_version: flow.schema_version,
             entrypoints,
             meta: flow.meta.clone(),
-            nodes,
+            nodes: nodes.into(),
+            spans: SourceMap::default(),
+        });
+    }
+
+    if plan.insert_before {
+        let new_id = plan.new_node.id.clone();
+        let mut new_nodes = IndexMap::new();
+        for (id, mut node) in nodes.into_iter() {
+            for route in &mut node.routing {
+                if route.to.as_deref() == Some(plan.anchor.as_str()) {
+                    route.to = Some(new_id.clone());
+                }
+            }
+            if id == plan.anchor {
+                new_nodes.insert(new_id.clone(), plan.new_node.clone());
+            }
+            new_nodes.insert(id, node);
+        }
+
+        let mut entrypoints = flow.entrypoints.clone();
+        for (_name, target) in entrypoints.iter_mut() {
+            if target == &plan.anchor {
+                *target = new_id.clone();
+            }
+        }
+        let start = flow.start.as_ref().map(|start| {
+            if start == &plan.anchor {
+                new_id.clone()
+            } else {
+                start.clone()
+            }
+        });
+
+        return Ok(FlowIr {
+            id: flow.id.clone(),
+            title: flow.title.clone(),
+            description: flow.description.clone(),
+            kind: flow.kind.clone(),
+            start,
+            parameters: flow.parameters.clone(),
+            tags: flow.tags.clone(),
+            schema_version: flow.schema_version,
+            entrypoints,
+            meta: flow.meta.clone(),
+            nodes: new_nodes.into(),
+            spans: SourceMap::default(),
         });
     }
 
@@ -288,7 +443,8 @@ pub fn apply_plan(flow: &FlowIr, plan: AddStepPlan, allow_cycles: bool) -> Resul
             schema_version: flow.schema_version,
             entrypoints,
             meta: flow.meta.clone(),
-            nodes: new_nodes,
+            nodes: new_nodes.into(),
+            spans: SourceMap::default(),
         });
     }
 
@@ -329,7 +485,8 @@ pub fn apply_plan(flow: &FlowIr, plan: AddStepPlan, allow_cycles: bool) -> Resul
         schema_version: flow.schema_version,
         entrypoints: flow.entrypoints.clone(),
         meta: flow.meta.clone(),
-        nodes: reordered,
+        nodes: reordered.into(),
+        spans: SourceMap::default(),
     })
 }
 
@@ -457,15 +614,18 @@ pub fn add_step_from_config_flow(
         location: FlowErrorLocation::at_path(config_flow_path.display().to_string())
             .with_source_path(Some(config_flow_path)),
     })?;
+    let config_yaml = resolve_param_defaults_in_yaml(&config_yaml, &flow_ir.parameters)?;
     let output = run_config_flow(&config_yaml, schema_path, answers, None)?;
     let node_id_hint = normalize_node_id_hint(Some(output.node_id.clone()), &output.node);
 
     let spec = AddStepSpec {
         after,
+        before: None,
         node_id_hint,
         node: output.node.clone(),
         allow_cycles,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan =
@@ -4,7 +4,7 @@ use serde_json::{Map, Value, json};
 
 use crate::{
     component_catalog::ComponentCatalog, config_flow::run_config_flow, error::Result,
-    loader::load_ygtc_from_str_with_schema,
+    loader::load_ygtc_from_str_with_schema, state_keys::StateKeyCollision,
 };
 
 use super::normalize::normalize_node_map;
@@ -25,10 +25,20 @@ pub enum AddStepModeInput {
     },
 }
 
+/// Node produced by [`materialize_node`].
+#[derive(Debug, Clone)]
+pub struct MaterializedNode {
+    /// Suggested node id.
+    pub hint: Option<String>,
+    pub node: Value,
+    /// State key collisions from running a config flow (config mode only).
+    pub state_key_collisions: Vec<StateKeyCollision>,
+}
+
 pub fn materialize_node(
     mode: AddStepModeInput,
     _catalog: &dyn ComponentCatalog,
-) -> Result<(Option<String>, Value)> {
+) -> Result<MaterializedNode> {
     match mode {
         AddStepModeInput::Default {
             operation,
@@ -48,7 +58,11 @@ pub fn materialize_node(
             let value = Value::Object(node.clone());
             // Ensure shape is valid up front.
             let normalized = normalize_node_map(value.clone())?;
-            Ok((Some(normalized.operation.clone()), Value::Object(node)))
+            Ok(MaterializedNode {
+                hint: Some(normalized.operation.clone()),
+                node: Value::Object(node),
+                state_key_collisions: Vec::new(),
+            })
         }
         AddStepModeInput::Config {
             config_flow,
@@ -65,7 +79,11 @@ pub fn materialize_node(
                 hint = None;
             }
             let _ = manifest_path;
-            Ok((hint, output.node))
+            Ok(MaterializedNode {
+                hint,
+                node: output.node,
+                state_key_collisions: output.state_key_collisions,
+            })
         }
     }
 }
@@ -91,13 +91,17 @@ pub fn normalize_node_map(value: Value) -> Result<NormalizedNode> {
         });
     }
 
+    if map.len() > 1 {
+        let keys: Vec<String> = map.keys().map(|k| format!("'{k}'")).collect();
+        return Err(FlowError::Internal {
+            message: format!(
+                "node must have exactly one operation key, found multiple: {}",
+                keys.join(", ")
+            ),
+            location: FlowErrorLocation::at_path("node".to_string()),
+        });
+    }
     for (key, val) in map {
-        if op_key.is_some() {
-            return Err(FlowError::Internal {
-                message: "node must have exactly one operation key".to_string(),
-                location: FlowErrorLocation::at_path("node".to_string()),
-            });
-        }
         op_key = Some(key);
         op_value = Some(val);
     }
@@ -175,7 +179,7 @@ fn parse_routes(raw: Value) -> Result<Vec<Route>> {
         })?;
         for key in obj.keys() {
             match key.as_str() {
-                "to" | "out" | "status" | "reply" => {}
+                "to" | "out" | "status" | "reply" | "label" | "description" => {}
                 other => {
                     return Err(FlowError::Internal {
                         message: format!("unsupported routing key '{other}'"),
@@ -192,6 +196,14 @@ fn parse_routes(raw: Value) -> Result<Vec<Route>> {
                 .and_then(Value::as_str)
                 .map(|s| s.to_string()),
             reply: obj.get("reply").and_then(Value::as_bool).unwrap_or(false),
+            label: obj
+                .get("label")
+                .and_then(Value::as_str)
+                .map(|s| s.to_string()),
+            description: obj
+                .get("description")
+                .and_then(Value::as_str)
+                .map(|s| s.to_string()),
         });
     }
 
@@ -0,0 +1,214 @@
+//! Upgrade advisories for pinned components, classified by how their manifest schema changed.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    component_catalog::normalize_manifest_value,
+    schema_diff::{SchemaChange, diff_manifests, relevant_changes},
+};
+
+/// Published versions of components, keyed by reference without tag or digest
+/// (`oci://ghcr.io/acme/charge`).
+///
+/// ```json
+/// { "components": { "oci://ghcr.io/acme/charge": [
+///     { "version": "1.2.0", "digest": "sha256:...", "manifest": "charge-1.2.0.manifest.json" }
+/// ] } }
+/// ```
+///
+/// `manifest` paths are relative to the catalog file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReleaseCatalog {
+    pub components: BTreeMap<String, Vec<ComponentRelease>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ComponentRelease {
+    pub version: String,
+    pub digest: String,
+    /// Component manifest of this release, used to classify the upgrade.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<PathBuf>,
+}
+
+impl ReleaseCatalog {
+    pub fn load_from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("unable to read release catalog at {}", path.display()))?;
+        let mut catalog: Self = serde_json::from_str(&text)
+            .with_context(|| format!("invalid release catalog JSON in {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        for release in catalog.components.values_mut().flatten() {
+            if let Some(manifest) = &mut release.manifest
+                && manifest.is_relative()
+            {
+                *manifest = base.join(&*manifest);
+            }
+        }
+        Ok(catalog)
+    }
+}
+
+/// How risky moving a node to a candidate release is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UpgradeClass {
+    /// The schema the node is validated against did not narrow.
+    Safe,
+    /// The schema narrowed without breaking this node's payload, or a manifest is unavailable.
+    NeedsReview,
+    /// The node's current payload would fail under the new schema.
+    Breaking,
+}
+
+/// A node pinned to an older release than the catalog offers.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Advisory {
+    pub flow: String,
+    pub node_id: String,
+    /// Reference without tag or digest, as keyed in the [`ReleaseCatalog`].
+    pub component: String,
+    pub current_version: String,
+    pub current_digest: String,
+    /// Newer releases, oldest first.
+    pub candidates: Vec<UpgradeCandidate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UpgradeCandidate {
+    pub version: String,
+    pub digest: String,
+    pub classification: UpgradeClass,
+    /// Schema changes that apply to the node's operation.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<SchemaChange>,
+    /// Why the node's payload breaks (for [`UpgradeClass::Breaking`]).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub breaks: Vec<String>,
+    /// Command that re-pins the node to this release.
+    pub apply: String,
+}
+
+/// A pinned component binding to check against the catalog.
+#[derive(Clone, Copy, Debug)]
+pub struct PinnedNode<'a> {
+    /// Flow path, as it should appear in the apply command.
+    pub flow: &'a str,
+    pub node_id: &'a str,
+    pub reference: &'a str,
+    pub digest: &'a str,
+    pub operation: &'a str,
+    /// Node config, as validated against the component schema.
+    pub payload: &'a Value,
+}
+
+impl Advisory {
+    /// The newest candidate; the one dashboards usually surface.
+    pub fn latest(&self) -> Option<&UpgradeCandidate> {
+        self.candidates.last()
+    }
+}
+
+/// Advise on `node` when the catalog lists its pinned digest and newer versions of it.
+///
+/// Nodes whose digest is not in the catalog, or whose versions do not parse as semver, get no
+/// advisory: without a known current release there is nothing to compare against.
+pub fn advise(catalog: &ReleaseCatalog, node: &PinnedNode<'_>) -> Option<Advisory> {
+    let component = reference_base(node.reference);
+    let releases = catalog.components.get(component)?;
+    let current = releases
+        .iter()
+        .find(|release| release.digest == node.digest)?;
+    let current_version = semver::Version::parse(&current.version).ok()?;
+    let mut newer: Vec<(semver::Version, &ComponentRelease)> = releases
+        .iter()
+        .filter_map(|release| Some((semver::Version::parse(&release.version).ok()?, release)))
+        .filter(|(version, _)| *version > current_version)
+        .collect();
+    if newer.is_empty() {
+        return None;
+    }
+    newer.sort_by(|a, b| a.0.cmp(&b.0));
+    let current_manifest = current.manifest.as_deref().and_then(read_manifest);
+    let candidates = newer
+        .into_iter()
+        .map(|(_, release)| {
+            let manifest = release.manifest.as_deref().and_then(read_manifest);
+            let (classification, changes, breaks) = match (&current_manifest, &manifest) {
+                (Some(old), Some(new)) => classify(old, new, node.operation, node.payload),
+                _ => (UpgradeClass::NeedsReview, Vec::new(), Vec::new()),
+            };
+            UpgradeCandidate {
+                version: release.version.clone(),
+                digest: release.digest.clone(),
+                classification,
+                changes,
+                breaks,
+                apply: format!(
+                    "greentic-flow bind-component --flow {} --step {} --component {component}@{} --write",
+                    node.flow, node.node_id, release.digest
+                ),
+            }
+        })
+        .collect();
+    Some(Advisory {
+        flow: node.flow.to_string(),
+        node_id: node.node_id.to_string(),
+        component: component.to_string(),
+        current_version: current.version.clone(),
+        current_digest: current.digest.clone(),
+        candidates,
+    })
+}
+
+fn classify(
+    old: &Value,
+    new: &Value,
+    operation: &str,
+    payload: &Value,
+) -> (UpgradeClass, Vec<SchemaChange>, Vec<String>) {
+    let all = diff_manifests(old, new);
+    let changes: Vec<SchemaChange> = relevant_changes(old, &all, operation)
+        .into_iter()
+        .cloned()
+        .collect();
+    let breaks: Vec<String> = changes
+        .iter()
+        .filter_map(|change| change.breaks(payload))
+        .collect();
+    let classification = if !breaks.is_empty() {
+        UpgradeClass::Breaking
+    } else if !changes.is_empty() {
+        UpgradeClass::NeedsReview
+    } else {
+        UpgradeClass::Safe
+    };
+    (classification, changes, breaks)
+}
+
+fn read_manifest(path: &Path) -> Option<Value> {
+    let text = fs::read_to_string(path).ok()?;
+    let mut manifest = serde_json::from_str(&text).ok()?;
+    normalize_manifest_value(&mut manifest);
+    Some(manifest)
+}
+
+/// Strip `@<digest>` and a trailing `:<tag>` from a component reference.
+pub fn reference_base(reference: &str) -> &str {
+    let reference = reference
+        .split_once('@')
+        .map_or(reference, |(base, _)| base);
+    let name_start = reference.rfind('/').map_or(0, |idx| idx + 1);
+    match reference[name_start..].rfind(':') {
+        Some(idx) => &reference[..name_start + idx],
+        None => reference,
+    }
+}
@@ -0,0 +1,235 @@
+//! Per-node wizard answer provenance, kept in the flow's resolve sidecar.
+//!
+//! `FlowResolveV1` has no room for it, so provenance lives under a top-level
+//! `answers_provenance` key of `<flow>.resolve.json` that sidecar writes carry over (see
+//! [`sidecar_json`]):
+//!
+//! ```json
+//! "answers_provenance": {
+//!   "salt": "<64 hex chars>",
+//!   "nodes": {
+//!     "fetch": {
+//!       "url": { "source": "user", "value": "https://example.com" },
+//!       "api_token": { "source": "env", "value_hash": "<keyed blake3>" }
+//!     }
+//!   }
+//! }
+//! ```
+
+use anyhow::{Context, Result, anyhow};
+use greentic_types::flow_resolve::{
+    FLOW_RESOLVE_SCHEMA_VERSION, FlowResolveV1, read_flow_resolve, sidecar_path_for_flow,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::{cancel, flow_meta::is_secret_answer_key};
+
+pub const ANSWERS_PROVENANCE_KEY: &str = "answers_provenance";
+
+/// Where a wizard answer value came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerSource {
+    /// Filled from the component's QA defaults.
+    Default,
+    /// Provided explicitly (`--answers`, answers file, or an interactive prompt).
+    User,
+    /// Loaded from a named pack preset.
+    Preset,
+    /// Read from a `GREENTIC_FLOW_ANSWER_<QUESTION>` environment variable.
+    Env,
+    /// Seeded from another node via `--defaults-from`.
+    Sibling,
+    /// Filled from the manifest's `operations[].defaults` for the node's operation.
+    OperationDefault,
+}
+
+/// Record per-answer provenance for a node in the flow's resolve sidecar, replacing what was
+/// recorded for it before.
+///
+/// Secret answers (see [`is_secret_answer_key`], plus `secret_keys` from the node's
+/// `secrets_hints`) keep only a blake3 hash keyed with the sidecar's random salt.
+pub fn record_answer_provenance(
+    flow_path: &Path,
+    node_id: &str,
+    answers: &BTreeMap<String, Value>,
+    sources: &BTreeMap<String, AnswerSource>,
+    secret_keys: &[String],
+) -> Result<()> {
+    let (resolve, mut provenance) = read_sidecar(flow_path)?;
+    let key = salt_key(&mut provenance)?;
+    let mut entries = Map::new();
+    for (name, value) in answers {
+        let source = sources.get(name).copied().unwrap_or(AnswerSource::User);
+        let mut entry = Map::new();
+        entry.insert("source".to_string(), serde_json::to_value(source)?);
+        if is_secret_answer_key(name) || secret_keys.iter().any(|hint| hint == name) {
+            let hash = blake3::keyed_hash(&key, value.to_string().as_bytes());
+            entry.insert(
+                "value_hash".to_string(),
+                Value::String(hash.to_hex().to_string()),
+            );
+        } else {
+            entry.insert("value".to_string(), value.clone());
+        }
+        entries.insert(name.clone(), Value::Object(entry));
+    }
+    nodes_map(&mut provenance).insert(node_id.to_string(), Value::Object(entries));
+    write_sidecar(flow_path, &resolve, &provenance)
+}
+
+/// The recorded answer sources for a node (empty when none were recorded).
+pub fn answer_provenance(
+    flow_path: &Path,
+    node_id: &str,
+) -> Result<BTreeMap<String, AnswerSource>> {
+    let provenance = read_provenance(&sidecar_path_for_flow(flow_path))?;
+    let Some(entries) = provenance
+        .get("nodes")
+        .and_then(|nodes| nodes.get(node_id))
+        .and_then(Value::as_object)
+    else {
+        return Ok(BTreeMap::new());
+    };
+    Ok(entries
+        .iter()
+        .filter_map(|(key, entry)| {
+            let source = serde_json::from_value(entry.get("source")?.clone()).ok()?;
+            Some((key.clone(), source))
+        })
+        .collect())
+}
+
+/// Move a node's recorded provenance to `to`, if it has any.
+pub fn rename_answer_provenance(flow_path: &Path, from: &str, to: &str) -> Result<()> {
+    update_nodes(flow_path, |nodes| match nodes.remove(from) {
+        Some(entry) => {
+            nodes.insert(to.to_string(), entry);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Drop a node's recorded provenance, if it has any.
+pub fn remove_answer_provenance(flow_path: &Path, node_id: &str) -> Result<()> {
+    update_nodes(flow_path, |nodes| nodes.remove(node_id).is_some())
+}
+
+/// The sidecar text for `doc`, keeping the answer provenance already stored at `sidecar_path`,
+/// so rewriting the typed sidecar does not drop it. Fails when the existing sidecar cannot be
+/// parsed, rather than writing over the provenance and salt it holds.
+pub fn sidecar_json(sidecar_path: &Path, doc: &FlowResolveV1) -> Result<String> {
+    let provenance = read_provenance(sidecar_path)?;
+    render_sidecar(doc, &provenance)
+}
+
+#[derive(Serialize)]
+struct SidecarWithProvenance<'a> {
+    #[serde(flatten)]
+    resolve: &'a FlowResolveV1,
+    #[serde(rename = "answers_provenance", skip_serializing_if = "Option::is_none")]
+    provenance: Option<&'a Map<String, Value>>,
+}
+
+fn render_sidecar(resolve: &FlowResolveV1, provenance: &Map<String, Value>) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&SidecarWithProvenance {
+        resolve,
+        provenance: (!provenance.is_empty()).then_some(provenance),
+    })?)
+}
+
+fn update_nodes(
+    flow_path: &Path,
+    edit: impl FnOnce(&mut Map<String, Value>) -> bool,
+) -> Result<()> {
+    if !sidecar_path_for_flow(flow_path).exists() {
+        return Ok(());
+    }
+    let (resolve, mut provenance) = read_sidecar(flow_path)?;
+    let Some(nodes) = provenance.get_mut("nodes").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+    if edit(nodes) {
+        write_sidecar(flow_path, &resolve, &provenance)?;
+    }
+    Ok(())
+}
+
+fn nodes_map(provenance: &mut Map<String, Value>) -> &mut Map<String, Value> {
+    let nodes = provenance
+        .entry("nodes".to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !nodes.is_object() {
+        *nodes = Value::Object(Map::new());
+    }
+    nodes
+        .as_object_mut()
+        .expect("provenance nodes is an object")
+}
+
+/// The sidecar's hashing key, generating and storing a random one on first use.
+fn salt_key(provenance: &mut Map<String, Value>) -> Result<[u8; 32]> {
+    if let Some(salt) = provenance.get("salt").and_then(Value::as_str)
+        && let Ok(hash) = blake3::Hash::from_hex(salt)
+    {
+        return Ok(*hash.as_bytes());
+    }
+    let mut key = [0u8; 32];
+    getrandom::fill(&mut key).map_err(|err| anyhow!("generate provenance salt: {err}"))?;
+    provenance.insert(
+        "salt".to_string(),
+        Value::String(blake3::Hash::from_bytes(key).to_hex().to_string()),
+    );
+    Ok(key)
+}
+
+/// The `answers_provenance` section of the sidecar at `path` (empty when there is none).
+fn read_provenance(path: &Path) -> Result<Map<String, Value>> {
+    if !path.exists() {
+        return Ok(Map::new());
+    }
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let mut value: Value =
+        serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+    Ok(
+        match value
+            .as_object_mut()
+            .and_then(|root| root.remove(ANSWERS_PROVENANCE_KEY))
+        {
+            Some(Value::Object(section)) => section,
+            _ => Map::new(),
+        },
+    )
+}
+
+fn read_sidecar(flow_path: &Path) -> Result<(FlowResolveV1, Map<String, Value>)> {
+    let path = sidecar_path_for_flow(flow_path);
+    if !path.exists() {
+        let flow = flow_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "flow.ygtc".to_string());
+        let resolve = FlowResolveV1 {
+            schema_version: FLOW_RESOLVE_SCHEMA_VERSION,
+            flow,
+            nodes: Default::default(),
+        };
+        return Ok((resolve, Map::new()));
+    }
+    let resolve = read_flow_resolve(&path).map_err(|err| anyhow!(err.to_string()))?;
+    Ok((resolve, read_provenance(&path)?))
+}
+
+fn write_sidecar(
+    flow_path: &Path,
+    resolve: &FlowResolveV1,
+    provenance: &Map<String, Value>,
+) -> Result<()> {
+    let path = sidecar_path_for_flow(flow_path);
+    cancel::replace_file_atomically(&path, render_sidecar(resolve, provenance)?.as_bytes())
+}
@@ -0,0 +1,172 @@
+//! Versioning of the interface an `http` flow exposes to external consumers.
+//!
+//! The interface is the flow's named entrypoints plus the request schema of each, declared
+//! under `meta.http.request_schemas.<entrypoint>` as JSON Schema. `meta.api_version` is a semver
+//! version for that interface:
+//!
+//! ```yaml
+//! type: http
+//! entrypoints:
+//!   "http:/orders": create_order
+//! meta:
+//!   api_version: 1.4.0
+//!   http:
+//!     request_schemas:
+//!       "http:/orders": { type: object, required: [sku], properties: { sku: { type: string } } }
+//! ```
+//!
+//! [`breaking_api_changes`] compares the surface of the last released flow with the current
+//! one; removing an entrypoint or narrowing its request schema is breaking and calls for a major
+//! version bump (a minor bump while the major version is 0, as in Cargo).
+
+use anyhow::{Context, Result};
+use semver::Version;
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{
+    flow_bundle::{FlowBundle, blake3_hex, canonicalize_json},
+    loader::load_ygtc_from_str,
+    model::FlowDoc,
+    schema_diff::diff_schemas,
+    source_map::pointer_token,
+};
+
+/// `meta` key holding the interface version.
+pub const API_VERSION_META_KEY: &str = "api_version";
+
+/// The consumer-facing interface of a flow.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiSurface {
+    pub flow_id: String,
+    pub kind: String,
+    /// `meta.api_version` as written; see [`parse_api_version`].
+    pub api_version: Option<Value>,
+    /// Named entrypoints (the implicit `default` entry is not part of the interface).
+    pub entrypoints: Vec<String>,
+    /// Request schema per entrypoint, from `meta.http.request_schemas`.
+    pub request_schemas: BTreeMap<String, Value>,
+    /// BLAKE3 hash of the canonical flow JSON, as recorded in bundles.
+    pub hash_blake3: String,
+}
+
+/// One breaking difference between a released surface and the current one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiChange {
+    pub entrypoint: String,
+    /// JSON pointer into the current document (`/entrypoints` for a removed entrypoint).
+    pub path: String,
+    pub message: String,
+}
+
+impl ApiSurface {
+    pub fn from_doc(doc: &FlowDoc) -> Self {
+        let json = serde_json::to_value(doc).unwrap_or(Value::Null);
+        Self::from_flow_json(&json)
+    }
+
+    /// Surface of a serialized flow document, such as a bundle's `json`.
+    pub fn from_flow_json(json: &Value) -> Self {
+        let str_at = |key: &str| json.get(key).and_then(Value::as_str).unwrap_or_default();
+        let meta = json.get("meta");
+        let canonical = serde_json::to_vec(&canonicalize_json(json)).unwrap_or_default();
+        Self {
+            flow_id: str_at("id").to_string(),
+            kind: str_at("type").to_string(),
+            api_version: meta
+                .and_then(|meta| meta.get(API_VERSION_META_KEY))
+                .cloned(),
+            entrypoints: json
+                .get("entrypoints")
+                .and_then(Value::as_object)
+                .map(|entrypoints| entrypoints.keys().cloned().collect())
+                .unwrap_or_default(),
+            request_schemas: meta
+                .and_then(|meta| meta.pointer("/http/request_schemas"))
+                .and_then(Value::as_object)
+                .map(|schemas| {
+                    schemas
+                        .iter()
+                        .map(|(name, schema)| (name.clone(), schema.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            hash_blake3: blake3_hex(canonical),
+        }
+    }
+
+    /// Read a released flow: a bundle JSON (as printed by `doctor --json` under `bundle`), a
+    /// flow document as JSON, or a `.ygtc` file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        if let Ok(json) = serde_json::from_str::<Value>(&text) {
+            let bundle = json.get("bundle").unwrap_or(&json);
+            if bundle.get("hash_blake3").is_some() {
+                let bundle: FlowBundle = serde_json::from_value(bundle.clone())
+                    .with_context(|| format!("parse bundle {}", path.display()))?;
+                return Ok(Self {
+                    hash_blake3: bundle.hash_blake3.clone(),
+                    ..Self::from_flow_json(&bundle.json)
+                });
+            }
+            return Ok(Self::from_flow_json(&json));
+        }
+        let doc = load_ygtc_from_str(&text)
+            .with_context(|| format!("load released flow {}", path.display()))?;
+        Ok(Self::from_doc(&doc))
+    }
+
+    /// The parsed `meta.api_version`, `None` when unset.
+    pub fn version(&self) -> Option<std::result::Result<Version, String>> {
+        self.api_version.as_ref().map(parse_api_version)
+    }
+}
+
+/// Parse a `meta.api_version` value: a semver string such as `1.4.0`.
+pub fn parse_api_version(value: &Value) -> std::result::Result<Version, String> {
+    let text = value
+        .as_str()
+        .ok_or_else(|| "api_version must be a semver string such as \"1.0.0\"".to_string())?;
+    Version::parse(text).map_err(|err| format!("api_version '{text}' is not semver: {err}"))
+}
+
+/// Whether going from `released` to `current` allows breaking changes.
+pub fn is_breaking_bump(released: &Version, current: &Version) -> bool {
+    if released.major == 0 && current.major == 0 {
+        current.minor > released.minor
+    } else {
+        current.major > released.major
+    }
+}
+
+/// Entrypoints the released surface had and the current one lost, and request schemas that
+/// accept less than before. A request schema added where there was none is not breaking.
+pub fn breaking_api_changes(released: &ApiSurface, current: &ApiSurface) -> Vec<ApiChange> {
+    let mut changes = Vec::new();
+    for name in &released.entrypoints {
+        if !current.entrypoints.contains(name) {
+            changes.push(ApiChange {
+                entrypoint: name.clone(),
+                path: "/entrypoints".to_string(),
+                message: format!("entrypoint '{name}' was removed"),
+            });
+        }
+    }
+    for (name, old_schema) in &released.request_schemas {
+        if !current.entrypoints.contains(name) {
+            continue;
+        }
+        let base = format!("/meta/http/request_schemas/{}", pointer_token(name));
+        let Some(new_schema) = current.request_schemas.get(name) else {
+            continue;
+        };
+        for change in diff_schemas(old_schema, new_schema) {
+            changes.push(ApiChange {
+                entrypoint: name.clone(),
+                path: format!("{base}{}", change.pointer),
+                message: format!("request schema of '{name}': {}", change.message),
+            });
+        }
+    }
+    changes
+}
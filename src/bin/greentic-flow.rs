@@ -1,14 +1,23 @@
 use anyhow::{Context, Result, anyhow};
 use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use include_dir::{Dir, include_dir};
+use similar::TextDiff;
 use std::{
+    cell::RefCell,
     collections::{BTreeMap, BTreeSet, HashMap},
     env,
     ffi::OsStr,
     fs,
-    io::{self, Read, Write},
+    io::{self, IsTerminal, Read, Write},
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
-    sync::{Mutex, OnceLock},
+    sync::{
+        Arc, Mutex, Once, OnceLock,
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
 };
 
 const EMBEDDED_FLOW_SCHEMA: &str = include_str!(concat!(
@@ -19,32 +28,94 @@ const EMBEDDED_I18N_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/i18n");
 const EMBEDDED_WIZARD_I18N_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/i18n/wizard");
 
 use greentic_distributor_client::{
-    DistClient, DistributorClient, DistributorClientConfig, DistributorEnvironmentId, EnvId,
+    DistOptions, DistributorClient, DistributorClientConfig, DistributorEnvironmentId, EnvId,
     HttpDistributorClient, ResolveComponentRequest, TenantCtx, TenantId,
 };
 use greentic_flow::{
     add_step::{
         AddStepSpec, apply_and_validate,
-        modes::{AddStepModeInput, materialize_node},
+        modes::{AddStepModeInput, MaterializedNode, materialize_node},
         normalize::normalize_node_map,
         normalize_node_id_hint, plan_add_step,
     },
+    advisories::{Advisory, PinnedNode, ReleaseCatalog, UpgradeClass, advise},
+    answer_provenance::{self, AnswerSource},
     answers,
-    component_catalog::ManifestCatalog,
+    api_compat::ApiSurface,
+    bundle_release::{
+        BUNDLE_FILE_SUFFIX, BundleRelease, ReleaseChannel, bundle_path_for_flow, load_bundle,
+        verify_deployed_bundle,
+    },
+    bundle_signature::{
+        BundleSignature, sign_bundle, signature_path_for_flow, signing_key_from_hex,
+        verify_bundle_signature, verifying_key_from_hex,
+    },
+    cancel,
+    cas::{ArtifactStore, CAS_DIR_ENV, cas_local_path, cas_reference, parse_cas_reference},
+    compile_flow,
+    component_catalog::{ManifestCatalog, normalize_manifest_value},
+    component_resolver::{FixtureResolver, ResolverRegistry, fixture_key, reference_scheme},
     component_schema::{
-        is_effectively_empty_schema, jsonschema_options_with_base, resolve_input_schema,
-        schema_guidance, validate_payload_against_schema,
+        apply_operation_defaults, is_effectively_empty_schema, jsonschema_options_with_base,
+        manifest_operation_names, manifest_operation_schema_hash, resolve_input_schema,
+        resolve_operation_defaults, resolve_output_schema, schema_guidance,
+        validate_payload_against_schema,
+    },
+    component_view::ComponentView,
+    config_flow::{resolve_param_defaults, resolve_param_defaults_in_yaml, run_config_flow},
+    config_store, contracts,
+    convert_type::{
+        KindConversion, KindIssue, Resolution, apply_kind_conversion, plan_kind_conversion,
     },
-    config_flow::run_config_flow,
-    contracts,
+    dist_cache::{DistCache, DistCacheEntry, EntryStatus},
+    edit_plan::{FlowEditPlan, PlanBinding, apply_edit_plan},
+    env_matrix::{DEFAULT_ENVS_DIR, EnvParameters, env_files, render_matrix},
     error::FlowError,
-    flow_bundle::{FlowBundle, load_and_validate_bundle_with_schema_text},
-    flow_ir::FlowIr,
+    expiry::{days_until, prune_expired},
+    extract_component_pins_with_sources,
+    features::{guarded_nodes, render_features},
+    flow_bundle::{
+        ComponentSource, FlowBundle, load_and_validate_bundle,
+        load_and_validate_bundle_with_schema_text,
+    },
+    flow_deps::{self, FLOW_MANIFEST_VERSION, FlowManifest, FlowManifestEntry},
+    flow_diff::diff_flows,
+    flow_edit::{DeleteStrategy, MultiplePredecessors},
+    flow_ir::{
+        FlowIr, format_canonical, to_dot, to_dot_highlighted, to_mermaid, to_mermaid_highlighted,
+    },
     flow_meta,
+    health::{
+        HealthCategory, HealthFinding, HealthScorecard, OWNERS_META_KEY, find_secret_literals,
+        flow_owners,
+    },
+    history,
     i18n::{I18nCatalog, resolve_cli_text, resolve_locale},
-    json_output::LintJsonOutput,
-    lint::{lint_builtin_rules, lint_with_registry},
+    ide_data::{IDE_DATA_VERSION, IdeData, IdeNode, manifest_operation_properties},
+    ir::{NodeKind, classify_node_type},
+    json_output::{
+        DoctorJsonOutput, JsonDiagnostic, LintJsonOutput, SarifLog, SidecarPruneDecision,
+    },
+    lint::{
+        ApiCompatRule, DeclaredStatuses, FixApplicability, FixEdit, LintBaseline, LintConfig,
+        LintDiagnostic, LintFix, LintSeverity, LintSuppression, PlaceholderValuesRule, RuleContext,
+        RuleRegistry, StatusRoutesRule, WasmLintRule, apply_lint_suppressions,
+        apply_machine_applicable_fixes, baseline_flow_key, expired_suppression_warnings,
+        parse_lint_suppressions, utc_date, utc_today,
+    },
     loader::{ensure_config_schema_path, load_ygtc_from_path, load_ygtc_from_str},
+    lockfile::{FlowLock, LOCKFILE_NAME, diff_lock, lock_flow, lock_flows, verify_flows},
+    map_flow_type,
+    migrate::{CURRENT_SCHEMA_VERSION, migrate_flow},
+    minimize::{MinimizeCheck, minimize_flow},
+    mirrors::MirrorRules,
+    node_shape::{
+        MultiOperationFix, apply_multi_operation_fixes, find_multi_operation_nodes,
+        suggest_multi_operation_fix,
+    },
+    pack_config::PackConfig,
+    presets,
+    prompt_style::{self, PromptFlags, PromptStyle},
     qa_runner,
     questions::{
         Answers as QuestionAnswers, Question, apply_writes_to, extract_answers_from_payload,
@@ -52,19 +123,36 @@ use greentic_flow::{
     },
     questions_schema::{example_for_questions, schema_for_questions},
     registry::AdapterCatalog,
+    rename::rename_node,
+    replay::{NodeSchemas, PayloadDirection, parse_recordings, validate_recordings},
+    report::{FlowGraph, FlowReport, render_html_report, render_svg},
+    requirements::{EnvFile, NodeRequirements, check_env_requirements, secret_env_name},
     resolve::resolve_parameters,
-    resolve_summary::{remove_flow_resolve_summary_node, write_flow_resolve_summary_for_node},
+    resolve_summary::{
+        compute_sha256, remove_flow_resolve_summary_node, remove_flow_resolve_summary_nodes,
+        rename_flow_resolve_summary_node, write_flow_resolve_summary_for_node,
+    },
+    runtime_capabilities::{RuntimeCapabilities, check_runtime_compatibility},
+    schema_diff::{diff_manifests, payload_breaks},
+    schema_import::json_schema_to_schema_ir,
     schema_mode::SchemaMode,
     schema_validate::{Severity, validate_value_against_schema},
+    selftest::run_determinism_suite,
+    source_map::SourceMap,
+    state_keys::{StateKeyReport, StateKeyUser, writes_to_collisions},
+    testing::{FlowGenerator, RoutingKind},
+    trace::{annotate_trace, parse_otlp_json, trace_ids},
     wizard_ops, wizard_state,
+    workspace_index::{IndexQuery, WorkspaceIndex, index_path, update_indexed_flow},
 };
 use greentic_qa_lib::{
     I18nConfig as QaI18nConfig, WizardDriver, WizardFrontend, WizardRunConfig as QaWizardRunConfig,
 };
 use greentic_types::flow_resolve::{
     ComponentSourceRefV1, FLOW_RESOLVE_SCHEMA_VERSION, FlowResolveV1, NodeResolveV1, ResolveModeV1,
-    read_flow_resolve, sidecar_path_for_flow, write_flow_resolve,
+    read_flow_resolve, sidecar_path_for_flow, validate_flow_resolve,
 };
+use greentic_types::flow_resolve_summary::resolve_summary_path_for_flow;
 use greentic_types::schemas::component::v0_6_0::{ComponentQaSpec, QuestionKind};
 use indexmap::IndexMap;
 use jsonschema::error::ValidationErrorKind;
@@ -498,6 +586,18 @@ struct Cli {
     /// Backup flow files before overwriting (suffix .bak).
     #[arg(long, global = true)]
     backup: bool,
+    /// Abort after this long (e.g. 30s, 500ms, 2m; bare numbers are seconds) with exit code 124.
+    #[arg(long, global = true, value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+    /// Prompts without colour (also NO_COLOR or `color = false` in the user config).
+    #[arg(long, global = true)]
+    no_color: bool,
+    /// Prompts with ASCII glyphs only.
+    #[arg(long, global = true)]
+    ascii: bool,
+    /// Screen-reader friendly prompts: a parsable marker line per question, no colour or glyphs.
+    #[arg(long, global = true)]
+    screen_reader: bool,
     #[command(subcommand)]
     command: Commands,
 }
@@ -514,13 +614,78 @@ enum Commands {
     New(NewArgs),
     /// Update flow metadata in-place without overwriting nodes.
     Update(UpdateArgs),
+    /// Convert a non-empty flow to another kind, resolving incompatible nodes and routes.
+    ConvertType(ConvertTypeArgs),
+    /// Upgrade a legacy flow to a newer schema_version, reporting each change.
+    Migrate(MigrateArgs),
     /// Insert a step after an anchor node.
     AddStep(AddStepArgs),
     /// Update an existing node (rerun config/default with overrides).
     UpdateStep(UpdateStepArgs),
     /// Delete a node and optionally splice routing.
     DeleteStep(DeleteStepArgs),
+    /// Rename a node and rewrite every reference to it.
+    RenameStep(RenameStepArgs),
+    /// Apply an ordered plan of add-step/wire/patch/delete operations in one write.
+    ApplyPlan(ApplyPlanArgs),
+    /// Generate a flow from a declarative pipeline spec of components and answers.
+    Generate(GenerateArgs),
+    /// Manage named answer presets in the pack's `presets/` directory.
+    Preset(PresetArgs),
+    /// Run component wizards outside of flow editing.
+    Component(ComponentArgs),
+    /// Convert component schemas between JSON Schema and canonical SchemaIr artifacts.
+    Schema(SchemaArgs),
+    /// Inspect and refresh the adapter catalog used by `doctor --registry`.
+    Adapters(AdaptersArgs),
+    /// Report component references that are not pinned and make deployments non-reproducible.
+    Pins(PinsArgs),
+    /// Record digests for every unpinned remote component of a flow, or strip them with --unpin.
+    Pin(PinArgs),
+    /// Score a pack: lint, pinned references, upgrades, secrets, owners and formatting.
+    Health(HealthArgs),
+    /// Resolve every bound component to a digest and write (or check) `flow.lock`.
+    Lock(LockArgs),
+    /// Maintain the workspace index (`.greentic-flow/index.json`) and query flows through it.
+    Index(IndexArgs),
+    /// Sign flow bundles and verify their signatures before a runtime loads them.
+    Bundle(BundleArgs),
+    /// Report which flows and components write which wizard state keys, and which keys are shared.
+    StateKeys(StateKeysArgs),
+    /// Compare two versions of a flow node by node (added/removed/modified nodes, routing, payloads).
+    Diff(DiffArgs),
+    /// Show a flow and its sidecar as they were at a git revision or timestamp.
+    Show(ShowArgs),
+    /// Render a flow diagram as Mermaid, Graphviz DOT, or SVG.
+    Graph(GraphArgs),
+    /// Print the effective flow for a set of enabled feature flags, with disabled nodes spliced out.
+    Render(RenderArgs),
+    /// Validate per-environment parameter files and render the resolved flow for each.
+    RenderMatrix(RenderMatrixArgs),
+    /// Map runtime traces back onto a flow.
+    Trace(TraceArgs),
+    /// Check recorded runtime inputs/outputs against the node components' operation schemas.
+    Replay(ReplayArgs),
+    /// List the env vars, secrets and capabilities each node's component needs at runtime.
+    Requirements(RequirementsArgs),
+    /// Export editor completion data: node ids and each bound component's payload properties.
+    IdeData(IdeDataArgs),
+    /// Rewrite flows into the canonical layout (key order, routing shorthand, indentation).
+    Fmt(FmtArgs),
+    /// Remove nodes whose `meta.expires` date has passed, splicing their routing through.
+    Prune(PruneArgs),
+    /// Shrink a flow to a minimal repro on which a lint rule or load error still reproduces.
+    Minimize(MinimizeArgs),
+    /// Generate a seeded random flow that loads, compiles, and lints clean.
+    GenerateRandom(GenerateRandomArgs),
+    /// Self-checks for downstream CI (e.g. that flow operations are deterministic).
+    Selftest(SelftestArgs),
+    /// Manage the shared content-addressed artifact store (GREENTIC_FLOW_CAS_DIR).
+    Cas(CasArgs),
+    /// Inspect the distributor's component cache (GREENTIC_CACHE_DIR).
+    Cache(CacheArgs),
     /// Validate flows.
+    #[command(alias = "lint")]
     Doctor(DoctorArgs),
     /// Validate answers JSON against a schema.
     DoctorAnswers(DoctorAnswersArgs),
@@ -598,6 +763,41 @@ struct UpdateArgs {
     /// Optional comma-separated tags.
     #[arg(long = "tags")]
     tags: Option<String>,
+    /// Print a unified diff of the change without writing the flow.
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+struct ConvertTypeArgs {
+    /// Path to the flow to convert.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Target flow type/kind (e.g., events, messaging, http).
+    #[arg(long = "to")]
+    to: String,
+    /// Prompt for how to resolve each incompatibility.
+    #[arg(long, conflicts_with = "apply_defaults")]
+    interactive: bool,
+    /// Resolve every incompatibility with its default (first listed) resolution.
+    #[arg(long = "apply-defaults")]
+    apply_defaults: bool,
+    /// Report the conversion without writing the flow.
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+struct MigrateArgs {
+    /// Path to the flow to migrate.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Target schema_version (defaults to the newest).
+    #[arg(long = "to", default_value_t = CURRENT_SCHEMA_VERSION)]
+    to: u32,
+    /// Report the changes without writing the flow.
+    #[arg(long = "dry-run")]
+    dry_run: bool,
 }
 
 #[derive(Args, Debug)]
@@ -617,11 +817,83 @@ struct DoctorArgs {
     /// Re-resolve components and verify contract drift (networked).
     #[arg(long)]
     online: bool,
+    /// Accept hand-edited node configs that drifted from the stored wizard config.
+    #[arg(long = "accept-drift")]
+    accept_drift: bool,
+    /// Placeholder value pattern to flag in payloads (`*` wildcard; repeatable; replaces defaults).
+    #[arg(long = "placeholder-pattern", value_name = "PATTERN")]
+    placeholder_patterns: Vec<String>,
+    /// Extra lint rule from a wasm component exporting `greentic:flow/lint-rule` (repeatable).
+    #[arg(long = "lint-plugin", value_name = "WASM")]
+    lint_plugins: Vec<PathBuf>,
+    /// Apply machine-applicable fixes (conflicting operation keys, routing shorthand, dangling entrypoints).
+    #[arg(long)]
+    fix: bool,
+    /// Suppress lint findings recorded in this baseline file; only new findings fail. When the
+    /// file does not exist yet, the current findings are recorded into it.
+    #[arg(long, value_name = "FILE", conflicts_with = "write_baseline")]
+    baseline: Option<PathBuf>,
+    /// Record the current lint findings into a baseline file instead of failing on them.
+    #[arg(long = "write-baseline", value_name = "FILE")]
+    write_baseline: Option<PathBuf>,
+    /// Also write a shareable report of every linted flow (format: html).
+    #[arg(long, num_args = 2, value_names = ["FORMAT", "FILE"])]
+    report: Option<Vec<String>>,
+    /// Runtime capability manifest (JSON worlds/schemas); fail nodes needing anything it lacks.
+    #[arg(long = "runtime-manifest", value_name = "FILE")]
+    runtime_manifest: Option<PathBuf>,
+    /// Release catalog (JSON) of published component versions; report upgrade advisories.
+    #[arg(long = "release-catalog", value_name = "FILE")]
+    release_catalog: Option<PathBuf>,
+    /// Dotenv file for the target environment; fail nodes whose env vars or secrets it lacks.
+    #[arg(long = "env-file", value_name = "FILE")]
+    env_file: Option<PathBuf>,
+    /// Last released version of an http flow (bundle JSON or flow file; repeatable, matched by
+    /// flow id); fail breaking interface changes made without a `meta.api_version` bump.
+    #[arg(long = "api-baseline", value_name = "FILE")]
+    api_baselines: Vec<PathBuf>,
+    /// Check deployed bundle files (from `bundle pack`) instead of flows: each must be intact,
+    /// packed for this channel and signed by `--public-key` (signature at `<bundle>.sig`).
+    #[arg(
+        long = "expect-channel",
+        value_name = "stable|beta",
+        requires = "public_key"
+    )]
+    expect_channel: Option<ReleaseChannel>,
+    /// Trusted hex-encoded ed25519 public key for `--expect-channel`.
+    #[arg(long = "public-key", value_name = "FILE", requires = "expect_channel")]
+    public_key: Option<PathBuf>,
+    /// Check up to N flows at once (default: available CPUs, or 1 when stdin is a terminal and
+    /// nothing is pre-selected for pruning); `--jobs 1` checks them in order and allows
+    /// interactive prompts.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    jobs: Option<u32>,
+    /// Prune unused sidecar entries without prompting: `stale` selects every entry whose node is
+    /// gone, a flow file name or stem selects that flow's entries, `<flow>:<node>` one entry.
+    #[arg(
+        long = "prune-select",
+        value_name = "SELECTOR",
+        value_delimiter = ',',
+        conflicts_with = "json"
+    )]
+    prune_select: Vec<String>,
+    /// Report format for all checked flows; `json` and `sarif` print one machine-readable report
+    /// on stdout.
+    #[arg(long, value_enum, default_value = "human", conflicts_with_all = ["json", "stdin"])]
+    output: DoctorOutput,
     /// Flow files or directories to lint.
     #[arg(required_unless_present = "stdin")]
     targets: Vec<PathBuf>,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum DoctorOutput {
+    Human,
+    Json,
+    /// SARIF 2.1.0, for code scanning (e.g. GitHub).
+    Sarif,
+}
+
 #[derive(Args, Debug)]
 struct DoctorAnswersArgs {
     /// Path to the answers JSON schema.
@@ -695,6 +967,9 @@ struct UpdateStepArgs {
     /// Answers file (JSON/YAML) to merge with existing payload.
     #[arg(long = "answers-file")]
     answers_file: Option<PathBuf>,
+    /// Named answer preset from the pack's `presets/` directory (merged below explicit answers).
+    #[arg(long = "preset")]
+    preset: Option<String>,
     /// Directory for wizard answers artifacts.
     #[arg(long = "answers-dir")]
     answers_dir: Option<PathBuf>,
@@ -710,6 +985,9 @@ struct UpdateStepArgs {
     /// Non-interactive mode (merge answers/prefill; fail if required missing).
     #[arg(long = "non-interactive")]
     non_interactive: bool,
+    /// Wizard mode: re-prompt only answers previously set by the user; keep defaults/presets.
+    #[arg(long = "changed-only")]
+    changed_only: bool,
     /// Allow interactive QA prompts (wizard mode only).
     #[arg(long = "interactive")]
     interactive: bool,
@@ -743,7 +1021,7 @@ struct UpdateStepArgs {
     /// Resolver override (fixture://...) for tests/CI.
     #[arg(long = "resolver")]
     resolver: Option<String>,
-    /// Show the updated flow without writing it.
+    /// Print a unified diff of the flow and sidecar changes without writing them.
     #[arg(long = "dry-run")]
     dry_run: bool,
     /// Backward-compatible write flag (ignored; writing is default).
@@ -752,6 +1030,9 @@ struct UpdateStepArgs {
     /// Allow contract drift when describe_hash changes.
     #[arg(long = "allow-contract-change")]
     allow_contract_change: bool,
+    /// Also rename the step to this id, rewiring every route into it and its sidecar entry.
+    #[arg(long = "rename", value_name = "NEW_ID")]
+    rename: Option<String>,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -762,9 +1043,10 @@ struct DeleteStepArgs {
     /// Flow file to update.
     #[arg(long = "flow")]
     flow_path: PathBuf,
-    /// Node id to delete (optional when component metadata exists).
-    #[arg(long = "step")]
-    step: Option<String>,
+    /// Node ids to delete; repeat the flag or pass `--steps a,b,c` (optional when component
+    /// metadata exists).
+    #[arg(long = "step", visible_alias = "steps", value_delimiter = ',')]
+    step: Vec<String>,
     /// Optional wizard mode (default/setup/update/remove).
     #[arg(long = "wizard-mode", value_enum)]
     wizard_mode: Option<WizardModeArg>,
@@ -786,7 +1068,8 @@ struct DeleteStepArgs {
     /// Locale (BCP47) for wizard prompts.
     #[arg(long = "locale")]
     locale: Option<String>,
-    /// Allow interactive QA prompts (wizard mode only).
+    /// Allow interactive prompts: wizard QA, and the choice for a step with multiple
+    /// predecessors.
     #[arg(long = "interactive")]
     interactive: bool,
     /// Optional component reference (oci://, repo://, store://).
@@ -822,2188 +1105,1454 @@ struct DeleteStepArgs {
     /// Strategy: splice (default) or remove-only.
     #[arg(long = "strategy", default_value = "splice", value_parser = ["splice", "remove-only"])]
     strategy: String,
-    /// Behavior when multiple predecessors are present.
-    #[arg(
-        long = "if-multiple-predecessors",
-        default_value = "error",
-        value_parser = ["error", "splice-all"]
-    )]
-    multi_pred: String,
+    /// Behavior when a step has multiple predecessors: error, splice-all or reroute-to:<node>.
+    /// When unset, --interactive asks with a preview of each choice; otherwise it errors.
+    #[arg(long = "if-multiple-predecessors")]
+    multi_pred: Option<MultiplePredecessors>,
     /// Skip confirmation prompt.
     #[arg(long = "assume-yes")]
     assume_yes: bool,
+    /// Run the component's remove wizard (teardown) with the node's current config before deleting.
+    #[arg(long = "run-remove", conflicts_with = "skip_remove")]
+    run_remove: bool,
+    /// Delete without running the component's remove wizard (default; for offline use).
+    #[arg(long = "skip-remove")]
+    skip_remove: bool,
     /// Write back to the flow file instead of stdout.
     #[arg(long = "write")]
     write: bool,
+    /// Print a unified diff of the flow and sidecar changes instead of the new flow.
+    #[arg(long = "dry-run", conflicts_with = "write")]
+    dry_run: bool,
+    /// Also delete the steps that are only reachable through this one, instead of splicing.
+    #[arg(long = "cascade", conflicts_with_all = ["strategy", "multi_pred"])]
+    cascade: bool,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
-enum AnswersMode {
-    Default,
-    Config,
+#[derive(Args, Debug, Clone)]
+struct RenameStepArgs {
+    /// Flow file to update.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Current node id.
+    #[arg(long = "from")]
+    from: String,
+    /// New node id.
+    #[arg(long = "to")]
+    to: String,
+    /// Print a unified diff of the flow and sidecar changes without writing them.
+    #[arg(long = "dry-run")]
+    dry_run: bool,
 }
 
-fn main() -> Result<()> {
-    if env::args().any(|arg| arg == "--dump-help-i18n") {
-        let mut entries = BTreeMap::new();
-        collect_help_i18n_entries(&Cli::command(), &[], &mut entries);
-        println!("{}", serde_json::to_string_pretty(&entries)?);
-        return Ok(());
-    }
-    let requested_locale = cli_requested_locale();
-    let (catalog, locale) = default_i18n_catalog(requested_locale.as_deref());
-    let cmd = localized_cli_command(&catalog, &locale);
-    let matches = match cmd.try_get_matches() {
-        Ok(matches) => matches,
-        Err(err) => err.exit(),
-    };
-    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
-    if let Some(locale) = cli.locale.as_deref()
-        && !locale.trim().is_empty()
-    {
-        unsafe {
-            std::env::set_var("GREENTIC_LOCALE", locale.trim());
-        }
-    }
-    let schema_mode = SchemaMode::resolve(cli.permissive)?;
-    match cli.command {
-        Commands::New(args) => handle_new(args, cli.backup),
-        Commands::Update(args) => handle_update(args, cli.backup),
-        Commands::AddStep(args) => handle_add_step(args, schema_mode, cli.format, cli.backup),
-        Commands::UpdateStep(args) => handle_update_step(args, schema_mode, cli.format, cli.backup),
-        Commands::DeleteStep(args) => handle_delete_step(args, cli.format, cli.backup),
-        Commands::Doctor(mut args) => {
-            if matches!(cli.format, OutputFormat::Json) {
-                args.json = true;
-            }
-            handle_doctor(args, schema_mode)
-        }
-        Commands::DoctorAnswers(args) => handle_doctor_answers(args),
-        Commands::Answers(args) => handle_answers(args, schema_mode),
-        Commands::BindComponent(args) => handle_bind_component(args),
-        Commands::Wizard(args) => handle_wizard(args),
-    }
+#[derive(Args, Debug)]
+struct ApplyPlanArgs {
+    /// Plan file (YAML or JSON) listing ordered operations.
+    #[arg(value_name = "PLAN", required_unless_present = "plan_file")]
+    plan: Option<PathBuf>,
+    /// Plan file, as an alternative to the positional argument.
+    #[arg(long = "plan", conflicts_with = "plan")]
+    plan_file: Option<PathBuf>,
+    /// Flow file to update (defaults to the plan's `flow` entry, relative to the plan file).
+    #[arg(long = "flow")]
+    flow_path: Option<PathBuf>,
+    /// Write back to the flow file instead of stdout.
+    #[arg(long = "write")]
+    write: bool,
 }
 
-fn handle_wizard(args: WizardArgs) -> Result<()> {
-    let stdin = io::stdin();
-    let stdout = io::stdout();
-    run_wizard_menu_with_config(
-        &args.pack,
-        stdin,
-        stdout,
-        WizardRunConfig {
-            answers_file: args.answers_file,
-            dry_run: args.dry_run,
-        },
-    )
+#[derive(Args, Debug)]
+struct GenerateArgs {
+    /// Pipeline spec (YAML or JSON) listing the flow and its ordered steps.
+    #[arg(long = "spec")]
+    spec: PathBuf,
+    /// Flow file to write (defaults to the spec's `flow` entry, relative to the spec file).
+    #[arg(long = "flow")]
+    flow_path: Option<PathBuf>,
+    /// Overwrite the flow file if it already exists.
+    #[arg(long)]
+    force: bool,
+    /// Resolver override (fixture://...) for tests/CI; takes precedence over the spec.
+    #[arg(long = "resolver")]
+    resolver: Option<String>,
+    /// Wizard steps whose components run at the same time (default: available CPUs; 1 runs
+    /// every wizard in step order).
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    jobs: Option<u32>,
 }
 
-#[derive(Debug, Clone)]
-enum WizardScreen {
-    MainMenu,
-    FlowSelect,
-    FlowOps { flow_path: PathBuf },
+/// Declarative pipeline consumed by `generate --spec`.
+#[derive(Debug, serde::Deserialize)]
+struct PipelineSpec {
+    #[serde(default)]
+    flow: Option<PathBuf>,
+    id: String,
+    #[serde(rename = "type")]
+    flow_type: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    resolver: Option<String>,
+    steps: Vec<PipelineStep>,
 }
 
-#[derive(Debug)]
-struct WizardSession {
-    real_pack_dir: PathBuf,
-    staged_pack_dir: PathBuf,
-    dirty: bool,
-    config: WizardRunConfig,
-    answers_log: serde_json::Map<String, serde_json::Value>,
-    answers_output_path: Option<PathBuf>,
+/// One component in a pipeline spec; steps without `operation` run the component wizard.
+#[derive(Debug, serde::Deserialize)]
+struct PipelineStep {
+    #[serde(default)]
+    node_id: Option<String>,
+    #[serde(default)]
+    component_id: Option<String>,
+    #[serde(default)]
+    component: Option<String>,
+    #[serde(default)]
+    local_wasm: Option<PathBuf>,
+    #[serde(default)]
+    operation: Option<String>,
+    #[serde(default)]
+    payload: Option<serde_json::Value>,
+    #[serde(default)]
+    answers: Option<serde_json::Value>,
+    #[serde(default)]
+    wizard_mode: Option<String>,
+    #[serde(default)]
+    pin: bool,
 }
 
-#[cfg(test)]
-fn run_wizard_menu_with_io<R: Read, W: Write>(
-    pack_dir: &Path,
-    mut reader: R,
-    mut writer: W,
-) -> Result<()> {
-    run_wizard_menu_with_config(
-        pack_dir,
-        &mut reader,
-        &mut writer,
-        WizardRunConfig::default(),
-    )
+#[derive(Args, Debug)]
+struct PresetArgs {
+    #[command(subcommand)]
+    command: PresetCommand,
 }
 
-fn run_wizard_menu_with_config<R: Read, W: Write>(
-    pack_dir: &Path,
-    mut reader: R,
-    mut writer: W,
-    config: WizardRunConfig,
-) -> Result<()> {
-    let staged_pack_dir = create_wizard_staging_pack(pack_dir)?;
-    let preloaded_answers = if let Some(path) = config.answers_file.as_ref() {
-        let resolved = if path.is_absolute() {
-            path.clone()
-        } else {
-            pack_dir.join(path)
-        };
-        load_wizard_answers_file(&resolved)?
-    } else {
-        serde_json::Map::new()
-    };
-    let mut session = WizardSession {
-        real_pack_dir: pack_dir.to_path_buf(),
-        staged_pack_dir,
-        dirty: false,
-        config,
-        answers_log: preloaded_answers,
-        answers_output_path: None,
-    };
-    let mut screen = WizardScreen::MainMenu;
-    loop {
-        match screen.clone() {
-            WizardScreen::MainMenu => {
-                let answer = wizard_menu_answer(
-                    &mut reader,
-                    &mut writer,
-                    "main.menu",
-                    &wizard_t("wizard.menu.main.prompt"),
-                    &["1", "2", "3", "4", "5", "0"],
-                )?;
-                session.answers_log.insert(
-                    "main.menu".to_string(),
-                    serde_json::Value::String(answer.clone()),
-                );
-                match answer.as_str() {
-                    "1" => {
-                        if let Err(err) = wizard_add_flow_with_io(
-                            &session.staged_pack_dir,
-                            &mut reader,
-                            &mut writer,
-                            &mut session.answers_log,
-                        ) {
-                            writeln!(writer, "{}", err).ok();
-                        } else {
-                            session.dirty = true;
-                        }
-                    }
-                    "2" => {
-                        screen = WizardScreen::FlowSelect;
-                    }
-                    "3" => {
-                        if let Err(err) = wizard_generate_translations_with_io(
-                            &session.staged_pack_dir,
-                            &mut reader,
-                            &mut writer,
-                            &mut session.answers_log,
-                        ) {
-                            writeln!(writer, "{}", err).ok();
-                        } else {
-                            session.dirty = true;
-                        }
-                    }
-                    "4" => {
-                        if let Err(err) =
-                            wizard_save_staged_changes(&mut session, &mut reader, &mut writer)
-                        {
-                            writeln!(writer, "{}", err).ok();
-                        }
-                    }
-                    "5" => {
-                        if let Err(err) =
-                            wizard_export_answers_with_io(&mut session, &mut reader, &mut writer)
-                        {
-                            writeln!(writer, "{}", err).ok();
-                        }
-                    }
-                    "0" => {
-                        if session.dirty {
-                            writeln!(writer, "{}", wizard_t("wizard.save.discarded")).ok();
-                        }
-                        let _ = fs::remove_dir_all(&session.staged_pack_dir);
-                        return Ok(());
-                    }
-                    _ => {}
-                }
-            }
-            WizardScreen::FlowSelect => {
-                let flows = collect_pack_flows(&session.staged_pack_dir)?;
-                let mut prompt = format!("{}\n", wizard_t("wizard.menu.flow_select.title"));
-                for (idx, flow) in flows.iter().enumerate() {
-                    let rel = flow.strip_prefix(&session.staged_pack_dir).unwrap_or(flow);
-                    prompt.push_str(&format!("{}. {}\n", idx + 1, rel.display()));
-                }
-                prompt.push_str(&format!(
-                    "{}\n{}",
-                    wizard_t("wizard.menu.nav.back"),
-                    wizard_t("wizard.menu.nav.main")
-                ));
-                let mut choices: Vec<String> = (1..=flows.len()).map(|n| n.to_string()).collect();
-                choices.push("0".to_string());
-                choices.push("M".to_string());
-                let choice_refs: Vec<&str> = choices.iter().map(String::as_str).collect();
-                let answer = wizard_menu_answer(
-                    &mut reader,
-                    &mut writer,
-                    "flow.select",
-                    &prompt,
-                    &choice_refs,
-                )?;
-                session.answers_log.insert(
-                    "flow.select".to_string(),
-                    serde_json::Value::String(answer.clone()),
-                );
-                match answer.as_str() {
-                    "0" => screen = WizardScreen::MainMenu,
-                    "M" => screen = WizardScreen::MainMenu,
-                    raw => {
-                        let idx = raw
-                            .parse::<usize>()
-                            .context("parse selected flow index")?
-                            .saturating_sub(1);
-                        if let Some(flow_path) = flows.get(idx) {
-                            screen = WizardScreen::FlowOps {
-                                flow_path: flow_path.clone(),
-                            };
-                        }
-                    }
-                }
-            }
-            WizardScreen::FlowOps { flow_path } => {
-                let rel = flow_path
-                    .strip_prefix(&session.staged_pack_dir)
-                    .unwrap_or(&flow_path);
-                let prompt = wizard_t_with(
-                    "wizard.menu.flow_ops.prompt",
-                    &[("flow", &rel.display().to_string())],
-                );
-                let answer = wizard_menu_answer(
-                    &mut reader,
-                    &mut writer,
-                    "flow.ops",
-                    &prompt,
-                    &["1", "2", "3", "4", "5", "6", "7", "0", "M"],
-                )?;
-                session.answers_log.insert(
-                    "flow.ops".to_string(),
-                    serde_json::Value::String(answer.clone()),
-                );
-                match answer.as_str() {
-                    "0" => screen = WizardScreen::FlowSelect,
-                    "M" => screen = WizardScreen::MainMenu,
-                    "1" => {
-                        if let Err(err) = wizard_edit_flow_summary_with_io(
-                            &flow_path,
-                            &mut reader,
-                            &mut writer,
-                            &mut session.answers_log,
-                        ) {
-                            writeln!(writer, "{}", err).ok();
-                        } else {
-                            session.dirty = true;
-                        }
-                    }
-                    "2" => {
-                        if let Err(err) = wizard_list_steps_with_io(&flow_path, &mut writer) {
-                            writeln!(writer, "{}", err).ok();
-                        }
-                    }
-                    "3" => {
-                        if let Err(err) = wizard_add_step_with_io(
-                            &session.staged_pack_dir,
-                            &flow_path,
-                            &mut reader,
-                            &mut writer,
-                        ) {
-                            writeln!(writer, "{}", err).ok();
-                        } else {
-                            session.dirty = true;
-                        }
-                    }
-                    "4" => {
-                        if let Err(err) = wizard_update_step_with_io(
-                            &session.staged_pack_dir,
-                            &flow_path,
-                            &mut reader,
-                            &mut writer,
-                        ) {
-                            writeln!(writer, "{}", err).ok();
-                        } else {
-                            session.dirty = true;
-                        }
-                    }
-                    "5" => {
-                        if let Err(err) =
-                            wizard_delete_step_with_io(&flow_path, &mut reader, &mut writer)
-                        {
-                            writeln!(writer, "{}", err).ok();
-                        } else {
-                            session.dirty = true;
-                        }
-                    }
-                    "6" => {
-                        if let Err(err) =
-                            wizard_delete_flow_with_io(&flow_path, &mut reader, &mut writer)
-                        {
-                            writeln!(writer, "{}", err).ok();
-                        } else {
-                            session.dirty = true;
-                            screen = WizardScreen::FlowSelect;
-                        }
-                    }
-                    "7" => {
-                        if let Err(err) =
-                            wizard_save_staged_changes(&mut session, &mut reader, &mut writer)
-                        {
-                            writeln!(writer, "{}", err).ok();
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
-    }
+#[derive(Subcommand, Debug)]
+enum PresetCommand {
+    /// Capture an existing node's answers as a named preset.
+    Save(PresetSaveArgs),
 }
 
-fn create_wizard_staging_pack(pack_dir: &Path) -> Result<PathBuf> {
-    if !pack_dir.exists() {
-        anyhow::bail!(
-            "{}",
-            wizard_t_with(
-                "wizard.error.pack_dir_not_found",
-                &[("path", &pack_dir.display().to_string())]
-            )
-        );
-    }
-    let unique = format!(
-        "greentic-flow-wizard-{}-{}",
-        std::process::id(),
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_nanos())
-            .unwrap_or_default()
-    );
-    let stage_root = env::temp_dir().join(unique);
-    fs::create_dir_all(&stage_root)
-        .with_context(|| format!("create directory {}", stage_root.display()))?;
-    for entry in ["flows", "i18n", "components"] {
-        let src = pack_dir.join(entry);
-        let dst = stage_root.join(entry);
-        if src.exists() {
-            copy_dir_recursive(&src, &dst)?;
-        }
-    }
-    Ok(stage_root)
+#[derive(Args, Debug)]
+struct PresetSaveArgs {
+    /// Preset name (written to presets/<name>.json).
+    name: String,
+    /// Flow file containing the node.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Node id whose answers should be captured.
+    #[arg(long = "from-step")]
+    from_step: String,
+    /// Directory for wizard answers artifacts.
+    #[arg(long = "answers-dir")]
+    answers_dir: Option<PathBuf>,
+    /// Overwrite an existing preset.
+    #[arg(long)]
+    force: bool,
 }
 
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
-    if src.is_file() {
-        if let Some(parent) = dst.parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("create directory {}", parent.display()))?;
-        }
-        fs::copy(src, dst)
-            .with_context(|| format!("copy file {} -> {}", src.display(), dst.display()))?;
-        return Ok(());
-    }
-    fs::create_dir_all(dst).with_context(|| format!("create directory {}", dst.display()))?;
-    for entry in fs::read_dir(src).with_context(|| format!("read directory {}", src.display()))? {
-        let entry = entry.with_context(|| format!("read entry under {}", src.display()))?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
-        if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
-            fs::copy(&src_path, &dst_path).with_context(|| {
-                format!("copy file {} -> {}", src_path.display(), dst_path.display())
-            })?;
-        }
-    }
-    Ok(())
+#[derive(Args, Debug)]
+struct ComponentArgs {
+    #[command(subcommand)]
+    command: ComponentCommand,
 }
 
-fn wizard_save_staged_changes<R: Read, W: Write>(
-    session: &mut WizardSession,
-    reader: &mut R,
-    writer: &mut W,
-) -> Result<()> {
-    if !session.dirty {
-        writeln!(writer, "{}", wizard_t("wizard.save.no_changes")).ok();
-        return Ok(());
-    }
+#[derive(Subcommand, Debug)]
+enum ComponentCommand {
+    /// Run a component's setup wizard standalone and write the resulting config.
+    Setup(ComponentSetupArgs),
+    /// Report breaking schema changes between two manifest versions and the flow nodes they break.
+    DiffSchema(ComponentDiffSchemaArgs),
+    /// Print a component's info, operations, schemas, capabilities and setup contract.
+    Show(ComponentShowArgs),
+}
 
-    if !session.answers_log.is_empty() {
-        let answers_path = wizard_answers_output_path(session, reader, writer)?;
-        write_wizard_answers_file(&answers_path, &session.answers_log)?;
-    }
+#[derive(Args, Debug)]
+struct ComponentShowArgs {
+    /// Component reference (oci://, repo://, store://).
+    #[arg(long = "component", required_unless_present = "local_wasm")]
+    component: Option<String>,
+    /// Local wasm path.
+    #[arg(long = "local-wasm", conflicts_with = "component")]
+    local_wasm: Option<PathBuf>,
+    /// Show only this operation.
+    #[arg(long)]
+    operation: Option<String>,
+    /// Resolver override (fixture://...) for tests/CI.
+    #[arg(long = "resolver")]
+    resolver: Option<String>,
+}
 
-    let flows_target = session.staged_pack_dir.join("flows");
-    if wizard_has_empty_flow_nodes(&flows_target)? {
-        anyhow::bail!("{}", wizard_t("wizard.save.empty_flow"));
-    }
-    if let Err(err) = wizard_validate_flows(&flows_target) {
-        let details = err.to_string();
-        if details.contains("start_node_exists: invalid start node ''")
-            || details.contains("NodeId must not be empty")
-        {
-            anyhow::bail!("{}", wizard_t("wizard.save.empty_flow"));
-        }
-        return Err(anyhow!(
-            "{}: {details}",
-            wizard_t("wizard.save.doctor_failed")
-        ));
-    }
+#[derive(Args, Debug)]
+struct ComponentDiffSchemaArgs {
+    /// Current component manifest (component.manifest.json).
+    #[arg(long)]
+    old: PathBuf,
+    /// Candidate component manifest to upgrade to.
+    #[arg(long)]
+    new: PathBuf,
+    /// Directory scanned recursively for flows with nodes bound to this component.
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+    /// Fail when any existing node would break.
+    #[arg(long)]
+    strict: bool,
+}
 
-    if !session.config.dry_run {
-        sync_staged_pack_back(session)?;
-        session.dirty = false;
-        writeln!(writer, "{}", wizard_t("wizard.save.done")).ok();
-    } else {
-        session.dirty = false;
-        writeln!(writer, "{}", wizard_t("wizard.save.dry_run_done")).ok();
-    }
-    Ok(())
+#[derive(Args, Debug)]
+struct ComponentSetupArgs {
+    /// Component reference (oci://, repo://, store://).
+    #[arg(long = "component", required_unless_present = "local_wasm")]
+    component: Option<String>,
+    /// Local wasm path for wizard ops.
+    #[arg(long = "local-wasm", conflicts_with = "component")]
+    local_wasm: Option<PathBuf>,
+    /// Answers JSON/YAML string to merge with wizard prompts.
+    #[arg(long = "answers")]
+    answers: Option<String>,
+    /// Answers file (JSON/YAML).
+    #[arg(long = "answers-file")]
+    answers_file: Option<PathBuf>,
+    /// Locale (BCP47) for wizard prompts.
+    #[arg(long = "locale")]
+    locale: Option<String>,
+    /// Allow interactive QA prompts.
+    #[arg(long = "interactive")]
+    interactive: bool,
+    /// Resolver override (fixture://...) for tests/CI.
+    #[arg(long = "resolver")]
+    resolver: Option<String>,
+    /// Output file; `.cbor` writes canonical CBOR, anything else writes JSON.
+    #[arg(long = "out")]
+    out: PathBuf,
+    /// Overwrite the output file if it already exists.
+    #[arg(long)]
+    force: bool,
 }
 
-fn wizard_has_empty_flow_nodes(root: &Path) -> Result<bool> {
-    if !root.exists() {
-        return Ok(false);
-    }
-    let mut stack = vec![root.to_path_buf()];
-    while let Some(dir) = stack.pop() {
-        for entry in
-            fs::read_dir(&dir).with_context(|| format!("read directory {}", dir.display()))?
-        {
-            let entry = entry.with_context(|| format!("read entry under {}", dir.display()))?;
-            let path = entry.path();
-            if path.is_dir() {
-                stack.push(path);
-                continue;
-            }
-            if path.extension().and_then(|ext| ext.to_str()) != Some("ygtc") {
-                continue;
-            }
-            let text = fs::read_to_string(&path)
-                .with_context(|| format!("read flow file {}", path.display()))?;
-            let doc: serde_yaml_bw::Value = serde_yaml_bw::from_str(&text)
-                .with_context(|| format!("parse flow file {}", path.display()))?;
-            let nodes_len = doc
-                .as_mapping()
-                .and_then(|map| {
-                    map.get(serde_yaml_bw::Value::String(
-                        "nodes".to_string(),
-                        None::<String>,
-                    ))
-                })
-                .and_then(serde_yaml_bw::Value::as_mapping)
-                .map(|map| map.len())
-                .unwrap_or(0);
-            if nodes_len == 0 {
-                return Ok(true);
-            }
-        }
-    }
-    Ok(false)
+#[derive(Args, Debug)]
+struct AdaptersArgs {
+    #[command(subcommand)]
+    command: AdaptersCommand,
 }
 
-fn wizard_validate_flows(target: &Path) -> Result<()> {
-    let schema_text = EMBEDDED_FLOW_SCHEMA.to_string();
-    let schema_label = "embedded ygtc.flow.schema.json".to_string();
-    let schema_path = PathBuf::from("schemas/ygtc.flow.schema.json");
-    let lint_ctx = LintContext {
-        schema_text: &schema_text,
-        schema_label: &schema_label,
-        schema_path: schema_path.as_path(),
-        registry: None,
-        schema_mode: SchemaMode::Strict,
-    };
-    let mut failures = 0usize;
-    lint_path(target, &lint_ctx, false, &mut failures)?;
-    if failures == 0 {
-        Ok(())
-    } else {
-        anyhow::bail!("{failures} flow(s) failed validation");
-    }
+#[derive(Subcommand, Debug)]
+enum AdaptersCommand {
+    /// List adapter operations found in the pack, the dist cache, and an optional index.
+    List(AdapterSourceArgs),
+    /// Rebuild the catalog and write it as a registry file for `doctor --registry`.
+    Refresh(AdaptersRefreshArgs),
 }
 
-fn wizard_answers_output_path<R: Read, W: Write>(
-    session: &mut WizardSession,
-    _reader: &mut R,
-    _writer: &mut W,
-) -> Result<PathBuf> {
-    if let Some(path) = session.answers_output_path.clone() {
-        return Ok(path);
-    }
-    let path = session
-        .config
-        .answers_file
-        .clone()
-        .unwrap_or_else(|| PathBuf::from("./answers.json"));
-    let resolved = if path.is_absolute() {
-        path
-    } else {
-        session.real_pack_dir.join(path)
-    };
-    session.answers_output_path = Some(resolved.clone());
-    Ok(resolved)
+#[derive(Args, Debug)]
+struct AdapterSourceArgs {
+    /// Pack root whose `components/` manifests contribute adapters.
+    #[arg(long, default_value = ".")]
+    pack: PathBuf,
+    /// Remote (http/https) or local adapter index in registry JSON format.
+    #[arg(long)]
+    index: Option<String>,
+    /// Skip component manifests in the distributor cache.
+    #[arg(long = "no-dist-cache")]
+    no_dist_cache: bool,
 }
 
-fn wizard_answers_output_path_interactive<R: Read, W: Write>(
-    session: &mut WizardSession,
-    reader: &mut R,
-    writer: &mut W,
-) -> Result<PathBuf> {
-    let answers = run_questions_with_qa_lib_io(
-        &[Question {
-            id: "wizard.answers.path".to_string(),
-            prompt: wizard_t("wizard.answers.path.prompt"),
-            kind: greentic_flow::questions::QuestionKind::String,
-            required: false,
-            default: Some(serde_json::Value::String("./answers.json".to_string())),
-            choices: Vec::new(),
-            show_if: None,
-            writes_to: None,
-        }],
-        HashMap::new(),
-        &mut *reader,
-        &mut *writer,
-    )?;
-    let raw = answers
-        .get("wizard.answers.path")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("./answers.json")
-        .trim();
-    let selected = if raw.is_empty() {
-        "./answers.json"
-    } else {
-        raw
-    };
-    session.answers_log.insert(
-        "wizard.answers.path".to_string(),
-        serde_json::Value::String(selected.to_string()),
-    );
-    let path = PathBuf::from(selected);
-    let resolved = if path.is_absolute() {
-        path
-    } else {
-        session.real_pack_dir.join(path)
-    };
-    session.answers_output_path = Some(resolved.clone());
-    Ok(resolved)
+#[derive(Args, Debug)]
+struct SchemaArgs {
+    #[command(subcommand)]
+    command: SchemaCommand,
 }
 
-fn wizard_export_answers_with_io<R: Read, W: Write>(
-    session: &mut WizardSession,
-    reader: &mut R,
-    writer: &mut W,
-) -> Result<()> {
-    let answers_path = wizard_answers_output_path_interactive(session, reader, writer)?;
-    write_wizard_answers_file(&answers_path, &session.answers_log)?;
-    writeln!(
-        writer,
-        "{}",
-        wizard_t_with(
-            "wizard.answers.path.saved",
-            &[("path", &answers_path.display().to_string())]
-        )
-    )
-    .ok();
-    Ok(())
+#[derive(Subcommand, Debug)]
+enum SchemaCommand {
+    /// Import a JSON Schema and write it as canonical SchemaIr CBOR.
+    Convert(SchemaConvertArgs),
 }
 
-fn write_wizard_answers_file(
-    path: &Path,
-    answers_log: &serde_json::Map<String, serde_json::Value>,
-) -> Result<()> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("create directory {}", parent.display()))?;
-    }
-    let payload = serde_json::Value::Object(answers_log.clone());
-    let text = serde_json::to_string_pretty(&payload).context("serialize wizard answers")?;
-    fs::write(path, text).with_context(|| format!("write wizard answers {}", path.display()))?;
-    Ok(())
+#[derive(Args, Debug)]
+struct SchemaConvertArgs {
+    /// JSON Schema file to import.
+    #[arg(long = "in")]
+    input: PathBuf,
+    /// Canonical CBOR file to write.
+    #[arg(long)]
+    out: PathBuf,
 }
 
-fn load_wizard_answers_file(path: &Path) -> Result<serde_json::Map<String, serde_json::Value>> {
-    if !path.exists() {
-        return Ok(serde_json::Map::new());
-    }
-    let text = fs::read_to_string(path)
-        .with_context(|| format!("read wizard answers {}", path.display()))?;
-    let value: serde_json::Value = serde_json::from_str(&text)
-        .with_context(|| format!("parse wizard answers {}", path.display()))?;
-    Ok(value.as_object().cloned().unwrap_or_default())
+#[derive(Args, Debug)]
+struct AdaptersRefreshArgs {
+    #[command(flatten)]
+    source: AdapterSourceArgs,
+    /// Registry file to write (defaults to `<pack>/adapters.json`).
+    #[arg(long)]
+    out: Option<PathBuf>,
 }
 
-fn sync_staged_pack_back(session: &mut WizardSession) -> Result<()> {
-    sync_staged_dir(session, "flows")?;
-    sync_staged_dir(session, "i18n")?;
-    sync_staged_dir(session, "components")?;
-    Ok(())
+#[derive(Args, Debug)]
+struct PinsArgs {
+    /// Directory scanned recursively for flows and their resolve sidecars.
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+    /// Also list pinned references.
+    #[arg(long)]
+    all: bool,
+    /// Fail when any reference is floating.
+    #[arg(long)]
+    strict: bool,
 }
 
-fn sync_staged_dir(session: &WizardSession, name: &str) -> Result<()> {
-    let staged_dir = session.staged_pack_dir.join(name);
-    let real_dir = session.real_pack_dir.join(name);
-    if real_dir.exists() {
-        fs::remove_dir_all(&real_dir)
-            .with_context(|| format!("remove directory {}", real_dir.display()))?;
-    }
-    if staged_dir.exists() {
-        copy_dir_recursive(&staged_dir, &real_dir)?;
-    } else {
-        fs::create_dir_all(&real_dir)
-            .with_context(|| format!("create directory {}", real_dir.display()))?;
-    }
-    Ok(())
+#[derive(Args, Debug)]
+struct PinArgs {
+    /// Flow whose resolve sidecar to update.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Only these nodes (repeatable; default: every bound node).
+    #[arg(long = "node")]
+    nodes: Vec<String>,
+    /// Strip recorded digests so the references follow their tags again.
+    #[arg(long)]
+    unpin: bool,
+    /// Show what would change without writing the sidecar.
+    #[arg(long = "dry-run")]
+    dry_run: bool,
 }
 
-fn wizard_menu_answer<R: Read, W: Write>(
-    reader: &mut R,
-    writer: &mut W,
-    question_id: &str,
-    prompt: &str,
-    choices: &[&str],
-) -> Result<String> {
-    let locale = resolve_locale(None);
-    let qa_i18n = wizard_qa_i18n_config_for_locale(&locale);
-    let spec = serde_json::json!({
-        "id": format!("wizard.{question_id}"),
-        "title": "wizard",
-        "version": "1.0.0",
-        "presentation": { "default_locale": locale },
-        "questions": [{
-            "id": question_id,
-            "type": "enum",
-            "title": prompt,
-            "required": true,
-            "choices": choices,
-        }]
-    });
+#[derive(Args, Debug)]
+struct HealthArgs {
+    /// Pack directory scanned recursively for flows and their resolve sidecars.
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+    /// Release catalog JSON used to score component upgrades (see `doctor --release-catalog`).
+    #[arg(long = "release-catalog")]
+    release_catalog: Option<PathBuf>,
+    /// Fail when the overall score is below this value (0-100).
+    #[arg(long = "min-score", value_parser = clap::value_parser!(u32).range(0..=100))]
+    min_score: Option<u32>,
+    /// Print the scorecard as JSON (same as `--format json`).
+    #[arg(long)]
+    json: bool,
+}
 
-    let mut driver = WizardDriver::new(QaWizardRunConfig {
-        spec_json: spec.to_string(),
-        initial_answers_json: None,
-        frontend: WizardFrontend::JsonUi,
-        i18n: qa_i18n,
-        verbose: false,
-    })
-    .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-    loop {
-        let ui_raw = driver
-            .next_payload_json()
-            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-        if driver.is_complete() {
-            break;
-        }
-        let ui: serde_json::Value = serde_json::from_str(&ui_raw)
-            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-        let next_id = ui
-            .get("next_question_id")
-            .and_then(serde_json::Value::as_str)
-            .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.qa_runner_failed")))?;
-        let question = ui
-            .get("questions")
-            .and_then(serde_json::Value::as_array)
-            .and_then(|questions| {
-                questions.iter().find(|q| {
-                    q.get("id")
-                        .and_then(serde_json::Value::as_str)
-                        .is_some_and(|id| id == next_id)
-                })
-            })
-            .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.qa_runner_failed")))?;
-        let title = question
-            .get("title")
-            .and_then(serde_json::Value::as_str)
-            .unwrap_or(prompt);
-        writeln!(writer, "{title}").ok();
-        let valid_choices: Vec<String> = question
-            .get("choices")
-            .and_then(serde_json::Value::as_array)
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(serde_json::Value::as_str)
-                    .map(ToOwned::to_owned)
-                    .collect()
-            })
-            .unwrap_or_default();
-        loop {
-            write!(writer, "> ").ok();
-            writer.flush().ok();
-            let line = read_input_line(reader)?;
-            if valid_choices.iter().any(|choice| choice == &line) {
-                let patch = serde_json::json!({ next_id: line });
-                driver
-                    .submit_patch_json(&patch.to_string())
-                    .map_err(|err| {
-                        anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed"))
-                    })?;
-                break;
-            }
-            writeln!(
-                writer,
-                "{}",
-                wizard_t_with(
-                    "wizard.error.invalid_choice",
-                    &[("choices", &valid_choices.join(", "))]
-                )
-            )
-            .ok();
-        }
-    }
-    let run = driver
-        .finish()
-        .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-    let value = run
-        .answer_set
-        .answers
-        .get(question_id)
-        .and_then(serde_json::Value::as_str)
-        .ok_or_else(|| {
-            anyhow!(
-                "{}",
-                wizard_t_with(
-                    "wizard.error.missing_answer_for_question",
-                    &[("id", question_id)]
-                )
-            )
-        })?;
-    Ok(value.to_string())
+#[derive(Args, Debug)]
+struct LockArgs {
+    /// Project root scanned recursively for flows and their resolve sidecars.
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+    /// Lock or check only these flows; the lockfile's other entries are kept.
+    #[arg(long = "flow")]
+    flows: Vec<PathBuf>,
+    /// Lockfile to write or check (default: `flow.lock` in `--dir`).
+    #[arg(long)]
+    lockfile: Option<PathBuf>,
+    /// Compare the flows with the lockfile instead of writing it; fails on any difference.
+    #[arg(long)]
+    check: bool,
+    /// Resolver override (fixture://...) for tests/CI.
+    #[arg(long = "resolver")]
+    resolver: Option<String>,
 }
 
-fn read_input_line<R: Read + ?Sized>(reader: &mut R) -> Result<String> {
-    let mut buf = Vec::new();
-    let mut byte = [0u8; 1];
-    loop {
-        let read = reader.read(&mut byte)?;
-        if read == 0 {
-            break;
-        }
-        if byte[0] == b'\n' {
-            break;
-        }
-        if byte[0] != b'\r' {
-            buf.push(byte[0]);
-        }
-    }
-    Ok(String::from_utf8(buf)
-        .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.invalid_utf8_input")))?
-        .trim()
-        .to_string())
+#[derive(Args, Debug)]
+struct IndexArgs {
+    #[command(subcommand)]
+    command: IndexCommand,
 }
 
-fn run_questions_with_qa_lib_io<R: Read, W: Write>(
-    questions: &[Question],
-    seed: HashMap<String, serde_json::Value>,
-    reader: &mut R,
-    writer: &mut W,
-) -> Result<HashMap<String, serde_json::Value>> {
-    let locale = resolve_locale(None);
-    let qa_i18n = wizard_qa_i18n_config_for_locale(&locale);
-    let spec = qa_form_from_questions(questions)?;
-    let mut driver = WizardDriver::new(QaWizardRunConfig {
-        spec_json: spec.to_string(),
-        initial_answers_json: Some(serde_json::Value::Object(map_from_answers(&seed)).to_string()),
-        frontend: WizardFrontend::JsonUi,
-        i18n: qa_i18n,
-        verbose: false,
-    })
-    .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+#[derive(Subcommand, Debug)]
+enum IndexCommand {
+    /// Summarize every flow under the workspace root into a fresh index.
+    Rebuild(IndexRebuildArgs),
+    /// Find nodes by bound component, flow tag or operation, refreshing stale entries first.
+    Query(IndexQueryArgs),
+}
 
-    while !driver.is_complete() {
-        let payload_raw = driver
-            .next_payload_json()
-            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-        let payload: serde_json::Value = serde_json::from_str(&payload_raw)
-            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-        let Some(question_id) = payload.get("next_question_id").and_then(|v| v.as_str()) else {
-            break;
-        };
-        let question = payload
-            .get("questions")
-            .and_then(|v| v.as_array())
-            .and_then(|questions| {
-                questions.iter().find(|question| {
-                    question
-                        .get("id")
-                        .and_then(|v| v.as_str())
-                        .is_some_and(|id| id == question_id)
-                })
-            })
-            .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.qa_runner_failed")))?;
-        let title = question
-            .get("title")
-            .and_then(|v| v.as_str())
-            .unwrap_or(question_id);
-        writeln!(writer, "{title}").ok();
-        write!(writer, "> ").ok();
-        writer.flush().ok();
-        let line = read_input_line(reader)?;
-        let answer = parse_qa_input_value(question, &line)?;
-        let patch = serde_json::json!({ question_id: answer });
-        driver
-            .submit_patch_json(&patch.to_string())
-            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-    }
+#[derive(Args, Debug)]
+struct IndexRebuildArgs {
+    /// Workspace root scanned recursively for flows; the index is written under it.
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+}
 
-    let result = driver
-        .finish()
-        .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-    let Some(answers) = result.answer_set.answers.as_object() else {
-        return Ok(seed);
-    };
-    Ok(answers
-        .iter()
-        .map(|(key, value)| (key.clone(), value.clone()))
-        .collect())
+#[derive(Args, Debug)]
+struct IndexQueryArgs {
+    /// Workspace root holding the index.
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+    /// Nodes bound to a component whose reference contains this text.
+    #[arg(long)]
+    component: Option<String>,
+    /// Only flows with this tag.
+    #[arg(long)]
+    tag: Option<String>,
+    /// Nodes with this operation.
+    #[arg(long)]
+    operation: Option<String>,
 }
 
-fn qa_form_from_questions(questions: &[Question]) -> Result<serde_json::Value> {
-    let mut mapped = Vec::with_capacity(questions.len());
-    for question in questions {
-        let qtype = match question.kind {
-            greentic_flow::questions::QuestionKind::String => "string",
-            greentic_flow::questions::QuestionKind::Bool => "boolean",
-            greentic_flow::questions::QuestionKind::Choice => "enum",
-            greentic_flow::questions::QuestionKind::Int => "integer",
-            greentic_flow::questions::QuestionKind::Float => "number",
-        };
-        let mut entry = serde_json::Map::new();
-        entry.insert(
-            "id".to_string(),
-            serde_json::Value::String(question.id.clone()),
-        );
-        entry.insert(
-            "type".to_string(),
-            serde_json::Value::String(qtype.to_string()),
-        );
-        entry.insert(
-            "title".to_string(),
-            serde_json::Value::String(question.prompt.clone()),
-        );
-        entry.insert(
-            "required".to_string(),
-            serde_json::Value::Bool(question.required),
-        );
-        if let Some(default) = question.default.as_ref() {
-            entry.insert(
-                "default_value".to_string(),
-                serde_json::Value::String(match default {
-                    serde_json::Value::String(text) => text.clone(),
-                    other => other.to_string(),
-                }),
-            );
-        }
-        if matches!(
-            question.kind,
-            greentic_flow::questions::QuestionKind::Choice
-        ) && !question.choices.is_empty()
-        {
-            let choices = question
-                .choices
-                .iter()
-                .map(|value| match value {
-                    serde_json::Value::String(text) => serde_json::Value::String(text.clone()),
-                    other => serde_json::Value::String(other.to_string()),
-                })
-                .collect::<Vec<_>>();
-            entry.insert("choices".to_string(), serde_json::Value::Array(choices));
-        }
-        if let Some(show_if) = question.show_if.as_ref()
-            && let Some(expr) = qa_visible_if_expr(show_if)
-        {
-            entry.insert("visible_if".to_string(), expr);
-        }
-        mapped.push(serde_json::Value::Object(entry));
-    }
+#[derive(Args, Debug)]
+struct BundleArgs {
+    #[command(subcommand)]
+    command: BundleCommand,
+}
 
-    Ok(serde_json::json!({
-        "id": "wizard.form",
-        "title": "wizard",
-        "version": "1.0.0",
-        "questions": mapped,
-    }))
+#[derive(Subcommand, Debug)]
+enum BundleCommand {
+    /// Write flow bundles stamped with a release channel, optionally signed; several flows are
+    /// packed in dependency order with an inter-flow manifest.
+    Pack(BundlePackArgs),
+    /// Write a detached ed25519 signature over a flow's bundle manifest.
+    Sign(BundleSignArgs),
+    /// Check a flow against its detached signature and a trusted public key.
+    Verify(BundleVerifyArgs),
 }
 
-fn qa_visible_if_expr(show_if: &serde_json::Value) -> Option<serde_json::Value> {
-    let id = show_if.get("id")?.as_str()?;
-    let equals = show_if.get("equals")?.clone();
-    Some(serde_json::json!({
-        "op": "and",
-        "expressions": [
-            { "op": "is_set", "path": format!("/{id}") },
-            {
-                "op": "eq",
-                "left": { "op": "answer", "path": format!("/{id}") },
-                "right": { "op": "literal", "value": equals }
-            }
-        ]
-    }))
+#[derive(Args, Debug)]
+struct BundlePackArgs {
+    /// Flow to pack; repeat to pack the flows of a pack together.
+    #[arg(long = "flow", required = true)]
+    flow_paths: Vec<PathBuf>,
+    /// Release channel recorded in the bundle.
+    #[arg(long, value_name = "stable|beta")]
+    channel: ReleaseChannel,
+    /// Bundle file to write (default: `<flow stem>.bundle.json` next to the flow). Only with a
+    /// single --flow.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Inter-flow manifest to write (default with several flows: `flows.manifest.json` next to
+    /// the first flow).
+    #[arg(long)]
+    manifest: Option<PathBuf>,
+    /// Also sign the bundle with this hex-encoded ed25519 signing key seed (`<out>.sig`).
+    #[arg(long)]
+    key: Option<PathBuf>,
 }
 
-fn parse_qa_input_value(question: &serde_json::Value, raw: &str) -> Result<serde_json::Value> {
-    let qtype = question
-        .get("type")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("string");
-    let required = question
-        .get("required")
-        .and_then(serde_json::Value::as_bool)
-        .unwrap_or(false);
-    let trimmed = raw.trim();
-    let effective = if trimmed.is_empty() {
-        if let Some(default) = question.get("default").and_then(serde_json::Value::as_str) {
-            default.to_string()
-        } else if let Some(default) = question
-            .get("default_value")
-            .and_then(serde_json::Value::as_str)
-        {
-            default.to_string()
-        } else if required {
-            anyhow::bail!("{}", wizard_t("wizard.error.required_input"));
-        } else {
-            match qtype {
-                "boolean" => "false".to_string(),
-                "integer" => "0".to_string(),
-                "number" => "0".to_string(),
-                "enum" => question
-                    .get("choices")
-                    .and_then(serde_json::Value::as_array)
-                    .and_then(|values| values.first())
-                    .and_then(serde_json::Value::as_str)
-                    .unwrap_or_default()
-                    .to_string(),
-                _ => String::new(),
-            }
-        }
-    } else {
-        trimmed.to_string()
-    };
-
-    match qtype {
-        "boolean" => {
-            let lower = effective.to_ascii_lowercase();
-            Ok(serde_json::Value::Bool(matches!(
-                lower.as_str(),
-                "true" | "t" | "yes" | "y" | "1"
-            )))
-        }
-        "integer" => {
-            let value = effective.parse::<i64>().with_context(|| {
-                wizard_t_with("wizard.error.invalid_integer", &[("value", &effective)])
-            })?;
-            Ok(serde_json::Value::Number(value.into()))
-        }
-        "number" => {
-            let value = effective.parse::<f64>().with_context(|| {
-                wizard_t_with("wizard.error.invalid_number", &[("value", &effective)])
-            })?;
-            let Some(number) = serde_json::Number::from_f64(value) else {
-                anyhow::bail!("{}", wizard_t("wizard.error.number_out_of_range"));
-            };
-            Ok(serde_json::Value::Number(number))
-        }
-        "enum" => {
-            let choices = question
-                .get("choices")
-                .and_then(serde_json::Value::as_array)
-                .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.enum_choices_missing")))?;
-            if let Ok(index) = effective.parse::<usize>()
-                && index > 0
-                && index <= choices.len()
-                && let Some(value) = choices[index - 1].as_str()
-            {
-                return Ok(serde_json::Value::String(value.to_string()));
-            }
-            if choices
-                .iter()
-                .any(|choice| choice.as_str().is_some_and(|value| value == effective))
-            {
-                return Ok(serde_json::Value::String(effective));
-            }
-            anyhow::bail!(
-                "{}",
-                wizard_t_with(
-                    "wizard.error.invalid_choice",
-                    &[(
-                        "choices",
-                        &choices
-                            .iter()
-                            .filter_map(serde_json::Value::as_str)
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    )]
-                )
-            );
-        }
-        _ => Ok(serde_json::Value::String(effective)),
-    }
+#[derive(Args, Debug)]
+struct BundleSignArgs {
+    /// Flow to sign.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// File holding the hex-encoded 32-byte ed25519 signing key seed.
+    #[arg(long)]
+    key: PathBuf,
+    /// Signature file to write (default: `<flow>.sig`).
+    #[arg(long)]
+    out: Option<PathBuf>,
 }
 
-struct QaInteractiveIo<'a> {
-    reader: &'a mut dyn Read,
-    writer: &'a mut dyn Write,
+#[derive(Args, Debug)]
+struct BundleVerifyArgs {
+    /// Flow to verify.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// File holding the trusted hex-encoded ed25519 public key.
+    #[arg(long = "public-key")]
+    public_key: PathBuf,
+    /// Signature file to check (default: `<flow>.sig`).
+    #[arg(long)]
+    signature: Option<PathBuf>,
 }
 
-fn run_component_qa_with_qa_lib(
-    spec: &ComponentQaSpec,
-    catalog: &I18nCatalog,
-    locale: &str,
-    mut answers: HashMap<String, serde_json::Value>,
-    interactive: bool,
-    mut qa_io: Option<&mut QaInteractiveIo<'_>>,
-) -> Result<HashMap<String, serde_json::Value>> {
-    let form_json = component_spec_to_qa_form_json(spec, catalog, locale)?;
-    if !interactive {
-        let form: qa_spec::FormSpec = serde_json::from_str(&form_json).context("parse qa form")?;
-        let result = qa_spec::validate(
-            &form,
-            &serde_json::Value::Object(map_from_answers(&answers)),
-        );
-        if !result.valid {
-            if !result.missing_required.is_empty() {
-                anyhow::bail!(
-                    "missing required answers: {} (provide --answers/--answers-file)",
-                    result.missing_required.join(", ")
-                );
-            }
-            let details = result
-                .errors
-                .iter()
-                .map(|err| err.message.as_str())
-                .collect::<Vec<_>>()
-                .join("; ");
-            anyhow::bail!("answers failed validation: {details}");
-        }
-        return Ok(answers);
-    }
-
-    let mut driver = WizardDriver::new(QaWizardRunConfig {
-        spec_json: form_json,
-        initial_answers_json: Some(
-            serde_json::Value::Object(map_from_answers(&answers)).to_string(),
-        ),
-        frontend: WizardFrontend::JsonUi,
-        i18n: QaI18nConfig {
-            locale: Some(locale.to_string()),
-            resolved: None,
-            debug: false,
-        },
-        verbose: false,
-    })
-    .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+#[derive(Args, Debug)]
+struct StateKeysArgs {
+    /// Directory scanned recursively for flows, config flows and their resolve sidecars.
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+    /// Fail when any state key is written by more than one wizard.
+    #[arg(long)]
+    strict: bool,
+}
 
-    while !driver.is_complete() {
-        let payload_raw = driver
-            .next_payload_json()
-            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-        let payload: serde_json::Value = serde_json::from_str(&payload_raw)
-            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-        let Some(next_question_id) = payload.get("next_question_id").and_then(|v| v.as_str())
-        else {
-            break;
-        };
-        let question = payload
-            .get("questions")
-            .and_then(|v| v.as_array())
-            .and_then(|questions| {
-                questions.iter().find(|question| {
-                    question
-                        .get("id")
-                        .and_then(|v| v.as_str())
-                        .is_some_and(|id| id == next_question_id)
-                })
-            })
-            .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.qa_runner_failed")))?;
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// Flow before the change.
+    old: PathBuf,
+    /// Flow after the change.
+    new: PathBuf,
+}
 
-        let title = question
-            .get("title")
-            .and_then(|v| v.as_str())
-            .unwrap_or(next_question_id);
-        if let Some(io) = qa_io.as_deref_mut() {
-            if let Some(description) = question.get("description").and_then(|v| v.as_str()) {
-                writeln!(io.writer, "{title} ({description})").ok();
-            } else {
-                writeln!(io.writer, "{title}").ok();
-            }
-            if let Some(choices) = question.get("choices").and_then(|v| v.as_array()) {
-                for (idx, choice) in choices.iter().enumerate() {
-                    if let Some(value) = choice.as_str() {
-                        writeln!(io.writer, "  {}. {}", idx + 1, value).ok();
-                    }
-                }
-            }
-        } else {
-            if let Some(description) = question.get("description").and_then(|v| v.as_str()) {
-                println!("{title} ({description})");
-            } else {
-                println!("{title}");
-            }
-            if let Some(choices) = question.get("choices").and_then(|v| v.as_array()) {
-                for (idx, choice) in choices.iter().enumerate() {
-                    if let Some(value) = choice.as_str() {
-                        println!("  {}. {}", idx + 1, value);
-                    }
-                }
-            }
-        }
+#[derive(Args, Debug)]
+struct ShowArgs {
+    /// Flow file (tracked in git).
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Git revision (`HEAD~3`, tag, commit) or ISO 8601 timestamp (`2026-10-01T14:30:00Z`).
+    #[arg(long, value_name = "REVISION|TIMESTAMP")]
+    at: String,
+    /// Compare the historical flow and sidecar with the current files instead of printing them.
+    #[arg(long)]
+    diff: bool,
+}
 
-        let prompt = match question.get("type").and_then(|v| v.as_str()) {
-            Some("enum") => wizard_t("wizard.qa.prompt.select_option"),
-            Some("boolean") => wizard_t("wizard.qa.prompt.enter_true_false"),
-            Some("number") => wizard_t("wizard.qa.prompt.enter_number"),
-            Some("integer") => wizard_t("wizard.qa.prompt.enter_integer"),
-            _ => wizard_t("wizard.qa.prompt.enter_text"),
-        };
-        let raw_owned = if let Some(io) = qa_io.as_deref_mut() {
-            write!(io.writer, "{prompt}: ").ok();
-            io.writer.flush().ok();
-            read_input_line(io.reader)?
-        } else {
-            print!("{prompt}: ");
-            io::stdout().flush().context("flush stdout")?;
-            let mut line = String::new();
-            io::stdin()
-                .read_line(&mut line)
-                .context("read interactive answer")?;
-            line.trim().to_string()
-        };
-        let raw = raw_owned.as_str();
-        let answer = parse_component_qa_input(question, raw)?;
-        let patch = serde_json::json!({ next_question_id: answer });
-        driver
-            .submit_patch_json(&patch.to_string())
-            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-    }
+#[derive(Args, Debug)]
+struct GraphArgs {
+    /// Flow file to render.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Diagram format (`--format` selects human/json output for every command).
+    #[arg(long = "graph-format", value_enum, default_value = "mermaid")]
+    graph_format: GraphFormat,
+    /// Write the diagram here instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
 
-    let result = driver
-        .finish()
-        .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
-    if let Some(object) = result.answer_set.answers.as_object() {
-        answers = object.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
-    }
-    Ok(answers)
+#[derive(Args, Debug)]
+struct RenderArgs {
+    /// Flow file to render.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Enabled feature flags (comma-separated); every other flag is off.
+    #[arg(long, value_delimiter = ',')]
+    features: Vec<String>,
+    /// Write the effective flow here instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
 }
 
-fn qa_key_fallback_label(key: &str) -> String {
-    let parts: Vec<&str> = key.split('.').collect();
-    let token = if parts.len() >= 2 {
-        match parts.last().copied().unwrap_or_default() {
-            "label" | "title" | "description" | "help" => {
-                parts.get(parts.len() - 2).copied().unwrap_or(key)
-            }
-            _ => parts.last().copied().unwrap_or(key),
-        }
-    } else {
-        key
-    };
-    token
-        .split(['_', '-'])
-        .filter(|part| !part.is_empty())
-        .map(|part| {
-            let mut chars = part.chars();
-            match chars.next() {
-                Some(first) => {
-                    let mut out = first.to_uppercase().collect::<String>();
-                    out.push_str(chars.as_str());
-                    out
-                }
-                None => String::new(),
-            }
-        })
-        .collect::<Vec<_>>()
-        .join(" ")
+#[derive(Args, Debug)]
+struct RenderMatrixArgs {
+    /// Flow file to render.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Directory of per-environment parameter files (default: `envs/` next to the flow).
+    #[arg(long = "envs")]
+    envs_dir: Option<PathBuf>,
+    /// Only these environments (repeatable); default: every file in the envs directory.
+    #[arg(long = "env")]
+    envs: Vec<String>,
+    /// Write each environment's resolved flow here as `<flow>.<env>.ygtc`.
+    #[arg(long = "out-dir")]
+    out_dir: Option<PathBuf>,
 }
 
-fn resolve_component_text(
-    text: &greentic_types::i18n_text::I18nText,
-    catalog: &I18nCatalog,
-    locale: &str,
-) -> String {
-    let resolved = greentic_flow::i18n::resolve_text(text, catalog, locale);
-    if resolved == text.key && text.key.starts_with("qa.") {
-        return qa_key_fallback_label(&text.key);
-    }
-    resolved
+#[derive(Args, Debug)]
+struct ReplayArgs {
+    /// Flow the captures were recorded from.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Recorded node payloads: a JSON array, `{"records": [...]}` or JSON lines.
+    #[arg(long)]
+    captures: PathBuf,
 }
 
-fn component_spec_to_qa_form_json(
-    spec: &ComponentQaSpec,
-    catalog: &I18nCatalog,
-    locale: &str,
-) -> Result<String> {
-    let mut questions = Vec::with_capacity(spec.questions.len());
-    for question in &spec.questions {
-        let (kind, choices) = match &question.kind {
-            QuestionKind::Text => ("string", None),
-            QuestionKind::Number => ("number", None),
-            QuestionKind::Bool => ("boolean", None),
-            QuestionKind::Choice { options } => (
-                "enum",
-                Some(
-                    options
-                        .iter()
-                        .map(|option| serde_json::Value::String(option.value.clone()))
-                        .collect::<Vec<_>>(),
-                ),
-            ),
-        };
-        let mut entry = serde_json::Map::new();
-        entry.insert(
-            "id".to_string(),
-            serde_json::Value::String(question.id.clone()),
-        );
-        entry.insert(
-            "type".to_string(),
-            serde_json::Value::String(kind.to_string()),
-        );
-        entry.insert(
-            "title".to_string(),
-            serde_json::Value::String(resolve_component_text(&question.label, catalog, locale)),
-        );
-        if let Some(help) = question.help.as_ref() {
-            entry.insert(
-                "description".to_string(),
-                serde_json::Value::String(resolve_component_text(help, catalog, locale)),
-            );
-        }
-        entry.insert(
-            "required".to_string(),
-            serde_json::Value::Bool(question.required),
-        );
-        if let Some(choice_values) = choices {
-            entry.insert(
-                "choices".to_string(),
-                serde_json::Value::Array(choice_values),
-            );
-        }
-        questions.push(serde_json::Value::Object(entry));
-    }
+#[derive(Args, Debug)]
+struct TraceArgs {
+    #[command(subcommand)]
+    command: TraceCommand,
+}
 
-    let form = serde_json::json!({
-        "id": "component-setup",
-        "title": resolve_component_text(&spec.title, catalog, locale),
-        "version": "0.6.0",
-        "description": spec.description.as_ref().map(|text| resolve_component_text(text, catalog, locale)),
-        "questions": questions,
-    });
-    serde_json::to_string(&form).context("serialize qa-lib form")
+#[derive(Subcommand, Debug)]
+enum TraceCommand {
+    /// Show the path an OTLP/JSON trace took through a flow, with taken and untaken routes.
+    Annotate(TraceAnnotateArgs),
 }
 
-fn parse_component_qa_input(question: &serde_json::Value, raw: &str) -> Result<serde_json::Value> {
-    let trimmed = raw.trim();
-    match question
-        .get("type")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("string")
-    {
-        "boolean" => {
-            let lower = trimmed.to_ascii_lowercase();
-            Ok(serde_json::Value::Bool(matches!(
-                lower.as_str(),
-                "true" | "t" | "yes" | "y" | "1"
-            )))
-        }
-        "number" => {
-            let parsed = trimmed.parse::<f64>().with_context(|| {
-                wizard_t_with("wizard.error.invalid_number", &[("value", trimmed)])
-            })?;
-            let Some(number) = serde_json::Number::from_f64(parsed) else {
-                anyhow::bail!("{}", wizard_t("wizard.error.number_out_of_range"));
-            };
-            Ok(serde_json::Value::Number(number))
-        }
-        "integer" => {
-            let parsed = trimmed.parse::<i64>().with_context(|| {
-                wizard_t_with("wizard.error.invalid_integer", &[("value", trimmed)])
-            })?;
-            Ok(serde_json::Value::Number(parsed.into()))
-        }
-        "enum" => {
-            let choices = question
-                .get("choices")
-                .and_then(serde_json::Value::as_array)
-                .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.enum_choices_missing")))?;
-            if let Ok(index) = trimmed.parse::<usize>()
-                && index > 0
-                && index <= choices.len()
-                && let Some(value) = choices[index - 1].as_str()
-            {
-                return Ok(serde_json::Value::String(value.to_string()));
-            }
-            if choices
-                .iter()
-                .any(|choice| choice.as_str().is_some_and(|value| value == trimmed))
-            {
-                return Ok(serde_json::Value::String(trimmed.to_string()));
-            }
-            anyhow::bail!(
-                "{}",
-                wizard_t_with(
-                    "wizard.error.invalid_choice",
-                    &[(
-                        "choices",
-                        &choices
-                            .iter()
-                            .filter_map(serde_json::Value::as_str)
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    )]
-                )
-            );
-        }
-        _ => Ok(serde_json::Value::String(trimmed.to_string())),
-    }
+#[derive(Args, Debug)]
+struct TraceAnnotateArgs {
+    /// Flow the trace ran.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// OTLP/JSON trace export from the runtime.
+    #[arg(long = "trace")]
+    trace_path: PathBuf,
+    /// Trace to annotate when the export holds several (default: the earliest).
+    #[arg(long = "trace-id")]
+    trace_id: Option<String>,
+    /// Diagram format for the highlighted graph.
+    #[arg(long = "graph-format", value_enum, default_value = "mermaid")]
+    graph_format: TraceGraphFormat,
+    /// Write the diagram here instead of printing it after the path.
+    #[arg(long)]
+    out: Option<PathBuf>,
 }
 
-fn map_from_answers(
-    answers: &HashMap<String, serde_json::Value>,
-) -> serde_json::Map<String, serde_json::Value> {
-    let mut map = serde_json::Map::new();
-    for (key, value) in answers {
-        if !value.is_null() {
-            map.insert(key.clone(), value.clone());
-        }
-    }
-    map
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum TraceGraphFormat {
+    Mermaid,
+    Dot,
 }
 
-fn collect_pack_flows(pack_dir: &Path) -> Result<Vec<PathBuf>> {
-    let flows_root = pack_dir.join("flows");
-    if !flows_root.exists() {
-        return Ok(Vec::new());
-    }
-    let mut out = Vec::new();
-    collect_pack_flows_recursive(&flows_root, &mut out)?;
-    out.sort();
-    Ok(out)
+#[derive(Args, Debug)]
+struct FmtArgs {
+    /// List flows that are not canonical and fail, without writing them.
+    #[arg(long)]
+    check: bool,
+    /// Reformat flows that contain comments; the comments are removed.
+    #[arg(long = "drop-comments")]
+    drop_comments: bool,
+    /// Flow files or directories to format.
+    #[arg(required = true)]
+    targets: Vec<PathBuf>,
 }
 
-fn collect_pack_flows_recursive(root: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
-    if root.is_file() {
-        if root.extension() == Some(OsStr::new("ygtc")) {
-            out.push(root.to_path_buf());
-        }
-        return Ok(());
-    }
-    if !root.is_dir() {
-        return Ok(());
-    }
-    for entry in fs::read_dir(root).with_context(|| format!("read directory {}", root.display()))? {
-        let path = entry
-            .with_context(|| format!("read directory entry in {}", root.display()))?
-            .path();
-        collect_pack_flows_recursive(&path, out)?;
-    }
-    Ok(())
+#[derive(Args, Debug)]
+struct PruneArgs {
+    /// Remove nodes whose `meta.expires` date is before today.
+    #[arg(long, required = true)]
+    expired: bool,
+    /// Date to prune as of (YYYY-MM-DD, default: today in UTC).
+    #[arg(long = "as-of")]
+    as_of: Option<String>,
+    /// Print the diff instead of writing.
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+    /// Flow files or directories to prune.
+    #[arg(required = true)]
+    targets: Vec<PathBuf>,
 }
 
-fn wizard_add_flow_with_io<R: Read, W: Write>(
-    pack_dir: &Path,
-    reader: &mut R,
-    writer: &mut W,
-    answers_log: &mut serde_json::Map<String, serde_json::Value>,
-) -> Result<()> {
-    let questions = vec![
-        Question {
-            id: "flow.scope".to_string(),
-            prompt: wizard_t("wizard.add_flow.scope.prompt"),
-            kind: greentic_flow::questions::QuestionKind::Choice,
-            required: true,
-            default: Some(serde_json::Value::String("global".to_string())),
-            choices: vec![
-                serde_json::Value::String("global".to_string()),
-                serde_json::Value::String("tenant".to_string()),
-            ],
-            show_if: None,
-            writes_to: None,
-        },
-        Question {
-            id: "flow.tenant_id".to_string(),
-            prompt: wizard_t("wizard.add_flow.tenant.prompt"),
-            kind: greentic_flow::questions::QuestionKind::String,
-            required: true,
-            default: None,
-            choices: Vec::new(),
-            show_if: Some(serde_json::json!({"id":"flow.scope","equals":"tenant"})),
-            writes_to: None,
-        },
-        Question {
-            id: "flow.team_scope".to_string(),
-            prompt: wizard_t("wizard.add_flow.team_scope.prompt"),
-            kind: greentic_flow::questions::QuestionKind::Choice,
-            required: true,
-            default: Some(serde_json::Value::String("all-teams".to_string())),
-            choices: vec![
-                serde_json::Value::String("all-teams".to_string()),
-                serde_json::Value::String("specific-team".to_string()),
-            ],
-            show_if: Some(serde_json::json!({"id":"flow.scope","equals":"tenant"})),
-            writes_to: None,
-        },
-        Question {
-            id: "flow.team_id".to_string(),
-            prompt: wizard_t("wizard.add_flow.team.prompt"),
-            kind: greentic_flow::questions::QuestionKind::String,
-            required: true,
-            default: None,
-            choices: Vec::new(),
-            show_if: Some(serde_json::json!({"id":"flow.team_scope","equals":"specific-team"})),
-            writes_to: None,
-        },
-        Question {
-            id: "flow.type".to_string(),
-            prompt: wizard_t("wizard.add_flow.type.prompt"),
-            kind: greentic_flow::questions::QuestionKind::Choice,
-            required: true,
-            default: Some(serde_json::Value::String("messaging".to_string())),
-            choices: vec![
-                serde_json::Value::String("messaging".to_string()),
-                serde_json::Value::String("events".to_string()),
-            ],
-            show_if: None,
-            writes_to: None,
-        },
-        Question {
-            id: "flow.name".to_string(),
-            prompt: wizard_t("wizard.add_flow.name.prompt"),
-            kind: greentic_flow::questions::QuestionKind::String,
-            required: true,
-            default: None,
-            choices: Vec::new(),
-            show_if: None,
-            writes_to: None,
-        },
-    ];
+#[derive(Args, Debug)]
+struct RequirementsArgs {
+    /// Flow whose bound components to inspect.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+}
 
-    let answers =
-        run_questions_with_qa_lib_io(&questions, HashMap::new(), &mut *reader, &mut *writer)?;
-    for (key, value) in &answers {
-        if !value.is_null() {
-            answers_log.insert(key.clone(), value.clone());
-        }
-    }
-    let scope = answer_str(&answers, "flow.scope")?;
-    let tenant = answers
-        .get("flow.tenant_id")
-        .and_then(serde_json::Value::as_str);
-    let team_scope = answers
-        .get("flow.team_scope")
-        .and_then(serde_json::Value::as_str);
-    let team_id = answers
-        .get("flow.team_id")
-        .and_then(serde_json::Value::as_str);
-    let flow_type = answer_str(&answers, "flow.type")?;
-    let flow_name = answer_str(&answers, "flow.name")?;
+#[derive(Args, Debug)]
+struct IdeDataArgs {
+    /// Flow to export completion data for.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Output file (default: `<flow>.ide.json` next to the flow); `-` prints to stdout.
+    #[arg(long = "out")]
+    out: Option<PathBuf>,
+}
 
-    let rel_path =
-        build_add_flow_relative_path(scope, tenant, team_scope, team_id, flow_type, flow_name)?;
-    let abs_path = pack_dir.join(&rel_path);
-    let flow_id = flow_id_from_name(flow_name)?;
-    write_new_flow_file(NewFlowFileSpec {
-        flow_path: abs_path.clone(),
-        flow_id,
-        flow_type: flow_type.to_string(),
-        schema_version: 2,
-        name: None,
-        description: None,
-        force: false,
-        backup: false,
-    })?;
-    writeln!(
-        writer,
-        "{}",
-        wizard_t_with(
-            "wizard.add_flow.created",
-            &[("path", &abs_path.display().to_string())]
-        )
-    )
-    .ok();
-    Ok(())
+#[derive(Args, Debug)]
+struct SelftestArgs {
+    #[command(subcommand)]
+    command: SelftestCommand,
 }
 
-struct NewFlowFileSpec {
+#[derive(Subcommand, Debug)]
+enum SelftestCommand {
+    /// Run parse/serialize, add-step and bundle hashing repeatedly and fail if any output differs.
+    Determinism(DeterminismArgs),
+}
+
+#[derive(Args, Debug)]
+struct DeterminismArgs {
+    /// Flow to run the suite against.
+    #[arg(long = "flow")]
     flow_path: PathBuf,
-    flow_id: String,
+    /// How many times to run each check (minimum 2).
+    #[arg(long = "runs", default_value_t = 2, value_parser = clap::value_parser!(u32).range(2..))]
+    runs: u32,
+}
+
+#[derive(Args, Debug)]
+struct CasArgs {
+    #[command(subcommand)]
+    command: CasCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum CasCommand {
+    /// Remove artifacts not used within --keep-days.
+    Gc(CasGcArgs),
+    /// Report how many artifacts the store holds and their total size.
+    Size(CasSizeArgs),
+}
+
+#[derive(Args, Debug)]
+struct CacheArgs {
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// List components in the distributor cache.
+    Ls(DistCacheArgs),
+    /// Show one distributor cache entry and check it against its digest.
+    Info(DistCacheEntryArgs),
+    /// Remove entries from the distributor cache.
+    Rm(DistCacheRmArgs),
+    /// Check every distributor cache entry for digest mismatches and orphaned manifests.
+    Verify(DistCacheVerifyArgs),
+}
+
+#[derive(Args, Debug)]
+struct CasGcArgs {
+    /// Keep artifacts used within this many days.
+    #[arg(long = "keep-days", default_value_t = 30)]
+    keep_days: u64,
+    /// Only report what would be removed.
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+    /// Store directory (default: $GREENTIC_FLOW_CAS_DIR).
+    #[arg(long = "dir")]
+    dir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct CasSizeArgs {
+    /// Store directory (default: $GREENTIC_FLOW_CAS_DIR).
+    #[arg(long = "dir")]
+    dir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct DistCacheArgs {
+    /// Cache directory (default: $GREENTIC_CACHE_DIR, else $GREENTIC_DIST_CACHE_DIR, else the
+    /// distributor's default).
+    #[arg(long = "cache-dir")]
+    cache_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct DistCacheEntryArgs {
+    /// Digest (`sha256:<hex>`, `<hex>`, or an unambiguous hex prefix).
+    digest: String,
+    #[command(flatten)]
+    cache: DistCacheArgs,
+}
+
+#[derive(Args, Debug)]
+struct DistCacheRmArgs {
+    /// Digests (`sha256:<hex>`, `<hex>`, or unambiguous hex prefixes).
+    #[arg(required = true)]
+    digests: Vec<String>,
+    #[command(flatten)]
+    cache: DistCacheArgs,
+}
+
+#[derive(Args, Debug)]
+struct DistCacheVerifyArgs {
+    /// Remove corrupt and orphaned entries instead of failing on them.
+    #[arg(long = "prune")]
+    prune: bool,
+    #[command(flatten)]
+    cache: DistCacheArgs,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum GraphFormat {
+    Mermaid,
+    Dot,
+    Svg,
+}
+
+#[derive(Args, Debug)]
+struct MinimizeArgs {
+    /// Flow file to minimize.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// What must keep reproducing: `lint:<rule>` or `error:<message text>`.
+    #[arg(long)]
+    check: MinimizeCheck,
+    /// Write the minimized flow here instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct GenerateRandomArgs {
+    /// Seed; the same seed and options always produce the same flow.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Number of nodes.
+    #[arg(long, default_value_t = 8)]
+    nodes: usize,
+    /// Probability (0.0-1.0) that a node branches on status.
+    #[arg(long, default_value_t = 0.3)]
+    branchiness: f64,
+    /// Routing kinds to draw from (repeatable or comma-separated; defaults to all).
+    #[arg(long = "routing", value_enum, value_delimiter = ',')]
+    routing: Vec<RandomRoutingKind>,
+    /// Flow type written to the document.
+    #[arg(long = "type", default_value = "messaging")]
     flow_type: String,
-    schema_version: u32,
-    name: Option<String>,
-    description: Option<String>,
+    /// Write the flow here instead of stdout.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Overwrite `--out` if it already exists.
+    #[arg(long)]
     force: bool,
-    backup: bool,
 }
 
-fn write_new_flow_file(spec: NewFlowFileSpec) -> Result<()> {
-    let doc = greentic_flow::model::FlowDoc {
-        id: spec.flow_id,
-        title: spec.name,
-        description: spec.description,
-        flow_type: spec.flow_type,
-        start: None,
-        parameters: serde_json::Value::Object(Default::default()),
-        tags: Vec::new(),
-        schema_version: Some(spec.schema_version),
-        entrypoints: IndexMap::new(),
-        meta: None,
-        nodes: IndexMap::new(),
-    };
-    let mut yaml = serde_yaml_bw::to_string(&doc)?;
-    if !yaml.ends_with('\n') {
-        yaml.push('\n');
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum RandomRoutingKind {
+    Next,
+    Branch,
+    Reply,
+    End,
+}
+
+impl From<RandomRoutingKind> for RoutingKind {
+    fn from(kind: RandomRoutingKind) -> Self {
+        match kind {
+            RandomRoutingKind::Next => RoutingKind::Next,
+            RandomRoutingKind::Branch => RoutingKind::Branch,
+            RandomRoutingKind::Reply => RoutingKind::Reply,
+            RandomRoutingKind::End => RoutingKind::End,
+        }
     }
-    write_flow_file(&spec.flow_path, &yaml, spec.force, spec.backup)
 }
 
-fn wizard_edit_flow_summary_with_io<R: Read, W: Write>(
-    flow_path: &Path,
-    reader: &mut R,
-    writer: &mut W,
-    answers_log: &mut serde_json::Map<String, serde_json::Value>,
-) -> Result<()> {
-    let doc = load_ygtc_from_path(flow_path)?;
-    let current_name = resolve_flow_summary_value(flow_path, doc.title.as_deref());
-    let current_description = resolve_flow_summary_value(flow_path, doc.description.as_deref());
-    let not_set = wizard_t("wizard.flow.summary.not_set");
-    writeln!(
-        writer,
-        "{}",
-        wizard_t_with(
-            "wizard.flow.summary.current_name",
-            &[("value", current_name.as_deref().unwrap_or(not_set.as_str()))]
-        )
-    )
-    .ok();
-    writeln!(
-        writer,
-        "{}",
-        wizard_t_with(
-            "wizard.flow.summary.current_description",
-            &[(
-                "value",
-                current_description.as_deref().unwrap_or(not_set.as_str())
-            )]
-        )
-    )
-    .ok();
-    let edit_prompt = format!(
-        "{}\n1) {}\n2) {}\nSelect action",
-        wizard_t("wizard.flow.summary.edit.prompt"),
-        wizard_t("wizard.choice.common.no"),
-        wizard_t("wizard.choice.common.yes")
-    );
-    let edit_answer = wizard_menu_answer(
-        &mut *reader,
-        &mut *writer,
-        "summary.edit",
-        &edit_prompt,
-        &["1", "2"],
-    )?;
-    answers_log.insert(
-        "summary.edit".to_string(),
-        serde_json::Value::String(edit_answer.clone()),
-    );
-    if edit_answer.trim() != "2" {
-        writeln!(writer, "{}", wizard_t("wizard.flow.summary.no_changes")).ok();
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum AnswersMode {
+    Default,
+    Config,
+}
+
+fn main() -> Result<()> {
+    if env::args().any(|arg| arg == "--dump-help-i18n") {
+        let mut entries = BTreeMap::new();
+        collect_help_i18n_entries(&Cli::command(), &[], &mut entries);
+        println!("{}", serde_json::to_string_pretty(&entries)?);
         return Ok(());
     }
-    let questions = vec![
-        Question {
-            id: "summary.name".to_string(),
-            prompt: wizard_t("wizard.flow.summary.name.prompt"),
-            kind: greentic_flow::questions::QuestionKind::String,
-            required: false,
-            default: current_name.map(serde_json::Value::String),
-            choices: Vec::new(),
-            show_if: None,
-            writes_to: None,
-        },
-        Question {
-            id: "summary.description".to_string(),
-            prompt: wizard_t("wizard.flow.summary.description.prompt"),
-            kind: greentic_flow::questions::QuestionKind::String,
-            required: false,
-            default: current_description.map(serde_json::Value::String),
-            choices: Vec::new(),
-            show_if: None,
-            writes_to: None,
-        },
-    ];
-    let answers =
-        run_questions_with_qa_lib_io(&questions, HashMap::new(), &mut *reader, &mut *writer)?;
-    for (key, value) in &answers {
-        if !value.is_null() {
-            answers_log.insert(key.clone(), value.clone());
+    let requested_locale = cli_requested_locale();
+    let (catalog, locale) = default_i18n_catalog(requested_locale.as_deref());
+    let cmd = localized_cli_command(&catalog, &locale);
+    let matches = match cmd.try_get_matches() {
+        Ok(matches) => matches,
+        Err(err) => err.exit(),
+    };
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    if let Some(locale) = cli.locale.as_deref()
+        && !locale.trim().is_empty()
+    {
+        unsafe {
+            std::env::set_var("GREENTIC_LOCALE", locale.trim());
         }
     }
-    let name = answers
-        .get("summary.name")
-        .and_then(serde_json::Value::as_str)
-        .map(str::trim)
-        .filter(|v| !v.is_empty())
-        .map(ToString::to_string);
-    let description = answers
-        .get("summary.description")
-        .and_then(serde_json::Value::as_str)
-        .map(str::trim)
-        .filter(|v| !v.is_empty())
-        .map(ToString::to_string);
-    if name.is_none() && description.is_none() {
-        writeln!(writer, "{}", wizard_t("wizard.flow.summary.no_changes")).ok();
-        return Ok(());
-    }
-    let flow_id = doc.id.clone();
-    let name_key = format!("flow.{flow_id}.title");
-    let description_key = format!("flow.{flow_id}.description");
-    let mut name_tag = None;
-    if let Some(name_value) = name.as_deref() {
-        write_pack_translation(flow_path, &name_key, name_value)?;
-        name_tag = Some(format!("i18n:{name_key}"));
-    }
-    let mut description_tag = None;
-    if let Some(description_value) = description.as_deref() {
-        write_pack_translation(flow_path, &description_key, description_value)?;
-        description_tag = Some(format!("i18n:{description_key}"));
-    }
-    handle_update(
-        UpdateArgs {
-            flow_path: flow_path.to_path_buf(),
-            flow_id: None,
-            flow_type: None,
-            schema_version: None,
-            name: name_tag,
-            description: description_tag,
-            tags: None,
+    install_cancellation(cli.timeout);
+    prompt_style::install(PromptStyle::resolve(
+        PromptFlags {
+            no_color: cli.no_color,
+            ascii: cli.ascii,
+            screen_reader: cli.screen_reader,
         },
-        false,
-    )?;
-    writeln!(writer, "{}", wizard_t("wizard.flow.summary.updated")).ok();
-    Ok(())
-}
-
-fn resolve_flow_summary_value(flow_path: &Path, value: Option<&str>) -> Option<String> {
-    let raw = value?.trim();
-    if raw.is_empty() {
-        return None;
-    }
-    let Some(key) = raw.strip_prefix("i18n:") else {
-        return Some(raw.to_string());
-    };
-    read_pack_translation(flow_path, key.trim()).or_else(|| Some(raw.to_string()))
-}
-
-fn read_pack_translation(flow_path: &Path, key: &str) -> Option<String> {
-    let pack_root = infer_pack_root_from_flow_path(flow_path).ok()?;
-    let i18n_dir = pack_root.join("i18n");
-    for filename in pack_i18n_candidate_files() {
-        let path = i18n_dir.join(filename);
-        let Ok(text) = fs::read_to_string(path) else {
-            continue;
-        };
-        let Ok(map) = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text)
-        else {
-            continue;
-        };
-        if let Some(value) = map.get(key).and_then(serde_json::Value::as_str) {
-            let trimmed = value.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
+        io::stdin().is_terminal() && io::stdout().is_terminal(),
+    )?);
+    let schema_mode = SchemaMode::resolve(cli.permissive)?;
+    let result = match cli.command {
+        Commands::New(args) => handle_new(args, cli.backup),
+        Commands::Update(args) => handle_update(args, cli.format, cli.backup),
+        Commands::ConvertType(args) => {
+            let mut reader = io::stdin().lock();
+            let mut writer = io::stdout();
+            handle_convert_type(args, cli.format, cli.backup, &mut reader, &mut writer)
+        }
+        Commands::Migrate(args) => handle_migrate(&args, cli.format, cli.backup),
+        Commands::AddStep(args) => handle_add_step(args, schema_mode, cli.format, cli.backup),
+        Commands::UpdateStep(args) => handle_update_step(args, schema_mode, cli.format, cli.backup),
+        Commands::DeleteStep(args) => handle_delete_step(args, cli.format, cli.backup),
+        Commands::RenameStep(args) => handle_rename_step(args, cli.format, cli.backup),
+        Commands::ApplyPlan(args) => handle_apply_plan(args, cli.format, cli.backup),
+        Commands::Generate(args) => handle_generate(args, schema_mode, cli.format, cli.backup),
+        Commands::Preset(args) => match args.command {
+            PresetCommand::Save(args) => handle_preset_save(args, cli.format),
+        },
+        Commands::Component(args) => match args.command {
+            ComponentCommand::Setup(args) => handle_component_setup(args, cli.format),
+            ComponentCommand::DiffSchema(args) => handle_component_diff_schema(&args, cli.format),
+            ComponentCommand::Show(args) => handle_component_show(&args, cli.format),
+        },
+        Commands::Schema(args) => match args.command {
+            SchemaCommand::Convert(args) => handle_schema_convert(&args, cli.format),
+        },
+        Commands::Adapters(args) => match args.command {
+            AdaptersCommand::List(args) => handle_adapters_list(&args, cli.format),
+            AdaptersCommand::Refresh(args) => handle_adapters_refresh(&args, cli.format),
+        },
+        Commands::Pins(args) => handle_pins(&args, cli.format),
+        Commands::Pin(args) => handle_pin(&args, cli.format),
+        Commands::Health(args) => handle_health(&args, cli.format),
+        Commands::Lock(args) => handle_lock(&args, cli.format, cli.backup),
+        Commands::Index(args) => match args.command {
+            IndexCommand::Rebuild(args) => handle_index_rebuild(&args, cli.format),
+            IndexCommand::Query(args) => handle_index_query(&args, cli.format),
+        },
+        Commands::StateKeys(args) => handle_state_keys(&args, cli.format),
+        Commands::Diff(args) => handle_diff(&args, cli.format),
+        Commands::Show(args) => handle_show(&args, cli.format),
+        Commands::Graph(args) => handle_graph(&args, cli.format),
+        Commands::Render(args) => handle_render(&args, cli.format),
+        Commands::RenderMatrix(args) => handle_render_matrix(&args, cli.format),
+        Commands::Requirements(args) => handle_requirements(&args, cli.format),
+        Commands::IdeData(args) => handle_ide_data(&args, cli.format),
+        Commands::Fmt(args) => handle_fmt(&args, cli.format, cli.backup),
+        Commands::Prune(args) => handle_prune(&args, cli.format, cli.backup),
+        Commands::Minimize(args) => handle_minimize(&args, cli.format),
+        Commands::GenerateRandom(args) => handle_generate_random(&args, cli.format),
+        Commands::Replay(args) => handle_replay(&args, cli.format),
+        Commands::Bundle(args) => match args.command {
+            BundleCommand::Pack(args) => handle_bundle_pack(&args, cli.format, cli.backup),
+            BundleCommand::Sign(args) => handle_bundle_sign(&args, cli.format, cli.backup),
+            BundleCommand::Verify(args) => handle_bundle_verify(&args, cli.format),
+        },
+        Commands::Trace(args) => match args.command {
+            TraceCommand::Annotate(args) => handle_trace_annotate(&args, cli.format),
+        },
+        Commands::Cas(args) => match args.command {
+            CasCommand::Gc(args) => handle_cas_gc(&args, cli.format),
+            CasCommand::Size(args) => handle_cas_size(&args, cli.format),
+        },
+        Commands::Cache(args) => match args.command {
+            CacheCommand::Ls(args) => handle_cache_ls(&args, cli.format),
+            CacheCommand::Info(args) => handle_cache_info(&args, cli.format),
+            CacheCommand::Rm(args) => handle_cache_rm(&args, cli.format),
+            CacheCommand::Verify(args) => handle_cache_verify(&args, cli.format),
+        },
+        Commands::Selftest(args) => match args.command {
+            SelftestCommand::Determinism(args) => handle_selftest_determinism(&args, cli.format),
+        },
+        Commands::Doctor(mut args) => {
+            if matches!(cli.format, OutputFormat::Json) {
+                args.json = true;
             }
+            handle_doctor(args, schema_mode)
         }
+        Commands::DoctorAnswers(args) => handle_doctor_answers(args),
+        Commands::Answers(args) => handle_answers(args, schema_mode),
+        Commands::BindComponent(args) => handle_bind_component(args),
+        Commands::Wizard(args) => handle_wizard(args),
+    };
+    if result.is_err()
+        && let Some(reason) = cancel::cancelled()
+    {
+        exit_cancelled(reason);
+    }
+    result
+}
+
+fn parse_timeout(raw: &str) -> std::result::Result<Duration, String> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid timeout '{raw}': expected e.g. 30s, 500ms or 2m"))?;
+    let seconds = match unit {
+        "" | "s" => value,
+        "ms" => value / 1000.0,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        _ => return Err(format!("invalid timeout unit '{unit}': use ms, s, m or h")),
+    };
+    if seconds <= 0.0 || !seconds.is_finite() {
+        return Err(format!("timeout must be positive, got '{raw}'"));
     }
-    None
+    Ok(Duration::from_secs_f64(seconds))
 }
 
-fn pack_i18n_candidate_files() -> Vec<String> {
-    let mut files = Vec::new();
-    let mut seen = BTreeSet::new();
-    let locale = resolve_locale(None);
-    let normalized = locale.trim().replace('_', "-");
-    if !normalized.is_empty() {
-        let full = format!("{normalized}.json");
-        if seen.insert(full.clone()) {
-            files.push(full);
-        }
-        if let Some((language, _)) = normalized.split_once('-') {
-            let lang = format!("{language}.json");
-            if seen.insert(lang.clone()) {
-                files.push(lang);
-            }
-        }
+/// Ctrl-C and `--timeout` end the process from a side thread: once any in-progress write phase
+/// finishes, tracked temp paths are removed and the process exits with 130 or 124. Writes replace
+/// files by rename, so nothing is left half-written.
+fn install_cancellation(timeout: Option<Duration>) {
+    if let Err(err) = ctrlc::set_handler(|| exit_cancelled(cancel::Cancelled::Interrupted)) {
+        eprintln!("warning: Ctrl-C handler unavailable: {err}");
     }
-    for fallback in ["en.json", "en-GB.json"] {
-        let fallback = fallback.to_string();
-        if seen.insert(fallback.clone()) {
-            files.push(fallback);
-        }
+    if let Some(timeout) = timeout {
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            exit_cancelled(cancel::Cancelled::TimedOut(timeout));
+        });
     }
-    files
 }
 
-fn write_pack_translation(flow_path: &Path, key: &str, value: &str) -> Result<()> {
-    let pack_root = infer_pack_root_from_flow_path(flow_path)?;
-    let i18n_dir = pack_root.join("i18n");
-    fs::create_dir_all(&i18n_dir)
-        .with_context(|| format!("create directory {}", i18n_dir.display()))?;
-    let i18n_path = i18n_dir.join("en-GB.json");
-    let mut map = if i18n_path.exists() {
-        let text = fs::read_to_string(&i18n_path)
-            .with_context(|| format!("read translation file {}", i18n_path.display()))?;
-        serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text)
-            .unwrap_or_default()
-    } else {
-        serde_json::Map::new()
-    };
-    map.insert(
-        key.to_string(),
-        serde_json::Value::String(value.to_string()),
-    );
-    let text = serde_json::to_string_pretty(&serde_json::Value::Object(map))
-        .context("serialize translation file")?;
-    fs::write(&i18n_path, text)
-        .with_context(|| format!("write translation file {}", i18n_path.display()))?;
-    Ok(())
+fn exit_cancelled(reason: cancel::Cancelled) {
+    static EXIT: Once = Once::new();
+    cancel::cancel(reason);
+    EXIT.call_once(|| {
+        let reason = cancel::cancelled().unwrap_or(reason);
+        cancel::wait_for_writes();
+        cancel::remove_temp_paths();
+        eprintln!("error: {reason}");
+        std::process::exit(reason.exit_code());
+    });
 }
 
-fn infer_pack_root_from_flow_path(flow_path: &Path) -> Result<PathBuf> {
-    let mut cursor = flow_path.parent();
-    while let Some(path) = cursor {
-        if path.file_name().and_then(|s| s.to_str()) == Some("flows") {
-            let root = path.parent().ok_or_else(|| {
-                anyhow!(
-                    "{}",
-                    wizard_t_with(
-                        "wizard.error.flow_path_has_no_pack_root",
-                        &[("path", &flow_path.display().to_string())]
-                    )
-                )
-            })?;
-            return Ok(root.to_path_buf());
-        }
-        cursor = path.parent();
-    }
-    anyhow::bail!(
-        "{}",
-        wizard_t_with(
-            "wizard.error.cannot_infer_pack_root",
-            &[("path", &flow_path.display().to_string())]
-        )
+fn handle_wizard(args: WizardArgs) -> Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    run_wizard_menu_with_config(
+        &args.pack,
+        stdin,
+        stdout,
+        WizardRunConfig {
+            answers_file: args.answers_file,
+            dry_run: args.dry_run,
+        },
     )
 }
 
-fn wizard_delete_flow_with_io<R: Read, W: Write>(
-    flow_path: &Path,
-    reader: &mut R,
-    writer: &mut W,
-) -> Result<()> {
-    let question = Question {
-        id: "delete.confirm".to_string(),
-        prompt: wizard_t("wizard.flow.delete.confirm.prompt"),
-        kind: greentic_flow::questions::QuestionKind::Choice,
-        required: true,
-        default: Some(serde_json::Value::String("no".to_string())),
-        choices: vec![
-            serde_json::Value::String("no".to_string()),
-            serde_json::Value::String("yes".to_string()),
-        ],
-        show_if: None,
-        writes_to: None,
-    };
-    let answers =
-        run_questions_with_qa_lib_io(&[question], HashMap::new(), &mut *reader, &mut *writer)?;
-    let confirm = answers
-        .get("delete.confirm")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("no");
-    if confirm != "yes" {
-        writeln!(writer, "{}", wizard_t("wizard.flow.delete.cancelled")).ok();
-        return Ok(());
-    }
+#[derive(Debug, Clone)]
+enum WizardScreen {
+    MainMenu,
+    FlowSelect,
+    FlowOps { flow_path: PathBuf },
+}
 
-    let sidecar_path = sidecar_path_for_flow(flow_path);
-    let wizard_state_path = {
-        let base = flow_path.parent().unwrap_or_else(|| Path::new("."));
-        base.join(".greentic/cache/flow_wizard")
-    };
+#[derive(Debug)]
+struct WizardSession {
+    real_pack_dir: PathBuf,
+    staged_pack_dir: PathBuf,
+    dirty: bool,
+    config: WizardRunConfig,
+    answers_log: serde_json::Map<String, serde_json::Value>,
+    answers_output_path: Option<PathBuf>,
+}
 
-    fs::remove_file(flow_path).with_context(|| format!("delete flow {}", flow_path.display()))?;
-    if sidecar_path.exists() {
-        let _ = fs::remove_file(&sidecar_path);
-    }
-    if wizard_state_path.exists() {
-        let _ = fs::remove_dir_all(&wizard_state_path);
-    }
-
-    writeln!(
-        writer,
-        "{}",
-        wizard_t_with(
-            "wizard.flow.delete.deleted",
-            &[("path", &flow_path.display().to_string())]
-        )
-    )
-    .ok();
-    Ok(())
-}
-
-fn wizard_delete_step_with_io<R: Read, W: Write>(
-    flow_path: &Path,
-    reader: &mut R,
-    writer: &mut W,
+#[cfg(test)]
+fn run_wizard_menu_with_io<R: Read, W: Write>(
+    pack_dir: &Path,
+    mut reader: R,
+    mut writer: W,
 ) -> Result<()> {
-    let doc = load_ygtc_from_path(flow_path)?;
-    let flow_ir = FlowIr::from_doc(doc)?;
-    if flow_ir.nodes.is_empty() {
-        writeln!(writer, "{}", wizard_t("wizard.step.delete.none")).ok();
-        return Ok(());
-    }
-    let mut choices: Vec<serde_json::Value> = flow_ir
-        .nodes
-        .keys()
-        .map(|id| serde_json::Value::String(id.clone()))
-        .collect();
-    let mut prompt = format!("{}\n", wizard_t("wizard.step.delete.prompt"));
-    for (idx, node_id) in flow_ir.nodes.keys().enumerate() {
-        prompt.push_str(&format!("{}. {}\n", idx + 1, node_id));
-    }
-    prompt.push_str(wizard_t("wizard.menu.nav.back").as_str());
-    choices.push(serde_json::Value::String("cancel".to_string()));
-    let question = Question {
-        id: "step.delete.id".to_string(),
-        prompt,
-        kind: greentic_flow::questions::QuestionKind::Choice,
-        required: true,
-        default: Some(serde_json::Value::String("cancel".to_string())),
-        choices,
-        show_if: None,
-        writes_to: None,
-    };
-    let answers =
-        run_questions_with_qa_lib_io(&[question], HashMap::new(), &mut *reader, &mut *writer)?;
-    let step_id = answers
-        .get("step.delete.id")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("cancel");
-    if step_id == "cancel" {
-        writeln!(writer, "{}", wizard_t("wizard.step.delete.cancelled")).ok();
-        return Ok(());
-    }
-    handle_delete_step(
-        DeleteStepArgs {
-            component_id: None,
-            flow_path: flow_path.to_path_buf(),
-            step: Some(step_id.to_string()),
-            wizard_mode: None,
-            answers: None,
-            answers_file: None,
-            answers_dir: None,
-            // Updating a step should replace prior answers artifacts for the same
-            // flow/node/mode instead of failing with "answers already exist".
-            overwrite_answers: true,
-            reask: false,
-            locale: None,
-            interactive: false,
-            component: None,
-            local_wasm: None,
-            distributor_url: None,
-            auth_token: None,
-            tenant: None,
-            env: None,
-            pack: None,
-            component_version: None,
-            abi_version: None,
-            resolver: None,
-            strategy: "splice".to_string(),
-            multi_pred: "error".to_string(),
-            assume_yes: true,
-            write: true,
-        },
-        OutputFormat::Human,
-        false,
-    )?;
-    writeln!(
-        writer,
-        "{}",
-        wizard_t_with("wizard.step.delete.deleted", &[("step", step_id)],)
+    run_wizard_menu_with_config(
+        pack_dir,
+        &mut reader,
+        &mut writer,
+        WizardRunConfig::default(),
     )
-    .ok();
-    Ok(())
-}
-
-fn wizard_list_steps_with_io<W: Write>(flow_path: &Path, writer: &mut W) -> Result<()> {
-    let doc = load_ygtc_from_path(flow_path)?;
-    let flow_ir = FlowIr::from_doc(doc)?;
-    if flow_ir.nodes.is_empty() {
-        writeln!(writer, "{}", wizard_t("wizard.step.list.none")).ok();
-        return Ok(());
-    }
-    writeln!(writer, "{}", wizard_t("wizard.step.list.header")).ok();
-    for (idx, node_id) in flow_ir.nodes.keys().enumerate() {
-        writeln!(writer, "{}. {}", idx + 1, node_id).ok();
-    }
-    Ok(())
-}
-
-#[derive(Debug, Clone)]
-struct WizardStepSourceSelection {
-    local_wasm: Option<PathBuf>,
-    component_ref: Option<String>,
-    pin: bool,
 }
 
-fn wizard_add_step_with_io<R: Read, W: Write>(
+fn run_wizard_menu_with_config<R: Read, W: Write>(
     pack_dir: &Path,
-    flow_path: &Path,
-    reader: &mut R,
-    writer: &mut W,
+    mut reader: R,
+    mut writer: W,
+    config: WizardRunConfig,
 ) -> Result<()> {
-    let doc = load_ygtc_from_path(flow_path)?;
-    let flow_ir = FlowIr::from_doc(doc)?;
-    let after = wizard_select_add_step_anchor_with_io(&flow_ir, &mut *reader, &mut *writer)?;
-
-    let source = match wizard_select_step_source(pack_dir, reader, writer, true)? {
-        Some(source) => source,
-        None => {
-            writeln!(writer, "{}", wizard_t("wizard.step.add.cancelled")).ok();
-            return Ok(());
-        }
-    };
-    let wizard_mode = wizard_select_setup_mode(reader, writer, "add")?;
-    let Some(wizard_mode) = wizard_mode else {
-        writeln!(writer, "{}", wizard_t("wizard.step.add.cancelled")).ok();
-        return Ok(());
+    let staged_pack_dir = create_wizard_staging_pack(pack_dir)?;
+    let preloaded_answers = if let Some(path) = config.answers_file.as_ref() {
+        let resolved = if path.is_absolute() {
+            path.clone()
+        } else {
+            pack_dir.join(path)
+        };
+        load_wizard_answers_file(&resolved)?
+    } else {
+        serde_json::Map::new()
     };
-
-    let resolver = env::var("GREENTIC_FLOW_WIZARD_RESOLVER")
-        .ok()
-        .filter(|value| !value.trim().is_empty());
-    let mut qa_io = QaInteractiveIo {
-        reader: &mut *reader,
-        writer: &mut *writer,
+    let mut session = WizardSession {
+        real_pack_dir: pack_dir.to_path_buf(),
+        staged_pack_dir,
+        dirty: false,
+        config,
+        answers_log: preloaded_answers,
+        answers_output_path: None,
     };
-    handle_add_step_with_qa_io(
-        AddStepArgs {
-            component_id: None,
-            flow_path: flow_path.to_path_buf(),
-            after,
-            mode: AddStepMode::Default,
-            pack_alias: None,
-            wizard_mode: Some(wizard_mode),
-            operation: None,
-            payload: "{}".to_string(),
-            routing_out: true,
-            routing_reply: false,
-            routing_next: None,
-            routing_multi_to: None,
-            routing_json: None,
-            routing_to_anchor: false,
-            config_flow: None,
-            answers: None,
-            answers_file: None,
-            answers_dir: None,
-            overwrite_answers: false,
-            reask: false,
-            locale: None,
-            interactive: true,
-            allow_cycles: false,
-            dry_run: false,
-            write: false,
-            validate_only: false,
-            manifests: Vec::new(),
-            node_id: None,
-            component_ref: source.component_ref,
-            local_wasm: source.local_wasm,
-            distributor_url: None,
-            auth_token: None,
-            tenant: None,
-            env: None,
-            pack: None,
-            component_version: None,
-            abi_version: None,
-            resolver,
-            pin: source.pin,
-            allow_contract_change: false,
-        },
-        SchemaMode::Strict,
-        OutputFormat::Human,
-        false,
-        Some(&mut qa_io),
-    )?;
-    writeln!(writer, "{}", wizard_t("wizard.step.add.done")).ok();
-    Ok(())
-}
-
-fn wizard_select_add_step_anchor_with_io<R: Read, W: Write>(
-    flow_ir: &FlowIr,
-    reader: &mut R,
-    writer: &mut W,
-) -> Result<Option<String>> {
-    let anchors: Vec<String> = flow_ir.nodes.keys().cloned().collect();
-    if anchors.is_empty() {
-        return Ok(None);
-    }
-
-    let mut prompt = format!("{}\n", wizard_t("wizard.step.add.after.prompt"));
-    let mut options = vec![wizard_t("wizard.choice.step.after.auto")];
-    options.extend(anchors.iter().cloned());
-    for (idx, option) in options.iter().enumerate() {
-        prompt.push_str(&format!("{}. {}\n", idx + 1, option));
-    }
-    prompt.push_str("Select anchor");
-
-    let choice_values: Vec<String> = (1..=options.len()).map(|n| n.to_string()).collect();
-    let choice_refs: Vec<&str> = choice_values.iter().map(String::as_str).collect();
-    let answer = wizard_menu_answer(
-        &mut *reader,
-        &mut *writer,
-        "step.add.after",
-        &prompt,
-        &choice_refs,
-    )?;
-    let selected_idx = answer
-        .trim()
-        .parse::<usize>()
-        .ok()
-        .filter(|idx| *idx >= 1 && *idx <= options.len())
-        .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.invalid_number")))?;
-    if selected_idx == 1 {
-        return Ok(None);
+    let mut screen = WizardScreen::MainMenu;
+    loop {
+        cancel::check()?;
+        match screen.clone() {
+            WizardScreen::MainMenu => {
+                let answer = wizard_menu_answer(
+                    &mut reader,
+                    &mut writer,
+                    "main.menu",
+                    &wizard_t("wizard.menu.main.prompt"),
+                    &["1", "2", "3", "4", "5", "0"],
+                )?;
+                session.answers_log.insert(
+                    "main.menu".to_string(),
+                    serde_json::Value::String(answer.clone()),
+                );
+                match answer.as_str() {
+                    "1" => {
+                        if let Err(err) = wizard_add_flow_with_io(
+                            &session.staged_pack_dir,
+                            &mut reader,
+                            &mut writer,
+                            &mut session.answers_log,
+                        ) {
+                            writeln!(writer, "{}", err).ok();
+                        } else {
+                            session.dirty = true;
+                        }
+                    }
+                    "2" => {
+                        screen = WizardScreen::FlowSelect;
+                    }
+                    "3" => {
+                        if let Err(err) = wizard_generate_translations_with_io(
+                            &session.staged_pack_dir,
+                            &mut reader,
+                            &mut writer,
+                            &mut session.answers_log,
+                        ) {
+                            writeln!(writer, "{}", err).ok();
+                        } else {
+                            session.dirty = true;
+                        }
+                    }
+                    "4" => {
+                        if let Err(err) =
+                            wizard_save_staged_changes(&mut session, &mut reader, &mut writer)
+                        {
+                            writeln!(writer, "{}", err).ok();
+                        }
+                    }
+                    "5" => {
+                        if let Err(err) =
+                            wizard_export_answers_with_io(&mut session, &mut reader, &mut writer)
+                        {
+                            writeln!(writer, "{}", err).ok();
+                        }
+                    }
+                    "0" => {
+                        if session.dirty {
+                            writeln!(writer, "{}", wizard_t("wizard.save.discarded")).ok();
+                        }
+                        let _ = fs::remove_dir_all(&session.staged_pack_dir);
+                        cancel::untrack_temp_path(&session.staged_pack_dir);
+                        return Ok(());
+                    }
+                    _ => {}
+                }
+            }
+            WizardScreen::FlowSelect => {
+                let flows = collect_pack_flows(&session.staged_pack_dir)?;
+                let mut prompt = format!("{}\n", wizard_t("wizard.menu.flow_select.title"));
+                for (idx, flow) in flows.iter().enumerate() {
+                    let rel = flow.strip_prefix(&session.staged_pack_dir).unwrap_or(flow);
+                    prompt.push_str(&format!("{}. {}\n", idx + 1, rel.display()));
+                }
+                prompt.push_str(&format!(
+                    "{}\n{}",
+                    wizard_t("wizard.menu.nav.back"),
+                    wizard_t("wizard.menu.nav.main")
+                ));
+                let mut choices: Vec<String> = (1..=flows.len()).map(|n| n.to_string()).collect();
+                choices.push("0".to_string());
+                choices.push("M".to_string());
+                let choice_refs: Vec<&str> = choices.iter().map(String::as_str).collect();
+                let answer = wizard_menu_answer(
+                    &mut reader,
+                    &mut writer,
+                    "flow.select",
+                    &prompt,
+                    &choice_refs,
+                )?;
+                session.answers_log.insert(
+                    "flow.select".to_string(),
+                    serde_json::Value::String(answer.clone()),
+                );
+                match answer.as_str() {
+                    "0" => screen = WizardScreen::MainMenu,
+                    "M" => screen = WizardScreen::MainMenu,
+                    raw => {
+                        let idx = raw
+                            .parse::<usize>()
+                            .context("parse selected flow index")?
+                            .saturating_sub(1);
+                        if let Some(flow_path) = flows.get(idx) {
+                            screen = WizardScreen::FlowOps {
+                                flow_path: flow_path.clone(),
+                            };
+                        }
+                    }
+                }
+            }
+            WizardScreen::FlowOps { flow_path } => {
+                let rel = flow_path
+                    .strip_prefix(&session.staged_pack_dir)
+                    .unwrap_or(&flow_path);
+                let prompt = wizard_t_with(
+                    "wizard.menu.flow_ops.prompt",
+                    &[("flow", &rel.display().to_string())],
+                );
+                let answer = wizard_menu_answer(
+                    &mut reader,
+                    &mut writer,
+                    "flow.ops",
+                    &prompt,
+                    &["1", "2", "3", "4", "5", "6", "7", "0", "M"],
+                )?;
+                session.answers_log.insert(
+                    "flow.ops".to_string(),
+                    serde_json::Value::String(answer.clone()),
+                );
+                match answer.as_str() {
+                    "0" => screen = WizardScreen::FlowSelect,
+                    "M" => screen = WizardScreen::MainMenu,
+                    "1" => {
+                        if let Err(err) = wizard_edit_flow_summary_with_io(
+                            &flow_path,
+                            &mut reader,
+                            &mut writer,
+                            &mut session.answers_log,
+                        ) {
+                            writeln!(writer, "{}", err).ok();
+                        } else {
+                            session.dirty = true;
+                        }
+                    }
+                    "2" => {
+                        if let Err(err) = wizard_list_steps_with_io(&flow_path, &mut writer) {
+                            writeln!(writer, "{}", err).ok();
+                        }
+                    }
+                    "3" => {
+                        if let Err(err) = wizard_add_step_with_io(
+                            &session.staged_pack_dir,
+                            &flow_path,
+                            &mut reader,
+                            &mut writer,
+                        ) {
+                            writeln!(writer, "{}", err).ok();
+                        } else {
+                            session.dirty = true;
+                        }
+                    }
+                    "4" => {
+                        if let Err(err) = wizard_update_step_with_io(
+                            &session.staged_pack_dir,
+                            &flow_path,
+                            &mut reader,
+                            &mut writer,
+                        ) {
+                            writeln!(writer, "{}", err).ok();
+                        } else {
+                            session.dirty = true;
+                        }
+                    }
+                    "5" => {
+                        if let Err(err) =
+                            wizard_delete_step_with_io(&flow_path, &mut reader, &mut writer)
+                        {
+                            writeln!(writer, "{}", err).ok();
+                        } else {
+                            session.dirty = true;
+                        }
+                    }
+                    "6" => {
+                        if let Err(err) =
+                            wizard_delete_flow_with_io(&flow_path, &mut reader, &mut writer)
+                        {
+                            writeln!(writer, "{}", err).ok();
+                        } else {
+                            session.dirty = true;
+                            screen = WizardScreen::FlowSelect;
+                        }
+                    }
+                    "7" => {
+                        if let Err(err) =
+                            wizard_save_staged_changes(&mut session, &mut reader, &mut writer)
+                        {
+                            writeln!(writer, "{}", err).ok();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
     }
-    Ok(anchors.get(selected_idx - 2).cloned())
 }
 
-fn wizard_update_step_with_io<R: Read, W: Write>(
-    pack_dir: &Path,
-    flow_path: &Path,
-    reader: &mut R,
-    writer: &mut W,
-) -> Result<()> {
-    let doc = load_ygtc_from_path(flow_path)?;
-    let flow_ir = FlowIr::from_doc(doc)?;
-    if flow_ir.nodes.is_empty() {
-        writeln!(writer, "{}", wizard_t("wizard.step.update.none")).ok();
-        return Ok(());
+fn create_wizard_staging_pack(pack_dir: &Path) -> Result<PathBuf> {
+    if !pack_dir.exists() {
+        anyhow::bail!(
+            "{}",
+            wizard_t_with(
+                "wizard.error.pack_dir_not_found",
+                &[("path", &pack_dir.display().to_string())]
+            )
+        );
     }
-
-    let mut step_choices: Vec<serde_json::Value> = flow_ir
-        .nodes
-        .keys()
-        .map(|id| serde_json::Value::String(id.clone()))
-        .collect();
-    let mut prompt = format!("{}\n", wizard_t("wizard.step.update.select.prompt"));
-    for (idx, node_id) in flow_ir.nodes.keys().enumerate() {
-        prompt.push_str(&format!("{}. {}\n", idx + 1, node_id));
+    let unique = format!(
+        "greentic-flow-wizard-{}-{}",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default()
+    );
+    let stage_root = env::temp_dir().join(unique);
+    fs::create_dir_all(&stage_root)
+        .with_context(|| format!("create directory {}", stage_root.display()))?;
+    cancel::track_temp_path(&stage_root);
+    for entry in ["flows", "i18n", "components"] {
+        let src = pack_dir.join(entry);
+        let dst = stage_root.join(entry);
+        if src.exists() {
+            copy_dir_recursive(&src, &dst)?;
+        }
     }
-    prompt.push_str(wizard_t("wizard.menu.nav.back").as_str());
-    step_choices.push(serde_json::Value::String("cancel".to_string()));
-    let step_answers = run_questions_with_qa_lib_io(
-        &[Question {
-            id: "step.update.id".to_string(),
-            prompt,
-            kind: greentic_flow::questions::QuestionKind::Choice,
-            required: true,
-            default: Some(serde_json::Value::String("cancel".to_string())),
-            choices: step_choices,
-            show_if: None,
-            writes_to: None,
-        }],
-        HashMap::new(),
-        &mut *reader,
-        &mut *writer,
-    )?;
-    let step_id = step_answers
-        .get("step.update.id")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("cancel")
-        .to_string();
-    if step_id == "cancel" {
-        writeln!(writer, "{}", wizard_t("wizard.step.update.cancelled")).ok();
+    Ok(stage_root)
+}
+
+fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+    if src.is_file() {
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("create directory {}", parent.display()))?;
+        }
+        fs::copy(src, dst)
+            .with_context(|| format!("copy file {} -> {}", src.display(), dst.display()))?;
         return Ok(());
     }
-
-    let source = match wizard_select_step_source(pack_dir, reader, writer, false)? {
-        Some(source) => source,
-        None => {
-            writeln!(writer, "{}", wizard_t("wizard.step.update.cancelled")).ok();
-            return Ok(());
+    fs::create_dir_all(dst).with_context(|| format!("create directory {}", dst.display()))?;
+    for entry in fs::read_dir(src).with_context(|| format!("read directory {}", src.display()))? {
+        let entry = entry.with_context(|| format!("read entry under {}", src.display()))?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        if src_path.is_dir() {
+            copy_dir_recursive(&src_path, &dst_path)?;
+        } else {
+            fs::copy(&src_path, &dst_path).with_context(|| {
+                format!("copy file {} -> {}", src_path.display(), dst_path.display())
+            })?;
         }
-    };
-    let wizard_mode = wizard_select_setup_mode(reader, writer, "update")?;
-    let Some(wizard_mode) = wizard_mode else {
-        writeln!(writer, "{}", wizard_t("wizard.step.update.cancelled")).ok();
+    }
+    Ok(())
+}
+
+fn wizard_save_staged_changes<R: Read, W: Write>(
+    session: &mut WizardSession,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<()> {
+    if !session.dirty {
+        writeln!(writer, "{}", wizard_t("wizard.save.no_changes")).ok();
         return Ok(());
+    }
+
+    if !session.answers_log.is_empty() {
+        let answers_path = wizard_answers_output_path(session, reader, writer)?;
+        write_wizard_answers_file(&answers_path, &session.answers_log)?;
+    }
+
+    let flows_target = session.staged_pack_dir.join("flows");
+    if wizard_has_empty_flow_nodes(&flows_target)? {
+        anyhow::bail!("{}", wizard_t("wizard.save.empty_flow"));
+    }
+    if let Err(err) = wizard_validate_flows(&flows_target) {
+        let details = err.to_string();
+        if details.contains("start_node_exists: invalid start node ''")
+            || details.contains("NodeId must not be empty")
+        {
+            anyhow::bail!("{}", wizard_t("wizard.save.empty_flow"));
+        }
+        return Err(anyhow!(
+            "{}: {details}",
+            wizard_t("wizard.save.doctor_failed")
+        ));
+    }
+
+    if !session.config.dry_run {
+        sync_staged_pack_back(session)?;
+        session.dirty = false;
+        writeln!(writer, "{}", wizard_t("wizard.save.done")).ok();
+    } else {
+        session.dirty = false;
+        writeln!(writer, "{}", wizard_t("wizard.save.dry_run_done")).ok();
+    }
+    Ok(())
+}
+
+fn wizard_has_empty_flow_nodes(root: &Path) -> Result<bool> {
+    if !root.exists() {
+        return Ok(false);
+    }
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("read directory {}", dir.display()))?
+        {
+            let entry = entry.with_context(|| format!("read entry under {}", dir.display()))?;
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            if path.extension().and_then(|ext| ext.to_str()) != Some("ygtc") {
+                continue;
+            }
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("read flow file {}", path.display()))?;
+            let doc: serde_yaml_bw::Value = serde_yaml_bw::from_str(&text)
+                .with_context(|| format!("parse flow file {}", path.display()))?;
+            let nodes_len = doc
+                .as_mapping()
+                .and_then(|map| {
+                    map.get(serde_yaml_bw::Value::String(
+                        "nodes".to_string(),
+                        None::<String>,
+                    ))
+                })
+                .and_then(serde_yaml_bw::Value::as_mapping)
+                .map(|map| map.len())
+                .unwrap_or(0);
+            if nodes_len == 0 {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn wizard_validate_flows(target: &Path) -> Result<()> {
+    let schema_text = EMBEDDED_FLOW_SCHEMA.to_string();
+    let schema_label = "embedded ygtc.flow.schema.json".to_string();
+    let schema_path = PathBuf::from("schemas/ygtc.flow.schema.json");
+    let lint_ctx = LintContext {
+        schema_text: &schema_text,
+        schema_label: &schema_label,
+        schema_path: schema_path.as_path(),
+        registry: None,
+        rules: &RuleRegistry::builtin(),
+        placeholders: &PlaceholderValuesRule::default(),
+        schema_mode: SchemaMode::Strict,
+        fix: false,
+        baseline: &BaselineMode::Off,
+        report: None,
+        runtime: None,
+        releases: None,
+        env: None,
+        prune_select: &[],
     };
+    let mut failures = 0usize;
+    lint_path(target, &lint_ctx, false, &mut failures)?;
+    if failures == 0 {
+        Ok(())
+    } else {
+        anyhow::bail!("{failures} flow(s) failed validation");
+    }
+}
 
-    let resolver = env::var("GREENTIC_FLOW_WIZARD_RESOLVER")
-        .ok()
-        .filter(|value| !value.trim().is_empty());
-    let mut qa_io = QaInteractiveIo {
-        reader: &mut *reader,
-        writer: &mut *writer,
+fn wizard_answers_output_path<R: Read, W: Write>(
+    session: &mut WizardSession,
+    _reader: &mut R,
+    _writer: &mut W,
+) -> Result<PathBuf> {
+    if let Some(path) = session.answers_output_path.clone() {
+        return Ok(path);
+    }
+    let path = session
+        .config
+        .answers_file
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("./answers.json"));
+    let resolved = if path.is_absolute() {
+        path
+    } else {
+        session.real_pack_dir.join(path)
     };
-    handle_update_step_with_qa_io(
-        UpdateStepArgs {
-            component_id: None,
-            flow_path: flow_path.to_path_buf(),
-            step: Some(step_id.clone()),
-            mode: "default".to_string(),
-            wizard_mode: Some(wizard_mode),
-            operation: None,
-            routing_out: false,
-            routing_reply: false,
-            routing_next: None,
-            routing_multi_to: None,
-            routing_json: None,
-            answers: None,
-            answers_file: None,
-            answers_dir: None,
-            overwrite_answers: false,
-            reask: false,
-            locale: None,
-            non_interactive: false,
-            interactive: true,
-            component: source.component_ref,
-            local_wasm: source.local_wasm,
-            distributor_url: None,
-            auth_token: None,
-            tenant: None,
-            env: None,
-            pack: None,
-            component_version: None,
-            abi_version: None,
-            resolver,
-            dry_run: false,
-            write: false,
-            allow_contract_change: false,
-        },
-        SchemaMode::Strict,
-        OutputFormat::Human,
-        false,
-        Some(&mut qa_io),
-    )?;
-    writeln!(
-        writer,
-        "{}",
-        wizard_t_with("wizard.step.update.done", &[("step", &step_id)],)
-    )
-    .ok();
-    Ok(())
+    session.answers_output_path = Some(resolved.clone());
+    Ok(resolved)
 }
 
-fn wizard_select_setup_mode<R: Read, W: Write>(
+fn wizard_answers_output_path_interactive<R: Read, W: Write>(
+    session: &mut WizardSession,
     reader: &mut R,
     writer: &mut W,
-    prefix: &str,
-) -> Result<Option<WizardModeArg>> {
+) -> Result<PathBuf> {
     let answers = run_questions_with_qa_lib_io(
         &[Question {
-            id: format!("step.{prefix}.setup_mode"),
-            prompt: wizard_t("wizard.step.setup_mode.prompt"),
-            kind: greentic_flow::questions::QuestionKind::Choice,
-            required: true,
-            default: Some(serde_json::Value::String("default".to_string())),
-            choices: vec![
-                serde_json::Value::String("default".to_string()),
-                serde_json::Value::String("personalised".to_string()),
-                serde_json::Value::String("cancel".to_string()),
-            ],
+            id: "wizard.answers.path".to_string(),
+            prompt: wizard_t("wizard.answers.path.prompt"),
+            kind: greentic_flow::questions::QuestionKind::String,
+            required: false,
+            default: Some(serde_json::Value::String("./answers.json".to_string())),
+            choices: Vec::new(),
             show_if: None,
             writes_to: None,
         }],
@@ -3011,1827 +2560,4754 @@ fn wizard_select_setup_mode<R: Read, W: Write>(
         &mut *reader,
         &mut *writer,
     )?;
-    let mode = answers
-        .get(&format!("step.{prefix}.setup_mode"))
+    let raw = answers
+        .get("wizard.answers.path")
         .and_then(serde_json::Value::as_str)
-        .unwrap_or("cancel");
-    let mapped = match mode {
-        "default" => Some(WizardModeArg::Default),
-        "personalised" => Some(WizardModeArg::Setup),
-        _ => None,
+        .unwrap_or("./answers.json")
+        .trim();
+    let selected = if raw.is_empty() {
+        "./answers.json"
+    } else {
+        raw
     };
-    Ok(mapped)
+    session.answers_log.insert(
+        "wizard.answers.path".to_string(),
+        serde_json::Value::String(selected.to_string()),
+    );
+    let path = PathBuf::from(selected);
+    let resolved = if path.is_absolute() {
+        path
+    } else {
+        session.real_pack_dir.join(path)
+    };
+    session.answers_output_path = Some(resolved.clone());
+    Ok(resolved)
 }
 
-fn wizard_generate_translations_with_io<R: Read, W: Write>(
-    pack_dir: &Path,
+fn wizard_export_answers_with_io<R: Read, W: Write>(
+    session: &mut WizardSession,
     reader: &mut R,
     writer: &mut W,
-    answers_log: &mut serde_json::Map<String, serde_json::Value>,
 ) -> Result<()> {
-    let answers = run_questions_with_qa_lib_io(
-        &[Question {
-            id: "wizard.translate.locales".to_string(),
-            prompt: wizard_t("wizard.translate.locales.prompt"),
-            kind: greentic_flow::questions::QuestionKind::String,
-            required: true,
-            default: None,
-            choices: Vec::new(),
-            show_if: None,
-            writes_to: None,
-        }],
-        HashMap::new(),
-        &mut *reader,
-        &mut *writer,
-    )?;
-    let locales = answer_str(&answers, "wizard.translate.locales")?.to_string();
-    answers_log.insert(
-        "wizard.translate.locales".to_string(),
-        serde_json::Value::String(locales.clone()),
-    );
-    let locale_list = parse_locale_list(&locales);
-    if locale_list.is_empty() {
-        anyhow::bail!("{}", wizard_t("wizard.translate.invalid_locales"));
-    }
+    let answers_path = wizard_answers_output_path_interactive(session, reader, writer)?;
+    write_wizard_answers_file(&answers_path, &session.answers_log)?;
+    writeln!(
+        writer,
+        "{}",
+        wizard_t_with(
+            "wizard.answers.path.saved",
+            &[("path", &answers_path.display().to_string())]
+        )
+    )
+    .ok();
+    Ok(())
+}
 
-    let en_path = pack_dir.join("i18n/en-GB.json");
-    if !en_path.exists() {
-        anyhow::bail!("{}", wizard_t("wizard.translate.missing_source"));
-    }
-    if env::var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB")
-        .ok()
-        .as_deref()
-        == Some("1")
-    {
-        generate_translations_stub(pack_dir, &locale_list)?;
-        writeln!(writer, "{}", wizard_t("wizard.translate.done")).ok();
-        return Ok(());
+fn write_wizard_answers_file(
+    path: &Path,
+    answers_log: &serde_json::Map<String, serde_json::Value>,
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("create directory {}", parent.display()))?;
     }
-    let i18n = greentic_i18n_translator::cli_i18n::CliI18n::from_request(Some("en"))
-        .map_err(anyhow::Error::msg)?;
-    let cli = greentic_i18n_translator::cli::Cli {
-        locale: Some("en".to_string()),
-        command: greentic_i18n_translator::cli::Command::Translate {
-            langs: locale_list.join(","),
-            en: en_path,
-            auth_mode: greentic_i18n_translator::cli::CliAuthMode::Auto,
-            codex_home: None,
-            batch_size: 50,
-            max_retries: 2,
-            glossary: None,
-            api_key_stdin: false,
-            overwrite_manual: false,
-            cache_dir: None,
-        },
-    };
-    greentic_i18n_translator::cli::run_with(cli, &i18n).map_err(anyhow::Error::msg)?;
-    writeln!(writer, "{}", wizard_t("wizard.translate.done")).ok();
+    let payload = serde_json::Value::Object(answers_log.clone());
+    let text = serde_json::to_string_pretty(&payload).context("serialize wizard answers")?;
+    fs::write(path, text).with_context(|| format!("write wizard answers {}", path.display()))?;
     Ok(())
 }
 
-fn parse_locale_list(raw: &str) -> Vec<String> {
-    let mut out = Vec::<String>::new();
-    for locale in raw.split(',').map(str::trim).filter(|v| !v.is_empty()) {
-        if !out.iter().any(|existing| existing == locale) {
-            out.push(locale.to_string());
-        }
+fn load_wizard_answers_file(path: &Path) -> Result<serde_json::Map<String, serde_json::Value>> {
+    if !path.exists() {
+        return Ok(serde_json::Map::new());
     }
-    out
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("read wizard answers {}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&text)
+        .with_context(|| format!("parse wizard answers {}", path.display()))?;
+    Ok(value.as_object().cloned().unwrap_or_default())
 }
 
-fn generate_translations_stub(pack_dir: &Path, locales: &[String]) -> Result<()> {
-    let i18n_dir = pack_dir.join("i18n");
-    fs::create_dir_all(&i18n_dir)
-        .with_context(|| format!("create directory {}", i18n_dir.display()))?;
-    let en_path = i18n_dir.join("en-GB.json");
-    let en_map = fs::read_to_string(&en_path)
-        .with_context(|| format!("read translation source {}", en_path.display()))
-        .and_then(|text| {
-            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text)
-                .context("parse source translation json")
-        })?;
-    for locale in locales {
-        if locale == "en" || locale == "en-GB" {
-            continue;
-        }
-        let mut target = serde_json::Map::new();
-        for (key, value) in &en_map {
-            let base = value.as_str().unwrap_or_default();
-            target.insert(
-                key.clone(),
-                serde_json::Value::String(format!("{base} [{locale}]")),
-            );
-        }
-        let out_path = i18n_dir.join(format!("{locale}.json"));
-        let text = serde_json::to_string_pretty(&serde_json::Value::Object(target))
-            .context("serialize stub translation json")?;
-        fs::write(&out_path, text)
-            .with_context(|| format!("write translation file {}", out_path.display()))?;
+fn sync_staged_pack_back(session: &mut WizardSession) -> Result<()> {
+    // Each directory is removed and copied back; hold off cancellation until all are in place.
+    let _writing = cancel::write_guard()?;
+    sync_staged_dir(session, "flows")?;
+    sync_staged_dir(session, "i18n")?;
+    sync_staged_dir(session, "components")?;
+    Ok(())
+}
+
+fn sync_staged_dir(session: &WizardSession, name: &str) -> Result<()> {
+    let staged_dir = session.staged_pack_dir.join(name);
+    let real_dir = session.real_pack_dir.join(name);
+    if real_dir.exists() {
+        fs::remove_dir_all(&real_dir)
+            .with_context(|| format!("remove directory {}", real_dir.display()))?;
+    }
+    if staged_dir.exists() {
+        copy_dir_recursive(&staged_dir, &real_dir)?;
+    } else {
+        fs::create_dir_all(&real_dir)
+            .with_context(|| format!("create directory {}", real_dir.display()))?;
     }
     Ok(())
 }
 
-fn wizard_select_step_source<R: Read, W: Write>(
-    pack_dir: &Path,
+fn wizard_menu_answer<R: Read, W: Write>(
     reader: &mut R,
     writer: &mut W,
-    ask_pin_for_remote: bool,
-) -> Result<Option<WizardStepSourceSelection>> {
-    let source_answers = run_questions_with_qa_lib_io(
-        &[Question {
-            id: "step.source.kind".to_string(),
-            prompt: wizard_t("wizard.step.source.kind.prompt"),
-            kind: greentic_flow::questions::QuestionKind::Choice,
-            required: true,
-            default: Some(serde_json::Value::String("local".to_string())),
-            choices: vec![
-                serde_json::Value::String("local".to_string()),
-                serde_json::Value::String("remote".to_string()),
-                serde_json::Value::String("cancel".to_string()),
-            ],
-            show_if: None,
-            writes_to: None,
-        }],
-        HashMap::new(),
-        &mut *reader,
-        &mut *writer,
-    )?;
-    let source_kind = source_answers
-        .get("step.source.kind")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("cancel");
-    if source_kind == "cancel" {
-        return Ok(None);
-    }
+    question_id: &str,
+    prompt: &str,
+    choices: &[&str],
+) -> Result<String> {
+    let locale = resolve_locale(None);
+    let qa_i18n = wizard_qa_i18n_config_for_locale(&locale);
+    let spec = serde_json::json!({
+        "id": format!("wizard.{question_id}"),
+        "title": "wizard",
+        "version": "1.0.0",
+        "presentation": { "default_locale": locale },
+        "questions": [{
+            "id": question_id,
+            "type": "enum",
+            "title": prompt,
+            "required": true,
+            "choices": choices,
+        }]
+    });
 
-    if source_kind == "local" {
-        let candidates = collect_pack_component_wasms(pack_dir)?;
-        let selected_path = if candidates.is_empty() {
-            let local_answers = run_questions_with_qa_lib_io(
-                &[Question {
-                    id: "step.source.local_path".to_string(),
-                    prompt: wizard_t("wizard.step.source.local.prompt"),
-                    kind: greentic_flow::questions::QuestionKind::String,
-                    required: true,
-                    default: None,
-                    choices: Vec::new(),
-                    show_if: None,
-                    writes_to: None,
-                }],
-                HashMap::new(),
-                &mut *reader,
-                &mut *writer,
-            )?;
-            PathBuf::from(answer_str(&local_answers, "step.source.local_path")?)
-        } else {
-            let mut choices = Vec::new();
-            for candidate in &candidates {
-                let display = candidate
-                    .strip_prefix(pack_dir)
-                    .unwrap_or(candidate)
-                    .display()
-                    .to_string();
-                choices.push(serde_json::Value::String(display));
-            }
-            choices.push(serde_json::Value::String("custom".to_string()));
-            choices.push(serde_json::Value::String("cancel".to_string()));
-            let choice_answers = run_questions_with_qa_lib_io(
-                &[Question {
-                    id: "step.source.local_choice".to_string(),
-                    prompt: wizard_t("wizard.step.source.local.choice.prompt"),
-                    kind: greentic_flow::questions::QuestionKind::Choice,
-                    required: true,
-                    default: choices.first().cloned(),
-                    choices: choices.clone(),
-                    show_if: None,
-                    writes_to: None,
-                }],
-                HashMap::new(),
-                &mut *reader,
-                &mut *writer,
-            )?;
-            let selected = choice_answers
-                .get("step.source.local_choice")
-                .and_then(serde_json::Value::as_str)
-                .unwrap_or("cancel");
-            if selected == "cancel" {
-                return Ok(None);
-            }
-            if selected == "custom" {
-                let local_answers = run_questions_with_qa_lib_io(
-                    &[Question {
-                        id: "step.source.local_custom_path".to_string(),
-                        prompt: wizard_t("wizard.step.source.local.prompt"),
-                        kind: greentic_flow::questions::QuestionKind::String,
-                        required: true,
-                        default: None,
-                        choices: Vec::new(),
-                        show_if: None,
-                        writes_to: None,
-                    }],
-                    HashMap::new(),
-                    &mut *reader,
-                    &mut *writer,
-                )?;
-                PathBuf::from(answer_str(&local_answers, "step.source.local_custom_path")?)
-            } else {
-                let rel = PathBuf::from(selected);
-                if rel.is_absolute() {
-                    rel
-                } else {
-                    pack_dir.join(rel)
-                }
-            }
-        };
-        let copied_local = copy_local_wasm_into_pack_components(pack_dir, &selected_path)?;
-        return Ok(Some(WizardStepSourceSelection {
-            local_wasm: Some(copied_local),
-            component_ref: None,
-            pin: true,
-        }));
-    }
-
-    let ref_answers = run_questions_with_qa_lib_io(
-        &[Question {
-            id: "step.source.remote_ref".to_string(),
-            prompt: wizard_t("wizard.step.source.remote.prompt"),
-            kind: greentic_flow::questions::QuestionKind::String,
-            required: true,
-            default: None,
-            choices: Vec::new(),
-            show_if: None,
-            writes_to: None,
-        }],
-        HashMap::new(),
-        &mut *reader,
-        &mut *writer,
-    )?;
-    let remote_ref = answer_str(&ref_answers, "step.source.remote_ref")?.to_string();
-    let pin = if ask_pin_for_remote {
-        let pin_answers = run_questions_with_qa_lib_io(
-            &[Question {
-                id: "step.source.remote_pin".to_string(),
-                prompt: wizard_t("wizard.step.source.remote.pin.prompt"),
-                kind: greentic_flow::questions::QuestionKind::Choice,
-                required: true,
-                default: Some(serde_json::Value::String("yes".to_string())),
-                choices: vec![
-                    serde_json::Value::String("yes".to_string()),
-                    serde_json::Value::String("no".to_string()),
-                ],
-                show_if: None,
-                writes_to: None,
-            }],
-            HashMap::new(),
-            &mut *reader,
-            &mut *writer,
-        )?;
-        pin_answers
-            .get("step.source.remote_pin")
+    let mut driver = WizardDriver::new(QaWizardRunConfig {
+        spec_json: spec.to_string(),
+        initial_answers_json: None,
+        frontend: WizardFrontend::JsonUi,
+        i18n: qa_i18n,
+        verbose: false,
+    })
+    .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+    loop {
+        let ui_raw = driver
+            .next_payload_json()
+            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+        if driver.is_complete() {
+            break;
+        }
+        let ui: serde_json::Value = serde_json::from_str(&ui_raw)
+            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+        let next_id = ui
+            .get("next_question_id")
             .and_then(serde_json::Value::as_str)
-            .unwrap_or("yes")
-            == "yes"
-    } else {
-        false
-    };
-    Ok(Some(WizardStepSourceSelection {
-        local_wasm: None,
-        component_ref: Some(remote_ref),
-        pin,
-    }))
-}
-
-fn copy_local_wasm_into_pack_components(pack_dir: &Path, selected_path: &Path) -> Result<PathBuf> {
-    let src_abs = if selected_path.is_absolute() {
-        selected_path.to_path_buf()
-    } else {
-        std::env::current_dir()
-            .context("resolve current directory")?
-            .join(selected_path)
-    };
-    let src_abs = fs::canonicalize(&src_abs)
-        .with_context(|| format!("resolve local wasm path {}", src_abs.display()))?;
-    if !src_abs.exists() {
-        anyhow::bail!(
-            "{}",
-            wizard_t_with(
-                "wizard.error.local_wasm_missing",
-                &[("path", &src_abs.display().to_string())]
+            .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.qa_runner_failed")))?;
+        let question = ui
+            .get("questions")
+            .and_then(serde_json::Value::as_array)
+            .and_then(|questions| {
+                questions.iter().find(|q| {
+                    q.get("id")
+                        .and_then(serde_json::Value::as_str)
+                        .is_some_and(|id| id == next_id)
+                })
+            })
+            .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.qa_runner_failed")))?;
+        let style = prompt_style::current();
+        let title = question
+            .get("title")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or(prompt);
+        if let Some(marker) = style.payload_question_marker(question) {
+            writeln!(writer, "{marker}").ok();
+        }
+        writeln!(writer, "{}", style.title(title)).ok();
+        let valid_choices: Vec<String> = question
+            .get("choices")
+            .and_then(serde_json::Value::as_array)
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(serde_json::Value::as_str)
+                    .map(ToOwned::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default();
+        loop {
+            write!(writer, "{}", style.input(None)).ok();
+            writer.flush().ok();
+            let line = read_input_line(reader)?;
+            if valid_choices.iter().any(|choice| choice == &line) {
+                let patch = serde_json::json!({ next_id: line });
+                driver
+                    .submit_patch_json(&patch.to_string())
+                    .map_err(|err| {
+                        anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed"))
+                    })?;
+                break;
+            }
+            writeln!(
+                writer,
+                "{}",
+                wizard_t_with(
+                    "wizard.error.invalid_choice",
+                    &[("choices", &valid_choices.join(", "))]
+                )
             )
-        );
-    }
-    let components_dir = pack_dir.join("components");
-    fs::create_dir_all(&components_dir)
-        .with_context(|| format!("create directory {}", components_dir.display()))?;
-    let digest = compute_local_digest(&src_abs)?;
-    let stem = src_abs
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .filter(|v| !v.is_empty())
-        .unwrap_or("component");
-    let dest = components_dir.join(format!("{stem}-{}.wasm", &digest[..12]));
-    if !dest.exists() {
-        fs::copy(&src_abs, &dest)
-            .with_context(|| format!("copy wasm {} -> {}", src_abs.display(), dest.display()))?;
-    }
-    Ok(dest)
-}
-
-fn collect_pack_component_wasms(pack_dir: &Path) -> Result<Vec<PathBuf>> {
-    let components_dir = pack_dir.join("components");
-    if !components_dir.exists() {
-        return Ok(Vec::new());
-    }
-    let mut out = Vec::new();
-    collect_component_wasms_recursive(&components_dir, &mut out)?;
-    out.sort();
-    Ok(out)
-}
-
-fn collect_component_wasms_recursive(root: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
-    if root.is_file() {
-        if root.extension() == Some(OsStr::new("wasm")) {
-            out.push(root.to_path_buf());
+            .ok();
         }
-        return Ok(());
-    }
-    if !root.is_dir() {
-        return Ok(());
-    }
-    for entry in fs::read_dir(root).with_context(|| format!("read directory {}", root.display()))? {
-        let entry = entry.with_context(|| format!("read entry under {}", root.display()))?;
-        collect_component_wasms_recursive(&entry.path(), out)?;
     }
-    Ok(())
-}
-
-fn answer_str<'a>(answers: &'a HashMap<String, serde_json::Value>, key: &str) -> Result<&'a str> {
-    answers
-        .get(key)
+    let run = driver
+        .finish()
+        .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+    let value = run
+        .answer_set
+        .answers
+        .get(question_id)
         .and_then(serde_json::Value::as_str)
-        .filter(|value| !value.trim().is_empty())
         .ok_or_else(|| {
             anyhow!(
                 "{}",
-                wizard_t_with("wizard.error.missing_required_answer", &[("key", key)])
+                wizard_t_with(
+                    "wizard.error.missing_answer_for_question",
+                    &[("id", question_id)]
+                )
             )
-        })
-}
-
-fn flow_id_from_name(name: &str) -> Result<String> {
-    let stem = Path::new(name)
-        .file_stem()
-        .and_then(|v| v.to_str())
-        .unwrap_or(name)
-        .trim();
-    if stem.is_empty() {
-        anyhow::bail!("{}", wizard_t("wizard.error.flow_name_empty"));
-    }
-    Ok(stem.replace(' ', "-"))
+        })?;
+    Ok(value.to_string())
 }
 
-fn ensure_ygtc_name(name: &str) -> Result<String> {
-    let trimmed = name.trim();
-    if trimmed.is_empty() {
-        anyhow::bail!("{}", wizard_t("wizard.error.flow_name_empty"));
-    }
-    if trimmed.ends_with(".ygtc") {
-        Ok(trimmed.to_string())
-    } else {
-        Ok(format!("{trimmed}.ygtc"))
-    }
-}
-
-fn build_add_flow_relative_path(
-    scope: &str,
-    tenant_id: Option<&str>,
-    team_scope: Option<&str>,
-    team_id: Option<&str>,
-    flow_type: &str,
-    flow_name: &str,
-) -> Result<PathBuf> {
-    if flow_type != "messaging" && flow_type != "events" {
-        anyhow::bail!(
-            "{}",
-            wizard_t_with(
-                "wizard.error.flow_type_unsupported",
-                &[("flow_type", flow_type)]
-            )
-        );
-    }
-    let file_name = ensure_ygtc_name(flow_name)?;
-    let path = match scope {
-        "global" => PathBuf::from("flows")
-            .join("global")
-            .join(flow_type)
-            .join(file_name),
-        "tenant" => {
-            let tenant = tenant_id
-                .map(str::trim)
-                .filter(|v| !v.is_empty())
-                .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.tenant_id_required")))?;
-            let team_segment = match team_scope {
-                Some("all-teams") | None => "all-teams".to_string(),
-                Some("specific-team") => {
-                    let team = team_id
-                        .map(str::trim)
-                        .filter(|v| !v.is_empty())
-                        .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.team_id_required")))?;
-                    team.to_string()
-                }
-                Some(other) => {
-                    anyhow::bail!(
-                        "{}",
-                        wizard_t_with("wizard.error.team_scope_unsupported", &[("scope", other)])
-                    )
-                }
-            };
-            PathBuf::from("flows")
-                .join(tenant)
-                .join(team_segment)
-                .join(flow_type)
-                .join(file_name)
+fn read_input_line<R: Read + ?Sized>(reader: &mut R) -> Result<String> {
+    let mut buf = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        let read = reader.read(&mut byte)?;
+        if read == 0 {
+            break;
         }
-        other => {
-            anyhow::bail!(
-                "{}",
-                wizard_t_with("wizard.error.flow_scope_unsupported", &[("scope", other)])
-            )
+        if byte[0] == b'\n' {
+            break;
         }
-    };
-    Ok(path)
-}
-
-fn wizard_catalog_for_locale(locale: &str) -> I18nCatalog {
-    static CATALOGS: OnceLock<Mutex<HashMap<String, I18nCatalog>>> = OnceLock::new();
-    let catalogs = CATALOGS.get_or_init(|| Mutex::new(HashMap::new()));
-    let mut guard = catalogs.lock().expect("wizard catalogs lock");
-    if let Some(cached) = guard.get(locale) {
-        return cached.clone();
-    }
-    let mut catalog = I18nCatalog::default();
-    merge_wizard_i18n_json_embedded(&mut catalog, "en");
-    if locale != "en" {
-        merge_wizard_i18n_json_embedded(&mut catalog, locale);
-    }
-    if let Some((language, _)) = locale.split_once('-')
-        && !language.is_empty()
-        && language != locale
-        && language != "en"
-    {
-        merge_wizard_i18n_json_embedded(&mut catalog, language);
-    }
-    guard.insert(locale.to_string(), catalog.clone());
-    catalog
-}
-
-fn wizard_resolved_map_for_locale(locale: &str) -> std::collections::BTreeMap<String, String> {
-    static RESOLVED: OnceLock<Mutex<HashMap<String, std::collections::BTreeMap<String, String>>>> =
-        OnceLock::new();
-    let resolved_cache = RESOLVED.get_or_init(|| Mutex::new(HashMap::new()));
-    let mut guard = resolved_cache.lock().expect("wizard resolved map lock");
-    if let Some(cached) = guard.get(locale) {
-        return cached.clone();
-    }
-    let catalog = wizard_catalog_for_locale(locale);
-    let mut resolved = std::collections::BTreeMap::new();
-    for key in embedded_wizard_keys() {
-        let value = resolve_cli_text(&catalog, locale, &key, &key);
-        if value != key {
-            resolved.insert(key.clone(), value.clone());
-            resolved.insert(format!("{locale}:{key}"), value.clone());
-            resolved.insert(format!("{locale}/{key}"), value);
+        if byte[0] != b'\r' {
+            buf.push(byte[0]);
         }
     }
-    guard.insert(locale.to_string(), resolved.clone());
-    resolved
+    Ok(String::from_utf8(buf)
+        .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.invalid_utf8_input")))?
+        .trim()
+        .to_string())
 }
 
-fn wizard_qa_i18n_config_for_locale(locale: &str) -> QaI18nConfig {
-    QaI18nConfig {
-        locale: Some(locale.to_string()),
-        resolved: Some(wizard_resolved_map_for_locale(locale)),
-        debug: false,
-    }
-}
+fn run_questions_with_qa_lib_io<R: Read, W: Write>(
+    questions: &[Question],
+    seed: HashMap<String, serde_json::Value>,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<HashMap<String, serde_json::Value>> {
+    let locale = resolve_locale(None);
+    let qa_i18n = wizard_qa_i18n_config_for_locale(&locale);
+    let spec = qa_form_from_questions(questions)?;
+    let mut driver = WizardDriver::new(QaWizardRunConfig {
+        spec_json: spec.to_string(),
+        initial_answers_json: Some(serde_json::Value::Object(map_from_answers(&seed)).to_string()),
+        frontend: WizardFrontend::JsonUi,
+        i18n: qa_i18n,
+        verbose: false,
+    })
+    .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
 
-fn merge_wizard_i18n_json_embedded(catalog: &mut I18nCatalog, locale: &str) {
-    let file_name = format!("{locale}.json");
-    let Some(file) = EMBEDDED_WIZARD_I18N_DIR.get_file(&file_name) else {
-        return;
-    };
-    let Some(text) = file.contents_utf8() else {
-        return;
-    };
-    let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
-        return;
-    };
-    let Some(entries) = value.as_object() else {
-        return;
-    };
-    for (key, value) in entries {
-        if let Some(message) = value.as_str() {
-            catalog.insert(key.clone(), locale.to_string(), message.to_string());
+    while !driver.is_complete() {
+        let payload_raw = driver
+            .next_payload_json()
+            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+        let payload: serde_json::Value = serde_json::from_str(&payload_raw)
+            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+        let Some(question_id) = payload.get("next_question_id").and_then(|v| v.as_str()) else {
+            break;
+        };
+        let question = payload
+            .get("questions")
+            .and_then(|v| v.as_array())
+            .and_then(|questions| {
+                questions.iter().find(|question| {
+                    question
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .is_some_and(|id| id == question_id)
+                })
+            })
+            .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.qa_runner_failed")))?;
+        let style = prompt_style::current();
+        let title = question
+            .get("title")
+            .and_then(|v| v.as_str())
+            .unwrap_or(question_id);
+        if let Some(marker) = style.payload_question_marker(question) {
+            writeln!(writer, "{marker}").ok();
         }
+        writeln!(writer, "{}", style.title(title)).ok();
+        write!(writer, "{}", style.input(None)).ok();
+        writer.flush().ok();
+        let line = read_input_line(reader)?;
+        let answer = parse_qa_input_value(question, &line)?;
+        let patch = serde_json::json!({ question_id: answer });
+        driver
+            .submit_patch_json(&patch.to_string())
+            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
     }
-}
 
-fn embedded_wizard_keys() -> Vec<String> {
-    let file = EMBEDDED_WIZARD_I18N_DIR
-        .get_file("en.json")
-        .or_else(|| EMBEDDED_WIZARD_I18N_DIR.get_file("en-GB.json"));
-    let Some(file) = file else {
-        return Vec::new();
-    };
-    let Some(text) = file.contents_utf8() else {
-        return Vec::new();
-    };
-    let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
-        return Vec::new();
-    };
-    let Some(entries) = value.as_object() else {
-        return Vec::new();
+    let result = driver
+        .finish()
+        .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+    let Some(answers) = result.answer_set.answers.as_object() else {
+        return Ok(seed);
     };
-    entries.keys().cloned().collect()
-}
-
-fn wizard_t(key: &str) -> String {
-    let locale = resolve_locale(None);
-    let catalog = wizard_catalog_for_locale(&locale);
-    let value = resolve_cli_text(&catalog, &locale, key, key);
-    if value == key {
-        format!("[[missing:{key}]]")
-    } else {
-        value
-    }
-}
-
-fn wizard_t_with(key: &str, replacements: &[(&str, &str)]) -> String {
-    let mut value = wizard_t(key);
-    for (name, replacement) in replacements {
-        value = value.replace(&format!("{{{name}}}"), replacement);
-    }
-    value
+    Ok(answers
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect())
 }
 
-fn handle_doctor(args: DoctorArgs, schema_mode: SchemaMode) -> Result<()> {
-    if args.stdin && !args.json {
-        anyhow::bail!("--stdin currently requires --json");
-    }
-    if args.stdin && !args.targets.is_empty() {
-        anyhow::bail!("--stdin cannot be combined with file targets");
-    }
-
-    let (schema_text, schema_label, schema_path) = if let Some(schema_path) = &args.schema {
-        let text = fs::read_to_string(schema_path)
-            .with_context(|| format!("failed to read schema {}", schema_path.display()))?;
-        (text, schema_path.display().to_string(), schema_path.clone())
-    } else {
-        (
-            EMBEDDED_FLOW_SCHEMA.to_string(),
-            "embedded ygtc.flow.schema.json".to_string(),
-            PathBuf::from("schemas/ygtc.flow.schema.json"),
-        )
-    };
-
-    let registry = if let Some(path) = &args.registry {
-        Some(AdapterCatalog::load_from_file(path)?)
-    } else {
-        None
-    };
-    let lint_ctx = LintContext {
-        schema_text: &schema_text,
-        schema_label: &schema_label,
-        schema_path: schema_path.as_path(),
-        registry: registry.as_ref(),
-        schema_mode,
-    };
-
-    if args.json {
-        let stdin_content = if args.stdin {
-            Some(read_stdin_flow()?)
-        } else {
-            None
+fn qa_form_from_questions(questions: &[Question]) -> Result<serde_json::Value> {
+    let mut mapped = Vec::with_capacity(questions.len());
+    for question in questions {
+        let qtype = match question.kind {
+            greentic_flow::questions::QuestionKind::String => "string",
+            greentic_flow::questions::QuestionKind::Bool => "boolean",
+            greentic_flow::questions::QuestionKind::Choice => "enum",
+            greentic_flow::questions::QuestionKind::Int => "integer",
+            greentic_flow::questions::QuestionKind::Float => "number",
         };
-        return run_json(
-            &args.targets,
-            stdin_content,
-            &schema_text,
-            &schema_label,
-            &schema_path,
-            registry.as_ref(),
-            schema_mode,
+        let mut entry = serde_json::Map::new();
+        entry.insert(
+            "id".to_string(),
+            serde_json::Value::String(question.id.clone()),
         );
-    }
-
-    let mut failures = 0usize;
-    for target in &args.targets {
-        lint_path(target, &lint_ctx, true, &mut failures)?;
-        if target.is_file() {
-            let mut contract_diags = validate_contracts_for_flow(target, args.online)?;
-            contract_diags.sort_by(|a, b| {
-                a.node_id
-                    .cmp(&b.node_id)
-                    .then_with(|| a.severity.cmp(&b.severity))
-                    .then_with(|| a.code.cmp(b.code))
-            });
-            for diag in &contract_diags {
-                match diag.severity {
-                    ContractSeverity::Error => {
-                        eprintln!("error: {} ({}:{})", diag.message, diag.node_id, diag.code)
-                    }
-                    ContractSeverity::Warning => {
-                        eprintln!("warning: {} ({}:{})", diag.message, diag.node_id, diag.code)
-                    }
-                }
-            }
-            if contract_diags
+        entry.insert(
+            "type".to_string(),
+            serde_json::Value::String(qtype.to_string()),
+        );
+        entry.insert(
+            "title".to_string(),
+            serde_json::Value::String(question.prompt.clone()),
+        );
+        entry.insert(
+            "required".to_string(),
+            serde_json::Value::Bool(question.required),
+        );
+        if let Some(default) = question.default.as_ref() {
+            entry.insert(
+                "default_value".to_string(),
+                serde_json::Value::String(match default {
+                    serde_json::Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                }),
+            );
+        }
+        if matches!(
+            question.kind,
+            greentic_flow::questions::QuestionKind::Choice
+        ) && !question.choices.is_empty()
+        {
+            let choices = question
+                .choices
                 .iter()
-                .any(|d| matches!(d.severity, ContractSeverity::Error))
-            {
-                failures += 1;
-            }
+                .map(|value| match value {
+                    serde_json::Value::String(text) => serde_json::Value::String(text.clone()),
+                    other => serde_json::Value::String(other.to_string()),
+                })
+                .collect::<Vec<_>>();
+            entry.insert("choices".to_string(), serde_json::Value::Array(choices));
+        }
+        if let Some(show_if) = question.show_if.as_ref()
+            && let Some(expr) = qa_visible_if_expr(show_if)
+        {
+            entry.insert("visible_if".to_string(), expr);
         }
+        mapped.push(serde_json::Value::Object(entry));
     }
 
-    if failures == 0 {
-        println!("All flows valid");
-        Ok(())
-    } else {
-        Err(anyhow::anyhow!("{failures} flow(s) failed validation"))
-    }
+    Ok(serde_json::json!({
+        "id": "wizard.form",
+        "title": "wizard",
+        "version": "1.0.0",
+        "questions": mapped,
+    }))
 }
 
-fn handle_new(args: NewArgs, backup: bool) -> Result<()> {
-    write_new_flow_file(NewFlowFileSpec {
-        flow_path: args.flow_path.clone(),
-        flow_id: args.flow_id.clone(),
-        flow_type: args.flow_type.clone(),
-        schema_version: args.schema_version,
-        name: args.name,
-        description: args.description,
-        force: args.force,
-        backup,
-    })?;
-    println!(
-        "Created flow '{}' at {} (type: {})",
-        args.flow_id,
-        args.flow_path.display(),
-        args.flow_type
-    );
-    Ok(())
+fn qa_visible_if_expr(show_if: &serde_json::Value) -> Option<serde_json::Value> {
+    let id = show_if.get("id")?.as_str()?;
+    let equals = show_if.get("equals")?.clone();
+    Some(serde_json::json!({
+        "op": "and",
+        "expressions": [
+            { "op": "is_set", "path": format!("/{id}") },
+            {
+                "op": "eq",
+                "left": { "op": "answer", "path": format!("/{id}") },
+                "right": { "op": "literal", "value": equals }
+            }
+        ]
+    }))
 }
 
-fn handle_doctor_answers(args: DoctorAnswersArgs) -> Result<()> {
-    let schema_text = fs::read_to_string(&args.schema)
-        .with_context(|| format!("read schema {}", args.schema.display()))?;
-    let answers_text = fs::read_to_string(&args.answers)
-        .with_context(|| format!("read answers {}", args.answers.display()))?;
-    let schema: serde_json::Value =
-        serde_json::from_str(&schema_text).context("parse schema as JSON")?;
-    let answers: serde_json::Value =
-        serde_json::from_str(&answers_text).context("parse answers as JSON")?;
-
-    let compiled = jsonschema::options()
-        .with_draft(Draft::Draft202012)
-        .build(&schema)
-        .context("compile answers schema")?;
-    if let Err(error) = compiled.validate(&answers) {
-        let messages = vec![error.to_string()];
-        if args.json {
-            let payload = json!({ "ok": false, "errors": messages });
-            print_json_payload(&payload)?;
-            std::process::exit(1);
+fn parse_qa_input_value(question: &serde_json::Value, raw: &str) -> Result<serde_json::Value> {
+    let qtype = question
+        .get("type")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("string");
+    let required = question
+        .get("required")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false);
+    let trimmed = raw.trim();
+    let effective = if trimmed.is_empty() {
+        if let Some(default) = question.get("default").and_then(serde_json::Value::as_str) {
+            default.to_string()
+        } else if let Some(default) = question
+            .get("default_value")
+            .and_then(serde_json::Value::as_str)
+        {
+            default.to_string()
+        } else if required {
+            anyhow::bail!("{}", wizard_t("wizard.error.required_input"));
         } else {
-            for msg in &messages {
-                eprintln!("error: {msg}");
+            match qtype {
+                "boolean" => "false".to_string(),
+                "integer" => "0".to_string(),
+                "number" => "0".to_string(),
+                "enum" => question
+                    .get("choices")
+                    .and_then(serde_json::Value::as_array)
+                    .and_then(|values| values.first())
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                _ => String::new(),
             }
         }
-        anyhow::bail!("answers failed schema validation");
-    }
+    } else {
+        trimmed.to_string()
+    };
 
-    if args.json {
-        let payload = json!({ "ok": true, "errors": [] });
-        print_json_payload(&payload)?;
+    match qtype {
+        "boolean" => {
+            let lower = effective.to_ascii_lowercase();
+            Ok(serde_json::Value::Bool(matches!(
+                lower.as_str(),
+                "true" | "t" | "yes" | "y" | "1"
+            )))
+        }
+        "integer" => {
+            let value = effective.parse::<i64>().with_context(|| {
+                wizard_t_with("wizard.error.invalid_integer", &[("value", &effective)])
+            })?;
+            Ok(serde_json::Value::Number(value.into()))
+        }
+        "number" => {
+            let value = effective.parse::<f64>().with_context(|| {
+                wizard_t_with("wizard.error.invalid_number", &[("value", &effective)])
+            })?;
+            let Some(number) = serde_json::Number::from_f64(value) else {
+                anyhow::bail!("{}", wizard_t("wizard.error.number_out_of_range"));
+            };
+            Ok(serde_json::Value::Number(number))
+        }
+        "enum" => {
+            let choices = question
+                .get("choices")
+                .and_then(serde_json::Value::as_array)
+                .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.enum_choices_missing")))?;
+            if let Ok(index) = effective.parse::<usize>()
+                && index > 0
+                && index <= choices.len()
+                && let Some(value) = choices[index - 1].as_str()
+            {
+                return Ok(serde_json::Value::String(value.to_string()));
+            }
+            if choices
+                .iter()
+                .any(|choice| choice.as_str().is_some_and(|value| value == effective))
+            {
+                return Ok(serde_json::Value::String(effective));
+            }
+            anyhow::bail!(
+                "{}",
+                wizard_t_with(
+                    "wizard.error.invalid_choice",
+                    &[(
+                        "choices",
+                        &choices
+                            .iter()
+                            .filter_map(serde_json::Value::as_str)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )]
+                )
+            );
+        }
+        _ => Ok(serde_json::Value::String(effective)),
     }
-    Ok(())
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
-enum ContractSeverity {
-    Error,
-    Warning,
-}
-
-struct ContractDiagnostic {
-    code: &'static str,
-    severity: ContractSeverity,
-    message: String,
-    node_id: String,
+struct QaInteractiveIo<'a> {
+    reader: &'a mut dyn Read,
+    writer: &'a mut dyn Write,
 }
 
-fn validate_contracts_for_flow(flow_path: &Path, online: bool) -> Result<Vec<ContractDiagnostic>> {
-    let doc = load_ygtc_from_path(flow_path)?;
-    let flow_ir = FlowIr::from_doc(doc)?;
-    let mut diags = Vec::new();
-
-    for (node_id, node) in &flow_ir.nodes {
-        if !node_payload_looks_like_component(&node.payload) {
-            continue;
+fn run_component_qa_with_qa_lib(
+    spec: &ComponentQaSpec,
+    catalog: &I18nCatalog,
+    locale: &str,
+    mut answers: HashMap<String, serde_json::Value>,
+    interactive: bool,
+    mut qa_io: Option<&mut QaInteractiveIo<'_>>,
+) -> Result<HashMap<String, serde_json::Value>> {
+    let form_json = component_spec_to_qa_form_json(spec, catalog, locale)?;
+    if !interactive {
+        let form: qa_spec::FormSpec = serde_json::from_str(&form_json).context("parse qa form")?;
+        let result = qa_spec::validate(
+            &form,
+            &serde_json::Value::Object(map_from_answers(&answers)),
+        );
+        if !result.valid {
+            if !result.missing_required.is_empty() {
+                anyhow::bail!(
+                    "missing required answers: {} (provide --answers/--answers-file)",
+                    result.missing_required.join(", ")
+                );
+            }
+            let details = result
+                .errors
+                .iter()
+                .map(|err| err.message.as_str())
+                .collect::<Vec<_>>()
+                .join("; ");
+            anyhow::bail!("answers failed validation: {details}");
         }
-        let meta = flow_ir
-            .meta
-            .as_ref()
-            .and_then(|meta| meta.as_object())
-            .and_then(|root| root.get(flow_meta::META_NAMESPACE))
-            .and_then(|value| value.as_object())
-            .and_then(|greentic| greentic.get("components"))
-            .and_then(|value| value.as_object())
-            .and_then(|components| components.get(node_id))
-            .and_then(|value| value.as_object());
+        return Ok(answers);
+    }
 
-        let Some(entry) = meta else {
-            diags.push(ContractDiagnostic {
-                code: "FLOW_MISSING_METADATA",
-                severity: ContractSeverity::Error,
-                message: "missing component contract metadata".to_string(),
-                node_id: node_id.clone(),
-            });
-            continue;
+    let mut driver = WizardDriver::new(QaWizardRunConfig {
+        spec_json: form_json,
+        initial_answers_json: Some(
+            serde_json::Value::Object(map_from_answers(&answers)).to_string(),
+        ),
+        frontend: WizardFrontend::JsonUi,
+        i18n: QaI18nConfig {
+            locale: Some(locale.to_string()),
+            resolved: None,
+            debug: false,
+        },
+        verbose: false,
+    })
+    .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+
+    while !driver.is_complete() {
+        let payload_raw = driver
+            .next_payload_json()
+            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+        let payload: serde_json::Value = serde_json::from_str(&payload_raw)
+            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+        let Some(next_question_id) = payload.get("next_question_id").and_then(|v| v.as_str())
+        else {
+            break;
         };
+        let question = payload
+            .get("questions")
+            .and_then(|v| v.as_array())
+            .and_then(|questions| {
+                questions.iter().find(|question| {
+                    question
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .is_some_and(|id| id == next_question_id)
+                })
+            })
+            .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.qa_runner_failed")))?;
 
-        for field in ["describe_hash", "schema_hash", "operation_id"] {
-            if entry.get(field).and_then(|v| v.as_str()).is_none() {
-                diags.push(ContractDiagnostic {
-                    code: "FLOW_MISSING_METADATA",
-                    severity: ContractSeverity::Error,
-                    message: format!("missing required metadata field '{field}'"),
-                    node_id: node_id.clone(),
-                });
-            }
+        let style = prompt_style::current();
+        let title = question
+            .get("title")
+            .and_then(|v| v.as_str())
+            .unwrap_or(next_question_id);
+        let mut lines: Vec<String> = style
+            .payload_question_marker(question)
+            .into_iter()
+            .collect();
+        match question.get("description").and_then(|v| v.as_str()) {
+            Some(description) => lines.push(format!(
+                "{} {}",
+                style.title(title),
+                style.hint(&format!("({description})"))
+            )),
+            None => lines.push(style.title(title)),
         }
-
-        if !online {
-            if let Some(schema_hex) = entry.get("config_schema_cbor").and_then(|v| v.as_str()) {
-                let schema_bytes = match hex_to_bytes(schema_hex) {
-                    Ok(bytes) => bytes,
-                    Err(err) => {
-                        diags.push(ContractDiagnostic {
-                            code: "FLOW_SCHEMA_DECODE",
-                            severity: ContractSeverity::Error,
-                            message: format!("failed to decode stored config schema: {err}"),
-                            node_id: node_id.clone(),
-                        });
-                        continue;
-                    }
-                };
-                let schema: greentic_types::schemas::common::schema_ir::SchemaIr =
-                    match greentic_types::cbor::canonical::from_cbor(&schema_bytes) {
-                        Ok(schema) => schema,
-                        Err(err) => {
-                            diags.push(ContractDiagnostic {
-                                code: "FLOW_SCHEMA_DECODE",
-                                severity: ContractSeverity::Error,
-                                message: format!("failed to parse stored config schema: {err}"),
-                                node_id: node_id.clone(),
-                            });
-                            continue;
-                        }
-                    };
-                let config_value = extract_config_value(&node.payload);
-                let config_cbor =
-                    greentic_types::cbor::canonical::to_canonical_cbor_allow_floats(&config_value)
-                        .map_err(|err| anyhow!("encode config for validation: {err}"))?;
-                let config_val: ciborium::value::Value =
-                    ciborium::de::from_reader(config_cbor.as_slice())
-                        .map_err(|err| anyhow!("decode config cbor: {err}"))?;
-                let schema_diags = validate_value_against_schema(&schema, &config_val);
-                for diag in schema_diags {
-                    let severity = match diag.severity {
-                        Severity::Error => ContractSeverity::Error,
-                        Severity::Warning => ContractSeverity::Warning,
-                    };
-                    diags.push(ContractDiagnostic {
-                        code: diag.code,
-                        severity,
-                        message: diag.message,
-                        node_id: node_id.clone(),
-                    });
+        if let Some(choices) = question.get("choices").and_then(|v| v.as_array()) {
+            for (idx, choice) in choices.iter().enumerate() {
+                if let Some(value) = choice.as_str() {
+                    lines.push(style.choice(idx + 1, value, "."));
                 }
+            }
+        }
+        for line in &lines {
+            if let Some(io) = qa_io.as_deref_mut() {
+                writeln!(io.writer, "{line}").ok();
             } else {
-                diags.push(ContractDiagnostic {
-                    code: "FLOW_SCHEMA_MISSING",
-                    severity: ContractSeverity::Warning,
-                    message: "missing stored config schema for offline validation".to_string(),
-                    node_id: node_id.clone(),
-                });
+                println!("{line}");
             }
-            continue;
         }
 
-        let Some(operation_id) = entry
-            .get("operation_id")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-        else {
-            continue;
-        };
-
-        let Some(sidecar) = read_flow_resolve(flow_path).ok() else {
-            diags.push(ContractDiagnostic {
-                code: "FLOW_MISSING_SIDECAR",
-                severity: ContractSeverity::Error,
-                message: "missing resolve sidecar for online validation".to_string(),
-                node_id: node_id.clone(),
-            });
-            continue;
-        };
-        let Some(node_resolve) = sidecar.nodes.get(node_id) else {
-            diags.push(ContractDiagnostic {
-                code: "FLOW_MISSING_SIDECAR",
-                severity: ContractSeverity::Error,
-                message: "missing sidecar entry for node".to_string(),
-                node_id: node_id.clone(),
-            });
-            continue;
+        let prompt = match question.get("type").and_then(|v| v.as_str()) {
+            Some("enum") => wizard_t("wizard.qa.prompt.select_option"),
+            Some("boolean") => wizard_t("wizard.qa.prompt.enter_true_false"),
+            Some("number") => wizard_t("wizard.qa.prompt.enter_number"),
+            Some("integer") => wizard_t("wizard.qa.prompt.enter_integer"),
+            _ => wizard_t("wizard.qa.prompt.enter_text"),
         };
-
-        let resolved = resolve_source_to_wasm(flow_path, &node_resolve.source)?;
-        let spec = wizard_ops::fetch_wizard_spec(&resolved, wizard_ops::WizardMode::Default)?;
-        let (config_schema, computed_meta) = if !spec.describe_cbor.is_empty() {
-            let (describe, meta) = derive_contract_meta(&spec.describe_cbor, &operation_id)?;
-            (Some(describe.config_schema), meta)
-        } else if let Some(descriptor) = spec.descriptor.as_ref() {
-            derive_contract_meta_from_descriptor(descriptor, &operation_id)?
+        let prompt = style.input(Some(&prompt));
+        let raw_owned = if let Some(io) = qa_io.as_deref_mut() {
+            write!(io.writer, "{prompt}").ok();
+            io.writer.flush().ok();
+            read_input_line(io.reader)?
         } else {
-            diags.push(ContractDiagnostic {
-                code: "FLOW_CONTRACT_SKIPPED",
-                severity: ContractSeverity::Warning,
-                message:
-                    "descriptor and describe_cbor are both unavailable; skipping contract checks"
-                        .to_string(),
-                node_id: node_id.clone(),
-            });
-            continue;
+            print!("{prompt}");
+            io::stdout().flush().context("flush stdout")?;
+            let mut line = String::new();
+            io::stdin()
+                .read_line(&mut line)
+                .context("read interactive answer")?;
+            line.trim().to_string()
         };
+        let raw = raw_owned.as_str();
+        let answer = parse_component_qa_input(question, raw)?;
+        let patch = serde_json::json!({ next_question_id: answer });
+        driver
+            .submit_patch_json(&patch.to_string())
+            .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+    }
 
-        if let Some(stored) = entry.get("describe_hash").and_then(|v| v.as_str())
-            && stored != computed_meta.describe_hash
-        {
-            diags.push(ContractDiagnostic {
-                code: "FLOW_CONTRACT_DRIFT",
-                severity: ContractSeverity::Error,
-                message: "describe_hash mismatch (contract drift)".to_string(),
-                node_id: node_id.clone(),
-            });
-        }
-        if let Some(stored) = entry.get("schema_hash").and_then(|v| v.as_str())
-            && stored != computed_meta.schema_hash
-        {
-            diags.push(ContractDiagnostic {
-                code: "FLOW_SCHEMA_HASH_MISMATCH",
-                severity: ContractSeverity::Error,
-                message: "schema_hash mismatch".to_string(),
-                node_id: node_id.clone(),
-            });
+    let result = driver
+        .finish()
+        .map_err(|err| anyhow!("{}: {err}", wizard_t("wizard.error.qa_runner_failed")))?;
+    if let Some(object) = result.answer_set.answers.as_object() {
+        answers = object.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    }
+    Ok(answers)
+}
+
+fn qa_key_fallback_label(key: &str) -> String {
+    let parts: Vec<&str> = key.split('.').collect();
+    let token = if parts.len() >= 2 {
+        match parts.last().copied().unwrap_or_default() {
+            "label" | "title" | "description" | "help" => {
+                parts.get(parts.len() - 2).copied().unwrap_or(key)
+            }
+            _ => parts.last().copied().unwrap_or(key),
         }
+    } else {
+        key
+    };
+    token
+        .split(['_', '-'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => {
+                    let mut out = first.to_uppercase().collect::<String>();
+                    out.push_str(chars.as_str());
+                    out
+                }
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-        let config_value = extract_config_value(&node.payload);
-        let config_cbor =
-            greentic_types::cbor::canonical::to_canonical_cbor_allow_floats(&config_value)
-                .map_err(|err| anyhow!("encode config for validation: {err}"))?;
-        let Some(schema) = config_schema else {
-            diags.push(ContractDiagnostic {
-                code: "FLOW_SCHEMA_MISSING",
-                severity: ContractSeverity::Warning,
-                message: "missing inline input schema in descriptor for online validation"
-                    .to_string(),
-                node_id: node_id.clone(),
-            });
-            continue;
+fn resolve_component_text(
+    text: &greentic_types::i18n_text::I18nText,
+    catalog: &I18nCatalog,
+    locale: &str,
+) -> String {
+    let resolved = greentic_flow::i18n::resolve_text(text, catalog, locale);
+    if resolved == text.key && text.key.starts_with("qa.") {
+        return qa_key_fallback_label(&text.key);
+    }
+    resolved
+}
+
+fn component_spec_to_qa_form_json(
+    spec: &ComponentQaSpec,
+    catalog: &I18nCatalog,
+    locale: &str,
+) -> Result<String> {
+    let mut questions = Vec::with_capacity(spec.questions.len());
+    for question in &spec.questions {
+        let (kind, choices) = match &question.kind {
+            QuestionKind::Text => ("string", None),
+            QuestionKind::Number => ("number", None),
+            QuestionKind::Bool => ("boolean", None),
+            QuestionKind::Choice { options } => (
+                "enum",
+                Some(
+                    options
+                        .iter()
+                        .map(|option| serde_json::Value::String(option.value.clone()))
+                        .collect::<Vec<_>>(),
+                ),
+            ),
         };
-        let schema_diags = validate_value_against_schema(
-            &schema,
-            &ciborium::de::from_reader(config_cbor.as_slice())
-                .map_err(|err| anyhow!("decode config cbor: {err}"))?,
+        let mut entry = serde_json::Map::new();
+        entry.insert(
+            "id".to_string(),
+            serde_json::Value::String(question.id.clone()),
         );
-        for diag in schema_diags {
-            let severity = match diag.severity {
-                Severity::Error => ContractSeverity::Error,
-                Severity::Warning => ContractSeverity::Warning,
-            };
-            diags.push(ContractDiagnostic {
-                code: diag.code,
-                severity,
-                message: diag.message,
-                node_id: node_id.clone(),
-            });
+        entry.insert(
+            "type".to_string(),
+            serde_json::Value::String(kind.to_string()),
+        );
+        entry.insert(
+            "title".to_string(),
+            serde_json::Value::String(resolve_component_text(&question.label, catalog, locale)),
+        );
+        if let Some(help) = question.help.as_ref() {
+            entry.insert(
+                "description".to_string(),
+                serde_json::Value::String(resolve_component_text(help, catalog, locale)),
+            );
+        }
+        entry.insert(
+            "required".to_string(),
+            serde_json::Value::Bool(question.required),
+        );
+        if let Some(choice_values) = choices {
+            entry.insert(
+                "choices".to_string(),
+                serde_json::Value::Array(choice_values),
+            );
         }
+        questions.push(serde_json::Value::Object(entry));
     }
 
-    Ok(diags)
+    let form = serde_json::json!({
+        "id": "component-setup",
+        "title": resolve_component_text(&spec.title, catalog, locale),
+        "version": "0.6.0",
+        "description": spec.description.as_ref().map(|text| resolve_component_text(text, catalog, locale)),
+        "questions": questions,
+    });
+    serde_json::to_string(&form).context("serialize qa-lib form")
 }
 
-fn node_payload_looks_like_component(payload: &serde_json::Value) -> bool {
-    if let Some(obj) = payload.as_object() {
-        if obj.contains_key("component") || obj.contains_key("config") {
-            return true;
+fn parse_component_qa_input(question: &serde_json::Value, raw: &str) -> Result<serde_json::Value> {
+    let trimmed = raw.trim();
+    match question
+        .get("type")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("string")
+    {
+        "boolean" => {
+            let lower = trimmed.to_ascii_lowercase();
+            Ok(serde_json::Value::Bool(matches!(
+                lower.as_str(),
+                "true" | "t" | "yes" | "y" | "1"
+            )))
         }
-        if let Some(exec) = obj.get("component.exec") {
-            return exec.is_object();
+        "number" => {
+            let parsed = trimmed.parse::<f64>().with_context(|| {
+                wizard_t_with("wizard.error.invalid_number", &[("value", trimmed)])
+            })?;
+            let Some(number) = serde_json::Number::from_f64(parsed) else {
+                anyhow::bail!("{}", wizard_t("wizard.error.number_out_of_range"));
+            };
+            Ok(serde_json::Value::Number(number))
+        }
+        "integer" => {
+            let parsed = trimmed.parse::<i64>().with_context(|| {
+                wizard_t_with("wizard.error.invalid_integer", &[("value", trimmed)])
+            })?;
+            Ok(serde_json::Value::Number(parsed.into()))
+        }
+        "enum" => {
+            let choices = question
+                .get("choices")
+                .and_then(serde_json::Value::as_array)
+                .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.enum_choices_missing")))?;
+            if let Ok(index) = trimmed.parse::<usize>()
+                && index > 0
+                && index <= choices.len()
+                && let Some(value) = choices[index - 1].as_str()
+            {
+                return Ok(serde_json::Value::String(value.to_string()));
+            }
+            if choices
+                .iter()
+                .any(|choice| choice.as_str().is_some_and(|value| value == trimmed))
+            {
+                return Ok(serde_json::Value::String(trimmed.to_string()));
+            }
+            anyhow::bail!(
+                "{}",
+                wizard_t_with(
+                    "wizard.error.invalid_choice",
+                    &[(
+                        "choices",
+                        &choices
+                            .iter()
+                            .filter_map(serde_json::Value::as_str)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )]
+                )
+            );
         }
+        _ => Ok(serde_json::Value::String(trimmed.to_string())),
     }
-    false
 }
 
-fn extract_config_value(payload: &serde_json::Value) -> serde_json::Value {
-    if let Some(obj) = payload.as_object()
-        && let Some(config) = obj.get("config")
-    {
-        return config.clone();
+fn map_from_answers(
+    answers: &HashMap<String, serde_json::Value>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut map = serde_json::Map::new();
+    for (key, value) in answers {
+        if !value.is_null() {
+            map.insert(key.clone(), value.clone());
+        }
     }
-    payload.clone()
+    map
 }
 
-fn resolve_source_to_wasm(flow_path: &Path, source: &ComponentSourceRefV1) -> Result<Vec<u8>> {
-    match source {
-        ComponentSourceRefV1::Local { path, .. } => {
-            let local_path = local_path_from_sidecar(path, flow_path);
-            let bytes = fs::read(&local_path)
-                .with_context(|| format!("read wasm at {}", local_path.display()))?;
-            Ok(bytes)
-        }
-        ComponentSourceRefV1::Oci { r#ref, .. }
-        | ComponentSourceRefV1::Repo { r#ref, .. }
-        | ComponentSourceRefV1::Store { r#ref, .. } => {
-            let resolved = resolve_ref_to_bytes(r#ref, None)?;
-            Ok(resolved.bytes)
-        }
+fn collect_pack_flows(pack_dir: &Path) -> Result<Vec<PathBuf>> {
+    let flows_root = pack_dir.join("flows");
+    if !flows_root.exists() {
+        return Ok(Vec::new());
     }
+    let mut out = Vec::new();
+    collect_pack_flows_recursive(&flows_root, &mut out)?;
+    out.sort();
+    Ok(out)
 }
 
-fn handle_answers(args: AnswersArgs, schema_mode: SchemaMode) -> Result<()> {
-    let manifest_path = resolve_manifest_path_for_component(&args.component)?;
-    let manifest = load_manifest_json(&manifest_path)?;
-    let requested_flow = match args.mode {
-        AnswersMode::Default => args.operation.as_str(),
-        AnswersMode::Config => "custom",
-    };
-    let (questions, used_flow) = questions_for_operation(&manifest, requested_flow)?;
-    if used_flow.as_deref() != Some(requested_flow)
-        && let Some(flow) = &used_flow
-    {
-        eprintln!(
-            "warning: dev_flows.{} not found; using dev_flows.{} for questions",
-            requested_flow, flow
-        );
+fn collect_pack_flows_recursive(root: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    if root.is_file() {
+        if root.extension() == Some(OsStr::new("ygtc")) {
+            out.push(root.to_path_buf());
+        }
+        return Ok(());
     }
-
-    let flow_name = used_flow.as_deref().unwrap_or(requested_flow);
-    let source_desc = format!("dev_flows.{flow_name}");
-    let component_id = manifest
-        .get("id")
-        .and_then(|v| v.as_str())
-        .unwrap_or("unknown")
-        .to_string();
-    let schema = schema_for_questions(&questions);
-    let use_manifest_schema = questions.is_empty() || is_effectively_empty_schema(&schema);
-    let schema_resolution = if use_manifest_schema {
-        Some(resolve_input_schema(&manifest_path, &args.operation)?)
-    } else {
-        None
-    };
-    let (schema_source_desc, schema_operation, schema_manifest_path, schema_component_id) =
-        if let Some(resolution) = &schema_resolution {
-            (
-                "operations[].input_schema".to_string(),
-                resolution.operation.clone(),
-                resolution.manifest_path.as_path(),
-                resolution.component_id.as_str(),
-            )
-        } else {
-            (
-                source_desc,
-                flow_name.to_string(),
-                manifest_path.as_path(),
-                component_id.as_str(),
-            )
-        };
-    let schema_ref = if let Some(resolution) = &schema_resolution {
-        resolution.schema.as_ref()
-    } else {
-        Some(&schema)
-    };
-    require_schema(
-        schema_mode,
-        schema_component_id,
-        &schema_operation,
-        schema_manifest_path,
-        &schema_source_desc,
-        schema_ref,
-    )?;
-
-    let example = example_for_questions(&questions);
-    validate_example_against_schema(&schema, &example)?;
-
-    let out_dir = match args.out_dir {
-        Some(dir) => dir,
-        None => env::current_dir().context("resolve current directory")?,
-    };
-    fs::create_dir_all(&out_dir)
-        .with_context(|| format!("create output dir {}", out_dir.display()))?;
-    let schema_path = out_dir.join(format!("{}.schema.json", args.name));
-    let example_path = out_dir.join(format!("{}.example.json", args.name));
-    write_json_file(&schema_path, &schema)?;
-    write_json_file(&example_path, &example)?;
-    println!(
-        "Wrote answers schema to {} and example to {}",
-        schema_path.display(),
-        example_path.display()
-    );
-    Ok(())
-}
-
-fn handle_update(args: UpdateArgs, backup: bool) -> Result<()> {
-    if !args.flow_path.exists() {
-        anyhow::bail!(
-            "flow file {} not found; use `greentic-flow new` to create it",
-            args.flow_path.display()
-        );
-    }
-    let mut doc = load_ygtc_from_path(&args.flow_path)?;
-
-    if let Some(id) = args.flow_id {
-        doc.id = id;
-    }
-
-    if let Some(name) = args.name {
-        doc.title = Some(name);
-    }
-
-    if let Some(desc) = args.description {
-        doc.description = Some(desc);
+    if !root.is_dir() {
+        return Ok(());
     }
-
-    if let Some(tags_raw) = args.tags {
-        let tags = tags_raw
-            .split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string())
-            .collect::<Vec<_>>();
-        doc.tags = tags;
+    for entry in fs::read_dir(root).with_context(|| format!("read directory {}", root.display()))? {
+        let path = entry
+            .with_context(|| format!("read directory entry in {}", root.display()))?
+            .path();
+        collect_pack_flows_recursive(&path, out)?;
     }
+    Ok(())
+}
 
-    if let Some(schema_version) = args.schema_version {
-        doc.schema_version = Some(schema_version);
-    }
+fn wizard_add_flow_with_io<R: Read, W: Write>(
+    pack_dir: &Path,
+    reader: &mut R,
+    writer: &mut W,
+    answers_log: &mut serde_json::Map<String, serde_json::Value>,
+) -> Result<()> {
+    let questions = vec![
+        Question {
+            id: "flow.scope".to_string(),
+            prompt: wizard_t("wizard.add_flow.scope.prompt"),
+            kind: greentic_flow::questions::QuestionKind::Choice,
+            required: true,
+            default: Some(serde_json::Value::String("global".to_string())),
+            choices: vec![
+                serde_json::Value::String("global".to_string()),
+                serde_json::Value::String("tenant".to_string()),
+            ],
+            show_if: None,
+            writes_to: None,
+        },
+        Question {
+            id: "flow.tenant_id".to_string(),
+            prompt: wizard_t("wizard.add_flow.tenant.prompt"),
+            kind: greentic_flow::questions::QuestionKind::String,
+            required: true,
+            default: None,
+            choices: Vec::new(),
+            show_if: Some(serde_json::json!({"id":"flow.scope","equals":"tenant"})),
+            writes_to: None,
+        },
+        Question {
+            id: "flow.team_scope".to_string(),
+            prompt: wizard_t("wizard.add_flow.team_scope.prompt"),
+            kind: greentic_flow::questions::QuestionKind::Choice,
+            required: true,
+            default: Some(serde_json::Value::String("all-teams".to_string())),
+            choices: vec![
+                serde_json::Value::String("all-teams".to_string()),
+                serde_json::Value::String("specific-team".to_string()),
+            ],
+            show_if: Some(serde_json::json!({"id":"flow.scope","equals":"tenant"})),
+            writes_to: None,
+        },
+        Question {
+            id: "flow.team_id".to_string(),
+            prompt: wizard_t("wizard.add_flow.team.prompt"),
+            kind: greentic_flow::questions::QuestionKind::String,
+            required: true,
+            default: None,
+            choices: Vec::new(),
+            show_if: Some(serde_json::json!({"id":"flow.team_scope","equals":"specific-team"})),
+            writes_to: None,
+        },
+        Question {
+            id: "flow.type".to_string(),
+            prompt: wizard_t("wizard.add_flow.type.prompt"),
+            kind: greentic_flow::questions::QuestionKind::Choice,
+            required: true,
+            default: Some(serde_json::Value::String("messaging".to_string())),
+            choices: vec![
+                serde_json::Value::String("messaging".to_string()),
+                serde_json::Value::String("events".to_string()),
+            ],
+            show_if: None,
+            writes_to: None,
+        },
+        Question {
+            id: "flow.name".to_string(),
+            prompt: wizard_t("wizard.add_flow.name.prompt"),
+            kind: greentic_flow::questions::QuestionKind::String,
+            required: true,
+            default: None,
+            choices: Vec::new(),
+            show_if: None,
+            writes_to: None,
+        },
+    ];
 
-    if let Some(flow_type) = args.flow_type {
-        let is_empty_flow =
-            doc.nodes.is_empty() && doc.entrypoints.is_empty() && doc.start.is_none();
-        if !is_empty_flow {
-            anyhow::bail!(
-                "refusing to change type on a non-empty flow; create a new flow or migrate explicitly"
-            );
+    let answers =
+        run_questions_with_qa_lib_io(&questions, HashMap::new(), &mut *reader, &mut *writer)?;
+    for (key, value) in &answers {
+        if !value.is_null() {
+            answers_log.insert(key.clone(), value.clone());
         }
-        doc.flow_type = flow_type;
     }
+    let scope = answer_str(&answers, "flow.scope")?;
+    let tenant = answers
+        .get("flow.tenant_id")
+        .and_then(serde_json::Value::as_str);
+    let team_scope = answers
+        .get("flow.team_scope")
+        .and_then(serde_json::Value::as_str);
+    let team_id = answers
+        .get("flow.team_id")
+        .and_then(serde_json::Value::as_str);
+    let flow_type = answer_str(&answers, "flow.type")?;
+    let flow_name = answer_str(&answers, "flow.name")?;
 
-    let yaml = serialize_doc(&doc)?;
-    // Validate final doc to catch accidental schema violations.
-    load_ygtc_from_str(&yaml)?;
-    write_flow_file(&args.flow_path, &yaml, true, backup)?;
-    println!("Updated flow metadata at {}", args.flow_path.display());
+    let rel_path =
+        build_add_flow_relative_path(scope, tenant, team_scope, team_id, flow_type, flow_name)?;
+    let abs_path = pack_dir.join(&rel_path);
+    let flow_id = flow_id_from_name(flow_name)?;
+    write_new_flow_file(NewFlowFileSpec {
+        flow_path: abs_path.clone(),
+        flow_id,
+        flow_type: flow_type.to_string(),
+        schema_version: 2,
+        name: None,
+        description: None,
+        force: false,
+        backup: false,
+    })?;
+    writeln!(
+        writer,
+        "{}",
+        wizard_t_with(
+            "wizard.add_flow.created",
+            &[("path", &abs_path.display().to_string())]
+        )
+    )
+    .ok();
     Ok(())
 }
 
-struct LintContext<'a> {
-    schema_text: &'a str,
-    schema_label: &'a str,
-    schema_path: &'a Path,
-    registry: Option<&'a AdapterCatalog>,
-    schema_mode: SchemaMode,
+struct NewFlowFileSpec {
+    flow_path: PathBuf,
+    flow_id: String,
+    flow_type: String,
+    schema_version: u32,
+    name: Option<String>,
+    description: Option<String>,
+    force: bool,
+    backup: bool,
 }
 
-fn lint_path(
-    path: &Path,
-    ctx: &LintContext<'_>,
-    interactive: bool,
-    failures: &mut usize,
-) -> Result<()> {
-    if path.is_file() {
-        lint_file(path, ctx, interactive, failures)?;
-    } else if path.is_dir() {
-        let entries = fs::read_dir(path)
-            .with_context(|| format!("failed to read directory {}", path.display()))?;
-        for entry in entries {
-            let entry = entry
-                .with_context(|| format!("failed to read directory entry in {}", path.display()))?;
-            lint_path(&entry.path(), ctx, interactive, failures)?;
-        }
+fn write_new_flow_file(spec: NewFlowFileSpec) -> Result<()> {
+    let doc = greentic_flow::model::FlowDoc {
+        id: spec.flow_id,
+        title: spec.name,
+        description: spec.description,
+        flow_type: spec.flow_type,
+        start: None,
+        parameters: serde_json::Value::Object(Default::default()),
+        tags: Vec::new(),
+        schema_version: Some(spec.schema_version),
+        entrypoints: IndexMap::new(),
+        meta: None,
+        nodes: IndexMap::new(),
+        spans: Default::default(),
+    };
+    let mut yaml = serde_yaml_bw::to_string(&doc)?;
+    if !yaml.ends_with('\n') {
+        yaml.push('\n');
     }
-    Ok(())
+    write_flow_file(&spec.flow_path, &yaml, spec.force, spec.backup)
 }
 
-fn lint_file(
-    path: &Path,
-    ctx: &LintContext<'_>,
-    interactive: bool,
-    failures: &mut usize,
+fn wizard_edit_flow_summary_with_io<R: Read, W: Write>(
+    flow_path: &Path,
+    reader: &mut R,
+    writer: &mut W,
+    answers_log: &mut serde_json::Map<String, serde_json::Value>,
 ) -> Result<()> {
-    if path.extension() != Some(OsStr::new("ygtc")) {
+    let doc = load_ygtc_from_path(flow_path)?;
+    let current_name = resolve_flow_summary_value(flow_path, doc.title.as_deref());
+    let current_description = resolve_flow_summary_value(flow_path, doc.description.as_deref());
+    let not_set = wizard_t("wizard.flow.summary.not_set");
+    writeln!(
+        writer,
+        "{}",
+        wizard_t_with(
+            "wizard.flow.summary.current_name",
+            &[("value", current_name.as_deref().unwrap_or(not_set.as_str()))]
+        )
+    )
+    .ok();
+    writeln!(
+        writer,
+        "{}",
+        wizard_t_with(
+            "wizard.flow.summary.current_description",
+            &[(
+                "value",
+                current_description.as_deref().unwrap_or(not_set.as_str())
+            )]
+        )
+    )
+    .ok();
+    let edit_prompt = format!(
+        "{}\n1) {}\n2) {}\nSelect action",
+        wizard_t("wizard.flow.summary.edit.prompt"),
+        wizard_t("wizard.choice.common.no"),
+        wizard_t("wizard.choice.common.yes")
+    );
+    let edit_answer = wizard_menu_answer(
+        &mut *reader,
+        &mut *writer,
+        "summary.edit",
+        &edit_prompt,
+        &["1", "2"],
+    )?;
+    answers_log.insert(
+        "summary.edit".to_string(),
+        serde_json::Value::String(edit_answer.clone()),
+    );
+    if edit_answer.trim() != "2" {
+        writeln!(writer, "{}", wizard_t("wizard.flow.summary.no_changes")).ok();
+        return Ok(());
+    }
+    let questions = vec![
+        Question {
+            id: "summary.name".to_string(),
+            prompt: wizard_t("wizard.flow.summary.name.prompt"),
+            kind: greentic_flow::questions::QuestionKind::String,
+            required: false,
+            default: current_name.map(serde_json::Value::String),
+            choices: Vec::new(),
+            show_if: None,
+            writes_to: None,
+        },
+        Question {
+            id: "summary.description".to_string(),
+            prompt: wizard_t("wizard.flow.summary.description.prompt"),
+            kind: greentic_flow::questions::QuestionKind::String,
+            required: false,
+            default: current_description.map(serde_json::Value::String),
+            choices: Vec::new(),
+            show_if: None,
+            writes_to: None,
+        },
+    ];
+    let answers =
+        run_questions_with_qa_lib_io(&questions, HashMap::new(), &mut *reader, &mut *writer)?;
+    for (key, value) in &answers {
+        if !value.is_null() {
+            answers_log.insert(key.clone(), value.clone());
+        }
+    }
+    let name = answers
+        .get("summary.name")
+        .and_then(serde_json::Value::as_str)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string);
+    let description = answers
+        .get("summary.description")
+        .and_then(serde_json::Value::as_str)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string);
+    if name.is_none() && description.is_none() {
+        writeln!(writer, "{}", wizard_t("wizard.flow.summary.no_changes")).ok();
         return Ok(());
     }
+    let flow_id = doc.id.clone();
+    let name_key = format!("flow.{flow_id}.title");
+    let description_key = format!("flow.{flow_id}.description");
+    let mut name_tag = None;
+    if let Some(name_value) = name.as_deref() {
+        write_pack_translation(flow_path, &name_key, name_value)?;
+        name_tag = Some(format!("i18n:{name_key}"));
+    }
+    let mut description_tag = None;
+    if let Some(description_value) = description.as_deref() {
+        write_pack_translation(flow_path, &description_key, description_value)?;
+        description_tag = Some(format!("i18n:{description_key}"));
+    }
+    handle_update(
+        UpdateArgs {
+            flow_path: flow_path.to_path_buf(),
+            flow_id: None,
+            flow_type: None,
+            schema_version: None,
+            name: name_tag,
+            description: description_tag,
+            tags: None,
+            dry_run: false,
+        },
+        OutputFormat::Human,
+        false,
+    )?;
+    writeln!(writer, "{}", wizard_t("wizard.flow.summary.updated")).ok();
+    Ok(())
+}
 
-    let content =
-        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+fn resolve_flow_summary_value(flow_path: &Path, value: Option<&str>) -> Option<String> {
+    let raw = value?.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    let Some(key) = raw.strip_prefix("i18n:") else {
+        return Some(raw.to_string());
+    };
+    read_pack_translation(flow_path, key.trim()).or_else(|| Some(raw.to_string()))
+}
 
-    match lint_flow(
-        &content,
-        Some(path),
-        ctx.schema_text,
-        ctx.schema_label,
-        ctx.schema_path,
-        ctx.registry,
-        ctx.schema_mode,
-    ) {
-        Ok(result) => {
-            let mut had_errors = false;
-            if result.lint_errors.is_empty() {
-                let i18n_tag_errors = lint_i18n_tag_fields(path);
-                if !i18n_tag_errors.is_empty() {
-                    *failures += 1;
-                    had_errors = true;
-                    for err in i18n_tag_errors {
-                        eprintln!("ERR  {}: {err}", path.display());
-                    }
-                }
-                if result.bundle.kind != "component-config" {
-                    let validation =
-                        validate_sidecar_for_flow(path, &result.flow, interactive, true)?;
-                    let mut sidecar_error = false;
-                    if !validation.missing.is_empty() {
-                        eprintln!(
-                            "ERR  {}: missing sidecar entries for nodes: {}",
-                            path.display(),
-                            validation.missing.join(", ")
-                        );
-                        sidecar_error = true;
-                    }
-                    if !validation.extra.is_empty() {
-                        eprintln!(
-                            "ERR  {}: unused sidecar entries: {}",
-                            path.display(),
-                            validation.extra.join(", ")
-                        );
-                        sidecar_error = true;
-                    }
-                    if !validation.invalid.is_empty() {
-                        eprintln!(
-                            "ERR  {}: invalid sidecar entries: {}",
-                            path.display(),
-                            validation.invalid.join(", ")
-                        );
-                        sidecar_error = true;
-                    }
-                    if sidecar_error {
-                        *failures += 1;
-                        had_errors = true;
-                    }
-                    if validation.updated {
-                        println!("Updated sidecar {}", validation.path.display());
-                    }
-                }
-                if !had_errors {
-                    println!("OK  {} ({})", path.display(), result.bundle.id);
-                }
-            } else {
-                *failures += 1;
-                eprintln!("ERR {}:", path.display());
-                for err in result.lint_errors {
-                    eprintln!("  {err}");
-                }
+fn read_pack_translation(flow_path: &Path, key: &str) -> Option<String> {
+    let pack_root = infer_pack_root_from_flow_path(flow_path).ok()?;
+    let i18n_dir = pack_root.join("i18n");
+    for filename in pack_i18n_candidate_files() {
+        let path = i18n_dir.join(filename);
+        let Ok(text) = fs::read_to_string(path) else {
+            continue;
+        };
+        let Ok(map) = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text)
+        else {
+            continue;
+        };
+        if let Some(value) = map.get(key).and_then(serde_json::Value::as_str) {
+            let trimmed = value.trim();
+            if !trimmed.is_empty() {
+                return Some(trimmed.to_string());
             }
         }
-        Err(err) => {
-            *failures += 1;
-            eprintln!("ERR {}: {err}", path.display());
-        }
     }
-    Ok(())
+    None
 }
 
-fn lint_i18n_tag_fields(path: &Path) -> Vec<String> {
-    let mut errors = Vec::new();
-    let Ok(doc) = load_ygtc_from_path(path) else {
-        return errors;
-    };
-    let i18n_source = load_pack_i18n_source_for_flow(path);
-    if let Some(title) = doc.title.as_deref() {
-        lint_i18n_tag_value("title", title, i18n_source.as_ref(), &mut errors);
+fn pack_i18n_candidate_files() -> Vec<String> {
+    let mut files = Vec::new();
+    let mut seen = BTreeSet::new();
+    let locale = resolve_locale(None);
+    let normalized = locale.trim().replace('_', "-");
+    if !normalized.is_empty() {
+        let full = format!("{normalized}.json");
+        if seen.insert(full.clone()) {
+            files.push(full);
+        }
+        if let Some((language, _)) = normalized.split_once('-') {
+            let lang = format!("{language}.json");
+            if seen.insert(lang.clone()) {
+                files.push(lang);
+            }
+        }
     }
-    if let Some(description) = doc.description.as_deref() {
-        lint_i18n_tag_value(
-            "description",
-            description,
-            i18n_source.as_ref(),
-            &mut errors,
-        );
+    for fallback in ["en.json", "en-GB.json"] {
+        let fallback = fallback.to_string();
+        if seen.insert(fallback.clone()) {
+            files.push(fallback);
+        }
     }
-    errors
+    files
 }
 
-fn lint_i18n_tag_value(
-    field: &str,
-    value: &str,
-    i18n_source: Option<&serde_json::Map<String, serde_json::Value>>,
-    errors: &mut Vec<String>,
-) {
-    let trimmed = value.trim();
-    if trimmed.is_empty() {
-        return;
-    }
-    let Some(key) = trimmed.strip_prefix("i18n:") else {
-        errors.push(format!(
-            "{field} must be an i18n tag (expected prefix i18n:)"
-        ));
-        return;
-    };
-    let key = key.trim();
-    if key.is_empty() {
-        errors.push(format!("{field} i18n tag key cannot be empty"));
-        return;
-    }
-    if let Some(source) = i18n_source {
-        match source.get(key).and_then(serde_json::Value::as_str) {
-            Some(v) if !v.trim().is_empty() => {}
-            _ => errors.push(format!(
-                "{field} i18n key '{key}' missing from pack i18n/en-GB.json"
-            )),
-        }
-    }
-}
-
-fn load_pack_i18n_source_for_flow(
-    path: &Path,
-) -> Option<serde_json::Map<String, serde_json::Value>> {
-    let pack_root = infer_pack_root_from_flow_path(path).ok()?;
-    let i18n_path = pack_root.join("i18n/en-GB.json");
-    let text = fs::read_to_string(i18n_path).ok()?;
-    serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text).ok()
-}
-
-struct LintResult {
-    bundle: FlowBundle,
-    flow: greentic_types::Flow,
-    lint_errors: Vec<String>,
-}
-
-#[allow(clippy::result_large_err)]
-fn lint_flow(
-    content: &str,
-    source_path: Option<&Path>,
-    schema_text: &str,
-    schema_label: &str,
-    schema_path: &Path,
-    registry: Option<&AdapterCatalog>,
-    schema_mode: SchemaMode,
-) -> Result<LintResult, FlowError> {
-    let (bundle, flow) = load_and_validate_bundle_with_schema_text(
-        content,
-        schema_text,
-        schema_label.to_string(),
-        Some(schema_path),
-        source_path,
-    )?;
-    let mut lint_errors = if let Some(cat) = registry {
-        lint_with_registry(&flow, cat)
+fn write_pack_translation(flow_path: &Path, key: &str, value: &str) -> Result<()> {
+    let pack_root = infer_pack_root_from_flow_path(flow_path)?;
+    let i18n_dir = pack_root.join("i18n");
+    fs::create_dir_all(&i18n_dir)
+        .with_context(|| format!("create directory {}", i18n_dir.display()))?;
+    let i18n_path = i18n_dir.join("en-GB.json");
+    let mut map = if i18n_path.exists() {
+        let text = fs::read_to_string(&i18n_path)
+            .with_context(|| format!("read translation file {}", i18n_path.display()))?;
+        serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text)
+            .unwrap_or_default()
     } else {
-        lint_builtin_rules(&flow)
+        serde_json::Map::new()
     };
-    lint_errors.extend(lint_component_configs(
-        &flow,
-        source_path,
-        bundle.kind.as_str(),
-        schema_mode,
-    ));
-    Ok(LintResult {
-        bundle,
-        flow,
-        lint_errors,
-    })
+    map.insert(
+        key.to_string(),
+        serde_json::Value::String(value.to_string()),
+    );
+    let text = serde_json::to_string_pretty(&serde_json::Value::Object(map))
+        .context("serialize translation file")?;
+    fs::write(&i18n_path, text)
+        .with_context(|| format!("write translation file {}", i18n_path.display()))?;
+    Ok(())
 }
 
-fn lint_component_configs(
-    flow: &greentic_types::Flow,
-    source_path: Option<&Path>,
-    flow_kind: &str,
-    schema_mode: SchemaMode,
-) -> Vec<String> {
-    if flow_kind == "component-config" {
-        return Vec::new();
+fn infer_pack_root_from_flow_path(flow_path: &Path) -> Result<PathBuf> {
+    let mut cursor = flow_path.parent();
+    while let Some(path) = cursor {
+        if path.file_name().and_then(|s| s.to_str()) == Some("flows") {
+            let root = path.parent().ok_or_else(|| {
+                anyhow!(
+                    "{}",
+                    wizard_t_with(
+                        "wizard.error.flow_path_has_no_pack_root",
+                        &[("path", &flow_path.display().to_string())]
+                    )
+                )
+            })?;
+            return Ok(root.to_path_buf());
+        }
+        cursor = path.parent();
     }
-    let Some(flow_path) = source_path else {
-        return Vec::new();
+    anyhow::bail!(
+        "{}",
+        wizard_t_with(
+            "wizard.error.cannot_infer_pack_root",
+            &[("path", &flow_path.display().to_string())]
+        )
+    )
+}
+
+fn wizard_delete_flow_with_io<R: Read, W: Write>(
+    flow_path: &Path,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<()> {
+    let question = Question {
+        id: "delete.confirm".to_string(),
+        prompt: wizard_t("wizard.flow.delete.confirm.prompt"),
+        kind: greentic_flow::questions::QuestionKind::Choice,
+        required: true,
+        default: Some(serde_json::Value::String("no".to_string())),
+        choices: vec![
+            serde_json::Value::String("no".to_string()),
+            serde_json::Value::String("yes".to_string()),
+        ],
+        show_if: None,
+        writes_to: None,
     };
-    if !flow_path.exists() {
-        return Vec::new();
+    let answers =
+        run_questions_with_qa_lib_io(&[question], HashMap::new(), &mut *reader, &mut *writer)?;
+    let confirm = answers
+        .get("delete.confirm")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("no");
+    if confirm != "yes" {
+        writeln!(writer, "{}", wizard_t("wizard.flow.delete.cancelled")).ok();
+        return Ok(());
     }
+
     let sidecar_path = sidecar_path_for_flow(flow_path);
-    if !sidecar_path.exists() {
-        return Vec::new();
-    }
-    let sidecar = match read_flow_resolve(&sidecar_path) {
-        Ok(doc) => doc,
-        Err(err) => {
-            return vec![format!(
-                "component_config: failed to read sidecar {}: {err}",
-                sidecar_path.display()
-            )];
-        }
+    let wizard_state_path = {
+        let base = flow_path.parent().unwrap_or_else(|| Path::new("."));
+        base.join(".greentic/cache/flow_wizard")
     };
 
-    let mut errors = Vec::new();
-    for (node_id, node) in &flow.nodes {
-        let node_key = node_id.as_str();
-        if matches!(node.component.id.as_str(), "questions" | "template") {
-            continue;
-        }
-        let Some(entry) = sidecar.nodes.get(node_key) else {
-            continue;
-        };
-        let manifest_path = match resolve_component_manifest_path(&entry.source, flow_path) {
-            Ok(path) => path,
-            Err(_) => continue,
-        };
-        let operation = node.component.operation.as_deref().unwrap_or("unknown");
-        let schema_resolution = match resolve_input_schema(&manifest_path, operation) {
-            Ok(resolution) => resolution,
-            Err(err) => {
-                errors.push(format!(
-                    "component_config: node '{node_key}' failed to read {}: {err}",
-                    manifest_path.display()
-                ));
-                continue;
-            }
-        };
-        let source_desc = "operations[].input_schema";
-        let schema_ref = match require_schema(
-            schema_mode,
-            &schema_resolution.component_id,
-            &schema_resolution.operation,
-            &schema_resolution.manifest_path,
-            source_desc,
-            schema_resolution.schema.as_ref(),
-        ) {
-            Ok(Some(schema)) => schema,
-            Ok(None) => continue,
-            Err(err) => {
-                errors.push(err.to_string());
-                continue;
-            }
-        };
-        let validator = match jsonschema_options_with_base(Some(manifest_path.as_path()))
-            .build(schema_ref)
-        {
-            Ok(validator) => validator,
-            Err(err) => {
-                if let ValidationErrorKind::Referencing(ReferencingError::Unretrievable {
-                    uri, ..
-                }) = err.kind()
-                    && uri.starts_with("file://")
-                    && !Path::new(uri.trim_start_matches("file://")).exists()
-                {
-                    eprintln!(
-                        "WARN component_config: node '{node_key}' schema validation for component '{}' skipped because '{uri}' is missing (manifest: {}). Continuing without this schema.",
-                        schema_resolution.component_id,
-                        manifest_path.display()
-                    );
-                    continue;
-                }
-                errors.push(format!(
-                    "component_config: node '{node_key}' schema compile failed for component '{}': {err}",
-                    schema_resolution.component_id
-                ));
-                continue;
-            }
-        };
-        let payload = match resolve_parameters(
-            &node.input.mapping,
-            &flow.metadata.extra,
-            &format!("nodes.{node_key}"),
-        ) {
-            Ok(value) => value,
-            Err(err) => {
-                errors.push(format!(
-                    "component_config: node '{node_key}' parameters resolution failed: {err}",
-                ));
-                continue;
-            }
-        };
-        let config_payload = extract_config_value(&payload);
-        for err in validator.iter_errors(&config_payload) {
-            let pointer = err.instance_path().to_string();
-            let pointer = if pointer.is_empty() {
-                "/".to_string()
-            } else {
-                pointer
-            };
-            errors.push(format!(
-                "component_config: node '{node_key}' payload invalid for component '{}' at {pointer}: {err}",
-                schema_resolution.component_id
-            ));
-        }
+    fs::remove_file(flow_path).with_context(|| format!("delete flow {}", flow_path.display()))?;
+    if sidecar_path.exists() {
+        let _ = fs::remove_file(&sidecar_path);
+    }
+    if wizard_state_path.exists() {
+        let _ = fs::remove_dir_all(&wizard_state_path);
     }
 
-    errors
+    writeln!(
+        writer,
+        "{}",
+        wizard_t_with(
+            "wizard.flow.delete.deleted",
+            &[("path", &flow_path.display().to_string())]
+        )
+    )
+    .ok();
+    Ok(())
 }
 
-fn run_json(
-    targets: &[PathBuf],
-    stdin_content: Option<String>,
-    schema_text: &str,
-    schema_label: &str,
-    schema_path: &Path,
-    registry: Option<&AdapterCatalog>,
-    schema_mode: SchemaMode,
+fn wizard_delete_step_with_io<R: Read, W: Write>(
+    flow_path: &Path,
+    reader: &mut R,
+    writer: &mut W,
 ) -> Result<()> {
-    let (content, source_display, source_path) = if let Some(stdin_flow) = stdin_content {
-        (
-            stdin_flow,
-            "<stdin>".to_string(),
-            Some(Path::new("<stdin>")),
-        )
-    } else {
-        if targets.len() != 1 {
-            anyhow::bail!("--json mode expects exactly one target file");
-        }
-        let target = &targets[0];
-        if target.is_dir() {
-            anyhow::bail!(
-                "--json target must be a file, found directory {}",
-                target.display()
-            );
-        }
-        if target.extension() != Some(OsStr::new("ygtc")) {
-            anyhow::bail!("--json target must be a .ygtc file");
-        }
-        let content = fs::read_to_string(target)
-            .with_context(|| format!("failed to read {}", target.display()))?;
-        (
-            content,
-            target.display().to_string(),
-            Some(target.as_path()),
-        )
-    };
-
-    let lint_result = lint_flow(
-        &content,
-        source_path,
-        schema_text,
-        schema_label,
-        schema_path,
-        registry,
-        schema_mode,
-    );
-
-    let output = match lint_result {
-        Ok(result) => {
-            if !result.lint_errors.is_empty() {
-                LintJsonOutput::lint_failure(result.lint_errors, Some(source_display.clone()))
-            } else if let Some(path) = source_path
-                && path.exists()
-            {
-                if result.bundle.kind == "component-config" {
-                    LintJsonOutput::success(result.bundle)
-                } else {
-                    let validation = validate_sidecar_for_flow(path, &result.flow, false, false)?;
-                    let mut errors = Vec::new();
-                    if !validation.missing.is_empty() {
-                        errors.push(format!(
-                            "missing sidecar entries for nodes: {}",
-                            validation.missing.join(", ")
-                        ));
-                    }
-                    if !validation.extra.is_empty() {
-                        errors.push(format!(
-                            "unused sidecar entries: {}",
-                            validation.extra.join(", ")
-                        ));
-                    }
-                    if !validation.invalid.is_empty() {
-                        errors.push(format!(
-                            "invalid sidecar entries: {}",
-                            validation.invalid.join(", ")
-                        ));
-                    }
-                    let i18n_errors = lint_i18n_tag_fields(path);
-                    errors.extend(i18n_errors);
-                    if errors.is_empty() {
-                        LintJsonOutput::success(result.bundle)
-                    } else {
-                        LintJsonOutput::lint_failure(
-                            errors,
-                            Some(validation.path.display().to_string()),
-                        )
-                    }
-                }
-            } else {
-                LintJsonOutput::success(result.bundle)
-            }
-        }
-        Err(err) => LintJsonOutput::error(err),
+    let doc = load_ygtc_from_path(flow_path)?;
+    let flow_ir = FlowIr::from_doc(doc)?;
+    if flow_ir.nodes.is_empty() {
+        writeln!(writer, "{}", wizard_t("wizard.step.delete.none")).ok();
+        return Ok(());
+    }
+    let mut choices: Vec<serde_json::Value> = flow_ir
+        .nodes
+        .keys()
+        .map(|id| serde_json::Value::String(id.clone()))
+        .collect();
+    let mut prompt = format!("{}\n", wizard_t("wizard.step.delete.prompt"));
+    for (idx, node_id) in flow_ir.nodes.keys().enumerate() {
+        prompt.push_str(&format!("{}. {}\n", idx + 1, node_id));
+    }
+    prompt.push_str(wizard_t("wizard.menu.nav.back").as_str());
+    choices.push(serde_json::Value::String("cancel".to_string()));
+    let question = Question {
+        id: "step.delete.id".to_string(),
+        prompt,
+        kind: greentic_flow::questions::QuestionKind::Choice,
+        required: true,
+        default: Some(serde_json::Value::String("cancel".to_string())),
+        choices,
+        show_if: None,
+        writes_to: None,
     };
-
-    let ok = output.ok;
-    let line = output.into_string();
-    write_stdout_line(&line)?;
-    if ok {
-        Ok(())
-    } else {
-        Err(anyhow::anyhow!("validation failed"))
+    let answers =
+        run_questions_with_qa_lib_io(&[question], HashMap::new(), &mut *reader, &mut *writer)?;
+    let step_id = answers
+        .get("step.delete.id")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("cancel");
+    if step_id == "cancel" {
+        writeln!(writer, "{}", wizard_t("wizard.step.delete.cancelled")).ok();
+        return Ok(());
     }
+    handle_delete_step(
+        DeleteStepArgs {
+            component_id: None,
+            flow_path: flow_path.to_path_buf(),
+            step: vec![step_id.to_string()],
+            wizard_mode: None,
+            answers: None,
+            answers_file: None,
+            answers_dir: None,
+            // Updating a step should replace prior answers artifacts for the same
+            // flow/node/mode instead of failing with "answers already exist".
+            overwrite_answers: true,
+            reask: false,
+            locale: None,
+            interactive: false,
+            component: None,
+            local_wasm: None,
+            distributor_url: None,
+            auth_token: None,
+            tenant: None,
+            env: None,
+            pack: None,
+            component_version: None,
+            abi_version: None,
+            resolver: None,
+            strategy: "splice".to_string(),
+            multi_pred: None,
+            assume_yes: true,
+            run_remove: false,
+            skip_remove: false,
+            write: true,
+            dry_run: false,
+            cascade: false,
+        },
+        OutputFormat::Human,
+        false,
+    )?;
+    writeln!(
+        writer,
+        "{}",
+        wizard_t_with("wizard.step.delete.deleted", &[("step", step_id)],)
+    )
+    .ok();
+    Ok(())
 }
 
-fn confirm_delete_unused(path: &Path, unused: &[String]) -> Result<bool> {
-    eprintln!(
-        "Unused sidecar entries detected in {}: {}",
-        path.display(),
-        unused.join(", ")
-    );
-    eprint!("Delete unused sidecar entries? [y/N]: ");
-    io::stdout().flush().ok();
-    let mut input = String::new();
-    if io::stdin().read_line(&mut input).is_err() {
-        return Ok(false);
+fn wizard_list_steps_with_io<W: Write>(flow_path: &Path, writer: &mut W) -> Result<()> {
+    let doc = load_ygtc_from_path(flow_path)?;
+    let flow_ir = FlowIr::from_doc(doc)?;
+    if flow_ir.nodes.is_empty() {
+        writeln!(writer, "{}", wizard_t("wizard.step.list.none")).ok();
+        return Ok(());
     }
-    let response = input.trim().to_lowercase();
-    Ok(response == "y" || response == "yes")
-}
-
-fn read_stdin_flow() -> Result<String> {
-    let mut buf = String::new();
-    io::stdin()
-        .read_to_string(&mut buf)
-        .context("failed to read flow YAML from stdin")?;
-    Ok(buf)
-}
-
-fn write_stdout_line(line: &str) -> Result<()> {
-    let mut stdout = io::stdout().lock();
-    match writeln!(stdout, "{line}") {
-        Ok(_) => Ok(()),
-        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
-        Err(e) => Err(e.into()),
+    writeln!(writer, "{}", wizard_t("wizard.step.list.header")).ok();
+    for (idx, (node_id, node)) in flow_ir.nodes.iter().enumerate() {
+        match node.doc_summary() {
+            Some(doc) => writeln!(writer, "{}. {} - {}", idx + 1, node_id, doc).ok(),
+            None => writeln!(writer, "{}. {}", idx + 1, node_id).ok(),
+        };
     }
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::AddStepArgs;
-    use super::AddStepMode;
-    use super::DeleteStepArgs;
-    use super::NewArgs;
-    use super::OutputFormat;
-    use super::SchemaMode;
-    use super::UpdateArgs;
-    use super::UpdateStepArgs;
-    use super::WizardModeArg;
-    use super::handle_add_step;
-    use super::handle_delete_step;
-    use super::handle_new;
-    use super::handle_update;
-    use super::handle_update_step;
-    use super::parse_answers_map;
-    use super::resolve_config_flow;
-    use super::serialize_doc;
-    use greentic_flow::flow_ir::FlowIr;
-    use greentic_flow::loader::load_ygtc_from_path;
-    use greentic_flow::wizard_ops::WizardMode;
-    use serde_json::Value;
-    use serde_json::json;
-    use std::env;
-    use std::fs;
-    use std::io::Cursor;
-    use std::path::{Path, PathBuf};
-    use std::sync::{Mutex, OnceLock};
-    use tempfile::NamedTempFile;
-    use tempfile::tempdir;
+#[derive(Debug, Clone)]
+struct WizardStepSourceSelection {
+    local_wasm: Option<PathBuf>,
+    component_ref: Option<String>,
+    pin: bool,
+}
 
-    fn extract_config_payload(payload: &serde_json::Value) -> &serde_json::Value {
-        payload
-            .get("config")
-            .and_then(|value| value.as_object().map(|_| value))
-            .unwrap_or(payload)
-    }
+fn wizard_add_step_with_io<R: Read, W: Write>(
+    pack_dir: &Path,
+    flow_path: &Path,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<()> {
+    let doc = load_ygtc_from_path(flow_path)?;
+    let flow_ir = FlowIr::from_doc(doc)?;
+    let after = wizard_select_add_step_anchor_with_io(&flow_ir, &mut *reader, &mut *writer)?;
 
-    fn env_test_lock() -> std::sync::MutexGuard<'static, ()> {
-        static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
-        LOCK.get_or_init(|| Mutex::new(()))
-            .lock()
-            .expect("env test lock")
-    }
+    let source = match wizard_select_step_source(pack_dir, reader, writer, true)? {
+        Some(source) => source,
+        None => {
+            writeln!(writer, "{}", wizard_t("wizard.step.add.cancelled")).ok();
+            return Ok(());
+        }
+    };
+    let wizard_mode = wizard_select_setup_mode(reader, writer, "add")?;
+    let Some(wizard_mode) = wizard_mode else {
+        writeln!(writer, "{}", wizard_t("wizard.step.add.cancelled")).ok();
+        return Ok(());
+    };
 
-    #[test]
-    fn wizard_menu_main_zero_exits() {
-        let dir = tempdir().expect("temp dir");
-        let input = Cursor::new("0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard exit");
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.contains("Main Menu"));
-    }
-
-    #[test]
-    fn wizard_menu_m_returns_to_main_menu() {
-        let dir = tempdir().expect("temp dir");
-        let flows_dir = dir.path().join("flows");
-        fs::create_dir_all(&flows_dir).expect("create flows dir");
-        fs::write(
-            flows_dir.join("sample.ygtc"),
-            "id: sample\ntype: messaging\nnodes: {}\n",
-        )
-        .expect("write flow");
+    let resolver = env::var("GREENTIC_FLOW_WIZARD_RESOLVER")
+        .ok()
+        .filter(|value| !value.trim().is_empty());
+    let mut qa_io = QaInteractiveIo {
+        reader: &mut *reader,
+        writer: &mut *writer,
+    };
+    handle_add_step_with_qa_io(
+        AddStepArgs {
+            component_id: None,
+            flow_path: flow_path.to_path_buf(),
+            after,
+            before: None,
+            mode: AddStepMode::Default,
+            pack_alias: None,
+            wizard_mode: Some(wizard_mode),
+            operation: None,
+            payload: "{}".to_string(),
+            routing_out: true,
+            routing_reply: false,
+            routing_next: None,
+            routing_multi_to: None,
+            routing_json: None,
+            routing_to_anchor: false,
+            config_flow: None,
+            answers: None,
+            answers_file: None,
+            preset: None,
+            answers_dir: None,
+            overwrite_answers: false,
+            reask: false,
+            locale: None,
+            interactive: true,
+            allow_cycles: false,
+            dry_run: false,
+            write: false,
+            validate_only: false,
+            manifests: Vec::new(),
+            node_id: None,
+            defaults_from: None,
+            component_ref: source.component_ref,
+            local_wasm: source.local_wasm,
+            distributor_url: None,
+            auth_token: None,
+            tenant: None,
+            env: None,
+            pack: None,
+            component_version: None,
+            abi_version: None,
+            resolver,
+            pin: source.pin,
+            allow_contract_change: false,
+        },
+        SchemaMode::Strict,
+        OutputFormat::Human,
+        false,
+        Some(&mut qa_io),
+    )?;
+    writeln!(writer, "{}", wizard_t("wizard.step.add.done")).ok();
+    Ok(())
+}
 
-        // 2 => edit/delete flows, M => main menu, 0 => exit
-        let input = Cursor::new("2\nM\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard navigation");
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.contains("Select number of flow"));
-        assert!(rendered.matches("Main Menu").count() >= 2);
+fn wizard_select_add_step_anchor_with_io<R: Read, W: Write>(
+    flow_ir: &FlowIr,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<Option<String>> {
+    let anchors: Vec<String> = flow_ir.nodes.keys().cloned().collect();
+    if anchors.is_empty() {
+        return Ok(None);
     }
 
-    #[test]
-    fn wizard_menu_flow_ops_zero_returns_to_flow_select() {
-        let dir = tempdir().expect("temp dir");
-        let flows_dir = dir.path().join("flows");
-        fs::create_dir_all(&flows_dir).expect("create flows dir");
-        fs::write(
-            flows_dir.join("sample.ygtc"),
-            "id: sample\ntype: messaging\nnodes: {}\n",
-        )
-        .expect("write flow");
-
-        // 2 => flow list, 1 => flow ops, 0 => back to flow list, 0 => main, 0 => exit.
-        let input = Cursor::new("2\n1\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard flow-ops back");
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.contains("Select number of flow"));
-        assert!(rendered.contains("Select operation"));
+    let mut prompt = format!("{}\n", wizard_t("wizard.step.add.after.prompt"));
+    let mut options = vec![wizard_t("wizard.choice.step.after.auto")];
+    options.extend(anchors.iter().cloned());
+    for (idx, option) in options.iter().enumerate() {
+        prompt.push_str(&format!("{}. {}\n", idx + 1, option));
     }
+    prompt.push_str("Select anchor");
 
-    #[test]
-    fn wizard_menu_flow_ops_m_returns_to_main_menu() {
-        let dir = tempdir().expect("temp dir");
-        let flows_dir = dir.path().join("flows");
-        fs::create_dir_all(&flows_dir).expect("create flows dir");
-        fs::write(
-            flows_dir.join("sample.ygtc"),
-            "id: sample\ntype: messaging\nnodes: {}\n",
-        )
-        .expect("write flow");
-
-        // 2 => flow list, 1 => flow ops, M => main, 0 => exit.
-        let input = Cursor::new("2\n1\nM\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard flow-ops main menu");
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.matches("Main Menu").count() >= 2);
+    let choice_values: Vec<String> = (1..=options.len()).map(|n| n.to_string()).collect();
+    let choice_refs: Vec<&str> = choice_values.iter().map(String::as_str).collect();
+    let answer = wizard_menu_answer(
+        &mut *reader,
+        &mut *writer,
+        "step.add.after",
+        &prompt,
+        &choice_refs,
+    )?;
+    let selected_idx = answer
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|idx| *idx >= 1 && *idx <= options.len())
+        .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.invalid_number")))?;
+    if selected_idx == 1 {
+        return Ok(None);
     }
+    Ok(anchors.get(selected_idx - 2).cloned())
+}
 
-    #[test]
-    fn wizard_generate_translations_requires_source_bundle() {
-        let dir = tempdir().expect("temp dir");
-        let input = Cursor::new("3\nes\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard should continue after translation error");
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.contains("missing i18n/en-GB.json"));
+fn wizard_update_step_with_io<R: Read, W: Write>(
+    pack_dir: &Path,
+    flow_path: &Path,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<()> {
+    let doc = load_ygtc_from_path(flow_path)?;
+    let flow_ir = FlowIr::from_doc(doc)?;
+    if flow_ir.nodes.is_empty() {
+        writeln!(writer, "{}", wizard_t("wizard.step.update.none")).ok();
+        return Ok(());
     }
 
-    #[test]
-    fn wizard_generate_translations_stub_writes_locale_files() {
-        let _guard = env_test_lock();
-        let dir = tempdir().expect("temp dir");
-        fs::create_dir_all(dir.path().join("i18n")).expect("create i18n");
-        fs::write(
-            dir.path().join("i18n/en-GB.json"),
-            r#"{"wizard.hello":"Hello"}"#,
-        )
-        .expect("write source bundle");
+    let mut step_choices: Vec<serde_json::Value> = flow_ir
+        .nodes
+        .keys()
+        .map(|id| serde_json::Value::String(id.clone()))
+        .collect();
+    let mut prompt = format!("{}\n", wizard_t("wizard.step.update.select.prompt"));
+    for (idx, node_id) in flow_ir.nodes.keys().enumerate() {
+        prompt.push_str(&format!("{}. {}\n", idx + 1, node_id));
+    }
+    prompt.push_str(wizard_t("wizard.menu.nav.back").as_str());
+    step_choices.push(serde_json::Value::String("cancel".to_string()));
+    let step_answers = run_questions_with_qa_lib_io(
+        &[Question {
+            id: "step.update.id".to_string(),
+            prompt,
+            kind: greentic_flow::questions::QuestionKind::Choice,
+            required: true,
+            default: Some(serde_json::Value::String("cancel".to_string())),
+            choices: step_choices,
+            show_if: None,
+            writes_to: None,
+        }],
+        HashMap::new(),
+        &mut *reader,
+        &mut *writer,
+    )?;
+    let step_id = step_answers
+        .get("step.update.id")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("cancel")
+        .to_string();
+    if step_id == "cancel" {
+        writeln!(writer, "{}", wizard_t("wizard.step.update.cancelled")).ok();
+        return Ok(());
+    }
 
-        let previous = env::var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB").ok();
-        unsafe {
-            env::set_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB", "1");
-        }
-        let mut input = Cursor::new("es,fr\n");
-        let mut output = Vec::new();
-        let mut answers_log = serde_json::Map::new();
-        super::wizard_generate_translations_with_io(
-            dir.path(),
-            &mut input,
-            &mut output,
-            &mut answers_log,
-        )
-        .expect("generate translations (stub)");
-        if let Some(value) = previous {
-            unsafe {
-                env::set_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB", value);
-            }
-        } else {
-            unsafe {
-                env::remove_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB");
-            }
+    let source = match wizard_select_step_source(pack_dir, reader, writer, false)? {
+        Some(source) => source,
+        None => {
+            writeln!(writer, "{}", wizard_t("wizard.step.update.cancelled")).ok();
+            return Ok(());
         }
+    };
+    let wizard_mode = wizard_select_setup_mode(reader, writer, "update")?;
+    let Some(wizard_mode) = wizard_mode else {
+        writeln!(writer, "{}", wizard_t("wizard.step.update.cancelled")).ok();
+        return Ok(());
+    };
 
-        let es_text = fs::read_to_string(dir.path().join("i18n/es.json")).expect("read es");
-        let fr_text = fs::read_to_string(dir.path().join("i18n/fr.json")).expect("read fr");
-        assert!(es_text.contains("Hello [es]"));
-        assert!(fr_text.contains("Hello [fr]"));
-    }
-
-    #[test]
-    fn wizard_menu_generate_translations_pack_wide_and_save() {
-        let _guard = env_test_lock();
-        let dir = tempdir().expect("temp dir");
-        fs::create_dir_all(dir.path().join("i18n")).expect("create i18n");
-        fs::write(
-            dir.path().join("i18n/en-GB.json"),
-            r#"{"flow.alpha.title":"Alpha","flow.beta.title":"Beta"}"#,
-        )
-        .expect("write source bundle");
+    let resolver = env::var("GREENTIC_FLOW_WIZARD_RESOLVER")
+        .ok()
+        .filter(|value| !value.trim().is_empty());
+    let mut qa_io = QaInteractiveIo {
+        reader: &mut *reader,
+        writer: &mut *writer,
+    };
+    handle_update_step_with_qa_io(
+        UpdateStepArgs {
+            component_id: None,
+            flow_path: flow_path.to_path_buf(),
+            step: Some(step_id.clone()),
+            mode: "default".to_string(),
+            wizard_mode: Some(wizard_mode),
+            operation: None,
+            routing_out: false,
+            routing_reply: false,
+            routing_next: None,
+            routing_multi_to: None,
+            routing_json: None,
+            answers: None,
+            answers_file: None,
+            preset: None,
+            answers_dir: None,
+            overwrite_answers: false,
+            reask: false,
+            locale: None,
+            non_interactive: false,
+            changed_only: false,
+            interactive: true,
+            component: source.component_ref,
+            local_wasm: source.local_wasm,
+            distributor_url: None,
+            auth_token: None,
+            tenant: None,
+            env: None,
+            pack: None,
+            component_version: None,
+            abi_version: None,
+            resolver,
+            dry_run: false,
+            write: false,
+            allow_contract_change: false,
+            rename: None,
+        },
+        SchemaMode::Strict,
+        OutputFormat::Human,
+        false,
+        Some(&mut qa_io),
+    )?;
+    writeln!(
+        writer,
+        "{}",
+        wizard_t_with("wizard.step.update.done", &[("step", &step_id)],)
+    )
+    .ok();
+    Ok(())
+}
 
-        let previous = env::var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB").ok();
-        unsafe {
-            env::set_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB", "1");
-        }
+fn wizard_select_setup_mode<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    prefix: &str,
+) -> Result<Option<WizardModeArg>> {
+    let answers = run_questions_with_qa_lib_io(
+        &[Question {
+            id: format!("step.{prefix}.setup_mode"),
+            prompt: wizard_t("wizard.step.setup_mode.prompt"),
+            kind: greentic_flow::questions::QuestionKind::Choice,
+            required: true,
+            default: Some(serde_json::Value::String("default".to_string())),
+            choices: vec![
+                serde_json::Value::String("default".to_string()),
+                serde_json::Value::String("personalised".to_string()),
+                serde_json::Value::String("cancel".to_string()),
+            ],
+            show_if: None,
+            writes_to: None,
+        }],
+        HashMap::new(),
+        &mut *reader,
+        &mut *writer,
+    )?;
+    let mode = answers
+        .get(&format!("step.{prefix}.setup_mode"))
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("cancel");
+    let mapped = match mode {
+        "default" => Some(WizardModeArg::Default),
+        "personalised" => Some(WizardModeArg::Setup),
+        _ => None,
+    };
+    Ok(mapped)
+}
 
-        // 3=Generate translations, enter locales, 4=Save, enter default answers path, 0=Exit.
-        let input = Cursor::new("3\nes,fr\n4\n\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard menu generate translations");
+fn wizard_generate_translations_with_io<R: Read, W: Write>(
+    pack_dir: &Path,
+    reader: &mut R,
+    writer: &mut W,
+    answers_log: &mut serde_json::Map<String, serde_json::Value>,
+) -> Result<()> {
+    let answers = run_questions_with_qa_lib_io(
+        &[Question {
+            id: "wizard.translate.locales".to_string(),
+            prompt: wizard_t("wizard.translate.locales.prompt"),
+            kind: greentic_flow::questions::QuestionKind::String,
+            required: true,
+            default: None,
+            choices: Vec::new(),
+            show_if: None,
+            writes_to: None,
+        }],
+        HashMap::new(),
+        &mut *reader,
+        &mut *writer,
+    )?;
+    let locales = answer_str(&answers, "wizard.translate.locales")?.to_string();
+    answers_log.insert(
+        "wizard.translate.locales".to_string(),
+        serde_json::Value::String(locales.clone()),
+    );
+    let locale_list = parse_locale_list(&locales);
+    if locale_list.is_empty() {
+        anyhow::bail!("{}", wizard_t("wizard.translate.invalid_locales"));
+    }
 
-        if let Some(value) = previous {
-            unsafe {
-                env::set_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB", value);
-            }
-        } else {
-            unsafe {
-                env::remove_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB");
-            }
+    let en_path = pack_dir.join("i18n/en-GB.json");
+    if !en_path.exists() {
+        anyhow::bail!("{}", wizard_t("wizard.translate.missing_source"));
+    }
+    if env::var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB")
+        .ok()
+        .as_deref()
+        == Some("1")
+    {
+        generate_translations_stub(pack_dir, &locale_list)?;
+        writeln!(writer, "{}", wizard_t("wizard.translate.done")).ok();
+        return Ok(());
+    }
+    let i18n = greentic_i18n_translator::cli_i18n::CliI18n::from_request(Some("en"))
+        .map_err(anyhow::Error::msg)?;
+    let cli = greentic_i18n_translator::cli::Cli {
+        locale: Some("en".to_string()),
+        command: greentic_i18n_translator::cli::Command::Translate {
+            langs: locale_list.join(","),
+            en: en_path,
+            auth_mode: greentic_i18n_translator::cli::CliAuthMode::Auto,
+            codex_home: None,
+            batch_size: 50,
+            max_retries: 2,
+            glossary: None,
+            api_key_stdin: false,
+            overwrite_manual: false,
+            cache_dir: None,
+        },
+    };
+    greentic_i18n_translator::cli::run_with(cli, &i18n).map_err(anyhow::Error::msg)?;
+    writeln!(writer, "{}", wizard_t("wizard.translate.done")).ok();
+    Ok(())
+}
+
+fn parse_locale_list(raw: &str) -> Vec<String> {
+    let mut out = Vec::<String>::new();
+    for locale in raw.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+        if !out.iter().any(|existing| existing == locale) {
+            out.push(locale.to_string());
         }
+    }
+    out
+}
 
-        let es_text = fs::read_to_string(dir.path().join("i18n/es.json")).expect("read es");
-        let fr_text = fs::read_to_string(dir.path().join("i18n/fr.json")).expect("read fr");
-        let es_json: serde_json::Value = serde_json::from_str(&es_text).expect("parse es");
-        let fr_json: serde_json::Value = serde_json::from_str(&fr_text).expect("parse fr");
-        assert!(
-            es_json
-                .get("flow.alpha.title")
-                .and_then(|v| v.as_str())
-                .is_some()
-        );
-        assert!(
-            es_json
-                .get("flow.beta.title")
-                .and_then(|v| v.as_str())
-                .is_some()
-        );
-        assert!(
-            fr_json
-                .get("flow.alpha.title")
-                .and_then(|v| v.as_str())
-                .is_some()
-        );
-        assert!(
-            fr_json
-                .get("flow.beta.title")
-                .and_then(|v| v.as_str())
-                .is_some()
-        );
+fn generate_translations_stub(pack_dir: &Path, locales: &[String]) -> Result<()> {
+    let i18n_dir = pack_dir.join("i18n");
+    fs::create_dir_all(&i18n_dir)
+        .with_context(|| format!("create directory {}", i18n_dir.display()))?;
+    let en_path = i18n_dir.join("en-GB.json");
+    let en_map = fs::read_to_string(&en_path)
+        .with_context(|| format!("read translation source {}", en_path.display()))
+        .and_then(|text| {
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text)
+                .context("parse source translation json")
+        })?;
+    for locale in locales {
+        if locale == "en" || locale == "en-GB" {
+            continue;
+        }
+        let mut target = serde_json::Map::new();
+        for (key, value) in &en_map {
+            let base = value.as_str().unwrap_or_default();
+            target.insert(
+                key.clone(),
+                serde_json::Value::String(format!("{base} [{locale}]")),
+            );
+        }
+        let out_path = i18n_dir.join(format!("{locale}.json"));
+        let text = serde_json::to_string_pretty(&serde_json::Value::Object(target))
+            .context("serialize stub translation json")?;
+        fs::write(&out_path, text)
+            .with_context(|| format!("write translation file {}", out_path.display()))?;
     }
+    Ok(())
+}
 
-    #[test]
-    fn wizard_i18n_smoke_no_missing_key_markers() {
-        let keys = [
-            "wizard.menu.main.prompt",
-            "wizard.menu.flow_select.title",
-            "wizard.menu.flow_ops.prompt",
-            "wizard.menu.nav.back",
-            "wizard.menu.nav.main",
-            "wizard.add_flow.scope.prompt",
-            "wizard.add_flow.created",
-            "wizard.flow.summary.name.prompt",
-            "wizard.flow.summary.edit.prompt",
-            "wizard.flow.summary.current_name",
-            "wizard.flow.summary.current_description",
+fn wizard_select_step_source<R: Read, W: Write>(
+    pack_dir: &Path,
+    reader: &mut R,
+    writer: &mut W,
+    ask_pin_for_remote: bool,
+) -> Result<Option<WizardStepSourceSelection>> {
+    let source_answers = run_questions_with_qa_lib_io(
+        &[Question {
+            id: "step.source.kind".to_string(),
+            prompt: wizard_t("wizard.step.source.kind.prompt"),
+            kind: greentic_flow::questions::QuestionKind::Choice,
+            required: true,
+            default: Some(serde_json::Value::String("local".to_string())),
+            choices: vec![
+                serde_json::Value::String("local".to_string()),
+                serde_json::Value::String("remote".to_string()),
+                serde_json::Value::String("cancel".to_string()),
+            ],
+            show_if: None,
+            writes_to: None,
+        }],
+        HashMap::new(),
+        &mut *reader,
+        &mut *writer,
+    )?;
+    let source_kind = source_answers
+        .get("step.source.kind")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("cancel");
+    if source_kind == "cancel" {
+        return Ok(None);
+    }
+
+    if source_kind == "local" {
+        let candidates = collect_pack_component_wasms(pack_dir)?;
+        let selected_path = if candidates.is_empty() {
+            let local_answers = run_questions_with_qa_lib_io(
+                &[Question {
+                    id: "step.source.local_path".to_string(),
+                    prompt: wizard_t("wizard.step.source.local.prompt"),
+                    kind: greentic_flow::questions::QuestionKind::String,
+                    required: true,
+                    default: None,
+                    choices: Vec::new(),
+                    show_if: None,
+                    writes_to: None,
+                }],
+                HashMap::new(),
+                &mut *reader,
+                &mut *writer,
+            )?;
+            PathBuf::from(answer_str(&local_answers, "step.source.local_path")?)
+        } else {
+            let mut choices = Vec::new();
+            for candidate in &candidates {
+                let display = candidate
+                    .strip_prefix(pack_dir)
+                    .unwrap_or(candidate)
+                    .display()
+                    .to_string();
+                choices.push(serde_json::Value::String(display));
+            }
+            choices.push(serde_json::Value::String("custom".to_string()));
+            choices.push(serde_json::Value::String("cancel".to_string()));
+            let choice_answers = run_questions_with_qa_lib_io(
+                &[Question {
+                    id: "step.source.local_choice".to_string(),
+                    prompt: wizard_t("wizard.step.source.local.choice.prompt"),
+                    kind: greentic_flow::questions::QuestionKind::Choice,
+                    required: true,
+                    default: choices.first().cloned(),
+                    choices: choices.clone(),
+                    show_if: None,
+                    writes_to: None,
+                }],
+                HashMap::new(),
+                &mut *reader,
+                &mut *writer,
+            )?;
+            let selected = choice_answers
+                .get("step.source.local_choice")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("cancel");
+            if selected == "cancel" {
+                return Ok(None);
+            }
+            if selected == "custom" {
+                let local_answers = run_questions_with_qa_lib_io(
+                    &[Question {
+                        id: "step.source.local_custom_path".to_string(),
+                        prompt: wizard_t("wizard.step.source.local.prompt"),
+                        kind: greentic_flow::questions::QuestionKind::String,
+                        required: true,
+                        default: None,
+                        choices: Vec::new(),
+                        show_if: None,
+                        writes_to: None,
+                    }],
+                    HashMap::new(),
+                    &mut *reader,
+                    &mut *writer,
+                )?;
+                PathBuf::from(answer_str(&local_answers, "step.source.local_custom_path")?)
+            } else {
+                let rel = PathBuf::from(selected);
+                if rel.is_absolute() {
+                    rel
+                } else {
+                    pack_dir.join(rel)
+                }
+            }
+        };
+        let copied_local = copy_local_wasm_into_pack_components(pack_dir, &selected_path)?;
+        return Ok(Some(WizardStepSourceSelection {
+            local_wasm: Some(copied_local),
+            component_ref: None,
+            pin: true,
+        }));
+    }
+
+    let ref_answers = run_questions_with_qa_lib_io(
+        &[Question {
+            id: "step.source.remote_ref".to_string(),
+            prompt: wizard_t("wizard.step.source.remote.prompt"),
+            kind: greentic_flow::questions::QuestionKind::String,
+            required: true,
+            default: None,
+            choices: Vec::new(),
+            show_if: None,
+            writes_to: None,
+        }],
+        HashMap::new(),
+        &mut *reader,
+        &mut *writer,
+    )?;
+    let remote_ref = answer_str(&ref_answers, "step.source.remote_ref")?.to_string();
+    let pin = if ask_pin_for_remote {
+        let pin_answers = run_questions_with_qa_lib_io(
+            &[Question {
+                id: "step.source.remote_pin".to_string(),
+                prompt: wizard_t("wizard.step.source.remote.pin.prompt"),
+                kind: greentic_flow::questions::QuestionKind::Choice,
+                required: true,
+                default: Some(serde_json::Value::String("yes".to_string())),
+                choices: vec![
+                    serde_json::Value::String("yes".to_string()),
+                    serde_json::Value::String("no".to_string()),
+                ],
+                show_if: None,
+                writes_to: None,
+            }],
+            HashMap::new(),
+            &mut *reader,
+            &mut *writer,
+        )?;
+        pin_answers
+            .get("step.source.remote_pin")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("yes")
+            == "yes"
+    } else {
+        false
+    };
+    Ok(Some(WizardStepSourceSelection {
+        local_wasm: None,
+        component_ref: Some(remote_ref),
+        pin,
+    }))
+}
+
+fn copy_local_wasm_into_pack_components(pack_dir: &Path, selected_path: &Path) -> Result<PathBuf> {
+    let src_abs = if selected_path.is_absolute() {
+        selected_path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .context("resolve current directory")?
+            .join(selected_path)
+    };
+    let src_abs = fs::canonicalize(&src_abs)
+        .with_context(|| format!("resolve local wasm path {}", src_abs.display()))?;
+    if !src_abs.exists() {
+        anyhow::bail!(
+            "{}",
+            wizard_t_with(
+                "wizard.error.local_wasm_missing",
+                &[("path", &src_abs.display().to_string())]
+            )
+        );
+    }
+    if ArtifactStore::from_env().is_some() {
+        // Binding stores it in the shared artifact store; no pack copy needed.
+        return Ok(src_abs);
+    }
+    let components_dir = pack_dir.join("components");
+    fs::create_dir_all(&components_dir)
+        .with_context(|| format!("create directory {}", components_dir.display()))?;
+    let digest = compute_sha256(&src_abs)?;
+    let stem = src_abs
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|v| !v.is_empty())
+        .unwrap_or("component");
+    let dest = components_dir.join(format!("{stem}-{}.wasm", &digest[..12]));
+    if !dest.exists() {
+        fs::copy(&src_abs, &dest)
+            .with_context(|| format!("copy wasm {} -> {}", src_abs.display(), dest.display()))?;
+    }
+    Ok(dest)
+}
+
+fn collect_pack_component_wasms(pack_dir: &Path) -> Result<Vec<PathBuf>> {
+    let components_dir = pack_dir.join("components");
+    if !components_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut out = Vec::new();
+    collect_component_wasms_recursive(&components_dir, &mut out)?;
+    out.sort();
+    Ok(out)
+}
+
+fn collect_component_wasms_recursive(root: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    if root.is_file() {
+        if root.extension() == Some(OsStr::new("wasm")) {
+            out.push(root.to_path_buf());
+        }
+        return Ok(());
+    }
+    if !root.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(root).with_context(|| format!("read directory {}", root.display()))? {
+        let entry = entry.with_context(|| format!("read entry under {}", root.display()))?;
+        collect_component_wasms_recursive(&entry.path(), out)?;
+    }
+    Ok(())
+}
+
+fn answer_str<'a>(answers: &'a HashMap<String, serde_json::Value>, key: &str) -> Result<&'a str> {
+    answers
+        .get(key)
+        .and_then(serde_json::Value::as_str)
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| {
+            anyhow!(
+                "{}",
+                wizard_t_with("wizard.error.missing_required_answer", &[("key", key)])
+            )
+        })
+}
+
+fn flow_id_from_name(name: &str) -> Result<String> {
+    let stem = Path::new(name)
+        .file_stem()
+        .and_then(|v| v.to_str())
+        .unwrap_or(name)
+        .trim();
+    if stem.is_empty() {
+        anyhow::bail!("{}", wizard_t("wizard.error.flow_name_empty"));
+    }
+    Ok(stem.replace(' ', "-"))
+}
+
+fn ensure_ygtc_name(name: &str) -> Result<String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        anyhow::bail!("{}", wizard_t("wizard.error.flow_name_empty"));
+    }
+    if trimmed.ends_with(".ygtc") {
+        Ok(trimmed.to_string())
+    } else {
+        Ok(format!("{trimmed}.ygtc"))
+    }
+}
+
+fn build_add_flow_relative_path(
+    scope: &str,
+    tenant_id: Option<&str>,
+    team_scope: Option<&str>,
+    team_id: Option<&str>,
+    flow_type: &str,
+    flow_name: &str,
+) -> Result<PathBuf> {
+    if flow_type != "messaging" && flow_type != "events" {
+        anyhow::bail!(
+            "{}",
+            wizard_t_with(
+                "wizard.error.flow_type_unsupported",
+                &[("flow_type", flow_type)]
+            )
+        );
+    }
+    let file_name = ensure_ygtc_name(flow_name)?;
+    let path = match scope {
+        "global" => PathBuf::from("flows")
+            .join("global")
+            .join(flow_type)
+            .join(file_name),
+        "tenant" => {
+            let tenant = tenant_id
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.tenant_id_required")))?;
+            let team_segment = match team_scope {
+                Some("all-teams") | None => "all-teams".to_string(),
+                Some("specific-team") => {
+                    let team = team_id
+                        .map(str::trim)
+                        .filter(|v| !v.is_empty())
+                        .ok_or_else(|| anyhow!("{}", wizard_t("wizard.error.team_id_required")))?;
+                    team.to_string()
+                }
+                Some(other) => {
+                    anyhow::bail!(
+                        "{}",
+                        wizard_t_with("wizard.error.team_scope_unsupported", &[("scope", other)])
+                    )
+                }
+            };
+            PathBuf::from("flows")
+                .join(tenant)
+                .join(team_segment)
+                .join(flow_type)
+                .join(file_name)
+        }
+        other => {
+            anyhow::bail!(
+                "{}",
+                wizard_t_with("wizard.error.flow_scope_unsupported", &[("scope", other)])
+            )
+        }
+    };
+    Ok(path)
+}
+
+/// Wizard results `generate` computed ahead of time; empty for every other command.
+fn wizard_cache() -> &'static wizard_ops::WizardCache {
+    static CACHE: OnceLock<wizard_ops::WizardCache> = OnceLock::new();
+    CACHE.get_or_init(wizard_ops::WizardCache::default)
+}
+
+fn wizard_catalog_for_locale(locale: &str) -> I18nCatalog {
+    static CATALOGS: OnceLock<Mutex<HashMap<String, I18nCatalog>>> = OnceLock::new();
+    let catalogs = CATALOGS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut guard = catalogs.lock().expect("wizard catalogs lock");
+    if let Some(cached) = guard.get(locale) {
+        return cached.clone();
+    }
+    let mut catalog = I18nCatalog::default();
+    merge_wizard_i18n_json_embedded(&mut catalog, "en");
+    if locale != "en" {
+        merge_wizard_i18n_json_embedded(&mut catalog, locale);
+    }
+    if let Some((language, _)) = locale.split_once('-')
+        && !language.is_empty()
+        && language != locale
+        && language != "en"
+    {
+        merge_wizard_i18n_json_embedded(&mut catalog, language);
+    }
+    guard.insert(locale.to_string(), catalog.clone());
+    catalog
+}
+
+fn wizard_resolved_map_for_locale(locale: &str) -> std::collections::BTreeMap<String, String> {
+    static RESOLVED: OnceLock<Mutex<HashMap<String, std::collections::BTreeMap<String, String>>>> =
+        OnceLock::new();
+    let resolved_cache = RESOLVED.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut guard = resolved_cache.lock().expect("wizard resolved map lock");
+    if let Some(cached) = guard.get(locale) {
+        return cached.clone();
+    }
+    let catalog = wizard_catalog_for_locale(locale);
+    let mut resolved = std::collections::BTreeMap::new();
+    for key in embedded_wizard_keys() {
+        let value = resolve_cli_text(&catalog, locale, &key, &key);
+        if value != key {
+            resolved.insert(key.clone(), value.clone());
+            resolved.insert(format!("{locale}:{key}"), value.clone());
+            resolved.insert(format!("{locale}/{key}"), value);
+        }
+    }
+    guard.insert(locale.to_string(), resolved.clone());
+    resolved
+}
+
+fn wizard_qa_i18n_config_for_locale(locale: &str) -> QaI18nConfig {
+    QaI18nConfig {
+        locale: Some(locale.to_string()),
+        resolved: Some(wizard_resolved_map_for_locale(locale)),
+        debug: false,
+    }
+}
+
+fn merge_wizard_i18n_json_embedded(catalog: &mut I18nCatalog, locale: &str) {
+    let file_name = format!("{locale}.json");
+    let Some(file) = EMBEDDED_WIZARD_I18N_DIR.get_file(&file_name) else {
+        return;
+    };
+    let Some(text) = file.contents_utf8() else {
+        return;
+    };
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+        return;
+    };
+    let Some(entries) = value.as_object() else {
+        return;
+    };
+    for (key, value) in entries {
+        if let Some(message) = value.as_str() {
+            catalog.insert(key.clone(), locale.to_string(), message.to_string());
+        }
+    }
+}
+
+fn embedded_wizard_keys() -> Vec<String> {
+    let file = EMBEDDED_WIZARD_I18N_DIR
+        .get_file("en.json")
+        .or_else(|| EMBEDDED_WIZARD_I18N_DIR.get_file("en-GB.json"));
+    let Some(file) = file else {
+        return Vec::new();
+    };
+    let Some(text) = file.contents_utf8() else {
+        return Vec::new();
+    };
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+        return Vec::new();
+    };
+    let Some(entries) = value.as_object() else {
+        return Vec::new();
+    };
+    entries.keys().cloned().collect()
+}
+
+fn wizard_t(key: &str) -> String {
+    let locale = resolve_locale(None);
+    let catalog = wizard_catalog_for_locale(&locale);
+    let value = resolve_cli_text(&catalog, &locale, key, key);
+    if value == key {
+        format!("[[missing:{key}]]")
+    } else {
+        value
+    }
+}
+
+fn wizard_t_with(key: &str, replacements: &[(&str, &str)]) -> String {
+    let mut value = wizard_t(key);
+    for (name, replacement) in replacements {
+        value = value.replace(&format!("{{{name}}}"), replacement);
+    }
+    value
+}
+
+/// A line doctor prints while checking one flow.
+enum DoctorLine {
+    Stdout(String),
+    Stderr(String),
+}
+
+impl DoctorLine {
+    fn print(&self) {
+        match self {
+            DoctorLine::Stdout(line) => println!("{line}"),
+            DoctorLine::Stderr(line) => eprintln!("{line}"),
+        }
+    }
+}
+
+thread_local! {
+    /// Lines of the flow a doctor worker is checking, printed as one block once it finishes.
+    static DOCTOR_CAPTURE: RefCell<Option<Vec<DoctorLine>>> = const { RefCell::new(None) };
+}
+
+fn doctor_emit(line: DoctorLine) {
+    let uncaptured = DOCTOR_CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+        Some(lines) => {
+            lines.push(line);
+            None
+        }
+        None => Some(line),
+    });
+    if let Some(line) = uncaptured {
+        line.print();
+    }
+}
+
+/// Run `check` with its doctor output captured instead of printed.
+fn capture_doctor_output<T>(check: impl FnOnce() -> T) -> (T, Vec<DoctorLine>) {
+    DOCTOR_CAPTURE.with(|capture| *capture.borrow_mut() = Some(Vec::new()));
+    let result = check();
+    let lines = DOCTOR_CAPTURE
+        .with(|capture| capture.borrow_mut().take())
+        .unwrap_or_default();
+    (result, lines)
+}
+
+/// `println!` for doctor's per-flow checks; captured per flow when flows run concurrently.
+macro_rules! doctor_println {
+    ($($arg:tt)*) => {
+        doctor_emit(DoctorLine::Stdout(format!($($arg)*)))
+    };
+}
+
+/// `eprintln!` counterpart of [`doctor_println!`].
+macro_rules! doctor_eprintln {
+    ($($arg:tt)*) => {
+        doctor_emit(DoctorLine::Stderr(format!($($arg)*)))
+    };
+}
+
+fn handle_doctor(args: DoctorArgs, schema_mode: SchemaMode) -> Result<()> {
+    if let (Some(channel), Some(public_key)) = (args.expect_channel, &args.public_key) {
+        return handle_doctor_deployed_bundles(&args.targets, channel, public_key, args.json);
+    }
+    if args.stdin && !args.json {
+        anyhow::bail!("--stdin currently requires --json");
+    }
+    if args.stdin && !args.targets.is_empty() {
+        anyhow::bail!("--stdin cannot be combined with file targets");
+    }
+    let report_path = match args.report.as_deref() {
+        None => None,
+        Some(_) if args.json => anyhow::bail!("--report cannot be combined with --json"),
+        Some([format, path]) if format == "html" => Some(PathBuf::from(path)),
+        Some([format, _]) => anyhow::bail!("unsupported report format '{format}' (expected html)"),
+        Some(_) => {
+            anyhow::bail!("--report expects a format and a file, e.g. --report html report.html")
+        }
+    };
+    let report = Mutex::new(Vec::new());
+    let machine_output = args.output != DoctorOutput::Human;
+
+    let (schema_text, schema_label, schema_path) = if let Some(schema_path) = &args.schema {
+        let text = fs::read_to_string(schema_path)
+            .with_context(|| format!("failed to read schema {}", schema_path.display()))?;
+        (text, schema_path.display().to_string(), schema_path.clone())
+    } else {
+        (
+            EMBEDDED_FLOW_SCHEMA.to_string(),
+            "embedded ygtc.flow.schema.json".to_string(),
+            PathBuf::from("schemas/ygtc.flow.schema.json"),
+        )
+    };
+
+    let registry = if let Some(path) = &args.registry {
+        Some(AdapterCatalog::load_from_file(path)?)
+    } else {
+        None
+    };
+    let mut rules = RuleRegistry::builtin();
+    for path in &args.lint_plugins {
+        rules.register(WasmLintRule::load(path)?);
+    }
+    if !args.api_baselines.is_empty() {
+        let released = args
+            .api_baselines
+            .iter()
+            .map(|path| ApiSurface::load(path))
+            .collect::<Result<Vec<_>>>()?;
+        rules.register(ApiCompatRule::new(released));
+    }
+    let placeholders = if args.placeholder_patterns.is_empty() {
+        PlaceholderValuesRule::default()
+    } else {
+        PlaceholderValuesRule::new(args.placeholder_patterns.clone())
+    };
+    let baseline = if let Some(path) = &args.write_baseline {
+        BaselineMode::Record {
+            path: path.clone(),
+            baseline: Mutex::new(LintBaseline::default()),
+        }
+    } else if let Some(path) = &args.baseline
+        && !path.exists()
+    {
+        BaselineMode::Record {
+            path: path.clone(),
+            baseline: Mutex::new(LintBaseline::default()),
+        }
+    } else if let Some(path) = &args.baseline {
+        BaselineMode::Apply {
+            baseline: LintBaseline::load(path)?,
+            path: path.clone(),
+        }
+    } else {
+        BaselineMode::Off
+    };
+    let runtime = args
+        .runtime_manifest
+        .as_deref()
+        .map(RuntimeCapabilities::load)
+        .transpose()?;
+    let releases = args
+        .release_catalog
+        .as_deref()
+        .map(ReleaseCatalog::load_from_file)
+        .transpose()?;
+    let env = args.env_file.as_deref().map(EnvFile::load).transpose()?;
+    let lint_ctx = LintContext {
+        schema_text: &schema_text,
+        schema_label: &schema_label,
+        schema_path: schema_path.as_path(),
+        registry: registry.as_ref(),
+        rules: &rules,
+        placeholders: &placeholders,
+        schema_mode,
+        fix: args.fix,
+        baseline: &baseline,
+        report: (report_path.is_some() || machine_output).then_some(&report),
+        runtime: runtime.as_ref(),
+        releases: releases.as_ref(),
+        env: env.as_ref(),
+        prune_select: &args.prune_select,
+    };
+
+    if args.json {
+        let stdin_content = if args.stdin {
+            Some(read_stdin_flow()?)
+        } else {
+            None
+        };
+        let result = run_json(&args.targets, stdin_content, &lint_ctx);
+        if let Some((path, count)) = baseline.write()? {
+            eprintln!("Wrote baseline {} ({count} finding(s))", path.display());
+        }
+        return result;
+    }
+
+    let mut jobs = Vec::new();
+    for target in &args.targets {
+        collect_doctor_jobs(target, true, &mut jobs)?;
+    }
+    // Prompts for unused sidecar entries need one flow at a time, so a terminal session that
+    // could be asked about them stays sequential unless `--jobs` says otherwise.
+    let may_prompt = args.prune_select.is_empty() && !machine_output && io::stdin().is_terminal();
+    let workers = args
+        .jobs
+        .map_or_else(
+            || {
+                if may_prompt {
+                    1
+                } else {
+                    thread::available_parallelism().map_or(1, usize::from)
+                }
+            },
+            |jobs| jobs as usize,
+        )
+        .min(jobs.len())
+        .max(1);
+    let failed = run_doctor_jobs(&jobs, workers, &lint_ctx, &args, machine_output);
+    cancel::check()?;
+    let mut flows = report
+        .into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    flows.sort_by_key(|entry| {
+        jobs.iter()
+            .position(|job| job.path.display().to_string() == entry.path)
+    });
+
+    // With `--output json` or `sarif`, stdout carries only the report.
+    let note = |line: String| {
+        if machine_output {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+    };
+    if let Some((path, count)) = baseline.write()? {
+        note(format!(
+            "Wrote baseline {} ({count} finding(s))",
+            path.display()
+        ));
+    }
+    if let Some(path) = &report_path {
+        fs::write(path, render_html_report(&flows))
+            .with_context(|| format!("failed to write report {}", path.display()))?;
+        note(format!(
+            "Wrote report {} ({} flow(s))",
+            path.display(),
+            flows.len()
+        ));
+    }
+    let results = flows.into_iter().map(|entry| (entry.path, entry.lint));
+    match args.output {
+        DoctorOutput::Human => {}
+        DoctorOutput::Json => write_stdout_line(&DoctorJsonOutput::new(results).into_string())?,
+        DoctorOutput::Sarif => write_stdout_line(&SarifLog::new(results).into_string())?,
+    }
+    if failed.is_empty() {
+        note("All flows valid".to_string());
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "{} flow(s) failed validation: {}",
+            failed.len(),
+            failed
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+}
+
+/// One flow doctor checks. Flows named on the command line also get contract, config drift
+/// and upgrade checks; flows found by walking a directory get the lint checks.
+struct DoctorJob {
+    path: PathBuf,
+    explicit: bool,
+}
+
+/// Collect the flows under `path` in directory order.
+fn collect_doctor_jobs(path: &Path, explicit: bool, jobs: &mut Vec<DoctorJob>) -> Result<()> {
+    if path.is_file() {
+        if explicit || path.extension() == Some(OsStr::new("ygtc")) {
+            jobs.push(DoctorJob {
+                path: path.to_path_buf(),
+                explicit,
+            });
+        }
+    } else if path.is_dir() {
+        let entries = fs::read_dir(path)
+            .with_context(|| format!("failed to read directory {}", path.display()))?;
+        for entry in entries {
+            let entry = entry
+                .with_context(|| format!("failed to read directory entry in {}", path.display()))?;
+            collect_doctor_jobs(&entry.path(), false, jobs)?;
+        }
+    }
+    Ok(())
+}
+
+/// Check every flow, `workers` at a time, and return the ones that failed.
+///
+/// Each flow is isolated: an error or panic fails that flow only. With several workers, each
+/// flow's output is printed as one block, in the order the flows were found; `quiet` drops it.
+fn run_doctor_jobs<'j>(
+    jobs: &'j [DoctorJob],
+    workers: usize,
+    ctx: &LintContext<'_>,
+    args: &DoctorArgs,
+    quiet: bool,
+) -> Vec<&'j Path> {
+    let mut failed = Vec::new();
+    if workers <= 1 {
+        for job in jobs {
+            if cancel::cancelled().is_some() {
+                break;
+            }
+            let job_failed = if quiet {
+                capture_doctor_output(|| run_doctor_job(job, ctx, args, false)).0
+            } else {
+                run_doctor_job(job, ctx, args, true)
+            };
+            if job_failed {
+                failed.push(job.path.as_path());
+            }
+        }
+        return failed;
+    }
+
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(job) = jobs.get(index) else {
+                        break;
+                    };
+                    if cancel::cancelled().is_some() {
+                        break;
+                    }
+                    let (job_failed, lines) =
+                        capture_doctor_output(|| run_doctor_job(job, ctx, args, false));
+                    if sender.send((index, job_failed, lines)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        let mut printed = 0;
+        for (index, job_failed, lines) in receiver {
+            pending.insert(index, (job_failed, lines));
+            while let Some((job_failed, lines)) = pending.remove(&printed) {
+                if !quiet {
+                    for line in &lines {
+                        line.print();
+                    }
+                }
+                if job_failed {
+                    failed.push(jobs[printed].path.as_path());
+                }
+                printed += 1;
+            }
+        }
+    });
+    failed
+}
+
+/// Check one flow, turning errors and panics into a failure reported against its path.
+fn run_doctor_job(
+    job: &DoctorJob,
+    ctx: &LintContext<'_>,
+    args: &DoctorArgs,
+    interactive: bool,
+) -> bool {
+    match panic::catch_unwind(AssertUnwindSafe(|| {
+        doctor_flow(job, ctx, args, interactive)
+    })) {
+        Ok(Ok(failed)) => failed,
+        Ok(Err(err)) => {
+            let message = format!("{err:#}");
+            doctor_eprintln!("ERR {}: {message}", job.path.display());
+            report_doctor_error(ctx, &job.path, message);
+            true
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            let message = format!("internal error: {message}");
+            doctor_eprintln!("ERR {}: {message}", job.path.display());
+            report_doctor_error(ctx, &job.path, message);
+            true
+        }
+    }
+}
+
+/// Record a check that failed outright in the flow's report entry, adding one if needed.
+fn report_doctor_error(ctx: &LintContext<'_>, path: &Path, message: String) {
+    let Some(report) = ctx.report else {
+        return;
+    };
+    let path = path.display().to_string();
+    let mut report = report
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let index = match report.iter().rposition(|entry| entry.path == path) {
+        Some(index) => index,
+        None => {
+            report.push(FlowReport::new(
+                path.clone(),
+                LintJsonOutput::failure(Vec::new()),
+            ));
+            report.len() - 1
+        }
+    };
+    report[index].push_finding(JsonDiagnostic::from_message(message, Some(path)));
+}
+
+/// Run every doctor check on one flow; returns whether it failed.
+fn doctor_flow(
+    job: &DoctorJob,
+    ctx: &LintContext<'_>,
+    args: &DoctorArgs,
+    interactive: bool,
+) -> Result<bool> {
+    let target = job.path.as_path();
+    let mut failures = 0usize;
+    lint_file(target, ctx, interactive, &mut failures)?;
+    if !job.explicit {
+        return Ok(failures > 0);
+    }
+    let mut contract_diags = validate_contracts_for_flow(target, args.online)?;
+    contract_diags.extend(check_config_drift(target, args.accept_drift)?);
+    if let Some(catalog) = ctx.releases {
+        for advisory in collect_upgrade_advisories(target, catalog)? {
+            let Some(latest) = advisory.latest() else {
+                continue;
+            };
+            doctor_println!(
+                "advisory: node '{}' pins {} {}; {} available ({}): {}",
+                advisory.node_id,
+                advisory.component,
+                advisory.current_version,
+                latest.version,
+                upgrade_class_label(latest.classification),
+                latest.apply
+            );
+        }
+    }
+    contract_diags.sort_by(|a, b| {
+        a.node_id
+            .cmp(&b.node_id)
+            .then_with(|| a.severity.cmp(&b.severity))
+            .then_with(|| a.code.cmp(b.code))
+    });
+    for diag in &contract_diags {
+        match diag.severity {
+            ContractSeverity::Error => {
+                doctor_eprintln!("error: {} ({}:{})", diag.message, diag.node_id, diag.code)
+            }
+            ContractSeverity::Warning => {
+                doctor_eprintln!("warning: {} ({}:{})", diag.message, diag.node_id, diag.code)
+            }
+        }
+    }
+    if contract_diags
+        .iter()
+        .any(|d| matches!(d.severity, ContractSeverity::Error))
+    {
+        failures += 1;
+    }
+    if let Some(report) = ctx.report {
+        let target_display = target.display().to_string();
+        let mut report = report
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(entry) = report
+            .iter_mut()
+            .rev()
+            .find(|entry| entry.path == target_display)
+        {
+            for diag in contract_diags {
+                let severity = match diag.severity {
+                    ContractSeverity::Error => LintSeverity::Error,
+                    ContractSeverity::Warning => LintSeverity::Warning,
+                };
+                let mut finding =
+                    JsonDiagnostic::from_message(diag.message, Some(target_display.clone()));
+                finding.rule = Some(diag.code.to_string());
+                finding.severity = Some(severity);
+                finding.sidecar_path = Some(sidecar_path_for_flow(target).display().to_string());
+                finding.json_pointer = Some(format!("/nodes/{}", diag.node_id));
+                entry.push_finding(finding);
+            }
+        }
+    }
+    Ok(failures > 0)
+}
+
+fn handle_new(args: NewArgs, backup: bool) -> Result<()> {
+    write_new_flow_file(NewFlowFileSpec {
+        flow_path: args.flow_path.clone(),
+        flow_id: args.flow_id.clone(),
+        flow_type: args.flow_type.clone(),
+        schema_version: args.schema_version,
+        name: args.name,
+        description: args.description,
+        force: args.force,
+        backup,
+    })?;
+    println!(
+        "Created flow '{}' at {} (type: {})",
+        args.flow_id,
+        args.flow_path.display(),
+        args.flow_type
+    );
+    Ok(())
+}
+
+fn handle_doctor_answers(args: DoctorAnswersArgs) -> Result<()> {
+    let schema_text = fs::read_to_string(&args.schema)
+        .with_context(|| format!("read schema {}", args.schema.display()))?;
+    let answers_text = fs::read_to_string(&args.answers)
+        .with_context(|| format!("read answers {}", args.answers.display()))?;
+    let schema: serde_json::Value =
+        serde_json::from_str(&schema_text).context("parse schema as JSON")?;
+    let answers: serde_json::Value =
+        serde_json::from_str(&answers_text).context("parse answers as JSON")?;
+
+    let compiled = jsonschema::options()
+        .with_draft(Draft::Draft202012)
+        .build(&schema)
+        .context("compile answers schema")?;
+    if let Err(error) = compiled.validate(&answers) {
+        let messages = vec![error.to_string()];
+        if args.json {
+            let payload = json!({ "ok": false, "errors": messages });
+            print_json_payload(&payload)?;
+            std::process::exit(1);
+        } else {
+            for msg in &messages {
+                eprintln!("error: {msg}");
+            }
+        }
+        anyhow::bail!("answers failed schema validation");
+    }
+
+    if args.json {
+        let payload = json!({ "ok": true, "errors": [] });
+        print_json_payload(&payload)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum ContractSeverity {
+    Error,
+    Warning,
+}
+
+struct ContractDiagnostic {
+    code: &'static str,
+    severity: ContractSeverity,
+    message: String,
+    node_id: String,
+}
+
+fn validate_contracts_for_flow(flow_path: &Path, online: bool) -> Result<Vec<ContractDiagnostic>> {
+    let doc = load_ygtc_from_path(flow_path)?;
+    let flow_ir = FlowIr::from_doc(doc)?;
+    let mut diags = Vec::new();
+
+    for (node_id, node) in &flow_ir.nodes {
+        if !node_payload_looks_like_component(&node.payload) {
+            continue;
+        }
+        let meta = flow_ir
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.as_object())
+            .and_then(|root| root.get(flow_meta::META_NAMESPACE))
+            .and_then(|value| value.as_object())
+            .and_then(|greentic| greentic.get("components"))
+            .and_then(|value| value.as_object())
+            .and_then(|components| components.get(node_id))
+            .and_then(|value| value.as_object());
+
+        let Some(entry) = meta else {
+            diags.push(ContractDiagnostic {
+                code: "FLOW_MISSING_METADATA",
+                severity: ContractSeverity::Error,
+                message: "missing component contract metadata".to_string(),
+                node_id: node_id.clone(),
+            });
+            continue;
+        };
+
+        for field in ["describe_hash", "schema_hash", "operation_id"] {
+            if entry.get(field).and_then(|v| v.as_str()).is_none() {
+                diags.push(ContractDiagnostic {
+                    code: "FLOW_MISSING_METADATA",
+                    severity: ContractSeverity::Error,
+                    message: format!("missing required metadata field '{field}'"),
+                    node_id: node_id.clone(),
+                });
+            }
+        }
+
+        if !online {
+            if let Some(schema_hex) = entry.get("config_schema_cbor").and_then(|v| v.as_str()) {
+                let schema_bytes = match hex_to_bytes(schema_hex) {
+                    Ok(bytes) => bytes,
+                    Err(err) => {
+                        diags.push(ContractDiagnostic {
+                            code: "FLOW_SCHEMA_DECODE",
+                            severity: ContractSeverity::Error,
+                            message: format!("failed to decode stored config schema: {err}"),
+                            node_id: node_id.clone(),
+                        });
+                        continue;
+                    }
+                };
+                let schema: greentic_types::schemas::common::schema_ir::SchemaIr =
+                    match greentic_types::cbor::canonical::from_cbor(&schema_bytes) {
+                        Ok(schema) => schema,
+                        Err(err) => {
+                            diags.push(ContractDiagnostic {
+                                code: "FLOW_SCHEMA_DECODE",
+                                severity: ContractSeverity::Error,
+                                message: format!("failed to parse stored config schema: {err}"),
+                                node_id: node_id.clone(),
+                            });
+                            continue;
+                        }
+                    };
+                let config_value = extract_config_value(&node.payload);
+                let config_cbor =
+                    greentic_types::cbor::canonical::to_canonical_cbor_allow_floats(&config_value)
+                        .map_err(|err| anyhow!("encode config for validation: {err}"))?;
+                let config_val: ciborium::value::Value =
+                    ciborium::de::from_reader(config_cbor.as_slice())
+                        .map_err(|err| anyhow!("decode config cbor: {err}"))?;
+                let schema_diags = validate_value_against_schema(&schema, &config_val);
+                for diag in schema_diags {
+                    let severity = match diag.severity {
+                        Severity::Error => ContractSeverity::Error,
+                        Severity::Warning => ContractSeverity::Warning,
+                    };
+                    diags.push(ContractDiagnostic {
+                        code: diag.code,
+                        severity,
+                        message: diag.message,
+                        node_id: node_id.clone(),
+                    });
+                }
+            } else {
+                diags.push(ContractDiagnostic {
+                    code: "FLOW_SCHEMA_MISSING",
+                    severity: ContractSeverity::Warning,
+                    message: "missing stored config schema for offline validation".to_string(),
+                    node_id: node_id.clone(),
+                });
+            }
+            continue;
+        }
+
+        let Some(operation_id) = entry
+            .get("operation_id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+        else {
+            continue;
+        };
+
+        let Some(sidecar) = read_flow_resolve(flow_path).ok() else {
+            diags.push(ContractDiagnostic {
+                code: "FLOW_MISSING_SIDECAR",
+                severity: ContractSeverity::Error,
+                message: "missing resolve sidecar for online validation".to_string(),
+                node_id: node_id.clone(),
+            });
+            continue;
+        };
+        let Some(node_resolve) = sidecar.nodes.get(node_id) else {
+            diags.push(ContractDiagnostic {
+                code: "FLOW_MISSING_SIDECAR",
+                severity: ContractSeverity::Error,
+                message: "missing sidecar entry for node".to_string(),
+                node_id: node_id.clone(),
+            });
+            continue;
+        };
+
+        let resolved = resolve_source_to_wasm(flow_path, &node_resolve.source)?;
+        let spec = wizard_ops::fetch_wizard_spec(&resolved, wizard_ops::WizardMode::Default)?;
+        let (config_schema, computed_meta) = if !spec.describe_cbor.is_empty() {
+            let (describe, meta) = derive_contract_meta(&spec.describe_cbor, &operation_id)?;
+            (Some(describe.config_schema), meta)
+        } else if let Some(descriptor) = spec.descriptor.as_ref() {
+            derive_contract_meta_from_descriptor(descriptor, &operation_id)?
+        } else {
+            diags.push(ContractDiagnostic {
+                code: "FLOW_CONTRACT_SKIPPED",
+                severity: ContractSeverity::Warning,
+                message:
+                    "descriptor and describe_cbor are both unavailable; skipping contract checks"
+                        .to_string(),
+                node_id: node_id.clone(),
+            });
+            continue;
+        };
+
+        if let Some(stored) = entry.get("describe_hash").and_then(|v| v.as_str())
+            && stored != computed_meta.describe_hash
+        {
+            diags.push(ContractDiagnostic {
+                code: "FLOW_CONTRACT_DRIFT",
+                severity: ContractSeverity::Error,
+                message: "describe_hash mismatch (contract drift)".to_string(),
+                node_id: node_id.clone(),
+            });
+        }
+        if let Some(stored) = entry.get("schema_hash").and_then(|v| v.as_str())
+            && stored != computed_meta.schema_hash
+        {
+            diags.push(ContractDiagnostic {
+                code: "FLOW_SCHEMA_HASH_MISMATCH",
+                severity: ContractSeverity::Error,
+                message: "schema_hash mismatch".to_string(),
+                node_id: node_id.clone(),
+            });
+        }
+
+        let config_value = extract_config_value(&node.payload);
+        let config_cbor =
+            greentic_types::cbor::canonical::to_canonical_cbor_allow_floats(&config_value)
+                .map_err(|err| anyhow!("encode config for validation: {err}"))?;
+        let Some(schema) = config_schema else {
+            diags.push(ContractDiagnostic {
+                code: "FLOW_SCHEMA_MISSING",
+                severity: ContractSeverity::Warning,
+                message: "missing inline input schema in descriptor for online validation"
+                    .to_string(),
+                node_id: node_id.clone(),
+            });
+            continue;
+        };
+        let schema_diags = validate_value_against_schema(
+            &schema,
+            &ciborium::de::from_reader(config_cbor.as_slice())
+                .map_err(|err| anyhow!("decode config cbor: {err}"))?,
+        );
+        for diag in schema_diags {
+            let severity = match diag.severity {
+                Severity::Error => ContractSeverity::Error,
+                Severity::Warning => ContractSeverity::Warning,
+            };
+            diags.push(ContractDiagnostic {
+                code: diag.code,
+                severity,
+                message: diag.message,
+                node_id: node_id.clone(),
+            });
+        }
+    }
+
+    Ok(diags)
+}
+
+/// Advisories for the pinned sidecar bindings of `flow_path` that the catalog has newer
+/// releases of. Flows without a sidecar have no bindings to advise on.
+fn collect_upgrade_advisories(flow_path: &Path, catalog: &ReleaseCatalog) -> Result<Vec<Advisory>> {
+    let Ok(sidecar) = read_flow_resolve(&sidecar_path_for_flow(flow_path)) else {
+        return Ok(Vec::new());
+    };
+    let flow_ir = FlowIr::from_doc(load_ygtc_from_path(flow_path)?)?;
+    let flow = flow_path.display().to_string();
+    let mut advisories = Vec::new();
+    for (node_id, entry) in &sidecar.nodes {
+        let Some(node) = flow_ir.nodes.get(node_id) else {
+            continue;
+        };
+        let source = ComponentSource::from_sidecar(&entry.source, entry.mode);
+        if source.is_floating() {
+            continue;
+        }
+        let Some(digest) = source
+            .digest
+            .as_deref()
+            .or_else(|| source.reference.split_once('@').map(|(_, digest)| digest))
+        else {
+            continue;
+        };
+        let payload = extract_config_value(&node.payload);
+        let pinned = PinnedNode {
+            flow: &flow,
+            node_id,
+            reference: &source.reference,
+            digest,
+            operation: &node.operation,
+            payload: &payload,
+        };
+        advisories.extend(advise(catalog, &pinned));
+    }
+    Ok(advisories)
+}
+
+fn upgrade_class_label(class: UpgradeClass) -> &'static str {
+    match class {
+        UpgradeClass::Safe => "safe",
+        UpgradeClass::NeedsReview => "needs-review",
+        UpgradeClass::Breaking => "breaking",
+    }
+}
+
+fn node_payload_looks_like_component(payload: &serde_json::Value) -> bool {
+    if let Some(obj) = payload.as_object() {
+        if obj.contains_key("component") || obj.contains_key("config") {
+            return true;
+        }
+        if let Some(exec) = obj.get("component.exec") {
+            return exec.is_object();
+        }
+    }
+    false
+}
+
+/// Compare node configs with the canonical config CBOR recorded by the wizard.
+fn check_config_drift(flow_path: &Path, accept_drift: bool) -> Result<Vec<ContractDiagnostic>> {
+    let doc = load_ygtc_from_path(flow_path)?;
+    let flow_ir = FlowIr::from_doc(doc)?;
+    let mut diags = Vec::new();
+    for (node_id, node) in &flow_ir.nodes {
+        let Some(stored) = config_store::read_node_config(flow_path, node_id)? else {
+            continue;
+        };
+        let expected = wizard_ops::cbor_to_json(&stored)?;
+        let actual = extract_config_value(&node.payload);
+        if expected == actual {
+            continue;
+        }
+        if accept_drift {
+            config_store::write_node_config(
+                flow_path,
+                node_id,
+                &wizard_ops::json_to_cbor(&actual)?,
+            )?;
+            diags.push(ContractDiagnostic {
+                code: "FLOW_CONFIG_DRIFT_ACCEPTED",
+                severity: ContractSeverity::Warning,
+                message: "accepted hand-edited config; stored wizard config updated".to_string(),
+                node_id: node_id.clone(),
+            });
+        } else {
+            diags.push(ContractDiagnostic {
+                code: "FLOW_CONFIG_DRIFT",
+                severity: ContractSeverity::Error,
+                message: format!(
+                    "config drifted from the wizard-produced config (keys: {}); rerun update-step or pass --accept-drift",
+                    drifted_keys(&expected, &actual).join(", ")
+                ),
+                node_id: node_id.clone(),
+            });
+        }
+    }
+    Ok(diags)
+}
+
+fn drifted_keys(expected: &serde_json::Value, actual: &serde_json::Value) -> Vec<String> {
+    match (expected.as_object(), actual.as_object()) {
+        (Some(expected), Some(actual)) => expected
+            .keys()
+            .chain(actual.keys())
+            .filter(|key| expected.get(*key) != actual.get(*key))
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+        _ => vec!["<root>".to_string()],
+    }
+}
+
+fn extract_config_value(payload: &serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = payload.as_object()
+        && let Some(config) = obj.get("config")
+    {
+        return config.clone();
+    }
+    payload.clone()
+}
+
+fn resolve_source_to_wasm(
+    flow_path: &Path,
+    source: &ComponentSourceRefV1,
+) -> Result<wizard_ops::ComponentWasm> {
+    match source {
+        ComponentSourceRefV1::Local { path, .. } => {
+            let local_path = local_path_from_sidecar(path, flow_path);
+            if !local_path.is_file() {
+                anyhow::bail!("read wasm at {}: not a file", local_path.display());
+            }
+            Ok(wizard_ops::ComponentWasm::File(local_path))
+        }
+        ComponentSourceRefV1::Oci { r#ref, .. }
+        | ComponentSourceRefV1::Repo { r#ref, .. }
+        | ComponentSourceRefV1::Store { r#ref, .. } => {
+            let resolved = resolve_ref_to_wasm(r#ref, None)?;
+            Ok(resolved.wasm)
+        }
+    }
+}
+
+fn handle_answers(args: AnswersArgs, schema_mode: SchemaMode) -> Result<()> {
+    let manifest_path = resolve_manifest_path_for_component(&args.component)?;
+    let manifest = load_manifest_json(&manifest_path)?;
+    let requested_flow = match args.mode {
+        AnswersMode::Default => args.operation.as_str(),
+        AnswersMode::Config => "custom",
+    };
+    let (questions, used_flow) = questions_for_operation(&manifest, requested_flow)?;
+    if used_flow.as_deref() != Some(requested_flow)
+        && let Some(flow) = &used_flow
+    {
+        eprintln!(
+            "warning: dev_flows.{} not found; using dev_flows.{} for questions",
+            requested_flow, flow
+        );
+    }
+
+    let flow_name = used_flow.as_deref().unwrap_or(requested_flow);
+    let source_desc = format!("dev_flows.{flow_name}");
+    let component_id = manifest
+        .get("id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let schema = schema_for_questions(&questions);
+    let use_manifest_schema = questions.is_empty() || is_effectively_empty_schema(&schema);
+    let schema_resolution = if use_manifest_schema {
+        Some(resolve_input_schema(&manifest_path, &args.operation)?)
+    } else {
+        None
+    };
+    let (schema_source_desc, schema_operation, schema_manifest_path, schema_component_id) =
+        if let Some(resolution) = &schema_resolution {
+            (
+                "operations[].input_schema".to_string(),
+                resolution.operation.clone(),
+                resolution.manifest_path.as_path(),
+                resolution.component_id.as_str(),
+            )
+        } else {
+            (
+                source_desc,
+                flow_name.to_string(),
+                manifest_path.as_path(),
+                component_id.as_str(),
+            )
+        };
+    let schema_ref = if let Some(resolution) = &schema_resolution {
+        resolution.schema.as_ref()
+    } else {
+        Some(&schema)
+    };
+    require_schema(
+        schema_mode,
+        schema_component_id,
+        &schema_operation,
+        schema_manifest_path,
+        &schema_source_desc,
+        schema_ref,
+    )?;
+
+    let example = example_for_questions(&questions);
+    validate_example_against_schema(&schema, &example)?;
+
+    let out_dir = match args.out_dir {
+        Some(dir) => dir,
+        None => env::current_dir().context("resolve current directory")?,
+    };
+    fs::create_dir_all(&out_dir)
+        .with_context(|| format!("create output dir {}", out_dir.display()))?;
+    let schema_path = out_dir.join(format!("{}.schema.json", args.name));
+    let example_path = out_dir.join(format!("{}.example.json", args.name));
+    write_json_file(&schema_path, &schema)?;
+    write_json_file(&example_path, &example)?;
+    println!(
+        "Wrote answers schema to {} and example to {}",
+        schema_path.display(),
+        example_path.display()
+    );
+    Ok(())
+}
+
+fn handle_update(args: UpdateArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    if !args.flow_path.exists() {
+        anyhow::bail!(
+            "flow file {} not found; use `greentic-flow new` to create it",
+            args.flow_path.display()
+        );
+    }
+    let mut doc = load_ygtc_from_path(&args.flow_path)?;
+
+    if let Some(id) = args.flow_id {
+        doc.id = id;
+    }
+
+    if let Some(name) = args.name {
+        doc.title = Some(name);
+    }
+
+    if let Some(desc) = args.description {
+        doc.description = Some(desc);
+    }
+
+    if let Some(tags_raw) = args.tags {
+        let tags = tags_raw
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        doc.tags = tags;
+    }
+
+    if let Some(schema_version) = args.schema_version {
+        doc.schema_version = Some(schema_version);
+    }
+
+    if let Some(flow_type) = args.flow_type {
+        let is_empty_flow =
+            doc.nodes.is_empty() && doc.entrypoints.is_empty() && doc.start.is_none();
+        if !is_empty_flow {
+            anyhow::bail!(
+                "refusing to change type on a non-empty flow; use `greentic-flow convert-type --flow {} --to {flow_type}`",
+                args.flow_path.display()
+            );
+        }
+        doc.flow_type = flow_type;
+    }
+
+    let yaml = serialize_doc(&doc)?;
+    // Validate final doc to catch accidental schema violations.
+    load_ygtc_from_str(&yaml)?;
+    if args.dry_run {
+        let diff = dry_run_diff(&args.flow_path, &yaml, None)?;
+        if matches!(format, OutputFormat::Json) {
+            print_json_payload(&dry_run_payload("update", &yaml, &diff))?;
+        } else {
+            print_dry_run_diff(&diff);
+        }
+        return Ok(());
+    }
+    write_flow_file(&args.flow_path, &yaml, true, backup)?;
+    println!("Updated flow metadata at {}", args.flow_path.display());
+    Ok(())
+}
+
+fn handle_convert_type<R: Read, W: Write>(
+    args: ConvertTypeArgs,
+    format: OutputFormat,
+    backup: bool,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<()> {
+    let mut doc = load_ygtc_from_path(&args.flow_path)?;
+    if map_flow_type(&doc.flow_type).ok() == Some(map_flow_type(&args.to)?) {
+        anyhow::bail!(
+            "flow {} is already of type '{}'",
+            args.flow_path.display(),
+            doc.flow_type
+        );
+    }
+    let conversion = plan_kind_conversion(&doc, &args.to)?;
+    let resolutions = if conversion.issues.is_empty() || args.apply_defaults {
+        conversion
+            .issues
+            .iter()
+            .map(|issue| issue.resolutions[0])
+            .collect()
+    } else if args.interactive {
+        prompt_kind_resolutions(&conversion, reader, writer)?
+    } else {
+        for issue in &conversion.issues {
+            eprintln!("{}", describe_kind_issue(issue));
+        }
+        anyhow::bail!(
+            "{} item(s) in {} are incompatible with type '{}'; rerun with --interactive or --apply-defaults",
+            conversion.issues.len(),
+            args.flow_path.display(),
+            args.to
+        );
+    };
+    apply_kind_conversion(&mut doc, &conversion, &resolutions)?;
+    let yaml = serialize_doc(&doc)?;
+    load_ygtc_from_str(&yaml)?;
+    if !args.dry_run {
+        write_flow_file(&args.flow_path, &yaml, true, backup)?;
+    }
+
+    if matches!(format, OutputFormat::Json) {
+        let applied: Vec<serde_json::Value> = conversion
+            .issues
+            .iter()
+            .zip(&resolutions)
+            .map(|(issue, resolution)| {
+                json!({
+                    "location": issue.location,
+                    "message": issue.message,
+                    "resolution": resolution,
+                })
+            })
+            .collect();
+        return print_json_payload(&json!({
+            "ok": true,
+            "flow": args.flow_path.display().to_string(),
+            "from": conversion.from,
+            "to": conversion.to,
+            "dry_run": args.dry_run,
+            "resolved": applied,
+        }));
+    }
+    for (issue, resolution) in conversion.issues.iter().zip(&resolutions) {
+        writeln!(writer, "{}: {resolution}", issue.location).ok();
+    }
+    let verb = if args.dry_run {
+        "Would convert"
+    } else {
+        "Converted"
+    };
+    writeln!(
+        writer,
+        "{verb} flow {} from '{}' to '{}'",
+        args.flow_path.display(),
+        conversion.from,
+        conversion.to
+    )
+    .ok();
+    Ok(())
+}
+
+fn handle_migrate(args: &MigrateArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    let yaml = fs::read_to_string(&args.flow_path)
+        .with_context(|| format!("failed to read {}", args.flow_path.display()))?;
+    let migrated = migrate_flow(&yaml, args.to)
+        .with_context(|| format!("failed to migrate {}", args.flow_path.display()))?;
+    let changed = !migrated.changes.is_empty();
+    if changed && !args.dry_run {
+        write_flow_file(&args.flow_path, &migrated.yaml, true, backup)?;
+    }
+
+    if matches!(format, OutputFormat::Json) {
+        return print_json_payload(&json!({
+            "ok": true,
+            "flow": args.flow_path.display().to_string(),
+            "from": migrated.from,
+            "to": migrated.to,
+            "dry_run": args.dry_run,
+            "changes": migrated.changes,
+        }));
+    }
+    if !changed {
+        println!(
+            "{} is already at schema_version {}",
+            args.flow_path.display(),
+            migrated.to
+        );
+        return Ok(());
+    }
+    for change in &migrated.changes {
+        println!("{}: {} ({})", change.location, change.message, change.rule);
+    }
+    let verb = if args.dry_run {
+        "Would migrate"
+    } else {
+        "Migrated"
+    };
+    println!(
+        "{verb} {} from schema_version {} to {} ({} change(s))",
+        args.flow_path.display(),
+        migrated.from,
+        migrated.to,
+        migrated.changes.len()
+    );
+    Ok(())
+}
+
+fn describe_kind_issue(issue: &KindIssue) -> String {
+    let options: Vec<&str> = issue.resolutions.iter().map(|r| r.as_str()).collect();
+    format!(
+        "{}: {} (options: {})",
+        issue.location,
+        issue.message,
+        options.join(", ")
+    )
+}
+
+fn prompt_kind_resolutions<R: Read, W: Write>(
+    conversion: &KindConversion,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<Vec<Resolution>> {
+    let mut resolutions = Vec::with_capacity(conversion.issues.len());
+    for issue in &conversion.issues {
+        if let [only] = issue.resolutions.as_slice() {
+            writeln!(
+                writer,
+                "{} (no automatic fix; kept)",
+                describe_kind_issue(issue)
+            )
+            .ok();
+            resolutions.push(*only);
+            continue;
+        }
+        let question = Question {
+            id: "convert.resolution".to_string(),
+            prompt: describe_kind_issue(issue),
+            kind: greentic_flow::questions::QuestionKind::Choice,
+            required: true,
+            default: Some(serde_json::Value::String(
+                issue.resolutions[0].as_str().to_string(),
+            )),
+            choices: issue
+                .resolutions
+                .iter()
+                .map(|r| serde_json::Value::String(r.as_str().to_string()))
+                .collect(),
+            show_if: None,
+            writes_to: None,
+        };
+        let answers =
+            run_questions_with_qa_lib_io(&[question], HashMap::new(), &mut *reader, &mut *writer)?;
+        let chosen = answers
+            .get("convert.resolution")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+        let resolution = issue
+            .resolutions
+            .iter()
+            .find(|r| r.as_str() == chosen)
+            .copied()
+            .ok_or_else(|| anyhow!("unknown resolution '{chosen}' for {}", issue.location))?;
+        resolutions.push(resolution);
+    }
+    Ok(resolutions)
+}
+
+struct LintContext<'a> {
+    schema_text: &'a str,
+    schema_label: &'a str,
+    schema_path: &'a Path,
+    registry: Option<&'a AdapterCatalog>,
+    /// Lint rules run on every compiled flow.
+    rules: &'a RuleRegistry,
+    placeholders: &'a PlaceholderValuesRule,
+    schema_mode: SchemaMode,
+    fix: bool,
+    baseline: &'a BaselineMode,
+    /// `--report`: collects each linted flow for the report.
+    report: Option<&'a Mutex<Vec<FlowReport>>>,
+    /// `--runtime-manifest`: worlds and schema ids the target runtime provides.
+    runtime: Option<&'a RuntimeCapabilities>,
+    /// `--release-catalog`: newer component versions to advise upgrades to.
+    releases: Option<&'a ReleaseCatalog>,
+    /// `--env-file`: variables and secrets set in the target environment.
+    env: Option<&'a EnvFile>,
+    /// `--prune-select`: unused sidecar entries to prune without prompting.
+    prune_select: &'a [String],
+}
+
+/// How doctor treats lint findings recorded in a baseline file.
+enum BaselineMode {
+    Off,
+    /// `--baseline`: drop findings recorded in the file.
+    Apply {
+        path: PathBuf,
+        baseline: LintBaseline,
+    },
+    /// `--write-baseline`, or `--baseline` with no file yet: collect every finding instead of
+    /// reporting it.
+    Record {
+        path: PathBuf,
+        baseline: Mutex<LintBaseline>,
+    },
+}
+
+impl BaselineMode {
+    /// Remove the findings the baseline covers (or, when recording, all of them).
+    fn filter(
+        &self,
+        source_path: Option<&Path>,
+        diagnostics: Vec<LintDiagnostic>,
+    ) -> Vec<LintDiagnostic> {
+        let key = |baseline_path: &Path| baseline_key(baseline_path, source_path);
+        match self {
+            BaselineMode::Off => diagnostics,
+            BaselineMode::Apply { path, baseline } => {
+                let (kept, suppressed) = baseline.suppress(&key(path), diagnostics);
+                if suppressed > 0 {
+                    doctor_eprintln!(
+                        "note: {suppressed} finding(s) suppressed by baseline {}",
+                        path.display()
+                    );
+                }
+                kept
+            }
+            BaselineMode::Record { path, baseline } => {
+                baseline
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .record(&key(path), &diagnostics);
+                Vec::new()
+            }
+        }
+    }
+
+    /// The findings an applied baseline does not cover, without reporting anything. A baseline
+    /// being recorded covers nothing yet.
+    fn uncovered(
+        &self,
+        source_path: Option<&Path>,
+        diagnostics: Vec<LintDiagnostic>,
+    ) -> Vec<LintDiagnostic> {
+        match self {
+            BaselineMode::Apply { path, baseline } => {
+                baseline
+                    .suppress(&baseline_key(path, source_path), diagnostics)
+                    .0
+            }
+            BaselineMode::Off | BaselineMode::Record { .. } => diagnostics,
+        }
+    }
+
+    /// Write a recorded baseline; returns its path and finding count.
+    fn write(&self) -> Result<Option<(&Path, usize)>> {
+        let BaselineMode::Record { path, baseline } = self else {
+            return Ok(None);
+        };
+        let baseline = baseline
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        fs::write(path, baseline.to_json_string())
+            .with_context(|| format!("failed to write baseline {}", path.display()))?;
+        Ok(Some((path.as_path(), baseline.findings.len())))
+    }
+}
+
+/// The baseline key of the flow at `source_path` (stdin when `None`).
+fn baseline_key(baseline_path: &Path, source_path: Option<&Path>) -> String {
+    source_path
+        .map(|flow| baseline_flow_key(baseline_path, flow))
+        .unwrap_or_else(|| "<stdin>".to_string())
+}
+
+fn lint_path(
+    path: &Path,
+    ctx: &LintContext<'_>,
+    interactive: bool,
+    failures: &mut usize,
+) -> Result<()> {
+    if path.is_file() {
+        lint_file(path, ctx, interactive, failures)?;
+    } else if path.is_dir() {
+        let entries = fs::read_dir(path)
+            .with_context(|| format!("failed to read directory {}", path.display()))?;
+        for entry in entries {
+            let entry = entry
+                .with_context(|| format!("failed to read directory entry in {}", path.display()))?;
+            lint_path(&entry.path(), ctx, interactive, failures)?;
+        }
+    }
+    Ok(())
+}
+
+fn lint_file(
+    path: &Path,
+    ctx: &LintContext<'_>,
+    interactive: bool,
+    failures: &mut usize,
+) -> Result<()> {
+    if path.extension() != Some(OsStr::new("ygtc")) {
+        return Ok(());
+    }
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let content = repair_multi_operation_nodes(path, content, ctx)?;
+    let content = apply_lint_fixes(path, content, ctx)?;
+
+    let output = match lint_flow(&content, Some(path), ctx) {
+        Ok(result) => {
+            let mut problems = Vec::new();
+            let mut prune_decisions = Vec::new();
+            if result.lint_errors.is_empty() {
+                let i18n_tag_errors = lint_i18n_tag_fields(path);
+                if !i18n_tag_errors.is_empty() {
+                    *failures += 1;
+                    for err in &i18n_tag_errors {
+                        doctor_eprintln!("ERR  {}: {err}", path.display());
+                    }
+                    problems.extend(i18n_diagnostics(path, i18n_tag_errors));
+                }
+                if result.bundle.kind != "component-config" {
+                    let prune = if !ctx.prune_select.is_empty() {
+                        SidecarPrune::Select(ctx.prune_select)
+                    } else if interactive {
+                        SidecarPrune::Prompt
+                    } else {
+                        SidecarPrune::Keep
+                    };
+                    let validation = validate_sidecar_for_flow(path, &result.flow, prune, true)?;
+                    for decision in &validation.decisions {
+                        doctor_println!(
+                            "{} sidecar entry {} in {}: {} ({})",
+                            if decision.pruned { "Pruned" } else { "Kept" },
+                            decision.node_id,
+                            validation.path.display(),
+                            describe_prune_entry(decision),
+                            decision.reason
+                        );
+                    }
+                    prune_decisions = validation.decisions.clone();
+                    let sidecar_errors = sidecar_diagnostics(path, &validation);
+                    if !sidecar_errors.is_empty() {
+                        *failures += 1;
+                        for err in &sidecar_errors {
+                            doctor_eprintln!("ERR  {}: {}", path.display(), err.message);
+                        }
+                        problems.extend(sidecar_errors);
+                    }
+                    if validation.updated {
+                        doctor_println!("Updated sidecar {}", validation.path.display());
+                    }
+                }
+                if problems.is_empty() {
+                    doctor_println!("OK  {} ({})", path.display(), result.bundle.id);
+                }
+            } else {
+                *failures += 1;
+                doctor_eprintln!("ERR {}:", path.display());
+                for err in &result.lint_errors {
+                    doctor_eprintln!("  {}", describe_lint_diagnostic(err));
+                }
+            }
+            let source = Some(path.display().to_string());
+            let output = if !result.lint_errors.is_empty() {
+                LintJsonOutput::lint_diagnostics(result.lint_errors, source.clone())
+            } else if problems.is_empty() {
+                LintJsonOutput::success(result.bundle)
+            } else {
+                LintJsonOutput::failure(problems)
+            };
+            output
+                .with_warnings(result.lint_warnings, source)
+                .with_suppressions(result.suppressions)
+                .with_sidecar_prunes(prune_decisions)
+        }
+        Err(err) => {
+            *failures += 1;
+            doctor_eprintln!("ERR {}: {err}", path.display());
+            LintJsonOutput::error(err)
+        }
+    };
+    if let Some(report) = ctx.report {
+        report
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(flow_report(path, &content, output));
+    }
+    Ok(())
+}
+
+/// Report entry for one flow: its lint output plus, when it loads, graph and component sources.
+fn flow_report(path: &Path, content: &str, output: LintJsonOutput) -> FlowReport {
+    let entry = FlowReport::new(path.display().to_string(), output);
+    let Some(flow_ir) = load_ygtc_from_str(content)
+        .ok()
+        .and_then(|doc| FlowIr::from_doc(doc).ok())
+    else {
+        return entry;
+    };
+    let sidecar = read_flow_resolve(&sidecar_path_for_flow(path)).ok();
+    entry.with_flow(&flow_ir, |node_id| {
+        let source = &sidecar.as_ref()?.nodes.get(node_id)?.source;
+        Some(match source {
+            ComponentSourceRefV1::Local { path, .. } => path.clone(),
+            ComponentSourceRefV1::Oci { r#ref, .. }
+            | ComponentSourceRefV1::Repo { r#ref, .. }
+            | ComponentSourceRefV1::Store { r#ref, .. } => r#ref.clone(),
+        })
+    })
+}
+
+/// Suggest (or, with `--fix`, apply) repairs for nodes carrying several operation keys.
+///
+/// A key counts as the operation when it is a builtin, a registry adapter operation, or an
+/// operation of the node's locally bound component; the remaining keys move into the payload
+/// or under `annotations`. Returns the (possibly rewritten) flow content.
+fn repair_multi_operation_nodes(
+    path: &Path,
+    content: String,
+    ctx: &LintContext<'_>,
+) -> Result<String> {
+    let Ok(nodes) = find_multi_operation_nodes(&content) else {
+        return Ok(content);
+    };
+    if nodes.is_empty() {
+        return Ok(content);
+    }
+    let sidecar = read_flow_resolve(&sidecar_path_for_flow(path)).ok();
+    let mut fixes: Vec<MultiOperationFix> = Vec::new();
+    for node in &nodes {
+        let component_ops: Vec<String> = sidecar
+            .as_ref()
+            .and_then(|doc| doc.nodes.get(&node.node_id))
+            .filter(|entry| matches!(entry.source, ComponentSourceRefV1::Local { .. }))
+            .and_then(|entry| resolve_component_manifest_path(&entry.source, path).ok())
+            .and_then(|manifest| manifest_operation_names(&manifest).ok())
+            .unwrap_or_default();
+        let is_operation = |key: &str| {
+            if matches!(key, "questions" | "template") || component_ops.iter().any(|op| op == key) {
+                return true;
+            }
+            match (ctx.registry, classify_node_type(key)) {
+                (
+                    Some(catalog),
+                    NodeKind::Adapter {
+                        namespace,
+                        adapter,
+                        operation,
+                    },
+                ) => catalog.contains(&namespace, &adapter, &operation),
+                _ => false,
+            }
+        };
+        let Some(fix) = suggest_multi_operation_fix(&content, node, is_operation)? else {
+            continue;
+        };
+        if ctx.fix {
+            fixes.push(fix);
+        } else {
+            doctor_eprintln!("hint {}: {fix} (run doctor --fix)", path.display());
+        }
+    }
+    if fixes.is_empty() {
+        return Ok(content);
+    }
+    let repaired = apply_multi_operation_fixes(&content, &fixes)?;
+    fs::write(path, &repaired).with_context(|| format!("failed to write {}", path.display()))?;
+    for fix in &fixes {
+        doctor_eprintln!("fixed {}: {fix}", path.display());
+    }
+    Ok(repaired)
+}
+
+/// Findings for a flow's sidecar problems, each naming the sidecar and how to fix it.
+///
+/// A finding lists every affected node; it points at the node when there is only one.
+fn sidecar_diagnostics(flow_path: &Path, validation: &SidecarValidation) -> Vec<JsonDiagnostic> {
+    let flow = flow_path.display().to_string();
+    let sidecar = validation.path.display().to_string();
+    let checks = [
+        (
+            "sidecar_missing",
+            "missing sidecar entries for nodes",
+            &validation.missing,
+            format!(
+                "bind each node with `greentic-flow bind-component --flow {flow} --step <node> \
+                 --local-wasm <file> --write` (or `--component <ref>`)"
+            ),
+        ),
+        (
+            "sidecar_unused",
+            "unused sidecar entries",
+            &validation.extra,
+            format!(
+                "remove the entries from {sidecar}, run doctor with `--prune-select stale`, or \
+                 run it with `--jobs 1` and answer the prompts"
+            ),
+        ),
+        (
+            "sidecar_invalid",
+            "invalid sidecar entries",
+            &validation.invalid,
+            "rebind each node with `greentic-flow bind-component --write`".to_string(),
+        ),
+    ];
+    checks
+        .into_iter()
+        .filter(|(_, _, nodes, _)| !nodes.is_empty())
+        .map(|(rule, label, nodes, fix)| {
+            let mut diagnostic = JsonDiagnostic::from_message(
+                format!("{label}: {}", nodes.join(", ")),
+                Some(sidecar.clone()),
+            );
+            diagnostic.rule = Some(rule.to_string());
+            diagnostic.severity = Some(LintSeverity::Error);
+            diagnostic.sidecar_path = Some(sidecar.clone());
+            if let [node] = nodes.as_slice() {
+                diagnostic.json_pointer = Some(format!("/nodes/{node}"));
+            }
+            diagnostic.fix = Some(LintFix::new(
+                fix,
+                FixApplicability::MaybeIncorrect,
+                Vec::new(),
+            ));
+            diagnostic
+        })
+        .collect()
+}
+
+/// Findings for the `i18n:` tag checks on a flow's title and description.
+fn i18n_diagnostics(flow_path: &Path, errors: Vec<String>) -> Vec<JsonDiagnostic> {
+    errors
+        .into_iter()
+        .map(|message| {
+            let mut diagnostic =
+                JsonDiagnostic::from_message(message, Some(flow_path.display().to_string()));
+            diagnostic.rule = Some("i18n_tag".to_string());
+            diagnostic.severity = Some(LintSeverity::Error);
+            diagnostic
+        })
+        .collect()
+}
+
+fn lint_i18n_tag_fields(path: &Path) -> Vec<String> {
+    let mut errors = Vec::new();
+    let Ok(doc) = load_ygtc_from_path(path) else {
+        return errors;
+    };
+    let i18n_source = load_pack_i18n_source_for_flow(path);
+    if let Some(title) = doc.title.as_deref() {
+        lint_i18n_tag_value("title", title, i18n_source.as_ref(), &mut errors);
+    }
+    if let Some(description) = doc.description.as_deref() {
+        lint_i18n_tag_value(
+            "description",
+            description,
+            i18n_source.as_ref(),
+            &mut errors,
+        );
+    }
+    errors
+}
+
+fn lint_i18n_tag_value(
+    field: &str,
+    value: &str,
+    i18n_source: Option<&serde_json::Map<String, serde_json::Value>>,
+    errors: &mut Vec<String>,
+) {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return;
+    }
+    let Some(key) = trimmed.strip_prefix("i18n:") else {
+        errors.push(format!(
+            "{field} must be an i18n tag (expected prefix i18n:)"
+        ));
+        return;
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        errors.push(format!("{field} i18n tag key cannot be empty"));
+        return;
+    }
+    if let Some(source) = i18n_source {
+        match source.get(key).and_then(serde_json::Value::as_str) {
+            Some(v) if !v.trim().is_empty() => {}
+            _ => errors.push(format!(
+                "{field} i18n key '{key}' missing from pack i18n/en-GB.json"
+            )),
+        }
+    }
+}
+
+fn load_pack_i18n_source_for_flow(
+    path: &Path,
+) -> Option<serde_json::Map<String, serde_json::Value>> {
+    let pack_root = infer_pack_root_from_flow_path(path).ok()?;
+    let i18n_path = pack_root.join("i18n/en-GB.json");
+    let text = fs::read_to_string(i18n_path).ok()?;
+    serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text).ok()
+}
+
+struct LintResult {
+    bundle: FlowBundle,
+    flow: greentic_types::Flow,
+    lint_errors: Vec<LintDiagnostic>,
+    lint_warnings: Vec<LintDiagnostic>,
+    suppressions: Vec<LintSuppression>,
+}
+
+#[allow(clippy::result_large_err)]
+fn lint_flow(
+    content: &str,
+    source_path: Option<&Path>,
+    ctx: &LintContext<'_>,
+) -> Result<LintResult, FlowError> {
+    let (bundle, flow) = load_and_validate_bundle_with_schema_text(
+        content,
+        ctx.schema_text,
+        ctx.schema_label.to_string(),
+        Some(ctx.schema_path),
+        source_path,
+    )?;
+    let doc = serde_yaml_bw::from_str::<greentic_flow::model::FlowDoc>(content).ok();
+    let today = utc_today();
+    let flow_path = source_path.and_then(|path| std::path::absolute(path).ok());
+    let lint_config = discover_lint_config(flow_path.as_deref())?;
+    let mut lint_errors = run_lint_rules(
+        &flow,
+        doc.as_ref(),
+        lint_config.as_ref().map(|(_, config)| config),
+        ctx,
+    );
+    let flow_ir = doc.and_then(|doc| FlowIr::from_doc(doc).ok());
+    lint_errors.extend(lint_component_configs(
+        &flow,
+        flow_ir.as_ref(),
+        source_path,
+        bundle.kind.as_str(),
+        ctx.schema_mode,
+    ));
+    if let Some(runtime) = ctx.runtime {
+        lint_errors.extend(check_runtime_compatibility(&bundle, runtime));
+    }
+    if let (Some(env), Some(path)) = (ctx.env, source_path)
+        && let Ok((nodes, unresolved)) = collect_node_requirements(path)
+    {
+        lint_errors.extend(check_env_requirements(&nodes, env));
+        for node_id in unresolved {
+            lint_errors.push(
+                LintDiagnostic::error(
+                    "env_requirements",
+                    format!(
+                        "node '{node_id}' component manifest is unavailable; its env vars and secrets were not checked"
+                    ),
+                )
+                .with_severity(LintSeverity::Warning)
+                .with_path(format!("/nodes/{node_id}")),
+            );
+        }
+    }
+    let spans = SourceMap::from_yaml(content);
+    for diagnostic in &mut lint_errors {
+        diagnostic.locate_in(&spans);
+    }
+    let (mut suppressions, suppression_errors) = parse_lint_suppressions(content);
+    lint_errors.extend(suppression_errors);
+    let (lint_errors, mut silenced) = match &lint_config {
+        Some((_, config)) => config.apply(flow_path.as_deref(), lint_errors),
+        None => (lint_errors, 0),
+    };
+    let mut lint_errors = apply_lint_suppressions(&mut suppressions, lint_errors, &today);
+    lint_errors.extend(expired_suppression_warnings(&suppressions, &today));
+    let (lint_warnings, mut lint_errors): (Vec<_>, Vec<_>) = lint_errors
+        .into_iter()
+        .partition(|diagnostic| diagnostic.severity == LintSeverity::Warning);
+    for warning in &lint_warnings {
+        doctor_eprintln!("WARN {}", describe_lint_diagnostic(warning));
+    }
+    let mut placeholder_hits = ctx.placeholders.check(&flow);
+    for hit in &mut placeholder_hits {
+        hit.locate_in(&spans);
+    }
+    let placeholder_hits = match &lint_config {
+        Some((_, config)) => {
+            let (hits, dropped) = config.apply(flow_path.as_deref(), placeholder_hits);
+            silenced += dropped;
+            hits
+        }
+        None => placeholder_hits,
+    };
+    let placeholder_hits = apply_lint_suppressions(&mut suppressions, placeholder_hits, &today);
+    for suppression in &suppressions {
+        doctor_eprintln!("note: {}", describe_suppression(suppression));
+    }
+    if let Some((config_path, _)) = &lint_config
+        && silenced > 0
+    {
+        doctor_eprintln!(
+            "note: {} silenced {silenced} finding(s)",
+            config_path.display()
+        );
+    }
+    if ctx.schema_mode.is_permissive() {
+        for hit in placeholder_hits {
+            doctor_eprintln!("WARN {hit}");
+        }
+    } else {
+        lint_errors.extend(placeholder_hits);
+    }
+    let lint_errors = ctx.baseline.filter(source_path, lint_errors);
+    Ok(LintResult {
+        bundle,
+        flow,
+        lint_errors,
+        lint_warnings,
+        suppressions,
+    })
+}
+
+/// Run doctor's lint rules over `flow`, with the flow's document and project settings.
+fn run_lint_rules(
+    flow: &greentic_types::Flow,
+    doc: Option<&greentic_flow::model::FlowDoc>,
+    config: Option<&LintConfig>,
+    ctx: &LintContext<'_>,
+) -> Vec<LintDiagnostic> {
+    let mut rule_ctx = RuleContext::default();
+    if let Some(doc) = doc {
+        rule_ctx = rule_ctx.with_doc(doc);
+    }
+    if let Some(catalog) = ctx.registry {
+        rule_ctx = rule_ctx.with_catalog(catalog);
+    }
+    if let Some(config) = config {
+        rule_ctx = rule_ctx.with_config(config);
+    }
+    ctx.rules.run(flow, &rule_ctx)
+}
+
+/// The `.greentic-lint.toml` governing the flow at `flow_path`, searched upward from its
+/// directory (from the working directory for stdin).
+#[allow(clippy::result_large_err)]
+fn discover_lint_config(
+    flow_path: Option<&Path>,
+) -> Result<Option<(PathBuf, LintConfig)>, FlowError> {
+    let start = match flow_path {
+        Some(path) => path.parent().map(Path::to_path_buf),
+        None => std::env::current_dir().ok(),
+    };
+    match start {
+        Some(dir) => LintConfig::discover(&dir),
+        None => Ok(None),
+    }
+}
+
+/// Summary line for an inline suppression: what it covers, how often it applied, and why.
+fn describe_suppression(suppression: &LintSuppression) -> String {
+    let until = suppression
+        .until
+        .as_deref()
+        .map(|until| format!(", until {until}"))
+        .unwrap_or_default();
+    format!(
+        "node '{}' suppresses {} ({} finding(s)): {}{until}",
+        suppression.node_id,
+        suppression.rules.join(", "),
+        suppression.suppressed,
+        suppression.reason
+    )
+}
+
+/// Human form of a lint finding, mentioning its fix when the rule offers one.
+fn describe_lint_diagnostic(diagnostic: &LintDiagnostic) -> String {
+    match &diagnostic.fix {
+        Some(fix) if fix.is_machine_applicable() => {
+            format!("{diagnostic} (fix: {fix}; run doctor --fix)")
+        }
+        Some(fix) => format!("{diagnostic} (suggested fix: {fix})"),
+        None => diagnostic.to_string(),
+    }
+}
+
+/// With `--fix`, apply the machine-applicable fixes of the findings doctor reports.
+///
+/// Fixes are edits on the flow IR, so the rewritten file is re-rendered from the IR and
+/// validated before it is written. Returns the (possibly rewritten) flow content.
+fn apply_lint_fixes(path: &Path, content: String, ctx: &LintContext<'_>) -> Result<String> {
+    if !ctx.fix {
+        return Ok(content);
+    }
+    let Ok(doc) = load_ygtc_from_str(&content) else {
+        return Ok(content);
+    };
+    let Ok(flow) = compile_flow(doc.clone()) else {
+        return Ok(content);
+    };
+    // Only findings doctor would report get fixed: project rule levels, inline suppressions
+    // and the baseline all apply.
+    let flow_path = std::path::absolute(path).ok();
+    let lint_config = discover_lint_config(flow_path.as_deref())?;
+    let lint_config = lint_config.as_ref().map(|(_, config)| config);
+    let mut diagnostics = run_lint_rules(&flow, Some(&doc), lint_config, ctx);
+    let spans = SourceMap::from_yaml(&content);
+    for diagnostic in &mut diagnostics {
+        diagnostic.locate_in(&spans);
+    }
+    if let Some(config) = lint_config {
+        diagnostics = config.apply(flow_path.as_deref(), diagnostics).0;
+    }
+    let (mut suppressions, _) = parse_lint_suppressions(&content);
+    let diagnostics = apply_lint_suppressions(&mut suppressions, diagnostics, &utc_today());
+    let diagnostics = ctx.baseline.uncovered(Some(path), diagnostics);
+    if !diagnostics.iter().any(|d| {
+        d.fix
+            .as_ref()
+            .is_some_and(|fix| fix.is_machine_applicable())
+    }) {
+        return Ok(content);
+    }
+    let mut flow_ir = FlowIr::from_doc(doc)?;
+    let applied = apply_machine_applicable_fixes(&mut flow_ir, &diagnostics)?;
+    let fixed = serialize_doc(&flow_ir.to_doc()?)?;
+    load_ygtc_from_str(&fixed)
+        .with_context(|| format!("fixed flow {} failed validation", path.display()))?;
+    let _writing = cancel::write_guard()?;
+    cancel::replace_file_atomically(path, fixed.as_bytes())?;
+    for fix in applied {
+        for edit in &fix.edits {
+            if let FixEdit::RenameNode { from, to } = edit {
+                move_renamed_node_files(path, &flow_ir.id, from, to)?;
+            }
+        }
+        doctor_eprintln!("fixed {}: {fix}", path.display());
+    }
+    Ok(fixed)
+}
+
+/// Move what is stored per node id next to the flow (sidecar, answer provenance and resolve
+/// summary entries, wizard config and state) after a fix renamed node `from` to `to`, as
+/// `rename-step` does.
+fn move_renamed_node_files(flow_path: &Path, flow_id: &str, from: &str, to: &str) -> Result<()> {
+    let sidecar_path = sidecar_path_for_flow(flow_path);
+    if sidecar_path.exists() {
+        let mut sidecar =
+            read_flow_resolve(&sidecar_path).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        if let Some(entry) = sidecar.nodes.remove(from) {
+            sidecar.nodes.insert(to.to_string(), entry);
+            write_sidecar(&sidecar_path, &sidecar)?;
+        }
+    }
+    answer_provenance::rename_answer_provenance(flow_path, from, to)?;
+    rename_flow_resolve_summary_node(flow_path, from, to)
+        .with_context(|| format!("update resolve summary for {}", flow_path.display()))?;
+    if let Err(err) = config_store::rename_node_config(flow_path, from, to) {
+        doctor_eprintln!("warning: {err}");
+    }
+    if let Err(err) = wizard_state::rename_wizard_step(flow_path, flow_id, from, to) {
+        doctor_eprintln!("warning: {err}");
+    }
+    Ok(())
+}
+
+fn lint_component_configs(
+    flow: &greentic_types::Flow,
+    flow_ir: Option<&FlowIr>,
+    source_path: Option<&Path>,
+    flow_kind: &str,
+    schema_mode: SchemaMode,
+) -> Vec<LintDiagnostic> {
+    if flow_kind == "component-config" {
+        return Vec::new();
+    }
+    let Some(flow_path) = source_path else {
+        return Vec::new();
+    };
+    if !flow_path.exists() {
+        return Vec::new();
+    }
+    let sidecar_path = sidecar_path_for_flow(flow_path);
+    if !sidecar_path.exists() {
+        return Vec::new();
+    }
+    let sidecar = match read_flow_resolve(&sidecar_path) {
+        Ok(doc) => doc,
+        Err(err) => {
+            return vec![LintDiagnostic::error(
+                "component_config",
+                format!("failed to read sidecar {}: {err}", sidecar_path.display()),
+            )];
+        }
+    };
+
+    let mut errors = Vec::new();
+    for (node_id, node) in &flow.nodes {
+        let node_key = node_id.as_str();
+        if matches!(node.component.id.as_str(), "questions" | "template") {
+            continue;
+        }
+        let Some(entry) = sidecar.nodes.get(node_key) else {
+            continue;
+        };
+        let node_path = format!("/nodes/{node_key}");
+        let diagnostic = |message: String| {
+            LintDiagnostic::error("component_config", message).with_path(node_path.clone())
+        };
+        let manifest_path = match resolve_component_manifest_path(&entry.source, flow_path) {
+            Ok(path) => path,
+            Err(_) => continue,
+        };
+        let operation = node.component.operation.as_deref().unwrap_or("unknown");
+        if let Some(node_ir) = flow_ir.and_then(|ir| ir.nodes.get(node_key))
+            && let Ok(Some(output_schema)) = resolve_output_schema(&manifest_path, operation)
+            && let Some(declared) = DeclaredStatuses::from_output_schema(&output_schema)
+        {
+            errors.extend(StatusRoutesRule::check(
+                node_key,
+                &node_ir.routing,
+                &declared,
+            ));
+        }
+        if let Some(recorded) =
+            flow_ir.and_then(|ir| flow_meta::manifest_schema_hash(&ir.meta, node_key))
+            && let Ok(Some(current)) = manifest_operation_schema_hash(&manifest_path, operation)
+            && recorded != current
+        {
+            errors.push(
+                LintDiagnostic::error(
+                    "schema_hash_drift",
+                    format!(
+                        "node '{node_key}' payload was authored against operation '{operation}' schema {recorded}, but {} now declares {current}; review the payload and re-run update-step",
+                        manifest_path.display()
+                    ),
+                )
+                .with_severity(LintSeverity::Warning)
+                .with_path(node_path.clone()),
+            );
+        }
+        let schema_resolution = match resolve_input_schema(&manifest_path, operation) {
+            Ok(resolution) => resolution,
+            Err(err) => {
+                errors.push(diagnostic(format!(
+                    "node '{node_key}' failed to read {}: {err}",
+                    manifest_path.display()
+                )));
+                continue;
+            }
+        };
+        let source_desc = "operations[].input_schema";
+        let schema_ref = match require_schema(
+            schema_mode,
+            &schema_resolution.component_id,
+            &schema_resolution.operation,
+            &schema_resolution.manifest_path,
+            source_desc,
+            schema_resolution.schema.as_ref(),
+        ) {
+            Ok(Some(schema)) => schema,
+            Ok(None) => continue,
+            Err(err) => {
+                errors.push(diagnostic(err.to_string()));
+                continue;
+            }
+        };
+        let validator = match jsonschema_options_with_base(Some(manifest_path.as_path()))
+            .build(schema_ref)
+        {
+            Ok(validator) => validator,
+            Err(err) => {
+                if let ValidationErrorKind::Referencing(ReferencingError::Unretrievable {
+                    uri, ..
+                }) = err.kind()
+                    && uri.starts_with("file://")
+                    && !Path::new(uri.trim_start_matches("file://")).exists()
+                {
+                    doctor_eprintln!(
+                        "WARN component_config: node '{node_key}' schema validation for component '{}' skipped because '{uri}' is missing (manifest: {}). Continuing without this schema.",
+                        schema_resolution.component_id,
+                        manifest_path.display()
+                    );
+                    continue;
+                }
+                errors.push(diagnostic(format!(
+                    "node '{node_key}' schema compile failed for component '{}': {err}",
+                    schema_resolution.component_id
+                )));
+                continue;
+            }
+        };
+        let payload = match resolve_parameters(
+            &node.input.mapping,
+            &flow.metadata.extra,
+            &format!("nodes.{node_key}"),
+        ) {
+            Ok(value) => value,
+            Err(err) => {
+                errors.push(diagnostic(format!(
+                    "node '{node_key}' parameters resolution failed: {err}",
+                )));
+                continue;
+            }
+        };
+        let config_payload = extract_config_value(&payload);
+        for err in validator.iter_errors(&config_payload) {
+            let pointer = err.instance_path().to_string();
+            let pointer = if pointer.is_empty() {
+                "/".to_string()
+            } else {
+                pointer
+            };
+            errors.push(
+                diagnostic(format!(
+                    "node '{node_key}' payload invalid for component '{}' at {pointer}: {err}",
+                    schema_resolution.component_id
+                ))
+                .with_path(format!("{node_path}{}", err.instance_path())),
+            );
+        }
+    }
+
+    errors
+}
+
+fn run_json(
+    targets: &[PathBuf],
+    stdin_content: Option<String>,
+    ctx: &LintContext<'_>,
+) -> Result<()> {
+    let (content, source_display, source_path) = if let Some(stdin_flow) = stdin_content {
+        (
+            stdin_flow,
+            "<stdin>".to_string(),
+            Some(Path::new("<stdin>")),
+        )
+    } else {
+        if targets.len() != 1 {
+            anyhow::bail!("--json mode expects exactly one target file");
+        }
+        let target = &targets[0];
+        if target.is_dir() {
+            anyhow::bail!(
+                "--json target must be a file, found directory {}",
+                target.display()
+            );
+        }
+        if target.extension() != Some(OsStr::new("ygtc")) {
+            anyhow::bail!("--json target must be a .ygtc file");
+        }
+        let content = fs::read_to_string(target)
+            .with_context(|| format!("failed to read {}", target.display()))?;
+        let content = repair_multi_operation_nodes(target, content, ctx)?;
+        let content = apply_lint_fixes(target, content, ctx)?;
+        (
+            content,
+            target.display().to_string(),
+            Some(target.as_path()),
+        )
+    };
+
+    let lint_result = lint_flow(&content, source_path, ctx);
+
+    let mut warnings = Vec::new();
+    let mut suppressions = Vec::new();
+    let output = match lint_result {
+        Ok(mut result) => {
+            warnings = std::mem::take(&mut result.lint_warnings);
+            suppressions = std::mem::take(&mut result.suppressions);
+            if !result.lint_errors.is_empty() {
+                LintJsonOutput::lint_diagnostics(result.lint_errors, Some(source_display.clone()))
+            } else if let Some(path) = source_path
+                && path.exists()
+            {
+                if result.bundle.kind == "component-config" {
+                    LintJsonOutput::success(result.bundle)
+                } else {
+                    let validation =
+                        validate_sidecar_for_flow(path, &result.flow, SidecarPrune::Keep, false)?;
+                    let mut errors = sidecar_diagnostics(path, &validation);
+                    errors.extend(i18n_diagnostics(path, lint_i18n_tag_fields(path)));
+                    if errors.is_empty() {
+                        LintJsonOutput::success(result.bundle)
+                    } else {
+                        LintJsonOutput::failure(errors)
+                    }
+                }
+            } else {
+                LintJsonOutput::success(result.bundle)
+            }
+        }
+        Err(err) => LintJsonOutput::error(err),
+    };
+    let advisories = match (ctx.releases, source_path) {
+        (Some(catalog), Some(path)) if path.exists() => {
+            Some(collect_upgrade_advisories(path, catalog)?)
+        }
+        (Some(_), _) => Some(Vec::new()),
+        (None, _) => None,
+    };
+    let output = output
+        .with_warnings(warnings, Some(source_display))
+        .with_suppressions(suppressions)
+        .with_advisories(advisories);
+
+    let ok = output.ok;
+    let line = output.into_string();
+    write_stdout_line(&line)?;
+    if ok {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("validation failed"))
+    }
+}
+
+/// `node (source, added YYYY-MM-DD)` context shown for a sidecar entry doctor may prune.
+fn describe_prune_entry(decision: &SidecarPruneDecision) -> String {
+    match decision.added_at {
+        Some(added_at) => format!("{}, added {}", decision.source, utc_date(added_at)),
+        None => decision.source.clone(),
+    }
+}
+
+/// Answer to the per-entry prune prompt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PruneAnswer {
+    Yes,
+    No,
+    /// Prune this and every remaining entry of the sidecar.
+    All,
+    /// Keep this and every remaining entry of the sidecar.
+    Quit,
+}
+
+fn prompt_prune_entry(path: &Path, decision: &SidecarPruneDecision) -> PruneAnswer {
+    eprintln!(
+        "Unused sidecar entry in {}: node '{}' is no longer in the flow",
+        path.display(),
+        decision.node_id
+    );
+    eprintln!("  source: {}", decision.source);
+    if let Some(added_at) = decision.added_at {
+        eprintln!("  added:  {}", utc_date(added_at));
+    }
+    eprint!("Prune it? [y/N/a(ll)/q(uit)]: ");
+    io::stderr().flush().ok();
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        return PruneAnswer::Quit;
+    }
+    match input.trim().to_lowercase().as_str() {
+        "y" | "yes" => PruneAnswer::Yes,
+        "a" | "all" => PruneAnswer::All,
+        "q" | "quit" => PruneAnswer::Quit,
+        _ => PruneAnswer::No,
+    }
+}
+
+/// Whether a `--prune-select` selector picks the unused entry `node_id` of the flow `flow_name`.
+fn prune_selector_matches(selector: &str, flow_name: &str, node_id: &str) -> bool {
+    let stem = flow_name.strip_suffix(".ygtc").unwrap_or(flow_name);
+    let names_flow = |name: &str| name == flow_name || name == stem;
+    match selector.trim() {
+        "stale" => true,
+        selector => match selector.rsplit_once(':') {
+            Some((flow, node)) => names_flow(flow) && node == node_id,
+            None => names_flow(selector),
+        },
+    }
+}
+
+fn read_stdin_flow() -> Result<String> {
+    let mut buf = String::new();
+    io::stdin()
+        .read_to_string(&mut buf)
+        .context("failed to read flow YAML from stdin")?;
+    Ok(buf)
+}
+
+fn write_stdout_line(line: &str) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match writeln!(stdout, "{line}") {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AddStepArgs;
+    use super::AddStepMode;
+    use super::DeleteStepArgs;
+    use super::NewArgs;
+    use super::OutputFormat;
+    use super::SchemaMode;
+    use super::UpdateArgs;
+    use super::UpdateStepArgs;
+    use super::WizardModeArg;
+    use super::handle_add_step;
+    use super::handle_delete_step;
+    use super::handle_new;
+    use super::handle_update;
+    use super::handle_update_step;
+    use super::parse_answers_map;
+    use super::resolve_config_flow;
+    use super::serialize_doc;
+    use greentic_flow::flow_ir::FlowIr;
+    use greentic_flow::loader::load_ygtc_from_path;
+    use greentic_flow::wizard_ops::WizardMode;
+    use serde_json::Value;
+    use serde_json::json;
+    use std::env;
+    use std::fs;
+    use std::io::Cursor;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Mutex, OnceLock};
+    use tempfile::NamedTempFile;
+    use tempfile::tempdir;
+
+    fn extract_config_payload(payload: &serde_json::Value) -> &serde_json::Value {
+        payload
+            .get("config")
+            .and_then(|value| value.as_object().map(|_| value))
+            .unwrap_or(payload)
+    }
+
+    fn env_test_lock() -> std::sync::MutexGuard<'static, ()> {
+        static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+        LOCK.get_or_init(|| Mutex::new(()))
+            .lock()
+            .expect("env test lock")
+    }
+
+    #[test]
+    fn wizard_menu_main_zero_exits() {
+        let dir = tempdir().expect("temp dir");
+        let input = Cursor::new("0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard exit");
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.contains("Main Menu"));
+    }
+
+    #[test]
+    fn wizard_menu_m_returns_to_main_menu() {
+        let dir = tempdir().expect("temp dir");
+        let flows_dir = dir.path().join("flows");
+        fs::create_dir_all(&flows_dir).expect("create flows dir");
+        fs::write(
+            flows_dir.join("sample.ygtc"),
+            "id: sample\ntype: messaging\nnodes: {}\n",
+        )
+        .expect("write flow");
+
+        // 2 => edit/delete flows, M => main menu, 0 => exit
+        let input = Cursor::new("2\nM\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard navigation");
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.contains("Select number of flow"));
+        assert!(rendered.matches("Main Menu").count() >= 2);
+    }
+
+    #[test]
+    fn wizard_menu_flow_ops_zero_returns_to_flow_select() {
+        let dir = tempdir().expect("temp dir");
+        let flows_dir = dir.path().join("flows");
+        fs::create_dir_all(&flows_dir).expect("create flows dir");
+        fs::write(
+            flows_dir.join("sample.ygtc"),
+            "id: sample\ntype: messaging\nnodes: {}\n",
+        )
+        .expect("write flow");
+
+        // 2 => flow list, 1 => flow ops, 0 => back to flow list, 0 => main, 0 => exit.
+        let input = Cursor::new("2\n1\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard flow-ops back");
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.contains("Select number of flow"));
+        assert!(rendered.contains("Select operation"));
+    }
+
+    #[test]
+    fn wizard_menu_flow_ops_m_returns_to_main_menu() {
+        let dir = tempdir().expect("temp dir");
+        let flows_dir = dir.path().join("flows");
+        fs::create_dir_all(&flows_dir).expect("create flows dir");
+        fs::write(
+            flows_dir.join("sample.ygtc"),
+            "id: sample\ntype: messaging\nnodes: {}\n",
+        )
+        .expect("write flow");
+
+        // 2 => flow list, 1 => flow ops, M => main, 0 => exit.
+        let input = Cursor::new("2\n1\nM\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard flow-ops main menu");
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.matches("Main Menu").count() >= 2);
+    }
+
+    #[test]
+    fn wizard_generate_translations_requires_source_bundle() {
+        let dir = tempdir().expect("temp dir");
+        let input = Cursor::new("3\nes\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard should continue after translation error");
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.contains("missing i18n/en-GB.json"));
+    }
+
+    #[test]
+    fn wizard_generate_translations_stub_writes_locale_files() {
+        let _guard = env_test_lock();
+        let dir = tempdir().expect("temp dir");
+        fs::create_dir_all(dir.path().join("i18n")).expect("create i18n");
+        fs::write(
+            dir.path().join("i18n/en-GB.json"),
+            r#"{"wizard.hello":"Hello"}"#,
+        )
+        .expect("write source bundle");
+
+        let previous = env::var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB").ok();
+        unsafe {
+            env::set_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB", "1");
+        }
+        let mut input = Cursor::new("es,fr\n");
+        let mut output = Vec::new();
+        let mut answers_log = serde_json::Map::new();
+        super::wizard_generate_translations_with_io(
+            dir.path(),
+            &mut input,
+            &mut output,
+            &mut answers_log,
+        )
+        .expect("generate translations (stub)");
+        if let Some(value) = previous {
+            unsafe {
+                env::set_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB", value);
+            }
+        } else {
+            unsafe {
+                env::remove_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB");
+            }
+        }
+
+        let es_text = fs::read_to_string(dir.path().join("i18n/es.json")).expect("read es");
+        let fr_text = fs::read_to_string(dir.path().join("i18n/fr.json")).expect("read fr");
+        assert!(es_text.contains("Hello [es]"));
+        assert!(fr_text.contains("Hello [fr]"));
+    }
+
+    #[test]
+    fn wizard_menu_generate_translations_pack_wide_and_save() {
+        let _guard = env_test_lock();
+        let dir = tempdir().expect("temp dir");
+        fs::create_dir_all(dir.path().join("i18n")).expect("create i18n");
+        fs::write(
+            dir.path().join("i18n/en-GB.json"),
+            r#"{"flow.alpha.title":"Alpha","flow.beta.title":"Beta"}"#,
+        )
+        .expect("write source bundle");
+
+        let previous = env::var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB").ok();
+        unsafe {
+            env::set_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB", "1");
+        }
+
+        // 3=Generate translations, enter locales, 4=Save, enter default answers path, 0=Exit.
+        let input = Cursor::new("3\nes,fr\n4\n\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard menu generate translations");
+
+        if let Some(value) = previous {
+            unsafe {
+                env::set_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB", value);
+            }
+        } else {
+            unsafe {
+                env::remove_var("GREENTIC_FLOW_WIZARD_TRANSLATE_STUB");
+            }
+        }
+
+        let es_text = fs::read_to_string(dir.path().join("i18n/es.json")).expect("read es");
+        let fr_text = fs::read_to_string(dir.path().join("i18n/fr.json")).expect("read fr");
+        let es_json: serde_json::Value = serde_json::from_str(&es_text).expect("parse es");
+        let fr_json: serde_json::Value = serde_json::from_str(&fr_text).expect("parse fr");
+        assert!(
+            es_json
+                .get("flow.alpha.title")
+                .and_then(|v| v.as_str())
+                .is_some()
+        );
+        assert!(
+            es_json
+                .get("flow.beta.title")
+                .and_then(|v| v.as_str())
+                .is_some()
+        );
+        assert!(
+            fr_json
+                .get("flow.alpha.title")
+                .and_then(|v| v.as_str())
+                .is_some()
+        );
+        assert!(
+            fr_json
+                .get("flow.beta.title")
+                .and_then(|v| v.as_str())
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn wizard_i18n_smoke_no_missing_key_markers() {
+        let keys = [
+            "wizard.menu.main.prompt",
+            "wizard.menu.flow_select.title",
+            "wizard.menu.flow_ops.prompt",
+            "wizard.menu.nav.back",
+            "wizard.menu.nav.main",
+            "wizard.add_flow.scope.prompt",
+            "wizard.add_flow.created",
+            "wizard.flow.summary.name.prompt",
+            "wizard.flow.summary.edit.prompt",
+            "wizard.flow.summary.current_name",
+            "wizard.flow.summary.current_description",
             "wizard.flow.summary.not_set",
             "wizard.flow.summary.updated",
             "wizard.flow.delete.confirm.prompt",
@@ -4901,3076 +7377,6800 @@ mod tests {
         for key in keys {
             let value = super::wizard_t(key);
             assert!(
-                !value.contains("[[missing:"),
-                "expected key to resolve without missing marker: {key}"
+                !value.contains("[[missing:"),
+                "expected key to resolve without missing marker: {key}"
+            );
+        }
+    }
+
+    #[test]
+    fn add_flow_path_global_messaging() {
+        let path =
+            super::build_add_flow_relative_path("global", None, None, None, "messaging", "welcome")
+                .expect("global path");
+        assert_eq!(path, PathBuf::from("flows/global/messaging/welcome.ygtc"));
+    }
+
+    #[test]
+    fn add_flow_path_tenant_all_teams_events() {
+        let path = super::build_add_flow_relative_path(
+            "tenant",
+            Some("tenant-a"),
+            Some("all-teams"),
+            None,
+            "events",
+            "audit",
+        )
+        .expect("tenant all-teams path");
+        assert_eq!(
+            path,
+            PathBuf::from("flows/tenant-a/all-teams/events/audit.ygtc")
+        );
+    }
+
+    #[test]
+    fn add_flow_path_tenant_specific_team() {
+        let path = super::build_add_flow_relative_path(
+            "tenant",
+            Some("tenant-a"),
+            Some("specific-team"),
+            Some("blue"),
+            "messaging",
+            "alerts",
+        )
+        .expect("tenant team path");
+        assert_eq!(
+            path,
+            PathBuf::from("flows/tenant-a/blue/messaging/alerts.ygtc")
+        );
+    }
+
+    #[test]
+    fn add_flow_path_rejects_unknown_type() {
+        let err = super::build_add_flow_relative_path("global", None, None, None, "http", "x")
+            .expect_err("invalid type");
+        assert!(err.to_string().to_lowercase().contains("flow type"));
+    }
+
+    #[test]
+    fn wizard_add_flow_menu_creates_global_flow() {
+        let dir = tempdir().expect("temp dir");
+        // scope=1(global), type=1(messaging), name=welcome
+        let mut input = Cursor::new("1\n1\nwelcome\n");
+        let mut output = Vec::new();
+        let mut answers_log = serde_json::Map::new();
+        super::wizard_add_flow_with_io(dir.path(), &mut input, &mut output, &mut answers_log)
+            .expect("wizard add flow");
+        let path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        assert!(path.exists(), "expected flow file {}", path.display());
+    }
+
+    #[test]
+    fn wizard_flow_summary_menu_updates_title_and_description() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        write_two_step_flow(&flow_path);
+        handle_update(
+            UpdateArgs {
+                flow_path: flow_path.clone(),
+                flow_id: None,
+                flow_type: None,
+                schema_version: None,
+                name: Some("Old Name".to_string()),
+                description: Some("Old Description".to_string()),
+                tags: None,
+                dry_run: false,
+            },
+            OutputFormat::Human,
+            false,
+        )
+        .expect("seed metadata");
+
+        // 2 flow list, 1 select flow, 1 summary, provide new name/desc, 7 save, then back/back/exit
+        let input = Cursor::new("2\n1\n1\n2\nNew Name\nNew Description\n7\n\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard summary update");
+        let output_text = String::from_utf8_lossy(&output);
+        assert!(output_text.contains("Current name/title: Old Name"));
+        assert!(output_text.contains("Current description: Old Description"));
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        assert_eq!(doc.title.as_deref(), Some("i18n:flow.main.title"));
+        assert_eq!(
+            doc.description.as_deref(),
+            Some("i18n:flow.main.description")
+        );
+        let i18n_map: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(dir.path().join("i18n/en-GB.json")).expect("read i18n"),
+        )
+        .expect("parse i18n");
+        assert_eq!(
+            i18n_map.get("flow.main.title").and_then(|v| v.as_str()),
+            Some("New Name")
+        );
+        assert_eq!(
+            i18n_map
+                .get("flow.main.description")
+                .and_then(|v| v.as_str()),
+            Some("New Description")
+        );
+    }
+
+    #[test]
+    fn wizard_flow_summary_shows_resolved_i18n_values() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/main.ygtc");
+        if let Some(parent) = flow_path.parent() {
+            fs::create_dir_all(parent).expect("create parent");
+        }
+        fs::write(
+            &flow_path,
+            r#"id: main
+type: messaging
+schema_version: 2
+title: i18n:flow.misc.title
+description: i18n:flow.misc.description
+nodes: {}
+"#,
+        )
+        .expect("write flow");
+        fs::create_dir_all(dir.path().join("i18n")).expect("create i18n dir");
+        fs::write(
+            dir.path().join("i18n/en-GB.json"),
+            r#"{
+  "flow.misc.title": "Best flow title",
+  "flow.misc.description": "The best flow ever"
+}"#,
+        )
+        .expect("write i18n");
+
+        let mut input = Cursor::new("1\n");
+        let mut output = Vec::new();
+        let mut answers_log = serde_json::Map::new();
+        super::wizard_edit_flow_summary_with_io(
+            &flow_path,
+            &mut input,
+            &mut output,
+            &mut answers_log,
+        )
+        .expect("summary view");
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.contains("Best flow title"));
+        assert!(rendered.contains("The best flow ever"));
+        assert!(!rendered.contains("i18n:flow.misc.title"));
+        assert!(!rendered.contains("i18n:flow.misc.description"));
+    }
+
+    #[test]
+    fn wizard_changes_without_save_are_discarded() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: Some("Old Name".to_string()),
+                description: Some("Old Description".to_string()),
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+
+        // 2 flow list, 1 select flow, 1 summary edit, change fields, then back to main and exit without save.
+        let input = Cursor::new("2\n1\n1\n2\nDraft Name\nDraft Description\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard summary update without save");
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        assert_eq!(doc.title.as_deref(), Some("Old Name"));
+        assert_eq!(doc.description.as_deref(), Some("Old Description"));
+    }
+
+    #[test]
+    fn wizard_save_persists_changes() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        write_two_step_flow(&flow_path);
+        handle_update(
+            UpdateArgs {
+                flow_path: flow_path.clone(),
+                flow_id: None,
+                flow_type: None,
+                schema_version: None,
+                name: Some("Old Name".to_string()),
+                description: Some("Old Description".to_string()),
+                tags: None,
+                dry_run: false,
+            },
+            OutputFormat::Human,
+            false,
+        )
+        .expect("seed metadata");
+
+        // 2 flow list, 1 select flow, 1 summary edit, then 7 save, back to main and exit.
+        let input = Cursor::new("2\n1\n1\n2\nNew Name\nNew Description\n7\n\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard summary update with save");
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        assert_eq!(doc.title.as_deref(), Some("i18n:flow.main.title"));
+        assert_eq!(
+            doc.description.as_deref(),
+            Some("i18n:flow.main.description")
+        );
+    }
+
+    #[test]
+    fn wizard_save_doctor_failure_does_not_persist() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+
+        with_wizard_resolver_env(fixture_registry_resolver(), || {
+            // Add step with fixture oci ref, try save (doctor should fail on sidecar ref validation), then exit.
+            // No anchor prompt is shown when the flow has no steps.
+            let input = Cursor::new("2\n1\n3\n2\noci://acme/widget:1\n2\n1\n7\n\n0\n0\n0\n");
+            let mut output = Vec::new();
+            super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard run");
+            let rendered = String::from_utf8(output).expect("utf8");
+            assert!(rendered.contains("Save blocked: doctor failed"));
+            assert!(rendered.contains("Select operation"));
+        });
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        assert!(flow_ir.nodes.is_empty(), "failed save should not persist");
+    }
+
+    #[test]
+    fn wizard_save_default_answers_path_writes_file() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: Some("Old Name".to_string()),
+                description: Some("Old Description".to_string()),
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+
+        let input = Cursor::new("2\n1\n1\n2\nNew Name\nNew Description\n7\n\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard save");
+        let answers_path = dir.path().join("answers.json");
+        assert!(
+            answers_path.exists(),
+            "default answers file should be written"
+        );
+    }
+
+    #[test]
+    fn wizard_save_custom_answers_path_writes_file() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: Some("Old Name".to_string()),
+                description: Some("Old Description".to_string()),
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+
+        let input = Cursor::new("2\n1\n1\n2\nNew Name\nNew Description\n7\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_config(
+            dir.path(),
+            input,
+            &mut output,
+            super::WizardRunConfig {
+                answers_file: Some(PathBuf::from("artifacts/answers-out.json")),
+                dry_run: false,
+            },
+        )
+        .expect("wizard save");
+        let answers_path = dir.path().join("artifacts/answers-out.json");
+        assert!(
+            answers_path.exists(),
+            "custom answers file should be written"
+        );
+    }
+
+    #[test]
+    fn wizard_main_menu_save_answers_option_writes_file() {
+        let dir = tempdir().expect("temp dir");
+        let input = Cursor::new("5\n\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard save answers");
+        let answers_path = dir.path().join("answers.json");
+        assert!(
+            answers_path.exists(),
+            "answers file should be written from main menu option 5"
+        );
+    }
+
+    #[test]
+    fn wizard_answers_export_and_reload_flow_matches_answers() {
+        let dir = tempdir().expect("temp dir");
+        let answers_rel = PathBuf::from("answers/replay.json");
+        let answers_path = dir.path().join(&answers_rel);
+        let flow_path = dir.path().join("flows/global/messaging/main.ygtc");
+        write_two_step_flow(&flow_path);
+
+        // Session 1: exercise menu flows, export answers via main menu 5, then save via 4.
+        let input = Cursor::new(
+            "2\n1\n\
+             1\n2\nFlow One\nDesc One\n\
+             2\n\
+             4\ncancel\n\
+             5\ncancel\n\
+             0\nM\n\
+             5\nanswers/replay.json\n\
+             4\n\
+             0\n",
+        );
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard run 1");
+
+        assert!(answers_path.exists(), "wizard answers export should exist");
+        assert!(flow_path.exists(), "flow should be persisted after save");
+
+        let answers_text = fs::read_to_string(&answers_path).expect("read answers file");
+        let answers_json: serde_json::Value =
+            serde_json::from_str(&answers_text).expect("parse answers json");
+        let answers = answers_json.as_object().expect("answers object");
+        assert_eq!(
+            answers.get("wizard.answers.path").and_then(|v| v.as_str()),
+            Some("answers/replay.json")
+        );
+        assert_eq!(
+            answers.get("summary.name").and_then(|v| v.as_str()),
+            Some("Flow One")
+        );
+        assert_eq!(
+            answers.get("summary.description").and_then(|v| v.as_str()),
+            Some("Desc One")
+        );
+
+        let before = fs::read_to_string(&flow_path).expect("read flow before reload");
+
+        // Session 2: preload answers file and run main menu 4 (save).
+        let input = Cursor::new("4\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_config(
+            dir.path(),
+            input,
+            &mut output,
+            super::WizardRunConfig {
+                answers_file: Some(answers_rel.clone()),
+                dry_run: false,
+            },
+        )
+        .expect("wizard run 2");
+
+        let after = fs::read_to_string(&flow_path).expect("read flow after reload");
+        assert_eq!(before, after, "reload+save should preserve flow");
+
+        let flow_doc = load_ygtc_from_path(&flow_path).expect("load saved flow");
+        assert_eq!(flow_doc.id, "main");
+        assert_eq!(flow_doc.flow_type, "messaging");
+        assert_eq!(flow_doc.title.as_deref(), Some("i18n:flow.main.title"));
+        assert_eq!(
+            flow_doc.description.as_deref(),
+            Some("i18n:flow.main.description")
+        );
+        let flow_ir = FlowIr::from_doc(flow_doc).expect("flow ir");
+        assert!(
+            flow_ir.nodes.contains_key("first") && flow_ir.nodes.contains_key("second"),
+            "flow should preserve existing steps"
+        );
+    }
+
+    #[test]
+    fn wizard_dry_run_does_not_persist_flow_but_writes_answers_file() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: Some("Old Name".to_string()),
+                description: Some("Old Description".to_string()),
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+
+        let answers_path = dir.path().join("dry-run-answers.json");
+        let input = Cursor::new("2\n1\n1\n2\nDry Name\nDry Description\n7\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_config(
+            dir.path(),
+            input,
+            &mut output,
+            super::WizardRunConfig {
+                answers_file: Some(answers_path.clone()),
+                dry_run: true,
+            },
+        )
+        .expect("wizard dry-run save");
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        assert_eq!(doc.title.as_deref(), Some("Old Name"));
+        assert_eq!(doc.description.as_deref(), Some("Old Description"));
+        assert!(
+            answers_path.exists(),
+            "answers file should be written in dry-run"
+        );
+    }
+
+    #[test]
+    fn wizard_delete_flow_cancelled_keeps_file() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+        let mut input = Cursor::new("1\n");
+        let mut output = Vec::new();
+        super::wizard_delete_flow_with_io(&flow_path, &mut input, &mut output)
+            .expect("delete prompt");
+        assert!(flow_path.exists(), "flow should remain after cancel");
+    }
+
+    #[test]
+    fn wizard_menu_delete_flow_removes_file() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+
+        // 2 flow list, 1 select flow, 6 delete, 2 yes, then 0 back to main, 4 save(default answers path), 0 exit
+        let input = Cursor::new("2\n1\n6\n2\n0\n4\n\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard delete flow");
+        assert!(!flow_path.exists(), "flow should be deleted");
+    }
+
+    fn write_two_step_flow(path: &Path) {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent");
+        }
+        fs::write(
+            path,
+            r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  first:
+    op: {}
+    routing:
+    - to: second
+  second:
+    op: {}
+    routing:
+    - out: true
+"#,
+        )
+        .expect("write two-step flow");
+        let sidecar_path = super::sidecar_path_for_flow(path);
+        fs::write(
+            sidecar_path,
+            r#"{
+  "schema_version": 1,
+  "flow": "welcome.ygtc",
+  "nodes": {
+    "first": {
+      "source": { "kind": "repo", "ref": "repo://placeholder/first" }
+    },
+    "second": {
+      "source": { "kind": "repo", "ref": "repo://placeholder/second" }
+    }
+  }
+}"#,
+        )
+        .expect("write sidecar");
+    }
+
+    #[test]
+    fn wizard_delete_step_removes_selected_node() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        write_two_step_flow(&flow_path);
+        let mut input = Cursor::new("first\n");
+        let mut output = Vec::new();
+        super::wizard_delete_step_with_io(&flow_path, &mut input, &mut output)
+            .expect("delete step");
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        assert!(!flow_ir.nodes.contains_key("first"));
+        assert!(flow_ir.nodes.contains_key("second"));
+    }
+
+    #[test]
+    fn wizard_menu_delete_step_path_removes_node() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        write_two_step_flow(&flow_path);
+        // 2 flow list, 1 select flow, 5 delete-step, first, 7 save, then 0(back) 0(main) 0(exit)
+        let input = Cursor::new("2\n1\n5\nfirst\n7\n\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard menu delete step");
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        assert!(!flow_ir.nodes.contains_key("first"));
+        assert!(flow_ir.nodes.contains_key("second"));
+    }
+
+    #[test]
+    fn wizard_menu_list_steps_shows_current_nodes() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        write_two_step_flow(&flow_path);
+        // 2 flow list, 1 select flow, 2 list-steps, then 0(back) 0(main) 0(exit)
+        let input = Cursor::new("2\n1\n2\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+            .expect("wizard menu list steps");
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.contains("Current steps"));
+        assert!(rendered.contains("1. first"));
+        assert!(rendered.contains("2. second"));
+    }
+
+    #[test]
+    fn wizard_update_step_prompt_lists_current_nodes() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        write_two_step_flow(&flow_path);
+        let mut input = Cursor::new("cancel\n");
+        let mut output = Vec::new();
+        super::wizard_update_step_with_io(dir.path(), &flow_path, &mut input, &mut output)
+            .expect("wizard update step");
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.contains("1. first"));
+        assert!(rendered.contains("2. second"));
+    }
+
+    #[test]
+    fn wizard_save_empty_flow_reports_clear_error() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        fs::create_dir_all(flow_path.parent().expect("parent")).expect("mkdirs");
+        fs::write(
+            &flow_path,
+            r#"id: welcome
+type: messaging
+start: first
+parameters: {}
+tags: []
+schema_version: 2
+entrypoints: {}
+nodes:
+  first:
+    op: {}
+    routing:
+    - out: true
+"#,
+        )
+        .expect("write single-step flow");
+        let sidecar_path = super::sidecar_path_for_flow(&flow_path);
+        fs::write(
+            sidecar_path,
+            r#"{
+  "schema_version": 1,
+  "flow": "welcome.ygtc",
+  "nodes": {
+    "first": {
+      "source": { "kind": "repo", "ref": "repo://placeholder/first" }
+    }
+  }
+}"#,
+        )
+        .expect("write sidecar");
+
+        // 2 flow list, 1 select flow, 5 delete-step, first, 7 save (fails), then back/main/exit
+        let input = Cursor::new("2\n1\n5\nfirst\n7\n0\n0\n0\n");
+        let mut output = Vec::new();
+        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard run");
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.contains("Save blocked: a flow must contain at least one step."));
+    }
+
+    #[test]
+    fn doctor_lints_raw_summary_literals() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: Some("Raw Name".to_string()),
+                description: Some("Raw Description".to_string()),
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+        let errors = super::lint_i18n_tag_fields(&flow_path);
+        assert!(!errors.is_empty(), "expected i18n tag lint errors");
+    }
+
+    fn with_wizard_resolver_env<F: FnOnce()>(resolver: String, run: F) {
+        let _guard = env_test_lock();
+        let previous = env::var("GREENTIC_FLOW_WIZARD_RESOLVER").ok();
+        unsafe {
+            env::set_var("GREENTIC_FLOW_WIZARD_RESOLVER", resolver);
+        }
+        run();
+        if let Some(value) = previous {
+            unsafe {
+                env::set_var("GREENTIC_FLOW_WIZARD_RESOLVER", value);
+            }
+        } else {
+            unsafe {
+                env::remove_var("GREENTIC_FLOW_WIZARD_RESOLVER");
+            }
+        }
+    }
+
+    #[test]
+    fn wizard_menu_add_step_remote_fixture() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+
+        with_wizard_resolver_env(fixture_registry_resolver(), || {
+            // 2 flow list, 1 flow, 3 add step, 2 remote, ref, 2 no-pin, 1 default mode, then back/main/exit.
+            // No anchor prompt is shown when the flow has no steps.
+            let input = Cursor::new("2\n1\n3\n2\noci://acme/widget:1\n2\n1\n0\n0\n0\n");
+            let mut output = Vec::new();
+            super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+                .expect("wizard menu add step");
+            let rendered = String::from_utf8(output).expect("utf8");
+            assert!(rendered.contains("Step added."));
+        });
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        assert!(
+            flow_ir.nodes.is_empty(),
+            "changes should not persist without save"
+        );
+    }
+
+    #[test]
+    fn wizard_add_step_anchor_no_nodes_defaults_to_auto_without_prompt() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        let mut input = Cursor::new("");
+        let mut output = Vec::new();
+        let selected =
+            super::wizard_select_add_step_anchor_with_io(&flow_ir, &mut input, &mut output)
+                .expect("select anchor");
+        assert_eq!(selected, None);
+        assert!(output.is_empty(), "no anchor prompt should be rendered");
+    }
+
+    #[test]
+    fn wizard_add_step_anchor_multiple_nodes_uses_numbered_selection() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        write_two_step_flow(&flow_path);
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        let mut input = Cursor::new("3\n");
+        let mut output = Vec::new();
+        let selected =
+            super::wizard_select_add_step_anchor_with_io(&flow_ir, &mut input, &mut output)
+                .expect("select anchor");
+        assert_eq!(selected.as_deref(), Some("second"));
+        let rendered = String::from_utf8(output).expect("utf8");
+        assert!(rendered.contains("1. Auto"));
+        assert!(rendered.contains("2. first"));
+        assert!(rendered.contains("3. second"));
+    }
+
+    #[test]
+    fn wizard_menu_update_step_remote_fixture() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+        let resolver = fixture_registry_resolver();
+        handle_add_step(
+            AddStepArgs {
+                component_id: None,
+                flow_path: flow_path.clone(),
+                after: None,
+                before: None,
+                mode: AddStepMode::Default,
+                pack_alias: None,
+                wizard_mode: Some(WizardModeArg::Default),
+                operation: None,
+                payload: "{}".to_string(),
+                routing_out: true,
+                routing_reply: false,
+                routing_next: None,
+                routing_multi_to: None,
+                routing_json: None,
+                routing_to_anchor: false,
+                config_flow: None,
+                answers: None,
+                answers_file: None,
+                preset: None,
+                answers_dir: None,
+                overwrite_answers: false,
+                reask: false,
+                locale: None,
+                interactive: false,
+                allow_cycles: false,
+                dry_run: false,
+                write: false,
+                validate_only: false,
+                manifests: Vec::new(),
+                node_id: Some("widget".to_string()),
+                defaults_from: None,
+                component_ref: Some("oci://acme/widget:1".to_string()),
+                local_wasm: None,
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: Some(resolver),
+                pin: false,
+                allow_contract_change: false,
+            },
+            SchemaMode::Strict,
+            OutputFormat::Human,
+            false,
+        )
+        .expect("add step");
+
+        with_wizard_resolver_env(fixture_registry_resolver(), || {
+            // 2 flow list, 1 flow, 4 update step, 1 step, 2 remote, ref, 1 default mode, 7 save, then back/main/exit.
+            let input = Cursor::new("2\n1\n4\n1\n2\nrepo://acme/widget:1\n1\n7\n\n0\n0\n0\n");
+            let mut output = Vec::new();
+            super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+                .expect("wizard menu update step");
+            let _rendered = String::from_utf8(output).expect("utf8");
+        });
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        assert!(flow_ir.nodes.contains_key("widget"));
+    }
+
+    #[test]
+    fn wizard_menu_update_step_twice_overwrites_answers_artifact() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "welcome".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("seed flow");
+        let resolver = fixture_registry_resolver();
+        handle_add_step(
+            AddStepArgs {
+                component_id: None,
+                flow_path: flow_path.clone(),
+                after: None,
+                before: None,
+                mode: AddStepMode::Default,
+                pack_alias: None,
+                wizard_mode: Some(WizardModeArg::Default),
+                operation: None,
+                payload: "{}".to_string(),
+                routing_out: true,
+                routing_reply: false,
+                routing_next: None,
+                routing_multi_to: None,
+                routing_json: None,
+                routing_to_anchor: false,
+                config_flow: None,
+                answers: None,
+                answers_file: None,
+                preset: None,
+                answers_dir: None,
+                overwrite_answers: false,
+                reask: false,
+                locale: None,
+                interactive: false,
+                allow_cycles: false,
+                dry_run: false,
+                write: false,
+                validate_only: false,
+                manifests: Vec::new(),
+                node_id: Some("widget".to_string()),
+                defaults_from: None,
+                component_ref: Some("oci://acme/widget:1".to_string()),
+                local_wasm: None,
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: Some(resolver),
+                pin: false,
+                allow_contract_change: false,
+            },
+            SchemaMode::Strict,
+            OutputFormat::Human,
+            false,
+        )
+        .expect("add step");
+
+        with_wizard_resolver_env(fixture_registry_resolver(), || {
+            // Update same step twice in default mode, then save.
+            let input = Cursor::new(
+                "2\n1\n4\n1\n2\nrepo://acme/widget:1\n1\n4\n1\n2\nrepo://acme/widget:1\n1\n7\n\n0\n0\n0\n",
+            );
+            let mut output = Vec::new();
+            super::run_wizard_menu_with_io(dir.path(), input, &mut output)
+                .expect("wizard menu update step twice");
+            let rendered = String::from_utf8(output).expect("utf8");
+            assert!(
+                !rendered.contains("answers already exist"),
+                "update-step should overwrite existing answers artifacts"
+            );
+        });
+    }
+
+    #[test]
+    fn wizard_local_wasm_copy_places_file_under_pack_components() {
+        let dir = tempdir().expect("temp dir");
+        let external = dir.path().join("external-widget.wasm");
+        fs::write(&external, b"\0asm....").expect("write wasm");
+        let copied =
+            super::copy_local_wasm_into_pack_components(dir.path(), &external).expect("copy wasm");
+        assert!(copied.starts_with(dir.path().join("components")));
+        assert!(copied.exists(), "copied wasm should exist");
+        let src = fs::read(&external).expect("read source");
+        let dst = fs::read(&copied).expect("read copied");
+        assert_eq!(src, dst);
+    }
+
+    #[test]
+    fn resolves_default_config_flow_from_manifest() {
+        let manifest = json!({
+            "id": "ai.greentic.hello",
+            "dev_flows": {
+                "default": {
+                    "graph": {
+                        "id": "cfg",
+                        "type": "component-config",
+                        "nodes": {}
+                    }
+                }
+            }
+        });
+        let manifest_file = NamedTempFile::new().expect("temp file");
+        std::fs::write(manifest_file.path(), manifest.to_string()).expect("write manifest");
+
+        let (yaml, schema_path) =
+            resolve_config_flow(None, &[manifest_file.path().to_path_buf()], "default")
+                .expect("resolve");
+        assert!(yaml.contains("id: cfg"));
+        assert!(
+            schema_path.starts_with(env::temp_dir()),
+            "expected schema path {schema_path:?} under the temp directory"
+        );
+    }
+
+    #[test]
+    fn config_flow_schema_resides_in_temp_dir() {
+        let manifest = json!({
+            "id": "ai.greentic.custom",
+            "dev_flows": {
+                "custom": {
+                    "graph": {
+                        "id": "cfg",
+                        "type": "component-config",
+                        "nodes": {}
+                    }
+                }
+            }
+        });
+        let manifest_file = NamedTempFile::new().expect("temp file");
+        fs::write(manifest_file.path(), manifest.to_string()).expect("write manifest");
+
+        let (_, schema_path) =
+            resolve_config_flow(None, &[manifest_file.path().to_path_buf()], "custom")
+                .expect("resolve");
+        assert!(
+            schema_path.starts_with(env::temp_dir()),
+            "expected schema path {schema_path:?} to live in temp dir"
+        );
+    }
+
+    #[test]
+    fn answers_merge_prefers_cli_over_file() {
+        let file = NamedTempFile::new().expect("temp file");
+        std::fs::write(file.path(), r#"{"value":"from-file","keep":1}"#).unwrap();
+        let merged = parse_answers_map(Some(r#"{"value":"from-cli"}"#), Some(file.path())).unwrap();
+        assert_eq!(
+            merged.get("value").and_then(|v| v.as_str()),
+            Some("from-cli")
+        );
+        assert_eq!(merged.get("keep").and_then(|v| v.as_i64()), Some(1));
+    }
+
+    #[test]
+    fn answers_map_accepts_yaml() {
+        let merged = parse_answers_map(Some("value: hello\ncount: 2"), None).unwrap();
+        assert_eq!(merged.get("value").and_then(|v| v.as_str()), Some("hello"));
+        assert_eq!(merged.get("count").and_then(|v| v.as_i64()), Some(2));
+    }
+
+    fn fixture_registry_resolver() -> String {
+        let registry = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("registry");
+        format!("fixture://{}", registry.display())
+    }
+
+    #[test]
+    fn fixture_registry_resolves_wizard() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flow.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "main".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("create flow");
+
+        let resolver = fixture_registry_resolver();
+
+        let args = AddStepArgs {
+            component_id: None,
+            flow_path: flow_path.clone(),
+            after: None,
+            before: None,
+            mode: AddStepMode::Default,
+            pack_alias: None,
+            wizard_mode: Some(WizardModeArg::Default),
+            operation: None,
+            payload: "{}".to_string(),
+            routing_out: true,
+            routing_reply: false,
+            routing_next: None,
+            routing_multi_to: None,
+            routing_json: None,
+            routing_to_anchor: false,
+            config_flow: None,
+            answers: None,
+            answers_file: None,
+            preset: None,
+            answers_dir: None,
+            overwrite_answers: false,
+            reask: false,
+            locale: None,
+            interactive: false,
+            allow_cycles: false,
+            dry_run: false,
+            write: false,
+            validate_only: false,
+            manifests: Vec::new(),
+            node_id: Some("widget".to_string()),
+            defaults_from: None,
+            component_ref: Some("oci://acme/widget:1".to_string()),
+            local_wasm: None,
+            distributor_url: None,
+            auth_token: None,
+            tenant: None,
+            env: None,
+            pack: None,
+            component_version: None,
+            abi_version: None,
+            resolver: Some(resolver),
+            pin: false,
+            allow_contract_change: false,
+        };
+        handle_add_step(args, SchemaMode::Strict, OutputFormat::Human, false).expect("add step");
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        let node = flow_ir.nodes.get("widget").expect("node exists");
+        assert_eq!(node.operation, "run");
+        let config = extract_config_payload(&node.payload);
+        assert_eq!(config.get("foo").and_then(|v| v.as_str()), Some("bar"));
+    }
+
+    #[test]
+    fn wizard_mode_upgrade_alias_maps_to_update() {
+        assert_eq!(
+            WizardModeArg::UpgradeDeprecated.to_mode(),
+            WizardMode::Update
+        );
+    }
+
+    #[test]
+    fn fixture_registry_update_and_remove() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flow.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "main".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("create flow");
+
+        let resolver = fixture_registry_resolver();
+        handle_add_step(
+            AddStepArgs {
+                component_id: None,
+                flow_path: flow_path.clone(),
+                after: None,
+                before: None,
+                mode: AddStepMode::Default,
+                pack_alias: None,
+                wizard_mode: Some(WizardModeArg::Default),
+                operation: None,
+                payload: "{}".to_string(),
+                routing_out: true,
+                routing_reply: false,
+                routing_next: None,
+                routing_multi_to: None,
+                routing_json: None,
+                routing_to_anchor: false,
+                config_flow: None,
+                answers: None,
+                answers_file: None,
+                preset: None,
+                answers_dir: None,
+                overwrite_answers: false,
+                reask: false,
+                locale: None,
+                interactive: false,
+                allow_cycles: false,
+                dry_run: false,
+                write: false,
+                validate_only: false,
+                manifests: Vec::new(),
+                node_id: Some("widget".to_string()),
+                defaults_from: None,
+                component_ref: Some("oci://acme/widget:1".to_string()),
+                local_wasm: None,
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: Some(resolver.clone()),
+                pin: false,
+                allow_contract_change: false,
+            },
+            SchemaMode::Strict,
+            OutputFormat::Human,
+            false,
+        )
+        .expect("add step");
+
+        handle_update_step(
+            UpdateStepArgs {
+                component_id: None,
+                flow_path: flow_path.clone(),
+                step: Some("widget".to_string()),
+                mode: "default".to_string(),
+                wizard_mode: Some(WizardModeArg::Update),
+                operation: None,
+                routing_out: false,
+                routing_reply: false,
+                routing_next: None,
+                routing_multi_to: None,
+                routing_json: None,
+                answers: None,
+                answers_file: None,
+                preset: None,
+                answers_dir: None,
+                overwrite_answers: false,
+                reask: false,
+                locale: None,
+                non_interactive: true,
+                changed_only: false,
+                interactive: false,
+                component: Some("oci://acme/widget:1".to_string()),
+                local_wasm: None,
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: Some(resolver.clone()),
+                dry_run: false,
+                write: false,
+                allow_contract_change: false,
+                rename: None,
+            },
+            SchemaMode::Strict,
+            OutputFormat::Human,
+            false,
+        )
+        .expect("update step");
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        let node = flow_ir.nodes.get("widget").expect("node exists");
+        let config = extract_config_payload(&node.payload);
+        assert_eq!(config.get("foo").and_then(|v| v.as_str()), Some("updated"));
+
+        let remove_err = handle_delete_step(
+            DeleteStepArgs {
+                component_id: None,
+                flow_path: flow_path.clone(),
+                step: vec!["widget".to_string()],
+                wizard_mode: Some(WizardModeArg::Remove),
+                answers: None,
+                answers_file: None,
+                answers_dir: None,
+                overwrite_answers: false,
+                reask: false,
+                locale: None,
+                interactive: false,
+                component: Some("oci://acme/widget:1".to_string()),
+                local_wasm: None,
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: Some(resolver),
+                strategy: "splice".to_string(),
+                multi_pred: None,
+                assume_yes: true,
+                run_remove: false,
+                skip_remove: false,
+                write: true,
+                dry_run: false,
+                cascade: false,
+            },
+            OutputFormat::Human,
+            false,
+        )
+        .expect_err("remove mode should require explicit confirmation");
+        assert!(
+            remove_err.to_string().contains("Type REMOVE to confirm"),
+            "unexpected remove confirmation error: {remove_err}"
+        );
+
+        handle_delete_step(
+            DeleteStepArgs {
+                component_id: None,
+                flow_path: flow_path.clone(),
+                step: vec!["widget".to_string()],
+                wizard_mode: None,
+                answers: None,
+                answers_file: None,
+                answers_dir: None,
+                overwrite_answers: false,
+                reask: false,
+                locale: None,
+                interactive: false,
+                component: None,
+                local_wasm: None,
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: None,
+                strategy: "splice".to_string(),
+                multi_pred: None,
+                assume_yes: true,
+                run_remove: false,
+                skip_remove: false,
+                write: true,
+                dry_run: false,
+                cascade: false,
+            },
+            OutputFormat::Human,
+            false,
+        )
+        .expect("delete step");
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        assert!(flow_ir.nodes.is_empty());
+    }
+
+    #[test]
+    fn update_step_blocks_contract_drift() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flow.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "main".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("create flow");
+
+        let resolver = fixture_registry_resolver();
+        handle_add_step(
+            AddStepArgs {
+                component_id: None,
+                flow_path: flow_path.clone(),
+                after: None,
+                before: None,
+                mode: AddStepMode::Default,
+                pack_alias: None,
+                wizard_mode: Some(WizardModeArg::Default),
+                operation: None,
+                payload: "{}".to_string(),
+                routing_out: true,
+                routing_reply: false,
+                routing_next: None,
+                routing_multi_to: None,
+                routing_json: None,
+                routing_to_anchor: false,
+                config_flow: None,
+                answers: None,
+                answers_file: None,
+                preset: None,
+                answers_dir: None,
+                overwrite_answers: false,
+                reask: false,
+                locale: None,
+                interactive: false,
+                allow_cycles: false,
+                dry_run: false,
+                write: false,
+                validate_only: false,
+                manifests: Vec::new(),
+                node_id: Some("widget".to_string()),
+                defaults_from: None,
+                component_ref: Some("oci://acme/widget:1".to_string()),
+                local_wasm: None,
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: Some(resolver.clone()),
+                pin: false,
+                allow_contract_change: false,
+            },
+            SchemaMode::Strict,
+            OutputFormat::Human,
+            false,
+        )
+        .expect("add step");
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let mut flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        if let Some(meta) = flow_ir.meta.as_mut()
+            && let Some(root) = meta.as_object_mut()
+            && let Some(greentic) = root.get_mut("greentic").and_then(Value::as_object_mut)
+            && let Some(components) = greentic
+                .get_mut("components")
+                .and_then(Value::as_object_mut)
+            && let Some(entry) = components.get_mut("widget").and_then(Value::as_object_mut)
+        {
+            entry.insert(
+                "describe_hash".to_string(),
+                Value::String("deadbeef".to_string()),
+            );
+        }
+        let doc_out = flow_ir.to_doc().expect("to doc");
+        let yaml = serialize_doc(&doc_out).expect("serialize doc");
+        fs::write(&flow_path, yaml).expect("write flow");
+
+        let result = handle_update_step(
+            UpdateStepArgs {
+                component_id: None,
+                flow_path: flow_path.clone(),
+                step: Some("widget".to_string()),
+                mode: "default".to_string(),
+                wizard_mode: Some(WizardModeArg::Update),
+                operation: None,
+                routing_out: false,
+                routing_reply: false,
+                routing_next: None,
+                routing_multi_to: None,
+                routing_json: None,
+                answers: None,
+                answers_file: None,
+                preset: None,
+                answers_dir: None,
+                overwrite_answers: false,
+                reask: false,
+                locale: None,
+                non_interactive: true,
+                changed_only: false,
+                interactive: false,
+                component: Some("oci://acme/widget:1".to_string()),
+                local_wasm: None,
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: Some(resolver),
+                dry_run: false,
+                write: false,
+                allow_contract_change: false,
+                rename: None,
+            },
+            SchemaMode::Strict,
+            OutputFormat::Human,
+            false,
+        );
+        assert!(
+            result.is_ok(),
+            "canonical setup path without describe_cbor should skip hash drift checks"
+        );
+    }
+
+    #[test]
+    fn add_step_dry_run_does_not_write() {
+        let dir = tempdir().expect("temp dir");
+        let flow_path = dir.path().join("flow.ygtc");
+        handle_new(
+            NewArgs {
+                flow_path: flow_path.clone(),
+                flow_id: "main".to_string(),
+                flow_type: "messaging".to_string(),
+                schema_version: 2,
+                name: None,
+                description: None,
+                force: true,
+            },
+            false,
+        )
+        .expect("create flow");
+
+        let resolver = fixture_registry_resolver();
+        handle_add_step(
+            AddStepArgs {
+                component_id: None,
+                flow_path: flow_path.clone(),
+                after: None,
+                before: None,
+                mode: AddStepMode::Default,
+                pack_alias: None,
+                wizard_mode: Some(WizardModeArg::Default),
+                operation: None,
+                payload: "{}".to_string(),
+                routing_out: true,
+                routing_reply: false,
+                routing_next: None,
+                routing_multi_to: None,
+                routing_json: None,
+                routing_to_anchor: false,
+                config_flow: None,
+                answers: None,
+                answers_file: None,
+                preset: None,
+                answers_dir: None,
+                overwrite_answers: false,
+                reask: false,
+                locale: None,
+                interactive: false,
+                allow_cycles: false,
+                dry_run: true,
+                write: false,
+                validate_only: false,
+                manifests: Vec::new(),
+                node_id: Some("widget".to_string()),
+                defaults_from: None,
+                component_ref: Some("oci://acme/widget:1".to_string()),
+                local_wasm: None,
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: Some(resolver),
+                pin: false,
+                allow_contract_change: false,
+            },
+            SchemaMode::Strict,
+            OutputFormat::Human,
+            false,
+        )
+        .expect("dry run");
+
+        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
+        assert!(flow_ir.nodes.is_empty(), "dry-run should not write flow");
+    }
+}
+fn backup_path(path: &Path) -> PathBuf {
+    let mut os = path.as_os_str().to_os_string();
+    os.push(".bak");
+    PathBuf::from(os)
+}
+
+fn write_flow_file(path: &Path, content: &str, force: bool, backup: bool) -> Result<()> {
+    if path.exists() && !force {
+        anyhow::bail!(
+            "refusing to overwrite existing file {}; pass --force to replace it",
+            path.display()
+        );
+    }
+
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create parent directory {}", parent.display()))?;
+    }
+
+    if backup && path.exists() {
+        let bak = backup_path(path);
+        fs::copy(path, &bak)
+            .with_context(|| format!("failed to write backup {}", bak.display()))?;
+    }
+    cancel::replace_file_atomically(path, content.as_bytes())?;
+    refresh_workspace_index(path);
+    Ok(())
+}
+
+/// Keep the workspace index, if the flow's workspace has one, in step with a write to
+/// `flow_path` or its sidecar. A stale index is only a slower query, so failures just warn.
+fn refresh_workspace_index(flow_path: &Path) {
+    if flow_path.extension() != Some(OsStr::new("ygtc")) {
+        return;
+    }
+    if let Err(err) = update_indexed_flow(flow_path) {
+        eprintln!(
+            "warning: workspace index not updated for {}: {err:#}",
+            flow_path.display()
+        );
+    }
+}
+
+fn resolve_config_flow(
+    config_flow_arg: Option<PathBuf>,
+    manifests: &[PathBuf],
+    flow_name: &str,
+) -> Result<(String, PathBuf)> {
+    if let Some(path) = config_flow_arg {
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("read config flow {}", path.display()))?;
+        return Ok((text, path));
+    }
+
+    let manifest_path = manifests.first().ok_or_else(|| {
+        anyhow::anyhow!(
+            "config mode requires --config-flow or a component manifest with dev_flows.{}",
+            flow_name
+        )
+    })?;
+    resolve_config_flow_from_manifest(manifest_path, flow_name)
+}
+
+fn resolve_config_flow_from_manifest(
+    manifest_path: &Path,
+    flow_name: &str,
+) -> Result<(String, PathBuf)> {
+    let manifest_text = fs::read_to_string(manifest_path)
+        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
+    let manifest_json: serde_json::Value =
+        serde_json::from_str(&manifest_text).context("parse manifest JSON")?;
+    let default_graph = manifest_json
+        .get("dev_flows")
+        .and_then(|v| v.get(flow_name))
+        .and_then(|v| v.get("graph"))
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("manifest missing dev_flows.{}.graph", flow_name))?;
+    let mut graph = default_graph;
+    if let Some(obj) = graph.as_object_mut()
+        && !obj.contains_key("type")
+    {
+        obj.insert(
+            "type".to_string(),
+            serde_json::Value::String("component-config".to_string()),
+        );
+    }
+    let yaml =
+        serde_yaml_bw::to_string(&graph).context("render dev_flows.default.graph to YAML")?;
+    let schema_path =
+        ensure_config_schema_path().context("prepare embedded flow schema for config flows")?;
+    Ok((yaml, schema_path))
+}
+
+fn load_manifest_json(path: &Path) -> Result<serde_json::Value> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("read manifest {}", path.display()))?;
+    serde_json::from_str(&text).context("parse manifest JSON")
+}
+
+fn resolve_manifest_path_for_component(component: &str) -> Result<PathBuf> {
+    if component.starts_with("oci://")
+        || component.starts_with("repo://")
+        || component.starts_with("store://")
+    {
+        validate_component_ref(component)?;
+        let source = classify_remote_source(component, None);
+        return resolve_component_manifest_path(&source, Path::new("."));
+    }
+
+    let raw = component.strip_prefix("file://").unwrap_or(component);
+    let path = PathBuf::from(raw);
+    if !path.exists() {
+        anyhow::bail!("component path {} not found", path.display());
+    }
+    if path.is_dir() {
+        let manifest_path = path.join("component.manifest.json");
+        if !manifest_path.exists() {
+            anyhow::bail!(
+                "component.manifest.json not found at {}",
+                manifest_path.display()
             );
         }
+        return Ok(manifest_path);
+    }
+    if path.is_file() {
+        return Ok(path);
+    }
+    anyhow::bail!(
+        "component path {} is not a file or directory",
+        path.display()
+    )
+}
+
+fn questions_for_operation(
+    manifest: &serde_json::Value,
+    operation: &str,
+) -> Result<(Vec<Question>, Option<String>)> {
+    if let Some(graph) = dev_flow_graph_from_manifest(manifest, operation)? {
+        let questions = extract_questions_from_flow(&graph)?;
+        return Ok((questions, Some(operation.to_string())));
+    }
+    if let Some(graph) = dev_flow_graph_from_manifest(manifest, "default")? {
+        let questions = extract_questions_from_flow(&graph)?;
+        return Ok((questions, Some("default".to_string())));
+    }
+    Ok((Vec::new(), None))
+}
+
+fn dev_flow_graph_from_manifest(
+    manifest: &serde_json::Value,
+    flow_name: &str,
+) -> Result<Option<serde_json::Value>> {
+    let Some(graph) = manifest
+        .get("dev_flows")
+        .and_then(|v| v.get(flow_name))
+        .and_then(|v| v.get("graph"))
+        .cloned()
+    else {
+        return Ok(None);
+    };
+    Ok(Some(graph))
+}
+
+/// Questions of a manifest dev flow, with `{{params.*}}` defaults resolved against `parameters`.
+fn questions_from_manifest(
+    manifest_path: &Path,
+    flow_name: &str,
+    parameters: &serde_json::Value,
+) -> Result<Vec<Question>> {
+    let manifest = load_manifest_json(manifest_path)?;
+    let Some(mut graph) = dev_flow_graph_from_manifest(&manifest, flow_name)? else {
+        return Ok(Vec::new());
+    };
+    resolve_param_defaults(&mut graph, parameters)?;
+    extract_questions_from_flow(&graph)
+}
+
+fn questions_from_config_flow_text(text: &str) -> Result<Vec<Question>> {
+    let flow_value: serde_json::Value =
+        serde_yaml_bw::from_str(text).context("parse config flow as YAML")?;
+    extract_questions_from_flow(&flow_value)
+}
+
+fn validate_example_against_schema(
+    schema: &serde_json::Value,
+    example: &serde_json::Value,
+) -> Result<()> {
+    let compiled = jsonschema::options()
+        .with_draft(Draft::Draft202012)
+        .build(schema)
+        .context("compile answers schema")?;
+    if let Err(error) = compiled.validate(example) {
+        let messages = error.to_string();
+        anyhow::bail!("generated example does not validate against schema: {messages}");
+    }
+    Ok(())
+}
+
+fn write_json_file(path: &Path, value: &serde_json::Value) -> Result<()> {
+    let mut text = serde_json::to_string_pretty(value).context("serialize json")?;
+    text.push('\n');
+    fs::write(path, text).with_context(|| format!("write {}", path.display()))
+}
+
+fn print_json_payload(value: &serde_json::Value) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, value).context("write json")?;
+    writeln!(stdout).context("write newline")?;
+    Ok(())
+}
+
+fn answers_to_json_map(answers: QuestionAnswers) -> serde_json::Map<String, serde_json::Value> {
+    answers.into_iter().collect()
+}
+
+fn answers_to_value(answers: &QuestionAnswers) -> Option<serde_json::Value> {
+    if answers.is_empty() {
+        None
+    } else {
+        Some(serde_json::Value::Object(
+            answers.clone().into_iter().collect(),
+        ))
     }
+}
 
-    #[test]
-    fn add_flow_path_global_messaging() {
-        let path =
-            super::build_add_flow_relative_path("global", None, None, None, "messaging", "welcome")
-                .expect("global path");
-        assert_eq!(path, PathBuf::from("flows/global/messaging/welcome.ygtc"));
-    }
+fn wizard_header(component: &str, mode: &str) -> String {
+    format!("== {component} ({mode}) ==")
+}
 
-    #[test]
-    fn add_flow_path_tenant_all_teams_events() {
-        let path = super::build_add_flow_relative_path(
-            "tenant",
-            Some("tenant-a"),
-            Some("all-teams"),
-            None,
-            "events",
-            "audit",
-        )
-        .expect("tenant all-teams path");
-        assert_eq!(
-            path,
-            PathBuf::from("flows/tenant-a/all-teams/events/audit.ygtc")
+fn warn_deprecated_wizard_mode(mode: WizardModeArg) -> Option<serde_json::Value> {
+    if matches!(mode, WizardModeArg::UpgradeDeprecated) {
+        eprintln!(
+            "warning: wizard mode 'upgrade' is deprecated; use 'update' (will be removed in a future release)"
         );
+        return Some(json!({
+            "kind": "deprecation",
+            "field": "mode",
+            "old": "upgrade",
+            "new": "update",
+            "message": "wizard mode 'upgrade' is deprecated; use 'update' (will be removed in a future release)"
+        }));
     }
+    None
+}
 
-    #[test]
-    fn add_flow_path_tenant_specific_team() {
-        let path = super::build_add_flow_relative_path(
-            "tenant",
-            Some("tenant-a"),
-            Some("specific-team"),
-            Some("blue"),
-            "messaging",
-            "alerts",
-        )
-        .expect("tenant team path");
-        assert_eq!(
-            path,
-            PathBuf::from("flows/tenant-a/blue/messaging/alerts.ygtc")
+fn print_json_payload_with_optional_diagnostic(
+    mut payload: serde_json::Value,
+    diagnostic: Option<&serde_json::Value>,
+) -> Result<()> {
+    if let Some(diag) = diagnostic
+        && let Some(object) = payload.as_object_mut()
+    {
+        object.insert(
+            "diagnostics".to_string(),
+            serde_json::Value::Array(vec![diag.clone()]),
         );
     }
+    print_json_payload(&payload)
+}
 
-    #[test]
-    fn add_flow_path_rejects_unknown_type() {
-        let err = super::build_add_flow_relative_path("global", None, None, None, "http", "x")
-            .expect_err("invalid type");
-        assert!(err.to_string().to_lowercase().contains("flow type"));
+fn normalize_capability_group(raw: &str) -> String {
+    let normalized = raw.trim().to_ascii_lowercase();
+    if normalized.is_empty() {
+        return normalized;
     }
-
-    #[test]
-    fn wizard_add_flow_menu_creates_global_flow() {
-        let dir = tempdir().expect("temp dir");
-        // scope=1(global), type=1(messaging), name=welcome
-        let mut input = Cursor::new("1\n1\nwelcome\n");
-        let mut output = Vec::new();
-        let mut answers_log = serde_json::Map::new();
-        super::wizard_add_flow_with_io(dir.path(), &mut input, &mut output, &mut answers_log)
-            .expect("wizard add flow");
-        let path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        assert!(path.exists(), "expected flow file {}", path.display());
+    if let Some(rest) = normalized.strip_prefix("wasi.") {
+        let head = rest.split(['.', ':', '/']).next().unwrap_or(rest);
+        return format!("wasi:{head}");
     }
-
-    #[test]
-    fn wizard_flow_summary_menu_updates_title_and_description() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        write_two_step_flow(&flow_path);
-        handle_update(
-            UpdateArgs {
-                flow_path: flow_path.clone(),
-                flow_id: None,
-                flow_type: None,
-                schema_version: None,
-                name: Some("Old Name".to_string()),
-                description: Some("Old Description".to_string()),
-                tags: None,
-            },
-            false,
-        )
-        .expect("seed metadata");
-
-        // 2 flow list, 1 select flow, 1 summary, provide new name/desc, 7 save, then back/back/exit
-        let input = Cursor::new("2\n1\n1\n2\nNew Name\nNew Description\n7\n\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard summary update");
-        let output_text = String::from_utf8_lossy(&output);
-        assert!(output_text.contains("Current name/title: Old Name"));
-        assert!(output_text.contains("Current description: Old Description"));
-
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        assert_eq!(doc.title.as_deref(), Some("i18n:flow.main.title"));
-        assert_eq!(
-            doc.description.as_deref(),
-            Some("i18n:flow.main.description")
-        );
-        let i18n_map: serde_json::Value = serde_json::from_str(
-            &fs::read_to_string(dir.path().join("i18n/en-GB.json")).expect("read i18n"),
-        )
-        .expect("parse i18n");
-        assert_eq!(
-            i18n_map.get("flow.main.title").and_then(|v| v.as_str()),
-            Some("New Name")
-        );
-        assert_eq!(
-            i18n_map
-                .get("flow.main.description")
-                .and_then(|v| v.as_str()),
-            Some("New Description")
-        );
+    if let Some(rest) = normalized.strip_prefix("host.") {
+        let head = rest.split(['.', ':', '/']).next().unwrap_or(rest);
+        return format!("host:{head}");
     }
-
-    #[test]
-    fn wizard_flow_summary_shows_resolved_i18n_values() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/main.ygtc");
-        if let Some(parent) = flow_path.parent() {
-            fs::create_dir_all(parent).expect("create parent");
-        }
-        fs::write(
-            &flow_path,
-            r#"id: main
-type: messaging
-schema_version: 2
-title: i18n:flow.misc.title
-description: i18n:flow.misc.description
-nodes: {}
-"#,
-        )
-        .expect("write flow");
-        fs::create_dir_all(dir.path().join("i18n")).expect("create i18n dir");
-        fs::write(
-            dir.path().join("i18n/en-GB.json"),
-            r#"{
-  "flow.misc.title": "Best flow title",
-  "flow.misc.description": "The best flow ever"
-}"#,
-        )
-        .expect("write i18n");
-
-        let mut input = Cursor::new("1\n");
-        let mut output = Vec::new();
-        let mut answers_log = serde_json::Map::new();
-        super::wizard_edit_flow_summary_with_io(
-            &flow_path,
-            &mut input,
-            &mut output,
-            &mut answers_log,
-        )
-        .expect("summary view");
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.contains("Best flow title"));
-        assert!(rendered.contains("The best flow ever"));
-        assert!(!rendered.contains("i18n:flow.misc.title"));
-        assert!(!rendered.contains("i18n:flow.misc.description"));
+    if normalized.contains(':') {
+        return normalized;
     }
+    if let Some((left, right)) = normalized.split_once('.') {
+        let head = right.split(['.', ':', '/']).next().unwrap_or(right);
+        return format!("{left}:{head}");
+    }
+    normalized
+}
 
-    #[test]
-    fn wizard_changes_without_save_are_discarded() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: Some("Old Name".to_string()),
-                description: Some("Old Description".to_string()),
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
-
-        // 2 flow list, 1 select flow, 1 summary edit, change fields, then back to main and exit without save.
-        let input = Cursor::new("2\n1\n1\n2\nDraft Name\nDraft Description\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard summary update without save");
-
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        assert_eq!(doc.title.as_deref(), Some("Old Name"));
-        assert_eq!(doc.description.as_deref(), Some("Old Description"));
+fn grouped_capabilities(caps: &[String]) -> Vec<String> {
+    let mut groups = BTreeSet::new();
+    for cap in caps {
+        groups.insert(normalize_capability_group(cap));
     }
+    groups.into_iter().collect()
+}
 
-    #[test]
-    fn wizard_save_persists_changes() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        write_two_step_flow(&flow_path);
-        handle_update(
-            UpdateArgs {
-                flow_path: flow_path.clone(),
-                flow_id: None,
-                flow_type: None,
-                schema_version: None,
-                name: Some("Old Name".to_string()),
-                description: Some("Old Description".to_string()),
-                tags: None,
-            },
-            false,
-        )
-        .expect("seed metadata");
+fn capability_hint_from_error(
+    err: &anyhow::Error,
+    describe: Option<&greentic_types::schemas::component::v0_6_0::ComponentDescribe>,
+) -> Option<String> {
+    let lower = err.to_string().to_ascii_lowercase();
+    let inferred = if lower.contains("secret") {
+        Some("host:secrets")
+    } else if lower.contains("state") {
+        Some("host:state")
+    } else if lower.contains("http") {
+        Some("host:http")
+    } else if lower.contains("config") {
+        Some("host:config")
+    } else {
+        None
+    };
+    if let Some(cap) = inferred {
+        return Some(cap.to_string());
+    }
+    describe.and_then(|d| {
+        grouped_capabilities(&d.required_capabilities)
+            .into_iter()
+            .next()
+    })
+}
 
-        // 2 flow list, 1 select flow, 1 summary edit, then 7 save, back to main and exit.
-        let input = Cursor::new("2\n1\n1\n2\nNew Name\nNew Description\n7\n\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard summary update with save");
+fn wizard_op_from_error(err: &anyhow::Error, fallback: &str) -> String {
+    let lower = err.to_string().to_ascii_lowercase();
+    if lower.contains("call describe") {
+        "describe".to_string()
+    } else if lower.contains("call qa-spec") {
+        "qa-spec".to_string()
+    } else if lower.contains("call apply-answers") {
+        "apply-answers".to_string()
+    } else {
+        fallback.to_string()
+    }
+}
 
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        assert_eq!(doc.title.as_deref(), Some("i18n:flow.main.title"));
-        assert_eq!(
-            doc.description.as_deref(),
-            Some("i18n:flow.main.description")
+fn setup_contract_hint_from_error(err: &anyhow::Error) -> Option<&'static str> {
+    let lower = err.to_string().to_ascii_lowercase();
+    if lower.contains("missing exported component-qa instance")
+        || lower.contains("missing exported component-descriptor instance")
+        || lower.contains("missing exported component-qa.qa-spec function")
+        || lower.contains("missing exported component-qa.apply-answers function")
+    {
+        return Some(
+            "component is missing wizard setup exports (component-qa/component-descriptor). use a component built for setup flows, or add the step via explicit --operation/--payload without wizard mode",
         );
     }
+    None
+}
 
-    #[test]
-    fn wizard_save_doctor_failure_does_not_persist() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
+fn wrap_wizard_error(
+    err: anyhow::Error,
+    component_id: &str,
+    op_fallback: &str,
+    describe: Option<&greentic_types::schemas::component::v0_6_0::ComponentDescribe>,
+) -> anyhow::Error {
+    let op = wizard_op_from_error(&err, op_fallback);
+    if let Some(hint) = setup_contract_hint_from_error(&err) {
+        return err.context(format!(
+            "component '{component_id}' operation '{op}' failed: {hint}"
+        ));
+    }
+    if let Some(cap) = capability_hint_from_error(&err, describe) {
+        err.context(format!(
+            "component '{component_id}' operation '{op}' failed due to denied host ref; requested capability '{cap}'. hint: grant capability {cap} to this component"
+        ))
+    } else {
+        err.context(format!(
+            "component '{component_id}' operation '{op}' failed"
+        ))
+    }
+}
 
-        with_wizard_resolver_env(fixture_registry_resolver(), || {
-            // Add step with fixture oci ref, try save (doctor should fail on sidecar ref validation), then exit.
-            // No anchor prompt is shown when the flow has no steps.
-            let input = Cursor::new("2\n1\n3\n2\noci://acme/widget:1\n2\n1\n7\n\n0\n0\n0\n");
-            let mut output = Vec::new();
-            super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard run");
-            let rendered = String::from_utf8(output).expect("utf8");
-            assert!(rendered.contains("Save blocked: doctor failed"));
-            assert!(rendered.contains("Select operation"));
-        });
+fn ensure_wizard_config_not_error(
+    component_id: &str,
+    mode: wizard_ops::WizardMode,
+    config_json: &serde_json::Value,
+) -> Result<()> {
+    let Some(error_obj) = config_json.get("error").and_then(|value| value.as_object()) else {
+        return Ok(());
+    };
+    let code = error_obj
+        .get("code")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("UNKNOWN");
+    let message = error_obj
+        .get("message")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("component returned an error payload");
+    let details = error_obj
+        .get("details")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("");
+    if details.is_empty() {
+        anyhow::bail!(
+            "component '{component_id}' setup failed in mode '{}': {code}: {message}",
+            mode.as_str()
+        );
+    }
+    anyhow::bail!(
+        "component '{component_id}' setup failed in mode '{}': {code}: {message} ({details})",
+        mode.as_str()
+    );
+}
 
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        assert!(flow_ir.nodes.is_empty(), "failed save should not persist");
+fn wizard_mode_legacy_label(mode: wizard_ops::WizardMode) -> Option<&'static str> {
+    match mode {
+        wizard_ops::WizardMode::Update => Some("upgrade"),
+        _ => None,
     }
+}
 
-    #[test]
-    fn wizard_save_default_answers_path_writes_file() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: Some("Old Name".to_string()),
-                description: Some("Old Description".to_string()),
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
+fn wizard_answers_json_path(
+    base_dir: &Path,
+    flow_id: &str,
+    node_id: &str,
+    mode: wizard_ops::WizardMode,
+) -> PathBuf {
+    answers::answers_paths(base_dir, flow_id, node_id, mode.as_str()).json
+}
 
-        let input = Cursor::new("2\n1\n1\n2\nNew Name\nNew Description\n7\n\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard save");
-        let answers_path = dir.path().join("answers.json");
-        assert!(
-            answers_path.exists(),
-            "default answers file should be written"
-        );
+fn wizard_answers_json_path_compat(
+    base_dir: &Path,
+    flow_id: &str,
+    node_id: &str,
+    mode: wizard_ops::WizardMode,
+) -> Option<PathBuf> {
+    let primary = wizard_answers_json_path(base_dir, flow_id, node_id, mode);
+    if primary.exists() {
+        return Some(primary);
     }
+    let legacy = wizard_mode_legacy_label(mode).map(|label| {
+        answers::answers_paths(base_dir, flow_id, node_id, label)
+            .json
+            .to_path_buf()
+    });
+    legacy.filter(|path| path.exists())
+}
 
-    #[test]
-    fn wizard_save_custom_answers_path_writes_file() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: Some("Old Name".to_string()),
-                description: Some("Old Description".to_string()),
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
+fn warn_unknown_keys(answers: &QuestionAnswers, questions: &[Question]) {
+    if questions.is_empty() || answers.is_empty() {
+        return;
+    }
+    let mut known = std::collections::BTreeSet::new();
+    for q in questions {
+        known.insert(q.id.as_str());
+    }
+    let mut unknown = Vec::new();
+    for key in answers.keys() {
+        if !known.contains(key.as_str()) {
+            unknown.push(key.clone());
+        }
+    }
+    if !unknown.is_empty() {
+        eprintln!("warning: unknown answer keys: {}", unknown.join(", "));
+    }
+}
 
-        let input = Cursor::new("2\n1\n1\n2\nNew Name\nNew Description\n7\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_config(
-            dir.path(),
-            input,
-            &mut output,
-            super::WizardRunConfig {
-                answers_file: Some(PathBuf::from("artifacts/answers-out.json")),
-                dry_run: false,
-            },
-        )
-        .expect("wizard save");
-        let answers_path = dir.path().join("artifacts/answers-out.json");
-        assert!(
-            answers_path.exists(),
-            "custom answers file should be written"
-        );
+/// Ask how to delete `node_id`, which several steps route to. Every choice is previewed as the
+/// predecessors' routing it would leave: splicing into all of them, or pointing them at one of
+/// the step's successors. Other targets are reachable through `reroute-to:<node>`.
+fn prompt_multiple_predecessors<R: Read, W: Write>(
+    flow: &FlowIr,
+    node_id: &str,
+    strategy: DeleteStrategy,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<MultiplePredecessors> {
+    let mut probe = flow.clone();
+    let predecessors: Vec<String> = probe
+        .predecessors_of(node_id)
+        .into_iter()
+        .filter(|pred| pred != node_id)
+        .collect();
+    let mut candidates = vec![MultiplePredecessors::SpliceAll];
+    let node = flow
+        .nodes
+        .get(node_id)
+        .ok_or_else(|| anyhow!("node '{node_id}' not found"))?;
+    for route in &node.routing {
+        if let Some(to) = &route.to {
+            let choice = MultiplePredecessors::RerouteTo(to.clone());
+            if to != node_id && !candidates.contains(&choice) {
+                candidates.push(choice);
+            }
+        }
+    }
+    writeln!(
+        writer,
+        "Step '{node_id}' has {} predecessors: {}",
+        predecessors.len(),
+        predecessors.join(", ")
+    )
+    .ok();
+    let arrow = prompt_style::current().arrow();
+    let mut choices = Vec::new();
+    for candidate in candidates {
+        let mut preview = flow.clone();
+        writeln!(writer, "  {candidate}:").ok();
+        match preview.remove_node(node_id, strategy, candidate.clone()) {
+            Ok(_) => {
+                for pred in &predecessors {
+                    let routing = serde_json::to_string(&preview.nodes[pred.as_str()].routing)?;
+                    writeln!(writer, "    {pred} {arrow} {routing}").ok();
+                }
+                choices.push(candidate);
+            }
+            Err(err) => {
+                writeln!(writer, "    (not possible: {err})").ok();
+            }
+        }
+    }
+    let options: Vec<String> = choices
+        .iter()
+        .map(ToString::to_string)
+        .chain(["abort".to_string()])
+        .collect();
+    let question = Question {
+        id: "delete_step.multiple_predecessors".to_string(),
+        prompt: format!(
+            "How should the predecessors of '{node_id}' be rerouted? (options: {})",
+            options.join(", ")
+        ),
+        kind: greentic_flow::questions::QuestionKind::Choice,
+        required: true,
+        default: Some(serde_json::Value::String(options[0].clone())),
+        choices: options
+            .iter()
+            .map(|option| serde_json::Value::String(option.clone()))
+            .collect(),
+        show_if: None,
+        writes_to: None,
+    };
+    let answers =
+        run_questions_with_qa_lib_io(&[question], HashMap::new(), &mut *reader, &mut *writer)?;
+    let chosen = answers
+        .get("delete_step.multiple_predecessors")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("abort");
+    if chosen == "abort" {
+        anyhow::bail!("delete-step cancelled: '{node_id}' was kept");
     }
+    chosen.parse().map_err(|err: String| anyhow!(err))
+}
 
-    #[test]
-    fn wizard_main_menu_save_answers_option_writes_file() {
-        let dir = tempdir().expect("temp dir");
-        let input = Cursor::new("5\n\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard save answers");
-        let answers_path = dir.path().join("answers.json");
-        assert!(
-            answers_path.exists(),
-            "answers file should be written from main menu option 5"
-        );
+fn confirm_remove_mode(interactive: bool) -> Result<()> {
+    if !interactive {
+        anyhow::bail!("remove mode requires interactive confirmation: Type REMOVE to confirm");
     }
+    let mut stdout = io::stdout().lock();
+    write!(stdout, "Type REMOVE to confirm: ").context("write confirmation prompt")?;
+    stdout.flush().context("flush confirmation prompt")?;
+    let mut line = String::new();
+    io::stdin()
+        .read_line(&mut line)
+        .context("read remove confirmation")?;
+    if line.trim() != "REMOVE" {
+        anyhow::bail!("remove cancelled: confirmation did not match 'REMOVE'");
+    }
+    Ok(())
+}
 
-    #[test]
-    fn wizard_answers_export_and_reload_flow_matches_answers() {
-        let dir = tempdir().expect("temp dir");
-        let answers_rel = PathBuf::from("answers/replay.json");
-        let answers_path = dir.path().join(&answers_rel);
-        let flow_path = dir.path().join("flows/global/messaging/main.ygtc");
-        write_two_step_flow(&flow_path);
-
-        // Session 1: exercise menu flows, export answers via main menu 5, then save via 4.
-        let input = Cursor::new(
-            "2\n1\n\
-             1\n2\nFlow One\nDesc One\n\
-             2\n\
-             4\ncancel\n\
-             5\ncancel\n\
-             0\nM\n\
-             5\nanswers/replay.json\n\
-             4\n\
-             0\n",
-        );
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard run 1");
-
-        assert!(answers_path.exists(), "wizard answers export should exist");
-        assert!(flow_path.exists(), "flow should be persisted after save");
-
-        let answers_text = fs::read_to_string(&answers_path).expect("read answers file");
-        let answers_json: serde_json::Value =
-            serde_json::from_str(&answers_text).expect("parse answers json");
-        let answers = answers_json.as_object().expect("answers object");
-        assert_eq!(
-            answers.get("wizard.answers.path").and_then(|v| v.as_str()),
-            Some("answers/replay.json")
-        );
-        assert_eq!(
-            answers.get("summary.name").and_then(|v| v.as_str()),
-            Some("Flow One")
-        );
-        assert_eq!(
-            answers.get("summary.description").and_then(|v| v.as_str()),
-            Some("Desc One")
-        );
-
-        let before = fs::read_to_string(&flow_path).expect("read flow before reload");
-
-        // Session 2: preload answers file and run main menu 4 (save).
-        let input = Cursor::new("4\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_config(
-            dir.path(),
-            input,
-            &mut output,
-            super::WizardRunConfig {
-                answers_file: Some(answers_rel.clone()),
-                dry_run: false,
-            },
-        )
-        .expect("wizard run 2");
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum AddStepMode {
+    Default,
+    Config,
+}
 
-        let after = fs::read_to_string(&flow_path).expect("read flow after reload");
-        assert_eq!(before, after, "reload+save should preserve flow");
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
+enum WizardModeArg {
+    Default,
+    Setup,
+    Update,
+    #[value(name = "upgrade", hide = true)]
+    UpgradeDeprecated,
+    Remove,
+}
 
-        let flow_doc = load_ygtc_from_path(&flow_path).expect("load saved flow");
-        assert_eq!(flow_doc.id, "main");
-        assert_eq!(flow_doc.flow_type, "messaging");
-        assert_eq!(flow_doc.title.as_deref(), Some("i18n:flow.main.title"));
-        assert_eq!(
-            flow_doc.description.as_deref(),
-            Some("i18n:flow.main.description")
-        );
-        let flow_ir = FlowIr::from_doc(flow_doc).expect("flow ir");
-        assert!(
-            flow_ir.nodes.contains_key("first") && flow_ir.nodes.contains_key("second"),
-            "flow should preserve existing steps"
-        );
+impl WizardModeArg {
+    fn to_mode(self) -> wizard_ops::WizardMode {
+        match self {
+            WizardModeArg::Default => wizard_ops::WizardMode::Default,
+            WizardModeArg::Setup => wizard_ops::WizardMode::Setup,
+            WizardModeArg::Update | WizardModeArg::UpgradeDeprecated => {
+                wizard_ops::WizardMode::Update
+            }
+            WizardModeArg::Remove => wizard_ops::WizardMode::Remove,
+        }
     }
+}
+
+#[derive(Args, Debug)]
+struct AddStepArgs {
+    /// Component id to resolve via wizard ops (preferred for new flows).
+    #[arg(value_name = "component_id")]
+    component_id: Option<String>,
+    /// Path to the flow file to modify.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Optional anchor node id; defaults to entrypoint or first node.
+    #[arg(long = "after", conflicts_with = "before")]
+    after: Option<String>,
+    /// Insert in front of this node instead, rewiring all of its predecessors.
+    #[arg(long = "before")]
+    before: Option<String>,
+    /// How to source the node to insert.
+    #[arg(long = "mode", value_enum, default_value = "default")]
+    mode: AddStepMode,
+    /// Optional pack alias for the new node.
+    #[arg(long = "pack-alias")]
+    pack_alias: Option<String>,
+    /// Optional wizard mode (default/setup/update/remove).
+    #[arg(long = "wizard-mode", value_enum)]
+    wizard_mode: Option<WizardModeArg>,
+    /// Optional operation for the new node.
+    #[arg(long = "operation")]
+    operation: Option<String>,
+    /// Payload JSON for the new node (default mode).
+    #[arg(long = "payload", default_value = "{}")]
+    payload: String,
+    /// Routing shorthand: make the new node terminal (out).
+    #[arg(long = "routing-out", conflicts_with_all = ["routing_reply", "routing_next", "routing_multi_to", "routing_json", "routing_to_anchor"])]
+    routing_out: bool,
+    /// Routing shorthand: reply to origin.
+    #[arg(long = "routing-reply", conflicts_with_all = ["routing_out", "routing_next", "routing_multi_to", "routing_json", "routing_to_anchor"])]
+    routing_reply: bool,
+    /// Route to a specific node id.
+    #[arg(long = "routing-next", conflicts_with_all = ["routing_out", "routing_reply", "routing_multi_to", "routing_json"])]
+    routing_next: Option<String>,
+    /// Route to multiple node ids (comma-separated).
+    #[arg(long = "routing-multi-to", conflicts_with_all = ["routing_out", "routing_reply", "routing_next", "routing_json"])]
+    routing_multi_to: Option<String>,
+    /// Explicit routing JSON file (escape hatch).
+    #[arg(long = "routing-json", conflicts_with_all = ["routing_out", "routing_reply", "routing_next", "routing_multi_to"])]
+    routing_json: Option<PathBuf>,
+    /// Explicitly thread to the anchor’s existing targets (default if no routing flag is given).
+    #[arg(long = "routing-to-anchor", conflicts_with_all = ["routing_out", "routing_reply", "routing_next", "routing_multi_to", "routing_json"])]
+    routing_to_anchor: bool,
+    /// Config flow file to execute (config mode).
+    #[arg(long = "config-flow")]
+    config_flow: Option<PathBuf>,
+    /// Answers JSON for config mode.
+    #[arg(long = "answers")]
+    answers: Option<String>,
+    /// Answers file (JSON) for config mode.
+    #[arg(long = "answers-file")]
+    answers_file: Option<PathBuf>,
+    /// Named answer preset from the pack's `presets/` directory (merged below explicit answers).
+    #[arg(long = "preset")]
+    preset: Option<String>,
+    /// Directory for wizard answers artifacts.
+    #[arg(long = "answers-dir")]
+    answers_dir: Option<PathBuf>,
+    /// Overwrite existing answers artifacts.
+    #[arg(long = "overwrite-answers")]
+    overwrite_answers: bool,
+    /// Force re-asking wizard questions even if answers exist.
+    #[arg(long = "reask")]
+    reask: bool,
+    /// Locale (BCP47) for wizard prompts.
+    #[arg(long = "locale")]
+    locale: Option<String>,
+    /// Allow interactive QA prompts (wizard mode only).
+    #[arg(long = "interactive")]
+    interactive: bool,
+    /// Allow cycles/back-edges during insertion.
+    #[arg(long = "allow-cycles")]
+    allow_cycles: bool,
+    /// Print a unified diff of the flow and sidecar changes without writing them.
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+    /// Backward-compatible write flag (ignored; writing is default).
+    #[arg(long = "write", hide = true)]
+    write: bool,
+    /// Validate only without writing output.
+    #[arg(long = "validate-only")]
+    validate_only: bool,
+    /// Optional component manifest paths for catalog validation or config flow discovery.
+    #[arg(long = "manifest")]
+    manifests: Vec<PathBuf>,
+    /// Optional node id override.
+    #[arg(long = "node-id")]
+    node_id: Option<String>,
+    /// Pre-seed answers from an existing node's payload (explicit answers still win).
+    #[arg(long = "defaults-from")]
+    defaults_from: Option<String>,
+    /// Remote component reference (oci://, repo://, store://, etc.) for sidecar binding.
+    #[arg(long = "component")]
+    component_ref: Option<String>,
+    /// Local wasm path for sidecar binding (relative to the flow file).
+    #[arg(long = "local-wasm")]
+    local_wasm: Option<PathBuf>,
+    /// Distributor URL for component-id resolution.
+    #[arg(long = "distributor-url")]
+    distributor_url: Option<String>,
+    /// Distributor auth token (optional).
+    #[arg(long = "auth-token")]
+    auth_token: Option<String>,
+    /// Tenant id for component-id resolution.
+    #[arg(long = "tenant")]
+    tenant: Option<String>,
+    /// Environment id for component-id resolution.
+    #[arg(long = "env")]
+    env: Option<String>,
+    /// Pack id for component-id resolution.
+    #[arg(long = "pack")]
+    pack: Option<String>,
+    /// Component version for component-id resolution.
+    #[arg(long = "component-version")]
+    component_version: Option<String>,
+    /// ABI version override for wizard ops.
+    #[arg(long = "abi-version")]
+    abi_version: Option<String>,
+    /// Resolver override (fixture://...) for tests/CI.
+    #[arg(long = "resolver")]
+    resolver: Option<String>,
+    /// Pin the component (resolve tag to digest or hash local wasm).
+    #[arg(long = "pin")]
+    pin: bool,
+    /// Allow contract drift when describe_hash changes.
+    #[arg(long = "allow-contract-change")]
+    allow_contract_change: bool,
+}
 
-    #[test]
-    fn wizard_dry_run_does_not_persist_flow_but_writes_answers_file() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: Some("Old Name".to_string()),
-                description: Some("Old Description".to_string()),
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
+/// Where add-step put the node, for its summary line.
+fn insert_position(args: &AddStepArgs) -> String {
+    match (&args.after, &args.before) {
+        (_, Some(before)) => format!("before '{before}'"),
+        (Some(after), None) => format!("after '{after}'"),
+        (None, None) => "after '<default anchor>'".to_string(),
+    }
+}
 
-        let answers_path = dir.path().join("dry-run-answers.json");
-        let input = Cursor::new("2\n1\n1\n2\nDry Name\nDry Description\n7\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_config(
-            dir.path(),
-            input,
-            &mut output,
-            super::WizardRunConfig {
-                answers_file: Some(answers_path.clone()),
-                dry_run: true,
-            },
-        )
-        .expect("wizard dry-run save");
+#[derive(Args, Debug)]
+struct BindComponentArgs {
+    /// Path to the flow file to modify.
+    #[arg(long = "flow")]
+    flow_path: PathBuf,
+    /// Node id to bind.
+    #[arg(long = "step")]
+    step: String,
+    /// Remote component reference (oci://, repo://, store://, etc.).
+    #[arg(long = "component")]
+    component_ref: Option<String>,
+    /// Local wasm path (relative to the flow file).
+    #[arg(long = "local-wasm")]
+    local_wasm: Option<PathBuf>,
+    /// Pin the component (resolve tag to digest or hash local wasm).
+    #[arg(long = "pin")]
+    pin: bool,
+    /// Write back to the sidecar.
+    #[arg(long = "write")]
+    write: bool,
+}
 
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        assert_eq!(doc.title.as_deref(), Some("Old Name"));
-        assert_eq!(doc.description.as_deref(), Some("Old Description"));
-        assert!(
-            answers_path.exists(),
-            "answers file should be written in dry-run"
-        );
+fn build_routing_value(args: &AddStepArgs) -> Result<(Option<serde_json::Value>, bool)> {
+    if let Some(path) = &args.routing_json {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("read routing json {}", path.display()))?;
+        let parsed: serde_json::Value =
+            serde_json::from_str(&text).context("parse --routing-json as JSON")?;
+        return Ok((Some(parsed), false));
     }
-
-    #[test]
-    fn wizard_delete_flow_cancelled_keeps_file() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
-        let mut input = Cursor::new("1\n");
-        let mut output = Vec::new();
-        super::wizard_delete_flow_with_io(&flow_path, &mut input, &mut output)
-            .expect("delete prompt");
-        assert!(flow_path.exists(), "flow should remain after cancel");
+    if args.routing_out {
+        return Ok((Some(serde_json::Value::String("out".to_string())), false));
     }
+    if args.routing_reply {
+        return Ok((Some(serde_json::Value::String("reply".to_string())), false));
+    }
+    if let Some(next) = &args.routing_next {
+        return Ok((Some(json!([{ "to": next }])), false));
+    }
+    if let Some(multi) = &args.routing_multi_to {
+        let targets: Vec<_> = multi
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if targets.is_empty() {
+            anyhow::bail!("--routing-multi-to requires at least one target");
+        }
+        let routes: Vec<_> = targets.into_iter().map(|t| json!({ "to": t })).collect();
+        return Ok((Some(serde_json::Value::Array(routes)), false));
+    }
+    // Default: thread to anchor routes (placeholder-based internally).
+    let placeholder = json!([{ "to": greentic_flow::splice::NEXT_NODE_PLACEHOLDER }]);
+    Ok((Some(placeholder), true))
+}
 
-    #[test]
-    fn wizard_menu_delete_flow_removes_file() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
+/// Routing from the pack's `.greentic-flow.toml` for new terminal nodes of flow type `kind`.
+fn pack_default_routing(
+    flow_path: &Path,
+    kind: &str,
+) -> Result<Option<Vec<greentic_flow::flow_ir::Route>>> {
+    let dir = flow_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    Ok(PackConfig::discover(&dir)?
+        .and_then(|(_, config)| config.default_routing_for(kind))
+        .map(|routing| routing.routes()))
+}
 
-        // 2 flow list, 1 select flow, 6 delete, 2 yes, then 0 back to main, 4 save(default answers path), 0 exit
-        let input = Cursor::new("2\n1\n6\n2\n0\n4\n\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard delete flow");
-        assert!(!flow_path.exists(), "flow should be deleted");
+fn build_update_routing(
+    args: &UpdateStepArgs,
+) -> Result<Option<Vec<greentic_flow::flow_ir::Route>>> {
+    if let Some(path) = &args.routing_json {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("read routing json {}", path.display()))?;
+        let routes = parse_routing_arg(&text)?;
+        return Ok(Some(routes));
     }
-
-    fn write_two_step_flow(path: &Path) {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).expect("create parent");
-        }
-        fs::write(
-            path,
-            r#"id: main
-type: messaging
-schema_version: 2
-nodes:
-  first:
-    op: {}
-    routing:
-    - to: second
-  second:
-    op: {}
-    routing:
-    - out: true
-"#,
-        )
-        .expect("write two-step flow");
-        let sidecar_path = super::sidecar_path_for_flow(path);
-        fs::write(
-            sidecar_path,
-            r#"{
-  "schema_version": 1,
-  "flow": "welcome.ygtc",
-  "nodes": {
-    "first": {
-      "source": { "kind": "repo", "ref": "repo://placeholder/first" }
-    },
-    "second": {
-      "source": { "kind": "repo", "ref": "repo://placeholder/second" }
+    if args.routing_out {
+        return Ok(Some(vec![greentic_flow::flow_ir::Route {
+            out: true,
+            ..greentic_flow::flow_ir::Route::default()
+        }]));
     }
-  }
-}"#,
-        )
-        .expect("write sidecar");
+    if args.routing_reply {
+        return Ok(Some(vec![greentic_flow::flow_ir::Route {
+            reply: true,
+            ..greentic_flow::flow_ir::Route::default()
+        }]));
+    }
+    if let Some(next) = &args.routing_next {
+        return Ok(Some(vec![greentic_flow::flow_ir::Route {
+            to: Some(next.clone()),
+            ..greentic_flow::flow_ir::Route::default()
+        }]));
+    }
+    if let Some(multi) = &args.routing_multi_to {
+        let targets: Vec<_> = multi
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if targets.is_empty() {
+            anyhow::bail!("--routing-multi-to requires at least one target");
+        }
+        let routes = targets
+            .into_iter()
+            .map(|t| greentic_flow::flow_ir::Route {
+                to: Some(t.to_string()),
+                ..greentic_flow::flow_ir::Route::default()
+            })
+            .collect();
+        return Ok(Some(routes));
     }
+    Ok(None)
+}
 
-    #[test]
-    fn wizard_delete_step_removes_selected_node() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        write_two_step_flow(&flow_path);
-        let mut input = Cursor::new("first\n");
-        let mut output = Vec::new();
-        super::wizard_delete_step_with_io(&flow_path, &mut input, &mut output)
-            .expect("delete step");
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        assert!(!flow_ir.nodes.contains_key("first"));
-        assert!(flow_ir.nodes.contains_key("second"));
+fn infer_node_id_hint(args: &AddStepArgs) -> Option<String> {
+    if let Some(explicit) = args.node_id.clone() {
+        return Some(explicit);
+    }
+    if let Some(comp_ref) = &args.component_ref {
+        let trimmed = comp_ref
+            .trim_start_matches("oci://")
+            .trim_start_matches("repo://")
+            .trim_start_matches("store://");
+        let last = trimmed.rsplit(['/', '\\']).next()?;
+        let base = last.split([':', '@']).next().unwrap_or(last);
+        if !base.is_empty() {
+            return Some(base.replace('_', "-"));
+        }
+    }
+    if let Some(path) = &args.local_wasm
+        && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+    {
+        let normalized = stem.replace('_', "-");
+        if !normalized.is_empty() {
+            return Some(normalized);
+        }
     }
+    None
+}
 
-    #[test]
-    fn wizard_menu_delete_step_path_removes_node() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        write_two_step_flow(&flow_path);
-        // 2 flow list, 1 select flow, 5 delete-step, first, 7 save, then 0(back) 0(main) 0(exit)
-        let input = Cursor::new("2\n1\n5\nfirst\n7\n\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard menu delete step");
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        assert!(!flow_ir.nodes.contains_key("first"));
-        assert!(flow_ir.nodes.contains_key("second"));
+fn resolve_step_id(
+    step: Option<String>,
+    component_id: Option<&String>,
+    meta: &Option<serde_json::Value>,
+) -> Result<String> {
+    if let Some(step) = step {
+        return Ok(step);
     }
-
-    #[test]
-    fn wizard_menu_list_steps_shows_current_nodes() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        write_two_step_flow(&flow_path);
-        // 2 flow list, 1 select flow, 2 list-steps, then 0(back) 0(main) 0(exit)
-        let input = Cursor::new("2\n1\n2\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-            .expect("wizard menu list steps");
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.contains("Current steps"));
-        assert!(rendered.contains("1. first"));
-        assert!(rendered.contains("2. second"));
+    if let Some(component_id) = component_id {
+        return flow_meta::find_node_for_component(meta, component_id);
     }
+    anyhow::bail!("--step or component_id is required")
+}
 
-    #[test]
-    fn wizard_update_step_prompt_lists_current_nodes() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        write_two_step_flow(&flow_path);
-        let mut input = Cursor::new("cancel\n");
-        let mut output = Vec::new();
-        super::wizard_update_step_with_io(dir.path(), &flow_path, &mut input, &mut output)
-            .expect("wizard update step");
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.contains("1. first"));
-        assert!(rendered.contains("2. second"));
-    }
+fn handle_add_step(
+    args: AddStepArgs,
+    schema_mode: SchemaMode,
+    format: OutputFormat,
+    backup: bool,
+) -> Result<()> {
+    handle_add_step_with_qa_io(args, schema_mode, format, backup, None)
+}
 
-    #[test]
-    fn wizard_save_empty_flow_reports_clear_error() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        fs::create_dir_all(flow_path.parent().expect("parent")).expect("mkdirs");
-        fs::write(
-            &flow_path,
-            r#"id: welcome
-type: messaging
-start: first
-parameters: {}
-tags: []
-schema_version: 2
-entrypoints: {}
-nodes:
-  first:
-    op: {}
-    routing:
-    - out: true
-"#,
-        )
-        .expect("write single-step flow");
-        let sidecar_path = super::sidecar_path_for_flow(&flow_path);
-        fs::write(
-            sidecar_path,
-            r#"{
-  "schema_version": 1,
-  "flow": "welcome.ygtc",
-  "nodes": {
-    "first": {
-      "source": { "kind": "repo", "ref": "repo://placeholder/first" }
+fn handle_add_step_with_qa_io(
+    args: AddStepArgs,
+    schema_mode: SchemaMode,
+    format: OutputFormat,
+    backup: bool,
+    qa_io: Option<&mut QaInteractiveIo<'_>>,
+) -> Result<()> {
+    let flow_path = args.flow_path.clone();
+    let position = insert_position(&args);
+    let report = add_step(args, schema_mode, backup, qa_io, true)?;
+    let diagnostic = report.diagnostic.as_ref();
+    match report.outcome {
+        AddStepOutcome::Validated => {
+            if matches!(format, OutputFormat::Json) {
+                let payload = json!({"ok": true, "action": "add-step", "validate_only": true});
+                print_json_payload_with_optional_diagnostic(payload, diagnostic)?;
+            } else {
+                println!("add-step validation succeeded");
+            }
+        }
+        AddStepOutcome::Inserted { node_id } => {
+            if matches!(format, OutputFormat::Json) {
+                let payload = json!({
+                    "ok": true,
+                    "action": "add-step",
+                    "node_id": node_id,
+                    "flow_path": flow_path.display().to_string()
+                });
+                print_json_payload_with_optional_diagnostic(payload, diagnostic)?;
+            } else {
+                println!("Inserted node {position} and wrote {}", flow_path.display());
+            }
+        }
+        AddStepOutcome::DryRun { output, diff } => {
+            if matches!(format, OutputFormat::Json) {
+                let payload = dry_run_payload("add-step", &output, &diff);
+                print_json_payload_with_optional_diagnostic(payload, diagnostic)?;
+            } else {
+                print_dry_run_diff(&diff);
+            }
+        }
     }
-  }
-}"#,
-        )
-        .expect("write sidecar");
+    Ok(())
+}
 
-        // 2 flow list, 1 select flow, 5 delete-step, first, 7 save (fails), then back/main/exit
-        let input = Cursor::new("2\n1\n5\nfirst\n7\n0\n0\n0\n");
-        let mut output = Vec::new();
-        super::run_wizard_menu_with_io(dir.path(), input, &mut output).expect("wizard run");
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.contains("Save blocked: a flow must contain at least one step."));
-    }
+/// What [`add_step`] did, for its caller to report.
+enum AddStepOutcome {
+    /// `--validate-only`: the step would be added.
+    Validated,
+    /// The step was added as `node_id` and the files were written.
+    Inserted { node_id: String },
+    /// `--dry-run`: the flow that would be written and the diff of every file it would change.
+    DryRun { output: String, diff: String },
+}
 
-    #[test]
-    fn doctor_lints_raw_summary_literals() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: Some("Raw Name".to_string()),
-                description: Some("Raw Description".to_string()),
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
-        let errors = super::lint_i18n_tag_fields(&flow_path);
-        assert!(!errors.is_empty(), "expected i18n tag lint errors");
-    }
+struct AddStepReport {
+    outcome: AddStepOutcome,
+    /// Deprecation notice for the requested wizard mode, added to JSON output.
+    diagnostic: Option<serde_json::Value>,
+}
 
-    fn with_wizard_resolver_env<F: FnOnce()>(resolver: String, run: F) {
-        let _guard = env_test_lock();
-        let previous = env::var("GREENTIC_FLOW_WIZARD_RESOLVER").ok();
-        unsafe {
-            env::set_var("GREENTIC_FLOW_WIZARD_RESOLVER", resolver);
+/// Add a step without reporting it on stdout; `headers` prints the wizard section headers
+/// before questions are asked.
+fn add_step(
+    args: AddStepArgs,
+    schema_mode: SchemaMode,
+    backup: bool,
+    qa_io: Option<&mut QaInteractiveIo<'_>>,
+    headers: bool,
+) -> Result<AddStepReport> {
+    let (routing_value, require_placeholder) = build_routing_value(&args)?;
+    let component_identity = args
+        .component_id
+        .clone()
+        .or_else(|| args.component_ref.clone())
+        .or_else(|| {
+            args.local_wasm
+                .as_ref()
+                .and_then(|p| p.file_stem().and_then(|s| s.to_str()))
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_else(|| "component".to_string());
+
+    let wizard_requested = args.component_id.is_some() || args.wizard_mode.is_some();
+    if wizard_requested {
+        let (sidecar_path, mut sidecar) = open_sidecar(&args.flow_path, args.dry_run)?;
+        let doc = load_ygtc_from_path(&args.flow_path)?;
+        let flow_ir = FlowIr::from_doc(doc)?;
+        let wizard_mode_arg = args.wizard_mode.unwrap_or(WizardModeArg::Default);
+        let deprecation_diagnostic = warn_deprecated_wizard_mode(wizard_mode_arg);
+        let wizard_mode = wizard_mode_arg.to_mode();
+        let resolved = resolve_wizard_component(
+            &args.flow_path,
+            wizard_mode,
+            args.local_wasm.as_ref(),
+            args.component_ref.as_ref(),
+            args.component_id.as_ref(),
+            args.resolver.as_ref(),
+            args.distributor_url.as_ref(),
+            args.auth_token.as_ref(),
+            args.tenant.as_ref(),
+            args.env.as_ref(),
+            args.pack.as_ref(),
+            args.component_version.as_ref(),
+        )?;
+        let spec = if let Some(fixture) = resolved.fixture.as_ref() {
+            wizard_ops::WizardSpecOutput {
+                abi: fixture.abi,
+                describe_cbor: fixture.describe_cbor.clone(),
+                descriptor: None,
+                qa_spec_cbor: fixture.qa_spec_cbor.clone(),
+                answers_schema_cbor: None,
+            }
+        } else {
+            wizard_cache()
+                .fetch_wizard_spec(&resolved.wasm, wizard_mode)
+                .map_err(|err| wrap_wizard_error(err, &component_identity, "describe", None))?
+        };
+        let qa_spec = wizard_ops::decode_component_qa_spec(&spec.qa_spec_cbor, wizard_mode)?;
+        let (mut catalog, locale) = default_i18n_catalog(args.locale.as_deref());
+        merge_component_i18n_catalog(&mut catalog, &locale, &args.flow_path, &resolved.source);
+
+        let mut answers = QuestionAnswers::new();
+        let mut answer_sources = BTreeMap::new();
+        if let Some(name) = args.preset.as_deref() {
+            let preset = presets::load_preset(&preset_root_for_flow(&args.flow_path), name)?;
+            layer_answers(
+                &mut answers,
+                &mut answer_sources,
+                preset,
+                AnswerSource::Preset,
+            );
         }
-        run();
-        if let Some(value) = previous {
-            unsafe {
-                env::set_var("GREENTIC_FLOW_WIZARD_RESOLVER", value);
+        layer_answers(
+            &mut answers,
+            &mut answer_sources,
+            env_answers(&qa_spec),
+            AnswerSource::Env,
+        );
+        layer_answers(
+            &mut answers,
+            &mut answer_sources,
+            parse_answers_map(args.answers.as_deref(), args.answers_file.as_deref())?,
+            AnswerSource::User,
+        );
+        if let Some(sibling) = args.defaults_from.as_deref() {
+            let payload = sibling_node_payload(&flow_ir, sibling)?;
+            let config = payload.get("config").unwrap_or(&payload);
+            let seeded: Vec<(String, serde_json::Value)> = qa_spec
+                .questions
+                .iter()
+                .filter(|question| !answers.contains_key(&question.id))
+                .filter_map(|question| {
+                    config
+                        .get(&question.id)
+                        .map(|value| (question.id.clone(), value.clone()))
+                })
+                .collect();
+            layer_answers(
+                &mut answers,
+                &mut answer_sources,
+                seeded,
+                AnswerSource::Sibling,
+            );
+        }
+        let before_defaults = answers.clone();
+        wizard_ops::merge_default_answers(&qa_spec, &mut answers);
+        if args.interactive && matches!(wizard_mode, wizard_ops::WizardMode::Default) {
+            seed_optional_answers_for_default_setup(&qa_spec, &mut answers);
+        }
+        track_answer_changes(
+            &before_defaults,
+            &answers,
+            &mut answer_sources,
+            AnswerSource::Default,
+        );
+        if !qa_spec.questions.is_empty() {
+            qa_runner::warn_unknown_keys(&answers, &qa_spec, &catalog, &locale);
+            if headers {
+                println!(
+                    "{}",
+                    wizard_header(&component_identity, wizard_mode.as_str())
+                );
             }
+            let before_prompts = answers.clone();
+            answers = run_component_qa_with_qa_lib(
+                &qa_spec,
+                &catalog,
+                &locale,
+                answers,
+                args.interactive,
+                qa_io,
+            )?;
+            track_answer_changes(
+                &before_prompts,
+                &answers,
+                &mut answer_sources,
+                AnswerSource::User,
+            );
+        }
+
+        let answers_cbor = wizard_ops::answers_to_cbor(&answers)?;
+        let current_config = wizard_ops::empty_cbor_map();
+        let config_cbor = if let Some(fixture) = resolved.fixture.as_ref() {
+            fixture.apply_answers_cbor.clone()
         } else {
-            unsafe {
-                env::remove_var("GREENTIC_FLOW_WIZARD_RESOLVER");
+            wizard_cache()
+                .apply_wizard_answers(
+                    &resolved.wasm,
+                    spec.abi,
+                    wizard_mode,
+                    &current_config,
+                    &answers_cbor,
+                )
+                .map_err(|err| wrap_wizard_error(err, &component_identity, "apply-answers", None))?
+        };
+        let operation_id = args.operation.clone().unwrap_or_else(|| "run".to_string());
+        let mut config_json = wizard_ops::cbor_to_json(&config_cbor)?;
+        ensure_wizard_config_not_error(&component_identity, wizard_mode, &config_json)?;
+        let mut provenance_answers: BTreeMap<String, serde_json::Value> = answers
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        if let Some(manifest_path) = args
+            .manifests
+            .first()
+            .cloned()
+            .or_else(|| resolve_component_manifest_path(&resolved.source, &args.flow_path).ok())
+            && let Some(defaults) = resolve_operation_defaults(&manifest_path, &operation_id)?
+        {
+            for (path, value) in apply_operation_defaults(&mut config_json, &defaults) {
+                answer_sources.insert(path.clone(), AnswerSource::OperationDefault);
+                provenance_answers.insert(path, value);
             }
         }
-    }
 
-    #[test]
-    fn wizard_menu_add_step_remote_fixture() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
+        let operation = operation_id;
+        let contract_meta = spec
+            .descriptor
+            .as_ref()
+            .map(|descriptor| derive_contract_meta_from_descriptor(descriptor, &operation))
+            .transpose()?
+            .map(|(_, meta)| meta);
+        let routing_json = routing_value
+            .clone()
+            .unwrap_or(serde_json::Value::Array(Vec::new()));
+        let component_id_label = component_identity.clone();
+        let node_value = json!({
+            "component.exec": {
+                "component": component_id_label,
+                "config": config_json
             },
-            false,
-        )
-        .expect("seed flow");
-
-        with_wizard_resolver_env(fixture_registry_resolver(), || {
-            // 2 flow list, 1 flow, 3 add step, 2 remote, ref, 2 no-pin, 1 default mode, then back/main/exit.
-            // No anchor prompt is shown when the flow has no steps.
-            let input = Cursor::new("2\n1\n3\n2\noci://acme/widget:1\n2\n1\n0\n0\n0\n");
-            let mut output = Vec::new();
-            super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-                .expect("wizard menu add step");
-            let rendered = String::from_utf8(output).expect("utf8");
-            assert!(rendered.contains("Step added."));
+            "operation": operation,
+            "routing": routing_json
         });
 
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        assert!(
-            flow_ir.nodes.is_empty(),
-            "changes should not persist without save"
-        );
-    }
-
-    #[test]
-    fn wizard_add_step_anchor_no_nodes_defaults_to_auto_without_prompt() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        let mut input = Cursor::new("");
-        let mut output = Vec::new();
-        let selected =
-            super::wizard_select_add_step_anchor_with_io(&flow_ir, &mut input, &mut output)
-                .expect("select anchor");
-        assert_eq!(selected, None);
-        assert!(output.is_empty(), "no anchor prompt should be rendered");
-    }
-
-    #[test]
-    fn wizard_add_step_anchor_multiple_nodes_uses_numbered_selection() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        write_two_step_flow(&flow_path);
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        let mut input = Cursor::new("3\n");
-        let mut output = Vec::new();
-        let selected =
-            super::wizard_select_add_step_anchor_with_io(&flow_ir, &mut input, &mut output)
-                .expect("select anchor");
-        assert_eq!(selected.as_deref(), Some("second"));
-        let rendered = String::from_utf8(output).expect("utf8");
-        assert!(rendered.contains("1. Auto"));
-        assert!(rendered.contains("2. first"));
-        assert!(rendered.contains("3. second"));
-    }
-
-    #[test]
-    fn wizard_menu_update_step_remote_fixture() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
-        let resolver = fixture_registry_resolver();
-        handle_add_step(
-            AddStepArgs {
-                component_id: None,
-                flow_path: flow_path.clone(),
-                after: None,
-                mode: AddStepMode::Default,
-                pack_alias: None,
-                wizard_mode: Some(WizardModeArg::Default),
-                operation: None,
-                payload: "{}".to_string(),
-                routing_out: true,
-                routing_reply: false,
-                routing_next: None,
-                routing_multi_to: None,
-                routing_json: None,
-                routing_to_anchor: false,
-                config_flow: None,
-                answers: None,
-                answers_file: None,
-                answers_dir: None,
-                overwrite_answers: false,
-                reask: false,
-                locale: None,
-                interactive: false,
-                allow_cycles: false,
-                dry_run: false,
-                write: false,
-                validate_only: false,
-                manifests: Vec::new(),
-                node_id: Some("widget".to_string()),
-                component_ref: Some("oci://acme/widget:1".to_string()),
-                local_wasm: None,
-                distributor_url: None,
-                auth_token: None,
-                tenant: None,
-                env: None,
-                pack: None,
-                component_version: None,
-                abi_version: None,
-                resolver: Some(resolver),
-                pin: false,
-                allow_contract_change: false,
-            },
-            SchemaMode::Strict,
-            OutputFormat::Human,
-            false,
-        )
-        .expect("add step");
-
-        with_wizard_resolver_env(fixture_registry_resolver(), || {
-            // 2 flow list, 1 flow, 4 update step, 1 step, 2 remote, ref, 1 default mode, 7 save, then back/main/exit.
-            let input = Cursor::new("2\n1\n4\n1\n2\nrepo://acme/widget:1\n1\n7\n\n0\n0\n0\n");
-            let mut output = Vec::new();
-            super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-                .expect("wizard menu update step");
-            let _rendered = String::from_utf8(output).expect("utf8");
-        });
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        assert!(flow_ir.nodes.contains_key("widget"));
-    }
+        let mut node_id_hint =
+            infer_node_id_hint(&args).or_else(|| Some(component_identity.clone()));
+        if args.node_id.is_none() {
+            node_id_hint = normalize_node_id_hint(node_id_hint, &node_value);
+        }
 
-    #[test]
-    fn wizard_menu_update_step_twice_overwrites_answers_artifact() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "welcome".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("seed flow");
-        let resolver = fixture_registry_resolver();
-        handle_add_step(
-            AddStepArgs {
-                component_id: None,
-                flow_path: flow_path.clone(),
-                after: None,
-                mode: AddStepMode::Default,
-                pack_alias: None,
-                wizard_mode: Some(WizardModeArg::Default),
-                operation: None,
-                payload: "{}".to_string(),
-                routing_out: true,
-                routing_reply: false,
-                routing_next: None,
-                routing_multi_to: None,
-                routing_json: None,
-                routing_to_anchor: false,
-                config_flow: None,
-                answers: None,
-                answers_file: None,
-                answers_dir: None,
-                overwrite_answers: false,
-                reask: false,
-                locale: None,
-                interactive: false,
-                allow_cycles: false,
-                dry_run: false,
-                write: false,
-                validate_only: false,
-                manifests: Vec::new(),
-                node_id: Some("widget".to_string()),
-                component_ref: Some("oci://acme/widget:1".to_string()),
-                local_wasm: None,
-                distributor_url: None,
-                auth_token: None,
-                tenant: None,
-                env: None,
-                pack: None,
-                component_version: None,
-                abi_version: None,
-                resolver: Some(resolver),
-                pin: false,
-                allow_contract_change: false,
+        let spec_plan = AddStepSpec {
+            after: args.after.clone(),
+            before: args.before.clone(),
+            node_id_hint,
+            node: node_value,
+            allow_cycles: args.allow_cycles,
+            require_placeholder,
+            default_routing: if require_placeholder {
+                pack_default_routing(&args.flow_path, &flow_ir.kind)?
+            } else {
+                None
             },
-            SchemaMode::Strict,
-            OutputFormat::Human,
-            false,
-        )
-        .expect("add step");
+        };
 
-        with_wizard_resolver_env(fixture_registry_resolver(), || {
-            // Update same step twice in default mode, then save.
-            let input = Cursor::new(
-                "2\n1\n4\n1\n2\nrepo://acme/widget:1\n1\n4\n1\n2\nrepo://acme/widget:1\n1\n7\n\n0\n0\n0\n",
+        let empty_paths: Vec<PathBuf> = Vec::new();
+        let empty_catalog = ManifestCatalog::load_from_paths(&empty_paths);
+        let plan = plan_add_step(&flow_ir, spec_plan, &empty_catalog)
+            .map_err(|diags| anyhow::anyhow!("planning failed: {:?}", diags))?;
+        let inserted_id = plan.new_node.id.clone();
+        let mut updated = apply_and_validate(&flow_ir, plan, &empty_catalog, args.allow_cycles)?;
+
+        let abi_version = args
+            .abi_version
+            .clone()
+            .unwrap_or_else(|| wizard_ops::abi_version_from_abi(spec.abi));
+        flow_meta::set_component_entry(
+            &mut updated.meta,
+            &inserted_id,
+            &component_identity,
+            &abi_version,
+            resolved.digest.as_deref(),
+            &wizard_ops::describe_exports_for_meta(spec.abi),
+            contract_meta.as_ref(),
+        );
+        flow_meta::ensure_hints_empty(&mut updated.meta, &inserted_id);
+
+        let updated_doc = updated.to_doc()?;
+        let mut output = serde_yaml_bw::to_string(&updated_doc)?;
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+
+        let report = |outcome| AddStepReport {
+            outcome,
+            diagnostic: deprecation_diagnostic.clone(),
+        };
+        if args.validate_only {
+            return Ok(report(AddStepOutcome::Validated));
+        }
+
+        if !args.dry_run {
+            let _writing = cancel::write_guard()?;
+            let mut sorted = std::collections::BTreeMap::new();
+            for (key, value) in &answers {
+                sorted.insert(key.clone(), value.clone());
+            }
+            let base_dir = answers_base_dir(&args.flow_path, args.answers_dir.as_deref());
+            let _paths = answers::write_answers(
+                &base_dir,
+                &flow_ir.id,
+                &inserted_id,
+                wizard_mode.as_str(),
+                &sorted,
+                args.overwrite_answers,
+            )?;
+            wizard_state::update_wizard_state(
+                &args.flow_path,
+                &flow_ir.id,
+                &inserted_id,
+                wizard_mode.as_str(),
+                &locale,
+            )?;
+            write_flow_file(&args.flow_path, &output, true, backup)?;
+            config_store::write_node_config(&args.flow_path, &inserted_id, &config_cbor)?;
+            sidecar.nodes.insert(
+                inserted_id.clone(),
+                NodeResolveV1 {
+                    source: resolved.source,
+                    mode: None,
+                },
             );
-            let mut output = Vec::new();
-            super::run_wizard_menu_with_io(dir.path(), input, &mut output)
-                .expect("wizard menu update step twice");
-            let rendered = String::from_utf8(output).expect("utf8");
-            assert!(
-                !rendered.contains("answers already exist"),
-                "update-step should overwrite existing answers artifacts"
+            write_sidecar(&sidecar_path, &sidecar)?;
+            answer_provenance::record_answer_provenance(
+                &args.flow_path,
+                &inserted_id,
+                &provenance_answers,
+                &answer_sources,
+                &flow_meta::secrets_hints(&updated.meta, &inserted_id),
+            )?;
+            if let Err(err) =
+                write_flow_resolve_summary_for_node(&args.flow_path, &inserted_id, &sidecar)
+                    .with_context(|| {
+                        format!("update resolve summary for {}", args.flow_path.display())
+                    })
+            {
+                eprintln!("warning: {err}");
+            }
+            return Ok(report(AddStepOutcome::Inserted {
+                node_id: inserted_id,
+            }));
+        } else {
+            sidecar.nodes.insert(
+                inserted_id.clone(),
+                NodeResolveV1 {
+                    source: resolved.source,
+                    mode: None,
+                },
             );
-        });
+            let diff = dry_run_diff(&args.flow_path, &output, Some((&sidecar_path, &sidecar)))?;
+            return Ok(report(AddStepOutcome::DryRun { output, diff }));
+        }
     }
-
-    #[test]
-    fn wizard_local_wasm_copy_places_file_under_pack_components() {
-        let dir = tempdir().expect("temp dir");
-        let external = dir.path().join("external-widget.wasm");
-        fs::write(&external, b"\0asm....").expect("write wasm");
-        let copied =
-            super::copy_local_wasm_into_pack_components(dir.path(), &external).expect("copy wasm");
-        assert!(copied.starts_with(dir.path().join("components")));
-        assert!(copied.exists(), "copied wasm should exist");
-        let src = fs::read(&external).expect("read source");
-        let dst = fs::read(&copied).expect("read copied");
-        assert_eq!(src, dst);
+    let (sidecar_path, mut sidecar) = open_sidecar(&args.flow_path, args.dry_run)?;
+    let (component_source, resolve_mode) = resolve_component_source_inputs(
+        args.local_wasm.as_ref(),
+        args.component_ref.as_ref(),
+        args.pin,
+        &args.flow_path,
+    )?;
+    let doc = load_ygtc_from_path(&args.flow_path)?;
+    let flow_ir = FlowIr::from_doc(doc)?;
+    let manifest_path_for_schema = args
+        .manifests
+        .first()
+        .cloned()
+        .or_else(|| resolve_component_manifest_path(&component_source, &args.flow_path).ok());
+    let mut manifest_paths = args.manifests.clone();
+    if args.mode == AddStepMode::Config
+        && args.config_flow.is_none()
+        && manifest_paths.is_empty()
+        && let Some(path) = manifest_path_for_schema.clone()
+    {
+        manifest_paths.push(path);
     }
-
-    #[test]
-    fn resolves_default_config_flow_from_manifest() {
-        let manifest = json!({
-            "id": "ai.greentic.hello",
-            "dev_flows": {
-                "default": {
-                    "graph": {
-                        "id": "cfg",
-                        "type": "component-config",
-                        "nodes": {}
-                    }
-                }
-            }
-        });
-        let manifest_file = NamedTempFile::new().expect("temp file");
-        std::fs::write(manifest_file.path(), manifest.to_string()).expect("write manifest");
-
-        let (yaml, schema_path) =
-            resolve_config_flow(None, &[manifest_file.path().to_path_buf()], "default")
-                .expect("resolve");
-        assert!(yaml.contains("id: cfg"));
-        assert!(
-            schema_path.starts_with(env::temp_dir()),
-            "expected schema path {schema_path:?} under the temp directory"
+    if args.mode == AddStepMode::Config && args.config_flow.is_none() && manifest_paths.is_empty() {
+        anyhow::bail!(
+            "config mode requires --config-flow or a component manifest to provide dev_flows.custom"
         );
     }
+    let catalog = ManifestCatalog::load_from_paths(&manifest_paths);
+    for warning in catalog.warnings() {
+        eprintln!("warning: component manifest {warning}");
+    }
 
-    #[test]
-    fn config_flow_schema_resides_in_temp_dir() {
-        let manifest = json!({
-            "id": "ai.greentic.custom",
-            "dev_flows": {
-                "custom": {
-                    "graph": {
-                        "id": "cfg",
-                        "type": "component-config",
-                        "nodes": {}
+    let mut answers = parse_answers_with_preset(
+        &args.flow_path,
+        args.preset.as_deref(),
+        args.answers.as_deref(),
+        args.answers_file.as_deref(),
+    )?;
+    let has_answer_inputs =
+        args.answers.is_some() || args.answers_file.is_some() || args.preset.is_some();
+    let mut defaulted_fields = Vec::new();
+    let (mode_input, require_placeholder_flag) = match args.mode {
+        AddStepMode::Default => {
+            let mut payload_json: serde_json::Value =
+                serde_json::from_str(&args.payload).context("parse --payload as JSON")?;
+            let mut used_writes = false;
+            let mut used_dev_flow = false;
+            if let Some(manifest_path) = &manifest_path_for_schema {
+                let questions =
+                    questions_from_manifest(manifest_path, "default", &flow_ir.parameters)?;
+                if !questions.is_empty() {
+                    warn_unknown_keys(&answers, &questions);
+                    if let Some(sibling) = args.defaults_from.as_deref() {
+                        seed_answers_from_node(&flow_ir, sibling, &questions, &mut answers)?;
+                    }
+                    if headers {
+                        println!("{}", wizard_header(&component_identity, "default"));
+                    }
+                    if has_answer_inputs {
+                        validate_required(&questions, &answers)?;
+                    } else {
+                        answers = run_interactive_with_seed(&questions, answers)?;
+                    }
+                    if questions.iter().any(|q| q.writes_to.is_some()) {
+                        warn_writes_to_collisions(&questions, &answers);
+                        payload_json = apply_writes_to(payload_json, &questions, &answers)?;
+                        used_writes = true;
                     }
+                    used_dev_flow = true;
                 }
             }
-        });
-        let manifest_file = NamedTempFile::new().expect("temp file");
-        fs::write(manifest_file.path(), manifest.to_string()).expect("write manifest");
-
-        let (_, schema_path) =
-            resolve_config_flow(None, &[manifest_file.path().to_path_buf()], "custom")
-                .expect("resolve");
-        assert!(
-            schema_path.starts_with(env::temp_dir()),
-            "expected schema path {schema_path:?} to live in temp dir"
-        );
-    }
+            let operation = args.operation.clone().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "--operation is required in default mode (component id is not stored in flows)"
+                )
+            })?;
+            if !used_writes {
+                payload_json = merge_payload(payload_json, answers_to_value(&answers));
+            }
+            if let Some(manifest_path) = &manifest_path_for_schema
+                && let Some(defaults) = resolve_operation_defaults(manifest_path, &operation)?
+            {
+                defaulted_fields = apply_operation_defaults(&mut payload_json, &defaults);
+            }
+            if !used_dev_flow && let Some(manifest_path) = &manifest_path_for_schema {
+                let schema_resolution = resolve_input_schema(manifest_path, &operation)?;
+                let schema_present = require_schema(
+                    schema_mode,
+                    &schema_resolution.component_id,
+                    &schema_resolution.operation,
+                    &schema_resolution.manifest_path,
+                    "operations[].input_schema",
+                    schema_resolution.schema.as_ref(),
+                )?;
+                if schema_present.is_some() {
+                    validate_payload_against_schema(&schema_resolution, &payload_json)?;
+                }
+            }
+            let routing_json = routing_value.clone();
+            (
+                AddStepModeInput::Default {
+                    operation,
+                    payload: payload_json,
+                    routing: routing_json,
+                },
+                require_placeholder,
+            )
+        }
+        AddStepMode::Config => {
+            let (config_flow, schema_path) =
+                resolve_config_flow(args.config_flow.clone(), &manifest_paths, "custom")?;
+            let config_flow = resolve_param_defaults_in_yaml(&config_flow, &flow_ir.parameters)?;
+            let questions = questions_from_config_flow_text(&config_flow)?;
+            if !questions.is_empty() {
+                warn_unknown_keys(&answers, &questions);
+                if let Some(sibling) = args.defaults_from.as_deref() {
+                    seed_answers_from_node(&flow_ir, sibling, &questions, &mut answers)?;
+                }
+                if headers {
+                    println!("{}", wizard_header(&component_identity, "config"));
+                }
+                if has_answer_inputs {
+                    validate_required(&questions, &answers)?;
+                } else {
+                    answers = run_interactive_with_seed(&questions, answers)?;
+                }
+            }
+            let manifest_path_for_validation = manifest_paths.first().cloned().or_else(|| {
+                resolve_component_manifest_path(&component_source, &args.flow_path).ok()
+            });
+            (
+                AddStepModeInput::Config {
+                    config_flow,
+                    schema_path: schema_path.into_boxed_path(),
+                    answers: answers_to_json_map(answers),
+                    manifest_id: Some(component_identity.clone()),
+                    manifest_path: manifest_path_for_validation,
+                },
+                true,
+            )
+        }
+    };
 
-    #[test]
-    fn answers_merge_prefers_cli_over_file() {
-        let file = NamedTempFile::new().expect("temp file");
-        std::fs::write(file.path(), r#"{"value":"from-file","keep":1}"#).unwrap();
-        let merged = parse_answers_map(Some(r#"{"value":"from-cli"}"#), Some(file.path())).unwrap();
-        assert_eq!(
-            merged.get("value").and_then(|v| v.as_str()),
-            Some("from-cli")
-        );
-        assert_eq!(merged.get("keep").and_then(|v| v.as_i64()), Some(1));
+    let MaterializedNode {
+        hint,
+        node: node_value,
+        state_key_collisions,
+    } = materialize_node(mode_input, &catalog)?;
+    for collision in &state_key_collisions {
+        eprintln!("warning: {collision}; keeping the first value");
     }
-
-    #[test]
-    fn answers_map_accepts_yaml() {
-        let merged = parse_answers_map(Some("value: hello\ncount: 2"), None).unwrap();
-        assert_eq!(merged.get("value").and_then(|v| v.as_str()), Some("hello"));
-        assert_eq!(merged.get("count").and_then(|v| v.as_i64()), Some(2));
+    let mut node_id_hint = infer_node_id_hint(&args);
+    if node_id_hint.is_none() {
+        node_id_hint = hint;
     }
-
-    fn fixture_registry_resolver() -> String {
-        let registry = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
-            .join("tests")
-            .join("fixtures")
-            .join("registry");
-        format!("fixture://{}", registry.display())
+    if args.node_id.is_none() {
+        node_id_hint = normalize_node_id_hint(node_id_hint, &node_value);
     }
 
-    #[test]
-    fn fixture_registry_resolves_wizard() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flow.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "main".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("create flow");
-
-        let resolver = fixture_registry_resolver();
-
-        let args = AddStepArgs {
-            component_id: None,
-            flow_path: flow_path.clone(),
-            after: None,
-            mode: AddStepMode::Default,
-            pack_alias: None,
-            wizard_mode: Some(WizardModeArg::Default),
-            operation: None,
-            payload: "{}".to_string(),
-            routing_out: true,
-            routing_reply: false,
-            routing_next: None,
-            routing_multi_to: None,
-            routing_json: None,
-            routing_to_anchor: false,
-            config_flow: None,
-            answers: None,
-            answers_file: None,
-            answers_dir: None,
-            overwrite_answers: false,
-            reask: false,
-            locale: None,
-            interactive: false,
-            allow_cycles: false,
-            dry_run: false,
-            write: false,
-            validate_only: false,
-            manifests: Vec::new(),
-            node_id: Some("widget".to_string()),
-            component_ref: Some("oci://acme/widget:1".to_string()),
-            local_wasm: None,
-            distributor_url: None,
-            auth_token: None,
-            tenant: None,
-            env: None,
-            pack: None,
-            component_version: None,
-            abi_version: None,
-            resolver: Some(resolver),
-            pin: false,
-            allow_contract_change: false,
-        };
-        handle_add_step(args, SchemaMode::Strict, OutputFormat::Human, false).expect("add step");
+    let spec = AddStepSpec {
+        after: args.after.clone(),
+        before: args.before.clone(),
+        node_id_hint,
+        node: node_value,
+        allow_cycles: args.allow_cycles,
+        require_placeholder: require_placeholder_flag,
+        default_routing: if require_placeholder_flag {
+            pack_default_routing(&args.flow_path, &flow_ir.kind)?
+        } else {
+            None
+        },
+    };
 
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        let node = flow_ir.nodes.get("widget").expect("node exists");
-        assert_eq!(node.operation, "run");
-        let config = extract_config_payload(&node.payload);
-        assert_eq!(config.get("foo").and_then(|v| v.as_str()), Some("bar"));
+    let plan = plan_add_step(&flow_ir, spec, &catalog)
+        .map_err(|diags| anyhow::anyhow!("planning failed: {:?}", diags))?;
+    let inserted_id = plan.new_node.id.clone();
+    let mut updated = apply_and_validate(&flow_ir, plan, &catalog, args.allow_cycles)?;
+    if let Some(manifest_path) = &manifest_path_for_schema
+        && let Some(node) = updated.nodes.get(&inserted_id)
+        && let Ok(Some(schema_hash)) =
+            manifest_operation_schema_hash(manifest_path, &node.operation)
+    {
+        flow_meta::set_manifest_schema_hash(&mut updated.meta, &inserted_id, &schema_hash);
     }
-
-    #[test]
-    fn wizard_mode_upgrade_alias_maps_to_update() {
-        assert_eq!(
-            WizardModeArg::UpgradeDeprecated.to_mode(),
-            WizardMode::Update
-        );
+    let updated_doc = updated.to_doc()?;
+    let mut output = serde_yaml_bw::to_string(&updated_doc)?;
+    if !output.ends_with('\n') {
+        output.push('\n');
     }
 
-    #[test]
-    fn fixture_registry_update_and_remove() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flow.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "main".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("create flow");
-
-        let resolver = fixture_registry_resolver();
-        handle_add_step(
-            AddStepArgs {
-                component_id: None,
-                flow_path: flow_path.clone(),
-                after: None,
-                mode: AddStepMode::Default,
-                pack_alias: None,
-                wizard_mode: Some(WizardModeArg::Default),
-                operation: None,
-                payload: "{}".to_string(),
-                routing_out: true,
-                routing_reply: false,
-                routing_next: None,
-                routing_multi_to: None,
-                routing_json: None,
-                routing_to_anchor: false,
-                config_flow: None,
-                answers: None,
-                answers_file: None,
-                answers_dir: None,
-                overwrite_answers: false,
-                reask: false,
-                locale: None,
-                interactive: false,
-                allow_cycles: false,
-                dry_run: false,
-                write: false,
-                validate_only: false,
-                manifests: Vec::new(),
-                node_id: Some("widget".to_string()),
-                component_ref: Some("oci://acme/widget:1".to_string()),
-                local_wasm: None,
-                distributor_url: None,
-                auth_token: None,
-                tenant: None,
-                env: None,
-                pack: None,
-                component_version: None,
-                abi_version: None,
-                resolver: Some(resolver.clone()),
-                pin: false,
-                allow_contract_change: false,
-            },
-            SchemaMode::Strict,
-            OutputFormat::Human,
-            false,
-        )
-        .expect("add step");
+    let report = |outcome| AddStepReport {
+        outcome,
+        diagnostic: None,
+    };
+    if args.validate_only {
+        return Ok(report(AddStepOutcome::Validated));
+    }
 
-        handle_update_step(
-            UpdateStepArgs {
-                component_id: None,
-                flow_path: flow_path.clone(),
-                step: Some("widget".to_string()),
-                mode: "default".to_string(),
-                wizard_mode: Some(WizardModeArg::Update),
-                operation: None,
-                routing_out: false,
-                routing_reply: false,
-                routing_next: None,
-                routing_multi_to: None,
-                routing_json: None,
-                answers: None,
-                answers_file: None,
-                answers_dir: None,
-                overwrite_answers: false,
-                reask: false,
-                locale: None,
-                non_interactive: true,
-                interactive: false,
-                component: Some("oci://acme/widget:1".to_string()),
-                local_wasm: None,
-                distributor_url: None,
-                auth_token: None,
-                tenant: None,
-                env: None,
-                pack: None,
-                component_version: None,
-                abi_version: None,
-                resolver: Some(resolver.clone()),
-                dry_run: false,
-                write: false,
-                allow_contract_change: false,
-            },
-            SchemaMode::Strict,
-            OutputFormat::Human,
-            false,
-        )
-        .expect("update step");
+    sidecar.nodes.insert(
+        inserted_id.clone(),
+        NodeResolveV1 {
+            source: component_source,
+            mode: resolve_mode,
+        },
+    );
+    if !args.dry_run {
+        let _writing = cancel::write_guard()?;
+        write_flow_file(&args.flow_path, &output, true, backup)?;
+        write_sidecar(&sidecar_path, &sidecar)?;
+        if !defaulted_fields.is_empty() {
+            let sources = defaulted_fields
+                .iter()
+                .map(|(path, _)| (path.clone(), AnswerSource::OperationDefault))
+                .collect();
+            answer_provenance::record_answer_provenance(
+                &args.flow_path,
+                &inserted_id,
+                &defaulted_fields.into_iter().collect(),
+                &sources,
+                &flow_meta::secrets_hints(&updated.meta, &inserted_id),
+            )?;
+        }
+        if let Err(err) =
+            write_flow_resolve_summary_for_node(&args.flow_path, &inserted_id, &sidecar)
+                .with_context(|| format!("update resolve summary for {}", args.flow_path.display()))
+        {
+            eprintln!("warning: {err}");
+        }
+        Ok(report(AddStepOutcome::Inserted {
+            node_id: inserted_id,
+        }))
+    } else {
+        let diff = dry_run_diff(&args.flow_path, &output, Some((&sidecar_path, &sidecar)))?;
+        Ok(report(AddStepOutcome::DryRun { output, diff }))
+    }
+}
 
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        let node = flow_ir.nodes.get("widget").expect("node exists");
-        let config = extract_config_payload(&node.payload);
-        assert_eq!(config.get("foo").and_then(|v| v.as_str()), Some("updated"));
+fn handle_update_step(
+    args: UpdateStepArgs,
+    schema_mode: SchemaMode,
+    format: OutputFormat,
+    backup: bool,
+) -> Result<()> {
+    handle_update_step_with_qa_io(args, schema_mode, format, backup, None)
+}
 
-        let remove_err = handle_delete_step(
-            DeleteStepArgs {
-                component_id: None,
-                flow_path: flow_path.clone(),
-                step: Some("widget".to_string()),
-                wizard_mode: Some(WizardModeArg::Remove),
-                answers: None,
-                answers_file: None,
-                answers_dir: None,
-                overwrite_answers: false,
-                reask: false,
-                locale: None,
-                interactive: false,
-                component: Some("oci://acme/widget:1".to_string()),
-                local_wasm: None,
-                distributor_url: None,
-                auth_token: None,
-                tenant: None,
-                env: None,
-                pack: None,
-                component_version: None,
-                abi_version: None,
-                resolver: Some(resolver),
-                strategy: "splice".to_string(),
-                multi_pred: "error".to_string(),
-                assume_yes: true,
-                write: true,
-            },
-            OutputFormat::Human,
-            false,
-        )
-        .expect_err("remove mode should require explicit confirmation");
-        assert!(
-            remove_err.to_string().contains("Type REMOVE to confirm"),
-            "unexpected remove confirmation error: {remove_err}"
+fn handle_update_step_with_qa_io(
+    args: UpdateStepArgs,
+    schema_mode: SchemaMode,
+    format: OutputFormat,
+    backup: bool,
+    qa_io: Option<&mut QaInteractiveIo<'_>>,
+) -> Result<()> {
+    let doc = load_ygtc_from_path(&args.flow_path)?;
+    let mut flow_ir = FlowIr::from_doc(doc)?;
+    let component_identity = args
+        .component_id
+        .clone()
+        .or_else(|| args.component.clone())
+        .or_else(|| {
+            args.local_wasm
+                .as_ref()
+                .and_then(|p| p.file_stem().and_then(|s| s.to_str()))
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_else(|| "component".to_string());
+    let step_id = resolve_step_id(args.step.clone(), args.component_id.as_ref(), &flow_ir.meta)?;
+    // A rename that cannot happen fails before any wizard runs.
+    if let Some(to) = args.rename.as_deref() {
+        flow_ir.clone().rename_node(&step_id, to)?;
+    }
+    let final_id = args.rename.clone().unwrap_or_else(|| step_id.clone());
+    let wizard_requested = args.component_id.is_some() || args.wizard_mode.is_some();
+    if wizard_requested {
+        let (sidecar_path, mut sidecar) = open_sidecar(&args.flow_path, args.dry_run)?;
+        let wizard_mode_arg = args.wizard_mode.unwrap_or(WizardModeArg::Update);
+        let deprecation_diagnostic = warn_deprecated_wizard_mode(wizard_mode_arg);
+        let wizard_mode = wizard_mode_arg.to_mode();
+        let resolved = resolve_wizard_component(
+            &args.flow_path,
+            wizard_mode,
+            args.local_wasm.as_ref(),
+            args.component.as_ref(),
+            args.component_id.as_ref(),
+            args.resolver.as_ref(),
+            args.distributor_url.as_ref(),
+            args.auth_token.as_ref(),
+            args.tenant.as_ref(),
+            args.env.as_ref(),
+            args.pack.as_ref(),
+            args.component_version.as_ref(),
+        )?;
+        let spec = if let Some(fixture) = resolved.fixture.as_ref() {
+            wizard_ops::WizardSpecOutput {
+                abi: fixture.abi,
+                describe_cbor: fixture.describe_cbor.clone(),
+                descriptor: None,
+                qa_spec_cbor: fixture.qa_spec_cbor.clone(),
+                answers_schema_cbor: None,
+            }
+        } else {
+            wizard_ops::fetch_wizard_spec(&resolved.wasm, wizard_mode)
+                .map_err(|err| wrap_wizard_error(err, &component_identity, "describe", None))?
+        };
+        let qa_spec = wizard_ops::decode_component_qa_spec(&spec.qa_spec_cbor, wizard_mode)?;
+        let (mut catalog, locale) = default_i18n_catalog(args.locale.as_deref());
+        merge_component_i18n_catalog(&mut catalog, &locale, &args.flow_path, &resolved.source);
+
+        let base_dir = answers_base_dir(&args.flow_path, args.answers_dir.as_deref());
+        let fallback_path = if !args.reask
+            && args.answers.is_none()
+            && args.answers_file.is_none()
+            && args.preset.is_none()
+        {
+            wizard_answers_json_path_compat(&base_dir, &flow_ir.id, &step_id, wizard_mode)
+        } else {
+            None
+        };
+        let prior_sources = answer_provenance::answer_provenance(&args.flow_path, &step_id)?;
+        if args.changed_only && prior_sources.is_empty() {
+            anyhow::bail!(
+                "no recorded answer provenance for '{}'; rerun update-step without --changed-only first",
+                step_id
+            );
+        }
+        let mut answers = QuestionAnswers::new();
+        let mut answer_sources = BTreeMap::new();
+        if let Some(path) = fallback_path.as_deref() {
+            // Stored answers keep their recorded source; --changed-only drops user-set ones so
+            // they are asked again.
+            for (key, value) in parse_answers_map(None, Some(path))? {
+                let source = prior_sources
+                    .get(&key)
+                    .copied()
+                    .unwrap_or(AnswerSource::User);
+                if args.changed_only && source == AnswerSource::User {
+                    continue;
+                }
+                answer_sources.insert(key.clone(), source);
+                answers.insert(key, value);
+            }
+        }
+        if let Some(name) = args.preset.as_deref() {
+            let preset = presets::load_preset(&preset_root_for_flow(&args.flow_path), name)?;
+            layer_answers(
+                &mut answers,
+                &mut answer_sources,
+                preset,
+                AnswerSource::Preset,
+            );
+        }
+        layer_answers(
+            &mut answers,
+            &mut answer_sources,
+            env_answers(&qa_spec),
+            AnswerSource::Env,
+        );
+        layer_answers(
+            &mut answers,
+            &mut answer_sources,
+            parse_answers_map(args.answers.as_deref(), args.answers_file.as_deref())?,
+            AnswerSource::User,
+        );
+        let interactive = args.interactive || args.changed_only;
+        let before_defaults = answers.clone();
+        if !args.changed_only {
+            wizard_ops::merge_default_answers(&qa_spec, &mut answers);
+        }
+        if interactive && matches!(wizard_mode, wizard_ops::WizardMode::Default) {
+            seed_optional_answers_for_default_setup(&qa_spec, &mut answers);
+        }
+        track_answer_changes(
+            &before_defaults,
+            &answers,
+            &mut answer_sources,
+            AnswerSource::Default,
         );
+        if !qa_spec.questions.is_empty() {
+            qa_runner::warn_unknown_keys(&answers, &qa_spec, &catalog, &locale);
+            println!(
+                "{}",
+                wizard_header(&component_identity, wizard_mode.as_str())
+            );
+            let before_prompts = answers.clone();
+            answers = run_component_qa_with_qa_lib(
+                &qa_spec,
+                &catalog,
+                &locale,
+                answers,
+                interactive,
+                qa_io,
+            )?;
+            track_answer_changes(
+                &before_prompts,
+                &answers,
+                &mut answer_sources,
+                AnswerSource::User,
+            );
+        }
 
-        handle_delete_step(
-            DeleteStepArgs {
-                component_id: None,
-                flow_path: flow_path.clone(),
-                step: Some("widget".to_string()),
-                wizard_mode: None,
-                answers: None,
-                answers_file: None,
-                answers_dir: None,
-                overwrite_answers: false,
-                reask: false,
-                locale: None,
-                interactive: false,
-                component: None,
-                local_wasm: None,
-                distributor_url: None,
-                auth_token: None,
-                tenant: None,
-                env: None,
-                pack: None,
-                component_version: None,
-                abi_version: None,
-                resolver: None,
-                strategy: "splice".to_string(),
-                multi_pred: "error".to_string(),
-                assume_yes: true,
-                write: true,
-            },
-            OutputFormat::Human,
-            false,
-        )
-        .expect("delete step");
+        let answers_cbor = wizard_ops::answers_to_cbor(&answers)?;
+        let mut node = flow_ir
+            .nodes
+            .get(&step_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("step '{}' not found", step_id))?;
+        let config_schema = wizard_config_schema(&spec, &node.operation);
+        let current_config = stored_or_payload_config(
+            &args.flow_path,
+            &step_id,
+            &node.payload,
+            config_schema.as_ref(),
+        )?;
+        let config_cbor = if let Some(fixture) = resolved.fixture.as_ref() {
+            fixture.apply_answers_cbor.clone()
+        } else {
+            wizard_ops::apply_wizard_answers(
+                &resolved.wasm,
+                spec.abi,
+                wizard_mode,
+                &current_config,
+                &answers_cbor,
+            )
+            .map_err(|err| wrap_wizard_error(err, &component_identity, "apply-answers", None))?
+        };
+        let mut new_operation = node.operation.clone();
+        if let Some(op) = args.operation.clone() {
+            new_operation = op;
+        }
+        let contract_meta = spec
+            .descriptor
+            .as_ref()
+            .map(|descriptor| derive_contract_meta_from_descriptor(descriptor, &new_operation))
+            .transpose()?
+            .map(|(_, meta)| meta);
+        let config_json = wizard_ops::cbor_to_json(&config_cbor)?;
+        ensure_wizard_config_not_error(&component_identity, wizard_mode, &config_json)?;
+        node.payload = config_json;
+        node.operation = new_operation.clone();
+        if let Some(routing) = build_update_routing(&args)? {
+            node.routing = routing;
+        }
+        flow_ir.nodes.insert(step_id.clone(), node);
 
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        assert!(flow_ir.nodes.is_empty());
+        let abi_version = args
+            .abi_version
+            .clone()
+            .unwrap_or_else(|| wizard_ops::abi_version_from_abi(spec.abi));
+        flow_meta::set_component_entry(
+            &mut flow_ir.meta,
+            &step_id,
+            &component_identity,
+            &abi_version,
+            resolved.digest.as_deref(),
+            &wizard_ops::describe_exports_for_meta(spec.abi),
+            contract_meta.as_ref(),
+        );
+        flow_meta::ensure_hints_empty(&mut flow_ir.meta, &step_id);
+        if final_id != step_id {
+            flow_ir.rename_node(&step_id, &final_id)?;
+        }
+
+        let doc_out = flow_ir.to_doc()?;
+        let yaml = serialize_doc(&doc_out)?;
+        load_ygtc_from_str(&yaml)?;
+        if final_id != step_id {
+            sidecar.nodes.remove(&step_id);
+        }
+        if !args.dry_run {
+            let _writing = cancel::write_guard()?;
+            let mut sorted = std::collections::BTreeMap::new();
+            for (key, value) in &answers {
+                sorted.insert(key.clone(), value.clone());
+            }
+            let _paths = answers::write_answers(
+                &base_dir,
+                &flow_ir.id,
+                &final_id,
+                wizard_mode.as_str(),
+                &sorted,
+                args.overwrite_answers,
+            )?;
+            if final_id != step_id {
+                rename_step_records(&args.flow_path, &flow_ir.id, &step_id, &final_id);
+            }
+            wizard_state::update_wizard_state(
+                &args.flow_path,
+                &flow_ir.id,
+                &final_id,
+                wizard_mode.as_str(),
+                &locale,
+            )?;
+            write_flow_file(&args.flow_path, &yaml, true, backup)?;
+            config_store::write_node_config(&args.flow_path, &final_id, &config_cbor)?;
+            sidecar.nodes.insert(
+                final_id.clone(),
+                NodeResolveV1 {
+                    source: resolved.source,
+                    mode: None,
+                },
+            );
+            write_sidecar(&sidecar_path, &sidecar)?;
+            answer_provenance::record_answer_provenance(
+                &args.flow_path,
+                &final_id,
+                &answers
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+                &answer_sources,
+                &flow_meta::secrets_hints(&flow_ir.meta, &final_id),
+            )?;
+            if let Err(err) =
+                write_flow_resolve_summary_for_node(&args.flow_path, &final_id, &sidecar)
+                    .with_context(|| {
+                        format!("update resolve summary for {}", args.flow_path.display())
+                    })
+            {
+                eprintln!("warning: {err}");
+            }
+            if matches!(format, OutputFormat::Json) {
+                let mut payload = json!({
+                    "ok": true,
+                    "action": "update-step",
+                    "node_id": final_id,
+                    "flow_path": args.flow_path.display().to_string()
+                });
+                if final_id != step_id {
+                    payload["renamed_from"] = json!(step_id);
+                }
+                print_json_payload_with_optional_diagnostic(
+                    payload,
+                    deprecation_diagnostic.as_ref(),
+                )?;
+            } else {
+                print_updated_step(&step_id, &final_id, &args.flow_path);
+            }
+        } else {
+            sidecar.nodes.insert(
+                final_id.clone(),
+                NodeResolveV1 {
+                    source: resolved.source,
+                    mode: None,
+                },
+            );
+            let diff = dry_run_diff(&args.flow_path, &yaml, Some((&sidecar_path, &sidecar)))?;
+            if matches!(format, OutputFormat::Json) {
+                let payload = dry_run_payload("update-step", &yaml, &diff);
+                print_json_payload_with_optional_diagnostic(
+                    payload,
+                    deprecation_diagnostic.as_ref(),
+                )?;
+            } else {
+                print_dry_run_diff(&diff);
+            }
+        }
+        return Ok(());
     }
-
-    #[test]
-    fn update_step_blocks_contract_drift() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flow.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "main".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("create flow");
-
-        let resolver = fixture_registry_resolver();
-        handle_add_step(
-            AddStepArgs {
-                component_id: None,
-                flow_path: flow_path.clone(),
-                after: None,
-                mode: AddStepMode::Default,
-                pack_alias: None,
-                wizard_mode: Some(WizardModeArg::Default),
-                operation: None,
-                payload: "{}".to_string(),
-                routing_out: true,
-                routing_reply: false,
-                routing_next: None,
-                routing_multi_to: None,
-                routing_json: None,
-                routing_to_anchor: false,
-                config_flow: None,
-                answers: None,
-                answers_file: None,
-                answers_dir: None,
-                overwrite_answers: false,
-                reask: false,
-                locale: None,
-                interactive: false,
-                allow_cycles: false,
-                dry_run: false,
-                write: false,
-                validate_only: false,
-                manifests: Vec::new(),
-                node_id: Some("widget".to_string()),
-                component_ref: Some("oci://acme/widget:1".to_string()),
-                local_wasm: None,
-                distributor_url: None,
-                auth_token: None,
-                tenant: None,
-                env: None,
-                pack: None,
-                component_version: None,
-                abi_version: None,
-                resolver: Some(resolver.clone()),
-                pin: false,
-                allow_contract_change: false,
-            },
-            SchemaMode::Strict,
-            OutputFormat::Human,
-            false,
+    let (sidecar_path, mut sidecar) = open_sidecar(&args.flow_path, args.dry_run)?;
+    if let Some(component) = args.component.as_deref() {
+        validate_component_ref(component)?;
+    }
+    let sidecar_entry = sidecar.nodes.get(&step_id).ok_or_else(|| {
+        anyhow::anyhow!(
+            "no sidecar mapping for node '{}'; run greentic-flow bind-component or re-add the step with --component/--local-wasm",
+            step_id
         )
-        .expect("add step");
-
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let mut flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        if let Some(meta) = flow_ir.meta.as_mut()
-            && let Some(root) = meta.as_object_mut()
-            && let Some(greentic) = root.get_mut("greentic").and_then(Value::as_object_mut)
-            && let Some(components) = greentic
-                .get_mut("components")
-                .and_then(Value::as_object_mut)
-            && let Some(entry) = components.get_mut("widget").and_then(Value::as_object_mut)
+    })?;
+    let component_payload = load_component_payload(&sidecar_entry.source, &args.flow_path)?;
+    let mut node = flow_ir
+        .nodes
+        .get(&step_id)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("step '{}' not found", step_id))?;
+    let mut merged_payload = node.payload.clone();
+    if let Some(component_defaults) = component_payload {
+        merged_payload = merge_payload(merged_payload, Some(component_defaults));
+    }
+    let mut answers = parse_answers_with_preset(
+        &args.flow_path,
+        args.preset.as_deref(),
+        args.answers.as_deref(),
+        args.answers_file.as_deref(),
+    )?;
+    let mut new_operation = args
+        .operation
+        .clone()
+        .unwrap_or_else(|| node.operation.clone());
+    let new_payload = if args.mode == "config" {
+        let manifest_path =
+            resolve_component_manifest_path(&sidecar_entry.source, &args.flow_path)?;
+        let (config_flow, schema_path) =
+            resolve_config_flow(None, std::slice::from_ref(&manifest_path), "custom")?;
+        let config_flow = resolve_param_defaults_in_yaml(&config_flow, &flow_ir.parameters)?;
+        let mut base_answers = QuestionAnswers::new();
+        if let Some(obj) = merged_payload.as_object() {
+            base_answers.extend(obj.clone());
+        }
+        base_answers.extend(answers.clone());
+        let questions = questions_from_config_flow_text(&config_flow)?;
+        if !questions.is_empty() {
+            warn_unknown_keys(&answers, &questions);
+            println!("{}", wizard_header(&component_identity, "config"));
+            if args.non_interactive {
+                validate_required(&questions, &base_answers)?;
+            } else {
+                base_answers = run_interactive_with_seed(&questions, base_answers)?;
+            }
+        }
+        let flow_name = "custom";
+        let source_desc = format!("dev_flows.{flow_name}");
+        if questions.is_empty() {
+            require_schema(
+                schema_mode,
+                &component_identity,
+                flow_name,
+                &manifest_path,
+                &source_desc,
+                None,
+            )?;
+        } else {
+            let dev_schema = schema_for_questions(&questions);
+            require_schema(
+                schema_mode,
+                &component_identity,
+                flow_name,
+                &manifest_path,
+                &source_desc,
+                Some(&dev_schema),
+            )?;
+        }
+        let answers_map = answers_to_json_map(base_answers);
+        let output = run_config_flow(
+            &config_flow,
+            &schema_path,
+            &answers_map,
+            Some(component_identity.clone()),
+        )?;
+        for collision in &output.state_key_collisions {
+            eprintln!("warning: {collision}; keeping the first value");
+        }
+        let normalized = normalize_node_map(output.node)?;
+        if args.operation.is_none() {
+            new_operation = normalized.operation.clone();
+        }
+        normalized.payload
+    } else if args.mode == "default" {
+        let mut payload = merged_payload;
+        let mut used_writes = false;
+        let mut manifest_path_for_validation: Option<PathBuf> = None;
+        if let Ok(manifest_path) =
+            resolve_component_manifest_path(&sidecar_entry.source, &args.flow_path)
         {
-            entry.insert(
-                "describe_hash".to_string(),
-                Value::String("deadbeef".to_string()),
-            );
+            manifest_path_for_validation = Some(manifest_path.clone());
+            let questions =
+                questions_from_manifest(&manifest_path, "default", &flow_ir.parameters)?;
+            if !questions.is_empty() {
+                let mut base_answers = extract_answers_from_payload(&questions, &payload);
+                warn_unknown_keys(&answers, &questions);
+                base_answers.extend(answers.clone());
+                println!("{}", wizard_header(&component_identity, "default"));
+                if args.non_interactive {
+                    validate_required(&questions, &base_answers)?;
+                } else {
+                    base_answers = run_interactive_with_seed(&questions, base_answers)?;
+                }
+                answers = base_answers;
+                if questions.iter().any(|q| q.writes_to.is_some()) {
+                    warn_writes_to_collisions(&questions, &answers);
+                    payload = apply_writes_to(payload, &questions, &answers)?;
+                    used_writes = true;
+                }
+            }
         }
-        let doc_out = flow_ir.to_doc().expect("to doc");
-        let yaml = serialize_doc(&doc_out).expect("serialize doc");
-        fs::write(&flow_path, yaml).expect("write flow");
+        let final_payload = if used_writes {
+            payload.clone()
+        } else {
+            merge_payload(payload, answers_to_value(&answers))
+        };
+        if let Some(manifest_path) = manifest_path_for_validation.as_ref() {
+            let schema_resolution = resolve_input_schema(manifest_path, &new_operation)?;
+            let schema_present = require_schema(
+                schema_mode,
+                &schema_resolution.component_id,
+                &schema_resolution.operation,
+                &schema_resolution.manifest_path,
+                "operations[].input_schema",
+                schema_resolution.schema.as_ref(),
+            )?;
+            if schema_present.is_some() {
+                validate_payload_against_schema(&schema_resolution, &final_payload)?;
+            }
+        }
+        final_payload
+    } else {
+        merged_payload
+    };
+    let new_routing = if let Some(routing) = build_update_routing(&args)? {
+        routing
+    } else {
+        node.routing.clone()
+    };
 
-        let result = handle_update_step(
-            UpdateStepArgs {
-                component_id: None,
-                flow_path: flow_path.clone(),
-                step: Some("widget".to_string()),
-                mode: "default".to_string(),
-                wizard_mode: Some(WizardModeArg::Update),
-                operation: None,
-                routing_out: false,
-                routing_reply: false,
-                routing_next: None,
-                routing_multi_to: None,
-                routing_json: None,
-                answers: None,
-                answers_file: None,
-                answers_dir: None,
-                overwrite_answers: false,
-                reask: false,
-                locale: None,
-                non_interactive: true,
-                interactive: false,
-                component: Some("oci://acme/widget:1".to_string()),
-                local_wasm: None,
-                distributor_url: None,
-                auth_token: None,
-                tenant: None,
-                env: None,
-                pack: None,
-                component_version: None,
-                abi_version: None,
-                resolver: Some(resolver),
-                dry_run: false,
-                write: false,
-                allow_contract_change: false,
-            },
-            SchemaMode::Strict,
-            OutputFormat::Human,
-            false,
-        );
-        assert!(
-            result.is_ok(),
-            "canonical setup path without describe_cbor should skip hash drift checks"
-        );
+    // The payload was just re-authored, so it now matches the manifest's current schema.
+    if matches!(args.mode.as_str(), "config" | "default")
+        && let Ok(manifest_path) =
+            resolve_component_manifest_path(&sidecar_entry.source, &args.flow_path)
+        && let Ok(Some(schema_hash)) =
+            manifest_operation_schema_hash(&manifest_path, &new_operation)
+    {
+        flow_meta::set_manifest_schema_hash(&mut flow_ir.meta, &step_id, &schema_hash);
+    }
+    node.operation = new_operation;
+    node.payload = new_payload;
+    node.routing = new_routing;
+    flow_ir.nodes.insert(step_id.clone(), node);
+    let renamed = final_id != step_id;
+    if renamed {
+        flow_ir.rename_node(&step_id, &final_id)?;
+        if let Some(entry) = sidecar.nodes.remove(&step_id) {
+            sidecar.nodes.insert(final_id.clone(), entry);
+        }
     }
 
-    #[test]
-    fn add_step_dry_run_does_not_write() {
-        let dir = tempdir().expect("temp dir");
-        let flow_path = dir.path().join("flow.ygtc");
-        handle_new(
-            NewArgs {
-                flow_path: flow_path.clone(),
-                flow_id: "main".to_string(),
-                flow_type: "messaging".to_string(),
-                schema_version: 2,
-                name: None,
-                description: None,
-                force: true,
-            },
-            false,
-        )
-        .expect("create flow");
-
-        let resolver = fixture_registry_resolver();
-        handle_add_step(
-            AddStepArgs {
-                component_id: None,
-                flow_path: flow_path.clone(),
-                after: None,
-                mode: AddStepMode::Default,
-                pack_alias: None,
-                wizard_mode: Some(WizardModeArg::Default),
-                operation: None,
-                payload: "{}".to_string(),
-                routing_out: true,
-                routing_reply: false,
-                routing_next: None,
-                routing_multi_to: None,
-                routing_json: None,
-                routing_to_anchor: false,
-                config_flow: None,
-                answers: None,
-                answers_file: None,
-                answers_dir: None,
-                overwrite_answers: false,
-                reask: false,
-                locale: None,
-                interactive: false,
-                allow_cycles: false,
-                dry_run: true,
-                write: false,
-                validate_only: false,
-                manifests: Vec::new(),
-                node_id: Some("widget".to_string()),
-                component_ref: Some("oci://acme/widget:1".to_string()),
-                local_wasm: None,
-                distributor_url: None,
-                auth_token: None,
-                tenant: None,
-                env: None,
-                pack: None,
-                component_version: None,
-                abi_version: None,
-                resolver: Some(resolver),
-                pin: false,
-                allow_contract_change: false,
-            },
-            SchemaMode::Strict,
-            OutputFormat::Human,
-            false,
-        )
-        .expect("dry run");
-
-        let doc = load_ygtc_from_path(&flow_path).expect("load flow");
-        let flow_ir = FlowIr::from_doc(doc).expect("flow ir");
-        assert!(flow_ir.nodes.is_empty(), "dry-run should not write flow");
+    let doc_out = flow_ir.to_doc()?;
+    let yaml = serialize_doc(&doc_out)?;
+    load_ygtc_from_str(&yaml)?; // schema validation
+    if !args.dry_run {
+        write_flow_file(&args.flow_path, &yaml, true, backup)?;
+        if renamed {
+            write_sidecar(&sidecar_path, &sidecar)?;
+            rename_step_records(&args.flow_path, &flow_ir.id, &step_id, &final_id);
+        }
+        if let Err(err) = write_flow_resolve_summary_for_node(&args.flow_path, &final_id, &sidecar)
+            .with_context(|| format!("update resolve summary for {}", args.flow_path.display()))
+        {
+            eprintln!("warning: {err}");
+        }
+        if matches!(format, OutputFormat::Json) {
+            let mut payload = json!({
+                "ok": true,
+                "action": "update-step",
+                "node_id": final_id,
+                "flow_path": args.flow_path.display().to_string()
+            });
+            if renamed {
+                payload["renamed_from"] = json!(step_id);
+            }
+            print_json_payload(&payload)?;
+        } else {
+            print_updated_step(&step_id, &final_id, &args.flow_path);
+        }
+    } else {
+        // Default/config updates only touch the flow; the sidecar binding only moves on rename.
+        let sidecar_diff = renamed.then_some((sidecar_path.as_path(), &sidecar));
+        let diff = dry_run_diff(&args.flow_path, &yaml, sidecar_diff)?;
+        if matches!(format, OutputFormat::Json) {
+            print_json_payload(&dry_run_payload("update-step", &yaml, &diff))?;
+        } else {
+            print_dry_run_diff(&diff);
+        }
     }
-}
-fn backup_path(path: &Path) -> PathBuf {
-    let mut os = path.as_os_str().to_os_string();
-    os.push(".bak");
-    PathBuf::from(os)
+    Ok(())
 }
 
-fn write_flow_file(path: &Path, content: &str, force: bool, backup: bool) -> Result<()> {
-    if path.exists() && !force {
-        anyhow::bail!(
-            "refusing to overwrite existing file {}; pass --force to replace it",
-            path.display()
+fn print_updated_step(step_id: &str, final_id: &str, flow_path: &Path) {
+    if final_id == step_id {
+        println!("Updated step '{step_id}' in {}", flow_path.display());
+    } else {
+        println!(
+            "Updated step '{step_id}' and renamed it to '{final_id}' in {}",
+            flow_path.display()
         );
     }
+}
 
-    if let Some(parent) = path.parent()
-        && !parent.as_os_str().is_empty()
-    {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("failed to create parent directory {}", parent.display()))?;
+/// Move the per-step records kept beside a flow (stored config, wizard state, answer
+/// provenance, resolve summary) from `from` to `to`. Failures are warnings: the flow and sidecar
+/// are already written.
+fn rename_step_records(flow_path: &Path, flow_id: &str, from: &str, to: &str) {
+    if let Err(err) = config_store::rename_node_config(flow_path, from, to) {
+        eprintln!("warning: {err}");
     }
-
-    if backup && path.exists() {
-        let bak = backup_path(path);
-        fs::copy(path, &bak)
-            .with_context(|| format!("failed to write backup {}", bak.display()))?;
+    if let Err(err) = answer_provenance::rename_answer_provenance(flow_path, from, to) {
+        eprintln!("warning: {err}");
     }
-    let tmp_path = path.with_extension("tmp");
-    fs::write(&tmp_path, content)
-        .with_context(|| format!("failed to write {}", tmp_path.display()))?;
-    fs::rename(&tmp_path, path).with_context(|| format!("failed to replace {}", path.display()))?;
-    Ok(())
-}
-
-fn resolve_config_flow(
-    config_flow_arg: Option<PathBuf>,
-    manifests: &[PathBuf],
-    flow_name: &str,
-) -> Result<(String, PathBuf)> {
-    if let Some(path) = config_flow_arg {
-        let text = fs::read_to_string(&path)
-            .with_context(|| format!("read config flow {}", path.display()))?;
-        return Ok((text, path));
+    if let Err(err) = wizard_state::rename_wizard_step(flow_path, flow_id, from, to) {
+        eprintln!("warning: {err}");
+    }
+    if let Err(err) = rename_flow_resolve_summary_node(flow_path, from, to)
+        .with_context(|| format!("update resolve summary for {}", flow_path.display()))
+    {
+        eprintln!("warning: {err}");
     }
-
-    let manifest_path = manifests.first().ok_or_else(|| {
-        anyhow::anyhow!(
-            "config mode requires --config-flow or a component manifest with dev_flows.{}",
-            flow_name
-        )
-    })?;
-    resolve_config_flow_from_manifest(manifest_path, flow_name)
 }
 
-fn resolve_config_flow_from_manifest(
-    manifest_path: &Path,
-    flow_name: &str,
-) -> Result<(String, PathBuf)> {
-    let manifest_text = fs::read_to_string(manifest_path)
-        .with_context(|| format!("read manifest {}", manifest_path.display()))?;
-    let manifest_json: serde_json::Value =
-        serde_json::from_str(&manifest_text).context("parse manifest JSON")?;
-    let default_graph = manifest_json
-        .get("dev_flows")
-        .and_then(|v| v.get(flow_name))
-        .and_then(|v| v.get("graph"))
-        .cloned()
-        .ok_or_else(|| anyhow::anyhow!("manifest missing dev_flows.{}.graph", flow_name))?;
-    let mut graph = default_graph;
-    if let Some(obj) = graph.as_object_mut()
-        && !obj.contains_key("type")
+fn handle_delete_step(args: DeleteStepArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    let (sidecar_path, mut sidecar) = open_sidecar(&args.flow_path, !args.write)?;
+    let doc = load_ygtc_from_path(&args.flow_path)?;
+    let mut flow_ir = FlowIr::from_doc(doc)?;
+    let mut targets: Vec<String> = Vec::new();
+    for step in &args.step {
+        if !targets.contains(step) {
+            targets.push(step.clone());
+        }
+    }
+    if targets.is_empty() {
+        targets.push(resolve_step_id(
+            None,
+            args.component_id.as_ref(),
+            &flow_ir.meta,
+        )?);
+    }
+    if targets.len() > 1
+        && (args.component_id.is_some() || args.wizard_mode.is_some() || args.run_remove)
     {
-        obj.insert(
-            "type".to_string(),
-            serde_json::Value::String("component-config".to_string()),
+        anyhow::bail!(
+            "component wizards and --run-remove delete one step at a time; pass a single --step"
         );
     }
-    let yaml =
-        serde_yaml_bw::to_string(&graph).context("render dev_flows.default.graph to YAML")?;
-    let schema_path =
-        ensure_config_schema_path().context("prepare embedded flow schema for config flows")?;
-    Ok((yaml, schema_path))
-}
-
-fn load_manifest_json(path: &Path) -> Result<serde_json::Value> {
-    let text =
-        fs::read_to_string(path).with_context(|| format!("read manifest {}", path.display()))?;
-    serde_json::from_str(&text).context("parse manifest JSON")
-}
-
-fn resolve_manifest_path_for_component(component: &str) -> Result<PathBuf> {
-    if component.starts_with("oci://")
-        || component.starts_with("repo://")
-        || component.starts_with("store://")
+    let target = targets[0].clone();
+    if args.run_remove
+        && let Some(mode) = args.wizard_mode
+        && !matches!(mode.to_mode(), wizard_ops::WizardMode::Remove)
     {
-        validate_component_ref(component)?;
-        let source = classify_remote_source(component, None);
-        return resolve_component_manifest_path(&source, Path::new("."));
+        anyhow::bail!(
+            "--run-remove runs the remove wizard; drop --wizard-mode or set it to remove"
+        );
     }
+    let (bound_local_wasm, bound_component) = if args.run_remove
+        && args.component_id.is_none()
+        && args.component.is_none()
+        && args.local_wasm.is_none()
+    {
+        let binding = sidecar.nodes.get(&target).ok_or_else(|| {
+            anyhow::anyhow!(
+                "--run-remove needs a component for '{}': no sidecar binding found (pass --component or --local-wasm)",
+                target
+            )
+        })?;
+        match &binding.source {
+            ComponentSourceRefV1::Local { path, .. } => {
+                (Some(local_path_from_sidecar(path, &args.flow_path)), None)
+            }
+            ComponentSourceRefV1::Oci { r#ref, .. }
+            | ComponentSourceRefV1::Repo { r#ref, .. }
+            | ComponentSourceRefV1::Store { r#ref, .. } => (None, Some(r#ref.clone())),
+        }
+    } else {
+        (args.local_wasm.clone(), args.component.clone())
+    };
+    let component_identity = args
+        .component_id
+        .clone()
+        .or_else(|| bound_component.clone())
+        .or_else(|| {
+            bound_local_wasm
+                .as_ref()
+                .and_then(|p| p.file_stem().and_then(|s| s.to_str()))
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_else(|| "component".to_string());
+    let wizard_requested =
+        args.component_id.is_some() || args.wizard_mode.is_some() || args.run_remove;
+    let mut deprecation_diagnostic: Option<serde_json::Value> = None;
+    let mut teardown_output: Option<serde_json::Value> = None;
+    if wizard_requested {
+        let wizard_mode_arg = args.wizard_mode.unwrap_or(WizardModeArg::Remove);
+        deprecation_diagnostic = warn_deprecated_wizard_mode(wizard_mode_arg);
+        let wizard_mode = wizard_mode_arg.to_mode();
+        // `--run-remove` is itself the explicit teardown opt-in.
+        if matches!(wizard_mode, wizard_ops::WizardMode::Remove) && !args.run_remove {
+            confirm_remove_mode(args.interactive)?;
+        }
+        let resolved = resolve_wizard_component(
+            &args.flow_path,
+            wizard_mode,
+            bound_local_wasm.as_ref(),
+            bound_component.as_ref(),
+            args.component_id.as_ref(),
+            args.resolver.as_ref(),
+            args.distributor_url.as_ref(),
+            args.auth_token.as_ref(),
+            args.tenant.as_ref(),
+            args.env.as_ref(),
+            args.pack.as_ref(),
+            args.component_version.as_ref(),
+        )?;
+        let spec = if let Some(fixture) = resolved.fixture.as_ref() {
+            wizard_ops::WizardSpecOutput {
+                abi: fixture.abi,
+                describe_cbor: fixture.describe_cbor.clone(),
+                descriptor: None,
+                qa_spec_cbor: fixture.qa_spec_cbor.clone(),
+                answers_schema_cbor: None,
+            }
+        } else {
+            wizard_ops::fetch_wizard_spec(&resolved.wasm, wizard_mode)
+                .map_err(|err| wrap_wizard_error(err, &component_identity, "describe", None))?
+        };
+        let qa_spec = wizard_ops::decode_component_qa_spec(&spec.qa_spec_cbor, wizard_mode)?;
+        let (mut catalog, locale) = default_i18n_catalog(args.locale.as_deref());
+        merge_component_i18n_catalog(&mut catalog, &locale, &args.flow_path, &resolved.source);
 
-    let raw = component.strip_prefix("file://").unwrap_or(component);
-    let path = PathBuf::from(raw);
-    if !path.exists() {
-        anyhow::bail!("component path {} not found", path.display());
-    }
-    if path.is_dir() {
-        let manifest_path = path.join("component.manifest.json");
-        if !manifest_path.exists() {
-            anyhow::bail!(
-                "component.manifest.json not found at {}",
-                manifest_path.display()
+        let base_dir = answers_base_dir(&args.flow_path, args.answers_dir.as_deref());
+        let fallback_path = if !args.reask && args.answers.is_none() && args.answers_file.is_none()
+        {
+            wizard_answers_json_path_compat(&base_dir, &flow_ir.id, &target, wizard_mode)
+        } else {
+            None
+        };
+        let answers_file = args.answers_file.as_deref().or(fallback_path.as_deref());
+        let mut answers = parse_answers_map(args.answers.as_deref(), answers_file)?;
+        wizard_ops::merge_default_answers(&qa_spec, &mut answers);
+        if !qa_spec.questions.is_empty() {
+            qa_runner::warn_unknown_keys(&answers, &qa_spec, &catalog, &locale);
+            println!(
+                "{}",
+                wizard_header(&component_identity, wizard_mode.as_str())
             );
+            answers = run_component_qa_with_qa_lib(
+                &qa_spec,
+                &catalog,
+                &locale,
+                answers,
+                args.interactive,
+                None,
+            )?;
         }
-        return Ok(manifest_path);
-    }
-    if path.is_file() {
-        return Ok(path);
-    }
-    anyhow::bail!(
-        "component path {} is not a file or directory",
-        path.display()
-    )
-}
 
-fn questions_for_operation(
-    manifest: &serde_json::Value,
-    operation: &str,
-) -> Result<(Vec<Question>, Option<String>)> {
-    if let Some(graph) = dev_flow_graph_from_manifest(manifest, operation)? {
-        let questions = extract_questions_from_flow(&graph)?;
-        return Ok((questions, Some(operation.to_string())));
-    }
-    if let Some(graph) = dev_flow_graph_from_manifest(manifest, "default")? {
-        let questions = extract_questions_from_flow(&graph)?;
-        return Ok((questions, Some("default".to_string())));
+        let answers_cbor = wizard_ops::answers_to_cbor(&answers)?;
+        let target_node = flow_ir
+            .nodes
+            .get(&target)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("step '{}' not found", target))?;
+        let config_schema = wizard_config_schema(&spec, &target_node.operation);
+        let current_config = stored_or_payload_config(
+            &args.flow_path,
+            &target,
+            &target_node.payload,
+            config_schema.as_ref(),
+        )?;
+        let output_cbor = if let Some(fixture) = resolved.fixture.as_ref() {
+            fixture.apply_answers_cbor.clone()
+        } else {
+            wizard_ops::apply_wizard_answers(
+                &resolved.wasm,
+                spec.abi,
+                wizard_mode,
+                &current_config,
+                &answers_cbor,
+            )
+            .map_err(|err| wrap_wizard_error(err, &component_identity, "apply-answers", None))?
+        };
+        if args.run_remove {
+            let output = wizard_ops::cbor_to_json(&output_cbor)?;
+            ensure_wizard_config_not_error(&component_identity, wizard_mode, &output)?;
+            teardown_output = Some(output);
+        }
+        flow_meta::clear_component_entry(&mut flow_ir.meta, &target);
+        if args.write {
+            let mut sorted = std::collections::BTreeMap::new();
+            for (key, value) in &answers {
+                sorted.insert(key.clone(), value.clone());
+            }
+            let _paths = answers::write_answers(
+                &base_dir,
+                &flow_ir.id,
+                &target,
+                wizard_mode.as_str(),
+                &sorted,
+                args.overwrite_answers,
+            )?;
+            wizard_state::update_wizard_state(
+                &args.flow_path,
+                &flow_ir.id,
+                &target,
+                wizard_mode.as_str(),
+                &locale,
+            )?;
+        }
     }
-    Ok((Vec::new(), None))
-}
 
-fn dev_flow_graph_from_manifest(
-    manifest: &serde_json::Value,
-    flow_name: &str,
-) -> Result<Option<serde_json::Value>> {
-    let Some(graph) = manifest
-        .get("dev_flows")
-        .and_then(|v| v.get(flow_name))
-        .and_then(|v| v.get("graph"))
-        .cloned()
-    else {
-        return Ok(None);
+    let strategy = if args.strategy == "remove-only" {
+        DeleteStrategy::RemoveOnly
+    } else {
+        DeleteStrategy::Splice
     };
-    Ok(Some(graph))
-}
-
-fn questions_from_manifest(manifest_path: &Path, flow_name: &str) -> Result<Vec<Question>> {
-    let manifest = load_manifest_json(manifest_path)?;
-    let Some(graph) = dev_flow_graph_from_manifest(&manifest, flow_name)? else {
-        return Ok(Vec::new());
+    // All targets are removed from the IR before anything is validated or written, so a chain
+    // can go in one run even when deleting only part of it would leave an invalid flow. With
+    // --cascade, the nodes only the targets led to go too (targets first).
+    let removed: Vec<String> = if args.cascade {
+        let ids: Vec<&str> = targets.iter().map(String::as_str).collect();
+        flow_ir
+            .remove_nodes_cascade(&ids)?
+            .into_iter()
+            .map(|node| node.id)
+            .collect()
+    } else {
+        for node_id in &targets {
+            let if_multiple_predecessors = match &args.multi_pred {
+                Some(choice) => choice.clone(),
+                None if args.interactive && flow_ir.predecessors_of(node_id).len() > 1 => {
+                    prompt_multiple_predecessors(
+                        &flow_ir,
+                        node_id,
+                        strategy,
+                        &mut io::stdin().lock(),
+                        &mut io::stderr(),
+                    )?
+                }
+                None => MultiplePredecessors::Error,
+            };
+            flow_ir.remove_node(node_id, strategy, if_multiple_predecessors)?;
+        }
+        targets.clone()
     };
-    extract_questions_from_flow(&graph)
-}
-
-fn questions_from_config_flow_text(text: &str) -> Result<Vec<Question>> {
-    let flow_value: serde_json::Value =
-        serde_yaml_bw::from_str(text).context("parse config flow as YAML")?;
-    extract_questions_from_flow(&flow_value)
-}
+    let cascaded = &removed[targets.len()..];
+    let deleted = targets.join("', '");
 
-fn validate_example_against_schema(
-    schema: &serde_json::Value,
-    example: &serde_json::Value,
-) -> Result<()> {
-    let compiled = jsonschema::options()
-        .with_draft(Draft::Draft202012)
-        .build(schema)
-        .context("compile answers schema")?;
-    if let Err(error) = compiled.validate(example) {
-        let messages = error.to_string();
-        anyhow::bail!("generated example does not validate against schema: {messages}");
+    let doc_out = flow_ir.to_doc()?;
+    let yaml = serialize_doc(&doc_out)?;
+    load_ygtc_from_str(&yaml)?;
+    if args.write {
+        let _writing = cancel::write_guard()?;
+        write_flow_file(&args.flow_path, &yaml, true, backup)?;
+        for node_id in &removed {
+            sidecar.nodes.remove(node_id);
+        }
+        write_sidecar(&sidecar_path, &sidecar)?;
+        for node_id in &removed {
+            let _ = wizard_state::remove_wizard_step(&args.flow_path, &flow_ir.id, node_id);
+            let _ = answer_provenance::remove_answer_provenance(&args.flow_path, node_id);
+            config_store::remove_node_config(&args.flow_path, node_id)?;
+        }
+        if let Err(err) = remove_flow_resolve_summary_nodes(&args.flow_path, &removed)
+            .with_context(|| format!("update resolve summary for {}", args.flow_path.display()))
+        {
+            eprintln!("warning: {err}");
+        }
+        if matches!(format, OutputFormat::Json) {
+            let mut payload = json!({
+                "ok": true,
+                "action": "delete-step",
+                "node_id": target,
+                "node_ids": targets,
+                "flow_path": args.flow_path.display().to_string()
+            });
+            if args.cascade {
+                payload["cascade"] = json!(cascaded);
+            }
+            if let Some(teardown) = teardown_output {
+                payload["teardown"] = teardown;
+            }
+            print_json_payload_with_optional_diagnostic(payload, deprecation_diagnostic.as_ref())?;
+        } else {
+            print_teardown_output(&target, teardown_output.as_ref())?;
+            println!(
+                "Deleted {} '{deleted}' from {}",
+                if targets.len() > 1 { "steps" } else { "step" },
+                args.flow_path.display()
+            );
+            print_cascaded_steps(&deleted, cascaded, "Also deleted");
+        }
+    } else if args.dry_run {
+        for node_id in &removed {
+            sidecar.nodes.remove(node_id);
+        }
+        let diff = dry_run_diff(&args.flow_path, &yaml, Some((&sidecar_path, &sidecar)))?;
+        if matches!(format, OutputFormat::Json) {
+            let mut payload = dry_run_payload("delete-step", &yaml, &diff);
+            if args.cascade {
+                payload["cascade"] = json!(cascaded);
+            }
+            if let Some(teardown) = teardown_output {
+                payload["teardown"] = teardown;
+            }
+            print_json_payload_with_optional_diagnostic(payload, deprecation_diagnostic.as_ref())?;
+        } else {
+            print_teardown_output(&target, teardown_output.as_ref())?;
+            print_cascaded_steps(&deleted, cascaded, "Would also delete");
+            print_dry_run_diff(&diff);
+        }
+    } else if matches!(format, OutputFormat::Json) {
+        let mut payload =
+            json!({"ok": true, "action": "delete-step", "dry_run": true, "flow": yaml});
+        if args.cascade {
+            payload["cascade"] = json!(cascaded);
+        }
+        if let Some(teardown) = teardown_output {
+            payload["teardown"] = teardown;
+        }
+        print_json_payload_with_optional_diagnostic(payload, deprecation_diagnostic.as_ref())?;
+    } else {
+        print_teardown_output(&target, teardown_output.as_ref())?;
+        print_cascaded_steps(&deleted, cascaded, "Would also delete");
+        print!("{yaml}");
     }
     Ok(())
 }
 
-fn write_json_file(path: &Path, value: &serde_json::Value) -> Result<()> {
-    let mut text = serde_json::to_string_pretty(value).context("serialize json")?;
-    text.push('\n');
-    fs::write(path, text).with_context(|| format!("write {}", path.display()))
+/// Report the nodes `delete-step --cascade` removed (or would remove) along with the
+/// `deleted` steps.
+fn print_cascaded_steps(deleted: &str, cascaded: &[String], verb: &str) {
+    if cascaded.is_empty() {
+        return;
+    }
+    eprintln!(
+        "{verb} {} step(s) only reachable through '{deleted}': {}",
+        cascaded.len(),
+        cascaded.join(", ")
+    );
 }
 
-fn print_json_payload(value: &serde_json::Value) -> Result<()> {
-    let mut stdout = io::stdout().lock();
-    serde_json::to_writer_pretty(&mut stdout, value).context("write json")?;
-    writeln!(stdout).context("write newline")?;
-    Ok(())
-}
+fn handle_rename_step(args: RenameStepArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    let (sidecar_path, mut sidecar) = open_sidecar(&args.flow_path, args.dry_run)?;
+    let original = fs::read_to_string(&args.flow_path)
+        .with_context(|| format!("failed to read {}", args.flow_path.display()))?;
+    let yaml = rename_node(&original, &args.from, &args.to)?;
+    if let Some(entry) = sidecar.nodes.remove(&args.from) {
+        sidecar.nodes.insert(args.to.clone(), entry);
+    }
 
-fn answers_to_json_map(answers: QuestionAnswers) -> serde_json::Map<String, serde_json::Value> {
-    answers.into_iter().collect()
-}
+    if args.dry_run {
+        let diff = dry_run_diff(&args.flow_path, &yaml, Some((&sidecar_path, &sidecar)))?;
+        if matches!(format, OutputFormat::Json) {
+            print_json_payload(&dry_run_payload("rename-step", &yaml, &diff))?;
+        } else {
+            print_dry_run_diff(&diff);
+        }
+        return Ok(());
+    }
 
-fn answers_to_value(answers: &QuestionAnswers) -> Option<serde_json::Value> {
-    if answers.is_empty() {
-        None
+    // The flow, sidecar and resolve summary change together: if a later write fails, the
+    // earlier files are put back so no dangling references are left behind.
+    let _writing = cancel::write_guard()?;
+    let original_sidecar = fs::read(&sidecar_path)
+        .with_context(|| format!("failed to read {}", sidecar_path.display()))?;
+    write_flow_file(&args.flow_path, &yaml, true, backup)?;
+    let written = write_sidecar(&sidecar_path, &sidecar)
+        .and_then(|()| {
+            answer_provenance::rename_answer_provenance(&args.flow_path, &args.from, &args.to)
+        })
+        .and_then(|()| {
+            rename_flow_resolve_summary_node(&args.flow_path, &args.from, &args.to)
+                .with_context(|| format!("update resolve summary for {}", args.flow_path.display()))
+        });
+    if let Err(err) = written {
+        fs::write(&args.flow_path, &original)
+            .with_context(|| format!("restore {}", args.flow_path.display()))?;
+        fs::write(&sidecar_path, &original_sidecar)
+            .with_context(|| format!("restore {}", sidecar_path.display()))?;
+        return Err(err.context(format!(
+            "rename '{}' to '{}' rolled back",
+            args.from, args.to
+        )));
+    }
+
+    let flow_id = load_ygtc_from_str(&yaml)?.id;
+    if let Err(err) = config_store::rename_node_config(&args.flow_path, &args.from, &args.to) {
+        eprintln!("warning: {err}");
+    }
+    if let Err(err) =
+        wizard_state::rename_wizard_step(&args.flow_path, &flow_id, &args.from, &args.to)
+    {
+        eprintln!("warning: {err}");
+    }
+
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": true,
+            "action": "rename-step",
+            "from": args.from,
+            "to": args.to,
+            "flow_path": args.flow_path.display().to_string()
+        }))?;
     } else {
-        Some(serde_json::Value::Object(
-            answers.clone().into_iter().collect(),
-        ))
+        println!(
+            "Renamed step '{}' to '{}' in {}",
+            args.from,
+            args.to,
+            args.flow_path.display()
+        );
     }
+    Ok(())
 }
 
-fn wizard_header(component: &str, mode: &str) -> String {
-    format!("== {component} ({mode}) ==")
-}
-
-fn warn_deprecated_wizard_mode(mode: WizardModeArg) -> Option<serde_json::Value> {
-    if matches!(mode, WizardModeArg::UpgradeDeprecated) {
+/// Report the remove wizard's teardown output on stderr so stdout stays usable for the flow.
+fn print_teardown_output(node_id: &str, teardown: Option<&serde_json::Value>) -> Result<()> {
+    if let Some(teardown) = teardown {
         eprintln!(
-            "warning: wizard mode 'upgrade' is deprecated; use 'update' (will be removed in a future release)"
+            "Remove wizard output for '{}':\n{}",
+            node_id,
+            serde_json::to_string_pretty(teardown)?
         );
-        return Some(json!({
-            "kind": "deprecation",
-            "field": "mode",
-            "old": "upgrade",
-            "new": "update",
-            "message": "wizard mode 'upgrade' is deprecated; use 'update' (will be removed in a future release)"
-        }));
     }
-    None
+    Ok(())
 }
 
-fn print_json_payload_with_optional_diagnostic(
-    mut payload: serde_json::Value,
-    diagnostic: Option<&serde_json::Value>,
-) -> Result<()> {
-    if let Some(diag) = diagnostic
-        && let Some(object) = payload.as_object_mut()
-    {
-        object.insert(
-            "diagnostics".to_string(),
-            serde_json::Value::Array(vec![diag.clone()]),
-        );
+/// Canonical config recorded by the last wizard run, falling back to the YAML payload (re-typed
+/// through the component's config schema) for nodes written before configs were stored.
+fn stored_or_payload_config(
+    flow_path: &Path,
+    node_id: &str,
+    payload: &serde_json::Value,
+    config_schema: Option<&greentic_types::schemas::common::schema_ir::SchemaIr>,
+) -> Result<Vec<u8>> {
+    match config_store::read_node_config(flow_path, node_id)? {
+        Some(stored) => Ok(stored),
+        None => wizard_ops::config_json_to_cbor(payload, config_schema),
     }
-    print_json_payload(&payload)
 }
 
-fn normalize_capability_group(raw: &str) -> String {
-    let normalized = raw.trim().to_ascii_lowercase();
-    if normalized.is_empty() {
-        return normalized;
+/// Config schema advertised by a wizard component, if its describe or descriptor carries one.
+fn wizard_config_schema(
+    spec: &wizard_ops::WizardSpecOutput,
+    operation_id: &str,
+) -> Option<greentic_types::schemas::common::schema_ir::SchemaIr> {
+    if !spec.describe_cbor.is_empty() {
+        return contracts::decode_component_describe(&spec.describe_cbor)
+            .ok()
+            .map(|describe| describe.config_schema);
+    }
+    spec.descriptor.as_ref().and_then(|descriptor| {
+        derive_contract_meta_from_descriptor(descriptor, operation_id)
+            .ok()
+            .and_then(|(schema, _)| schema)
+    })
+}
+
+fn load_adapter_catalog(args: &AdapterSourceArgs) -> Result<AdapterCatalog> {
+    let mut catalog = AdapterCatalog::from_pack(&args.pack)?;
+    if !args.no_dist_cache {
+        catalog.merge(AdapterCatalog::from_manifest_tree(
+            DistOptions::default().cache_dir,
+        )?);
     }
-    if let Some(rest) = normalized.strip_prefix("wasi.") {
-        let head = rest.split(['.', ':', '/']).next().unwrap_or(rest);
-        return format!("wasi:{head}");
+    if let Some(index) = &args.index {
+        let text = if index.starts_with("http://") || index.starts_with("https://") {
+            let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
+            rt.block_on(async {
+                reqwest::get(index.as_str())
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await
+            })
+            .with_context(|| format!("fetch adapter index {index}"))?
+        } else {
+            let path = index.strip_prefix("file://").unwrap_or(index);
+            fs::read_to_string(path).with_context(|| format!("read adapter index {path}"))?
+        };
+        catalog.merge(
+            AdapterCatalog::from_json_str(&text)
+                .with_context(|| format!("parse adapter index {index}"))?,
+        );
     }
-    if let Some(rest) = normalized.strip_prefix("host.") {
-        let head = rest.split(['.', ':', '/']).next().unwrap_or(rest);
-        return format!("host:{head}");
+    Ok(catalog)
+}
+
+fn handle_adapters_list(args: &AdapterSourceArgs, format: OutputFormat) -> Result<()> {
+    let catalog = load_adapter_catalog(args)?;
+    let adapters = catalog.sorted();
+    let versions = catalog.sorted_versions();
+    if matches!(format, OutputFormat::Json) {
+        return print_json_payload(&json!({ "adapters": adapters, "versions": versions }));
     }
-    if normalized.contains(':') {
-        return normalized;
+    if adapters.is_empty() {
+        println!("No adapters found.");
     }
-    if let Some((left, right)) = normalized.split_once('.') {
-        let head = right.split(['.', ':', '/']).next().unwrap_or(right);
-        return format!("{left}:{head}");
+    for (adapter, operations) in &adapters {
+        let operations: Vec<&str> = operations.iter().map(String::as_str).collect();
+        match versions.get(adapter) {
+            Some(known) => {
+                let known: Vec<&str> = known.iter().map(String::as_str).collect();
+                println!(
+                    "{adapter} ({}): {}",
+                    known.join(", "),
+                    operations.join(", ")
+                );
+            }
+            None => println!("{adapter}: {}", operations.join(", ")),
+        }
     }
-    normalized
+    Ok(())
 }
 
-fn grouped_capabilities(caps: &[String]) -> Vec<String> {
-    let mut groups = BTreeSet::new();
-    for cap in caps {
-        groups.insert(normalize_capability_group(cap));
+fn handle_schema_convert(args: &SchemaConvertArgs, format: OutputFormat) -> Result<()> {
+    let text = fs::read_to_string(&args.input)
+        .with_context(|| format!("read {}", args.input.display()))?;
+    let schema: serde_json::Value = serde_json::from_str(&text)
+        .with_context(|| format!("parse JSON Schema {}", args.input.display()))?;
+    let import = json_schema_to_schema_ir(&schema)
+        .with_context(|| format!("convert {}", args.input.display()))?;
+    let bytes = greentic_types::cbor::canonical::to_canonical_cbor_allow_floats(&import.schema)
+        .map_err(|err| anyhow!("encode schema cbor: {err}"))?;
+    if let Some(parent) = args.out.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("create output directory {}", parent.display()))?;
+    }
+    fs::write(&args.out, &bytes).with_context(|| format!("write {}", args.out.display()))?;
+    if matches!(format, OutputFormat::Json) {
+        return print_json_payload(&json!({
+            "ok": true,
+            "action": "schema-convert",
+            "in": args.input.display().to_string(),
+            "out": args.out.display().to_string(),
+            "bytes": bytes.len(),
+            "diagnostics": import.diagnostics,
+        }));
     }
-    groups.into_iter().collect()
-}
-
-fn capability_hint_from_error(
-    err: &anyhow::Error,
-    describe: Option<&greentic_types::schemas::component::v0_6_0::ComponentDescribe>,
-) -> Option<String> {
-    let lower = err.to_string().to_ascii_lowercase();
-    let inferred = if lower.contains("secret") {
-        Some("host:secrets")
-    } else if lower.contains("state") {
-        Some("host:state")
-    } else if lower.contains("http") {
-        Some("host:http")
-    } else if lower.contains("config") {
-        Some("host:config")
-    } else {
-        None
-    };
-    if let Some(cap) = inferred {
-        return Some(cap.to_string());
+    for diagnostic in &import.diagnostics {
+        let pointer = if diagnostic.pointer.is_empty() {
+            "/"
+        } else {
+            diagnostic.pointer.as_str()
+        };
+        eprintln!("warning: {pointer}: {}", diagnostic.message);
     }
-    describe.and_then(|d| {
-        grouped_capabilities(&d.required_capabilities)
-            .into_iter()
-            .next()
-    })
+    println!(
+        "Wrote {} ({} bytes) from {}",
+        args.out.display(),
+        bytes.len(),
+        args.input.display()
+    );
+    Ok(())
 }
 
-fn wizard_op_from_error(err: &anyhow::Error, fallback: &str) -> String {
-    let lower = err.to_string().to_ascii_lowercase();
-    if lower.contains("call describe") {
-        "describe".to_string()
-    } else if lower.contains("call qa-spec") {
-        "qa-spec".to_string()
-    } else if lower.contains("call apply-answers") {
-        "apply-answers".to_string()
+fn handle_adapters_refresh(args: &AdaptersRefreshArgs, format: OutputFormat) -> Result<()> {
+    let catalog = load_adapter_catalog(&args.source)?;
+    let adapters = catalog.sorted();
+    let out = args
+        .out
+        .clone()
+        .unwrap_or_else(|| args.source.pack.join("adapters.json"));
+    if let Some(parent) = out.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("create output directory {}", parent.display()))?;
+    }
+    let mut registry = json!({ "adapters": adapters });
+    let versions = catalog.sorted_versions();
+    if !versions.is_empty() {
+        registry["versions"] = json!(versions);
+    }
+    let text = serde_json::to_string_pretty(&registry)?;
+    fs::write(&out, format!("{text}\n")).with_context(|| format!("write {}", out.display()))?;
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": true,
+            "out": out.display().to_string(),
+            "adapters": adapters.len(),
+        }))
     } else {
-        fallback.to_string()
+        println!("Wrote {} adapter(s) to {}", adapters.len(), out.display());
+        Ok(())
     }
 }
 
-fn setup_contract_hint_from_error(err: &anyhow::Error) -> Option<&'static str> {
-    let lower = err.to_string().to_ascii_lowercase();
-    if lower.contains("missing exported component-qa instance")
-        || lower.contains("missing exported component-descriptor instance")
-        || lower.contains("missing exported component-qa.qa-spec function")
-        || lower.contains("missing exported component-qa.apply-answers function")
+fn handle_pin(args: &PinArgs, format: OutputFormat) -> Result<()> {
+    let sidecar_path = sidecar_path_for_flow(&args.flow_path);
+    let mut sidecar = read_flow_resolve(&sidecar_path)
+        .with_context(|| format!("read sidecar {}", sidecar_path.display()))?;
+    if let Some(unknown) = args
+        .nodes
+        .iter()
+        .find(|node_id| !sidecar.nodes.contains_key(node_id.as_str()))
     {
-        return Some(
-            "component is missing wizard setup exports (component-qa/component-descriptor). use a component built for setup flows, or add the step via explicit --operation/--payload without wizard mode",
+        anyhow::bail!(
+            "node '{unknown}' is not bound in {}",
+            sidecar_path.display()
         );
     }
-    None
-}
-
-fn wrap_wizard_error(
-    err: anyhow::Error,
-    component_id: &str,
-    op_fallback: &str,
-    describe: Option<&greentic_types::schemas::component::v0_6_0::ComponentDescribe>,
-) -> anyhow::Error {
-    let op = wizard_op_from_error(&err, op_fallback);
-    if let Some(hint) = setup_contract_hint_from_error(&err) {
-        return err.context(format!(
-            "component '{component_id}' operation '{op}' failed: {hint}"
-        ));
+    let mut remote = 0;
+    let mut changes = Vec::new();
+    for (node_id, entry) in sidecar.nodes.iter_mut() {
+        if !args.nodes.is_empty() && !args.nodes.contains(node_id) {
+            continue;
+        }
+        let (reference, digest) = match &mut entry.source {
+            ComponentSourceRefV1::Oci { r#ref, digest }
+            | ComponentSourceRefV1::Repo { r#ref, digest }
+            | ComponentSourceRefV1::Store { r#ref, digest, .. } => (r#ref.clone(), digest),
+            ComponentSourceRefV1::Local { .. } => continue,
+        };
+        remote += 1;
+        if args.unpin {
+            if digest.take().is_none() {
+                continue;
+            }
+            if entry.mode == Some(ResolveModeV1::Pinned) {
+                entry.mode = None;
+            }
+            changes.push(json!({ "node_id": node_id, "reference": reference, "digest": null }));
+        } else {
+            // A reference that names its digest is pinned already.
+            if digest.is_some() || reference.contains("@sha256:") {
+                continue;
+            }
+            let resolved = resolve_remote_digest(&reference)
+                .with_context(|| format!("resolve digest of node '{node_id}' ({reference})"))?;
+            *digest = Some(resolved.clone());
+            entry.mode = Some(ResolveModeV1::Pinned);
+            changes.push(json!({ "node_id": node_id, "reference": reference, "digest": resolved }));
+        }
     }
-    if let Some(cap) = capability_hint_from_error(&err, describe) {
-        err.context(format!(
-            "component '{component_id}' operation '{op}' failed due to denied host ref; requested capability '{cap}'. hint: grant capability {cap} to this component"
-        ))
-    } else {
-        err.context(format!(
-            "component '{component_id}' operation '{op}' failed"
-        ))
+    if !changes.is_empty() && !args.dry_run {
+        write_sidecar(&sidecar_path, &sidecar)?;
     }
-}
 
-fn ensure_wizard_config_not_error(
-    component_id: &str,
-    mode: wizard_ops::WizardMode,
-    config_json: &serde_json::Value,
-) -> Result<()> {
-    let Some(error_obj) = config_json.get("error").and_then(|value| value.as_object()) else {
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "flow": args.flow_path.display().to_string(),
+            "sidecar": sidecar_path.display().to_string(),
+            "unpin": args.unpin,
+            "dry_run": args.dry_run,
+            "remote": remote,
+            "changes": changes,
+        }))?;
         return Ok(());
-    };
-    let code = error_obj
-        .get("code")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("UNKNOWN");
-    let message = error_obj
-        .get("message")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("component returned an error payload");
-    let details = error_obj
-        .get("details")
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or("");
-    if details.is_empty() {
-        anyhow::bail!(
-            "component '{component_id}' setup failed in mode '{}': {code}: {message}",
-            mode.as_str()
-        );
     }
-    anyhow::bail!(
-        "component '{component_id}' setup failed in mode '{}': {code}: {message} ({details})",
-        mode.as_str()
-    );
-}
-
-fn wizard_mode_legacy_label(mode: wizard_ops::WizardMode) -> Option<&'static str> {
-    match mode {
-        wizard_ops::WizardMode::Update => Some("upgrade"),
-        _ => None,
+    for change in &changes {
+        match change["digest"].as_str() {
+            Some(digest) => println!(
+                "PIN   node '{}': {} -> {digest}",
+                change["node_id"].as_str().unwrap_or_default(),
+                change["reference"].as_str().unwrap_or_default()
+            ),
+            None => println!(
+                "UNPIN node '{}': {}",
+                change["node_id"].as_str().unwrap_or_default(),
+                change["reference"].as_str().unwrap_or_default()
+            ),
+        }
     }
+    println!(
+        "{} {} of {remote} remote component reference(s) in {}",
+        match (args.unpin, args.dry_run) {
+            (false, false) => "Pinned",
+            (false, true) => "Would pin",
+            (true, false) => "Unpinned",
+            (true, true) => "Would unpin",
+        },
+        changes.len(),
+        sidecar_path.display()
+    );
+    Ok(())
 }
 
-fn wizard_answers_json_path(
-    base_dir: &Path,
-    flow_id: &str,
-    node_id: &str,
-    mode: wizard_ops::WizardMode,
-) -> PathBuf {
-    answers::answers_paths(base_dir, flow_id, node_id, mode.as_str()).json
-}
-
-fn wizard_answers_json_path_compat(
-    base_dir: &Path,
-    flow_id: &str,
-    node_id: &str,
-    mode: wizard_ops::WizardMode,
-) -> Option<PathBuf> {
-    let primary = wizard_answers_json_path(base_dir, flow_id, node_id, mode);
-    if primary.exists() {
-        return Some(primary);
+fn handle_pins(args: &PinsArgs, format: OutputFormat) -> Result<()> {
+    let mut flow_paths = Vec::new();
+    collect_flow_files(&args.dir, &mut flow_paths)?;
+    flow_paths.sort();
+    let mut pins = Vec::new();
+    for flow_path in &flow_paths {
+        let sidecar_path = sidecar_path_for_flow(flow_path);
+        if !sidecar_path.exists() {
+            continue;
+        }
+        let flow = fs::read_to_string(flow_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|yaml| Ok(compile_flow(load_ygtc_from_str(&yaml)?)?));
+        let (flow, resolve) = match (flow, read_flow_resolve(&sidecar_path)) {
+            (Ok(flow), Ok(resolve)) => (flow, resolve),
+            (Err(err), _) => {
+                eprintln!("WARN {}: skipped ({err})", flow_path.display());
+                continue;
+            }
+            (_, Err(err)) => {
+                eprintln!("WARN {}: skipped ({err})", sidecar_path.display());
+                continue;
+            }
+        };
+        let display = flow_path
+            .strip_prefix(&args.dir)
+            .unwrap_or(flow_path)
+            .display()
+            .to_string();
+        for (node_id, pin) in extract_component_pins_with_sources(&flow, &resolve) {
+            if let Some(source) = pin.source {
+                pins.push((display.clone(), node_id, pin.name, source));
+            }
+        }
     }
-    let legacy = wizard_mode_legacy_label(mode).map(|label| {
-        answers::answers_paths(base_dir, flow_id, node_id, label)
-            .json
-            .to_path_buf()
-    });
-    legacy.filter(|path| path.exists())
-}
+    let floating = pins
+        .iter()
+        .filter(|(_, _, _, source)| source.is_floating())
+        .count();
+    let pinned = pins.len() - floating;
+    let mirrors = MirrorRules::from_env()?;
+    let via = |reference: &str| {
+        mirrors
+            .rewrite(reference)
+            .map(|effective| format!(" (via mirror {effective})"))
+            .unwrap_or_default()
+    };
 
-fn warn_unknown_keys(answers: &QuestionAnswers, questions: &[Question]) {
-    if questions.is_empty() || answers.is_empty() {
-        return;
-    }
-    let mut known = std::collections::BTreeSet::new();
-    for q in questions {
-        known.insert(q.id.as_str());
-    }
-    let mut unknown = Vec::new();
-    for key in answers.keys() {
-        if !known.contains(key.as_str()) {
-            unknown.push(key.clone());
+    if matches!(format, OutputFormat::Json) {
+        let entries: Vec<_> = pins
+            .iter()
+            .map(|(flow, node_id, component, source)| {
+                let mut entry = json!({ "flow": flow, "node_id": node_id, "component": component, "source": source });
+                if let Some(effective) = mirrors.rewrite(&source.reference) {
+                    entry["effective_reference"] = json!(effective);
+                }
+                entry
+            })
+            .collect();
+        print_json_payload(&json!({ "pins": entries, "pinned": pinned, "floating": floating }))?;
+    } else {
+        for (flow, node_id, _, source) in &pins {
+            match &source.reason {
+                Some(reason) => println!(
+                    "FLOAT {flow} node '{node_id}': {} ({reason}){}",
+                    source.reference,
+                    via(&source.reference)
+                ),
+                None if args.all => println!(
+                    "PIN   {flow} node '{node_id}': {}{}",
+                    source.reference,
+                    via(&source.reference)
+                ),
+                None => {}
+            }
         }
+        println!("{pinned} pinned, {floating} floating component reference(s)");
     }
-    if !unknown.is_empty() {
-        eprintln!("warning: unknown answer keys: {}", unknown.join(", "));
+    if args.strict && floating > 0 {
+        anyhow::bail!("{floating} floating component reference(s)");
     }
+    Ok(())
 }
 
-fn confirm_remove_mode(interactive: bool) -> Result<()> {
-    if !interactive {
-        anyhow::bail!("remove mode requires interactive confirmation: Type REMOVE to confirm");
+fn handle_health(args: &HealthArgs, format: OutputFormat) -> Result<()> {
+    let mut flow_paths = Vec::new();
+    collect_flow_files(&args.dir, &mut flow_paths)?;
+    flow_paths.sort();
+    let catalog = args
+        .release_catalog
+        .as_deref()
+        .map(ReleaseCatalog::load_from_file)
+        .transpose()?;
+    let schema_path = PathBuf::from("schemas/ygtc.flow.schema.json");
+    let lint_ctx = LintContext {
+        schema_text: EMBEDDED_FLOW_SCHEMA,
+        schema_label: "embedded ygtc.flow.schema.json",
+        schema_path: schema_path.as_path(),
+        registry: None,
+        rules: &RuleRegistry::builtin(),
+        placeholders: &PlaceholderValuesRule::default(),
+        schema_mode: SchemaMode::Strict,
+        fix: false,
+        baseline: &BaselineMode::Off,
+        report: None,
+        runtime: None,
+        releases: None,
+        env: None,
+        prune_select: &[],
+    };
+
+    let mut lint = HealthTally::default();
+    let mut format_check = HealthTally::default();
+    let mut secrets = HealthTally::default();
+    let mut owners = HealthTally::default();
+    let mut pins = HealthTally::default();
+    let mut upgrades = HealthTally::default();
+    for path in &flow_paths {
+        let display = path
+            .strip_prefix(&args.dir)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) => {
+                lint.fail(HealthFinding::new(&display, format!("unreadable: {err}")));
+                continue;
+            }
+        };
+
+        let (linted, _) = capture_doctor_output(|| lint_flow(&content, Some(path), &lint_ctx));
+        match linted {
+            // Committed credentials are scored by the `secrets` category instead.
+            Ok(result) => lint.check(
+                result
+                    .lint_errors
+                    .iter()
+                    .filter(|diagnostic| diagnostic.rule != "secret_literals")
+                    .map(|diagnostic| HealthFinding::new(&display, diagnostic.to_string())),
+            ),
+            Err(err) => lint.fail(HealthFinding::new(&display, err.to_string())),
+        }
+        match format_canonical(&content) {
+            Ok(formatted) if formatted == content || formatted == strip_comments(&content) => {
+                format_check.pass()
+            }
+            Ok(_) => format_check.fail(HealthFinding::new(
+                &display,
+                "not canonically formatted; run greentic-flow fmt",
+            )),
+            Err(err) => format_check.fail(HealthFinding::new(
+                &display,
+                format!("cannot format: {err}"),
+            )),
+        }
+
+        let Ok(doc) = load_ygtc_from_str(&content) else {
+            continue;
+        };
+        let compiled = compile_flow(doc.clone());
+        let Ok(flow_ir) = FlowIr::from_doc(doc) else {
+            continue;
+        };
+        secrets.check(
+            find_secret_literals(&flow_ir)
+                .into_iter()
+                .map(|(node_id, pointer)| {
+                    HealthFinding::new(
+                        &display,
+                        format!("literal value under a secret-looking key at {pointer}"),
+                    )
+                    .with_node(node_id)
+                }),
+        );
+        if flow_owners(&flow_ir).is_empty() {
+            owners.fail(HealthFinding::new(
+                &display,
+                format!("no meta.{OWNERS_META_KEY}"),
+            ));
+        } else {
+            owners.pass();
+        }
+
+        let sidecar_path = sidecar_path_for_flow(path);
+        let (Ok(flow), Ok(resolve)) = (compiled, read_flow_resolve(&sidecar_path)) else {
+            continue;
+        };
+        let mut pinned_here = 0usize;
+        for (node_id, pin) in extract_component_pins_with_sources(&flow, &resolve) {
+            let Some(source) = pin.source else {
+                continue;
+            };
+            match &source.reason {
+                Some(reason) => pins.fail(
+                    HealthFinding::new(
+                        &display,
+                        format!("floating {} ({reason})", source.reference),
+                    )
+                    .with_node(node_id),
+                ),
+                None => {
+                    pins.pass();
+                    pinned_here += 1;
+                }
+            }
+        }
+        if let Some(catalog) = &catalog {
+            let advisories = collect_upgrade_advisories(path, catalog)?;
+            for _ in advisories.len()..pinned_here {
+                upgrades.pass();
+            }
+            for advisory in advisories {
+                let latest = advisory
+                    .latest()
+                    .map(|candidate| {
+                        format!(
+                            " -> {} ({})",
+                            candidate.version,
+                            upgrade_class_label(candidate.classification)
+                        )
+                    })
+                    .unwrap_or_default();
+                upgrades.fail(
+                    HealthFinding::new(
+                        &display,
+                        format!(
+                            "{} {}{latest}",
+                            advisory.component, advisory.current_version
+                        ),
+                    )
+                    .with_node(advisory.node_id),
+                );
+            }
+        }
     }
-    let mut stdout = io::stdout().lock();
-    write!(stdout, "Type REMOVE to confirm: ").context("write confirmation prompt")?;
-    stdout.flush().context("flush confirmation prompt")?;
-    let mut line = String::new();
-    io::stdin()
-        .read_line(&mut line)
-        .context("read remove confirmation")?;
-    if line.trim() != "REMOVE" {
-        anyhow::bail!("remove cancelled: confirmation did not match 'REMOVE'");
+
+    let scorecard = HealthScorecard::new(
+        flow_paths.len(),
+        vec![
+            lint.into_category("lint"),
+            pins.into_category("pins"),
+            match catalog {
+                Some(_) => upgrades.into_category("upgrades"),
+                None => HealthCategory::skipped("upgrades", "no --release-catalog given"),
+            },
+            secrets.into_category("secrets"),
+            owners.into_category("owners"),
+            format_check.into_category("format"),
+        ],
+    );
+    let below = args.min_score.filter(|min| scorecard.score < *min);
+
+    if args.json || matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": below.is_none(),
+            "dir": args.dir.display().to_string(),
+            "min_score": args.min_score,
+            "scorecard": scorecard,
+        }))?;
+    } else {
+        println!(
+            "health {}/100 for {} ({} flow(s))",
+            scorecard.score,
+            args.dir.display(),
+            scorecard.flows
+        );
+        for category in &scorecard.categories {
+            match (category.score, &category.skipped) {
+                (Some(score), _) => println!(
+                    "  {:<9}{score:>3}  {}/{} passed",
+                    category.name, category.passed, category.total
+                ),
+                (None, reason) => println!(
+                    "  {:<9}  -  skipped: {}",
+                    category.name,
+                    reason.as_deref().unwrap_or("not checked")
+                ),
+            }
+            for finding in &category.findings {
+                match &finding.node_id {
+                    Some(node_id) => {
+                        println!("    {} node '{node_id}': {}", finding.flow, finding.message)
+                    }
+                    None => println!("    {}: {}", finding.flow, finding.message),
+                }
+            }
+        }
+    }
+    if let Some(min) = below {
+        anyhow::bail!(
+            "health score {} is below --min-score {min}",
+            scorecard.score
+        );
     }
     Ok(())
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
-enum AddStepMode {
-    Default,
-    Config,
+/// Running count for one health category.
+#[derive(Default)]
+struct HealthTally {
+    passed: usize,
+    total: usize,
+    findings: Vec<HealthFinding>,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
-enum WizardModeArg {
-    Default,
-    Setup,
-    Update,
-    #[value(name = "upgrade", hide = true)]
-    UpgradeDeprecated,
-    Remove,
-}
+impl HealthTally {
+    fn pass(&mut self) {
+        self.passed += 1;
+        self.total += 1;
+    }
 
-impl WizardModeArg {
-    fn to_mode(self) -> wizard_ops::WizardMode {
-        match self {
-            WizardModeArg::Default => wizard_ops::WizardMode::Default,
-            WizardModeArg::Setup => wizard_ops::WizardMode::Setup,
-            WizardModeArg::Update | WizardModeArg::UpgradeDeprecated => {
-                wizard_ops::WizardMode::Update
-            }
-            WizardModeArg::Remove => wizard_ops::WizardMode::Remove,
+    fn fail(&mut self, finding: HealthFinding) {
+        self.total += 1;
+        self.findings.push(finding);
+    }
+
+    /// One item that passes when it has no findings.
+    fn check(&mut self, findings: impl IntoIterator<Item = HealthFinding>) {
+        let before = self.findings.len();
+        self.findings.extend(findings);
+        self.total += 1;
+        if self.findings.len() == before {
+            self.passed += 1;
         }
     }
-}
 
-#[derive(Args, Debug)]
-struct AddStepArgs {
-    /// Component id to resolve via wizard ops (preferred for new flows).
-    #[arg(value_name = "component_id")]
-    component_id: Option<String>,
-    /// Path to the flow file to modify.
-    #[arg(long = "flow")]
-    flow_path: PathBuf,
-    /// Optional anchor node id; defaults to entrypoint or first node.
-    #[arg(long = "after")]
-    after: Option<String>,
-    /// How to source the node to insert.
-    #[arg(long = "mode", value_enum, default_value = "default")]
-    mode: AddStepMode,
-    /// Optional pack alias for the new node.
-    #[arg(long = "pack-alias")]
-    pack_alias: Option<String>,
-    /// Optional wizard mode (default/setup/update/remove).
-    #[arg(long = "wizard-mode", value_enum)]
-    wizard_mode: Option<WizardModeArg>,
-    /// Optional operation for the new node.
-    #[arg(long = "operation")]
-    operation: Option<String>,
-    /// Payload JSON for the new node (default mode).
-    #[arg(long = "payload", default_value = "{}")]
-    payload: String,
-    /// Routing shorthand: make the new node terminal (out).
-    #[arg(long = "routing-out", conflicts_with_all = ["routing_reply", "routing_next", "routing_multi_to", "routing_json", "routing_to_anchor"])]
-    routing_out: bool,
-    /// Routing shorthand: reply to origin.
-    #[arg(long = "routing-reply", conflicts_with_all = ["routing_out", "routing_next", "routing_multi_to", "routing_json", "routing_to_anchor"])]
-    routing_reply: bool,
-    /// Route to a specific node id.
-    #[arg(long = "routing-next", conflicts_with_all = ["routing_out", "routing_reply", "routing_multi_to", "routing_json"])]
-    routing_next: Option<String>,
-    /// Route to multiple node ids (comma-separated).
-    #[arg(long = "routing-multi-to", conflicts_with_all = ["routing_out", "routing_reply", "routing_next", "routing_json"])]
-    routing_multi_to: Option<String>,
-    /// Explicit routing JSON file (escape hatch).
-    #[arg(long = "routing-json", conflicts_with_all = ["routing_out", "routing_reply", "routing_next", "routing_multi_to"])]
-    routing_json: Option<PathBuf>,
-    /// Explicitly thread to the anchor’s existing targets (default if no routing flag is given).
-    #[arg(long = "routing-to-anchor", conflicts_with_all = ["routing_out", "routing_reply", "routing_next", "routing_multi_to", "routing_json"])]
-    routing_to_anchor: bool,
-    /// Config flow file to execute (config mode).
-    #[arg(long = "config-flow")]
-    config_flow: Option<PathBuf>,
-    /// Answers JSON for config mode.
-    #[arg(long = "answers")]
-    answers: Option<String>,
-    /// Answers file (JSON) for config mode.
-    #[arg(long = "answers-file")]
-    answers_file: Option<PathBuf>,
-    /// Directory for wizard answers artifacts.
-    #[arg(long = "answers-dir")]
-    answers_dir: Option<PathBuf>,
-    /// Overwrite existing answers artifacts.
-    #[arg(long = "overwrite-answers")]
-    overwrite_answers: bool,
-    /// Force re-asking wizard questions even if answers exist.
-    #[arg(long = "reask")]
-    reask: bool,
-    /// Locale (BCP47) for wizard prompts.
-    #[arg(long = "locale")]
-    locale: Option<String>,
-    /// Allow interactive QA prompts (wizard mode only).
-    #[arg(long = "interactive")]
-    interactive: bool,
-    /// Allow cycles/back-edges during insertion.
-    #[arg(long = "allow-cycles")]
-    allow_cycles: bool,
-    /// Show the updated flow without writing it.
-    #[arg(long = "dry-run")]
-    dry_run: bool,
-    /// Backward-compatible write flag (ignored; writing is default).
-    #[arg(long = "write", hide = true)]
-    write: bool,
-    /// Validate only without writing output.
-    #[arg(long = "validate-only")]
-    validate_only: bool,
-    /// Optional component manifest paths for catalog validation or config flow discovery.
-    #[arg(long = "manifest")]
-    manifests: Vec<PathBuf>,
-    /// Optional node id override.
-    #[arg(long = "node-id")]
-    node_id: Option<String>,
-    /// Remote component reference (oci://, repo://, store://, etc.) for sidecar binding.
-    #[arg(long = "component")]
-    component_ref: Option<String>,
-    /// Local wasm path for sidecar binding (relative to the flow file).
-    #[arg(long = "local-wasm")]
-    local_wasm: Option<PathBuf>,
-    /// Distributor URL for component-id resolution.
-    #[arg(long = "distributor-url")]
-    distributor_url: Option<String>,
-    /// Distributor auth token (optional).
-    #[arg(long = "auth-token")]
-    auth_token: Option<String>,
-    /// Tenant id for component-id resolution.
-    #[arg(long = "tenant")]
-    tenant: Option<String>,
-    /// Environment id for component-id resolution.
-    #[arg(long = "env")]
-    env: Option<String>,
-    /// Pack id for component-id resolution.
-    #[arg(long = "pack")]
-    pack: Option<String>,
-    /// Component version for component-id resolution.
-    #[arg(long = "component-version")]
-    component_version: Option<String>,
-    /// ABI version override for wizard ops.
-    #[arg(long = "abi-version")]
-    abi_version: Option<String>,
-    /// Resolver override (fixture://...) for tests/CI.
-    #[arg(long = "resolver")]
-    resolver: Option<String>,
-    /// Pin the component (resolve tag to digest or hash local wasm).
-    #[arg(long = "pin")]
-    pin: bool,
-    /// Allow contract drift when describe_hash changes.
-    #[arg(long = "allow-contract-change")]
-    allow_contract_change: bool,
+    fn into_category(self, name: &str) -> HealthCategory {
+        HealthCategory::checked(name, self.passed, self.total, self.findings)
+    }
 }
 
-#[derive(Args, Debug)]
-struct BindComponentArgs {
-    /// Path to the flow file to modify.
-    #[arg(long = "flow")]
-    flow_path: PathBuf,
-    /// Node id to bind.
-    #[arg(long = "step")]
-    step: String,
-    /// Remote component reference (oci://, repo://, store://, etc.).
-    #[arg(long = "component")]
-    component_ref: Option<String>,
-    /// Local wasm path (relative to the flow file).
-    #[arg(long = "local-wasm")]
-    local_wasm: Option<PathBuf>,
-    /// Pin the component (resolve tag to digest or hash local wasm).
-    #[arg(long = "pin")]
-    pin: bool,
-    /// Write back to the sidecar.
-    #[arg(long = "write")]
-    write: bool,
+fn handle_index_rebuild(args: &IndexRebuildArgs, format: OutputFormat) -> Result<()> {
+    let mut flow_paths = Vec::new();
+    collect_flow_files(&args.dir, &mut flow_paths)?;
+    flow_paths.sort();
+    let mut index = WorkspaceIndex::default();
+    let report = index.refresh(&args.dir, &flow_paths)?;
+    let path = index.save(&args.dir)?;
+    for (flow, reason) in &report.skipped {
+        eprintln!("WARN {flow}: skipped ({reason})");
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "index": path.display().to_string(),
+            "flows": index.flows.len(),
+            "skipped": report.skipped.iter().map(|(flow, _)| flow).collect::<Vec<_>>(),
+        }))?;
+    } else {
+        println!(
+            "Indexed {} flow(s) into {}",
+            index.flows.len(),
+            path.display()
+        );
+    }
+    Ok(())
 }
 
-fn build_routing_value(args: &AddStepArgs) -> Result<(Option<serde_json::Value>, bool)> {
-    if let Some(path) = &args.routing_json {
-        let text = fs::read_to_string(path)
-            .with_context(|| format!("read routing json {}", path.display()))?;
-        let parsed: serde_json::Value =
-            serde_json::from_str(&text).context("parse --routing-json as JSON")?;
-        return Ok((Some(parsed), false));
+fn handle_index_query(args: &IndexQueryArgs, format: OutputFormat) -> Result<()> {
+    let path = index_path(&args.dir);
+    if !path.is_file() {
+        anyhow::bail!(
+            "no index at {} (run `greentic-flow index rebuild --dir {}`)",
+            path.display(),
+            args.dir.display()
+        );
     }
-    if args.routing_out {
-        return Ok((Some(serde_json::Value::String("out".to_string())), false));
+    let mut index = WorkspaceIndex::load(&path)?;
+    // Freshness check: only flows whose file or sidecar changed since indexing are re-parsed.
+    let mut flow_paths = Vec::new();
+    collect_flow_files(&args.dir, &mut flow_paths)?;
+    flow_paths.sort();
+    let report = index.refresh(&args.dir, &flow_paths)?;
+    if report.changed() {
+        index.save(&args.dir)?;
+    }
+    for (flow, reason) in &report.skipped {
+        eprintln!("WARN {flow}: skipped ({reason})");
+    }
+    let query = IndexQuery {
+        component: args.component.clone(),
+        tag: args.tag.clone(),
+        operation: args.operation.clone(),
+    };
+    let matches = index.query(&query);
+    let flows: BTreeSet<&str> = matches.iter().map(|m| m.flow.as_str()).collect();
+
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "matches": matches,
+            "flows": flows.len(),
+            "refreshed": report.updated,
+            "removed": report.removed,
+        }))?;
+    } else {
+        for found in &matches {
+            let component = found
+                .component
+                .as_deref()
+                .map(|component| format!(" -> {component}"))
+                .unwrap_or_default();
+            println!(
+                "{} node '{}': {}{component}",
+                found.flow, found.node_id, found.operation
+            );
+        }
+        println!(
+            "{} node(s) in {} flow(s) ({} indexed, {} refreshed)",
+            matches.len(),
+            flows.len(),
+            index.flows.len(),
+            report.updated.len()
+        );
     }
-    if args.routing_reply {
-        return Ok((Some(serde_json::Value::String("reply".to_string())), false));
+    Ok(())
+}
+
+fn handle_lock(args: &LockArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    let mut registry = ResolverRegistry::current();
+    if let Some(fixture) = args
+        .resolver
+        .as_deref()
+        .and_then(FixtureResolver::from_override)
+    {
+        registry = registry.with(Arc::new(fixture));
     }
-    if let Some(next) = &args.routing_next {
-        return Ok((Some(json!([{ "to": next }])), false));
+    let lock_path = args
+        .lockfile
+        .clone()
+        .unwrap_or_else(|| args.dir.join(LOCKFILE_NAME));
+    let whole_project = args.flows.is_empty();
+    let flows = if whole_project {
+        let mut found = Vec::new();
+        collect_flow_files(&args.dir, &mut found)?;
+        found.sort();
+        found
+    } else {
+        args.flows.clone()
+    };
+
+    if args.check {
+        if !lock_path.exists() {
+            anyhow::bail!(
+                "{} does not exist; run greentic-flow lock to create it",
+                lock_path.display()
+            );
+        }
+        let drift = if whole_project {
+            let current = lock_flows(&registry, &lock_path, &flows)?;
+            diff_lock(&FlowLock::load(&lock_path)?, &current, true)
+        } else {
+            verify_flows(&registry, &lock_path, &flows)?
+        };
+        if matches!(format, OutputFormat::Json) {
+            print_json_payload(&json!({
+                "ok": drift.is_empty(),
+                "lockfile": lock_path.display().to_string(),
+                "flows": flows.len(),
+                "drift": drift,
+            }))?;
+        } else {
+            for entry in &drift {
+                println!("DRIFT {entry}");
+            }
+            if drift.is_empty() {
+                println!("{} flow(s) match {}", flows.len(), lock_path.display());
+            }
+        }
+        if !drift.is_empty() {
+            anyhow::bail!(
+                "{} node(s) differ from {}; run greentic-flow lock to update it",
+                drift.len(),
+                lock_path.display()
+            );
+        }
+        return Ok(());
     }
-    if let Some(multi) = &args.routing_multi_to {
-        let targets: Vec<_> = multi
-            .split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .collect();
-        if targets.is_empty() {
-            anyhow::bail!("--routing-multi-to requires at least one target");
+
+    let lock = if whole_project {
+        lock_flows(&registry, &lock_path, &flows)?
+    } else {
+        let mut lock = if lock_path.exists() {
+            FlowLock::load(&lock_path)?
+        } else {
+            FlowLock::default()
+        };
+        for flow_path in &flows {
+            let key = baseline_flow_key(&lock_path, flow_path);
+            let locked = lock_flow(&registry, flow_path)?;
+            if locked.is_empty() {
+                lock.flows.remove(&key);
+            } else {
+                lock.flows.insert(key, locked);
+            }
         }
-        let routes: Vec<_> = targets.into_iter().map(|t| json!({ "to": t })).collect();
-        return Ok((Some(serde_json::Value::Array(routes)), false));
+        lock
+    };
+    let text = lock.to_json_string();
+    let changed = fs::read_to_string(&lock_path).ok().as_deref() != Some(text.as_str());
+    if changed {
+        write_flow_file(&lock_path, &text, true, backup)?;
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": true,
+            "lockfile": lock_path.display().to_string(),
+            "changed": changed,
+            "flows": lock.flows.len(),
+            "components": lock.components(),
+        }))?;
+    } else {
+        println!(
+            "{} {} component(s) across {} flow(s) in {}",
+            if changed { "Locked" } else { "Unchanged:" },
+            lock.components(),
+            lock.flows.len(),
+            lock_path.display()
+        );
     }
-    // Default: thread to anchor routes (placeholder-based internally).
-    let placeholder = json!([{ "to": greentic_flow::splice::NEXT_NODE_PLACEHOLDER }]);
-    Ok((Some(placeholder), true))
+    Ok(())
 }
 
-fn build_update_routing(
-    args: &UpdateStepArgs,
-) -> Result<Option<Vec<greentic_flow::flow_ir::Route>>> {
-    if let Some(path) = &args.routing_json {
-        let text = fs::read_to_string(path)
-            .with_context(|| format!("read routing json {}", path.display()))?;
-        let routes = parse_routing_arg(&text)?;
-        return Ok(Some(routes));
+fn load_flow_bundle(flow_path: &Path) -> Result<FlowBundle> {
+    let yaml = fs::read_to_string(flow_path)
+        .with_context(|| format!("failed to read {}", flow_path.display()))?;
+    load_and_validate_bundle(&yaml, Some(flow_path))
+        .with_context(|| format!("failed to load {}", flow_path.display()))
+}
+
+fn handle_bundle_pack(args: &BundlePackArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    if args.out.is_some() && args.flow_paths.len() > 1 {
+        anyhow::bail!(
+            "--out takes a single --flow; with several flows each bundle goes next to its flow"
+        );
     }
-    if args.routing_out {
-        return Ok(Some(vec![greentic_flow::flow_ir::Route {
-            out: true,
-            ..greentic_flow::flow_ir::Route::default()
-        }]));
+    let key = args
+        .key
+        .as_ref()
+        .map(|key_path| {
+            let key_text = fs::read_to_string(key_path)
+                .with_context(|| format!("failed to read {}", key_path.display()))?;
+            signing_key_from_hex(&key_text)
+                .with_context(|| format!("failed to parse {}", key_path.display()))
+        })
+        .transpose()?;
+
+    // Load and order every flow before writing anything, so a cycle leaves no partial pack.
+    let mut flows: IndexMap<String, (PathBuf, FlowBundle)> = IndexMap::new();
+    let mut requires: IndexMap<String, Vec<String>> = IndexMap::new();
+    for flow_path in &args.flow_paths {
+        let bundle = load_flow_bundle(flow_path)?.with_release(BundleRelease::new(args.channel));
+        let doc = load_ygtc_from_path(flow_path)?;
+        let required = flow_deps::required_flows(doc.meta.as_ref())
+            .with_context(|| format!("failed to load {}", flow_path.display()))?;
+        if let Some((other, _)) = flows.get(&bundle.id) {
+            anyhow::bail!(
+                "flows {} and {} share the id '{}'",
+                other.display(),
+                flow_path.display(),
+                bundle.id
+            );
+        }
+        requires.insert(bundle.id.clone(), required);
+        flows.insert(bundle.id.clone(), (flow_path.clone(), bundle));
     }
-    if args.routing_reply {
-        return Ok(Some(vec![greentic_flow::flow_ir::Route {
-            reply: true,
-            ..greentic_flow::flow_ir::Route::default()
-        }]));
+    let manifest_path = args.manifest.clone().or_else(|| {
+        (args.flow_paths.len() > 1).then(|| {
+            args.flow_paths[0]
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join("flows.manifest.json")
+        })
+    });
+    let order = if manifest_path.is_some() {
+        flow_deps::dependency_order(&requires)?
+    } else {
+        flows.keys().cloned().collect()
+    };
+
+    let mut packed = Vec::new();
+    for id in &order {
+        let (flow_path, bundle) = &flows[id];
+        let out = args
+            .out
+            .clone()
+            .unwrap_or_else(|| bundle_path_for_flow(flow_path));
+        let mut text = serde_json::to_string_pretty(bundle)?;
+        text.push('\n');
+        write_flow_file(&out, &text, true, backup)?;
+        let signature = match &key {
+            Some(key) => {
+                let signature = sign_bundle(bundle, key);
+                let signature_path = signature_path_for_flow(&out);
+                write_flow_file(&signature_path, &signature.to_json_string(), true, backup)?;
+                Some((signature_path, signature))
+            }
+            None => None,
+        };
+        packed.push((flow_path, bundle, out, signature));
     }
-    if let Some(next) = &args.routing_next {
-        return Ok(Some(vec![greentic_flow::flow_ir::Route {
-            to: Some(next.clone()),
-            ..greentic_flow::flow_ir::Route::default()
-        }]));
+    if let Some(manifest_path) = &manifest_path {
+        let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+        let manifest = FlowManifest {
+            version: FLOW_MANIFEST_VERSION,
+            flows: packed
+                .iter()
+                .map(|(_, bundle, out, _)| FlowManifestEntry {
+                    id: bundle.id.clone(),
+                    bundle: diff_paths(out, manifest_dir)
+                        .unwrap_or_else(|| out.clone())
+                        .display()
+                        .to_string(),
+                    hash_blake3: bundle.hash_blake3.clone(),
+                    requires: requires[&bundle.id].clone(),
+                })
+                .collect(),
+        };
+        let mut text = serde_json::to_string_pretty(&manifest)?;
+        text.push('\n');
+        write_flow_file(manifest_path, &text, true, backup)?;
     }
-    if let Some(multi) = &args.routing_multi_to {
-        let targets: Vec<_> = multi
-            .split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .collect();
-        if targets.is_empty() {
-            anyhow::bail!("--routing-multi-to requires at least one target");
-        }
-        let routes = targets
-            .into_iter()
-            .map(|t| greentic_flow::flow_ir::Route {
-                to: Some(t.to_string()),
-                ..greentic_flow::flow_ir::Route::default()
+
+    if matches!(format, OutputFormat::Json) {
+        let entries: Vec<serde_json::Value> = packed
+            .iter()
+            .map(|(flow_path, bundle, out, signature)| {
+                json!({
+                    "flow": flow_path.display().to_string(),
+                    "bundle_path": out.display().to_string(),
+                    "hash_blake3": bundle.hash_blake3,
+                    "release": bundle.release,
+                    "signature_path": signature.as_ref().map(|(path, _)| path.display().to_string()),
+                })
             })
             .collect();
-        return Ok(Some(routes));
+        let payload = match (&manifest_path, entries.as_slice()) {
+            (None, [entry]) => {
+                let mut payload = entry.clone();
+                payload["ok"] = json!(true);
+                payload
+            }
+            _ => json!({
+                "ok": true,
+                "manifest_path": manifest_path.as_ref().map(|path| path.display().to_string()),
+                "bundles": entries,
+            }),
+        };
+        print_json_payload(&payload)?;
+    } else {
+        for (flow_path, bundle, out, signature) in &packed {
+            println!(
+                "Packed {} ({}, {}) -> {}",
+                flow_path.display(),
+                args.channel,
+                bundle.hash_blake3,
+                out.display()
+            );
+            if let Some((path, signature)) = signature {
+                println!(
+                    "Signed with key {} -> {}",
+                    signature.public_key,
+                    path.display()
+                );
+            }
+        }
+        if let Some(manifest_path) = &manifest_path {
+            println!(
+                "Wrote flow manifest ({} flow(s) in dependency order) -> {}",
+                packed.len(),
+                manifest_path.display()
+            );
+        }
     }
-    Ok(None)
+    Ok(())
 }
 
-fn infer_node_id_hint(args: &AddStepArgs) -> Option<String> {
-    if let Some(explicit) = args.node_id.clone() {
-        return Some(explicit);
-    }
-    if let Some(comp_ref) = &args.component_ref {
-        let trimmed = comp_ref
-            .trim_start_matches("oci://")
-            .trim_start_matches("repo://")
-            .trim_start_matches("store://");
-        let last = trimmed.rsplit(['/', '\\']).next()?;
-        let base = last.split([':', '@']).next().unwrap_or(last);
-        if !base.is_empty() {
-            return Some(base.replace('_', "-"));
+/// `doctor --expect-channel`: check deployed bundle files the way a production host would
+/// before loading them.
+fn handle_doctor_deployed_bundles(
+    targets: &[PathBuf],
+    channel: ReleaseChannel,
+    public_key: &Path,
+    json_output: bool,
+) -> Result<()> {
+    let key_text = fs::read_to_string(public_key)
+        .with_context(|| format!("failed to read {}", public_key.display()))?;
+    let trusted = verifying_key_from_hex(&key_text)
+        .with_context(|| format!("failed to parse {}", public_key.display()))?;
+    let mut paths = Vec::new();
+    for target in targets {
+        if target.is_dir() {
+            let mut found = Vec::new();
+            collect_bundle_files(target, &mut found)?;
+            found.sort();
+            paths.extend(found);
+        } else {
+            paths.push(target.clone());
         }
     }
-    if let Some(path) = &args.local_wasm
-        && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
-    {
-        let normalized = stem.replace('_', "-");
-        if !normalized.is_empty() {
-            return Some(normalized);
-        }
+    if paths.is_empty() {
+        anyhow::bail!("no *{BUNDLE_FILE_SUFFIX} files found");
     }
-    None
-}
 
-fn resolve_step_id(
-    step: Option<String>,
-    component_id: Option<&String>,
-    meta: &Option<serde_json::Value>,
-) -> Result<String> {
-    if let Some(step) = step {
-        return Ok(step);
+    let mut results = Vec::new();
+    for path in &paths {
+        let result = load_bundle(path).and_then(|bundle| {
+            let signature = BundleSignature::load(&signature_path_for_flow(path))?;
+            verify_deployed_bundle(&bundle, &signature, &trusted, channel)?;
+            Ok(bundle)
+        });
+        match &result {
+            Ok(bundle) if !json_output => {
+                println!("OK  {} ({}, {channel})", path.display(), bundle.id)
+            }
+            Err(err) if !json_output => eprintln!("ERR {}: {err:#}", path.display()),
+            _ => {}
+        }
+        results.push((path, result));
     }
-    if let Some(component_id) = component_id {
-        return flow_meta::find_node_for_component(meta, component_id);
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    if json_output {
+        print_json_payload(&json!({
+            "ok": failed == 0,
+            "channel": channel,
+            "bundles": results
+                .iter()
+                .map(|(path, result)| json!({
+                    "path": path.display().to_string(),
+                    "ok": result.is_ok(),
+                    "flow_id": result.as_ref().ok().map(|bundle| bundle.id.clone()),
+                    "error": result.as_ref().err().map(|err| format!("{err:#}")),
+                }))
+                .collect::<Vec<_>>(),
+        }))?;
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{failed} of {} bundle(s) failed {channel} channel verification",
+            results.len()
+        );
     }
-    anyhow::bail!("--step or component_id is required")
+    Ok(())
 }
 
-fn handle_add_step(
-    args: AddStepArgs,
-    schema_mode: SchemaMode,
-    format: OutputFormat,
-    backup: bool,
-) -> Result<()> {
-    handle_add_step_with_qa_io(args, schema_mode, format, backup, None)
+fn handle_bundle_sign(args: &BundleSignArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    let key_text = fs::read_to_string(&args.key)
+        .with_context(|| format!("failed to read {}", args.key.display()))?;
+    let key = signing_key_from_hex(&key_text)
+        .with_context(|| format!("failed to parse {}", args.key.display()))?;
+    let bundle = load_flow_bundle(&args.flow_path)?;
+    let signature = sign_bundle(&bundle, &key);
+    let out = args
+        .out
+        .clone()
+        .unwrap_or_else(|| signature_path_for_flow(&args.flow_path));
+    write_flow_file(&out, &signature.to_json_string(), true, backup)?;
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": true,
+            "flow": args.flow_path.display().to_string(),
+            "signature_path": out.display().to_string(),
+            "signature": signature,
+        }))?;
+    } else {
+        println!(
+            "Signed {} ({}) with key {} -> {}",
+            args.flow_path.display(),
+            bundle.hash_blake3,
+            signature.public_key,
+            out.display()
+        );
+    }
+    Ok(())
 }
 
-fn handle_add_step_with_qa_io(
-    args: AddStepArgs,
-    schema_mode: SchemaMode,
-    format: OutputFormat,
-    backup: bool,
-    qa_io: Option<&mut QaInteractiveIo<'_>>,
-) -> Result<()> {
-    let (routing_value, require_placeholder) = build_routing_value(&args)?;
-    let component_identity = args
-        .component_id
+fn handle_bundle_verify(args: &BundleVerifyArgs, format: OutputFormat) -> Result<()> {
+    let key_text = fs::read_to_string(&args.public_key)
+        .with_context(|| format!("failed to read {}", args.public_key.display()))?;
+    let trusted = verifying_key_from_hex(&key_text)
+        .with_context(|| format!("failed to parse {}", args.public_key.display()))?;
+    let signature_path = args
+        .signature
         .clone()
-        .or_else(|| args.component_ref.clone())
-        .or_else(|| {
-            args.local_wasm
-                .as_ref()
-                .and_then(|p| p.file_stem().and_then(|s| s.to_str()))
-                .map(|s| s.to_string())
-        })
-        .unwrap_or_else(|| "component".to_string());
-
-    let wizard_requested = args.component_id.is_some() || args.wizard_mode.is_some();
-    if wizard_requested {
-        let (sidecar_path, mut sidecar) = ensure_sidecar(&args.flow_path)?;
-        let doc = load_ygtc_from_path(&args.flow_path)?;
-        let flow_ir = FlowIr::from_doc(doc)?;
-        let wizard_mode_arg = args.wizard_mode.unwrap_or(WizardModeArg::Default);
-        let deprecation_diagnostic = warn_deprecated_wizard_mode(wizard_mode_arg);
-        let wizard_mode = wizard_mode_arg.to_mode();
-        let resolved = resolve_wizard_component(
-            &args.flow_path,
-            wizard_mode,
-            args.local_wasm.as_ref(),
-            args.component_ref.as_ref(),
-            args.component_id.as_ref(),
-            args.resolver.as_ref(),
-            args.distributor_url.as_ref(),
-            args.auth_token.as_ref(),
-            args.tenant.as_ref(),
-            args.env.as_ref(),
-            args.pack.as_ref(),
-            args.component_version.as_ref(),
-        )?;
-        let spec = if let Some(fixture) = resolved.fixture.as_ref() {
-            wizard_ops::WizardSpecOutput {
-                abi: fixture.abi,
-                describe_cbor: fixture.describe_cbor.clone(),
-                descriptor: None,
-                qa_spec_cbor: fixture.qa_spec_cbor.clone(),
-                answers_schema_cbor: None,
+        .unwrap_or_else(|| signature_path_for_flow(&args.flow_path));
+    let signature = BundleSignature::load(&signature_path)?;
+    let bundle = load_flow_bundle(&args.flow_path)?;
+    let result = verify_bundle_signature(&bundle, &signature, &trusted);
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": result.is_ok(),
+            "flow": args.flow_path.display().to_string(),
+            "signature_path": signature_path.display().to_string(),
+            "hash_blake3": bundle.hash_blake3,
+            "error": result.as_ref().err().map(|err| err.to_string()),
+        }))?;
+    }
+    result.with_context(|| {
+        format!(
+            "{} does not verify against {}",
+            args.flow_path.display(),
+            signature_path.display()
+        )
+    })?;
+    if !matches!(format, OutputFormat::Json) {
+        println!(
+            "OK  {} signed by {}",
+            args.flow_path.display(),
+            signature.public_key
+        );
+    }
+    Ok(())
+}
+
+fn handle_state_keys(args: &StateKeysArgs, format: OutputFormat) -> Result<()> {
+    let mut flow_paths = Vec::new();
+    collect_flow_files(&args.dir, &mut flow_paths)?;
+    flow_paths.sort();
+    let mut report = StateKeyReport::default();
+    for flow_path in &flow_paths {
+        let display = flow_path
+            .strip_prefix(&args.dir)
+            .unwrap_or(flow_path)
+            .display()
+            .to_string();
+        let flow: serde_json::Value = match fs::read_to_string(flow_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|yaml| Ok(serde_yaml_bw::from_str(&yaml)?))
+        {
+            Ok(flow) => flow,
+            Err(err) => {
+                eprintln!("WARN {display}: skipped ({err})");
+                continue;
             }
-        } else {
-            wizard_ops::fetch_wizard_spec(&resolved.wasm_bytes, wizard_mode)
-                .map_err(|err| wrap_wizard_error(err, &component_identity, "describe", None))?
         };
-        let qa_spec = wizard_ops::decode_component_qa_spec(&spec.qa_spec_cbor, wizard_mode)?;
-        let (mut catalog, locale) = default_i18n_catalog(args.locale.as_deref());
-        merge_component_i18n_catalog(&mut catalog, &locale, &args.flow_path, &resolved.source);
-
-        let mut answers = parse_answers_map(args.answers.as_deref(), args.answers_file.as_deref())?;
-        wizard_ops::merge_default_answers(&qa_spec, &mut answers);
-        if args.interactive && matches!(wizard_mode, wizard_ops::WizardMode::Default) {
-            seed_optional_answers_for_default_setup(&qa_spec, &mut answers);
+        let user = StateKeyUser {
+            flow: display.clone(),
+            node_id: None,
+            component: None,
+            dev_flow: None,
+            questions_node: String::new(),
+            question: String::new(),
+        };
+        if flow.get("type").and_then(|v| v.as_str()) == Some("component-config") {
+            report.add_config_flow(&flow, &user);
+            continue;
         }
-        if !qa_spec.questions.is_empty() {
-            qa_runner::warn_unknown_keys(&answers, &qa_spec, &catalog, &locale);
-            println!(
-                "{}",
-                wizard_header(&component_identity, wizard_mode.as_str())
-            );
-            answers = run_component_qa_with_qa_lib(
-                &qa_spec,
-                &catalog,
-                &locale,
-                answers,
-                args.interactive,
-                qa_io,
-            )?;
+        let Ok(sidecar) = read_flow_resolve(&sidecar_path_for_flow(flow_path)) else {
+            continue;
+        };
+        for (node_id, entry) in &sidecar.nodes {
+            let Some(manifest) = resolve_component_manifest_path(&entry.source, flow_path)
+                .ok()
+                .and_then(|path| load_manifest_json(&path).ok())
+            else {
+                continue;
+            };
+            let Some(dev_flows) = manifest.get("dev_flows").and_then(|v| v.as_object()) else {
+                continue;
+            };
+            let component = ComponentSource::from_sidecar(&entry.source, entry.mode).reference;
+            for (name, dev_flow) in dev_flows {
+                let Some(graph) = dev_flow.get("graph") else {
+                    continue;
+                };
+                report.add_config_flow(
+                    graph,
+                    &StateKeyUser {
+                        node_id: Some(node_id.clone()),
+                        component: Some(component.clone()),
+                        dev_flow: Some(name.clone()),
+                        ..user.clone()
+                    },
+                );
+            }
         }
+    }
+    let shared: Vec<&str> = report.shared_keys().collect();
 
-        let answers_cbor = wizard_ops::answers_to_cbor(&answers)?;
-        let current_config = wizard_ops::empty_cbor_map();
-        let config_cbor = if let Some(fixture) = resolved.fixture.as_ref() {
-            fixture.apply_answers_cbor.clone()
-        } else {
-            wizard_ops::apply_wizard_answers(
-                &resolved.wasm_bytes,
-                spec.abi,
-                wizard_mode,
-                &current_config,
-                &answers_cbor,
-            )
-            .map_err(|err| wrap_wizard_error(err, &component_identity, "apply-answers", None))?
-        };
-        let operation_id = args.operation.clone().unwrap_or_else(|| "run".to_string());
-        let config_json = wizard_ops::cbor_to_json(&config_cbor)?;
-        ensure_wizard_config_not_error(&component_identity, wizard_mode, &config_json)?;
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({ "keys": report.keys, "shared": shared }))?;
+    } else {
+        for (key, users) in &report.keys {
+            let marker = if shared.contains(&key.as_str()) {
+                " (shared)"
+            } else {
+                ""
+            };
+            println!("{key}{marker}");
+            for user in users {
+                let mut writer = user.flow.clone();
+                if let Some(node_id) = &user.node_id {
+                    writer.push_str(&format!(" node '{node_id}'"));
+                }
+                if let (Some(component), Some(dev_flow)) = (&user.component, &user.dev_flow) {
+                    writer.push_str(&format!(" {component} dev_flows.{dev_flow}"));
+                }
+                println!("  {writer}: {}.{}", user.questions_node, user.question);
+            }
+        }
+        println!(
+            "{} state key(s), {} shared",
+            report.keys.len(),
+            shared.len()
+        );
+    }
+    if args.strict && !shared.is_empty() {
+        anyhow::bail!(
+            "{} state key(s) written by more than one wizard: {}",
+            shared.len(),
+            shared.join(", ")
+        );
+    }
+    Ok(())
+}
 
-        let operation = operation_id;
-        let contract_meta = spec
-            .descriptor
-            .as_ref()
-            .map(|descriptor| derive_contract_meta_from_descriptor(descriptor, &operation))
-            .transpose()?
-            .map(|(_, meta)| meta);
-        let routing_json = routing_value
-            .clone()
-            .unwrap_or(serde_json::Value::Array(Vec::new()));
-        let component_id_label = component_identity.clone();
-        let node_value = json!({
-            "component.exec": {
-                "component": component_id_label,
-                "config": config_json
-            },
-            "operation": operation,
-            "routing": routing_json
-        });
+fn handle_diff(args: &DiffArgs, format: OutputFormat) -> Result<()> {
+    let load = |path: &Path| -> Result<FlowIr> {
+        let doc = load_ygtc_from_path(path)
+            .with_context(|| format!("failed to load {}", path.display()))?;
+        Ok(FlowIr::from_doc(doc)?)
+    };
+    let diff = diff_flows(&load(&args.old)?, &load(&args.new)?);
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "old": args.old.display().to_string(),
+            "new": args.new.display().to_string(),
+            "identical": diff.is_empty(),
+            "diff": diff,
+        }))?;
+    } else if diff.is_empty() {
+        println!("no changes");
+    } else {
+        print!("{}", diff.render_text());
+    }
+    Ok(())
+}
 
-        let mut node_id_hint =
-            infer_node_id_hint(&args).or_else(|| Some(component_identity.clone()));
-        if args.node_id.is_none() {
-            node_id_hint = normalize_node_id_hint(node_id_hint, &node_value);
+fn handle_show(args: &ShowArgs, format: OutputFormat) -> Result<()> {
+    let then = history::flow_at(&args.flow_path, &args.at)?;
+    let revision = &then.revision;
+    let heading = format!(
+        "{} at {} ({}, {:?})",
+        args.flow_path.display(),
+        history::short(&revision.commit),
+        utc_date(revision.committed_at),
+        revision.summary
+    );
+    if !args.diff {
+        if matches!(format, OutputFormat::Json) {
+            print_json_payload(&json!({
+                "flow": args.flow_path.display().to_string(),
+                "at": args.at,
+                "revision": revision,
+                "yaml": then.yaml,
+                "sidecar": then.sidecar,
+            }))?;
+        } else {
+            println!("# {heading}");
+            print!("{}", then.yaml);
+            if let Some(sidecar) = &then.sidecar {
+                println!("# sidecar");
+                println!("{}", serde_json::to_string_pretty(sidecar)?);
+            }
         }
+        return Ok(());
+    }
 
-        let spec_plan = AddStepSpec {
-            after: args.after.clone(),
-            node_id_hint,
-            node: node_value,
-            allow_cycles: args.allow_cycles,
-            require_placeholder,
+    let old = FlowIr::from_doc(
+        load_ygtc_from_str(&then.yaml).with_context(|| format!("failed to load {heading}"))?,
+    )?;
+    let new = FlowIr::from_doc(
+        load_ygtc_from_path(&args.flow_path)
+            .with_context(|| format!("failed to load {}", args.flow_path.display()))?,
+    )?;
+    let diff = diff_flows(&old, &new);
+    let sidecar_path = sidecar_path_for_flow(&args.flow_path);
+    let current_sidecar: Option<serde_json::Value> = match fs::read_to_string(&sidecar_path) {
+        Ok(text) => Some(
+            serde_json::from_str(&text)
+                .with_context(|| format!("failed to parse {}", sidecar_path.display()))?,
+        ),
+        Err(_) => None,
+    };
+    let sidecar_changes =
+        history::diff_sidecar_nodes(then.sidecar.as_ref(), current_sidecar.as_ref());
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "flow": args.flow_path.display().to_string(),
+            "at": args.at,
+            "revision": revision,
+            "identical": diff.is_empty() && sidecar_changes.is_empty(),
+            "diff": diff,
+            "sidecar_changes": sidecar_changes,
+        }))?;
+        return Ok(());
+    }
+    println!("# {heading} -> working tree");
+    if diff.is_empty() {
+        println!("flow: no changes");
+    } else {
+        print!("{}", diff.render_text());
+    }
+    if sidecar_changes.is_empty() {
+        println!("sidecar: no changes");
+    }
+    for change in &sidecar_changes {
+        let binding = |entry: &Option<serde_json::Value>| {
+            entry
+                .as_ref()
+                .map(|entry| entry["source"].to_string())
+                .unwrap_or_default()
         };
+        match (&change.before, &change.after) {
+            (None, Some(_)) => {
+                println!("sidecar + {}: {}", change.node_id, binding(&change.after))
+            }
+            (Some(_), None) => {
+                println!("sidecar - {}: {}", change.node_id, binding(&change.before))
+            }
+            _ => println!(
+                "sidecar ~ {}: {} => {}",
+                change.node_id,
+                binding(&change.before),
+                binding(&change.after)
+            ),
+        }
+    }
+    Ok(())
+}
 
-        let empty_paths: Vec<PathBuf> = Vec::new();
-        let empty_catalog = ManifestCatalog::load_from_paths(&empty_paths);
-        let plan = plan_add_step(&flow_ir, spec_plan, &empty_catalog)
-            .map_err(|diags| anyhow::anyhow!("planning failed: {:?}", diags))?;
-        let inserted_id = plan.new_node.id.clone();
-        let mut updated = apply_and_validate(&flow_ir, plan, &empty_catalog, args.allow_cycles)?;
+fn handle_graph(args: &GraphArgs, format: OutputFormat) -> Result<()> {
+    let doc = load_ygtc_from_path(&args.flow_path)
+        .with_context(|| format!("failed to load {}", args.flow_path.display()))?;
+    let flow = FlowIr::from_doc(doc)?;
+    let rendered = match args.graph_format {
+        GraphFormat::Mermaid => to_mermaid(&flow),
+        GraphFormat::Dot => to_dot(&flow),
+        GraphFormat::Svg => render_svg(&FlowGraph::from_ir(&flow)),
+    };
+    if let Some(out) = &args.out {
+        fs::write(out, &rendered).with_context(|| format!("failed to write {}", out.display()))?;
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "flow": args.flow_path.display().to_string(),
+            "format": args.graph_format.to_possible_value().map(|v| v.get_name().to_string()),
+            "out": args.out.as_ref().map(|out| out.display().to_string()),
+            "diagram": rendered,
+        }))?;
+    } else if let Some(out) = &args.out {
+        println!("Wrote graph to {}", out.display());
+    } else {
+        print!("{rendered}");
+    }
+    Ok(())
+}
 
-        let abi_version = args
-            .abi_version
-            .clone()
-            .unwrap_or_else(|| wizard_ops::abi_version_from_abi(spec.abi));
-        flow_meta::set_component_entry(
-            &mut updated.meta,
-            &inserted_id,
-            &component_identity,
-            &abi_version,
-            resolved.digest.as_deref(),
-            &wizard_ops::describe_exports_for_meta(spec.abi),
-            contract_meta.as_ref(),
+fn handle_render(args: &RenderArgs, format: OutputFormat) -> Result<()> {
+    let doc = load_ygtc_from_path(&args.flow_path)
+        .with_context(|| format!("failed to load {}", args.flow_path.display()))?;
+    let flow = FlowIr::from_doc(doc)?;
+    let guarded = guarded_nodes(&flow, &args.features);
+    let rendered = render_features(&flow, &args.features)?;
+    let yaml = format_canonical(&serialize_doc(&rendered.flow.to_doc()?)?)?;
+    for name in &rendered.undeclared {
+        eprintln!("warning: feature '{name}' is not declared in meta.features");
+    }
+    if let Some(out) = &args.out {
+        fs::write(out, &yaml).with_context(|| format!("failed to write {}", out.display()))?;
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "flow": args.flow_path.display().to_string(),
+            "features": args.features,
+            "guarded": guarded,
+            "removed": rendered.removed,
+            "undeclared": rendered.undeclared,
+            "out": args.out.as_ref().map(|out| out.display().to_string()),
+            "yaml": yaml,
+        }))?;
+    } else if let Some(out) = &args.out {
+        println!(
+            "Wrote {} ({} node(s) removed) to {}",
+            flow.id,
+            rendered.removed.len(),
+            out.display()
         );
-        flow_meta::ensure_hints_empty(&mut updated.meta, &inserted_id);
+    } else {
+        print!("{yaml}");
+    }
+    Ok(())
+}
 
-        let updated_doc = updated.to_doc()?;
-        let mut output = serde_yaml_bw::to_string(&updated_doc)?;
-        if !output.ends_with('\n') {
-            output.push('\n');
+fn handle_render_matrix(args: &RenderMatrixArgs, format: OutputFormat) -> Result<()> {
+    let doc = load_ygtc_from_path(&args.flow_path)
+        .with_context(|| format!("failed to load {}", args.flow_path.display()))?;
+    let envs_dir = args.envs_dir.clone().unwrap_or_else(|| {
+        args.flow_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(DEFAULT_ENVS_DIR)
+    });
+    let mut envs = env_files(&envs_dir)?
+        .iter()
+        .map(|path| EnvParameters::load(path))
+        .collect::<Result<Vec<_>>>()?;
+    if !args.envs.is_empty() {
+        if let Some(missing) = args
+            .envs
+            .iter()
+            .find(|name| !envs.iter().any(|env| &env.name == *name))
+        {
+            anyhow::bail!(
+                "no parameter file for environment '{missing}' in {}",
+                envs_dir.display()
+            );
         }
+        envs.retain(|env| args.envs.contains(&env.name));
+    }
+    if envs.is_empty() {
+        anyhow::bail!("no environment parameter files in {}", envs_dir.display());
+    }
+    let matrix = render_matrix(&doc, &envs)?;
 
-        if args.validate_only {
-            if matches!(format, OutputFormat::Json) {
-                let payload = json!({"ok": true, "action": "add-step", "validate_only": true});
-                print_json_payload_with_optional_diagnostic(
-                    payload,
-                    deprecation_diagnostic.as_ref(),
-                )?;
-            } else {
-                println!("add-step validation succeeded");
-            }
-            return Ok(());
-        }
+    let stem = args
+        .flow_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(&doc.id);
+    let mut rows = Vec::new();
+    for render in &matrix {
+        let mut out = None;
+        if let (Some(dir), Some(flow)) = (&args.out_dir, &render.flow) {
+            let yaml = format_canonical(&serialize_doc(flow)?)?;
+            load_ygtc_from_str(&yaml)
+                .with_context(|| format!("rendered flow for '{}' does not load", render.env))?;
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+            let path = dir.join(format!("{stem}.{}.ygtc", render.env));
+            fs::write(&path, yaml)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            out = Some(path.display().to_string());
+        }
+        rows.push(json!({
+            "env": render.env,
+            "path": render.path.display().to_string(),
+            "ok": render.ok(),
+            "issues": render.issues,
+            "out": out,
+        }));
+    }
+    let failed: Vec<&str> = matrix
+        .iter()
+        .filter(|render| !render.ok())
+        .map(|render| render.env.as_str())
+        .collect();
 
-        if !args.dry_run {
-            let mut sorted = std::collections::BTreeMap::new();
-            for (key, value) in &answers {
-                sorted.insert(key.clone(), value.clone());
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": failed.is_empty(),
+            "flow": args.flow_path.display().to_string(),
+            "envs": rows,
+        }))?;
+    } else {
+        for (render, row) in matrix.iter().zip(&rows) {
+            if render.ok() {
+                match row["out"].as_str() {
+                    Some(out) => println!("OK   {}: wrote {out}", render.env),
+                    None => println!("OK   {}", render.env),
+                }
+                continue;
             }
-            let base_dir = answers_base_dir(&args.flow_path, args.answers_dir.as_deref());
-            let _paths = answers::write_answers(
-                &base_dir,
-                &flow_ir.id,
-                &inserted_id,
-                wizard_mode.as_str(),
-                &sorted,
-                args.overwrite_answers,
-            )?;
-            wizard_state::update_wizard_state(
-                &args.flow_path,
-                &flow_ir.id,
-                &inserted_id,
-                wizard_mode.as_str(),
-                &locale,
-            )?;
-            write_flow_file(&args.flow_path, &output, true, backup)?;
-            sidecar.nodes.insert(
-                inserted_id.clone(),
-                NodeResolveV1 {
-                    source: resolved.source,
-                    mode: None,
-                },
-            );
-            write_sidecar(&sidecar_path, &sidecar)?;
-            if let Err(err) =
-                write_flow_resolve_summary_for_node(&args.flow_path, &inserted_id, &sidecar)
-                    .with_context(|| {
-                        format!("update resolve summary for {}", args.flow_path.display())
-                    })
-            {
-                eprintln!("warning: {err}");
+            println!("FAIL {}:", render.env);
+            for issue in &render.issues {
+                println!("  {}: {}", issue.pointer, issue.message);
             }
-            if matches!(format, OutputFormat::Json) {
-                let payload = json!({
-                    "ok": true,
-                    "action": "add-step",
-                    "node_id": inserted_id,
-                    "flow_path": args.flow_path.display().to_string()
-                });
-                print_json_payload_with_optional_diagnostic(
-                    payload,
-                    deprecation_diagnostic.as_ref(),
-                )?;
-            } else {
-                println!(
-                    "Inserted node after '{}' and wrote {}",
-                    args.after.unwrap_or_else(|| "<default anchor>".to_string()),
-                    args.flow_path.display()
-                );
+        }
+        println!(
+            "{} of {} environment(s) valid",
+            matrix.len() - failed.len(),
+            matrix.len()
+        );
+    }
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "invalid parameters for environment(s): {}",
+            failed.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn handle_replay(args: &ReplayArgs, format: OutputFormat) -> Result<()> {
+    let doc = load_ygtc_from_path(&args.flow_path)
+        .with_context(|| format!("failed to load {}", args.flow_path.display()))?;
+    let flow = FlowIr::from_doc(doc)?;
+    let text = fs::read_to_string(&args.captures)
+        .with_context(|| format!("failed to read {}", args.captures.display()))?;
+    let records = parse_recordings(&text)
+        .with_context(|| format!("failed to parse {}", args.captures.display()))?;
+    let sidecar = read_flow_resolve(&sidecar_path_for_flow(&args.flow_path)).ok();
+    let report = validate_recordings(&flow, &records, |node_id| {
+        let entry = sidecar
+            .as_ref()
+            .and_then(|sidecar| sidecar.nodes.get(node_id))
+            .ok_or_else(|| "not bound in the resolve sidecar".to_string())?;
+        let manifest = resolve_component_manifest_path(&entry.source, &args.flow_path)
+            .map_err(|err| err.to_string())?;
+        let operation = &flow.nodes[node_id].operation;
+        let declared = |schema: Option<serde_json::Value>| {
+            schema.filter(|schema| !is_effectively_empty_schema(schema))
+        };
+        Ok(NodeSchemas {
+            input: declared(
+                resolve_input_schema(&manifest, operation)
+                    .map_err(|err| err.to_string())?
+                    .schema,
+            ),
+            output: declared(
+                resolve_output_schema(&manifest, operation).map_err(|err| err.to_string())?,
+            ),
+            manifest_path: Some(manifest),
+        })
+    })?;
+    let drifted = report.drifted_nodes();
+
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": drifted.is_empty(),
+            "flow": args.flow_path.display().to_string(),
+            "captures": args.captures.display().to_string(),
+            "report": report,
+        }))?;
+    } else {
+        for mismatch in &report.mismatches {
+            let direction = match mismatch.direction {
+                PayloadDirection::Input => "input",
+                PayloadDirection::Output => "output",
+            };
+            println!(
+                "DRIFT node '{}' {direction} (record {}) at {}: {}",
+                mismatch.node_id, mismatch.record, mismatch.pointer, mismatch.message
+            );
+        }
+        for node in &report.nodes {
+            match &node.skipped {
+                Some(reason) if node.checked == 0 => {
+                    println!("SKIP  node '{}': {reason}", node.node_id)
+                }
+                _ => println!(
+                    "{} node '{}': {} payload(s) checked, {} failed",
+                    if node.failed > 0 { "FAIL " } else { "OK   " },
+                    node.node_id,
+                    node.checked,
+                    node.failed
+                ),
             }
-        } else if matches!(format, OutputFormat::Json) {
-            let payload =
-                json!({"ok": true, "action": "add-step", "dry_run": true, "flow": output});
-            print_json_payload_with_optional_diagnostic(payload, deprecation_diagnostic.as_ref())?;
-        } else {
-            print!("{output}");
         }
-
-        return Ok(());
-    }
-    let (sidecar_path, mut sidecar) = ensure_sidecar(&args.flow_path)?;
-    let (component_source, resolve_mode) = resolve_component_source_inputs(
-        args.local_wasm.as_ref(),
-        args.component_ref.as_ref(),
-        args.pin,
-        &args.flow_path,
-    )?;
-    let doc = load_ygtc_from_path(&args.flow_path)?;
-    let flow_ir = FlowIr::from_doc(doc)?;
-    let manifest_path_for_schema = args
-        .manifests
-        .first()
-        .cloned()
-        .or_else(|| resolve_component_manifest_path(&component_source, &args.flow_path).ok());
-    let mut manifest_paths = args.manifests.clone();
-    if args.mode == AddStepMode::Config
-        && args.config_flow.is_none()
-        && manifest_paths.is_empty()
-        && let Some(path) = manifest_path_for_schema.clone()
-    {
-        manifest_paths.push(path);
+        for node_id in &report.unknown_nodes {
+            println!("WARN  captures record node '{node_id}', which is not in the flow");
+        }
+        println!(
+            "Replayed {} record(s): {} node(s) drifted from their schemas",
+            report.records,
+            drifted.len()
+        );
     }
-    if args.mode == AddStepMode::Config && args.config_flow.is_none() && manifest_paths.is_empty() {
+    if !drifted.is_empty() {
         anyhow::bail!(
-            "config mode requires --config-flow or a component manifest to provide dev_flows.custom"
+            "recorded traffic no longer matches the schemas of node(s): {}",
+            drifted.join(", ")
         );
     }
-    let catalog = ManifestCatalog::load_from_paths(&manifest_paths);
+    Ok(())
+}
 
-    let mut answers = parse_answers_map(args.answers.as_deref(), args.answers_file.as_deref())?;
-    let has_answer_inputs = args.answers.is_some() || args.answers_file.is_some();
-    let (mode_input, require_placeholder_flag) = match args.mode {
-        AddStepMode::Default => {
-            let mut payload_json: serde_json::Value =
-                serde_json::from_str(&args.payload).context("parse --payload as JSON")?;
-            let mut used_writes = false;
-            let mut used_dev_flow = false;
-            if let Some(manifest_path) = &manifest_path_for_schema {
-                let questions = questions_from_manifest(manifest_path, "default")?;
-                if !questions.is_empty() {
-                    warn_unknown_keys(&answers, &questions);
-                    println!("{}", wizard_header(&component_identity, "default"));
-                    if has_answer_inputs {
-                        validate_required(&questions, &answers)?;
-                    } else {
-                        answers = run_interactive_with_seed(&questions, answers)?;
-                    }
-                    if questions.iter().any(|q| q.writes_to.is_some()) {
-                        payload_json = apply_writes_to(payload_json, &questions, &answers)?;
-                        used_writes = true;
-                    }
-                    used_dev_flow = true;
-                }
+fn handle_trace_annotate(args: &TraceAnnotateArgs, format: OutputFormat) -> Result<()> {
+    let doc = load_ygtc_from_path(&args.flow_path)
+        .with_context(|| format!("failed to load {}", args.flow_path.display()))?;
+    let flow = FlowIr::from_doc(doc)?;
+    let text = fs::read_to_string(&args.trace_path)
+        .with_context(|| format!("failed to read {}", args.trace_path.display()))?;
+    let spans = parse_otlp_json(&text)
+        .with_context(|| format!("failed to parse {}", args.trace_path.display()))?;
+    let traces = trace_ids(&spans);
+    let annotation = annotate_trace(&flow, &spans, args.trace_id.as_deref())?;
+    let highlight = annotation.highlight();
+    let diagram = match args.graph_format {
+        TraceGraphFormat::Mermaid => to_mermaid_highlighted(&flow, &highlight),
+        TraceGraphFormat::Dot => to_dot_highlighted(&flow, &highlight),
+    };
+    if let Some(out) = &args.out {
+        fs::write(out, &diagram).with_context(|| format!("failed to write {}", out.display()))?;
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "flow": args.flow_path.display().to_string(),
+            "traces": traces,
+            "annotation": annotation,
+            "out": args.out.as_ref().map(|out| out.display().to_string()),
+            "diagram": diagram,
+        }))?;
+        return Ok(());
+    }
+
+    let others = traces.len().saturating_sub(1);
+    println!(
+        "trace {}: {} step(s) through {}{}",
+        annotation.trace_id,
+        annotation.steps.len(),
+        flow.id,
+        if others > 0 {
+            format!(" ({others} other trace(s) in the export; pick one with --trace-id)")
+        } else {
+            String::new()
+        }
+    );
+    for (index, step) in annotation.steps.iter().enumerate() {
+        let mut line = format!(
+            "  {}. {} {:.1}ms",
+            index + 1,
+            step.node_id,
+            step.duration_nanos as f64 / 1_000_000.0
+        );
+        if let Some(status) = &step.status {
+            line.push_str(&format!(" status {status}"));
+        }
+        if step.error {
+            line.push_str(" ERROR");
+            if let Some(message) = &step.error_message {
+                line.push_str(&format!(" ({message})"));
             }
-            let operation = args.operation.clone().ok_or_else(|| {
-                anyhow::anyhow!(
-                    "--operation is required in default mode (component id is not stored in flows)"
-                )
-            })?;
-            if !used_writes {
-                payload_json = merge_payload(payload_json, answers_to_value(&answers));
+        }
+        if let Some(next) = &step.next {
+            line.push_str(&format!(" -> {next}"));
+            if step.route.is_none() {
+                line.push_str(" (no route)");
             }
-            if !used_dev_flow && let Some(manifest_path) = &manifest_path_for_schema {
-                let schema_resolution = resolve_input_schema(manifest_path, &operation)?;
-                let schema_present = require_schema(
-                    schema_mode,
-                    &schema_resolution.component_id,
-                    &schema_resolution.operation,
-                    &schema_resolution.manifest_path,
-                    "operations[].input_schema",
-                    schema_resolution.schema.as_ref(),
-                )?;
-                if schema_present.is_some() {
-                    validate_payload_against_schema(&schema_resolution, &payload_json)?;
-                }
+        }
+        println!("{line}");
+    }
+    if !annotation.untaken.is_empty() {
+        println!("untaken routes:");
+        for route in &annotation.untaken {
+            match &route.label {
+                Some(label) => println!("  {} -> {} [{label}]", route.from, route.to),
+                None => println!("  {} -> {}", route.from, route.to),
             }
-            let routing_json = routing_value.clone();
-            (
-                AddStepModeInput::Default {
-                    operation,
-                    payload: payload_json,
-                    routing: routing_json,
-                },
-                require_placeholder,
-            )
         }
-        AddStepMode::Config => {
-            let (config_flow, schema_path) =
-                resolve_config_flow(args.config_flow.clone(), &manifest_paths, "custom")?;
-            let questions = questions_from_config_flow_text(&config_flow)?;
-            if !questions.is_empty() {
-                warn_unknown_keys(&answers, &questions);
-                println!("{}", wizard_header(&component_identity, "config"));
-                if has_answer_inputs {
-                    validate_required(&questions, &answers)?;
-                } else {
-                    answers = run_interactive_with_seed(&questions, answers)?;
+    }
+    for route in &annotation.unexpected {
+        println!(
+            "warning: {} ran after {} but no route leads there; the flow may have changed since the trace",
+            route.to, route.from
+        );
+    }
+    if annotation.unmatched_spans > 0 {
+        println!(
+            "{} span(s) did not match a node",
+            annotation.unmatched_spans
+        );
+    }
+    match &args.out {
+        Some(out) => println!("Wrote graph to {}", out.display()),
+        None => {
+            println!();
+            print!("{diagram}");
+        }
+    }
+    Ok(())
+}
+
+/// Requirements of every node bound in the sidecar, in flow order, plus the ids of bound nodes
+/// whose component manifest could not be resolved. Flows without a sidecar have no bindings.
+fn collect_node_requirements(flow_path: &Path) -> Result<(Vec<NodeRequirements>, Vec<String>)> {
+    let Ok(sidecar) = read_flow_resolve(&sidecar_path_for_flow(flow_path)) else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+    let flow_ir = FlowIr::from_doc(load_ygtc_from_path(flow_path)?)?;
+    let mut nodes = Vec::new();
+    let mut unresolved = Vec::new();
+    for node_id in flow_ir.nodes.keys() {
+        let Some(entry) = sidecar.nodes.get(node_id) else {
+            continue;
+        };
+        let manifest = resolve_component_manifest_path(&entry.source, flow_path)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok());
+        let Some(manifest) = manifest else {
+            unresolved.push(node_id.clone());
+            continue;
+        };
+        let source = ComponentSource::from_sidecar(&entry.source, entry.mode);
+        nodes.push(NodeRequirements::from_manifest(
+            node_id,
+            &source.reference,
+            &manifest,
+        ));
+    }
+    Ok((nodes, unresolved))
+}
+
+fn collect_ide_data(flow_path: &Path) -> Result<IdeData> {
+    let flow_ir = FlowIr::from_doc(load_ygtc_from_path(flow_path)?)?;
+    let sidecar = read_flow_resolve(&sidecar_path_for_flow(flow_path)).ok();
+    let mut data = IdeData {
+        version: IDE_DATA_VERSION,
+        flow: flow_ir.id.clone(),
+        flow_type: flow_ir.kind.clone(),
+        node_ids: flow_ir.nodes.keys().cloned().collect(),
+        reserved_keys: greentic_flow::model::reserved_node_keys(),
+        ..IdeData::default()
+    };
+    for (node_id, node) in &flow_ir.nodes {
+        let entry = sidecar
+            .as_ref()
+            .and_then(|sidecar| sidecar.nodes.get(node_id));
+        let component =
+            entry.map(|entry| ComponentSource::from_sidecar(&entry.source, entry.mode).reference);
+        if let (Some(entry), Some(reference)) = (entry, component.as_ref())
+            && !data.components.contains_key(reference)
+        {
+            let operations = resolve_component_manifest_path(&entry.source, flow_path)
+                .and_then(|path| manifest_operation_properties(&path).map_err(Into::into));
+            match operations {
+                Ok(operations) => {
+                    data.components.insert(reference.clone(), operations);
                 }
+                Err(_) => data.unresolved.push(node_id.clone()),
+            }
+        }
+        data.nodes.push(IdeNode {
+            id: node_id.clone(),
+            operation: node.operation.clone(),
+            component,
+        });
+    }
+    Ok(data)
+}
+
+fn handle_selftest_determinism(args: &DeterminismArgs, format: OutputFormat) -> Result<()> {
+    let yaml = fs::read_to_string(&args.flow_path)
+        .with_context(|| format!("failed to read {}", args.flow_path.display()))?;
+    let checks = run_determinism_suite(&yaml, Some(&args.flow_path), args.runs as usize)?;
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": failed == 0,
+            "flow": args.flow_path.display().to_string(),
+            "runs": args.runs,
+            "checks": checks,
+        }))?;
+    } else {
+        for check in &checks {
+            let status = if check.passed { "PASS" } else { "FAIL" };
+            match &check.detail {
+                Some(detail) => println!("{status} {}: {detail}", check.name),
+                None => println!("{status} {} {}", check.name, check.digest),
             }
-            let manifest_path_for_validation = manifest_paths.first().cloned().or_else(|| {
-                resolve_component_manifest_path(&component_source, &args.flow_path).ok()
-            });
-            (
-                AddStepModeInput::Config {
-                    config_flow,
-                    schema_path: schema_path.into_boxed_path(),
-                    answers: answers_to_json_map(answers),
-                    manifest_id: Some(component_identity.clone()),
-                    manifest_path: manifest_path_for_validation,
-                },
-                true,
-            )
         }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{failed} of {} determinism check(s) failed for {}",
+            checks.len(),
+            args.flow_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// The store `cas` commands work on: `--dir`, else GREENTIC_FLOW_CAS_DIR.
+fn cas_store(dir: Option<&PathBuf>) -> Result<ArtifactStore> {
+    dir.map(ArtifactStore::new)
+        .or_else(ArtifactStore::from_env)
+        .ok_or_else(|| anyhow!("no artifact store configured; pass --dir or set {CAS_DIR_ENV}"))
+}
+
+fn handle_cas_gc(args: &CasGcArgs, format: OutputFormat) -> Result<()> {
+    let store = cas_store(args.dir.as_ref())?;
+    let keep_for = Duration::from_secs(args.keep_days.saturating_mul(24 * 60 * 60));
+    let report = store.gc(keep_for, args.dry_run)?;
+    if matches!(format, OutputFormat::Json) {
+        return print_json_payload(&json!({
+            "ok": true,
+            "store": store.root().display().to_string(),
+            "dry_run": args.dry_run,
+            "removed": report.removed,
+            "freed_bytes": report.freed_bytes,
+            "kept": report.kept,
+        }));
+    }
+    let verb = if args.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
     };
+    for artifact in &report.removed {
+        println!("{verb} {} ({} bytes)", artifact.digest, artifact.bytes);
+    }
+    println!(
+        "{verb} {} artifact(s), {} bytes; kept {} artifact(s), {} bytes in {}",
+        report.removed.len(),
+        report.freed_bytes,
+        report.kept.artifacts,
+        report.kept.bytes,
+        store.root().display()
+    );
+    Ok(())
+}
 
-    let (hint, node_value) = materialize_node(mode_input, &catalog)?;
-    let mut node_id_hint = infer_node_id_hint(&args);
-    if node_id_hint.is_none() {
-        node_id_hint = hint;
+fn handle_cas_size(args: &CasSizeArgs, format: OutputFormat) -> Result<()> {
+    let store = cas_store(args.dir.as_ref())?;
+    let usage = store.usage()?;
+    if matches!(format, OutputFormat::Json) {
+        return print_json_payload(&json!({
+            "ok": true,
+            "store": store.root().display().to_string(),
+            "artifacts": usage.artifacts,
+            "bytes": usage.bytes,
+        }));
     }
-    if args.node_id.is_none() {
-        node_id_hint = normalize_node_id_hint(node_id_hint, &node_value);
+    println!(
+        "{} artifact(s), {} bytes in {}",
+        usage.artifacts,
+        usage.bytes,
+        store.root().display()
+    );
+    Ok(())
+}
+
+impl DistCacheArgs {
+    fn cache(&self) -> DistCache {
+        DistCache::new(
+            self.cache_dir
+                .clone()
+                .unwrap_or_else(|| DistOptions::default().cache_dir),
+        )
     }
+}
 
-    let spec = AddStepSpec {
-        after: args.after.clone(),
-        node_id_hint,
-        node: node_value,
-        allow_cycles: args.allow_cycles,
-        require_placeholder: require_placeholder_flag,
+fn describe_dist_entry(entry: &DistCacheEntry) -> String {
+    let mut line = format!("{}  {} bytes", entry.digest, entry.bytes);
+    if let Some(id) = &entry.component_id {
+        line.push_str(&format!("  {id}"));
+    }
+    if entry.wasm.is_none() {
+        line.push_str("  (no wasm)");
+    }
+    line
+}
+
+fn handle_cache_ls(args: &DistCacheArgs, format: OutputFormat) -> Result<()> {
+    let cache = args.cache();
+    let entries = cache.entries()?;
+    if matches!(format, OutputFormat::Json) {
+        return print_json_payload(&json!({
+            "ok": true,
+            "cache": cache.root().display().to_string(),
+            "entries": entries,
+        }));
+    }
+    for entry in &entries {
+        println!("{}", describe_dist_entry(entry));
+    }
+    println!(
+        "{} entr{} in {}",
+        entries.len(),
+        if entries.len() == 1 { "y" } else { "ies" },
+        cache.root().display()
+    );
+    Ok(())
+}
+
+fn handle_cache_info(args: &DistCacheEntryArgs, format: OutputFormat) -> Result<()> {
+    let cache = args.cache.cache();
+    let entry = cache.find(&args.digest)?;
+    let status = cache.check(&entry)?;
+    if matches!(format, OutputFormat::Json) {
+        return print_json_payload(&json!({
+            "ok": !status.is_problem(),
+            "entry": entry,
+            "verification": status,
+        }));
+    }
+    println!("digest: {}", entry.digest);
+    println!("dir: {}", entry.dir.display());
+    if let Some(id) = &entry.component_id {
+        println!("component: {id}");
+    }
+    let show = |path: &Option<PathBuf>| {
+        path.as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "-".to_string())
     };
+    println!("wasm: {}", show(&entry.wasm));
+    println!("manifest: {}", show(&entry.manifest));
+    println!("bytes: {}", entry.bytes);
+    if let Some(last_used) = entry.last_used {
+        println!("last used: {last_used} (unix seconds)");
+    }
+    println!("status: {}", describe_entry_status(&status));
+    Ok(())
+}
 
-    let plan = plan_add_step(&flow_ir, spec, &catalog)
-        .map_err(|diags| anyhow::anyhow!("planning failed: {:?}", diags))?;
-    let inserted_id = plan.new_node.id.clone();
-    let updated = apply_and_validate(&flow_ir, plan, &catalog, args.allow_cycles)?;
-    let updated_doc = updated.to_doc()?;
-    let mut output = serde_yaml_bw::to_string(&updated_doc)?;
-    if !output.ends_with('\n') {
-        output.push('\n');
+fn describe_entry_status(status: &EntryStatus) -> String {
+    match status {
+        EntryStatus::Ok => "ok".to_string(),
+        EntryStatus::Corrupt { actual } => format!("corrupt (wasm hashes to {actual})"),
+        EntryStatus::Orphaned => "orphaned (manifest without wasm)".to_string(),
+        EntryStatus::Unverified => "present (keyed by OCI manifest digest; not hashed)".to_string(),
+    }
+}
+
+fn handle_cache_rm(args: &DistCacheRmArgs, format: OutputFormat) -> Result<()> {
+    let cache = args.cache.cache();
+    // Resolve every digest first so a typo removes nothing.
+    let entries = args
+        .digests
+        .iter()
+        .map(|digest| cache.find(digest))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut removed = Vec::new();
+    for entry in entries {
+        if removed.contains(&entry.digest) {
+            continue;
+        }
+        cache.remove(&entry)?;
+        removed.push(entry.digest);
+    }
+    if matches!(format, OutputFormat::Json) {
+        return print_json_payload(&json!({ "ok": true, "removed": removed }));
+    }
+    for digest in &removed {
+        println!("Removed {digest}");
+    }
+    Ok(())
+}
+
+fn handle_cache_verify(args: &DistCacheVerifyArgs, format: OutputFormat) -> Result<()> {
+    let cache = args.cache.cache();
+    let verified = cache.verify()?;
+    let mut pruned = Vec::new();
+    if args.prune {
+        for item in verified.iter().filter(|item| item.status.is_problem()) {
+            cache.remove(&item.entry)?;
+            pruned.push(item.entry.digest.clone());
+        }
+    }
+    let problems = verified
+        .iter()
+        .filter(|item| item.status.is_problem() && !pruned.contains(&item.entry.digest))
+        .count();
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": problems == 0,
+            "cache": cache.root().display().to_string(),
+            "entries": verified,
+            "pruned": pruned,
+        }))?;
+        if problems > 0 {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    for item in &verified {
+        let pruned_note = if pruned.contains(&item.entry.digest) {
+            " [removed]"
+        } else {
+            ""
+        };
+        println!(
+            "{} {}{pruned_note}",
+            item.entry.digest,
+            describe_entry_status(&item.status)
+        );
+    }
+    if problems > 0 {
+        anyhow::bail!(
+            "{problems} corrupt or orphaned cache entr{} in {}; rerun with --prune to remove",
+            if problems == 1 { "y" } else { "ies" },
+            cache.root().display()
+        );
     }
+    println!(
+        "{} entr{} checked, {} removed",
+        verified.len(),
+        if verified.len() == 1 { "y" } else { "ies" },
+        pruned.len()
+    );
+    Ok(())
+}
 
-    if args.validate_only {
-        if matches!(format, OutputFormat::Json) {
-            let payload = json!({"ok": true, "action": "add-step", "validate_only": true});
-            print_json_payload(&payload)?;
-        } else {
-            println!("add-step validation succeeded");
-        }
+fn handle_ide_data(args: &IdeDataArgs, format: OutputFormat) -> Result<()> {
+    let data = collect_ide_data(&args.flow_path)?;
+    let mut text = serde_json::to_string_pretty(&data)?;
+    text.push('\n');
+    if args.out.as_deref() == Some(Path::new("-")) {
+        print!("{text}");
         return Ok(());
     }
-
-    if !args.dry_run {
-        write_flow_file(&args.flow_path, &output, true, backup)?;
-        sidecar.nodes.insert(
-            inserted_id.clone(),
-            NodeResolveV1 {
-                source: component_source,
-                mode: resolve_mode,
-            },
+    let out = args.out.clone().unwrap_or_else(|| {
+        let mut name = args
+            .flow_path
+            .file_name()
+            .map(|name| name.to_os_string())
+            .unwrap_or_default();
+        name.push(".ide.json");
+        args.flow_path.with_file_name(name)
+    });
+    fs::write(&out, text).with_context(|| format!("failed to write {}", out.display()))?;
+    for node_id in &data.unresolved {
+        eprintln!("warning: node '{node_id}': component manifest unavailable");
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": true,
+            "out": out.display().to_string(),
+            "nodes": data.nodes.len(),
+            "components": data.components.len(),
+            "unresolved": data.unresolved,
+        }))
+    } else {
+        println!(
+            "Wrote completion data for {} node(s) and {} component(s) to {}",
+            data.nodes.len(),
+            data.components.len(),
+            out.display()
         );
-        write_sidecar(&sidecar_path, &sidecar)?;
-        if let Err(err) =
-            write_flow_resolve_summary_for_node(&args.flow_path, &inserted_id, &sidecar)
-                .with_context(|| format!("update resolve summary for {}", args.flow_path.display()))
-        {
-            eprintln!("warning: {err}");
+        Ok(())
+    }
+}
+
+fn handle_requirements(args: &RequirementsArgs, format: OutputFormat) -> Result<()> {
+    let (nodes, unresolved) = collect_node_requirements(&args.flow_path)?;
+    if matches!(format, OutputFormat::Json) {
+        return print_json_payload(&json!({
+            "flow": args.flow_path.display().to_string(),
+            "nodes": nodes,
+            "unresolved": unresolved,
+        }));
+    }
+    for node in &nodes {
+        if node.is_empty() {
+            println!("{} ({}): no requirements", node.node_id, node.component);
+            continue;
         }
-        if matches!(format, OutputFormat::Json) {
-            let payload = json!({
-                "ok": true,
-                "action": "add-step",
-                "node_id": inserted_id,
-                "flow_path": args.flow_path.display().to_string()
-            });
-            print_json_payload(&payload)?;
-        } else {
+        println!("{} ({})", node.node_id, node.component);
+        if !node.env.is_empty() {
+            println!("  env: {}", node.env.join(", "));
+        }
+        for secret in &node.secrets {
+            let optional = if secret.required { "" } else { " (optional)" };
             println!(
-                "Inserted node after '{}' and wrote {}",
-                args.after.unwrap_or_else(|| "<default anchor>".to_string()),
-                args.flow_path.display()
+                "  secret: {} [{}]{optional}",
+                secret.key,
+                secret_env_name(&secret.key)
             );
         }
-    } else if matches!(format, OutputFormat::Json) {
-        let payload = json!({"ok": true, "action": "add-step", "dry_run": true, "flow": output});
-        print_json_payload(&payload)?;
-    } else {
-        print!("{output}");
+        if !node.capabilities.is_empty() {
+            println!("  capabilities: {}", node.capabilities.join(", "));
+        }
+    }
+    for node_id in &unresolved {
+        println!("{node_id}: component manifest unavailable");
+    }
+    if nodes.is_empty() && unresolved.is_empty() {
+        println!("no bound components in {}", args.flow_path.display());
     }
-
     Ok(())
 }
 
-fn handle_update_step(
-    args: UpdateStepArgs,
-    schema_mode: SchemaMode,
-    format: OutputFormat,
-    backup: bool,
-) -> Result<()> {
-    handle_update_step_with_qa_io(args, schema_mode, format, backup, None)
-}
-
-fn handle_update_step_with_qa_io(
-    args: UpdateStepArgs,
-    schema_mode: SchemaMode,
-    format: OutputFormat,
-    backup: bool,
-    qa_io: Option<&mut QaInteractiveIo<'_>>,
-) -> Result<()> {
-    let doc = load_ygtc_from_path(&args.flow_path)?;
-    let mut flow_ir = FlowIr::from_doc(doc)?;
-    let component_identity = args
-        .component_id
-        .clone()
-        .or_else(|| args.component.clone())
-        .or_else(|| {
-            args.local_wasm
-                .as_ref()
-                .and_then(|p| p.file_stem().and_then(|s| s.to_str()))
-                .map(|s| s.to_string())
-        })
-        .unwrap_or_else(|| "component".to_string());
-    let step_id = resolve_step_id(args.step.clone(), args.component_id.as_ref(), &flow_ir.meta)?;
-    let wizard_requested = args.component_id.is_some() || args.wizard_mode.is_some();
-    if wizard_requested {
-        let (sidecar_path, mut sidecar) = ensure_sidecar(&args.flow_path)?;
-        let wizard_mode_arg = args.wizard_mode.unwrap_or(WizardModeArg::Update);
-        let deprecation_diagnostic = warn_deprecated_wizard_mode(wizard_mode_arg);
-        let wizard_mode = wizard_mode_arg.to_mode();
-        let resolved = resolve_wizard_component(
-            &args.flow_path,
-            wizard_mode,
-            args.local_wasm.as_ref(),
-            args.component.as_ref(),
-            args.component_id.as_ref(),
-            args.resolver.as_ref(),
-            args.distributor_url.as_ref(),
-            args.auth_token.as_ref(),
-            args.tenant.as_ref(),
-            args.env.as_ref(),
-            args.pack.as_ref(),
-            args.component_version.as_ref(),
-        )?;
-        let spec = if let Some(fixture) = resolved.fixture.as_ref() {
-            wizard_ops::WizardSpecOutput {
-                abi: fixture.abi,
-                describe_cbor: fixture.describe_cbor.clone(),
-                descriptor: None,
-                qa_spec_cbor: fixture.qa_spec_cbor.clone(),
-                answers_schema_cbor: None,
+fn handle_fmt(args: &FmtArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    let mut files = Vec::new();
+    for target in &args.targets {
+        if target.is_dir() {
+            let mut found = Vec::new();
+            collect_flow_files(target, &mut found)?;
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(target.clone());
+        }
+    }
+    let mut changed = Vec::new();
+    for path in &files {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let formatted = format_canonical(&text)
+            .with_context(|| format!("failed to format {}", path.display()))?;
+        if formatted == text || formatted == strip_comments(&text) {
+            continue;
+        }
+        if !args.check {
+            if !args.drop_comments
+                && let Some(line) = first_comment_line(&text)
+            {
+                anyhow::bail!(
+                    "{}:{line} has comments, which formatting would remove; pass --drop-comments to format it anyway",
+                    path.display()
+                );
             }
+            write_flow_file(path, &formatted, true, backup)?;
+        }
+        changed.push(path.display().to_string());
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": !args.check || changed.is_empty(),
+            "check": args.check,
+            "files": files.len(),
+            "changed": changed,
+        }))?;
+    } else {
+        let verb = if args.check {
+            "would reformat"
         } else {
-            wizard_ops::fetch_wizard_spec(&resolved.wasm_bytes, wizard_mode)
-                .map_err(|err| wrap_wizard_error(err, &component_identity, "describe", None))?
+            "formatted"
         };
-        let qa_spec = wizard_ops::decode_component_qa_spec(&spec.qa_spec_cbor, wizard_mode)?;
-        let (mut catalog, locale) = default_i18n_catalog(args.locale.as_deref());
-        merge_component_i18n_catalog(&mut catalog, &locale, &args.flow_path, &resolved.source);
+        for path in &changed {
+            println!("{verb} {path}");
+        }
+        println!(
+            "{} of {} flow(s) {}",
+            changed.len(),
+            files.len(),
+            if args.check {
+                "not canonical"
+            } else {
+                "reformatted"
+            }
+        );
+    }
+    if args.check && !changed.is_empty() {
+        anyhow::bail!("{} flow(s) are not canonically formatted", changed.len());
+    }
+    Ok(())
+}
 
-        let base_dir = answers_base_dir(&args.flow_path, args.answers_dir.as_deref());
-        let fallback_path = if !args.reask && args.answers.is_none() && args.answers_file.is_none()
-        {
-            wizard_answers_json_path_compat(&base_dir, &flow_ir.id, &step_id, wizard_mode)
+fn handle_prune(args: &PruneArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    let today = match &args.as_of {
+        Some(date) if days_until(date, date).is_none() => {
+            anyhow::bail!("--as-of must be a YYYY-MM-DD date, got '{date}'")
+        }
+        Some(date) => date.clone(),
+        None => utc_today(),
+    };
+    let mut files = Vec::new();
+    for target in &args.targets {
+        if target.is_dir() {
+            let mut found = Vec::new();
+            collect_flow_files(target, &mut found)?;
+            found.sort();
+            files.extend(found);
         } else {
-            None
-        };
-        let answers_file = args.answers_file.as_deref().or(fallback_path.as_deref());
-        let mut answers = parse_answers_map(args.answers.as_deref(), answers_file)?;
-        wizard_ops::merge_default_answers(&qa_spec, &mut answers);
-        if args.interactive && matches!(wizard_mode, wizard_ops::WizardMode::Default) {
-            seed_optional_answers_for_default_setup(&qa_spec, &mut answers);
+            files.push(target.clone());
         }
-        if !qa_spec.questions.is_empty() {
-            qa_runner::warn_unknown_keys(&answers, &qa_spec, &catalog, &locale);
-            println!(
-                "{}",
-                wizard_header(&component_identity, wizard_mode.as_str())
-            );
-            answers = run_component_qa_with_qa_lib(
-                &qa_spec,
-                &catalog,
-                &locale,
-                answers,
-                args.interactive,
-                qa_io,
+    }
+    let mut pruned = Vec::new();
+    for path in &files {
+        let doc = load_ygtc_from_path(path)?;
+        let (pruned_doc, removed) = prune_expired(&doc, &today)
+            .with_context(|| format!("failed to prune {}", path.display()))?;
+        if removed.is_empty() {
+            continue;
+        }
+        let yaml = serialize_doc(&pruned_doc)?;
+        load_ygtc_from_str(&yaml)
+            .with_context(|| format!("pruned {} no longer loads", path.display()))?;
+        let (sidecar_path, mut sidecar) = load_sidecar_or_default(path)?;
+        let has_sidecar = sidecar_path.exists();
+        for node_id in &removed {
+            sidecar.nodes.remove(node_id);
+        }
+        if args.dry_run {
+            let diff = dry_run_diff(
+                path,
+                &yaml,
+                has_sidecar.then_some((sidecar_path.as_path(), &sidecar)),
             )?;
+            pruned.push(json!({
+                "path": path.display().to_string(),
+                "removed": removed,
+                "diff": diff,
+            }));
+            continue;
         }
+        let _writing = cancel::write_guard()?;
+        write_flow_file(path, &yaml, true, backup)?;
+        if has_sidecar {
+            write_sidecar(&sidecar_path, &sidecar)?;
+        }
+        for node_id in &removed {
+            let _ = wizard_state::remove_wizard_step(path, &doc.id, node_id);
+            let _ = answer_provenance::remove_answer_provenance(path, node_id);
+            config_store::remove_node_config(path, node_id)?;
+            if let Err(err) = remove_flow_resolve_summary_node(path, node_id)
+                .with_context(|| format!("update resolve summary for {}", path.display()))
+            {
+                eprintln!("warning: {err}");
+            }
+        }
+        pruned.push(json!({"path": path.display().to_string(), "removed": removed}));
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": true,
+            "as_of": today,
+            "dry_run": args.dry_run,
+            "files": files.len(),
+            "flows": pruned,
+        }))?;
+        return Ok(());
+    }
+    for flow in &pruned {
+        if let Some(diff) = flow["diff"].as_str() {
+            print_dry_run_diff(diff);
+            continue;
+        }
+        let removed: Vec<&str> = flow["removed"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| id.as_str())
+            .collect();
+        println!(
+            "pruned {} from {}",
+            removed.join(", "),
+            flow["path"].as_str().unwrap_or_default()
+        );
+    }
+    println!(
+        "{} of {} flow(s) had expired nodes (as of {today})",
+        pruned.len(),
+        files.len()
+    );
+    Ok(())
+}
 
-        let answers_cbor = wizard_ops::answers_to_cbor(&answers)?;
-        let mut node = flow_ir
-            .nodes
-            .get(&step_id)
-            .cloned()
-            .ok_or_else(|| anyhow::anyhow!("step '{}' not found", step_id))?;
-        let current_config = wizard_ops::json_to_cbor(&node.payload)?;
-        let config_cbor = if let Some(fixture) = resolved.fixture.as_ref() {
-            fixture.apply_answers_cbor.clone()
-        } else {
-            wizard_ops::apply_wizard_answers(
-                &resolved.wasm_bytes,
-                spec.abi,
-                wizard_mode,
-                &current_config,
-                &answers_cbor,
-            )
-            .map_err(|err| wrap_wizard_error(err, &component_identity, "apply-answers", None))?
-        };
-        let mut new_operation = node.operation.clone();
-        if let Some(op) = args.operation.clone() {
-            new_operation = op;
+/// Byte offset where a YAML comment starts in `line`: a `#` at the start or after whitespace,
+/// outside quotes. Block scalar text that looks like a comment counts too, erring on the side
+/// of keeping it.
+fn comment_start(line: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (idx, c) in line.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && prev.is_whitespace() => return Some(idx),
+            None => {}
         }
-        let contract_meta = spec
-            .descriptor
-            .as_ref()
-            .map(|descriptor| derive_contract_meta_from_descriptor(descriptor, &new_operation))
-            .transpose()?
-            .map(|(_, meta)| meta);
-        let config_json = wizard_ops::cbor_to_json(&config_cbor)?;
-        ensure_wizard_config_not_error(&component_identity, wizard_mode, &config_json)?;
-        node.payload = config_json;
-        node.operation = new_operation.clone();
-        if let Some(routing) = build_update_routing(&args)? {
-            node.routing = routing;
+        prev = c;
+    }
+    None
+}
+
+fn first_comment_line(text: &str) -> Option<usize> {
+    text.lines()
+        .position(|line| comment_start(line).is_some())
+        .map(|idx| idx + 1)
+}
+
+/// `text` without comments, so a commented flow whose layout is already canonical is left alone.
+fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        let kept = match comment_start(line) {
+            Some(idx) if line[..idx].trim().is_empty() => continue,
+            Some(idx) => line[..idx].trim_end(),
+            None => line,
+        };
+        out.push_str(kept);
+        out.push('\n');
+    }
+    out
+}
+
+fn handle_minimize(args: &MinimizeArgs, format: OutputFormat) -> Result<()> {
+    let yaml = fs::read_to_string(&args.flow_path)
+        .with_context(|| format!("failed to read {}", args.flow_path.display()))?;
+    let minimized = minimize_flow(&yaml, |candidate| args.check.reproduces(candidate))?;
+    if let Some(out) = &args.out {
+        write_flow_file(out, &minimized.yaml, true, false)?;
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "flow": args.out.as_ref().map(|out| out.display().to_string()),
+            "minimized": minimized,
+        }))?;
+    } else {
+        if args.out.is_none() {
+            print!("{}", minimized.yaml);
         }
-        flow_ir.nodes.insert(step_id.clone(), node);
+        eprintln!(
+            "minimized {} -> {} node(s), {} -> {} route(s) in {} check(s)",
+            minimized.nodes_before,
+            minimized.nodes_after,
+            minimized.routes_before,
+            minimized.routes_after,
+            minimized.checks
+        );
+    }
+    Ok(())
+}
 
-        let abi_version = args
-            .abi_version
-            .clone()
-            .unwrap_or_else(|| wizard_ops::abi_version_from_abi(spec.abi));
-        flow_meta::set_component_entry(
-            &mut flow_ir.meta,
-            &step_id,
-            &component_identity,
-            &abi_version,
-            resolved.digest.as_deref(),
-            &wizard_ops::describe_exports_for_meta(spec.abi),
-            contract_meta.as_ref(),
+fn handle_generate_random(args: &GenerateRandomArgs, format: OutputFormat) -> Result<()> {
+    if !(0.0..=1.0).contains(&args.branchiness) {
+        anyhow::bail!(
+            "--branchiness must be between 0.0 and 1.0, got {}",
+            args.branchiness
         );
-        flow_meta::ensure_hints_empty(&mut flow_ir.meta, &step_id);
+    }
+    let mut generator = FlowGenerator::new(args.seed);
+    generator.nodes = args.nodes;
+    generator.branchiness = args.branchiness;
+    generator.flow_type = args.flow_type.clone();
+    if !args.routing.is_empty() {
+        generator.routing_kinds = args
+            .routing
+            .iter()
+            .copied()
+            .map(RoutingKind::from)
+            .collect();
+    }
+    let yaml = generator.generate_yaml();
+    compile_flow(load_ygtc_from_str(&yaml)?)
+        .with_context(|| format!("generated flow for seed {} does not compile", args.seed))?;
+    if let Some(out) = &args.out {
+        write_flow_file(out, &yaml, args.force, false)?;
+    }
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "seed": args.seed,
+            "flow": args.out.as_ref().map(|out| out.display().to_string()),
+            "yaml": yaml,
+        }))?;
+    } else if let Some(out) = &args.out {
+        println!(
+            "Wrote random flow (seed {}) to {}",
+            args.seed,
+            out.display()
+        );
+    } else {
+        print!("{yaml}");
+    }
+    Ok(())
+}
 
-        let doc_out = flow_ir.to_doc()?;
-        let yaml = serialize_doc(&doc_out)?;
-        load_ygtc_from_str(&yaml)?;
-        if !args.dry_run {
-            let mut sorted = std::collections::BTreeMap::new();
-            for (key, value) in &answers {
-                sorted.insert(key.clone(), value.clone());
+fn handle_component_diff_schema(
+    args: &ComponentDiffSchemaArgs,
+    format: OutputFormat,
+) -> Result<()> {
+    let read_manifest = |path: &Path| -> Result<serde_json::Value> {
+        let mut manifest = load_manifest_json(path)?;
+        normalize_manifest_value(&mut manifest);
+        Ok(manifest)
+    };
+    let old = read_manifest(&args.old)?;
+    let new = read_manifest(&args.new)?;
+    let component_id = old
+        .get("id")
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| anyhow!("manifest {} has no id", args.old.display()))?;
+    let changes = diff_manifests(&old, &new);
+
+    let mut flow_paths = Vec::new();
+    if !changes.is_empty() {
+        collect_flow_files(&args.dir, &mut flow_paths)?;
+        flow_paths.sort();
+    }
+    let mut breaks = Vec::new();
+    for flow_path in &flow_paths {
+        let sidecar_path = sidecar_path_for_flow(flow_path);
+        let Ok(sidecar) = read_flow_resolve(&sidecar_path) else {
+            continue;
+        };
+        let flow_ir = match load_ygtc_from_path(flow_path).and_then(FlowIr::from_doc) {
+            Ok(flow_ir) => flow_ir,
+            Err(err) => {
+                eprintln!("WARN {}: skipped ({err})", flow_path.display());
+                continue;
             }
-            let _paths = answers::write_answers(
-                &base_dir,
-                &flow_ir.id,
-                &step_id,
-                wizard_mode.as_str(),
-                &sorted,
-                args.overwrite_answers,
-            )?;
-            wizard_state::update_wizard_state(
-                &args.flow_path,
-                &flow_ir.id,
-                &step_id,
-                wizard_mode.as_str(),
-                &locale,
-            )?;
-            write_flow_file(&args.flow_path, &yaml, true, backup)?;
-            sidecar.nodes.insert(
-                step_id.clone(),
-                NodeResolveV1 {
-                    source: resolved.source,
-                    mode: None,
-                },
-            );
-            write_sidecar(&sidecar_path, &sidecar)?;
-            if let Err(err) =
-                write_flow_resolve_summary_for_node(&args.flow_path, &step_id, &sidecar)
-                    .with_context(|| {
-                        format!("update resolve summary for {}", args.flow_path.display())
-                    })
-            {
-                eprintln!("warning: {err}");
+        };
+        let display = flow_path
+            .strip_prefix(&args.dir)
+            .unwrap_or(flow_path)
+            .display()
+            .to_string();
+        for (node_id, entry) in &sidecar.nodes {
+            let Some(node) = flow_ir.nodes.get(node_id) else {
+                continue;
+            };
+            // Stay offline: local bindings are matched by the manifest next to the wasm, others
+            // by the component id recorded in flow meta.
+            let bound_id = match &entry.source {
+                ComponentSourceRefV1::Local { .. } => {
+                    resolve_component_manifest_path(&entry.source, flow_path)
+                        .ok()
+                        .and_then(|path| read_manifest(&path).ok())
+                        .and_then(|manifest| {
+                            manifest
+                                .get("id")
+                                .and_then(|id| id.as_str())
+                                .map(str::to_string)
+                        })
+                }
+                _ => flow_ir
+                    .meta
+                    .as_ref()
+                    .and_then(|meta| meta.get(flow_meta::META_NAMESPACE))
+                    .and_then(|greentic| greentic.get("components"))
+                    .and_then(|components| components.get(node_id))
+                    .and_then(|component| component.get("component_id"))
+                    .and_then(|id| id.as_str())
+                    .map(str::to_string),
+            };
+            if bound_id.as_deref() != Some(component_id) {
+                continue;
             }
-            if matches!(format, OutputFormat::Json) {
-                let payload = json!({
-                    "ok": true,
-                    "action": "update-step",
-                    "node_id": step_id,
-                    "flow_path": args.flow_path.display().to_string()
-                });
-                print_json_payload_with_optional_diagnostic(
-                    payload,
-                    deprecation_diagnostic.as_ref(),
-                )?;
-            } else {
-                println!("Updated step '{}' in {}", step_id, args.flow_path.display());
+            let payload = extract_config_value(&node.payload);
+            for reason in payload_breaks(&old, &changes, &node.operation, &payload) {
+                breaks.push((display.clone(), node_id.clone(), reason));
             }
-        } else if matches!(format, OutputFormat::Json) {
-            let payload =
-                json!({"ok": true, "action": "update-step", "dry_run": true, "flow": yaml});
-            print_json_payload_with_optional_diagnostic(payload, deprecation_diagnostic.as_ref())?;
-        } else {
-            print!("{yaml}");
         }
-        return Ok(());
     }
-    let (_sidecar_path, sidecar) = ensure_sidecar(&args.flow_path)?;
-    if let Some(component) = args.component.as_deref() {
-        validate_component_ref(component)?;
+
+    if matches!(format, OutputFormat::Json) {
+        let entries: Vec<_> = breaks
+            .iter()
+            .map(|(flow, node_id, reason)| json!({ "flow": flow, "node_id": node_id, "reason": reason }))
+            .collect();
+        print_json_payload(&json!({
+            "component_id": component_id,
+            "changes": changes,
+            "breaks": entries,
+        }))?;
+    } else {
+        for change in &changes {
+            let scope = change
+                .operation
+                .as_deref()
+                .map_or("config_schema".to_string(), |op| {
+                    format!("operation '{op}'")
+                });
+            println!("CHANGE {scope}: {}", change.message);
+        }
+        for (flow, node_id, reason) in &breaks {
+            println!("BREAK  {flow} node '{node_id}': {reason}");
+        }
+        println!(
+            "{} breaking change(s), {} affected node finding(s)",
+            changes.len(),
+            breaks.len()
+        );
     }
-    let sidecar_entry = sidecar.nodes.get(&step_id).ok_or_else(|| {
-        anyhow::anyhow!(
-            "no sidecar mapping for node '{}'; run greentic-flow bind-component or re-add the step with --component/--local-wasm",
-            step_id
-        )
-    })?;
-    let component_payload = load_component_payload(&sidecar_entry.source, &args.flow_path)?;
-    let mut node = flow_ir
-        .nodes
-        .get(&step_id)
-        .cloned()
-        .ok_or_else(|| anyhow::anyhow!("step '{}' not found", step_id))?;
-    let mut merged_payload = node.payload.clone();
-    if let Some(component_defaults) = component_payload {
-        merged_payload = merge_payload(merged_payload, Some(component_defaults));
+    if args.strict && !breaks.is_empty() {
+        anyhow::bail!(
+            "{} node finding(s) would break under {}",
+            breaks.len(),
+            args.new.display()
+        );
     }
-    let mut answers = parse_answers_map(args.answers.as_deref(), args.answers_file.as_deref())?;
-    let mut new_operation = args
-        .operation
-        .clone()
-        .unwrap_or_else(|| node.operation.clone());
-    let new_payload = if args.mode == "config" {
-        let manifest_path =
-            resolve_component_manifest_path(&sidecar_entry.source, &args.flow_path)?;
-        let (config_flow, schema_path) =
-            resolve_config_flow(None, std::slice::from_ref(&manifest_path), "custom")?;
-        let mut base_answers = QuestionAnswers::new();
-        if let Some(obj) = merged_payload.as_object() {
-            base_answers.extend(obj.clone());
-        }
-        base_answers.extend(answers.clone());
-        let questions = questions_from_config_flow_text(&config_flow)?;
-        if !questions.is_empty() {
-            warn_unknown_keys(&answers, &questions);
-            println!("{}", wizard_header(&component_identity, "config"));
-            if args.non_interactive {
-                validate_required(&questions, &base_answers)?;
-            } else {
-                base_answers = run_interactive_with_seed(&questions, base_answers)?;
+    Ok(())
+}
+
+/// Collect `*.ygtc` files under `dir`, skipping hidden directories.
+fn collect_flow_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("failed to read directory {}", dir.display()))?;
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("failed to read directory entry in {}", dir.display()))?
+            .path();
+        if path.is_dir() {
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if !hidden {
+                collect_flow_files(&path, out)?;
             }
+        } else if path.extension() == Some(OsStr::new("ygtc")) {
+            out.push(path);
         }
-        let flow_name = "custom";
-        let source_desc = format!("dev_flows.{flow_name}");
-        if questions.is_empty() {
-            require_schema(
-                schema_mode,
-                &component_identity,
-                flow_name,
-                &manifest_path,
-                &source_desc,
-                None,
-            )?;
-        } else {
-            let dev_schema = schema_for_questions(&questions);
-            require_schema(
-                schema_mode,
-                &component_identity,
-                flow_name,
-                &manifest_path,
-                &source_desc,
-                Some(&dev_schema),
-            )?;
+    }
+    Ok(())
+}
+
+fn collect_bundle_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        fs::read_dir(dir).with_context(|| format!("failed to read directory {}", dir.display()))?;
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("failed to read directory entry in {}", dir.display()))?
+            .path();
+        if path.is_dir() {
+            collect_bundle_files(&path, out)?;
+        } else if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().ends_with(BUNDLE_FILE_SUFFIX))
+        {
+            out.push(path);
         }
-        let answers_map = answers_to_json_map(base_answers);
-        let output = run_config_flow(
-            &config_flow,
-            &schema_path,
-            &answers_map,
-            Some(component_identity.clone()),
-        )?;
-        let normalized = normalize_node_map(output.node)?;
-        if args.operation.is_none() {
-            new_operation = normalized.operation.clone();
+    }
+    Ok(())
+}
+
+fn handle_component_show(args: &ComponentShowArgs, format: OutputFormat) -> Result<()> {
+    let component_identity = args
+        .component
+        .clone()
+        .or_else(|| {
+            args.local_wasm
+                .as_ref()
+                .and_then(|p| p.file_stem().and_then(|s| s.to_str()))
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_else(|| "component".to_string());
+    // No flow is involved; relative paths resolve against the working directory.
+    let anchor_path = Path::new(".").join("component.show");
+    let wizard_mode = wizard_ops::WizardMode::Setup;
+    let resolved = resolve_wizard_component(
+        &anchor_path,
+        wizard_mode,
+        args.local_wasm.as_ref(),
+        args.component.as_ref(),
+        None,
+        args.resolver.as_ref(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )?;
+    let spec = if let Some(fixture) = resolved.fixture.as_ref() {
+        wizard_ops::WizardSpecOutput {
+            abi: fixture.abi,
+            describe_cbor: fixture.describe_cbor.clone(),
+            descriptor: None,
+            qa_spec_cbor: fixture.qa_spec_cbor.clone(),
+            answers_schema_cbor: None,
         }
-        normalized.payload
-    } else if args.mode == "default" {
-        let mut payload = merged_payload;
-        let mut used_writes = false;
-        let mut manifest_path_for_validation: Option<PathBuf> = None;
-        if let Ok(manifest_path) =
-            resolve_component_manifest_path(&sidecar_entry.source, &args.flow_path)
-        {
-            manifest_path_for_validation = Some(manifest_path.clone());
-            let questions = questions_from_manifest(&manifest_path, "default")?;
-            if !questions.is_empty() {
-                let mut base_answers = extract_answers_from_payload(&questions, &payload);
-                warn_unknown_keys(&answers, &questions);
-                base_answers.extend(answers.clone());
-                println!("{}", wizard_header(&component_identity, "default"));
-                if args.non_interactive {
-                    validate_required(&questions, &base_answers)?;
-                } else {
-                    base_answers = run_interactive_with_seed(&questions, base_answers)?;
-                }
-                answers = base_answers;
-                if questions.iter().any(|q| q.writes_to.is_some()) {
-                    payload = apply_writes_to(payload, &questions, &answers)?;
-                    used_writes = true;
-                }
-            }
+    } else {
+        wizard_ops::fetch_wizard_spec(&resolved.wasm, wizard_mode)
+            .map_err(|err| wrap_wizard_error(err, &component_identity, "describe", None))?
+    };
+    let mut view = if let Some(descriptor) = spec.descriptor.as_ref() {
+        ComponentView::from_descriptor(descriptor)?
+    } else if !spec.describe_cbor.is_empty() {
+        ComponentView::from_describe(&contracts::decode_component_describe(&spec.describe_cbor)?)?
+    } else {
+        anyhow::bail!("component {component_identity} returned no describe payload");
+    };
+    if !spec.qa_spec_cbor.is_empty() {
+        let qa_spec = wizard_ops::decode_component_qa_spec(&spec.qa_spec_cbor, wizard_mode)?;
+        view = view.with_setup_spec(&qa_spec)?;
+    }
+    if let Some(operation) = &args.operation {
+        view = view.select_operation(operation)?;
+    }
+
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": true,
+            "action": "component-show",
+            "reference": component_identity,
+            "component": view,
+        }))?;
+    } else {
+        print!("{}", view.render());
+    }
+    Ok(())
+}
+
+fn handle_component_setup(args: ComponentSetupArgs, format: OutputFormat) -> Result<()> {
+    if args.out.exists() && !args.force {
+        anyhow::bail!(
+            "output {} already exists; use --force to overwrite",
+            args.out.display()
+        );
+    }
+    if let Some(parent) = args.out.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("create output directory {}", parent.display()))?;
+    }
+    let component_identity = args
+        .component
+        .clone()
+        .or_else(|| {
+            args.local_wasm
+                .as_ref()
+                .and_then(|p| p.file_stem().and_then(|s| s.to_str()))
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_else(|| "component".to_string());
+    // No flow exists yet; the output file anchors relative paths and component i18n lookup.
+    let anchor_buf = match args.out.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => args.out.clone(),
+        _ => Path::new(".").join(&args.out),
+    };
+    let anchor_path = anchor_buf.as_path();
+    let wizard_mode = wizard_ops::WizardMode::Setup;
+    let resolved = resolve_wizard_component(
+        anchor_path,
+        wizard_mode,
+        args.local_wasm.as_ref(),
+        args.component.as_ref(),
+        None,
+        args.resolver.as_ref(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )?;
+    let spec = if let Some(fixture) = resolved.fixture.as_ref() {
+        wizard_ops::WizardSpecOutput {
+            abi: fixture.abi,
+            describe_cbor: fixture.describe_cbor.clone(),
+            descriptor: None,
+            qa_spec_cbor: fixture.qa_spec_cbor.clone(),
+            answers_schema_cbor: None,
         }
-        let final_payload = if used_writes {
-            payload.clone()
-        } else {
-            merge_payload(payload, answers_to_value(&answers))
-        };
-        if let Some(manifest_path) = manifest_path_for_validation.as_ref() {
-            let schema_resolution = resolve_input_schema(manifest_path, &new_operation)?;
-            let schema_present = require_schema(
-                schema_mode,
-                &schema_resolution.component_id,
-                &schema_resolution.operation,
-                &schema_resolution.manifest_path,
-                "operations[].input_schema",
-                schema_resolution.schema.as_ref(),
-            )?;
-            if schema_present.is_some() {
-                validate_payload_against_schema(&schema_resolution, &final_payload)?;
-            }
+    } else {
+        wizard_ops::fetch_wizard_spec(&resolved.wasm, wizard_mode)
+            .map_err(|err| wrap_wizard_error(err, &component_identity, "describe", None))?
+    };
+    let qa_spec = wizard_ops::decode_component_qa_spec(&spec.qa_spec_cbor, wizard_mode)?;
+    let (mut catalog, locale) = default_i18n_catalog(args.locale.as_deref());
+    merge_component_i18n_catalog(&mut catalog, &locale, anchor_path, &resolved.source);
+
+    let mut answers = QuestionAnswers::new();
+    answers.extend(parse_answers_map(
+        args.answers.as_deref(),
+        args.answers_file.as_deref(),
+    )?);
+    wizard_ops::merge_default_answers(&qa_spec, &mut answers);
+    if !qa_spec.questions.is_empty() {
+        qa_runner::warn_unknown_keys(&answers, &qa_spec, &catalog, &locale);
+        if matches!(format, OutputFormat::Human) {
+            println!(
+                "{}",
+                wizard_header(&component_identity, wizard_mode.as_str())
+            );
         }
-        final_payload
+        answers = run_component_qa_with_qa_lib(
+            &qa_spec,
+            &catalog,
+            &locale,
+            answers,
+            args.interactive,
+            None,
+        )?;
+    }
+
+    let answers_cbor = wizard_ops::answers_to_cbor(&answers)?;
+    let config_cbor = if let Some(fixture) = resolved.fixture.as_ref() {
+        fixture.apply_answers_cbor.clone()
     } else {
-        merged_payload
+        wizard_ops::apply_wizard_answers(
+            &resolved.wasm,
+            spec.abi,
+            wizard_mode,
+            &wizard_ops::empty_cbor_map(),
+            &answers_cbor,
+        )
+        .map_err(|err| wrap_wizard_error(err, &component_identity, "apply-answers", None))?
     };
-    let new_routing = if let Some(routing) = build_update_routing(&args)? {
-        routing
+    let config_json = wizard_ops::cbor_to_json(&config_cbor)?;
+    ensure_wizard_config_not_error(&component_identity, wizard_mode, &config_json)?;
+
+    let as_cbor = args
+        .out
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("cbor"));
+    if as_cbor {
+        fs::write(&args.out, &config_cbor)
     } else {
-        node.routing.clone()
-    };
+        fs::write(
+            &args.out,
+            format!("{}\n", serde_json::to_string_pretty(&config_json)?),
+        )
+    }
+    .with_context(|| format!("write {}", args.out.display()))?;
+
+    if matches!(format, OutputFormat::Json) {
+        print_json_payload(&json!({
+            "ok": true,
+            "action": "component-setup",
+            "component": component_identity,
+            "out": args.out.display().to_string(),
+            "encoding": if as_cbor { "cbor" } else { "json" },
+            "config": config_json,
+        }))?;
+    } else {
+        println!(
+            "Wrote {} setup config to {}",
+            component_identity,
+            args.out.display()
+        );
+    }
+    Ok(())
+}
 
-    node.operation = new_operation;
-    node.payload = new_payload;
-    node.routing = new_routing;
-    flow_ir.nodes.insert(step_id.clone(), node);
+fn handle_apply_plan(args: ApplyPlanArgs, format: OutputFormat, backup: bool) -> Result<()> {
+    let plan_path = args
+        .plan_file
+        .clone()
+        .or_else(|| args.plan.clone())
+        .ok_or_else(|| anyhow!("pass a plan file"))?;
+    let plan_text = fs::read_to_string(&plan_path)
+        .with_context(|| format!("read plan {}", plan_path.display()))?;
+    let plan = FlowEditPlan::from_yaml_str(&plan_text)?;
+    let plan_dir = plan_path.parent().unwrap_or_else(|| Path::new("."));
+    let flow_path = match (&args.flow_path, &plan.flow) {
+        (Some(path), _) => path.clone(),
+        (None, Some(rel)) => plan_dir.join(rel),
+        (None, None) => anyhow::bail!(
+            "plan {} does not name a flow; pass --flow",
+            plan_path.display()
+        ),
+    };
+    let doc = load_ygtc_from_path(&flow_path)?;
+    let flow_ir = FlowIr::from_doc(doc)?;
+    let empty_paths: Vec<PathBuf> = Vec::new();
+    let catalog = ManifestCatalog::load_from_paths(&empty_paths);
+    let outcome = apply_edit_plan(&flow_ir, &plan, &catalog)?;
 
-    let doc_out = flow_ir.to_doc()?;
-    // Adjust entrypoint if it targeted the removed node in other ops; here node stays, so no-op.
+    let doc_out = outcome.flow.to_doc()?;
     let yaml = serialize_doc(&doc_out)?;
-    load_ygtc_from_str(&yaml)?; // schema validation
-    if !args.dry_run {
-        write_flow_file(&args.flow_path, &yaml, true, backup)?;
-        if let Err(err) = write_flow_resolve_summary_for_node(&args.flow_path, &step_id, &sidecar)
-            .with_context(|| format!("update resolve summary for {}", args.flow_path.display()))
-        {
-            eprintln!("warning: {err}");
+    load_ygtc_from_str(&yaml)?;
+    // Resolve every binding before touching disk so a bad reference rejects the whole plan.
+    let mut bound = Vec::new();
+    for (node_id, binding) in &outcome.bindings {
+        let (source, mode) = match binding {
+            PlanBinding::Component(reference) => {
+                resolve_component_source_inputs(None, Some(reference), false, &flow_path)
+            }
+            PlanBinding::LocalWasm(path) => {
+                let path = plan_dir.join(path);
+                resolve_component_source_inputs(Some(&path), None, false, &flow_path)
+            }
+        }
+        .with_context(|| format!("bind node '{node_id}'"))?;
+        bound.push((node_id.clone(), NodeResolveV1 { source, mode }));
+    }
+    let steps: Vec<serde_json::Value> = outcome
+        .steps
+        .iter()
+        .map(|step| json!({"index": step.index, "op": step.op, "node_id": step.node_id}))
+        .collect();
+    if args.write {
+        let _writing = cancel::write_guard()?;
+        let sidecar_changes = !outcome.removed.is_empty() || !bound.is_empty();
+        let original = fs::read_to_string(&flow_path)
+            .with_context(|| format!("failed to read {}", flow_path.display()))?;
+        write_flow_file(&flow_path, &yaml, true, backup)?;
+        if sidecar_changes {
+            // One sidecar write for the whole plan; if it fails the flow is put back.
+            let written = ensure_sidecar(&flow_path).and_then(|(sidecar_path, mut sidecar)| {
+                for node_id in &outcome.removed {
+                    sidecar.nodes.remove(node_id);
+                }
+                for (node_id, entry) in &bound {
+                    sidecar.nodes.insert(node_id.clone(), entry.clone());
+                }
+                write_sidecar(&sidecar_path, &sidecar)?;
+                Ok(sidecar)
+            });
+            let sidecar = match written {
+                Ok(sidecar) => sidecar,
+                Err(err) => {
+                    write_flow_file(&flow_path, &original, true, false)
+                        .with_context(|| format!("restore {}", flow_path.display()))?;
+                    return Err(err.context("apply-plan rolled back"));
+                }
+            };
+            for node_id in &outcome.removed {
+                let _ = wizard_state::remove_wizard_step(&flow_path, &outcome.flow.id, node_id);
+                let _ = answer_provenance::remove_answer_provenance(&flow_path, node_id);
+                // The flow and sidecar are committed; leftover records only warn.
+                if let Err(err) = config_store::remove_node_config(&flow_path, node_id) {
+                    eprintln!("warning: {err}");
+                }
+                if let Err(err) = remove_flow_resolve_summary_node(&flow_path, node_id)
+                    .with_context(|| format!("update resolve summary for {}", flow_path.display()))
+                {
+                    eprintln!("warning: {err}");
+                }
+            }
+            for (node_id, _) in &bound {
+                if let Err(err) = write_flow_resolve_summary_for_node(&flow_path, node_id, &sidecar)
+                    .with_context(|| format!("update resolve summary for {}", flow_path.display()))
+                {
+                    eprintln!("warning: {err}");
+                }
+            }
         }
         if matches!(format, OutputFormat::Json) {
             let payload = json!({
                 "ok": true,
-                "action": "update-step",
-                "node_id": step_id,
-                "flow_path": args.flow_path.display().to_string()
+                "action": "apply-plan",
+                "flow_path": flow_path.display().to_string(),
+                "operations": steps,
+                "bound": bound.iter().map(|(node_id, _)| node_id).collect::<Vec<_>>()
             });
             print_json_payload(&payload)?;
         } else {
-            println!("Updated step '{}' in {}", step_id, args.flow_path.display());
+            println!(
+                "Applied {} operation(s) to {}",
+                outcome.steps.len(),
+                flow_path.display()
+            );
         }
     } else if matches!(format, OutputFormat::Json) {
-        let payload = json!({"ok": true, "action": "update-step", "dry_run": true, "flow": yaml});
+        let payload = json!({
+            "ok": true,
+            "action": "apply-plan",
+            "dry_run": true,
+            "operations": steps,
+            "flow": yaml
+        });
         print_json_payload(&payload)?;
     } else {
         print!("{yaml}");
@@ -7978,229 +14178,365 @@ fn handle_update_step_with_qa_io(
     Ok(())
 }
 
-fn handle_delete_step(args: DeleteStepArgs, format: OutputFormat, backup: bool) -> Result<()> {
-    let (sidecar_path, mut sidecar) = ensure_sidecar(&args.flow_path)?;
-    let doc = load_ygtc_from_path(&args.flow_path)?;
-    let mut flow_ir = FlowIr::from_doc(doc)?;
-    let component_identity = args
-        .component_id
-        .clone()
-        .or_else(|| args.component.clone())
-        .or_else(|| {
-            args.local_wasm
-                .as_ref()
-                .and_then(|p| p.file_stem().and_then(|s| s.to_str()))
-                .map(|s| s.to_string())
-        })
-        .unwrap_or_else(|| "component".to_string());
-    let target = resolve_step_id(args.step.clone(), args.component_id.as_ref(), &flow_ir.meta)?;
-    let wizard_requested = args.component_id.is_some() || args.wizard_mode.is_some();
-    let mut deprecation_diagnostic: Option<serde_json::Value> = None;
-    if wizard_requested {
-        let wizard_mode_arg = args.wizard_mode.unwrap_or(WizardModeArg::Remove);
-        deprecation_diagnostic = warn_deprecated_wizard_mode(wizard_mode_arg);
-        let wizard_mode = wizard_mode_arg.to_mode();
-        if matches!(wizard_mode, wizard_ops::WizardMode::Remove) {
-            confirm_remove_mode(args.interactive)?;
+fn handle_generate(
+    args: GenerateArgs,
+    schema_mode: SchemaMode,
+    format: OutputFormat,
+    backup: bool,
+) -> Result<()> {
+    let spec_text = fs::read_to_string(&args.spec)
+        .with_context(|| format!("read spec {}", args.spec.display()))?;
+    let spec: PipelineSpec = serde_yaml_bw::from_str(&spec_text)
+        .with_context(|| format!("parse spec {}", args.spec.display()))?;
+    let spec_dir = args.spec.parent().unwrap_or_else(|| Path::new("."));
+    let flow_path = match (&args.flow_path, &spec.flow) {
+        (Some(path), _) => path.clone(),
+        (None, Some(rel)) => spec_dir.join(rel),
+        (None, None) => anyhow::bail!(
+            "spec {} does not name a flow; pass --flow",
+            args.spec.display()
+        ),
+    };
+    if spec.steps.is_empty() {
+        anyhow::bail!("spec {} has no steps", args.spec.display());
+    }
+    let resolver = args.resolver.clone().or_else(|| spec.resolver.clone());
+
+    // Snapshot everything the steps write so a failing step leaves the previous state untouched.
+    let snapshot = GenerateSnapshot::capture(&flow_path, &spec.id);
+    let jobs = args.jobs.map(|jobs| jobs as usize).unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(usize::from)
+            .unwrap_or(1)
+    });
+    let result = generate_pipeline(
+        &spec,
+        spec_dir,
+        &flow_path,
+        resolver,
+        args.force,
+        schema_mode,
+        backup,
+        jobs,
+    );
+    let node_ids = match result {
+        Ok(node_ids) => node_ids,
+        Err(err) => {
+            snapshot.restore();
+            return Err(err);
         }
-        let resolved = resolve_wizard_component(
-            &args.flow_path,
-            wizard_mode,
-            args.local_wasm.as_ref(),
-            args.component.as_ref(),
-            args.component_id.as_ref(),
-            args.resolver.as_ref(),
-            args.distributor_url.as_ref(),
-            args.auth_token.as_ref(),
-            args.tenant.as_ref(),
-            args.env.as_ref(),
-            args.pack.as_ref(),
-            args.component_version.as_ref(),
-        )?;
-        let spec = if let Some(fixture) = resolved.fixture.as_ref() {
-            wizard_ops::WizardSpecOutput {
-                abi: fixture.abi,
-                describe_cbor: fixture.describe_cbor.clone(),
-                descriptor: None,
-                qa_spec_cbor: fixture.qa_spec_cbor.clone(),
-                answers_schema_cbor: None,
-            }
-        } else {
-            wizard_ops::fetch_wizard_spec(&resolved.wasm_bytes, wizard_mode)
-                .map_err(|err| wrap_wizard_error(err, &component_identity, "describe", None))?
+    };
+
+    if matches!(format, OutputFormat::Json) {
+        let payload = json!({
+            "ok": true,
+            "action": "generate",
+            "flow_path": flow_path.display().to_string(),
+            "nodes": node_ids
+        });
+        print_json_payload(&payload)?;
+    } else {
+        println!(
+            "Generated flow '{}' with {} step(s) at {}",
+            spec.id,
+            node_ids.len(),
+            flow_path.display()
+        );
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn generate_pipeline(
+    spec: &PipelineSpec,
+    spec_dir: &Path,
+    flow_path: &Path,
+    resolver: Option<String>,
+    force: bool,
+    schema_mode: SchemaMode,
+    backup: bool,
+    jobs: usize,
+) -> Result<Vec<String>> {
+    write_new_flow_file(NewFlowFileSpec {
+        flow_path: flow_path.to_path_buf(),
+        flow_id: spec.id.clone(),
+        flow_type: spec.flow_type.clone(),
+        schema_version: 2,
+        name: spec.name.clone(),
+        description: spec.description.clone(),
+        force,
+        backup,
+    })?;
+    let wizard_modes = spec
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(idx, step)| match (&step.wizard_mode, &step.operation) {
+            (Some(mode), _) => WizardModeArg::from_str(mode, true)
+                .map(Some)
+                .map_err(|err| anyhow!("steps[{idx}]: invalid wizard_mode: {err}")),
+            (None, None) => Ok(Some(WizardModeArg::Default)),
+            (None, Some(_)) => Ok(None),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if jobs > 1 {
+        prefetch_pipeline_wizards(spec, spec_dir, flow_path, &resolver, &wizard_modes, jobs);
+    }
+    let mut previous: Option<String> = None;
+    let mut node_ids = Vec::new();
+    for (idx, step) in spec.steps.iter().enumerate() {
+        let wizard_mode = wizard_modes[idx];
+        let answers = step
+            .answers
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let payload = step
+            .payload
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?
+            .unwrap_or_else(|| "{}".to_string());
+        let report = add_step(
+            AddStepArgs {
+                component_id: step.component_id.clone(),
+                flow_path: flow_path.to_path_buf(),
+                after: previous.clone(),
+                before: None,
+                mode: AddStepMode::Default,
+                pack_alias: None,
+                wizard_mode,
+                operation: step.operation.clone(),
+                payload,
+                routing_out: true,
+                routing_reply: false,
+                routing_next: None,
+                routing_multi_to: None,
+                routing_json: None,
+                routing_to_anchor: false,
+                config_flow: None,
+                answers,
+                answers_file: None,
+                preset: None,
+                answers_dir: None,
+                overwrite_answers: true,
+                reask: false,
+                locale: None,
+                interactive: false,
+                allow_cycles: false,
+                dry_run: false,
+                write: false,
+                validate_only: false,
+                manifests: Vec::new(),
+                node_id: step.node_id.clone(),
+                defaults_from: None,
+                component_ref: step.component.clone(),
+                local_wasm: step.local_wasm.as_ref().map(|p| spec_dir.join(p)),
+                distributor_url: None,
+                auth_token: None,
+                tenant: None,
+                env: None,
+                pack: None,
+                component_version: None,
+                abi_version: None,
+                resolver: resolver.clone(),
+                pin: step.pin,
+                allow_contract_change: false,
+            },
+            schema_mode,
+            false,
+            None,
+            false,
+        )
+        .with_context(|| format!("generate step #{idx}"))?;
+        let AddStepOutcome::Inserted { node_id: added } = report.outcome else {
+            anyhow::bail!("generate step #{idx} did not add a node");
         };
-        let qa_spec = wizard_ops::decode_component_qa_spec(&spec.qa_spec_cbor, wizard_mode)?;
-        let (mut catalog, locale) = default_i18n_catalog(args.locale.as_deref());
-        merge_component_i18n_catalog(&mut catalog, &locale, &args.flow_path, &resolved.source);
+        node_ids.push(added.clone());
+        previous = Some(added);
+    }
+    Ok(node_ids)
+}
 
-        let base_dir = answers_base_dir(&args.flow_path, args.answers_dir.as_deref());
-        let fallback_path = if !args.reask && args.answers.is_none() && args.answers_file.is_none()
-        {
-            wizard_answers_json_path_compat(&base_dir, &flow_ir.id, &target, wizard_mode)
-        } else {
-            None
+/// Run the component wizards of a pipeline's steps concurrently, `jobs` at a time, so the
+/// sequential add-step pass finds their results in [`wizard_cache`]. Every step of a pipeline
+/// is non-interactive, so its wizard sees no other input than the spec's answers and an empty
+/// config. Steps whose component cannot be resolved here are skipped; their add-step reports
+/// the error.
+fn prefetch_pipeline_wizards(
+    spec: &PipelineSpec,
+    spec_dir: &Path,
+    flow_path: &Path,
+    resolver: &Option<String>,
+    wizard_modes: &[Option<WizardModeArg>],
+    jobs: usize,
+) {
+    let mut runs = Vec::new();
+    for (step, mode) in spec.steps.iter().zip(wizard_modes) {
+        let Some(mode) = mode.map(WizardModeArg::to_mode) else {
+            continue;
         };
-        let answers_file = args.answers_file.as_deref().or(fallback_path.as_deref());
-        let mut answers = parse_answers_map(args.answers.as_deref(), answers_file)?;
-        wizard_ops::merge_default_answers(&qa_spec, &mut answers);
-        if !qa_spec.questions.is_empty() {
-            qa_runner::warn_unknown_keys(&answers, &qa_spec, &catalog, &locale);
-            println!(
-                "{}",
-                wizard_header(&component_identity, wizard_mode.as_str())
-            );
-            answers = run_component_qa_with_qa_lib(
-                &qa_spec,
-                &catalog,
-                &locale,
+        let local_wasm = step.local_wasm.as_ref().map(|path| spec_dir.join(path));
+        let resolved = resolve_wizard_component(
+            flow_path,
+            mode,
+            local_wasm.as_ref(),
+            step.component.as_ref(),
+            step.component_id.as_ref(),
+            resolver.as_ref(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        let answers = step
+            .answers
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(anyhow::Error::from)
+            .and_then(|answers| parse_answers_map(answers.as_deref(), None));
+        if let (Ok(resolved), Ok(answers)) = (resolved, answers)
+            && resolved.fixture.is_none()
+        {
+            runs.push(wizard_ops::WizardPrefetch {
+                wasm: resolved.wasm,
+                mode,
                 answers,
-                args.interactive,
-                None,
-            )?;
-        }
-
-        let answers_cbor = wizard_ops::answers_to_cbor(&answers)?;
-        let target_node = flow_ir
-            .nodes
-            .get(&target)
-            .cloned()
-            .ok_or_else(|| anyhow::anyhow!("step '{}' not found", target))?;
-        let current_config = wizard_ops::json_to_cbor(&target_node.payload)?;
-        if let Some(fixture) = resolved.fixture.as_ref() {
-            let _ = fixture.apply_answers_cbor.clone();
-        } else {
-            let _ = wizard_ops::apply_wizard_answers(
-                &resolved.wasm_bytes,
-                spec.abi,
-                wizard_mode,
-                &current_config,
-                &answers_cbor,
-            )
-            .map_err(|err| wrap_wizard_error(err, &component_identity, "apply-answers", None))?;
-        }
-        flow_meta::clear_component_entry(&mut flow_ir.meta, &target);
-        if args.write {
-            let mut sorted = std::collections::BTreeMap::new();
-            for (key, value) in &answers {
-                sorted.insert(key.clone(), value.clone());
-            }
-            let _paths = answers::write_answers(
-                &base_dir,
-                &flow_ir.id,
-                &target,
-                wizard_mode.as_str(),
-                &sorted,
-                args.overwrite_answers,
-            )?;
-            wizard_state::update_wizard_state(
-                &args.flow_path,
-                &flow_ir.id,
-                &target,
-                wizard_mode.as_str(),
-                &locale,
-            )?;
+            });
         }
     }
+    if runs.len() > 1 {
+        wizard_cache().prefetch(&runs, jobs);
+    }
+}
 
-    let target_node = flow_ir
-        .nodes
-        .get(&target)
-        .cloned()
-        .ok_or_else(|| anyhow::anyhow!("step '{}' not found", target))?;
+/// The files `generate` may write for a flow: the flow itself, its resolve sidecar (which also
+/// holds answer provenance) and summary, the wizard state, and the stored node configs and
+/// answers of every step.
+struct GenerateSnapshot {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+    dirs: Vec<(PathBuf, Option<DirSnapshot>)>,
+}
 
-    let mut predecessors = Vec::new();
-    for (id, node) in &flow_ir.nodes {
-        if node
-            .routing
-            .iter()
-            .any(|r| r.to.as_deref() == Some(target.as_str()))
-        {
-            predecessors.push(id.clone());
-        }
-    }
+/// Every file under a directory, with its contents.
+type DirSnapshot = Vec<(PathBuf, Vec<u8>)>;
 
-    if predecessors.len() > 1 && args.multi_pred == "error" {
-        anyhow::bail!(
-            "multiple predecessors for '{}': {} (use --if-multiple-predecessors splice-all)",
-            target,
-            predecessors.join(", ")
-        );
+impl GenerateSnapshot {
+    fn capture(flow_path: &Path, flow_id: &str) -> Self {
+        let files = [
+            flow_path.to_path_buf(),
+            sidecar_path_for_flow(flow_path),
+            resolve_summary_path_for_flow(flow_path),
+            wizard_state::wizard_state_path(flow_path, flow_id),
+        ]
+        .into_iter()
+        .map(|path| {
+            let previous = fs::read(&path).ok();
+            (path, previous)
+        })
+        .collect();
+        let dirs = [
+            config_store::config_dir_for_flow(flow_path),
+            answers_base_dir(flow_path, None).join(flow_id),
+        ]
+        .into_iter()
+        .map(|dir| {
+            let previous = dir.is_dir().then(|| {
+                let mut entries = Vec::new();
+                collect_dir_files(&dir, &mut entries);
+                entries
+            });
+            (dir, previous)
+        })
+        .collect();
+        Self { files, dirs }
     }
 
-    if args.strategy == "splice" {
-        for pred_id in predecessors {
-            if let Some(pred) = flow_ir.nodes.get_mut(&pred_id) {
-                let mut new_routes = Vec::new();
-                for route in &pred.routing {
-                    if route.to.as_deref() == Some(target.as_str()) {
-                        if target_node.routing.is_empty()
-                            || target_node
-                                .routing
-                                .iter()
-                                .all(|r| r.to.is_none() && (r.out || r.reply))
-                        {
-                            // drop this edge; terminal target
-                            continue;
-                        } else {
-                            new_routes.extend(target_node.routing.clone());
-                            continue;
-                        }
-                    }
-                    new_routes.push(route.clone());
+    /// Put every captured file back, removing the ones that did not exist before.
+    fn restore(&self) {
+        for (path, previous) in &self.files {
+            restore_or_remove(path, previous.as_deref());
+        }
+        for (dir, previous) in &self.dirs {
+            let _ = fs::remove_dir_all(dir);
+            for (path, bytes) in previous.iter().flatten() {
+                if let Some(parent) = path.parent() {
+                    let _ = fs::create_dir_all(parent);
                 }
-                pred.routing = new_routes;
+                let _ = fs::write(path, bytes);
+            }
+            if previous.is_some() {
+                let _ = fs::create_dir_all(dir);
             }
         }
     }
+}
 
-    flow_ir.nodes.swap_remove(&target);
-    flow_meta::clear_component_entry(&mut flow_ir.meta, &target);
-    // Fix entrypoint if it pointed to deleted node.
-    let mut new_entrypoints = flow_ir.entrypoints.clone();
-    for (_, v) in new_entrypoints.iter_mut() {
-        if v == &target {
-            if let Some(first) = flow_ir.nodes.keys().next() {
-                *v = first.clone();
-            } else {
-                *v = String::new();
-            }
+fn collect_dir_files(dir: &Path, out: &mut DirSnapshot) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_dir_files(&path, out);
+        } else if let Ok(bytes) = fs::read(&path) {
+            out.push((path, bytes));
         }
     }
-    flow_ir.entrypoints = new_entrypoints;
+}
 
-    let doc_out = flow_ir.to_doc()?;
-    let yaml = serialize_doc(&doc_out)?;
-    load_ygtc_from_str(&yaml)?;
-    if args.write {
-        write_flow_file(&args.flow_path, &yaml, true, backup)?;
-        sidecar.nodes.remove(&target);
-        write_sidecar(&sidecar_path, &sidecar)?;
-        let _ = wizard_state::remove_wizard_step(&args.flow_path, &flow_ir.id, &target);
-        if let Err(err) = remove_flow_resolve_summary_node(&args.flow_path, &target)
-            .with_context(|| format!("update resolve summary for {}", args.flow_path.display()))
-        {
-            eprintln!("warning: {err}");
-        }
-        if matches!(format, OutputFormat::Json) {
-            let payload = json!({
-                "ok": true,
-                "action": "delete-step",
-                "node_id": target,
-                "flow_path": args.flow_path.display().to_string()
-            });
-            print_json_payload_with_optional_diagnostic(payload, deprecation_diagnostic.as_ref())?;
-        } else {
-            println!(
-                "Deleted step '{}' from {}",
-                target,
-                args.flow_path.display()
-            );
+fn restore_or_remove(path: &Path, previous: Option<&[u8]>) {
+    let _ = match previous {
+        Some(bytes) => fs::write(path, bytes),
+        None => fs::remove_file(path),
+    };
+}
+
+fn handle_preset_save(args: PresetSaveArgs, format: OutputFormat) -> Result<()> {
+    let doc = load_ygtc_from_path(&args.flow_path)?;
+    let flow_ir = FlowIr::from_doc(doc)?;
+    let node = flow_ir
+        .nodes
+        .get(&args.from_step)
+        .ok_or_else(|| anyhow!("step '{}' not found", args.from_step))?;
+    // Prefer the wizard answers recorded for the node; fall back to its config/payload.
+    let base_dir = answers_base_dir(&args.flow_path, args.answers_dir.as_deref());
+    let recorded = ["default", "setup", "update"]
+        .iter()
+        .map(|mode| answers::answers_paths(&base_dir, &flow_ir.id, &args.from_step, mode).json)
+        .find(|path| path.exists());
+    let answers: BTreeMap<String, serde_json::Value> = match recorded {
+        Some(path) => parse_answers_map(None, Some(&path))?.into_iter().collect(),
+        None => {
+            let source = node.payload.get("config").unwrap_or(&node.payload);
+            source
+                .as_object()
+                .ok_or_else(|| anyhow!("step '{}' payload is not an object", args.from_step))?
+                .clone()
+                .into_iter()
+                .collect()
         }
-    } else if matches!(format, OutputFormat::Json) {
-        let payload = json!({"ok": true, "action": "delete-step", "dry_run": true, "flow": yaml});
-        print_json_payload_with_optional_diagnostic(payload, deprecation_diagnostic.as_ref())?;
+    };
+    let path = presets::save_preset(
+        &preset_root_for_flow(&args.flow_path),
+        &args.name,
+        &answers,
+        args.force,
+    )?;
+    if matches!(format, OutputFormat::Json) {
+        let payload = json!({
+            "ok": true,
+            "action": "preset-save",
+            "preset": args.name,
+            "path": path.display().to_string()
+        });
+        print_json_payload(&payload)?;
     } else {
-        print!("{yaml}");
+        println!("Saved preset '{}' to {}", args.name, path.display());
     }
     Ok(())
 }
@@ -8319,6 +14655,124 @@ fn parse_answers_map(
     Ok(merged)
 }
 
+/// Load a named preset (if any) and layer explicit answers on top of it.
+fn parse_answers_with_preset(
+    flow_path: &Path,
+    preset: Option<&str>,
+    answers: Option<&str>,
+    answers_file: Option<&Path>,
+) -> Result<QuestionAnswers> {
+    let mut merged = QuestionAnswers::new();
+    if let Some(name) = preset {
+        merged.extend(presets::load_preset(
+            &preset_root_for_flow(flow_path),
+            name,
+        )?);
+    }
+    merged.extend(parse_answers_map(answers, answers_file)?);
+    Ok(merged)
+}
+
+/// Pack root for presets: the parent of `flows/`, or the flow's own directory.
+fn preset_root_for_flow(flow_path: &Path) -> PathBuf {
+    infer_pack_root_from_flow_path(flow_path).unwrap_or_else(|_| {
+        flow_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .to_path_buf()
+    })
+}
+
+/// Insert a layer of answers (overriding earlier layers) and record their source.
+fn layer_answers(
+    answers: &mut QuestionAnswers,
+    sources: &mut BTreeMap<String, AnswerSource>,
+    layer: impl IntoIterator<Item = (String, serde_json::Value)>,
+    source: AnswerSource,
+) {
+    for (key, value) in layer {
+        sources.insert(key.clone(), source);
+        answers.insert(key, value);
+    }
+}
+
+/// Answers set through `GREENTIC_FLOW_ANSWER_<QUESTION>` environment variables, where
+/// `<QUESTION>` is the question id upper-cased with other characters turned into `_`. Values
+/// parse as JSON when they can and are taken as strings otherwise.
+fn env_answers(qa_spec: &ComponentQaSpec) -> Vec<(String, serde_json::Value)> {
+    qa_spec
+        .questions
+        .iter()
+        .filter_map(|question| {
+            let suffix: String = question
+                .id
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            let raw = env::var(format!("GREENTIC_FLOW_ANSWER_{suffix}")).ok()?;
+            let value = serde_json::from_str(&raw).unwrap_or(serde_json::Value::String(raw));
+            Some((question.id.clone(), value))
+        })
+        .collect()
+}
+
+/// Attribute answers added or changed between two snapshots to `source`.
+fn track_answer_changes(
+    before: &QuestionAnswers,
+    after: &QuestionAnswers,
+    sources: &mut BTreeMap<String, AnswerSource>,
+    source: AnswerSource,
+) {
+    for (key, value) in after {
+        if before.get(key) != Some(value) {
+            sources.insert(key.clone(), source);
+        }
+    }
+}
+
+fn sibling_node_payload(flow_ir: &FlowIr, node_id: &str) -> Result<serde_json::Value> {
+    flow_ir
+        .nodes
+        .get(node_id)
+        .map(|node| node.payload.clone())
+        .ok_or_else(|| anyhow!("--defaults-from node '{}' not found in flow", node_id))
+}
+
+/// Warn when questions that share a `writes_to` path were answered differently; the later
+/// question overwrites the earlier one.
+fn warn_writes_to_collisions(questions: &[Question], answers: &QuestionAnswers) {
+    for collision in writes_to_collisions(questions, answers) {
+        eprintln!("warning: {collision}; the last question wins");
+    }
+}
+
+/// Seed answers from an existing node's payload without overriding explicit answers.
+fn seed_answers_from_node(
+    flow_ir: &FlowIr,
+    node_id: &str,
+    questions: &[Question],
+    answers: &mut QuestionAnswers,
+) -> Result<()> {
+    let payload = sibling_node_payload(flow_ir, node_id)?;
+    let mut seeded = extract_answers_from_payload(questions, &payload);
+    // Questions without `writes_to` are merged into the payload under their id.
+    for question in questions.iter().filter(|q| q.writes_to.is_none()) {
+        if let Some(value) = payload.get(&question.id) {
+            seeded.insert(question.id.clone(), value.clone());
+        }
+    }
+    for (key, value) in seeded {
+        answers.entry(key).or_insert(value);
+    }
+    Ok(())
+}
+
 fn seed_optional_answers_for_default_setup(
     qa_spec: &greentic_types::schemas::component::v0_6_0::ComponentQaSpec,
     answers: &mut QuestionAnswers,
@@ -8373,6 +14827,23 @@ fn serialize_doc(doc: &greentic_flow::model::FlowDoc) -> Result<String> {
 }
 
 fn ensure_sidecar(flow_path: &Path) -> Result<(PathBuf, FlowResolveV1)> {
+    let (sidecar_path, doc) = load_sidecar_or_default(flow_path)?;
+    if !sidecar_path.exists() {
+        write_sidecar(&sidecar_path, &doc)?;
+    }
+    Ok((sidecar_path, doc))
+}
+
+/// Like [`ensure_sidecar`] for `--dry-run`: a missing sidecar loads empty and is not created.
+fn open_sidecar(flow_path: &Path, dry_run: bool) -> Result<(PathBuf, FlowResolveV1)> {
+    if dry_run {
+        load_sidecar_or_default(flow_path)
+    } else {
+        ensure_sidecar(flow_path)
+    }
+}
+
+fn load_sidecar_or_default(flow_path: &Path) -> Result<(PathBuf, FlowResolveV1)> {
     let sidecar_path = sidecar_path_for_flow(flow_path);
     if sidecar_path.exists() {
         let doc = read_flow_resolve(&sidecar_path).map_err(|e| anyhow::anyhow!(e.to_string()))?;
@@ -8387,12 +14858,69 @@ fn ensure_sidecar(flow_path: &Path) -> Result<(PathBuf, FlowResolveV1)> {
         flow: flow_name,
         nodes: Default::default(),
     };
-    write_sidecar(&sidecar_path, &doc)?;
     Ok((sidecar_path, doc))
 }
 
+/// Unified diff of the flow (and sidecar) a `--dry-run` would write, against what is on disk.
+fn dry_run_diff(
+    flow_path: &Path,
+    yaml: &str,
+    sidecar: Option<(&Path, &FlowResolveV1)>,
+) -> Result<String> {
+    let mut files = vec![(flow_path.to_path_buf(), yaml.to_string())];
+    if let Some((path, doc)) = sidecar {
+        files.push((
+            path.to_path_buf(),
+            answer_provenance::sidecar_json(path, doc)?,
+        ));
+    }
+    let mut out = String::new();
+    for (path, new) in &files {
+        let old = fs::read_to_string(path).ok();
+        if old.as_deref() == Some(new.as_str()) {
+            continue;
+        }
+        let label = path.display().to_string();
+        let old_label = if old.is_some() {
+            label.clone()
+        } else {
+            "/dev/null".to_string()
+        };
+        let diff = TextDiff::from_lines(old.as_deref().unwrap_or(""), new.as_str());
+        out.push_str(
+            &diff
+                .unified_diff()
+                .context_radius(3)
+                .header(&old_label, &label)
+                .to_string(),
+        );
+    }
+    Ok(out)
+}
+
+fn dry_run_payload(action: &str, yaml: &str, diff: &str) -> serde_json::Value {
+    json!({"ok": true, "action": action, "dry_run": true, "flow": yaml, "diff": diff})
+}
+
+fn print_dry_run_diff(diff: &str) {
+    if diff.is_empty() {
+        println!("no changes");
+    } else {
+        print!("{diff}");
+    }
+}
+
 fn write_sidecar(path: &Path, doc: &FlowResolveV1) -> Result<()> {
-    write_flow_resolve(path, doc).map_err(|e| anyhow::anyhow!(e.to_string()))
+    validate_flow_resolve(doc).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    let raw = answer_provenance::sidecar_json(path, doc)?;
+    cancel::replace_file_atomically(path, raw.as_bytes())?;
+    if let Some(flow_path) = path
+        .to_str()
+        .and_then(|path| path.strip_suffix(".resolve.json"))
+    {
+        refresh_workspace_index(Path::new(flow_path));
+    }
+    Ok(())
 }
 
 struct SidecarValidation {
@@ -8401,12 +14929,25 @@ struct SidecarValidation {
     missing: Vec<String>,
     extra: Vec<String>,
     invalid: Vec<String>,
+    /// One per unused entry doctor prompted about or matched against `--prune-select`.
+    decisions: Vec<SidecarPruneDecision>,
+}
+
+/// What [`validate_sidecar_for_flow`] does with entries whose node is gone from the flow.
+#[derive(Clone, Copy)]
+enum SidecarPrune<'a> {
+    /// Report them as `sidecar_unused`.
+    Keep,
+    /// Ask about each entry in turn.
+    Prompt,
+    /// Prune the entries any selector matches; report the rest.
+    Select(&'a [String]),
 }
 
 fn validate_sidecar_for_flow(
     flow_path: &Path,
     flow: &greentic_types::Flow,
-    prompt_unused: bool,
+    prune: SidecarPrune<'_>,
     apply_updates: bool,
 ) -> Result<SidecarValidation> {
     let sidecar_path = sidecar_path_for_flow(flow_path);
@@ -8424,6 +14965,7 @@ fn validate_sidecar_for_flow(
                 missing: Vec::new(),
                 extra: Vec::new(),
                 invalid: Vec::new(),
+                decisions: Vec::new(),
             });
         }
         return Ok(SidecarValidation {
@@ -8432,13 +14974,14 @@ fn validate_sidecar_for_flow(
             missing: node_ids.into_iter().collect(),
             extra: Vec::new(),
             invalid: Vec::new(),
+            decisions: Vec::new(),
         });
     }
 
     let mut doc = read_flow_resolve(&sidecar_path).map_err(|e| anyhow::anyhow!(e.to_string()))?;
     let mut updated = false;
     if apply_updates && doc.flow != flow_name {
-        doc.flow = flow_name;
+        doc.flow = flow_name.clone();
         updated = true;
     }
 
@@ -8456,12 +14999,50 @@ fn validate_sidecar_for_flow(
         }
     }
 
-    if prompt_unused && !extra.is_empty() && confirm_delete_unused(&sidecar_path, &extra)? {
-        for id in &extra {
-            doc.nodes.remove(id);
+    let mut decisions = Vec::new();
+    if !matches!(prune, SidecarPrune::Keep) && !extra.is_empty() {
+        // Removed nodes usually keep their `meta.greentic.components` entry, which dates them.
+        let meta = load_ygtc_from_path(flow_path)
+            .ok()
+            .and_then(|flow_doc| flow_doc.meta);
+        let mut remaining = None;
+        for id in std::mem::take(&mut extra) {
+            let entry = &doc.nodes[&id];
+            let mut decision = SidecarPruneDecision {
+                source: ComponentSource::from_sidecar(&entry.source, entry.mode).reference,
+                added_at: flow_meta::component_added_at(&meta, &id),
+                node_id: id,
+                pruned: false,
+                reason: String::new(),
+            };
+            let (pruned, reason) = match prune {
+                SidecarPrune::Keep => unreachable!("checked above"),
+                SidecarPrune::Select(selectors) => {
+                    let selected = selectors.iter().any(|selector| {
+                        prune_selector_matches(selector, &flow_name, &decision.node_id)
+                    });
+                    (selected, if selected { "selected" } else { "not_selected" })
+                }
+                SidecarPrune::Prompt => {
+                    let answer =
+                        remaining.unwrap_or_else(|| prompt_prune_entry(&sidecar_path, &decision));
+                    if matches!(answer, PruneAnswer::All | PruneAnswer::Quit) {
+                        remaining = Some(answer);
+                    }
+                    let pruned = matches!(answer, PruneAnswer::Yes | PruneAnswer::All);
+                    (pruned, if pruned { "confirmed" } else { "declined" })
+                }
+            };
+            decision.pruned = pruned;
+            decision.reason = reason.to_string();
+            if pruned {
+                doc.nodes.remove(&decision.node_id);
+                updated = true;
+            } else {
+                extra.push(decision.node_id.clone());
+            }
+            decisions.push(decision);
         }
-        updated = true;
-        extra.clear();
     }
 
     let mut invalid = Vec::new();
@@ -8481,11 +15062,20 @@ fn validate_sidecar_for_flow(
         missing,
         extra,
         invalid,
+        decisions,
     })
 }
 
+/// Whether `reference` uses a scheme an embedder registered rather than a built-in one.
+fn is_custom_scheme(reference: &str) -> bool {
+    reference_scheme(reference)
+        .is_some_and(|scheme| !matches!(scheme, "oci" | "repo" | "store" | "file"))
+        && ResolverRegistry::current().handles(reference)
+}
+
 fn classify_remote_source(reference: &str, digest: Option<String>) -> ComponentSourceRefV1 {
-    if reference.starts_with("repo://") {
+    // Sidecars have no variant for custom schemes; they are recorded as repo sources.
+    if reference.starts_with("repo://") || is_custom_scheme(reference) {
         ComponentSourceRefV1::Repo {
             r#ref: reference.to_string(),
             digest,
@@ -8520,6 +15110,9 @@ fn validate_component_ref(reference: &str) -> Result<()> {
         }
         return Ok(());
     }
+    if is_custom_scheme(reference) {
+        return Ok(());
+    }
     anyhow::bail!("--component must start with oci://, repo://, or store://");
 }
 
@@ -8553,6 +15146,9 @@ fn validate_sidecar_source(source: &ComponentSourceRefV1, flow_path: &Path) -> R
             if path.trim().is_empty() {
                 anyhow::bail!("local wasm path is empty");
             }
+            if parse_cas_reference(path).is_some() && ArtifactStore::from_env().is_none() {
+                anyhow::bail!("{path} needs {CAS_DIR_ENV} to point at the artifact store");
+            }
             let abs = local_path_from_sidecar(path, flow_path);
             if !abs.exists() {
                 anyhow::bail!("local wasm missing at {}", abs.display());
@@ -8571,7 +15167,7 @@ fn validate_sidecar_source(source: &ComponentSourceRefV1, flow_path: &Path) -> R
             if r#ref.trim().is_empty() {
                 anyhow::bail!("repo reference is empty");
             }
-            if !r#ref.starts_with("repo://") {
+            if !r#ref.starts_with("repo://") && !is_custom_scheme(r#ref) {
                 anyhow::bail!("repo reference must start with repo://");
             }
         }
@@ -8587,26 +15183,13 @@ fn validate_sidecar_source(source: &ComponentSourceRefV1, flow_path: &Path) -> R
     Ok(())
 }
 
-fn compute_local_digest(path: &Path) -> Result<String> {
-    let data = fs::read(path).with_context(|| format!("read wasm at {}", path.display()))?;
-    let mut hasher = Sha256::new();
-    hasher.update(data);
-    let digest = format!("sha256:{:x}", hasher.finalize());
-    Ok(digest)
-}
-
 fn resolve_remote_digest(reference: &str) -> Result<String> {
     if let Ok(mock) = std::env::var("GREENTIC_FLOW_TEST_DIGEST")
         && !mock.is_empty()
     {
         return Ok(mock);
     }
-    let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
-    let client = DistClient::new(Default::default());
-    let resolved = rt
-        .block_on(client.resolve_ref(reference))
-        .map_err(|e| anyhow::anyhow!("failed to resolve reference {reference}: {e}"))?;
-    Ok(resolved.digest)
+    ResolverRegistry::current().resolve_digest(reference)
 }
 
 fn normalize_local_wasm_path(local: &Path, flow_path: &Path) -> Result<(PathBuf, String)> {
@@ -8635,10 +15218,17 @@ fn normalize_local_wasm_path(local: &Path, flow_path: &Path) -> Result<(PathBuf,
     if rel_str.trim().is_empty() {
         anyhow::bail!("local wasm path resolves to an empty relative path");
     }
+    if let Some(store) = ArtifactStore::from_env() {
+        let digest = store.insert_file(&abs_path)?;
+        return Ok((store.artifact_path(&digest)?, cas_reference(&digest)));
+    }
     Ok((abs_path, format!("file://{rel_str}")))
 }
 
 fn local_path_from_sidecar(path: &str, flow_path: &Path) -> PathBuf {
+    if let Some(artifact) = cas_local_path(path) {
+        return artifact;
+    }
     let trimmed = path.strip_prefix("file://").unwrap_or(path);
     let raw = PathBuf::from(trimmed);
     if raw.is_absolute() {
@@ -8660,7 +15250,7 @@ fn resolve_component_source_inputs(
     if let Some(local) = local_wasm {
         let (abs_path, uri_path) = normalize_local_wasm_path(local, flow_path)?;
         let digest = if pin {
-            Some(compute_local_digest(&abs_path)?)
+            Some(compute_sha256(&abs_path)?)
         } else {
             None
         };
@@ -8688,7 +15278,8 @@ fn resolve_component_source_inputs(
 }
 
 struct WizardComponentResolution {
-    wasm_bytes: Vec<u8>,
+    /// The component file; empty bytes for fixture wizards, which never invoke it.
+    wasm: wizard_ops::ComponentWasm,
     digest: Option<String>,
     source: ComponentSourceRefV1,
     fixture: Option<WizardFixture>,
@@ -8718,15 +15309,16 @@ fn resolve_wizard_component(
 ) -> Result<WizardComponentResolution> {
     if let Some(local) = local_wasm {
         let (abs_path, uri_path) = normalize_local_wasm_path(local, flow_path)?;
-        let bytes =
-            fs::read(&abs_path).with_context(|| format!("read wasm at {}", abs_path.display()))?;
-        let digest = Some(compute_local_digest(&abs_path)?);
+        let digest = Some(
+            compute_sha256(&abs_path)
+                .with_context(|| format!("read wasm at {}", abs_path.display()))?,
+        );
         let source = ComponentSourceRefV1::Local {
             path: uri_path,
             digest: digest.clone(),
         };
         return Ok(WizardComponentResolution {
-            wasm_bytes: bytes,
+            wasm: wizard_ops::ComponentWasm::File(abs_path),
             digest,
             source,
             fixture: None,
@@ -8737,16 +15329,16 @@ fn resolve_wizard_component(
         if let Some(fixture) = resolve_fixture_wizard(reference, resolver, wizard_mode)? {
             let source = classify_remote_source(reference, None);
             return Ok(WizardComponentResolution {
-                wasm_bytes: Vec::new(),
+                wasm: wizard_ops::ComponentWasm::Bytes(Vec::new()),
                 digest: None,
                 source,
                 fixture: Some(fixture),
             });
         }
-        let resolved = resolve_ref_to_bytes(reference, resolver)?;
+        let resolved = resolve_ref_to_wasm(reference, resolver)?;
         let source = classify_remote_source(reference, resolved.digest.clone());
         return Ok(WizardComponentResolution {
-            wasm_bytes: resolved.bytes,
+            wasm: resolved.wasm,
             digest: resolved.digest,
             source,
             fixture: None,
@@ -8776,18 +15368,18 @@ fn resolve_wizard_component(
                 classify_remote_source(&reference, None)
             };
             return Ok(WizardComponentResolution {
-                wasm_bytes: Vec::new(),
+                wasm: wizard_ops::ComponentWasm::Bytes(Vec::new()),
                 digest: None,
                 source,
                 fixture: Some(fixture),
             });
         }
-        let resolved = resolve_ref_to_bytes(&reference, resolver)?;
+        let resolved = resolve_ref_to_wasm(&reference, resolver)?;
         let source = if reference.starts_with("file://") {
             let local_path = reference.trim_start_matches("file://");
             let path = PathBuf::from(local_path);
             let (abs_path, uri_path) = normalize_local_wasm_path(&path, flow_path)?;
-            let digest = Some(compute_local_digest(&abs_path)?);
+            let digest = Some(compute_sha256(&abs_path)?);
             ComponentSourceRefV1::Local {
                 path: uri_path,
                 digest,
@@ -8796,7 +15388,7 @@ fn resolve_wizard_component(
             classify_remote_source(&reference, resolved.digest.clone())
         };
         return Ok(WizardComponentResolution {
-            wasm_bytes: resolved.bytes,
+            wasm: resolved.wasm,
             digest: resolved.digest,
             source,
             fixture: None,
@@ -8808,152 +15400,36 @@ fn resolve_wizard_component(
     );
 }
 
-struct ResolvedRefBytes {
-    bytes: Vec<u8>,
+struct ResolvedRefWasm {
+    wasm: wizard_ops::ComponentWasm,
     digest: Option<String>,
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct FixtureIndex {
-    components: std::collections::BTreeMap<String, FixtureComponentEntry>,
-}
-
-#[derive(Debug, serde::Deserialize)]
-struct FixtureComponentEntry {
-    #[serde(default)]
-    path: Option<String>,
-}
-
-fn fixture_key(reference: &str) -> String {
-    reference
-        .trim_start_matches("oci://")
-        .trim_start_matches("repo://")
-        .trim_start_matches("store://")
-        .trim_start_matches("file://")
-        .replace(['/', ':', '@'], "_")
-}
-
-fn strip_reference_scheme(reference: &str) -> &str {
-    reference
-        .strip_prefix("oci://")
-        .or_else(|| reference.strip_prefix("repo://"))
-        .or_else(|| reference.strip_prefix("store://"))
-        .or_else(|| reference.strip_prefix("file://"))
-        .unwrap_or(reference)
-}
-
-fn load_fixture_index(root: &Path) -> Result<Option<FixtureIndex>> {
-    let path = root.join("index.json");
-    if !path.exists() {
-        return Ok(None);
-    }
-    let text = fs::read_to_string(&path)
-        .with_context(|| format!("read fixture index {}", path.display()))?;
-    let parsed: FixtureIndex = serde_json::from_str(&text).context("parse fixture index JSON")?;
-    Ok(Some(parsed))
-}
-
-fn fixture_entry_for_reference<'a>(
-    index: &'a FixtureIndex,
-    reference: &str,
-) -> Option<&'a FixtureComponentEntry> {
-    if let Some(entry) = index.components.get(reference) {
-        return Some(entry);
-    }
-    let stripped = strip_reference_scheme(reference);
-    index.components.get(stripped)
-}
-
-fn fixture_component_dir(
-    root: &Path,
-    reference: &str,
-    entry: Option<&FixtureComponentEntry>,
-) -> PathBuf {
-    if let Some(entry) = entry
-        && let Some(path) = entry.path.as_ref()
-    {
-        return root.join(path);
-    }
-    root.join("components").join(fixture_key(reference))
-}
-
-fn resolve_ref_to_bytes(reference: &str, resolver: Option<&String>) -> Result<ResolvedRefBytes> {
-    if let Some(resolver) = resolver
-        && let Some(root) = resolver.strip_prefix("fixture://")
-    {
-        return resolve_fixture_bytes(reference, Path::new(root));
+fn resolve_ref_to_wasm(reference: &str, resolver: Option<&String>) -> Result<ResolvedRefWasm> {
+    let mut registry = ResolverRegistry::current();
+    if let Some(fixture) = resolver.and_then(|resolver| FixtureResolver::from_override(resolver)) {
+        registry = registry.with(Arc::new(fixture));
     }
-
-    let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
-    let client = DistClient::new(Default::default());
-    let resolved = rt
-        .block_on(client.resolve_ref(reference))
-        .map_err(|e| anyhow::anyhow!("resolve reference {reference}: {e}"))?;
-    let path = resolved
-        .cache_path
-        .ok_or_else(|| anyhow::anyhow!("resolved reference {reference} without cache path"))?;
-    let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
-    Ok(ResolvedRefBytes {
-        bytes,
+    let resolved = registry.resolve(reference, None)?;
+    Ok(ResolvedRefWasm {
+        wasm: wizard_ops::ComponentWasm::File(resolved.wasm_path),
         digest: Some(resolved.digest),
     })
 }
 
-fn resolve_fixture_bytes(reference: &str, root: &Path) -> Result<ResolvedRefBytes> {
-    let index = load_fixture_index(root)?;
-    if let Some(index) = index
-        && let Some(entry) = fixture_entry_for_reference(&index, reference)
-    {
-        let dir = fixture_component_dir(root, reference, Some(entry));
-        let wasm_path = dir.join("component.wasm");
-        if !wasm_path.exists() {
-            anyhow::bail!(
-                "fixture resolver missing wasm for {} (expected {})",
-                reference,
-                wasm_path.display()
-            );
-        }
-        let bytes =
-            fs::read(&wasm_path).with_context(|| format!("read {}", wasm_path.display()))?;
-        let digest = Some(compute_local_digest(&wasm_path)?);
-        return Ok(ResolvedRefBytes { bytes, digest });
-    }
-
-    let key = fixture_key(reference);
-    let direct = root.join(format!("{key}.wasm"));
-    let nested = root.join(&key).join("component.wasm");
-    let path = if direct.exists() { &direct } else { &nested };
-    if !path.exists() {
-        anyhow::bail!(
-            "fixture resolver missing {} (looked for {} or {})",
-            reference,
-            direct.display(),
-            nested.display()
-        );
-    }
-    let bytes = fs::read(path).with_context(|| format!("read {}", path.display()))?;
-    let digest = Some(compute_local_digest(path)?);
-    Ok(ResolvedRefBytes { bytes, digest })
-}
-
 fn resolve_fixture_wizard(
     reference: &str,
     resolver: Option<&String>,
     wizard_mode: wizard_ops::WizardMode,
 ) -> Result<Option<WizardFixture>> {
-    let Some(resolver) = resolver else {
-        return Ok(None);
-    };
-    let Some(root) = resolver.strip_prefix("fixture://") else {
+    let Some(fixture) = resolver.and_then(|resolver| FixtureResolver::from_override(resolver))
+    else {
         return Ok(None);
     };
-    let root = Path::new(root);
+    let root = fixture.root();
     let mode = wizard_mode.as_str();
     let legacy_mode = wizard_mode_legacy_label(wizard_mode);
-    if let Some(index) = load_fixture_index(root)?
-        && let Some(entry) = fixture_entry_for_reference(&index, reference)
-    {
-        let dir = fixture_component_dir(root, reference, Some(entry));
+    if let Some(dir) = fixture.indexed_dir(reference)? {
         let describe_path = dir.join("describe.cbor");
         let qa_spec_path = {
             let path = dir.join(format!("qa_{mode}.cbor"));
@@ -9148,23 +15624,18 @@ fn ensure_sidecar_source_available(source: &ComponentSourceRefV1, flow_path: &Pa
         ComponentSourceRefV1::Oci { r#ref, digest }
         | ComponentSourceRefV1::Repo { r#ref, digest }
         | ComponentSourceRefV1::Store { r#ref, digest, .. } => {
-            let client = DistClient::new(Default::default());
-            let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
-            if let Some(d) = digest {
-                rt.block_on(client.fetch_digest(d)).map_err(|e| {
-                    anyhow::anyhow!(
+            ResolverRegistry::current()
+                .resolve(r#ref, digest.as_deref())
+                .map_err(|e| match digest {
+                    Some(d) => anyhow::anyhow!(
                         "component digest {} not cached; pull or pin locally first: {e}",
                         d
-                    )
-                })?;
-            } else {
-                rt.block_on(client.ensure_cached(r#ref)).map_err(|e| {
-                    anyhow::anyhow!(
+                    ),
+                    None => anyhow::anyhow!(
                         "component reference {} not available locally; pull or pin digest: {e}",
                         r#ref
-                    )
+                    ),
                 })?;
-            }
         }
     }
     Ok(())
@@ -9184,56 +15655,10 @@ fn resolve_component_manifest_path(
                     .unwrap_or_else(|| Path::new("."))
                     .join("component.manifest.json")
             }),
-        ComponentSourceRefV1::Oci { r#ref, digest } => {
-            let client = DistClient::new(Default::default());
-            let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
-            let cached = if let Some(d) = digest {
-                rt.block_on(client.fetch_digest(d))
-            } else {
-                rt.block_on(client.ensure_cached(r#ref))
-                    .map(|r| r.cache_path.unwrap_or_default())
-            };
-            let mut candidate = cached
-                .ok()
-                .and_then(|artifact| artifact.parent().map(|p| p.join("component.manifest.json")))
-                .unwrap_or_else(|| PathBuf::from("component.manifest.json"));
-            if candidate.exists() {
-                return Ok(candidate);
-            }
-            let resolved_ref = if let Some(d) = digest {
-                if r#ref.contains('@') {
-                    r#ref.to_string()
-                } else {
-                    format!("{}@{}", r#ref, d)
-                }
-            } else {
-                r#ref.to_string()
-            };
-            let resolved = rt
-                .block_on(client.resolve_ref(&resolved_ref))
-                .map_err(|e| anyhow::anyhow!("resolve component {}: {e}", resolved_ref))?;
-            if let Some(path) = resolved.cache_path
-                && let Some(parent) = path.parent()
-            {
-                candidate = parent.join("component.manifest.json");
-            }
-            candidate
-        }
-        ComponentSourceRefV1::Repo { r#ref, digest }
+        ComponentSourceRefV1::Oci { r#ref, digest }
+        | ComponentSourceRefV1::Repo { r#ref, digest }
         | ComponentSourceRefV1::Store { r#ref, digest, .. } => {
-            let client = DistClient::new(Default::default());
-            let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
-            let artifact = if let Some(d) = digest {
-                rt.block_on(client.fetch_digest(d))
-            } else {
-                rt.block_on(client.ensure_cached(r#ref))
-                    .map(|r| r.cache_path.unwrap_or_default())
-            }
-            .map_err(|e| anyhow::anyhow!("resolve component {}: {e}", r#ref))?;
-            artifact
-                .parent()
-                .map(|p| p.join("component.manifest.json"))
-                .unwrap_or_else(|| PathBuf::from("component.manifest.json"))
+            remote_source_manifest_path(r#ref, digest.as_deref())?
         }
     };
 
@@ -9246,6 +15671,20 @@ fn resolve_component_manifest_path(
     Ok(manifest_path)
 }
 
+/// Where the manifest of a remote sidecar source lives once its resolver has fetched it.
+fn remote_source_manifest_path(reference: &str, digest: Option<&str>) -> Result<PathBuf> {
+    let resolved = ResolverRegistry::current()
+        .resolve(reference, digest)
+        .map_err(|e| anyhow::anyhow!("resolve component {}: {e}", reference))?;
+    Ok(resolved.manifest_path.unwrap_or_else(|| {
+        resolved
+            .wasm_path
+            .parent()
+            .map(|p| p.join("component.manifest.json"))
+            .unwrap_or_else(|| PathBuf::from("component.manifest.json"))
+    }))
+}
+
 fn load_component_payload(
     source: &ComponentSourceRefV1,
     flow_path: &Path,
@@ -9264,19 +15703,7 @@ fn load_component_payload(
         ComponentSourceRefV1::Oci { r#ref, digest }
         | ComponentSourceRefV1::Repo { r#ref, digest }
         | ComponentSourceRefV1::Store { r#ref, digest, .. } => {
-            let client = DistClient::new(Default::default());
-            let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
-            let artifact = if let Some(d) = digest {
-                rt.block_on(client.fetch_digest(d))
-            } else {
-                rt.block_on(client.ensure_cached(r#ref))
-                    .map(|r| r.cache_path.unwrap_or_default())
-            }
-            .map_err(|e| anyhow::anyhow!("resolve component {}: {e}", r#ref))?;
-            artifact
-                .parent()
-                .map(|p| p.join("component.manifest.json"))
-                .unwrap_or_else(|| PathBuf::from("component.manifest.json"))
+            remote_source_manifest_path(r#ref, digest.as_deref())?
         }
     };
 
@@ -0,0 +1,337 @@
+//! Fluent construction of flows from Rust.
+//!
+//! ```
+//! use greentic_flow::builder::FlowBuilder;
+//! use serde_json::json;
+//!
+//! let yaml = FlowBuilder::new("main")
+//!     .kind("messaging")
+//!     .node("start", "handle_message", json!({ "text": "hi" }))
+//!     .route_to("end")
+//!     .node("end", "emit", json!({ "text": "bye" }))
+//!     .out()
+//!     .to_yaml()
+//!     .unwrap();
+//! assert!(yaml.contains("handle_message"));
+//! ```
+//!
+//! Routing methods on [`FlowBuilder`] apply to the node added last; [`NodeBuilder`] builds a
+//! node on its own for [`FlowBuilder::add_node`]. Nothing is checked until [`FlowBuilder::build`],
+//! which runs the same schema and routing validation as loading a YGTC file.
+
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_ir::{FlowIr, NodeIr, NodeLimits, Route},
+    loader::load_ygtc_from_str,
+    source_map::SourceMap,
+};
+
+/// Builds a [`FlowIr`] (and its YGTC text) step by step.
+#[derive(Debug, Clone)]
+pub struct FlowBuilder {
+    id: String,
+    kind: Option<String>,
+    title: Option<String>,
+    description: Option<String>,
+    start: Option<String>,
+    parameters: Value,
+    tags: Vec<String>,
+    schema_version: u32,
+    entrypoints: IndexMap<String, String>,
+    meta: Option<Value>,
+    nodes: Vec<NodeBuilder>,
+}
+
+/// One node: an operation, its payload and its routing.
+#[derive(Debug, Clone)]
+pub struct NodeBuilder {
+    id: String,
+    operation: String,
+    payload: Value,
+    output: Value,
+    routing: Vec<Route>,
+    telemetry: Option<Value>,
+    limits: Option<NodeLimits>,
+    feature: Option<String>,
+    doc: Option<String>,
+}
+
+impl FlowBuilder {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            kind: None,
+            title: None,
+            description: None,
+            start: None,
+            parameters: Value::Object(Map::new()),
+            tags: Vec::new(),
+            schema_version: 2,
+            entrypoints: IndexMap::new(),
+            meta: None,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// The flow `type`, e.g. `messaging`.
+    pub fn kind(mut self, kind: impl Into<String>) -> Self {
+        self.kind = Some(kind.into());
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// The entry node; defaults to the first node.
+    pub fn start(mut self, node_id: impl Into<String>) -> Self {
+        self.start = Some(node_id.into());
+        self
+    }
+
+    pub fn parameters(mut self, parameters: Value) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// The `schema_version` to write; defaults to 2, like `greentic-flow new`.
+    pub fn schema_version(mut self, version: u32) -> Self {
+        self.schema_version = version;
+        self
+    }
+
+    /// A named entrypoint besides the start node.
+    pub fn entrypoint(mut self, name: impl Into<String>, node_id: impl Into<String>) -> Self {
+        self.entrypoints.insert(name.into(), node_id.into());
+        self
+    }
+
+    pub fn meta(mut self, meta: Value) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
+    /// Add a node running `operation` with `payload`; routing methods that follow apply to it.
+    pub fn node(self, id: impl Into<String>, operation: impl Into<String>, payload: Value) -> Self {
+        self.add_node(NodeBuilder::new(id, operation).payload(payload))
+    }
+
+    pub fn add_node(mut self, node: NodeBuilder) -> Self {
+        self.nodes.push(node);
+        self
+    }
+
+    /// Describe the last node (its `doc` key).
+    pub fn doc(self, doc: impl Into<String>) -> Self {
+        self.with_last(|node| node.doc(doc))
+    }
+
+    /// Route the last node to `target`.
+    pub fn route_to(self, target: impl Into<String>) -> Self {
+        self.with_last(|node| node.route_to(target))
+    }
+
+    /// Route the last node to `target` when it produces `status` (see
+    /// [`StatusMatcher`](crate::flow_ir::StatusMatcher) for `*` and `!status`).
+    pub fn route_on_status(self, status: impl Into<String>, target: impl Into<String>) -> Self {
+        self.with_last(|node| node.route_on_status(status, target))
+    }
+
+    /// End the flow after the last node.
+    pub fn out(self) -> Self {
+        self.with_last(NodeBuilder::out)
+    }
+
+    /// Reply after the last node.
+    pub fn reply(self) -> Self {
+        self.with_last(NodeBuilder::reply)
+    }
+
+    /// Add `route` to the last node.
+    pub fn route(self, route: Route) -> Self {
+        self.with_last(|node| node.route(route))
+    }
+
+    fn with_last(mut self, edit: impl FnOnce(NodeBuilder) -> NodeBuilder) -> Self {
+        if let Some(node) = self.nodes.pop() {
+            self.nodes.push(edit(node));
+        }
+        self
+    }
+
+    /// Assemble the flow and validate it like a loaded document.
+    pub fn build(self) -> Result<FlowIr> {
+        self.build_with_yaml().map(|(flow, _)| flow)
+    }
+
+    /// [`build`](Self::build) and render the flow as YGTC YAML.
+    pub fn to_yaml(self) -> Result<String> {
+        self.build_with_yaml().map(|(_, yaml)| yaml)
+    }
+
+    fn build_with_yaml(self) -> Result<(FlowIr, String)> {
+        let location = || FlowErrorLocation::at_path(format!("flow.{}", self.id));
+        let kind = self.kind.clone().ok_or_else(|| FlowError::Internal {
+            message: format!("flow '{}' has no kind; call FlowBuilder::kind", self.id),
+            location: location(),
+        })?;
+        if self.nodes.is_empty() {
+            return Err(FlowError::Internal {
+                message: format!("flow '{}' has no nodes", self.id),
+                location: location(),
+            });
+        }
+        let mut nodes = IndexMap::new();
+        for node in self.nodes {
+            let id = node.id.clone();
+            if nodes.insert(id.clone(), node.into_ir()).is_some() {
+                return Err(FlowError::Internal {
+                    message: format!("node '{id}' is added more than once"),
+                    location: FlowErrorLocation::at_path(format!("nodes.{id}")),
+                });
+            }
+        }
+        let start = self
+            .start
+            .or_else(|| nodes.keys().next().cloned())
+            .expect("at least one node");
+        let mut entrypoints = IndexMap::new();
+        entrypoints.insert("default".to_string(), start.clone());
+        entrypoints.extend(self.entrypoints);
+
+        let flow = FlowIr {
+            id: self.id,
+            title: self.title,
+            description: self.description,
+            kind,
+            start: Some(start),
+            parameters: self.parameters,
+            tags: self.tags,
+            schema_version: Some(self.schema_version),
+            entrypoints,
+            meta: self.meta,
+            nodes: nodes.into(),
+            spans: SourceMap::default(),
+        };
+        let doc = flow.to_doc()?;
+        let mut yaml = serde_yaml_bw::to_string(&doc).map_err(|e| FlowError::Internal {
+            message: format!("serialize flow '{}': {e}", flow.id),
+            location: FlowErrorLocation::at_path(format!("flow.{}", flow.id)),
+        })?;
+        if !yaml.ends_with('\n') {
+            yaml.push('\n');
+        }
+        load_ygtc_from_str(&yaml)?;
+        Ok((flow, yaml))
+    }
+}
+
+impl NodeBuilder {
+    pub fn new(id: impl Into<String>, operation: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            operation: operation.into(),
+            payload: Value::Object(Map::new()),
+            output: Value::Object(Map::new()),
+            routing: Vec::new(),
+            telemetry: None,
+            limits: None,
+            feature: None,
+            doc: None,
+        }
+    }
+
+    pub fn payload(mut self, payload: Value) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    pub fn output(mut self, output: Value) -> Self {
+        self.output = output;
+        self
+    }
+
+    pub fn telemetry(mut self, telemetry: Value) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    pub fn limits(mut self, limits: NodeLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Guard the node behind a feature flag (see [`crate::features`]).
+    pub fn feature(mut self, flag: impl Into<String>) -> Self {
+        self.feature = Some(flag.into());
+        self
+    }
+
+    /// Describe the node for readers of the flow (the `doc` key).
+    pub fn doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    pub fn route_to(self, target: impl Into<String>) -> Self {
+        self.route(Route {
+            to: Some(target.into()),
+            ..Route::default()
+        })
+    }
+
+    pub fn route_on_status(self, status: impl Into<String>, target: impl Into<String>) -> Self {
+        self.route(Route {
+            to: Some(target.into()),
+            status: Some(status.into()),
+            ..Route::default()
+        })
+    }
+
+    pub fn out(self) -> Self {
+        self.route(Route {
+            out: true,
+            ..Route::default()
+        })
+    }
+
+    pub fn reply(self) -> Self {
+        self.route(Route {
+            reply: true,
+            ..Route::default()
+        })
+    }
+
+    pub fn route(mut self, route: Route) -> Self {
+        self.routing.push(route);
+        self
+    }
+
+    fn into_ir(self) -> NodeIr {
+        NodeIr {
+            id: self.id,
+            operation: self.operation,
+            payload: self.payload,
+            output: self.output,
+            routing: self.routing,
+            telemetry: self.telemetry,
+            limits: self.limits,
+            feature: self.feature,
+            doc: self.doc,
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Release channels stamped into packed bundles, and the checks a production host runs on them.
+//!
+//! `bundle pack --channel stable` records a [`BundleRelease`] under the bundle's `release` key:
+//!
+//! ```json
+//! "release": {
+//!   "channel": "stable",
+//!   "tool": "greentic-flow",
+//!   "tool_version": "0.4.51",
+//!   "built_at": 1760745600
+//! }
+//! ```
+//!
+//! The release is part of the signed manifest (see [`crate::bundle_signature::bundle_manifest`]),
+//! so a beta bundle cannot be relabelled as stable without breaking its signature.
+//! [`verify_deployed_bundle`] is what `doctor --expect-channel` runs: the bundle must be intact,
+//! stamped with the expected channel and signed by the trusted key.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    bundle_signature::{BundleSignature, VerifyingKey, verify_bundle_signature},
+    flow_bundle::{FlowBundle, blake3_hex, canonicalize_json},
+    loader::load_ygtc_from_str,
+};
+
+/// Suffix of packed bundle files (`main.bundle.json`).
+pub const BUNDLE_FILE_SUFFIX: &str = ".bundle.json";
+
+/// Where a bundle is allowed to run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    Stable,
+    Beta,
+}
+
+impl ReleaseChannel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+        }
+    }
+}
+
+impl fmt::Display for ReleaseChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ReleaseChannel {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text {
+            "stable" => Ok(Self::Stable),
+            "beta" => Ok(Self::Beta),
+            other => bail!("unknown release channel '{other}' (expected stable or beta)"),
+        }
+    }
+}
+
+/// Channel and build metadata of a packed bundle.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleRelease {
+    pub channel: ReleaseChannel,
+    /// Tool that packed the bundle.
+    pub tool: String,
+    pub tool_version: String,
+    /// Unix time of the build in seconds; `SOURCE_DATE_EPOCH` when set, for reproducible builds.
+    pub built_at: u64,
+}
+
+impl BundleRelease {
+    /// Release metadata for a bundle packed now by this crate.
+    pub fn new(channel: ReleaseChannel) -> Self {
+        let built_at = std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|epoch| epoch.trim().parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0)
+            });
+        Self {
+            channel,
+            tool: env!("CARGO_PKG_NAME").to_string(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            built_at,
+        }
+    }
+}
+
+/// Where `bundle pack` writes the bundle of `flow_path` by default (`main.ygtc` ->
+/// `main.bundle.json`).
+pub fn bundle_path_for_flow(flow_path: &Path) -> PathBuf {
+    let stem = flow_path.file_stem().unwrap_or_default().to_string_lossy();
+    flow_path.with_file_name(format!("{stem}{BUNDLE_FILE_SUFFIX}"))
+}
+
+/// Read a packed bundle file.
+pub fn load_bundle(path: &Path) -> Result<FlowBundle> {
+    let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("parse bundle {}", path.display()))
+}
+
+/// Check that `bundle` still matches its hash: its YAML and JSON must both hash to
+/// `hash_blake3`, so neither was edited after packing.
+pub fn verify_bundle_integrity(bundle: &FlowBundle) -> Result<()> {
+    let json_hash = blake3_hex(serde_json::to_vec(&canonicalize_json(&bundle.json))?);
+    if json_hash != bundle.hash_blake3 {
+        bail!(
+            "bundle '{}' json does not match its hash (recorded {}, actual {json_hash})",
+            bundle.id,
+            bundle.hash_blake3
+        );
+    }
+    let doc = load_ygtc_from_str(&bundle.yaml)
+        .with_context(|| format!("bundle '{}' yaml is not a valid flow", bundle.id))?;
+    let yaml_hash = blake3_hex(serde_json::to_vec(&canonicalize_json(
+        &serde_json::to_value(&doc)?,
+    ))?);
+    if yaml_hash != bundle.hash_blake3 {
+        bail!(
+            "bundle '{}' yaml does not match its hash (recorded {}, actual {yaml_hash})",
+            bundle.id,
+            bundle.hash_blake3
+        );
+    }
+    Ok(())
+}
+
+/// Everything a production host checks before loading a deployed bundle: it is intact, stamped
+/// with the `expected` channel, and `signature` was made by `trusted` over it.
+pub fn verify_deployed_bundle(
+    bundle: &FlowBundle,
+    signature: &BundleSignature,
+    trusted: &VerifyingKey,
+    expected: ReleaseChannel,
+) -> Result<()> {
+    verify_bundle_integrity(bundle)?;
+    match bundle.release.as_ref().map(|release| release.channel) {
+        None => bail!(
+            "bundle '{}' has no release channel (pack it with `bundle pack --channel {expected}`)",
+            bundle.id
+        ),
+        Some(channel) if channel != expected => bail!(
+            "bundle '{}' was packed for the {channel} channel, expected {expected}",
+            bundle.id
+        ),
+        Some(_) => {}
+    }
+    verify_bundle_signature(bundle, signature, trusted)
+}
@@ -0,0 +1,202 @@
+//! Detached ed25519 signatures over a [`FlowBundle`].
+//!
+//! What gets signed is the bundle manifest: the canonical JSON of the flow's id, kind, entry and
+//! BLAKE3 hash, plus the release channel and build metadata of a packed bundle. The hash already covers the whole canonical flow, so any edit to the flow (or a
+//! different flow under the same id) invalidates the signature. Signatures live next to the
+//! flow as JSON:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "algorithm": "ed25519",
+//!   "flow_id": "main",
+//!   "hash_blake3": "5c1e...",
+//!   "public_key": "d75a...",
+//!   "signature": "e556..."
+//! }
+//! ```
+//!
+//! Keys are hex strings: 32 bytes for a signing key's seed, 32 bytes for a public key.
+
+use anyhow::{Context, Result, bail};
+use ed25519_dalek::{Signature, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use crate::flow_bundle::{FlowBundle, canonicalize_json};
+
+/// Algorithm name recorded in every signature.
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// Extension appended to a flow's file name for its detached signature (`main.ygtc.sig`).
+pub const SIGNATURE_EXTENSION: &str = "sig";
+
+/// A detached signature over a bundle manifest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleSignature {
+    pub version: u32,
+    pub algorithm: String,
+    pub flow_id: String,
+    /// The bundle hash the signature covers.
+    pub hash_blake3: String,
+    /// Hex public key of the signer.
+    pub public_key: String,
+    /// Hex ed25519 signature over [`bundle_manifest`].
+    pub signature: String,
+}
+
+impl BundleSignature {
+    pub const VERSION: u32 = 1;
+
+    pub fn from_json_str(text: &str) -> Result<Self> {
+        let signature: Self =
+            serde_json::from_str(text).context("invalid bundle signature JSON")?;
+        if signature.version != Self::VERSION {
+            bail!(
+                "unsupported bundle signature version {} (expected {})",
+                signature.version,
+                Self::VERSION
+            );
+        }
+        if signature.algorithm != SIGNATURE_ALGORITHM {
+            bail!(
+                "unsupported signature algorithm '{}' (expected {SIGNATURE_ALGORITHM})",
+                signature.algorithm
+            );
+        }
+        Ok(signature)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        Self::from_json_str(&text).with_context(|| format!("load {}", path.display()))
+    }
+
+    /// Pretty JSON ending in a newline.
+    pub fn to_json_string(&self) -> String {
+        let mut text = serde_json::to_string_pretty(self).expect("signature serializes");
+        text.push('\n');
+        text
+    }
+}
+
+/// Where the detached signature of `flow_path` goes by default.
+pub fn signature_path_for_flow(flow_path: &Path) -> PathBuf {
+    let mut name = flow_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(SIGNATURE_EXTENSION);
+    flow_path.with_file_name(name)
+}
+
+/// The bytes a bundle signature covers: canonical JSON of id, kind, entry and hash, plus the
+/// release of a packed bundle.
+pub fn bundle_manifest(bundle: &FlowBundle) -> Vec<u8> {
+    let mut manifest = json!({
+        "version": BundleSignature::VERSION,
+        "id": bundle.id,
+        "kind": bundle.kind,
+        "entry": bundle.entry,
+        "hash_blake3": bundle.hash_blake3,
+    });
+    if let Some(release) = &bundle.release {
+        manifest["release"] = json!(release);
+    }
+    serde_json::to_vec(&canonicalize_json(&manifest)).expect("manifest serializes")
+}
+
+/// Sign `bundle`'s manifest with `key`.
+pub fn sign_bundle(bundle: &FlowBundle, key: &SigningKey) -> BundleSignature {
+    let signature = key.sign(&bundle_manifest(bundle));
+    BundleSignature {
+        version: BundleSignature::VERSION,
+        algorithm: SIGNATURE_ALGORITHM.to_string(),
+        flow_id: bundle.id.clone(),
+        hash_blake3: bundle.hash_blake3.clone(),
+        public_key: to_hex(key.verifying_key().as_bytes()),
+        signature: to_hex(&signature.to_bytes()),
+    }
+}
+
+/// Check that `signature` was made by `trusted` over `bundle` as it is now.
+///
+/// The public key recorded in the signature is only compared with `trusted`, never trusted on
+/// its own.
+pub fn verify_bundle_signature(
+    bundle: &FlowBundle,
+    signature: &BundleSignature,
+    trusted: &VerifyingKey,
+) -> Result<()> {
+    if signature.algorithm != SIGNATURE_ALGORITHM {
+        bail!(
+            "unsupported signature algorithm '{}' (expected {SIGNATURE_ALGORITHM})",
+            signature.algorithm
+        );
+    }
+    let trusted_hex = to_hex(trusted.as_bytes());
+    if !signature.public_key.eq_ignore_ascii_case(&trusted_hex) {
+        bail!(
+            "signed by key {}, not the trusted key {trusted_hex}",
+            signature.public_key
+        );
+    }
+    if signature.flow_id != bundle.id {
+        bail!(
+            "signature is for flow '{}', not '{}'",
+            signature.flow_id,
+            bundle.id
+        );
+    }
+    if signature.hash_blake3 != bundle.hash_blake3 {
+        bail!(
+            "flow '{}' changed since it was signed (signed hash {}, current {})",
+            bundle.id,
+            signature.hash_blake3,
+            bundle.hash_blake3
+        );
+    }
+    let bytes: [u8; 64] = from_hex(&signature.signature)
+        .context("invalid signature")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("invalid signature: expected 64 bytes"))?;
+    trusted
+        .verify(&bundle_manifest(bundle), &Signature::from_bytes(&bytes))
+        .map_err(|_| anyhow::anyhow!("signature does not match flow '{}'", bundle.id))
+}
+
+/// Parse a signing key from the hex of its 32-byte seed.
+pub fn signing_key_from_hex(text: &str) -> Result<SigningKey> {
+    Ok(SigningKey::from_bytes(&key_bytes(text, "signing key")?))
+}
+
+/// Parse a public key from hex.
+pub fn verifying_key_from_hex(text: &str) -> Result<VerifyingKey> {
+    VerifyingKey::from_bytes(&key_bytes(text, "public key")?)
+        .map_err(|e| anyhow::anyhow!("invalid public key: {e}"))
+}
+
+fn key_bytes(text: &str, what: &str) -> Result<[u8; 32]> {
+    from_hex(text.trim())
+        .with_context(|| format!("invalid {what}"))?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("invalid {what}: expected 32 bytes of hex"))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        bail!("not a hex string");
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&text[index..index + 2], 16).context("not a hex string"))
+        .collect()
+}
@@ -0,0 +1,181 @@
+//! Process-wide cancellation shared by Ctrl-C handling and the CLI's `--timeout`.
+//!
+//! Long operations poll [`cancelled`]. File writes hold a [`WriteGuard`] so the process never
+//! exits halfway through replacing a file, and temporary paths registered with
+//! [`track_temp_path`] are removed on the way out.
+
+use anyhow::Context;
+use std::{
+    cell::Cell,
+    fmt,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+/// Exit code for an operation interrupted with Ctrl-C (128 + SIGINT).
+pub const EXIT_INTERRUPTED: i32 = 130;
+/// Exit code for an operation that ran past `--timeout`, matching `timeout(1)`.
+pub const EXIT_TIMED_OUT: i32 = 124;
+
+/// Why an operation was cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cancelled {
+    Interrupted,
+    TimedOut(Duration),
+}
+
+impl Cancelled {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Cancelled::Interrupted => EXIT_INTERRUPTED,
+            Cancelled::TimedOut(_) => EXIT_TIMED_OUT,
+        }
+    }
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cancelled::Interrupted => write!(f, "interrupted"),
+            Cancelled::TimedOut(timeout) => write!(f, "timed out after {timeout:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+struct State {
+    reason: Option<Cancelled>,
+    temp_paths: Vec<PathBuf>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    reason: None,
+    temp_paths: Vec::new(),
+});
+static WRITERS: AtomicUsize = AtomicUsize::new(0);
+
+fn state() -> std::sync::MutexGuard<'static, State> {
+    STATE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Request cancellation. Returns `false` when the process was already cancelled; the first
+/// reason wins.
+pub fn cancel(reason: Cancelled) -> bool {
+    let mut state = state();
+    if state.reason.is_some() {
+        return false;
+    }
+    state.reason = Some(reason);
+    true
+}
+
+/// The cancellation reason, once one was requested.
+pub fn cancelled() -> Option<Cancelled> {
+    state().reason
+}
+
+/// `Err` once cancellation was requested; call between steps of long operations.
+pub fn check() -> Result<(), Cancelled> {
+    cancelled().map_or(Ok(()), Err)
+}
+
+/// Clear the cancellation reason, for hosts that run several operations in one process.
+pub fn reset() {
+    state().reason = None;
+}
+
+/// Held while files are being replaced; see [`write_guard`].
+#[derive(Debug)]
+pub struct WriteGuard {
+    // Guards count per thread, so they must be dropped on the thread that took them.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+        WRITERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+thread_local! {
+    /// Write guards held by this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Mark the start of a write phase. Refuses to start once cancellation was requested; a phase
+/// that already started runs to completion before [`wait_for_writes`] returns.
+///
+/// Guards nest: only the outermost guard of a thread checks for cancellation, so a handler
+/// holding one across several files that must change together finishes all of them.
+pub fn write_guard() -> Result<WriteGuard, Cancelled> {
+    WRITERS.fetch_add(1, Ordering::SeqCst);
+    let outermost = DEPTH.with(|depth| {
+        depth.set(depth.get() + 1);
+        depth.get() == 1
+    });
+    let guard = WriteGuard {
+        _not_send: PhantomData,
+    };
+    if outermost {
+        check()?;
+    }
+    Ok(guard)
+}
+
+/// Block until no write phase is in progress.
+pub fn wait_for_writes() {
+    while WRITERS.load(Ordering::SeqCst) > 0 {
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Remove `path` (file or directory) if the process is cancelled before it is untracked.
+pub fn track_temp_path(path: impl Into<PathBuf>) {
+    state().temp_paths.push(path.into());
+}
+
+/// Stop tracking `path`, e.g. after it was renamed into place or cleaned up normally.
+pub fn untrack_temp_path(path: &Path) {
+    state().temp_paths.retain(|tracked| tracked != path);
+}
+
+/// Remove every tracked temporary path. Errors are ignored: this runs on the way out.
+pub fn remove_temp_paths() {
+    let paths = std::mem::take(&mut state().temp_paths);
+    for path in paths {
+        if path.is_dir() {
+            let _ = std::fs::remove_dir_all(&path);
+        } else {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
+
+/// Write `content` next to `path` and rename it into place under a [`write_guard`], so neither
+/// cancellation nor a failed write leaves a truncated file or a stray temporary behind.
+pub fn replace_file_atomically(path: &Path, content: &[u8]) -> anyhow::Result<()> {
+    let _writing = write_guard()?;
+    let tmp_path = path.with_extension("tmp");
+    track_temp_path(&tmp_path);
+    let written = std::fs::write(&tmp_path, content)
+        .with_context(|| format!("failed to write {}", tmp_path.display()))
+        .and_then(|()| {
+            std::fs::rename(&tmp_path, path)
+                .with_context(|| format!("failed to replace {}", path.display()))
+        });
+    untrack_temp_path(&tmp_path);
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    written
+}
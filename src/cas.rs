@@ -0,0 +1,310 @@
+//! Content-addressed store for component artifacts, shared across packs.
+//!
+//! When [`CAS_DIR_ENV`] names a directory, local components bound to a flow are stored there by
+//! digest and sidecars reference them as `cas://sha256:<hex>` instead of a path into the pack.
+//! Each artifact lives in `<root>/sha256/<hex>/component.wasm`, next to its
+//! `component.manifest.json` when the source had one, so manifest lookups work unchanged.
+//! Reading an artifact refreshes its modification time, which [`ArtifactStore::gc`] treats as
+//! the last use.
+
+use serde::Serialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    resolve_summary::{compute_sha256, sha256_digest},
+};
+
+/// Environment variable naming the shared store; unset means packs keep their own copies.
+pub const CAS_DIR_ENV: &str = "GREENTIC_FLOW_CAS_DIR";
+/// Scheme of sidecar paths that point into the store.
+pub const CAS_SCHEME: &str = "cas://";
+
+const ARTIFACT_FILE: &str = "component.wasm";
+const MANIFEST_FILE: &str = "component.manifest.json";
+const TMP_DIR: &str = "tmp";
+
+/// `cas://sha256:<hex>` for a `sha256:<hex>` digest.
+pub fn cas_reference(digest: &str) -> String {
+    format!("{CAS_SCHEME}{digest}")
+}
+
+/// The digest a `cas://` reference names, or `None` for any other path.
+pub fn parse_cas_reference(reference: &str) -> Option<&str> {
+    reference.strip_prefix(CAS_SCHEME)
+}
+
+/// Where a sidecar path points when it is a `cas://` reference, or `None` for other paths.
+///
+/// The store comes from [`CAS_DIR_ENV`]; without one the reference is returned as is, so
+/// "missing" errors name it.
+pub fn cas_local_path(path: &str) -> Option<PathBuf> {
+    let digest = parse_cas_reference(path)?;
+    let located = ArtifactStore::from_env().and_then(|store| {
+        store
+            .locate(path)
+            .or_else(|_| store.artifact_path(digest))
+            .ok()
+    });
+    Some(located.unwrap_or_else(|| PathBuf::from(path)))
+}
+
+/// Artifacts in a store, as reported by `cas size`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct StoreUsage {
+    pub artifacts: usize,
+    pub bytes: u64,
+}
+
+/// An artifact `gc` removed (or, on a dry run, would remove).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RemovedArtifact {
+    pub digest: String,
+    pub bytes: u64,
+}
+
+/// Outcome of [`ArtifactStore::gc`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GcReport {
+    pub removed: Vec<RemovedArtifact>,
+    pub freed_bytes: u64,
+    pub kept: StoreUsage,
+}
+
+/// A content-addressed artifact directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The store named by [`CAS_DIR_ENV`], when set.
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os(CAS_DIR_ENV)
+            .filter(|dir| !dir.is_empty())
+            .map(Self::new)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the artifact with `digest` is (or would be) stored.
+    pub fn artifact_path(&self, digest: &str) -> Result<PathBuf> {
+        let hex = digest
+            .strip_prefix("sha256:")
+            .filter(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| cas_error(format!("invalid artifact digest '{digest}'")))?;
+        Ok(self
+            .root
+            .join("sha256")
+            .join(hex.to_ascii_lowercase())
+            .join(ARTIFACT_FILE))
+    }
+
+    /// Store the wasm at `source` (plus a `component.manifest.json` next to it) and return its
+    /// digest. Storing an artifact that is already present only refreshes its last use.
+    pub fn insert_file(&self, source: &Path) -> Result<String> {
+        let digest = compute_sha256(source).map_err(|err| cas_error(format!("{err:#}")))?;
+        let dest = self.artifact_path(&digest)?;
+        let dir = dest.parent().expect("artifact path has a parent");
+        if !dest.exists() {
+            let tmp_dir = self.root.join(TMP_DIR);
+            fs::create_dir_all(&tmp_dir).map_err(|err| io_error(&tmp_dir, err))?;
+            fs::create_dir_all(dir).map_err(|err| io_error(dir, err))?;
+            let manifest = source.with_file_name(MANIFEST_FILE);
+            if manifest.is_file() {
+                copy_atomically(&manifest, &dir.join(MANIFEST_FILE), &tmp_dir)?;
+            }
+            copy_atomically(source, &dest, &tmp_dir)?;
+        }
+        touch(&dest);
+        Ok(digest)
+    }
+
+    /// Store a wasm (plus its `component.manifest.json`) that is only in memory, such as a
+    /// download, and return its digest.
+    pub fn insert_bytes(&self, wasm: &[u8], manifest: Option<&[u8]>) -> Result<String> {
+        let digest = sha256_digest(wasm);
+        let dest = self.artifact_path(&digest)?;
+        let dir = dest.parent().expect("artifact path has a parent");
+        if !dest.exists() {
+            let tmp_dir = self.root.join(TMP_DIR);
+            fs::create_dir_all(&tmp_dir).map_err(|err| io_error(&tmp_dir, err))?;
+            fs::create_dir_all(dir).map_err(|err| io_error(dir, err))?;
+            if let Some(manifest) = manifest {
+                write_atomically(manifest, &dir.join(MANIFEST_FILE), &tmp_dir)?;
+            }
+            write_atomically(wasm, &dest, &tmp_dir)?;
+        }
+        touch(&dest);
+        Ok(digest)
+    }
+
+    /// Path of the artifact with `digest`, when the store has it.
+    pub fn get(&self, digest: &str) -> Option<PathBuf> {
+        let path = self.artifact_path(digest).ok()?;
+        path.is_file().then(|| {
+            touch(&path);
+            path
+        })
+    }
+
+    /// Path of the artifact a `cas://` reference names; fails when it is not in the store.
+    pub fn locate(&self, reference: &str) -> Result<PathBuf> {
+        let digest = parse_cas_reference(reference)
+            .ok_or_else(|| cas_error(format!("'{reference}' is not a {CAS_SCHEME} reference")))?;
+        let path = self.artifact_path(digest)?;
+        if !path.is_file() {
+            return Err(cas_error(format!(
+                "artifact {digest} is not in the store at {}",
+                self.root.display()
+            )));
+        }
+        touch(&path);
+        Ok(path)
+    }
+
+    /// Count and total size of the stored artifacts (manifests included).
+    pub fn usage(&self) -> Result<StoreUsage> {
+        let mut usage = StoreUsage::default();
+        for artifact in self.artifacts()? {
+            usage.artifacts += 1;
+            usage.bytes += artifact.bytes;
+        }
+        Ok(usage)
+    }
+
+    /// Remove artifacts not used within `keep_for`, plus leftover temporary files. With
+    /// `dry_run`, only report what would be removed.
+    pub fn gc(&self, keep_for: Duration, dry_run: bool) -> Result<GcReport> {
+        let cutoff = SystemTime::now()
+            .checked_sub(keep_for)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut report = GcReport::default();
+        for artifact in self.artifacts()? {
+            if artifact.last_used >= cutoff {
+                report.kept.artifacts += 1;
+                report.kept.bytes += artifact.bytes;
+                continue;
+            }
+            if !dry_run {
+                fs::remove_dir_all(&artifact.dir).map_err(|err| io_error(&artifact.dir, err))?;
+            }
+            report.freed_bytes += artifact.bytes;
+            report.removed.push(RemovedArtifact {
+                digest: artifact.digest,
+                bytes: artifact.bytes,
+            });
+        }
+        let tmp_dir = self.root.join(TMP_DIR);
+        if !dry_run && let Ok(entries) = fs::read_dir(&tmp_dir) {
+            for entry in entries.flatten() {
+                let stale = entry
+                    .metadata()
+                    .and_then(|meta| meta.modified())
+                    .is_ok_and(|modified| modified < cutoff);
+                if stale {
+                    let _ = fs::remove_file(entry.path());
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    fn artifacts(&self) -> Result<Vec<StoredArtifact>> {
+        let dir = self.root.join("sha256");
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut artifacts = Vec::new();
+        for entry in fs::read_dir(&dir).map_err(|err| io_error(&dir, err))? {
+            let entry = entry.map_err(|err| io_error(&dir, err))?;
+            let artifact_dir = entry.path();
+            let wasm = artifact_dir.join(ARTIFACT_FILE);
+            let Ok(meta) = fs::metadata(&wasm) else {
+                continue;
+            };
+            let mut bytes = meta.len();
+            if let Ok(manifest) = fs::metadata(artifact_dir.join(MANIFEST_FILE)) {
+                bytes += manifest.len();
+            }
+            artifacts.push(StoredArtifact {
+                digest: format!("sha256:{}", entry.file_name().to_string_lossy()),
+                last_used: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                bytes,
+                dir: artifact_dir,
+            });
+        }
+        artifacts.sort_by(|a, b| a.digest.cmp(&b.digest));
+        Ok(artifacts)
+    }
+}
+
+struct StoredArtifact {
+    digest: String,
+    dir: PathBuf,
+    bytes: u64,
+    last_used: SystemTime,
+}
+
+/// Copy via a temporary file in `tmp_dir` (on the same filesystem) so readers never see a
+/// partial artifact.
+fn copy_atomically(source: &Path, dest: &Path, tmp_dir: &Path) -> Result<()> {
+    let tmp = tmp_path(dest, tmp_dir);
+    fs::copy(source, &tmp).map_err(|err| io_error(source, err))?;
+    rename_into_place(&tmp, dest)
+}
+
+/// [`copy_atomically`] for bytes in memory.
+fn write_atomically(bytes: &[u8], dest: &Path, tmp_dir: &Path) -> Result<()> {
+    let tmp = tmp_path(dest, tmp_dir);
+    fs::write(&tmp, bytes).map_err(|err| io_error(&tmp, err))?;
+    rename_into_place(&tmp, dest)
+}
+
+fn tmp_path(dest: &Path, tmp_dir: &Path) -> PathBuf {
+    let name = dest
+        .parent()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    tmp_dir.join(format!(
+        "{name}.{}.{}.tmp",
+        dest.file_name().unwrap_or_default().to_string_lossy(),
+        std::process::id()
+    ))
+}
+
+fn rename_into_place(tmp: &Path, dest: &Path) -> Result<()> {
+    fs::rename(tmp, dest).map_err(|err| {
+        let _ = fs::remove_file(tmp);
+        io_error(dest, err)
+    })
+}
+
+/// Record a use of `path`. Best effort: a read-only store still serves artifacts.
+fn touch(path: &Path) {
+    if let Ok(file) = fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> FlowError {
+    cas_error(format!("{}: {err}", path.display()))
+}
+
+fn cas_error(message: String) -> FlowError {
+    FlowError::Internal {
+        message,
+        location: FlowErrorLocation::at_path("cas"),
+    }
+}
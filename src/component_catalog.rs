@@ -1,6 +1,9 @@
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
 
-use serde::Deserialize;
 use serde_json::{Value, json};
 /// Minimal metadata needed to validate that a component exists and which config keys
 /// are required.
@@ -15,57 +18,284 @@ pub trait ComponentCatalog: Send + Sync {
 }
 
 /// Catalog backed by component.manifest.json files on disk.
+///
+/// Manifests written by older component SDKs are read through [`parse_manifest`]; anything the
+/// catalog could only partly recover, or had to skip, is kept in [`ManifestCatalog::warnings`].
 #[derive(Debug, Default, Clone)]
 pub struct ManifestCatalog {
     entries: HashMap<String, ComponentMetadata>,
+    warnings: Vec<ManifestWarning>,
 }
 
-#[derive(Deserialize)]
-struct Manifest {
-    id: String,
-    #[serde(default)]
-    config_schema: Option<Schema>,
+/// Component SDK generation a manifest was written by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ManifestVersion {
+    /// Component named by `name`; config JSON Schema under `config`.
+    V0_4,
+    /// `id`; required config declared by the legacy QA spec under `qa.questions`, or by a
+    /// `config_schema` when the SDK emitted one.
+    V0_5,
+    /// `id` and `config_schema` (current).
+    V0_6,
 }
 
-#[derive(Deserialize, Default)]
-struct Schema {
-    #[serde(default)]
-    required: Vec<String>,
+impl ManifestVersion {
+    /// The generation declared by `manifest_version` or the `world` version (`...@0.5.2`),
+    /// otherwise guessed from the manifest's shape. Versions newer than 0.6 are returned as
+    /// `Err(version)`.
+    pub fn detect(value: &Value) -> Result<Self, String> {
+        let declared = value
+            .get("manifest_version")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| {
+                value
+                    .get("world")
+                    .and_then(Value::as_str)
+                    .and_then(|world| world.rsplit_once('@'))
+                    .map(|(_, version)| version.to_string())
+            });
+        if let Some(version) = declared {
+            let mut parts = version.split('.');
+            let major = parts.next().and_then(|p| p.parse::<u64>().ok());
+            let minor = parts.next().and_then(|p| p.parse::<u64>().ok());
+            match (major, minor) {
+                (Some(0), Some(minor)) if minor <= 4 => return Ok(Self::V0_4),
+                (Some(0), Some(5)) => return Ok(Self::V0_5),
+                (Some(0), Some(6)) => return Ok(Self::V0_6),
+                (Some(_), Some(_)) => return Err(version),
+                _ => {}
+            }
+        }
+        if value.get("id").is_none() && value.get("name").is_some() {
+            Ok(Self::V0_4)
+        } else if value.get("config_schema").is_none() && value.get("qa").is_some() {
+            Ok(Self::V0_5)
+        } else {
+            Ok(Self::V0_6)
+        }
+    }
+}
+
+impl fmt::Display for ManifestVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::V0_4 => "0.4",
+            Self::V0_5 => "0.5",
+            Self::V0_6 => "0.6",
+        })
+    }
+}
+
+/// Something the catalog could not fully read from one manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestWarning {
+    pub path: PathBuf,
+    /// The component, when the manifest got far enough to name one.
+    pub component_id: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ManifestWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.component_id {
+            Some(id) => write!(f, "{} ({id}): {}", self.path.display(), self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+/// A manifest read into the current shape, with notes on what was lost or guessed.
+#[derive(Debug, Clone)]
+pub struct ParsedManifest {
+    pub metadata: ComponentMetadata,
+    pub version: ManifestVersion,
+    pub warnings: Vec<String>,
+}
+
+/// Read a manifest of any supported generation, adapting older ones to the current shape.
+pub fn parse_manifest(mut value: Value) -> Result<ParsedManifest, String> {
+    normalize_manifest_value(&mut value);
+    let mut warnings = Vec::new();
+    let version = match ManifestVersion::detect(&value) {
+        Ok(version) => version,
+        Err(newer) => {
+            warnings.push(format!(
+                "manifest version {newer} is newer than 0.6; read as 0.6, newer fields are ignored"
+            ));
+            ManifestVersion::V0_6
+        }
+    };
+    let (id, required_fields) = match version {
+        ManifestVersion::V0_4 => parse_v0_4(&value, &mut warnings)?,
+        ManifestVersion::V0_5 => parse_v0_5(&value, &mut warnings)?,
+        ManifestVersion::V0_6 => parse_v0_6(&value, &mut warnings)?,
+    };
+    Ok(ParsedManifest {
+        metadata: ComponentMetadata {
+            id,
+            required_fields,
+        },
+        version,
+        warnings,
+    })
+}
+
+fn manifest_id(value: &Value, key: &str) -> Result<String, String> {
+    match value.get(key) {
+        Some(Value::String(id)) if !id.trim().is_empty() => Ok(id.clone()),
+        Some(_) => Err(format!("`{key}` must be a non-empty string")),
+        None => Err(format!("missing `{key}`")),
+    }
+}
+
+/// `required` of a config JSON Schema.
+fn schema_required(schema: &Value, key: &str, warnings: &mut Vec<String>) -> Vec<String> {
+    let schema = match schema {
+        // Some SDKs wrote the schema as a JSON string.
+        Value::String(text) => match serde_json::from_str::<Value>(text) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                warnings.push(format!(
+                    "`{key}` is not valid JSON ({err}); required config fields are unknown"
+                ));
+                return Vec::new();
+            }
+        },
+        other => other.clone(),
+    };
+    match schema.get("required") {
+        None => Vec::new(),
+        Some(Value::Array(items)) => {
+            let fields: Vec<String> = items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect();
+            if fields.len() != items.len() {
+                warnings.push(format!(
+                    "`{key}.required` has non-string entries; they were ignored"
+                ));
+            }
+            fields
+        }
+        Some(_) => {
+            warnings.push(format!(
+                "`{key}.required` is not an array; required config fields are unknown"
+            ));
+            Vec::new()
+        }
+    }
+}
+
+fn parse_v0_6(value: &Value, warnings: &mut Vec<String>) -> Result<(String, Vec<String>), String> {
+    let id = manifest_id(value, "id")?;
+    let required = value
+        .get("config_schema")
+        .map(|schema| schema_required(schema, "config_schema", warnings))
+        .unwrap_or_default();
+    Ok((id, required))
+}
+
+fn parse_v0_5(value: &Value, warnings: &mut Vec<String>) -> Result<(String, Vec<String>), String> {
+    if value.get("config_schema").is_some() {
+        return parse_v0_6(value, warnings);
+    }
+    let id = manifest_id(value, "id")?;
+    let Some(questions) = value
+        .get("qa")
+        .and_then(|qa| qa.get("questions"))
+        .and_then(Value::as_array)
+    else {
+        warnings.push(
+            "0.5 manifest has neither `config_schema` nor `qa.questions`; required config \
+             fields are unknown"
+                .to_string(),
+        );
+        return Ok((id, Vec::new()));
+    };
+    let required = questions
+        .iter()
+        .filter(|question| question.get("required").and_then(Value::as_bool) == Some(true))
+        .filter_map(|question| question.get("id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    warnings.push(
+        "required config fields inferred from the 0.5 QA questions; schema-only constraints \
+         are not known"
+            .to_string(),
+    );
+    Ok((id, required))
+}
+
+fn parse_v0_4(value: &Value, warnings: &mut Vec<String>) -> Result<(String, Vec<String>), String> {
+    let id = match manifest_id(value, "id") {
+        Ok(id) => id,
+        Err(_) => manifest_id(value, "name")?,
+    };
+    let required = match (value.get("config_schema"), value.get("config")) {
+        (Some(schema), _) => schema_required(schema, "config_schema", warnings),
+        (None, Some(schema)) => schema_required(schema, "config", warnings),
+        (None, None) => {
+            warnings.push(
+                "0.4 manifest declares no config schema; required config fields are unknown"
+                    .to_string(),
+            );
+            Vec::new()
+        }
+    };
+    Ok((id, required))
 }
 
 impl ManifestCatalog {
     pub fn load_from_paths(paths: &[impl AsRef<Path>]) -> Self {
-        let mut entries = HashMap::new();
+        let mut catalog = ManifestCatalog::default();
         for path in paths {
             let path = path.as_ref();
-            if let Ok(text) = fs::read_to_string(path)
-                && let Ok(mut value) = serde_json::from_str::<Value>(&text)
+            let warn = |component_id: Option<String>, message: String| ManifestWarning {
+                path: path.to_path_buf(),
+                component_id,
+                message,
+            };
+            // Unreadable manifests are skipped with a warning to keep the catalog usable.
+            let value = match fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()))
             {
-                normalize_manifest_value(&mut value);
-                if let Ok(manifest) = serde_json::from_value::<Manifest>(value) {
-                    entries.insert(
-                        manifest.id.clone(),
-                        ComponentMetadata {
-                            id: manifest.id,
-                            required_fields: manifest
-                                .config_schema
-                                .unwrap_or_default()
-                                .required
-                                .clone(),
-                        },
-                    );
-                    entries
-                        .entry("component.exec".to_string())
-                        .or_insert(ComponentMetadata {
-                            id: "component.exec".to_string(),
-                            required_fields: Vec::new(),
-                        });
+                Ok(value) => value,
+                Err(err) => {
+                    catalog.warnings.push(warn(None, format!("skipped: {err}")));
                     continue;
                 }
-            }
-            // Continue without crashing on unreadable manifests to keep the catalog usable.
+            };
+            let parsed = match parse_manifest(value) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    catalog.warnings.push(warn(None, format!("skipped: {err}")));
+                    continue;
+                }
+            };
+            let id = parsed.metadata.id.clone();
+            catalog.warnings.extend(
+                parsed
+                    .warnings
+                    .into_iter()
+                    .map(|message| warn(Some(id.clone()), message)),
+            );
+            catalog.entries.insert(id, parsed.metadata);
+            catalog
+                .entries
+                .entry("component.exec".to_string())
+                .or_insert(ComponentMetadata {
+                    id: "component.exec".to_string(),
+                    required_fields: Vec::new(),
+                });
         }
-        ManifestCatalog { entries }
+        catalog
+    }
+
+    /// Manifests that were skipped or only partly understood, in load order.
+    pub fn warnings(&self) -> &[ManifestWarning] {
+        &self.warnings
     }
 }
 
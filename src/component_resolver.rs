@@ -0,0 +1,444 @@
+//! Scheme-based component resolution.
+//!
+//! A [`ComponentResolver`] turns a component reference into its digest, wasm artifact and
+//! manifest. [`ResolverRegistry`] picks the resolver from the reference's scheme: the built-in
+//! backends cover `oci://`, `repo://`, `store://` and `file://`, and embedders add their own
+//! schemes with [`register_resolver`]. The `s3` and `gcs` features add `s3://` and `gs://`
+//! backends. add-step, doctor and the resolve summary all resolve through
+//! [`ResolverRegistry::current`].
+//!
+//! The sidecar format only knows local, oci, repo and store sources, so references with a
+//! custom scheme are recorded as `repo` sources and dispatched on their scheme when read back.
+//!
+//! [`ResolverRegistry::current`] also applies the [mirror rules](crate::mirrors) named by
+//! `GREENTIC_FLOW_MIRRORS` before dispatching, so a reference may be fetched from another
+//! registry than the one it names.
+
+use anyhow::{Context, Result, anyhow, bail};
+use greentic_distributor_client::DistClient;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::mirrors::MirrorRules;
+#[cfg(any(feature = "s3", feature = "gcs"))]
+use crate::object_store_resolver::ObjectStoreResolver;
+use crate::resolve_summary::compute_sha256;
+
+const MANIFEST_FILE: &str = "component.manifest.json";
+
+/// What a resolver found for a reference.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedComponent {
+    /// `sha256:<hex>` digest of the component.
+    pub digest: String,
+    /// The wasm artifact on local disk.
+    pub wasm_path: PathBuf,
+    /// `component.manifest.json` for the artifact, when the backend has one.
+    pub manifest_path: Option<PathBuf>,
+    /// Set by [`ResolverRegistry`] when a mirror rule rewrote the reference.
+    pub provenance: Option<ResolutionProvenance>,
+}
+
+/// The reference a caller asked for and the one that was actually fetched.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ResolutionProvenance {
+    pub requested: String,
+    pub effective: String,
+}
+
+/// A backend that resolves references with the schemes it names.
+pub trait ComponentResolver: Send + Sync {
+    /// Schemes this resolver handles, without `://` (e.g. `["s3"]`).
+    fn schemes(&self) -> &[&str];
+
+    /// Fetch the component `reference` names. `pinned` is the digest the sidecar pins, when
+    /// there is one; backends that can fetch by digest should prefer it.
+    fn resolve(&self, reference: &str, pinned: Option<&str>) -> Result<ResolvedComponent>;
+
+    /// The digest `reference` currently names. Defaults to a full [`resolve`](Self::resolve);
+    /// override it when the backend can answer without fetching the artifact.
+    fn resolve_digest(&self, reference: &str) -> Result<String> {
+        Ok(self.resolve(reference, None)?.digest)
+    }
+}
+
+/// The scheme of `reference` (`oci` for `oci://...`), if it has one.
+pub fn reference_scheme(reference: &str) -> Option<&str> {
+    reference
+        .split_once("://")
+        .map(|(scheme, _)| scheme)
+        .filter(|scheme| !scheme.is_empty())
+}
+
+static REGISTERED: Mutex<Vec<Arc<dyn ComponentResolver>>> = Mutex::new(Vec::new());
+
+/// Add a resolver for the rest of the process. It takes precedence over the built-in backends
+/// and earlier registrations for the schemes it names.
+pub fn register_resolver(resolver: Arc<dyn ComponentResolver>) {
+    REGISTERED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .push(resolver);
+}
+
+/// Resolvers keyed by scheme; later additions win.
+#[derive(Clone, Default)]
+pub struct ResolverRegistry {
+    resolvers: Vec<Arc<dyn ComponentResolver>>,
+    mirrors: MirrorRules,
+    /// Why the mirrors file could not be read; every resolution reports it.
+    mirrors_error: Option<String>,
+}
+
+impl ResolverRegistry {
+    /// The built-in backends: the distributor for `oci`/`repo`/`store`, local files, and
+    /// `s3`/`gs` object storage when the `s3`/`gcs` features are enabled.
+    pub fn builtin() -> Self {
+        let registry = Self::default()
+            .with(Arc::new(DistResolver))
+            .with(Arc::new(FileResolver));
+        #[cfg(feature = "s3")]
+        let registry = registry.with(Arc::new(ObjectStoreResolver::s3()));
+        #[cfg(feature = "gcs")]
+        let registry = registry.with(Arc::new(ObjectStoreResolver::gcs()));
+        registry
+    }
+
+    /// The built-in backends plus every resolver added with [`register_resolver`], with the
+    /// mirror rules from [`MIRRORS_FILE_ENV`](crate::mirrors::MIRRORS_FILE_ENV).
+    pub fn current() -> Self {
+        let mut registry = Self::builtin();
+        let registered = REGISTERED
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        registry.resolvers.extend(registered.iter().cloned());
+        match MirrorRules::from_env() {
+            Ok(mirrors) => registry.mirrors = mirrors,
+            Err(err) => registry.mirrors_error = Some(err.to_string()),
+        }
+        registry
+    }
+
+    /// Rewrite references with `mirrors` before resolving them.
+    pub fn with_mirrors(mut self, mirrors: MirrorRules) -> Self {
+        self.mirrors = mirrors;
+        self.mirrors_error = None;
+        self
+    }
+
+    pub fn mirrors(&self) -> &MirrorRules {
+        &self.mirrors
+    }
+
+    /// The reference resolution fetches for `reference`, after mirror rules.
+    pub fn effective_reference(&self, reference: &str) -> Result<String> {
+        if let Some(err) = &self.mirrors_error {
+            bail!("{err}");
+        }
+        Ok(self
+            .mirrors
+            .rewrite(reference)
+            .unwrap_or_else(|| reference.to_string()))
+    }
+
+    /// Add `resolver` on top of the existing ones.
+    pub fn with(mut self, resolver: Arc<dyn ComponentResolver>) -> Self {
+        self.resolvers.push(resolver);
+        self
+    }
+
+    /// The resolver for `reference`'s scheme.
+    pub fn resolver_for(&self, reference: &str) -> Result<&dyn ComponentResolver> {
+        let scheme = reference_scheme(reference)
+            .ok_or_else(|| anyhow!("component reference {reference} has no scheme"))?;
+        self.resolvers
+            .iter()
+            .rev()
+            .find(|resolver| resolver.schemes().contains(&scheme))
+            .map(|resolver| resolver.as_ref())
+            .ok_or_else(|| {
+                anyhow!(
+                    "no resolver for {scheme}:// references (known: {})",
+                    self.schemes()
+                        .iter()
+                        .map(|scheme| format!("{scheme}://"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+
+    /// Whether some resolver handles `reference`'s scheme.
+    pub fn handles(&self, reference: &str) -> bool {
+        self.resolver_for(reference).is_ok()
+    }
+
+    /// Every scheme with a resolver, sorted.
+    pub fn schemes(&self) -> Vec<&str> {
+        let mut schemes: Vec<&str> = self
+            .resolvers
+            .iter()
+            .flat_map(|resolver| resolver.schemes().iter().copied())
+            .collect();
+        schemes.sort_unstable();
+        schemes.dedup();
+        schemes
+    }
+
+    pub fn resolve(&self, reference: &str, pinned: Option<&str>) -> Result<ResolvedComponent> {
+        let effective = self.effective_reference(reference)?;
+        let mut resolved = self
+            .resolver_for(&effective)?
+            .resolve(&effective, pinned)
+            .map_err(|err| via_mirror(err, reference, &effective))?;
+        if effective != reference {
+            resolved.provenance = Some(ResolutionProvenance {
+                requested: reference.to_string(),
+                effective,
+            });
+        }
+        Ok(resolved)
+    }
+
+    pub fn resolve_digest(&self, reference: &str) -> Result<String> {
+        let effective = self.effective_reference(reference)?;
+        self.resolver_for(&effective)?
+            .resolve_digest(&effective)
+            .map_err(|err| via_mirror(err, reference, &effective))
+    }
+}
+
+/// Name the mirror in errors for rewritten references; other errors pass through unchanged.
+fn via_mirror(err: anyhow::Error, reference: &str, effective: &str) -> anyhow::Error {
+    if reference == effective {
+        err
+    } else {
+        anyhow!("{reference} via mirror {effective}: {err:#}")
+    }
+}
+
+/// `oci://`, `repo://` and `store://` references, fetched through the distributor cache.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DistResolver;
+
+impl ComponentResolver for DistResolver {
+    fn schemes(&self) -> &[&str] {
+        &["oci", "repo", "store"]
+    }
+
+    fn resolve(&self, reference: &str, pinned: Option<&str>) -> Result<ResolvedComponent> {
+        let client = DistClient::new(Default::default());
+        let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
+        // A pinned digest is resolved by digest only: falling back to the reference would
+        // validate whatever its tag points to now.
+        let (digest, mut wasm_path) = match pinned {
+            Some(digest) => {
+                let path = rt.block_on(client.fetch_digest(digest)).map_err(|e| {
+                    anyhow!(
+                        "component digest {digest} for {reference} not cached; pull or pin locally first: {e}"
+                    )
+                })?;
+                (digest.to_string(), path)
+            }
+            None => {
+                let resolved = rt.block_on(client.ensure_cached(reference)).map_err(|e| {
+                    anyhow!("component reference {reference} not available locally: {e}")
+                })?;
+                let path = resolved
+                    .cache_path
+                    .ok_or_else(|| anyhow!("component reference {reference} has no cache path"))?;
+                (resolved.digest, path)
+            }
+        };
+        let manifest_path = wasm_path
+            .parent()
+            .map(|dir| dir.join(MANIFEST_FILE))
+            .filter(|path| path.is_file());
+        if let Some(manifest_wasm) = manifest_path
+            .as_deref()
+            .map(manifest_component_wasm)
+            .transpose()?
+            .flatten()
+        {
+            wasm_path = manifest_wasm;
+        }
+        Ok(ResolvedComponent {
+            digest,
+            wasm_path,
+            manifest_path,
+            provenance: None,
+        })
+    }
+
+    fn resolve_digest(&self, reference: &str) -> Result<String> {
+        let client = DistClient::new(Default::default());
+        let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
+        let resolved = rt
+            .block_on(client.resolve_ref(reference))
+            .map_err(|e| anyhow!("failed to resolve reference {reference}: {e}"))?;
+        Ok(resolved.digest)
+    }
+}
+
+/// The wasm a cached `component.manifest.json` names under `artifacts.component_wasm`, when
+/// it exists next to the manifest.
+fn manifest_component_wasm(manifest_path: &Path) -> Result<Option<PathBuf>> {
+    let raw = fs::read_to_string(manifest_path)
+        .with_context(|| format!("read {}", manifest_path.display()))?;
+    let json: Value = serde_json::from_str(&raw).context("parse component.manifest.json")?;
+    let Some(rel) = json
+        .pointer("/artifacts/component_wasm")
+        .and_then(Value::as_str)
+    else {
+        return Ok(None);
+    };
+    let candidate = manifest_path.with_file_name(rel);
+    Ok(candidate.exists().then_some(candidate))
+}
+
+/// `file://` references to a wasm on local disk.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileResolver;
+
+impl ComponentResolver for FileResolver {
+    fn schemes(&self) -> &[&str] {
+        &["file"]
+    }
+
+    fn resolve(&self, reference: &str, _pinned: Option<&str>) -> Result<ResolvedComponent> {
+        let wasm_path = PathBuf::from(reference.strip_prefix("file://").unwrap_or(reference));
+        let digest = compute_sha256(&wasm_path)?;
+        let manifest_path = Some(wasm_path.with_file_name(MANIFEST_FILE)).filter(|p| p.is_file());
+        Ok(ResolvedComponent {
+            digest,
+            wasm_path,
+            manifest_path,
+            provenance: None,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FixtureIndex {
+    components: BTreeMap<String, FixtureComponentEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FixtureComponentEntry {
+    #[serde(default)]
+    path: Option<String>,
+}
+
+/// Resolves every built-in scheme from a fixture directory, for tests and CI
+/// (`--resolver fixture://<dir>`).
+///
+/// Components listed in `<dir>/index.json` live in the directory their entry names (default
+/// `components/<key>`); others are `<dir>/<key>.wasm` or `<dir>/<key>/component.wasm`, where
+/// `<key>` comes from [`fixture_key`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixtureResolver {
+    root: PathBuf,
+}
+
+impl FixtureResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The fixture resolver a `fixture://<dir>` override names, or `None` for other overrides.
+    pub fn from_override(resolver: &str) -> Option<Self> {
+        resolver.strip_prefix("fixture://").map(Self::new)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory of `reference` when `index.json` lists it.
+    pub fn indexed_dir(&self, reference: &str) -> Result<Option<PathBuf>> {
+        let path = self.root.join("index.json");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("read fixture index {}", path.display()))?;
+        let index: FixtureIndex =
+            serde_json::from_str(&text).context("parse fixture index JSON")?;
+        let entry = index
+            .components
+            .get(reference)
+            .or_else(|| index.components.get(strip_builtin_scheme(reference)));
+        Ok(entry.map(|entry| match &entry.path {
+            Some(path) => self.root.join(path),
+            None => self.root.join("components").join(fixture_key(reference)),
+        }))
+    }
+}
+
+impl ComponentResolver for FixtureResolver {
+    fn schemes(&self) -> &[&str] {
+        &["oci", "repo", "store", "file"]
+    }
+
+    fn resolve(&self, reference: &str, _pinned: Option<&str>) -> Result<ResolvedComponent> {
+        let wasm_path = if let Some(dir) = self.indexed_dir(reference)? {
+            let wasm_path = dir.join("component.wasm");
+            if !wasm_path.exists() {
+                anyhow::bail!(
+                    "fixture resolver missing wasm for {} (expected {})",
+                    reference,
+                    wasm_path.display()
+                );
+            }
+            wasm_path
+        } else {
+            let key = fixture_key(reference);
+            let direct = self.root.join(format!("{key}.wasm"));
+            let nested = self.root.join(&key).join("component.wasm");
+            if direct.exists() {
+                direct
+            } else if nested.exists() {
+                nested
+            } else {
+                anyhow::bail!(
+                    "fixture resolver missing {} (looked for {} or {})",
+                    reference,
+                    direct.display(),
+                    nested.display()
+                );
+            }
+        };
+        let digest = compute_sha256(&wasm_path)?;
+        let manifest_path = Some(wasm_path.with_file_name(MANIFEST_FILE)).filter(|p| p.is_file());
+        Ok(ResolvedComponent {
+            digest,
+            wasm_path,
+            manifest_path,
+            provenance: None,
+        })
+    }
+}
+
+/// File-name key of a reference inside a fixture directory: the reference without its
+/// built-in scheme, with `/`, `:` and `@` replaced by `_`.
+pub fn fixture_key(reference: &str) -> String {
+    reference
+        .trim_start_matches("oci://")
+        .trim_start_matches("repo://")
+        .trim_start_matches("store://")
+        .trim_start_matches("file://")
+        .replace(['/', ':', '@'], "_")
+}
+
+fn strip_builtin_scheme(reference: &str) -> &str {
+    ["oci://", "repo://", "store://", "file://"]
+        .iter()
+        .find_map(|scheme| reference.strip_prefix(scheme))
+        .unwrap_or(reference)
+}
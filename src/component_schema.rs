@@ -37,15 +37,7 @@ impl SchemaResolution {
 }
 
 pub fn resolve_input_schema(manifest_path: &Path, operation: &str) -> Result<SchemaResolution> {
-    let text = fs::read_to_string(manifest_path).map_err(|err| FlowError::Internal {
-        message: format!("read manifest {}: {err}", manifest_path.display()),
-        location: FlowErrorLocation::at_path(manifest_path.display().to_string()),
-    })?;
-    let mut json: Value = serde_json::from_str(&text).map_err(|err| FlowError::Internal {
-        message: format!("parse manifest {}: {err}", manifest_path.display()),
-        location: FlowErrorLocation::at_path(manifest_path.display().to_string()),
-    })?;
-    normalize_manifest_value(&mut json);
+    let json = read_manifest(manifest_path)?;
     let component_id = json
         .get("id")
         .and_then(Value::as_str)
@@ -70,6 +62,131 @@ pub fn resolve_input_schema(manifest_path: &Path, operation: &str) -> Result<Sch
     ))
 }
 
+/// The `operations[].output_schema` declared for `operation`, if any.
+pub fn resolve_output_schema(manifest_path: &Path, operation: &str) -> Result<Option<Value>> {
+    let json = read_manifest(manifest_path)?;
+    Ok(json
+        .get("operations")
+        .and_then(Value::as_array)
+        .and_then(|ops| ops.iter().find(|entry| matches_operation(entry, operation)))
+        .and_then(|entry| entry.get("output_schema"))
+        .filter(|schema| !schema.is_null())
+        .cloned())
+}
+
+/// Operation names declared by a component manifest, in manifest order.
+pub fn manifest_operation_names(manifest_path: &Path) -> Result<Vec<String>> {
+    let json = read_manifest(manifest_path)?;
+    Ok(json
+        .get("operations")
+        .and_then(Value::as_array)
+        .map(|ops| {
+            ops.iter()
+                .filter_map(operation_name)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+fn read_manifest(manifest_path: &Path) -> Result<Value> {
+    let text = fs::read_to_string(manifest_path).map_err(|err| FlowError::Internal {
+        message: format!("read manifest {}: {err}", manifest_path.display()),
+        location: FlowErrorLocation::at_path(manifest_path.display().to_string()),
+    })?;
+    let mut json: Value = serde_json::from_str(&text).map_err(|err| FlowError::Internal {
+        message: format!("parse manifest {}: {err}", manifest_path.display()),
+        location: FlowErrorLocation::at_path(manifest_path.display().to_string()),
+    })?;
+    normalize_manifest_value(&mut json);
+    Ok(json)
+}
+
+/// The `operations[].schema_hash` declared for `operation`, if any.
+pub fn manifest_operation_schema_hash(
+    manifest_path: &Path,
+    operation: &str,
+) -> Result<Option<String>> {
+    let json = read_manifest(manifest_path)?;
+    Ok(json
+        .get("operations")
+        .and_then(Value::as_array)
+        .and_then(|ops| ops.iter().find(|entry| matches_operation(entry, operation)))
+        .and_then(|entry| entry.get("schema_hash"))
+        .and_then(Value::as_str)
+        .filter(|hash| !hash.is_empty())
+        .map(str::to_string))
+}
+
+/// The `operations[].defaults` declared for `operation`, if any. Defaults must be an object.
+pub fn resolve_operation_defaults(
+    manifest_path: &Path,
+    operation: &str,
+) -> Result<Option<Map<String, Value>>> {
+    let json = read_manifest(manifest_path)?;
+    let Some(defaults) = json
+        .get("operations")
+        .and_then(Value::as_array)
+        .and_then(|ops| ops.iter().find(|entry| matches_operation(entry, operation)))
+        .and_then(|entry| entry.get("defaults"))
+        .filter(|defaults| !defaults.is_null())
+    else {
+        return Ok(None);
+    };
+    match defaults {
+        Value::Object(map) => Ok(Some(map.clone())),
+        _ => Err(FlowError::Internal {
+            message: format!(
+                "operations[].defaults for '{operation}' in {} must be an object",
+                manifest_path.display()
+            ),
+            location: FlowErrorLocation::at_path(manifest_path.display().to_string()),
+        }),
+    }
+}
+
+/// Fill the fields `payload` leaves out with `defaults`, recursing into objects both sides
+/// define; values already in `payload` (including `null`) win. A `null` payload counts as an
+/// empty object. Returns the dotted path and value of each field taken from `defaults`.
+pub fn apply_operation_defaults(
+    payload: &mut Value,
+    defaults: &Map<String, Value>,
+) -> Vec<(String, Value)> {
+    if payload.is_null() {
+        *payload = Value::Object(Map::new());
+    }
+    let mut filled = Vec::new();
+    if let Value::Object(target) = payload {
+        fill_defaults(target, defaults, "", &mut filled);
+    }
+    filled
+}
+
+fn fill_defaults(
+    target: &mut Map<String, Value>,
+    defaults: &Map<String, Value>,
+    prefix: &str,
+    filled: &mut Vec<(String, Value)>,
+) {
+    for (key, default) in defaults {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match (target.get_mut(key), default) {
+            (None, _) => {
+                target.insert(key.clone(), default.clone());
+                filled.push((path, default.clone()));
+            }
+            (Some(Value::Object(existing)), Value::Object(nested)) => {
+                fill_defaults(existing, nested, &path, filled);
+            }
+            (Some(_), _) => {}
+        }
+    }
+}
+
 fn matches_operation(entry: &Value, operation: &str) -> bool {
     operation_name(entry)
         .map(|name| name == operation)
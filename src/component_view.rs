@@ -0,0 +1,358 @@
+//! Readable summary of a component's self-description, for `greentic-flow component show`.
+//!
+//! A [`ComponentView`] is built from the component's `describe` payload or its canonical
+//! descriptor, optionally extended with the setup QA spec, and rendered as indented text with
+//! schemas expanded from [`SchemaIr`].
+
+use anyhow::{Result, anyhow};
+use greentic_interfaces_host::component_v0_6::exports::greentic::component::node::{
+    ComponentDescriptor, SchemaSource, SetupOutput,
+};
+use greentic_types::cbor::canonical;
+use greentic_types::i18n_text::I18nText;
+use greentic_types::schemas::common::schema_ir::SchemaIr;
+use greentic_types::schemas::component::v0_6_0::{
+    ComponentDescribe, ComponentQaSpec, QuestionKind,
+};
+use serde::{Serialize, Serializer};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+
+use crate::schema_render::{
+    render_schema_table, schema_constraints, schema_ir_to_json_schema, schema_kind,
+};
+use crate::wizard_ops::cbor_value_to_json;
+
+/// Schema of an operation input/output, or a reference when it is not shipped inline. Inline
+/// schemas serialize as JSON Schema.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaView {
+    Inline(#[serde(serialize_with = "json_schema")] SchemaIr),
+    Reference(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OperationView {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub input: SchemaView,
+    pub output: SchemaView,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub defaults: BTreeMap<String, JsonValue>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SetupQuestionView {
+    pub id: String,
+    pub label: String,
+    /// `text`, `number`, `bool`, or `choice`.
+    pub kind: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<JsonValue>,
+}
+
+/// The setup contract: the QA questions asked when the component is set up, and what setup
+/// produces.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SetupView {
+    pub questions: Vec<SetupQuestionView>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answers_schema: Option<SchemaView>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ComponentView {
+    pub id: String,
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub provided_capabilities: Vec<String>,
+    pub required_capabilities: Vec<String>,
+    pub operations: Vec<OperationView>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "optional_json_schema"
+    )]
+    pub config_schema: Option<SchemaIr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub setup: Option<SetupView>,
+}
+
+impl ComponentView {
+    pub fn from_describe(describe: &ComponentDescribe) -> Result<Self> {
+        let operations = describe
+            .operations
+            .iter()
+            .map(|op| {
+                Ok(OperationView {
+                    name: op.id.clone(),
+                    summary: op.display_name.as_ref().map(text),
+                    input: SchemaView::Inline(op.input.schema.clone()),
+                    output: SchemaView::Inline(op.output.schema.clone()),
+                    defaults: op
+                        .defaults
+                        .iter()
+                        .map(|(key, value)| Ok((key.clone(), cbor_value_to_json(value)?)))
+                        .collect::<Result<_>>()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            id: describe.info.id.clone(),
+            version: describe.info.version.clone(),
+            role: Some(describe.info.role.clone()),
+            summary: describe.info.display_name.as_ref().map(text),
+            provided_capabilities: describe.provided_capabilities.clone(),
+            required_capabilities: describe.required_capabilities.clone(),
+            operations,
+            config_schema: Some(describe.config_schema.clone()),
+            setup: None,
+        })
+    }
+
+    /// The descriptor does not split capabilities; they are listed as provided.
+    pub fn from_descriptor(descriptor: &ComponentDescriptor) -> Result<Self> {
+        let operations = descriptor
+            .ops
+            .iter()
+            .map(|op| {
+                Ok(OperationView {
+                    name: op.name.clone(),
+                    summary: op.summary.clone(),
+                    input: schema_view(&op.input.schema)?,
+                    output: schema_view(&op.output.schema)?,
+                    defaults: BTreeMap::new(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        let setup = descriptor
+            .setup
+            .as_ref()
+            .map(|setup| -> Result<SetupView> {
+                Ok(SetupView {
+                    questions: Vec::new(),
+                    outputs: setup
+                        .outputs
+                        .iter()
+                        .map(|output| match output {
+                            SetupOutput::ConfigOnly => "config".to_string(),
+                            SetupOutput::TemplateScaffold(scaffold) => {
+                                format!("template scaffold {}", scaffold.template_ref)
+                            }
+                        })
+                        .collect(),
+                    answers_schema: Some(schema_view(&setup.answers_schema)?),
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            id: descriptor.name.clone(),
+            version: descriptor.version.clone(),
+            role: None,
+            summary: descriptor.summary.clone(),
+            provided_capabilities: descriptor.capabilities.clone(),
+            required_capabilities: Vec::new(),
+            operations,
+            config_schema: None,
+            setup,
+        })
+    }
+
+    /// Add the setup questions from the component's QA spec.
+    pub fn with_setup_spec(mut self, spec: &ComponentQaSpec) -> Result<Self> {
+        let questions = spec
+            .questions
+            .iter()
+            .map(|question| {
+                let (kind, options) = match &question.kind {
+                    QuestionKind::Text => ("text", Vec::new()),
+                    QuestionKind::Number => ("number", Vec::new()),
+                    QuestionKind::Bool => ("bool", Vec::new()),
+                    QuestionKind::Choice { options } => (
+                        "choice",
+                        options.iter().map(|option| option.value.clone()).collect(),
+                    ),
+                };
+                Ok(SetupQuestionView {
+                    id: question.id.clone(),
+                    label: text(&question.label),
+                    kind: kind.to_string(),
+                    options,
+                    required: question.required,
+                    default: question
+                        .default
+                        .as_ref()
+                        .or_else(|| spec.defaults.get(&question.id))
+                        .map(cbor_value_to_json)
+                        .transpose()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        self.setup.get_or_insert_with(SetupView::default).questions = questions;
+        Ok(self)
+    }
+
+    /// Keep only `operation`; fails listing the available operations when it is unknown.
+    pub fn select_operation(mut self, operation: &str) -> Result<Self> {
+        if !self.operations.iter().any(|op| op.name == operation) {
+            let available: Vec<&str> = self.operations.iter().map(|op| op.name.as_str()).collect();
+            return Err(anyhow!(
+                "component {} has no operation '{operation}' (available: {})",
+                self.id,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            ));
+        }
+        self.operations.retain(|op| op.name == operation);
+        Ok(self)
+    }
+
+    pub fn render(&self) -> String {
+        let mut lines = Vec::new();
+        let role = self
+            .role
+            .as_ref()
+            .map(|role| format!(" ({role})"))
+            .unwrap_or_default();
+        lines.push(format!("{} {}{role}", self.id, self.version));
+        if let Some(summary) = &self.summary {
+            lines.push(format!("  {summary}"));
+        }
+        lines.push(String::new());
+        lines.push("capabilities:".to_string());
+        lines.push(format!(
+            "  provides: {}",
+            list_or_none(&self.provided_capabilities)
+        ));
+        lines.push(format!(
+            "  requires: {}",
+            list_or_none(&self.required_capabilities)
+        ));
+        lines.push(String::new());
+        lines.push("operations:".to_string());
+        if self.operations.is_empty() {
+            lines.push("  (none)".to_string());
+        }
+        for op in &self.operations {
+            match &op.summary {
+                Some(summary) => lines.push(format!("  {} - {summary}", op.name)),
+                None => lines.push(format!("  {}", op.name)),
+            }
+            write_schema_view(&mut lines, "input: ", &op.input, 2);
+            write_schema_view(&mut lines, "output: ", &op.output, 2);
+            if !op.defaults.is_empty() {
+                lines.push("    defaults:".to_string());
+                for (key, value) in &op.defaults {
+                    lines.push(format!("      {key}: {value}"));
+                }
+            }
+        }
+        if let Some(schema) = &self.config_schema {
+            lines.push(String::new());
+            write_schema(&mut lines, "config schema: ", schema, 0);
+        }
+        if let Some(setup) = &self.setup {
+            lines.push(String::new());
+            lines.push("setup:".to_string());
+            if setup.questions.is_empty() {
+                lines.push("  no questions".to_string());
+            }
+            for question in &setup.questions {
+                let mut detail = question.kind.clone();
+                if !question.options.is_empty() {
+                    detail.push_str(&format!(": {}", question.options.join(" | ")));
+                }
+                if question.required {
+                    detail.push_str(", required");
+                }
+                if let Some(default) = &question.default {
+                    detail.push_str(&format!(", default {default}"));
+                }
+                lines.push(format!("  {} ({detail}) - {}", question.id, question.label));
+            }
+            if !setup.outputs.is_empty() {
+                lines.push(format!("  produces: {}", setup.outputs.join(", ")));
+            }
+            if let Some(schema) = &setup.answers_schema {
+                write_schema_view(&mut lines, "answers: ", schema, 1);
+            }
+        }
+        let mut out = lines.join("\n");
+        out.push('\n');
+        out
+    }
+}
+
+fn write_schema_view(lines: &mut Vec<String>, label: &str, view: &SchemaView, depth: usize) {
+    match view {
+        SchemaView::Inline(schema) => write_schema(lines, label, schema, depth),
+        SchemaView::Reference(reference) => {
+            lines.push(format!("{}{label}{reference}", "  ".repeat(depth)));
+        }
+    }
+}
+
+/// One `label kind (constraints)` line, followed by the field table for objects.
+fn write_schema(lines: &mut Vec<String>, label: &str, schema: &SchemaIr, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let constraints = schema_constraints(schema);
+    let mut head = format!("{indent}{label}{}", schema_kind(schema));
+    if !constraints.is_empty() {
+        head.push_str(&format!(" ({})", constraints.join(", ")));
+    }
+    lines.push(head);
+    if matches!(schema, SchemaIr::Object { .. }) {
+        for row in render_schema_table(schema).lines() {
+            lines.push(format!("{indent}  {row}"));
+        }
+    }
+}
+
+fn schema_view(source: &SchemaSource) -> Result<SchemaView> {
+    Ok(match source {
+        SchemaSource::InlineCbor(bytes) => SchemaView::Inline(
+            canonical::from_cbor(bytes).map_err(|err| anyhow!("decode schema cbor: {err}"))?,
+        ),
+        SchemaSource::CborSchemaId(id) => SchemaView::Reference(format!("schema id {id}")),
+        SchemaSource::RefPackPath(path) => SchemaView::Reference(format!("pack path {path}")),
+        SchemaSource::RefUri(uri) => SchemaView::Reference(uri.clone()),
+    })
+}
+
+fn json_schema<S: Serializer>(schema: &SchemaIr, serializer: S) -> Result<S::Ok, S::Error> {
+    schema_ir_to_json_schema(schema).serialize(serializer)
+}
+
+fn optional_json_schema<S: Serializer>(
+    schema: &Option<SchemaIr>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    schema
+        .as_ref()
+        .map(schema_ir_to_json_schema)
+        .serialize(serializer)
+}
+
+fn text(text: &I18nText) -> String {
+    text.fallback.clone().unwrap_or_else(|| text.key.clone())
+}
+
+fn list_or_none(items: &[String]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
@@ -1,25 +1,37 @@
 use std::path::Path;
 
+use lazy_static::lazy_static;
+use regex::Regex;
 use serde_json::{Map, Value};
 
 use crate::{
     compile_flow,
     error::{FlowError, FlowErrorLocation, Result},
     loader::load_ygtc_from_str_with_schema,
+    state_keys::{StateKeyCollision, StateKeyRegistry, record_question_fields},
     template::TemplateRenderer,
 };
 
+lazy_static! {
+    static ref PARAM_REF_RE: Regex =
+        Regex::new(r"\{\{\s*params\.([A-Za-z_]\w*(?:\.[A-Za-z_]\w*)*)\s*\}\}").unwrap();
+}
+
 /// Result of executing a config flow: a node identifier and the node object to insert.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConfigFlowOutput {
     pub node_id: String,
     pub node: Value,
+    /// Questions nodes that gave the same state key different values during the run.
+    pub state_key_collisions: Vec<StateKeyCollision>,
 }
 
 /// Execute a minimal, single-pass config-flow harness.
 ///
 /// Supported components:
-/// - `questions`: seeds state values from provided answers or defaults.
+/// - `questions`: seeds state values from provided answers or defaults. When two questions
+///   nodes give the same state key different values, the first wins and the collision is
+///   reported in [`ConfigFlowOutput::state_key_collisions`].
 /// - `template`: renders the template payload with simple Handlebars helpers and `{{state.key}}`
 ///   interpolation.
 ///
@@ -35,6 +47,7 @@ pub fn run_config_flow(
     let doc = load_ygtc_from_str_with_schema(&normalized_yaml, schema_path)?;
     let flow = compile_flow(doc.clone())?;
     let mut state = answers.clone();
+    let mut registry = StateKeyRegistry::new();
     let renderer = TemplateRenderer::new(manifest_id);
 
     let mut current = resolve_entry(&doc);
@@ -60,10 +73,15 @@ pub fn run_config_flow(
         match node.component.id.as_str() {
             "questions" => {
                 apply_questions(&node.input.mapping, &mut state)?;
+                if let Some(fields) = node.input.mapping.get("fields").and_then(Value::as_array) {
+                    record_question_fields(&mut registry, &current, fields, answers);
+                }
             }
             "template" => {
                 let payload = render_template(&node.input.mapping, &state, &renderer, &current)?;
-                return extract_config_output(payload);
+                let mut output = extract_config_output(payload)?;
+                output.state_key_collisions = registry.into_collisions();
+                return Ok(output);
             }
             other => {
                 return Err(FlowError::Internal {
@@ -111,6 +129,85 @@ pub fn run_config_flow_from_path(
     run_config_flow(&text, schema_path, answers, manifest_id)
 }
 
+/// Resolve `{{params.<path>}}` references in question defaults against the target flow's
+/// `parameters`, so packs can keep environment-specific values in one place.
+///
+/// A default that is a single reference takes the parameter's value as is (numbers stay
+/// numbers); references inside a longer string are interpolated as text. Referencing a
+/// parameter the flow does not define is an error.
+pub fn resolve_param_defaults(config_flow: &mut Value, parameters: &Value) -> Result<()> {
+    let Some(nodes) = config_flow.get_mut("nodes").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+    for (node_id, node) in nodes.iter_mut() {
+        let Some(fields) = node
+            .pointer_mut("/questions/fields")
+            .and_then(Value::as_array_mut)
+        else {
+            continue;
+        };
+        for field in fields {
+            let id = field
+                .get("id")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let Some(Value::String(default)) = field.get("default") else {
+                continue;
+            };
+            let loc = format!("nodes.{node_id}.questions.fields.{id}.default");
+            let resolved = resolve_param_refs(default, parameters, &loc)?;
+            field["default"] = resolved;
+        }
+    }
+    Ok(())
+}
+
+/// [`resolve_param_defaults`] over config flow YAML. Text without references is returned as is.
+pub fn resolve_param_defaults_in_yaml(yaml: &str, parameters: &Value) -> Result<String> {
+    if !PARAM_REF_RE.is_match(yaml) {
+        return Ok(yaml.to_string());
+    }
+    let mut value: Value = serde_yaml_bw::from_str(yaml).map_err(|e| FlowError::Yaml {
+        message: e.to_string(),
+        location: FlowErrorLocation::at_path("config_flow".to_string()),
+    })?;
+    resolve_param_defaults(&mut value, parameters)?;
+    serde_yaml_bw::to_string(&value).map_err(|e| FlowError::Internal {
+        message: format!("render config flow: {e}"),
+        location: FlowErrorLocation::at_path("config_flow".to_string()),
+    })
+}
+
+fn resolve_param_refs(text: &str, parameters: &Value, loc: &str) -> Result<Value> {
+    let lookup = |path: &str| {
+        path.split('.')
+            .try_fold(parameters, |current, part| current.get(part))
+            .ok_or_else(|| FlowError::Internal {
+                message: format!("{loc} references params.{path}, which the flow does not define"),
+                location: FlowErrorLocation::at_path(loc.to_string()),
+            })
+    };
+    if let Some(caps) = PARAM_REF_RE.captures(text)
+        && caps[0].len() == text.len()
+    {
+        return lookup(&caps[1]).cloned();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in PARAM_REF_RE.captures_iter(text) {
+        let whole = caps.get(0).expect("match");
+        out.push_str(&text[last..whole.start()]);
+        match lookup(&caps[1])? {
+            Value::String(s) => out.push_str(s),
+            other => out.push_str(&other.to_string()),
+        }
+        last = whole.end();
+    }
+    out.push_str(&text[last..]);
+    Ok(Value::String(out))
+}
+
 fn resolve_entry(doc: &crate::model::FlowDoc) -> String {
     if let Some(start) = &doc.start {
         return start.clone();
@@ -201,7 +298,11 @@ fn extract_config_output(value: Value) -> Result<ConfigFlowOutput> {
             location: FlowErrorLocation::at_path("node_id".to_string()),
         });
     }
-    Ok(ConfigFlowOutput { node_id, node })
+    Ok(ConfigFlowOutput {
+        node_id,
+        node,
+        state_key_collisions: Vec::new(),
+    })
 }
 
 fn normalize_config_flow_yaml(yaml: &str) -> Result<String> {
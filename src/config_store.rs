@@ -0,0 +1,85 @@
+use crate::error::{FlowError, FlowErrorLocation, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory next to a flow holding the canonical config CBOR produced by each node's wizard.
+///
+/// `flows/main.ygtc` stores node configs under `flows/main.ygtc.config/<node>.cbor`, alongside the
+/// `flows/main.ygtc.resolve.json` sidecar.
+pub fn config_dir_for_flow(flow_path: &Path) -> PathBuf {
+    let mut name = flow_path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    name.push(".config");
+    flow_path.with_file_name(name)
+}
+
+pub fn node_config_path(flow_path: &Path, node_id: &str) -> PathBuf {
+    config_dir_for_flow(flow_path).join(format!("{node_id}.cbor"))
+}
+
+pub fn read_node_config(flow_path: &Path, node_id: &str) -> Result<Option<Vec<u8>>> {
+    let path = node_config_path(flow_path, node_id);
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::read(&path)
+        .map(Some)
+        .map_err(|err| FlowError::Internal {
+            message: format!("read node config {}: {err}", path.display()),
+            location: FlowErrorLocation::at_path(path.display().to_string()),
+        })
+}
+
+pub fn write_node_config(flow_path: &Path, node_id: &str, config_cbor: &[u8]) -> Result<PathBuf> {
+    let path = node_config_path(flow_path, node_id);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| FlowError::Internal {
+            message: format!("create node config directory: {err}"),
+            location: FlowErrorLocation::new(None, None, None),
+        })?;
+    }
+    fs::write(&path, config_cbor).map_err(|err| FlowError::Internal {
+        message: format!("write node config {}: {err}", path.display()),
+        location: FlowErrorLocation::new(None, None, None),
+    })?;
+    Ok(path)
+}
+
+/// Remove a node's stored config, dropping the directory once it is empty.
+pub fn remove_node_config(flow_path: &Path, node_id: &str) -> Result<()> {
+    let path = node_config_path(flow_path, node_id);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|err| FlowError::Internal {
+            message: format!("remove node config {}: {err}", path.display()),
+            location: FlowErrorLocation::new(None, None, None),
+        })?;
+    }
+    let dir = config_dir_for_flow(flow_path);
+    if dir
+        .read_dir()
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(false)
+    {
+        let _ = fs::remove_dir(&dir);
+    }
+    Ok(())
+}
+
+/// Move a node's stored config to `to`, if it has one.
+pub fn rename_node_config(flow_path: &Path, from: &str, to: &str) -> Result<()> {
+    let path = node_config_path(flow_path, from);
+    if !path.exists() {
+        return Ok(());
+    }
+    let target = node_config_path(flow_path, to);
+    fs::rename(&path, &target).map_err(|err| FlowError::Internal {
+        message: format!(
+            "move node config {} to {}: {err}",
+            path.display(),
+            target.display()
+        ),
+        location: FlowErrorLocation::new(None, None, None),
+    })
+}
@@ -0,0 +1,245 @@
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_meta::ensure_greentic_meta,
+    map_flow_type,
+    model::FlowDoc,
+};
+use greentic_types::FlowKind;
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::fmt;
+
+/// How an incompatibility found by [`plan_kind_conversion`] is resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Resolution {
+    /// Turn a `reply` route into an `out` route.
+    ReplaceWithOut,
+    /// Drop the route entry.
+    RemoveRoute,
+    /// Drop the `status` field but keep the route.
+    RemoveStatus,
+    /// Drop the entrypoint.
+    RemoveEntrypoint,
+    /// Leave it as is and fix it by hand later.
+    Keep,
+}
+
+impl Resolution {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Resolution::ReplaceWithOut => "replace-with-out",
+            Resolution::RemoveRoute => "remove-route",
+            Resolution::RemoveStatus => "remove-status",
+            Resolution::RemoveEntrypoint => "remove-entrypoint",
+            Resolution::Keep => "keep",
+        }
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum IssueTarget {
+    ReplyShorthand { node_id: String },
+    Route { node_id: String, index: usize },
+    Entrypoint { name: String },
+    Node,
+}
+
+/// A node, route or entrypoint that does not fit the target kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KindIssue {
+    /// Dotted location in the flow (e.g. `nodes.ask.routing[0]`).
+    pub location: String,
+    pub message: String,
+    /// Available resolutions; the first one is the default.
+    pub resolutions: Vec<Resolution>,
+    #[serde(skip)]
+    target: IssueTarget,
+}
+
+/// Outcome of checking a flow against a new kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KindConversion {
+    pub from: String,
+    pub to: String,
+    pub issues: Vec<KindIssue>,
+}
+
+/// List the parts of `doc` that are incompatible with flow kind `to`.
+pub fn plan_kind_conversion(doc: &FlowDoc, to: &str) -> Result<KindConversion> {
+    let target = map_flow_type(to)?;
+    let replies = matches!(target, FlowKind::Messaging | FlowKind::Http);
+    let is_http = matches!(target, FlowKind::Http);
+    let questions = matches!(target, FlowKind::Messaging | FlowKind::ComponentConfig);
+
+    let mut issues = Vec::new();
+    for name in doc.entrypoints.keys() {
+        if name.starts_with("http:") && !is_http {
+            issues.push(KindIssue {
+                location: format!("entrypoints.{name}"),
+                message: format!(
+                    "entrypoint '{name}' is an HTTP route, which '{to}' flows do not serve"
+                ),
+                resolutions: vec![Resolution::RemoveEntrypoint, Resolution::Keep],
+                target: IssueTarget::Entrypoint { name: name.clone() },
+            });
+        }
+    }
+    for (node_id, node) in &doc.nodes {
+        if node.raw.contains_key("questions") && !questions {
+            issues.push(KindIssue {
+                location: format!("nodes.{node_id}"),
+                message: format!(
+                    "node '{node_id}' asks interactive questions, which '{to}' flows cannot answer"
+                ),
+                resolutions: vec![Resolution::Keep],
+                target: IssueTarget::Node,
+            });
+        }
+        match &node.routing {
+            Value::String(shorthand) if shorthand == "reply" && !replies => {
+                issues.push(KindIssue {
+                    location: format!("nodes.{node_id}.routing"),
+                    message: format!(
+                        "node '{node_id}' replies, but '{to}' flows have no caller to reply to"
+                    ),
+                    resolutions: vec![Resolution::ReplaceWithOut, Resolution::RemoveRoute],
+                    target: IssueTarget::ReplyShorthand {
+                        node_id: node_id.clone(),
+                    },
+                });
+            }
+            Value::Array(routes) => {
+                for (index, route) in routes.iter().enumerate() {
+                    let location = format!("nodes.{node_id}.routing[{index}]");
+                    let target = IssueTarget::Route {
+                        node_id: node_id.clone(),
+                        index,
+                    };
+                    if route.get("reply") == Some(&Value::Bool(true)) && !replies {
+                        issues.push(KindIssue {
+                            location,
+                            message: format!(
+                                "node '{node_id}' replies, but '{to}' flows have no caller to reply to"
+                            ),
+                            resolutions: vec![Resolution::ReplaceWithOut, Resolution::RemoveRoute],
+                            target,
+                        });
+                    } else if route.get("status").is_some() && !is_http {
+                        issues.push(KindIssue {
+                            location,
+                            message: format!(
+                                "node '{node_id}' sets an HTTP status, which only 'http' flows return"
+                            ),
+                            resolutions: vec![Resolution::RemoveStatus, Resolution::RemoveRoute],
+                            target,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(KindConversion {
+        from: doc.flow_type.clone(),
+        to: to.to_string(),
+        issues,
+    })
+}
+
+/// Convert `doc` to the target kind, applying one resolution per issue (in issue order).
+///
+/// The source kind's `meta.<kind>` block is moved to `meta.greentic.converted_from` so that
+/// kind-specific settings are not applied to the new kind but remain recoverable.
+pub fn apply_kind_conversion(
+    doc: &mut FlowDoc,
+    conversion: &KindConversion,
+    resolutions: &[Resolution],
+) -> Result<()> {
+    if resolutions.len() != conversion.issues.len() {
+        return Err(FlowError::Internal {
+            message: format!(
+                "expected {} resolution(s), got {}",
+                conversion.issues.len(),
+                resolutions.len()
+            ),
+            location: FlowErrorLocation::at_path("type"),
+        });
+    }
+    let mut dropped_routes: Vec<(String, usize)> = Vec::new();
+    for (issue, resolution) in conversion.issues.iter().zip(resolutions) {
+        if !issue.resolutions.contains(resolution) {
+            return Err(FlowError::Internal {
+                message: format!(
+                    "resolution '{resolution}' does not apply to {}",
+                    issue.location
+                ),
+                location: FlowErrorLocation::at_path(issue.location.clone()),
+            });
+        }
+        match (&issue.target, resolution) {
+            (_, Resolution::Keep) | (IssueTarget::Node, _) => {}
+            (IssueTarget::Entrypoint { name }, _) => {
+                doc.entrypoints.shift_remove(name);
+            }
+            (IssueTarget::ReplyShorthand { node_id }, resolution) => {
+                if let Some(node) = doc.nodes.get_mut(node_id) {
+                    node.routing = match resolution {
+                        Resolution::ReplaceWithOut => Value::String("out".to_string()),
+                        _ => Value::Array(Vec::new()),
+                    };
+                }
+            }
+            (IssueTarget::Route { node_id, index }, resolution) => {
+                let route = doc
+                    .nodes
+                    .get_mut(node_id)
+                    .and_then(|node| node.routing.get_mut(*index))
+                    .and_then(Value::as_object_mut);
+                let Some(route) = route else {
+                    continue;
+                };
+                match resolution {
+                    Resolution::ReplaceWithOut => {
+                        route.remove("reply");
+                        route.insert("out".to_string(), Value::Bool(true));
+                    }
+                    Resolution::RemoveStatus => {
+                        route.remove("status");
+                    }
+                    _ => dropped_routes.push((node_id.clone(), *index)),
+                }
+            }
+        }
+    }
+    dropped_routes.sort_by(|a, b| b.1.cmp(&a.1));
+    for (node_id, index) in dropped_routes {
+        if let Some(routes) = doc
+            .nodes
+            .get_mut(&node_id)
+            .and_then(|node| node.routing.as_array_mut())
+        {
+            routes.remove(index);
+        }
+    }
+
+    let previous = doc
+        .meta
+        .as_mut()
+        .and_then(Value::as_object_mut)
+        .and_then(|meta| meta.remove(&conversion.from));
+    let mut record = Map::new();
+    record.insert("kind".to_string(), Value::String(conversion.from.clone()));
+    if let Some(previous) = previous {
+        record.insert("meta".to_string(), previous);
+    }
+    ensure_greentic_meta(&mut doc.meta).insert("converted_from".to_string(), json!(record));
+    doc.flow_type = conversion.to.clone();
+    Ok(())
+}
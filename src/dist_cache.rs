@@ -0,0 +1,248 @@
+//! Inspection of the distributor's component cache.
+//!
+//! The distributor client stores each fetched component in `<root>/<hex>/`, where `<hex>` is
+//! the digest it resolved: `component.wasm` (or the file `component.manifest.json` names under
+//! `artifacts.component_wasm`), an optional manifest, a `last_used` marker, and for OCI pulls a
+//! `metadata.json`. OCI entries are keyed by the image manifest digest rather than the wasm
+//! digest, so their bytes cannot be checked against the directory name.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    resolve_summary::compute_sha256,
+};
+
+const DEFAULT_WASM_FILE: &str = "component.wasm";
+const MANIFEST_FILE: &str = "component.manifest.json";
+const METADATA_FILE: &str = "metadata.json";
+const LAST_USED_FILE: &str = "last_used";
+
+/// One digest directory in the cache.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DistCacheEntry {
+    pub digest: String,
+    pub dir: PathBuf,
+    /// The component wasm, when present.
+    pub wasm: Option<PathBuf>,
+    pub manifest: Option<PathBuf>,
+    /// Component id from the manifest, when it has one.
+    pub component_id: Option<String>,
+    /// Total size of the files in the entry.
+    pub bytes: u64,
+    /// Seconds since the Unix epoch of the last use the distributor recorded.
+    pub last_used: Option<u64>,
+    /// Whether the digest names an OCI image manifest rather than the wasm itself.
+    #[serde(skip)]
+    keyed_by_oci_manifest: bool,
+}
+
+/// Result of checking an entry's contents against its digest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum EntryStatus {
+    Ok,
+    /// The wasm bytes hash to `actual`, not to the entry's digest.
+    Corrupt {
+        actual: String,
+    },
+    /// Manifest or metadata files with no wasm next to them.
+    Orphaned,
+    /// OCI entry keyed by the image manifest digest; only its presence was checked.
+    Unverified,
+}
+
+impl EntryStatus {
+    /// Corrupt and orphaned entries; `cache verify --prune` removes these.
+    pub fn is_problem(&self) -> bool {
+        matches!(self, Self::Corrupt { .. } | Self::Orphaned)
+    }
+}
+
+/// An entry together with the outcome of verifying it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct VerifiedEntry {
+    #[serde(flatten)]
+    pub entry: DistCacheEntry,
+    #[serde(flatten)]
+    pub status: EntryStatus,
+}
+
+/// The distributor cache rooted at a directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistCache {
+    root: PathBuf,
+}
+
+impl DistCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Every digest directory in the cache, sorted by digest.
+    pub fn entries(&self) -> Result<Vec<DistCacheEntry>> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.root).map_err(|err| io_error(&self.root, err))? {
+            let dir_entry = dir_entry.map_err(|err| io_error(&self.root, err))?;
+            let name = dir_entry.file_name().to_string_lossy().into_owned();
+            if !dir_entry.path().is_dir() || !is_hex_digest(&name) {
+                continue;
+            }
+            entries.push(read_entry(&dir_entry.path(), &name));
+        }
+        entries.sort_by(|a, b| a.digest.cmp(&b.digest));
+        Ok(entries)
+    }
+
+    /// The entry named by a full digest (`sha256:<hex>` or `<hex>`) or an unambiguous hex prefix.
+    pub fn find(&self, digest: &str) -> Result<DistCacheEntry> {
+        let wanted = digest.strip_prefix("sha256:").unwrap_or(digest);
+        let wanted = wanted.to_ascii_lowercase();
+        if wanted.is_empty() || !wanted.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(cache_error(format!("invalid digest '{digest}'")));
+        }
+        let mut matches: Vec<_> = self
+            .entries()?
+            .into_iter()
+            .filter(|entry| entry.digest["sha256:".len()..].starts_with(&wanted))
+            .collect();
+        match matches.len() {
+            0 => Err(cache_error(format!(
+                "no cache entry matches '{digest}' in {}",
+                self.root.display()
+            ))),
+            1 => Ok(matches.remove(0)),
+            n => Err(cache_error(format!(
+                "'{digest}' matches {n} cache entries; use a longer prefix"
+            ))),
+        }
+    }
+
+    /// Check an entry's wasm against its digest.
+    pub fn check(&self, entry: &DistCacheEntry) -> Result<EntryStatus> {
+        let Some(wasm) = &entry.wasm else {
+            return Ok(EntryStatus::Orphaned);
+        };
+        if entry.keyed_by_oci_manifest {
+            return Ok(EntryStatus::Unverified);
+        }
+        let actual = compute_sha256(wasm).map_err(|err| cache_error(format!("{err:#}")))?;
+        if actual == entry.digest {
+            Ok(EntryStatus::Ok)
+        } else {
+            Ok(EntryStatus::Corrupt { actual })
+        }
+    }
+
+    /// Check every entry in the cache.
+    pub fn verify(&self) -> Result<Vec<VerifiedEntry>> {
+        self.entries()?
+            .into_iter()
+            .map(|entry| {
+                let status = self.check(&entry)?;
+                Ok(VerifiedEntry { entry, status })
+            })
+            .collect()
+    }
+
+    /// Delete an entry's directory.
+    pub fn remove(&self, entry: &DistCacheEntry) -> Result<()> {
+        fs::remove_dir_all(&entry.dir).map_err(|err| io_error(&entry.dir, err))
+    }
+}
+
+fn read_entry(dir: &Path, hex: &str) -> DistCacheEntry {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let manifest: Option<Value> = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok());
+    let wasm_name = manifest
+        .as_ref()
+        .and_then(|json| json.pointer("/artifacts/component_wasm"))
+        .and_then(Value::as_str)
+        .filter(|name| stays_in_entry(name) && dir.join(name).is_file())
+        .unwrap_or(DEFAULT_WASM_FILE);
+    let wasm = Some(dir.join(wasm_name)).filter(|path| path.is_file());
+    let digest = format!("sha256:{}", hex.to_ascii_lowercase());
+    let keyed_by_oci_manifest = fs::read_to_string(dir.join(METADATA_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+        .and_then(|json| {
+            json.get("manifest_digest")
+                .and_then(Value::as_str)
+                .map(|manifest_digest| manifest_digest == digest)
+        })
+        .unwrap_or(false);
+    let bytes = fs::read_dir(dir)
+        .map(|files| {
+            files
+                .flatten()
+                .filter_map(|file| file.metadata().ok())
+                .filter(|meta| meta.is_file())
+                .map(|meta| meta.len())
+                .sum()
+        })
+        .unwrap_or(0);
+    let last_used = [dir.join(LAST_USED_FILE)]
+        .into_iter()
+        .chain(wasm.clone())
+        .find_map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+        .map(unix_seconds);
+    DistCacheEntry {
+        digest,
+        dir: dir.to_path_buf(),
+        wasm,
+        manifest: manifest_path.is_file().then_some(manifest_path),
+        component_id: manifest
+            .as_ref()
+            .and_then(|json| json.get("id"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        bytes,
+        last_used,
+        keyed_by_oci_manifest,
+    }
+}
+
+/// Whether a file name from the manifest stays inside the entry directory: no absolute paths
+/// or `..` components, so a crafted manifest cannot make `verify` hash (or `info` report) files
+/// elsewhere on disk.
+fn stays_in_entry(name: &str) -> bool {
+    Path::new(name)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+fn is_hex_digest(name: &str) -> bool {
+    name.len() == 64 && name.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> FlowError {
+    cache_error(format!("{}: {err}", path.display()))
+}
+
+fn cache_error(message: String) -> FlowError {
+    FlowError::Internal {
+        message,
+        location: FlowErrorLocation::at_path("dist-cache"),
+    }
+}
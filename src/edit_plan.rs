@@ -0,0 +1,362 @@
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::{
+    add_step::{AddStepSpec, apply_plan, plan_add_step, validate::validate_schema_and_flow},
+    component_catalog::ComponentCatalog,
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_edit::missing_node,
+    flow_ir::{FlowIr, Route},
+    splice::NEXT_NODE_PLACEHOLDER,
+};
+
+pub use crate::flow_edit::{DeleteStrategy, MultiplePredecessors};
+
+/// Declarative list of flow edits applied in order against a single in-memory [`FlowIr`].
+///
+/// ```yaml
+/// flow: flows/main.ygtc
+/// operations:
+///   - op: add-step
+///     node_id: greet
+///     operation: handle_message
+///     payload: { text: "hi" }
+///     component: oci://ghcr.io/acme/greet:1.0.0
+///   - op: wire
+///     from: greet
+///     routing: out
+///   - op: patch
+///     node: greet
+///     payload: { text: "hello" }
+///   - op: delete
+///     node: legacy
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct FlowEditPlan {
+    /// Optional flow path (relative to the plan file) used when the CLI is not given `--flow`.
+    #[serde(default)]
+    pub flow: Option<String>,
+    pub operations: Vec<PlanOperation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum PlanOperation {
+    /// Insert a node after an anchor, threading the anchor's routing when no routing is given.
+    /// With `before`, insert in front of that node instead and redirect its predecessors.
+    AddStep {
+        #[serde(default)]
+        after: Option<String>,
+        #[serde(default)]
+        before: Option<String>,
+        #[serde(default)]
+        node_id: Option<String>,
+        operation: String,
+        #[serde(default = "empty_object")]
+        payload: Value,
+        #[serde(default)]
+        routing: Option<Value>,
+        #[serde(default)]
+        allow_cycles: bool,
+        /// Component reference to bind the new node to in the resolve sidecar.
+        #[serde(default)]
+        component: Option<String>,
+        /// Local wasm to bind the new node to, relative to the plan file.
+        #[serde(default)]
+        local_wasm: Option<String>,
+    },
+    /// Replace (or append to) the routing of an existing node.
+    Wire {
+        from: String,
+        #[serde(default)]
+        to: Option<String>,
+        #[serde(default)]
+        routing: Option<Value>,
+        #[serde(default)]
+        append: bool,
+    },
+    /// Apply a JSON merge patch to a node payload, optionally renaming its operation.
+    #[serde(alias = "update-step", alias = "update")]
+    Patch {
+        node: String,
+        #[serde(default)]
+        payload: Option<Value>,
+        #[serde(default)]
+        operation: Option<String>,
+    },
+    /// Remove a node, splicing its routing into predecessors by default.
+    #[serde(alias = "delete-step")]
+    Delete {
+        node: String,
+        #[serde(default)]
+        strategy: DeleteStrategy,
+        #[serde(default)]
+        if_multiple_predecessors: MultiplePredecessors,
+    },
+}
+
+/// Summary of one applied plan operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanStepReport {
+    pub index: usize,
+    pub op: &'static str,
+    pub node_id: String,
+}
+
+/// Sidecar binding requested by an `add-step` operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanBinding {
+    Component(String),
+    LocalWasm(String),
+}
+
+/// Outcome of applying a full plan.
+#[derive(Debug, Clone)]
+pub struct PlanOutcome {
+    pub flow: FlowIr,
+    pub steps: Vec<PlanStepReport>,
+    /// Node ids removed by `delete` operations (for sidecar cleanup).
+    pub removed: Vec<String>,
+    /// Bindings of nodes added by the plan and still present at the end, in plan order.
+    pub bindings: Vec<(String, PlanBinding)>,
+}
+
+fn empty_object() -> Value {
+    Value::Object(Map::new())
+}
+
+impl FlowEditPlan {
+    /// Parse a plan from YAML (JSON plans are accepted as YAML).
+    pub fn from_yaml_str(text: &str) -> Result<Self> {
+        serde_yaml_bw::from_str(text).map_err(|e| FlowError::Yaml {
+            message: format!("edit plan: {e}"),
+            location: FlowErrorLocation::at_path("plan"),
+        })
+    }
+}
+
+impl PlanOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlanOperation::AddStep { .. } => "add-step",
+            PlanOperation::Wire { .. } => "wire",
+            PlanOperation::Patch { .. } => "patch",
+            PlanOperation::Delete { .. } => "delete",
+        }
+    }
+}
+
+/// Apply every plan operation in order, validating the flow after each one.
+///
+/// The input flow is never mutated; on the first failing operation the whole plan is rejected
+/// so callers can write the result exactly once.
+pub fn apply_edit_plan(
+    flow: &FlowIr,
+    plan: &FlowEditPlan,
+    catalog: &dyn ComponentCatalog,
+) -> Result<PlanOutcome> {
+    let mut current = flow.clone();
+    let mut steps = Vec::new();
+    let mut removed = Vec::new();
+    let mut bindings = Vec::new();
+    for (index, op) in plan.operations.iter().enumerate() {
+        let node_id = apply_operation(&mut current, op, catalog)
+            .and_then(|node_id| {
+                validate_schema_and_flow(&current, catalog)?;
+                Ok(node_id)
+            })
+            .map_err(|err| FlowError::Internal {
+                message: format!("operation #{index} ({}) failed: {err}", op.name()),
+                location: FlowErrorLocation::at_path(format!("plan.operations[{index}]")),
+            })?;
+        match op {
+            PlanOperation::Delete { .. } => {
+                bindings.retain(|(bound, _)| bound != &node_id);
+                removed.push(node_id.clone());
+            }
+            PlanOperation::AddStep {
+                component,
+                local_wasm,
+                ..
+            } => {
+                let binding = match (component, local_wasm) {
+                    (Some(_), Some(_)) => {
+                        return Err(FlowError::Internal {
+                            message: format!(
+                                "operation #{index} (add-step) sets both component and local_wasm"
+                            ),
+                            location: FlowErrorLocation::at_path(format!(
+                                "plan.operations[{index}]"
+                            )),
+                        });
+                    }
+                    (Some(reference), None) => Some(PlanBinding::Component(reference.clone())),
+                    (None, Some(path)) => Some(PlanBinding::LocalWasm(path.clone())),
+                    (None, None) => None,
+                };
+                if let Some(binding) = binding {
+                    bindings.push((node_id.clone(), binding));
+                }
+            }
+            _ => {}
+        }
+        steps.push(PlanStepReport {
+            index,
+            op: op.name(),
+            node_id,
+        });
+    }
+    Ok(PlanOutcome {
+        flow: current,
+        steps,
+        removed,
+        bindings,
+    })
+}
+
+fn apply_operation(
+    flow: &mut FlowIr,
+    op: &PlanOperation,
+    catalog: &dyn ComponentCatalog,
+) -> Result<String> {
+    match op {
+        PlanOperation::AddStep {
+            after,
+            before,
+            node_id,
+            operation,
+            payload,
+            routing,
+            allow_cycles,
+            ..
+        } => {
+            let mut node = Map::new();
+            node.insert(operation.clone(), payload.clone());
+            let require_placeholder = routing.is_none();
+            node.insert(
+                "routing".to_string(),
+                routing
+                    .clone()
+                    .unwrap_or_else(|| json!([{ "to": NEXT_NODE_PLACEHOLDER }])),
+            );
+            let spec = AddStepSpec {
+                after: after.clone(),
+                before: before.clone(),
+                node_id_hint: node_id.clone(),
+                node: Value::Object(node),
+                allow_cycles: *allow_cycles,
+                require_placeholder,
+                default_routing: None,
+            };
+            let plan = plan_add_step(flow, spec, catalog).map_err(|diags| FlowError::Internal {
+                message: diags
+                    .into_iter()
+                    .map(|d| format!("{}: {}", d.code, d.message))
+                    .collect::<Vec<_>>()
+                    .join("; "),
+                location: FlowErrorLocation::at_path("add_step"),
+            })?;
+            let inserted = plan.new_node.id.clone();
+            *flow = apply_plan(flow, plan, *allow_cycles)?;
+            Ok(inserted)
+        }
+        PlanOperation::Wire {
+            from,
+            to,
+            routing,
+            append,
+        } => {
+            let routes = match (to, routing) {
+                (Some(_), Some(_)) => {
+                    return Err(FlowError::Internal {
+                        message: "wire accepts either 'to' or 'routing', not both".to_string(),
+                        location: FlowErrorLocation::at_path(format!("nodes.{from}.routing")),
+                    });
+                }
+                (Some(target), None) => vec![Route {
+                    to: Some(target.clone()),
+                    ..Route::default()
+                }],
+                (None, Some(value)) => parse_routing_value(value, from)?,
+                (None, None) => {
+                    return Err(FlowError::Internal {
+                        message: "wire requires 'to' or 'routing'".to_string(),
+                        location: FlowErrorLocation::at_path(format!("nodes.{from}.routing")),
+                    });
+                }
+            };
+            if *append {
+                for route in routes {
+                    flow.add_route(from, route)?;
+                }
+            } else {
+                flow.set_routing(from, routes)?;
+            }
+            Ok(from.clone())
+        }
+        PlanOperation::Patch {
+            node: node_id,
+            payload,
+            operation,
+        } => {
+            // Only the payload and operation change; routing (and so the predecessor index)
+            // is left alone.
+            let node = flow
+                .nodes
+                .get_mut(node_id)
+                .ok_or_else(|| missing_node(node_id))?;
+            if let Some(patch) = payload {
+                merge_patch(&mut node.payload, patch);
+            }
+            if let Some(op) = operation {
+                node.operation = op.clone();
+            }
+            Ok(node_id.clone())
+        }
+        PlanOperation::Delete {
+            node,
+            strategy,
+            if_multiple_predecessors,
+        } => {
+            flow.remove_node(node, *strategy, if_multiple_predecessors.clone())?;
+            Ok(node.clone())
+        }
+    }
+}
+
+fn parse_routing_value(value: &Value, node_id: &str) -> Result<Vec<Route>> {
+    match value {
+        Value::String(s) if s == "out" => Ok(vec![Route {
+            out: true,
+            ..Route::default()
+        }]),
+        Value::String(s) if s == "reply" => Ok(vec![Route {
+            reply: true,
+            ..Route::default()
+        }]),
+        other => serde_json::from_value(other.clone()).map_err(|e| FlowError::Routing {
+            node_id: node_id.to_string(),
+            message: e.to_string(),
+            location: FlowErrorLocation::at_path(format!("nodes.{node_id}.routing")),
+        }),
+    }
+}
+
+/// RFC 7396 JSON merge patch: objects merge recursively, `null` removes a key.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch_map) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let target_map = target.as_object_mut().expect("object target");
+    for (key, value) in patch_map {
+        if value.is_null() {
+            target_map.remove(key);
+        } else {
+            merge_patch(target_map.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
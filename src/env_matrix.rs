@@ -0,0 +1,212 @@
+//! Per-environment parameter sets and the flows they render.
+//!
+//! A flow's `parameters` hold its defaults; each environment overrides some of them in its own
+//! file (`envs/dev.yaml`, `envs/prod.yaml`):
+//!
+//! ```yaml
+//! # envs/prod.yaml
+//! base_url: https://api.example.com
+//! http:
+//!   timeout_ms: 2000
+//! ```
+//!
+//! Each set is checked against the flow's parameter schema: `meta.parameters_schema` when the
+//! flow declares one, otherwise a schema inferred from the defaults (same JSON types, no keys
+//! the flow does not define). The merged parameters then replace every `parameters.*` reference
+//! in the node payloads, giving the fully-resolved flow for that environment.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    component_schema::jsonschema_options_with_base, model::FlowDoc, model::reserved_node_keys,
+    resolve::resolve_parameters,
+};
+
+/// Flow `meta` key holding an explicit JSON Schema for the parameters.
+pub const PARAMETERS_SCHEMA_META_KEY: &str = "parameters_schema";
+
+/// Default directory, next to the flow, holding one parameter file per environment.
+pub const DEFAULT_ENVS_DIR: &str = "envs";
+
+/// One environment's parameter file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvParameters {
+    /// File stem, e.g. `prod` for `envs/prod.yaml`.
+    pub name: String,
+    pub path: PathBuf,
+    pub values: Value,
+}
+
+impl EnvParameters {
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .with_context(|| format!("{} has no usable file name", path.display()))?
+            .to_string();
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        let values: Value = if text.trim().is_empty() {
+            Value::Object(Map::new())
+        } else {
+            serde_yaml_bw::from_str(&text)
+                .with_context(|| format!("parse parameters in {}", path.display()))?
+        };
+        Ok(Self {
+            name,
+            path: path.to_path_buf(),
+            values,
+        })
+    }
+}
+
+/// The `.yaml`/`.yml` files directly under `dir`, sorted by name.
+pub fn env_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        let is_yaml = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| matches!(ext, "yaml" | "yml"));
+        if is_yaml && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The schema environment parameters must satisfy: `meta.parameters_schema`, or one inferred
+/// from the flow's `parameters`.
+pub fn parameter_schema(doc: &FlowDoc) -> Value {
+    doc.meta
+        .as_ref()
+        .and_then(|meta| meta.get(PARAMETERS_SCHEMA_META_KEY))
+        .cloned()
+        .unwrap_or_else(|| infer_schema(&doc.parameters))
+}
+
+fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => json!({
+            "type": "object",
+            "properties": map
+                .iter()
+                .map(|(key, value)| (key.clone(), infer_schema(value)))
+                .collect::<Map<_, _>>(),
+            "additionalProperties": false,
+        }),
+        Value::String(_) => json!({"type": "string"}),
+        Value::Bool(_) => json!({"type": "boolean"}),
+        Value::Number(number) if number.is_f64() => json!({"type": "number"}),
+        Value::Number(_) => json!({"type": "integer"}),
+        Value::Array(_) => json!({"type": "array"}),
+        Value::Null => json!({}),
+    }
+}
+
+/// `overrides` laid over `defaults`; objects merge key by key, anything else is replaced.
+pub fn merge_parameters(defaults: &Value, overrides: &Value) -> Value {
+    match (defaults, overrides) {
+        (Value::Object(base), Value::Object(over)) => {
+            let mut merged = base.clone();
+            for (key, value) in over {
+                let value = match base.get(key) {
+                    Some(existing) => merge_parameters(existing, value),
+                    None => value.clone(),
+                };
+                merged.insert(key.clone(), value);
+            }
+            Value::Object(merged)
+        }
+        (_, over) => over.clone(),
+    }
+}
+
+/// A problem with one environment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct EnvIssue {
+    /// JSON pointer into the environment's parameters, or `/nodes/<id>` for a payload that
+    /// failed to resolve.
+    pub pointer: String,
+    pub message: String,
+}
+
+/// One environment's column of the matrix.
+#[derive(Clone, Debug)]
+pub struct EnvRender {
+    pub env: String,
+    pub path: PathBuf,
+    /// Flow defaults merged with the environment's values.
+    pub parameters: Value,
+    /// The resolved flow; `None` when the environment has issues.
+    pub flow: Option<FlowDoc>,
+    pub issues: Vec<EnvIssue>,
+}
+
+impl EnvRender {
+    pub fn ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Validate `env` against the flow's parameter schema and resolve the flow's payloads with it.
+pub fn render_env(doc: &FlowDoc, env: &EnvParameters) -> Result<EnvRender> {
+    let schema = parameter_schema(doc);
+    let validator = jsonschema_options_with_base(None)
+        .build(&schema)
+        .map_err(|err| anyhow::anyhow!("compile parameter schema of flow '{}': {err}", doc.id))?;
+    let mut issues: Vec<EnvIssue> = validator
+        .iter_errors(&env.values)
+        .map(|err| {
+            let pointer = err.instance_path().to_string();
+            EnvIssue {
+                pointer: if pointer.is_empty() {
+                    "/".to_string()
+                } else {
+                    pointer
+                },
+                message: err.to_string(),
+            }
+        })
+        .collect();
+    let parameters = merge_parameters(&doc.parameters, &env.values);
+
+    let mut flow = doc.clone();
+    flow.parameters = parameters.clone();
+    if issues.is_empty() {
+        let reserved = reserved_node_keys();
+        for (node_id, node) in flow.nodes.iter_mut() {
+            for (key, value) in node.raw.iter_mut() {
+                if reserved.iter().any(|r| r == key) {
+                    continue;
+                }
+                match resolve_parameters(value, &parameters, &format!("nodes.{node_id}.{key}")) {
+                    Ok(resolved) => *value = resolved,
+                    Err(err) => issues.push(EnvIssue {
+                        pointer: format!("/nodes/{node_id}"),
+                        message: err.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+    Ok(EnvRender {
+        env: env.name.clone(),
+        path: env.path.clone(),
+        parameters,
+        flow: issues.is_empty().then_some(flow),
+        issues,
+    })
+}
+
+/// [`render_env`] for every environment, in order.
+pub fn render_matrix(doc: &FlowDoc, envs: &[EnvParameters]) -> Result<Vec<EnvRender>> {
+    envs.iter().map(|env| render_env(doc, env)).collect()
+}
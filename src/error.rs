@@ -1,3 +1,4 @@
+use crate::source_map::SourceSpan;
 use std::{fmt, path::PathBuf};
 use thiserror::Error;
 
@@ -43,6 +44,15 @@ impl FlowErrorLocation {
         self
     }
 
+    /// Set `line`/`col` from a source position, when one is known.
+    pub fn with_span(mut self, span: Option<SourceSpan>) -> Self {
+        if let Some(span) = span {
+            self.line = Some(span.line);
+            self.col = Some(span.col);
+        }
+        self
+    }
+
     pub fn describe(&self) -> Option<String> {
         if self.path.is_none() && self.line.is_none() && self.col.is_none() {
             return None;
@@ -130,10 +140,22 @@ pub enum FlowError {
         location: FlowErrorLocation,
     },
     #[error(
-        "Node '{node_id}' must contain exactly one component key like 'qa.process' plus optional 'routing'{location}"
+        "Node '{node_id}' must contain exactly one component key like 'qa.process' plus optional 'routing'{location}; found {}",
+        describe_keys(.keys)
     )]
     NodeComponentShape {
         node_id: String,
+        /// Non-reserved keys present on the node.
+        keys: Vec<String>,
+        location: FlowErrorLocation,
+    },
+    #[error(
+        "Node '{node_id}' uses reserved key '{key}' as its operation name{location}; reserved node keys: {reserved}"
+    )]
+    ReservedOperationKey {
+        node_id: String,
+        key: String,
+        reserved: String,
         location: FlowErrorLocation,
     },
     #[error(
@@ -156,6 +178,12 @@ pub enum FlowError {
         node_id: String,
         location: FlowErrorLocation,
     },
+    #[error("Flow needs capabilities the target runtime does not provide{location}:\n{message}")]
+    RuntimeIncompatible {
+        message: String,
+        details: Vec<SchemaErrorDetail>,
+        location: FlowErrorLocation,
+    },
     #[error("Internal error{location}: {message}")]
     Internal {
         message: String,
@@ -163,5 +191,16 @@ pub enum FlowError {
     },
 }
 
+fn describe_keys(keys: &[String]) -> String {
+    if keys.is_empty() {
+        "no component key".to_string()
+    } else {
+        keys.iter()
+            .map(|k| format!("'{k}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
 #[allow(clippy::result_large_err)]
 pub type Result<T> = std::result::Result<T, FlowError>;
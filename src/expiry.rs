@@ -0,0 +1,104 @@
+//! Sunset dates for temporary nodes.
+//!
+//! A node meant to live for a limited time (a campaign step, a migration shim) carries its last
+//! day under `meta.expires`:
+//!
+//! ```yaml
+//! nodes:
+//!   spring_promo:
+//!     template: "20% off until Sunday"
+//!     meta:
+//!       expires: 2026-01-01
+//!     routing:
+//!       - to: checkout
+//! ```
+//!
+//! The `node_expiry` lint rule warns in the weeks before that date and fails once it has passed;
+//! [`prune_expired`] splices expired nodes out of the flow.
+
+use crate::{
+    error::Result,
+    flow_edit::splice_out_of_doc,
+    model::{FlowDoc, NodeDoc},
+};
+
+/// Key under a node's `meta` holding its last day, as `YYYY-MM-DD`.
+pub const NODE_EXPIRES_KEY: &str = "expires";
+
+/// How many days before its expiry date a node starts drawing a warning.
+pub const DEFAULT_EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// A node's `meta.expires` date, or why it is not a valid date; `None` when it has none.
+pub fn node_expiry(node: &NodeDoc) -> Option<std::result::Result<String, String>> {
+    let value = node.raw.get("meta")?.get(NODE_EXPIRES_KEY)?;
+    Some(
+        value
+            .as_str()
+            .filter(|date| days_from_civil(date).is_some())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                format!("meta.{NODE_EXPIRES_KEY} must be a YYYY-MM-DD date, got {value}")
+            }),
+    )
+}
+
+/// Days from `today` until `date` (both `YYYY-MM-DD`); negative once `date` has passed.
+pub fn days_until(today: &str, date: &str) -> Option<i64> {
+    Some(days_from_civil(date)? - days_from_civil(today)?)
+}
+
+/// Nodes whose `meta.expires` date is before `today`, in flow order.
+pub fn expired_nodes(doc: &FlowDoc, today: &str) -> Vec<String> {
+    doc.nodes
+        .iter()
+        .filter(|(_, node)| match node_expiry(node) {
+            Some(Ok(date)) => days_until(today, &date).is_some_and(|days| days < 0),
+            _ => false,
+        })
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// Splice every node that expired before `today` out of `doc`, returning the new document and
+/// the removed node ids. Other nodes keep their keys as written.
+pub fn prune_expired(doc: &FlowDoc, today: &str) -> Result<(FlowDoc, Vec<String>)> {
+    let expired = expired_nodes(doc, today);
+    if expired.is_empty() {
+        return Ok((doc.clone(), expired));
+    }
+    Ok((splice_out_of_doc(doc, &expired)?, expired))
+}
+
+/// Days since 1970-01-01 for a valid `YYYY-MM-DD` date (Howard Hinnant's days-from-civil).
+fn days_from_civil(date: &str) -> Option<i64> {
+    let bytes = date.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let text = &date[range];
+        text.bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| text.parse().ok())
+            .flatten()
+    };
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    if !(1..=month_days).contains(&day) {
+        return None;
+    }
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe - 719_468)
+}
@@ -0,0 +1,140 @@
+//! Feature-flag gated nodes.
+//!
+//! A node with `feature: <flag>` only takes part in the flow when that flag is enabled. Flags are
+//! declared in `meta.features`, either as a list of names or as a mapping from name to a
+//! description:
+//!
+//! ```yaml
+//! meta:
+//!   features: [beta_pricing]
+//! nodes:
+//!   quote:
+//!     pricing.quote: {}
+//!     feature: beta_pricing
+//!     routing:
+//!       - to: charge
+//! ```
+//!
+//! [`render_features`] produces the effective flow for a set of enabled flags: nodes whose flag
+//! is off are removed the way `delete-step` removes them, with their predecessors routed on to
+//! their successors.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{error::Result, flow_ir::FlowIr};
+
+/// Node key naming the flag that guards the node.
+pub const FEATURE_NODE_KEY: &str = "feature";
+
+/// Flow `meta` key declaring the known flags.
+pub const FEATURES_META_KEY: &str = "features";
+
+/// Flag names: letters, digits, `_`, `-` and `.`, not starting with `-` or `.`.
+pub fn is_valid_feature_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphanumeric() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Parse a node's `feature` value.
+pub fn parse_feature(value: &Value) -> std::result::Result<String, String> {
+    match value.as_str() {
+        Some(name) if is_valid_feature_name(name) => Ok(name.to_string()),
+        Some(name) => Err(format!(
+            "invalid feature flag '{name}': use letters, digits, '_', '-' and '.'"
+        )),
+        None => Err("feature must be a flag name".to_string()),
+    }
+}
+
+/// The flags declared under `meta.features`, or `None` when the flow declares none.
+pub fn declared_features(meta: Option<&Value>) -> std::result::Result<Option<Vec<String>>, String> {
+    let Some(declared) = meta.and_then(|meta| meta.get(FEATURES_META_KEY)) else {
+        return Ok(None);
+    };
+    let names: Vec<&str> = match declared {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .ok_or_else(|| "meta.features entries must be flag names".to_string())
+            })
+            .collect::<std::result::Result<_, _>>()?,
+        Value::Object(map) => map.keys().map(String::as_str).collect(),
+        _ => return Err("meta.features must be a list of flag names or a mapping".to_string()),
+    };
+    names
+        .into_iter()
+        .map(|name| parse_feature(&Value::String(name.to_string())))
+        .collect::<std::result::Result<_, _>>()
+        .map(Some)
+}
+
+/// Result of [`render_features`].
+#[derive(Clone, Debug)]
+pub struct RenderedFlow {
+    pub flow: FlowIr,
+    /// Nodes removed because their flag is off, in flow order.
+    pub removed: Vec<String>,
+    /// Enabled flags the flow does not declare in `meta.features`.
+    pub undeclared: Vec<String>,
+}
+
+/// A guarded node and whether its flag was enabled, for reports.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GuardedNode {
+    pub node_id: String,
+    pub feature: String,
+    pub enabled: bool,
+}
+
+/// Every node with a `feature` guard.
+pub fn guarded_nodes(flow: &FlowIr, enabled: &[String]) -> Vec<GuardedNode> {
+    flow.nodes
+        .values()
+        .filter_map(|node| {
+            node.feature.as_ref().map(|feature| GuardedNode {
+                node_id: node.id.clone(),
+                feature: feature.clone(),
+                enabled: enabled.contains(feature),
+            })
+        })
+        .collect()
+}
+
+/// The flow as it runs with `enabled` flags on and every other flag off.
+///
+/// Nodes whose flag is off are spliced out with [`FlowIr::splice_out_node`]. The remaining
+/// nodes lose their `feature` guard.
+pub fn render_features(flow: &FlowIr, enabled: &[String]) -> Result<RenderedFlow> {
+    let declared = declared_features(flow.meta.as_ref()).unwrap_or_default();
+    let undeclared = enabled
+        .iter()
+        .filter(|name| {
+            declared
+                .as_ref()
+                .is_none_or(|declared| !declared.contains(name))
+        })
+        .cloned()
+        .collect();
+
+    let mut rendered = flow.clone();
+    let mut removed = Vec::new();
+    for guarded in guarded_nodes(flow, enabled) {
+        if !guarded.enabled {
+            rendered.splice_out_node(&guarded.node_id)?;
+            removed.push(guarded.node_id);
+        }
+    }
+    for node in rendered.nodes.values_mut() {
+        node.feature = None;
+    }
+    Ok(RenderedFlow {
+        flow: rendered,
+        removed,
+        undeclared,
+    })
+}
@@ -1,9 +1,16 @@
 use crate::{
-    error::{FlowError, FlowErrorLocation, Result},
+    bundle_release::{BundleRelease, ReleaseChannel},
+    error::{FlowError, FlowErrorLocation, Result, SchemaErrorDetail},
+    flow_ir::NodeLimits,
+    flow_meta::META_NAMESPACE,
     loader,
+    runtime_capabilities::{RuntimeCapabilities, check_runtime_compatibility},
 };
 use blake3::Hasher;
-use greentic_types::Flow;
+use greentic_types::{
+    Flow,
+    flow_resolve::{ComponentSourceRefV1, FlowResolveV1, ResolveModeV1},
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::path::Path;
@@ -18,6 +25,132 @@ pub type NodeId = String;
 pub struct ComponentPin {
     pub name: String,
     pub version_req: String,
+    /// Where the component comes from, when the flow's resolve sidecar binds the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<ComponentSource>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentSourceKind {
+    Local,
+    Oci,
+    Repo,
+    Store,
+    Git,
+}
+
+/// Whether a reference always resolves to the same artifact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pinning {
+    Pinned,
+    Floating,
+}
+
+/// A component source reference classified as pinned or floating.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentSource {
+    pub kind: ComponentSourceKind,
+    pub reference: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    pub pinning: Pinning,
+    /// Why a floating reference can resolve differently between deployments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ComponentSource {
+    /// Classify a sidecar source.
+    ///
+    /// A reference is pinned when it carries a digest (in the sidecar or as `@sha256:...`),
+    /// or, for git sources, a full commit id (`#<sha>` or `@<sha>`). A `tracked` resolve mode
+    /// always floats.
+    pub fn from_sidecar(source: &ComponentSourceRefV1, mode: Option<ResolveModeV1>) -> Self {
+        let (kind, reference, digest) = match source {
+            ComponentSourceRefV1::Local { path, digest } => {
+                (ComponentSourceKind::Local, path, digest)
+            }
+            ComponentSourceRefV1::Oci { r#ref, digest } => {
+                (ComponentSourceKind::Oci, r#ref, digest)
+            }
+            ComponentSourceRefV1::Repo { r#ref, digest } => {
+                (ComponentSourceKind::Repo, r#ref, digest)
+            }
+            ComponentSourceRefV1::Store { r#ref, digest, .. } => {
+                (ComponentSourceKind::Store, r#ref, digest)
+            }
+        };
+        let kind = if is_git_reference(reference) {
+            ComponentSourceKind::Git
+        } else {
+            kind
+        };
+        let digest = digest
+            .as_deref()
+            .map(str::trim)
+            .filter(|digest| !digest.is_empty())
+            .map(str::to_string);
+        let pinned = digest.is_some()
+            || reference.contains("@sha256:")
+            || (kind == ComponentSourceKind::Git && has_commit_rev(reference));
+        let reason = if mode == Some(ResolveModeV1::Tracked) {
+            Some("resolve mode is tracked".to_string())
+        } else if pinned {
+            None
+        } else {
+            Some(floating_reason(kind, reference))
+        };
+        Self {
+            kind,
+            reference: reference.clone(),
+            digest,
+            pinning: if reason.is_some() {
+                Pinning::Floating
+            } else {
+                Pinning::Pinned
+            },
+            reason,
+        }
+    }
+
+    pub fn is_floating(&self) -> bool {
+        self.pinning == Pinning::Floating
+    }
+}
+
+fn is_git_reference(reference: &str) -> bool {
+    reference.starts_with("git+")
+        || reference.starts_with("git://")
+        || reference.starts_with("git@")
+        || reference
+            .split(['#', '@'])
+            .next()
+            .is_some_and(|base| base.ends_with(".git"))
+}
+
+fn has_commit_rev(reference: &str) -> bool {
+    reference
+        .rsplit_once(['#', '@'])
+        .is_some_and(|(_, rev)| rev.len() == 40 && rev.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn floating_reason(kind: ComponentSourceKind, reference: &str) -> String {
+    match kind {
+        ComponentSourceKind::Local => "local file without a digest".to_string(),
+        ComponentSourceKind::Git => "git reference without a commit id".to_string(),
+        ComponentSourceKind::Oci => {
+            let name = reference.rsplit('/').next().unwrap_or(reference);
+            match name.split_once(':') {
+                Some((_, tag)) => format!("tag '{tag}' can move"),
+                None => "no tag or digest (resolves to latest)".to_string(),
+            }
+        }
+        ComponentSourceKind::Repo | ComponentSourceKind::Store => {
+            "no digest (resolves to the newest matching version)".to_string()
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -25,6 +158,50 @@ pub struct NodeRef {
     pub node_id: String,
     pub component: ComponentPin,
     pub schema_id: Option<String>,
+    /// Component world recorded in `meta.greentic.components.<node>.world`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world: Option<String>,
+    /// Throttling hints from the node's `limits` key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<NodeLimits>,
+}
+
+/// The `limits` of a flow's nodes, summed up for hosts sizing their throttles.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FlowLimits {
+    /// Nodes that declare limits, in flow order.
+    pub nodes: Vec<String>,
+    /// Sum of the declared `concurrency` values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_concurrency: Option<u32>,
+    /// The lowest declared `rps`: the flow cannot sustain more through its slowest limited node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_rps: Option<f64>,
+}
+
+impl FlowLimits {
+    /// Aggregate the limits of `nodes`; `None` when no node declares any.
+    pub fn from_nodes(nodes: &[NodeRef]) -> Option<Self> {
+        let mut summary = Self::default();
+        for node in nodes {
+            let Some(limits) = &node.limits else {
+                continue;
+            };
+            summary.nodes.push(node.node_id.clone());
+            if let Some(concurrency) = limits.concurrency {
+                summary.total_concurrency = Some(
+                    summary
+                        .total_concurrency
+                        .unwrap_or(0)
+                        .saturating_add(concurrency),
+                );
+            }
+            if let Some(rps) = limits.rps {
+                summary.min_rps = Some(summary.min_rps.map_or(rps, |min| min.min(rps)));
+            }
+        }
+        (!summary.nodes.is_empty()).then_some(summary)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -36,6 +213,24 @@ pub struct FlowBundle {
     pub json: Value,
     pub hash_blake3: String,
     pub nodes: Vec<NodeRef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<FlowLimits>,
+    /// Release channel and build metadata, set by `bundle pack` (see [`crate::bundle_release`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<BundleRelease>,
+}
+
+impl FlowBundle {
+    /// Stamp the bundle for a release channel.
+    pub fn with_release(mut self, release: BundleRelease) -> Self {
+        self.release = Some(release);
+        self
+    }
+
+    /// The channel the bundle was packed for, if any.
+    pub fn channel(&self) -> Option<ReleaseChannel> {
+        self.release.as_ref().map(|release| release.channel)
+    }
 }
 
 /// Canonicalize a JSON value by sorting object keys recursively.
@@ -81,12 +276,30 @@ pub fn extract_component_pins(flow: &Flow) -> Vec<(NodeId, ComponentPin)> {
                 ComponentPin {
                     name: component_name,
                     version_req: "*".to_string(),
+                    source: None,
                 },
             )
         })
         .collect()
 }
 
+/// Like [`extract_component_pins`], attaching each node's source from its resolve sidecar.
+pub fn extract_component_pins_with_sources(
+    flow: &Flow,
+    resolve: &FlowResolveV1,
+) -> Vec<(NodeId, ComponentPin)> {
+    extract_component_pins(flow)
+        .into_iter()
+        .map(|(node_id, mut pin)| {
+            pin.source = resolve
+                .nodes
+                .get(&node_id)
+                .map(|entry| ComponentSource::from_sidecar(&entry.source, entry.mode));
+            (node_id, pin)
+        })
+        .collect()
+}
+
 /// Load YAML into a canonical [`FlowBundle`] using the embedded schema.
 pub fn load_and_validate_bundle(yaml: &str, source: Option<&Path>) -> Result<FlowBundle> {
     load_and_validate_bundle_with_schema_text(
@@ -99,6 +312,41 @@ pub fn load_and_validate_bundle(yaml: &str, source: Option<&Path>) -> Result<Flo
     .map(|(bundle, _)| bundle)
 }
 
+/// Like [`load_and_validate_bundle`], also failing when a node needs a world or schema id the
+/// target runtime does not provide (one detail per finding).
+pub fn load_and_validate_bundle_for_runtime(
+    yaml: &str,
+    source: Option<&Path>,
+    runtime: &RuntimeCapabilities,
+) -> Result<FlowBundle> {
+    let bundle = load_and_validate_bundle(yaml, source)?;
+    let findings = check_runtime_compatibility(&bundle, runtime);
+    if findings.is_empty() {
+        return Ok(bundle);
+    }
+    let source_label = source
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| INLINE_SOURCE_LABEL.to_string());
+    let details: Vec<SchemaErrorDetail> = findings
+        .into_iter()
+        .map(|finding| SchemaErrorDetail {
+            location: FlowErrorLocation::at_path(source_label.clone())
+                .with_source_path(source)
+                .with_json_pointer(finding.path.clone()),
+            message: finding.message,
+        })
+        .collect();
+    Err(FlowError::RuntimeIncompatible {
+        message: details
+            .iter()
+            .map(|detail| format!("- {}", detail.message))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        details,
+        location: FlowErrorLocation::at_path(source_label).with_source_path(source),
+    })
+}
+
 pub fn load_and_validate_bundle_with_flow(
     yaml: &str,
     source: Option<&Path>,
@@ -158,12 +406,32 @@ fn build_bundle_from_parts(
     hash_blake3: String,
 ) -> FlowBundle {
     let entry = resolve_entry(doc);
-    let nodes = extract_component_pins(flow)
+    let components = doc
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.get(META_NAMESPACE))
+        .and_then(|greentic| greentic.get("components"));
+    let recorded = |node_id: &str, key: &str| {
+        components
+            .and_then(|components| components.get(node_id))
+            .and_then(|entry| entry.get(key))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let limits = |node_id: &str| {
+        doc.nodes
+            .get(node_id)
+            .and_then(|node| node.raw.get("limits"))
+            .and_then(|value| NodeLimits::from_value(value).ok())
+    };
+    let nodes: Vec<NodeRef> = extract_component_pins(flow)
         .into_iter()
         .map(|(node_id, component)| NodeRef {
+            schema_id: recorded(&node_id, "schema_id"),
+            world: recorded(&node_id, "world"),
+            limits: limits(&node_id),
             node_id,
             component,
-            schema_id: None,
         })
         .collect();
 
@@ -174,7 +442,9 @@ fn build_bundle_from_parts(
         yaml: yaml.to_string(),
         json: canonical_json,
         hash_blake3,
+        limits: FlowLimits::from_nodes(&nodes),
         nodes,
+        release: None,
     }
 }
 
@@ -0,0 +1,129 @@
+//! Dependencies between the flows of a pack, for dependency-ordered bundling.
+//!
+//! A flow names the flows it calls under `meta.greentic.requires_flows`:
+//!
+//! ```yaml
+//! meta:
+//!   greentic:
+//!     requires_flows: [billing, notify]
+//! ```
+//!
+//! `bundle pack` with several flows writes their bundles in [`dependency_order`], each flow after
+//! the flows it requires, and records the order in a [`FlowManifest`] so a deployer can activate
+//! the bundles one by one instead of finding ordering problems at activation time.
+
+use anyhow::{Result, anyhow, bail};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::flow_meta::META_NAMESPACE;
+
+/// Key under `meta.greentic` listing the ids of the flows a flow calls.
+pub const REQUIRES_FLOWS_KEY: &str = "requires_flows";
+
+/// Version of the [`FlowManifest`] layout.
+pub const FLOW_MANIFEST_VERSION: u32 = 1;
+
+/// The flows of a pack in activation order, with what each one requires.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowManifest {
+    pub version: u32,
+    /// Every flow after the flows it requires.
+    pub flows: Vec<FlowManifestEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowManifestEntry {
+    pub id: String,
+    /// Bundle file, relative to the manifest when possible.
+    pub bundle: String,
+    pub hash_blake3: String,
+    /// Ids of the flows this flow calls.
+    pub requires: Vec<String>,
+}
+
+/// The flow ids listed under `meta.greentic.requires_flows` (empty when there are none).
+pub fn required_flows(meta: Option<&Value>) -> Result<Vec<String>> {
+    let Some(value) = meta
+        .and_then(|meta| meta.get(META_NAMESPACE))
+        .and_then(|greentic| greentic.get(REQUIRES_FLOWS_KEY))
+    else {
+        return Ok(Vec::new());
+    };
+    let invalid =
+        || anyhow!("meta.{META_NAMESPACE}.{REQUIRES_FLOWS_KEY} must be a list of flow ids");
+    let mut ids: Vec<String> = Vec::new();
+    for item in value.as_array().ok_or_else(invalid)? {
+        let id = item
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .ok_or_else(invalid)?;
+        if !ids.iter().any(|seen| seen == id) {
+            ids.push(id.to_string());
+        }
+    }
+    Ok(ids)
+}
+
+/// The ids of `flows` (flow id -> required flow ids) with every flow after the flows it
+/// requires. Otherwise flows keep their input order; a required flow moves up to just before the
+/// first flow that needs it.
+///
+/// Fails when a flow requires one that is not in `flows`, or when the requirements form a
+/// cycle; the error spells the cycle out (`circular flow dependency: a -> b -> a`).
+pub fn dependency_order(flows: &IndexMap<String, Vec<String>>) -> Result<Vec<String>> {
+    for (id, requires) in flows {
+        if let Some(missing) = requires.iter().find(|dep| !flows.contains_key(*dep)) {
+            bail!("flow '{id}' requires '{missing}', which is not in the pack");
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Unvisited,
+        InProgress,
+        Done,
+    }
+    let mut marks = vec![Mark::Unvisited; flows.len()];
+    let mut order = Vec::with_capacity(flows.len());
+    for root in 0..flows.len() {
+        if marks[root] != Mark::Unvisited {
+            continue;
+        }
+        // Depth-first, with an explicit stack of (flow, next requirement to visit).
+        let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+        marks[root] = Mark::InProgress;
+        while let Some((index, next)) = stack.last_mut() {
+            let (_, requires) = flows.get_index(*index).expect("valid index");
+            let Some(dep) = requires.get(*next) else {
+                marks[*index] = Mark::Done;
+                order.push(flows.get_index(*index).expect("valid index").0.clone());
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+            let dep_index = flows.get_index_of(dep).expect("checked above");
+            match marks[dep_index] {
+                Mark::Done => {}
+                Mark::Unvisited => {
+                    marks[dep_index] = Mark::InProgress;
+                    stack.push((dep_index, 0));
+                }
+                Mark::InProgress => {
+                    let start = stack
+                        .iter()
+                        .position(|(index, _)| *index == dep_index)
+                        .expect("in-progress flows are on the stack");
+                    let cycle: Vec<&str> = stack[start..]
+                        .iter()
+                        .map(|(index, _)| flows.get_index(*index).expect("valid index").0.as_str())
+                        .chain(std::iter::once(dep.as_str()))
+                        .collect();
+                    bail!("circular flow dependency: {}", cycle.join(" -> "));
+                }
+            }
+        }
+    }
+    Ok(order)
+}
@@ -0,0 +1,341 @@
+//! Structural diff between two versions of a flow, for reviewing rewired graphs.
+
+use crate::flow_ir::{FlowIr, NodeIr, Route};
+use crate::source_map::pointer_token;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Write as _;
+
+/// Differences between two [`FlowIr`] graphs, keyed by node id.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct FlowDiff {
+    /// Changes to flow-level fields (`id`, `type`, `start`, `entrypoints/<name>`, ...).
+    pub flow: Vec<ValueChange>,
+    pub added_nodes: Vec<NodeSummary>,
+    pub removed_nodes: Vec<NodeSummary>,
+    pub modified_nodes: Vec<NodeDiff>,
+}
+
+/// A node that exists on only one side of the diff.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct NodeSummary {
+    pub node_id: String,
+    pub operation: String,
+    pub routing: Vec<String>,
+}
+
+/// Changes to a node present on both sides.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct NodeDiff {
+    pub node_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation: Option<ValueChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub routing: Vec<RouteChange>,
+    /// Payload changes, with JSON pointers into the node payload.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub payload: Vec<ValueChange>,
+    /// Output mapping changes, with JSON pointers into the node `output`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<ValueChange>,
+}
+
+/// A value at `path` that was added (`old` is `None`), removed (`new` is `None`), or changed.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ValueChange {
+    pub path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// A route, identified by its status (`None` for the unconditional route), whose destination
+/// was added, removed, or retargeted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RouteChange {
+    pub status: Option<String>,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl FlowDiff {
+    pub fn is_empty(&self) -> bool {
+        self.flow.is_empty()
+            && self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.modified_nodes.is_empty()
+    }
+
+    /// Human-readable summary, one change per line (`+` added, `-` removed, `~` changed).
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for change in &self.flow {
+            let _ = writeln!(out, "~ {}: {}", change.path, describe_change(change));
+        }
+        for node in &self.removed_nodes {
+            let _ = writeln!(
+                out,
+                "- node '{}' ({}) -> {}",
+                node.node_id,
+                node.operation,
+                routing_label(&node.routing)
+            );
+        }
+        for node in &self.added_nodes {
+            let _ = writeln!(
+                out,
+                "+ node '{}' ({}) -> {}",
+                node.node_id,
+                node.operation,
+                routing_label(&node.routing)
+            );
+        }
+        for node in &self.modified_nodes {
+            let _ = writeln!(out, "~ node '{}'", node.node_id);
+            if let Some(change) = &node.operation {
+                let _ = writeln!(out, "    operation: {}", describe_change(change));
+            }
+            for route in &node.routing {
+                let status = route
+                    .status
+                    .as_deref()
+                    .map_or("default".to_string(), |status| format!("status {status}"));
+                let line = match (&route.old, &route.new) {
+                    (Some(old), Some(new)) => format!("{status}: {old} => {new}"),
+                    (None, Some(new)) => format!("+ {status} => {new}"),
+                    (Some(old), None) => format!("- {status} => {old}"),
+                    (None, None) => continue,
+                };
+                let _ = writeln!(out, "    routing {line}");
+            }
+            for change in &node.payload {
+                let _ = writeln!(
+                    out,
+                    "    payload {}: {}",
+                    pointer_label(&change.path),
+                    describe_change(change)
+                );
+            }
+            for change in &node.output {
+                let _ = writeln!(
+                    out,
+                    "    output {}: {}",
+                    pointer_label(&change.path),
+                    describe_change(change)
+                );
+            }
+        }
+        out
+    }
+}
+
+/// Compare two flows. Nodes are matched by id; a renamed node shows up as removed + added.
+pub fn diff_flows(old: &FlowIr, new: &FlowIr) -> FlowDiff {
+    let mut flow = Vec::new();
+    let mut field = |path: &str, old: Option<Value>, new: Option<Value>| {
+        if old != new {
+            flow.push(ValueChange {
+                path: path.to_string(),
+                old,
+                new,
+            });
+        }
+    };
+    field(
+        "id",
+        Some(old.id.clone().into()),
+        Some(new.id.clone().into()),
+    );
+    field(
+        "type",
+        Some(old.kind.clone().into()),
+        Some(new.kind.clone().into()),
+    );
+    field(
+        "start",
+        old.start.clone().map(Value::from),
+        new.start.clone().map(Value::from),
+    );
+    for (name, target) in &old.entrypoints {
+        field(
+            &format!("entrypoints/{name}"),
+            Some(target.clone().into()),
+            new.entrypoints.get(name).cloned().map(Value::from),
+        );
+    }
+    for (name, target) in &new.entrypoints {
+        if !old.entrypoints.contains_key(name) {
+            field(
+                &format!("entrypoints/{name}"),
+                None,
+                Some(target.clone().into()),
+            );
+        }
+    }
+
+    let removed_nodes = old
+        .nodes
+        .values()
+        .filter(|node| !new.nodes.contains_key(&node.id))
+        .map(summarize)
+        .collect();
+    let added_nodes = new
+        .nodes
+        .values()
+        .filter(|node| !old.nodes.contains_key(&node.id))
+        .map(summarize)
+        .collect();
+    let modified_nodes = old
+        .nodes
+        .values()
+        .filter_map(|old_node| {
+            let new_node = new.nodes.get(&old_node.id)?;
+            let diff = diff_node(old_node, new_node);
+            let unchanged = diff.operation.is_none()
+                && diff.routing.is_empty()
+                && diff.payload.is_empty()
+                && diff.output.is_empty();
+            (!unchanged).then_some(diff)
+        })
+        .collect();
+
+    FlowDiff {
+        flow,
+        added_nodes,
+        removed_nodes,
+        modified_nodes,
+    }
+}
+
+fn summarize(node: &NodeIr) -> NodeSummary {
+    NodeSummary {
+        node_id: node.id.clone(),
+        operation: node.operation.clone(),
+        routing: node
+            .routing
+            .iter()
+            .map(|route| match &route.status {
+                Some(status) => format!("{status}: {}", destination(route)),
+                None => destination(route),
+            })
+            .collect(),
+    }
+}
+
+fn diff_node(old: &NodeIr, new: &NodeIr) -> NodeDiff {
+    let operation = (old.operation != new.operation).then(|| ValueChange {
+        path: "operation".to_string(),
+        old: Some(old.operation.clone().into()),
+        new: Some(new.operation.clone().into()),
+    });
+    let mut payload = Vec::new();
+    diff_values("", Some(&old.payload), Some(&new.payload), &mut payload);
+    let mut output = Vec::new();
+    diff_values("", Some(&old.output), Some(&new.output), &mut output);
+    NodeDiff {
+        node_id: old.id.clone(),
+        operation,
+        routing: diff_routing(&old.routing, &new.routing),
+        payload,
+        output,
+    }
+}
+
+/// Routes are paired by status (first occurrence wins), so rewiring one branch reads as a
+/// single retarget rather than a removal plus an addition.
+fn diff_routing(old: &[Route], new: &[Route]) -> Vec<RouteChange> {
+    let destinations = |routes: &[Route]| {
+        let mut keyed: Vec<(Option<String>, String)> = Vec::new();
+        if routes.is_empty() {
+            keyed.push((None, "out".to_string()));
+        }
+        for route in routes {
+            if !keyed.iter().any(|(status, _)| *status == route.status) {
+                keyed.push((route.status.clone(), destination(route)));
+            }
+        }
+        keyed
+    };
+    let old = destinations(old);
+    let new = destinations(new);
+    let mut changes = Vec::new();
+    for (status, old_dest) in &old {
+        let new_dest = new
+            .iter()
+            .find(|(new_status, _)| new_status == status)
+            .map(|(_, dest)| dest);
+        if new_dest != Some(old_dest) {
+            changes.push(RouteChange {
+                status: status.clone(),
+                old: Some(old_dest.clone()),
+                new: new_dest.cloned(),
+            });
+        }
+    }
+    for (status, new_dest) in &new {
+        if !old.iter().any(|(old_status, _)| old_status == status) {
+            changes.push(RouteChange {
+                status: status.clone(),
+                old: None,
+                new: Some(new_dest.clone()),
+            });
+        }
+    }
+    changes
+}
+
+fn destination(route: &Route) -> String {
+    match (&route.to, route.out, route.reply) {
+        (_, _, true) => "reply".to_string(),
+        (Some(to), false, false) => to.clone(),
+        _ => "out".to_string(),
+    }
+}
+
+fn diff_values(
+    path: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<ValueChange>,
+) {
+    match (old, new) {
+        (Some(Value::Object(old_map)), Some(Value::Object(new_map))) => {
+            for (key, old_value) in old_map {
+                let child = format!("{path}/{}", pointer_token(key));
+                diff_values(&child, Some(old_value), new_map.get(key), changes);
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    let child = format!("{path}/{}", pointer_token(key));
+                    diff_values(&child, None, Some(new_value), changes);
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(ValueChange {
+            path: path.to_string(),
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+fn pointer_label(path: &str) -> &str {
+    if path.is_empty() { "/" } else { path }
+}
+
+fn routing_label(routing: &[String]) -> String {
+    if routing.is_empty() {
+        "out".to_string()
+    } else {
+        routing.join(", ")
+    }
+}
+
+fn describe_change(change: &ValueChange) -> String {
+    match (&change.old, &change.new) {
+        (Some(old), Some(new)) => format!("{old} -> {new}"),
+        (None, Some(new)) => format!("added {new}"),
+        (Some(old), None) => format!("removed {old}"),
+        (None, None) => "unchanged".to_string(),
+    }
+}
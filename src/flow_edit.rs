@@ -0,0 +1,511 @@
+//! In-place edits on [`FlowIr`] that keep the flow's references consistent.
+//!
+//! Each method validates its inputs before touching the IR, so a failed edit leaves the flow
+//! unchanged. The CLI's delete-step and the edit-plan operations are built on these.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    str::FromStr,
+};
+
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_ir::{
+        FlowIr, NodeIr, Route, analysis::FlowAnalysis, routing_to_value, validate_status_routes,
+    },
+    flow_meta,
+    model::FlowDoc,
+    rename::is_valid_node_id,
+    source_map::SourceMap,
+};
+
+/// What happens to a removed node's predecessors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeleteStrategy {
+    /// Replace each route into the node with the node's own routing (or drop it when the node
+    /// was terminal).
+    #[default]
+    Splice,
+    /// Leave predecessors alone; routes into the node must be fixed separately.
+    RemoveOnly,
+}
+
+/// How [`FlowIr::remove_node`] treats a node reached from more than one other node.
+///
+/// Written as `error`, `splice-all` or `reroute-to:<node>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum MultiplePredecessors {
+    #[default]
+    Error,
+    SpliceAll,
+    /// Point every route into the removed node at this node instead, keeping the routes'
+    /// statuses; the removed node's own routing is dropped.
+    RerouteTo(String),
+}
+
+impl FromStr for MultiplePredecessors {
+    type Err = String;
+
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        match text.trim() {
+            "error" => Ok(Self::Error),
+            "splice-all" => Ok(Self::SpliceAll),
+            other => match other.strip_prefix("reroute-to:").map(str::trim) {
+                Some("") => Err("reroute-to needs a node id: reroute-to:<node>".to_string()),
+                Some(node) => Ok(Self::RerouteTo(node.to_string())),
+                None => Err(format!(
+                    "unknown choice '{other}' (expected error, splice-all or reroute-to:<node>)"
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for MultiplePredecessors {
+    type Error = String;
+
+    fn try_from(text: String) -> std::result::Result<Self, String> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for MultiplePredecessors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => f.write_str("error"),
+            Self::SpliceAll => f.write_str("splice-all"),
+            Self::RerouteTo(node) => write!(f, "reroute-to:{node}"),
+        }
+    }
+}
+
+impl FlowIr {
+    /// Rename node `from` to `to`, rewriting routing targets, `start`, entrypoints and the
+    /// node-keyed `meta.greentic` entries. The node keeps its position.
+    pub fn rename_node(&mut self, from: &str, to: &str) -> Result<()> {
+        self.node(from)?;
+        if from == to {
+            return Err(FlowError::Internal {
+                message: format!("node '{from}' already has that id"),
+                location: node_location(from),
+            });
+        }
+        if !is_valid_node_id(to) {
+            return Err(FlowError::Internal {
+                message: format!(
+                    "invalid node id '{to}': use letters, digits, '_' and '-', starting with a letter or '_'"
+                ),
+                location: node_location(from),
+            });
+        }
+        if self.nodes.contains_key(to) {
+            return Err(FlowError::Internal {
+                message: format!("node '{to}' already exists"),
+                location: node_location(to),
+            });
+        }
+
+        let predecessors = self.predecessors_of(from);
+        let index = self.nodes.get_index_of(from).expect("node checked above");
+        let (_, mut node) = self
+            .nodes
+            .map
+            .shift_remove_index(index)
+            .expect("valid index");
+        self.nodes.index.remove_routes(from, &node.routing);
+        node.id = to.to_string();
+        self.nodes.index.add_routes(to, &node.routing);
+        self.nodes.map.shift_insert(index, to.to_string(), node);
+        self.nodes.index.rename_target(from, to);
+        for pred_id in predecessors {
+            let pred_id = if pred_id == from {
+                to
+            } else {
+                pred_id.as_str()
+            };
+            for route in &mut self.nodes.map[pred_id].routing {
+                if route.to.as_deref() == Some(from) {
+                    route.to = Some(to.to_string());
+                }
+            }
+        }
+        if self.start.as_deref() == Some(from) {
+            self.start = Some(to.to_string());
+        }
+        for target in self.entrypoints.values_mut() {
+            if target == from {
+                *target = to.to_string();
+            }
+        }
+        flow_meta::rename_component_entry(&mut self.meta, from, to);
+        Ok(())
+    }
+
+    /// Remove `node_id` and return it.
+    ///
+    /// With [`DeleteStrategy::Splice`], predecessors route to the removed node's successors.
+    /// A node with several predecessors is handled as `if_multiple_predecessors` says; with
+    /// [`MultiplePredecessors::RerouteTo`] that replaces the strategy. Entrypoints that
+    /// targeted the node move to the first remaining node.
+    pub fn remove_node(
+        &mut self,
+        node_id: &str,
+        strategy: DeleteStrategy,
+        if_multiple_predecessors: MultiplePredecessors,
+    ) -> Result<NodeIr> {
+        let target = self.node(node_id)?.clone();
+        let predecessors = self.predecessors_of(node_id);
+        let multiple = predecessors.len() > 1;
+        match &if_multiple_predecessors {
+            MultiplePredecessors::Error if multiple => {
+                return Err(FlowError::Routing {
+                    node_id: node_id.to_string(),
+                    message: format!(
+                        "multiple predecessors: {} (splice-all splices into each of them; \
+                         reroute-to:<node> points them at another step)",
+                        predecessors.join(", ")
+                    ),
+                    location: node_location(node_id),
+                });
+            }
+            MultiplePredecessors::RerouteTo(to) if multiple => {
+                if to == node_id || !self.nodes.contains_key(to) {
+                    return Err(FlowError::Routing {
+                        node_id: node_id.to_string(),
+                        message: format!(
+                            "cannot reroute predecessors to '{to}': not another step of the flow"
+                        ),
+                        location: node_location(node_id),
+                    });
+                }
+            }
+            _ => {}
+        }
+        if let MultiplePredecessors::RerouteTo(to) = &if_multiple_predecessors
+            && multiple
+        {
+            for pred_id in predecessors.iter().filter(|id| *id != node_id) {
+                let pred = self.nodes.map.get_mut(pred_id).expect("predecessor exists");
+                let old = pred.routing.clone();
+                for route in &mut pred.routing {
+                    if route.to.as_deref() == Some(node_id) {
+                        route.to = Some(to.clone());
+                    }
+                }
+                self.nodes.index.remove_routes(pred_id, &old);
+                self.nodes
+                    .index
+                    .add_routes(pred_id, &self.nodes.map[pred_id.as_str()].routing);
+            }
+        } else if strategy == DeleteStrategy::Splice {
+            let terminal = target
+                .routing
+                .iter()
+                .all(|r| r.to.is_none() && (r.out || r.reply));
+            for pred_id in predecessors.iter().filter(|id| *id != node_id) {
+                let pred = self.nodes.map.get_mut(pred_id).expect("predecessor exists");
+                let mut routes = Vec::new();
+                for route in &pred.routing {
+                    if route.to.as_deref() == Some(node_id) {
+                        if !terminal {
+                            routes.extend(target.routing.iter().cloned());
+                        }
+                        continue;
+                    }
+                    routes.push(route.clone());
+                }
+                let old = std::mem::replace(&mut pred.routing, routes);
+                self.nodes.index.remove_routes(pred_id, &old);
+                self.nodes
+                    .index
+                    .add_routes(pred_id, &self.nodes.map[pred_id.as_str()].routing);
+            }
+        }
+
+        self.nodes.map.swap_remove(node_id);
+        self.nodes.index.remove_routes(node_id, &target.routing);
+        flow_meta::clear_component_entry(&mut self.meta, node_id);
+        let fallback = self.nodes.keys().next().cloned().unwrap_or_default();
+        for entry in self.entrypoints.values_mut() {
+            if entry == node_id {
+                *entry = fallback.clone();
+            }
+        }
+        if self.start.as_deref() == Some(node_id) {
+            self.start = self.nodes.keys().next().cloned();
+        }
+        Ok(target)
+    }
+
+    /// Remove `node_id` as if it had never been in the flow: predecessors route on to its
+    /// successors (however many predecessors there are), `start` and entrypoints that named it
+    /// move to its first successor, and the other nodes keep their order.
+    pub fn splice_out_node(&mut self, node_id: &str) -> Result<NodeIr> {
+        let position = self.node_position(node_id)?;
+        let entrypoints: Vec<String> = self
+            .entrypoints
+            .iter()
+            .filter(|(_, target)| *target == node_id)
+            .map(|(name, _)| name.clone())
+            .collect();
+        let was_start = self.start.as_deref() == Some(node_id);
+        let node = self.remove_node(
+            node_id,
+            DeleteStrategy::Splice,
+            MultiplePredecessors::SpliceAll,
+        )?;
+        if let Some(next) = node
+            .routing
+            .iter()
+            .find_map(|route| route.to.clone().filter(|to| self.nodes.contains_key(to)))
+        {
+            for name in entrypoints {
+                self.entrypoints.insert(name, next.clone());
+            }
+            if was_start {
+                self.start = Some(next);
+            }
+        }
+        // `remove_node` swaps the last node into the gap; move it back to the end.
+        if position < self.nodes.len() {
+            self.nodes
+                .map
+                .move_index(position, self.nodes.map.len() - 1);
+        }
+        Ok(node)
+    }
+
+    /// Nodes [`FlowIr::remove_node_cascade`] would remove: `node_id` first, then, in flow
+    /// order, every node the entry nodes reach now but would no longer reach without it.
+    /// Nodes that are already unreachable are left to `doctor`.
+    pub fn cascade_removal(&self, node_id: &str) -> Result<Vec<String>> {
+        self.cascade_removal_all(&[node_id])
+    }
+
+    /// [`FlowIr::cascade_removal`] for several nodes at once: `node_ids` first, then the nodes
+    /// only they led to, so a node reached from two removed branches goes too.
+    pub fn cascade_removal_all(&self, node_ids: &[&str]) -> Result<Vec<String>> {
+        let analysis = FlowAnalysis::new(self);
+        for node_id in node_ids {
+            self.node(node_id)?;
+            if analysis.entry_nodes().iter().any(|entry| entry == node_id) {
+                return Err(FlowError::Routing {
+                    node_id: node_id.to_string(),
+                    message: "is the start node or an entrypoint target; a cascade would remove \
+                              everything after it"
+                        .to_string(),
+                    location: node_location(node_id),
+                });
+            }
+        }
+        let reached = analysis.reachable();
+        let mut seen: HashSet<&str> = analysis.entry_nodes().iter().map(String::as_str).collect();
+        let mut queue: VecDeque<&str> = seen.iter().copied().collect();
+        while let Some(id) = queue.pop_front() {
+            for next in analysis.successors(id) {
+                if !node_ids.contains(&next.as_str()) && seen.insert(next.as_str()) {
+                    queue.push_back(next.as_str());
+                }
+            }
+        }
+        let mut removed: Vec<String> = Vec::new();
+        for node_id in node_ids {
+            if !removed.iter().any(|id| id == node_id) {
+                removed.push(node_id.to_string());
+            }
+        }
+        removed.extend(
+            reached
+                .into_iter()
+                .filter(|id| !node_ids.contains(&id.as_str()) && !seen.contains(id.as_str())),
+        );
+        Ok(removed)
+    }
+
+    /// Remove `node_id` together with the nodes only it led to (see
+    /// [`FlowIr::cascade_removal`]), returning them in that order. Routes from the remaining
+    /// nodes into any removed node are dropped; the other nodes keep their order.
+    pub fn remove_node_cascade(&mut self, node_id: &str) -> Result<Vec<NodeIr>> {
+        self.remove_nodes_cascade(&[node_id])
+    }
+
+    /// [`FlowIr::remove_node_cascade`] for several nodes at once (see
+    /// [`FlowIr::cascade_removal_all`]).
+    pub fn remove_nodes_cascade(&mut self, node_ids: &[&str]) -> Result<Vec<NodeIr>> {
+        let ids = self.cascade_removal_all(node_ids)?;
+        let mut removed = Vec::with_capacity(ids.len());
+        for id in &ids {
+            let node = self
+                .nodes
+                .map
+                .shift_remove(id)
+                .expect("cascade nodes exist");
+            self.nodes.index.remove_routes(id, &node.routing);
+            flow_meta::clear_component_entry(&mut self.meta, id);
+            removed.push(node);
+        }
+        let survivors: Vec<String> = self.nodes.keys().cloned().collect();
+        for pred_id in survivors {
+            let routing = &self.nodes[pred_id.as_str()].routing;
+            if routing
+                .iter()
+                .any(|route| route.to.as_ref().is_some_and(|to| ids.contains(to)))
+            {
+                let kept = routing
+                    .iter()
+                    .filter(|route| !route.to.as_ref().is_some_and(|to| ids.contains(to)))
+                    .cloned()
+                    .collect();
+                self.set_routing(&pred_id, kept)?;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Replace the payload of `node_id`, returning the previous one.
+    pub fn replace_node_payload(&mut self, node_id: &str, payload: Value) -> Result<Value> {
+        let node = self.node_mut(node_id)?;
+        Ok(std::mem::replace(&mut node.payload, payload))
+    }
+
+    /// Replace the routing of `node_id`, returning the previous routes.
+    pub fn set_routing(&mut self, node_id: &str, routing: Vec<Route>) -> Result<Vec<Route>> {
+        let node = self
+            .nodes
+            .get_mut(node_id)
+            .ok_or_else(|| missing_node(node_id))?;
+        let old = std::mem::replace(&mut node.routing, routing);
+        self.nodes.index.remove_routes(node_id, &old);
+        self.nodes
+            .index
+            .add_routes(node_id, &self.nodes.map[node_id].routing);
+        Ok(old)
+    }
+
+    /// Append `route` to the routing of `from`.
+    ///
+    /// The target must exist and the node's status routes must stay reachable.
+    pub fn add_route(&mut self, from: &str, route: Route) -> Result<()> {
+        self.node(from)?;
+        if let Some(target) = route.to.as_deref()
+            && !self.nodes.contains_key(target)
+        {
+            return Err(FlowError::MissingNode {
+                target: target.to_string(),
+                node_id: from.to_string(),
+                location: routing_location(from),
+            });
+        }
+        let node = self.node_mut(from)?;
+        validate_status_routes(
+            node.routing
+                .iter()
+                .chain(std::iter::once(&route))
+                .map(|r| r.status.as_deref()),
+        )
+        .map_err(|message| FlowError::Routing {
+            node_id: from.to_string(),
+            message,
+            location: routing_location(from),
+        })?;
+        node.routing.push(route);
+        let added = node.routing.last().expect("route just pushed").clone();
+        self.nodes
+            .index
+            .add_routes(from, std::slice::from_ref(&added));
+        Ok(())
+    }
+
+    /// Remove the route at `index` from the routing of `from`, returning it.
+    pub fn remove_route(&mut self, from: &str, index: usize) -> Result<Route> {
+        let node = self.node_mut(from)?;
+        if index >= node.routing.len() {
+            return Err(FlowError::Routing {
+                node_id: from.to_string(),
+                message: format!(
+                    "route {index} does not exist (node has {} route(s))",
+                    node.routing.len()
+                ),
+                location: routing_location(from),
+            });
+        }
+        let route = node.routing.remove(index);
+        self.nodes
+            .index
+            .remove_routes(from, std::slice::from_ref(&route));
+        Ok(route)
+    }
+
+    fn node_position(&self, node_id: &str) -> Result<usize> {
+        self.nodes
+            .get_index_of(node_id)
+            .ok_or_else(|| missing_node(node_id))
+    }
+
+    fn node(&self, node_id: &str) -> Result<&NodeIr> {
+        self.nodes.get(node_id).ok_or_else(|| missing_node(node_id))
+    }
+
+    /// The node, without forgetting the predecessor index: callers that change its routing
+    /// update the index themselves.
+    fn node_mut(&mut self, node_id: &str) -> Result<&mut NodeIr> {
+        self.nodes
+            .map
+            .get_mut(node_id)
+            .ok_or_else(|| missing_node(node_id))
+    }
+}
+
+/// Splice `node_ids` out of `doc` with [`FlowIr::splice_out_node`], writing back only what the
+/// removal changed: every other node keeps its keys (reserved settings, `meta`) as written.
+pub fn splice_out_of_doc(doc: &FlowDoc, node_ids: &[String]) -> Result<FlowDoc> {
+    let before = FlowIr::from_doc(doc.clone())?;
+    let mut after = before.clone();
+    for node_id in node_ids {
+        after.splice_out_node(node_id)?;
+    }
+
+    let mut out = doc.clone();
+    out.spans = SourceMap::default();
+    out.nodes.retain(|id, _| after.nodes.contains_key(id));
+    for (id, node) in &mut out.nodes {
+        let routing = &after.nodes[id].routing;
+        if *routing != before.nodes[id].routing {
+            node.routing = routing_to_value(routing, id)?;
+        }
+    }
+    if after.entrypoints.get("default") != before.entrypoints.get("default") {
+        out.start = after.entrypoints.get("default").cloned();
+    }
+    for (name, target) in &mut out.entrypoints {
+        if target
+            .as_str()
+            .is_some_and(|target| node_ids.iter().any(|id| id == target))
+            && let Some(moved) = after.entrypoints.get(name)
+        {
+            *target = Value::String(moved.clone());
+        }
+    }
+    out.meta = after.meta;
+    Ok(out)
+}
+
+pub(crate) fn missing_node(node_id: &str) -> FlowError {
+    FlowError::Internal {
+        message: format!("node '{node_id}' not found"),
+        location: node_location(node_id),
+    }
+}
+
+fn node_location(node_id: &str) -> FlowErrorLocation {
+    FlowErrorLocation::at_path(format!("nodes.{node_id}"))
+}
+
+fn routing_location(node_id: &str) -> FlowErrorLocation {
+    FlowErrorLocation::at_path(format!("nodes.{node_id}.routing"))
+}
@@ -1,11 +1,19 @@
+pub mod analysis;
+pub mod index;
+
+pub use index::Nodes;
+
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use std::collections::BTreeSet;
 
 use crate::{
     error::{FlowError, FlowErrorLocation, Result},
+    features::{FEATURE_NODE_KEY, parse_feature},
     loader::load_ygtc_from_str,
-    model::{FlowDoc, NodeDoc},
+    model::{FlowDoc, NodeDoc, reserved_node_keys},
+    source_map::SourceMap,
 };
 
 /// Typed intermediate representation for flows, suitable for planning edits before
@@ -22,7 +30,10 @@ pub struct FlowIr {
     pub schema_version: Option<u32>,
     pub entrypoints: IndexMap<String, String>,
     pub meta: Option<Value>,
-    pub nodes: IndexMap<String, NodeIr>,
+    /// The nodes in document order, with the predecessor index the editing methods keep.
+    pub nodes: Nodes,
+    /// Source positions of the document this IR was read from; empty for edited or built IR.
+    pub spans: SourceMap,
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +44,57 @@ pub struct NodeIr {
     pub output: Value,
     pub routing: Vec<Route>,
     pub telemetry: Option<Value>,
+    /// Throttling hints from the node's `limits` key.
+    pub limits: Option<NodeLimits>,
+    /// Flag from the node's `feature` key; the node only runs when it is enabled.
+    pub feature: Option<String>,
+    /// Free-form description from the node's `doc` key, shown by graph exports and step lists.
+    pub doc: Option<String>,
+}
+
+impl NodeIr {
+    /// First non-blank line of the node's `doc`, for one-line listings.
+    pub fn doc_summary(&self) -> Option<&str> {
+        self.doc
+            .as_deref()
+            .and_then(|doc| doc.lines().map(str::trim).find(|line| !line.is_empty()))
+    }
+}
+
+/// Per-node rate-limit and concurrency hints (`limits: {rps: 10, concurrency: 2}`).
+///
+/// The runtime does not enforce these; hosts read them from the flow bundle to configure
+/// throttling. At least one field is set, `rps` is positive and `concurrency` at least 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeLimits {
+    /// Sustained requests per second; may be fractional (`0.5` is one every two seconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rps: Option<f64>,
+    /// Executions of the node allowed in flight at once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<u32>,
+}
+
+impl NodeLimits {
+    /// Parse and sanity-check a node's `limits` value.
+    pub fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        let limits: Self =
+            serde_json::from_value(value.clone()).map_err(|e| format!("invalid limits: {e}"))?;
+        limits.validate()?;
+        Ok(limits)
+    }
+
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        match (self.rps, self.concurrency) {
+            (None, None) => Err("limits must set rps or concurrency".to_string()),
+            (Some(rps), _) if !rps.is_finite() || rps <= 0.0 => {
+                Err(format!("limits.rps must be a positive number, got {rps}"))
+            }
+            (_, Some(0)) => Err("limits.concurrency must be at least 1".to_string()),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -45,6 +107,143 @@ pub struct Route {
     pub status: Option<String>,
     #[serde(default, skip_serializing_if = "is_false")]
     pub reply: bool,
+    /// Short human-readable name for the branch (documentation only; ignored at runtime).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Longer explanation of when the branch is taken (documentation only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl Route {
+    /// Whether the route carries `label`/`description` documentation.
+    pub fn has_docs(&self) -> bool {
+        self.label.is_some() || self.description.is_some()
+    }
+
+    pub fn status_matcher(&self) -> Option<StatusMatcher<'_>> {
+        self.status.as_deref().map(StatusMatcher::parse)
+    }
+}
+
+/// How a route's `status` is compared with the status a node produced.
+///
+/// Status routes are tried in declaration order and the first match wins, so `*` and `!<status>`
+/// routes usually come after the specific ones they complement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusMatcher<'a> {
+    /// `status: "*"` matches every status.
+    Any,
+    /// `status: "!ok"` matches every status except `ok`.
+    Not(&'a str),
+    /// `status: "e412"` matches exactly that status.
+    Is(&'a str),
+}
+
+impl<'a> StatusMatcher<'a> {
+    pub fn parse(raw: &'a str) -> Self {
+        if raw == "*" {
+            StatusMatcher::Any
+        } else if let Some(excluded) = raw.strip_prefix('!') {
+            StatusMatcher::Not(excluded)
+        } else {
+            StatusMatcher::Is(raw)
+        }
+    }
+
+    pub fn matches(&self, status: &str) -> bool {
+        match self {
+            StatusMatcher::Any => true,
+            StatusMatcher::Not(excluded) => *excluded != status,
+            StatusMatcher::Is(expected) => *expected == status,
+        }
+    }
+}
+
+/// Statuses already claimed by earlier routes of a node.
+enum Covered<'a> {
+    Only(Vec<&'a str>),
+    AllExcept(Vec<&'a str>),
+}
+
+/// Check a node's status routes (in declaration order): at most one `*` catch-all, no empty
+/// negation, and no route that earlier routes already make unreachable.
+pub fn validate_status_routes<'a>(
+    statuses: impl IntoIterator<Item = Option<&'a str>>,
+) -> std::result::Result<(), String> {
+    match status_route_conflicts(statuses).into_iter().next() {
+        Some((_, message)) => Err(message),
+        None => Ok(()),
+    }
+}
+
+/// Every problem [`validate_status_routes`] looks for, with the index of the offending route.
+/// A route that is reported does not count as covering any status for the later ones.
+pub fn status_route_conflicts<'a>(
+    statuses: impl IntoIterator<Item = Option<&'a str>>,
+) -> Vec<(usize, String)> {
+    let mut conflicts = Vec::new();
+    let mut covered = Covered::Only(Vec::new());
+    let mut catch_all: Option<usize> = None;
+    for (idx, status) in statuses.into_iter().enumerate() {
+        let Some(raw) = status else {
+            continue;
+        };
+        let matcher = StatusMatcher::parse(raw);
+        if matcher == StatusMatcher::Not("") {
+            conflicts.push((idx, format!("route {idx} has an empty status negation '!'")));
+            continue;
+        }
+        if matcher == StatusMatcher::Any
+            && let Some(first) = catch_all
+        {
+            conflicts.push((
+                idx,
+                format!("route {idx} is a second catch-all status '*' (first at route {first})"),
+            ));
+            continue;
+        }
+        let reachable = match (&covered, matcher) {
+            (Covered::Only(seen), StatusMatcher::Is(s)) => !seen.contains(&s),
+            (Covered::AllExcept(left), StatusMatcher::Is(s)) => left.contains(&s),
+            (Covered::Only(_), _) => true,
+            (Covered::AllExcept(left), StatusMatcher::Not(s)) => left.iter().any(|l| *l != s),
+            (Covered::AllExcept(left), StatusMatcher::Any) => !left.is_empty(),
+        };
+        if !reachable {
+            conflicts.push((
+                idx,
+                format!(
+                    "route {idx} status '{raw}' is unreachable; earlier routes already match every status it matches"
+                ),
+            ));
+            continue;
+        }
+        covered = match (covered, matcher) {
+            (Covered::Only(mut seen), StatusMatcher::Is(s)) => {
+                seen.push(s);
+                Covered::Only(seen)
+            }
+            (Covered::AllExcept(left), StatusMatcher::Is(s)) => {
+                Covered::AllExcept(left.into_iter().filter(|l| *l != s).collect())
+            }
+            (Covered::Only(seen), StatusMatcher::Not(s)) => {
+                Covered::AllExcept(if seen.contains(&s) {
+                    Vec::new()
+                } else {
+                    vec![s]
+                })
+            }
+            (Covered::AllExcept(left), StatusMatcher::Not(s)) => {
+                Covered::AllExcept(left.into_iter().filter(|l| *l == s).collect())
+            }
+            (_, StatusMatcher::Any) => {
+                catch_all = Some(idx);
+                Covered::AllExcept(Vec::new())
+            }
+        };
+    }
+    conflicts
 }
 
 fn is_false(value: &bool) -> bool {
@@ -52,6 +251,24 @@ fn is_false(value: &bool) -> bool {
 }
 
 impl FlowIr {
+    /// An empty flow of type `kind`; set the other fields directly.
+    pub fn new(id: impl Into<String>, kind: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: None,
+            description: None,
+            kind: kind.into(),
+            start: None,
+            parameters: Value::Object(Map::new()),
+            tags: Vec::new(),
+            schema_version: None,
+            entrypoints: IndexMap::new(),
+            meta: None,
+            nodes: Nodes::new(),
+            spans: SourceMap::default(),
+        }
+    }
+
     pub fn from_doc(doc: FlowDoc) -> Result<Self> {
         let schema_version = doc.schema_version;
         let entrypoints = resolve_entrypoints(&doc);
@@ -59,6 +276,25 @@ impl FlowIr {
         for (id, node_doc) in doc.nodes {
             let (operation, payload) = extract_operation(&node_doc, &id)?;
             let routing = parse_routing(&node_doc, &id)?;
+            let limits = parse_limits(&node_doc, &id)?;
+            let feature = node_doc
+                .raw
+                .get(FEATURE_NODE_KEY)
+                .map(parse_feature)
+                .transpose()
+                .map_err(|message| FlowError::Internal {
+                    message: format!("node '{id}': {message}"),
+                    location: FlowErrorLocation::at_path(format!("nodes.{id}.feature")),
+                })?;
+            let doc = node_doc
+                .raw
+                .get(DOC_NODE_KEY)
+                .map(parse_node_doc)
+                .transpose()
+                .map_err(|message| FlowError::Internal {
+                    message: format!("node '{id}': {message}"),
+                    location: FlowErrorLocation::at_path(format!("nodes.{id}.{DOC_NODE_KEY}")),
+                })?;
             let output = node_doc
                 .raw
                 .get("output")
@@ -76,6 +312,9 @@ impl FlowIr {
                         .telemetry
                         .clone()
                         .and_then(|t| serde_json::to_value(t).ok()),
+                    limits,
+                    feature,
+                    doc,
                 },
             );
         }
@@ -91,13 +330,16 @@ impl FlowIr {
             schema_version,
             entrypoints,
             meta: doc.meta,
-            nodes,
+            nodes: nodes.into(),
+            spans: doc.spans,
         })
     }
 
     pub fn to_doc(&self) -> Result<FlowDoc> {
         let mut nodes: IndexMap<String, NodeDoc> = IndexMap::new();
+        let reserved = reserved_node_keys();
         for (id, node_ir) in &self.nodes {
+            ensure_operation_not_reserved(id, &node_ir.operation, &reserved)?;
             let mut raw = IndexMap::new();
             raw.insert(node_ir.operation.clone(), node_ir.payload.clone());
             if !node_ir.output.is_object()
@@ -109,28 +351,20 @@ impl FlowIr {
             {
                 raw.insert("output".to_string(), node_ir.output.clone());
             }
-            let routing_value =
-                serde_json::to_value(&node_ir.routing).map_err(|e| FlowError::Internal {
-                    message: format!("serialize routing for node '{id}': {e}"),
-                    location: FlowErrorLocation::at_path(format!("nodes.{id}.routing")),
+            if let Some(limits) = &node_ir.limits {
+                let value = serde_json::to_value(limits).map_err(|e| FlowError::Internal {
+                    message: format!("serialize limits for node '{id}': {e}"),
+                    location: FlowErrorLocation::at_path(format!("nodes.{id}.limits")),
                 })?;
-            let routing_yaml = if node_ir.routing.len() == 1
-                && node_ir.routing[0].out
-                && node_ir.routing[0].to.is_none()
-                && !node_ir.routing[0].reply
-                && node_ir.routing[0].status.is_none()
-            {
-                Value::String("out".to_string())
-            } else if node_ir.routing.len() == 1
-                && node_ir.routing[0].reply
-                && node_ir.routing[0].to.is_none()
-                && !node_ir.routing[0].out
-                && node_ir.routing[0].status.is_none()
-            {
-                Value::String("reply".to_string())
-            } else {
-                routing_value
-            };
+                raw.insert("limits".to_string(), value);
+            }
+            if let Some(feature) = &node_ir.feature {
+                raw.insert(FEATURE_NODE_KEY.to_string(), Value::String(feature.clone()));
+            }
+            if let Some(doc) = &node_ir.doc {
+                raw.insert(DOC_NODE_KEY.to_string(), Value::String(doc.clone()));
+            }
+            let routing_yaml = routing_to_value(&node_ir.routing, id)?;
             nodes.insert(
                 id.clone(),
                 NodeDoc {
@@ -172,8 +406,566 @@ impl FlowIr {
             entrypoints,
             meta: self.meta.clone(),
             nodes,
+            spans: self.spans.clone(),
+        })
+    }
+}
+
+/// Routing as written in YAML: `out`/`reply` shorthand for a lone plain terminal route,
+/// otherwise the route list.
+pub(crate) fn routing_to_value(routing: &[Route], node_id: &str) -> Result<Value> {
+    if let [route] = routing
+        && route.to.is_none()
+        && route.status.is_none()
+        && !route.has_docs()
+        && route.out != route.reply
+    {
+        let shorthand = if route.out { "out" } else { "reply" };
+        return Ok(Value::String(shorthand.to_string()));
+    }
+    serde_json::to_value(routing).map_err(|e| FlowError::Internal {
+        message: format!("serialize routing for node '{node_id}': {e}"),
+        location: FlowErrorLocation::at_path(format!("nodes.{node_id}.routing")),
+    })
+}
+
+/// Rewrite flow YAML into its canonical layout without changing what it means.
+///
+/// - Top-level keys come in a fixed order (`id`, `title`, `description`, `type`,
+///   `schema_version`, `start`, `tags`, `parameters`, `entrypoints`, `meta`, `nodes`); empty
+///   `tags`, `parameters` and `entrypoints` are dropped.
+/// - Each node lists its operation first, then the reserved settings in
+///   [`reserved_node_keys`] order, then `telemetry` and `routing`.
+/// - Routing uses the `out`/`reply` shorthand where it applies, and routes drop `false`
+///   flags. Empty routing is dropped.
+/// - Keys inside payloads, routes and `meta` are sorted. Nodes and entrypoints keep their order.
+///
+/// Formatting is idempotent. Comments are not preserved.
+pub fn format_canonical(yaml: &str) -> Result<String> {
+    use serde_yaml_bw::{Mapping, Value as YamlValue};
+
+    let yaml_value = |value: &Value, path: &str| {
+        serde_yaml_bw::to_value(value).map_err(|e| FlowError::Internal {
+            message: format!("render {path}: {e}"),
+            location: FlowErrorLocation::at_path(path.to_string()),
+        })
+    };
+    let key = |name: &str| YamlValue::String(name.to_string(), None);
+
+    let doc = load_ygtc_from_str(yaml)?;
+    let ir = FlowIr::from_doc(doc.clone())?;
+    let reserved = reserved_node_keys();
+
+    let mut root = Mapping::new();
+    root.insert(key("id"), key(&doc.id));
+    if let Some(title) = &doc.title {
+        root.insert(key("title"), key(title));
+    }
+    if let Some(description) = &doc.description {
+        root.insert(key("description"), key(description));
+    }
+    root.insert(key("type"), key(&doc.flow_type));
+    if let Some(version) = doc.schema_version {
+        root.insert(
+            key("schema_version"),
+            yaml_value(&version.into(), "schema_version")?,
+        );
+    }
+    if let Some(start) = &doc.start {
+        root.insert(key("start"), key(start));
+    }
+    if !doc.tags.is_empty() {
+        root.insert(key("tags"), yaml_value(&doc.tags.clone().into(), "tags")?);
+    }
+    if !(doc.parameters.is_null() || doc.parameters.as_object().is_some_and(Map::is_empty)) {
+        root.insert(
+            key("parameters"),
+            yaml_value(&doc.parameters, "parameters")?,
+        );
+    }
+    if !doc.entrypoints.is_empty() {
+        let mut entrypoints = Mapping::new();
+        for (name, target) in &doc.entrypoints {
+            entrypoints.insert(key(name), yaml_value(target, "entrypoints")?);
+        }
+        root.insert(key("entrypoints"), YamlValue::Mapping(entrypoints));
+    }
+    if let Some(meta) = &doc.meta {
+        root.insert(key("meta"), yaml_value(meta, "meta")?);
+    }
+
+    let mut nodes = Mapping::new();
+    for (id, node) in &doc.nodes {
+        let path = format!("nodes.{id}");
+        let mut out = Mapping::new();
+        for (name, value) in node.raw.iter().filter(|(name, _)| !reserved.contains(name)) {
+            out.insert(key(name), yaml_value(value, &path)?);
+        }
+        for name in &reserved {
+            if matches!(name.as_str(), "routing" | "telemetry") {
+                continue;
+            }
+            if let Some(value) = node.raw.get(name) {
+                out.insert(key(name), yaml_value(value, &path)?);
+            }
+        }
+        if let Some(telemetry) = &node.telemetry {
+            let mut telemetry =
+                serde_json::to_value(telemetry).map_err(|e| FlowError::Internal {
+                    message: format!("render telemetry for node '{id}': {e}"),
+                    location: FlowErrorLocation::at_path(format!("{path}.telemetry")),
+                })?;
+            if let Some(fields) = telemetry.as_object_mut() {
+                fields.retain(|_, value| {
+                    !(value.is_null() || value.as_object().is_some_and(Map::is_empty))
+                });
+            }
+            out.insert(key("telemetry"), yaml_value(&telemetry, &path)?);
+        }
+        let routing = &ir.nodes[id.as_str()].routing;
+        if !routing.is_empty() {
+            out.insert(
+                key("routing"),
+                yaml_value(&routing_to_value(routing, id)?, &path)?,
+            );
+        }
+        nodes.insert(key(id), YamlValue::Mapping(out));
+    }
+    root.insert(key("nodes"), YamlValue::Mapping(nodes));
+
+    serde_yaml_bw::to_string(&YamlValue::Mapping(root)).map_err(|e| FlowError::Internal {
+        message: format!("render canonical flow: {e}"),
+        location: FlowErrorLocation::at_path("flow".to_string()),
+    })
+}
+
+/// Render the flow as a Mermaid `flowchart`, for embedding diagrams in Markdown.
+///
+/// Nodes show their id, operation and the first line of their `doc`; edges carry the route
+/// label (or `status <s>`); `out`/`reply` routes point at shared terminal nodes and entrypoints
+/// at named stadiums.
+pub fn to_mermaid(flow: &FlowIr) -> String {
+    render_mermaid(flow, None)
+}
+
+/// Nodes and routes to emphasize when rendering a graph, e.g. the path a runtime trace took.
+///
+/// Highlighted diagrams draw the listed nodes and routes solid and everything else dimmed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphHighlight {
+    /// Nodes that ran.
+    pub nodes: BTreeSet<String>,
+    /// Nodes that ran and failed; drawn as failures instead of as executed.
+    pub failed: BTreeSet<String>,
+    /// Routes taken, as `(node id, index in its routing)`. The implicit `out` of a node without
+    /// routing is index 0.
+    pub routes: BTreeSet<(String, usize)>,
+}
+
+impl GraphHighlight {
+    fn node_class(&self, id: &str) -> &'static str {
+        if self.failed.contains(id) {
+            "failed"
+        } else if self.nodes.contains(id) {
+            "executed"
+        } else {
+            "idle"
+        }
+    }
+
+    fn takes(&self, from: &str, index: usize) -> bool {
+        self.routes.contains(&(from.to_string(), index))
+    }
+}
+
+/// [`to_mermaid`] with executed nodes and taken routes highlighted.
+pub fn to_mermaid_highlighted(flow: &FlowIr, highlight: &GraphHighlight) -> String {
+    render_mermaid(flow, Some(highlight))
+}
+
+fn render_mermaid(flow: &FlowIr, highlight: Option<&GraphHighlight>) -> String {
+    let ids: IndexMap<&str, String> = flow
+        .nodes
+        .keys()
+        .enumerate()
+        .map(|(idx, id)| (id.as_str(), format!("n{idx}")))
+        .collect();
+    let mut missing: IndexMap<&str, String> = IndexMap::new();
+    let mut lines = vec!["flowchart LR".to_string()];
+    for (idx, name) in flow.entrypoints.keys().enumerate() {
+        lines.push(format!("    entry{idx}([\"{}\"])", mermaid_text(name)));
+    }
+    for (id, node) in &flow.nodes {
+        let doc = node
+            .doc_summary()
+            .map(|doc| format!("<br/><i>{}</i>", mermaid_text(doc)))
+            .unwrap_or_default();
+        lines.push(format!(
+            "    {}[\"{}<br/>{}{doc}\"]",
+            ids[id.as_str()],
+            mermaid_text(id),
+            mermaid_text(&node.operation)
+        ));
+    }
+    let mut edges = Vec::new();
+    for (idx, target) in flow.entrypoints.values().enumerate() {
+        let to = mermaid_node(target, &ids, &mut missing);
+        edges.push(format!("    entry{idx} --> {to}"));
+    }
+    let mut terminals = Vec::new();
+    let mut link_styles = Vec::new();
+    for (from, index, target, label) in graph_edges(flow) {
+        if let Some(highlight) = highlight {
+            let style = if highlight.takes(from, index) {
+                "stroke:#2b8a3e,stroke-width:3px"
+            } else {
+                "stroke:#adb5bd,stroke-dasharray:4 4"
+            };
+            link_styles.push(format!("    linkStyle {} {style}", edges.len()));
+        }
+        let to = match target {
+            GraphTarget::Node(id) => mermaid_node(id, &ids, &mut missing),
+            GraphTarget::Out | GraphTarget::Reply => {
+                let name = target.terminal_name();
+                if !terminals.contains(&name) {
+                    terminals.push(name);
+                }
+                format!("{name}_")
+            }
+        };
+        let arrow = match label {
+            Some(label) => format!("-->|\"{}\"|", mermaid_text(&label)),
+            None => "-->".to_string(),
+        };
+        edges.push(format!("    {} {arrow} {to}", ids[from]));
+    }
+    for (id, alias) in &missing {
+        lines.push(format!("    {alias}[\"{} (missing)\"]", mermaid_text(id)));
+    }
+    for name in terminals {
+        lines.push(format!("    {name}_(({name}))"));
+    }
+    lines.extend(edges);
+    if let Some(highlight) = highlight {
+        lines.push("    classDef executed fill:#d3f9d8,stroke:#2b8a3e".to_string());
+        lines.push("    classDef failed fill:#ffe3e3,stroke:#c92a2a".to_string());
+        lines.push("    classDef idle fill:#f1f3f5,stroke:#adb5bd,color:#868e96".to_string());
+        for class in ["executed", "failed", "idle"] {
+            let members: Vec<&str> = flow
+                .nodes
+                .keys()
+                .filter(|id| highlight.node_class(id) == class)
+                .map(|id| ids[id.as_str()].as_str())
+                .collect();
+            if !members.is_empty() {
+                lines.push(format!("    class {} {class}", members.join(",")));
+            }
+        }
+        lines.extend(link_styles);
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Render the flow as a Graphviz DOT `digraph`, with the same nodes, edges, terminals and
+/// entrypoints as [`to_mermaid`]. A node's full `doc` becomes its tooltip.
+pub fn to_dot(flow: &FlowIr) -> String {
+    render_dot(flow, None)
+}
+
+/// [`to_dot`] with executed nodes and taken routes highlighted.
+pub fn to_dot_highlighted(flow: &FlowIr, highlight: &GraphHighlight) -> String {
+    render_dot(flow, Some(highlight))
+}
+
+fn render_dot(flow: &FlowIr, highlight: Option<&GraphHighlight>) -> String {
+    let mut lines = vec![
+        format!("digraph {} {{", dot_quote(&flow.id)),
+        "    rankdir=LR;".to_string(),
+        "    node [shape=box, style=rounded];".to_string(),
+    ];
+    for (idx, name) in flow.entrypoints.keys().enumerate() {
+        lines.push(format!(
+            "    \"__entry{idx}\" [label={}, shape=plaintext];",
+            dot_quote(name)
+        ));
+    }
+    for (id, node) in &flow.nodes {
+        let style = match highlight.map(|highlight| highlight.node_class(id)) {
+            Some("executed") => {
+                ", style=\"rounded,filled\", fillcolor=\"#d3f9d8\", color=\"#2b8a3e\""
+            }
+            Some("failed") => {
+                ", style=\"rounded,filled\", fillcolor=\"#ffe3e3\", color=\"#c92a2a\""
+            }
+            Some(_) => ", color=\"#adb5bd\", fontcolor=\"#868e96\"",
+            None => "",
+        };
+        let mut label = format!("{}\\n{}", dot_escape(id), dot_escape(&node.operation));
+        let mut tooltip = String::new();
+        if let (Some(summary), Some(doc)) = (node.doc_summary(), &node.doc) {
+            label.push_str(&format!("\\n{}", dot_escape(summary)));
+            tooltip = format!(", tooltip={}", dot_quote(doc.trim()));
+        }
+        lines.push(format!(
+            "    {} [label=\"{label}\"{tooltip}{style}];",
+            dot_quote(id)
+        ));
+    }
+    let mut missing: Vec<&str> = Vec::new();
+    let mut terminals = Vec::new();
+    let mut edges = Vec::new();
+    for (idx, target) in flow.entrypoints.values().enumerate() {
+        if !flow.nodes.contains_key(target) && !missing.contains(&target.as_str()) {
+            missing.push(target);
+        }
+        edges.push(format!("    \"__entry{idx}\" -> {};", dot_quote(target)));
+    }
+    for (from, index, target, label) in graph_edges(flow) {
+        let to = match target {
+            GraphTarget::Node(id) => {
+                if !flow.nodes.contains_key(id) && !missing.contains(&id) {
+                    missing.push(id);
+                }
+                dot_quote(id)
+            }
+            GraphTarget::Out | GraphTarget::Reply => {
+                let name = target.terminal_name();
+                if !terminals.contains(&name) {
+                    terminals.push(name);
+                }
+                format!("\"__{name}\"")
+            }
+        };
+        let mut attrs: Vec<String> = label
+            .map(|label| format!("label={}", dot_quote(&label)))
+            .into_iter()
+            .collect();
+        if let Some(highlight) = highlight {
+            attrs.push(if highlight.takes(from, index) {
+                "color=\"#2b8a3e\", penwidth=3".to_string()
+            } else {
+                "color=\"#adb5bd\", style=dashed".to_string()
+            });
+        }
+        let attrs = if attrs.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", attrs.join(", "))
+        };
+        edges.push(format!("    {} -> {to}{attrs};", dot_quote(from)));
+    }
+    for id in missing {
+        lines.push(format!(
+            "    {} [label={}, style=dashed];",
+            dot_quote(id),
+            dot_quote(&format!("{id} (missing)"))
+        ));
+    }
+    for name in terminals {
+        lines.push(format!(
+            "    \"__{name}\" [label=\"{name}\", shape=doublecircle];"
+        ));
+    }
+    lines.extend(edges);
+    lines.push("}".to_string());
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+#[derive(Clone, Copy)]
+enum GraphTarget<'a> {
+    Node(&'a str),
+    Out,
+    Reply,
+}
+
+impl GraphTarget<'_> {
+    fn terminal_name(self) -> &'static str {
+        match self {
+            GraphTarget::Reply => "reply",
+            _ => "out",
+        }
+    }
+}
+
+/// Every route as `(from, route index, target, label)`; an empty routing list is an implicit
+/// `out` at index 0.
+fn graph_edges(flow: &FlowIr) -> Vec<(&str, usize, GraphTarget<'_>, Option<String>)> {
+    let mut edges = Vec::new();
+    for (id, node) in &flow.nodes {
+        if node.routing.is_empty() {
+            edges.push((id.as_str(), 0, GraphTarget::Out, None));
+        }
+        for (index, route) in node.routing.iter().enumerate() {
+            let label = route.label.clone().or_else(|| {
+                route
+                    .status
+                    .as_ref()
+                    .map(|status| format!("status {status}"))
+            });
+            let target = match (&route.to, route.out, route.reply) {
+                (_, _, true) => GraphTarget::Reply,
+                (Some(to), false, false) if to != "out" => GraphTarget::Node(to),
+                _ => GraphTarget::Out,
+            };
+            edges.push((id.as_str(), index, target, label));
+        }
+    }
+    edges
+}
+
+fn mermaid_node<'a>(
+    id: &'a str,
+    ids: &IndexMap<&str, String>,
+    missing: &mut IndexMap<&'a str, String>,
+) -> String {
+    if let Some(alias) = ids.get(id) {
+        return alias.clone();
+    }
+    let next = format!("missing{}", missing.len());
+    missing.entry(id).or_insert(next).clone()
+}
+
+fn mermaid_text(text: &str) -> String {
+    text.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", dot_escape(text))
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Routing cycles, one per strongly connected group of nodes (a node routing to itself counts).
+///
+/// Each cycle is a node path that starts at the group's first node in flow order and closes from
+/// its last node back to the first; it is the shortest such loop, preferring earlier routes on
+/// ties. Listing every elementary cycle could be exponential, so a group with several loops is
+/// reported once. Routes to missing nodes are ignored.
+pub fn find_cycles(flow: &FlowIr) -> Vec<Vec<String>> {
+    let index: IndexMap<&str, usize> = flow
+        .nodes
+        .keys()
+        .enumerate()
+        .map(|(idx, id)| (id.as_str(), idx))
+        .collect();
+    let successors: Vec<Vec<usize>> = flow
+        .nodes
+        .values()
+        .map(|node| {
+            let mut next: Vec<usize> = Vec::new();
+            for to in node.routing.iter().filter_map(|route| route.to.as_deref()) {
+                if let Some(&target) = index.get(to)
+                    && !next.contains(&target)
+                {
+                    next.push(target);
+                }
+            }
+            next
         })
+        .collect();
+    let component = strongly_connected_components(&successors);
+
+    let mut cycles = Vec::new();
+    let mut reported = vec![false; successors.len()];
+    for start in 0..successors.len() {
+        let group = component[start];
+        if reported[group] {
+            continue;
+        }
+        reported[group] = true;
+        // Breadth-first search inside the group for the shortest path back to `start`.
+        let mut parent: Vec<Option<usize>> = vec![None; successors.len()];
+        let mut queue = std::collections::VecDeque::from([start]);
+        let mut closing = None;
+        'search: while let Some(node) = queue.pop_front() {
+            for &next in &successors[node] {
+                if next == start {
+                    closing = Some(node);
+                    break 'search;
+                }
+                if component[next] == group && parent[next].is_none() {
+                    parent[next] = Some(node);
+                    queue.push_back(next);
+                }
+            }
+        }
+        let Some(mut node) = closing else {
+            continue;
+        };
+        let mut path = vec![node];
+        while node != start {
+            node = parent[node].expect("every queued node except start has a parent");
+            path.push(node);
+        }
+        path.reverse();
+        cycles.push(
+            path.into_iter()
+                .map(|idx| flow.nodes.get_index(idx).expect("node index").0.clone())
+                .collect(),
+        );
     }
+    cycles
+}
+
+/// Kosaraju's algorithm without recursion, so very large flows cannot overflow the stack.
+/// Returns each node's component number.
+fn strongly_connected_components(successors: &[Vec<usize>]) -> Vec<usize> {
+    let count = successors.len();
+    let mut order = Vec::with_capacity(count);
+    let mut visited = vec![false; count];
+    for root in 0..count {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let mut stack = vec![(root, 0usize)];
+        while let Some((node, edge)) = stack.last_mut() {
+            if let Some(&next) = successors[*node].get(*edge) {
+                *edge += 1;
+                if !visited[next] {
+                    visited[next] = true;
+                    stack.push((next, 0));
+                }
+            } else {
+                order.push(*node);
+                stack.pop();
+            }
+        }
+    }
+
+    let mut predecessors = vec![Vec::new(); count];
+    for (node, next) in successors.iter().enumerate() {
+        for &target in next {
+            predecessors[target].push(node);
+        }
+    }
+    let mut component = vec![usize::MAX; count];
+    let mut groups = 0;
+    for &root in order.iter().rev() {
+        if component[root] != usize::MAX {
+            continue;
+        }
+        component[root] = groups;
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            for &prev in &predecessors[node] {
+                if component[prev] == usize::MAX {
+                    component[prev] = groups;
+                    stack.push(prev);
+                }
+            }
+        }
+        groups += 1;
+    }
+    component
 }
 
 fn resolve_entrypoints(doc: &FlowDoc) -> IndexMap<String, String> {
@@ -193,6 +985,30 @@ fn resolve_entrypoints(doc: &FlowDoc) -> IndexMap<String, String> {
     entries
 }
 
+/// Node key holding the node's documentation string.
+pub const DOC_NODE_KEY: &str = "doc";
+
+/// Parse a node's `doc` value: a non-blank string, kept as written.
+pub fn parse_node_doc(value: &Value) -> std::result::Result<String, String> {
+    match value.as_str() {
+        Some(text) if !text.trim().is_empty() => Ok(text.to_string()),
+        Some(_) => Err("doc must not be empty".to_string()),
+        None => Err("doc must be a string".to_string()),
+    }
+}
+
+fn parse_limits(node: &NodeDoc, node_id: &str) -> Result<Option<NodeLimits>> {
+    let Some(value) = node.raw.get("limits") else {
+        return Ok(None);
+    };
+    NodeLimits::from_value(value)
+        .map(Some)
+        .map_err(|message| FlowError::Internal {
+            message: format!("node '{node_id}': {message}"),
+            location: FlowErrorLocation::at_path(format!("nodes.{node_id}.limits")),
+        })
+}
+
 fn parse_routing(node: &NodeDoc, node_id: &str) -> Result<Vec<Route>> {
     if node.routing.is_null() {
         return Ok(Vec::new());
@@ -224,6 +1040,10 @@ fn parse_routing(node: &NodeDoc, node_id: &str) -> Result<Vec<Route>> {
         status: Option<String>,
         #[serde(default)]
         reply: Option<bool>,
+        #[serde(default)]
+        label: Option<String>,
+        #[serde(default)]
+        description: Option<String>,
     }
 
     let routes: Vec<RouteDoc> =
@@ -239,6 +1059,8 @@ fn parse_routing(node: &NodeDoc, node_id: &str) -> Result<Vec<Route>> {
             out: r.out.unwrap_or(false),
             status: r.status,
             reply: r.reply.unwrap_or(false),
+            label: r.label,
+            description: r.description,
         })
         .collect())
 }
@@ -249,17 +1071,25 @@ pub fn parse_flow_to_ir(yaml: &str) -> Result<FlowIr> {
     FlowIr::from_doc(doc)
 }
 
+/// Reject operation names that collide with a reserved node key (see [`reserved_node_keys`]).
+pub fn ensure_operation_not_reserved(
+    node_id: &str,
+    operation: &str,
+    reserved: &[String],
+) -> Result<()> {
+    if reserved.iter().any(|key| key == operation) {
+        return Err(FlowError::ReservedOperationKey {
+            node_id: node_id.to_string(),
+            key: operation.to_string(),
+            reserved: reserved.join(", "),
+            location: FlowErrorLocation::at_path(format!("nodes.{node_id}")),
+        });
+    }
+    Ok(())
+}
+
 fn extract_operation(node: &NodeDoc, node_id: &str) -> Result<(String, Value)> {
-    let reserved = [
-        "routing",
-        "telemetry",
-        "output",
-        "retry",
-        "timeout",
-        "when",
-        "annotations",
-        "meta",
-    ];
+    let reserved = reserved_node_keys();
     if let Some(exec) = node.raw.get("component.exec") {
         let op = node
             .raw
@@ -273,30 +1103,31 @@ fn extract_operation(node: &NodeDoc, node_id: &str) -> Result<(String, Value)> {
                 location: FlowErrorLocation::at_path(format!("nodes.{node_id}")),
             });
         }
+        ensure_operation_not_reserved(node_id, op, &reserved)?;
         return Ok((op.to_string(), exec.clone()));
     }
-    let mut op_key: Option<String> = None;
-    let mut payload: Option<Value> = None;
-    for (k, v) in &node.raw {
-        if reserved.contains(&k.as_str()) {
-            continue;
-        }
-        if op_key.is_some() {
+    let op_entries: Vec<(&String, &Value)> = node
+        .raw
+        .iter()
+        .filter(|(k, _)| !reserved.iter().any(|key| key == *k))
+        .collect();
+    match op_entries.as_slice() {
+        [(k, v)] => return Ok(((*k).clone(), (*v).clone())),
+        [] => {}
+        entries => {
+            let keys: Vec<String> = entries.iter().map(|(k, _)| format!("'{k}'")).collect();
             return Err(FlowError::Internal {
                 message: format!(
-                    "node '{node_id}' must have exactly one operation key, found multiple"
+                    "node '{node_id}' must have exactly one operation key, found multiple: {}",
+                    keys.join(", ")
                 ),
                 location: FlowErrorLocation::at_path(format!("nodes.{node_id}")),
             });
         }
-        op_key = Some(k.clone());
-        payload = Some(v.clone());
-    }
-    if let (Some(k), Some(v)) = (op_key, payload) {
-        return Ok((k, v));
     }
 
     if let Some(op) = &node.operation {
+        ensure_operation_not_reserved(node_id, op, &reserved)?;
         return Ok((op.clone(), node.payload.clone()));
     }
 
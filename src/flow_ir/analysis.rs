@@ -0,0 +1,289 @@
+//! Graph queries over a [`FlowIr`]: successors and predecessors, reachability, topological
+//! order, entry-to-exit paths and fan-in/fan-out.
+//!
+//! [`FlowAnalysis`] builds the adjacency once; every query reads from it. Edges come from routes
+//! with a `to` target that names a node in the flow, so routes to missing nodes and the
+//! `out`/`reply` terminals are not edges. Node lists keep flow order.
+
+use indexmap::{IndexMap, IndexSet};
+use serde::Serialize;
+use std::collections::{BTreeSet, VecDeque};
+
+use super::FlowIr;
+
+/// Adjacency of a flow's routing graph, in flow order.
+#[derive(Clone, Debug)]
+pub struct FlowAnalysis {
+    successors: IndexMap<String, Vec<String>>,
+    predecessors: IndexMap<String, Vec<String>>,
+    entries: Vec<String>,
+    exits: Vec<String>,
+}
+
+/// Fan-in and fan-out of one node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct NodeDegree {
+    pub node_id: String,
+    /// Distinct nodes routing to this one.
+    pub fan_in: usize,
+    /// Distinct nodes this one routes to.
+    pub fan_out: usize,
+}
+
+/// Result of [`FlowAnalysis::paths`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FlowPaths {
+    pub paths: Vec<Vec<String>>,
+    /// The limit was hit; more paths exist.
+    pub truncated: bool,
+}
+
+impl FlowAnalysis {
+    pub fn new(flow: &FlowIr) -> Self {
+        let mut successors: IndexMap<String, Vec<String>> = IndexMap::new();
+        let mut predecessors: IndexMap<String, Vec<String>> = flow
+            .nodes
+            .keys()
+            .map(|id| (id.clone(), Vec::new()))
+            .collect();
+        let mut exits = Vec::new();
+        for (id, node) in &flow.nodes {
+            let mut next: Vec<String> = Vec::new();
+            for to in node.routing.iter().filter_map(|route| route.to.as_deref()) {
+                if flow.nodes.contains_key(to) && !next.iter().any(|n| n == to) {
+                    next.push(to.to_string());
+                }
+            }
+            for to in &next {
+                predecessors[to.as_str()].push(id.clone());
+            }
+            if node.routing.is_empty()
+                || node
+                    .routing
+                    .iter()
+                    .any(|route| route.out || route.reply || route.to.as_deref() == Some("out"))
+            {
+                exits.push(id.clone());
+            }
+            successors.insert(id.clone(), next);
+        }
+        let mut entries: Vec<String> = Vec::new();
+        for target in flow.start.iter().chain(flow.entrypoints.values()) {
+            if flow.nodes.contains_key(target) && !entries.contains(target) {
+                entries.push(target.clone());
+            }
+        }
+        Self {
+            successors,
+            predecessors,
+            entries,
+            exits,
+        }
+    }
+
+    /// Nodes `node_id` routes to; empty for unknown nodes.
+    pub fn successors(&self, node_id: &str) -> &[String] {
+        self.successors.get(node_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Nodes routing to `node_id`; empty for unknown nodes.
+    pub fn predecessors(&self, node_id: &str) -> &[String] {
+        self.predecessors.get(node_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every node's successors.
+    pub fn successor_map(&self) -> &IndexMap<String, Vec<String>> {
+        &self.successors
+    }
+
+    /// Every node's predecessors.
+    pub fn predecessor_map(&self) -> &IndexMap<String, Vec<String>> {
+        &self.predecessors
+    }
+
+    /// `start` and the targets of entrypoints that name existing nodes.
+    pub fn entry_nodes(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Nodes that can end the flow: an `out`/`reply` route, or no routing at all.
+    pub fn exit_nodes(&self) -> &[String] {
+        &self.exits
+    }
+
+    /// Nodes reachable from `from` (including the starting nodes), in flow order.
+    pub fn reachable_from<'a>(&self, from: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut seen: IndexSet<&str> = IndexSet::new();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+        for id in from {
+            if let Some((id, _)) = self.successors.get_key_value(id)
+                && seen.insert(id.as_str())
+            {
+                queue.push_back(id.as_str());
+            }
+        }
+        while let Some(id) = queue.pop_front() {
+            for next in self.successors(id) {
+                if seen.insert(next.as_str()) {
+                    queue.push_back(next.as_str());
+                }
+            }
+        }
+        self.successors
+            .keys()
+            .filter(|id| seen.contains(id.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Nodes reachable from the entry nodes.
+    pub fn reachable(&self) -> Vec<String> {
+        self.reachable_from(self.entries.iter().map(String::as_str))
+    }
+
+    /// Nodes no entry node reaches. Empty when the flow has no valid entry, since then every
+    /// node would be reported.
+    pub fn unreachable(&self) -> Vec<String> {
+        if self.entries.is_empty() {
+            return Vec::new();
+        }
+        let reached = self.reachable();
+        self.successors
+            .keys()
+            .filter(|id| !reached.contains(id))
+            .cloned()
+            .collect()
+    }
+
+    /// Nodes ordered so every route goes forward, ties broken by flow order; `None` when the
+    /// flow has a routing cycle (see [`super::find_cycles`]).
+    pub fn topological_order(&self) -> Option<Vec<String>> {
+        let mut pending: Vec<usize> = self.predecessors.values().map(Vec::len).collect();
+        let mut ready: BTreeSet<usize> = (0..pending.len()).filter(|&i| pending[i] == 0).collect();
+        let mut order = Vec::with_capacity(pending.len());
+        while let Some(index) = ready.pop_first() {
+            let (id, next) = self.successors.get_index(index).expect("valid index");
+            for to in next {
+                let to = self
+                    .successors
+                    .get_index_of(to)
+                    .expect("successor is a node");
+                pending[to] -= 1;
+                if pending[to] == 0 {
+                    ready.insert(to);
+                }
+            }
+            order.push(id.clone());
+        }
+        (order.len() == pending.len()).then_some(order)
+    }
+
+    /// Simple paths from each entry node to an exit node, at most `limit` of them.
+    ///
+    /// A path never visits a node twice, so loops contribute their acyclic prefixes only. An
+    /// exit node that also routes on ends one path and continues others.
+    pub fn paths(&self, limit: usize) -> FlowPaths {
+        let mut result = FlowPaths::default();
+        for entry in &self.entries {
+            let mut path = vec![entry.as_str()];
+            let mut stack = vec![0usize];
+            if self.is_exit(entry) && !self.push_path(&mut result, &path, limit) {
+                return result;
+            }
+            while let Some(edge) = stack.last_mut() {
+                let node = *path.last().expect("path follows stack");
+                let Some(next) = self.successors(node).get(*edge) else {
+                    stack.pop();
+                    path.pop();
+                    continue;
+                };
+                *edge += 1;
+                if path.contains(&next.as_str()) {
+                    continue;
+                }
+                path.push(next);
+                stack.push(0);
+                if self.is_exit(next) && !self.push_path(&mut result, &path, limit) {
+                    return result;
+                }
+            }
+        }
+        result
+    }
+
+    /// The longest route from an entry node, as the nodes it visits.
+    ///
+    /// Routes back into the path being walked are ignored, so each loop counts once. Empty when
+    /// the flow has no entry node.
+    pub fn longest_path(&self) -> Vec<String> {
+        // Per node: the length of the longest route onwards and the next node on it.
+        let mut longest: IndexMap<&str, (usize, Option<&str>)> = IndexMap::new();
+        let mut on_path: IndexSet<&str> = IndexSet::new();
+        let mut best: Option<(usize, &str)> = None;
+        for entry in &self.entries {
+            let length = self.longest_from(entry, &mut longest, &mut on_path);
+            if best.is_none_or(|(best, _)| length > best) {
+                best = Some((length, entry));
+            }
+        }
+        let mut path = Vec::new();
+        let mut next = best.map(|(_, entry)| entry);
+        while let Some(id) = next {
+            path.push(id.to_string());
+            next = longest.get(id).and_then(|(_, next)| *next);
+        }
+        path
+    }
+
+    fn longest_from<'a>(
+        &'a self,
+        node_id: &'a str,
+        longest: &mut IndexMap<&'a str, (usize, Option<&'a str>)>,
+        on_path: &mut IndexSet<&'a str>,
+    ) -> usize {
+        if let Some((length, _)) = longest.get(node_id) {
+            return *length;
+        }
+        on_path.insert(node_id);
+        let mut tail = (0, None);
+        for next in self.successors(node_id) {
+            if on_path.contains(next.as_str()) {
+                continue;
+            }
+            let length = self.longest_from(next, longest, on_path);
+            if length > tail.0 {
+                tail = (length, Some(next.as_str()));
+            }
+        }
+        on_path.swap_remove(node_id);
+        longest.insert(node_id, (tail.0 + 1, tail.1));
+        tail.0 + 1
+    }
+
+    /// Fan-in and fan-out of every node, in flow order.
+    pub fn degrees(&self) -> Vec<NodeDegree> {
+        self.successors
+            .iter()
+            .map(|(id, next)| NodeDegree {
+                node_id: id.clone(),
+                fan_in: self.predecessors(id).len(),
+                fan_out: next.len(),
+            })
+            .collect()
+    }
+
+    fn is_exit(&self, node_id: &str) -> bool {
+        self.exits.iter().any(|id| id == node_id)
+    }
+
+    fn push_path(&self, result: &mut FlowPaths, path: &[&str], limit: usize) -> bool {
+        if result.paths.len() >= limit {
+            result.truncated = true;
+            return false;
+        }
+        result
+            .paths
+            .push(path.iter().map(|id| id.to_string()).collect());
+        true
+    }
+}
@@ -0,0 +1,212 @@
+//! Reverse routing edges kept alongside a [`FlowIr`]'s nodes for its editing methods.
+//!
+//! Finding a node's predecessors by scanning every node makes each `remove_node` or
+//! `rename_node` linear in the flow size, and a batch of edits on a large flow quadratic.
+//! [`PredecessorIndex`] is built from the routing on first use and then updated edge by edge by
+//! the editing methods in [`crate::flow_edit`]. It lives inside [`Nodes`], whose mutable access
+//! drops it, so code that changes `nodes` or a node's `routing` directly cannot leave it stale:
+//! the next lookup rebuilds it.
+
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+
+use super::{FlowIr, NodeIr, Route};
+
+/// Route target -> node with a route to it -> number of such routes.
+///
+/// Targets are kept whether or not a node of that name exists, so routes to a missing node
+/// start counting as soon as the node appears.
+#[derive(Debug, Clone, Default)]
+pub struct PredecessorIndex {
+    edges: Option<HashMap<String, IndexMap<String, usize>>>,
+}
+
+impl PredecessorIndex {
+    /// Whether the index holds edges; an unbuilt index is rebuilt on the next lookup.
+    pub fn is_built(&self) -> bool {
+        self.edges.is_some()
+    }
+
+    /// Drop the edges so the next lookup rebuilds them from the routing.
+    pub fn invalidate(&mut self) {
+        self.edges = None;
+    }
+
+    /// Nodes with a route to `node_id`, in no particular order. Builds the index from `nodes`'
+    /// routing when it is not built yet.
+    pub(crate) fn predecessors(
+        &mut self,
+        nodes: &IndexMap<String, NodeIr>,
+        node_id: &str,
+    ) -> Vec<&str> {
+        let edges = self.edges.get_or_insert_with(|| build(nodes));
+        edges
+            .get(node_id)
+            .map(|preds| preds.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn add_routes(&mut self, from: &str, routes: &[Route]) {
+        let Some(edges) = self.edges.as_mut() else {
+            return;
+        };
+        for to in routes.iter().filter_map(|route| route.to.as_deref()) {
+            *edges
+                .entry(to.to_string())
+                .or_default()
+                .entry(from.to_string())
+                .or_default() += 1;
+        }
+    }
+
+    pub(crate) fn remove_routes(&mut self, from: &str, routes: &[Route]) {
+        let Some(edges) = self.edges.as_mut() else {
+            return;
+        };
+        for to in routes.iter().filter_map(|route| route.to.as_deref()) {
+            let Some(preds) = edges.get_mut(to) else {
+                continue;
+            };
+            if let Some(count) = preds.get_mut(from) {
+                *count -= 1;
+                if *count == 0 {
+                    preds.swap_remove(from);
+                }
+            }
+            if preds.is_empty() {
+                edges.remove(to);
+            }
+        }
+    }
+
+    /// Move routes into `from` over to `to`; `from`'s own routes are moved by the caller.
+    pub(crate) fn rename_target(&mut self, from: &str, to: &str) {
+        let Some(edges) = self.edges.as_mut() else {
+            return;
+        };
+        if let Some(preds) = edges.remove(from) {
+            let merged = edges.entry(to.to_string()).or_default();
+            for (pred, count) in preds {
+                *merged.entry(pred).or_default() += count;
+            }
+        }
+    }
+}
+
+fn build(nodes: &IndexMap<String, NodeIr>) -> HashMap<String, IndexMap<String, usize>> {
+    let mut edges: HashMap<String, IndexMap<String, usize>> = HashMap::new();
+    for (id, node) in nodes {
+        for to in node.routing.iter().filter_map(|route| route.to.as_deref()) {
+            *edges
+                .entry(to.to_string())
+                .or_default()
+                .entry(id.clone())
+                .or_default() += 1;
+        }
+    }
+    edges
+}
+
+/// A flow's nodes in document order, with the predecessor index over their routing.
+///
+/// Derefs to the node map. Mutable access (`nodes[id].routing.push(..)`, `nodes.insert(..)`)
+/// forgets the index, so the next predecessor lookup rebuilds it from the routing.
+#[derive(Debug, Clone, Default)]
+pub struct Nodes {
+    pub(crate) map: IndexMap<String, NodeIr>,
+    pub(crate) index: PredecessorIndex,
+}
+
+impl Nodes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The node map, without the index.
+    pub fn into_map(self) -> IndexMap<String, NodeIr> {
+        self.map
+    }
+}
+
+impl Deref for Nodes {
+    type Target = IndexMap<String, NodeIr>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl DerefMut for Nodes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.index.invalidate();
+        &mut self.map
+    }
+}
+
+impl From<IndexMap<String, NodeIr>> for Nodes {
+    fn from(map: IndexMap<String, NodeIr>) -> Self {
+        Self {
+            map,
+            index: PredecessorIndex::default(),
+        }
+    }
+}
+
+impl FromIterator<(String, NodeIr)> for Nodes {
+    fn from_iter<I: IntoIterator<Item = (String, NodeIr)>>(iter: I) -> Self {
+        IndexMap::from_iter(iter).into()
+    }
+}
+
+impl IntoIterator for Nodes {
+    type Item = (String, NodeIr);
+    type IntoIter = indexmap::map::IntoIter<String, NodeIr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Nodes {
+    type Item = (&'a String, &'a NodeIr);
+    type IntoIter = indexmap::map::Iter<'a, String, NodeIr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Nodes {
+    type Item = (&'a String, &'a mut NodeIr);
+    type IntoIter = indexmap::map::IterMut<'a, String, NodeIr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.deref_mut().iter_mut()
+    }
+}
+
+impl FlowIr {
+    /// The predecessor index, as built so far.
+    pub fn predecessor_index(&self) -> &PredecessorIndex {
+        &self.nodes.index
+    }
+
+    /// Forget the predecessor index. Mutable access to `nodes` already does this.
+    pub fn invalidate_predecessor_index(&mut self) {
+        self.nodes.index.invalidate();
+    }
+
+    /// Nodes with a route to `node_id`, in flow order. Uses (and builds on first use) the
+    /// predecessor index, so the cost is proportional to the number of predecessors.
+    pub fn predecessors_of(&mut self, node_id: &str) -> Vec<String> {
+        let Nodes { map, index } = &mut self.nodes;
+        let mut preds: Vec<(usize, String)> = index
+            .predecessors(map, node_id)
+            .into_iter()
+            .filter_map(|id| map.get_index_of(id).map(|index| (index, id.to_string())))
+            .collect();
+        preds.sort_unstable();
+        preds.into_iter().map(|(_, id)| id).collect()
+    }
+}
@@ -133,6 +133,14 @@ pub struct ComponentContractMeta {
     pub config_schema_cbor: Option<String>,
 }
 
+/// Maps under `meta.greentic` keyed by node id.
+pub const NODE_KEYED_META_MAPS: &[&str] = &[
+    "components",
+    "secrets_hints",
+    "bindings_hints",
+    MANIFEST_SCHEMA_HASHES_KEY,
+];
+
 pub fn clear_component_entry(meta: &mut Option<Value>, node_id: &str) {
     let Some(Value::Object(root)) = meta else {
         return;
@@ -140,17 +148,93 @@ pub fn clear_component_entry(meta: &mut Option<Value>, node_id: &str) {
     let Some(Value::Object(greentic)) = root.get_mut(META_NAMESPACE) else {
         return;
     };
-    if let Some(Value::Object(components)) = greentic.get_mut("components") {
-        components.remove(node_id);
-    }
-    if let Some(Value::Object(secrets)) = greentic.get_mut("secrets_hints") {
-        secrets.remove(node_id);
+    for key in NODE_KEYED_META_MAPS {
+        if let Some(Value::Object(entries)) = greentic.get_mut(*key) {
+            entries.remove(node_id);
+        }
     }
-    if let Some(Value::Object(bindings)) = greentic.get_mut("bindings_hints") {
-        bindings.remove(node_id);
+}
+
+/// Move the node-keyed `meta.greentic` entries of `from` to `to`.
+pub fn rename_component_entry(meta: &mut Option<Value>, from: &str, to: &str) {
+    let Some(Value::Object(root)) = meta else {
+        return;
+    };
+    let Some(Value::Object(greentic)) = root.get_mut(META_NAMESPACE) else {
+        return;
+    };
+    for key in NODE_KEYED_META_MAPS {
+        if let Some(Value::Object(entries)) = greentic.get_mut(*key)
+            && let Some(entry) = entries.remove(from)
+        {
+            entries.insert(to.to_string(), entry);
+        }
     }
 }
 
+pub const MANIFEST_SCHEMA_HASHES_KEY: &str = "manifest_schema_hashes";
+
+/// Record the manifest `operations[].schema_hash` a node's payload was authored against, under
+/// `meta.greentic.manifest_schema_hashes.<node>`.
+pub fn set_manifest_schema_hash(meta: &mut Option<Value>, node_id: &str, schema_hash: &str) {
+    let greentic = ensure_greentic_meta(meta);
+    ensure_child_map(greentic, MANIFEST_SCHEMA_HASHES_KEY)
+        .insert(node_id.to_string(), Value::String(schema_hash.to_string()));
+}
+
+/// The manifest schema hash recorded for a node by [`set_manifest_schema_hash`].
+pub fn manifest_schema_hash<'a>(meta: &'a Option<Value>, node_id: &str) -> Option<&'a str> {
+    meta.as_ref()
+        .and_then(|root| root.get(META_NAMESPACE))
+        .and_then(|greentic| greentic.get(MANIFEST_SCHEMA_HASHES_KEY))
+        .and_then(|hashes| hashes.get(node_id))
+        .and_then(Value::as_str)
+}
+
+/// When a node's component was first bound (`meta.greentic.components.<node>.added_at`, Unix
+/// seconds), as recorded by [`set_component_entry`].
+pub fn component_added_at(meta: &Option<Value>, node_id: &str) -> Option<u64> {
+    meta.as_ref()
+        .and_then(|root| root.get(META_NAMESPACE))
+        .and_then(|greentic| greentic.get("components"))
+        .and_then(|components| components.get(node_id))
+        .and_then(|entry| entry.get("added_at"))
+        .and_then(Value::as_u64)
+}
+
+/// Heuristic for answers whose values must not be stored in clear text.
+pub fn is_secret_answer_key(key: &str) -> bool {
+    let lower = key.to_ascii_lowercase();
+    [
+        "secret",
+        "password",
+        "passwd",
+        "token",
+        "api_key",
+        "apikey",
+        "private_key",
+    ]
+    .iter()
+    .any(|needle| lower.contains(needle))
+}
+
+/// Answer keys listed in a node's `meta.greentic.secrets_hints`.
+pub fn secrets_hints(meta: &Option<Value>, node_id: &str) -> Vec<String> {
+    meta.as_ref()
+        .and_then(|root| root.get(META_NAMESPACE))
+        .and_then(|greentic| greentic.get("secrets_hints"))
+        .and_then(|hints| hints.get(node_id))
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 pub fn ensure_hints_empty(meta: &mut Option<Value>, node_id: &str) {
     let greentic = ensure_greentic_meta(meta);
     {
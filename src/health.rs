@@ -0,0 +1,183 @@
+//! Pack health scorecard: one score out of 100 built from per-category checks.
+//!
+//! Each category counts the items that pass (flows, or component references for pins and
+//! upgrades) and scores `passed * 100 / total`; a category with nothing to check scores 100.
+//! The overall score is the mean of the categories that were checked, so a category skipped for
+//! lack of input (e.g. upgrades without a release catalog) neither helps nor hurts.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{flow_ir::FlowIr, flow_meta::is_secret_answer_key, source_map::pointer_token};
+
+/// Flow `meta` key listing the people or teams responsible for the flow.
+pub const OWNERS_META_KEY: &str = "owners";
+
+/// Something a category counted against the pack.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct HealthFinding {
+    pub flow: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    pub message: String,
+}
+
+impl HealthFinding {
+    pub fn new(flow: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            flow: flow.into(),
+            node_id: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_node(mut self, node_id: impl Into<String>) -> Self {
+        self.node_id = Some(node_id.into());
+        self
+    }
+}
+
+/// One scored aspect of the pack (`lint`, `pins`, `upgrades`, `secrets`, `owners`, `format`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct HealthCategory {
+    pub name: String,
+    pub passed: usize,
+    pub total: usize,
+    /// 0-100, or `None` when the category was skipped.
+    pub score: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+    pub findings: Vec<HealthFinding>,
+}
+
+impl HealthCategory {
+    pub fn checked(
+        name: impl Into<String>,
+        passed: usize,
+        total: usize,
+        findings: Vec<HealthFinding>,
+    ) -> Self {
+        let score = if total == 0 {
+            100
+        } else {
+            (passed.min(total) * 100 / total) as u32
+        };
+        Self {
+            name: name.into(),
+            passed,
+            total,
+            score: Some(score),
+            skipped: None,
+            findings,
+        }
+    }
+
+    /// A category that could not be checked, with the reason.
+    pub fn skipped(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            passed: 0,
+            total: 0,
+            score: None,
+            skipped: Some(reason.into()),
+            findings: Vec::new(),
+        }
+    }
+}
+
+/// The consolidated result of `greentic-flow health`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct HealthScorecard {
+    pub flows: usize,
+    pub score: u32,
+    pub categories: Vec<HealthCategory>,
+}
+
+impl HealthScorecard {
+    pub fn new(flows: usize, categories: Vec<HealthCategory>) -> Self {
+        let scores: Vec<u32> = categories.iter().filter_map(|c| c.score).collect();
+        let score = if scores.is_empty() {
+            100
+        } else {
+            let sum: u32 = scores.iter().sum();
+            (sum + scores.len() as u32 / 2) / scores.len() as u32
+        };
+        Self {
+            flows,
+            score,
+            categories,
+        }
+    }
+}
+
+/// JSON pointers (under `/nodes/<id>/<operation>`) of payload strings stored under
+/// secret-looking keys (see [`is_secret_answer_key`]), paired with their node id.
+///
+/// Empty strings and templated values (`{{...}}`, `${...}`) are not literals and are skipped.
+pub fn find_secret_literals(flow: &FlowIr) -> Vec<(String, String)> {
+    let mut hits = Vec::new();
+    for node in flow.nodes.values() {
+        let pointer = format!(
+            "/nodes/{}/{}",
+            pointer_token(&node.id),
+            pointer_token(&node.operation)
+        );
+        collect_secret_literals(&node.payload, &pointer, false, &mut |pointer| {
+            hits.push((node.id.clone(), pointer))
+        });
+    }
+    hits
+}
+
+fn collect_secret_literals(
+    value: &Value,
+    pointer: &str,
+    secret_key: bool,
+    hit: &mut impl FnMut(String),
+) {
+    match value {
+        Value::String(text) if secret_key => {
+            let text = text.trim();
+            if !text.is_empty() && !text.contains("{{") && !text.contains("${") {
+                hit(pointer.to_string());
+            }
+        }
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_pointer = format!("{pointer}/{}", pointer_token(key));
+                collect_secret_literals(
+                    child,
+                    &child_pointer,
+                    secret_key || is_secret_answer_key(key),
+                    hit,
+                );
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                collect_secret_literals(child, &format!("{pointer}/{index}"), secret_key, hit);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Owners listed under `meta.owners`, as a string or a list of strings.
+pub fn flow_owners(flow: &FlowIr) -> Vec<String> {
+    match flow
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.get(OWNERS_META_KEY))
+    {
+        Some(Value::String(owner)) => vec![owner.clone()],
+        Some(Value::Array(owners)) => owners
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+    .into_iter()
+    .filter(|owner| !owner.trim().is_empty())
+    .collect()
+}
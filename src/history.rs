@@ -0,0 +1,180 @@
+//! Historical versions of a flow and its resolve sidecar, read from git.
+//!
+//! `at` is either a git revision (`HEAD~3`, a tag, a commit id) or an ISO 8601 timestamp
+//! (`2026-10-01`, `2026-10-01T14:30:00Z`), which selects the last commit on `HEAD` made at or
+//! before that time; a bare date covers the whole day in UTC. The flow and its sidecar are both
+//! read from that commit, so the pair is what was deployed together at that point.
+
+use anyhow::{Context, Result, anyhow, bail};
+use greentic_types::flow_resolve::sidecar_path_for_flow;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The commit a historical flow was read from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FlowRevision {
+    pub commit: String,
+    /// Committer time, Unix seconds.
+    pub committed_at: u64,
+    /// First line of the commit message.
+    pub summary: String,
+    /// Last commit up to `commit` that changed the flow file.
+    pub flow_commit: Option<String>,
+}
+
+/// A flow and its sidecar as they were at a [`FlowRevision`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct HistoricalFlow {
+    pub revision: FlowRevision,
+    pub yaml: String,
+    /// The resolve sidecar at the same commit, if it was committed.
+    pub sidecar: Option<Value>,
+}
+
+/// A node binding that differs between two sidecars.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SidecarChange {
+    pub node_id: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Whether `at` is a timestamp rather than a revision: it starts with a `YYYY-MM-DD` date.
+pub fn is_timestamp(at: &str) -> bool {
+    let bytes = at.as_bytes();
+    bytes.len() >= 10
+        && bytes[..10].iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+        && (bytes.len() == 10 || matches!(bytes[10], b'T' | b' '))
+}
+
+/// Read `flow_path` and its sidecar as they were at `at` (see the module docs).
+pub fn flow_at(flow_path: &Path, at: &str) -> Result<HistoricalFlow> {
+    let dir = match flow_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_name = flow_path
+        .file_name()
+        .ok_or_else(|| anyhow!("{} is not a file path", flow_path.display()))?
+        .to_string_lossy()
+        .into_owned();
+
+    let commit = if is_timestamp(at) {
+        // A bare date means the end of that day (UTC); git would otherwise use the current time
+        // of day.
+        let before = if at.len() == 10 {
+            format!("{at} 23:59:59 +0000")
+        } else {
+            at.to_string()
+        };
+        let commit = git(
+            &dir,
+            &["rev-list", "-1", &format!("--before={before}"), "HEAD"],
+        )?;
+        if commit.is_empty() {
+            bail!("no commit at or before {at}");
+        }
+        commit
+    } else {
+        git(
+            &dir,
+            &["rev-parse", "--verify", &format!("{at}^{{commit}}")],
+        )
+        .with_context(|| format!("unknown revision '{at}'"))?
+    };
+    let info = git(&dir, &["show", "-s", "--format=%H%x00%ct%x00%s", &commit])?;
+    let mut fields = info.splitn(3, '\0');
+    let (Some(commit), Some(committed_at), summary) = (fields.next(), fields.next(), fields.next())
+    else {
+        bail!("unexpected git output for {commit}");
+    };
+    let flow_commit = git(
+        &dir,
+        &["log", "-1", "--format=%H", commit, "--", &file_name],
+    )?;
+    let revision = FlowRevision {
+        commit: commit.to_string(),
+        committed_at: committed_at.parse().unwrap_or(0),
+        summary: summary.unwrap_or_default().to_string(),
+        flow_commit: (!flow_commit.is_empty()).then_some(flow_commit),
+    };
+
+    let yaml = git_raw(&dir, &["show", &format!("{commit}:./{file_name}")]).with_context(|| {
+        format!(
+            "{} did not exist at {}",
+            flow_path.display(),
+            short(&revision.commit)
+        )
+    })?;
+    let sidecar_name = sidecar_path_for_flow(Path::new(&file_name))
+        .to_string_lossy()
+        .into_owned();
+    let sidecar = match git_raw(&dir, &["show", &format!("{commit}:./{sidecar_name}")]) {
+        Ok(text) => Some(
+            serde_json::from_str(&text)
+                .with_context(|| format!("parse {sidecar_name} at {}", short(commit)))?,
+        ),
+        Err(_) => None,
+    };
+    Ok(HistoricalFlow {
+        revision,
+        yaml,
+        sidecar,
+    })
+}
+
+/// Node bindings added, removed or changed between two sidecars, by node id.
+pub fn diff_sidecar_nodes(before: Option<&Value>, after: Option<&Value>) -> Vec<SidecarChange> {
+    let nodes = |sidecar: Option<&Value>| {
+        sidecar
+            .and_then(|sidecar| sidecar.get("nodes"))
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let (before, after) = (nodes(before), nodes(after));
+    let ids: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    ids.into_iter()
+        .filter(|id| before.get(*id) != after.get(*id))
+        .map(|id| SidecarChange {
+            node_id: id.clone(),
+            before: before.get(id).cloned(),
+            after: after.get(id).cloned(),
+        })
+        .collect()
+}
+
+/// The first 12 characters of a commit id.
+pub fn short(commit: &str) -> &str {
+    commit.get(..12).unwrap_or(commit)
+}
+
+/// Run git in `dir` and return its trimmed stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    git_raw(dir, args).map(|out| out.trim().to_string())
+}
+
+fn git_raw(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).context("git output is not UTF-8")
+}
@@ -0,0 +1,123 @@
+//! Completion data for editor plugins: a flow's node ids and the payload properties each bound
+//! component accepts, taken from the component manifests.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    component_schema::{manifest_operation_names, resolve_input_schema},
+    error::Result,
+};
+
+pub const IDE_DATA_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdeData {
+    pub version: u32,
+    /// Flow id.
+    pub flow: String,
+    #[serde(rename = "type")]
+    pub flow_type: String,
+    /// Every node id, in flow order; completion candidates for routing `to:`.
+    pub node_ids: Vec<String>,
+    /// Keys a node may carry besides its operation (`routing`, `retry`, ...).
+    pub reserved_keys: Vec<String>,
+    pub nodes: Vec<IdeNode>,
+    /// Payload properties per component reference and operation.
+    pub components: BTreeMap<String, BTreeMap<String, Vec<IdeProperty>>>,
+    /// Nodes bound in the sidecar whose manifest could not be read.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unresolved: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdeNode {
+    pub id: String,
+    pub operation: String,
+    /// Key into [`IdeData::components`]; absent for nodes without a sidecar binding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdeProperty {
+    pub name: String,
+    /// JSON Schema type; a union is joined with `|`, e.g. `string|null`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Schema `description`, else `title`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(rename = "enum", default, skip_serializing_if = "Vec::is_empty")]
+    pub enum_values: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    /// Nested properties of an object-typed property.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<IdeProperty>,
+}
+
+/// Top-level properties of an object schema, with their nested properties.
+pub fn schema_properties(schema: &Value) -> Vec<IdeProperty> {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    properties
+        .iter()
+        .map(|(name, property)| IdeProperty {
+            name: name.clone(),
+            kind: schema_type(property),
+            description: property
+                .get("description")
+                .or_else(|| property.get("title"))
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            required: required.contains(&name.as_str()),
+            enum_values: property
+                .get("enum")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default(),
+            default: property.get("default").cloned(),
+            properties: schema_properties(property),
+        })
+        .collect()
+}
+
+fn schema_type(schema: &Value) -> Option<String> {
+    match schema.get("type")? {
+        Value::String(kind) => Some(kind.clone()),
+        Value::Array(kinds) => {
+            let kinds: Vec<&str> = kinds.iter().filter_map(Value::as_str).collect();
+            (!kinds.is_empty()).then(|| kinds.join("|"))
+        }
+        _ => None,
+    }
+}
+
+/// Payload properties of every operation a component manifest declares.
+pub fn manifest_operation_properties(
+    manifest_path: &Path,
+) -> Result<BTreeMap<String, Vec<IdeProperty>>> {
+    let mut operations = BTreeMap::new();
+    for operation in manifest_operation_names(manifest_path)? {
+        let resolution = resolve_input_schema(manifest_path, &operation)?;
+        let properties = resolution
+            .schema
+            .as_ref()
+            .map(schema_properties)
+            .unwrap_or_default();
+        operations.insert(operation, properties);
+    }
+    Ok(operations)
+}
@@ -1,7 +1,9 @@
 use crate::{
+    advisories::Advisory,
     error::{FlowError, FlowErrorLocation},
     flow_bundle::{FlowBundle, load_and_validate_bundle_with_flow},
-    lint::lint_builtin_rules,
+    lint::{LintDiagnostic, LintFix, LintSeverity, LintSuppression, lint_builtin_diagnostics},
+    source_map::SourceMap,
 };
 use serde::Serialize;
 
@@ -9,13 +11,23 @@ use serde::Serialize;
 pub struct JsonDiagnostic {
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<LintSeverity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub source_path: Option<String>,
+    /// Sidecar (`*.ygtc.resolve.json`) the finding concerns, for binding and contract checks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sidecar_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub col: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub json_pointer: Option<String>,
+    /// Structured fix for the finding, when its rule offers one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<LintFix>,
 }
 
 impl JsonDiagnostic {
@@ -29,23 +41,54 @@ impl JsonDiagnostic {
         } = location;
         JsonDiagnostic {
             message,
+            rule: None,
+            severity: None,
             source_path: source_path
                 .as_ref()
                 .map(|p| p.display().to_string())
                 .or(path),
+            sidecar_path: None,
             line,
             col,
             json_pointer,
+            fix: None,
         }
     }
 
     pub fn from_message(message: String, source_path: Option<String>) -> Self {
         JsonDiagnostic {
             message,
+            rule: None,
+            severity: None,
             source_path,
+            sidecar_path: None,
             line: None,
             col: None,
             json_pointer: None,
+            fix: None,
+        }
+    }
+
+    pub fn from_lint(diagnostic: LintDiagnostic, source_path: Option<String>) -> Self {
+        let message = diagnostic.to_string();
+        let LintDiagnostic {
+            rule,
+            severity,
+            path,
+            span,
+            fix,
+            ..
+        } = diagnostic;
+        JsonDiagnostic {
+            message,
+            rule: Some(rule),
+            severity: Some(severity),
+            source_path,
+            sidecar_path: None,
+            line: span.map(|s| s.line),
+            col: span.map(|s| s.col),
+            json_pointer: path,
+            fix,
         }
     }
 }
@@ -59,6 +102,32 @@ pub struct LintJsonOutput {
     pub hash_blake3: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub errors: Vec<JsonDiagnostic>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<JsonDiagnostic>,
+    /// Inline `greentic-lint: disable=...` suppressions found in the flow.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<LintSuppression>,
+    /// Upgrade advisories for pinned components, present when a release catalog was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub advisories: Option<Vec<Advisory>>,
+    /// What doctor did with each unused sidecar entry.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sidecar_prunes: Vec<SidecarPruneDecision>,
+}
+
+/// Doctor's decision on one sidecar entry whose node is no longer in the flow.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SidecarPruneDecision {
+    pub node_id: String,
+    /// Component reference the entry points at.
+    pub source: String,
+    /// When the node's component was first bound (Unix seconds, from `meta.greentic.components`),
+    /// if the flow still records it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added_at: Option<u64>,
+    pub pruned: bool,
+    /// `selected` / `not_selected` for `--prune-select`, `confirmed` / `declined` for prompts.
+    pub reason: String,
 }
 
 impl LintJsonOutput {
@@ -69,19 +138,51 @@ impl LintJsonOutput {
             hash_blake3: Some(hash),
             bundle: Some(bundle),
             errors: Vec::new(),
+            warnings: Vec::new(),
+            suppressions: Vec::new(),
+            advisories: None,
+            sidecar_prunes: Vec::new(),
         }
     }
 
     pub fn lint_failure(messages: Vec<String>, source_path: Option<String>) -> Self {
-        let errors = messages
+        Self::failure(
+            messages
+                .into_iter()
+                .map(|message| JsonDiagnostic::from_message(message, source_path.clone()))
+                .collect(),
+        )
+    }
+
+    /// Failure report carrying already-built diagnostics.
+    pub fn failure(errors: Vec<JsonDiagnostic>) -> Self {
+        LintJsonOutput {
+            ok: false,
+            bundle: None,
+            hash_blake3: None,
+            errors,
+            warnings: Vec::new(),
+            suppressions: Vec::new(),
+            advisories: None,
+            sidecar_prunes: Vec::new(),
+        }
+    }
+
+    /// Failure report carrying typed lint findings (rule, severity, JSON pointer).
+    pub fn lint_diagnostics(diagnostics: Vec<LintDiagnostic>, source_path: Option<String>) -> Self {
+        let errors = diagnostics
             .into_iter()
-            .map(|message| JsonDiagnostic::from_message(message, source_path.clone()))
+            .map(|diagnostic| JsonDiagnostic::from_lint(diagnostic, source_path.clone()))
             .collect();
         LintJsonOutput {
             ok: false,
             bundle: None,
             hash_blake3: None,
             errors,
+            warnings: Vec::new(),
+            suppressions: Vec::new(),
+            advisories: None,
+            sidecar_prunes: Vec::new(),
         }
     }
 
@@ -91,19 +192,274 @@ impl LintJsonOutput {
             bundle: None,
             hash_blake3: None,
             errors: flow_error_to_reports(err),
+            warnings: Vec::new(),
+            suppressions: Vec::new(),
+            advisories: None,
+            sidecar_prunes: Vec::new(),
         }
     }
 
+    /// Attach lint warnings (reported without failing the run).
+    pub fn with_warnings(
+        mut self,
+        warnings: Vec<LintDiagnostic>,
+        source_path: Option<String>,
+    ) -> Self {
+        self.warnings = warnings
+            .into_iter()
+            .map(|diagnostic| JsonDiagnostic::from_lint(diagnostic, source_path.clone()))
+            .collect();
+        self
+    }
+
+    pub fn with_suppressions(mut self, suppressions: Vec<LintSuppression>) -> Self {
+        self.suppressions = suppressions;
+        self
+    }
+
+    pub fn with_advisories(mut self, advisories: Option<Vec<Advisory>>) -> Self {
+        self.advisories = advisories;
+        self
+    }
+
+    pub fn with_sidecar_prunes(mut self, decisions: Vec<SidecarPruneDecision>) -> Self {
+        self.sidecar_prunes = decisions;
+        self
+    }
+
     pub fn into_string(self) -> String {
         serde_json::to_string(&self).expect("lint output serialization")
     }
 }
 
+/// `doctor --output json`: the result of every checked flow, in the order the flows were found.
+#[derive(Serialize, Clone, Debug)]
+pub struct DoctorJsonOutput {
+    pub ok: bool,
+    /// Paths of the flows that failed.
+    pub failed: Vec<String>,
+    pub flows: Vec<DoctorFlowJson>,
+}
+
+/// One flow in a [`DoctorJsonOutput`]: its path plus the payload `doctor --json` prints for it,
+/// without the bundle.
+#[derive(Serialize, Clone, Debug)]
+pub struct DoctorFlowJson {
+    pub path: String,
+    #[serde(flatten)]
+    pub result: LintJsonOutput,
+}
+
+impl DoctorJsonOutput {
+    pub fn new(flows: impl IntoIterator<Item = (String, LintJsonOutput)>) -> Self {
+        let flows: Vec<DoctorFlowJson> = flows
+            .into_iter()
+            .map(|(path, mut result)| {
+                result.bundle = None;
+                DoctorFlowJson { path, result }
+            })
+            .collect();
+        let failed: Vec<String> = flows
+            .iter()
+            .filter(|flow| !flow.result.ok)
+            .map(|flow| flow.path.clone())
+            .collect();
+        DoctorJsonOutput {
+            ok: failed.is_empty(),
+            failed,
+            flows,
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        serde_json::to_string(&self).expect("doctor output serialization")
+    }
+}
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+/// Rule id for findings that come from flow validation rather than a named lint rule.
+pub const SARIF_VALIDATION_RULE: &str = "flow_validation";
+
+/// `doctor --output sarif`: a SARIF 2.1.0 log with one run covering every checked flow, for
+/// code scanning tools.
+#[derive(Serialize, Clone, Debug)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct SarifRun {
+    tool: SarifTool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct SarifRule {
+    id: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    logical_locations: Vec<SarifLogicalLocation>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<usize>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SarifLogicalLocation {
+    fully_qualified_name: String,
+}
+
+impl SarifLog {
+    /// Build the log from each flow's path and result. Errors map to level `error` and warnings
+    /// to `warning` unless the finding carries its own severity; findings without a rule use
+    /// [`SARIF_VALIDATION_RULE`].
+    pub fn new(flows: impl IntoIterator<Item = (String, LintJsonOutput)>) -> Self {
+        let mut results = Vec::new();
+        for (path, output) in flows {
+            let findings = output
+                .errors
+                .into_iter()
+                .map(|diagnostic| (diagnostic, LintSeverity::Error))
+                .chain(
+                    output
+                        .warnings
+                        .into_iter()
+                        .map(|diagnostic| (diagnostic, LintSeverity::Warning)),
+                );
+            for (diagnostic, default_severity) in findings {
+                results.push(sarif_result(&path, diagnostic, default_severity));
+            }
+        }
+        let mut rule_ids: Vec<String> = results.iter().map(|r| r.rule_id.clone()).collect();
+        rule_ids.sort();
+        rule_ids.dedup();
+        SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "greentic-flow",
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: env!("CARGO_PKG_REPOSITORY"),
+                        rules: rule_ids.into_iter().map(|id| SarifRule { id }).collect(),
+                    },
+                },
+                results,
+            }],
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        serde_json::to_string(&self).expect("sarif serialization")
+    }
+}
+
+fn sarif_result(
+    flow_path: &str,
+    diagnostic: JsonDiagnostic,
+    default_severity: LintSeverity,
+) -> SarifResult {
+    let level = match diagnostic.severity.unwrap_or(default_severity) {
+        LintSeverity::Error => "error",
+        LintSeverity::Warning => "warning",
+    };
+    let uri = diagnostic
+        .source_path
+        .unwrap_or_else(|| flow_path.to_string())
+        .replace('\\', "/");
+    let uri = uri.strip_prefix("./").map(str::to_string).unwrap_or(uri);
+    SarifResult {
+        rule_id: diagnostic
+            .rule
+            .unwrap_or_else(|| SARIF_VALIDATION_RULE.to_string()),
+        level,
+        message: SarifMessage {
+            text: diagnostic.message,
+        },
+        locations: vec![SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation { uri },
+                region: diagnostic.line.map(|start_line| SarifRegion {
+                    start_line,
+                    start_column: diagnostic.col,
+                }),
+            },
+            logical_locations: diagnostic
+                .json_pointer
+                .map(|fully_qualified_name| SarifLogicalLocation {
+                    fully_qualified_name,
+                })
+                .into_iter()
+                .collect(),
+        }],
+    }
+}
+
 pub fn flow_error_to_reports(err: FlowError) -> Vec<JsonDiagnostic> {
     let display_message = err.to_string();
     match err {
         FlowError::Schema {
             details, location, ..
+        }
+        | FlowError::RuntimeIncompatible {
+            details, location, ..
         } => {
             if details.is_empty() {
                 vec![JsonDiagnostic::from_location(display_message, location)]
@@ -118,6 +474,7 @@ pub fn flow_error_to_reports(err: FlowError) -> Vec<JsonDiagnostic> {
         | FlowError::UnknownFlowType { location, .. }
         | FlowError::InvalidIdentifier { location, .. }
         | FlowError::NodeComponentShape { location, .. }
+        | FlowError::ReservedOperationKey { location, .. }
         | FlowError::BadComponentKey { location, .. }
         | FlowError::Routing { location, .. }
         | FlowError::MissingNode { location, .. }
@@ -131,11 +488,22 @@ pub fn flow_error_to_reports(err: FlowError) -> Vec<JsonDiagnostic> {
 pub fn lint_to_stdout_json(ygtc: &str) -> String {
     match load_and_validate_bundle_with_flow(ygtc, None) {
         Ok((bundle, flow)) => {
-            let lint_errors = lint_builtin_rules(&flow);
+            let spans = SourceMap::from_yaml(ygtc);
+            let (lint_errors, warnings): (Vec<_>, Vec<_>) = lint_builtin_diagnostics(&flow)
+                .into_iter()
+                .map(|mut diagnostic| {
+                    diagnostic.locate_in(&spans);
+                    diagnostic
+                })
+                .partition(|diagnostic| diagnostic.severity == LintSeverity::Error);
             if lint_errors.is_empty() {
-                LintJsonOutput::success(bundle).into_string()
+                LintJsonOutput::success(bundle)
+                    .with_warnings(warnings, None)
+                    .into_string()
             } else {
-                LintJsonOutput::lint_failure(lint_errors, None).into_string()
+                LintJsonOutput::lint_diagnostics(lint_errors, None)
+                    .with_warnings(warnings, None)
+                    .into_string()
             }
         }
         Err(err) => LintJsonOutput::error(err).into_string(),
@@ -5,46 +5,96 @@
 #![allow(clippy::result_large_err)]
 
 pub mod add_step;
+pub mod advisories;
+pub mod answer_provenance;
 pub mod answers;
+pub mod api_compat;
+pub mod builder;
+pub mod bundle_release;
+pub mod bundle_signature;
+pub mod cancel;
+pub mod cas;
 pub mod component_catalog;
+pub mod component_resolver;
 pub mod component_schema;
 pub mod component_setup;
+pub mod component_view;
 pub mod config_flow;
+pub mod config_store;
 pub mod contracts;
+pub mod convert_type;
+pub mod dist_cache;
+pub mod edit_plan;
+pub mod env_matrix;
 pub mod error;
+pub mod expiry;
+pub mod features;
 pub mod flow_bundle;
+pub mod flow_deps;
+pub mod flow_diff;
+pub mod flow_edit;
 pub mod flow_ir;
 pub mod flow_meta;
+pub mod health;
+pub mod history;
 pub mod i18n;
+pub mod ide_data;
 pub mod ir;
 pub mod json_output;
 pub mod lint;
 pub mod loader;
+pub mod lockfile;
+pub mod migrate;
+pub mod minimize;
+pub mod mirrors;
 pub mod model;
+pub mod node_shape;
+#[cfg(any(feature = "s3", feature = "gcs"))]
+pub mod object_store_resolver;
+pub mod output_mapping;
+pub mod pack_config;
 pub mod path_safety;
+pub mod presets;
+pub mod prompt_style;
 pub mod qa_runner;
 pub mod questions;
 pub mod questions_schema;
 pub mod registry;
+pub mod rename;
+pub mod replay;
+pub mod report;
+pub mod requirements;
 pub mod resolve;
 pub mod resolve_summary;
+pub mod runtime_capabilities;
+pub mod schema_diff;
+pub mod schema_import;
 pub mod schema_mode;
+pub mod schema_render;
 pub mod schema_validate;
+pub mod selftest;
+pub mod source_map;
 pub mod splice;
+pub mod state_keys;
 pub mod template;
+pub mod testing;
+pub mod trace;
 pub mod util;
 pub mod wizard;
 pub mod wizard_ops;
 pub mod wizard_state;
+pub mod workspace_index;
 
 pub use flow_bundle::{
-    ComponentPin, FlowBundle, NodeRef, blake3_hex, canonicalize_json, extract_component_pins,
-    load_and_validate_bundle, load_and_validate_bundle_with_flow,
+    ComponentPin, ComponentSource, ComponentSourceKind, FlowBundle, NodeRef, Pinning, blake3_hex,
+    canonicalize_json, extract_component_pins, extract_component_pins_with_sources,
+    load_and_validate_bundle, load_and_validate_bundle_for_runtime,
+    load_and_validate_bundle_with_flow,
 };
 pub use json_output::{JsonDiagnostic, LintJsonOutput, lint_to_stdout_json};
 pub use splice::{NEXT_NODE_PLACEHOLDER, splice_node_after};
 
-use crate::{error::Result, model::FlowDoc};
+use crate::{error::Result, flow_ir::StatusMatcher, model::FlowDoc};
 use greentic_types::{
     ComponentId, Flow, FlowComponentRef, FlowId, FlowKind, FlowMetadata, InputMapping, Node,
     NodeId, OutputMapping, Routing, TelemetryHints, flow::FlowHasher,
@@ -79,6 +129,7 @@ pub fn compile_flow(doc: FlowDoc) -> Result<Flow> {
             .or_insert_with(|| Value::String(entry));
     }
 
+    let reserved = model::reserved_node_keys();
     let mut nodes: IndexMap<NodeId, Node, FlowHasher> = IndexMap::default();
     for (node_id_str, node_doc) in doc.nodes.iter() {
         let node_id = NodeId::new(node_id_str.as_str()).map_err(|e| {
@@ -99,12 +150,25 @@ pub fn compile_flow(doc: FlowDoc) -> Result<Flow> {
                 sampling: t.sampling.clone(),
             })
             .unwrap_or_default();
-        // V2: single op key in raw.
+        // V2: single op key in raw, next to optional reserved keys (output, meta, ...).
         let mut op_key: Option<String> = None;
         let mut payload: Option<Value> = None;
-        for (k, v) in &node_doc.raw {
-            op_key = Some(k.clone());
-            payload = Some(v.clone());
+        let exec_pair = node_doc
+            .raw
+            .get("component.exec")
+            .zip(node_doc.raw.get("operation").and_then(Value::as_str));
+        if let Some((exec, op)) = exec_pair {
+            // Legacy `component.exec` + `operation` pair.
+            op_key = Some(op.to_string());
+            payload = Some(exec.clone());
+        } else {
+            for (k, v) in &node_doc.raw {
+                if op_key.is_some() && reserved.iter().any(|r| r == k) {
+                    continue;
+                }
+                op_key = Some(k.clone());
+                payload = Some(v.clone());
+            }
         }
         let output_mapping = node_doc
             .raw
@@ -117,7 +181,7 @@ pub fn compile_flow(doc: FlowDoc) -> Result<Flow> {
         })?;
         let is_builtin = matches!(operation.as_str(), "questions" | "template");
         let is_legacy = doc.schema_version.unwrap_or(1) < 2;
-        let (component_id, op_field) = if is_builtin || is_legacy {
+        let (component_id, op_field) = if (is_builtin || is_legacy) && exec_pair.is_none() {
             (operation.clone(), None)
         } else {
             ("component.exec".to_string(), Some(operation.clone()))
@@ -183,6 +247,9 @@ fn compile_routing(
     nodes: &IndexMap<String, crate::model::NodeDoc>,
     node_id: &str,
 ) -> Result<Routing> {
+    let runtime_raw = strip_route_docs(raw);
+    let raw = &runtime_raw;
+
     #[derive(serde::Deserialize)]
     struct RouteDoc {
         #[serde(default)]
@@ -307,11 +374,18 @@ fn compile_routing(
                     )),
                 }
             })?;
-            if let Some(status) = &route.status {
-                any_status = true;
-                on_status.insert(status.clone(), to_id);
-            } else {
-                default = Some(to_id);
+            match route.status.as_deref().map(StatusMatcher::parse) {
+                Some(StatusMatcher::Is(status)) => {
+                    any_status = true;
+                    on_status.insert(status.to_string(), to_id);
+                }
+                // Negations have no Branch equivalent; keep the routes as written.
+                Some(StatusMatcher::Not(_)) => return Ok(Routing::Custom(raw.clone())),
+                Some(StatusMatcher::Any) => {
+                    any_status = true;
+                    default = Some(to_id);
+                }
+                None => default = Some(to_id),
             }
         }
         if any_status {
@@ -328,6 +402,18 @@ fn compile_routing(
     Ok(Routing::Custom(raw.clone()))
 }
 
+/// Route `label`/`description` are documentation only; keep them out of the compiled flow.
+fn strip_route_docs(raw: &Value) -> Value {
+    let mut raw = raw.clone();
+    if let Some(routes) = raw.as_array_mut() {
+        for route in routes.iter_mut().filter_map(Value::as_object_mut) {
+            route.remove("label");
+            route.remove("description");
+        }
+    }
+    raw
+}
+
 fn resolve_entry(doc: &FlowDoc) -> Option<String> {
     if let Some(start) = &doc.start {
         return Some(start.clone());
@@ -1,22 +1,77 @@
-use crate::{ir::NodeKind, ir::classify_node_type, registry::AdapterCatalog};
+use super::{LintDiagnostic, LintSeverity, node_operation_key, node_pointer};
+use crate::{ir::NodeKind, ir::classify_node_type, model::FlowDoc, registry::AdapterCatalog};
 use greentic_types::Flow;
+use semver::{Version, VersionReq};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Adapter version ranges requested by a flow.
+///
+/// Flow-wide ranges come from `meta.adapters` (`{"<namespace>.<adapter>": "<range>"}`); a node's
+/// `meta.adapter_version` overrides the flow-wide range for that node's adapter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdapterVersionRequirements {
+    pub flow: BTreeMap<String, String>,
+    pub nodes: BTreeMap<String, String>,
+}
+
+impl AdapterVersionRequirements {
+    pub fn from_doc(doc: &FlowDoc) -> Self {
+        let flow = doc
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.get("adapters"))
+            .and_then(Value::as_object)
+            .map(|adapters| {
+                adapters
+                    .iter()
+                    .filter_map(|(adapter, range)| {
+                        range.as_str().map(|r| (adapter.clone(), r.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let nodes = doc
+            .nodes
+            .iter()
+            .filter_map(|(node_id, node)| {
+                node.raw
+                    .get("meta")
+                    .and_then(|meta| meta.get("adapter_version"))
+                    .and_then(Value::as_str)
+                    .map(|range| (node_id.clone(), range.to_string()))
+            })
+            .collect();
+        Self { flow, nodes }
+    }
+
+    fn range_for(&self, node_id: &str, adapter: &str) -> Option<(&str, String)> {
+        if let Some(range) = self.nodes.get(node_id) {
+            return Some((range, format!("/nodes/{node_id}/meta/adapter_version")));
+        }
+        self.flow
+            .get(adapter)
+            .map(|range| (range.as_str(), format!("/meta/adapters/{adapter}")))
+    }
+}
 
 #[derive(Clone, Debug, Default)]
 pub struct AdapterResolvableRule;
 
 impl AdapterResolvableRule {
-    pub fn check(flow: &Flow, catalog: &AdapterCatalog) -> Vec<String> {
+    pub fn check(flow: &Flow, catalog: &AdapterCatalog) -> Vec<LintDiagnostic> {
+        Self::check_with_requirements(flow, catalog, &AdapterVersionRequirements::default())
+    }
+
+    /// Like [`Self::check`], and also verify resolvable adapters satisfy the requested ranges.
+    pub fn check_with_requirements(
+        flow: &Flow,
+        catalog: &AdapterCatalog,
+        requirements: &AdapterVersionRequirements,
+    ) -> Vec<LintDiagnostic> {
         let mut errors = Vec::new();
         for (idx, (node_id, node)) in flow.nodes.iter().enumerate() {
-            let comp_str = if let Some(op) = &node.component.operation {
-                if node.component.id.as_str() == "component.exec" {
-                    op.clone()
-                } else {
-                    format!("{}.{}", node.component.id, op)
-                }
-            } else {
-                node.component.id.to_string()
-            };
+            let comp_str = node_operation_key(node);
             match classify_node_type(&comp_str) {
                 NodeKind::Adapter {
                     namespace,
@@ -24,10 +79,25 @@ impl AdapterResolvableRule {
                     operation,
                 } => {
                     if !catalog.contains(&namespace, &adapter, &operation) {
-                        errors.push(format!(
-                            "adapter_resolvable: node #{idx} ('{node_id}') component '{}' missing adapter '{}.{}' operation '{}'",
-                            comp_str, namespace, adapter, operation
-                        ));
+                        errors.push(
+                            LintDiagnostic::error(
+                                "adapter_resolvable",
+                                format!(
+                                    "node #{idx} ('{node_id}') component '{}' missing adapter '{}.{}' operation '{}'",
+                                    comp_str, namespace, adapter, operation
+                                ),
+                            )
+                            .with_path(node_pointer(node_id.as_str(), node)),
+                        );
+                        continue;
+                    }
+                    let adapter_key = format!("{namespace}.{adapter}");
+                    if let Some((range, path)) =
+                        requirements.range_for(node_id.as_str(), &adapter_key)
+                        && let Some(message) =
+                            check_version(node_id.as_str(), &adapter_key, range, catalog)
+                    {
+                        errors.push(message.with_path(path));
                     }
                 }
                 NodeKind::Builtin(_) => {}
@@ -36,3 +106,65 @@ impl AdapterResolvableRule {
         errors
     }
 }
+
+fn check_version(
+    node_id: &str,
+    adapter: &str,
+    range: &str,
+    catalog: &AdapterCatalog,
+) -> Option<LintDiagnostic> {
+    let req = match VersionReq::parse(range) {
+        Ok(req) => req,
+        Err(err) => {
+            return Some(LintDiagnostic::error(
+                "adapter_version",
+                format!(
+                    "node '{node_id}' has invalid version range '{range}' for adapter '{adapter}': {err}"
+                ),
+            ));
+        }
+    };
+    let available = catalog.adapter_versions(adapter);
+    if available.is_empty() {
+        return Some(
+            LintDiagnostic::error(
+                "adapter_version",
+                format!(
+                    "node '{node_id}' requires adapter '{adapter}' {range} but the catalog lists no versions for it"
+                ),
+            )
+            .with_severity(LintSeverity::Warning),
+        );
+    }
+    if available.iter().any(|v| req.matches(v)) {
+        return None;
+    }
+    let listed: Vec<String> = available.iter().map(Version::to_string).collect();
+    let mut message = format!(
+        "node '{node_id}' requires adapter '{adapter}' {range} but the catalog only has {}",
+        listed.join(", ")
+    );
+    if let Some(nearest) = nearest_version(&req, &available) {
+        message.push_str(&format!(
+            "; nearest available version is {nearest} (widen the range or update the catalog)"
+        ));
+    }
+    Some(LintDiagnostic::error("adapter_version", message))
+}
+
+/// The available version closest to the lower bound of `req`, preferring the same major line.
+fn nearest_version<'a>(req: &VersionReq, available: &'a [Version]) -> Option<&'a Version> {
+    let target = req
+        .comparators
+        .first()
+        .map(|c| (c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0)))
+        .unwrap_or_default();
+    available.iter().min_by_key(|v| {
+        (
+            v.major.abs_diff(target.0),
+            v.minor.abs_diff(target.1),
+            v.patch.abs_diff(target.2),
+            std::cmp::Reverse((*v).clone()),
+        )
+    })
+}
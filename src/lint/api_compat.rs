@@ -0,0 +1,97 @@
+use super::LintDiagnostic;
+use crate::{
+    api_compat::{ApiSurface, breaking_api_changes, is_breaking_bump},
+    model::FlowDoc,
+};
+
+/// Compares `http` flows with their last released version (see [`crate::api_compat`]).
+///
+/// Released surfaces are matched to flows by id; a flow without one is only checked for a valid
+/// `meta.api_version`. Each breaking change is an error unless `meta.api_version` was bumped past
+/// the released version's compatibility range. A flow whose hash equals the release is skipped.
+#[derive(Clone, Debug, Default)]
+pub struct ApiCompatRule {
+    released: Vec<ApiSurface>,
+}
+
+impl ApiCompatRule {
+    pub fn new(released: impl IntoIterator<Item = ApiSurface>) -> Self {
+        Self {
+            released: released.into_iter().collect(),
+        }
+    }
+
+    pub fn released(&self) -> &[ApiSurface] {
+        &self.released
+    }
+
+    pub fn check(&self, doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        if doc.flow_type != "http" {
+            return Vec::new();
+        }
+        let current = ApiSurface::from_doc(doc);
+        let version = match current.version() {
+            Some(Err(message)) => {
+                return vec![
+                    LintDiagnostic::error("api_compat", message).with_path("/meta/api_version"),
+                ];
+            }
+            Some(Ok(version)) => Some(version),
+            None => None,
+        };
+        let Some(released) = self
+            .released
+            .iter()
+            .find(|released| released.flow_id == current.flow_id)
+        else {
+            return Vec::new();
+        };
+        if released.hash_blake3 == current.hash_blake3 {
+            return Vec::new();
+        }
+        let released_version = released.version().and_then(Result::ok);
+        let release = match &released_version {
+            Some(version) => format!("released api_version {version}"),
+            None => format!(
+                "released bundle {}",
+                released
+                    .hash_blake3
+                    .get(..12)
+                    .unwrap_or(&released.hash_blake3)
+            ),
+        };
+
+        if let (Some(old), Some(new)) = (&released_version, &version)
+            && new < old
+        {
+            return vec![
+                LintDiagnostic::error(
+                    "api_compat",
+                    format!("api_version {new} is older than the {release}"),
+                )
+                .with_path("/meta/api_version"),
+            ];
+        }
+        let remedy = match (&released_version, &version) {
+            (Some(old), Some(new)) if is_breaking_bump(old, new) => return Vec::new(),
+            (Some(old), Some(new)) => {
+                format!("bump meta.api_version past {old} (now {new})")
+            }
+            (None, Some(_)) => return Vec::new(),
+            (_, None) => "set meta.api_version to a new major version".to_string(),
+        };
+        breaking_api_changes(released, &current)
+            .into_iter()
+            .map(|change| {
+                LintDiagnostic::error(
+                    "api_compat",
+                    format!(
+                        "breaking change since the {release}: {}; {remedy}",
+                        change.message
+                    ),
+                )
+                .with_path(change.path)
+            })
+            .collect()
+    }
+}
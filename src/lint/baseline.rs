@@ -0,0 +1,150 @@
+use super::LintDiagnostic;
+use crate::error::{FlowError, FlowErrorLocation, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Component, Path},
+};
+
+/// Findings recorded by `doctor --write-baseline`; later runs suppress them and fail only on
+/// new ones.
+///
+/// A finding is matched on its flow, rule, JSON pointer and message. Each recorded finding
+/// suppresses one occurrence, so a rule firing more often than recorded still fails.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintBaseline {
+    pub version: u32,
+    #[serde(default)]
+    pub findings: Vec<BaselineFinding>,
+}
+
+/// One suppressed finding. `flow` is relative to the baseline file's directory.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineFinding {
+    pub flow: String,
+    pub rule: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub message: String,
+}
+
+impl BaselineFinding {
+    fn matches(&self, flow: &str, diagnostic: &LintDiagnostic) -> bool {
+        self.flow == flow
+            && self.rule == diagnostic.rule
+            && self.path == diagnostic.path
+            && self.message == diagnostic.message
+    }
+}
+
+impl Default for LintBaseline {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            findings: Vec::new(),
+        }
+    }
+}
+
+impl LintBaseline {
+    pub const VERSION: u32 = 1;
+
+    pub fn from_json_str(text: &str) -> Result<Self> {
+        let baseline: Self = serde_json::from_str(text).map_err(|e| FlowError::Internal {
+            message: format!("invalid lint baseline: {e}"),
+            location: FlowErrorLocation::at_path("baseline".to_string()),
+        })?;
+        if baseline.version != Self::VERSION {
+            return Err(FlowError::Internal {
+                message: format!(
+                    "unsupported lint baseline version {} (expected {})",
+                    baseline.version,
+                    Self::VERSION
+                ),
+                location: FlowErrorLocation::at_path("baseline.version".to_string()),
+            });
+        }
+        Ok(baseline)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| FlowError::Internal {
+            message: format!("read {}: {e}", path.display()),
+            location: FlowErrorLocation::at_path(path.display().to_string())
+                .with_source_path(Some(path)),
+        })?;
+        Self::from_json_str(&text).map_err(|err| match err {
+            FlowError::Internal { message, .. } => FlowError::Internal {
+                message: format!("{message} ({})", path.display()),
+                location: FlowErrorLocation::at_path(path.display().to_string())
+                    .with_source_path(Some(path)),
+            },
+            other => other,
+        })
+    }
+
+    /// Pretty JSON with findings sorted, so rewriting an unchanged baseline is a no-op.
+    pub fn to_json_string(&self) -> String {
+        let mut sorted = self.clone();
+        sorted.findings.sort();
+        let mut text = serde_json::to_string_pretty(&sorted).expect("baseline serializes");
+        text.push('\n');
+        text
+    }
+
+    /// Record every diagnostic reported for `flow`.
+    pub fn record(&mut self, flow: &str, diagnostics: &[LintDiagnostic]) {
+        self.findings
+            .extend(diagnostics.iter().map(|diagnostic| BaselineFinding {
+                flow: flow.to_string(),
+                rule: diagnostic.rule.clone(),
+                path: diagnostic.path.clone(),
+                message: diagnostic.message.clone(),
+            }));
+    }
+
+    /// Split `diagnostics` into the ones not covered by the baseline and the number suppressed.
+    pub fn suppress(
+        &self,
+        flow: &str,
+        diagnostics: Vec<LintDiagnostic>,
+    ) -> (Vec<LintDiagnostic>, usize) {
+        let mut unused: Vec<&BaselineFinding> =
+            self.findings.iter().filter(|f| f.flow == flow).collect();
+        let mut kept = Vec::new();
+        let mut suppressed = 0;
+        for diagnostic in diagnostics {
+            match unused.iter().position(|f| f.matches(flow, &diagnostic)) {
+                Some(idx) => {
+                    unused.swap_remove(idx);
+                    suppressed += 1;
+                }
+                None => kept.push(diagnostic),
+            }
+        }
+        (kept, suppressed)
+    }
+}
+
+/// Key a flow path in a baseline stored at `baseline_path`: relative to the baseline's
+/// directory when possible, with `/` separators.
+pub fn baseline_flow_key(baseline_path: &Path, flow_path: &Path) -> String {
+    let root = baseline_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let absolute = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let flow = absolute(flow_path);
+    let relative = flow.strip_prefix(absolute(root)).unwrap_or(&flow);
+    relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            Component::Prefix(prefix) => Some(prefix.as_os_str().to_string_lossy().into_owned()),
+            Component::RootDir => Some(String::new()),
+            Component::CurDir => None,
+            Component::ParentDir => Some("..".to_string()),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
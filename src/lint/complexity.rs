@@ -0,0 +1,116 @@
+use serde::Deserialize;
+
+use super::{LintDiagnostic, diagnostic::pointer_token};
+use crate::{
+    flow_ir::{FlowIr, analysis::FlowAnalysis},
+    model::FlowDoc,
+};
+
+/// Size limits checked by [`ComplexityRule`]; `[complexity]` in `.greentic-lint.toml` overrides
+/// them, and keys it leaves out keep their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComplexityLimits {
+    /// Nodes in the flow.
+    pub max_nodes: usize,
+    /// Nodes on the longest route from an entrypoint (a loop counts once).
+    pub max_depth: usize,
+    /// Distinct nodes a single node routes to.
+    pub max_fan_out: usize,
+}
+
+impl Default for ComplexityLimits {
+    fn default() -> Self {
+        Self {
+            max_nodes: 150,
+            max_depth: 50,
+            max_fan_out: 20,
+        }
+    }
+}
+
+/// Reports flows that outgrew [`ComplexityLimits`]: too many nodes, a route too deep, or a node
+/// routing to too many others. Such flows are hard to review and are better split into smaller
+/// flows.
+#[derive(Clone, Debug, Default)]
+pub struct ComplexityRule {
+    limits: ComplexityLimits,
+}
+
+impl ComplexityRule {
+    pub fn new(limits: ComplexityLimits) -> Self {
+        Self { limits }
+    }
+
+    pub fn limits(&self) -> ComplexityLimits {
+        self.limits
+    }
+
+    pub fn check(&self, doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let limits = &self.limits;
+        let Ok(flow) = FlowIr::from_doc(doc.clone()) else {
+            return Vec::new();
+        };
+        let analysis = FlowAnalysis::new(&flow);
+        let mut diagnostics = Vec::new();
+
+        if flow.nodes.len() > limits.max_nodes {
+            diagnostics.push(
+                LintDiagnostic::error(
+                    "complexity",
+                    format!(
+                        "flow has {} nodes (limit {}); split it into smaller flows",
+                        flow.nodes.len(),
+                        limits.max_nodes
+                    ),
+                )
+                .with_path("/nodes"),
+            );
+        }
+
+        let path = analysis.longest_path();
+        if path.len() > limits.max_depth {
+            let last = path.last().expect("path is longer than the limit");
+            diagnostics.push(
+                LintDiagnostic::error(
+                    "complexity",
+                    format!(
+                        "longest route has {} nodes (limit {}): {}",
+                        path.len(),
+                        limits.max_depth,
+                        abbreviate_path(&path)
+                    ),
+                )
+                .with_path(format!("/nodes/{}", pointer_token(last))),
+            );
+        }
+
+        for degree in analysis.degrees() {
+            if degree.fan_out > limits.max_fan_out {
+                diagnostics.push(
+                    LintDiagnostic::error(
+                        "complexity",
+                        format!(
+                            "node '{}' routes to {} nodes (limit {})",
+                            degree.node_id, degree.fan_out, limits.max_fan_out
+                        ),
+                    )
+                    .with_path(format!("/nodes/{}/routing", pointer_token(&degree.node_id))),
+                );
+            }
+        }
+        diagnostics
+    }
+}
+
+/// `a -> b -> c`, keeping the first and last three nodes of long paths.
+fn abbreviate_path(path: &[String]) -> String {
+    if path.len() <= 7 {
+        return path.join(" -> ");
+    }
+    format!(
+        "{} -> ... -> {}",
+        path[..3].join(" -> "),
+        path[path.len() - 3..].join(" -> ")
+    )
+}
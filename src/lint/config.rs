@@ -0,0 +1,171 @@
+//! Project lint settings from `.greentic-lint.toml`, found next to a flow or in an ancestor.
+//!
+//! ```toml
+//! [rules]
+//! unreachable_node = "off"
+//! routing_shorthand = "error"
+//!
+//! [[suppress]]
+//! rule = "placeholder_values"
+//! flow = "legacy/onboarding.ygtc"
+//! node = "ask_name"
+//! reason = "legacy copy, rewritten in Q3"
+//!
+//! [complexity]
+//! max_nodes = 80
+//! ```
+//!
+//! `[rules]` sets a rule to `off`, `warning` or `error` everywhere. A `[[suppress]]` entry drops
+//! one rule's findings on the flows whose path ends with `flow` (every flow when omitted),
+//! limited to `node` when given. `[complexity]` sets the limits of the `complexity` rule (see
+//! [`ComplexityLimits`]).
+
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{ComplexityLimits, LintDiagnostic, LintSeverity, diagnostic::pointer_token};
+use crate::error::{FlowError, FlowErrorLocation, Result};
+
+/// File name of the project lint settings.
+pub const LINT_CONFIG_FILE: &str = ".greentic-lint.toml";
+
+/// Level a rule is set to under `[rules]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    #[serde(alias = "warn")]
+    Warning,
+    Error,
+}
+
+/// A `[[suppress]]` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigSuppression {
+    pub rule: String,
+    /// Flow path suffix, compared component by component (`legacy/onboarding.ygtc`).
+    pub flow: Option<PathBuf>,
+    pub node: Option<String>,
+    pub reason: String,
+}
+
+impl ConfigSuppression {
+    fn covers(&self, flow_path: Option<&Path>, diagnostic: &LintDiagnostic) -> bool {
+        if !self.rule.eq_ignore_ascii_case(&diagnostic.rule) {
+            return false;
+        }
+        if let Some(flow) = &self.flow
+            && !flow_path.is_some_and(|path| path.ends_with(flow))
+        {
+            return false;
+        }
+        let Some(node) = &self.node else {
+            return true;
+        };
+        let node_path = format!("/nodes/{}", pointer_token(node));
+        diagnostic.path.as_deref().is_some_and(|path| {
+            path.strip_prefix(&node_path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+/// Parsed `.greentic-lint.toml`; the default changes nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintConfig {
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleLevel>,
+    #[serde(default)]
+    pub suppress: Vec<ConfigSuppression>,
+    pub complexity: Option<ComplexityLimits>,
+}
+
+impl LintConfig {
+    pub fn from_toml_str(text: &str) -> Result<Self> {
+        let config: Self = toml::from_str(text).map_err(|e| FlowError::Internal {
+            message: format!("invalid {LINT_CONFIG_FILE}: {}", e.message()),
+            location: FlowErrorLocation::at_path(LINT_CONFIG_FILE.to_string()),
+        })?;
+        if let Some(entry) = config.suppress.iter().find(|s| s.reason.trim().is_empty()) {
+            return Err(FlowError::Internal {
+                message: format!(
+                    "invalid {LINT_CONFIG_FILE}: suppression of '{}' needs a reason",
+                    entry.rule
+                ),
+                location: FlowErrorLocation::at_path(LINT_CONFIG_FILE.to_string()),
+            });
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| FlowError::Internal {
+            message: format!("read {}: {e}", path.display()),
+            location: FlowErrorLocation::at_path(path.display().to_string())
+                .with_source_path(Some(path)),
+        })?;
+        Self::from_toml_str(&text).map_err(|err| match err {
+            FlowError::Internal { message, .. } => FlowError::Internal {
+                message: format!("{message} ({})", path.display()),
+                location: FlowErrorLocation::at_path(path.display().to_string())
+                    .with_source_path(Some(path)),
+            },
+            other => other,
+        })
+    }
+
+    /// Find the nearest `.greentic-lint.toml` in `start` or one of its ancestors.
+    pub fn discover(start: &Path) -> Result<Option<(PathBuf, Self)>> {
+        for dir in start.ancestors() {
+            let candidate = dir.join(LINT_CONFIG_FILE);
+            if candidate.is_file() {
+                let config = Self::load(&candidate)?;
+                return Ok(Some((candidate, config)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Level set for `rule` under `[rules]`, if any.
+    pub fn rule_level(&self, rule: &str) -> Option<RuleLevel> {
+        self.rules
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(rule))
+            .map(|(_, level)| *level)
+    }
+
+    /// Apply rule levels and suppressions to the findings for the flow at `flow_path`; with no
+    /// path, suppressions scoped to a flow do not apply. Returns the kept findings and the
+    /// number dropped.
+    pub fn apply(
+        &self,
+        flow_path: Option<&Path>,
+        diagnostics: Vec<LintDiagnostic>,
+    ) -> (Vec<LintDiagnostic>, usize) {
+        let total = diagnostics.len();
+        let kept: Vec<LintDiagnostic> = diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                match self.rule_level(&diagnostic.rule) {
+                    Some(RuleLevel::Off) => return None,
+                    Some(RuleLevel::Warning) => diagnostic.severity = LintSeverity::Warning,
+                    Some(RuleLevel::Error) => diagnostic.severity = LintSeverity::Error,
+                    None => {}
+                }
+                let suppressed = self
+                    .suppress
+                    .iter()
+                    .any(|entry| entry.covers(flow_path, &diagnostic));
+                (!suppressed).then_some(diagnostic)
+            })
+            .collect();
+        let dropped = total - kept.len();
+        (kept, dropped)
+    }
+}
@@ -0,0 +1,91 @@
+use super::{FixApplicability, FixEdit, LintDiagnostic, LintFix, diagnostic::pointer_token};
+use crate::{
+    flow_ir::{FlowIr, Route, status_route_conflicts},
+    model::FlowDoc,
+};
+use std::collections::BTreeMap;
+
+/// Flags routes that repeat an earlier route of the same node, or that can never fire because
+/// earlier status routes already match every status they match.
+///
+/// The loader rejects unreachable status routes, so in practice this rule catches documents
+/// built in code and duplicated routes without a status (fanning out to the same node twice).
+/// Each finding points at the route index (`/nodes/<id>/routing/<index>`). The attached fix drops
+/// the reported routes of the node; it is machine-applicable when none of them can fire.
+#[derive(Clone, Debug, Default)]
+pub struct ConflictingRoutesRule;
+
+impl ConflictingRoutesRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let Ok(flow) = FlowIr::from_doc(doc.clone()) else {
+            return Vec::new();
+        };
+        let mut diagnostics = Vec::new();
+        for (node_id, node) in &flow.nodes {
+            // Route index -> (message, whether the route can still fire).
+            let mut problems: BTreeMap<usize, (String, bool)> = BTreeMap::new();
+            for (idx, route) in node.routing.iter().enumerate() {
+                if let Some(first) = node.routing[..idx]
+                    .iter()
+                    .position(|earlier| same_route(earlier, route))
+                {
+                    problems.insert(
+                        idx,
+                        (
+                            format!(
+                                "route {idx} duplicates route {first} (same status and target)"
+                            ),
+                            route.status.is_none(),
+                        ),
+                    );
+                }
+            }
+            for (idx, message) in
+                status_route_conflicts(node.routing.iter().map(|route| route.status.as_deref()))
+            {
+                problems.entry(idx).or_insert((message, false));
+            }
+            if problems.is_empty() {
+                continue;
+            }
+
+            let kept: Vec<Route> = node
+                .routing
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| !problems.contains_key(idx))
+                .map(|(_, route)| route.clone())
+                .collect();
+            let applicability = if problems.values().any(|(_, fires)| *fires) {
+                FixApplicability::MaybeIncorrect
+            } else {
+                FixApplicability::MachineApplicable
+            };
+            let removed: Vec<String> = problems.keys().map(ToString::to_string).collect();
+            let fix = LintFix::new(
+                format!("remove route {} of node '{node_id}'", removed.join(", ")),
+                applicability,
+                vec![FixEdit::SetRouting {
+                    node_id: node_id.clone(),
+                    routing: kept,
+                }],
+            );
+            for (idx, (message, _)) in problems {
+                diagnostics.push(
+                    LintDiagnostic::error(
+                        "conflicting_routes",
+                        format!("node '{node_id}': {message}"),
+                    )
+                    .with_path(format!("/nodes/{}/routing/{idx}", pointer_token(node_id)))
+                    .with_fix(fix.clone()),
+                );
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Same status and same destination; labels and descriptions do not matter.
+fn same_route(a: &Route, b: &Route) -> bool {
+    a.status == b.status && a.to == b.to && a.out == b.out && a.reply == b.reply
+}
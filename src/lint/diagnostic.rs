@@ -0,0 +1,82 @@
+use super::LintFix;
+pub(crate) use crate::source_map::pointer_token;
+use crate::source_map::{SourceMap, SourceSpan};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How strongly a lint finding should be treated by callers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+}
+
+/// 1-based source position of a finding, when the caller can map it back to the flow file.
+pub type LintSpan = SourceSpan;
+
+/// A single lint finding.
+///
+/// `Display` renders the historical `"<rule>: <message>"` string, so string-based consumers
+/// keep seeing the same text.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LintDiagnostic {
+    /// Rule identifier, e.g. `start_node_exists`.
+    pub rule: String,
+    pub severity: LintSeverity,
+    /// Human-readable message without the rule prefix.
+    pub message: String,
+    /// JSON pointer into the flow document (e.g. `/nodes/entry/qa.process/prompt`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<LintSpan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<LintFix>,
+}
+
+impl LintDiagnostic {
+    pub fn error(rule: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            rule: rule.into(),
+            severity: LintSeverity::Error,
+            message: message.into(),
+            path: None,
+            span: None,
+            fix: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn with_severity(mut self, severity: LintSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_span(mut self, span: LintSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_fix(mut self, fix: LintFix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
+    /// Fill in `span` from the finding's `path`, unless it already has one.
+    pub fn locate_in(&mut self, spans: &SourceMap) {
+        if self.span.is_none() {
+            self.span = self.path.as_deref().and_then(|path| spans.locate(path));
+        }
+    }
+}
+
+impl fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.rule, self.message)
+    }
+}
@@ -0,0 +1,71 @@
+use super::{FixApplicability, FixEdit, LintDiagnostic, LintFix, diagnostic::pointer_token};
+use crate::model::FlowDoc;
+
+/// Flags named entrypoints whose target node does not exist.
+///
+/// When exactly one node id is the same ignoring case, the attached fix retargets to it and is
+/// machine-applicable. Otherwise the fix needs review: it retargets to the only node id at most
+/// two edits away (short ids are often that close by accident), or else to the start node.
+#[derive(Clone, Debug, Default)]
+pub struct EntrypointTargetsRule;
+
+impl EntrypointTargetsRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let mut diagnostics = Vec::new();
+        for (name, target) in &doc.entrypoints {
+            let Some(target) = target.as_str() else {
+                continue;
+            };
+            if doc.nodes.contains_key(target) {
+                continue;
+            }
+            let mut diagnostic = LintDiagnostic::error(
+                "entrypoint_targets",
+                format!("entrypoint '{name}' targets missing node '{target}'"),
+            )
+            .with_path(format!("/entrypoints/{}", pointer_token(name)));
+            let fix = match single_match(doc, |id| target.eq_ignore_ascii_case(id)) {
+                Some(id) => Some((id.clone(), FixApplicability::MachineApplicable)),
+                None => single_match(doc, |id| edit_distance(target, id) <= 2)
+                    .or(doc
+                        .start
+                        .as_ref()
+                        .filter(|start| doc.nodes.contains_key(start.as_str())))
+                    .map(|id| (id.clone(), FixApplicability::MaybeIncorrect)),
+            };
+            if let Some((retarget, applicability)) = fix {
+                diagnostic = diagnostic.with_fix(LintFix::new(
+                    format!("retarget entrypoint '{name}' to '{retarget}'"),
+                    applicability,
+                    vec![FixEdit::RetargetEntrypoint {
+                        name: name.clone(),
+                        target: retarget,
+                    }],
+                ));
+            }
+            diagnostics.push(diagnostic);
+        }
+        diagnostics
+    }
+}
+
+/// The node id matching `matches`, when exactly one does.
+fn single_match(doc: &FlowDoc, matches: impl Fn(&str) -> bool) -> Option<&String> {
+    let mut found = doc.nodes.keys().filter(|id| matches(id));
+    let first = found.next()?;
+    found.next().is_none().then_some(first)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            row.push(substitute.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
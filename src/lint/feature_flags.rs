@@ -0,0 +1,42 @@
+use super::{LintDiagnostic, LintSeverity, diagnostic::pointer_token};
+use crate::{
+    features::{FEATURE_NODE_KEY, declared_features},
+    model::FlowDoc,
+};
+
+/// Warns about nodes guarded by a feature flag that `meta.features` does not declare; such a
+/// node is dropped whenever the flow is rendered, usually because of a typo in the flag name.
+#[derive(Clone, Debug, Default)]
+pub struct FeatureFlagsRule;
+
+impl FeatureFlagsRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let declared = declared_features(doc.meta.as_ref())
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let mut diagnostics = Vec::new();
+        for (node_id, node) in &doc.nodes {
+            let Some(feature) = node.raw.get(FEATURE_NODE_KEY).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            if declared.iter().any(|name| name == feature) {
+                continue;
+            }
+            diagnostics.push(
+                LintDiagnostic::error(
+                    "undefined_feature",
+                    format!(
+                        "node '{node_id}' is guarded by feature '{feature}', which meta.features does not declare"
+                    ),
+                )
+                .with_severity(LintSeverity::Warning)
+                .with_path(format!(
+                    "/nodes/{}/{FEATURE_NODE_KEY}",
+                    pointer_token(node_id)
+                )),
+            );
+        }
+        diagnostics
+    }
+}
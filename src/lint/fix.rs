@@ -0,0 +1,110 @@
+use super::LintDiagnostic;
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_ir::{FlowIr, Route},
+};
+use serde::Serialize;
+use std::fmt;
+
+/// Whether a fix can be applied without a human checking it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FixApplicability {
+    /// Preserves the flow's meaning; `doctor --fix` applies it.
+    MachineApplicable,
+    /// A best guess that should be reviewed; only offered, never applied automatically.
+    MaybeIncorrect,
+}
+
+/// One edit on the flow IR.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FixEdit {
+    /// Replace a node's routing.
+    SetRouting {
+        node_id: String,
+        routing: Vec<Route>,
+    },
+    /// Point an entrypoint at another node.
+    RetargetEntrypoint { name: String, target: String },
+    /// Rename a node and rewrite every reference to it (see [`FlowIr::rename_node`]).
+    RenameNode { from: String, to: String },
+}
+
+/// A structured fix attached to a [`LintDiagnostic`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LintFix {
+    pub description: String,
+    pub applicability: FixApplicability,
+    pub edits: Vec<FixEdit>,
+}
+
+impl LintFix {
+    pub fn new(
+        description: impl Into<String>,
+        applicability: FixApplicability,
+        edits: Vec<FixEdit>,
+    ) -> Self {
+        Self {
+            description: description.into(),
+            applicability,
+            edits,
+        }
+    }
+
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == FixApplicability::MachineApplicable
+    }
+
+    pub fn apply(&self, flow: &mut FlowIr) -> Result<()> {
+        for edit in &self.edits {
+            match edit {
+                FixEdit::SetRouting { node_id, routing } => {
+                    if !flow.nodes.contains_key(node_id) {
+                        return Err(missing("node", node_id, format!("nodes.{node_id}")));
+                    }
+                    flow.set_routing(node_id, routing.clone())?;
+                }
+                FixEdit::RetargetEntrypoint { name, target } => {
+                    if !flow.nodes.contains_key(target) {
+                        return Err(missing("node", target, format!("entrypoints.{name}")));
+                    }
+                    let entry = flow.entrypoints.get_mut(name).ok_or_else(|| {
+                        missing("entrypoint", name, format!("entrypoints.{name}"))
+                    })?;
+                    *entry = target.clone();
+                }
+                FixEdit::RenameNode { from, to } => flow.rename_node(from, to)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for LintFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.description)
+    }
+}
+
+fn missing(kind: &str, name: &str, path: String) -> FlowError {
+    FlowError::Internal {
+        message: format!("fix refers to missing {kind} '{name}'"),
+        location: FlowErrorLocation::at_path(path),
+    }
+}
+
+/// Apply every machine-applicable fix attached to `diagnostics`, returning the applied fixes.
+pub fn apply_machine_applicable_fixes<'a>(
+    flow: &mut FlowIr,
+    diagnostics: &'a [LintDiagnostic],
+) -> Result<Vec<&'a LintFix>> {
+    let mut applied = Vec::new();
+    for fix in diagnostics.iter().filter_map(|d| d.fix.as_ref()) {
+        if fix.is_machine_applicable() {
+            fix.apply(flow)?;
+            applied.push(fix);
+        }
+    }
+    Ok(applied)
+}
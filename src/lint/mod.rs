@@ -1,36 +1,142 @@
 mod adapter_resolvable;
+mod api_compat;
+mod baseline;
+mod complexity;
+mod config;
+mod conflicting_routes;
+mod diagnostic;
+mod entrypoint_targets;
+mod feature_flags;
+mod fix;
+mod node_expiry;
+mod node_naming;
+mod output_mapping;
+mod placeholder_values;
+#[cfg(not(target_arch = "wasm32"))]
+mod plugin;
+mod routing_cycles;
+mod routing_shorthand;
+mod rule;
+mod secret_literals;
+mod start_node;
+mod state_references;
+mod status_routes;
+mod suppressions;
+mod terminal_paths;
+mod unreachable_nodes;
 
-pub use adapter_resolvable::AdapterResolvableRule;
+pub use adapter_resolvable::{AdapterResolvableRule, AdapterVersionRequirements};
+pub use api_compat::ApiCompatRule;
+pub use baseline::{BaselineFinding, LintBaseline, baseline_flow_key};
+pub use complexity::{ComplexityLimits, ComplexityRule};
+pub use config::{ConfigSuppression, LINT_CONFIG_FILE, LintConfig, RuleLevel};
+pub use conflicting_routes::ConflictingRoutesRule;
+pub use diagnostic::{LintDiagnostic, LintSeverity, LintSpan};
+pub use entrypoint_targets::EntrypointTargetsRule;
+pub use feature_flags::FeatureFlagsRule;
+pub use fix::{FixApplicability, FixEdit, LintFix, apply_machine_applicable_fixes};
+pub use node_expiry::NodeExpiryRule;
+pub use node_naming::NodeNamingRule;
+pub use output_mapping::OutputMappingRule;
+pub use placeholder_values::{DEFAULT_PLACEHOLDER_PATTERNS, PlaceholderValuesRule};
+#[cfg(not(target_arch = "wasm32"))]
+pub use plugin::WasmLintRule;
+pub use routing_cycles::RoutingCyclesRule;
+pub use routing_shorthand::RoutingShorthandRule;
+pub use rule::{LintRule, RuleContext, RuleRegistry};
+pub use secret_literals::SecretLiteralsRule;
+pub use start_node::StartNodeExistsRule;
+pub use state_references::StateReferencesRule;
+pub use status_routes::{DeclaredStatuses, StatusRoutesRule};
+pub use suppressions::{
+    LintSuppression, apply_lint_suppressions, expired_suppression_warnings,
+    parse_lint_suppressions, utc_date, utc_today,
+};
+pub use terminal_paths::TerminalPathsRule;
+pub use unreachable_nodes::UnreachableNodesRule;
 
 use crate::registry::AdapterCatalog;
-use greentic_types::{Flow, NodeId};
-use serde_json::Value;
+use greentic_types::{Flow, flow::Node};
+
+/// Run the built-in lint rules that do not require external data: the rules of
+/// [`RuleRegistry::builtin`] that need only the compiled flow.
+pub fn lint_builtin_diagnostics(flow: &Flow) -> Vec<LintDiagnostic> {
+    RuleRegistry::builtin().run(flow, &RuleContext::default())
+}
+
+/// Run all lint rules including adapter resolution backed by a catalog, then apply `config`'s
+/// rule levels and the suppressions that are not scoped to a flow file.
+pub fn lint_diagnostics_with_registry(
+    flow: &Flow,
+    catalog: &AdapterCatalog,
+    config: &LintConfig,
+) -> Vec<LintDiagnostic> {
+    let diagnostics =
+        lint_with_registry_requirements(flow, catalog, &AdapterVersionRequirements::default());
+    config.apply(None, diagnostics).0
+}
+
+/// Like [`lint_diagnostics_with_registry`], also checking adapter versions against the flow's
+/// requested ranges, without applying a lint config.
+pub fn lint_with_registry_requirements(
+    flow: &Flow,
+    catalog: &AdapterCatalog,
+    requirements: &AdapterVersionRequirements,
+) -> Vec<LintDiagnostic> {
+    let mut diagnostics = lint_builtin_diagnostics(flow);
+    diagnostics.extend(AdapterResolvableRule::check_with_requirements(
+        flow,
+        catalog,
+        requirements,
+    ));
+    diagnostics
+}
 
 /// Run the built-in lint rules that do not require external data.
+///
+/// Returns the error findings of [`lint_builtin_diagnostics`] as `rule: message` strings.
+#[deprecated(note = "use lint_builtin_diagnostics, which returns typed LintDiagnostic values")]
 pub fn lint_builtin_rules(flow: &Flow) -> Vec<String> {
-    let mut errors = Vec::new();
-    if let Some(Value::String(default_entry)) = flow.entrypoints.get("default") {
-        match NodeId::new(default_entry.as_str()) {
-            Ok(id) => {
-                if !flow.nodes.contains_key(&id) {
-                    errors.push(format!(
-                        "start_node_exists: start node '{}' not found in nodes",
-                        default_entry
-                    ));
-                }
-            }
-            Err(e) => errors.push(format!(
-                "start_node_exists: invalid start node '{}' ({e})",
-                default_entry
-            )),
-        }
-    }
-    errors
+    error_messages(lint_builtin_diagnostics(flow))
 }
 
 /// Run all lint rules including adapter resolution backed by a catalog.
+///
+/// Returns the error findings of [`lint_diagnostics_with_registry`] (with the default config) as
+/// `rule: message` strings.
+#[deprecated(
+    note = "use lint_diagnostics_with_registry, which returns typed LintDiagnostic values"
+)]
 pub fn lint_with_registry(flow: &Flow, catalog: &AdapterCatalog) -> Vec<String> {
-    let mut errors = lint_builtin_rules(flow);
-    errors.extend(AdapterResolvableRule::check(flow, catalog));
-    errors
+    error_messages(lint_diagnostics_with_registry(
+        flow,
+        catalog,
+        &LintConfig::default(),
+    ))
+}
+
+/// The string shims predate warnings, so they report only what was an error.
+fn error_messages(diagnostics: Vec<LintDiagnostic>) -> Vec<String> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == LintSeverity::Error)
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// The YAML key holding a compiled node's operation payload.
+pub(crate) fn node_operation_key(node: &Node) -> String {
+    match &node.component.operation {
+        Some(op) if node.component.id.as_str() == "component.exec" => op.clone(),
+        Some(op) => format!("{}.{}", node.component.id, op),
+        None => node.component.id.to_string(),
+    }
+}
+
+pub(crate) fn node_pointer(node_id: &str, node: &Node) -> String {
+    format!(
+        "/nodes/{}/{}",
+        diagnostic::pointer_token(node_id),
+        diagnostic::pointer_token(&node_operation_key(node))
+    )
 }
@@ -0,0 +1,56 @@
+use super::{LintDiagnostic, LintSeverity, diagnostic::pointer_token};
+use crate::{
+    expiry::{DEFAULT_EXPIRY_WARNING_DAYS, NODE_EXPIRES_KEY, days_until, node_expiry},
+    model::FlowDoc,
+};
+
+/// Flags nodes past or near their `meta.expires` date (see [`crate::expiry`]).
+///
+/// A node whose date has passed is an error; one expiring within `warning_days` is a warning,
+/// so temporary steps get removed (`greentic-flow prune --expired`) instead of lingering.
+#[derive(Clone, Debug)]
+pub struct NodeExpiryRule {
+    /// Today as `YYYY-MM-DD`.
+    pub today: String,
+    pub warning_days: i64,
+}
+
+impl NodeExpiryRule {
+    pub fn new(today: impl Into<String>) -> Self {
+        Self {
+            today: today.into(),
+            warning_days: DEFAULT_EXPIRY_WARNING_DAYS,
+        }
+    }
+
+    pub fn check(&self, doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let mut diagnostics = Vec::new();
+        for (node_id, node) in &doc.nodes {
+            let Some(expiry) = node_expiry(node) else {
+                continue;
+            };
+            let path = format!("/nodes/{}/meta/{NODE_EXPIRES_KEY}", pointer_token(node_id));
+            let diagnostic = match expiry {
+                Err(message) => {
+                    LintDiagnostic::error("node_expiry", format!("node '{node_id}': {message}"))
+                }
+                Ok(date) => match days_until(&self.today, &date) {
+                    Some(days) if days < 0 => LintDiagnostic::error(
+                        "node_expiry",
+                        format!(
+                            "node '{node_id}' expired on {date}; remove it with `greentic-flow prune --expired`"
+                        ),
+                    ),
+                    Some(days) if days <= self.warning_days => LintDiagnostic::error(
+                        "node_expiry",
+                        format!("node '{node_id}' expires on {date} (in {days} day(s))"),
+                    )
+                    .with_severity(LintSeverity::Warning),
+                    _ => continue,
+                },
+            };
+            diagnostics.push(diagnostic.with_path(path));
+        }
+        diagnostics
+    }
+}
@@ -0,0 +1,87 @@
+use super::{
+    FixApplicability, FixEdit, LintDiagnostic, LintFix, LintSeverity, diagnostic::pointer_token,
+};
+use crate::{model::FlowDoc, rename::is_valid_node_id};
+use std::collections::BTreeSet;
+
+/// Route targets that are not nodes; a node renamed to one of them would change routing.
+const RESERVED_TARGETS: &[&str] = &["out", "reply"];
+
+/// Flags node ids that are not snake_case (`fetchUser`, `send-reply`), the convention every
+/// generated flow follows.
+///
+/// The fix renames the node to its snake_case form and rewrites every reference, like
+/// `rename-step`. It is only offered when that id is free and no other node wants it.
+#[derive(Clone, Debug, Default)]
+pub struct NodeNamingRule;
+
+impl NodeNamingRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let mut taken: BTreeSet<String> = doc.nodes.keys().cloned().collect();
+        let mut diagnostics = Vec::new();
+        for node_id in doc.nodes.keys() {
+            if is_snake_case(node_id) {
+                continue;
+            }
+            let suggestion = snake_case_id(node_id);
+            let mut diagnostic = LintDiagnostic::error(
+                "node_naming",
+                format!("node id '{node_id}' is not snake_case (expected '{suggestion}')"),
+            )
+            .with_severity(LintSeverity::Warning)
+            .with_path(format!("/nodes/{}", pointer_token(node_id)));
+            if is_valid_node_id(&suggestion)
+                && !RESERVED_TARGETS.contains(&suggestion.as_str())
+                && !taken.contains(&suggestion)
+            {
+                diagnostic = diagnostic.with_fix(LintFix::new(
+                    format!("rename node '{node_id}' to '{suggestion}'"),
+                    FixApplicability::MachineApplicable,
+                    vec![FixEdit::RenameNode {
+                        from: node_id.clone(),
+                        to: suggestion.clone(),
+                    }],
+                ));
+                taken.remove(node_id);
+                taken.insert(suggestion);
+            }
+            diagnostics.push(diagnostic);
+        }
+        diagnostics
+    }
+}
+
+fn is_snake_case(id: &str) -> bool {
+    id.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// `fetchUser` -> `fetch_user`, `Send-Reply` -> `send_reply`, `HTTPCall` -> `http_call`.
+fn snake_case_id(id: &str) -> String {
+    let chars: Vec<char> = id.chars().collect();
+    let mut out = String::with_capacity(id.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '-' || c == '_' || c.is_whitespace() {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            continue;
+        }
+        if c.is_ascii_uppercase() {
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            let boundary = prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+                || (prev.is_some_and(|p| p.is_ascii_uppercase())
+                    && next.is_some_and(|n| n.is_ascii_lowercase()));
+            if boundary && !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out.trim_end_matches('_').to_string()
+}
@@ -0,0 +1,80 @@
+use super::{LintDiagnostic, LintSeverity, diagnostic::pointer_token};
+use crate::{
+    flow_ir::{FlowIr, analysis::FlowAnalysis},
+    model::FlowDoc,
+    output_mapping::{OutputCapture, parse_output_mapping},
+};
+
+/// Checks each node's `output:` mapping (see [`crate::output_mapping`]).
+///
+/// Malformed mappings, keys and selectors, and keys that overlap within one node, are errors
+/// reported at `/nodes/<id>/output[/<key>]`. A key that a downstream node captures again is a
+/// warning: the later node silently replaces the value the earlier one stored.
+#[derive(Clone, Debug, Default)]
+pub struct OutputMappingRule;
+
+impl OutputMappingRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let Ok(flow) = FlowIr::from_doc(doc.clone()) else {
+            return Vec::new();
+        };
+        let mut diagnostics = Vec::new();
+        let mut captures: Vec<(&str, Vec<OutputCapture>)> = Vec::new();
+        for (node_id, node) in &flow.nodes {
+            let (node_captures, problems) = parse_output_mapping(&node.output);
+            let base = format!("/nodes/{}/output", pointer_token(node_id));
+            for problem in problems {
+                let path = match &problem.key {
+                    Some(key) => format!("{base}/{}", pointer_token(key)),
+                    None => base.clone(),
+                };
+                diagnostics.push(
+                    LintDiagnostic::error(
+                        "output_mapping",
+                        format!("node '{node_id}': {}", problem.message),
+                    )
+                    .with_path(path),
+                );
+            }
+            if !node_captures.is_empty() {
+                captures.push((node_id.as_str(), node_captures));
+            }
+        }
+
+        let analysis = FlowAnalysis::new(&flow);
+        for (node_id, node_captures) in &captures {
+            let downstream =
+                analysis.reachable_from(analysis.successors(node_id).iter().map(String::as_str));
+            for (later_id, later_captures) in &captures {
+                if later_id == node_id || !downstream.iter().any(|id| id == later_id) {
+                    continue;
+                }
+                for capture in node_captures {
+                    let Some(later) = later_captures
+                        .iter()
+                        .find(|later| later.overlaps(&capture.key))
+                    else {
+                        continue;
+                    };
+                    diagnostics.push(
+                        LintDiagnostic::error(
+                            "output_mapping",
+                            format!(
+                                "state key '{}' captured by node '{node_id}' is overwritten by \
+                                 '{}' of downstream node '{later_id}'",
+                                capture.key, later.key
+                            ),
+                        )
+                        .with_severity(LintSeverity::Warning)
+                        .with_path(format!(
+                            "/nodes/{}/output/{}",
+                            pointer_token(later_id),
+                            pointer_token(&later.key)
+                        )),
+                    );
+                }
+            }
+        }
+        diagnostics
+    }
+}
@@ -0,0 +1,102 @@
+use super::{LintDiagnostic, diagnostic::pointer_token, node_pointer};
+use crate::splice::NEXT_NODE_PLACEHOLDER;
+use greentic_types::Flow;
+use serde_json::Value;
+
+/// Placeholder values checked when no custom pattern list is configured.
+///
+/// A pattern matches a whole (trimmed) string value; `*` matches any run of characters, so
+/// `__*__` covers `__PLACEHOLDER__`, `__API_KEY__`, and similar template stubs.
+pub const DEFAULT_PLACEHOLDER_PATTERNS: &[&str] =
+    &[NEXT_NODE_PLACEHOLDER, "TODO", "CHANGEME", "__*__"];
+
+/// Flags payload strings that still hold template placeholders (typically left behind when
+/// wizard answers were skipped in permissive mode).
+#[derive(Clone, Debug)]
+pub struct PlaceholderValuesRule {
+    patterns: Vec<String>,
+}
+
+impl Default for PlaceholderValuesRule {
+    fn default() -> Self {
+        Self::new(DEFAULT_PLACEHOLDER_PATTERNS.iter().map(|p| p.to_string()))
+    }
+}
+
+impl PlaceholderValuesRule {
+    pub fn new(patterns: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            patterns: patterns.into_iter().collect(),
+        }
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn check(&self, flow: &Flow) -> Vec<LintDiagnostic> {
+        let mut errors = Vec::new();
+        for (node_id, node) in &flow.nodes {
+            let mut hits = Vec::new();
+            self.scan(&node.input.mapping, String::new(), &mut hits);
+            let node_path = node_pointer(node_id.as_str(), node);
+            for (pointer, value) in hits {
+                errors.push(
+                    LintDiagnostic::error(
+                        "placeholder_values",
+                        format!(
+                            "node '{node_id}' payload at {} holds unresolved placeholder '{value}'",
+                            if pointer.is_empty() { "/" } else { &pointer }
+                        ),
+                    )
+                    .with_path(format!("{node_path}{pointer}")),
+                );
+            }
+        }
+        errors
+    }
+
+    fn scan(&self, value: &Value, pointer: String, hits: &mut Vec<(String, String)>) {
+        match value {
+            Value::String(text) => {
+                let trimmed = text.trim();
+                if self
+                    .patterns
+                    .iter()
+                    .any(|pattern| glob_matches(pattern, trimmed))
+                {
+                    hits.push((pointer, trimmed.to_string()));
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    self.scan(item, format!("{pointer}/{idx}"), hits);
+                }
+            }
+            Value::Object(map) => {
+                for (key, item) in map {
+                    self.scan(item, format!("{pointer}/{}", pointer_token(key)), hits);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(prefix) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let suffix = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(pos) => remaining = &remaining[pos + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= suffix.len() && remaining.ends_with(suffix)
+}
@@ -0,0 +1,142 @@
+//! Lint rules loaded from WebAssembly components.
+//!
+//! A plugin is a component exporting this interface (it may import WASI, nothing else):
+//!
+//! ```wit
+//! package greentic:flow@0.1.0;
+//!
+//! interface lint-rule {
+//!     /// Rule id reported with the plugin's findings.
+//!     id: func() -> string;
+//!     /// Flow IR in, findings out, both as CBOR.
+//!     check: func(flow: list<u8>) -> list<u8>;
+//! }
+//! ```
+//!
+//! `check` receives the flow IR rendered back to a schema v2 document ([`FlowIr::to_doc`]) and
+//! returns an array of `{ message, path?, severity?, rule? }` maps. `path` is a JSON
+//! pointer into the flow, `severity` is `error` (the default) or `warning`, and `rule` defaults to
+//! the plugin's `id`.
+
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{LintDiagnostic, LintRule, LintSeverity, RuleContext};
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_ir::FlowIr,
+    model::FlowDoc,
+    wizard_ops::{self, ComponentWasm},
+};
+use greentic_types::{Flow, cbor::canonical};
+
+/// Export names accepted for the plugin interface.
+const LINT_RULE_EXPORTS: &[&str] = &[
+    "greentic:flow/lint-rule@0.1.0",
+    "greentic:flow/lint-rule",
+    "lint-rule",
+];
+
+#[derive(Deserialize)]
+struct PluginFinding {
+    message: String,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    severity: Option<LintSeverity>,
+    #[serde(default)]
+    rule: Option<String>,
+}
+
+/// A [`LintRule`] backed by a wasm component.
+#[derive(Clone, Debug)]
+pub struct WasmLintRule {
+    id: String,
+    path: PathBuf,
+    wasm: ComponentWasm,
+}
+
+impl WasmLintRule {
+    /// Load the component at `path` and ask it for its rule id.
+    pub fn load(path: &Path) -> Result<Self> {
+        fs::metadata(path).map_err(|e| plugin_error(path, format!("read: {e}")))?;
+        Self::from_wasm(path, ComponentWasm::File(path.to_path_buf()))
+    }
+
+    /// Use already-read component bytes; `path` only labels findings and errors.
+    pub fn from_bytes(path: &Path, wasm: Vec<u8>) -> Result<Self> {
+        Self::from_wasm(path, ComponentWasm::Bytes(wasm))
+    }
+
+    fn from_wasm(path: &Path, wasm: ComponentWasm) -> Result<Self> {
+        let id = wizard_ops::call_export_string(&wasm, LINT_RULE_EXPORTS, "id")
+            .map_err(|e| plugin_error(path, e.to_string()))?;
+        if id.trim().is_empty() {
+            return Err(plugin_error(path, "id() returned an empty rule id"));
+        }
+        Ok(Self {
+            id,
+            path: path.to_path_buf(),
+            wasm,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn run(&self, doc: &FlowDoc) -> std::result::Result<Vec<LintDiagnostic>, String> {
+        let normalized = FlowIr::from_doc(doc.clone())
+            .and_then(|ir| ir.to_doc())
+            .map_err(|e| format!("build flow IR: {e}"))?;
+        let input = canonical::to_canonical_cbor_allow_floats(&normalized)
+            .map_err(|e| format!("encode flow: {e}"))?;
+        let output = wizard_ops::call_export_bytes(&self.wasm, LINT_RULE_EXPORTS, "check", &input)
+            .map_err(|e| e.to_string())?;
+        let findings: Vec<PluginFinding> =
+            canonical::from_cbor(&output).map_err(|e| format!("decode findings: {e}"))?;
+        Ok(findings
+            .into_iter()
+            .map(|finding| {
+                let rule = finding.rule.unwrap_or_else(|| self.id.clone());
+                let mut diagnostic = LintDiagnostic::error(rule, finding.message)
+                    .with_severity(finding.severity.unwrap_or(LintSeverity::Error));
+                if let Some(path) = finding.path {
+                    diagnostic = diagnostic.with_path(path);
+                }
+                diagnostic
+            })
+            .collect())
+    }
+}
+
+impl LintRule for WasmLintRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        // Like the other document rules, nothing to report without the authored flow. A plugin
+        // that fails is reported as a finding rather than aborting the lint run.
+        let Some(doc) = ctx.doc else {
+            return Vec::new();
+        };
+        self.run(doc).unwrap_or_else(|message| {
+            vec![LintDiagnostic::error(
+                self.id.clone(),
+                format!("lint plugin {} failed: {message}", self.path.display()),
+            )]
+        })
+    }
+}
+
+fn plugin_error(path: &Path, message: impl Into<String>) -> FlowError {
+    FlowError::Internal {
+        message: format!("lint plugin {}: {}", path.display(), message.into()),
+        location: FlowErrorLocation::at_path(path.display().to_string())
+            .with_source_path(Some(path)),
+    }
+}
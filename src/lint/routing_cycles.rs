@@ -0,0 +1,46 @@
+use super::{LintDiagnostic, diagnostic::pointer_token};
+use crate::{
+    flow_ir::{FlowIr, find_cycles},
+    model::FlowDoc,
+};
+use serde_json::Value;
+
+/// Flags routing cycles, reporting the node path of each (see [`find_cycles`]).
+///
+/// `add-step` refuses to create cycles unless `--allow-cycles` is passed; this rule catches the
+/// ones already in a flow. Flows that loop on purpose (retries, polling) opt out with
+/// `meta.allow_cycles: true`.
+#[derive(Clone, Debug, Default)]
+pub struct RoutingCyclesRule;
+
+impl RoutingCyclesRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let allowed = doc
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.get("allow_cycles"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if allowed {
+            return Vec::new();
+        }
+        let Ok(flow) = FlowIr::from_doc(doc.clone()) else {
+            return Vec::new();
+        };
+        find_cycles(&flow)
+            .into_iter()
+            .map(|cycle| {
+                let mut path = cycle.join(" -> ");
+                path.push_str(" -> ");
+                path.push_str(&cycle[0]);
+                LintDiagnostic::error(
+                    "routing_cycles",
+                    format!(
+                        "routing cycle {path}; set meta.allow_cycles: true if the loop is intended"
+                    ),
+                )
+                .with_path(format!("/nodes/{}", pointer_token(&cycle[0])))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,61 @@
+use super::{
+    FixApplicability, FixEdit, LintDiagnostic, LintFix, LintSeverity, diagnostic::pointer_token,
+};
+use crate::{flow_ir::Route, model::FlowDoc};
+use serde_json::Value;
+
+/// Flags single terminal routes spelled out as a list (`[{out: true}]`, `[{to: out}]`,
+/// `[{reply: true}]`) where the `out`/`reply` shorthand applies.
+#[derive(Clone, Debug, Default)]
+pub struct RoutingShorthandRule;
+
+impl RoutingShorthandRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let mut diagnostics = Vec::new();
+        for (node_id, node) in &doc.nodes {
+            let Some([Value::Object(route)]) = node.routing.as_array().map(Vec::as_slice) else {
+                continue;
+            };
+            let only = |keys: &[&str]| route.keys().all(|key| keys.contains(&key.as_str()));
+            let shorthand = if !route.is_empty()
+                && only(&["out", "to"])
+                && route.get("out").is_none_or(|out| *out == Value::Bool(true))
+                && route.get("to").is_none_or(|to| to.as_str() == Some("out"))
+            {
+                "out"
+            } else if only(&["reply"]) && route.get("reply") == Some(&Value::Bool(true)) {
+                "reply"
+            } else {
+                continue;
+            };
+            let routing = if shorthand == "out" {
+                Route {
+                    out: true,
+                    ..Route::default()
+                }
+            } else {
+                Route {
+                    reply: true,
+                    ..Route::default()
+                }
+            };
+            diagnostics.push(
+                LintDiagnostic::error(
+                    "routing_shorthand",
+                    format!("node '{node_id}' routing can be written as `routing: {shorthand}`"),
+                )
+                .with_severity(LintSeverity::Warning)
+                .with_path(format!("/nodes/{}/routing", pointer_token(node_id)))
+                .with_fix(LintFix::new(
+                    format!("rewrite node '{node_id}' routing as `{shorthand}`"),
+                    FixApplicability::MachineApplicable,
+                    vec![FixEdit::SetRouting {
+                        node_id: node_id.clone(),
+                        routing: vec![routing],
+                    }],
+                )),
+            );
+        }
+        diagnostics
+    }
+}
@@ -0,0 +1,362 @@
+//! The [`LintRule`] trait and the [`RuleRegistry`] that runs a set of rules over a flow.
+//!
+//! Each built-in rule implements [`LintRule`]; [`RuleRegistry::builtin`] holds the ones that need
+//! nothing beyond the flow, its authored document and an optional adapter catalog. Embedders add
+//! their own rules with [`RuleRegistry::register`], which also replaces a rule of the same id.
+
+use super::{
+    AdapterResolvableRule, AdapterVersionRequirements, ApiCompatRule, ComplexityRule,
+    ConflictingRoutesRule, EntrypointTargetsRule, FeatureFlagsRule, LintConfig, LintDiagnostic,
+    LintSeverity, NodeExpiryRule, NodeNamingRule, OutputMappingRule, PlaceholderValuesRule,
+    RoutingCyclesRule, RoutingShorthandRule, SecretLiteralsRule, StartNodeExistsRule,
+    StateReferencesRule, TerminalPathsRule, UnreachableNodesRule, utc_today,
+};
+use crate::{model::FlowDoc, registry::AdapterCatalog};
+use greentic_types::Flow;
+
+/// Inputs a rule may need besides the compiled flow.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RuleContext<'a> {
+    /// The authored document, when the flow was loaded from YAML. Rules that inspect authoring
+    /// details (shorthand routing, entrypoint spelling, `meta`) report nothing without it.
+    pub doc: Option<&'a FlowDoc>,
+    /// Adapter catalog; `adapter_resolvable` is skipped without one.
+    pub catalog: Option<&'a AdapterCatalog>,
+    /// Project settings for the flow; `[complexity]` replaces the `complexity` rule's limits.
+    pub config: Option<&'a LintConfig>,
+}
+
+impl<'a> RuleContext<'a> {
+    pub fn with_doc(mut self, doc: &'a FlowDoc) -> Self {
+        self.doc = Some(doc);
+        self
+    }
+
+    pub fn with_catalog(mut self, catalog: &'a AdapterCatalog) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    pub fn with_config(mut self, config: &'a LintConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+}
+
+/// A lint check over a compiled flow.
+pub trait LintRule: Send + Sync {
+    /// Identifier of the rule, used as the `rule` of the findings it reports.
+    fn id(&self) -> &str;
+
+    /// Severity of the rule's findings; individual findings may be milder (e.g. a node that
+    /// expires soon rather than one already expired).
+    fn severity(&self) -> LintSeverity {
+        LintSeverity::Error
+    }
+
+    fn check(&self, flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic>;
+}
+
+/// An ordered set of lint rules, at most one per id.
+#[derive(Default)]
+pub struct RuleRegistry {
+    rules: Vec<Box<dyn LintRule>>,
+}
+
+impl RuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in rules, in the order doctor reports them. `placeholder_values`,
+    /// `status_routes` and `api_compat` are not included: they depend on caller configuration,
+    /// component manifests and released flows.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry
+            .register(StartNodeExistsRule)
+            .register(UnreachableNodesRule)
+            .register(AdapterResolvableRule)
+            .register(EntrypointTargetsRule)
+            .register(RoutingShorthandRule)
+            .register(NodeNamingRule)
+            .register(RoutingCyclesRule)
+            .register(ConflictingRoutesRule)
+            .register(TerminalPathsRule)
+            .register(OutputMappingRule)
+            .register(StateReferencesRule)
+            .register(SecretLiteralsRule)
+            .register(FeatureFlagsRule)
+            .register(NodeExpiryRule::new(utc_today()))
+            .register(ComplexityRule::default());
+        registry
+    }
+
+    /// Add `rule`, replacing (in place) a registered rule with the same id.
+    pub fn register(&mut self, rule: impl LintRule + 'static) -> &mut Self {
+        let rule: Box<dyn LintRule> = Box::new(rule);
+        match self
+            .rules
+            .iter()
+            .position(|existing| existing.id() == rule.id())
+        {
+            Some(index) => self.rules[index] = rule,
+            None => self.rules.push(rule),
+        }
+        self
+    }
+
+    /// Remove the rule with this id, returning it.
+    pub fn unregister(&mut self, id: &str) -> Option<Box<dyn LintRule>> {
+        let index = self.rules.iter().position(|rule| rule.id() == id)?;
+        Some(self.rules.remove(index))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn LintRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.id() == id)
+            .map(|rule| rule.as_ref())
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|rule| rule.id())
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Run every rule in registration order.
+    pub fn run(&self, flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        self.rules
+            .iter()
+            .flat_map(|rule| rule.check(flow, ctx))
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for RuleRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.ids()).finish()
+    }
+}
+
+impl LintRule for StartNodeExistsRule {
+    fn id(&self) -> &str {
+        "start_node_exists"
+    }
+
+    fn check(&self, flow: &Flow, _ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        StartNodeExistsRule::check(flow)
+    }
+}
+
+impl LintRule for UnreachableNodesRule {
+    fn id(&self) -> &str {
+        "unreachable_node"
+    }
+
+    fn severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, flow: &Flow, _ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        UnreachableNodesRule::check(flow)
+    }
+}
+
+impl LintRule for AdapterResolvableRule {
+    fn id(&self) -> &str {
+        "adapter_resolvable"
+    }
+
+    fn check(&self, flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        let Some(catalog) = ctx.catalog else {
+            return Vec::new();
+        };
+        let requirements = ctx
+            .doc
+            .map(AdapterVersionRequirements::from_doc)
+            .unwrap_or_default();
+        AdapterResolvableRule::check_with_requirements(flow, catalog, &requirements)
+    }
+}
+
+impl LintRule for EntrypointTargetsRule {
+    fn id(&self) -> &str {
+        "entrypoint_targets"
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc
+            .map(EntrypointTargetsRule::check)
+            .unwrap_or_default()
+    }
+}
+
+impl LintRule for RoutingShorthandRule {
+    fn id(&self) -> &str {
+        "routing_shorthand"
+    }
+
+    fn severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc.map(RoutingShorthandRule::check).unwrap_or_default()
+    }
+}
+
+impl LintRule for RoutingCyclesRule {
+    fn id(&self) -> &str {
+        "routing_cycles"
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc.map(RoutingCyclesRule::check).unwrap_or_default()
+    }
+}
+
+impl LintRule for ConflictingRoutesRule {
+    fn id(&self) -> &str {
+        "conflicting_routes"
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc
+            .map(ConflictingRoutesRule::check)
+            .unwrap_or_default()
+    }
+}
+
+impl LintRule for NodeNamingRule {
+    fn id(&self) -> &str {
+        "node_naming"
+    }
+
+    fn severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc.map(NodeNamingRule::check).unwrap_or_default()
+    }
+}
+
+impl LintRule for TerminalPathsRule {
+    fn id(&self) -> &str {
+        "terminal_paths"
+    }
+
+    fn severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc.map(TerminalPathsRule::check).unwrap_or_default()
+    }
+}
+
+impl LintRule for OutputMappingRule {
+    fn id(&self) -> &str {
+        "output_mapping"
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc.map(OutputMappingRule::check).unwrap_or_default()
+    }
+}
+
+impl LintRule for StateReferencesRule {
+    fn id(&self) -> &str {
+        "state_references"
+    }
+
+    fn severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc.map(StateReferencesRule::check).unwrap_or_default()
+    }
+}
+
+impl LintRule for SecretLiteralsRule {
+    fn id(&self) -> &str {
+        "secret_literals"
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc.map(SecretLiteralsRule::check).unwrap_or_default()
+    }
+}
+
+impl LintRule for ApiCompatRule {
+    fn id(&self) -> &str {
+        "api_compat"
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc
+            .map(|doc| ApiCompatRule::check(self, doc))
+            .unwrap_or_default()
+    }
+}
+
+impl LintRule for FeatureFlagsRule {
+    fn id(&self) -> &str {
+        "undefined_feature"
+    }
+
+    fn severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc.map(FeatureFlagsRule::check).unwrap_or_default()
+    }
+}
+
+impl LintRule for NodeExpiryRule {
+    fn id(&self) -> &str {
+        "node_expiry"
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        ctx.doc
+            .map(|doc| NodeExpiryRule::check(self, doc))
+            .unwrap_or_default()
+    }
+}
+
+impl LintRule for ComplexityRule {
+    fn id(&self) -> &str {
+        "complexity"
+    }
+
+    fn check(&self, _flow: &Flow, ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        let configured = ctx.config.and_then(|config| config.complexity);
+        ctx.doc
+            .map(|doc| match configured {
+                Some(limits) => ComplexityRule::new(limits).check(doc),
+                None => ComplexityRule::check(self, doc),
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl LintRule for PlaceholderValuesRule {
+    fn id(&self) -> &str {
+        "placeholder_values"
+    }
+
+    fn check(&self, flow: &Flow, _ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        PlaceholderValuesRule::check(self, flow)
+    }
+}
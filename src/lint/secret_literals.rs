@@ -0,0 +1,159 @@
+use super::{LintDiagnostic, LintSeverity, diagnostic::pointer_token};
+use crate::{flow_ir::FlowIr, health::find_secret_literals, model::FlowDoc};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Credential formats recognised anywhere in a payload, with the name used in messages.
+const CREDENTIAL_PATTERNS: &[(&str, &str)] = &[
+    (r"-----BEGIN [A-Z ]*PRIVATE KEY-----", "a private key"),
+    (r"\bAKIA[0-9A-Z]{16}\b", "an AWS access key id"),
+    (r"\bgh[pousr]_[A-Za-z0-9]{36,}\b", "a GitHub token"),
+    (r"\bxox[abprs]-[A-Za-z0-9-]{10,}", "a Slack token"),
+    (r"\bsk-[A-Za-z0-9_-]{20,}", "an API secret key"),
+    (
+        r"\beyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]+",
+        "a JSON Web Token",
+    ),
+    (r"(?i)\bbearer\s+[A-Za-z0-9._~+/=-]{20,}", "a bearer token"),
+    (
+        r"[a-z][a-z0-9+.-]*://[^/\s:@]+:[^/\s@]+@",
+        "a URL with a password",
+    ),
+];
+
+/// Shortest token considered for the entropy check.
+const MIN_ENTROPY_LEN: usize = 24;
+/// Shannon entropy (bits per character) above which a token looks random. Hex digests top out
+/// at 4.0, so they stay below it.
+const ENTROPY_THRESHOLD: f64 = 4.2;
+
+/// Flags payload strings that look like credentials committed in the flow.
+///
+/// - A literal under a secret-looking key (`password`, `api_key`, `*token*`, ...; see
+///   [`find_secret_literals`]) or a string matching a known credential format (private keys,
+///   AWS, GitHub and Slack tokens, JWTs, bearer headers, URLs with a password) is an error.
+/// - A long random-looking token (high Shannon entropy) is a warning, since ids and digests can
+///   look the same.
+///
+/// Templated values (`{{...}}`, `${...}`) are references, not literals, and are skipped. Messages
+/// name the kind of secret and never repeat the value.
+#[derive(Clone, Debug, Default)]
+pub struct SecretLiteralsRule;
+
+impl SecretLiteralsRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let Ok(flow) = FlowIr::from_doc(doc.clone()) else {
+            return Vec::new();
+        };
+        let mut diagnostics = Vec::new();
+        let mut reported = BTreeSet::new();
+        for (node_id, pointer) in find_secret_literals(&flow) {
+            diagnostics.push(
+                LintDiagnostic::error(
+                    "secret_literals",
+                    format!(
+                        "node '{node_id}' has a literal value under a secret-looking key at \
+                         {pointer}; reference a secret instead"
+                    ),
+                )
+                .with_path(pointer.clone()),
+            );
+            reported.insert(pointer);
+        }
+
+        let patterns: Vec<(Regex, &str)> = CREDENTIAL_PATTERNS
+            .iter()
+            .map(|(pattern, kind)| (Regex::new(pattern).unwrap(), *kind))
+            .collect();
+        for node in flow.nodes.values() {
+            let base = format!(
+                "/nodes/{}/{}",
+                pointer_token(&node.id),
+                pointer_token(&node.operation)
+            );
+            let mut strings = Vec::new();
+            collect_strings(&node.payload, base, &mut strings);
+            for (pointer, text) in strings {
+                if reported.contains(&pointer) || is_reference(text) {
+                    continue;
+                }
+                let node_id = &node.id;
+                if let Some((_, kind)) = patterns.iter().find(|(re, _)| re.is_match(text)) {
+                    diagnostics.push(
+                        LintDiagnostic::error(
+                            "secret_literals",
+                            format!(
+                                "node '{node_id}' payload at {pointer} looks like {kind}; \
+                                 reference a secret instead"
+                            ),
+                        )
+                        .with_path(pointer),
+                    );
+                } else if text.split_whitespace().any(is_high_entropy) {
+                    diagnostics.push(
+                        LintDiagnostic::error(
+                            "secret_literals",
+                            format!(
+                                "node '{node_id}' payload at {pointer} holds a random-looking \
+                                 token that may be a credential"
+                            ),
+                        )
+                        .with_severity(LintSeverity::Warning)
+                        .with_path(pointer),
+                    );
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+fn collect_strings<'a>(value: &'a Value, pointer: String, out: &mut Vec<(String, &'a str)>) {
+    match value {
+        Value::String(text) => out.push((pointer, text)),
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                collect_strings(item, format!("{pointer}/{idx}"), out);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                collect_strings(item, format!("{pointer}/{}", pointer_token(key)), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_reference(text: &str) -> bool {
+    text.contains("{{") || text.contains("${")
+}
+
+/// A long token of base64/url-safe characters, mixing letters and digits, that looks random.
+fn is_high_entropy(token: &str) -> bool {
+    if token.len() < MIN_ENTROPY_LEN
+        || token.contains("://")
+        || !token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+/=_-.".contains(c))
+        || !token.chars().any(|c| c.is_ascii_digit())
+        || !token.chars().any(|c| c.is_ascii_alphabetic())
+    {
+        return false;
+    }
+    let mut counts = [0usize; 128];
+    for byte in token.bytes() {
+        counts[byte as usize] += 1;
+    }
+    let len = token.len() as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    entropy > ENTROPY_THRESHOLD
+}
@@ -0,0 +1,21 @@
+use super::LintDiagnostic;
+use greentic_types::{Flow, NodeId};
+use serde_json::Value;
+
+/// Flags a `start` (the `default` entrypoint) that names no node of the flow.
+#[derive(Clone, Debug, Default)]
+pub struct StartNodeExistsRule;
+
+impl StartNodeExistsRule {
+    pub fn check(flow: &Flow) -> Vec<LintDiagnostic> {
+        let Some(Value::String(default_entry)) = flow.entrypoints.get("default") else {
+            return Vec::new();
+        };
+        let message = match NodeId::new(default_entry.as_str()) {
+            Ok(id) if flow.nodes.contains_key(&id) => return Vec::new(),
+            Ok(_) => format!("start node '{}' not found in nodes", default_entry),
+            Err(e) => format!("invalid start node '{}' ({e})", default_entry),
+        };
+        vec![LintDiagnostic::error("start_node_exists", message).with_path("/start")]
+    }
+}
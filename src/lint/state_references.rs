@@ -0,0 +1,116 @@
+use super::{LintDiagnostic, LintSeverity, diagnostic::pointer_token};
+use crate::{
+    flow_ir::{FlowIr, analysis::FlowAnalysis},
+    model::FlowDoc,
+    output_mapping::{keys_overlap, parse_output_mapping},
+    state_keys::field_state_key,
+    template::template_state_references,
+};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// Warns when a template reads `{{state.<key>}}` that no node upstream of it writes.
+///
+/// State is written by `output:` mappings and by the fields of `questions` nodes. Reading inside a
+/// captured value (`state.weather.temp` after capturing `weather`) or a parent of a nested key
+/// counts as resolved. State seeded by the host before the flow starts is invisible here, so the
+/// finding is a warning.
+#[derive(Clone, Debug, Default)]
+pub struct StateReferencesRule;
+
+impl StateReferencesRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let Ok(flow) = FlowIr::from_doc(doc.clone()) else {
+            return Vec::new();
+        };
+        let writes: BTreeMap<&str, Vec<String>> = flow
+            .nodes
+            .iter()
+            .map(|(id, node)| {
+                let mut keys: Vec<String> = parse_output_mapping(&node.output)
+                    .0
+                    .into_iter()
+                    .map(|capture| capture.key)
+                    .collect();
+                if node.operation == "questions" {
+                    let fields = node.payload.get("fields").and_then(Value::as_array);
+                    keys.extend(
+                        fields
+                            .into_iter()
+                            .flatten()
+                            .filter_map(field_state_key)
+                            .map(str::to_string),
+                    );
+                }
+                (id.as_str(), keys)
+            })
+            .collect();
+
+        let analysis = FlowAnalysis::new(&flow);
+        let mut diagnostics = Vec::new();
+        for (node_id, node) in &flow.nodes {
+            let mut references = Vec::new();
+            collect_references(&node.payload, &mut references);
+            if references.is_empty() {
+                continue;
+            }
+            let available: Vec<&str> = upstream(&analysis, node_id)
+                .into_iter()
+                .flat_map(|id| writes.get(id).into_iter().flatten())
+                .map(String::as_str)
+                .collect();
+            for reference in references {
+                if available.iter().any(|key| keys_overlap(key, &reference)) {
+                    continue;
+                }
+                diagnostics.push(
+                    LintDiagnostic::error(
+                        "state_references",
+                        format!(
+                            "node '{node_id}' reads state.{reference}, which no upstream node \
+                             writes (via `output` or a questions field)"
+                        ),
+                    )
+                    .with_severity(LintSeverity::Warning)
+                    .with_path(format!(
+                        "/nodes/{}/{}",
+                        pointer_token(node_id),
+                        pointer_token(&node.operation)
+                    )),
+                );
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Nodes with a path to `node_id`; `node_id` itself only when it sits on a cycle.
+fn upstream<'a>(analysis: &'a FlowAnalysis, node_id: &str) -> BTreeSet<&'a str> {
+    let mut seen = BTreeSet::new();
+    let mut queue: VecDeque<&str> = analysis
+        .predecessors(node_id)
+        .iter()
+        .map(String::as_str)
+        .collect();
+    while let Some(id) = queue.pop_front() {
+        if seen.insert(id) {
+            queue.extend(analysis.predecessors(id).iter().map(String::as_str));
+        }
+    }
+    seen
+}
+
+fn collect_references(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(text) => {
+            for reference in template_state_references(text) {
+                if !out.contains(&reference) {
+                    out.push(reference);
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_references(item, out)),
+        Value::Object(map) => map.values().for_each(|item| collect_references(item, out)),
+        _ => {}
+    }
+}
@@ -0,0 +1,133 @@
+use super::{LintDiagnostic, diagnostic::pointer_token};
+use crate::flow_ir::{Route, StatusMatcher};
+use serde_json::Value;
+
+/// Status values a component operation declares in its output schema.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeclaredStatuses {
+    pub all: Vec<String>,
+    /// Statuses that must be matched by some route (`*` and `!<status>` routes count).
+    pub errors: Vec<String>,
+}
+
+impl DeclaredStatuses {
+    /// Read `properties.status` from an output schema.
+    ///
+    /// Values come from `enum` or `oneOf[].const`. Error statuses come from
+    /// `x-error-statuses` when present; otherwise values shaped like `e412`, `error_*` or a
+    /// 4xx/5xx code are treated as errors.
+    pub fn from_output_schema(schema: &Value) -> Option<Self> {
+        let status = schema.get("properties")?.get("status")?;
+        let all: Vec<String> = if let Some(values) = status.get("enum").and_then(Value::as_array) {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        } else {
+            status
+                .get("oneOf")
+                .and_then(Value::as_array)?
+                .iter()
+                .filter_map(|variant| variant.get("const").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect()
+        };
+        if all.is_empty() {
+            return None;
+        }
+        let errors = match status.get("x-error-statuses").and_then(Value::as_array) {
+            Some(explicit) => explicit
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            None => all
+                .iter()
+                .filter(|value| looks_like_error_status(value))
+                .cloned()
+                .collect(),
+        };
+        Some(Self { all, errors })
+    }
+}
+
+fn looks_like_error_status(value: &str) -> bool {
+    let lower = value.to_ascii_lowercase();
+    if let Some(code) = lower.strip_prefix('e')
+        && !code.is_empty()
+        && code.chars().all(|c| c.is_ascii_digit())
+    {
+        return true;
+    }
+    if lower.starts_with("err") {
+        return true;
+    }
+    lower.len() == 3
+        && lower
+            .parse::<u16>()
+            .is_ok_and(|code| (400..600).contains(&code))
+}
+
+/// Checks a node's `status:` routes against the statuses its component declares.
+///
+/// Nodes that never route on `status` are skipped; once a node routes one status, every
+/// declared error status must be matched by a route (a `*` or `!ok` route covers several).
+#[derive(Clone, Debug, Default)]
+pub struct StatusRoutesRule;
+
+impl StatusRoutesRule {
+    pub fn check(
+        node_id: &str,
+        routes: &[Route],
+        declared: &DeclaredStatuses,
+    ) -> Vec<LintDiagnostic> {
+        let routing_path = format!("/nodes/{}/routing", pointer_token(node_id));
+        let mut diagnostics = Vec::new();
+        let matchers: Vec<StatusMatcher<'_>> =
+            routes.iter().filter_map(Route::status_matcher).collect();
+        for (idx, route) in routes.iter().enumerate() {
+            let Some(status) = route.status.as_deref() else {
+                continue;
+            };
+            let named = match StatusMatcher::parse(status) {
+                StatusMatcher::Is(name) | StatusMatcher::Not(name) => name,
+                StatusMatcher::Any => continue,
+            };
+            if !declared.all.iter().any(|value| value == named) {
+                diagnostics.push(
+                    LintDiagnostic::error(
+                        "status_routes",
+                        format!(
+                            "node '{node_id}' routes status '{status}', which the component does not declare (declared: {})",
+                            declared.all.join(", ")
+                        ),
+                    )
+                    .with_path(format!("{routing_path}/{idx}/status")),
+                );
+            }
+        }
+        if matchers.is_empty() {
+            return diagnostics;
+        }
+        let unhandled: Vec<&str> = declared
+            .errors
+            .iter()
+            .filter(|value| !matchers.iter().any(|matcher| matcher.matches(value)))
+            .map(String::as_str)
+            .collect();
+        if !unhandled.is_empty() {
+            diagnostics.push(
+                LintDiagnostic::error(
+                    "status_routes",
+                    format!(
+                        "node '{node_id}' does not route error status(es) {}; add a route for each, a `status: \"*\"` catch-all or a negation such as `status: \"!ok\"`",
+                        unhandled.join(", ")
+                    ),
+                )
+                .with_path(routing_path),
+            );
+        }
+        diagnostics
+    }
+}
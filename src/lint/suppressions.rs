@@ -0,0 +1,261 @@
+use super::{LintDiagnostic, LintSeverity, LintSpan, diagnostic::pointer_token};
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DIRECTIVE: &str = "greentic-lint:";
+
+/// An inline `# greentic-lint: disable=<rule>[,<rule>] reason="..." [until=YYYY-MM-DD]`
+/// comment attached to a node.
+///
+/// The comment sits on the lines directly above the node key (no blank line between) or
+/// trails the key line. Rules match case-insensitively; `until` is inclusive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LintSuppression {
+    pub node_id: String,
+    pub rules: Vec<String>,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// 1-based line of the comment.
+    pub line: usize,
+    /// Findings suppressed so far by [`apply_lint_suppressions`].
+    pub suppressed: usize,
+}
+
+impl LintSuppression {
+    pub fn is_expired(&self, today: &str) -> bool {
+        self.until.as_deref().is_some_and(|until| until < today)
+    }
+
+    fn covers(&self, diagnostic: &LintDiagnostic) -> bool {
+        let node_path = format!("/nodes/{}", pointer_token(&self.node_id));
+        let on_node = diagnostic.path.as_deref().is_some_and(|path| {
+            path.strip_prefix(&node_path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        });
+        on_node
+            && self
+                .rules
+                .iter()
+                .any(|rule| rule.eq_ignore_ascii_case(&diagnostic.rule))
+    }
+}
+
+/// Collect the suppression comments attached to nodes in `source`.
+///
+/// Malformed directives (unknown keys, no `reason`, bad `until` date) are returned as
+/// `lint_suppression` errors and suppress nothing.
+pub fn parse_lint_suppressions(source: &str) -> (Vec<LintSuppression>, Vec<LintDiagnostic>) {
+    let mut suppressions = Vec::new();
+    let mut problems = Vec::new();
+    let lines: Vec<&str> = source.lines().collect();
+    let Some(nodes_idx) = lines.iter().position(|line| {
+        line.strip_prefix("nodes:")
+            .is_some_and(|rest| rest.trim().is_empty() || rest.trim_start().starts_with('#'))
+    }) else {
+        return (suppressions, problems);
+    };
+    let mut node_indent = None;
+    let mut pending: Vec<(usize, usize, &str)> = Vec::new();
+    for (idx, line) in lines.iter().enumerate().skip(nodes_idx + 1) {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() {
+            pending.clear();
+            continue;
+        }
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            pending.push((idx + 1, indent + 1, comment));
+            continue;
+        }
+        if indent == 0 {
+            break;
+        }
+        if *node_indent.get_or_insert(indent) != indent {
+            pending.clear();
+            continue;
+        }
+        let Some((node_id, trailing)) = split_node_key(trimmed) else {
+            pending.clear();
+            continue;
+        };
+        if let Some(trailing) = trailing {
+            let col = line.trim_end().len() - trailing.len() + 1;
+            pending.push((idx + 1, col, trailing.trim_start_matches('#')));
+        }
+        for (line_no, col, comment) in pending.drain(..) {
+            let Some(body) = comment.trim().strip_prefix(DIRECTIVE) else {
+                continue;
+            };
+            match parse_directive(body) {
+                Ok((rules, reason, until)) => suppressions.push(LintSuppression {
+                    node_id: node_id.clone(),
+                    rules,
+                    reason,
+                    until,
+                    line: line_no,
+                    suppressed: 0,
+                }),
+                Err(message) => problems.push(
+                    LintDiagnostic::error(
+                        "lint_suppression",
+                        format!("node '{node_id}': {message}"),
+                    )
+                    .with_path(format!("/nodes/{}", pointer_token(&node_id)))
+                    .with_span(LintSpan { line: line_no, col }),
+                ),
+            }
+        }
+    }
+    (suppressions, problems)
+}
+
+/// Split `key: value  # comment` into the unquoted key and the trailing comment.
+fn split_node_key(line: &str) -> Option<(String, Option<&str>)> {
+    let (key, rest) = match line.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = line[1..].find(quote)? + 1;
+            (line[1..end].to_string(), line[end + 1..].strip_prefix(':')?)
+        }
+        _ => {
+            let (key, rest) = line.split_once(':')?;
+            (key.trim_end().to_string(), rest)
+        }
+    };
+    let trailing = rest.find(" #").map(|at| rest[at + 1..].trim_end());
+    Some((key, trailing))
+}
+
+type Directive = (Vec<String>, String, Option<String>);
+
+fn parse_directive(body: &str) -> Result<Directive, String> {
+    let mut rules = Vec::new();
+    let mut reason = None;
+    let mut until = None;
+    let mut rest = body.trim();
+    while !rest.is_empty() {
+        let (key, after) = rest.split_once('=').ok_or_else(|| {
+            format!("expected key=value in greentic-lint comment, found '{rest}'")
+        })?;
+        let (value, after) = if let Some(quoted) = after.strip_prefix('"') {
+            let end = quoted
+                .find('"')
+                .ok_or_else(|| format!("unterminated quote after {key}="))?;
+            (&quoted[..end], &quoted[end + 1..])
+        } else {
+            after.split_once(char::is_whitespace).unwrap_or((after, ""))
+        };
+        match key.trim() {
+            "disable" => rules.extend(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|rule| !rule.is_empty())
+                    .map(str::to_string),
+            ),
+            "reason" => reason = Some(value.trim().to_string()),
+            "until" => {
+                if !is_iso_date(value) {
+                    return Err(format!("until must be a YYYY-MM-DD date, found '{value}'"));
+                }
+                until = Some(value.to_string());
+            }
+            other => return Err(format!("unknown greentic-lint key '{other}'")),
+        }
+        rest = after.trim_start();
+    }
+    if rules.is_empty() {
+        return Err("greentic-lint comment must name rules with disable=<rule>".to_string());
+    }
+    match reason {
+        Some(reason) if !reason.is_empty() => Ok((rules, reason, until)),
+        _ => Err(format!(
+            "suppression of {} needs a justification: add reason=\"...\"",
+            rules.join(", ")
+        )),
+    }
+}
+
+fn is_iso_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(idx, b)| match idx {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+/// Drop the findings covered by an unexpired suppression, counting them on the suppression.
+pub fn apply_lint_suppressions(
+    suppressions: &mut [LintSuppression],
+    diagnostics: Vec<LintDiagnostic>,
+    today: &str,
+) -> Vec<LintDiagnostic> {
+    diagnostics
+        .into_iter()
+        .filter(|diagnostic| {
+            let Some(suppression) = suppressions
+                .iter_mut()
+                .find(|s| !s.is_expired(today) && s.covers(diagnostic))
+            else {
+                return true;
+            };
+            suppression.suppressed += 1;
+            false
+        })
+        .collect()
+}
+
+/// Warnings for suppressions whose `until` date has passed.
+pub fn expired_suppression_warnings(
+    suppressions: &[LintSuppression],
+    today: &str,
+) -> Vec<LintDiagnostic> {
+    suppressions
+        .iter()
+        .filter(|s| s.is_expired(today))
+        .map(|s| {
+            LintDiagnostic::error(
+                "lint_suppression",
+                format!(
+                    "node '{}': suppression of {} expired on {}; its findings are reported again",
+                    s.node_id,
+                    s.rules.join(", "),
+                    s.until.as_deref().unwrap_or_default()
+                ),
+            )
+            .with_severity(LintSeverity::Warning)
+            .with_path(format!("/nodes/{}", pointer_token(&s.node_id)))
+            .with_span(LintSpan {
+                line: s.line,
+                col: 1,
+            })
+        })
+        .collect()
+}
+
+/// Today's UTC date as `YYYY-MM-DD`, for comparing against `until`.
+pub fn utc_today() -> String {
+    utc_date(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    )
+}
+
+/// The UTC date (`YYYY-MM-DD`) of a Unix timestamp in seconds.
+pub fn utc_date(epoch_seconds: u64) -> String {
+    let days = (epoch_seconds / 86_400) as i64;
+    // Civil-from-days (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,61 @@
+use super::{LintDiagnostic, LintSeverity, diagnostic::pointer_token};
+use crate::{
+    flow_ir::{FlowIr, analysis::FlowAnalysis},
+    model::FlowDoc,
+};
+use std::collections::{BTreeSet, VecDeque};
+
+/// Reports nodes, reachable from an entrypoint, from which no path ends the flow.
+///
+/// A flow ends at a route with `out: true` or `reply: true` (or `to: out`). A node without any
+/// route does not count: the runtime stops there without replying, which leaves the conversation
+/// hanging. Nodes no entrypoint reaches are left to `unreachable_node`.
+#[derive(Clone, Debug, Default)]
+pub struct TerminalPathsRule;
+
+impl TerminalPathsRule {
+    pub fn check(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+        let Ok(flow) = FlowIr::from_doc(doc.clone()) else {
+            return Vec::new();
+        };
+        let analysis = FlowAnalysis::new(&flow);
+
+        // Walk the routes backwards from every node with a terminal route.
+        let mut queue: VecDeque<&str> = flow
+            .nodes
+            .values()
+            .filter(|node| {
+                node.routing
+                    .iter()
+                    .any(|route| route.out || route.reply || route.to.as_deref() == Some("out"))
+            })
+            .map(|node| node.id.as_str())
+            .collect();
+        let mut terminates: BTreeSet<&str> = queue.iter().copied().collect();
+        while let Some(id) = queue.pop_front() {
+            for previous in analysis.predecessors(id) {
+                if terminates.insert(previous.as_str()) {
+                    queue.push_back(previous.as_str());
+                }
+            }
+        }
+
+        analysis
+            .reachable()
+            .into_iter()
+            .filter(|id| !terminates.contains(id.as_str()))
+            .map(|id| {
+                let message = if analysis.successors(&id).is_empty() {
+                    format!(
+                        "node '{id}' dead-ends: it has no route to another node, `out` or `reply`"
+                    )
+                } else {
+                    format!("no path from node '{id}' reaches an `out` or `reply` route")
+                };
+                LintDiagnostic::error("terminal_paths", message)
+                    .with_severity(LintSeverity::Warning)
+                    .with_path(format!("/nodes/{}", pointer_token(&id)))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,73 @@
+use super::{LintDiagnostic, LintSeverity, diagnostic::pointer_token};
+use greentic_types::{Flow, flow::Routing};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// Reports nodes that no route reaches from any entrypoint.
+///
+/// Dead nodes typically pile up after deletions and only show up at runtime. The walk starts
+/// from every entrypoint that names an existing node (`start` compiles to the `default`
+/// entrypoint); when none does, `start_node_exists` already reports the flow and this rule stays
+/// quiet rather than flagging every node.
+#[derive(Clone, Debug, Default)]
+pub struct UnreachableNodesRule;
+
+impl UnreachableNodesRule {
+    pub fn check(flow: &Flow) -> Vec<LintDiagnostic> {
+        let routing: HashMap<&str, &Routing> = flow
+            .nodes
+            .iter()
+            .map(|(id, node)| (id.as_str(), &node.routing))
+            .collect();
+        let mut queue: VecDeque<&str> = flow
+            .entrypoints
+            .values()
+            .filter_map(Value::as_str)
+            .filter(|target| routing.contains_key(target))
+            .collect();
+        if queue.is_empty() {
+            return Vec::new();
+        }
+        let mut reached: BTreeSet<&str> = queue.iter().copied().collect();
+        while let Some(id) = queue.pop_front() {
+            let Some(node_routing) = routing.get(id) else {
+                continue;
+            };
+            for target in routing_targets(node_routing) {
+                if reached.insert(target) {
+                    queue.push_back(target);
+                }
+            }
+        }
+
+        flow.nodes
+            .keys()
+            .filter(|id| !reached.contains(id.as_str()))
+            .map(|id| {
+                LintDiagnostic::error(
+                    "unreachable_node",
+                    format!("node '{id}' is not reachable from any entrypoint"),
+                )
+                .with_severity(LintSeverity::Warning)
+                .with_path(format!("/nodes/{}", pointer_token(id.as_str())))
+            })
+            .collect()
+    }
+}
+
+fn routing_targets(routing: &Routing) -> Vec<&str> {
+    match routing {
+        Routing::Next { node_id } => vec![node_id.as_str()],
+        Routing::Branch { on_status, default } => on_status
+            .values()
+            .chain(default)
+            .map(|id| id.as_str())
+            .collect(),
+        // Routes kept as written (negated statuses, mixed `out`/`reply` entries).
+        Routing::Custom(Value::Array(routes)) => routes
+            .iter()
+            .filter_map(|route| route.get("to").and_then(Value::as_str))
+            .collect(),
+        Routing::End | Routing::Reply | Routing::Custom(_) => Vec::new(),
+    }
+}
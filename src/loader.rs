@@ -1,8 +1,11 @@
 use crate::{
     component_schema::jsonschema_options_with_base,
     error::{FlowError, FlowErrorLocation, Result, SchemaErrorDetail},
-    model::FlowDoc,
+    features::{FEATURE_NODE_KEY, FEATURES_META_KEY, declared_features, parse_feature},
+    flow_ir::{DOC_NODE_KEY, NodeLimits, parse_node_doc, validate_status_routes},
+    model::{FlowDoc, reserved_node_keys},
     path_safety::normalize_under_root,
+    source_map::{SourceMap, pointer_token},
 };
 use serde::Deserialize;
 use serde_json::Value as JsonValue;
@@ -175,6 +178,7 @@ pub(crate) fn load_with_schema_text(
             location: yaml_error_location(&source_label, source_path, e.location()),
         })?;
     ensure_nodes_mapping(&mut v_yaml);
+    let spans = SourceMap::from_yaml(yaml);
     let v_json: Value = serde_json::to_value(&v_yaml).map_err(|e| FlowError::Internal {
         message: format!("yaml->json: {e}"),
         location: FlowErrorLocation::at_path(source_label.clone()).with_source_path(source_path),
@@ -188,26 +192,14 @@ pub(crate) fn load_with_schema_text(
         .and_then(Value::as_object)
         .map(|m| m.is_empty())
         .unwrap_or(false);
-    let reserved_for_count = [
-        "routing",
-        "telemetry",
-        "output",
-        "retry",
-        "timeout",
-        "when",
-        "annotations",
-        "meta",
-        "operation",
-    ];
+    let reserved = reserved_node_keys();
+    let is_reserved = |key: &str| reserved.iter().any(|r| r == key);
     let looks_legacy = v_json.get("nodes").and_then(Value::as_object).map(|nodes| {
         nodes.values().any(|n| {
             let (op_count, has_dot_key) = n
                 .as_object()
                 .map(|obj| {
-                    let op_count = obj
-                        .keys()
-                        .filter(|k| !reserved_for_count.contains(&k.as_str()))
-                        .count();
+                    let op_count = obj.keys().filter(|k| !is_reserved(k.as_str())).count();
                     let has_dot_key = obj.keys().any(|k| k.contains('.'));
                     (op_count, has_dot_key)
                 })
@@ -241,16 +233,40 @@ pub(crate) fn load_with_schema_text(
             let Some(obj) = node_val.as_object() else {
                 continue;
             };
-            let op_count = obj
+            let op_keys: Vec<String> = obj
                 .keys()
-                .filter(|k| !reserved_for_count.contains(&k.as_str()))
-                .count();
+                .filter(|k| !is_reserved(k.as_str()))
+                .cloned()
+                .collect();
+            let op_count = op_keys.len();
             let is_component_exec = obj.contains_key("component.exec");
             let component_combo = is_component_exec && op_count == 2;
             if op_count != 1 && !(component_combo || schema_version < 2) {
+                // A lone object under a reserved key is almost always an operation that
+                // collides with a reserved name; say so instead of the generic shape error.
+                let reserved_payloads: Vec<&String> = obj
+                    .iter()
+                    .filter(|(k, v)| {
+                        is_reserved(k.as_str())
+                            && !matches!(k.as_str(), "routing" | "telemetry")
+                            && v.is_object()
+                    })
+                    .map(|(k, _)| k)
+                    .collect();
+                if op_count == 0
+                    && let [key] = reserved_payloads.as_slice()
+                {
+                    return Err(FlowError::ReservedOperationKey {
+                        node_id: id.clone(),
+                        key: (*key).clone(),
+                        reserved: reserved.join(", "),
+                        location: node_location(&source_label, source_path, &spans, id),
+                    });
+                }
                 return Err(FlowError::NodeComponentShape {
                     node_id: id.clone(),
-                    location: node_location(&source_label, source_path, id),
+                    keys: op_keys,
+                    location: node_location(&source_label, source_path, &spans, id),
                 });
             }
         }
@@ -264,6 +280,7 @@ pub(crate) fn load_with_schema_text(
             schema_path,
             &source_label,
             source_path,
+            &spans,
         )?;
     }
 
@@ -277,6 +294,7 @@ pub(crate) fn load_with_schema_text(
                 schema_path,
                 &source_label,
                 source_path,
+                &spans,
             )?;
             return Err(FlowError::Yaml {
                 message: e.to_string(),
@@ -292,36 +310,36 @@ pub(crate) fn load_with_schema_text(
     for id in &node_ids {
         let node = flow.nodes.get_mut(id).ok_or_else(|| FlowError::Internal {
             message: format!("node '{id}' missing after load"),
-            location: node_location(&source_label, source_path, id),
+            location: node_location(&source_label, source_path, &spans, id),
         })?;
-        let reserved = [
-            "routing",
-            "telemetry",
-            "output",
-            "retry",
-            "timeout",
-            "when",
-            "annotations",
-            "meta",
-            "operation",
-        ];
-        let op_count = node
+        let op_keys: Vec<String> = node
             .raw
             .keys()
-            .filter(|k| !reserved.contains(&k.as_str()))
-            .count();
+            .filter(|k| !is_reserved(k.as_str()))
+            .cloned()
+            .collect();
+        let op_count = op_keys.len();
         let is_component_exec = node.raw.contains_key("component.exec");
         let component_combo = is_component_exec && op_count == 2;
         if op_count != 1 && !(component_combo || flow.schema_version.unwrap_or(1) < 2) {
             return Err(FlowError::NodeComponentShape {
                 node_id: id.clone(),
-                location: node_location(&source_label, source_path, id),
+                keys: op_keys,
+                location: node_location(&source_label, source_path, &spans, id),
             });
         }
     }
 
     for (from_id, node) in &flow.nodes {
-        for route in parse_routes(&node.routing, from_id, &source_label, source_path)? {
+        let routes = parse_routes(&node.routing, from_id, &source_label, source_path, &spans)?;
+        validate_status_routes(routes.iter().map(|route| route.status.as_deref())).map_err(
+            |message| FlowError::Routing {
+                node_id: from_id.clone(),
+                message,
+                location: routing_location(&source_label, source_path, &spans, from_id),
+            },
+        )?;
+        for route in routes {
             if let Some(to) = &route.to
                 && to != "out"
                 && !flow.nodes.contains_key(to)
@@ -329,15 +347,50 @@ pub(crate) fn load_with_schema_text(
                 return Err(FlowError::MissingNode {
                     target: to.clone(),
                     node_id: from_id.clone(),
-                    location: routing_location(&source_label, source_path, from_id),
+                    location: routing_location(&source_label, source_path, &spans, from_id),
                 });
             }
         }
+        let problem = match (
+            node.raw.get("limits").map(NodeLimits::from_value),
+            node.raw.get(FEATURE_NODE_KEY).map(parse_feature),
+            node.raw.get(DOC_NODE_KEY).map(parse_node_doc),
+        ) {
+            (Some(Err(message)), _, _) => Some(("limits", message)),
+            (_, Some(Err(message)), _) => Some((FEATURE_NODE_KEY, message)),
+            (_, _, Some(Err(message))) => Some((DOC_NODE_KEY, message)),
+            _ => None,
+        };
+        if let Some((key, message)) = problem {
+            let pointer = format!("/nodes/{}/{key}", pointer_token(from_id));
+            return Err(FlowError::Internal {
+                message: format!("node '{from_id}': {message}"),
+                location: FlowErrorLocation::at_path(format!(
+                    "{source_label}::nodes.{from_id}.{key}"
+                ))
+                .with_source_path(source_path)
+                .with_span(spans.locate(&pointer))
+                .with_json_pointer(Some(pointer)),
+            });
+        }
+    }
+    if let Err(message) = declared_features(flow.meta.as_ref()) {
+        let pointer = format!("/meta/{FEATURES_META_KEY}");
+        return Err(FlowError::Internal {
+            message,
+            location: FlowErrorLocation::at_path(format!(
+                "{source_label}::meta.{FEATURES_META_KEY}"
+            ))
+            .with_source_path(source_path)
+            .with_span(spans.locate(&pointer))
+            .with_json_pointer(Some(pointer)),
+        });
     }
 
     if flow.start.is_none() && flow.nodes.contains_key("in") {
         flow.start = Some("in".to_string());
     }
+    flow.spans = spans;
 
     Ok(flow)
 }
@@ -347,6 +400,7 @@ fn parse_routes(
     node_id: &str,
     source_label: &str,
     source_path: Option<&Path>,
+    spans: &SourceMap,
 ) -> Result<Vec<RouteDoc>> {
     if raw.is_null() {
         return Ok(Vec::new());
@@ -368,14 +422,14 @@ fn parse_routes(
             other => Err(FlowError::Routing {
                 node_id: node_id.to_string(),
                 message: format!("invalid routing shorthand '{other}'"),
-                location: routing_location(source_label, source_path, node_id),
+                location: routing_location(source_label, source_path, spans, node_id),
             }),
         };
     }
     serde_json::from_value::<Vec<RouteDoc>>(raw.clone()).map_err(|e| FlowError::Routing {
         node_id: node_id.to_string(),
         message: e.to_string(),
-        location: routing_location(source_label, source_path, node_id),
+        location: routing_location(source_label, source_path, spans, node_id),
     })
 }
 
@@ -386,7 +440,6 @@ struct RouteDoc {
     #[allow(dead_code)]
     #[serde(default)]
     pub out: Option<bool>,
-    #[allow(dead_code)]
     #[serde(default)]
     pub status: Option<String>,
     #[allow(dead_code)]
@@ -401,6 +454,7 @@ fn validate_json(
     schema_path: Option<&Path>,
     source_label: &str,
     source_path: Option<&Path>,
+    spans: &SourceMap,
 ) -> Result<()> {
     let schema: Value = serde_json::from_str(schema_text).map_err(|e| FlowError::Internal {
         message: format!("schema parse for {schema_label}: {e}"),
@@ -427,6 +481,7 @@ fn validate_json(
                 message: e.to_string(),
                 location: FlowErrorLocation::at_path(format!("{source_label}{pointer}"))
                     .with_source_path(source_path)
+                    .with_span(spans.locate(&pointer))
                     .with_json_pointer(Some(pointer.clone())),
             }
         })
@@ -476,19 +531,26 @@ fn ensure_nodes_mapping(doc: &mut serde_yaml_bw::Value) {
 fn node_location(
     source_label: &str,
     source_path: Option<&Path>,
+    spans: &SourceMap,
     node_id: &str,
 ) -> FlowErrorLocation {
     FlowErrorLocation::at_path(format!("{source_label}::nodes.{node_id}"))
         .with_source_path(source_path)
+        .with_json_pointer(Some(format!("/nodes/{}", pointer_token(node_id))))
+        .with_span(spans.node(node_id))
 }
 
 fn routing_location(
     source_label: &str,
     source_path: Option<&Path>,
+    spans: &SourceMap,
     node_id: &str,
 ) -> FlowErrorLocation {
+    let pointer = format!("/nodes/{}/routing", pointer_token(node_id));
     FlowErrorLocation::at_path(format!("{source_label}::nodes.{node_id}.routing"))
         .with_source_path(source_path)
+        .with_span(spans.locate(&pointer))
+        .with_json_pointer(Some(pointer))
 }
 
 pub(crate) fn yaml_error_location(
@@ -0,0 +1,250 @@
+//! Whole-project component lockfile (`flow.lock`).
+//!
+//! The lockfile records, for every flow under a project root, the component each bound node
+//! resolves to: the sidecar reference and its `sha256:` digest. Flows are keyed by their path
+//! relative to the lockfile and everything is sorted, so locking an unchanged project rewrites
+//! the same bytes.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "flows": {
+//!     "flows/main.ygtc": {
+//!       "charge": { "kind": "oci", "reference": "oci://ghcr.io/acme/charge:1.2.0", "digest": "sha256:..." }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Digests come from the sidecar when it pins one (`digest` or an `@sha256:` suffix); local
+//! files are hashed, and other references are resolved through a [`ResolverRegistry`].
+
+use anyhow::{Context, Result, bail};
+use greentic_types::flow_resolve::{
+    ComponentSourceRefV1, read_flow_resolve, sidecar_path_for_flow,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    component_resolver::ResolverRegistry,
+    flow_bundle::{ComponentSource, ComponentSourceKind},
+    lint::baseline_flow_key,
+    resolve_summary::{compute_sha256, local_path_from_sidecar},
+};
+
+/// Default lockfile name, at the project root.
+pub const LOCKFILE_NAME: &str = "flow.lock";
+
+/// Locked components of one flow, keyed by node id.
+pub type LockedFlow = BTreeMap<String, LockedComponent>;
+
+/// The locked components of every flow in a project.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowLock {
+    pub version: u32,
+    /// Keyed by flow path relative to the lockfile, with `/` separators.
+    pub flows: BTreeMap<String, LockedFlow>,
+}
+
+/// What one node is locked to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedComponent {
+    pub kind: ComponentSourceKind,
+    /// The reference as written in the sidecar.
+    pub reference: String,
+    pub digest: String,
+}
+
+impl Default for FlowLock {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            flows: BTreeMap::new(),
+        }
+    }
+}
+
+impl FlowLock {
+    pub const VERSION: u32 = 1;
+
+    pub fn from_json_str(text: &str) -> Result<Self> {
+        let lock: Self = serde_json::from_str(text).context("invalid flow.lock JSON")?;
+        if lock.version != Self::VERSION {
+            bail!(
+                "unsupported flow.lock version {} (expected {})",
+                lock.version,
+                Self::VERSION
+            );
+        }
+        Ok(lock)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        Self::from_json_str(&text).with_context(|| format!("load {}", path.display()))
+    }
+
+    /// Pretty JSON ending in a newline.
+    pub fn to_json_string(&self) -> String {
+        let mut text = serde_json::to_string_pretty(self).expect("lockfile serializes");
+        text.push('\n');
+        text
+    }
+
+    /// Number of locked nodes across all flows.
+    pub fn components(&self) -> usize {
+        self.flows.values().map(BTreeMap::len).sum()
+    }
+}
+
+/// Lock every bound node of `flows` for a lockfile at `lock_path`.
+///
+/// Flows without a sidecar, or whose sidecar binds no node, are left out.
+pub fn lock_flows(
+    registry: &ResolverRegistry,
+    lock_path: &Path,
+    flows: &[PathBuf],
+) -> Result<FlowLock> {
+    let mut lock = FlowLock::default();
+    for flow_path in flows {
+        let locked = lock_flow(registry, flow_path)?;
+        if !locked.is_empty() {
+            lock.flows
+                .insert(baseline_flow_key(lock_path, flow_path), locked);
+        }
+    }
+    Ok(lock)
+}
+
+/// The locked components of the nodes bound in `flow_path`'s sidecar.
+pub fn lock_flow(registry: &ResolverRegistry, flow_path: &Path) -> Result<LockedFlow> {
+    let sidecar_path = sidecar_path_for_flow(flow_path);
+    if !sidecar_path.exists() {
+        return Ok(LockedFlow::new());
+    }
+    let sidecar = read_flow_resolve(&sidecar_path)
+        .with_context(|| format!("read sidecar {}", sidecar_path.display()))?;
+    let mut locked = LockedFlow::new();
+    for (node_id, entry) in &sidecar.nodes {
+        let component = lock_component(registry, flow_path, &entry.source)
+            .with_context(|| format!("lock node '{node_id}' of {}", flow_path.display()))?;
+        locked.insert(node_id.clone(), component);
+    }
+    Ok(locked)
+}
+
+/// Resolve one sidecar source to the digest it names today.
+pub fn lock_component(
+    registry: &ResolverRegistry,
+    flow_path: &Path,
+    source: &ComponentSourceRefV1,
+) -> Result<LockedComponent> {
+    let classified = ComponentSource::from_sidecar(source, None);
+    let digest = match source {
+        ComponentSourceRefV1::Local { path, .. } => {
+            compute_sha256(&local_path_from_sidecar(path, flow_path))?
+        }
+        _ => match classified.digest.clone().or_else(|| {
+            classified
+                .reference
+                .split_once("@sha256:")
+                .map(|(_, hex)| format!("sha256:{hex}"))
+        }) {
+            Some(digest) => digest,
+            None => registry.resolve_digest(&classified.reference)?,
+        },
+    };
+    Ok(LockedComponent {
+        kind: classified.kind,
+        reference: classified.reference,
+        digest,
+    })
+}
+
+/// A node whose current binding differs from the lockfile.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LockDrift {
+    pub flow: String,
+    pub node_id: String,
+    /// `None` when the node is bound but not locked.
+    pub locked: Option<LockedComponent>,
+    /// `None` when the node is locked but no longer bound.
+    pub current: Option<LockedComponent>,
+}
+
+impl fmt::Display for LockDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} node '{}': ", self.flow, self.node_id)?;
+        match (&self.locked, &self.current) {
+            (None, Some(current)) => write!(f, "not locked ({})", current.reference),
+            (Some(locked), None) => write!(f, "locked but no longer bound ({})", locked.reference),
+            (Some(locked), Some(current)) if locked.reference != current.reference => write!(
+                f,
+                "reference changed from {} to {}",
+                locked.reference, current.reference
+            ),
+            (Some(locked), Some(current)) => write!(
+                f,
+                "{} resolves to {}, locked {}",
+                current.reference, current.digest, locked.digest
+            ),
+            (None, None) => f.write_str("unchanged"),
+        }
+    }
+}
+
+/// Nodes of the flows in `current` whose binding differs from `lock`.
+///
+/// Only flows present in `current` are compared, so checking part of a project ignores the rest
+/// of the lockfile; pass `all_flows` to also report locked flows missing from `current`.
+pub fn diff_lock(lock: &FlowLock, current: &FlowLock, all_flows: bool) -> Vec<LockDrift> {
+    let empty = LockedFlow::new();
+    let mut flows: Vec<&String> = current.flows.keys().collect();
+    if all_flows {
+        flows.extend(lock.flows.keys());
+        flows.sort();
+        flows.dedup();
+    }
+    let mut drift = Vec::new();
+    for flow in flows {
+        let locked = lock.flows.get(flow).unwrap_or(&empty);
+        let now = current.flows.get(flow).unwrap_or(&empty);
+        let mut nodes: Vec<&String> = locked.keys().chain(now.keys()).collect();
+        nodes.sort();
+        nodes.dedup();
+        for node_id in nodes {
+            let (locked, now) = (locked.get(node_id), now.get(node_id));
+            if locked != now {
+                drift.push(LockDrift {
+                    flow: flow.clone(),
+                    node_id: node_id.clone(),
+                    locked: locked.cloned(),
+                    current: now.cloned(),
+                });
+            }
+        }
+    }
+    drift
+}
+
+/// Re-lock `flows` and report where they differ from the lockfile at `lock_path`.
+pub fn verify_flows(
+    registry: &ResolverRegistry,
+    lock_path: &Path,
+    flows: &[PathBuf],
+) -> Result<Vec<LockDrift>> {
+    let lock = FlowLock::load(lock_path)?;
+    let mut current = FlowLock::default();
+    for flow_path in flows {
+        current.flows.insert(
+            baseline_flow_key(lock_path, flow_path),
+            lock_flow(registry, flow_path)?,
+        );
+    }
+    Ok(diff_lock(&lock, &current, false))
+}
@@ -0,0 +1,313 @@
+//! Upgrades of legacy YGTC documents to newer `schema_version`s.
+//!
+//! Each upgrade step takes a document from one version to the next and records every change it
+//! makes, so `migrate --to N` can chain steps and report all of them. The document is edited as
+//! YAML: keys and nodes keep their order, and values the steps do not touch are left as they are.
+//!
+//! Version 1 to 2:
+//! - `component.exec` plus `operation` (on the node or inside the payload) becomes a single
+//!   operation key holding the payload;
+//! - `pack_alias` moves under the node's `meta`;
+//! - a routing mapping becomes a one-route list, a routing string other than `out`/`reply`
+//!   becomes a route to that node, and string list entries become `{to: ...}` routes.
+//!
+//! A document without `schema_version` is treated as version 1.
+
+use serde::Serialize;
+use serde_yaml_bw::{Mapping, Sequence, Value as YamlValue};
+
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    loader::{load_ygtc_from_str, yaml_error_location},
+    model::reserved_node_keys,
+};
+
+/// The `schema_version` the loader, editors and `greentic-flow new` write.
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// One transformation applied by [`migrate_flow`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MigrationChange {
+    /// Dotted location in the flow (e.g. `nodes.fetch.routing`).
+    pub location: String,
+    /// The rewrite that made the change, e.g. `component-exec`.
+    pub rule: &'static str,
+    pub message: String,
+}
+
+/// Result of [`migrate_flow`].
+#[derive(Clone, Debug, Serialize)]
+pub struct MigratedFlow {
+    pub yaml: String,
+    pub from: u32,
+    pub to: u32,
+    pub changes: Vec<MigrationChange>,
+}
+
+type Step = fn(&mut Mapping, &mut Vec<MigrationChange>) -> Result<()>;
+
+/// Upgrade steps; entry `i` takes a document from version `i + 1` to `i + 2`.
+const STEPS: [Step; (CURRENT_SCHEMA_VERSION - 1) as usize] = [v1_to_v2];
+
+/// Upgrade `yaml` to schema version `to`, returning the new text and the changes made.
+///
+/// The result is validated like a loaded flow. A document already at `to` is returned as is;
+/// downgrades and versions newer than [`CURRENT_SCHEMA_VERSION`] are rejected.
+pub fn migrate_flow(yaml: &str, to: u32) -> Result<MigratedFlow> {
+    let mut doc: YamlValue = serde_yaml_bw::from_str(yaml).map_err(|e| FlowError::Yaml {
+        message: e.to_string(),
+        location: yaml_error_location("<inline>", None, e.location()),
+    })?;
+    let root = doc.as_mapping_mut().ok_or_else(|| FlowError::Internal {
+        message: "flow document must be a mapping".to_string(),
+        location: FlowErrorLocation::at_path("<inline>"),
+    })?;
+    let from = match root.get("schema_version") {
+        None => 1,
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| (1..=CURRENT_SCHEMA_VERSION).contains(v))
+            .ok_or_else(|| FlowError::Internal {
+                message: format!(
+                    "unsupported schema_version {}; known versions are 1 to {CURRENT_SCHEMA_VERSION}",
+                    render_scalar(value)
+                ),
+                location: FlowErrorLocation::at_path("schema_version"),
+            })?,
+    };
+    if to > CURRENT_SCHEMA_VERSION {
+        return Err(FlowError::Internal {
+            message: format!(
+                "cannot migrate to schema_version {to}; the newest is {CURRENT_SCHEMA_VERSION}"
+            ),
+            location: FlowErrorLocation::at_path("schema_version"),
+        });
+    }
+    if to < from {
+        return Err(FlowError::Internal {
+            message: format!("cannot downgrade schema_version {from} to {to}"),
+            location: FlowErrorLocation::at_path("schema_version"),
+        });
+    }
+    let declared = root.contains_key("schema_version");
+    if to == from && declared {
+        return Ok(MigratedFlow {
+            yaml: yaml.to_string(),
+            from,
+            to,
+            changes: Vec::new(),
+        });
+    }
+
+    let mut changes = Vec::new();
+    for step in &STEPS[(from - 1) as usize..(to - 1) as usize] {
+        step(root, &mut changes)?;
+    }
+    set_schema_version(root, to);
+    changes.push(MigrationChange {
+        location: "schema_version".to_string(),
+        rule: "schema-version",
+        message: if declared {
+            format!("schema_version {from} -> {to}")
+        } else {
+            format!("added schema_version {to}")
+        },
+    });
+
+    let mut out = serde_yaml_bw::to_string(&doc).map_err(|e| FlowError::Internal {
+        message: format!("serialize migrated flow: {e}"),
+        location: FlowErrorLocation::at_path("<inline>"),
+    })?;
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    if to == CURRENT_SCHEMA_VERSION {
+        load_ygtc_from_str(&out)?;
+    }
+    Ok(MigratedFlow {
+        yaml: out,
+        from,
+        to,
+        changes,
+    })
+}
+
+fn v1_to_v2(root: &mut Mapping, changes: &mut Vec<MigrationChange>) -> Result<()> {
+    let Some(nodes) = root.get_mut("nodes").and_then(YamlValue::as_mapping_mut) else {
+        return Ok(());
+    };
+    let reserved = reserved_node_keys();
+    for (id, node) in nodes.iter_mut() {
+        let Some(id) = id.as_str().map(str::to_string) else {
+            continue;
+        };
+        let Some(node) = node.as_mapping_mut() else {
+            continue;
+        };
+        let location = format!("nodes.{id}");
+        if node.contains_key("component.exec") {
+            let operation = exec_operation(node).ok_or_else(|| FlowError::Internal {
+                message: format!(
+                    "node '{id}' uses component.exec without an operation; add `operation:` before migrating"
+                ),
+                location: FlowErrorLocation::at_path(location.clone()),
+            })?;
+            *node = node
+                .clone()
+                .into_iter()
+                .filter(|(key, _)| key.as_str() != Some("operation"))
+                .map(|(key, value)| match key.as_str() {
+                    Some("component.exec") => (key_value(&operation), without_operation(value)),
+                    _ => (key, value),
+                })
+                .collect();
+            changes.push(MigrationChange {
+                location: location.clone(),
+                rule: "component-exec",
+                message: format!("component.exec with operation '{operation}' -> '{operation}:'"),
+            });
+        }
+        if let Some(alias) = node.remove("pack_alias") {
+            let meta = node
+                .entry(key_value("meta"))
+                .or_insert_with(|| YamlValue::Mapping(Mapping::new()));
+            let Some(meta) = meta.as_mapping_mut() else {
+                return Err(FlowError::Internal {
+                    message: format!("node '{id}' has pack_alias but its meta is not a mapping"),
+                    location: FlowErrorLocation::at_path(format!("{location}.meta")),
+                });
+            };
+            meta.insert(key_value("pack_alias"), alias);
+            changes.push(MigrationChange {
+                location: location.clone(),
+                rule: "pack-alias",
+                message: "pack_alias -> meta.pack_alias".to_string(),
+            });
+        }
+        if let Some(routing) = node.get_mut("routing") {
+            migrate_routing(routing, &format!("{location}.routing"), changes);
+        }
+
+        let op_keys: Vec<String> = node
+            .keys()
+            .filter_map(YamlValue::as_str)
+            .filter(|key| !reserved.iter().any(|r| r == key))
+            .map(str::to_string)
+            .collect();
+        if op_keys.len() != 1 {
+            return Err(FlowError::NodeComponentShape {
+                node_id: id,
+                keys: op_keys,
+                location: FlowErrorLocation::at_path(location),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The operation of a `component.exec` node: its `operation` key, else the payload's.
+fn exec_operation(node: &Mapping) -> Option<String> {
+    node.get("operation")
+        .and_then(YamlValue::as_str)
+        .filter(|op| !op.trim().is_empty())
+        .or_else(|| {
+            node.get("component.exec")
+                .and_then(|exec| exec.get("operation"))
+                .and_then(YamlValue::as_str)
+                .filter(|op| !op.trim().is_empty())
+        })
+        .map(str::to_string)
+}
+
+fn without_operation(mut payload: YamlValue) -> YamlValue {
+    if let Some(map) = payload.as_mapping_mut() {
+        map.shift_remove("operation");
+    }
+    payload
+}
+
+fn migrate_routing(routing: &mut YamlValue, location: &str, changes: &mut Vec<MigrationChange>) {
+    match routing {
+        YamlValue::Mapping(route) => {
+            let route = std::mem::take(route);
+            *routing = sequence(vec![YamlValue::Mapping(route)]);
+            changes.push(MigrationChange {
+                location: location.to_string(),
+                rule: "routing-mapping",
+                message: "single route mapping -> route list".to_string(),
+            });
+        }
+        YamlValue::String(target, _) if !matches!(target.as_str(), "out" | "reply") => {
+            let target = target.clone();
+            *routing = sequence(vec![route_to(&target)]);
+            changes.push(MigrationChange {
+                location: location.to_string(),
+                rule: "routing-string",
+                message: format!("routing '{target}' -> [{{to: {target}}}]"),
+            });
+        }
+        YamlValue::Sequence(routes) => {
+            for (index, route) in routes.iter_mut().enumerate() {
+                if let Some(target) = route.as_str().map(str::to_string) {
+                    *route = route_to(&target);
+                    changes.push(MigrationChange {
+                        location: format!("{location}[{index}]"),
+                        rule: "routing-string",
+                        message: format!("route '{target}' -> {{to: {target}}}"),
+                    });
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn set_schema_version(root: &mut Mapping, version: u32) {
+    if let Some(value) = root.get_mut("schema_version") {
+        *value = YamlValue::from(version);
+        return;
+    }
+    // Place it where `FlowDoc` serializes it: after `tags`, or after `type` without tags.
+    let anchor = if root.contains_key("tags") {
+        "tags"
+    } else {
+        "type"
+    };
+    let entries = std::mem::take(root);
+    let mut placed = false;
+    for (key, value) in entries {
+        let after = key.as_str() == Some(anchor);
+        root.insert(key, value);
+        if after {
+            root.insert(key_value("schema_version"), YamlValue::from(version));
+            placed = true;
+        }
+    }
+    if !placed {
+        root.insert(key_value("schema_version"), YamlValue::from(version));
+    }
+}
+
+fn route_to(target: &str) -> YamlValue {
+    let mut route = Mapping::new();
+    route.insert(key_value("to"), key_value(target));
+    YamlValue::Mapping(route)
+}
+
+fn sequence(elements: Vec<YamlValue>) -> YamlValue {
+    YamlValue::Sequence(Sequence {
+        anchor: None,
+        elements,
+    })
+}
+
+fn key_value(text: &str) -> YamlValue {
+    YamlValue::String(text.to_string(), None)
+}
+
+fn render_scalar(value: &YamlValue) -> String {
+    serde_yaml_bw::to_string(value)
+        .map(|text| text.trim().to_string())
+        .unwrap_or_else(|_| "?".to_string())
+}
@@ -0,0 +1,271 @@
+use crate::{
+    compile_flow,
+    error::{FlowError, FlowErrorLocation, Result},
+    lint::{RuleContext, RuleRegistry},
+    loader::{load_ygtc_from_str, yaml_error_location},
+};
+use serde::Serialize;
+use serde_yaml_bw::{Mapping, Value as YamlValue};
+use std::{collections::BTreeSet, str::FromStr};
+
+/// What a minimized flow must keep reproducing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MinimizeCheck {
+    /// `lint:<rule>`: one of the built-in lint rules reports `<rule>`.
+    LintRule(String),
+    /// `error:<text>`: loading or compiling the flow fails with a message containing `<text>`.
+    ErrorContains(String),
+}
+
+impl FromStr for MinimizeCheck {
+    type Err = String;
+
+    fn from_str(spec: &str) -> std::result::Result<Self, Self::Err> {
+        let (kind, arg) = spec
+            .split_once(':')
+            .map(|(kind, arg)| (kind.trim(), arg.trim()))
+            .ok_or_else(|| format!("expected lint:<rule> or error:<text>, found '{spec}'"))?;
+        if arg.is_empty() {
+            return Err(format!("check '{spec}' needs a value after '{kind}:'"));
+        }
+        match kind {
+            "lint" => Ok(Self::LintRule(arg.to_string())),
+            "error" => Ok(Self::ErrorContains(arg.to_string())),
+            other => Err(format!(
+                "unknown check kind '{other}' (expected lint or error)"
+            )),
+        }
+    }
+}
+
+impl MinimizeCheck {
+    pub fn reproduces(&self, yaml: &str) -> bool {
+        let compiled = load_ygtc_from_str(yaml)
+            .and_then(|doc| compile_flow(doc.clone()).map(|flow| (flow, doc)));
+        match (self, compiled) {
+            (Self::LintRule(rule), Ok((flow, doc))) => RuleRegistry::builtin()
+                .run(&flow, &RuleContext::default().with_doc(&doc))
+                .iter()
+                .any(|d| d.rule.eq_ignore_ascii_case(rule)),
+            (Self::ErrorContains(text), Err(err)) => err.to_string().contains(text.as_str()),
+            _ => false,
+        }
+    }
+}
+
+/// Result of [`minimize_flow`].
+#[derive(Clone, Debug, Serialize)]
+pub struct MinimizedFlow {
+    pub yaml: String,
+    pub nodes_before: usize,
+    pub nodes_after: usize,
+    pub routes_before: usize,
+    pub routes_after: usize,
+    /// Number of candidate flows the check was run against.
+    pub checks: usize,
+}
+
+/// Shrink `yaml` to a smaller flow on which `reproduces` still holds, by delta debugging.
+///
+/// Nodes are removed first (together with routes and entrypoints that target them, and `start`
+/// when it is removed), then individual route entries. Candidates are tried in document order,
+/// so the same input and check always yield the same flow. The result is 1-minimal: removing
+/// any single remaining node or route stops the check from reproducing.
+pub fn minimize_flow(
+    yaml: &str,
+    mut reproduces: impl FnMut(&str) -> bool,
+) -> Result<MinimizedFlow> {
+    let original: YamlValue = serde_yaml_bw::from_str(yaml).map_err(|e| FlowError::Yaml {
+        message: e.to_string(),
+        location: yaml_error_location("<inline>", None, e.location()),
+    })?;
+    if nodes(&original).is_none() {
+        return Err(FlowError::Internal {
+            message: "flow document has no nodes mapping".to_string(),
+            location: FlowErrorLocation::at_path("nodes"),
+        });
+    }
+    if !reproduces(yaml) {
+        return Err(FlowError::Internal {
+            message: "the check does not reproduce on the original flow".to_string(),
+            location: FlowErrorLocation::at_path("<inline>"),
+        });
+    }
+    let mut checks = 1;
+    let mut test = |candidate: &YamlValue| {
+        checks += 1;
+        render(candidate).is_some_and(|text| reproduces(&text))
+    };
+
+    let node_ids = node_ids(&original);
+    let kept_nodes = ddmin(node_ids.clone(), |kept| {
+        test(&without_nodes(&original, &removed(&node_ids, kept)))
+    });
+    let reduced = without_nodes(&original, &removed(&node_ids, &kept_nodes));
+
+    let routes = route_ids(&reduced);
+    let kept_routes = ddmin(routes.clone(), |kept| {
+        test(&without_routes(&reduced, &removed(&routes, kept)))
+    });
+    let minimized = without_routes(&reduced, &removed(&routes, &kept_routes));
+
+    Ok(MinimizedFlow {
+        yaml: render(&minimized).ok_or_else(|| FlowError::Internal {
+            message: "serialize minimized flow".to_string(),
+            location: FlowErrorLocation::at_path("<inline>"),
+        })?,
+        nodes_before: node_ids.len(),
+        nodes_after: kept_nodes.len(),
+        routes_before: route_ids(&original).len(),
+        routes_after: kept_routes.len(),
+        checks,
+    })
+}
+
+/// Zeller's ddmin, reducing to complements only: split into `n` chunks, keep the first
+/// complement that still passes, otherwise refine the split.
+fn ddmin<T: Clone>(mut items: Vec<T>, mut passes: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    if items.len() == 1 && passes(&[]) {
+        return Vec::new();
+    }
+    let mut n = 2;
+    while items.len() >= 2 {
+        let chunk = items.len().div_ceil(n);
+        let reduced = (0..items.len()).step_by(chunk).find_map(|start| {
+            let complement: Vec<T> = items[..start]
+                .iter()
+                .chain(items[(start + chunk).min(items.len())..].iter())
+                .cloned()
+                .collect();
+            passes(&complement).then_some(complement)
+        });
+        match reduced {
+            Some(complement) => {
+                items = complement;
+                n = (n - 1).max(2);
+            }
+            None if n >= items.len() => break,
+            None => n = (n * 2).min(items.len()),
+        }
+    }
+    if items.len() == 1 && passes(&[]) {
+        items.clear();
+    }
+    items
+}
+
+fn removed<T: Clone + Ord>(all: &[T], kept: &[T]) -> BTreeSet<T> {
+    let kept: BTreeSet<&T> = kept.iter().collect();
+    all.iter()
+        .filter(|item| !kept.contains(item))
+        .cloned()
+        .collect()
+}
+
+fn render(doc: &YamlValue) -> Option<String> {
+    serde_yaml_bw::to_string(doc).ok()
+}
+
+fn key(text: &str) -> YamlValue {
+    YamlValue::String(text.to_string(), None)
+}
+
+fn nodes(doc: &YamlValue) -> Option<&Mapping> {
+    doc.get("nodes").and_then(YamlValue::as_mapping)
+}
+
+fn node_ids(doc: &YamlValue) -> Vec<String> {
+    nodes(doc)
+        .map(|nodes| {
+            nodes
+                .keys()
+                .filter_map(YamlValue::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `(node id, index)` of every route entry in a sequence-valued `routing`.
+fn route_ids(doc: &YamlValue) -> Vec<(String, usize)> {
+    node_ids(doc)
+        .into_iter()
+        .flat_map(|node_id| {
+            let count = nodes(doc)
+                .and_then(|nodes| nodes.get(key(&node_id)))
+                .and_then(|node| node.get("routing"))
+                .and_then(YamlValue::as_sequence)
+                .map_or(0, |routes| routes.len());
+            (0..count).map(move |idx| (node_id.clone(), idx))
+        })
+        .collect()
+}
+
+fn targets_removed(value: &YamlValue, gone: &BTreeSet<String>) -> bool {
+    value.as_str().is_some_and(|target| gone.contains(target))
+}
+
+fn without_nodes(doc: &YamlValue, gone: &BTreeSet<String>) -> YamlValue {
+    let mut doc = doc.clone();
+    let Some(root) = doc.as_mapping_mut() else {
+        return doc;
+    };
+    if root
+        .get("start")
+        .is_some_and(|start| targets_removed(start, gone))
+    {
+        root.remove("start");
+    }
+    if let Some(entrypoints) = root
+        .get_mut("entrypoints")
+        .and_then(YamlValue::as_mapping_mut)
+    {
+        entrypoints.retain(|_, target| !targets_removed(target, gone));
+    }
+    if let Some(nodes) = root.get_mut("nodes").and_then(YamlValue::as_mapping_mut) {
+        nodes.retain(|id, _| !id.as_str().is_some_and(|id| gone.contains(id)));
+        for node in nodes.values_mut() {
+            if let Some(routes) = node
+                .as_mapping_mut()
+                .and_then(|node| node.get_mut(key("routing")))
+                .and_then(YamlValue::as_sequence_mut)
+            {
+                routes.retain(|route| {
+                    !route
+                        .get("to")
+                        .is_some_and(|target| targets_removed(target, gone))
+                });
+            }
+        }
+    }
+    doc
+}
+
+fn without_routes(doc: &YamlValue, gone: &BTreeSet<(String, usize)>) -> YamlValue {
+    let mut doc = doc.clone();
+    let Some(nodes) = doc
+        .as_mapping_mut()
+        .and_then(|root| root.get_mut(key("nodes")))
+        .and_then(YamlValue::as_mapping_mut)
+    else {
+        return doc;
+    };
+    for (id, node) in nodes.iter_mut() {
+        let Some(node_id) = id.as_str() else {
+            continue;
+        };
+        if let Some(routes) = node
+            .as_mapping_mut()
+            .and_then(|node| node.get_mut(key("routing")))
+            .and_then(YamlValue::as_sequence_mut)
+        {
+            let mut idx = 0;
+            routes.retain(|_| {
+                let keep = !gone.contains(&(node_id.to_string(), idx));
+                idx += 1;
+                keep
+            });
+        }
+    }
+    doc
+}
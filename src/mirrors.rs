@@ -0,0 +1,124 @@
+//! Registry mirror rules: rewrite component references before they are resolved.
+//!
+//! A mirrors file lists rules in order; the first whose `from` matches rewrites the reference.
+//! A pattern ending in `*` matches by prefix and carries the rest of the reference over to the
+//! `*` at the end of `to`; any other pattern matches one reference exactly.
+//!
+//! ```toml
+//! [[mirror]]
+//! from = "oci://ghcr.io/acme/*"
+//! to = "oci://registry.internal/acme/*"
+//! ```
+//!
+//! Sidecars and flows keep the authored reference; only resolution uses the rewritten one, and
+//! [`ResolvedComponent::provenance`](crate::component_resolver::ResolvedComponent::provenance)
+//! records both.
+
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+use crate::error::{FlowError, FlowErrorLocation, Result};
+
+/// Environment variable naming the mirrors file; unset means references resolve as written.
+pub const MIRRORS_FILE_ENV: &str = "GREENTIC_FLOW_MIRRORS";
+
+/// One `from` → `to` rewrite.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MirrorRule {
+    pub from: String,
+    pub to: String,
+}
+
+/// Ordered mirror rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MirrorRules {
+    #[serde(default, rename = "mirror")]
+    pub rules: Vec<MirrorRule>,
+}
+
+impl MirrorRule {
+    fn validate(&self, index: usize) -> Result<()> {
+        let wildcard = |pattern: &str| pattern.strip_suffix('*').unwrap_or(pattern).contains('*');
+        if wildcard(&self.from) || wildcard(&self.to) {
+            return Err(mirror_error(format!(
+                "mirror {index}: '*' is only allowed at the end of a pattern"
+            )));
+        }
+        if self.from.ends_with('*') != self.to.ends_with('*') {
+            return Err(mirror_error(format!(
+                "mirror {index}: '{}' and '{}' must both end in '*' or neither",
+                self.from, self.to
+            )));
+        }
+        if self.from.is_empty() || self.from == "*" {
+            return Err(mirror_error(format!(
+                "mirror {index}: 'from' must name a registry or reference prefix"
+            )));
+        }
+        Ok(())
+    }
+
+    /// `reference` rewritten by this rule, when it matches.
+    pub fn apply(&self, reference: &str) -> Option<String> {
+        match self.from.strip_suffix('*') {
+            Some(prefix) => {
+                let rest = reference.strip_prefix(prefix)?;
+                Some(format!(
+                    "{}{rest}",
+                    self.to.strip_suffix('*').unwrap_or(&self.to)
+                ))
+            }
+            None => (reference == self.from).then(|| self.to.clone()),
+        }
+    }
+}
+
+impl MirrorRules {
+    pub fn from_toml_str(text: &str) -> Result<Self> {
+        let rules: Self = toml::from_str(text)
+            .map_err(|e| mirror_error(format!("invalid mirrors file: {}", e.message())))?;
+        for (index, rule) in rules.rules.iter().enumerate() {
+            rule.validate(index)?;
+        }
+        Ok(rules)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| mirror_error(format!("read {}: {e}", path.display())))?;
+        Self::from_toml_str(&text).map_err(|err| match err {
+            FlowError::Internal { message, .. } => FlowError::Internal {
+                message: format!("{message} ({})", path.display()),
+                location: FlowErrorLocation::at_path(path.display().to_string())
+                    .with_source_path(Some(path)),
+            },
+            other => other,
+        })
+    }
+
+    /// The rules in the file named by [`MIRRORS_FILE_ENV`]; empty when it is unset.
+    pub fn from_env() -> Result<Self> {
+        match std::env::var_os(MIRRORS_FILE_ENV).filter(|path| !path.is_empty()) {
+            Some(path) => Self::load(Path::new(&path)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// `reference` rewritten by the first matching rule, or `None` when no rule matches.
+    pub fn rewrite(&self, reference: &str) -> Option<String> {
+        self.rules.iter().find_map(|rule| rule.apply(reference))
+    }
+}
+
+fn mirror_error(message: String) -> FlowError {
+    FlowError::Internal {
+        message,
+        location: FlowErrorLocation::at_path(MIRRORS_FILE_ENV),
+    }
+}
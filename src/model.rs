@@ -1,8 +1,44 @@
+use crate::source_map::SourceMap;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
 
+/// Node keys reserved for node-level settings; any other key on a node names its operation.
+///
+/// `operation` is reserved for the legacy `component.exec` + `operation` node shape.
+pub const RESERVED_NODE_KEYS: &[&str] = &[
+    "routing",
+    "telemetry",
+    "output",
+    "retry",
+    "timeout",
+    "limits",
+    "feature",
+    "doc",
+    "when",
+    "annotations",
+    "meta",
+    "operation",
+];
+
+/// Environment variable listing extra reserved node keys (comma-separated), for forks that add
+/// their own node-level settings.
+pub const RESERVED_NODE_KEYS_ENV: &str = "GREENTIC_FLOW_RESERVED_KEYS";
+
+/// [`RESERVED_NODE_KEYS`] plus any keys listed in [`RESERVED_NODE_KEYS_ENV`].
+pub fn reserved_node_keys() -> Vec<String> {
+    let mut keys: Vec<String> = RESERVED_NODE_KEYS.iter().map(|k| k.to_string()).collect();
+    if let Ok(extra) = std::env::var(RESERVED_NODE_KEYS_ENV) {
+        for key in extra.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+            if !keys.iter().any(|existing| existing == key) {
+                keys.push(key.to_string());
+            }
+        }
+    }
+    keys
+}
+
 fn default_parameters() -> Value {
     Value::Object(Default::default())
 }
@@ -37,6 +73,9 @@ pub struct FlowDoc {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub meta: Option<Value>,
     pub nodes: IndexMap<String, NodeDoc>,
+    /// Where each value sits in the source; filled by the loader, empty otherwise.
+    #[serde(skip)]
+    pub spans: SourceMap,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
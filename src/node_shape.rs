@@ -0,0 +1,181 @@
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    loader::yaml_error_location,
+    model::reserved_node_keys,
+};
+use serde_yaml_bw::{Mapping, Value as YamlValue};
+use std::fmt;
+
+const ANNOTATIONS_KEY: &str = "annotations";
+
+/// A node carrying more than one non-reserved key, so its operation is ambiguous.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiOperationNode {
+    pub node_id: String,
+    /// Conflicting keys in document order.
+    pub keys: Vec<String>,
+}
+
+/// Where a misplaced key is moved when repairing a multi-operation node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixTarget {
+    /// Into the operation payload (the payload is a mapping without that key).
+    Payload,
+    /// Into the node's `annotations` mapping.
+    Annotations,
+}
+
+/// Repair for a [`MultiOperationNode`]: keep `operation` and move the other keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiOperationFix {
+    pub node_id: String,
+    pub operation: String,
+    pub moves: Vec<(String, FixTarget)>,
+}
+
+impl fmt::Display for MultiOperationFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let moves: Vec<String> = self
+            .moves
+            .iter()
+            .map(|(key, target)| match target {
+                FixTarget::Payload => format!("move '{key}' into the '{}' payload", self.operation),
+                FixTarget::Annotations => format!("move '{key}' under '{ANNOTATIONS_KEY}'"),
+            })
+            .collect();
+        write!(
+            f,
+            "node '{}': keep '{}' as the operation; {}",
+            self.node_id,
+            self.operation,
+            moves.join(", ")
+        )
+    }
+}
+
+/// List nodes whose mapping holds more than one non-reserved key.
+///
+/// Legacy `component.exec` nodes are skipped; they legitimately pair `component.exec` with an
+/// `operation` key.
+pub fn find_multi_operation_nodes(flow_yaml: &str) -> Result<Vec<MultiOperationNode>> {
+    let doc = parse(flow_yaml)?;
+    let reserved = reserved_node_keys();
+    let Some(nodes) = doc.get("nodes").and_then(YamlValue::as_mapping) else {
+        return Ok(Vec::new());
+    };
+    let mut found = Vec::new();
+    for (id, node) in nodes {
+        let (Some(node_id), Some(map)) = (id.as_str(), node.as_mapping()) else {
+            continue;
+        };
+        if map.contains_key("component.exec") {
+            continue;
+        }
+        let keys: Vec<String> = map
+            .keys()
+            .filter_map(YamlValue::as_str)
+            .filter(|key| !reserved.iter().any(|r| r == key))
+            .map(str::to_string)
+            .collect();
+        if keys.len() > 1 {
+            found.push(MultiOperationNode {
+                node_id: node_id.to_string(),
+                keys,
+            });
+        }
+    }
+    Ok(found)
+}
+
+/// Suggest a repair when exactly one conflicting key is a known component/operation id.
+pub fn suggest_multi_operation_fix(
+    flow_yaml: &str,
+    node: &MultiOperationNode,
+    is_operation: impl Fn(&str) -> bool,
+) -> Result<Option<MultiOperationFix>> {
+    let matches: Vec<&String> = node.keys.iter().filter(|key| is_operation(key)).collect();
+    let [operation] = matches.as_slice() else {
+        return Ok(None);
+    };
+    let doc = parse(flow_yaml)?;
+    let payload = doc
+        .get("nodes")
+        .and_then(|nodes| nodes.get(node.node_id.as_str()))
+        .and_then(|value| value.get(operation.as_str()));
+    let payload_map = match payload {
+        Some(YamlValue::Mapping(map)) => Some(map.clone()),
+        Some(YamlValue::Null(_)) | None => Some(Mapping::new()),
+        Some(_) => None,
+    };
+    let moves = node
+        .keys
+        .iter()
+        .filter(|key| key != operation)
+        .map(|key| {
+            let target = match &payload_map {
+                Some(map) if !map.contains_key(key.as_str()) => FixTarget::Payload,
+                _ => FixTarget::Annotations,
+            };
+            (key.clone(), target)
+        })
+        .collect();
+    Ok(Some(MultiOperationFix {
+        node_id: node.node_id.clone(),
+        operation: (*operation).clone(),
+        moves,
+    }))
+}
+
+/// Apply repairs to the flow YAML, returning the rewritten document.
+pub fn apply_multi_operation_fixes(flow_yaml: &str, fixes: &[MultiOperationFix]) -> Result<String> {
+    let mut doc = parse(flow_yaml)?;
+    for fix in fixes {
+        let node = doc
+            .as_mapping_mut()
+            .and_then(|root| root.get_mut("nodes"))
+            .and_then(YamlValue::as_mapping_mut)
+            .and_then(|nodes| nodes.get_mut(fix.node_id.as_str()))
+            .and_then(YamlValue::as_mapping_mut)
+            .ok_or_else(|| FlowError::Internal {
+                message: format!("node '{}' not found", fix.node_id),
+                location: FlowErrorLocation::at_path(format!("nodes.{}", fix.node_id)),
+            })?;
+        for (key, target) in &fix.moves {
+            let Some(value) = node.shift_remove(key.as_str()) else {
+                continue;
+            };
+            let destination = match target {
+                FixTarget::Payload => fix.operation.as_str(),
+                FixTarget::Annotations => ANNOTATIONS_KEY,
+            };
+            let slot = node
+                .entry(YamlValue::from(destination))
+                .or_insert_with(|| YamlValue::Mapping(Mapping::new()));
+            if matches!(slot, YamlValue::Null(_)) {
+                *slot = YamlValue::Mapping(Mapping::new());
+            }
+            let map = slot.as_mapping_mut().ok_or_else(|| FlowError::Internal {
+                message: format!(
+                    "node '{}': cannot move '{key}' into non-mapping '{destination}'",
+                    fix.node_id
+                ),
+                location: FlowErrorLocation::at_path(format!(
+                    "nodes.{}.{destination}",
+                    fix.node_id
+                )),
+            })?;
+            map.insert(YamlValue::from(key.as_str()), value);
+        }
+    }
+    serde_yaml_bw::to_string(&doc).map_err(|e| FlowError::Internal {
+        message: format!("serialize repaired flow: {e}"),
+        location: FlowErrorLocation::at_path("flow"),
+    })
+}
+
+fn parse(flow_yaml: &str) -> Result<YamlValue> {
+    serde_yaml_bw::from_str(flow_yaml).map_err(|e| FlowError::Yaml {
+        message: e.to_string(),
+        location: yaml_error_location("<inline>", None, e.location()),
+    })
+}
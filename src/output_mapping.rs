@@ -0,0 +1,198 @@
+//! A node's `output:` mapping: which parts of the component result land in flow state.
+//!
+//! `output` maps state keys to selectors into the result the component returned:
+//!
+//! ```yaml
+//! output:
+//!   weather: "$"                 # the whole result
+//!   temperature: "$.current.temp"
+//!   first_alert: "$.alerts[0]"
+//!   forecast.today: "$.days[0]"  # nested: state.forecast.today
+//! ```
+//!
+//! A state key is one or more dot-separated identifiers (`[A-Za-z_][A-Za-z0-9_-]*`); dots nest,
+//! so templates read the value above as `{{state.forecast.today}}`. A selector is `$` followed by
+//! `.field` and `[index]` steps. A selector that does not match the result captures nothing.
+//! Keys are applied in the order written, so a later key overwrites an earlier one that overlaps
+//! it; [`parse_output_mapping`] reports such overlaps.
+
+use serde_json::{Map, Value};
+
+/// One `state key: selector` entry of an output mapping.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputCapture {
+    pub key: String,
+    pub selector: String,
+    pub steps: Vec<SelectorStep>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SelectorStep {
+    Field(String),
+    Index(usize),
+}
+
+/// A malformed entry; `key` is `None` when the mapping itself is not an object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputMappingProblem {
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl OutputCapture {
+    /// The part of `result` this capture selects.
+    pub fn select<'a>(&self, result: &'a Value) -> Option<&'a Value> {
+        self.steps
+            .iter()
+            .try_fold(result, |value, step| match step {
+                SelectorStep::Field(name) => value.get(name),
+                SelectorStep::Index(idx) => value.get(idx),
+            })
+    }
+
+    /// Whether writing this key replaces (part of) the value under `other`, or the reverse.
+    pub fn overlaps(&self, other: &str) -> bool {
+        keys_overlap(&self.key, other)
+    }
+}
+
+/// Parse a node's `output` value (a missing mapping is an empty object). Well-formed entries are
+/// returned even when others have problems.
+pub fn parse_output_mapping(output: &Value) -> (Vec<OutputCapture>, Vec<OutputMappingProblem>) {
+    let Some(map) = output.as_object() else {
+        return (
+            Vec::new(),
+            vec![OutputMappingProblem {
+                key: None,
+                message: "output must map state keys to selectors".to_string(),
+            }],
+        );
+    };
+    let mut captures: Vec<OutputCapture> = Vec::new();
+    let mut problems = Vec::new();
+    for (key, selector) in map {
+        let mut problem = |message: String| {
+            problems.push(OutputMappingProblem {
+                key: Some(key.clone()),
+                message,
+            })
+        };
+        if !is_state_key(key) {
+            problem(format!(
+                "output key '{key}' is not a valid state key (dot-separated identifiers)"
+            ));
+            continue;
+        }
+        let Some(selector) = selector.as_str() else {
+            problem(format!(
+                "output '{key}' must be a selector string such as \"$.field\""
+            ));
+            continue;
+        };
+        let steps = match parse_selector(selector) {
+            Ok(steps) => steps,
+            Err(message) => {
+                problem(format!("output '{key}': {message}"));
+                continue;
+            }
+        };
+        if let Some(earlier) = captures.iter().find(|capture| capture.overlaps(key)) {
+            problem(format!(
+                "output '{key}' overlaps '{}'; the later key overwrites the earlier one",
+                earlier.key
+            ));
+            continue;
+        }
+        captures.push(OutputCapture {
+            key: key.clone(),
+            selector: selector.to_string(),
+            steps,
+        });
+    }
+    (captures, problems)
+}
+
+/// Write what each capture selects from `result` into `state`, nesting dotted keys.
+pub fn apply_output_mapping(
+    captures: &[OutputCapture],
+    result: &Value,
+    state: &mut Map<String, Value>,
+) {
+    for capture in captures {
+        let Some(value) = capture.select(result) else {
+            continue;
+        };
+        let mut segments = capture.key.split('.').peekable();
+        let mut target = &mut *state;
+        while let Some(segment) = segments.next() {
+            if segments.peek().is_none() {
+                target.insert(segment.to_string(), value.clone());
+                break;
+            }
+            let entry = target
+                .entry(segment.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            target = entry.as_object_mut().expect("just made an object");
+        }
+    }
+}
+
+/// Whether `key` is a valid state key.
+pub fn is_state_key(key: &str) -> bool {
+    !key.is_empty() && key.split('.').all(is_identifier)
+}
+
+/// Whether one state key is the other or nested under it (`a` and `a.b`).
+pub fn keys_overlap(a: &str, b: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    };
+    nested(a, b) || nested(b, a)
+}
+
+fn is_identifier(segment: &str) -> bool {
+    let mut chars = segment.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn parse_selector(selector: &str) -> Result<Vec<SelectorStep>, String> {
+    let Some(mut rest) = selector.strip_prefix('$') else {
+        return Err(format!("selector '{selector}' must start with '$'"));
+    };
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let field = &after[..end];
+            if !is_identifier(field) {
+                return Err(format!(
+                    "selector '{selector}' has an invalid field '{field}'"
+                ));
+            }
+            steps.push(SelectorStep::Field(field.to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (index, tail) = after
+                .split_once(']')
+                .ok_or_else(|| format!("selector '{selector}' has an unclosed '['"))?;
+            let index = index
+                .parse()
+                .map_err(|_| format!("selector '{selector}' has an invalid index '{index}'"))?;
+            steps.push(SelectorStep::Index(index));
+            rest = tail;
+        } else {
+            return Err(format!(
+                "selector '{selector}' must continue with '.field' or '[index]'"
+            ));
+        }
+    }
+    Ok(steps)
+}
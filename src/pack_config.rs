@@ -0,0 +1,102 @@
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_ir::Route,
+};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// File name of the pack-level settings file.
+pub const PACK_CONFIG_FILE: &str = ".greentic-flow.toml";
+
+/// Routing given to a new terminal node when add-step receives no `--routing-*` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultRouting {
+    Out,
+    Reply,
+}
+
+impl DefaultRouting {
+    pub fn routes(self) -> Vec<Route> {
+        match self {
+            DefaultRouting::Out => vec![Route {
+                out: true,
+                ..Route::default()
+            }],
+            DefaultRouting::Reply => vec![Route {
+                reply: true,
+                ..Route::default()
+            }],
+        }
+    }
+}
+
+/// Per flow kind overrides, keyed by the flow `type` under `[kinds.<type>]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct KindConfig {
+    pub default_routing: Option<DefaultRouting>,
+}
+
+/// Settings shared by every flow in a pack, read from `.greentic-flow.toml`.
+///
+/// ```toml
+/// default_routing = "reply"
+///
+/// [kinds.events]
+/// default_routing = "out"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PackConfig {
+    pub default_routing: Option<DefaultRouting>,
+    #[serde(default)]
+    pub kinds: BTreeMap<String, KindConfig>,
+}
+
+impl PackConfig {
+    pub fn from_toml_str(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| FlowError::Internal {
+            message: format!("invalid {PACK_CONFIG_FILE}: {}", e.message()),
+            location: FlowErrorLocation::at_path(PACK_CONFIG_FILE.to_string()),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| FlowError::Internal {
+            message: format!("read {}: {e}", path.display()),
+            location: FlowErrorLocation::at_path(path.display().to_string())
+                .with_source_path(Some(path)),
+        })?;
+        Self::from_toml_str(&text).map_err(|err| match err {
+            FlowError::Internal { message, .. } => FlowError::Internal {
+                message: format!("{message} ({})", path.display()),
+                location: FlowErrorLocation::at_path(path.display().to_string())
+                    .with_source_path(Some(path)),
+            },
+            other => other,
+        })
+    }
+
+    /// Find the nearest `.greentic-flow.toml` in `start` or one of its ancestors.
+    pub fn discover(start: &Path) -> Result<Option<(PathBuf, Self)>> {
+        for dir in start.ancestors() {
+            let candidate = dir.join(PACK_CONFIG_FILE);
+            if candidate.is_file() {
+                let config = Self::load(&candidate)?;
+                return Ok(Some((candidate, config)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Default routing for flows of type `kind`, preferring the kind override.
+    pub fn default_routing_for(&self, kind: &str) -> Option<DefaultRouting> {
+        self.kinds
+            .get(kind)
+            .and_then(|k| k.default_routing)
+            .or(self.default_routing)
+    }
+}
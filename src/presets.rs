@@ -0,0 +1,100 @@
+use crate::error::{FlowError, FlowErrorLocation, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory (relative to the pack root) holding named answer presets.
+pub const PRESETS_DIR: &str = "presets";
+
+const PRESET_EXTENSIONS: [&str; 3] = ["json", "yaml", "yml"];
+
+pub fn presets_dir(pack_root: &Path) -> PathBuf {
+    pack_root.join(PRESETS_DIR)
+}
+
+/// Locate an existing preset file (`<name>.json`, `.yaml`, or `.yml`).
+pub fn find_preset(pack_root: &Path, name: &str) -> Result<Option<PathBuf>> {
+    validate_preset_name(name)?;
+    let dir = presets_dir(pack_root);
+    Ok(PRESET_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{name}.{ext}")))
+        .find(|path| path.exists()))
+}
+
+pub fn load_preset(pack_root: &Path, name: &str) -> Result<BTreeMap<String, Value>> {
+    let path = find_preset(pack_root, name)?.ok_or_else(|| FlowError::Internal {
+        message: format!(
+            "preset '{name}' not found in {}",
+            presets_dir(pack_root).display()
+        ),
+        location: FlowErrorLocation::new(None, None, None),
+    })?;
+    let text = fs::read_to_string(&path).map_err(|err| FlowError::Internal {
+        message: format!("read preset {}: {err}", path.display()),
+        location: FlowErrorLocation::at_path(path.display().to_string()),
+    })?;
+    let parsed: Value = serde_yaml_bw::from_str(&text).map_err(|err| FlowError::Internal {
+        message: format!("parse preset {}: {err}", path.display()),
+        location: FlowErrorLocation::at_path(path.display().to_string()),
+    })?;
+    let Value::Object(map) = parsed else {
+        return Err(FlowError::Internal {
+            message: format!("preset {} must contain an object", path.display()),
+            location: FlowErrorLocation::at_path(path.display().to_string()),
+        });
+    };
+    Ok(map.into_iter().collect())
+}
+
+pub fn save_preset(
+    pack_root: &Path,
+    name: &str,
+    answers: &BTreeMap<String, Value>,
+    overwrite: bool,
+) -> Result<PathBuf> {
+    validate_preset_name(name)?;
+    if !overwrite && let Some(existing) = find_preset(pack_root, name)? {
+        return Err(FlowError::Internal {
+            message: format!(
+                "preset '{name}' already exists at {}; use --force",
+                existing.display()
+            ),
+            location: FlowErrorLocation::new(None, None, None),
+        });
+    }
+    let dir = presets_dir(pack_root);
+    fs::create_dir_all(&dir).map_err(|err| FlowError::Internal {
+        message: format!("create presets directory: {err}"),
+        location: FlowErrorLocation::new(None, None, None),
+    })?;
+    let path = dir.join(format!("{name}.json"));
+    let text = serde_json::to_string_pretty(answers).map_err(|err| FlowError::Internal {
+        message: format!("encode preset json: {err}"),
+        location: FlowErrorLocation::new(None, None, None),
+    })?;
+    fs::write(&path, format!("{text}\n")).map_err(|err| FlowError::Internal {
+        message: format!("write preset {}: {err}", path.display()),
+        location: FlowErrorLocation::new(None, None, None),
+    })?;
+    Ok(path)
+}
+
+fn validate_preset_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !name.starts_with('.');
+    if valid {
+        Ok(())
+    } else {
+        Err(FlowError::InvalidIdentifier {
+            kind: "preset",
+            value: name.to_string(),
+            detail: "use letters, digits, '-', '_' or '.'".to_string(),
+            location: FlowErrorLocation::new(None, None, None),
+        })
+    }
+}
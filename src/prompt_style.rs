@@ -0,0 +1,236 @@
+//! How interactive prompts look: colour, Unicode glyphs, and a screen-reader mode.
+//!
+//! The style comes from the global `--no-color`, `--ascii` and `--screen-reader` flags, then the
+//! `NO_COLOR` environment variable, then the `[prompts]` table of the user config, then the
+//! terminal: colour and Unicode glyphs are only used when prompts go to a terminal, so piped
+//! output stays plain.
+//!
+//! ```toml
+//! # ~/.config/greentic-flow/config.toml (or $GREENTIC_FLOW_CONFIG)
+//! [prompts]
+//! color = false
+//! ascii = true
+//! screen_reader = true
+//! ```
+//!
+//! In screen-reader mode every question starts with a line a tool can parse, such as
+//! `[question id=channel type=enum required=true default="support"]`, choices are listed as
+//! `[choice 1] support`, and the input line starts with `[input]`. Colour and glyphs are off.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// Environment variable naming the user config file.
+pub const USER_CONFIG_ENV: &str = "GREENTIC_FLOW_CONFIG";
+
+/// Where the user config lives: [`USER_CONFIG_ENV`], else `greentic-flow/config.toml` under
+/// `$XDG_CONFIG_HOME` or `~/.config`. `None` when none of these is set.
+pub fn user_config_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(USER_CONFIG_ENV).filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .filter(|home| !home.is_empty())
+                .map(|home| Path::new(&home).join(".config"))
+        })?;
+    Some(base.join("greentic-flow").join("config.toml"))
+}
+
+/// The `[prompts]` table of the user config; unset keys leave the choice to the terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PromptConfig {
+    pub color: Option<bool>,
+    pub ascii: Option<bool>,
+    pub screen_reader: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct UserConfig {
+    #[serde(default)]
+    prompts: PromptConfig,
+}
+
+impl PromptConfig {
+    /// The `[prompts]` table of `text`, a user config; other tables are ignored.
+    pub fn from_toml_str(text: &str) -> Result<Self> {
+        let config: UserConfig = toml::from_str(text).context("invalid user config")?;
+        Ok(config.prompts)
+    }
+
+    /// The prompt settings of the user config at `path`; a missing file sets nothing.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                Self::from_toml_str(&text).with_context(|| format!("load {}", path.display()))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("read {}", path.display())),
+        }
+    }
+}
+
+/// Prompt options given on the command line; `false` leaves the choice to the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PromptFlags {
+    pub no_color: bool,
+    pub ascii: bool,
+    pub screen_reader: bool,
+}
+
+/// The resolved prompt style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptStyle {
+    pub color: bool,
+    /// Only ASCII glyphs.
+    pub ascii: bool,
+    /// Machine-parsable markers, no colour and no glyphs.
+    pub screen_reader: bool,
+}
+
+static CURRENT: OnceLock<PromptStyle> = OnceLock::new();
+
+/// Make `style` the style of every prompt for the rest of the process. Only the first call
+/// has an effect.
+pub fn install(style: PromptStyle) {
+    let _ = CURRENT.set(style);
+}
+
+/// The installed style, or [`PromptStyle::PLAIN`] when none was installed.
+pub fn current() -> PromptStyle {
+    CURRENT.get().copied().unwrap_or(PromptStyle::PLAIN)
+}
+
+impl PromptStyle {
+    /// No colour, ASCII only, no markers: what prompts look like when piped.
+    pub const PLAIN: Self = Self {
+        color: false,
+        ascii: true,
+        screen_reader: false,
+    };
+
+    /// Combine `flags`, the `NO_COLOR` setting, `config` and whether prompts go to a
+    /// terminal, in that order of precedence.
+    pub fn from_settings(
+        flags: PromptFlags,
+        no_color_env: bool,
+        config: PromptConfig,
+        terminal: bool,
+    ) -> Self {
+        let screen_reader = flags.screen_reader || config.screen_reader.unwrap_or(false);
+        if screen_reader {
+            return Self {
+                color: false,
+                ascii: true,
+                screen_reader,
+            };
+        }
+        let color = !flags.no_color && !no_color_env && config.color.unwrap_or(terminal);
+        let ascii = flags.ascii || config.ascii.unwrap_or(!terminal);
+        Self {
+            color,
+            ascii,
+            screen_reader,
+        }
+    }
+
+    /// [`PromptStyle::from_settings`] with `NO_COLOR` and the user config of this process.
+    pub fn resolve(flags: PromptFlags, terminal: bool) -> Result<Self> {
+        let no_color_env = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let config = match user_config_path() {
+            Some(path) => PromptConfig::load(&path)?,
+            None => PromptConfig::default(),
+        };
+        Ok(Self::from_settings(flags, no_color_env, config, terminal))
+    }
+
+    /// The marker line announcing a question in screen-reader mode, `None` otherwise.
+    pub fn question_marker(
+        &self,
+        id: &str,
+        kind: &str,
+        required: bool,
+        default: Option<&Value>,
+    ) -> Option<String> {
+        if !self.screen_reader {
+            return None;
+        }
+        let mut marker = format!("[question id={id} type={kind} required={required}");
+        if let Some(default) = default {
+            marker.push_str(&format!(" default={default}"));
+        }
+        marker.push(']');
+        Some(marker)
+    }
+
+    /// [`PromptStyle::question_marker`] for a question of a QA runner payload.
+    pub fn payload_question_marker(&self, question: &Value) -> Option<String> {
+        let id = question
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let kind = question
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("string");
+        let required = question
+            .get("required")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        self.question_marker(id, kind, required, question.get("default"))
+    }
+
+    /// A question's title, in bold when colour is on.
+    pub fn title(&self, text: &str) -> String {
+        self.paint("1", text)
+    }
+
+    /// Secondary text (defaults, descriptions), dimmed when colour is on.
+    pub fn hint(&self, text: &str) -> String {
+        self.paint("2", text)
+    }
+
+    /// Choice `index` (1-based); `separator` follows the number outside screen-reader mode.
+    pub fn choice(&self, index: usize, label: &str, separator: &str) -> String {
+        if self.screen_reader {
+            format!("[choice {index}] {label}")
+        } else {
+            format!("  {index}{separator} {label}")
+        }
+    }
+
+    /// What precedes the answer: `label: ` when given, else a `>` pointer (`›` with glyphs).
+    /// Screen-reader mode starts it with `[input]` instead of the pointer.
+    pub fn input(&self, label: Option<&str>) -> String {
+        match (label, self.screen_reader) {
+            (Some(label), true) => format!("[input] {label}: "),
+            (None, true) => "[input] ".to_string(),
+            (Some(label), false) => format!("{label}: "),
+            (None, false) if self.ascii => "> ".to_string(),
+            (None, false) => "\u{203a} ".to_string(),
+        }
+    }
+
+    /// The arrow between a step and its routing in previews.
+    pub fn arrow(&self) -> &'static str {
+        if self.ascii { "->" } else { "\u{2192}" }
+    }
+
+    fn paint(&self, sgr: &str, text: &str) -> String {
+        if self.color {
+            format!("\u{1b}[{sgr}m{text}\u{1b}[0m")
+        } else {
+            text.to_string()
+        }
+    }
+}
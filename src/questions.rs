@@ -3,6 +3,8 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::io::{self, Read, Write};
 
+use crate::prompt_style;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum QuestionKind {
     String,
@@ -190,19 +192,51 @@ fn write_prompt<W: Write>(
     question: &Question,
     default_override: Option<&Value>,
 ) -> Result<()> {
-    write!(writer, "Question ({}): {}", question.id, question.prompt).context("write prompt")?;
-    if let Some(default) = default_override.or(question.default.as_ref()) {
-        write!(writer, " [default: {}]", display_value(default)).ok();
+    let style = prompt_style::current();
+    let default = default_override.or(question.default.as_ref());
+    if let Some(marker) = style.question_marker(
+        &question.id,
+        kind_name(&question.kind),
+        question.required,
+        default,
+    ) {
+        writeln!(writer, "{marker}").context("write prompt")?;
+    }
+    write!(
+        writer,
+        "Question ({}): {}",
+        question.id,
+        style.title(&question.prompt)
+    )
+    .context("write prompt")?;
+    if let Some(default) = default {
+        let hint = format!(" [default: {}]", display_value(default));
+        write!(writer, "{}", style.hint(&hint)).ok();
     }
     writeln!(writer).ok();
     if question.kind == QuestionKind::Choice && !question.choices.is_empty() {
         for (idx, choice) in question.choices.iter().enumerate() {
-            writeln!(writer, "  {}) {}", idx + 1, display_value(choice)).ok();
+            writeln!(
+                writer,
+                "{}",
+                style.choice(idx + 1, &display_value(choice), ")")
+            )
+            .ok();
         }
     }
     Ok(())
 }
 
+fn kind_name(kind: &QuestionKind) -> &'static str {
+    match kind {
+        QuestionKind::String => "string",
+        QuestionKind::Bool => "bool",
+        QuestionKind::Choice => "choice",
+        QuestionKind::Int => "int",
+        QuestionKind::Float => "float",
+    }
+}
+
 fn read_line<R: Read>(reader: &mut R, buf: &mut String) -> Result<bool> {
     let mut bytes = Vec::new();
     let mut cursor = 0usize;
@@ -1,22 +1,171 @@
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     env, fs,
     path::Path,
 };
 
-use crate::path_safety::normalize_under_root;
+use crate::{
+    component_catalog::normalize_manifest_value,
+    ir::{NodeKind, classify_node_type},
+    path_safety::normalize_under_root,
+};
+
+const COMPONENT_MANIFEST: &str = "component.manifest.json";
 
 /// Catalog of known adapters and their supported operations.
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct AdapterCatalog {
     /// Map of `<namespace>.<adapter>` to the operations that adapter exposes.
     pub adapters: HashMap<String, HashSet<String>>,
+    /// Map of `<namespace>.<adapter>` to the semver versions available in the catalog.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub versions: HashMap<String, HashSet<String>>,
 }
 
 impl AdapterCatalog {
-    /// Load a registry from disk, accepting JSON by default and TOML when the `toml` feature is enabled.
+    /// Parse a registry document (the same JSON shape accepted by [`Self::load_from_file`]).
+    pub fn from_json_str(text: &str) -> anyhow::Result<Self> {
+        serde_json::from_str(text).context("invalid adapter registry JSON")
+    }
+
+    /// Build a catalog from the component manifests bundled in a pack (`<pack>/components`).
+    pub fn from_pack(pack_root: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Self::from_manifest_tree(pack_root.as_ref().join("components"))
+    }
+
+    /// Build a catalog from every `component.manifest.json` below `root` (e.g. the dist cache).
+    ///
+    /// A missing root yields an empty catalog; unreadable manifests are skipped.
+    pub fn from_manifest_tree(root: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let mut catalog = Self::default();
+        let root = root.as_ref();
+        if root.is_dir() {
+            catalog.collect_manifests(root)?;
+        }
+        Ok(catalog)
+    }
+
+    fn collect_manifests(&mut self, dir: &Path) -> anyhow::Result<()> {
+        let entries =
+            fs::read_dir(dir).with_context(|| format!("unable to read {}", dir.display()))?;
+        for entry in entries {
+            let path = entry
+                .with_context(|| format!("unable to read entry in {}", dir.display()))?
+                .path();
+            if path.is_dir() {
+                self.collect_manifests(&path)?;
+            } else if path.file_name().and_then(|n| n.to_str()) == Some(COMPONENT_MANIFEST) {
+                self.insert_manifest(&path);
+            }
+        }
+        Ok(())
+    }
+
+    fn insert_manifest(&mut self, path: &Path) {
+        let Ok(text) = fs::read_to_string(path) else {
+            return;
+        };
+        let Ok(mut value) = serde_json::from_str::<Value>(&text) else {
+            return;
+        };
+        normalize_manifest_value(&mut value);
+        let Some(id) = value.get("id").and_then(Value::as_str) else {
+            return;
+        };
+        let version = value.get("version").and_then(Value::as_str);
+        let operations = value
+            .get("operations")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|op| {
+                op.get("name")
+                    .or_else(|| op.get("operation"))
+                    .or_else(|| op.get("id"))
+                    .and_then(Value::as_str)
+            });
+        for operation in operations {
+            if let Some(adapter) = adapter_key(id, operation) {
+                self.insert_operation(id, operation);
+                if let Some(version) = version {
+                    self.insert_version(&adapter, version);
+                }
+            }
+        }
+    }
+
+    /// Register `operation` of component `component_id` under the adapter key that
+    /// [`classify_node_type`] derives from `<component_id>.<operation>`.
+    ///
+    /// Returns `false` when the combined name is not adapter-shaped (fewer than three segments).
+    pub fn insert_operation(&mut self, component_id: &str, operation: &str) -> bool {
+        match classify_node_type(&format!("{component_id}.{operation}")) {
+            NodeKind::Adapter {
+                namespace,
+                adapter,
+                operation,
+            } => {
+                self.adapters
+                    .entry(format!("{namespace}.{adapter}"))
+                    .or_default()
+                    .insert(operation);
+                true
+            }
+            NodeKind::Builtin(_) => false,
+        }
+    }
+
+    /// Record that `version` of the `<namespace>.<adapter>` key is available.
+    pub fn insert_version(&mut self, adapter: &str, version: &str) {
+        self.versions
+            .entry(adapter.to_string())
+            .or_default()
+            .insert(version.to_string());
+    }
+
+    /// Parsed versions known for an adapter, ascending; unparsable entries are skipped.
+    pub fn adapter_versions(&self, adapter: &str) -> Vec<semver::Version> {
+        let mut versions: Vec<semver::Version> = self
+            .versions
+            .get(adapter)
+            .into_iter()
+            .flatten()
+            .filter_map(|v| semver::Version::parse(v).ok())
+            .collect();
+        versions.sort();
+        versions
+    }
+
+    /// Add every adapter operation and version from `other`.
+    pub fn merge(&mut self, other: AdapterCatalog) {
+        for (adapter, operations) in other.adapters {
+            self.adapters.entry(adapter).or_default().extend(operations);
+        }
+        for (adapter, versions) in other.versions {
+            self.versions.entry(adapter).or_default().extend(versions);
+        }
+    }
+
+    /// Adapters and operations in sorted order, for stable output.
+    pub fn sorted(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.adapters
+            .iter()
+            .map(|(adapter, ops)| (adapter.clone(), ops.iter().cloned().collect()))
+            .collect()
+    }
+
+    /// Adapter versions in sorted order, for stable output.
+    pub fn sorted_versions(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.versions
+            .iter()
+            .map(|(adapter, versions)| (adapter.clone(), versions.iter().cloned().collect()))
+            .collect()
+    }
+
+    /// Load a registry from disk, accepting JSON or TOML.
     pub fn load_from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
         let path_ref = path.as_ref();
         let registry_root = env::current_dir().context("unable to resolve registry root")?;
@@ -28,28 +177,14 @@ impl AdapterCatalog {
             return Ok(value);
         }
 
-        #[cfg(feature = "toml")]
-        {
-            if let Ok(value) = toml::from_str::<Self>(&txt) {
-                return Ok(value);
-            }
-        }
-
-        #[cfg(feature = "toml")]
-        {
-            anyhow::bail!(
-                "unsupported registry format in {}: expected JSON or TOML",
-                path_ref.display()
-            );
+        if let Ok(value) = toml::from_str::<Self>(&txt) {
+            return Ok(value);
         }
 
-        #[cfg(not(feature = "toml"))]
-        {
-            anyhow::bail!(
-                "unsupported registry format in {}: expected JSON (enable `toml` feature for TOML support)",
-                path_ref.display()
-            );
-        }
+        anyhow::bail!(
+            "unsupported registry format in {}: expected JSON or TOML",
+            path_ref.display()
+        );
     }
 
     /// Check if the catalog contains the given adapter operation.
@@ -61,3 +196,12 @@ impl AdapterCatalog {
             .unwrap_or(false)
     }
 }
+
+fn adapter_key(component_id: &str, operation: &str) -> Option<String> {
+    match classify_node_type(&format!("{component_id}.{operation}")) {
+        NodeKind::Adapter {
+            namespace, adapter, ..
+        } => Some(format!("{namespace}.{adapter}")),
+        NodeKind::Builtin(_) => None,
+    }
+}
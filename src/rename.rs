@@ -0,0 +1,144 @@
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_meta::{META_NAMESPACE, NODE_KEYED_META_MAPS},
+    loader::{load_ygtc_from_str, yaml_error_location},
+};
+use serde_yaml_bw::{Mapping, Value as YamlValue};
+
+/// Rename node `from` to `to` and rewrite every reference to it: routing `to:` targets, `start`,
+/// entrypoint targets and the node-keyed `meta.greentic` entries.
+///
+/// The node keeps its position and every other key, including reserved settings such as
+/// `retry`. The new id must match the schema's node key pattern, `^[a-zA-Z_][\w-]*$`.
+pub fn rename_node(flow_yaml: &str, from: &str, to: &str) -> Result<String> {
+    let source_label = "<inline>";
+    if from == to {
+        return Err(FlowError::Internal {
+            message: format!("node '{from}' already has that id"),
+            location: FlowErrorLocation::at_path(format!("nodes.{from}")),
+        });
+    }
+    if !is_valid_node_id(to) {
+        return Err(FlowError::Internal {
+            message: format!(
+                "invalid node id '{to}': use letters, digits, '_' and '-', starting with a letter or '_'"
+            ),
+            location: FlowErrorLocation::at_path(format!("nodes.{from}")),
+        });
+    }
+    let mut doc: YamlValue = serde_yaml_bw::from_str(flow_yaml).map_err(|e| FlowError::Yaml {
+        message: e.to_string(),
+        location: yaml_error_location(source_label, None, e.location()),
+    })?;
+    let doc_map = doc.as_mapping_mut().ok_or_else(|| FlowError::Internal {
+        message: "flow document must be a mapping".to_string(),
+        location: FlowErrorLocation::at_path(source_label),
+    })?;
+
+    let nodes = doc_map
+        .get_mut(yaml_string("nodes"))
+        .and_then(YamlValue::as_mapping_mut)
+        .ok_or_else(|| FlowError::Internal {
+            message: "flow missing nodes mapping".to_string(),
+            location: FlowErrorLocation::at_path("nodes"),
+        })?;
+    if !nodes.contains_key(yaml_string(from)) {
+        return Err(FlowError::Internal {
+            message: format!("node '{from}' not found"),
+            location: FlowErrorLocation::at_path(format!("nodes.{from}")),
+        });
+    }
+    if nodes.contains_key(yaml_string(to)) {
+        return Err(FlowError::Internal {
+            message: format!("node '{to}' already exists"),
+            location: FlowErrorLocation::at_path(format!("nodes.{to}")),
+        });
+    }
+    rename_key(nodes, from, to);
+    for (_, node) in nodes.iter_mut() {
+        let Some(routes) = node
+            .as_mapping_mut()
+            .and_then(|node| node.get_mut(yaml_string("routing")))
+            .and_then(YamlValue::as_sequence_mut)
+        else {
+            continue;
+        };
+        for route in routes.iter_mut() {
+            if let Some(target) = route
+                .as_mapping_mut()
+                .and_then(|route| route.get_mut(yaml_string("to")))
+            {
+                rename_string(target, from, to);
+            }
+        }
+    }
+
+    if let Some(start) = doc_map.get_mut(yaml_string("start")) {
+        rename_string(start, from, to);
+    }
+    if let Some(entrypoints) = doc_map
+        .get_mut(yaml_string("entrypoints"))
+        .and_then(YamlValue::as_mapping_mut)
+    {
+        for (_, target) in entrypoints.iter_mut() {
+            rename_string(target, from, to);
+        }
+    }
+    if let Some(greentic) = doc_map
+        .get_mut(yaml_string("meta"))
+        .and_then(YamlValue::as_mapping_mut)
+        .and_then(|meta| meta.get_mut(yaml_string(META_NAMESPACE)))
+        .and_then(YamlValue::as_mapping_mut)
+    {
+        for key in NODE_KEYED_META_MAPS {
+            if let Some(entries) = greentic
+                .get_mut(yaml_string(key))
+                .and_then(YamlValue::as_mapping_mut)
+            {
+                rename_key(entries, from, to);
+            }
+        }
+    }
+
+    let yaml = serde_yaml_bw::to_string(&doc).map_err(|e| FlowError::Internal {
+        message: format!("serialize renamed flow: {e}"),
+        location: FlowErrorLocation::at_path(source_label),
+    })?;
+    load_ygtc_from_str(&yaml)?;
+    Ok(yaml)
+}
+
+pub(crate) fn is_valid_node_id(id: &str) -> bool {
+    let mut chars = id.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Rename `from` to `to` in place, keeping the entry's position.
+fn rename_key(map: &mut Mapping, from: &str, to: &str) {
+    if !map.contains_key(yaml_string(from)) {
+        return;
+    }
+    let entries = std::mem::take(map);
+    map.anchor = entries.anchor.clone();
+    for (key, value) in entries {
+        let key = if key.as_str() == Some(from) {
+            yaml_string(to)
+        } else {
+            key
+        };
+        map.insert(key, value);
+    }
+}
+
+fn rename_string(value: &mut YamlValue, from: &str, to: &str) {
+    if value.as_str() == Some(from) {
+        *value = yaml_string(to);
+    }
+}
+
+fn yaml_string(value: &str) -> YamlValue {
+    YamlValue::String(value.to_string(), None)
+}
@@ -0,0 +1,231 @@
+//! Validate recorded runtime traffic against the schemas a flow's components declare.
+//!
+//! A capture file holds what nodes actually received and produced, one record per node
+//! execution, as a JSON array, a `{"records": [...]}` object or JSON lines:
+//!
+//! ```json
+//! {"node_id": "fetch", "input": {"city": "Paris"}, "output": {"temp": 21}}
+//! ```
+//!
+//! Each `input` is checked against the operation's `input_schema` and each `output` against its
+//! `output_schema`. Traffic that no longer matches is an early sign that a component (or its
+//! schema) drifted from what the flow was built against.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{component_schema::jsonschema_options_with_base, flow_ir::FlowIr};
+
+/// One recorded node execution.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RecordedPayload {
+    pub node_id: String,
+    /// Flow the record came from; records of other flows are ignored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flow: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+}
+
+/// Parse a capture file: a JSON array, `{"records": [...]}`, or one JSON record per line.
+pub fn parse_recordings(text: &str) -> Result<Vec<RecordedPayload>> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('[') {
+        return serde_json::from_str(text).context("invalid capture array");
+    }
+    if let Ok(Value::Object(map)) = serde_json::from_str::<Value>(text)
+        && let Some(records) = map.get("records")
+    {
+        return serde_json::from_value(records.clone()).context("invalid capture records");
+    }
+    let mut records = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(
+            serde_json::from_str(line)
+                .with_context(|| format!("invalid capture record on line {}", index + 1))?,
+        );
+    }
+    if records.is_empty() {
+        bail!("capture file has no records");
+    }
+    Ok(records)
+}
+
+/// The schemas a node's operation declares, with the manifest they came from (relative `$ref`s
+/// resolve against it).
+#[derive(Clone, Debug, Default)]
+pub struct NodeSchemas {
+    pub input: Option<Value>,
+    pub output: Option<Value>,
+    pub manifest_path: Option<PathBuf>,
+}
+
+/// Which side of a node execution a mismatch is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadDirection {
+    Input,
+    Output,
+}
+
+/// A recorded payload that its schema rejects.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ReplayMismatch {
+    /// Index of the record in the capture file.
+    pub record: usize,
+    pub node_id: String,
+    pub direction: PayloadDirection,
+    /// JSON pointer into the payload.
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Per-node tally of the replay.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct NodeReplay {
+    pub node_id: String,
+    pub operation: String,
+    pub records: usize,
+    /// Payloads checked against a schema.
+    pub checked: usize,
+    /// Payloads that failed their schema.
+    pub failed: usize,
+    /// Why the node's payloads could not be checked, e.g. no component manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// Result of [`validate_recordings`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ReplayReport {
+    pub records: usize,
+    pub nodes: Vec<NodeReplay>,
+    pub mismatches: Vec<ReplayMismatch>,
+    /// Node ids recorded in the capture that the flow does not have.
+    pub unknown_nodes: Vec<String>,
+}
+
+impl ReplayReport {
+    /// Nodes with at least one payload their schema rejects.
+    pub fn drifted_nodes(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|node| node.failed > 0)
+            .map(|node| node.node_id.as_str())
+            .collect()
+    }
+}
+
+/// Check every record of `flow`'s nodes against the schemas `schemas_of(node_id)` returns.
+///
+/// `schemas_of` returns `Err(reason)` when a node's schemas cannot be found; its records are
+/// then counted but not checked. A missing or empty schema skips that side only.
+pub fn validate_recordings(
+    flow: &FlowIr,
+    records: &[RecordedPayload],
+    mut schemas_of: impl FnMut(&str) -> std::result::Result<NodeSchemas, String>,
+) -> Result<ReplayReport> {
+    let mut report = ReplayReport::default();
+    let mut by_node: BTreeMap<&str, Vec<(usize, &RecordedPayload)>> = BTreeMap::new();
+    for (index, record) in records.iter().enumerate() {
+        if record.flow.as_deref().is_some_and(|id| id != flow.id) {
+            continue;
+        }
+        report.records += 1;
+        if flow.nodes.contains_key(&record.node_id) {
+            by_node
+                .entry(&record.node_id)
+                .or_default()
+                .push((index, record));
+        } else if !report.unknown_nodes.contains(&record.node_id) {
+            report.unknown_nodes.push(record.node_id.clone());
+        }
+    }
+
+    for node in flow.nodes.values() {
+        let Some(node_records) = by_node.get(node.id.as_str()) else {
+            continue;
+        };
+        let mut tally = NodeReplay {
+            node_id: node.id.clone(),
+            operation: node.operation.clone(),
+            records: node_records.len(),
+            checked: 0,
+            failed: 0,
+            skipped: None,
+        };
+        let schemas = match schemas_of(&node.id) {
+            Ok(schemas) => schemas,
+            Err(reason) => {
+                tally.skipped = Some(reason);
+                report.nodes.push(tally);
+                continue;
+            }
+        };
+        let base = schemas.manifest_path.as_deref();
+        let input = compile(schemas.input.as_ref(), base, &node.id, "input")?;
+        let output = compile(schemas.output.as_ref(), base, &node.id, "output")?;
+        if input.is_none() && output.is_none() {
+            tally.skipped = Some(format!(
+                "operation '{}' declares no input or output schema",
+                node.operation
+            ));
+        }
+        for (index, record) in node_records {
+            for (direction, validator, payload) in [
+                (PayloadDirection::Input, &input, &record.input),
+                (PayloadDirection::Output, &output, &record.output),
+            ] {
+                let (Some(validator), Some(payload)) = (validator, payload) else {
+                    continue;
+                };
+                tally.checked += 1;
+                let before = report.mismatches.len();
+                for error in validator.iter_errors(payload) {
+                    let pointer = error.instance_path().to_string();
+                    report.mismatches.push(ReplayMismatch {
+                        record: *index,
+                        node_id: node.id.clone(),
+                        direction,
+                        pointer: if pointer.is_empty() {
+                            "/".to_string()
+                        } else {
+                            pointer
+                        },
+                        message: error.to_string(),
+                    });
+                }
+                if report.mismatches.len() > before {
+                    tally.failed += 1;
+                }
+            }
+        }
+        report.nodes.push(tally);
+    }
+    Ok(report)
+}
+
+fn compile(
+    schema: Option<&Value>,
+    base: Option<&Path>,
+    node_id: &str,
+    side: &str,
+) -> Result<Option<jsonschema::Validator>> {
+    let Some(schema) = schema.filter(|schema| !schema.is_null()) else {
+        return Ok(None);
+    };
+    jsonschema_options_with_base(base)
+        .build(schema)
+        .map(Some)
+        .map_err(|err| anyhow::anyhow!("compile {side} schema of node '{node_id}': {err}"))
+}
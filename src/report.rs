@@ -0,0 +1,487 @@
+use crate::{
+    flow_ir::FlowIr,
+    json_output::{JsonDiagnostic, LintJsonOutput},
+    lint::LintSeverity,
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt::Write as _};
+
+/// Findings, graph and components of one flow, as rendered by [`render_html_report`].
+#[derive(Clone, Debug, Serialize)]
+pub struct FlowReport {
+    pub path: String,
+    /// The same payload `doctor --json` prints for the flow.
+    pub lint: LintJsonOutput,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph: Option<FlowGraph>,
+    pub components: Vec<ComponentUse>,
+}
+
+impl FlowReport {
+    pub fn new(path: impl Into<String>, lint: LintJsonOutput) -> Self {
+        Self {
+            path: path.into(),
+            lint,
+            graph: None,
+            components: Vec::new(),
+        }
+    }
+
+    /// Attach the graph and node operations of a flow that loaded into IR.
+    ///
+    /// `source_of` maps a node id to its sidecar component source, when bound.
+    pub fn with_flow(mut self, flow: &FlowIr, source_of: impl Fn(&str) -> Option<String>) -> Self {
+        self.graph = Some(FlowGraph::from_ir(flow));
+        self.components = flow
+            .nodes
+            .values()
+            .map(|node| ComponentUse {
+                node_id: node.id.clone(),
+                operation: node.operation.clone(),
+                source: source_of(&node.id),
+            })
+            .collect();
+        self
+    }
+
+    /// Record a finding reported outside the lint pass (e.g. contract checks).
+    pub fn push_finding(&mut self, diagnostic: JsonDiagnostic) {
+        if diagnostic.severity == Some(LintSeverity::Warning) {
+            self.lint.warnings.push(diagnostic);
+        } else {
+            self.lint.ok = false;
+            self.lint.errors.push(diagnostic);
+        }
+    }
+}
+
+/// Which operation a node runs and, when the sidecar binds it, from which component source.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ComponentUse {
+    pub node_id: String,
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Nodes laid out in columns by their distance from the entrypoints.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FlowGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub operation: String,
+    /// Column; nodes unreachable from an entrypoint go in the last column.
+    pub depth: usize,
+    /// Terminal routes (`out`, `reply`) leaving the node.
+    pub terminals: Vec<String>,
+    pub entrypoints: Vec<String>,
+    /// The node's `doc`, shown as the node's tooltip.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl FlowGraph {
+    pub fn from_ir(flow: &FlowIr) -> Self {
+        let mut edges = Vec::new();
+        let mut terminals: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for node in flow.nodes.values() {
+            for route in &node.routing {
+                let label = route.label.clone().or_else(|| {
+                    route
+                        .status
+                        .as_ref()
+                        .map(|status| format!("status {status}"))
+                });
+                if let Some(to) = &route.to
+                    && flow.nodes.contains_key(to)
+                {
+                    edges.push(GraphEdge {
+                        from: node.id.clone(),
+                        to: to.clone(),
+                        label,
+                    });
+                } else if route.out || route.to.as_deref() == Some("out") {
+                    terminals.entry(&node.id).or_default().push("out".into());
+                } else if route.reply {
+                    terminals.entry(&node.id).or_default().push("reply".into());
+                }
+            }
+        }
+
+        let mut roots: Vec<&str> = flow.start.iter().map(String::as_str).collect();
+        roots.extend(flow.entrypoints.values().map(String::as_str));
+        let mut depth: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut queue: std::collections::VecDeque<&str> = roots
+            .into_iter()
+            .filter(|id| flow.nodes.contains_key(*id))
+            .collect();
+        for id in &queue {
+            depth.insert(id, 0);
+        }
+        while let Some(id) = queue.pop_front() {
+            let next = depth[id] + 1;
+            for edge in edges.iter().filter(|edge| edge.from == id) {
+                if !depth.contains_key(edge.to.as_str()) {
+                    depth.insert(&edge.to, next);
+                    queue.push_back(&edge.to);
+                }
+            }
+        }
+        let unreachable = depth.values().max().map_or(0, |max| max + 1);
+
+        let nodes = flow
+            .nodes
+            .values()
+            .map(|node| GraphNode {
+                id: node.id.clone(),
+                operation: node.operation.clone(),
+                depth: depth.get(node.id.as_str()).copied().unwrap_or(unreachable),
+                terminals: terminals.remove(node.id.as_str()).unwrap_or_default(),
+                entrypoints: flow
+                    .entrypoints
+                    .iter()
+                    .filter(|(_, target)| **target == node.id)
+                    .map(|(name, _)| name.clone())
+                    .collect(),
+                doc: node.doc.clone(),
+            })
+            .collect();
+        Self { nodes, edges }
+    }
+}
+
+const NODE_WIDTH: usize = 160;
+const NODE_HEIGHT: usize = 40;
+const COLUMN_GAP: usize = 60;
+const ROW_GAP: usize = 24;
+
+/// Render a standalone HTML page (no external assets) for sharing a doctor run.
+///
+/// The page lists each flow's findings with severity/text filters, draws its graph as SVG,
+/// and ends with a component inventory across flows. The raw report is embedded as JSON in
+/// `<script id="report-data">`.
+pub fn render_html_report(flows: &[FlowReport]) -> String {
+    let errors: usize = flows.iter().map(|flow| flow.lint.errors.len()).sum();
+    let warnings: usize = flows.iter().map(|flow| flow.lint.warnings.len()).sum();
+    let failed = flows.iter().filter(|flow| !flow.lint.ok).count();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>greentic-flow doctor report</title>\n<style>\n");
+    html.push_str(STYLE);
+    html.push_str(GRAPH_STYLE);
+    html.push_str("</style>\n</head>\n<body>\n");
+    let _ = writeln!(
+        html,
+        "<h1>greentic-flow doctor report</h1>\n<p class=\"summary\">{} flow(s), {failed} failing, {errors} error(s), {warnings} warning(s) &middot; greentic-flow {}</p>",
+        flows.len(),
+        env!("CARGO_PKG_VERSION")
+    );
+    html.push_str(
+        "<div class=\"filters\">\n<label><input type=\"checkbox\" data-filter=\"error\" checked> errors</label>\n<label><input type=\"checkbox\" data-filter=\"warning\" checked> warnings</label>\n<label><input type=\"checkbox\" id=\"failing-only\"> failing flows only</label>\n<input type=\"search\" id=\"search\" placeholder=\"filter findings\">\n</div>\n",
+    );
+
+    for flow in flows {
+        render_flow(&mut html, flow);
+    }
+    render_inventory(&mut html, flows);
+
+    let data = serde_json::to_string(flows).expect("report serialization");
+    let _ = writeln!(
+        html,
+        "<script type=\"application/json\" id=\"report-data\">{}</script>",
+        data.replace("</", "<\\/")
+    );
+    html.push_str("<script>\n");
+    html.push_str(SCRIPT);
+    html.push_str("</script>\n</body>\n</html>\n");
+    html
+}
+
+fn render_flow(html: &mut String, flow: &FlowReport) {
+    let status = if flow.lint.ok { "ok" } else { "failed" };
+    let id = flow
+        .lint
+        .bundle
+        .as_ref()
+        .map(|bundle| format!(" ({})", escape(&bundle.id)))
+        .unwrap_or_default();
+    let _ = writeln!(
+        html,
+        "<details class=\"flow {status}\" open>\n<summary><span class=\"badge {status}\">{status}</span> {}{id} &middot; {} error(s), {} warning(s)</summary>",
+        escape(&flow.path),
+        flow.lint.errors.len(),
+        flow.lint.warnings.len()
+    );
+    if flow.lint.errors.is_empty() && flow.lint.warnings.is_empty() {
+        html.push_str("<p class=\"empty\">No findings.</p>\n");
+    } else {
+        html.push_str(
+            "<table class=\"findings\">\n<tr><th>severity</th><th>rule</th><th>location</th><th>message</th><th>fix</th></tr>\n",
+        );
+        for (severity, diagnostics) in [
+            ("error", &flow.lint.errors),
+            ("warning", &flow.lint.warnings),
+        ] {
+            for diagnostic in diagnostics {
+                render_finding(html, severity, diagnostic);
+            }
+        }
+        html.push_str("</table>\n");
+    }
+    if !flow.lint.suppressions.is_empty() {
+        html.push_str("<ul class=\"suppressions\">\n");
+        for suppression in &flow.lint.suppressions {
+            let _ = writeln!(
+                html,
+                "<li>node <code>{}</code> suppresses {} ({} finding(s)): {}</li>",
+                escape(&suppression.node_id),
+                escape(&suppression.rules.join(", ")),
+                suppression.suppressed,
+                escape(&suppression.reason)
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+    if let Some(graph) = &flow.graph {
+        render_graph(html, graph, false);
+    }
+    html.push_str("</details>\n");
+}
+
+fn render_finding(html: &mut String, severity: &str, diagnostic: &JsonDiagnostic) {
+    let location = match (&diagnostic.json_pointer, diagnostic.line) {
+        (Some(pointer), _) => escape(pointer),
+        (None, Some(line)) => format!("line {line}"),
+        (None, None) => String::new(),
+    };
+    let fix = diagnostic
+        .fix
+        .as_ref()
+        .map(|fix| escape(&fix.description))
+        .unwrap_or_default();
+    let rule = diagnostic.rule.as_deref().unwrap_or("");
+    // Lint messages repeat the rule as a `<rule>: ` prefix; it has its own column here.
+    let message = diagnostic
+        .message
+        .strip_prefix(rule)
+        .and_then(|rest| rest.strip_prefix(": "))
+        .unwrap_or(&diagnostic.message);
+    let _ = writeln!(
+        html,
+        "<tr class=\"finding {severity}\" data-severity=\"{severity}\"><td>{severity}</td><td>{}</td><td>{location}</td><td>{}</td><td>{fix}</td></tr>",
+        escape(rule),
+        escape(message)
+    );
+}
+
+/// Render a flow graph as a standalone SVG document (styles inlined), e.g. for embedding in docs.
+pub fn render_svg(graph: &FlowGraph) -> String {
+    let mut svg = String::new();
+    render_graph(&mut svg, graph, true);
+    svg
+}
+
+fn render_graph(html: &mut String, graph: &FlowGraph, standalone: bool) {
+    if graph.nodes.is_empty() && !standalone {
+        return;
+    }
+    let mut rows: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut positions: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for node in &graph.nodes {
+        let row = rows.entry(node.depth).or_insert(0);
+        positions.insert(
+            &node.id,
+            (
+                10 + node.depth * (NODE_WIDTH + COLUMN_GAP),
+                10 + *row * (NODE_HEIGHT + ROW_GAP),
+            ),
+        );
+        *row += 1;
+    }
+    let columns = rows.keys().max().map_or(1, |max| max + 1);
+    let max_rows = rows.values().max().copied().unwrap_or(1);
+    let width = 20 + columns * (NODE_WIDTH + COLUMN_GAP);
+    let height = 20 + max_rows * (NODE_HEIGHT + ROW_GAP);
+    let xmlns = if standalone {
+        " xmlns=\"http://www.w3.org/2000/svg\""
+    } else {
+        ""
+    };
+    let _ = writeln!(
+        html,
+        "<svg{xmlns} class=\"graph\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\" role=\"img\">\n<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"6\" markerHeight=\"6\" orient=\"auto\"><path d=\"M0,0 L10,5 L0,10 z\"/></marker></defs>"
+    );
+    if standalone {
+        let _ = writeln!(html, "<style>\n{GRAPH_STYLE}</style>");
+    }
+    for edge in &graph.edges {
+        let (Some(&(fx, fy)), Some(&(tx, ty))) = (
+            positions.get(edge.from.as_str()),
+            positions.get(edge.to.as_str()),
+        ) else {
+            continue;
+        };
+        let (x1, y1) = (fx + NODE_WIDTH, fy + NODE_HEIGHT / 2);
+        let (x2, y2) = (tx, ty + NODE_HEIGHT / 2);
+        let _ = writeln!(
+            html,
+            "<line class=\"edge\" x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" marker-end=\"url(#arrow)\"/>"
+        );
+        if let Some(label) = &edge.label {
+            let _ = writeln!(
+                html,
+                "<text class=\"edge-label\" x=\"{}\" y=\"{}\">{}</text>",
+                (x1 + x2) / 2,
+                (y1 + y2) / 2 - 4,
+                escape(label)
+            );
+        }
+    }
+    for node in &graph.nodes {
+        let (x, y) = positions[node.id.as_str()];
+        let mut title = node.operation.clone();
+        if !node.entrypoints.is_empty() {
+            let _ = write!(title, " (entrypoint: {})", node.entrypoints.join(", "));
+        }
+        if let Some(doc) = &node.doc {
+            let _ = write!(title, "\n{}", doc.trim());
+        }
+        let terminals = if node.terminals.is_empty() {
+            String::new()
+        } else {
+            format!(" \u{2192} {}", node.terminals.join(", "))
+        };
+        let _ = writeln!(
+            html,
+            "<g class=\"node{}\"><title>{}</title><rect x=\"{x}\" y=\"{y}\" width=\"{NODE_WIDTH}\" height=\"{NODE_HEIGHT}\" rx=\"6\"/><text x=\"{}\" y=\"{}\">{}{}</text></g>",
+            if node.entrypoints.is_empty() {
+                ""
+            } else {
+                " entry"
+            },
+            escape(&title),
+            x + 8,
+            y + NODE_HEIGHT / 2 + 4,
+            escape(&node.id),
+            escape(&terminals)
+        );
+    }
+    html.push_str("</svg>\n");
+}
+
+fn render_inventory(html: &mut String, flows: &[FlowReport]) {
+    // component (sidecar source, else the node operation) -> (operations, flows, node count)
+    let mut inventory: BTreeMap<&str, (Vec<&str>, Vec<&str>, usize)> = BTreeMap::new();
+    for flow in flows {
+        for component in &flow.components {
+            let key = component
+                .source
+                .as_deref()
+                .unwrap_or(component.operation.as_str());
+            let entry = inventory.entry(key).or_default();
+            if !entry.0.contains(&component.operation.as_str()) {
+                entry.0.push(&component.operation);
+            }
+            if !entry.1.contains(&flow.path.as_str()) {
+                entry.1.push(&flow.path);
+            }
+            entry.2 += 1;
+        }
+    }
+    html.push_str("<h2>Component inventory</h2>\n");
+    if inventory.is_empty() {
+        html.push_str("<p class=\"empty\">No nodes.</p>\n");
+        return;
+    }
+    html.push_str(
+        "<table class=\"inventory\">\n<tr><th>component</th><th>operations</th><th>flows</th><th>nodes</th></tr>\n",
+    );
+    for (component, (operations, flows, nodes)) in inventory {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{nodes}</td></tr>",
+            escape(component),
+            escape(&operations.join(", ")),
+            escape(&flows.join(", "))
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+const STYLE: &str = r#"body { font-family: system-ui, sans-serif; margin: 2rem; color: #1f2328; }
+.summary { color: #57606a; }
+.filters { display: flex; gap: 1rem; align-items: center; margin: 1rem 0; }
+details.flow { border: 1px solid #d0d7de; border-radius: 6px; margin: 0.75rem 0; padding: 0.5rem 1rem; }
+details.flow > summary { cursor: pointer; font-weight: 600; }
+.badge { border-radius: 4px; padding: 0 0.4rem; font-size: 0.8rem; color: #fff; }
+.badge.ok { background: #1a7f37; }
+.badge.failed { background: #cf222e; }
+table { border-collapse: collapse; margin: 0.5rem 0; width: 100%; }
+th, td { border-bottom: 1px solid #d0d7de; padding: 0.25rem 0.5rem; text-align: left; vertical-align: top; }
+tr.error td:first-child { color: #cf222e; }
+tr.warning td:first-child { color: #9a6700; }
+.empty { color: #57606a; }
+"#;
+
+const GRAPH_STYLE: &str = r#"svg.graph { display: block; margin: 0.5rem 0; overflow: visible; }
+svg.graph rect { fill: #f6f8fa; stroke: #57606a; }
+svg.graph .entry rect { stroke: #0969da; stroke-width: 2; }
+svg.graph text { font-size: 12px; }
+svg.graph .edge { stroke: #57606a; }
+svg.graph .edge-label { fill: #57606a; font-size: 10px; text-anchor: middle; }
+"#;
+
+const SCRIPT: &str = r#"(function () {
+  const severity = {};
+  const search = document.getElementById('search');
+  const failingOnly = document.getElementById('failing-only');
+  function apply() {
+    const needle = search.value.toLowerCase();
+    document.querySelectorAll('tr.finding').forEach(function (row) {
+      const visible = severity[row.dataset.severity] !== false &&
+        row.textContent.toLowerCase().includes(needle);
+      row.style.display = visible ? '' : 'none';
+    });
+    document.querySelectorAll('details.flow').forEach(function (flow) {
+      flow.style.display = failingOnly.checked && flow.classList.contains('ok') ? 'none' : '';
+    });
+  }
+  document.querySelectorAll('input[data-filter]').forEach(function (box) {
+    box.addEventListener('change', function () {
+      severity[box.dataset.filter] = box.checked;
+      apply();
+    });
+  });
+  search.addEventListener('input', apply);
+  failingOnly.addEventListener('change', apply);
+})();
+"#;
@@ -0,0 +1,224 @@
+//! Runtime requirements (env vars, secrets, capabilities) that component manifests declare.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    lint::{LintDiagnostic, LintSeverity},
+};
+
+/// What a node's component needs from the environment it runs in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct NodeRequirements {
+    pub node_id: String,
+    /// Component reference from the resolve sidecar.
+    pub component: String,
+    /// Environment variables the component reads (`capabilities.wasi.env.allow`).
+    pub env: Vec<String>,
+    /// Secrets the component resolves (`capabilities.host.secrets.required`).
+    pub secrets: Vec<SecretNeed>,
+    /// Other capability surfaces the host must grant, e.g. `wasi:filesystem`, `host:http`.
+    pub capabilities: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SecretNeed {
+    pub key: String,
+    /// Optional secrets do not fail the environment check when absent.
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+const WASI_FLAGS: &[&str] = &["random", "clocks"];
+const HOST_SURFACES: &[&str] = &["state", "messaging", "events", "http", "telemetry", "iac"];
+
+impl NodeRequirements {
+    /// Read the requirements declared under `capabilities` in a component manifest.
+    pub fn from_manifest(node_id: &str, component: &str, manifest: &Value) -> Self {
+        let capabilities = manifest.get("capabilities");
+        let wasi = capabilities.and_then(|caps| caps.get("wasi"));
+        let host = capabilities.and_then(|caps| caps.get("host"));
+
+        let env = wasi
+            .and_then(|wasi| wasi.pointer("/env/allow"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        let secrets = host
+            .and_then(|host| host.pointer("/secrets/required"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                Some(SecretNeed {
+                    key: entry.get("key")?.as_str()?.to_string(),
+                    required: entry
+                        .get("required")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(true),
+                    description: entry
+                        .get("description")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                })
+            })
+            .collect();
+
+        let mut surfaces = Vec::new();
+        if wasi
+            .and_then(|wasi| wasi.get("filesystem"))
+            .is_some_and(|fs| !fs.is_null())
+        {
+            surfaces.push("wasi:filesystem".to_string());
+        }
+        for flag in WASI_FLAGS {
+            if wasi
+                .and_then(|wasi| wasi.get(*flag))
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+            {
+                surfaces.push(format!("wasi:{flag}"));
+            }
+        }
+        for surface in HOST_SURFACES {
+            if host
+                .and_then(|host| host.get(*surface))
+                .is_some_and(|value| !value.is_null())
+            {
+                surfaces.push(format!("host:{surface}"));
+            }
+        }
+
+        Self {
+            node_id: node_id.to_string(),
+            component: component.to_string(),
+            env,
+            secrets,
+            capabilities: surfaces,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.env.is_empty() && self.secrets.is_empty() && self.capabilities.is_empty()
+    }
+}
+
+/// Variables from a dotenv-style file: `KEY=value` lines, optionally prefixed with `export`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvFile {
+    pub vars: BTreeMap<String, String>,
+}
+
+impl EnvFile {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut vars = BTreeMap::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(FlowError::Internal {
+                    message: format!("env file line {} is not KEY=value", idx + 1),
+                    location: FlowErrorLocation::new("env file".to_string(), Some(idx + 1), None),
+                });
+            };
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            vars.insert(key.trim().to_string(), value.to_string());
+        }
+        Ok(Self { vars })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| FlowError::Internal {
+            message: format!("read {}: {e}", path.display()),
+            location: FlowErrorLocation::at_path(path.display().to_string())
+                .with_source_path(Some(path)),
+        })?;
+        Self::parse(&text).map_err(|err| match err {
+            FlowError::Internal { message, location } => FlowError::Internal {
+                message: format!("{message} ({})", path.display()),
+                location: FlowErrorLocation::new(path.display().to_string(), location.line, None)
+                    .with_source_path(Some(path)),
+            },
+            other => other,
+        })
+    }
+
+    /// A set, non-empty variable.
+    pub fn provides_var(&self, name: &str) -> bool {
+        self.vars.get(name).is_some_and(|value| !value.is_empty())
+    }
+
+    /// Whether the secret is set under its key or its [`secret_env_name`].
+    pub fn provides_secret(&self, key: &str) -> bool {
+        self.provides_var(key) || self.provides_var(&secret_env_name(key))
+    }
+}
+
+/// Env-var spelling of a secret key: `db/password` becomes `DB_PASSWORD`.
+pub fn secret_env_name(key: &str) -> String {
+    key.chars()
+        .map(|c| match c {
+            '.' | '-' | '/' => '_',
+            c => c.to_ascii_uppercase(),
+        })
+        .collect()
+}
+
+/// `env_requirements` errors for env vars and required secrets the environment lacks.
+/// Missing optional secrets are warnings.
+pub fn check_env_requirements(nodes: &[NodeRequirements], env: &EnvFile) -> Vec<LintDiagnostic> {
+    let mut diagnostics = Vec::new();
+    for node in nodes {
+        let path = format!("/nodes/{}", node.node_id);
+        for var in &node.env {
+            if !env.provides_var(var) {
+                diagnostics.push(
+                    LintDiagnostic::error(
+                        "env_requirements",
+                        format!(
+                            "node '{}' component '{}' reads env var '{var}', which the environment does not set",
+                            node.node_id, node.component
+                        ),
+                    )
+                    .with_path(path.clone()),
+                );
+            }
+        }
+        for secret in &node.secrets {
+            if env.provides_secret(&secret.key) {
+                continue;
+            }
+            let diagnostic = LintDiagnostic::error(
+                "env_requirements",
+                format!(
+                    "node '{}' component '{}' needs secret '{}' ({}), which the environment does not set",
+                    node.node_id,
+                    node.component,
+                    secret.key,
+                    secret_env_name(&secret.key)
+                ),
+            )
+            .with_path(path.clone());
+            diagnostics.push(if secret.required {
+                diagnostic
+            } else {
+                diagnostic.with_severity(LintSeverity::Warning)
+            });
+        }
+    }
+    diagnostics
+}
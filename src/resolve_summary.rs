@@ -1,5 +1,6 @@
+use crate::cas::cas_local_path;
+use crate::component_resolver::{ResolvedComponent, ResolverRegistry};
 use anyhow::{Context, Result, anyhow};
-use greentic_distributor_client::DistClient;
 use greentic_types::ComponentId;
 use greentic_types::flow_resolve::{ComponentSourceRefV1, FlowResolveV1};
 use greentic_types::flow_resolve_summary::{
@@ -11,9 +12,13 @@ use semver::Version;
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 use std::fs;
+use std::io::{self, BufReader};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+/// Read size used when hashing component files.
+const DIGEST_CHUNK_SIZE: usize = 64 * 1024;
+
 pub fn write_flow_resolve_summary_for_node(
     flow_path: &Path,
     node_id: &str,
@@ -58,6 +63,34 @@ pub fn write_flow_resolve_summary_for_flow(
 pub fn remove_flow_resolve_summary_node(
     flow_path: &Path,
     node_id: &str,
+) -> Result<Option<PathBuf>> {
+    remove_flow_resolve_summary_nodes(flow_path, &[node_id.to_string()])
+}
+
+/// Remove several nodes' summary entries in one write; a flow without a summary is left alone.
+pub fn remove_flow_resolve_summary_nodes(
+    flow_path: &Path,
+    node_ids: &[String],
+) -> Result<Option<PathBuf>> {
+    let summary_path = resolve_summary_path_for_flow(flow_path);
+    if !summary_path.exists() {
+        return Ok(None);
+    }
+    let mut summary =
+        read_flow_resolve_summary(&summary_path).map_err(|e| anyhow!(e.to_string()))?;
+    summary.flow = flow_name_from_path(flow_path);
+    for node_id in node_ids {
+        summary.nodes.remove(node_id);
+    }
+    write_flow_resolve_summary(&summary_path, &summary).map_err(|e| anyhow!(e.to_string()))?;
+    Ok(Some(summary_path))
+}
+
+/// Move a node's summary entry to `to`; a flow without a summary is left alone.
+pub fn rename_flow_resolve_summary_node(
+    flow_path: &Path,
+    from: &str,
+    to: &str,
 ) -> Result<Option<PathBuf>> {
     let summary_path = resolve_summary_path_for_flow(flow_path);
     if !summary_path.exists() {
@@ -66,7 +99,9 @@ pub fn remove_flow_resolve_summary_node(
     let mut summary =
         read_flow_resolve_summary(&summary_path).map_err(|e| anyhow!(e.to_string()))?;
     summary.flow = flow_name_from_path(flow_path);
-    summary.nodes.remove(node_id);
+    if let Some(node) = summary.nodes.remove(from) {
+        summary.nodes.insert(to.to_string(), node);
+    }
     write_flow_resolve_summary(&summary_path, &summary).map_err(|e| anyhow!(e.to_string()))?;
     Ok(Some(summary_path))
 }
@@ -92,14 +127,17 @@ fn summarize_node(
     node_id: &str,
     source: &ComponentSourceRefV1,
 ) -> Result<NodeResolveSummaryV1> {
-    let (source_ref, wasm_path, digest) = resolve_source(flow_path, source)?;
-    let manifest_path = find_manifest_for_wasm(&wasm_path).with_context(|| {
-        format!(
-            "component.manifest.json not found for node '{}' ({})",
-            node_id,
-            wasm_path.display()
-        )
-    })?;
+    let (source_ref, resolved) = resolve_source(flow_path, source)?;
+    let manifest_path = match resolved.manifest_path {
+        Some(path) => path,
+        None => find_manifest_for_wasm(&resolved.wasm_path).with_context(|| {
+            format!(
+                "component.manifest.json not found for node '{}' ({})",
+                node_id,
+                resolved.wasm_path.display()
+            )
+        })?,
+    };
     let (component_id, manifest) = read_manifest_metadata(&manifest_path).with_context(|| {
         format!(
             "failed to read component.manifest.json for node '{}' ({})",
@@ -110,7 +148,7 @@ fn summarize_node(
     Ok(NodeResolveSummaryV1 {
         component_id,
         source: source_ref,
-        digest,
+        digest: resolved.digest,
         manifest,
     })
 }
@@ -118,29 +156,20 @@ fn summarize_node(
 fn resolve_source(
     flow_path: &Path,
     source: &ComponentSourceRefV1,
-) -> Result<(FlowResolveSummarySourceRefV1, PathBuf, String)> {
-    match source {
+) -> Result<(FlowResolveSummarySourceRefV1, ResolvedComponent)> {
+    let registry = ResolverRegistry::current();
+    let resolved = match source {
         ComponentSourceRefV1::Local { path, .. } => {
             let wasm_path = local_path_from_sidecar(path, flow_path);
-            let digest = compute_sha256(&wasm_path)?;
-            Ok((summary_source_ref(source), wasm_path, digest))
+            registry.resolve(&format!("file://{}", wasm_path.display()), None)?
         }
-        ComponentSourceRefV1::Oci { r#ref, digest } => {
-            resolve_remote(flow_path, r#ref, digest.as_deref(), RemoteKind::Oci)
+        ComponentSourceRefV1::Oci { r#ref, digest }
+        | ComponentSourceRefV1::Repo { r#ref, digest }
+        | ComponentSourceRefV1::Store { r#ref, digest, .. } => {
+            registry.resolve(r#ref, digest.as_deref())?
         }
-        ComponentSourceRefV1::Repo { r#ref, digest } => {
-            resolve_remote(flow_path, r#ref, digest.as_deref(), RemoteKind::Repo)
-        }
-        ComponentSourceRefV1::Store { r#ref, digest, .. } => {
-            resolve_remote(flow_path, r#ref, digest.as_deref(), RemoteKind::Store)
-        }
-    }
-}
-
-enum RemoteKind {
-    Oci,
-    Repo,
-    Store,
+    };
+    Ok((summary_source_ref(source), resolved))
 }
 
 fn summary_source_ref(source: &ComponentSourceRefV1) -> FlowResolveSummarySourceRefV1 {
@@ -160,78 +189,6 @@ fn summary_source_ref(source: &ComponentSourceRefV1) -> FlowResolveSummarySource
     }
 }
 
-fn resolve_remote(
-    _flow_path: &Path,
-    reference: &str,
-    digest_hint: Option<&str>,
-    kind: RemoteKind,
-) -> Result<(FlowResolveSummarySourceRefV1, PathBuf, String)> {
-    let client = DistClient::new(Default::default());
-    let rt = tokio::runtime::Runtime::new().context("create tokio runtime")?;
-    let digest = match digest_hint {
-        Some(d) => d.to_string(),
-        None => {
-            rt.block_on(client.resolve_ref(reference))
-                .map_err(|e| anyhow!("failed to resolve reference {reference}: {e}"))?
-                .digest
-        }
-    };
-    let mut wasm_path = if let Ok(path) = rt.block_on(client.fetch_digest(&digest)) {
-        path
-    } else {
-        let resolved = rt.block_on(client.ensure_cached(reference)).map_err(|e| {
-            anyhow!(
-                "component reference {} not available locally: {e}",
-                reference
-            )
-        })?;
-        resolved
-            .cache_path
-            .ok_or_else(|| anyhow!("component reference {} has no cache path", reference))?
-    };
-    if let Some(cache_dir) = wasm_path.parent()
-        && let Some(manifest_wasm) = manifest_wasm_from_dir(cache_dir)?
-    {
-        wasm_path = manifest_wasm;
-    }
-    let source_ref = match kind {
-        RemoteKind::Oci => FlowResolveSummarySourceRefV1::Oci {
-            r#ref: reference.to_string(),
-        },
-        RemoteKind::Repo => FlowResolveSummarySourceRefV1::Repo {
-            r#ref: reference.to_string(),
-        },
-        RemoteKind::Store => FlowResolveSummarySourceRefV1::Store {
-            r#ref: reference.to_string(),
-        },
-    };
-    Ok((source_ref, wasm_path, digest))
-}
-
-fn manifest_wasm_from_dir(cache_dir: &Path) -> Result<Option<PathBuf>> {
-    let manifest_path = cache_dir.join("component.manifest.json");
-    if !manifest_path.exists() {
-        return Ok(None);
-    }
-    let raw = fs::read_to_string(&manifest_path)
-        .with_context(|| format!("read {}", manifest_path.display()))?;
-    let json: serde_json::Value =
-        serde_json::from_str(&raw).context("parse component.manifest.json")?;
-    let rel = json
-        .get("artifacts")
-        .and_then(|v| v.get("component_wasm"))
-        .and_then(|v| v.as_str());
-    let Some(rel) = rel else {
-        return Ok(None);
-    };
-    let candidate = cache_dir.join(rel);
-    if candidate.exists() {
-        Ok(Some(candidate))
-    } else {
-        Ok(None)
-    }
-}
-
 fn find_manifest_for_wasm(wasm_path: &Path) -> Result<PathBuf> {
     let wasm_abs = fs::canonicalize(wasm_path)
         .with_context(|| format!("resolve wasm path {}", wasm_path.display()))?;
@@ -309,7 +266,10 @@ fn strip_file_prefix(path: &str) -> String {
     path.strip_prefix("file://").unwrap_or(path).to_string()
 }
 
-fn local_path_from_sidecar(path: &str, flow_path: &Path) -> PathBuf {
+pub(crate) fn local_path_from_sidecar(path: &str, flow_path: &Path) -> PathBuf {
+    if let Some(artifact) = cas_local_path(path) {
+        return artifact;
+    }
     let trimmed = path.strip_prefix("file://").unwrap_or(path);
     let raw = PathBuf::from(trimmed);
     if raw.is_absolute() {
@@ -322,9 +282,18 @@ fn local_path_from_sidecar(path: &str, flow_path: &Path) -> PathBuf {
     }
 }
 
-fn compute_sha256(path: &Path) -> Result<String> {
-    let bytes = fs::read(path).with_context(|| format!("read wasm at {}", path.display()))?;
+/// `sha256:<hex>` digest of the file at `path`.
+///
+/// The file is hashed in fixed-size chunks, so peak memory stays bounded for large components.
+pub fn compute_sha256(path: &Path) -> Result<String> {
+    let file = fs::File::open(path).with_context(|| format!("read wasm at {}", path.display()))?;
+    let mut reader = BufReader::with_capacity(DIGEST_CHUNK_SIZE, file);
     let mut sha = Sha256::new();
-    sha.update(bytes);
+    io::copy(&mut reader, &mut sha).with_context(|| format!("read wasm at {}", path.display()))?;
     Ok(format!("sha256:{:x}", sha.finalize()))
 }
+
+/// `sha256:<hex>` digest of bytes already in memory, matching [`compute_sha256`].
+pub fn sha256_digest(bytes: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(bytes))
+}
@@ -0,0 +1,112 @@
+use crate::{
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_bundle::FlowBundle,
+    lint::LintDiagnostic,
+};
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// Worlds and schema ids a target runtime provides.
+///
+/// ```json
+/// {"worlds": ["greentic:component@0.6.2"], "schemas": ["greentic.weather.v1"]}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct RuntimeCapabilities {
+    #[serde(default)]
+    pub worlds: Vec<String>,
+    #[serde(default)]
+    pub schemas: Vec<String>,
+}
+
+impl RuntimeCapabilities {
+    pub fn from_json_str(text: &str) -> Result<Self> {
+        serde_json::from_str(text).map_err(|e| FlowError::Internal {
+            message: format!("invalid runtime capability manifest: {e}"),
+            location: FlowErrorLocation::at_path("runtime manifest".to_string()),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| FlowError::Internal {
+            message: format!("read {}: {e}", path.display()),
+            location: FlowErrorLocation::at_path(path.display().to_string())
+                .with_source_path(Some(path)),
+        })?;
+        Self::from_json_str(&text).map_err(|err| match err {
+            FlowError::Internal { message, .. } => FlowError::Internal {
+                message: format!("{message} ({})", path.display()),
+                location: FlowErrorLocation::at_path(path.display().to_string())
+                    .with_source_path(Some(path)),
+            },
+            other => other,
+        })
+    }
+
+    /// Whether a provided world satisfies `required` (`package@version`).
+    ///
+    /// Versions are compared with caret semantics, so a runtime providing
+    /// `greentic:component@0.6.2` serves components built against `@0.6.0` but not `@0.7.0`.
+    /// Unversioned or non-semver worlds must match exactly.
+    pub fn provides_world(&self, required: &str) -> bool {
+        let Some((package, version)) = required.rsplit_once('@') else {
+            return self.worlds.iter().any(|world| world == required);
+        };
+        let Ok(req) = VersionReq::parse(&format!("^{version}")) else {
+            return self.worlds.iter().any(|world| world == required);
+        };
+        self.worlds
+            .iter()
+            .any(|world| match world.rsplit_once('@') {
+                Some((provided, provided_version)) if provided == package => {
+                    Version::parse(provided_version).is_ok_and(|v| req.matches(&v))
+                }
+                _ => world == required,
+            })
+    }
+
+    pub fn provides_schema(&self, schema_id: &str) -> bool {
+        self.schemas.iter().any(|schema| schema == schema_id)
+    }
+}
+
+/// Per-node `runtime_compat` errors for worlds and schema ids the runtime does not provide.
+pub fn check_runtime_compatibility(
+    bundle: &FlowBundle,
+    runtime: &RuntimeCapabilities,
+) -> Vec<LintDiagnostic> {
+    let mut diagnostics = Vec::new();
+    for node in &bundle.nodes {
+        let path = format!("/nodes/{}", node.node_id);
+        if let Some(world) = &node.world
+            && !runtime.provides_world(world)
+        {
+            diagnostics.push(
+                LintDiagnostic::error(
+                    "runtime_compat",
+                    format!(
+                        "node '{}' component '{}' requires world '{world}', which the target runtime does not provide",
+                        node.node_id, node.component.name
+                    ),
+                )
+                .with_path(path.clone()),
+            );
+        }
+        if let Some(schema_id) = &node.schema_id
+            && !runtime.provides_schema(schema_id)
+        {
+            diagnostics.push(
+                LintDiagnostic::error(
+                    "runtime_compat",
+                    format!(
+                        "node '{}' component '{}' uses schema '{schema_id}', which the target runtime does not know",
+                        node.node_id, node.component.name
+                    ),
+                )
+                .with_path(path),
+            );
+        }
+    }
+    diagnostics
+}
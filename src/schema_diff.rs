@@ -0,0 +1,337 @@
+//! Structural comparison of component manifest schemas, for spotting breaking upgrades.
+
+use crate::source_map::pointer_token;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// What kind of breaking change a [`SchemaChange`] describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaChangeKind {
+    /// The operation is no longer declared by the manifest.
+    OperationRemoved,
+    /// A property is no longer declared.
+    FieldRemoved,
+    /// A property accepts fewer JSON types than before.
+    TypeNarrowed,
+    /// A property's `enum` no longer allows some values.
+    EnumNarrowed,
+    /// A property became required.
+    FieldNowRequired,
+}
+
+/// One breaking difference between an old and a new schema.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SchemaChange {
+    /// Operation whose input schema changed; `None` for the manifest-level `config_schema`.
+    pub operation: Option<String>,
+    /// JSON pointer into the node payload (`""` for the operation itself).
+    pub pointer: String,
+    pub kind: SchemaChangeKind,
+    pub message: String,
+    /// JSON types the new schema accepts (for [`SchemaChangeKind::TypeNarrowed`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_types: Option<Vec<String>>,
+    /// Values the new schema accepts (for [`SchemaChangeKind::EnumNarrowed`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_values: Option<Vec<Value>>,
+}
+
+impl SchemaChange {
+    fn new(
+        operation: Option<&str>,
+        pointer: String,
+        kind: SchemaChangeKind,
+        message: String,
+    ) -> Self {
+        Self {
+            operation: operation.map(str::to_string),
+            pointer,
+            kind,
+            message,
+            allowed_types: None,
+            allowed_values: None,
+        }
+    }
+
+    /// Why `payload` (authored against the old schema) breaks under this change, if it does.
+    pub fn breaks(&self, payload: &Value) -> Option<String> {
+        match self.kind {
+            SchemaChangeKind::OperationRemoved => Some(self.message.clone()),
+            SchemaChangeKind::FieldRemoved => payload
+                .pointer(&self.pointer)
+                .map(|_| format!("sets {} which is no longer declared", self.pointer)),
+            SchemaChangeKind::FieldNowRequired => {
+                let (parent, _) = self.pointer.rsplit_once('/')?;
+                let parent = payload.pointer(parent)?;
+                (parent.is_object() && payload.pointer(&self.pointer).is_none())
+                    .then(|| format!("does not set newly required {}", self.pointer))
+            }
+            SchemaChangeKind::TypeNarrowed => {
+                let value = payload.pointer(&self.pointer)?;
+                let allowed = self.allowed_types.as_deref()?;
+                (!allowed.iter().any(|ty| type_matches(ty, value))).then(|| {
+                    format!(
+                        "sets {} to a {} but it now accepts {}",
+                        self.pointer,
+                        json_type(value),
+                        allowed.join(", ")
+                    )
+                })
+            }
+            SchemaChangeKind::EnumNarrowed => {
+                let value = payload.pointer(&self.pointer)?;
+                let allowed = self.allowed_values.as_deref()?;
+                (!allowed.contains(value)).then(|| {
+                    format!(
+                        "sets {} to {value}, which is no longer allowed",
+                        self.pointer
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Breaking changes between two component manifests: removed operations, changes to each
+/// remaining operation's input schema, and changes to the manifest-level `config_schema`.
+pub fn diff_manifests(old: &Value, new: &Value) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    let new_ops = operations(new);
+    for (name, old_op) in operations(old) {
+        match new_ops.iter().find(|(new_name, _)| *new_name == name) {
+            None => changes.push(SchemaChange::new(
+                Some(name),
+                String::new(),
+                SchemaChangeKind::OperationRemoved,
+                format!("operation '{name}' was removed"),
+            )),
+            Some((_, new_op)) => {
+                if let (Some(old_schema), Some(new_schema)) =
+                    (input_schema(old_op), input_schema(new_op))
+                {
+                    diff_schema_at(Some(name), "", old_schema, new_schema, &mut changes);
+                }
+            }
+        }
+    }
+    if let (Some(old_schema), Some(new_schema)) = (
+        old.get("config_schema").filter(|s| !s.is_null()),
+        new.get("config_schema").filter(|s| !s.is_null()),
+    ) {
+        diff_schema_at(None, "", old_schema, new_schema, &mut changes);
+    }
+    changes
+}
+
+/// Reasons a node payload for `operation` (authored against manifest `old`) breaks under
+/// `changes`. Operations without an input schema are checked against `config_schema` changes,
+/// matching how doctor picks the schema to validate against.
+pub fn payload_breaks(
+    old: &Value,
+    changes: &[SchemaChange],
+    operation: &str,
+    payload: &Value,
+) -> Vec<String> {
+    relevant_changes(old, changes, operation)
+        .into_iter()
+        .filter_map(|change| change.breaks(payload))
+        .collect()
+}
+
+/// The subset of `changes` that applies to nodes calling `operation` on manifest `old`.
+pub fn relevant_changes<'a>(
+    old: &Value,
+    changes: &'a [SchemaChange],
+    operation: &str,
+) -> Vec<&'a SchemaChange> {
+    let uses_config_schema = operations(old)
+        .into_iter()
+        .find(|(name, _)| *name == operation)
+        .and_then(|(_, op)| input_schema(op))
+        .is_none();
+    changes
+        .iter()
+        .filter(|change| match change.operation.as_deref() {
+            Some(changed) => changed == operation,
+            None => uses_config_schema,
+        })
+        .collect()
+}
+
+/// Breaking changes between two JSON schemas describing the same payload.
+pub fn diff_schemas(old: &Value, new: &Value) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    diff_schema_at(None, "", old, new, &mut changes);
+    changes
+}
+
+fn diff_schema_at(
+    operation: Option<&str>,
+    pointer: &str,
+    old: &Value,
+    new: &Value,
+    changes: &mut Vec<SchemaChange>,
+) {
+    let label = if pointer.is_empty() { "/" } else { pointer };
+    if let Some(new_types) = types(new) {
+        let old_types = types(old);
+        let lost: Vec<&str> = match &old_types {
+            Some(old_types) => old_types
+                .iter()
+                .filter(|ty| !new_types.iter().any(|new_ty| type_covers(new_ty, ty)))
+                .map(String::as_str)
+                .collect(),
+            None => vec!["any"],
+        };
+        if !lost.is_empty() {
+            let mut change = SchemaChange::new(
+                operation,
+                pointer.to_string(),
+                SchemaChangeKind::TypeNarrowed,
+                format!(
+                    "{label} no longer accepts {} (now {})",
+                    lost.join(", "),
+                    new_types.join(", ")
+                ),
+            );
+            change.allowed_types = Some(new_types);
+            changes.push(change);
+        }
+    }
+
+    if let Some(new_enum) = new.get("enum").and_then(Value::as_array) {
+        let dropped: Vec<&Value> = match old.get("enum").and_then(Value::as_array) {
+            Some(old_enum) => old_enum.iter().filter(|v| !new_enum.contains(v)).collect(),
+            None => Vec::new(),
+        };
+        if !dropped.is_empty() || old.get("enum").is_none() {
+            let message = if dropped.is_empty() {
+                format!("{label} is now restricted to an enum")
+            } else {
+                let dropped: Vec<String> = dropped.iter().map(ToString::to_string).collect();
+                format!("{label} no longer allows {}", dropped.join(", "))
+            };
+            let mut change = SchemaChange::new(
+                operation,
+                pointer.to_string(),
+                SchemaChangeKind::EnumNarrowed,
+                message,
+            );
+            change.allowed_values = Some(new_enum.clone());
+            changes.push(change);
+        }
+    }
+
+    let old_props = properties(old);
+    let new_props = properties(new);
+    if let (Some(old_props), Some(new_props)) = (old_props, new_props) {
+        for (key, old_prop) in old_props {
+            let child = format!("{pointer}/{}", pointer_token(key));
+            match new_props.get(key) {
+                None => changes.push(SchemaChange::new(
+                    operation,
+                    child.clone(),
+                    SchemaChangeKind::FieldRemoved,
+                    format!("{child} was removed"),
+                )),
+                Some(new_prop) => diff_schema_at(operation, &child, old_prop, new_prop, changes),
+            }
+        }
+    }
+    let old_required = required(old);
+    for key in required(new).difference(&old_required) {
+        let child = format!("{pointer}/{}", pointer_token(key));
+        changes.push(SchemaChange::new(
+            operation,
+            child.clone(),
+            SchemaChangeKind::FieldNowRequired,
+            format!("{child} is now required"),
+        ));
+    }
+}
+
+fn operations(manifest: &Value) -> Vec<(&str, &Value)> {
+    manifest
+        .get("operations")
+        .and_then(Value::as_array)
+        .map(|ops| {
+            ops.iter()
+                .filter_map(|op| {
+                    let name = ["name", "operation", "id"]
+                        .iter()
+                        .find_map(|key| op.get(*key).and_then(Value::as_str))?;
+                    Some((name, op))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn input_schema(op: &Value) -> Option<&Value> {
+    ["input_schema", "schema"]
+        .iter()
+        .find_map(|key| op.get(*key).filter(|schema| !schema.is_null()))
+}
+
+fn properties(schema: &Value) -> Option<&Map<String, Value>> {
+    schema.get("properties").and_then(Value::as_object)
+}
+
+fn required(schema: &Value) -> BTreeSet<String> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|keys| {
+            keys.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn types(schema: &Value) -> Option<Vec<String>> {
+    match schema.get("type")? {
+        Value::String(ty) => Some(vec![ty.clone()]),
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Whether values of JSON schema type `old` are all accepted by type `new`.
+fn type_covers(new: &str, old: &str) -> bool {
+    new == old || (new == "number" && old == "integer")
+}
+
+fn type_matches(ty: &str, value: &Value) -> bool {
+    match ty {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
@@ -0,0 +1,504 @@
+//! Import JSON Schema into [`SchemaIr`], for `greentic-flow schema convert`.
+//!
+//! Component authors write JSON Schema; describe payloads and canonical schema artifacts carry
+//! `SchemaIr`. [`json_schema_to_schema_ir`] covers the subset the two share and reports every
+//! keyword it had to drop or approximate as a [`SchemaImportDiagnostic`]:
+//!
+//! - `type` (a list of types becomes a union), `enum`, `const`, `oneOf` and `anyOf` (both become
+//!   a union that accepts a value matching any variant);
+//! - object `properties`, `required` and `additionalProperties`; array `items`, `minItems` and
+//!   `maxItems`; string `minLength`, `maxLength`, `pattern` and `format`; `minimum`/`maximum`
+//!   (exclusive bounds are made inclusive, exactly for integers);
+//! - strings with `contentEncoding: base64` become bytes;
+//! - `$ref`s into the document's `$defs`/`definitions` are inlined, recursive ones and other
+//!   references are kept as refs;
+//! - `allOf` of object schemas is merged.
+//!
+//! Annotations (`title`, `description`, `default`, `examples`, `x-*` ...) are dropped silently.
+//! A schema that accepts any value has no `SchemaIr` equivalent and imports as an open object.
+
+use anyhow::{Result, anyhow, bail};
+use ciborium::value::Value as CborValue;
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Result of [`json_schema_to_schema_ir`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaImport {
+    pub schema: SchemaIr,
+    pub diagnostics: Vec<SchemaImportDiagnostic>,
+}
+
+/// A JSON Schema feature that was dropped or approximated during the import.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SchemaImportDiagnostic {
+    /// JSON pointer to the keyword in the input schema.
+    pub pointer: String,
+    pub keyword: String,
+    pub message: String,
+}
+
+/// Keywords that only document a schema.
+const ANNOTATIONS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "$defs",
+    "definitions",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "readOnly",
+    "writeOnly",
+    "deprecated",
+    "contentMediaType",
+];
+
+/// Keywords this importer understands, in addition to [`ANNOTATIONS`].
+const HANDLED: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "oneOf",
+    "anyOf",
+    "allOf",
+    "$ref",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "format",
+    "contentEncoding",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+];
+
+/// Convert a JSON Schema document to [`SchemaIr`].
+///
+/// Fails when the document is not a schema (a keyword with the wrong JSON type, an unknown
+/// `type`); features `SchemaIr` cannot express are reported in the diagnostics instead.
+pub fn json_schema_to_schema_ir(schema: &Value) -> Result<SchemaImport> {
+    let mut importer = Importer {
+        root: schema,
+        diagnostics: Vec::new(),
+        resolving: Vec::new(),
+    };
+    let schema = importer.convert(schema, "")?;
+    Ok(SchemaImport {
+        schema,
+        diagnostics: importer.diagnostics,
+    })
+}
+
+struct Importer<'a> {
+    root: &'a Value,
+    diagnostics: Vec<SchemaImportDiagnostic>,
+    /// Local references being inlined, to stop at recursive ones.
+    resolving: Vec<String>,
+}
+
+impl Importer<'_> {
+    fn convert(&mut self, schema: &Value, pointer: &str) -> Result<SchemaIr> {
+        let map = match schema {
+            Value::Bool(true) => return Ok(self.any_value(pointer)),
+            Value::Bool(false) => {
+                self.note(
+                    pointer,
+                    "false",
+                    "a schema that rejects every value is not supported",
+                );
+                return Ok(self.any_value(pointer));
+            }
+            Value::Object(map) => map,
+            other => bail!("{}: expected a schema object, found {other}", at(pointer)),
+        };
+        for key in map.keys() {
+            if !ANNOTATIONS.contains(&key.as_str())
+                && !HANDLED.contains(&key.as_str())
+                && !key.starts_with("x-")
+            {
+                self.note(
+                    &child(pointer, key),
+                    key,
+                    format!("keyword '{key}' is not supported and was ignored"),
+                );
+            }
+        }
+
+        if let Some(reference) = map.get("$ref") {
+            let reference = reference
+                .as_str()
+                .ok_or_else(|| anyhow!("{}: $ref must be a string", at(pointer)))?;
+            return self.reference(reference, &child(pointer, "$ref"));
+        }
+        if let Some(value) = map.get("const") {
+            return Ok(SchemaIr::Enum {
+                values: vec![cbor(value, &child(pointer, "const"))?],
+            });
+        }
+        if let Some(values) = map.get("enum") {
+            let values = values
+                .as_array()
+                .ok_or_else(|| anyhow!("{}: enum must be an array", at(pointer)))?;
+            let pointer = child(pointer, "enum");
+            return Ok(SchemaIr::Enum {
+                values: values
+                    .iter()
+                    .map(|value| cbor(value, &pointer))
+                    .collect::<Result<_>>()?,
+            });
+        }
+        for keyword in ["oneOf", "anyOf"] {
+            if let Some(variants) = map.get(keyword) {
+                return Ok(SchemaIr::OneOf {
+                    variants: self.subschemas(variants, &child(pointer, keyword))?,
+                });
+            }
+        }
+        if let Some(parts) = map.get("allOf") {
+            return self.all_of(parts, &child(pointer, "allOf"));
+        }
+
+        match map.get("type") {
+            Some(Value::String(kind)) => self.typed(kind, map, pointer),
+            Some(Value::Array(kinds)) => {
+                let variants = kinds
+                    .iter()
+                    .map(|kind| match kind {
+                        Value::String(kind) => self.typed(kind, map, pointer),
+                        other => bail!("{}: unknown type {other}", at(&child(pointer, "type"))),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(match <[SchemaIr; 1]>::try_from(variants) {
+                    Ok([only]) => only,
+                    Err(variants) => SchemaIr::OneOf { variants },
+                })
+            }
+            Some(other) => bail!("{}: unknown type {other}", at(&child(pointer, "type"))),
+            None => match inferred_type(map) {
+                Some(kind) => self.typed(kind, map, pointer),
+                None => Ok(self.any_value(pointer)),
+            },
+        }
+    }
+
+    fn typed(&mut self, kind: &str, map: &Map<String, Value>, pointer: &str) -> Result<SchemaIr> {
+        Ok(match kind {
+            "object" => self.object(map, pointer)?,
+            "array" => {
+                let items = match map.get("items") {
+                    Some(Value::Array(_)) => {
+                        self.note(
+                            &child(pointer, "items"),
+                            "items",
+                            "tuple items are not supported; items accept any value",
+                        );
+                        self.any_value(&child(pointer, "items"))
+                    }
+                    Some(items) => self.convert(items, &child(pointer, "items"))?,
+                    None => self.any_value(&child(pointer, "items")),
+                };
+                SchemaIr::Array {
+                    items: Box::new(items),
+                    min_items: unsigned(map, "minItems", pointer)?,
+                    max_items: unsigned(map, "maxItems", pointer)?,
+                }
+            }
+            "string" if map.get("contentEncoding").and_then(Value::as_str) == Some("base64") => {
+                SchemaIr::Bytes
+            }
+            "string" => SchemaIr::String {
+                min_len: unsigned(map, "minLength", pointer)?,
+                max_len: unsigned(map, "maxLength", pointer)?,
+                regex: text(map, "pattern", pointer)?,
+                format: text(map, "format", pointer)?,
+            },
+            "integer" => SchemaIr::Int {
+                min: int_bound(map, "minimum", "exclusiveMinimum", pointer, true)?,
+                max: int_bound(map, "maximum", "exclusiveMaximum", pointer, false)?,
+            },
+            "number" => SchemaIr::Float {
+                min: self.float_bound(map, "minimum", "exclusiveMinimum", pointer)?,
+                max: self.float_bound(map, "maximum", "exclusiveMaximum", pointer)?,
+            },
+            "boolean" => SchemaIr::Bool,
+            "null" => SchemaIr::Null,
+            other => bail!("{}: unknown type '{other}'", at(&child(pointer, "type"))),
+        })
+    }
+
+    fn object(&mut self, map: &Map<String, Value>, pointer: &str) -> Result<SchemaIr> {
+        let mut properties = BTreeMap::new();
+        if let Some(declared) = map.get("properties") {
+            let declared = declared
+                .as_object()
+                .ok_or_else(|| anyhow!("{}: properties must be an object", at(pointer)))?;
+            let base = child(pointer, "properties");
+            for (name, property) in declared {
+                properties.insert(name.clone(), self.convert(property, &child(&base, name))?);
+            }
+        }
+        let required = match map.get("required") {
+            Some(Value::Array(names)) => names
+                .iter()
+                .map(|name| {
+                    name.as_str().map(str::to_string).ok_or_else(|| {
+                        anyhow!(
+                            "{}: must list property names",
+                            at(&child(pointer, "required"))
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => bail!("{}: required must be an array", at(pointer)),
+            None => Vec::new(),
+        };
+        let additional = match map.get("additionalProperties") {
+            None | Some(Value::Bool(true)) => AdditionalProperties::Allow,
+            Some(Value::Bool(false)) => AdditionalProperties::Forbid,
+            Some(extra) => AdditionalProperties::Schema(Box::new(
+                self.convert(extra, &child(pointer, "additionalProperties"))?,
+            )),
+        };
+        Ok(SchemaIr::Object {
+            properties,
+            required,
+            additional,
+        })
+    }
+
+    fn float_bound(
+        &mut self,
+        map: &Map<String, Value>,
+        inclusive: &str,
+        exclusive: &str,
+        pointer: &str,
+    ) -> Result<Option<f64>> {
+        let number = |keyword: &str| {
+            map.get(keyword)
+                .map(|value| {
+                    value.as_f64().ok_or_else(|| {
+                        anyhow!("{}: must be a number", at(&child(pointer, keyword)))
+                    })
+                })
+                .transpose()
+        };
+        if let Some(value) = number(exclusive)? {
+            self.note(
+                &child(pointer, exclusive),
+                exclusive,
+                format!("{exclusive} is imported as an inclusive bound"),
+            );
+            return Ok(Some(value));
+        }
+        number(inclusive)
+    }
+
+    fn subschemas(&mut self, schemas: &Value, pointer: &str) -> Result<Vec<SchemaIr>> {
+        let schemas = schemas
+            .as_array()
+            .ok_or_else(|| anyhow!("{}: must be an array of schemas", at(pointer)))?;
+        schemas
+            .iter()
+            .enumerate()
+            .map(|(idx, schema)| self.convert(schema, &child(pointer, &idx.to_string())))
+            .collect()
+    }
+
+    /// Merge object parts; anything else keeps the first part.
+    fn all_of(&mut self, parts: &Value, pointer: &str) -> Result<SchemaIr> {
+        let mut parts = self.subschemas(parts, pointer)?.into_iter();
+        let Some(mut merged) = parts.next() else {
+            return Ok(self.any_value(pointer));
+        };
+        for part in parts {
+            match (&mut merged, part) {
+                (
+                    SchemaIr::Object {
+                        properties,
+                        required,
+                        additional,
+                    },
+                    SchemaIr::Object {
+                        properties: more,
+                        required: more_required,
+                        additional: more_additional,
+                    },
+                ) => {
+                    properties.extend(more);
+                    for name in more_required {
+                        if !required.contains(&name) {
+                            required.push(name);
+                        }
+                    }
+                    if matches!(more_additional, AdditionalProperties::Forbid) {
+                        *additional = AdditionalProperties::Forbid;
+                    }
+                }
+                _ => {
+                    self.note(
+                        pointer,
+                        "allOf",
+                        "allOf is only merged for object schemas; later parts were ignored",
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(merged)
+    }
+
+    fn reference(&mut self, reference: &str, pointer: &str) -> Result<SchemaIr> {
+        let local = reference
+            .strip_prefix("#/$defs/")
+            .or_else(|| reference.strip_prefix("#/definitions/"));
+        let Some(name) = local else {
+            self.note(
+                pointer,
+                "$ref",
+                format!("reference '{reference}' is kept as a ref; payload validation does not follow refs"),
+            );
+            return Ok(SchemaIr::Ref {
+                id: reference.to_string(),
+            });
+        };
+        if self.resolving.iter().any(|seen| seen == reference) {
+            self.note(
+                pointer,
+                "$ref",
+                format!("recursive reference '{reference}' is kept as a ref"),
+            );
+            return Ok(SchemaIr::Ref {
+                id: reference.to_string(),
+            });
+        }
+        let section = if reference.starts_with("#/$defs/") {
+            "$defs"
+        } else {
+            "definitions"
+        };
+        let target = self
+            .root
+            .get(section)
+            .and_then(|defs| defs.get(name))
+            .ok_or_else(|| anyhow!("{}: unresolved reference '{reference}'", at(pointer)))?;
+        self.resolving.push(reference.to_string());
+        let schema = self.convert(target, &format!("/{section}/{name}"));
+        self.resolving.pop();
+        schema
+    }
+
+    fn any_value(&mut self, pointer: &str) -> SchemaIr {
+        self.note(
+            pointer,
+            "",
+            "schema accepts any value; SchemaIr has no such type, imported as an open object",
+        );
+        SchemaIr::Object {
+            properties: BTreeMap::new(),
+            required: Vec::new(),
+            additional: AdditionalProperties::Allow,
+        }
+    }
+
+    fn note(&mut self, pointer: &str, keyword: &str, message: impl Into<String>) {
+        self.diagnostics.push(SchemaImportDiagnostic {
+            pointer: pointer.to_string(),
+            keyword: keyword.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+/// The type implied by a schema's keywords when it has no `type`.
+fn inferred_type(map: &Map<String, Value>) -> Option<&'static str> {
+    let has = |keywords: &[&str]| keywords.iter().any(|keyword| map.contains_key(*keyword));
+    if has(&["properties", "required", "additionalProperties"]) {
+        Some("object")
+    } else if has(&["items", "minItems", "maxItems"]) {
+        Some("array")
+    } else if has(&[
+        "minLength",
+        "maxLength",
+        "pattern",
+        "format",
+        "contentEncoding",
+    ]) {
+        Some("string")
+    } else if has(&["minimum", "maximum", "exclusiveMinimum", "exclusiveMaximum"]) {
+        Some("number")
+    } else {
+        None
+    }
+}
+
+/// Integer bounds are exact: an exclusive minimum of 2.5 or 2 is an inclusive minimum of 3.
+fn int_bound(
+    map: &Map<String, Value>,
+    inclusive: &str,
+    exclusive: &str,
+    pointer: &str,
+    lower: bool,
+) -> Result<Option<i64>> {
+    let number = |keyword: &str| {
+        map.get(keyword)
+            .map(|value| {
+                value
+                    .as_f64()
+                    .ok_or_else(|| anyhow!("{}: must be a number", at(&child(pointer, keyword))))
+            })
+            .transpose()
+    };
+    Ok(match (number(exclusive)?, lower) {
+        (Some(value), true) => Some(value.floor() as i64 + 1),
+        (Some(value), false) => Some(value.ceil() as i64 - 1),
+        (None, true) => number(inclusive)?.map(|value| value.ceil() as i64),
+        (None, false) => number(inclusive)?.map(|value| value.floor() as i64),
+    })
+}
+
+fn unsigned(map: &Map<String, Value>, keyword: &str, pointer: &str) -> Result<Option<u64>> {
+    map.get(keyword)
+        .map(|value| {
+            value.as_u64().ok_or_else(|| {
+                anyhow!(
+                    "{}: must be a non-negative integer",
+                    at(&child(pointer, keyword))
+                )
+            })
+        })
+        .transpose()
+}
+
+fn text(map: &Map<String, Value>, keyword: &str, pointer: &str) -> Result<Option<String>> {
+    map.get(keyword)
+        .map(|value| {
+            value
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("{}: must be a string", at(&child(pointer, keyword))))
+        })
+        .transpose()
+}
+
+fn cbor(value: &Value, pointer: &str) -> Result<CborValue> {
+    CborValue::serialized(value).map_err(|err| anyhow!("{}: {err}", at(pointer)))
+}
+
+fn child(pointer: &str, token: &str) -> String {
+    format!("{pointer}/{}", token.replace('~', "~0").replace('/', "~1"))
+}
+
+fn at(pointer: &str) -> &str {
+    if pointer.is_empty() { "/" } else { pointer }
+}
@@ -0,0 +1,299 @@
+//! Human-readable and JSON Schema renderings of [`SchemaIr`].
+//!
+//! - [`schema_ir_to_json_schema`] converts to a JSON Schema document, for exports and `--format
+//!   json` output.
+//! - [`render_schema_table`] prints one row per field with its type, whether it is required, and
+//!   its constraints.
+//! - [`describe_schema_ir`] gives a one-line summary for diagnostics, such as
+//!   `object with fields: message (string, required), path (string)`.
+
+use ciborium::value::Value as CborValue;
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use serde_json::{Map, Value, json};
+
+use crate::wizard_ops::cbor_value_to_json;
+
+/// Convert `schema` to JSON Schema. Bytes become base64 strings and refs become `$ref`.
+pub fn schema_ir_to_json_schema(schema: &SchemaIr) -> Value {
+    let mut out = Map::new();
+    match schema {
+        SchemaIr::Object {
+            properties,
+            required,
+            additional,
+        } => {
+            out.insert("type".into(), json!("object"));
+            out.insert(
+                "properties".into(),
+                Value::Object(
+                    properties
+                        .iter()
+                        .map(|(name, property)| (name.clone(), schema_ir_to_json_schema(property)))
+                        .collect(),
+                ),
+            );
+            if !required.is_empty() {
+                out.insert("required".into(), json!(required));
+            }
+            match additional {
+                AdditionalProperties::Allow => {}
+                AdditionalProperties::Forbid => {
+                    out.insert("additionalProperties".into(), json!(false));
+                }
+                AdditionalProperties::Schema(extra) => {
+                    out.insert(
+                        "additionalProperties".into(),
+                        schema_ir_to_json_schema(extra),
+                    );
+                }
+            }
+        }
+        SchemaIr::Array {
+            items,
+            min_items,
+            max_items,
+        } => {
+            out.insert("type".into(), json!("array"));
+            out.insert("items".into(), schema_ir_to_json_schema(items));
+            insert_some(&mut out, "minItems", *min_items);
+            insert_some(&mut out, "maxItems", *max_items);
+        }
+        SchemaIr::String {
+            min_len,
+            max_len,
+            regex,
+            format,
+        } => {
+            out.insert("type".into(), json!("string"));
+            insert_some(&mut out, "minLength", *min_len);
+            insert_some(&mut out, "maxLength", *max_len);
+            insert_some(&mut out, "pattern", regex.as_ref());
+            insert_some(&mut out, "format", format.as_ref());
+        }
+        SchemaIr::Int { min, max } => {
+            out.insert("type".into(), json!("integer"));
+            insert_some(&mut out, "minimum", *min);
+            insert_some(&mut out, "maximum", *max);
+        }
+        SchemaIr::Float { min, max } => {
+            out.insert("type".into(), json!("number"));
+            insert_some(&mut out, "minimum", *min);
+            insert_some(&mut out, "maximum", *max);
+        }
+        SchemaIr::Bool => {
+            out.insert("type".into(), json!("boolean"));
+        }
+        SchemaIr::Null => {
+            out.insert("type".into(), json!("null"));
+        }
+        SchemaIr::Bytes => {
+            out.insert("type".into(), json!("string"));
+            out.insert("contentEncoding".into(), json!("base64"));
+        }
+        SchemaIr::Enum { values } => {
+            out.insert(
+                "enum".into(),
+                Value::Array(values.iter().map(enum_value).collect()),
+            );
+        }
+        SchemaIr::OneOf { variants } => {
+            out.insert(
+                "oneOf".into(),
+                Value::Array(variants.iter().map(schema_ir_to_json_schema).collect()),
+            );
+        }
+        SchemaIr::Ref { id } => {
+            out.insert("$ref".into(), json!(id));
+        }
+    }
+    Value::Object(out)
+}
+
+/// Render `schema` as a table with `FIELD`, `TYPE`, `REQUIRED` and `CONSTRAINTS` columns.
+///
+/// Nested fields use dotted paths, array items are listed as `field[]` and the value schema of
+/// an open map as `field.*`. A schema that is not an object is a single `(value)` row.
+pub fn render_schema_table(schema: &SchemaIr) -> String {
+    let mut rows = Vec::new();
+    match schema {
+        SchemaIr::Object { .. } => push_children(&mut rows, "", schema),
+        _ => push_row(&mut rows, "(value)".to_string(), None, schema),
+    }
+    if rows.is_empty() {
+        return "(no fields)\n".to_string();
+    }
+
+    let header = ["FIELD", "TYPE", "REQUIRED", "CONSTRAINTS"].map(str::to_string);
+    let mut widths = header.clone().map(|cell| cell.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Summarize `schema` on one line. Objects list their top-level fields.
+pub fn describe_schema_ir(schema: &SchemaIr) -> String {
+    match schema {
+        SchemaIr::Object {
+            properties,
+            required,
+            ..
+        } if !properties.is_empty() => {
+            let fields: Vec<String> = properties
+                .iter()
+                .map(|(name, property)| {
+                    let kind = schema_kind(property);
+                    if required.contains(name) {
+                        format!("{name} ({kind}, required)")
+                    } else {
+                        format!("{name} ({kind})")
+                    }
+                })
+                .collect();
+            format!("object with fields: {}", fields.join(", "))
+        }
+        _ => schema_kind(schema),
+    }
+}
+
+/// The type of `schema` without its constraints: `string`, `array of integer`, `one of "a" | "b"`.
+pub(crate) fn schema_kind(schema: &SchemaIr) -> String {
+    match schema {
+        SchemaIr::Object { .. } => "object".to_string(),
+        SchemaIr::Array { items, .. } => format!("array of {}", schema_kind(items)),
+        SchemaIr::String { .. } => "string".to_string(),
+        SchemaIr::Int { .. } => "integer".to_string(),
+        SchemaIr::Float { .. } => "number".to_string(),
+        SchemaIr::Bool => "boolean".to_string(),
+        SchemaIr::Null => "null".to_string(),
+        SchemaIr::Bytes => "bytes".to_string(),
+        SchemaIr::Enum { values } => {
+            let values: Vec<String> = values
+                .iter()
+                .map(|value| enum_value(value).to_string())
+                .collect();
+            format!("one of {}", values.join(" | "))
+        }
+        SchemaIr::OneOf { variants } => {
+            let variants: Vec<String> = variants.iter().map(schema_kind).collect();
+            format!("any of {}", variants.join(" | "))
+        }
+        SchemaIr::Ref { id } => format!("ref {id}"),
+    }
+}
+
+/// Constraints of `schema` as short phrases, such as `min length 3` or `no other keys`.
+pub(crate) fn schema_constraints(schema: &SchemaIr) -> Vec<String> {
+    let constraints = match schema {
+        SchemaIr::Object { additional, .. } => {
+            vec![matches!(additional, AdditionalProperties::Forbid).then(|| "no other keys".into())]
+        }
+        SchemaIr::Array {
+            min_items,
+            max_items,
+            ..
+        } => vec![
+            min_items.map(|n| format!("min {n} items")),
+            max_items.map(|n| format!("max {n} items")),
+        ],
+        SchemaIr::String {
+            min_len,
+            max_len,
+            regex,
+            format,
+        } => vec![
+            format.as_ref().map(|f| format!("format {f}")),
+            min_len.map(|n| format!("min length {n}")),
+            max_len.map(|n| format!("max length {n}")),
+            regex.as_ref().map(|r| format!("pattern {r}")),
+        ],
+        SchemaIr::Int { min, max } => vec![
+            min.map(|n| format!(">= {n}")),
+            max.map(|n| format!("<= {n}")),
+        ],
+        SchemaIr::Float { min, max } => vec![
+            min.map(|n| format!(">= {n}")),
+            max.map(|n| format!("<= {n}")),
+        ],
+        _ => Vec::new(),
+    };
+    constraints.into_iter().flatten().collect()
+}
+
+fn push_children(rows: &mut Vec<[String; 4]>, prefix: &str, schema: &SchemaIr) {
+    match schema {
+        SchemaIr::Object {
+            properties,
+            required,
+            additional,
+        } => {
+            for (name, property) in properties {
+                push_row(
+                    rows,
+                    format!("{prefix}{name}"),
+                    Some(required.contains(name)),
+                    property,
+                );
+            }
+            if let AdditionalProperties::Schema(extra) = additional {
+                push_row(rows, format!("{prefix}*"), None, extra);
+            }
+        }
+        SchemaIr::Array { items, .. } => {
+            push_row(
+                rows,
+                format!("{}[]", prefix.trim_end_matches('.')),
+                None,
+                items,
+            );
+        }
+        _ => {}
+    }
+}
+
+fn push_row(rows: &mut Vec<[String; 4]>, field: String, required: Option<bool>, schema: &SchemaIr) {
+    let kind = match schema {
+        // Items get their own row, so the type column only names the container.
+        SchemaIr::Array { .. } => "array".to_string(),
+        _ => schema_kind(schema),
+    };
+    let required = match required {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "-",
+    };
+    rows.push([
+        field.clone(),
+        kind,
+        required.to_string(),
+        schema_constraints(schema).join(", "),
+    ]);
+    match schema {
+        SchemaIr::Object { .. } => push_children(rows, &format!("{field}."), schema),
+        SchemaIr::Array { .. } => push_children(rows, &field, schema),
+        _ => {}
+    }
+}
+
+fn insert_some<T: serde::Serialize>(out: &mut Map<String, Value>, key: &str, value: Option<T>) {
+    if let Some(value) = value {
+        out.insert(key.to_string(), json!(value));
+    }
+}
+
+fn enum_value(value: &CborValue) -> Value {
+    cbor_value_to_json(value).unwrap_or_else(|_| Value::String(format!("{value:?}")))
+}
@@ -1,6 +1,8 @@
 use ciborium::value::Value as CborValue;
 use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
 
+use crate::schema_render::describe_schema_ir;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Severity {
     Error,
@@ -30,6 +32,15 @@ fn validate_inner(
     path: &str,
     diags: &mut Vec<SchemaDiagnostic>,
 ) {
+    if !kind_matches(schema, value) {
+        diags.push(SchemaDiagnostic {
+            code: "SCHEMA_TYPE_MISMATCH",
+            severity: Severity::Error,
+            message: format!("expected {} at {path}", describe_schema_ir(schema)),
+            path: path.to_string(),
+        });
+        return;
+    }
     match schema {
         SchemaIr::Object {
             properties,
@@ -57,9 +68,7 @@ fn validate_inner(
         ),
         SchemaIr::Int { min, max } => validate_int(*min, *max, value, path, diags),
         SchemaIr::Float { min, max } => validate_float(*min, *max, value, path, diags),
-        SchemaIr::Bool => require_kind("boolean", matches!(value, CborValue::Bool(_)), path, diags),
-        SchemaIr::Null => require_kind("null", matches!(value, CborValue::Null), path, diags),
-        SchemaIr::Bytes => require_kind("bytes", matches!(value, CborValue::Bytes(_)), path, diags),
+        SchemaIr::Bool | SchemaIr::Null | SchemaIr::Bytes => {}
         SchemaIr::Enum { values } => validate_enum(values, value, path, diags),
         SchemaIr::OneOf { variants } => validate_one_of(variants, value, path, diags),
         SchemaIr::Ref { id } => {
@@ -73,14 +82,18 @@ fn validate_inner(
     }
 }
 
-fn require_kind(kind: &str, ok: bool, path: &str, diags: &mut Vec<SchemaDiagnostic>) {
-    if !ok {
-        diags.push(SchemaDiagnostic {
-            code: "SCHEMA_TYPE_MISMATCH",
-            severity: Severity::Error,
-            message: format!("expected {kind} at {path}"),
-            path: path.to_string(),
-        });
+/// Whether `value` has the CBOR type `schema` expects; enums, unions and refs check their own.
+fn kind_matches(schema: &SchemaIr, value: &CborValue) -> bool {
+    match schema {
+        SchemaIr::Object { .. } => matches!(value, CborValue::Map(_)),
+        SchemaIr::Array { .. } => matches!(value, CborValue::Array(_)),
+        SchemaIr::String { .. } => matches!(value, CborValue::Text(_)),
+        SchemaIr::Int { .. } => matches!(value, CborValue::Integer(_)),
+        SchemaIr::Float { .. } => matches!(value, CborValue::Float(_) | CborValue::Integer(_)),
+        SchemaIr::Bool => matches!(value, CborValue::Bool(_)),
+        SchemaIr::Null => matches!(value, CborValue::Null),
+        SchemaIr::Bytes => matches!(value, CborValue::Bytes(_)),
+        SchemaIr::Enum { .. } | SchemaIr::OneOf { .. } | SchemaIr::Ref { .. } => true,
     }
 }
 
@@ -94,10 +107,7 @@ fn validate_object(
 ) {
     let map = match value {
         CborValue::Map(entries) => entries,
-        _ => {
-            require_kind("object", false, path, diags);
-            return;
-        }
+        _ => return,
     };
 
     let mut values: std::collections::BTreeMap<String, &CborValue> =
@@ -161,10 +171,7 @@ fn validate_array(
 ) {
     let items_val = match value {
         CborValue::Array(items) => items,
-        _ => {
-            require_kind("array", false, path, diags);
-            return;
-        }
+        _ => return,
     };
     let len = items_val.len() as u64;
     if let Some(min) = min_items
@@ -203,10 +210,7 @@ fn validate_string(
 ) {
     let text = match value {
         CborValue::Text(s) => s,
-        _ => {
-            require_kind("string", false, path, diags);
-            return;
-        }
+        _ => return,
     };
     let len = text.chars().count() as u64;
     if let Some(min) = min_len
@@ -256,10 +260,7 @@ fn validate_int(
 ) {
     let num = match value {
         CborValue::Integer(i) => i128::from(*i),
-        _ => {
-            require_kind("integer", false, path, diags);
-            return;
-        }
+        _ => return,
     };
     if let Some(min) = min
         && num < min as i128
@@ -293,10 +294,7 @@ fn validate_float(
     let num = match value {
         CborValue::Float(f) => *f,
         CborValue::Integer(i) => i128::from(*i) as f64,
-        _ => {
-            require_kind("number", false, path, diags);
-            return;
-        }
+        _ => return,
     };
     if let Some(min) = min
         && num < min
@@ -0,0 +1,130 @@
+//! Self-tests that downstream CI can run to catch environment-dependent behavior.
+
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+
+use crate::{
+    add_step::{AddStepSpec, apply_and_validate, plan_add_step},
+    component_catalog::MemoryCatalog,
+    error::{FlowError, FlowErrorLocation, Result},
+    flow_bundle::{blake3_hex, load_and_validate_bundle},
+    flow_ir::parse_flow_to_ir,
+    splice::NEXT_NODE_PLACEHOLDER,
+};
+
+/// Node id and operation of the step the `add-step` check inserts.
+pub const SELFTEST_NODE_ID: &str = "determinism_selftest";
+const SELFTEST_OPERATION: &str = "selftest.noop";
+
+/// One determinism check: the same operation run several times on the same input.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DeterminismCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    /// BLAKE3 of the first run's output; compare across machines to catch drift between
+    /// environments.
+    pub digest: String,
+    /// Where the first differing run diverged, when the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Run each check `runs` times (at least two) against `flow_yaml`:
+///
+/// - `parse-serialize`: parse into the IR and render back to YAML.
+/// - `add-step`: insert a fixed no-op step after the default anchor.
+/// - `bundle-hash`: build the canonical bundle, including its BLAKE3 hash.
+///
+/// Errors are returned only when an operation fails outright; differing output is a failed check.
+pub fn run_determinism_suite(
+    flow_yaml: &str,
+    source: Option<&Path>,
+    runs: usize,
+) -> Result<Vec<DeterminismCheck>> {
+    let runs = runs.max(2);
+    Ok(vec![
+        check("parse-serialize", runs, || parse_serialize(flow_yaml))?,
+        check("add-step", runs, || add_fixed_step(flow_yaml))?,
+        check("bundle-hash", runs, || bundle_output(flow_yaml, source))?,
+    ])
+}
+
+fn check(
+    name: &'static str,
+    runs: usize,
+    mut run: impl FnMut() -> Result<String>,
+) -> Result<DeterminismCheck> {
+    let first = run()?;
+    let mut detail = None;
+    for index in 2..=runs {
+        let output = run()?;
+        if output != first {
+            detail = Some(format!(
+                "run {index} differs from run 1 {}",
+                first_difference(&first, &output)
+            ));
+            break;
+        }
+    }
+    Ok(DeterminismCheck {
+        name,
+        passed: detail.is_none(),
+        digest: blake3_hex(first.as_bytes()),
+        detail,
+    })
+}
+
+fn first_difference(left: &str, right: &str) -> String {
+    let line = left
+        .lines()
+        .zip(right.lines())
+        .position(|(a, b)| a != b)
+        .unwrap_or_else(|| left.lines().count().min(right.lines().count()));
+    format!("at line {}", line + 1)
+}
+
+fn parse_serialize(flow_yaml: &str) -> Result<String> {
+    render(&parse_flow_to_ir(flow_yaml)?.to_doc()?)
+}
+
+fn add_fixed_step(flow_yaml: &str) -> Result<String> {
+    let flow = parse_flow_to_ir(flow_yaml)?;
+    let catalog = MemoryCatalog::default();
+    let spec = AddStepSpec {
+        after: None,
+        before: None,
+        node_id_hint: Some(SELFTEST_NODE_ID.to_string()),
+        node: json!({
+            SELFTEST_OPERATION: { "selftest": true },
+            "routing": [{ "to": NEXT_NODE_PLACEHOLDER }]
+        }),
+        allow_cycles: true,
+        require_placeholder: false,
+        default_routing: Some(Vec::new()),
+    };
+    let plan = plan_add_step(&flow, spec, &catalog).map_err(|diags| FlowError::Internal {
+        message: diags
+            .into_iter()
+            .map(|d| format!("{}: {}", d.code, d.message))
+            .collect::<Vec<_>>()
+            .join("; "),
+        location: FlowErrorLocation::at_path("selftest.add_step"),
+    })?;
+    render(&apply_and_validate(&flow, plan, &catalog, true)?.to_doc()?)
+}
+
+fn bundle_output(flow_yaml: &str, source: Option<&Path>) -> Result<String> {
+    let bundle = load_and_validate_bundle(flow_yaml, source)?;
+    serde_json::to_string(&bundle).map_err(|e| FlowError::Internal {
+        message: format!("serialize bundle: {e}"),
+        location: FlowErrorLocation::at_path("selftest.bundle"),
+    })
+}
+
+fn render(doc: &crate::model::FlowDoc) -> Result<String> {
+    serde_yaml_bw::to_string(doc).map_err(|e| FlowError::Internal {
+        message: format!("serialize flow: {e}"),
+        location: FlowErrorLocation::at_path("selftest"),
+    })
+}
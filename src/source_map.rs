@@ -0,0 +1,152 @@
+//! Line/column positions of the values in a YGTC document.
+//!
+//! `serde_yaml` values carry no positions, so the loader walks the YAML event stream once and
+//! records where each mapping entry and sequence item starts, keyed by JSON pointer. Mapping
+//! entries point at their key, which is the line an author looks for.
+
+use saphyr_parser::{Event, Parser};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 1-based line and column in the flow source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct SourceSpan {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// Positions of a document's values by JSON pointer (`""` is the document root).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    spans: BTreeMap<String, SourceSpan>,
+}
+
+enum Frame {
+    Mapping {
+        pointer: String,
+        key: Option<String>,
+    },
+    Sequence {
+        pointer: String,
+        index: usize,
+    },
+}
+
+impl SourceMap {
+    /// Map the first document in `source`.
+    ///
+    /// Best effort: positions recorded before a scan error are kept, and complex (non-scalar)
+    /// mapping keys are skipped together with their values.
+    pub fn from_yaml(source: &str) -> Self {
+        let mut spans = BTreeMap::new();
+        let mut stack: Vec<Frame> = Vec::new();
+        // Nesting depth inside a complex key that is being skipped.
+        let mut skipping = 0usize;
+        for item in Parser::new_from_str(source) {
+            let Ok((event, span)) = item else {
+                break;
+            };
+            let here = SourceSpan {
+                line: span.start.line(),
+                col: span.start.col() + 1,
+            };
+            if skipping > 0 {
+                match event {
+                    Event::MappingStart(..) | Event::SequenceStart(..) => skipping += 1,
+                    Event::MappingEnd | Event::SequenceEnd => skipping -= 1,
+                    _ => {}
+                }
+                if skipping == 0
+                    && let Some(Frame::Mapping { key, .. }) = stack.last_mut()
+                {
+                    *key = Some(String::new());
+                }
+                continue;
+            }
+            match event {
+                Event::DocumentEnd => break,
+                Event::MappingEnd | Event::SequenceEnd => {
+                    stack.pop();
+                    continue;
+                }
+                Event::Scalar(..)
+                | Event::Alias(..)
+                | Event::MappingStart(..)
+                | Event::SequenceStart(..) => {}
+                _ => continue,
+            }
+            let child = match stack.last_mut() {
+                None => String::new(),
+                Some(Frame::Mapping { pointer, key }) => match key.take() {
+                    Some(key) => format!("{pointer}/{}", pointer_token(&key)),
+                    None => {
+                        match &event {
+                            Event::Scalar(value, ..) => {
+                                let entry = format!("{pointer}/{}", pointer_token(value));
+                                spans.entry(entry).or_insert(here);
+                                *key = Some(value.to_string());
+                            }
+                            Event::Alias(..) => *key = Some(String::new()),
+                            _ => skipping = 1,
+                        }
+                        continue;
+                    }
+                },
+                Some(Frame::Sequence { pointer, index }) => {
+                    let child = format!("{pointer}/{index}");
+                    *index += 1;
+                    spans.entry(child.clone()).or_insert(here);
+                    child
+                }
+            };
+            if child.is_empty() {
+                spans.entry(String::new()).or_insert(here);
+            }
+            match event {
+                Event::MappingStart(..) => stack.push(Frame::Mapping {
+                    pointer: child,
+                    key: None,
+                }),
+                Event::SequenceStart(..) => stack.push(Frame::Sequence {
+                    pointer: child,
+                    index: 0,
+                }),
+                _ => {}
+            }
+        }
+        Self { spans }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Position of the value at `pointer`, if the source had one there.
+    pub fn get(&self, pointer: &str) -> Option<SourceSpan> {
+        self.spans.get(pointer).copied()
+    }
+
+    /// Position of `pointer`, or of its nearest ancestor that has one.
+    ///
+    /// Pointers into values the loader synthesised (a defaulted `routing`, say) resolve to the
+    /// enclosing node instead of nothing.
+    pub fn locate(&self, pointer: &str) -> Option<SourceSpan> {
+        let mut current = pointer.trim_end_matches('/');
+        loop {
+            if let Some(span) = self.get(current) {
+                return Some(span);
+            }
+            current = &current[..current.rfind('/')?];
+        }
+    }
+
+    /// Position of the node `node_id` under `nodes`.
+    pub fn node(&self, node_id: &str) -> Option<SourceSpan> {
+        self.get(&format!("/nodes/{}", pointer_token(node_id)))
+    }
+}
+
+/// Escape a single JSON pointer reference token.
+pub(crate) fn pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,217 @@
+//! Which wizard questions write which state keys, and where two of them disagree.
+//!
+//! A question's state key is its `writes_to` path, or its `id` when it has none.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::questions::{Answers, Question};
+
+/// A question that writes a state key.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct StateKeyUse {
+    pub key: String,
+    /// Questions node of the config flow.
+    pub node_id: String,
+    pub question: String,
+}
+
+/// Two writers that gave the same state key different values in one run.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StateKeyCollision {
+    pub key: String,
+    /// The writer whose value was recorded first, e.g. `ask.region`.
+    pub first: String,
+    pub first_value: Value,
+    pub second: String,
+    pub second_value: Value,
+}
+
+impl std::fmt::Display for StateKeyCollision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "state key '{}' written by {} ({}) and {} ({})",
+            self.key, self.first, self.first_value, self.second, self.second_value
+        )
+    }
+}
+
+/// Per-run record of the first value written to each state key.
+#[derive(Clone, Debug, Default)]
+pub struct StateKeyRegistry {
+    writes: BTreeMap<String, (String, Value)>,
+    collisions: Vec<StateKeyCollision>,
+}
+
+impl StateKeyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `writer` setting `key` to `value`. Returns the collision when another writer
+    /// already set the key to a different value. Repeated writes of the same value are fine.
+    pub fn record(&mut self, key: &str, writer: &str, value: &Value) -> Option<&StateKeyCollision> {
+        let Some((first, first_value)) = self.writes.get(key) else {
+            self.writes
+                .insert(key.to_string(), (writer.to_string(), value.clone()));
+            return None;
+        };
+        if first == writer || first_value == value {
+            return None;
+        }
+        self.collisions.push(StateKeyCollision {
+            key: key.to_string(),
+            first: first.clone(),
+            first_value: first_value.clone(),
+            second: writer.to_string(),
+            second_value: value.clone(),
+        });
+        self.collisions.last()
+    }
+
+    pub fn collisions(&self) -> &[StateKeyCollision] {
+        &self.collisions
+    }
+
+    pub fn into_collisions(self) -> Vec<StateKeyCollision> {
+        self.collisions
+    }
+}
+
+/// State key a questions field writes: `writes_to`, else `id`.
+pub fn field_state_key(field: &Value) -> Option<&str> {
+    field
+        .get("writes_to")
+        .and_then(Value::as_str)
+        .or_else(|| field.get("id").and_then(Value::as_str))
+}
+
+/// Every questions field of a config flow graph (`nodes.<id>.questions.fields`), in node order.
+pub fn config_flow_state_keys(config_flow: &Value) -> Vec<StateKeyUse> {
+    let Some(nodes) = config_flow.get("nodes").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let mut uses = Vec::new();
+    for (node_id, node) in nodes {
+        let fields = node
+            .pointer("/questions/fields")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for field in fields {
+            let (Some(key), Some(question)) = (
+                field_state_key(field),
+                field.get("id").and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            uses.push(StateKeyUse {
+                key: key.to_string(),
+                node_id: node_id.clone(),
+                question: question.to_string(),
+            });
+        }
+    }
+    uses
+}
+
+/// Record the value each questions field contributes: its answer, else its default.
+pub fn record_question_fields(
+    registry: &mut StateKeyRegistry,
+    node_id: &str,
+    fields: &[Value],
+    answers: &Map<String, Value>,
+) {
+    for field in fields {
+        let (Some(key), Some(id)) = (
+            field_state_key(field),
+            field.get("id").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        if let Some(value) = answers.get(id).or_else(|| field.get("default")) {
+            registry.record(key, &format!("{node_id}.{id}"), value);
+        }
+    }
+}
+
+/// Collisions between questions whose `writes_to` paths coincide but whose answers differ;
+/// the later question silently overwrites the earlier one in the payload.
+pub fn writes_to_collisions(questions: &[Question], answers: &Answers) -> Vec<StateKeyCollision> {
+    let mut registry = StateKeyRegistry::new();
+    for question in questions {
+        let (Some(key), Some(value)) = (question.writes_to.as_deref(), answers.get(&question.id))
+        else {
+            continue;
+        };
+        registry.record(key, &question.id, value);
+    }
+    registry.into_collisions()
+}
+
+/// Where in a pack a state key is written.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct StateKeyUser {
+    pub flow: String,
+    /// Flow node bound to the component whose dev flow asks the question; absent for config
+    /// flows checked into the pack.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+    /// `dev_flows` entry of the component manifest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_flow: Option<String>,
+    /// Questions node of the config flow.
+    pub questions_node: String,
+    pub question: String,
+}
+
+impl StateKeyUser {
+    /// The wizard that owns the question: a component (however many nodes use it) or a
+    /// config flow file.
+    fn owner(&self) -> &str {
+        self.component.as_deref().unwrap_or(&self.flow)
+    }
+}
+
+/// Pack-level index of state keys and the wizards that write them.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StateKeyReport {
+    pub keys: BTreeMap<String, Vec<StateKeyUser>>,
+}
+
+impl StateKeyReport {
+    pub fn add(&mut self, key: &str, user: StateKeyUser) {
+        self.keys.entry(key.to_string()).or_default().push(user);
+    }
+
+    /// Record every question of `config_flow`, attributing it with `user`'s flow, node,
+    /// component and dev flow.
+    pub fn add_config_flow(&mut self, config_flow: &Value, user: &StateKeyUser) {
+        for question in config_flow_state_keys(config_flow) {
+            self.add(
+                &question.key,
+                StateKeyUser {
+                    questions_node: question.node_id,
+                    question: question.question,
+                    ..user.clone()
+                },
+            );
+        }
+    }
+
+    /// Keys written by more than one component or config flow, where one can overwrite the
+    /// other.
+    pub fn shared_keys(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().filter_map(|(key, users)| {
+            let first = users.first()?.owner();
+            users
+                .iter()
+                .any(|user| user.owner() != first)
+                .then_some(key.as_str())
+        })
+    }
+}
@@ -66,6 +66,23 @@ impl TemplateRenderer {
     }
 }
 
+/// State paths a template reads inside `{{ ... }}` blocks, such as `user.name` for
+/// `{{state.user.name}}` or `{{#if state.ready}}`, in order of first use.
+pub fn template_state_references(template: &str) -> Vec<String> {
+    let blocks = Regex::new(r"\{\{(.*?)\}\}").unwrap();
+    let paths = Regex::new(r"(?:^|[^\w.])state\.([A-Za-z_][\w-]*(?:\.[A-Za-z_][\w-]*)*)").unwrap();
+    let mut references: Vec<String> = Vec::new();
+    for block in blocks.captures_iter(template) {
+        for path in paths.captures_iter(&block[1]) {
+            let path = path[1].to_string();
+            if !references.contains(&path) {
+                references.push(path);
+            }
+        }
+    }
+    references
+}
+
 fn manifest_label(manifest_id: Option<&str>) -> String {
     manifest_id
         .map(|id| format!(" in manifest '{id}'"))
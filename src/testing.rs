@@ -0,0 +1,162 @@
+//! Seeded generator for random, valid flows (corpus and property-test input).
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+
+/// Routing shapes a [`FlowGenerator`] may give a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RoutingKind {
+    /// A single `to:` route to the following node.
+    Next,
+    /// One to three status routes to later nodes, plus a default route to the following node.
+    Branch,
+    /// The `reply` shorthand.
+    Reply,
+    /// The `out` shorthand.
+    End,
+}
+
+impl RoutingKind {
+    pub const ALL: [RoutingKind; 4] = [Self::Next, Self::Branch, Self::Reply, Self::End];
+}
+
+/// Builds random flows that load, compile, and pass the built-in and document lint rules.
+///
+/// Nodes are named `n0`, `n1`, ... and only route forward, so every node is reachable from
+/// `start` and the flow is acyclic. The same settings always produce the same YAML.
+#[derive(Clone, Debug)]
+pub struct FlowGenerator {
+    pub seed: u64,
+    /// Number of nodes (at least one is always generated).
+    pub nodes: usize,
+    /// Probability in `0.0..=1.0` that a non-final node branches when [`RoutingKind::Branch`]
+    /// is allowed.
+    pub branchiness: f64,
+    /// Routing shapes to draw from; [`RoutingKind::Next`] and [`RoutingKind::End`] are used as
+    /// fallbacks when the allowed kinds cannot keep the flow connected or terminated.
+    pub routing_kinds: BTreeSet<RoutingKind>,
+    /// Flow `type` written to the document.
+    pub flow_type: String,
+}
+
+impl Default for FlowGenerator {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl FlowGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            nodes: 8,
+            branchiness: 0.3,
+            routing_kinds: RoutingKind::ALL.into_iter().collect(),
+            flow_type: "messaging".to_string(),
+        }
+    }
+
+    /// Generate the flow as YAML text.
+    pub fn generate_yaml(&self) -> String {
+        let mut rng = SplitMix64(self.seed);
+        let count = self.nodes.max(1);
+        let mut reached = vec![false; count];
+        reached[0] = true;
+        let mut nodes = IndexMap::new();
+
+        for idx in 0..count {
+            let last = idx + 1 == count;
+            let mut kinds: Vec<RoutingKind> = self
+                .routing_kinds
+                .iter()
+                .copied()
+                .filter(|kind| match kind {
+                    RoutingKind::Branch => !last && rng.chance(self.branchiness),
+                    RoutingKind::Next => !last,
+                    // Ending early is only safe once the next node has another way in.
+                    RoutingKind::Reply | RoutingKind::End => last || reached[idx + 1],
+                })
+                .collect();
+            if kinds.contains(&RoutingKind::Branch) {
+                kinds = vec![RoutingKind::Branch];
+            }
+            let kind = match kinds.as_slice() {
+                [] if last => RoutingKind::End,
+                [] => RoutingKind::Next,
+                kinds => kinds[rng.below(kinds.len())],
+            };
+
+            let routing = match kind {
+                RoutingKind::Next => {
+                    reached[idx + 1] = true;
+                    json!([{ "to": node_name(idx + 1) }])
+                }
+                RoutingKind::Branch => {
+                    let later = count - idx - 1;
+                    let statuses = 1 + rng.below(later.min(3));
+                    let mut routes = Vec::new();
+                    for status in 0..statuses {
+                        let target = idx + 1 + rng.below(later);
+                        reached[target] = true;
+                        routes.push(
+                            json!({ "status": format!("s{status}"), "to": node_name(target) }),
+                        );
+                    }
+                    reached[idx + 1] = true;
+                    routes.push(json!({ "to": node_name(idx + 1) }));
+                    Value::Array(routes)
+                }
+                RoutingKind::Reply => json!("reply"),
+                RoutingKind::End => json!("out"),
+            };
+            nodes.insert(
+                node_name(idx),
+                json!({ "qa.process": { "step": idx }, "routing": routing }),
+            );
+        }
+
+        let flow = GeneratedFlow {
+            id: format!("random-{}", self.seed),
+            flow_type: &self.flow_type,
+            start: node_name(0),
+            nodes,
+        };
+        serde_yaml_bw::to_string(&flow).expect("generated flow is plain data and always serializes")
+    }
+}
+
+#[derive(Serialize)]
+struct GeneratedFlow<'a> {
+    id: String,
+    #[serde(rename = "type")]
+    flow_type: &'a str,
+    start: String,
+    nodes: IndexMap<String, Value>,
+}
+
+fn node_name(idx: usize) -> String {
+    format!("n{idx}")
+}
+
+/// SplitMix64: tiny, dependency-free, and stable across releases, so seeds stay reproducible.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
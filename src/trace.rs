@@ -0,0 +1,432 @@
+//! Map runtime traces back onto a flow.
+//!
+//! The runtime exports one span per executed node as OTLP/JSON (`resourceSpans` → `scopeSpans` →
+//! `spans`). A span belongs to a node when it carries one of [`NODE_ID_ATTRIBUTES`], when its name
+//! is the node's `telemetry.span_name`, or when its name is the node id. Spans tagged with another
+//! [`FLOW_ID_ATTRIBUTE`] are ignored.
+//!
+//! The node spans of one trace, ordered by start time, are the executed path. Each step is paired
+//! with the route that leads to the next step (or, for the last step, to `out`/`reply`), using the
+//! node's [`NODE_STATUS_ATTRIBUTE`] to pick between status routes to the same target.
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::flow_ir::{FlowIr, GraphHighlight, Route};
+
+/// Span attributes naming the node a span executed, in the order they are checked.
+pub const NODE_ID_ATTRIBUTES: [&str; 3] = ["greentic.node.id", "greentic.flow.node_id", "node.id"];
+
+/// Span attribute naming the flow a span belongs to.
+pub const FLOW_ID_ATTRIBUTE: &str = "greentic.flow.id";
+
+/// Span attribute holding the status the node produced (what its routes match on).
+pub const NODE_STATUS_ATTRIBUTE: &str = "greentic.node.status";
+
+/// One span of an OTLP/JSON export.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TraceSpan {
+    pub trace_id: String,
+    pub span_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub start_unix_nano: u64,
+    pub end_unix_nano: u64,
+    /// Attribute values rendered as strings.
+    pub attributes: BTreeMap<String, String>,
+    /// The span status is `ERROR`.
+    pub error: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_message: Option<String>,
+}
+
+impl TraceSpan {
+    pub fn duration_nanos(&self) -> u64 {
+        self.end_unix_nano.saturating_sub(self.start_unix_nano)
+    }
+}
+
+/// Every span in an OTLP/JSON trace export, in file order.
+///
+/// Accepts the `scopeSpans` layout and the older `instrumentationLibrarySpans` one; ids may be
+/// hex or base64 strings and timestamps strings or numbers.
+pub fn parse_otlp_json(text: &str) -> Result<Vec<TraceSpan>> {
+    let export: Value = serde_json::from_str(text).context("invalid OTLP/JSON trace")?;
+    let Some(resource_spans) = export.get("resourceSpans").and_then(Value::as_array) else {
+        bail!("OTLP/JSON trace has no resourceSpans array");
+    };
+    let mut spans = Vec::new();
+    for resource in resource_spans {
+        let scopes = resource
+            .get("scopeSpans")
+            .or_else(|| resource.get("instrumentationLibrarySpans"))
+            .and_then(Value::as_array);
+        for scope in scopes.into_iter().flatten() {
+            let entries = scope.get("spans").and_then(Value::as_array);
+            for (index, span) in entries.into_iter().flatten().enumerate() {
+                spans.push(parse_span(span).with_context(|| format!("span {index}"))?);
+            }
+        }
+    }
+    Ok(spans)
+}
+
+fn parse_span(span: &Value) -> Result<TraceSpan> {
+    let text = |key: &str| span.get(key).and_then(Value::as_str).map(str::to_string);
+    let Some(trace_id) = text("traceId") else {
+        bail!("missing traceId");
+    };
+    let Some(span_id) = text("spanId") else {
+        bail!("missing spanId");
+    };
+    let attributes = span
+        .get("attributes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|attribute| {
+            let key = attribute.get("key")?.as_str()?;
+            Some((key.to_string(), any_value(attribute.get("value")?)?))
+        })
+        .collect();
+    let status = span.get("status");
+    let error = match status.and_then(|status| status.get("code")) {
+        Some(Value::Number(code)) => code.as_u64() == Some(2),
+        Some(Value::String(code)) => code == "STATUS_CODE_ERROR",
+        _ => false,
+    };
+    Ok(TraceSpan {
+        trace_id,
+        span_id,
+        parent_span_id: text("parentSpanId").filter(|id| !id.is_empty()),
+        name: text("name").unwrap_or_default(),
+        start_unix_nano: nanos(span.get("startTimeUnixNano")).context("startTimeUnixNano")?,
+        end_unix_nano: nanos(span.get("endTimeUnixNano")).context("endTimeUnixNano")?,
+        attributes,
+        error,
+        status_message: status
+            .and_then(|status| status.get("message"))
+            .and_then(Value::as_str)
+            .filter(|message| !message.is_empty())
+            .map(str::to_string),
+    })
+}
+
+fn nanos(value: Option<&Value>) -> Result<u64> {
+    match value {
+        None => Ok(0),
+        Some(Value::Number(number)) => number
+            .as_u64()
+            .context("timestamp must be a non-negative integer"),
+        Some(Value::String(text)) => text
+            .parse()
+            .with_context(|| format!("invalid timestamp '{text}'")),
+        Some(other) => bail!("invalid timestamp {other}"),
+    }
+}
+
+/// An OTLP `AnyValue` rendered as a string; arrays and maps are left out.
+fn any_value(value: &Value) -> Option<String> {
+    if let Some(text) = value.get("stringValue").and_then(Value::as_str) {
+        return Some(text.to_string());
+    }
+    ["intValue", "doubleValue", "boolValue"]
+        .iter()
+        .find_map(|key| match value.get(*key)? {
+            Value::String(text) => Some(text.clone()),
+            other => Some(other.to_string()),
+        })
+}
+
+/// Trace ids in `spans`, ordered by their earliest span.
+pub fn trace_ids(spans: &[TraceSpan]) -> Vec<String> {
+    let mut first: BTreeMap<&str, u64> = BTreeMap::new();
+    for span in spans {
+        let start = first.entry(&span.trace_id).or_insert(span.start_unix_nano);
+        *start = (*start).min(span.start_unix_nano);
+    }
+    let mut ids: Vec<(&str, u64)> = first.into_iter().collect();
+    ids.sort_by_key(|(id, start)| (*start, *id));
+    ids.into_iter().map(|(id, _)| id.to_string()).collect()
+}
+
+/// One executed node.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TraceStep {
+    pub node_id: String,
+    pub span_id: String,
+    pub start_unix_nano: u64,
+    pub duration_nanos: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    pub error: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    /// Index of the route taken out of the node, when one matches the next step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<usize>,
+    /// Where the taken route leads: a node id, `out` or `reply`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+/// A route as `from` → `to` (a node id, `out` or `reply`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TraceRoute {
+    pub from: String,
+    /// Index in the node's routing; the implicit `out` of a node without routing is 0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    pub to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A trace laid over a flow.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TraceAnnotation {
+    pub trace_id: String,
+    pub steps: Vec<TraceStep>,
+    /// Routes of executed nodes that the trace did not take.
+    pub untaken: Vec<TraceRoute>,
+    /// Consecutive steps that no route of the first node leads to (the flow on disk may differ
+    /// from the one that ran).
+    pub unexpected: Vec<TraceRoute>,
+    /// Spans of the trace that matched no node.
+    pub unmatched_spans: usize,
+}
+
+impl TraceAnnotation {
+    /// Executed and failed nodes and taken routes, for the highlighted graph renderers.
+    pub fn highlight(&self) -> GraphHighlight {
+        let mut highlight = GraphHighlight::default();
+        for step in &self.steps {
+            highlight.nodes.insert(step.node_id.clone());
+            if step.error {
+                highlight.failed.insert(step.node_id.clone());
+            }
+            if let Some(index) = step.route {
+                highlight.routes.insert((step.node_id.clone(), index));
+            }
+        }
+        highlight
+    }
+}
+
+/// Lay the trace `trace_id` (by default the earliest trace in `spans`) over `flow`.
+pub fn annotate_trace(
+    flow: &FlowIr,
+    spans: &[TraceSpan],
+    trace_id: Option<&str>,
+) -> Result<TraceAnnotation> {
+    let trace_id = match trace_id {
+        Some(id) => {
+            if !spans.iter().any(|span| span.trace_id == id) {
+                bail!("trace {id} is not in the export");
+            }
+            id.to_string()
+        }
+        None => match trace_ids(spans).into_iter().next() {
+            Some(id) => id,
+            None => bail!("the trace export has no spans"),
+        },
+    };
+    let spans: Vec<&TraceSpan> = spans
+        .iter()
+        .filter(|span| span.trace_id == trace_id)
+        .filter(|span| {
+            span.attributes
+                .get(FLOW_ID_ATTRIBUTE)
+                .is_none_or(|id| *id == flow.id)
+        })
+        .collect();
+    let node_of: BTreeMap<&str, &str> = spans
+        .iter()
+        .filter_map(|span| Some((span.span_id.as_str(), span_node(flow, span)?)))
+        .collect();
+    let mut node_spans: Vec<&TraceSpan> = spans
+        .iter()
+        .copied()
+        .filter(|span| {
+            let Some(node) = node_of.get(span.span_id.as_str()) else {
+                return false;
+            };
+            // Child spans a node opens for its own work are part of that step.
+            span.parent_span_id
+                .as_deref()
+                .and_then(|parent| node_of.get(parent))
+                .is_none_or(|parent| parent != node)
+        })
+        .collect();
+    node_spans.sort_by_key(|span| span.start_unix_nano);
+
+    let mut steps: Vec<TraceStep> = node_spans
+        .iter()
+        .map(|span| TraceStep {
+            node_id: node_of[span.span_id.as_str()].to_string(),
+            span_id: span.span_id.clone(),
+            start_unix_nano: span.start_unix_nano,
+            duration_nanos: span.duration_nanos(),
+            status: span.attributes.get(NODE_STATUS_ATTRIBUTE).cloned(),
+            error: span.error,
+            error_message: span.status_message.clone().filter(|_| span.error),
+            route: None,
+            next: None,
+        })
+        .collect();
+
+    let mut unexpected = Vec::new();
+    for index in 0..steps.len() {
+        let next = steps.get(index + 1).map(|step| step.node_id.clone());
+        let step = &mut steps[index];
+        let node = &flow.nodes[step.node_id.as_str()];
+        let taken = match &next {
+            Some(next) => pick_route(&node.routing, step.status.as_deref(), |route| {
+                route_target(route) == RouteTarget::Node
+                    && route.to.as_deref() == Some(next.as_str())
+            }),
+            None if node.routing.is_empty() => Some(0),
+            None => pick_route(&node.routing, step.status.as_deref(), |route| {
+                route_target(route) != RouteTarget::Node
+            }),
+        };
+        match (taken, next) {
+            (Some(route), next) => {
+                step.route = Some(route);
+                step.next = Some(next.unwrap_or_else(|| {
+                    node.routing
+                        .get(route)
+                        .map_or("out", |route| route_target_name(route))
+                        .to_string()
+                }));
+            }
+            (None, Some(next)) => {
+                step.next = Some(next.clone());
+                unexpected.push(TraceRoute {
+                    from: step.node_id.clone(),
+                    index: None,
+                    to: next,
+                    label: None,
+                });
+            }
+            (None, None) => {}
+        }
+    }
+
+    let taken: BTreeSet<(&str, usize)> = steps
+        .iter()
+        .filter_map(|step| Some((step.node_id.as_str(), step.route?)))
+        .collect();
+    let executed: BTreeSet<&str> = steps.iter().map(|step| step.node_id.as_str()).collect();
+    let mut untaken = Vec::new();
+    for (id, node) in &flow.nodes {
+        if !executed.contains(id.as_str()) {
+            continue;
+        }
+        if node.routing.is_empty() && !taken.contains(&(id.as_str(), 0)) {
+            untaken.push(TraceRoute {
+                from: id.clone(),
+                index: Some(0),
+                to: "out".to_string(),
+                label: None,
+            });
+        }
+        for (index, route) in node.routing.iter().enumerate() {
+            if taken.contains(&(id.as_str(), index)) {
+                continue;
+            }
+            untaken.push(TraceRoute {
+                from: id.clone(),
+                index: Some(index),
+                to: route_target_name(route).to_string(),
+                label: route.label.clone().or_else(|| {
+                    route
+                        .status
+                        .as_ref()
+                        .map(|status| format!("status {status}"))
+                }),
+            });
+        }
+    }
+
+    Ok(TraceAnnotation {
+        trace_id,
+        unmatched_spans: spans.len() - node_of.len(),
+        steps,
+        untaken,
+        unexpected,
+    })
+}
+
+/// The node a span executed, if any.
+fn span_node<'a>(flow: &'a FlowIr, span: &TraceSpan) -> Option<&'a str> {
+    if let Some(id) = NODE_ID_ATTRIBUTES
+        .iter()
+        .find_map(|key| span.attributes.get(*key))
+    {
+        return flow.nodes.get_key_value(id).map(|(id, _)| id.as_str());
+    }
+    flow.nodes
+        .values()
+        .find(|node| {
+            node.telemetry
+                .as_ref()
+                .and_then(|telemetry| telemetry.get("span_name"))
+                .and_then(Value::as_str)
+                == Some(span.name.as_str())
+        })
+        .or_else(|| flow.nodes.get(&span.name))
+        .map(|node| node.id.as_str())
+}
+
+/// The first route accepted by `wanted` whose status matches `status`; routes without a status
+/// are the fallback. Without a known status, the first accepted route wins.
+fn pick_route(
+    routing: &[Route],
+    status: Option<&str>,
+    wanted: impl Fn(&Route) -> bool,
+) -> Option<usize> {
+    let candidates = || {
+        routing
+            .iter()
+            .enumerate()
+            .filter(|(_, route)| wanted(route))
+    };
+    let Some(status) = status else {
+        return candidates().map(|(index, _)| index).next();
+    };
+    candidates()
+        .find(|(_, route)| {
+            route
+                .status_matcher()
+                .is_some_and(|matcher| matcher.matches(status))
+        })
+        .or_else(|| candidates().find(|(_, route)| route.status.is_none()))
+        .map(|(index, _)| index)
+}
+
+#[derive(PartialEq, Eq)]
+enum RouteTarget {
+    Node,
+    Out,
+    Reply,
+}
+
+fn route_target(route: &Route) -> RouteTarget {
+    match (&route.to, route.out, route.reply) {
+        (_, _, true) => RouteTarget::Reply,
+        (Some(to), false, false) if to != "out" => RouteTarget::Node,
+        _ => RouteTarget::Out,
+    }
+}
+
+fn route_target_name(route: &Route) -> &str {
+    match route_target(route) {
+        RouteTarget::Node => route.to.as_deref().unwrap_or("out"),
+        RouteTarget::Out => "out",
+        RouteTarget::Reply => "reply",
+    }
+}
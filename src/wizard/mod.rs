@@ -205,6 +205,7 @@ impl FlowScaffoldWizardProvider {
             entrypoints: IndexMap::new(),
             meta: None,
             nodes: IndexMap::new(),
+            spans: Default::default(),
         };
 
         if scaffold_nodes {
@@ -279,14 +280,21 @@ fn validate_flow_file(path: &Path) -> Result<()> {
         .map_err(|err| anyhow!("load scaffolded flow {}: {err}", path.display()))?;
     let compiled = crate::compile_flow(doc)
         .map_err(|err| anyhow!("compile scaffolded flow {}: {err}", path.display()))?;
-    let lint_errors = crate::lint::lint_builtin_rules(&compiled);
+    let lint_errors: Vec<_> = crate::lint::lint_builtin_diagnostics(&compiled)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == crate::lint::LintSeverity::Error)
+        .collect();
     if lint_errors.is_empty() {
         Ok(())
     } else {
         Err(anyhow!(
             "scaffolded flow {} failed builtin lint: {}",
             path.display(),
-            lint_errors.join("; ")
+            lint_errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; ")
         ))
     }
 }
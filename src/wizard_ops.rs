@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 
 use anyhow::{Result, anyhow};
 use serde_json::Value as JsonValue;
@@ -8,6 +9,7 @@ use greentic_interfaces_host::component_v0_6::exports::greentic::component::node
     ComponentDescriptor, SchemaSource,
 };
 use greentic_types::cbor::canonical;
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
 use greentic_types::schemas::component::v0_6_0::{ComponentQaSpec, QaMode, QuestionKind};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -43,6 +45,34 @@ impl WizardMode {
     }
 }
 
+/// The wasm of a component to run a wizard (or lint rule) against.
+///
+/// Files are compiled straight from disk, so a large component is not also held in a host
+/// buffer for the whole run.
+#[derive(Debug, Clone)]
+pub enum ComponentWasm {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+impl ComponentWasm {
+    /// blake3 of the component, streamed from disk for [`ComponentWasm::File`].
+    pub fn content_hash(&self) -> Result<blake3::Hash> {
+        match self {
+            ComponentWasm::File(path) => {
+                let file = std::fs::File::open(path)
+                    .map_err(|err| anyhow!("open {}: {err}", path.display()))?;
+                let mut hasher = blake3::Hasher::new();
+                hasher
+                    .update_reader(file)
+                    .map_err(|err| anyhow!("read {}: {err}", path.display()))?;
+                Ok(hasher.finalize())
+            }
+            ComponentWasm::Bytes(bytes) => Ok(blake3::hash(bytes)),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct WizardOutput {
     pub abi: WizardAbi,
@@ -54,6 +84,7 @@ pub struct WizardOutput {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
 pub struct WizardSpecOutput {
     pub abi: WizardAbi,
     pub describe_cbor: Vec<u8>,
@@ -73,7 +104,7 @@ mod host {
     };
     use wasmtime::component::{Component, Linker};
     use wasmtime::component::{ResourceTable, Val};
-    use wasmtime::{Config, Engine, Store, StoreContextMut};
+    use wasmtime::{Config, Engine, Store, StoreContextMut, UpdateDeadline};
     use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
 
     mod runtime {
@@ -82,6 +113,14 @@ mod host {
         pub type RuntimeComponent = greentic_interfaces_host::component_v0_6::ComponentV0V6V0;
     }
 
+    fn load_component(engine: &Engine, wasm: &ComponentWasm) -> Result<Component> {
+        match wasm {
+            ComponentWasm::File(path) => Component::from_file(engine, path),
+            ComponentWasm::Bytes(bytes) => Component::from_binary(engine, bytes),
+        }
+        .map_err(|err| anyhow!("load component: {err}"))
+    }
+
     struct HostState {
         wasi: WasiCtx,
         table: ResourceTable,
@@ -138,10 +177,37 @@ mod host {
         }
     }
 
+    /// How often a running component checks for cancellation (Ctrl-C, `--timeout`).
+    const EPOCH_TICK: std::time::Duration = std::time::Duration::from_millis(50);
+
     fn build_engine() -> Result<Engine> {
         let mut config = Config::new();
         config.wasm_component_model(true);
-        Engine::new(&config).map_err(|err| anyhow!("init wasm engine: {err}"))
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config).map_err(|err| anyhow!("init wasm engine: {err}"))?;
+        let weak = engine.weak();
+        std::thread::spawn(move || {
+            while let Some(engine) = weak.upgrade() {
+                engine.increment_epoch();
+                drop(engine);
+                std::thread::sleep(EPOCH_TICK);
+            }
+        });
+        Ok(engine)
+    }
+
+    /// A store whose guest traps at the next epoch tick once the process is cancelled.
+    fn new_store(engine: &Engine) -> Store<HostState> {
+        let mut store = Store::new(engine, HostState::new());
+        store.set_epoch_deadline(1);
+        store.epoch_deadline_callback(|_| {
+            Ok(if crate::cancel::cancelled().is_some() {
+                UpdateDeadline::Interrupt
+            } else {
+                UpdateDeadline::Continue(1)
+            })
+        });
+        store
     }
 
     fn add_wasi_imports(linker: &mut Linker<HostState>) -> Result<()> {
@@ -409,18 +475,17 @@ mod host {
     }
 
     fn invoke_setup_apply(
-        wasm_bytes: &[u8],
+        wasm: &ComponentWasm,
         mode: WizardMode,
         current_config: &[u8],
         answers: &[u8],
     ) -> Result<Vec<u8>> {
         let engine = build_engine()?;
-        let component = Component::from_binary(&engine, wasm_bytes)
-            .map_err(|err| anyhow!("load component: {err}"))?;
+        let component = load_component(&engine, wasm)?;
         let mut linker: Linker<HostState> = Linker::new(&engine);
         add_wasi_imports(&mut linker)?;
         add_control_imports(&mut linker)?;
-        let mut store = Store::new(&engine, HostState::new());
+        let mut store = new_store(&engine);
         let api = runtime::RuntimeComponent::instantiate(&mut store, &component, &linker)
             .map_err(|err| anyhow!("instantiate canonical component world: {err}"))?;
         let node = api.greentic_component_node();
@@ -472,18 +537,17 @@ mod host {
     }
 
     fn instantiate_root(
-        wasm_bytes: &[u8],
+        wasm: &ComponentWasm,
         add_control: bool,
     ) -> Result<(Store<HostState>, wasmtime::component::Instance)> {
         let engine = build_engine()?;
-        let component = Component::from_binary(&engine, wasm_bytes)
-            .map_err(|err| anyhow!("load component: {err}"))?;
+        let component = load_component(&engine, wasm)?;
         let mut linker: Linker<HostState> = Linker::new(&engine);
         add_wasi_imports(&mut linker)?;
         if add_control {
             add_control_imports(&mut linker)?;
         }
-        let mut store = Store::new(&engine, HostState::new());
+        let mut store = new_store(&engine);
         let instance = linker
             .instantiate(&mut store, &component)
             .map_err(|err| anyhow!("instantiate component root world: {err}"))?;
@@ -504,8 +568,8 @@ mod host {
         None
     }
 
-    fn fetch_descriptor_spec(wasm_bytes: &[u8], mode: WizardMode) -> Result<WizardSpecOutput> {
-        let (mut store, instance) = instantiate_root(wasm_bytes, false)?;
+    fn fetch_descriptor_spec(wasm: &ComponentWasm, mode: WizardMode) -> Result<WizardSpecOutput> {
+        let (mut store, instance) = instantiate_root(wasm, false)?;
         let descriptor_instance = find_export_index(
             &mut store,
             &instance,
@@ -577,12 +641,12 @@ mod host {
     }
 
     fn apply_descriptor_answers(
-        wasm_bytes: &[u8],
+        wasm: &ComponentWasm,
         mode: WizardMode,
         current_config: &[u8],
         answers: &[u8],
     ) -> Result<Vec<u8>> {
-        let (mut store, instance) = instantiate_root(wasm_bytes, false)?;
+        let (mut store, instance) = instantiate_root(wasm, false)?;
         let qa_instance = find_export_index(
             &mut store,
             &instance,
@@ -650,20 +714,66 @@ mod host {
         val_to_bytes(&results[0]).map_err(|err| anyhow!("{label} returned invalid bytes: {err}"))
     }
 
-    pub fn fetch_wizard_spec(wasm_bytes: &[u8], _mode: WizardMode) -> Result<WizardSpecOutput> {
+    /// Instantiate a component that needs only WASI imports and call `func` of the first of
+    /// `instances` it exports.
+    fn call_root_export(
+        wasm: &ComponentWasm,
+        instances: &[&str],
+        func: &str,
+        params: &[Val],
+    ) -> Result<Val> {
+        let (mut store, instance) = instantiate_root(wasm, false)?;
+        let parent = find_export_index(&mut store, &instance, None, instances)
+            .ok_or_else(|| anyhow!("missing exported {} instance", instances[0]))?;
+        let export = find_export_index(&mut store, &instance, Some(&parent), &[func])
+            .ok_or_else(|| anyhow!("missing exported {}.{func} function", instances[0]))?;
+        let func_handle = instance
+            .get_func(&mut store, export)
+            .ok_or_else(|| anyhow!("lookup {func}: function export not found"))?;
+        let mut results = [Val::Bool(false)];
+        func_handle
+            .call(&mut store, params, &mut results)
+            .map_err(|err| anyhow!("call {func}: {err}"))?;
+        let [result] = results;
+        Ok(result)
+    }
+
+    /// Call an exported `func() -> string`.
+    pub(crate) fn call_export_string(
+        wasm: &ComponentWasm,
+        instances: &[&str],
+        func: &str,
+    ) -> Result<String> {
+        match call_root_export(wasm, instances, func, &[])? {
+            Val::String(value) => Ok(value),
+            other => Err(anyhow!("{func} returned {other:?}, expected a string")),
+        }
+    }
+
+    /// Call an exported `func(list<u8>) -> list<u8>`.
+    pub(crate) fn call_export_bytes(
+        wasm: &ComponentWasm,
+        instances: &[&str],
+        func: &str,
+        input: &[u8],
+    ) -> Result<Vec<u8>> {
+        let result = call_root_export(wasm, instances, func, &[bytes_to_val(input)])?;
+        val_to_bytes(&result).map_err(|err| anyhow!("{func} returned invalid bytes: {err}"))
+    }
+
+    pub fn fetch_wizard_spec(wasm: &ComponentWasm, _mode: WizardMode) -> Result<WizardSpecOutput> {
         let engine = build_engine()?;
-        let component = Component::from_binary(&engine, wasm_bytes)
-            .map_err(|err| anyhow!("load component: {err}"))?;
+        let component = load_component(&engine, wasm)?;
         let mut linker: Linker<HostState> = Linker::new(&engine);
         add_wasi_imports(&mut linker)?;
         add_control_imports(&mut linker)?;
-        let mut store = Store::new(&engine, HostState::new());
+        let mut store = new_store(&engine);
         let api = match runtime::RuntimeComponent::instantiate(&mut store, &component, &linker) {
             Ok(api) => api,
             Err(err) => {
                 let err = anyhow!("instantiate canonical component world: {err}");
                 if is_missing_node_instance_error(&err) {
-                    return fetch_descriptor_spec(wasm_bytes, _mode);
+                    return fetch_descriptor_spec(wasm, _mode);
                 }
                 return Err(err);
             }
@@ -681,7 +791,7 @@ mod host {
             }) {
             Ok(values) => values,
             Err(err) if is_missing_setup_contract_error(&err) => {
-                return fetch_descriptor_spec(wasm_bytes, _mode);
+                return fetch_descriptor_spec(wasm, _mode);
             }
             Err(err) => return Err(err),
         };
@@ -696,32 +806,31 @@ mod host {
     }
 
     pub fn apply_wizard_answers(
-        wasm_bytes: &[u8],
+        wasm: &ComponentWasm,
         _abi: WizardAbi,
         mode: WizardMode,
         current_config: &[u8],
         answers: &[u8],
     ) -> Result<Vec<u8>> {
-        match invoke_setup_apply(wasm_bytes, mode, current_config, answers) {
+        match invoke_setup_apply(wasm, mode, current_config, answers) {
             Ok(config) => Ok(config),
             Err(err)
                 if is_missing_node_instance_error(&err) || is_missing_setup_apply_error(&err) =>
             {
-                apply_descriptor_answers(wasm_bytes, mode, current_config, answers)
+                apply_descriptor_answers(wasm, mode, current_config, answers)
             }
             Err(err) => Err(err),
         }
     }
 
     pub fn run_wizard_ops(
-        wasm_bytes: &[u8],
+        wasm: &ComponentWasm,
         mode: WizardMode,
         current_config: &[u8],
         answers: &[u8],
     ) -> Result<WizardOutput> {
-        let spec = fetch_wizard_spec(wasm_bytes, mode)?;
-        let config_cbor =
-            apply_wizard_answers(wasm_bytes, spec.abi, mode, current_config, answers)?;
+        let spec = fetch_wizard_spec(wasm, mode)?;
+        let config_cbor = apply_wizard_answers(wasm, spec.abi, mode, current_config, answers)?;
         Ok(WizardOutput {
             abi: spec.abi,
             describe_cbor: spec.describe_cbor,
@@ -736,9 +845,138 @@ mod host {
 #[cfg(not(target_arch = "wasm32"))]
 pub use host::{apply_wizard_answers, fetch_wizard_spec, run_wizard_ops};
 
+/// One wizard run to compute ahead of time with [`WizardCache::prefetch`]: a fully
+/// non-interactive setup whose answers are known up front.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct WizardPrefetch {
+    pub wasm: ComponentWasm,
+    pub mode: WizardMode,
+    /// Answers as given; the component's defaults are merged in as the sequential run does.
+    pub answers: HashMap<String, JsonValue>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type ApplyKey = (blake3::Hash, &'static str, Vec<u8>, Vec<u8>);
+
+/// Wizard results computed ahead of time, keyed by everything the component sees (its bytes,
+/// the mode, the current config and the answers). A lookup that misses falls back to invoking
+/// the component, so a prefetch that guessed different inputs only costs time.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+pub struct WizardCache {
+    specs: std::sync::Mutex<HashMap<(blake3::Hash, &'static str), WizardSpecOutput>>,
+    applied: std::sync::Mutex<HashMap<ApplyKey, Vec<u8>>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl WizardCache {
+    /// [`fetch_wizard_spec`], answered from the cache when it was prefetched.
+    pub fn fetch_wizard_spec(
+        &self,
+        wasm: &ComponentWasm,
+        mode: WizardMode,
+    ) -> Result<WizardSpecOutput> {
+        if let Ok(hash) = wasm.content_hash()
+            && let Some(spec) = self
+                .specs
+                .lock()
+                .expect("wizard cache lock")
+                .get(&(hash, mode.as_str()))
+        {
+            return Ok(spec.clone());
+        }
+        fetch_wizard_spec(wasm, mode)
+    }
+
+    /// [`apply_wizard_answers`], answered from the cache when it was prefetched.
+    pub fn apply_wizard_answers(
+        &self,
+        wasm: &ComponentWasm,
+        abi: WizardAbi,
+        mode: WizardMode,
+        current_config: &[u8],
+        answers: &[u8],
+    ) -> Result<Vec<u8>> {
+        if let Ok(hash) = wasm.content_hash() {
+            let key = (
+                hash,
+                mode.as_str(),
+                current_config.to_vec(),
+                answers.to_vec(),
+            );
+            if let Some(config) = self.applied.lock().expect("wizard cache lock").get(&key) {
+                return Ok(config.clone());
+            }
+        }
+        apply_wizard_answers(wasm, abi, mode, current_config, answers)
+    }
+
+    /// Run the describe and apply-answers invocations of `runs` on up to `jobs` threads and
+    /// keep the results. Runs that fail are left out; the sequential run reports the error.
+    pub fn prefetch(&self, runs: &[WizardPrefetch], jobs: usize) {
+        let next = std::sync::atomic::AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.clamp(1, runs.len().max(1)) {
+                scope.spawn(|| {
+                    while let Some(run) =
+                        runs.get(next.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+                    {
+                        let _ = self.prefetch_one(run);
+                    }
+                });
+            }
+        });
+    }
+
+    fn prefetch_one(&self, run: &WizardPrefetch) -> Result<()> {
+        let hash = run.wasm.content_hash()?;
+        let spec_key = (hash, run.mode.as_str());
+        let cached = self
+            .specs
+            .lock()
+            .expect("wizard cache lock")
+            .get(&spec_key)
+            .cloned();
+        let spec = match cached {
+            Some(spec) => spec,
+            None => {
+                let spec = fetch_wizard_spec(&run.wasm, run.mode)?;
+                self.specs
+                    .lock()
+                    .expect("wizard cache lock")
+                    .insert(spec_key, spec.clone());
+                spec
+            }
+        };
+        let qa_spec = decode_component_qa_spec(&spec.qa_spec_cbor, run.mode)?;
+        let mut answers = run.answers.clone();
+        merge_default_answers(&qa_spec, &mut answers);
+        let answers_cbor = answers_to_cbor(&answers)?;
+        let current_config = empty_cbor_map();
+        let key = (hash, run.mode.as_str(), current_config, answers_cbor);
+        if self
+            .applied
+            .lock()
+            .expect("wizard cache lock")
+            .contains_key(&key)
+        {
+            return Ok(());
+        }
+        let config = apply_wizard_answers(&run.wasm, spec.abi, run.mode, &key.2, &key.3)?;
+        self.applied
+            .lock()
+            .expect("wizard cache lock")
+            .insert(key, config);
+        Ok(())
+    }
+}
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use host::{call_export_bytes, call_export_string};
+
 #[cfg(target_arch = "wasm32")]
 pub fn run_wizard_ops(
-    _wasm_bytes: &[u8],
+    _wasm: &ComponentWasm,
     _mode: WizardMode,
     _current_config: &[u8],
     _answers: &[u8],
@@ -840,6 +1078,118 @@ pub fn cbor_value_to_json(value: &ciborium::value::Value) -> Result<JsonValue> {
     })
 }
 
+/// Rebuild canonical config CBOR from a node payload, using the component's config schema to
+/// undo the lossy CBOR→JSON mapping of [`cbor_value_to_json`] (bytes, wide integers, and
+/// integer/float distinctions). Values the schema does not describe are encoded as-is.
+pub fn config_json_to_cbor(value: &JsonValue, schema: Option<&SchemaIr>) -> Result<Vec<u8>> {
+    let cbor = json_to_cbor_value_with_schema(value, schema)?;
+    canonical::to_canonical_cbor_allow_floats(&cbor)
+        .map_err(|err| anyhow!("encode config as canonical cbor: {err}"))
+}
+
+fn json_to_cbor_value_with_schema(
+    value: &JsonValue,
+    schema: Option<&SchemaIr>,
+) -> Result<ciborium::value::Value> {
+    use ciborium::value::Value as CValue;
+    let Some(schema) = schema else {
+        return json_to_cbor_value_untyped(value);
+    };
+    Ok(match (schema, value) {
+        (SchemaIr::Int { .. }, JsonValue::Number(num)) => match num.as_i64() {
+            Some(v) => CValue::Integer(v.into()),
+            None => match num.as_u64() {
+                Some(v) => CValue::Integer(v.into()),
+                None => match num.as_f64() {
+                    Some(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => {
+                        CValue::Integer((f as i64).into())
+                    }
+                    _ => json_to_cbor_value_untyped(value)?,
+                },
+            },
+        },
+        (SchemaIr::Int { .. }, JsonValue::String(text)) => match text.parse::<i128>() {
+            Ok(wide) => CValue::Integer(
+                ciborium::value::Integer::try_from(wide)
+                    .map_err(|_| anyhow!("integer {wide} out of range for cbor"))?,
+            ),
+            Err(_) => CValue::Text(text.clone()),
+        },
+        (SchemaIr::Float { .. }, JsonValue::Number(num)) => match num.as_f64() {
+            Some(f) => CValue::Float(f),
+            None => json_to_cbor_value_untyped(value)?,
+        },
+        (SchemaIr::Bytes, JsonValue::Array(items)) => {
+            let bytes: Option<Vec<u8>> = items
+                .iter()
+                .map(|item| item.as_u64().and_then(|b| u8::try_from(b).ok()))
+                .collect();
+            match bytes {
+                Some(bytes) => CValue::Bytes(bytes),
+                None => json_to_cbor_value_untyped(value)?,
+            }
+        }
+        (SchemaIr::Array { items, .. }, JsonValue::Array(values)) => CValue::Array(
+            values
+                .iter()
+                .map(|item| json_to_cbor_value_with_schema(item, Some(items)))
+                .collect::<Result<_>>()?,
+        ),
+        (
+            SchemaIr::Object {
+                properties,
+                additional,
+                ..
+            },
+            JsonValue::Object(map),
+        ) => {
+            let mut entries = Vec::with_capacity(map.len());
+            for (key, item) in map {
+                let item_schema = properties.get(key).or(match additional {
+                    AdditionalProperties::Schema(schema) => Some(schema.as_ref()),
+                    _ => None,
+                });
+                entries.push((
+                    CValue::Text(key.clone()),
+                    json_to_cbor_value_with_schema(item, item_schema)?,
+                ));
+            }
+            CValue::Map(entries)
+        }
+        (SchemaIr::OneOf { variants }, _) => {
+            let variant = variants
+                .iter()
+                .find(|variant| schema_accepts(variant, value));
+            json_to_cbor_value_with_schema(value, variant)?
+        }
+        _ => json_to_cbor_value_untyped(value)?,
+    })
+}
+
+/// Shallow type check used to pick a `one_of` variant for a JSON value.
+fn schema_accepts(schema: &SchemaIr, value: &JsonValue) -> bool {
+    match (schema, value) {
+        (SchemaIr::Object { .. }, JsonValue::Object(_)) => true,
+        (SchemaIr::Array { .. } | SchemaIr::Bytes, JsonValue::Array(_)) => true,
+        (SchemaIr::String { .. }, JsonValue::String(_)) => true,
+        (SchemaIr::Int { .. }, JsonValue::Number(num)) => {
+            num.is_i64() || num.is_u64() || num.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        (SchemaIr::Float { .. }, JsonValue::Number(_)) => true,
+        (SchemaIr::Bool, JsonValue::Bool(_)) => true,
+        (SchemaIr::Null, JsonValue::Null) => true,
+        (SchemaIr::Enum { values }, _) => json_to_cbor_value_untyped(value)
+            .map(|cbor| values.contains(&cbor))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn json_to_cbor_value_untyped(value: &JsonValue) -> Result<ciborium::value::Value> {
+    ciborium::value::Value::serialized(value)
+        .map_err(|err| anyhow!("convert json to cbor value: {err}"))
+}
+
 pub fn qa_spec_to_questions(
     spec: &ComponentQaSpec,
     catalog: &I18nCatalog,
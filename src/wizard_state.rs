@@ -89,6 +89,22 @@ pub fn remove_wizard_step(flow_path: &Path, flow_id: &str, node_id: &str) -> Res
     write_wizard_state(flow_path, &state)
 }
 
+pub fn rename_wizard_step(flow_path: &Path, flow_id: &str, from: &str, to: &str) -> Result<()> {
+    let Some(mut state) = load_wizard_state(flow_path, flow_id)? else {
+        return Ok(());
+    };
+    let mut renamed = false;
+    for step in state.steps.iter_mut().filter(|step| step.node_id == from) {
+        step.node_id = to.to_string();
+        renamed = true;
+    }
+    if !renamed {
+        return Ok(());
+    }
+    state.last_updated = now_epoch_secs();
+    write_wizard_state(flow_path, &state)
+}
+
 fn write_wizard_state(flow_path: &Path, state: &WizardState) -> Result<()> {
     let path = wizard_state_path(flow_path, &state.flow_id);
     if let Some(parent) = path.parent() {
@@ -0,0 +1,344 @@
+//! Optional workspace index (`.greentic-flow/index.json`) for queries across many flows.
+//!
+//! The index keeps a summary of every flow under a workspace root: its id, type and tags, and
+//! each node's operation and bound component (from the resolve sidecar). Queries read the
+//! summaries instead of re-parsing every flow. Each entry records the size and modification
+//! time of the flow and its sidecar; an entry whose files changed since is stale and is
+//! re-summarized by [`WorkspaceIndex::refresh`], so only edited flows are parsed again.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "flows": {
+//!     "flows/main.ygtc": {
+//!       "id": "main",
+//!       "type": "messaging",
+//!       "tags": ["billing"],
+//!       "nodes": [
+//!         { "id": "charge", "operation": "pay", "component": "oci://ghcr.io/acme/charge:1.2.0" }
+//!       ],
+//!       "stamp": { "size": 412, "modified_ns": 1760745600000000000 },
+//!       "sidecar_stamp": { "size": 201, "modified_ns": 1760745600000000000 }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! The index only exists once `index rebuild` created it. From then on, commands that write a
+//! flow or its sidecar update that flow's entry through [`update_indexed_flow`].
+
+use anyhow::{Context, Result, bail};
+use greentic_types::flow_resolve::{read_flow_resolve, sidecar_path_for_flow};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::{
+    flow_bundle::ComponentSource, flow_ir::FlowIr, lint::baseline_flow_key,
+    loader::load_ygtc_from_path,
+};
+
+/// Directory holding the index, at the workspace root.
+pub const INDEX_DIR: &str = ".greentic-flow";
+
+/// File name of the index inside [`INDEX_DIR`].
+pub const INDEX_FILE: &str = "index.json";
+
+/// Where the index of the workspace at `root` lives.
+pub fn index_path(root: &Path) -> PathBuf {
+    root.join(INDEX_DIR).join(INDEX_FILE)
+}
+
+/// The workspace root owning the nearest index in `start` or one of its ancestors.
+pub fn discover_index_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| index_path(dir).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Summaries of every flow in a workspace.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceIndex {
+    pub version: u32,
+    /// Keyed by flow path relative to the workspace root, with `/` separators.
+    pub flows: BTreeMap<String, IndexedFlow>,
+}
+
+/// What the index knows about one flow.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedFlow {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub nodes: Vec<IndexedNode>,
+    pub stamp: FileStamp,
+    /// `None` when the flow had no sidecar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sidecar_stamp: Option<FileStamp>,
+}
+
+/// One node of an [`IndexedFlow`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedNode {
+    pub id: String,
+    pub operation: String,
+    /// The sidecar reference the node is bound to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+}
+
+/// Size and modification time of a file when it was indexed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    /// Nanoseconds since the Unix epoch.
+    pub modified_ns: u64,
+}
+
+impl FileStamp {
+    /// The stamp of `path`, or `None` when it does not exist.
+    pub fn of(path: &Path) -> Result<Option<Self>> {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).with_context(|| format!("stat {}", path.display())),
+        };
+        let modified_ns = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| u64::try_from(since.as_nanos()).unwrap_or(u64::MAX))
+            .unwrap_or(0);
+        Ok(Some(Self {
+            size: metadata.len(),
+            modified_ns,
+        }))
+    }
+}
+
+/// What [`WorkspaceIndex::refresh`] did.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct IndexRefresh {
+    /// Flows (re-)summarized because they were new or stale.
+    pub updated: Vec<String>,
+    /// Entries dropped because their flow is gone.
+    pub removed: Vec<String>,
+    /// Flows that could not be summarized, with the reason; their old entries are dropped.
+    pub skipped: Vec<(String, String)>,
+    pub unchanged: usize,
+}
+
+impl IndexRefresh {
+    pub fn changed(&self) -> bool {
+        !self.updated.is_empty() || !self.removed.is_empty() || !self.skipped.is_empty()
+    }
+}
+
+/// A node that matched [`WorkspaceIndex::query`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct IndexMatch {
+    pub flow: String,
+    pub flow_id: String,
+    pub node_id: String,
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+}
+
+/// Filters of [`WorkspaceIndex::query`]; unset filters match everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IndexQuery {
+    /// Substring of the bound component reference.
+    pub component: Option<String>,
+    /// Exact flow tag.
+    pub tag: Option<String>,
+    /// Exact node operation.
+    pub operation: Option<String>,
+}
+
+impl Default for WorkspaceIndex {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            flows: BTreeMap::new(),
+        }
+    }
+}
+
+impl WorkspaceIndex {
+    pub const VERSION: u32 = 1;
+
+    pub fn from_json_str(text: &str) -> Result<Self> {
+        let index: Self = serde_json::from_str(text).context("invalid index JSON")?;
+        if index.version != Self::VERSION {
+            bail!(
+                "unsupported index version {} (expected {})",
+                index.version,
+                Self::VERSION
+            );
+        }
+        Ok(index)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        Self::from_json_str(&text).with_context(|| format!("load {}", path.display()))
+    }
+
+    /// Pretty JSON ending in a newline.
+    pub fn to_json_string(&self) -> String {
+        let mut text = serde_json::to_string_pretty(self).expect("index serializes");
+        text.push('\n');
+        text
+    }
+
+    /// Write the index of the workspace at `root`, creating [`INDEX_DIR`] if needed.
+    pub fn save(&self, root: &Path) -> Result<PathBuf> {
+        let path = index_path(root);
+        let dir = root.join(INDEX_DIR);
+        fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        fs::write(&path, self.to_json_string())
+            .with_context(|| format!("write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Bring the index in line with `flows` (every flow of the workspace at `root`):
+    /// summarize new and stale flows, keep fresh entries and drop the ones whose flow is gone.
+    pub fn refresh(&mut self, root: &Path, flows: &[PathBuf]) -> Result<IndexRefresh> {
+        let mut report = IndexRefresh::default();
+        let mut current = BTreeMap::new();
+        for flow_path in flows {
+            let key = flow_key(root, flow_path);
+            if let Some(entry) = self.flows.remove(&key)
+                && is_fresh(&entry, flow_path)?
+            {
+                report.unchanged += 1;
+                current.insert(key, entry);
+                continue;
+            }
+            match summarize_flow(flow_path) {
+                Ok(entry) => {
+                    report.updated.push(key.clone());
+                    current.insert(key, entry);
+                }
+                Err(err) => report.skipped.push((key, format!("{err:#}"))),
+            }
+        }
+        report.removed = std::mem::replace(&mut self.flows, current)
+            .into_keys()
+            .collect();
+        Ok(report)
+    }
+
+    /// Re-summarize one flow of the workspace at `root`, dropping its entry if it is gone.
+    pub fn update_flow(&mut self, root: &Path, flow_path: &Path) -> Result<()> {
+        let key = flow_key(root, flow_path);
+        if flow_path.is_file() {
+            self.flows.insert(key, summarize_flow(flow_path)?);
+        } else {
+            self.flows.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Nodes matching every set filter of `query`, by flow path and then flow order.
+    pub fn query(&self, query: &IndexQuery) -> Vec<IndexMatch> {
+        let mut matches = Vec::new();
+        for (flow, entry) in &self.flows {
+            if let Some(tag) = &query.tag
+                && !entry.tags.contains(tag)
+            {
+                continue;
+            }
+            for node in &entry.nodes {
+                let component_matches = query.component.as_ref().is_none_or(|wanted| {
+                    node.component
+                        .as_ref()
+                        .is_some_and(|component| component.contains(wanted.as_str()))
+                });
+                let operation_matches = query
+                    .operation
+                    .as_ref()
+                    .is_none_or(|operation| *operation == node.operation);
+                if component_matches && operation_matches {
+                    matches.push(IndexMatch {
+                        flow: flow.clone(),
+                        flow_id: entry.id.clone(),
+                        node_id: node.id.clone(),
+                        operation: node.operation.clone(),
+                        component: node.component.clone(),
+                    });
+                }
+            }
+        }
+        matches
+    }
+}
+
+/// Parse `flow_path` and its sidecar into an index entry.
+pub fn summarize_flow(flow_path: &Path) -> Result<IndexedFlow> {
+    let stamp = FileStamp::of(flow_path)?
+        .with_context(|| format!("{} does not exist", flow_path.display()))?;
+    let sidecar_path = sidecar_path_for_flow(flow_path);
+    let sidecar_stamp = FileStamp::of(&sidecar_path)?;
+    let doc = load_ygtc_from_path(flow_path)?;
+    let flow = FlowIr::from_doc(doc)?;
+    let sidecar = match sidecar_stamp {
+        Some(_) => Some(
+            read_flow_resolve(&sidecar_path)
+                .with_context(|| format!("read sidecar {}", sidecar_path.display()))?,
+        ),
+        None => None,
+    };
+    let nodes = flow
+        .nodes
+        .values()
+        .map(|node| IndexedNode {
+            id: node.id.clone(),
+            operation: node.operation.clone(),
+            component: sidecar
+                .as_ref()
+                .and_then(|sidecar| sidecar.nodes.get(&node.id))
+                .map(|entry| ComponentSource::from_sidecar(&entry.source, None).reference),
+        })
+        .collect();
+    Ok(IndexedFlow {
+        id: flow.id,
+        kind: flow.kind,
+        tags: flow.tags,
+        nodes,
+        stamp,
+        sidecar_stamp,
+    })
+}
+
+/// Update the entry of `flow_path` in the index of its workspace, if it has one. Returns
+/// whether an index was updated.
+pub fn update_indexed_flow(flow_path: &Path) -> Result<bool> {
+    let absolute = fs::canonicalize(flow_path).unwrap_or_else(|_| flow_path.to_path_buf());
+    let Some(root) = absolute.parent().and_then(discover_index_root) else {
+        return Ok(false);
+    };
+    let mut index = WorkspaceIndex::load(&index_path(&root))?;
+    index.update_flow(&root, &absolute)?;
+    index.save(&root)?;
+    Ok(true)
+}
+
+fn is_fresh(entry: &IndexedFlow, flow_path: &Path) -> Result<bool> {
+    Ok(FileStamp::of(flow_path)? == Some(entry.stamp)
+        && FileStamp::of(&sidecar_path_for_flow(flow_path))? == entry.sidecar_stamp)
+}
+
+/// `flow_path` relative to `root`, with `/` separators.
+fn flow_key(root: &Path, flow_path: &Path) -> String {
+    // `baseline_flow_key` keys flows relative to the directory holding the given file.
+    baseline_flow_key(&root.join(INDEX_DIR), flow_path)
+}
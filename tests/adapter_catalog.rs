@@ -0,0 +1,140 @@
+use greentic_flow::{
+    compile_flow,
+    lint::{
+        AdapterVersionRequirements, LintConfig, LintSeverity, lint_diagnostics_with_registry,
+        lint_with_registry_requirements,
+    },
+    loader::load_ygtc_from_str,
+    registry::AdapterCatalog,
+};
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn pack_manifests_populate_catalog() {
+    let pack = tempdir().unwrap();
+    let component_dir = pack.path().join("components/telegram");
+    fs::create_dir_all(&component_dir).unwrap();
+    fs::write(
+        component_dir.join("component.manifest.json"),
+        json!({
+            "id": "messaging.telegram",
+            "operations": ["sendMessage", {"name": "editMessage"}]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    fs::write(
+        pack.path().join("components/component.manifest.json"),
+        json!({"id": "ai.greentic.echo", "operations": [{"name": "run"}]}).to_string(),
+    )
+    .unwrap();
+
+    let catalog = AdapterCatalog::from_pack(pack.path()).unwrap();
+    assert!(catalog.contains("messaging", "telegram", "sendMessage"));
+    assert!(catalog.contains("messaging", "telegram", "editMessage"));
+    assert!(catalog.contains("ai", "greentic", "echo.run"));
+
+    let yaml = std::fs::read_to_string("tests/data/flow_fail.ygtc").unwrap();
+    let flow = compile_flow(load_ygtc_from_str(&yaml).unwrap()).unwrap();
+    let errors = lint_diagnostics_with_registry(&flow, &catalog, &LintConfig::default());
+    assert_eq!(errors.len(), 2, "{errors:?}");
+}
+
+#[test]
+fn merge_combines_index_and_ignores_missing_roots() {
+    let mut catalog = AdapterCatalog::from_manifest_tree("does/not/exist").unwrap();
+    assert!(catalog.adapters.is_empty());
+    assert!(!catalog.insert_operation("echo", "run"));
+
+    catalog.merge(
+        AdapterCatalog::from_json_str(r#"{"adapters": {"email.google": ["send"]}}"#).unwrap(),
+    );
+    catalog.insert_operation("email.google", "draft.create");
+    let sorted = catalog.sorted();
+    assert_eq!(
+        sorted["email.google"].iter().collect::<Vec<_>>(),
+        vec!["draft.create", "send"]
+    );
+}
+
+#[test]
+fn version_ranges_are_checked_against_catalog_versions() {
+    let yaml = r#"
+id: versions
+type: messaging
+schema_version: 2
+start: send
+meta:
+  adapters:
+    messaging.telegram: "^3"
+nodes:
+  send:
+    messaging.telegram.sendMessage:
+      text: "hi"
+    routing:
+      - to: mail
+  mail:
+    email.google.send:
+      to: "team@greentic.dev"
+    meta:
+      adapter_version: ">=1.1, <2"
+    routing: out
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let requirements = AdapterVersionRequirements::from_doc(&doc);
+    let flow = compile_flow(doc).unwrap();
+
+    let mut catalog = AdapterCatalog::default();
+    catalog.insert_operation("messaging.telegram", "sendMessage");
+    catalog.insert_operation("email.google", "send");
+    for version in ["1.4.0", "2.1.0", "4.0.0"] {
+        catalog.insert_version("messaging.telegram", version);
+    }
+    catalog.insert_version("email.google", "1.2.3");
+
+    let errors = lint_with_registry_requirements(&flow, &catalog, &requirements);
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(errors[0].rule, "adapter_version");
+    assert_eq!(errors[0].severity, LintSeverity::Error);
+    assert_eq!(
+        errors[0].path.as_deref(),
+        Some("/meta/adapters/messaging.telegram")
+    );
+    assert!(
+        errors[0]
+            .message
+            .contains("only has 1.4.0, 2.1.0, 4.0.0; nearest available version is 4.0.0"),
+        "{errors:?}"
+    );
+
+    let unversioned = lint_with_registry_requirements(
+        &flow,
+        &AdapterCatalog::from_json_str(
+            r#"{"adapters": {"messaging.telegram": ["sendMessage"], "email.google": ["send"]}}"#,
+        )
+        .unwrap(),
+        &requirements,
+    );
+    assert_eq!(unversioned.len(), 2, "{unversioned:?}");
+    assert!(
+        unversioned
+            .iter()
+            .all(|d| d.severity == LintSeverity::Warning)
+    );
+}
+
+#[test]
+fn load_from_file_reads_toml_registries() {
+    let dir = tempfile::tempdir_in(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let path = dir.path().join("adapters.toml");
+    fs::write(&path, "[adapters]\n\"email.google\" = [\"send\"]\n").unwrap();
+    let relative = path
+        .strip_prefix(std::env::current_dir().unwrap())
+        .unwrap()
+        .to_path_buf();
+
+    let catalog = AdapterCatalog::load_from_file(relative).unwrap();
+    assert!(catalog.contains("email", "google", "send"));
+}
@@ -1,5 +1,8 @@
 use greentic_flow::{
-    compile_flow, lint::lint_with_registry, loader::load_ygtc_from_str, registry::AdapterCatalog,
+    compile_flow,
+    lint::{LintConfig, lint_diagnostics_with_registry},
+    loader::load_ygtc_from_str,
+    registry::AdapterCatalog,
 };
 
 #[test]
@@ -9,13 +12,31 @@ fn adapter_nodes_fail_with_missing_operations() {
     let flow = compile_flow(doc).unwrap();
     let catalog = AdapterCatalog::load_from_file("tests/data/registry_ok.json").unwrap();
 
-    let errors = lint_with_registry(&flow, &catalog);
+    let errors = lint_diagnostics_with_registry(&flow, &catalog, &LintConfig::default());
 
     assert_eq!(errors.len(), 2, "expected exactly two lint errors");
     assert!(
         errors
             .iter()
-            .any(|e| e.contains("messaging.telegram.deleteUniverse"))
+            .any(|e| e.message.contains("messaging.telegram.deleteUniverse"))
     );
-    assert!(errors.iter().any(|e| e.contains("email.google.beamMeUp")));
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.message.contains("email.google.beamMeUp"))
+    );
+    assert!(errors.iter().all(|e| e.rule == "adapter_resolvable"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn string_shim_keeps_the_original_signature() {
+    let yaml = std::fs::read_to_string("tests/data/flow_fail.ygtc").unwrap();
+    let flow = compile_flow(load_ygtc_from_str(&yaml).unwrap()).unwrap();
+    let catalog = AdapterCatalog::load_from_file("tests/data/registry_ok.json").unwrap();
+
+    let errors: Vec<String> = greentic_flow::lint::lint_with_registry(&flow, &catalog);
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|e| e.starts_with("adapter_resolvable: ")));
 }
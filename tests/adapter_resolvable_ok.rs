@@ -1,5 +1,8 @@
 use greentic_flow::{
-    compile_flow, lint::lint_with_registry, loader::load_ygtc_from_str, registry::AdapterCatalog,
+    compile_flow,
+    lint::{LintConfig, lint_diagnostics_with_registry},
+    loader::load_ygtc_from_str,
+    registry::AdapterCatalog,
 };
 
 #[test]
@@ -9,7 +12,7 @@ fn adapter_nodes_resolve_with_registry() {
     let flow = compile_flow(doc).unwrap();
     let catalog = AdapterCatalog::load_from_file("tests/data/registry_ok.json").unwrap();
 
-    let errors = lint_with_registry(&flow, &catalog);
+    let errors = lint_diagnostics_with_registry(&flow, &catalog, &LintConfig::default());
 
     assert!(errors.is_empty(), "expected no lint errors, got {errors:?}");
 }
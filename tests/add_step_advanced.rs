@@ -39,6 +39,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: None,
+        before: None,
         node_id_hint: Some("hello-world".to_string()),
         node: json!({
             "ai.greentic.echo": { "message": "hi" },
@@ -46,6 +47,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
@@ -87,6 +89,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: None,
+        before: None,
         node_id_hint: Some("inserted".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -94,6 +97,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
@@ -116,6 +120,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("mid".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -123,6 +128,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let updated = apply_and_validate(
@@ -176,6 +182,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: Some("anchor".to_string()),
+        before: None,
         node_id_hint: Some("inserted".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -183,6 +190,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let updated = apply_and_validate(
@@ -231,6 +239,7 @@ nodes:
     let catalog = catalog_echo();
     let spec = AddStepSpec {
         after: Some("b".to_string()),
+        before: None,
         node_id_hint: Some("mid".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -238,6 +247,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let updated = apply_and_validate(
@@ -276,6 +286,7 @@ nodes:
     let catalog = catalog_echo();
     let spec = AddStepSpec {
         after: Some("b".to_string()),
+        before: None,
         node_id_hint: Some("tail".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -283,6 +294,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let updated = apply_and_validate(
@@ -322,6 +334,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("hello-world".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -329,6 +342,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
@@ -360,6 +374,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("inserted".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -367,6 +382,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let updated = apply_and_validate(
@@ -402,6 +418,7 @@ nodes:
     let catalog = catalog_echo();
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("inserted".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -409,6 +426,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog);
@@ -430,6 +448,7 @@ nodes:
 
     let spec = || AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("mid".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -437,6 +456,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let ir1 = parse_flow_to_ir(flow).expect("parse first");
@@ -481,6 +501,7 @@ nodes:
     let catalog = catalog_echo();
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("bad".to_string()),
         node: json!({
             "tool": { "component": "ai.greentic.echo", "operation": "run" },
@@ -488,8 +509,116 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog);
     assert!(plan.is_err(), "tool output must be rejected");
 }
+
+#[test]
+fn before_anchor_rewires_every_predecessor() {
+    let flow = r#"id: main
+type: messaging
+start: start
+nodes:
+  start:
+    qa.process: {}
+    routing:
+      - to: charge
+        status: ok
+      - to: retry
+        status: busy
+  retry:
+    qa.process: {}
+    routing:
+      - to: charge
+  charge:
+    qa.process: {}
+    routing:
+      - to: done
+  done:
+    qa.process: {}
+    routing:
+      - out: true
+"#;
+    let ir = parse_flow_to_ir(flow).expect("parse");
+    let catalog = catalog_echo();
+
+    let spec = AddStepSpec {
+        after: None,
+        before: Some("charge".to_string()),
+        node_id_hint: Some("guard".to_string()),
+        node: json!({
+            "ai.greentic.echo": { "message": "check" },
+            "routing": [
+                { "to": NEXT_NODE_PLACEHOLDER, "status": "ok" },
+                { "reply": true, "status": "denied" }
+            ]
+        }),
+        allow_cycles: false,
+        require_placeholder: true,
+        default_routing: None,
+    };
+
+    let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
+    assert_eq!(plan.anchor, "charge");
+    let updated = apply_and_validate(&ir, plan, &catalog, false).expect("apply");
+
+    assert_eq!(
+        updated.nodes.keys().collect::<Vec<_>>(),
+        vec!["start", "retry", "guard", "charge", "done"]
+    );
+    let start = updated.nodes.get("start").unwrap();
+    assert_eq!(start.routing[0].to.as_deref(), Some("guard"));
+    assert_eq!(start.routing[0].status.as_deref(), Some("ok"));
+    assert_eq!(start.routing[1].to.as_deref(), Some("retry"));
+    assert_eq!(
+        updated.nodes["retry"].routing[0].to.as_deref(),
+        Some("guard")
+    );
+    let guard = updated.nodes.get("guard").unwrap();
+    assert_eq!(guard.routing[0].to.as_deref(), Some("charge"));
+    assert_eq!(guard.routing[0].status.as_deref(), Some("ok"));
+    assert!(guard.routing[1].reply);
+    assert_eq!(
+        updated.nodes["charge"].routing[0].to.as_deref(),
+        Some("done")
+    );
+    assert_eq!(updated.entrypoints.get("default").unwrap(), "start");
+}
+
+#[test]
+fn before_anchor_retargets_entrypoints_and_rejects_conflicts() {
+    let flow = r#"id: main
+type: messaging
+start: start
+nodes:
+  start:
+    qa.process: {}
+    routing:
+      - out: true
+"#;
+    let ir = parse_flow_to_ir(flow).expect("parse");
+    let catalog = catalog_echo();
+    let spec = |after: Option<&str>| AddStepSpec {
+        after: after.map(str::to_string),
+        before: Some("start".to_string()),
+        node_id_hint: Some("guard".to_string()),
+        node: json!({ "ai.greentic.echo": {} }),
+        allow_cycles: false,
+        require_placeholder: false,
+        default_routing: None,
+    };
+
+    let plan = plan_add_step(&ir, spec(None), &catalog).expect("plan");
+    let updated = apply_and_validate(&ir, plan, &catalog, false).expect("apply");
+    assert_eq!(updated.entrypoints.get("default").unwrap(), "guard");
+    assert_eq!(
+        updated.nodes["guard"].routing[0].to.as_deref(),
+        Some("start")
+    );
+
+    let diags = plan_add_step(&ir, spec(Some("start")), &catalog).unwrap_err();
+    assert_eq!(diags[0].code, "ADD_STEP_ANCHOR_CONFLICT");
+}
@@ -106,3 +106,73 @@ nodes:
     let anchors = anchor_candidates(&ir);
     assert_eq!(anchors, vec!["b".to_string(), "a".to_string()]);
 }
+
+#[test]
+fn add_step_from_config_flow_fills_defaults_from_flow_params() {
+    let pack_flow = r#"id: main
+type: messaging
+start: start
+parameters:
+  base_url: https://staging.example.com
+nodes:
+  start:
+    templating.handlebars:
+      text: Hello
+    routing:
+      - out: true
+"#;
+    let config_flow = r#"id: cfg
+type: component-config
+start: in
+nodes:
+  in:
+    questions:
+      fields:
+        - id: base_url
+          default: "{{params.base_url}}"
+    routing:
+      - to: emit
+  emit:
+    template: |
+      {
+        "node_id": "fetch",
+        "node": {
+          "component.exec": {
+            "component": "ai.greentic.fetch",
+            "input": { "url": "{{state.base_url}}" }
+          },
+          "operation": "get",
+          "routing": [ { "to": "NEXT_NODE_PLACEHOLDER" } ]
+        }
+      }
+"#;
+    let manifest = r#"{
+  "id": "component.exec",
+  "config_schema": { "required": [] }
+}"#;
+    let config_file = write_temp(".ygtc", config_flow);
+    let manifest_file = write_temp(".json", manifest);
+
+    let updated = add_step_from_config_flow(
+        pack_flow,
+        config_file.path(),
+        Path::new("schemas/ygtc.flow.schema.json"),
+        &[manifest_file.path()],
+        Some("start".to_string()),
+        &Map::new(),
+        false,
+    )
+    .expect("apply add-step from config flow");
+
+    let ir = FlowIr::from_doc(updated).expect("to ir");
+    let inserted = ir.nodes.get("fetch").expect("inserted node");
+    assert_eq!(
+        inserted
+            .payload
+            .pointer("/input/url")
+            .and_then(|v| v.as_str()),
+        Some("https://staging.example.com"),
+        "payload: {}",
+        inserted.payload
+    );
+}
@@ -39,6 +39,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("mid".to_string()),
         node: json!({
             "tool": { "component": "ai.greentic.hello", "message": "hi" },
@@ -46,6 +47,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog);
@@ -74,6 +76,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("echo_step".to_string()),
         node: json!({
             "ai.greentic.echo": { "message": "hi" },
@@ -81,6 +84,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
@@ -113,6 +117,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("COMPONENT_STEP".to_string()),
         node: json!({
             "ai.greentic.echo": { "message": "hi" },
@@ -120,6 +125,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog);
@@ -142,6 +148,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: None,
+        before: None,
         node_id_hint: None,
         node: json!({
             "ai.greentic.echo": {},
@@ -149,6 +156,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog);
@@ -170,6 +178,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: None,
+        before: None,
         node_id_hint: Some("echo".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -177,6 +186,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
@@ -210,6 +220,7 @@ nodes:
 
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("echo".to_string()),
         node: json!({
             "ai.greentic.echo": {},
@@ -217,6 +228,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
@@ -241,6 +253,7 @@ nodes:
     let catalog = catalog_with("component.exec", vec![]);
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: None,
         node: json!({
             "tool": { "component": "component.exec", "operation": "run" },
@@ -248,6 +261,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
     let plan = plan_add_step(&ir, spec, &catalog);
     assert!(plan.is_err());
@@ -268,6 +282,7 @@ nodes:
     let catalog = catalog_with("component.exec", vec![]);
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: None,
         node: json!({
             "component.exec": {},
@@ -275,6 +290,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
     let plan = plan_add_step(&ir, spec, &catalog);
     assert!(plan.is_err());
@@ -295,6 +311,7 @@ nodes:
     let catalog = catalog_with("component.exec", vec![]);
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: None,
         node: json!({
             "component.exec": { "foo": "bar" },
@@ -303,6 +320,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
     let updated = apply_and_validate(&ir, plan, &catalog, false).expect("apply");
@@ -324,6 +342,7 @@ nodes:
     let catalog = catalog_with("component.exec", vec![]);
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: None,
         node: json!({
             "component.exec": { "foo": "bar", "operation": "run" },
@@ -331,6 +350,7 @@ nodes:
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
     let updated = apply_and_validate(&ir, plan, &catalog, false).expect("apply");
@@ -348,6 +368,7 @@ nodes: {}
     let catalog = catalog_with("ai.greentic.echo", vec![]);
     let spec = AddStepSpec {
         after: None,
+        before: None,
         node_id_hint: None,
         node: json!({
             "ai.greentic.echo": {},
@@ -355,6 +376,7 @@ nodes: {}
         }),
         allow_cycles: false,
         require_placeholder: false,
+        default_routing: None,
     };
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan");
     let updated = apply_and_validate(&ir, plan, &catalog, false).expect("apply");
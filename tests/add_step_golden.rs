@@ -27,6 +27,7 @@ fn add_step_golden_flow() {
 
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("mid".to_string()),
         node: json!({
             "ai.greentic.echo": { "message": "hello" },
@@ -34,6 +35,7 @@ fn add_step_golden_flow() {
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = plan_add_step(&ir, spec, &catalog).expect("plan success");
@@ -49,6 +49,9 @@ fn add_step_with_real_manifest_catalog() {
                 ..Route::default()
             }],
             telemetry: None,
+            limits: None,
+            feature: None,
+            doc: None,
         },
     );
     nodes.insert(
@@ -63,25 +66,20 @@ fn add_step_with_real_manifest_catalog() {
                 ..Route::default()
             }],
             telemetry: None,
+            limits: None,
+            feature: None,
+            doc: None,
         },
     );
 
-    let flow = FlowIr {
-        id: "real-flow".to_string(),
-        title: None,
-        description: None,
-        kind: "messaging".to_string(),
-        start: None,
-        parameters: serde_json::Value::Object(Default::default()),
-        tags: Vec::new(),
-        schema_version: Some(2),
-        entrypoints: indexmap! {"default".to_string() => "start".to_string()},
-        meta: None,
-        nodes,
-    };
+    let mut flow = FlowIr::new("real-flow", "messaging");
+    flow.schema_version = Some(2);
+    flow.entrypoints = indexmap! {"default".to_string() => "start".to_string()};
+    flow.nodes = nodes.into();
 
     let spec = AddStepSpec {
         after: Some("start".to_string()),
+        before: None,
         node_id_hint: Some("mid".to_string()),
         node: json!({
             component_id.clone(): payload.clone(),
@@ -89,6 +87,7 @@ fn add_step_with_real_manifest_catalog() {
         }),
         allow_cycles: false,
         require_placeholder: true,
+        default_routing: None,
     };
 
     let plan = match plan_add_step(&flow, spec, &catalog) {
@@ -0,0 +1,120 @@
+use greentic_flow::advisories::{PinnedNode, ReleaseCatalog, UpgradeClass, advise, reference_base};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::tempdir;
+
+fn manifest(properties: Value, required: Value) -> Value {
+    json!({
+        "id": "acme.charge",
+        "operations": [{
+            "name": "charge",
+            "input_schema": { "type": "object", "properties": properties, "required": required }
+        }]
+    })
+}
+
+fn write_catalog(dir: &std::path::Path) -> ReleaseCatalog {
+    let v1 = manifest(
+        json!({ "amount": { "type": "number" }, "memo": { "type": "string" } }),
+        json!(["amount"]),
+    );
+    let v1_1 = v1.clone();
+    let v1_2 = manifest(
+        json!({ "amount": { "type": "number" }, "memo": { "type": "string" }, "currency": { "type": "string" } }),
+        json!(["amount", "currency"]),
+    );
+    for (name, manifest) in [("v1.json", v1), ("v1.1.json", v1_1), ("v1.2.json", v1_2)] {
+        fs::write(dir.join(name), manifest.to_string()).unwrap();
+    }
+    let catalog_path = dir.join("releases.json");
+    fs::write(
+        &catalog_path,
+        json!({
+            "components": {
+                "oci://ghcr.io/acme/charge": [
+                    { "version": "1.2.0", "digest": "sha256:c", "manifest": "v1.2.json" },
+                    { "version": "1.0.0", "digest": "sha256:a", "manifest": "v1.json" },
+                    { "version": "1.1.0", "digest": "sha256:b", "manifest": "v1.1.json" },
+                    { "version": "1.3.0", "digest": "sha256:d" }
+                ]
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    ReleaseCatalog::load_from_file(&catalog_path).unwrap()
+}
+
+#[test]
+fn advises_newer_releases_with_classification() {
+    let dir = tempdir().unwrap();
+    let catalog = write_catalog(dir.path());
+    let payload = json!({ "amount": 5 });
+    let advisory = advise(
+        &catalog,
+        &PinnedNode {
+            flow: "main.ygtc",
+            node_id: "pay",
+            reference: "oci://ghcr.io/acme/charge:1.0.0",
+            digest: "sha256:a",
+            operation: "charge",
+            payload: &payload,
+        },
+    )
+    .expect("advisory");
+    assert_eq!(advisory.component, "oci://ghcr.io/acme/charge");
+    assert_eq!(advisory.current_version, "1.0.0");
+    let summary: Vec<(&str, UpgradeClass)> = advisory
+        .candidates
+        .iter()
+        .map(|candidate| (candidate.version.as_str(), candidate.classification))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("1.1.0", UpgradeClass::Safe),
+            ("1.2.0", UpgradeClass::Breaking),
+            ("1.3.0", UpgradeClass::NeedsReview),
+        ]
+    );
+    let breaking = &advisory.candidates[1];
+    assert_eq!(
+        breaking.breaks,
+        vec!["does not set newly required /currency"]
+    );
+    assert_eq!(
+        advisory.candidates[0].apply,
+        "greentic-flow bind-component --flow main.ygtc --step pay --component oci://ghcr.io/acme/charge@sha256:b --write"
+    );
+    assert_eq!(advisory.latest().unwrap().version, "1.3.0");
+}
+
+#[test]
+fn no_advisory_for_latest_or_unknown_digest() {
+    let dir = tempdir().unwrap();
+    let catalog = write_catalog(dir.path());
+    let payload = json!({});
+    let node = |digest| PinnedNode {
+        flow: "main.ygtc",
+        node_id: "pay",
+        reference: "oci://ghcr.io/acme/charge@sha256:x",
+        digest,
+        operation: "charge",
+        payload: &payload,
+    };
+    assert!(advise(&catalog, &node("sha256:d")).is_none());
+    assert!(advise(&catalog, &node("sha256:unknown")).is_none());
+}
+
+#[test]
+fn reference_base_strips_tag_and_digest() {
+    assert_eq!(
+        reference_base("oci://ghcr.io/acme/charge:1.0.0"),
+        "oci://ghcr.io/acme/charge"
+    );
+    assert_eq!(
+        reference_base("oci://localhost:5000/acme/charge@sha256:abc"),
+        "oci://localhost:5000/acme/charge"
+    );
+    assert_eq!(reference_base("store://acme/charge"), "store://acme/charge");
+}
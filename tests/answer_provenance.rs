@@ -0,0 +1,41 @@
+use greentic_flow::answer_provenance::{
+    AnswerSource, answer_provenance, record_answer_provenance, sidecar_json,
+};
+use greentic_types::flow_resolve::{FLOW_RESOLVE_SCHEMA_VERSION, FlowResolveV1};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use tempfile::tempdir;
+
+fn empty_resolve() -> FlowResolveV1 {
+    FlowResolveV1 {
+        schema_version: FLOW_RESOLVE_SCHEMA_VERSION,
+        flow: "flow.ygtc".to_string(),
+        nodes: Default::default(),
+    }
+}
+
+#[test]
+fn sidecar_rewrites_keep_recorded_provenance() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let answers = BTreeMap::from([("channel".to_string(), json!("ops"))]);
+    let sources = BTreeMap::from([("channel".to_string(), AnswerSource::Env)]);
+    record_answer_provenance(&flow_path, "widget", &answers, &sources, &[]).unwrap();
+
+    let sidecar_path = dir.path().join("flow.ygtc.resolve.json");
+    let rewritten = sidecar_json(&sidecar_path, &empty_resolve()).unwrap();
+    fs::write(&sidecar_path, rewritten).unwrap();
+    assert_eq!(
+        answer_provenance(&flow_path, "widget").unwrap()["channel"],
+        AnswerSource::Env
+    );
+}
+
+#[test]
+fn unparseable_sidecar_is_not_overwritten_silently() {
+    let dir = tempdir().unwrap();
+    let sidecar_path = dir.path().join("flow.ygtc.resolve.json");
+    fs::write(&sidecar_path, "{ not json").unwrap();
+    assert!(sidecar_json(&sidecar_path, &empty_resolve()).is_err());
+}
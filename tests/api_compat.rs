@@ -0,0 +1,176 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    api_compat::{ApiSurface, breaking_api_changes, is_breaking_bump},
+    lint::ApiCompatRule,
+    loader::load_ygtc_from_str,
+};
+use semver::Version;
+use std::fs;
+use tempfile::tempdir;
+
+const RELEASED: &str = r#"id: orders
+type: http
+schema_version: 2
+start: create
+entrypoints:
+  "http:/orders": create
+  "http:/orders/cancel": cancel
+meta:
+  api_version: 1.4.0
+  http:
+    request_schemas:
+      "http:/orders":
+        type: object
+        properties:
+          sku: { type: string }
+          qty: { type: [integer, string] }
+nodes:
+  create:
+    op: {}
+    routing: reply
+  cancel:
+    op: {}
+    routing: reply
+"#;
+
+/// `RELEASED` without the cancel entrypoint, with `qty` narrowed to integers and `sku` required.
+fn narrowed(version: &str) -> String {
+    RELEASED
+        .replace("  \"http:/orders/cancel\": cancel\n", "")
+        .replace("qty: { type: [integer, string] }", "qty: { type: integer }")
+        .replace(
+            "        properties:\n",
+            "        required: [sku]\n        properties:\n",
+        )
+        .replace("api_version: 1.4.0", &format!("api_version: {version}"))
+}
+
+#[test]
+fn breaking_changes_cover_entrypoints_and_request_schemas() {
+    let released = ApiSurface::from_doc(&load_ygtc_from_str(RELEASED).unwrap());
+    let current = ApiSurface::from_doc(&load_ygtc_from_str(&narrowed("1.5.0")).unwrap());
+    let changes: Vec<(String, String)> = breaking_api_changes(&released, &current)
+        .into_iter()
+        .map(|change| (change.path, change.message))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (
+                "/entrypoints".to_string(),
+                "entrypoint 'http:/orders/cancel' was removed".to_string()
+            ),
+            (
+                "/meta/http/request_schemas/http:~1orders/qty".to_string(),
+                "request schema of 'http:/orders': /qty no longer accepts string (now integer)"
+                    .to_string()
+            ),
+            (
+                "/meta/http/request_schemas/http:~1orders/sku".to_string(),
+                "request schema of 'http:/orders': /sku is now required".to_string()
+            ),
+        ]
+    );
+    assert!(breaking_api_changes(&released, &released).is_empty());
+}
+
+#[test]
+fn breaking_bumps_follow_semver() {
+    let v = |text: &str| Version::parse(text).unwrap();
+    assert!(is_breaking_bump(&v("1.4.0"), &v("2.0.0")));
+    assert!(!is_breaking_bump(&v("1.4.0"), &v("1.5.0")));
+    assert!(is_breaking_bump(&v("0.3.1"), &v("0.4.0")));
+    assert!(!is_breaking_bump(&v("0.3.1"), &v("0.3.2")));
+}
+
+#[test]
+fn rule_requires_a_major_bump_for_breaking_changes() {
+    let rule = ApiCompatRule::new([ApiSurface::from_doc(&load_ygtc_from_str(RELEASED).unwrap())]);
+    let check = |yaml: &str| {
+        rule.check(&load_ygtc_from_str(yaml).unwrap())
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<_>>()
+    };
+
+    assert!(check(RELEASED).is_empty());
+    assert!(check(&narrowed("2.0.0")).is_empty());
+    let messages = check(&narrowed("1.5.0"));
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        messages[0],
+        "breaking change since the released api_version 1.4.0: entrypoint 'http:/orders/cancel' \
+         was removed; bump meta.api_version past 1.4.0 (now 1.5.0)"
+    );
+    assert_eq!(
+        check(&narrowed("1.3.0")),
+        vec!["api_version 1.3.0 is older than the released api_version 1.4.0"]
+    );
+    assert_eq!(
+        check(&narrowed("two")),
+        vec![
+            "api_version 'two' is not semver: unexpected character 't' while parsing major version number"
+        ]
+    );
+    // Other flow kinds have no external consumers to break.
+    assert!(check(&narrowed("1.5.0").replace("type: http", "type: messaging")).is_empty());
+}
+
+#[test]
+fn doctor_compares_against_a_released_bundle() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    let sidecar = |flow: &str| {
+        format!(
+            r#"{{"schema_version":1,"flow":"{flow}","nodes":{{"create":{{"source":{{"kind":"local","path":"comp.wasm"}}}},"cancel":{{"source":{{"kind":"local","path":"comp.wasm"}}}}}}}}"#
+        )
+    };
+    let released = dir.path().join("released.ygtc");
+    fs::write(&released, RELEASED).unwrap();
+    fs::write(
+        dir.path().join("released.ygtc.resolve.json"),
+        sidecar("released.ygtc"),
+    )
+    .unwrap();
+    let out = cargo_bin_cmd!("greentic-flow")
+        .args(["doctor", "--json"])
+        .arg(&released)
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(report["bundle"]["hash_blake3"].is_string(), "{report}");
+    let bundle_path = dir.path().join("orders.bundle.json");
+    fs::write(&bundle_path, report["bundle"].to_string()).unwrap();
+
+    let flow = dir.path().join("orders.ygtc");
+    fs::write(&flow, narrowed("1.5.0")).unwrap();
+    fs::write(
+        dir.path().join("orders.ygtc.resolve.json"),
+        sidecar("orders.ygtc"),
+    )
+    .unwrap();
+    let out = cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--api-baseline")
+        .arg(&bundle_path)
+        .arg(&flow)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!out.status.success(), "{stderr}");
+    assert!(
+        stderr.contains("api_compat: breaking change since the released api_version 1.4.0"),
+        "{stderr}"
+    );
+
+    fs::write(&flow, narrowed("2.0.0")).unwrap();
+    let out = cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--api-baseline")
+        .arg(&bundle_path)
+        .arg(&flow)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!stderr.contains("api_compat"), "{stderr}");
+}
@@ -0,0 +1,91 @@
+use greentic_flow::{
+    builder::{FlowBuilder, NodeBuilder},
+    error::FlowError,
+    flow_ir::parse_flow_to_ir,
+};
+use serde_json::json;
+
+#[test]
+fn builds_a_flow_that_loads_back_unchanged() {
+    let yaml = FlowBuilder::new("main")
+        .kind("messaging")
+        .title("Greeter")
+        .tag("demo")
+        .entrypoint("retry", "fallback")
+        .node("start", "handle_message", json!({ "text": "hi" }))
+        .route_on_status("ok", "end")
+        .route_on_status("*", "fallback")
+        .add_node(
+            NodeBuilder::new("fallback", "emit")
+                .payload(json!({ "text": "sorry" }))
+                .reply(),
+        )
+        .node("end", "emit", json!({ "text": "bye" }))
+        .out()
+        .to_yaml()
+        .unwrap();
+
+    let flow = parse_flow_to_ir(&yaml).unwrap();
+    assert_eq!(flow.kind, "messaging");
+    assert_eq!(flow.title.as_deref(), Some("Greeter"));
+    assert_eq!(flow.entrypoints["default"], "start");
+    assert_eq!(flow.entrypoints["retry"], "fallback");
+    let ids: Vec<&str> = flow.nodes.keys().map(String::as_str).collect();
+    assert_eq!(ids, ["start", "fallback", "end"]);
+    let start = &flow.nodes["start"];
+    assert_eq!(start.operation, "handle_message");
+    assert_eq!(start.payload, json!({ "text": "hi" }));
+    assert_eq!(start.routing.len(), 2);
+    assert_eq!(start.routing[1].status.as_deref(), Some("*"));
+    assert!(flow.nodes["end"].routing[0].out);
+    assert!(yaml.contains("routing: out"), "{yaml}");
+}
+
+#[test]
+fn build_returns_the_ir() {
+    let flow = FlowBuilder::new("main")
+        .kind("messaging")
+        .start("end")
+        .node("start", "handle_message", json!({}))
+        .route_to("end")
+        .node("end", "emit", json!({}))
+        .build()
+        .unwrap();
+    assert_eq!(flow.start.as_deref(), Some("end"));
+    assert_eq!(flow.nodes["start"].routing[0].to.as_deref(), Some("end"));
+}
+
+#[test]
+fn rejects_invalid_flows() {
+    let err = FlowBuilder::new("main")
+        .node("start", "emit", json!({}))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("no kind"), "{err}");
+
+    let err = FlowBuilder::new("main")
+        .kind("messaging")
+        .node("start", "emit", json!({}))
+        .node("start", "emit", json!({}))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("more than once"), "{err}");
+
+    let err = FlowBuilder::new("main")
+        .kind("messaging")
+        .node("start", "emit", json!({}))
+        .route_to("missing")
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, FlowError::MissingNode { .. }), "{err:?}");
+
+    let err = FlowBuilder::new("main")
+        .kind("messaging")
+        .node("start", "routing", json!({}))
+        .build()
+        .unwrap_err();
+    assert!(
+        matches!(err, FlowError::ReservedOperationKey { .. }),
+        "{err:?}"
+    );
+}
@@ -50,3 +50,66 @@ fn extract_component_pins_matches_bundle_nodes() {
         );
     }
 }
+
+#[test]
+fn component_sources_are_classified_as_pinned_or_floating() {
+    use greentic_flow::{ComponentSource, ComponentSourceKind, Pinning};
+    use greentic_types::flow_resolve::{ComponentSourceRefV1, ResolveModeV1};
+
+    let oci = |r: &str, digest: Option<&str>| ComponentSourceRefV1::Oci {
+        r#ref: r.to_string(),
+        digest: digest.map(str::to_string),
+    };
+    let classify = |source: &ComponentSourceRefV1| ComponentSource::from_sidecar(source, None);
+
+    let tagged = classify(&oci("oci://ghcr.io/acme/weather:1.2", None));
+    assert_eq!(tagged.pinning, Pinning::Floating);
+    assert_eq!(tagged.reason.as_deref(), Some("tag '1.2' can move"));
+    let untagged = classify(&oci("oci://ghcr.io/acme/weather", None));
+    assert_eq!(
+        untagged.reason.as_deref(),
+        Some("no tag or digest (resolves to latest)")
+    );
+    assert!(!classify(&oci("oci://ghcr.io/acme/weather@sha256:abc", None)).is_floating());
+    assert!(!classify(&oci("oci://ghcr.io/acme/weather:1.2", Some("sha256:abc"))).is_floating());
+    let tracked = ComponentSource::from_sidecar(
+        &oci("oci://ghcr.io/acme/weather:1.2", Some("sha256:abc")),
+        Some(ResolveModeV1::Tracked),
+    );
+    assert_eq!(tracked.reason.as_deref(), Some("resolve mode is tracked"));
+
+    let local = classify(&ComponentSourceRefV1::Local {
+        path: "file://comp.wasm".to_string(),
+        digest: None,
+    });
+    assert_eq!(local.kind, ComponentSourceKind::Local);
+    assert!(local.is_floating());
+
+    let repo = classify(&ComponentSourceRefV1::Repo {
+        r#ref: "repo://acme/weather".to_string(),
+        digest: Some("sha256:abc".to_string()),
+    });
+    assert_eq!(repo.kind, ComponentSourceKind::Repo);
+    assert!(!repo.is_floating());
+    let store = classify(&ComponentSourceRefV1::Store {
+        r#ref: "store://acme/weather".to_string(),
+        digest: None,
+        license_hint: None,
+        meter: None,
+    });
+    assert_eq!(store.kind, ComponentSourceKind::Store);
+    assert!(store.is_floating());
+
+    let git_branch = classify(&ComponentSourceRefV1::Repo {
+        r#ref: "git+https://github.com/acme/weather.git#main".to_string(),
+        digest: None,
+    });
+    assert_eq!(git_branch.kind, ComponentSourceKind::Git);
+    assert!(git_branch.is_floating());
+    let git_commit = classify(&ComponentSourceRefV1::Repo {
+        r#ref: "git+https://github.com/acme/weather.git#0123456789abcdef0123456789abcdef01234567"
+            .to_string(),
+        digest: None,
+    });
+    assert!(!git_commit.is_floating());
+}
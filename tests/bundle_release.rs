@@ -0,0 +1,209 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    bundle_release::{
+        BundleRelease, ReleaseChannel, bundle_path_for_flow, load_bundle, verify_bundle_integrity,
+        verify_deployed_bundle,
+    },
+    bundle_signature::{sign_bundle, signing_key_from_hex, verifying_key_from_hex},
+    flow_bundle::load_and_validate_bundle,
+};
+use predicates::str::contains;
+use serde_json::Value;
+use std::fs;
+use tempfile::tempdir;
+
+const FLOW: &str = r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  start:
+    send:
+      text: hi
+    routing: out
+"#;
+
+const SEED: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const PUBLIC: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+#[test]
+fn deployed_bundles_need_the_expected_channel_and_an_intact_signed_release() {
+    let key = signing_key_from_hex(SEED).unwrap();
+    let trusted = verifying_key_from_hex(PUBLIC).unwrap();
+    let beta = load_and_validate_bundle(FLOW, None)
+        .unwrap()
+        .with_release(BundleRelease::new(ReleaseChannel::Beta));
+    assert_eq!(beta.channel(), Some(ReleaseChannel::Beta));
+    let signature = sign_bundle(&beta, &key);
+
+    verify_deployed_bundle(&beta, &signature, &trusted, ReleaseChannel::Beta).unwrap();
+    let err = verify_deployed_bundle(&beta, &signature, &trusted, ReleaseChannel::Stable)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        "bundle 'main' was packed for the beta channel, expected stable"
+    );
+
+    // Relabelling the release breaks the signature, which covers it.
+    let mut relabelled = beta.clone();
+    relabelled.release.as_mut().unwrap().channel = ReleaseChannel::Stable;
+    let err = verify_deployed_bundle(&relabelled, &signature, &trusted, ReleaseChannel::Stable)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("signature does not match"), "{err}");
+
+    // Editing the yaml after packing is caught even though the recorded hash is unchanged.
+    let mut edited = beta.clone();
+    edited.yaml = FLOW.replace("text: hi", "text: bye");
+    let err = verify_bundle_integrity(&edited).unwrap_err().to_string();
+    assert!(err.contains("yaml does not match its hash"), "{err}");
+
+    // Bundles that were never packed have no channel.
+    let unpacked = load_and_validate_bundle(FLOW, None).unwrap();
+    let signature = sign_bundle(&unpacked, &key);
+    let err = verify_deployed_bundle(&unpacked, &signature, &trusted, ReleaseChannel::Stable)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("has no release channel"), "{err}");
+}
+
+#[test]
+fn bundle_pack_and_doctor_expect_channel_cli() {
+    let dir = tempdir().unwrap();
+    let flow = dir.path().join("main.ygtc");
+    let key = dir.path().join("signing.key");
+    let public = dir.path().join("signing.pub");
+    fs::write(&flow, FLOW).unwrap();
+    fs::write(&key, format!("{SEED}\n")).unwrap();
+    fs::write(&public, PUBLIC).unwrap();
+
+    let pack = |channel: &str| {
+        cargo_bin_cmd!("greentic-flow")
+            .env("SOURCE_DATE_EPOCH", "1760745600")
+            .args(["--format", "json", "bundle", "pack", "--channel", channel])
+            .arg("--flow")
+            .arg(&flow)
+            .arg("--key")
+            .arg(&key)
+            .output()
+            .unwrap()
+    };
+    let output = pack("beta");
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["release"]["channel"], "beta");
+    assert_eq!(payload["release"]["built_at"], 1760745600);
+    let bundle_path = bundle_path_for_flow(&flow);
+    assert_eq!(bundle_path, dir.path().join("main.bundle.json"));
+    assert_eq!(
+        load_bundle(&bundle_path).unwrap().channel(),
+        Some(ReleaseChannel::Beta)
+    );
+    assert!(dir.path().join("main.bundle.json.sig").exists());
+
+    let doctor = || {
+        cargo_bin_cmd!("greentic-flow")
+            .args(["doctor", "--expect-channel", "stable", "--public-key"])
+            .arg(&public)
+            .arg(dir.path())
+            .assert()
+    };
+    doctor()
+        .failure()
+        .stderr(contains("was packed for the beta channel, expected stable"));
+
+    assert!(pack("stable").status.success());
+    doctor()
+        .success()
+        .stdout(contains("main.bundle.json (main, stable)"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["doctor", "--expect-channel", "stable"])
+        .arg(&bundle_path)
+        .assert()
+        .failure()
+        .stderr(contains("--public-key"));
+}
+
+fn requiring_flow(id: &str, requires: &[&str]) -> String {
+    format!(
+        "id: {id}\ntype: messaging\nschema_version: 2\nmeta:\n  greentic:\n    requires_flows: [{}]\nnodes:\n  start:\n    send:\n      text: hi\n    routing: out\n",
+        requires.join(", ")
+    )
+}
+
+#[test]
+fn bundle_pack_orders_several_flows_and_fails_on_cycles() {
+    let dir = tempdir().unwrap();
+    let main = dir.path().join("main.ygtc");
+    let billing = dir.path().join("billing.ygtc");
+    let notify = dir.path().join("notify.ygtc");
+    fs::write(&main, requiring_flow("main", &["billing"])).unwrap();
+    fs::write(&billing, requiring_flow("billing", &["notify"])).unwrap();
+    fs::write(&notify, requiring_flow("notify", &[])).unwrap();
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "bundle", "pack", "--channel", "stable"])
+        .arg("--flow")
+        .arg(&main)
+        .arg("--flow")
+        .arg(&billing)
+        .arg("--flow")
+        .arg(&notify)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let packed: Vec<&str> = payload["bundles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["bundle_path"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        packed,
+        [&notify, &billing, &main].map(|flow| bundle_path_for_flow(flow).display().to_string())
+    );
+    let manifest_path = dir.path().join("flows.manifest.json");
+    assert_eq!(
+        payload["manifest_path"],
+        manifest_path.display().to_string()
+    );
+    let manifest: Value =
+        serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+    assert_eq!(manifest["version"], 1);
+    let entries = manifest["flows"].as_array().unwrap();
+    let ids: Vec<&str> = entries.iter().map(|e| e["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, ["notify", "billing", "main"]);
+    assert_eq!(entries[2]["bundle"], "main.bundle.json");
+    assert_eq!(entries[2]["requires"], serde_json::json!(["billing"]));
+    assert_eq!(
+        entries[2]["hash_blake3"],
+        load_bundle(&bundle_path_for_flow(&main))
+            .unwrap()
+            .hash_blake3
+    );
+
+    // A cycle fails with the cycle spelled out, before any bundle is written.
+    let cyclic = tempdir().unwrap();
+    let a = cyclic.path().join("a.ygtc");
+    let b = cyclic.path().join("b.ygtc");
+    fs::write(&a, requiring_flow("a", &["b"])).unwrap();
+    fs::write(&b, requiring_flow("b", &["a"])).unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["bundle", "pack", "--channel", "stable"])
+        .arg("--flow")
+        .arg(&a)
+        .arg("--flow")
+        .arg(&b)
+        .assert()
+        .failure()
+        .stderr(contains("circular flow dependency: a -> b -> a"));
+    assert!(!bundle_path_for_flow(&a).exists());
+    assert!(!bundle_path_for_flow(&b).exists());
+    assert!(!cyclic.path().join("flows.manifest.json").exists());
+}
@@ -0,0 +1,111 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    bundle_signature::{
+        BundleSignature, sign_bundle, signature_path_for_flow, signing_key_from_hex,
+        verify_bundle_signature, verifying_key_from_hex,
+    },
+    flow_bundle::load_and_validate_bundle,
+};
+use serde_json::Value;
+use std::fs;
+use tempfile::tempdir;
+
+const FLOW: &str = r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  start:
+    send:
+      text: hi
+    routing: out
+"#;
+
+const SEED: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const PUBLIC: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+const OTHER_SEED: &str = "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb";
+
+#[test]
+fn signatures_verify_only_for_the_signed_flow_and_key() {
+    let key = signing_key_from_hex(SEED).unwrap();
+    let trusted = verifying_key_from_hex(PUBLIC).unwrap();
+    let bundle = load_and_validate_bundle(FLOW, None).unwrap();
+    let signature = sign_bundle(&bundle, &key);
+    assert_eq!(signature.public_key, PUBLIC);
+    assert_eq!(signature.hash_blake3, bundle.hash_blake3);
+    verify_bundle_signature(&bundle, &signature, &trusted).unwrap();
+
+    let reloaded = BundleSignature::from_json_str(&signature.to_json_string()).unwrap();
+    assert_eq!(reloaded, signature);
+
+    let edited = load_and_validate_bundle(&FLOW.replace("text: hi", "text: bye"), None).unwrap();
+    let err = verify_bundle_signature(&edited, &signature, &trusted).unwrap_err();
+    assert!(
+        err.to_string().contains("changed since it was signed"),
+        "{err}"
+    );
+
+    let other = signing_key_from_hex(OTHER_SEED).unwrap();
+    let err = verify_bundle_signature(&bundle, &signature, &other.verifying_key()).unwrap_err();
+    assert!(err.to_string().contains("not the trusted key"), "{err}");
+
+    let mut forged = signature.clone();
+    forged.signature = sign_bundle(&bundle, &other).signature;
+    let err = verify_bundle_signature(&bundle, &forged, &trusted).unwrap_err();
+    assert!(err.to_string().contains("does not match"), "{err}");
+}
+
+#[test]
+fn malformed_keys_and_signatures_are_rejected() {
+    assert!(signing_key_from_hex("abcd").is_err());
+    assert!(verifying_key_from_hex("zz").is_err());
+    let mut signature = sign_bundle(
+        &load_and_validate_bundle(FLOW, None).unwrap(),
+        &signing_key_from_hex(SEED).unwrap(),
+    );
+    signature.algorithm = "rsa".to_string();
+    let err = BundleSignature::from_json_str(&signature.to_json_string()).unwrap_err();
+    assert!(err.to_string().contains("unsupported signature algorithm"));
+}
+
+#[test]
+fn bundle_sign_and_verify_cli() {
+    let dir = tempdir().unwrap();
+    let flow = dir.path().join("main.ygtc");
+    let key = dir.path().join("signing.key");
+    let public = dir.path().join("signing.pub");
+    fs::write(&flow, FLOW).unwrap();
+    fs::write(&key, format!("{SEED}\n")).unwrap();
+    fs::write(&public, PUBLIC).unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["bundle", "sign", "--flow"])
+        .arg(&flow)
+        .arg("--key")
+        .arg(&key)
+        .assert()
+        .success();
+    let signature_path = signature_path_for_flow(&flow);
+    assert_eq!(signature_path, dir.path().join("main.ygtc.sig"));
+    assert!(signature_path.exists());
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "bundle", "verify", "--flow"])
+        .arg(&flow)
+        .arg("--public-key")
+        .arg(&public)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["ok"], true);
+
+    fs::write(&flow, FLOW.replace("text: hi", "text: bye")).unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["bundle", "verify", "--flow"])
+        .arg(&flow)
+        .arg("--public-key")
+        .arg(&public)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("changed since it was signed"));
+}
@@ -0,0 +1,57 @@
+use greentic_flow::cancel::{self, Cancelled};
+use std::{fs, time::Duration};
+use tempfile::tempdir;
+
+// Cancellation is process-wide, so the whole lifecycle runs in one test.
+#[test]
+fn cancellation_blocks_new_writes_and_removes_tracked_paths() {
+    let dir = tempdir().unwrap();
+    let staged = dir.path().join("staged");
+    fs::create_dir_all(staged.join("flows")).unwrap();
+    let kept = dir.path().join("kept.tmp");
+    fs::write(&kept, "x").unwrap();
+    cancel::track_temp_path(&staged);
+    cancel::track_temp_path(&kept);
+    cancel::untrack_temp_path(&kept);
+
+    assert!(cancel::check().is_ok());
+    let in_progress = cancel::write_guard().unwrap();
+
+    assert!(cancel::cancel(Cancelled::TimedOut(Duration::from_secs(2))));
+    assert!(!cancel::cancel(Cancelled::Interrupted), "first reason wins");
+    let reason = cancel::check().unwrap_err();
+    assert_eq!(reason.to_string(), "timed out after 2s");
+    assert_eq!(reason.exit_code(), cancel::EXIT_TIMED_OUT);
+    // A guard nested in one that already started finishes with it.
+    let nested = cancel::write_guard().unwrap();
+    drop(nested);
+    let other_thread = std::thread::spawn(|| cancel::write_guard().map(drop))
+        .join()
+        .unwrap();
+    assert_eq!(
+        other_thread.unwrap_err(),
+        Cancelled::TimedOut(Duration::from_secs(2))
+    );
+
+    drop(in_progress);
+    assert_eq!(
+        cancel::write_guard().unwrap_err(),
+        Cancelled::TimedOut(Duration::from_secs(2))
+    );
+    let target = dir.path().join("flow.ygtc.resolve.json");
+    fs::write(&target, "old").unwrap();
+    assert!(cancel::replace_file_atomically(&target, b"new").is_err());
+    assert_eq!(fs::read_to_string(&target).unwrap(), "old");
+    cancel::wait_for_writes();
+    cancel::remove_temp_paths();
+    assert!(!staged.exists());
+    assert!(kept.exists());
+
+    cancel::reset();
+    assert_eq!(cancel::cancelled(), None);
+    assert!(cancel::write_guard().is_ok());
+    cancel::replace_file_atomically(&target, b"new").unwrap();
+    assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+    assert!(!target.with_extension("tmp").exists());
+    assert_eq!(Cancelled::Interrupted.exit_code(), 130);
+}
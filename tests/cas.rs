@@ -0,0 +1,101 @@
+use greentic_flow::cas::{ArtifactStore, cas_reference, parse_cas_reference};
+use std::{
+    fs,
+    time::{Duration, SystemTime},
+};
+use tempfile::tempdir;
+
+#[test]
+fn insert_dedupes_by_digest_and_keeps_the_manifest_alongside() {
+    let dir = tempdir().unwrap();
+    let store = ArtifactStore::new(dir.path().join("cas"));
+    let pack_a = dir.path().join("a");
+    let pack_b = dir.path().join("b");
+    for pack in [&pack_a, &pack_b] {
+        fs::create_dir_all(pack).unwrap();
+        fs::write(pack.join("comp.wasm"), b"wasm-bytes").unwrap();
+    }
+    fs::write(
+        pack_a.join("component.manifest.json"),
+        r#"{"id":"acme.comp"}"#,
+    )
+    .unwrap();
+
+    let digest = store.insert_file(&pack_a.join("comp.wasm")).unwrap();
+    assert_eq!(
+        store.insert_file(&pack_b.join("comp.wasm")).unwrap(),
+        digest
+    );
+
+    let reference = cas_reference(&digest);
+    assert_eq!(parse_cas_reference(&reference), Some(digest.as_str()));
+    assert_eq!(parse_cas_reference("file://comp.wasm"), None);
+    let stored = store.locate(&reference).unwrap();
+    assert_eq!(fs::read(&stored).unwrap(), b"wasm-bytes");
+    assert!(stored.with_file_name("component.manifest.json").is_file());
+
+    let usage = store.usage().unwrap();
+    assert_eq!(usage.artifacts, 1);
+    assert_eq!(usage.bytes, 10 + r#"{"id":"acme.comp"}"#.len() as u64);
+}
+
+#[test]
+fn gc_removes_only_artifacts_unused_within_the_window() {
+    let dir = tempdir().unwrap();
+    let store = ArtifactStore::new(dir.path().join("cas"));
+    let old_src = dir.path().join("old.wasm");
+    let new_src = dir.path().join("new.wasm");
+    fs::write(&old_src, b"old").unwrap();
+    fs::write(&new_src, b"new").unwrap();
+    let old = store.insert_file(&old_src).unwrap();
+    let new = store.insert_file(&new_src).unwrap();
+    let forty_days_ago = SystemTime::now() - Duration::from_secs(40 * 24 * 60 * 60);
+    fs::File::options()
+        .write(true)
+        .open(store.artifact_path(&old).unwrap())
+        .unwrap()
+        .set_modified(forty_days_ago)
+        .unwrap();
+
+    let keep = Duration::from_secs(30 * 24 * 60 * 60);
+    let dry = store.gc(keep, true).unwrap();
+    assert_eq!(dry.removed.len(), 1);
+    assert_eq!(dry.removed[0].digest, old);
+    assert!(store.locate(&cas_reference(&new)).is_ok());
+    assert!(store.artifact_path(&old).unwrap().exists());
+
+    let report = store.gc(keep, false).unwrap();
+    assert_eq!(report.removed.len(), 1);
+    assert_eq!(report.freed_bytes, 3);
+    assert_eq!(report.kept.artifacts, 1);
+    assert!(store.locate(&cas_reference(&old)).is_err());
+    assert!(store.locate(&cas_reference(&new)).is_ok());
+}
+
+#[test]
+fn artifact_path_rejects_malformed_digests() {
+    let store = ArtifactStore::new("/tmp/cas");
+    assert!(store.artifact_path("sha256:abc").is_err());
+    assert!(store.artifact_path("md5:00").is_err());
+    assert!(store.locate("file://comp.wasm").is_err());
+}
+
+#[test]
+fn insert_bytes_matches_insert_file() {
+    let dir = tempdir().unwrap();
+    let store = ArtifactStore::new(dir.path().join("cas"));
+    let digest = store
+        .insert_bytes(b"wasm-bytes", Some(br#"{"id":"acme.comp"}"#))
+        .unwrap();
+    let stored = store.get(&digest).expect("stored artifact");
+    assert_eq!(fs::read(&stored).unwrap(), b"wasm-bytes");
+    assert!(stored.with_file_name("component.manifest.json").is_file());
+
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    assert_eq!(
+        store.insert_file(&dir.path().join("comp.wasm")).unwrap(),
+        digest
+    );
+    assert_eq!(store.usage().unwrap().artifacts, 1);
+    assert!(store.get(&format!("sha256:{}", "0".repeat(64))).is_none());
+}
@@ -1,5 +1,8 @@
-use greentic_flow::component_catalog::{ComponentCatalog, ManifestCatalog};
+use greentic_flow::component_catalog::{
+    ComponentCatalog, ManifestCatalog, ManifestVersion, parse_manifest,
+};
 use serde_json::json;
+use std::path::PathBuf;
 use tempfile::NamedTempFile;
 
 #[test]
@@ -22,3 +25,137 @@ fn catalog_resolves_component_exec_alias() {
         .expect("component present");
     assert_eq!(component.required_fields, vec!["message".to_string()]);
 }
+
+fn write_manifest(dir: &std::path::Path, name: &str, manifest: &serde_json::Value) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, manifest.to_string()).expect("write manifest");
+    path
+}
+
+#[test]
+fn older_manifest_generations_are_adapted() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let v05 = write_manifest(
+        dir.path(),
+        "v05.json",
+        &json!({
+            "id": "ai.greentic.legacy-qa",
+            "world": "greentic:component/component@0.5.3",
+            "qa": {
+                "title": "Setup",
+                "questions": [
+                    { "id": "token", "label": "Token", "kind": "text", "required": true },
+                    { "id": "region", "label": "Region", "kind": "text", "required": false }
+                ]
+            }
+        }),
+    );
+    let v04 = write_manifest(
+        dir.path(),
+        "v04.json",
+        &json!({
+            "name": "ai.greentic.oldest",
+            "operations": ["run"],
+            "config": { "type": "object", "required": ["url"] }
+        }),
+    );
+    let current = write_manifest(
+        dir.path(),
+        "v06.json",
+        &json!({
+            "id": "ai.greentic.current",
+            "world": "greentic:component/component@0.6.0",
+            "config_schema": "{\"required\": [\"message\"]}"
+        }),
+    );
+
+    let catalog = ManifestCatalog::load_from_paths(&[v05, v04, current]);
+    assert_eq!(
+        catalog
+            .resolve("ai.greentic.legacy-qa")
+            .unwrap()
+            .required_fields,
+        vec!["token"]
+    );
+    assert_eq!(
+        catalog
+            .resolve("ai.greentic.oldest")
+            .unwrap()
+            .required_fields,
+        vec!["url"]
+    );
+    assert_eq!(
+        catalog
+            .resolve("ai.greentic.current")
+            .unwrap()
+            .required_fields,
+        vec!["message"]
+    );
+    // Only the QA-derived fields are a guess; the 0.4 schema and string schema are complete.
+    let warned: Vec<&str> = catalog
+        .warnings()
+        .iter()
+        .map(|w| w.component_id.as_deref().unwrap())
+        .collect();
+    assert_eq!(warned, vec!["ai.greentic.legacy-qa"]);
+    assert!(
+        catalog.warnings()[0]
+            .message
+            .contains("inferred from the 0.5 QA questions")
+    );
+}
+
+#[test]
+fn unusable_and_newer_manifests_are_reported() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let newer = write_manifest(
+        dir.path(),
+        "newer.json",
+        &json!({
+            "id": "ai.greentic.future",
+            "manifest_version": "0.7",
+            "config_schema": { "required": "message" }
+        }),
+    );
+    let broken = dir.path().join("broken.json");
+    std::fs::write(&broken, "{ not json").unwrap();
+    let nameless = write_manifest(dir.path(), "nameless.json", &json!({ "config_schema": {} }));
+
+    let catalog = ManifestCatalog::load_from_paths(&[newer.clone(), broken.clone(), nameless]);
+    let future = catalog
+        .resolve("ai.greentic.future")
+        .expect("newer manifest still loads");
+    assert!(future.required_fields.is_empty());
+
+    let messages: Vec<String> = catalog.warnings().iter().map(ToString::to_string).collect();
+    assert_eq!(messages.len(), 4, "{messages:?}");
+    assert!(messages[0].contains("newer than 0.6"), "{messages:?}");
+    assert!(messages[1].contains("`config_schema.required` is not an array"));
+    assert!(messages[2].starts_with(&format!("{}: skipped", broken.display())));
+    assert!(
+        messages[3].contains("skipped: missing `id`"),
+        "{messages:?}"
+    );
+}
+
+#[test]
+fn manifest_version_detection() {
+    let detect = |value| ManifestVersion::detect(&value);
+    assert_eq!(detect(json!({"id": "a"})), Ok(ManifestVersion::V0_6));
+    assert_eq!(detect(json!({"name": "a"})), Ok(ManifestVersion::V0_4));
+    assert_eq!(
+        detect(json!({"id": "a", "qa": {}})),
+        Ok(ManifestVersion::V0_5)
+    );
+    assert_eq!(
+        detect(json!({"id": "a", "world": "greentic:component@0.4.0"})),
+        Ok(ManifestVersion::V0_4)
+    );
+    assert_eq!(
+        detect(json!({"id": "a", "world": "greentic:component@1.0.0"})),
+        Err("1.0.0".to_string())
+    );
+    let parsed = parse_manifest(json!({"id": "a", "world": "x@0.5.0"})).unwrap();
+    assert_eq!(parsed.version, ManifestVersion::V0_5);
+    assert_eq!(parsed.warnings.len(), 1);
+}
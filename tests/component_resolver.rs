@@ -0,0 +1,157 @@
+use anyhow::Result;
+use greentic_flow::{
+    component_resolver::{
+        ComponentResolver, DistResolver, FixtureResolver, ResolvedComponent, ResolverRegistry,
+        fixture_key, register_resolver,
+    },
+    resolve_summary::{build_flow_resolve_summary, sha256_digest},
+};
+use greentic_types::flow_resolve::{ComponentSourceRefV1, FlowResolveV1, NodeResolveV1};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tempfile::tempdir;
+
+/// Serves `bucket://` references from a local directory and records what it was asked for.
+struct BucketResolver {
+    dir: PathBuf,
+    requests: Mutex<Vec<(String, Option<String>)>>,
+}
+
+impl ComponentResolver for BucketResolver {
+    fn schemes(&self) -> &[&str] {
+        &["bucket"]
+    }
+
+    fn resolve(&self, reference: &str, pinned: Option<&str>) -> Result<ResolvedComponent> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((reference.to_string(), pinned.map(str::to_string)));
+        let wasm_path = self.dir.join("component.wasm");
+        Ok(ResolvedComponent {
+            digest: sha256_digest(&fs::read(&wasm_path)?),
+            manifest_path: Some(self.dir.join("component.manifest.json")),
+            wasm_path,
+            provenance: None,
+        })
+    }
+}
+
+#[test]
+fn registered_scheme_drives_the_resolve_summary() {
+    let dir = tempdir().unwrap();
+    let bucket = dir.path().join("bucket");
+    fs::create_dir_all(&bucket).unwrap();
+    fs::write(bucket.join("component.wasm"), b"bucket-wasm").unwrap();
+    fs::write(
+        bucket.join("component.manifest.json"),
+        r#"{"id":"acme.bucket","world":"greentic:component/component@0.6.0","version":"1.2.3"}"#,
+    )
+    .unwrap();
+    let resolver = Arc::new(BucketResolver {
+        dir: bucket,
+        requests: Mutex::new(Vec::new()),
+    });
+    register_resolver(resolver.clone());
+
+    let registry = ResolverRegistry::current();
+    assert!(registry.handles("bucket://acme/comp.wasm"));
+    assert!(registry.schemes().contains(&"bucket"));
+    assert!(
+        ResolverRegistry::builtin()
+            .resolver_for("bucket://acme")
+            .is_err()
+    );
+
+    let sidecar = FlowResolveV1 {
+        schema_version: 1,
+        flow: "main.ygtc".to_string(),
+        nodes: BTreeMap::from([(
+            "greet".to_string(),
+            NodeResolveV1 {
+                source: ComponentSourceRefV1::Repo {
+                    r#ref: "bucket://acme/comp.wasm".to_string(),
+                    digest: Some("sha256:pinned".to_string()),
+                },
+                mode: None,
+            },
+        )]),
+    };
+    let summary = build_flow_resolve_summary(&dir.path().join("main.ygtc"), &sidecar).unwrap();
+    let node = &summary.nodes["greet"];
+    assert_eq!(node.digest, sha256_digest(b"bucket-wasm"));
+    assert_eq!(node.component_id.to_string(), "acme.bucket");
+    assert_eq!(
+        *resolver.requests.lock().unwrap(),
+        vec![(
+            "bucket://acme/comp.wasm".to_string(),
+            Some("sha256:pinned".to_string())
+        )]
+    );
+}
+
+#[test]
+fn fixture_resolver_overrides_builtin_schemes() {
+    let dir = tempdir().unwrap();
+    let reference = "oci://ghcr.io/acme/comp:1";
+    fs::write(
+        dir.path().join(format!("{}.wasm", fixture_key(reference))),
+        b"fixture",
+    )
+    .unwrap();
+
+    let registry = ResolverRegistry::builtin().with(Arc::new(FixtureResolver::new(dir.path())));
+    let resolved = registry.resolve(reference, None).unwrap();
+    assert_eq!(resolved.digest, sha256_digest(b"fixture"));
+    assert_eq!(
+        registry.resolve_digest(reference).unwrap(),
+        sha256_digest(b"fixture")
+    );
+    let err = registry
+        .resolve("oci://ghcr.io/acme/other:1", None)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("fixture resolver missing"),
+        "{err}"
+    );
+    let err = registry.resolve("ftp://host/comp", None).unwrap_err();
+    assert!(err.to_string().contains("no resolver for ftp://"), "{err}");
+}
+
+#[test]
+fn file_resolver_hashes_the_wasm_and_finds_its_manifest() {
+    let dir = tempdir().unwrap();
+    let wasm = dir.path().join("component.wasm");
+    fs::write(&wasm, b"local").unwrap();
+    fs::write(dir.path().join("component.manifest.json"), "{}").unwrap();
+
+    let resolved = ResolverRegistry::builtin()
+        .resolve(&format!("file://{}", wasm.display()), None)
+        .unwrap();
+    assert_eq!(resolved.digest, sha256_digest(b"local"));
+    assert_eq!(resolved.wasm_path, wasm);
+    assert_eq!(
+        resolved.manifest_path,
+        Some(dir.path().join("component.manifest.json"))
+    );
+}
+
+#[test]
+fn dist_resolver_does_not_fall_back_from_a_missing_pin_to_the_tag() {
+    let cache = tempdir().unwrap();
+    // SAFETY: no other test in this binary reads the distributor cache settings.
+    unsafe {
+        std::env::set_var("GREENTIC_CACHE_DIR", cache.path());
+        std::env::set_var("GREENTIC_DIST_OFFLINE", "1");
+    }
+    let pin = format!("sha256:{}", "0".repeat(64));
+    let err = DistResolver
+        .resolve("oci://acme/widget:latest", Some(&pin))
+        .unwrap_err();
+    assert!(err.to_string().contains(&pin), "{err}");
+    assert!(err.to_string().contains("not cached"), "{err}");
+}
@@ -0,0 +1,223 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::component_view::ComponentView;
+use greentic_types::cbor::canonical;
+use greentic_types::i18n_text::I18nText;
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use greentic_types::schemas::component::v0_6_0::{
+    ComponentDescribe, ComponentInfo, ComponentOperation, ComponentQaSpec, ComponentRunInput,
+    ComponentRunOutput, QaMode, Question, QuestionKind, schema_hash,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+const REFERENCE: &str = "oci://acme/mailer:1";
+
+fn input_schema() -> SchemaIr {
+    SchemaIr::Object {
+        properties: BTreeMap::from([
+            (
+                "to".to_string(),
+                SchemaIr::String {
+                    min_len: Some(3),
+                    max_len: None,
+                    regex: None,
+                    format: Some("email".to_string()),
+                },
+            ),
+            (
+                "retries".to_string(),
+                SchemaIr::Int {
+                    min: Some(0),
+                    max: Some(5),
+                },
+            ),
+            (
+                "tags".to_string(),
+                SchemaIr::Array {
+                    items: Box::new(SchemaIr::String {
+                        min_len: None,
+                        max_len: None,
+                        regex: None,
+                        format: None,
+                    }),
+                    min_items: None,
+                    max_items: Some(3),
+                },
+            ),
+        ]),
+        required: vec!["to".to_string()],
+        additional: AdditionalProperties::Forbid,
+    }
+}
+
+fn operation(id: &str, input: SchemaIr, output: SchemaIr) -> ComponentOperation {
+    let config = SchemaIr::Null;
+    ComponentOperation {
+        id: id.to_string(),
+        display_name: None,
+        schema_hash: schema_hash(&input, &output, &config).unwrap(),
+        input: ComponentRunInput { schema: input },
+        output: ComponentRunOutput { schema: output },
+        defaults: BTreeMap::from([("retries".to_string(), ciborium::value::Value::from(2))]),
+        redactions: Vec::new(),
+        constraints: BTreeMap::new(),
+    }
+}
+
+fn describe() -> ComponentDescribe {
+    ComponentDescribe {
+        info: ComponentInfo {
+            id: "acme.mailer".to_string(),
+            version: "1.4.0".to_string(),
+            role: "tool".to_string(),
+            display_name: Some(I18nText::new("mailer.name", Some("Send email".to_string()))),
+        },
+        provided_capabilities: vec!["email.send".to_string()],
+        required_capabilities: vec!["net.smtp".to_string()],
+        metadata: BTreeMap::new(),
+        operations: vec![
+            operation("send", input_schema(), SchemaIr::Bool),
+            operation("ping", SchemaIr::Null, SchemaIr::Null),
+        ],
+        config_schema: SchemaIr::Object {
+            properties: BTreeMap::new(),
+            required: Vec::new(),
+            additional: AdditionalProperties::Allow,
+        },
+    }
+}
+
+fn qa_spec() -> ComponentQaSpec {
+    ComponentQaSpec {
+        mode: QaMode::Setup,
+        title: I18nText::new("title", Some("Mailer setup".to_string())),
+        description: None,
+        questions: vec![Question {
+            id: "smtp_host".to_string(),
+            label: I18nText::new("q.host", Some("SMTP host".to_string())),
+            help: None,
+            error: None,
+            kind: QuestionKind::Text,
+            required: true,
+            default: Some(ciborium::value::Value::Text("localhost".to_string())),
+        }],
+        defaults: BTreeMap::new(),
+    }
+}
+
+fn write_fixture(dir: &Path) {
+    let key = REFERENCE
+        .trim_start_matches("oci://")
+        .replace(['/', ':', '@'], "_");
+    fs::write(
+        dir.join(format!("{key}.describe.cbor")),
+        canonical::to_canonical_cbor_allow_floats(&describe()).unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        dir.join(format!("{key}.qa-spec.cbor")),
+        canonical::to_canonical_cbor(&qa_spec()).unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        dir.join(format!("{key}.apply-answers.cbor")),
+        canonical::to_canonical_cbor(&BTreeMap::<String, String>::new()).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn view_selects_operations_and_adds_setup() {
+    let view = ComponentView::from_describe(&describe())
+        .unwrap()
+        .with_setup_spec(&qa_spec())
+        .unwrap();
+    assert_eq!(view.operations.len(), 2);
+    let setup = view.setup.as_ref().unwrap();
+    assert_eq!(setup.questions[0].default, Some(Value::from("localhost")));
+
+    let only_send = view.clone().select_operation("send").unwrap();
+    assert_eq!(only_send.operations.len(), 1);
+    let err = view.select_operation("run").unwrap_err().to_string();
+    assert!(err.contains("available: send, ping"), "{err}");
+}
+
+#[test]
+fn component_show_prints_the_resolved_component() {
+    let dir = tempdir().unwrap();
+    write_fixture(dir.path());
+    let resolver = format!("fixture://{}", dir.path().display());
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["component", "show", "--component", REFERENCE, "--resolver"])
+        .arg(&resolver)
+        .args(["--operation", "send"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.starts_with("acme.mailer 1.4.0 (tool)\n  Send email\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("  provides: email.send\n  requires: net.smtp\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("  send\n    input: object (no other keys)\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains(
+            "      FIELD    TYPE     REQUIRED  CONSTRAINTS\n      \
+             retries  integer  no        >= 0, <= 5\n"
+        ),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("      to       string   yes       format email, min length 3\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("    output: boolean\n    defaults:\n      retries: 2\n"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("  ping"), "{stdout}");
+    assert!(
+        stdout.contains("  smtp_host (text, required, default \"localhost\") - SMTP host"),
+        "{stdout}"
+    );
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args([
+            "--format",
+            "json",
+            "component",
+            "show",
+            "--component",
+            REFERENCE,
+        ])
+        .arg("--resolver")
+        .arg(&resolver)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["action"], "component-show");
+    assert_eq!(payload["component"]["operations"][1]["name"], "ping");
+    let input = &payload["component"]["operations"][0]["input"]["inline"];
+    assert_eq!(input["required"][0], "to");
+    assert_eq!(input["additionalProperties"], false);
+    assert_eq!(input["properties"]["to"]["format"], "email");
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["component", "show", "--component", REFERENCE, "--resolver"])
+        .arg(&resolver)
+        .args(["--operation", "run"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("has no operation 'run'"));
+}
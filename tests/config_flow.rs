@@ -1,4 +1,8 @@
-use greentic_flow::{compile_flow, config_flow::run_config_flow, loader::load_ygtc_from_str};
+use greentic_flow::{
+    compile_flow,
+    config_flow::{resolve_param_defaults, run_config_flow},
+    loader::load_ygtc_from_str,
+};
 use greentic_types::NodeId;
 use serde_json::{Map, Value, json};
 use std::{fs, path::Path};
@@ -181,3 +185,41 @@ fn config_flow_template_branching_renders_json() {
         .unwrap();
     assert_eq!(interaction.get("enabled"), Some(&json!(true)));
 }
+
+#[test]
+fn question_defaults_resolve_flow_params() {
+    let mut config_flow = json!({
+        "nodes": {
+            "ask": {
+                "questions": {
+                    "fields": [
+                        {"id": "base_url", "default": "{{params.base_url}}"},
+                        {"id": "timeout", "default": "{{ params.http.timeout_ms }}"},
+                        {"id": "health", "default": "{{params.base_url}}/health"},
+                        {"id": "plain", "default": "unchanged"}
+                    ]
+                }
+            }
+        }
+    });
+    let params = json!({"base_url": "https://api.example.com", "http": {"timeout_ms": 1500}});
+    resolve_param_defaults(&mut config_flow, &params).unwrap();
+    let fields = &config_flow["nodes"]["ask"]["questions"]["fields"];
+    assert_eq!(fields[0]["default"], json!("https://api.example.com"));
+    assert_eq!(fields[1]["default"], json!(1500));
+    assert_eq!(
+        fields[2]["default"],
+        json!("https://api.example.com/health")
+    );
+    assert_eq!(fields[3]["default"], json!("unchanged"));
+
+    let mut unresolved = json!({"nodes": {"ask": {"questions": {"fields": [
+        {"id": "region", "default": "{{params.region}}"}
+    ]}}}});
+    let err = resolve_param_defaults(&mut unresolved, &json!({"base_url": "x"})).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("nodes.ask.questions.fields.region.default"),
+        "unexpected error: {err}"
+    );
+}
@@ -0,0 +1,99 @@
+use greentic_flow::{
+    convert_type::{Resolution, apply_kind_conversion, plan_kind_conversion},
+    loader::load_ygtc_from_str,
+};
+use serde_json::json;
+
+const FLOW: &str = r#"
+id: main
+type: messaging
+schema_version: 2
+start: ask
+entrypoints:
+  default: ask
+  "http:/hook": ask
+meta:
+  messaging:
+    channel: telegram
+nodes:
+  ask:
+    questions:
+      fields: []
+    routing:
+      - to: done
+      - reply: true
+      - to: done
+        status: "201"
+  done:
+    finish: {}
+    routing: reply
+"#;
+
+#[test]
+fn plan_lists_incompatible_routes_entrypoints_and_nodes() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let conversion = plan_kind_conversion(&doc, "events").unwrap();
+    let locations: Vec<&str> = conversion
+        .issues
+        .iter()
+        .map(|issue| issue.location.as_str())
+        .collect();
+    assert_eq!(
+        locations,
+        vec![
+            "entrypoints.http:/hook",
+            "nodes.ask",
+            "nodes.ask.routing[1]",
+            "nodes.ask.routing[2]",
+            "nodes.done.routing",
+        ]
+    );
+    assert_eq!(conversion.issues[1].resolutions, vec![Resolution::Keep]);
+
+    let to_http = plan_kind_conversion(&doc, "http").unwrap();
+    assert_eq!(to_http.issues.len(), 1, "{:?}", to_http.issues);
+    assert_eq!(to_http.issues[0].location, "nodes.ask");
+
+    assert!(plan_kind_conversion(&doc, "nonsense").is_err());
+}
+
+#[test]
+fn apply_resolves_issues_and_moves_kind_metadata() {
+    let mut doc = load_ygtc_from_str(FLOW).unwrap();
+    let conversion = plan_kind_conversion(&doc, "events").unwrap();
+    apply_kind_conversion(
+        &mut doc,
+        &conversion,
+        &[
+            Resolution::RemoveEntrypoint,
+            Resolution::Keep,
+            Resolution::RemoveRoute,
+            Resolution::RemoveStatus,
+            Resolution::ReplaceWithOut,
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(doc.flow_type, "events");
+    assert!(!doc.entrypoints.contains_key("http:/hook"));
+    assert_eq!(
+        doc.nodes["ask"].routing,
+        json!([{"to": "done"}, {"to": "done"}])
+    );
+    assert_eq!(doc.nodes["done"].routing, json!("out"));
+    assert_eq!(
+        doc.meta.as_ref().unwrap(),
+        &json!({
+            "greentic": {
+                "converted_from": {"kind": "messaging", "meta": {"channel": "telegram"}}
+            }
+        })
+    );
+
+    let mut other = load_ygtc_from_str(FLOW).unwrap();
+    let err = apply_kind_conversion(&mut other, &conversion, &[Resolution::Keep]).unwrap_err();
+    assert!(
+        err.to_string().contains("expected 5 resolution(s)"),
+        "{err}"
+    );
+}
@@ -0,0 +1,31 @@
+use greentic_flow::resolve_summary::{compute_sha256, sha256_digest};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn streamed_file_digest_matches_in_memory_digest() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("component.wasm");
+    // Several read chunks plus a partial one.
+    let bytes: Vec<u8> = (0..200_003u32).map(|i| (i % 251) as u8).collect();
+    fs::write(&path, &bytes).unwrap();
+
+    let digest = compute_sha256(&path).unwrap();
+    assert_eq!(digest, sha256_digest(&bytes));
+    assert!(digest.starts_with("sha256:"));
+    assert_eq!(digest.len(), "sha256:".len() + 64);
+
+    fs::write(&path, b"").unwrap();
+    assert_eq!(
+        compute_sha256(&path).unwrap(),
+        "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+
+#[test]
+fn missing_file_digest_names_the_path() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("missing.wasm");
+    let err = compute_sha256(&path).unwrap_err();
+    assert!(format!("{err:#}").contains("missing.wasm"), "{err:#}");
+}
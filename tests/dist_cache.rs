@@ -0,0 +1,118 @@
+use greentic_flow::{
+    dist_cache::{DistCache, EntryStatus},
+    resolve_summary::sha256_digest,
+};
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+fn write_entry(root: &Path, bytes: &[u8]) -> String {
+    let digest = sha256_digest(bytes);
+    let dir = root.join(&digest["sha256:".len()..]);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("component.wasm"), bytes).unwrap();
+    fs::write(dir.join("last_used"), b"1").unwrap();
+    digest
+}
+
+#[test]
+fn verify_flags_digest_mismatches_and_orphaned_manifests() {
+    let dir = tempdir().unwrap();
+    let cache = DistCache::new(dir.path());
+    let good = write_entry(dir.path(), b"good");
+    let bad = write_entry(dir.path(), b"bad");
+    fs::write(
+        dir.path().join(&bad[7..]).join("component.wasm"),
+        b"tampered",
+    )
+    .unwrap();
+    let orphan = sha256_digest(b"gone");
+    let orphan_dir = dir.path().join(&orphan[7..]);
+    fs::create_dir_all(&orphan_dir).unwrap();
+    fs::write(
+        orphan_dir.join("component.manifest.json"),
+        r#"{"id":"acme.gone"}"#,
+    )
+    .unwrap();
+    fs::create_dir_all(dir.path().join("not-a-digest")).unwrap();
+
+    let verified = cache.verify().unwrap();
+    assert_eq!(verified.len(), 3);
+    let status = |digest: &str| {
+        verified
+            .iter()
+            .find(|item| item.entry.digest == digest)
+            .map(|item| item.status.clone())
+            .unwrap()
+    };
+    assert_eq!(status(&good), EntryStatus::Ok);
+    assert_eq!(
+        status(&bad),
+        EntryStatus::Corrupt {
+            actual: sha256_digest(b"tampered")
+        }
+    );
+    assert_eq!(status(&orphan), EntryStatus::Orphaned);
+    let orphan_entry = cache.find(&orphan).unwrap();
+    assert_eq!(orphan_entry.component_id.as_deref(), Some("acme.gone"));
+    assert!(orphan_entry.wasm.is_none());
+}
+
+#[test]
+fn manifest_wasm_paths_outside_the_entry_are_ignored() {
+    let dir = tempdir().unwrap();
+    let cache = DistCache::new(dir.path().join("cache"));
+    let outside = dir.path().join("outside.wasm");
+    fs::write(&outside, b"outside").unwrap();
+    let digest = sha256_digest(b"outside");
+    let entry_dir = dir.path().join("cache").join(&digest[7..]);
+    fs::create_dir_all(&entry_dir).unwrap();
+    for name in [
+        "../../outside.wasm".to_string(),
+        outside.display().to_string(),
+    ] {
+        fs::write(
+            entry_dir.join("component.manifest.json"),
+            serde_json::json!({"artifacts": {"component_wasm": name}}).to_string(),
+        )
+        .unwrap();
+        let entry = cache.find(&digest).unwrap();
+        assert!(entry.wasm.is_none(), "{name}");
+        assert_eq!(cache.check(&entry).unwrap(), EntryStatus::Orphaned);
+    }
+}
+
+#[test]
+fn oci_entries_keyed_by_manifest_digest_are_not_hashed() {
+    let dir = tempdir().unwrap();
+    let cache = DistCache::new(dir.path());
+    let digest = sha256_digest(b"image manifest");
+    let entry_dir = dir.path().join(&digest[7..]);
+    fs::create_dir_all(&entry_dir).unwrap();
+    fs::write(entry_dir.join("component.wasm"), b"wasm").unwrap();
+    fs::write(
+        entry_dir.join("metadata.json"),
+        format!(r#"{{"manifest_digest":"{digest}"}}"#),
+    )
+    .unwrap();
+
+    let entry = cache.find(&digest).unwrap();
+    assert_eq!(cache.check(&entry).unwrap(), EntryStatus::Unverified);
+}
+
+#[test]
+fn find_accepts_unique_prefixes_and_remove_deletes_the_entry() {
+    let dir = tempdir().unwrap();
+    let cache = DistCache::new(dir.path());
+    let digest = write_entry(dir.path(), b"component");
+    write_entry(dir.path(), b"other");
+
+    let entry = cache.find(&digest[7..19]).unwrap();
+    assert_eq!(entry.digest, digest);
+    assert!(cache.find("").is_err());
+    assert!(cache.find("zz").is_err());
+    assert!(cache.find(&"0".repeat(64)).is_err());
+
+    cache.remove(&entry).unwrap();
+    assert!(cache.find(&digest).is_err());
+    assert_eq!(cache.entries().unwrap().len(), 1);
+}
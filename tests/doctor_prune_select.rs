@@ -0,0 +1,142 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use serde_json::Value;
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+/// A flow with one bound node and a sidecar still carrying entries for two removed nodes.
+fn write_flow(dir: &Path, name: &str) {
+    fs::write(dir.join("comp.wasm"), b"wasm-bytes").unwrap();
+    fs::write(
+        dir.join(format!("{name}.ygtc")),
+        format!(
+            r#"id: {name}
+type: messaging
+schema_version: 2
+meta:
+  greentic:
+    components:
+      old:
+        component_id: acme.old
+        added_at: 1700000000
+nodes:
+  keep:
+    op: {{}}
+    routing: out
+"#
+        ),
+    )
+    .unwrap();
+    fs::write(
+        dir.join(format!("{name}.ygtc.resolve.json")),
+        format!(
+            r#"{{"schema_version":1,"flow":"{name}.ygtc","nodes":{{"keep":{{"source":{{"kind":"local","path":"comp.wasm"}}}},"old":{{"source":{{"kind":"local","path":"comp.wasm"}}}},"older":{{"source":{{"kind":"oci","ref":"oci://ghcr.io/acme/older:1.0.0"}}}}}}}}"#
+        ),
+    )
+    .unwrap();
+}
+
+fn sidecar_nodes(dir: &Path, name: &str) -> Vec<String> {
+    let sidecar: Value = serde_json::from_str(
+        &fs::read_to_string(dir.join(format!("{name}.ygtc.resolve.json"))).unwrap(),
+    )
+    .unwrap();
+    sidecar["nodes"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect()
+}
+
+fn doctor_json(dir: &Path, selectors: &str) -> (bool, Value) {
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["doctor", "--output", "json", "--prune-select", selectors])
+        .arg(dir)
+        .output()
+        .unwrap();
+    let payload = serde_json::from_slice(&output.stdout).unwrap();
+    (output.status.success(), payload)
+}
+
+fn flow<'a>(payload: &'a Value, name: &str) -> &'a Value {
+    payload["flows"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|flow| flow["path"].as_str().unwrap().ends_with(name))
+        .unwrap()
+}
+
+#[test]
+fn flow_name_selector_prunes_only_that_flow() {
+    let dir = tempdir().unwrap();
+    write_flow(dir.path(), "alpha");
+    write_flow(dir.path(), "beta");
+
+    let (ok, payload) = doctor_json(dir.path(), "alpha");
+    assert!(!ok, "beta still has unused entries");
+    assert_eq!(sidecar_nodes(dir.path(), "alpha"), vec!["keep"]);
+    assert_eq!(sidecar_nodes(dir.path(), "beta").len(), 3);
+
+    let alpha = &flow(&payload, "alpha.ygtc")["sidecar_prunes"];
+    assert_eq!(alpha[0]["node_id"], "old");
+    assert_eq!(alpha[0]["source"], "comp.wasm");
+    assert_eq!(alpha[0]["added_at"], 1_700_000_000u64);
+    assert_eq!(alpha[0]["pruned"], true);
+    assert_eq!(alpha[0]["reason"], "selected");
+    assert_eq!(alpha[1]["source"], "oci://ghcr.io/acme/older:1.0.0");
+    assert!(alpha[1].get("added_at").is_none());
+
+    let beta = flow(&payload, "beta.ygtc");
+    assert_eq!(beta["ok"], false);
+    let reasons: Vec<&str> = beta["sidecar_prunes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|decision| decision["reason"].as_str().unwrap())
+        .collect();
+    assert_eq!(reasons, vec!["not_selected", "not_selected"]);
+}
+
+#[test]
+fn stale_and_single_entry_selectors() {
+    let dir = tempdir().unwrap();
+    write_flow(dir.path(), "alpha");
+    write_flow(dir.path(), "beta");
+
+    let (ok, _) = doctor_json(dir.path(), "beta.ygtc:older");
+    assert!(!ok);
+    assert_eq!(sidecar_nodes(dir.path(), "beta"), vec!["keep", "old"]);
+
+    let (ok, payload) = doctor_json(dir.path(), "stale");
+    assert!(ok, "{payload}");
+    assert_eq!(sidecar_nodes(dir.path(), "alpha"), vec!["keep"]);
+    assert_eq!(sidecar_nodes(dir.path(), "beta"), vec!["keep"]);
+}
+
+#[test]
+fn prompt_asks_about_each_entry() {
+    let dir = tempdir().unwrap();
+    write_flow(dir.path(), "alpha");
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["doctor", "--jobs", "1"])
+        .arg(dir.path().join("alpha.ygtc"))
+        .write_stdin("n\ny\n")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("node 'old' is no longer in the flow"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("added:  2023-11-14"), "{stderr}");
+    assert!(
+        stderr.contains("source: oci://ghcr.io/acme/older:1.0.0"),
+        "{stderr}"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Kept sidecar entry old"), "{stdout}");
+    assert!(stdout.contains("Pruned sidecar entry older"), "{stdout}");
+    assert_eq!(sidecar_nodes(dir.path(), "alpha"), vec!["keep", "old"]);
+}
@@ -0,0 +1,298 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    component_catalog::MemoryCatalog,
+    edit_plan::{FlowEditPlan, PlanBinding, apply_edit_plan},
+    flow_ir::parse_flow_to_ir,
+    loader::load_ygtc_from_path,
+};
+use predicates::str::contains;
+use serde_json::{Value as JsonValue, json};
+use std::fs;
+use tempfile::tempdir;
+
+const FLOW: &str = r#"id: main
+type: messaging
+start: start
+nodes:
+  start:
+    handle_message:
+      text: "hi"
+    routing:
+      - to: end
+  end:
+    emit:
+      text: "bye"
+    routing: out
+"#;
+
+#[test]
+fn plan_applies_operations_in_order() {
+    let flow = parse_flow_to_ir(FLOW).expect("flow");
+    let plan = FlowEditPlan::from_yaml_str(
+        r#"operations:
+  - op: add-step
+    after: start
+    node_id: middle
+    operation: transform
+    payload: { mode: "upper", keep: true }
+  - op: patch
+    node: middle
+    payload: { mode: "lower", keep: null }
+  - op: wire
+    from: end
+    to: start
+  - op: delete
+    node: end
+"#,
+    )
+    .expect("plan");
+    let outcome = apply_edit_plan(&flow, &plan, &MemoryCatalog::default()).expect("apply");
+
+    let ops: Vec<_> = outcome.steps.iter().map(|s| s.op).collect();
+    assert_eq!(ops, vec!["add-step", "patch", "wire", "delete"]);
+    assert_eq!(outcome.removed, vec!["end".to_string()]);
+    let middle = outcome.flow.nodes.get("middle").expect("middle");
+    assert_eq!(middle.payload, serde_json::json!({"mode": "lower"}));
+    assert_eq!(middle.routing[0].to.as_deref(), Some("start"));
+    assert!(!outcome.flow.nodes.contains_key("end"));
+}
+
+#[test]
+fn failing_operation_rejects_whole_plan() {
+    let flow = parse_flow_to_ir(FLOW).expect("flow");
+    let plan = FlowEditPlan::from_yaml_str(
+        r#"operations:
+  - op: patch
+    node: start
+    payload: { text: "changed" }
+  - op: wire
+    from: start
+    to: missing
+"#,
+    )
+    .expect("plan");
+    let err = apply_edit_plan(&flow, &plan, &MemoryCatalog::default()).unwrap_err();
+    assert!(err.to_string().contains("operation #1 (wire)"), "{err}");
+}
+
+#[test]
+fn apply_plan_cli_writes_flow_once() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("main.ygtc");
+    fs::write(&flow_path, FLOW).unwrap();
+    let plan_path = dir.path().join("plan.yaml");
+    fs::write(
+        &plan_path,
+        r#"flow: main.ygtc
+operations:
+  - op: add-step
+    after: start
+    node_id: middle
+    operation: transform
+    payload: { mode: "upper" }
+"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("apply-plan")
+        .arg(&plan_path)
+        .arg("--write")
+        .assert()
+        .success();
+
+    let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+    assert!(doc.nodes.contains_key("middle"));
+    assert!(doc.nodes.contains_key("end"));
+}
+
+#[test]
+fn plan_collects_bindings_and_accepts_command_names() {
+    let flow = parse_flow_to_ir(FLOW).expect("flow");
+    let plan = FlowEditPlan::from_yaml_str(
+        r#"{"operations": [
+  {"op": "add-step", "after": "start", "node_id": "lookup", "operation": "get",
+   "component": "oci://ghcr.io/acme/lookup:1.0.0"},
+  {"op": "add-step", "after": "lookup", "node_id": "scratch", "operation": "tmp",
+   "local_wasm": "scratch.wasm"},
+  {"op": "update-step", "node": "lookup", "payload": {"key": "k"}},
+  {"op": "delete-step", "node": "scratch"}
+]}"#,
+    )
+    .expect("json plan");
+    let outcome = apply_edit_plan(&flow, &plan, &MemoryCatalog::default()).expect("apply");
+
+    let ops: Vec<_> = outcome.steps.iter().map(|s| s.op).collect();
+    assert_eq!(ops, vec!["add-step", "add-step", "patch", "delete"]);
+    assert_eq!(
+        outcome.bindings,
+        vec![(
+            "lookup".to_string(),
+            PlanBinding::Component("oci://ghcr.io/acme/lookup:1.0.0".to_string())
+        )]
+    );
+
+    let both = FlowEditPlan::from_yaml_str(
+        r#"operations:
+  - op: add-step
+    node_id: x
+    operation: run
+    component: oci://ghcr.io/acme/x:1
+    local_wasm: x.wasm
+"#,
+    )
+    .expect("plan");
+    let err = apply_edit_plan(&flow, &both, &MemoryCatalog::default()).unwrap_err();
+    assert!(
+        err.to_string().contains("both component and local_wasm"),
+        "{err}"
+    );
+}
+
+#[test]
+fn apply_plan_cli_binds_added_nodes_in_one_sidecar_write() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("main.ygtc");
+    fs::write(&flow_path, FLOW).unwrap();
+    fs::write(dir.path().join("lookup.wasm"), b"wasm-bytes").unwrap();
+    let plan_path = dir.path().join("plan.json");
+    fs::write(
+        &plan_path,
+        json!({
+            "operations": [
+                {"op": "add-step", "after": "start", "node_id": "lookup", "operation": "get",
+                 "local_wasm": "lookup.wasm"},
+                {"op": "add-step", "after": "lookup", "node_id": "audit", "operation": "log",
+                 "component": "oci://ghcr.io/acme/audit:1.0.0"},
+                {"op": "delete-step", "node": "end"}
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["apply-plan", "--write", "--plan"])
+        .arg(&plan_path)
+        .arg("--flow")
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains("Applied 3 operation(s)"));
+
+    let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+    assert!(doc.nodes.contains_key("lookup") && doc.nodes.contains_key("audit"));
+    assert!(!doc.nodes.contains_key("end"));
+    let sidecar: JsonValue = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("main.ygtc.resolve.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        sidecar["nodes"]["lookup"]["source"]["path"],
+        "file://lookup.wasm"
+    );
+    assert_eq!(
+        sidecar["nodes"]["audit"]["source"]["ref"],
+        "oci://ghcr.io/acme/audit:1.0.0"
+    );
+
+    // A binding that cannot be resolved rejects the plan before anything is written.
+    let before = fs::read_to_string(&flow_path).unwrap();
+    fs::write(
+        &plan_path,
+        json!({"operations": [
+            {"op": "add-step", "after": "start", "node_id": "ghost", "operation": "run",
+             "local_wasm": "missing.wasm"}
+        ]})
+        .to_string(),
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["apply-plan", "--write", "--plan"])
+        .arg(&plan_path)
+        .arg("--flow")
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(contains("bind node 'ghost'"));
+    assert_eq!(fs::read_to_string(&flow_path).unwrap(), before);
+}
+
+#[test]
+fn apply_plan_cli_cleans_up_every_removed_node_when_one_cleanup_fails() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("main.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+start: start
+nodes:
+  start:
+    handle_message: {}
+    routing:
+      - to: a
+  a:
+    emit: {}
+    routing:
+      - to: b
+  b:
+    emit: {}
+    routing:
+      - to: end
+  end:
+    emit: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    let config_dir = dir.path().join("main.ygtc.config");
+    // A directory where a's config file belongs makes removing it fail.
+    fs::create_dir_all(config_dir.join("a.cbor").join("stuck")).unwrap();
+    fs::write(config_dir.join("b.cbor"), b"\xa0").unwrap();
+    let plan_path = dir.path().join("plan.yaml");
+    fs::write(
+        &plan_path,
+        "operations:\n  - op: delete-step\n    node: a\n  - op: delete-step\n    node: b\n",
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["apply-plan", "--write", "--plan"])
+        .arg(&plan_path)
+        .arg("--flow")
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stderr(contains("warning"));
+
+    let doc = load_ygtc_from_path(&flow_path).expect("load flow");
+    assert!(!doc.nodes.contains_key("a") && !doc.nodes.contains_key("b"));
+    assert!(!config_dir.join("b.cbor").exists());
+}
+
+#[test]
+fn apply_plan_cli_restores_the_flow_when_the_sidecar_write_fails() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("main.ygtc");
+    fs::write(&flow_path, FLOW).unwrap();
+    fs::write(dir.path().join("main.ygtc.resolve.json"), "{ not json").unwrap();
+    let plan_path = dir.path().join("plan.yaml");
+    fs::write(
+        &plan_path,
+        "operations:\n  - op: add-step\n    after: start\n    node_id: audit\n    operation: log\n    component: oci://ghcr.io/acme/audit:1.0.0\n",
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["apply-plan", "--write", "--plan"])
+        .arg(&plan_path)
+        .arg("--flow")
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(contains("apply-plan rolled back"));
+
+    assert_eq!(fs::read_to_string(&flow_path).unwrap(), FLOW);
+    assert!(!flow_path.with_extension("tmp").exists());
+}
@@ -0,0 +1,172 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    env_matrix::{EnvParameters, merge_parameters, parameter_schema, render_env},
+    loader::load_ygtc_from_str,
+};
+use serde_json::{Value, json};
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+const FLOW: &str = r#"id: weather
+type: messaging
+start: fetch
+parameters:
+  base_url: https://dev.example.com
+  http:
+    timeout_ms: 500
+    retries: 1
+nodes:
+  fetch:
+    http.get:
+      url: parameters.base_url
+      timeout_ms: parameters.http.timeout_ms
+    routing: out
+"#;
+
+fn env(name: &str, values: Value) -> EnvParameters {
+    EnvParameters {
+        name: name.to_string(),
+        path: Path::new("envs").join(format!("{name}.yaml")),
+        values,
+    }
+}
+
+#[test]
+fn inferred_schema_follows_the_defaults() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let schema = parameter_schema(&doc);
+    assert_eq!(schema["properties"]["base_url"], json!({"type": "string"}));
+    assert_eq!(
+        schema["properties"]["http"]["properties"]["timeout_ms"],
+        json!({"type": "integer"})
+    );
+    assert_eq!(schema["additionalProperties"], json!(false));
+}
+
+#[test]
+fn explicit_schema_in_meta_wins() {
+    let yaml = FLOW.replace(
+        "start: fetch\n",
+        "start: fetch\nmeta:\n  parameters_schema:\n    type: object\n    required: [region]\n",
+    );
+    let doc = load_ygtc_from_str(&yaml).unwrap();
+    let render = render_env(&doc, &env("prod", json!({}))).unwrap();
+    assert!(!render.ok());
+    assert!(render.issues[0].message.contains("region"), "{render:?}");
+}
+
+#[test]
+fn merge_overrides_nested_values() {
+    let merged = merge_parameters(
+        &json!({"a": 1, "http": {"timeout_ms": 500, "retries": 1}}),
+        &json!({"http": {"timeout_ms": 2000}}),
+    );
+    assert_eq!(
+        merged,
+        json!({"a": 1, "http": {"timeout_ms": 2000, "retries": 1}})
+    );
+}
+
+#[test]
+fn render_resolves_payload_references() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let render = render_env(
+        &doc,
+        &env(
+            "prod",
+            json!({"base_url": "https://api.example.com", "http": {"timeout_ms": 2000}}),
+        ),
+    )
+    .unwrap();
+    assert!(render.ok(), "{:?}", render.issues);
+    let flow = render.flow.unwrap();
+    assert_eq!(
+        flow.nodes["fetch"].raw["http.get"],
+        json!({"url": "https://api.example.com", "timeout_ms": 2000})
+    );
+    assert_eq!(flow.parameters["http"]["retries"], json!(1));
+}
+
+#[test]
+fn invalid_values_are_reported_per_env() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let render = render_env(
+        &doc,
+        &env(
+            "staging",
+            json!({"http": {"timeout_ms": "fast"}, "region": "eu"}),
+        ),
+    )
+    .unwrap();
+    assert!(render.flow.is_none());
+    let pointers: Vec<&str> = render
+        .issues
+        .iter()
+        .map(|issue| issue.pointer.as_str())
+        .collect();
+    assert!(pointers.contains(&"/http/timeout_ms"), "{pointers:?}");
+    assert!(pointers.contains(&"/"), "{pointers:?}");
+}
+
+#[test]
+fn render_matrix_cli_reports_every_env_and_writes_valid_ones() {
+    let dir = tempdir().unwrap();
+    let flow = dir.path().join("weather.ygtc");
+    fs::write(&flow, FLOW).unwrap();
+    let envs = dir.path().join("envs");
+    fs::create_dir(&envs).unwrap();
+    fs::write(envs.join("dev.yaml"), "").unwrap();
+    fs::write(
+        envs.join("prod.yaml"),
+        "base_url: https://api.example.com\nhttp:\n  timeout_ms: 2000\n",
+    )
+    .unwrap();
+    fs::write(envs.join("staging.yaml"), "http:\n  timeout_ms: fast\n").unwrap();
+    let out_dir = dir.path().join("rendered");
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "render-matrix", "--flow"])
+        .arg(&flow)
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["ok"], false);
+    let envs: Vec<(&str, bool)> = payload["envs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| (row["env"].as_str().unwrap(), row["ok"].as_bool().unwrap()))
+        .collect();
+    assert_eq!(
+        envs,
+        vec![("dev", true), ("prod", true), ("staging", false)]
+    );
+    assert_eq!(
+        payload["envs"][2]["issues"][0]["pointer"],
+        "/http/timeout_ms"
+    );
+
+    let prod = load_ygtc_from_str(&fs::read_to_string(out_dir.join("weather.prod.ygtc")).unwrap())
+        .unwrap();
+    assert_eq!(
+        prod.nodes["fetch"].raw["http.get"]["url"],
+        "https://api.example.com"
+    );
+    assert!(out_dir.join("weather.dev.ygtc").exists());
+    assert!(!out_dir.join("weather.staging.ygtc").exists());
+
+    let only_prod = cargo_bin_cmd!("greentic-flow")
+        .args(["render-matrix", "--env", "prod", "--flow"])
+        .arg(&flow)
+        .output()
+        .unwrap();
+    assert!(
+        only_prod.status.success(),
+        "{}",
+        String::from_utf8_lossy(&only_prod.stderr)
+    );
+    assert!(String::from_utf8_lossy(&only_prod.stdout).contains("1 of 1 environment(s) valid"));
+}
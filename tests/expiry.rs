@@ -0,0 +1,174 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    expiry::{days_until, expired_nodes, node_expiry, prune_expired},
+    lint::{LintSeverity, NodeExpiryRule},
+    loader::load_ygtc_from_str,
+};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::tempdir;
+
+const FLOW: &str = r#"id: promo
+type: messaging
+start: greet
+nodes:
+  greet:
+    templating.handlebars:
+      text: "Hi"
+    meta:
+      owner: growth
+    routing:
+      - to: spring_promo
+  spring_promo:
+    templating.handlebars:
+      text: "20% off"
+    meta:
+      expires: 2026-03-31
+    routing:
+      - to: checkout
+  checkout:
+    payments.charge: {}
+    meta:
+      expires: "2027-01-01"
+    routing: out
+"#;
+
+#[test]
+fn days_until_counts_calendar_days() {
+    assert_eq!(days_until("2026-03-01", "2026-03-31"), Some(30));
+    assert_eq!(days_until("2024-02-28", "2024-03-01"), Some(2));
+    assert_eq!(days_until("2026-04-01", "2026-03-31"), Some(-1));
+    assert_eq!(days_until("2026-02-29", "2026-03-01"), None);
+    assert_eq!(days_until("2026-3-1", "2026-03-01"), None);
+}
+
+#[test]
+fn unquoted_dates_are_read_from_meta() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    assert_eq!(
+        node_expiry(&doc.nodes["spring_promo"]),
+        Some(Ok("2026-03-31".to_string()))
+    );
+    assert_eq!(node_expiry(&doc.nodes["greet"]), None);
+    assert_eq!(expired_nodes(&doc, "2026-03-31"), Vec::<String>::new());
+    assert_eq!(expired_nodes(&doc, "2026-04-01"), vec!["spring_promo"]);
+}
+
+#[test]
+fn rule_warns_before_and_fails_after_the_date() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+
+    let early = NodeExpiryRule::new("2026-01-01").check(&doc);
+    assert!(early.is_empty(), "{early:?}");
+
+    let near = NodeExpiryRule::new("2026-03-20").check(&doc);
+    assert_eq!(near.len(), 1, "{near:?}");
+    assert_eq!(near[0].rule, "node_expiry");
+    assert_eq!(near[0].severity, LintSeverity::Warning);
+    assert_eq!(
+        near[0].path.as_deref(),
+        Some("/nodes/spring_promo/meta/expires")
+    );
+    assert!(near[0].message.contains("in 11 day(s)"), "{near:?}");
+
+    let late = NodeExpiryRule::new("2026-04-02").check(&doc);
+    assert_eq!(late.len(), 1, "{late:?}");
+    assert_eq!(late[0].severity, LintSeverity::Error);
+    assert!(
+        late[0].message.contains("expired on 2026-03-31"),
+        "{late:?}"
+    );
+}
+
+#[test]
+fn invalid_dates_are_errors() {
+    let doc = load_ygtc_from_str(&FLOW.replace("2026-03-31", "2026-02-30")).unwrap();
+    let diagnostics = NodeExpiryRule::new("2026-01-01").check(&doc);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0].severity, LintSeverity::Error);
+    assert!(
+        diagnostics[0].message.contains("YYYY-MM-DD"),
+        "{diagnostics:?}"
+    );
+}
+
+#[test]
+fn prune_splices_expired_nodes_and_keeps_other_keys() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let (pruned, removed) = prune_expired(&doc, "2026-06-01").unwrap();
+    assert_eq!(removed, vec!["spring_promo"]);
+    assert_eq!(
+        pruned.nodes.keys().collect::<Vec<_>>(),
+        vec!["greet", "checkout"]
+    );
+    let greet = &pruned.nodes["greet"];
+    assert_eq!(greet.raw.get("meta"), Some(&json!({"owner": "growth"})));
+    assert_eq!(greet.routing, json!([{"to": "checkout"}]));
+    assert_eq!(
+        pruned.nodes["checkout"].raw.get("meta"),
+        Some(&json!({"expires": "2027-01-01"}))
+    );
+
+    let (unchanged, none) = prune_expired(&doc, "2026-01-01").unwrap();
+    assert!(none.is_empty());
+    assert_eq!(unchanged.nodes.len(), 3);
+}
+
+#[test]
+fn prune_start_node_moves_start_to_its_successor() {
+    let yaml = FLOW.replace("start: greet", "start: spring_promo");
+    let doc = load_ygtc_from_str(&yaml).unwrap();
+    let (pruned, _) = prune_expired(&doc, "2026-06-01").unwrap();
+    assert_eq!(pruned.start.as_deref(), Some("checkout"));
+}
+
+#[test]
+fn prune_cli_rewrites_flows_as_of_a_date() {
+    let dir = tempdir().unwrap();
+    let flow = dir.path().join("promo.ygtc");
+    fs::write(&flow, FLOW).unwrap();
+
+    let dry = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "prune", "--expired", "--dry-run"])
+        .args(["--as-of", "2026-06-01"])
+        .arg(&flow)
+        .output()
+        .unwrap();
+    assert!(
+        dry.status.success(),
+        "{}",
+        String::from_utf8_lossy(&dry.stderr)
+    );
+    let payload: Value = serde_json::from_slice(&dry.stdout).unwrap();
+    assert_eq!(payload["as_of"], "2026-06-01");
+    assert_eq!(payload["flows"][0]["removed"], json!(["spring_promo"]));
+    assert!(
+        payload["flows"][0]["diff"]
+            .as_str()
+            .unwrap()
+            .contains("-  spring_promo:")
+    );
+    assert_eq!(fs::read_to_string(&flow).unwrap(), FLOW);
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["prune", "--expired", "--as-of", "2026-06-01"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("pruned spring_promo"), "{stdout}");
+    let written = load_ygtc_from_str(&fs::read_to_string(&flow).unwrap()).unwrap();
+    assert!(!written.nodes.contains_key("spring_promo"));
+
+    let bad = cargo_bin_cmd!("greentic-flow")
+        .args(["prune", "--expired", "--as-of", "June"])
+        .arg(&flow)
+        .output()
+        .unwrap();
+    assert!(!bad.status.success());
+}
@@ -0,0 +1,144 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    features::{declared_features, render_features},
+    flow_ir::FlowIr,
+    lint::FeatureFlagsRule,
+    loader::load_ygtc_from_str,
+};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::tempdir;
+
+const FLOW: &str = r#"id: checkout
+type: messaging
+start: quote
+meta:
+  features: [beta_pricing, fraud_check]
+nodes:
+  quote:
+    pricing.quote: {}
+    feature: beta_pricing
+    routing:
+      - to: screen
+  screen:
+    fraud.screen: {}
+    feature: fraud_check
+    routing:
+      - to: charge
+  charge:
+    payments.charge: {}
+    routing: out
+"#;
+
+fn ir(yaml: &str) -> FlowIr {
+    FlowIr::from_doc(load_ygtc_from_str(yaml).unwrap()).unwrap()
+}
+
+#[test]
+fn feature_guards_parse_and_round_trip() {
+    let flow = ir(FLOW);
+    assert_eq!(flow.nodes["quote"].feature.as_deref(), Some("beta_pricing"));
+    assert_eq!(flow.nodes["charge"].feature, None);
+    let doc = flow.to_doc().unwrap();
+    assert_eq!(
+        doc.nodes["screen"].raw.get("feature"),
+        Some(&json!("fraud_check"))
+    );
+    assert_eq!(
+        declared_features(flow.meta.as_ref()).unwrap(),
+        Some(vec!["beta_pricing".to_string(), "fraud_check".to_string()])
+    );
+}
+
+#[test]
+fn invalid_guards_and_declarations_fail_to_load() {
+    let bad_flag = FLOW.replace("feature: beta_pricing", "feature: \"beta pricing\"");
+    let err = load_ygtc_from_str(&bad_flag).unwrap_err();
+    assert!(err.to_string().contains("feature"), "{err}");
+
+    let bad_meta = FLOW.replace("features: [beta_pricing, fraud_check]", "features: 3");
+    let err = load_ygtc_from_str(&bad_meta).unwrap_err();
+    assert!(err.to_string().contains("meta.features"), "{err}");
+
+    let mapping = FLOW.replace(
+        "features: [beta_pricing, fraud_check]",
+        "features:\n    beta_pricing: new quote engine\n    fraud_check: screening",
+    );
+    assert!(load_ygtc_from_str(&mapping).is_ok());
+}
+
+#[test]
+fn undefined_flags_are_lint_warnings() {
+    let yaml = FLOW.replace("feature: fraud_check", "feature: fraud_chek");
+    let diagnostics = FeatureFlagsRule::check(&load_ygtc_from_str(&yaml).unwrap());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].rule, "undefined_feature");
+    assert_eq!(
+        diagnostics[0].path.as_deref(),
+        Some("/nodes/screen/feature")
+    );
+    assert!(FeatureFlagsRule::check(&load_ygtc_from_str(FLOW).unwrap()).is_empty());
+}
+
+#[test]
+fn disabled_nodes_are_spliced_out() {
+    let flow = ir(FLOW);
+    let rendered = render_features(&flow, &["fraud_check".to_string()]).unwrap();
+    assert_eq!(rendered.removed, vec!["quote"]);
+    assert!(rendered.undeclared.is_empty());
+    assert_eq!(rendered.flow.start.as_deref(), Some("screen"));
+    assert_eq!(rendered.flow.nodes["screen"].feature, None);
+    assert_eq!(
+        rendered.flow.nodes.keys().collect::<Vec<_>>(),
+        vec!["screen", "charge"]
+    );
+
+    let none = render_features(&flow, &["dark_mode".to_string()]).unwrap();
+    assert_eq!(none.removed, vec!["quote", "screen"]);
+    assert_eq!(none.undeclared, vec!["dark_mode"]);
+    assert_eq!(none.flow.start.as_deref(), Some("charge"));
+}
+
+#[test]
+fn render_prints_the_effective_flow() {
+    let dir = tempdir().unwrap();
+    let flow = dir.path().join("checkout.ygtc");
+    fs::write(&flow, FLOW).unwrap();
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args([
+            "--format",
+            "json",
+            "render",
+            "--features",
+            "beta_pricing",
+            "--flow",
+        ])
+        .arg(&flow)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["removed"], json!(["screen"]));
+    let yaml = payload["yaml"].as_str().unwrap();
+    let rendered = ir(yaml);
+    assert_eq!(
+        rendered.nodes["quote"].routing[0].to.as_deref(),
+        Some("charge")
+    );
+    assert!(!yaml.contains("feature:"), "{yaml}");
+
+    let out = dir.path().join("effective.ygtc");
+    cargo_bin_cmd!("greentic-flow")
+        .args(["render", "--features", "beta_pricing,fraud_check", "--flow"])
+        .arg(&flow)
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success();
+    assert_eq!(ir(&fs::read_to_string(&out).unwrap()).nodes.len(), 3);
+}
@@ -0,0 +1,117 @@
+use greentic_flow::flow_ir::{FlowIr, analysis::FlowAnalysis, parse_flow_to_ir};
+
+const FLOW: &str = r#"id: checkout
+type: messaging
+start: quote
+nodes:
+  quote:
+    pricing.quote: {}
+    routing:
+      - status: declined
+        to: apologize
+      - to: charge
+  charge:
+    payments.charge: {}
+    routing:
+      - to: receipt
+  apologize:
+    template: "sorry"
+    routing:
+      - to: receipt
+  receipt:
+    template: "done"
+    routing: out
+  orphan:
+    template: "left over"
+    routing:
+      - to: receipt
+"#;
+
+fn ids(items: &[String]) -> Vec<&str> {
+    items.iter().map(String::as_str).collect()
+}
+
+fn flow() -> FlowIr {
+    parse_flow_to_ir(FLOW).unwrap()
+}
+
+#[test]
+fn successor_and_predecessor_maps_keep_flow_order() {
+    let analysis = FlowAnalysis::new(&flow());
+    assert_eq!(
+        ids(analysis.successors("quote")),
+        vec!["apologize", "charge"]
+    );
+    assert_eq!(
+        ids(analysis.predecessors("receipt")),
+        vec!["charge", "apologize", "orphan"]
+    );
+    assert!(analysis.predecessors("quote").is_empty());
+    assert!(analysis.successors("missing").is_empty());
+    assert_eq!(analysis.predecessor_map().len(), 5);
+    assert_eq!(ids(analysis.entry_nodes()), vec!["quote"]);
+    assert_eq!(ids(analysis.exit_nodes()), vec!["receipt"]);
+}
+
+#[test]
+fn reachability_finds_orphans() {
+    let analysis = FlowAnalysis::new(&flow());
+    assert_eq!(
+        analysis.reachable(),
+        vec!["quote", "charge", "apologize", "receipt"]
+    );
+    assert_eq!(analysis.unreachable(), vec!["orphan"]);
+    assert_eq!(
+        analysis.reachable_from(["apologize"]),
+        vec!["apologize", "receipt"]
+    );
+}
+
+#[test]
+fn topological_order_respects_routes_and_detects_cycles() {
+    let analysis = FlowAnalysis::new(&flow());
+    assert_eq!(
+        analysis.topological_order().unwrap(),
+        vec!["quote", "charge", "apologize", "orphan", "receipt"]
+    );
+
+    let looping = FLOW.replace("    routing: out\n", "    routing:\n      - to: quote\n");
+    assert!(
+        FlowAnalysis::new(&parse_flow_to_ir(&looping).unwrap())
+            .topological_order()
+            .is_none()
+    );
+}
+
+#[test]
+fn paths_run_from_entries_to_exits() {
+    let analysis = FlowAnalysis::new(&flow());
+    let paths = analysis.paths(10);
+    assert!(!paths.truncated);
+    assert_eq!(
+        paths.paths,
+        vec![
+            vec!["quote", "apologize", "receipt"],
+            vec!["quote", "charge", "receipt"],
+        ]
+    );
+    let limited = analysis.paths(1);
+    assert!(limited.truncated);
+    assert_eq!(limited.paths.len(), 1);
+
+    let looping = FLOW.replace(
+        "    routing: out\n",
+        "    routing:\n      - status: again\n        to: quote\n      - out: true\n",
+    );
+    let looping = FlowAnalysis::new(&parse_flow_to_ir(&looping).unwrap());
+    assert_eq!(looping.paths(10).paths.len(), 2);
+}
+
+#[test]
+fn degrees_report_fan_in_and_fan_out() {
+    let degrees = FlowAnalysis::new(&flow()).degrees();
+    let receipt = degrees.iter().find(|d| d.node_id == "receipt").unwrap();
+    assert_eq!((receipt.fan_in, receipt.fan_out), (3, 0));
+    let quote = degrees.iter().find(|d| d.node_id == "quote").unwrap();
+    assert_eq!((quote.fan_in, quote.fan_out), (0, 2));
+}
@@ -14,7 +14,11 @@ use serde_json::Value as JsonValue;
 use serde_json::json;
 use serde_yaml_bw::Value;
 use std::collections::BTreeMap;
-use std::{fs, path::Path, process::Command};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
 use tempfile::tempdir;
 
 fn read_yaml(path: &Path) -> Value {
@@ -125,6 +129,153 @@ fn add_step_into_empty_flow_succeeds() {
     );
 }
 
+#[test]
+fn add_step_stores_local_wasm_in_shared_cas_and_cas_reports_it() {
+    let dir = tempdir().unwrap();
+    let cas_dir = dir.path().join("cas");
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(
+        &flow_path,
+        "id: main\ntype: messaging\nschema_version: 2\nnodes: {}\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .env("GREENTIC_FLOW_CAS_DIR", &cas_dir)
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--node-id")
+        .arg("comp")
+        .arg("--operation")
+        .arg("handle_message")
+        .arg("--payload")
+        .arg(r#"{"input":"hi"}"#)
+        .arg("--routing-out")
+        .arg("--local-wasm")
+        .arg("comp.wasm")
+        .assert()
+        .success();
+
+    let sidecar: JsonValue = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("flow.ygtc.resolve.json")).unwrap(),
+    )
+    .unwrap();
+    let path = sidecar["nodes"]["comp"]["source"]["path"].as_str().unwrap();
+    assert!(path.starts_with("cas://sha256:"), "{path}");
+
+    cargo_bin_cmd!("greentic-flow")
+        .env("GREENTIC_FLOW_CAS_DIR", &cas_dir)
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .success();
+    cargo_bin_cmd!("greentic-flow")
+        .env_remove("GREENTIC_FLOW_CAS_DIR")
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(contains("GREENTIC_FLOW_CAS_DIR"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("cas")
+        .arg("size")
+        .arg("--dir")
+        .arg(&cas_dir)
+        .assert()
+        .success()
+        .stdout(contains("1 artifact(s), 10 bytes"));
+    cargo_bin_cmd!("greentic-flow")
+        .arg("cas")
+        .arg("gc")
+        .arg("--keep-days")
+        .arg("30")
+        .arg("--dir")
+        .arg(&cas_dir)
+        .assert()
+        .success()
+        .stdout(contains("Removed 0 artifact(s)"));
+    cargo_bin_cmd!("greentic-flow")
+        .env_remove("GREENTIC_FLOW_CAS_DIR")
+        .arg("cas")
+        .arg("size")
+        .assert()
+        .failure()
+        .stderr(contains("--dir"));
+}
+
+#[test]
+fn cache_verify_reports_corrupt_dist_entries_and_prune_removes_them() {
+    let dir = tempdir().unwrap();
+    let cache_dir = dir.path().join("dist");
+    let good = greentic_flow::resolve_summary::sha256_digest(b"good");
+    let bad = greentic_flow::resolve_summary::sha256_digest(b"bad");
+    for (digest, bytes) in [(&good, &b"good"[..]), (&bad, &b"tampered"[..])] {
+        let entry = cache_dir.join(&digest["sha256:".len()..]);
+        fs::create_dir_all(&entry).unwrap();
+        fs::write(entry.join("component.wasm"), bytes).unwrap();
+    }
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("cache")
+        .arg("ls")
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .assert()
+        .success()
+        .stdout(contains(good.as_str()))
+        .stdout(contains("2 entries"));
+    cargo_bin_cmd!("greentic-flow")
+        .arg("cache")
+        .arg("info")
+        .arg(&bad["sha256:".len().."sha256:".len() + 12])
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .assert()
+        .success()
+        .stdout(contains("status: corrupt"));
+    cargo_bin_cmd!("greentic-flow")
+        .arg("cache")
+        .arg("verify")
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .assert()
+        .failure()
+        .stderr(contains("--prune"));
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .arg("--format")
+        .arg("json")
+        .arg("cache")
+        .arg("verify")
+        .arg("--prune")
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: JsonValue = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["ok"], true);
+    assert_eq!(report["pruned"], serde_json::json!([bad]));
+    assert!(!cache_dir.join(&bad["sha256:".len()..]).exists());
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("cache")
+        .arg("rm")
+        .arg(&good)
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .assert()
+        .success()
+        .stdout(contains(format!("Removed {good}")));
+    assert!(!cache_dir.join(&good["sha256:".len()..]).exists());
+}
+
 #[test]
 fn add_step_wizard_uses_fixture_resolver() {
     let dir = tempdir().unwrap();
@@ -337,6 +488,51 @@ nodes:
         .stderr(predicates::str::contains("ADD_STEP_ROUTING_MISSING"));
 }
 
+#[test]
+fn add_step_uses_pack_default_routing_for_terminal_nodes() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    let flow = r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  start:
+    qa.process:
+      payload: true
+"#;
+    fs::write(
+        dir.path().join(".greentic-flow.toml"),
+        "default_routing = \"out\"\n\n[kinds.messaging]\ndefault_routing = \"reply\"\n",
+    )
+    .unwrap();
+    fs::write(&flow_path, flow).unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--mode")
+        .arg("default")
+        .arg("--after")
+        .arg("start")
+        .arg("--node-id")
+        .arg("next")
+        .arg("--operation")
+        .arg("handle_message")
+        .arg("--payload")
+        .arg(r#"{"msg":"hi"}"#)
+        .arg("--local-wasm")
+        .arg("comp.wasm")
+        .assert()
+        .success();
+
+    let doc = load_ygtc_from_path(&flow_path).unwrap();
+    assert_eq!(doc.nodes["next"].routing, json!("reply"));
+    assert_eq!(doc.nodes["start"].routing, json!([{"to": "next"}]));
+}
+
 #[test]
 fn add_step_creates_sidecar_local() {
     let dir = tempdir().unwrap();
@@ -1268,6 +1464,58 @@ nodes:
         .failure();
 }
 
+#[test]
+fn convert_type_lists_issues_then_applies_defaults() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+start: hello
+meta:
+  messaging:
+    channel: telegram
+nodes:
+  hello:
+    op: {}
+    routing: reply
+"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("convert-type")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--to")
+        .arg("events")
+        .assert()
+        .failure()
+        .stderr(contains("nodes.hello.routing"))
+        .stderr(contains("rerun with --interactive or --apply-defaults"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("convert-type")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--to")
+        .arg("events")
+        .arg("--apply-defaults")
+        .assert()
+        .success()
+        .stdout(contains("nodes.hello.routing: replace-with-out"));
+
+    let doc = load_ygtc_from_path(&flow_path).unwrap();
+    assert_eq!(doc.flow_type, "events");
+    assert_eq!(doc.nodes["hello"].routing, json!("out"));
+    assert_eq!(
+        doc.meta.unwrap()["greentic"]["converted_from"],
+        json!({"kind": "messaging", "meta": {"channel": "telegram"}})
+    );
+}
+
 #[test]
 fn update_fails_when_missing_file() {
     let dir = tempdir().unwrap();
@@ -1336,109 +1584,212 @@ entrypoints: {}
 }
 
 #[test]
-fn doctor_fails_on_raw_summary_literals() {
+fn doctor_jobs_isolates_failing_flows_and_names_them() {
     let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    fs::write(
-        &flow_path,
-        r#"id: raw-summary
-type: messaging
-schema_version: 2
-title: Raw title
-description: Raw description
-nodes: {}
-"#,
-    )
-    .unwrap();
+    for id in ["one", "two", "three"] {
+        fs::write(
+            dir.path().join(format!("{id}.ygtc")),
+            format!("id: {id}\ntype: messaging\nschema_version: 2\nnodes: {{}}\n"),
+        )
+        .unwrap();
+    }
+    fs::write(dir.path().join("broken.ygtc"), "id: [unterminated\n").unwrap();
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--jobs")
+        .arg("4")
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains("1 flow(s) failed validation:"))
+        .stderr(contains("broken.ygtc"))
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    for id in ["one", "two", "three"] {
+        assert!(stdout.contains(&format!("{id}.ygtc ({id})")), "{stdout}");
+    }
 
     cargo_bin_cmd!("greentic-flow")
         .arg("doctor")
-        .arg(&flow_path)
+        .arg("--jobs")
+        .arg("0")
+        .arg(dir.path())
         .assert()
         .failure()
-        .stderr(
-            contains("title must be an i18n tag").and(contains("description must be an i18n tag")),
-        );
+        .stderr(contains("--jobs"));
 }
 
 #[test]
-fn doctor_fails_when_i18n_summary_key_missing_from_pack_source() {
+fn doctor_output_json_reports_every_flow_with_typed_findings() {
     let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-    fs::create_dir_all(flow_path.parent().unwrap()).unwrap();
     fs::write(
-        &flow_path,
-        r#"id: welcome
-type: messaging
-schema_version: 2
-title: i18n:flow.welcome.title
-description: i18n:flow.welcome.description
-nodes: {}
-"#,
+        dir.path().join("ok.ygtc"),
+        "id: ok\ntype: messaging\nschema_version: 2\nnodes: {}\n",
     )
     .unwrap();
-    fs::create_dir_all(dir.path().join("i18n")).unwrap();
     fs::write(
-        dir.path().join("i18n/en-GB.json"),
-        r#"{"flow.welcome.title":"Welcome"}"#,
+        dir.path().join("unbound.ygtc"),
+        r#"id: unbound
+type: messaging
+schema_version: 2
+start: greet
+nodes:
+  greet:
+    handle_message:
+      text: hi
+    routing: out
+"#,
     )
     .unwrap();
+    fs::write(dir.path().join("broken.ygtc"), "id: [unterminated\n").unwrap();
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--output")
+        .arg("json")
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains("2 flow(s) failed validation"))
+        .get_output()
+        .stdout
+        .clone();
+    let report: JsonValue = serde_json::from_slice(&output).expect("stdout is one JSON report");
+    assert_eq!(report["ok"], false);
+    let flows = report["flows"].as_array().unwrap();
+    assert_eq!(flows.len(), 3);
+    let flow = |name: &str| {
+        flows
+            .iter()
+            .find(|flow| flow["path"].as_str().unwrap().ends_with(name))
+            .unwrap_or_else(|| panic!("{name} missing from {report}"))
+    };
+    assert_eq!(flow("ok.ygtc")["ok"], true);
+    assert!(flow("ok.ygtc").get("bundle").is_none());
+    assert_eq!(flow("broken.ygtc")["ok"], false);
+
+    let missing = &flow("unbound.ygtc")["errors"][0];
+    assert_eq!(missing["rule"], "sidecar_missing");
+    assert_eq!(missing["severity"], "error");
+    assert_eq!(missing["json_pointer"], "/nodes/greet");
+    assert!(
+        missing["sidecar_path"]
+            .as_str()
+            .unwrap()
+            .ends_with("unbound.ygtc.resolve.json")
+    );
+    assert!(
+        missing["fix"]["description"]
+            .as_str()
+            .unwrap()
+            .contains("bind-component")
+    );
+    let failed = report["failed"].as_array().unwrap();
+    assert_eq!(failed.len(), 2);
 
     cargo_bin_cmd!("greentic-flow")
         .arg("doctor")
-        .arg(&flow_path)
+        .arg("--output")
+        .arg("json")
+        .arg("--json")
+        .arg(dir.path().join("ok.ygtc"))
         .assert()
         .failure()
-        .stderr(contains(
-            "description i18n key 'flow.welcome.description' missing from pack i18n/en-GB.json",
-        ));
+        .stderr(contains("cannot be used with"));
 }
 
 #[test]
-fn doctor_accepts_i18n_summary_keys_present_in_pack_source() {
+fn doctor_output_sarif_maps_findings_to_rules_locations_and_levels() {
     let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
-    fs::create_dir_all(flow_path.parent().unwrap()).unwrap();
     fs::write(
-        &flow_path,
-        r#"id: welcome
-type: messaging
-schema_version: 2
-title: i18n:flow.welcome.title
-description: i18n:flow.welcome.description
-nodes: {}
-parameters: {}
-tags: []
-entrypoints: {}
-"#,
+        dir.path().join("ok.ygtc"),
+        "id: ok\ntype: messaging\nschema_version: 2\nnodes: {}\n",
     )
     .unwrap();
-    fs::create_dir_all(dir.path().join("i18n")).unwrap();
     fs::write(
-        dir.path().join("i18n/en-GB.json"),
-        r#"{"flow.welcome.title":"Welcome","flow.welcome.description":"Greeting flow"}"#,
+        dir.path().join("unbound.ygtc"),
+        r#"id: unbound
+type: messaging
+schema_version: 2
+start: greet
+nodes:
+  greet:
+    handle_message:
+      text: hi
+    routing: out
+"#,
     )
     .unwrap();
+    fs::write(dir.path().join("broken.ygtc"), "id: [unterminated\n").unwrap();
 
-    cargo_bin_cmd!("greentic-flow")
-        .arg("doctor")
-        .arg(&flow_path)
+    let output = cargo_bin_cmd!("greentic-flow")
+        .arg("lint")
+        .arg("--output")
+        .arg("sarif")
+        .arg(dir.path())
         .assert()
-        .success();
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let log: JsonValue = serde_json::from_slice(&output).expect("stdout is one SARIF log");
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "greentic-flow");
+    let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect();
+    assert!(rules.contains(&"sidecar_missing"), "{rules:?}");
+    assert!(rules.contains(&"flow_validation"), "{rules:?}");
+
+    let results = run["results"].as_array().unwrap();
+    let result = |rule: &str| {
+        results
+            .iter()
+            .find(|result| result["ruleId"] == rule)
+            .unwrap_or_else(|| panic!("{rule} missing from {log}"))
+    };
+    let missing = result("sidecar_missing");
+    assert_eq!(missing["level"], "error");
+    let location = &missing["locations"][0];
+    assert!(
+        location["physicalLocation"]["artifactLocation"]["uri"]
+            .as_str()
+            .unwrap()
+            .ends_with("unbound.ygtc.resolve.json")
+    );
+    assert_eq!(
+        location["logicalLocations"][0]["fullyQualifiedName"],
+        "/nodes/greet"
+    );
+    let broken = result("flow_validation");
+    assert_eq!(broken["level"], "error");
+    let region = &broken["locations"][0]["physicalLocation"]["region"];
+    assert!(region["startLine"].as_u64().unwrap() >= 1, "{broken}");
 }
 
 #[test]
-fn doctor_json_reports_raw_summary_i18n_lint_errors() {
+fn doctor_flags_placeholder_values_in_strict_mode_only() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
     fs::write(
         &flow_path,
-        r#"id: raw-summary
+        r#"id: main
 type: messaging
 schema_version: 2
-title: Raw title
-description: Raw description
-nodes: {}
+start: greet
+nodes:
+  greet:
+    handle_message:
+      text: "hi"
+      webhook: "CHANGEME"
+    routing: out
 "#,
     )
     .unwrap();
@@ -1449,908 +1800,1033 @@ nodes: {}
         .arg(&flow_path)
         .assert()
         .failure()
-        .stdout(
-            contains("title must be an i18n tag").and(contains("description must be an i18n tag")),
-        );
-}
+        .stdout(contains(
+            "placeholder_values: node 'greet' payload at /webhook",
+        ));
 
-#[test]
-fn doctor_reports_component_config_schema_errors() {
-    let dir = tempdir().unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--json")
+        .arg("--placeholder-pattern")
+        .arg("TBD")
+        .arg(&flow_path)
+        .assert()
+        .stdout(contains("placeholder_values").not());
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("--permissive")
+        .arg("doctor")
+        .arg("--json")
+        .arg(&flow_path)
+        .assert()
+        .stderr(contains("WARN placeholder_values"))
+        .stdout(contains("placeholder_values").not());
+}
+
+#[test]
+fn lint_fix_applies_machine_applicable_fixes() {
+    let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
-    let wasm_path = dir.path().join("comp.wasm");
-    let manifest_path = dir.path().join("component.manifest.json");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
-    fs::write(
-        &manifest_path,
-        r#"{"id":"ai.greentic.test","config_schema":{"type":"object","required":["message"],"properties":{"message":{"type":"string"}}}}"#,
-    )
-    .unwrap();
     fs::write(
         &flow_path,
         r#"id: main
 type: messaging
 schema_version: 2
+start: hello
+entrypoints:
+  telegram: Hello
 nodes:
   hello:
-    run:
-      message: 42
-    routing: out
+    questions: {fields: []}
+    routing:
+      - out: true
 "#,
     )
     .unwrap();
-    fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
-    )
-    .unwrap();
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("doctor")
+    cargo_bin_cmd!("greentic-flow")
+        .arg("lint")
         .arg(&flow_path)
         .assert()
         .failure()
-        .stderr(predicates::str::contains("component_config"));
+        .stderr(contains(
+            "entrypoint 'telegram' targets missing node 'Hello' (fix: retarget entrypoint 'telegram' to 'hello'; run doctor --fix)",
+        ))
+        .stderr(contains("WARN routing_shorthand"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("lint")
+        .arg("--fix")
+        .arg(&flow_path)
+        .assert()
+        .stderr(contains("fixed"))
+        .stderr(contains("entrypoint_targets").not())
+        .stderr(contains("WARN").not());
+
+    let doc = load_ygtc_from_path(&flow_path).unwrap();
+    assert_eq!(doc.entrypoints["telegram"], json!("hello"));
+    assert_eq!(doc.nodes["hello"].routing, json!("out"));
 }
 
 #[test]
-fn doctor_accepts_component_config_schema_matches() {
+fn lint_fix_renames_nodes_with_their_sidecar_entries() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
-    let wasm_path = dir.path().join("comp.wasm");
-    let manifest_path = dir.path().join("component.manifest.json");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
-    fs::write(
-        &manifest_path,
-        r#"{"id":"ai.greentic.test","config_schema":{"type":"object","required":["message"],"properties":{"message":{"type":"string"}}}}"#,
-    )
-    .unwrap();
     fs::write(
         &flow_path,
         r#"id: main
 type: messaging
 schema_version: 2
+start: sendReply
 nodes:
-  hello:
-    run:
-      message: "ok"
-    routing: out
+  sendReply:
+    op: {}
+    routing: reply
 "#,
     )
     .unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
     fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+        dir.path().join("flow.ygtc.resolve.json"),
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"sendReply":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
     )
     .unwrap();
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("doctor")
+    cargo_bin_cmd!("greentic-flow")
+        .arg("lint")
         .arg(&flow_path)
         .assert()
-        .success();
-}
+        .success()
+        .stderr(contains(
+            "WARN node_naming: node id 'sendReply' is not snake_case (expected 'send_reply') (fix: rename node 'sendReply' to 'send_reply'; run doctor --fix)",
+        ));
 
-#[test]
-fn add_step_rejects_component_payload_schema_mismatch() {
-    let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
     cargo_bin_cmd!("greentic-flow")
-        .arg("new")
-        .arg("--flow")
+        .arg("lint")
+        .arg("--fix")
         .arg(&flow_path)
-        .arg("--id")
-        .arg("main")
-        .arg("--type")
-        .arg("messaging")
         .assert()
-        .success();
+        .success()
+        .stderr(contains("fixed"))
+        .stderr(contains("WARN").not());
 
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    let doc = load_ygtc_from_path(&flow_path).unwrap();
+    assert_eq!(doc.start.as_deref(), Some("send_reply"));
+    let sidecar: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("flow.ygtc.resolve.json")).unwrap(),
+    )
+    .unwrap();
+    assert!(sidecar["nodes"]["send_reply"].is_object(), "{sidecar}");
+    assert!(sidecar["nodes"].get("sendReply").is_none(), "{sidecar}");
+}
 
-    let manifest_path = dir.path().join("component.manifest.json");
+#[test]
+fn lint_fix_leaves_findings_that_doctor_does_not_report() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let flow = r#"id: main
+type: messaging
+schema_version: 2
+start: sendReply
+nodes:
+  sendReply:
+    op: {}
+    routing: reply
+"#;
+    fs::write(&flow_path, flow).unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
     fs::write(
-        &manifest_path,
-        r#"{"id":"ai.greentic.test","config_schema":{"type":"object","required":["message"],"properties":{"message":{"type":"string"}}}}"#,
+        dir.path().join("flow.ygtc.resolve.json"),
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"sendReply":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join(".greentic-lint.toml"),
+        "[rules]\nnode_naming = \"off\"\n",
     )
     .unwrap();
 
     cargo_bin_cmd!("greentic-flow")
-        .current_dir(dir.path())
-        .arg("add-step")
-        .arg("--flow")
+        .arg("lint")
+        .arg("--fix")
         .arg(&flow_path)
-        .arg("--mode")
-        .arg("default")
-        .arg("--node-id")
-        .arg("hello")
-        .arg("--operation")
-        .arg("run")
-        .arg("--payload")
-        .arg(r#"{"message":42}"#)
-        .arg("--routing-out")
-        .arg("--local-wasm")
-        .arg("comp.wasm")
         .assert()
-        .failure()
-        .stderr(predicates::str::contains("component_config"));
+        .success()
+        .stderr(contains("fixed").not());
+    assert_eq!(fs::read_to_string(&flow_path).unwrap(), flow);
+
+    fs::remove_file(dir.path().join(".greentic-lint.toml")).unwrap();
+    let suppressed = flow.replace(
+        "  sendReply:\n",
+        "  # greentic-lint: disable=node_naming reason=\"legacy id\"\n  sendReply:\n",
+    );
+    fs::write(&flow_path, &suppressed).unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .arg("lint")
+        .arg("--fix")
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stderr(contains("fixed").not());
+    assert_eq!(fs::read_to_string(&flow_path).unwrap(), suppressed);
 }
 
 #[test]
-fn update_step_rejects_component_payload_schema_mismatch() {
+fn doctor_baseline_suppresses_recorded_findings_only() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
+    let baseline_path = dir.path().join("baseline.json");
+    let legacy = r#"id: main
+type: messaging
+schema_version: 2
+nodes: {}
+entrypoints:
+  telegram: gone
+"#;
+    fs::write(&flow_path, legacy).unwrap();
+
     cargo_bin_cmd!("greentic-flow")
-        .arg("new")
-        .arg("--flow")
+        .arg("doctor")
         .arg(&flow_path)
-        .arg("--id")
-        .arg("main")
-        .arg("--type")
-        .arg("messaging")
         .assert()
-        .success();
-
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+        .failure()
+        .stderr(contains(
+            "entrypoint 'telegram' targets missing node 'gone'",
+        ));
 
-    let manifest_path = dir.path().join("component.manifest.json");
-    fs::write(
-        &manifest_path,
-        r#"{"id":"ai.greentic.test","config_schema":{"type":"object","required":["message"],"properties":{"message":{"type":"string"}}}}"#,
-    )
-    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--write-baseline")
+        .arg(&baseline_path)
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains("(1 finding(s))"));
+    let baseline: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&baseline_path).unwrap()).unwrap();
+    assert_eq!(
+        baseline,
+        json!({
+            "version": 1,
+            "findings": [{
+                "flow": "flow.ygtc",
+                "rule": "entrypoint_targets",
+                "path": "/entrypoints/telegram",
+                "message": "entrypoint 'telegram' targets missing node 'gone'"
+            }]
+        })
+    );
 
     cargo_bin_cmd!("greentic-flow")
-        .current_dir(dir.path())
-        .arg("add-step")
-        .arg("--flow")
+        .arg("doctor")
+        .arg("--baseline")
+        .arg(&baseline_path)
         .arg(&flow_path)
-        .arg("--mode")
-        .arg("default")
-        .arg("--node-id")
-        .arg("hello")
-        .arg("--operation")
-        .arg("run")
-        .arg("--payload")
-        .arg(r#"{"message":"ok"}"#)
-        .arg("--routing-out")
-        .arg("--local-wasm")
-        .arg("comp.wasm")
         .assert()
-        .success();
+        .success()
+        .stderr(contains("1 finding(s) suppressed by baseline"));
 
+    fs::write(&flow_path, format!("{legacy}  webchat: lost\n")).unwrap();
     cargo_bin_cmd!("greentic-flow")
-        .current_dir(dir.path())
-        .arg("update-step")
-        .arg("--flow")
+        .arg("doctor")
+        .arg("--baseline")
+        .arg(&baseline_path)
         .arg(&flow_path)
-        .arg("--step")
-        .arg("hello")
-        .arg("--answers")
-        .arg(r#"{"message":42}"#)
-        .arg("--non-interactive")
         .assert()
         .failure()
-        .stderr(predicates::str::contains("component_config"));
+        .stderr(contains("entrypoint 'webchat' targets missing node 'lost'"))
+        .stderr(contains("'telegram'").not());
 }
 
 #[test]
-fn update_step_preserves_when_no_answers() {
+fn lint_baseline_is_recorded_on_first_run() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
-    let template = json!({
-        "node_id": "hello",
-        "node": {
-            "op": { "field": "{{state.field}}" },
-            "routing": [ { "to": "NEXT_NODE_PLACEHOLDER" } ]
-        }
-    });
-    let manifest = json!({
-        "id": "ai.greentic.test",
-        "dev_flows": {
-            "default": {
-                "graph": {
-                    "id": "cfg",
-                    "type": "component-config",
-                    "start": "in",
-                    "nodes": {
-                        "in": {
-                            "questions": { "fields": [ { "id": "field", "default": "old" } ] },
-                            "routing": [ { "to": "emit" } ]
-                        },
-                        "emit": {
-                            "template": serde_json::to_string(&template).unwrap()
-                        }
-                    }
-                }
-            },
-            "custom": {
-                "graph": {
-                    "id": "cfg",
-                    "type": "component-config",
-                    "start": "in",
-                    "nodes": {
-                        "in": {
-                            "questions": { "fields": [ { "id": "field", "default": "old" } ] },
-                            "routing": [ { "to": "emit" } ]
-                        },
-                        "emit": {
-                            "template": serde_json::to_string(&template).unwrap()
-                        }
-                    }
-                }
-            }
-        }
-    });
-    fs::write(
-        dir.path().join("component.manifest.json"),
-        manifest.to_string(),
-    )
-    .unwrap();
-    fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
-        )
-    .unwrap();
-    fs::write(
-        &flow_path,
-        r#"id: main
+    let baseline_path = dir.path().join("baseline.json");
+    let legacy = r#"id: main
 type: messaging
 schema_version: 2
-nodes:
-  hello:
-    op:
-      field: old
-    routing: out
-"#,
-    )
-    .unwrap();
+nodes: {}
+entrypoints:
+  telegram: gone
+"#;
+    fs::write(&flow_path, legacy).unwrap();
+
+    let lint = || {
+        let mut cmd = cargo_bin_cmd!("greentic-flow");
+        cmd.arg("lint")
+            .arg("--baseline")
+            .arg(&baseline_path)
+            .arg(&flow_path);
+        cmd
+    };
+    lint()
+        .assert()
+        .success()
+        .stdout(contains("Wrote baseline"))
+        .stdout(contains("(1 finding(s))"));
+    assert!(baseline_path.is_file());
 
-    cargo_bin_cmd!("greentic-flow")
-        .arg("update-step")
-        .arg("--flow")
-        .arg(&flow_path)
-        .arg("--step")
-        .arg("hello")
-        .arg("--mode")
-        .arg("config")
-        .arg("--non-interactive")
-        .arg("--write")
+    lint()
         .assert()
-        .success();
+        .success()
+        .stderr(contains("1 finding(s) suppressed by baseline"));
 
-    let yaml = read_yaml(&flow_path);
-    let nodes = yaml
-        .get("nodes")
-        .and_then(Value::as_mapping)
-        .expect("nodes map");
-    let hello = nodes
-        .get(Value::from("hello"))
-        .unwrap()
-        .as_mapping()
-        .unwrap();
-    let op = hello.get(Value::from("op")).unwrap().as_mapping().unwrap();
-    assert_eq!(op.get(Value::from("field")).unwrap().as_str(), Some("old"));
-    assert_eq!(
-        hello.get(Value::from("routing")).unwrap().as_str(),
-        Some("out")
-    );
+    fs::write(&flow_path, format!("{legacy}  webchat: lost\n")).unwrap();
+    lint()
+        .assert()
+        .failure()
+        .stderr(contains("entrypoint 'webchat' targets missing node 'lost'"));
 }
 
 #[test]
-fn update_step_config_prompts_for_questions() {
+fn doctor_honours_inline_suppression_comments() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    let flow = |comment: &str| {
+        format!(
+            r#"id: main
+type: messaging
+schema_version: 2
+start: greet
+nodes:
+  {comment}
+  greet:
+    handle_message:
+      text: "hi"
+      webhook: "CHANGEME"
+    routing: out
+"#
+        )
+    };
+    let doctor_json = |comment: &str| {
+        fs::write(&flow_path, flow(comment)).unwrap();
+        let output = cargo_bin_cmd!("greentic-flow")
+            .arg("doctor")
+            .arg("--json")
+            .arg(&flow_path)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        (
+            serde_json::from_str::<serde_json::Value>(&stdout).unwrap(),
+            stderr,
+        )
+    };
 
-    let template = json!({
-        "node_id": "hello",
-        "node": {
-            "card": { "msg": "{{state.msg}}" },
-            "routing": [ { "to": "NEXT_NODE_PLACEHOLDER" } ]
-        }
-    });
-    let manifest = json!({
-        "id": "ai.greentic.card",
-        "dev_flows": {
-            "custom": {
-                "graph": {
-                    "id": "cfg",
-                    "type": "component-config",
-                    "start": "ask",
-                    "nodes": {
-                        "ask": {
-                            "questions": {
-                                "fields": [
-                                    {
-                                        "id": "msg",
-                                        "prompt": "Message?",
-                                        "type": "string"
-                                    }
-                                ]
-                            },
-                            "routing": [ { "to": "emit" } ]
-                        },
-                        "emit": {
-                            "template": serde_json::to_string(&template).unwrap()
-                        }
-                    }
-                }
-            }
-        }
-    });
+    let (payload, stderr) = doctor_json(
+        r#"# greentic-lint: disable=PLACEHOLDER_VALUES reason="webhook provisioned at deploy""#,
+    );
+    assert!(!payload.to_string().contains("placeholder_values:"));
+    assert_eq!(payload["suppressions"][0]["node_id"], "greet");
+    assert_eq!(payload["suppressions"][0]["suppressed"], 1);
+    assert!(stderr.contains(
+        "note: node 'greet' suppresses PLACEHOLDER_VALUES (1 finding(s)): webhook provisioned at deploy"
+    ));
+
+    let (payload, stderr) = doctor_json(
+        r#"# greentic-lint: disable=placeholder_values reason="legacy" until=2000-01-01"#,
+    );
+    assert!(payload.to_string().contains("placeholder_values:"));
+    assert!(stderr.contains("WARN lint_suppression: node 'greet': suppression of placeholder_values expired on 2000-01-01"));
+
+    let (payload, _) = doctor_json("# greentic-lint: disable=placeholder_values");
+    let errors = payload["errors"].to_string();
+    assert!(errors.contains("suppression of placeholder_values needs a justification"));
+    assert!(errors.contains("placeholder_values:"));
+}
+
+#[test]
+fn doctor_writes_html_report_for_all_targets() {
+    let dir = tempdir().unwrap();
+    let flows = dir.path().join("flows");
+    fs::create_dir_all(&flows).unwrap();
     fs::write(
-        dir.path().join("component.manifest.json"),
-        manifest.to_string(),
+        flows.join("a.ygtc"),
+        r#"id: a
+type: messaging
+schema_version: 2
+start: greet
+nodes:
+  greet:
+    handle_message:
+      webhook: "CHANGEME"
+    routing: out
+"#,
     )
     .unwrap();
     fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+        flows.join("b.ygtc"),
+        "id: b\ntype: messaging\nschema_version: 2\nnodes: {}\n",
     )
     .unwrap();
+    let report_path = dir.path().join("report.html");
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("lint")
+        .arg("--report")
+        .arg("html")
+        .arg(&report_path)
+        .arg(&flows)
+        .assert()
+        .failure()
+        .stdout(contains("Wrote report"));
+    let html = fs::read_to_string(&report_path).unwrap();
+    assert!(html.contains("2 flow(s), 1 failing"));
+    assert!(html.contains("a.ygtc"));
+    assert!(html.contains("placeholder_values"));
+    assert!(html.contains("<td>handle_message</td>"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--report")
+        .arg("pdf")
+        .arg(&report_path)
+        .arg(&flows)
+        .assert()
+        .failure()
+        .stderr(contains("unsupported report format 'pdf'"));
+}
+
+#[test]
+fn pins_reports_floating_component_references() {
+    let dir = tempdir().unwrap();
+    let flows = dir.path().join("flows");
+    fs::create_dir_all(&flows).unwrap();
     fs::write(
-        &flow_path,
+        flows.join("main.ygtc"),
         r#"id: main
 type: messaging
 schema_version: 2
+start: fetch
 nodes:
-  hello:
-    card: {}
+  fetch:
+    fetch:
+      city: "Paris"
+    routing:
+      - to: store
+  store:
+    store:
+      key: "k"
     routing: out
 "#,
     )
     .unwrap();
+    fs::write(
+        flows.join("main.ygtc.resolve.json"),
+        json!({
+            "schema_version": 1,
+            "flow": "main.ygtc",
+            "nodes": {
+                "fetch": {"source": {"kind": "oci", "ref": "oci://ghcr.io/acme/fetch:latest"}},
+                "store": {"source": {"kind": "store", "ref": "store://acme/store", "digest": "sha256:abc"}}
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
 
     cargo_bin_cmd!("greentic-flow")
-        .arg("update-step")
-        .arg("--flow")
-        .arg(&flow_path)
-        .arg("--step")
-        .arg("hello")
-        .arg("--mode")
-        .arg("config")
-        .write_stdin("new\n")
+        .arg("pins")
+        .arg("--dir")
+        .arg(dir.path())
         .assert()
         .success()
-        .stdout(predicates::str::contains("Question (msg):"));
-
-    let yaml = read_yaml(&flow_path);
-    let nodes = yaml.get("nodes").and_then(Value::as_mapping).unwrap();
-    let hello = nodes
-        .get(Value::from("hello"))
-        .unwrap()
-        .as_mapping()
-        .unwrap();
-    let card = hello
-        .get(Value::from("card"))
-        .unwrap()
-        .as_mapping()
+        .stdout(contains(
+            "FLOAT flows/main.ygtc node 'fetch': oci://ghcr.io/acme/fetch:latest (tag 'latest' can move)",
+        ))
+        .stdout(contains("store://acme/store").not())
+        .stdout(contains("1 pinned, 1 floating component reference(s)"));
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .arg("--format")
+        .arg("json")
+        .arg("pins")
+        .arg("--dir")
+        .arg(dir.path())
+        .arg("--strict")
+        .output()
         .unwrap();
-    assert_eq!(card.get(Value::from("msg")).unwrap().as_str(), Some("new"));
+    assert!(!output.status.success());
+    let payload: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["floating"], 1);
+    assert_eq!(payload["pins"][1]["source"]["pinning"], "pinned");
+    assert_eq!(payload["pins"][1]["source"]["kind"], "store");
 }
 
 #[test]
-fn add_step_config_requires_custom_dev_flow() {
+fn pin_records_and_strips_remote_digests() {
+    const FETCHED: &str = "sha256:ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+    const STORE_DIGEST: &str =
+        "sha256:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
     let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
+    let flow_path = dir.path().join("main.ygtc");
+    let sidecar_path = dir.path().join("main.ygtc.resolve.json");
     fs::write(
         &flow_path,
         r#"id: main
 type: messaging
 schema_version: 2
+start: fetch
 nodes:
-  start:
-    op: {}
+  fetch:
+    fetch: {}
+    routing:
+      - to: store
+  store:
+    store: {}
+    routing:
+      - to: local
+  local:
+    local: {}
     routing: out
 "#,
     )
     .unwrap();
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
-
-    let manifest = json!({
-        "id": "ai.greentic.card",
-        "dev_flows": {
-            "default": {
-                "graph": {
-                    "id": "cfg",
-                    "type": "component-config",
-                    "nodes": {}
-                }
+    fs::write(dir.path().join("local.wasm"), b"wasm").unwrap();
+    fs::write(
+        &sidecar_path,
+        json!({
+            "schema_version": 1,
+            "flow": "main.ygtc",
+            "nodes": {
+                "fetch": {"source": {"kind": "oci", "ref": "oci://ghcr.io/acme/fetch:1"}},
+                "store": {"source": {"kind": "store", "ref": "store://acme/store", "digest": STORE_DIGEST}},
+                "local": {"source": {"kind": "local", "path": "file://local.wasm"}}
             }
-        }
-    });
-    let manifest_path = dir.path().join("component.manifest.json");
-    fs::write(&manifest_path, manifest.to_string()).unwrap();
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let sidecar = || -> JsonValue {
+        serde_json::from_str(&fs::read_to_string(&sidecar_path).unwrap()).unwrap()
+    };
 
     cargo_bin_cmd!("greentic-flow")
-        .current_dir(dir.path())
-        .arg("add-step")
-        .arg("--flow")
+        .env("GREENTIC_FLOW_TEST_DIGEST", FETCHED)
+        .args(["pin", "--dry-run", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains(format!(
+            "PIN   node 'fetch': oci://ghcr.io/acme/fetch:1 -> {FETCHED}"
+        )))
+        .stdout(contains("Would pin 1 of 2 remote component reference(s)"));
+    assert!(
+        sidecar()["nodes"]["fetch"]["source"]
+            .get("digest")
+            .is_none()
+    );
+
+    cargo_bin_cmd!("greentic-flow")
+        .env("GREENTIC_FLOW_TEST_DIGEST", FETCHED)
+        .args(["pin", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains("Pinned 1 of 2 remote component reference(s)"));
+    let pinned = sidecar();
+    assert_eq!(pinned["nodes"]["fetch"]["source"]["digest"], FETCHED);
+    assert_eq!(pinned["nodes"]["fetch"]["mode"], "pinned");
+    assert_eq!(pinned["nodes"]["store"]["source"]["digest"], STORE_DIGEST);
+    assert!(pinned["nodes"]["local"]["source"].get("digest").is_none());
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args([
+            "--format", "json", "pin", "--unpin", "--node", "store", "--flow",
+        ])
+        .arg(&flow_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["remote"], 1);
+    assert_eq!(payload["changes"][0]["node_id"], "store");
+    let unpinned = sidecar();
+    assert!(unpinned["nodes"]["store"]["source"].get("digest").is_none());
+    assert_eq!(unpinned["nodes"]["fetch"]["source"]["digest"], FETCHED);
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["pin", "--node", "ghost", "--flow"])
         .arg(&flow_path)
-        .arg("--mode")
-        .arg("config")
-        .arg("--node-id")
-        .arg("card1")
-        .arg("--operation")
-        .arg("card")
-        .arg("--payload")
-        .arg(r#"{}"#)
-        .arg("--local-wasm")
-        .arg("comp.wasm")
-        .arg("--manifest")
-        .arg(&manifest_path)
         .assert()
         .failure()
-        .stderr(predicates::str::contains("dev_flows.custom"));
+        .stderr(contains("node 'ghost' is not bound"));
 }
 
 #[test]
-fn add_step_default_fixture_prompts_and_applies_answers() {
+fn doctor_runtime_manifest_flags_nodes_the_runtime_cannot_serve() {
     let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    let fixture_flow =
-        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/flows/simple.ygtc");
-    fs::write(&flow_path, fs::read_to_string(&fixture_flow).unwrap()).unwrap();
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
-
-    let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR"))
-        .join("tests/fixtures/manifests/component.manifest.json");
+    let flow_path = dir.path().join("main.ygtc");
+    let manifest_path = dir.path().join("runtime.json");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+start: fetch
+meta:
+  greentic:
+    components:
+      fetch:
+        world: "greentic:component@0.7.0"
+        schema_id: "greentic.weather.v1"
+nodes:
+  fetch:
+    fetch:
+      city: "Paris"
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("main.ygtc.resolve.json"),
+        json!({
+            "schema_version": 1,
+            "flow": "main.ygtc",
+            "nodes": {
+                "fetch": {"source": {"kind": "store", "ref": "store://acme/fetch", "digest": "sha256:abc"}}
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    fs::write(
+        &manifest_path,
+        json!({"worlds": ["greentic:component@0.6.2"], "schemas": ["greentic.weather.v1"]})
+            .to_string(),
+    )
+    .unwrap();
 
     cargo_bin_cmd!("greentic-flow")
-        .current_dir(dir.path())
-        .arg("add-step")
-        .arg("--flow")
+        .arg("doctor")
         .arg(&flow_path)
-        .arg("--node-id")
-        .arg("card1")
-        .arg("--mode")
-        .arg("default")
-        .arg("--operation")
-        .arg("card")
-        .arg("--payload")
-        .arg(r#"{}"#)
-        .arg("--local-wasm")
-        .arg("comp.wasm")
-        .arg("--manifest")
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--runtime-manifest")
         .arg(&manifest_path)
-        .write_stdin("assets/cards/card.json\ny\n")
+        .arg(&flow_path)
         .assert()
-        .success()
-        .stdout(predicates::str::contains("Question (asset_path):"))
-        .stdout(predicates::str::contains("Question (needs_interaction):"));
+        .failure()
+        .stderr(contains(
+            "node 'fetch' component 'fetch' requires world 'greentic:component@0.7.0'",
+        ))
+        .stderr(contains("greentic.weather.v1").not());
 
-    let yaml = read_yaml(&flow_path);
-    let nodes = yaml.get("nodes").and_then(Value::as_mapping).unwrap();
-    let inserted = nodes
-        .get(Value::from("card1"))
-        .unwrap()
-        .as_mapping()
-        .unwrap();
-    let card = inserted
-        .get(Value::from("card"))
-        .unwrap()
-        .as_mapping()
-        .unwrap();
-    let card_spec = card
-        .get(Value::from("card_spec"))
-        .unwrap()
-        .as_mapping()
-        .unwrap();
-    assert_eq!(
-        card_spec.get(Value::from("asset_path")).unwrap().as_str(),
-        Some("assets/cards/card.json")
-    );
-    let interaction = card
-        .get(Value::from("interaction"))
-        .unwrap()
-        .as_mapping()
-        .unwrap();
-    assert_eq!(
-        interaction
-            .get(Value::from("enabled"))
-            .and_then(Value::as_bool),
-        Some(true)
-    );
+    fs::write(
+        &manifest_path,
+        json!({"worlds": ["greentic:component@0.7.3"], "schemas": ["greentic.weather.v1"]})
+            .to_string(),
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--runtime-manifest")
+        .arg(&manifest_path)
+        .arg(&flow_path)
+        .assert()
+        .success();
 }
 
 #[test]
-fn update_step_non_interactive_missing_required_reports_template() {
+fn doctor_release_catalog_reports_upgrade_advisories() {
     let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
-
-    let manifest_fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
-        .join("tests/fixtures/manifests/component.manifest.json");
+    let flow_path = dir.path().join("main.ygtc");
     fs::write(
-        dir.path().join("component.manifest.json"),
-        fs::read_to_string(&manifest_fixture).unwrap(),
+        &flow_path,
+        "id: main\ntype: messaging\nstart: fetch\nnodes:\n  fetch:\n    fetch:\n      city: Paris\n    routing: out\n",
     )
     .unwrap();
-
     fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"card1":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+        dir.path().join("main.ygtc.resolve.json"),
+        json!({
+            "schema_version": 1,
+            "flow": "main.ygtc",
+            "nodes": {
+                "fetch": {"source": {"kind": "store", "ref": "store://acme/fetch", "digest": "sha256:abc"}}
+            }
+        })
+        .to_string(),
     )
     .unwrap();
+    let manifest = |required: serde_json::Value| {
+        json!({
+            "id": "acme.fetch",
+            "operations": [{
+                "name": "fetch",
+                "input_schema": {
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}, "units": {"type": "string"}},
+                    "required": required
+                }
+            }]
+        })
+        .to_string()
+    };
+    fs::write(dir.path().join("fetch-1.0.json"), manifest(json!(["city"]))).unwrap();
     fs::write(
-        &flow_path,
-        r#"id: main
-type: messaging
-schema_version: 2
-nodes:
-  card1:
-    card: {}
-    routing: out
-"#,
+        dir.path().join("fetch-2.0.json"),
+        manifest(json!(["city", "units"])),
+    )
+    .unwrap();
+    let catalog_path = dir.path().join("releases.json");
+    fs::write(
+        &catalog_path,
+        json!({
+            "components": {
+                "store://acme/fetch": [
+                    {"version": "1.0.0", "digest": "sha256:abc", "manifest": "fetch-1.0.json"},
+                    {"version": "2.0.0", "digest": "sha256:def", "manifest": "fetch-2.0.json"}
+                ]
+            }
+        })
+        .to_string(),
     )
     .unwrap();
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("update-step")
-        .arg("--flow")
-        .arg(&flow_path)
-        .arg("--step")
-        .arg("card1")
-        .arg("--mode")
-        .arg("default")
-        .arg("--non-interactive")
-        .assert()
-        .failure()
-        .stderr(predicates::str::contains("missing required answers"))
-        .stderr(predicates::str::contains("--answers"))
-        .stderr(predicates::str::contains("asset_path"));
-}
-
-#[test]
-fn add_step_rejects_invalid_component_scheme() {
-    let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("new")
-        .arg("--flow")
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["doctor", "--json", "--release-catalog"])
+        .arg(&catalog_path)
         .arg(&flow_path)
-        .arg("--id")
-        .arg("main")
-        .arg("--type")
-        .arg("messaging")
-        .assert()
-        .success();
-
-    fs::write(dir.path().join("comp.wasm"), b"bytes").unwrap();
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    let advisory = &payload["advisories"][0];
+    assert_eq!(advisory["node_id"], json!("fetch"));
+    assert_eq!(advisory["current_version"], json!("1.0.0"));
+    let candidate = &advisory["candidates"][0];
+    assert_eq!(candidate["version"], json!("2.0.0"));
+    assert_eq!(candidate["classification"], json!("breaking"));
+    assert_eq!(
+        candidate["breaks"],
+        json!(["does not set newly required /units"])
+    );
+    assert!(
+        candidate["apply"]
+            .as_str()
+            .unwrap()
+            .ends_with("--step fetch --component store://acme/fetch@sha256:def --write")
+    );
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("add-step")
-        .arg("--flow")
+    cargo_bin_cmd!("greentic-flow")
+        .args(["doctor", "--release-catalog"])
+        .arg(&catalog_path)
         .arg(&flow_path)
-        .arg("--mode")
-        .arg("default")
-        .arg("--node-id")
-        .arg("comp")
-        .arg("--operation")
-        .arg("run")
-        .arg("--payload")
-        .arg(r#"{}"#)
-        .arg("--component")
-        .arg("badref")
         .assert()
-        .failure()
-        .stderr(predicates::str::contains(
-            "--component must start with oci://, repo://, or store://",
+        .success()
+        .stdout(contains(
+            "advisory: node 'fetch' pins store://acme/fetch 1.0.0; 2.0.0 available (breaking)",
         ));
-}
-
-#[test]
-fn add_step_rejects_private_oci_host() {
-    let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("new")
-        .arg("--flow")
-        .arg(&flow_path)
-        .arg("--id")
-        .arg("main")
-        .arg("--type")
-        .arg("messaging")
-        .assert()
-        .success();
 
-    fs::write(dir.path().join("comp.wasm"), b"bytes").unwrap();
-
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("add-step")
-        .arg("--flow")
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["doctor", "--json"])
         .arg(&flow_path)
-        .arg("--mode")
-        .arg("default")
-        .arg("--node-id")
-        .arg("comp")
-        .arg("--operation")
-        .arg("run")
-        .arg("--payload")
-        .arg(r#"{}"#)
-        .arg("--component")
-        .arg("oci://localhost/component:latest")
-        .assert()
-        .failure()
-        .stderr(predicates::str::contains(
-            "oci:// references must use a public registry host",
-        ));
+        .output()
+        .unwrap();
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(payload.get("advisories").is_none());
 }
 
 #[test]
-fn update_step_rejects_invalid_component_scheme() {
+fn doctor_fix_moves_stray_keys_into_the_operation_payload() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
-    fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
-    )
-    .unwrap();
     fs::write(
         &flow_path,
         r#"id: main
 type: messaging
 schema_version: 2
+start: send
 nodes:
-  hello:
-    op:
-      field: old
+  send:
+    messaging.telegram.sendMessage:
+      text: "hi"
+    chat_id: 42
     routing: out
 "#,
     )
     .unwrap();
+    fs::write(
+        dir.path().join("registry.json"),
+        r#"{"adapters": {"messaging.telegram": ["sendMessage"]}}"#,
+    )
+    .unwrap();
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("update-step")
-        .arg("--flow")
-        .arg(&flow_path)
-        .arg("--step")
-        .arg("hello")
-        .arg("--component")
-        .arg("badref")
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("doctor")
+        .arg("--registry")
+        .arg("registry.json")
+        .arg("flow.ygtc")
         .assert()
         .failure()
-        .stderr(predicates::str::contains(
-            "--component must start with oci://, repo://, or store://",
-        ));
+        .stderr(contains(
+            "node 'send': keep 'messaging.telegram.sendMessage' as the operation; move 'chat_id' into the 'messaging.telegram.sendMessage' payload (run doctor --fix)",
+        ))
+        .stderr(contains("found 'chat_id', 'messaging.telegram.sendMessage'"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("doctor")
+        .arg("--fix")
+        .arg("--registry")
+        .arg("registry.json")
+        .arg("flow.ygtc")
+        .assert()
+        .stderr(contains("fixed"));
+
+    let repaired: Value =
+        serde_yaml_bw::from_str(&fs::read_to_string(&flow_path).unwrap()).unwrap();
+    let node = &repaired["nodes"]["send"];
+    assert_eq!(
+        node["messaging.telegram.sendMessage"]["chat_id"],
+        Value::from(42)
+    );
+    assert!(node.get("chat_id").is_none());
 }
 
 #[test]
-fn update_step_rejects_private_oci_host() {
+fn adapters_refresh_writes_registry_usable_by_doctor() {
     let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let component_dir = dir.path().join("components/telegram");
+    fs::create_dir_all(&component_dir).unwrap();
     fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+        component_dir.join("component.manifest.json"),
+        json!({"id": "messaging.telegram", "operations": ["sendMessage"]}).to_string(),
     )
     .unwrap();
     fs::write(
-        &flow_path,
+        dir.path().join("index.json"),
+        r#"{"adapters": {"email.google": ["send"]}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("flow.ygtc"),
         r#"id: main
 type: messaging
 schema_version: 2
+start: send
 nodes:
-  hello:
-    op:
-      field: old
+  send:
+    messaging.telegram.sendMessage:
+      text: "hi"
+    routing:
+      - to: mail
+  mail:
+    email.google.beamMeUp:
+      to: "team@greentic.dev"
     routing: out
 "#,
     )
     .unwrap();
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("update-step")
-        .arg("--flow")
-        .arg(&flow_path)
-        .arg("--step")
-        .arg("hello")
-        .arg("--component")
-        .arg("oci://localhost/component:latest")
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .env("GREENTIC_CACHE_DIR", dir.path().join("dist-cache"))
+        .args(["adapters", "list", "--index", "index.json"])
+        .assert()
+        .success()
+        .stdout(contains("email.google: send"))
+        .stdout(contains("messaging.telegram: sendMessage"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .env("GREENTIC_CACHE_DIR", dir.path().join("dist-cache"))
+        .args(["adapters", "refresh", "--index", "index.json"])
+        .assert()
+        .success()
+        .stdout(contains("Wrote 2 adapter(s)"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["doctor", "--registry", "adapters.json", "flow.ygtc"])
         .assert()
         .failure()
-        .stderr(predicates::str::contains(
-            "oci:// references must use a public registry host",
-        ));
+        .stderr(contains(
+            "('mail') component 'email.google.beamMeUp' missing adapter 'email.google'",
+        ))
+        .stderr(contains("('send')").not());
 }
 
 #[test]
-fn doctor_prunes_sidecar_to_match_flow() {
+fn doctor_honors_extra_reserved_node_keys_from_env() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
     fs::write(
         &flow_path,
         r#"id: main
 type: messaging
 schema_version: 2
+start: greet
 nodes:
-  keep:
-    op: {}
+  greet:
+    handle_message:
+      text: "hi"
+    fork_policy:
+      mode: strict
     routing: out
 "#,
     )
     .unwrap();
-    fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"old.ygtc","nodes":{"keep":{"source":{"kind":"local","path":"comp.wasm"}},"stale":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
-    )
-    .unwrap();
 
     cargo_bin_cmd!("greentic-flow")
         .arg("doctor")
+        .arg("--json")
         .arg(&flow_path)
-        .write_stdin("y\n")
         .assert()
-        .success();
+        .failure()
+        .stdout(contains("exactly one component key"));
 
-    let sidecar: JsonValue =
-        serde_json::from_str(&fs::read_to_string(&sidecar_path).unwrap()).unwrap();
-    assert_eq!(
-        sidecar.get("flow").and_then(JsonValue::as_str),
-        flow_path.file_name().and_then(|s| s.to_str())
-    );
-    let nodes = sidecar.get("nodes").and_then(JsonValue::as_object).unwrap();
-    assert!(nodes.contains_key("keep"));
-    assert!(!nodes.contains_key("stale"));
+    cargo_bin_cmd!("greentic-flow")
+        .env("GREENTIC_FLOW_RESERVED_KEYS", "fork_policy")
+        .arg("doctor")
+        .arg("--json")
+        .arg(&flow_path)
+        .assert()
+        .stdout(
+            contains("exactly one component key")
+                .not()
+                .and(contains("missing sidecar entries for nodes: greet")),
+        );
 }
 
 #[test]
-fn doctor_reports_unused_sidecar_when_denied() {
+fn doctor_fails_on_raw_summary_literals() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
     fs::write(
         &flow_path,
-        r#"id: main
+        r#"id: raw-summary
 type: messaging
 schema_version: 2
-nodes:
-  keep:
-    op: {}
-    routing: out
+title: Raw title
+description: Raw description
+nodes: {}
 "#,
     )
     .unwrap();
-    fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"keep":{"source":{"kind":"local","path":"comp.wasm"}},"stale":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
-    )
-    .unwrap();
 
     cargo_bin_cmd!("greentic-flow")
         .arg("doctor")
         .arg(&flow_path)
-        .write_stdin("n\n")
         .assert()
         .failure()
-        .stderr(predicates::str::contains("unused sidecar entries"));
-
-    let sidecar: JsonValue =
-        serde_json::from_str(&fs::read_to_string(&sidecar_path).unwrap()).unwrap();
-    let nodes = sidecar.get("nodes").and_then(JsonValue::as_object).unwrap();
-    assert!(nodes.contains_key("stale"));
+        .stderr(
+            contains("title must be an i18n tag").and(contains("description must be an i18n tag")),
+        );
 }
 
 #[test]
-fn doctor_reports_invalid_sidecar_source() {
+fn doctor_fails_when_i18n_summary_key_missing_from_pack_source() {
     let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+    fs::create_dir_all(flow_path.parent().unwrap()).unwrap();
     fs::write(
         &flow_path,
-        r#"id: main
+        r#"id: welcome
 type: messaging
 schema_version: 2
-nodes:
-  hello:
-    op: {}
-    routing: out
+title: i18n:flow.welcome.title
+description: i18n:flow.welcome.description
+nodes: {}
 "#,
     )
     .unwrap();
+    fs::create_dir_all(dir.path().join("i18n")).unwrap();
     fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"oci","ref":"oci://localhost/component:latest"}}}}"#,
+        dir.path().join("i18n/en-GB.json"),
+        r#"{"flow.welcome.title":"Welcome"}"#,
     )
     .unwrap();
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+    cargo_bin_cmd!("greentic-flow")
         .arg("doctor")
         .arg(&flow_path)
         .assert()
         .failure()
-        .stderr(predicates::str::contains("invalid sidecar entries"));
+        .stderr(contains(
+            "description i18n key 'flow.welcome.description' missing from pack i18n/en-GB.json",
+        ));
 }
 
 #[test]
-fn doctor_reports_missing_sidecar_entries() {
+fn doctor_accepts_i18n_summary_keys_present_in_pack_source() {
     let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let flow_path = dir.path().join("flows/global/messaging/welcome.ygtc");
+    fs::create_dir_all(flow_path.parent().unwrap()).unwrap();
     fs::write(
         &flow_path,
-        r#"id: main
+        r#"id: welcome
 type: messaging
 schema_version: 2
-nodes:
-  hello:
-    op: {}
-    routing: out
+title: i18n:flow.welcome.title
+description: i18n:flow.welcome.description
+nodes: {}
+parameters: {}
+tags: []
+entrypoints: {}
 "#,
     )
     .unwrap();
+    fs::create_dir_all(dir.path().join("i18n")).unwrap();
     fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{}}"#,
+        dir.path().join("i18n/en-GB.json"),
+        r#"{"flow.welcome.title":"Welcome","flow.welcome.description":"Greeting flow"}"#,
     )
     .unwrap();
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+    cargo_bin_cmd!("greentic-flow")
         .arg("doctor")
         .arg(&flow_path)
         .assert()
-        .failure()
-        .stderr(predicates::str::contains("missing sidecar entries"));
+        .success();
 }
 
 #[test]
-fn doctor_reports_missing_local_wasm() {
+fn doctor_json_reports_raw_summary_i18n_lint_errors() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
-    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
     fs::write(
         &flow_path,
-        r#"id: main
+        r#"id: raw-summary
 type: messaging
 schema_version: 2
-nodes:
-  hello:
-    op: {}
-    routing: out
+title: Raw title
+description: Raw description
+nodes: {}
 "#,
     )
     .unwrap();
-    fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"missing.wasm"}}}}"#,
-    )
-    .unwrap();
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+    cargo_bin_cmd!("greentic-flow")
         .arg("doctor")
+        .arg("--json")
         .arg(&flow_path)
         .assert()
         .failure()
-        .stderr(predicates::str::contains("invalid sidecar entries"));
+        .stdout(
+            contains("title must be an i18n tag").and(contains("description must be an i18n tag")),
+        );
 }
 
 #[test]
-fn doctor_accepts_file_uri_local_wasm() {
+fn doctor_reports_component_config_schema_errors() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
     let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
     let wasm_path = dir.path().join("comp.wasm");
+    let manifest_path = dir.path().join("component.manifest.json");
     fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    fs::write(
+        &manifest_path,
+        r#"{"id":"ai.greentic.test","config_schema":{"type":"object","required":["message"],"properties":{"message":{"type":"string"}}}}"#,
+    )
+    .unwrap();
     fs::write(
         &flow_path,
         r#"id: main
@@ -2358,92 +2834,86 @@ type: messaging
 schema_version: 2
 nodes:
   hello:
-    op: {}
+    run:
+      message: 42
     routing: out
 "#,
     )
     .unwrap();
     fs::write(
         &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"file://comp.wasm"}}}}"#,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
     )
     .unwrap();
 
     Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .current_dir(dir.path())
         .arg("doctor")
         .arg(&flow_path)
         .assert()
-        .success();
+        .failure()
+        .stderr(predicates::str::contains("component_config"));
 }
 
 #[test]
-fn update_step_overrides_payload_and_routing() {
+fn doctor_checks_status_routes_against_output_schema() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
     let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
-    let wasm_path = dir.path().join("comp.wasm");
-    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
     fs::write(
-        &sidecar_path,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
-        )
+        dir.path().join("component.manifest.json"),
+        r#"{"id":"ai.greentic.pay","operations":[{"name":"charge","input_schema":{"type":"object","properties":{"amount":{"type":"integer"}}},"output_schema":{"type":"object","properties":{"status":{"enum":["ok","e402","e412"]}}}}]}"#,
+    )
     .unwrap();
     fs::write(
-        &flow_path,
-        r#"id: main
-type: messaging
-schema_version: 2
-nodes:
-  hello:
-    op:
-      field: old
-    routing: out
-"#,
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"pay":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
     )
     .unwrap();
+    let write_flow = |routing: &str| {
+        fs::write(
+            &flow_path,
+            format!(
+                "id: main\ntype: messaging\nschema_version: 2\nstart: pay\nnodes:\n  pay:\n    charge: {{}}\n    routing:\n{routing}"
+            ),
+        )
+        .unwrap();
+    };
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("update-step")
-        .arg("--flow")
+    write_flow(
+        "      - status: e412\n        out: true\n      - status: e500\n        out: true\n",
+    );
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
         .arg(&flow_path)
-        .arg("--step")
-        .arg("hello")
-        .arg("--answers")
-        .arg(r#"{"field":"new","extra":1}"#)
-        .arg("--routing-reply")
-        .arg("--write")
         .assert()
-        .success();
+        .failure()
+        .stderr(contains(
+            "node 'pay' routes status 'e500', which the component does not declare (declared: ok, e402, e412)",
+        ))
+        .stderr(contains("node 'pay' does not route error status(es) e402"));
 
-    let yaml = read_yaml(&flow_path);
-    let nodes = yaml
-        .get("nodes")
-        .and_then(Value::as_mapping)
-        .expect("nodes map");
-    let hello = nodes
-        .get(Value::from("hello"))
-        .unwrap()
-        .as_mapping()
-        .unwrap();
-    let op = hello.get(Value::from("op")).unwrap().as_mapping().unwrap();
-    assert_eq!(op.get(Value::from("field")).unwrap().as_str(), Some("new"));
-    assert_eq!(
-        hello.get(Value::from("routing")).unwrap().as_str(),
-        Some("reply")
+    write_flow(
+        "      - status: e412\n        out: true\n      - status: \"*\"\n        out: true\n",
     );
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .success();
 }
 
 #[test]
-fn delete_step_splices_single_predecessor() {
+fn doctor_accepts_component_config_schema_matches() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
     let wasm_path = dir.path().join("comp.wasm");
+    let manifest_path = dir.path().join("component.manifest.json");
     fs::write(&wasm_path, b"wasm-bytes").unwrap();
-    let sidecar = dir.path().join("flow.ygtc.resolve.json");
     fs::write(
-        &sidecar,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"mid":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+        &manifest_path,
+        r#"{"id":"ai.greentic.test","config_schema":{"type":"object","required":["message"],"properties":{"message":{"type":"string"}}}}"#,
     )
     .unwrap();
     fs::write(
@@ -2452,206 +2922,28 @@ fn delete_step_splices_single_predecessor() {
 type: messaging
 schema_version: 2
 nodes:
-  a:
-    hop: {}
-    routing:
-      - to: mid
-  mid:
-    op: {}
+  hello:
+    run:
+      message: "ok"
     routing: out
 "#,
     )
     .unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
 
     Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("delete-step")
-        .arg("--flow")
+        .arg("doctor")
         .arg(&flow_path)
-        .arg("--step")
-        .arg("mid")
-        .arg("--write")
         .assert()
         .success();
-
-    let yaml = read_yaml(&flow_path);
-    let nodes = yaml
-        .get("nodes")
-        .and_then(Value::as_mapping)
-        .expect("nodes map");
-    assert!(!nodes.contains_key(Value::from("mid")));
-    let a = nodes.get(Value::from("a")).unwrap().as_mapping().unwrap();
-    if let Some(r) = a.get(Value::from("routing")) {
-        if let Some(s) = r.as_str() {
-            assert_eq!(s, "out");
-        } else if let Some(seq) = r.as_sequence() {
-            assert!(
-                seq.is_empty()
-                    || seq
-                        .iter()
-                        .any(|v| v.get("out").and_then(Value::as_bool) == Some(true))
-            );
-        }
-    }
 }
 
 #[test]
-fn delete_step_errors_on_multiple_predecessors() {
-    let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    fs::write(
-        &flow_path,
-        r#"id: main
-type: messaging
-schema_version: 2
-nodes:
-  a:
-    hop: {}
-    routing:
-      - to: mid
-  b:
-    hop: {}
-    routing:
-      - to: mid
-  mid:
-    op: {}
-    routing:
-      - to: end
-  end:
-    noop: {}
-    routing: out
-"#,
-    )
-    .unwrap();
-
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("delete-step")
-        .arg("--flow")
-        .arg(&flow_path)
-        .arg("--step")
-        .arg("mid")
-        .assert()
-        .failure();
-}
-
-#[test]
-fn delete_step_splice_all_predecessors() {
-    let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    let sidecar = dir.path().join("flow.ygtc.resolve.json");
-    fs::write(
-        &sidecar,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"mid":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
-    )
-    .unwrap();
-    fs::write(
-        &flow_path,
-        r#"id: main
-type: messaging
-schema_version: 2
-nodes:
-  a:
-    hop: {}
-    routing:
-      - to: mid
-  b:
-    hop: {}
-    routing:
-      - to: mid
-  mid:
-    op: {}
-    routing:
-      - to: end
-  end:
-    noop: {}
-    routing: out
-"#,
-    )
-    .unwrap();
-
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("delete-step")
-        .arg("--flow")
-        .arg(&flow_path)
-        .arg("--step")
-        .arg("mid")
-        .arg("--if-multiple-predecessors")
-        .arg("splice-all")
-        .arg("--write")
-        .assert()
-        .success();
-
-    let yaml = read_yaml(&flow_path);
-    let nodes = yaml
-        .get("nodes")
-        .and_then(Value::as_mapping)
-        .expect("nodes map");
-    assert!(!nodes.contains_key(Value::from("mid")));
-    for pred in ["a", "b"] {
-        let n = nodes.get(Value::from(pred)).unwrap().as_mapping().unwrap();
-        let routing = n.get(Value::from("routing")).unwrap();
-        if let Some(arr) = routing.as_sequence() {
-            assert_eq!(
-                arr[0].get("to").and_then(Value::as_str).expect("to target"),
-                "end"
-            );
-        } else if let Some(s) = routing.as_str() {
-            assert_eq!(s, "out");
-        } else {
-            panic!("unexpected routing shape");
-        }
-    }
-}
-
-#[test]
-fn delete_step_removes_sidecar_mapping() {
-    let dir = tempdir().unwrap();
-    let flow_path = dir.path().join("flow.ygtc");
-    let sidecar = flow_path.with_extension("ygtc.resolve.json");
-    fs::write(
-        &sidecar,
-        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"mid":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
-    )
-    .unwrap();
-    fs::write(
-        &flow_path,
-        r#"id: main
-type: messaging
-schema_version: 2
-nodes:
-  a:
-    hop: {}
-    routing:
-      - to: mid
-  mid:
-    op: {}
-    routing: out
-"#,
-    )
-    .unwrap();
-
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("delete-step")
-        .arg("--flow")
-        .arg(&flow_path)
-        .arg("--step")
-        .arg("mid")
-        .arg("--write")
-        .assert()
-        .success();
-
-    let sidecar_json: JsonValue =
-        serde_json::from_str(&fs::read_to_string(&sidecar).unwrap()).unwrap();
-    assert!(
-        !sidecar_json
-            .get("nodes")
-            .and_then(JsonValue::as_object)
-            .unwrap()
-            .contains_key("mid")
-    );
-}
-
-#[test]
-fn add_step_rejects_empty_manifest_schema_by_default() {
+fn add_step_rejects_component_payload_schema_mismatch() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
     cargo_bin_cmd!("greentic-flow")
@@ -2668,9 +2960,10 @@ fn add_step_rejects_empty_manifest_schema_by_default() {
     let wasm_path = dir.path().join("comp.wasm");
     fs::write(&wasm_path, b"wasm-bytes").unwrap();
 
+    let manifest_path = dir.path().join("component.manifest.json");
     fs::write(
-        dir.path().join("component.manifest.json"),
-        r#"{"id":"ai.greentic.empty","operations":[{"name":"run","input_schema":{}}]}"#,
+        &manifest_path,
+        r#"{"id":"ai.greentic.test","config_schema":{"type":"object","required":["message"],"properties":{"message":{"type":"string"}}}}"#,
     )
     .unwrap();
 
@@ -2682,21 +2975,21 @@ fn add_step_rejects_empty_manifest_schema_by_default() {
         .arg("--mode")
         .arg("default")
         .arg("--node-id")
-        .arg("empty")
+        .arg("hello")
         .arg("--operation")
         .arg("run")
         .arg("--payload")
-        .arg("{}")
+        .arg(r#"{"message":42}"#)
         .arg("--routing-out")
         .arg("--local-wasm")
         .arg("comp.wasm")
         .assert()
         .failure()
-        .stderr(predicates::str::contains("E_SCHEMA_EMPTY"));
+        .stderr(predicates::str::contains("component_config"));
 }
 
 #[test]
-fn add_step_warns_on_empty_manifest_schema_with_permissive_flag() {
+fn update_step_rejects_component_payload_schema_mismatch() {
     let dir = tempdir().unwrap();
     let flow_path = dir.path().join("flow.ygtc");
     cargo_bin_cmd!("greentic-flow")
@@ -2713,130 +3006,172 @@ fn add_step_warns_on_empty_manifest_schema_with_permissive_flag() {
     let wasm_path = dir.path().join("comp.wasm");
     fs::write(&wasm_path, b"wasm-bytes").unwrap();
 
+    let manifest_path = dir.path().join("component.manifest.json");
     fs::write(
-        dir.path().join("component.manifest.json"),
-        r#"{"id":"ai.greentic.empty","operations":[{"name":"run","input_schema":{}}]}"#,
+        &manifest_path,
+        r#"{"id":"ai.greentic.test","config_schema":{"type":"object","required":["message"],"properties":{"message":{"type":"string"}}}}"#,
     )
     .unwrap();
 
     cargo_bin_cmd!("greentic-flow")
         .current_dir(dir.path())
-        .arg("--permissive")
         .arg("add-step")
         .arg("--flow")
         .arg(&flow_path)
         .arg("--mode")
         .arg("default")
         .arg("--node-id")
-        .arg("empty")
+        .arg("hello")
         .arg("--operation")
         .arg("run")
         .arg("--payload")
-        .arg("{}")
+        .arg(r#"{"message":"ok"}"#)
         .arg("--routing-out")
         .arg("--local-wasm")
         .arg("comp.wasm")
         .assert()
-        .success()
-        .stderr(predicates::str::contains("W_SCHEMA_EMPTY"));
+        .success();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("update-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("hello")
+        .arg("--answers")
+        .arg(r#"{"message":42}"#)
+        .arg("--non-interactive")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("component_config"));
 }
 
 #[test]
-fn answers_error_on_empty_question_graph_by_default() {
+fn update_step_preserves_when_no_answers() {
     let dir = tempdir().unwrap();
-    let manifest_path = dir.path().join("component.manifest.json");
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    let template = json!({
+        "node_id": "hello",
+        "node": {
+            "op": { "field": "{{state.field}}" },
+            "routing": [ { "to": "NEXT_NODE_PLACEHOLDER" } ]
+        }
+    });
     let manifest = json!({
-        "id": "ai.greentic.empty",
+        "id": "ai.greentic.test",
         "dev_flows": {
             "default": {
                 "graph": {
                     "id": "cfg",
                     "type": "component-config",
-                    "start": "ask",
+                    "start": "in",
                     "nodes": {
-                        "ask": {
-                            "template": "{}"
+                        "in": {
+                            "questions": { "fields": [ { "id": "field", "default": "old" } ] },
+                            "routing": [ { "to": "emit" } ]
+                        },
+                        "emit": {
+                            "template": serde_json::to_string(&template).unwrap()
                         }
                     }
                 }
-            }
-        }
-    });
-    fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
-
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("answers")
-        .arg("--component")
-        .arg(&manifest_path)
-        .arg("--operation")
-        .arg("default")
-        .arg("--name")
-        .arg("empty")
-        .assert()
-        .failure()
-        .stderr(predicates::str::contains("E_SCHEMA_EMPTY"));
-}
-
-#[test]
-fn answers_warns_on_empty_question_graph_with_permissive() {
-    let dir = tempdir().unwrap();
-    let manifest_path = dir.path().join("component.manifest.json");
-    let manifest = json!({
-        "id": "ai.greentic.empty",
-        "dev_flows": {
-            "default": {
+            },
+            "custom": {
                 "graph": {
                     "id": "cfg",
                     "type": "component-config",
-                    "start": "ask",
+                    "start": "in",
                     "nodes": {
-                        "ask": {
-                            "template": "{}"
+                        "in": {
+                            "questions": { "fields": [ { "id": "field", "default": "old" } ] },
+                            "routing": [ { "to": "emit" } ]
+                        },
+                        "emit": {
+                            "template": serde_json::to_string(&template).unwrap()
                         }
                     }
                 }
             }
         }
     });
-    fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
-
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("--permissive")
-        .arg("answers")
-        .arg("--component")
-        .arg(&manifest_path)
-        .arg("--operation")
-        .arg("default")
-        .arg("--name")
-        .arg("empty")
-        .arg("--out-dir")
-        .arg(dir.path())
+    fs::write(
+        dir.path().join("component.manifest.json"),
+        manifest.to_string(),
+    )
+    .unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+        )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  hello:
+    op:
+      field: old
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("update-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("hello")
+        .arg("--mode")
+        .arg("config")
+        .arg("--non-interactive")
+        .arg("--write")
         .assert()
-        .success()
-        .stderr(predicates::str::contains("W_SCHEMA_EMPTY"));
+        .success();
+
+    let yaml = read_yaml(&flow_path);
+    let nodes = yaml
+        .get("nodes")
+        .and_then(Value::as_mapping)
+        .expect("nodes map");
+    let hello = nodes
+        .get(Value::from("hello"))
+        .unwrap()
+        .as_mapping()
+        .unwrap();
+    let op = hello.get(Value::from("op")).unwrap().as_mapping().unwrap();
+    assert_eq!(op.get(Value::from("field")).unwrap().as_str(), Some("old"));
+    assert_eq!(
+        hello.get(Value::from("routing")).unwrap().as_str(),
+        Some("out")
+    );
 }
 
 #[test]
-fn answers_prefers_operations_schema_when_dev_flow_questions_empty() {
+fn update_step_config_prompts_for_questions() {
     let dir = tempdir().unwrap();
-    let manifest_path = dir.path().join("component.manifest.json");
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+
+    let template = json!({
+        "node_id": "hello",
+        "node": {
+            "card": { "msg": "{{state.msg}}" },
+            "routing": [ { "to": "NEXT_NODE_PLACEHOLDER" } ]
+        }
+    });
     let manifest = json!({
-        "id": "ai.greentic.empty-with-ops",
-        "operations": [
-            {
-                "name": "default",
-                "input_schema": {
-                    "type": "object",
-                    "properties": {
-                        "foo": {
-                            "type": "string"
-                        }
-                    }
-                }
-            }
-        ],
+        "id": "ai.greentic.card",
         "dev_flows": {
-            "default": {
+            "custom": {
                 "graph": {
                     "id": "cfg",
                     "type": "component-config",
@@ -2844,33 +3179,3203 @@ fn answers_prefers_operations_schema_when_dev_flow_questions_empty() {
                     "nodes": {
                         "ask": {
                             "questions": {
-                                "fields": []
+                                "fields": [
+                                    {
+                                        "id": "msg",
+                                        "prompt": "Message?",
+                                        "type": "string"
+                                    }
+                                ]
                             },
-                            "routing": [
-                                { "to": "emit" }
-                            ]
+                            "routing": [ { "to": "emit" } ]
                         },
                         "emit": {
-                            "template": "{}"
+                            "template": serde_json::to_string(&template).unwrap()
                         }
                     }
                 }
             }
         }
     });
-    fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
+    fs::write(
+        dir.path().join("component.manifest.json"),
+        manifest.to_string(),
+    )
+    .unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  hello:
+    card: {}
+    routing: out
+"#,
+    )
+    .unwrap();
 
-    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
-        .arg("answers")
-        .arg("--component")
-        .arg(&manifest_path)
+    cargo_bin_cmd!("greentic-flow")
+        .arg("update-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("hello")
+        .arg("--mode")
+        .arg("config")
+        .write_stdin("new\n")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Question (msg):"));
+
+    let yaml = read_yaml(&flow_path);
+    let nodes = yaml.get("nodes").and_then(Value::as_mapping).unwrap();
+    let hello = nodes
+        .get(Value::from("hello"))
+        .unwrap()
+        .as_mapping()
+        .unwrap();
+    let card = hello
+        .get(Value::from("card"))
+        .unwrap()
+        .as_mapping()
+        .unwrap();
+    assert_eq!(card.get(Value::from("msg")).unwrap().as_str(), Some("new"));
+}
+
+#[test]
+fn add_step_config_requires_custom_dev_flow() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  start:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+
+    let manifest = json!({
+        "id": "ai.greentic.card",
+        "dev_flows": {
+            "default": {
+                "graph": {
+                    "id": "cfg",
+                    "type": "component-config",
+                    "nodes": {}
+                }
+            }
+        }
+    });
+    let manifest_path = dir.path().join("component.manifest.json");
+    fs::write(&manifest_path, manifest.to_string()).unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--mode")
+        .arg("config")
+        .arg("--node-id")
+        .arg("card1")
         .arg("--operation")
+        .arg("card")
+        .arg("--payload")
+        .arg(r#"{}"#)
+        .arg("--local-wasm")
+        .arg("comp.wasm")
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("dev_flows.custom"));
+}
+
+#[test]
+fn add_step_default_fixture_prompts_and_applies_answers() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let fixture_flow =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/flows/simple.ygtc");
+    fs::write(&flow_path, fs::read_to_string(&fixture_flow).unwrap()).unwrap();
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+
+    let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/manifests/component.manifest.json");
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--node-id")
+        .arg("card1")
+        .arg("--mode")
         .arg("default")
-        .arg("--name")
-        .arg("empty-with-ops")
-        .arg("--out-dir")
-        .arg(dir.path())
+        .arg("--operation")
+        .arg("card")
+        .arg("--payload")
+        .arg(r#"{}"#)
+        .arg("--local-wasm")
+        .arg("comp.wasm")
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .write_stdin("assets/cards/card.json\ny\n")
         .assert()
         .success()
-        .stderr(predicates::str::contains("E_SCHEMA_EMPTY").not());
+        .stdout(predicates::str::contains("Question (asset_path):"))
+        .stdout(predicates::str::contains("Question (needs_interaction):"));
+
+    let yaml = read_yaml(&flow_path);
+    let nodes = yaml.get("nodes").and_then(Value::as_mapping).unwrap();
+    let inserted = nodes
+        .get(Value::from("card1"))
+        .unwrap()
+        .as_mapping()
+        .unwrap();
+    let card = inserted
+        .get(Value::from("card"))
+        .unwrap()
+        .as_mapping()
+        .unwrap();
+    let card_spec = card
+        .get(Value::from("card_spec"))
+        .unwrap()
+        .as_mapping()
+        .unwrap();
+    assert_eq!(
+        card_spec.get(Value::from("asset_path")).unwrap().as_str(),
+        Some("assets/cards/card.json")
+    );
+    let interaction = card
+        .get(Value::from("interaction"))
+        .unwrap()
+        .as_mapping()
+        .unwrap();
+    assert_eq!(
+        interaction
+            .get(Value::from("enabled"))
+            .and_then(Value::as_bool),
+        Some(true)
+    );
+}
+
+#[test]
+fn update_step_non_interactive_missing_required_reports_template() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+
+    let manifest_fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/manifests/component.manifest.json");
+    fs::write(
+        dir.path().join("component.manifest.json"),
+        fs::read_to_string(&manifest_fixture).unwrap(),
+    )
+    .unwrap();
+
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"card1":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  card1:
+    card: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("update-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("card1")
+        .arg("--mode")
+        .arg("default")
+        .arg("--non-interactive")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("missing required answers"))
+        .stderr(predicates::str::contains("--answers"))
+        .stderr(predicates::str::contains("asset_path"));
+}
+
+#[test]
+fn add_step_rejects_invalid_component_scheme() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("new")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--id")
+        .arg("main")
+        .arg("--type")
+        .arg("messaging")
+        .assert()
+        .success();
+
+    fs::write(dir.path().join("comp.wasm"), b"bytes").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--mode")
+        .arg("default")
+        .arg("--node-id")
+        .arg("comp")
+        .arg("--operation")
+        .arg("run")
+        .arg("--payload")
+        .arg(r#"{}"#)
+        .arg("--component")
+        .arg("badref")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "--component must start with oci://, repo://, or store://",
+        ));
+}
+
+#[test]
+fn add_step_rejects_private_oci_host() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("new")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--id")
+        .arg("main")
+        .arg("--type")
+        .arg("messaging")
+        .assert()
+        .success();
+
+    fs::write(dir.path().join("comp.wasm"), b"bytes").unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--mode")
+        .arg("default")
+        .arg("--node-id")
+        .arg("comp")
+        .arg("--operation")
+        .arg("run")
+        .arg("--payload")
+        .arg(r#"{}"#)
+        .arg("--component")
+        .arg("oci://localhost/component:latest")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "oci:// references must use a public registry host",
+        ));
+}
+
+#[test]
+fn update_step_rejects_invalid_component_scheme() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  hello:
+    op:
+      field: old
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("update-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("hello")
+        .arg("--component")
+        .arg("badref")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "--component must start with oci://, repo://, or store://",
+        ));
+}
+
+#[test]
+fn update_step_rejects_private_oci_host() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  hello:
+    op:
+      field: old
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("update-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("hello")
+        .arg("--component")
+        .arg("oci://localhost/component:latest")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "oci:// references must use a public registry host",
+        ));
+}
+
+#[test]
+fn doctor_prunes_sidecar_to_match_flow() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  keep:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"old.ygtc","nodes":{"keep":{"source":{"kind":"local","path":"comp.wasm"}},"stale":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg(&flow_path)
+        .write_stdin("y\n")
+        .assert()
+        .success();
+
+    let sidecar: JsonValue =
+        serde_json::from_str(&fs::read_to_string(&sidecar_path).unwrap()).unwrap();
+    assert_eq!(
+        sidecar.get("flow").and_then(JsonValue::as_str),
+        flow_path.file_name().and_then(|s| s.to_str())
+    );
+    let nodes = sidecar.get("nodes").and_then(JsonValue::as_object).unwrap();
+    assert!(nodes.contains_key("keep"));
+    assert!(!nodes.contains_key("stale"));
+}
+
+#[test]
+fn doctor_reports_unused_sidecar_when_denied() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  keep:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"keep":{"source":{"kind":"local","path":"comp.wasm"}},"stale":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg(&flow_path)
+        .write_stdin("n\n")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unused sidecar entries"));
+
+    let sidecar: JsonValue =
+        serde_json::from_str(&fs::read_to_string(&sidecar_path).unwrap()).unwrap();
+    let nodes = sidecar.get("nodes").and_then(JsonValue::as_object).unwrap();
+    assert!(nodes.contains_key("stale"));
+}
+
+#[test]
+fn doctor_reports_invalid_sidecar_source() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  hello:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"oci","ref":"oci://localhost/component:latest"}}}}"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("invalid sidecar entries"));
+}
+
+#[test]
+fn doctor_reports_missing_sidecar_entries() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  hello:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{}}"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("missing sidecar entries"));
+}
+
+#[test]
+fn doctor_reports_missing_local_wasm() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  hello:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"missing.wasm"}}}}"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("invalid sidecar entries"));
+}
+
+#[test]
+fn doctor_accepts_file_uri_local_wasm() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  hello:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"file://comp.wasm"}}}}"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .current_dir(dir.path())
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .success();
+}
+
+#[test]
+fn update_step_overrides_payload_and_routing() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = flow_path.with_extension("ygtc.resolve.json");
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    fs::write(
+        &sidecar_path,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"hello":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+        )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  hello:
+    op:
+      field: old
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("update-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("hello")
+        .arg("--answers")
+        .arg(r#"{"field":"new","extra":1}"#)
+        .arg("--routing-reply")
+        .arg("--write")
+        .assert()
+        .success();
+
+    let yaml = read_yaml(&flow_path);
+    let nodes = yaml
+        .get("nodes")
+        .and_then(Value::as_mapping)
+        .expect("nodes map");
+    let hello = nodes
+        .get(Value::from("hello"))
+        .unwrap()
+        .as_mapping()
+        .unwrap();
+    let op = hello.get(Value::from("op")).unwrap().as_mapping().unwrap();
+    assert_eq!(op.get(Value::from("field")).unwrap().as_str(), Some("new"));
+    assert_eq!(
+        hello.get(Value::from("routing")).unwrap().as_str(),
+        Some("reply")
+    );
+}
+
+#[test]
+fn delete_step_splices_single_predecessor() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+    let sidecar = dir.path().join("flow.ygtc.resolve.json");
+    fs::write(
+        &sidecar,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"mid":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  a:
+    hop: {}
+    routing:
+      - to: mid
+  mid:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("delete-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("mid")
+        .arg("--write")
+        .assert()
+        .success();
+
+    let yaml = read_yaml(&flow_path);
+    let nodes = yaml
+        .get("nodes")
+        .and_then(Value::as_mapping)
+        .expect("nodes map");
+    assert!(!nodes.contains_key(Value::from("mid")));
+    let a = nodes.get(Value::from("a")).unwrap().as_mapping().unwrap();
+    if let Some(r) = a.get(Value::from("routing")) {
+        if let Some(s) = r.as_str() {
+            assert_eq!(s, "out");
+        } else if let Some(seq) = r.as_sequence() {
+            assert!(
+                seq.is_empty()
+                    || seq
+                        .iter()
+                        .any(|v| v.get("out").and_then(Value::as_bool) == Some(true))
+            );
+        }
+    }
+}
+
+#[test]
+fn delete_step_errors_on_multiple_predecessors() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  a:
+    hop: {}
+    routing:
+      - to: mid
+  b:
+    hop: {}
+    routing:
+      - to: mid
+  mid:
+    op: {}
+    routing:
+      - to: end
+  end:
+    noop: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("delete-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("mid")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn delete_step_cascade_removes_newly_unreachable_steps() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar = dir.path().join("flow.ygtc.resolve.json");
+    fs::write(
+        &sidecar,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"mid":{"source":{"kind":"local","path":"comp.wasm"}},"tail":{"source":{"kind":"local","path":"comp.wasm"}},"end":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    let original = r#"id: main
+type: messaging
+schema_version: 2
+start: a
+nodes:
+  a:
+    hop: {}
+    routing:
+      - status: skip
+        to: end
+      - to: mid
+  mid:
+    op: {}
+    routing:
+      - to: tail
+  tail:
+    op: {}
+    routing:
+      - to: end
+  end:
+    noop: {}
+    routing: out
+"#;
+    fs::write(&flow_path, original).unwrap();
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "delete-step", "--cascade", "--dry-run"])
+        .args(["--step", "mid", "--flow"])
+        .arg(&flow_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["cascade"], json!(["tail"]));
+    assert_eq!(fs::read_to_string(&flow_path).unwrap(), original);
+
+    cargo_bin_cmd!("greentic-flow")
+        .args([
+            "delete-step",
+            "--cascade",
+            "--write",
+            "--step",
+            "mid",
+            "--flow",
+        ])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stderr(contains(
+            "Also deleted 1 step(s) only reachable through 'mid': tail",
+        ));
+    let yaml = read_yaml(&flow_path);
+    let nodes = yaml.get("nodes").and_then(Value::as_mapping).unwrap();
+    let ids: Vec<&str> = nodes.keys().filter_map(Value::as_str).collect();
+    assert_eq!(ids, ["a", "end"]);
+    let sidecar: JsonValue = serde_json::from_str(&fs::read_to_string(&sidecar).unwrap()).unwrap();
+    let bound: Vec<&String> = sidecar["nodes"].as_object().unwrap().keys().collect();
+    assert_eq!(bound, ["end"]);
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["delete-step", "--cascade", "--strategy", "remove-only"])
+        .args(["--step", "end", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(contains("cannot be used with"));
+}
+
+#[test]
+fn delete_step_removes_a_chain_in_one_run() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar = dir.path().join("flow.ygtc.resolve.json");
+    fs::write(
+        &sidecar,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"b":{"source":{"kind":"local","path":"comp.wasm"}},"c":{"source":{"kind":"local","path":"comp.wasm"}},"end":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+start: a
+nodes:
+  a:
+    hop: {}
+    routing:
+      - to: b
+  b:
+    op: {}
+    routing:
+      - to: c
+  c:
+    op: {}
+    routing:
+      - to: d
+  d:
+    op: {}
+    routing:
+      - to: end
+  end:
+    noop: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["delete-step", "--write", "--step", "c", "--steps", "b,d,c"])
+        .arg("--flow")
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains("Deleted steps 'c', 'b', 'd' from"));
+
+    let flow = load_ygtc_from_path(&flow_path).unwrap();
+    let ids: Vec<&String> = flow.nodes.keys().collect();
+    assert_eq!(ids, ["a", "end"]);
+    let yaml = read_yaml(&flow_path);
+    assert_eq!(yaml["nodes"]["a"]["routing"][0]["to"], Value::from("end"));
+    let sidecar: JsonValue = serde_json::from_str(&fs::read_to_string(&sidecar).unwrap()).unwrap();
+    let bound: Vec<&String> = sidecar["nodes"].as_object().unwrap().keys().collect();
+    assert_eq!(bound, ["end"]);
+
+    // A missing step fails the whole run before anything is written.
+    let before = fs::read_to_string(&flow_path).unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["delete-step", "--write", "--steps", "end,nope", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .failure();
+    assert_eq!(fs::read_to_string(&flow_path).unwrap(), before);
+}
+
+#[test]
+fn delete_step_splice_all_predecessors() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar = dir.path().join("flow.ygtc.resolve.json");
+    fs::write(
+        &sidecar,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"mid":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  a:
+    hop: {}
+    routing:
+      - to: mid
+  b:
+    hop: {}
+    routing:
+      - to: mid
+  mid:
+    op: {}
+    routing:
+      - to: end
+  end:
+    noop: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("delete-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("mid")
+        .arg("--if-multiple-predecessors")
+        .arg("splice-all")
+        .arg("--write")
+        .assert()
+        .success();
+
+    let yaml = read_yaml(&flow_path);
+    let nodes = yaml
+        .get("nodes")
+        .and_then(Value::as_mapping)
+        .expect("nodes map");
+    assert!(!nodes.contains_key(Value::from("mid")));
+    for pred in ["a", "b"] {
+        let n = nodes.get(Value::from(pred)).unwrap().as_mapping().unwrap();
+        let routing = n.get(Value::from("routing")).unwrap();
+        if let Some(arr) = routing.as_sequence() {
+            assert_eq!(
+                arr[0].get("to").and_then(Value::as_str).expect("to target"),
+                "end"
+            );
+        } else if let Some(s) = routing.as_str() {
+            assert_eq!(s, "out");
+        } else {
+            panic!("unexpected routing shape");
+        }
+    }
+}
+
+#[test]
+fn delete_step_reroutes_or_asks_about_multiple_predecessors() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let flow = r#"id: main
+type: messaging
+schema_version: 2
+start: a
+nodes:
+  a:
+    hop: {}
+    routing:
+      - to: mid
+      - status: alt
+        to: b
+  b:
+    hop: {}
+    routing:
+      - to: mid
+  mid:
+    op: {}
+    routing:
+      - to: end
+      - status: err
+        to: b
+  end:
+    noop: {}
+    routing: out
+"#;
+    fs::write(&flow_path, flow).unwrap();
+
+    // Without a choice, --interactive previews each one and asks.
+    cargo_bin_cmd!("greentic-flow")
+        .args(["delete-step", "--interactive", "--write", "--step", "mid"])
+        .arg("--flow")
+        .arg(&flow_path)
+        .write_stdin("reroute-to:end\n")
+        .assert()
+        .success()
+        .stderr(contains("Step 'mid' has 2 predecessors: a, b"))
+        .stderr(contains(
+            r#"a -> [{"to":"end"},{"to":"b","status":"err"},{"to":"b","status":"alt"}]"#,
+        ))
+        .stderr(contains("reroute-to:b:"))
+        .stderr(contains(
+            "(options: splice-all, reroute-to:end, reroute-to:b, abort)",
+        ));
+    let yaml = read_yaml(&flow_path);
+    let routing = serde_json::to_value(&yaml["nodes"]["a"]["routing"]).unwrap();
+    assert_eq!(
+        routing,
+        json!([{"to": "end"}, {"status": "alt", "to": "b"}])
+    );
+    let routing = serde_json::to_value(&yaml["nodes"]["b"]["routing"]).unwrap();
+    assert_eq!(routing, json!([{"to": "end"}]));
+
+    fs::write(&flow_path, flow).unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["delete-step", "--interactive", "--write", "--step", "mid"])
+        .arg("--flow")
+        .arg(&flow_path)
+        .write_stdin("abort\n")
+        .assert()
+        .failure()
+        .stderr(contains("delete-step cancelled: 'mid' was kept"));
+    assert_eq!(fs::read_to_string(&flow_path).unwrap(), flow);
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["delete-step", "--write", "--step", "mid"])
+        .args(["--if-multiple-predecessors", "reroute-to:b", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .success();
+    let yaml = read_yaml(&flow_path);
+    let routing = serde_json::to_value(&yaml["nodes"]["b"]["routing"]).unwrap();
+    assert_eq!(routing, json!([{"to": "b"}]));
+
+    fs::write(&flow_path, flow).unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["delete-step", "--step", "mid", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(contains("reroute-to:<node> points them at another step"));
+}
+
+#[test]
+fn delete_step_removes_sidecar_mapping() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar = flow_path.with_extension("ygtc.resolve.json");
+    fs::write(
+        &sidecar,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"mid":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  a:
+    hop: {}
+    routing:
+      - to: mid
+  mid:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("delete-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("mid")
+        .arg("--write")
+        .assert()
+        .success();
+
+    let sidecar_json: JsonValue =
+        serde_json::from_str(&fs::read_to_string(&sidecar).unwrap()).unwrap();
+    assert!(
+        !sidecar_json
+            .get("nodes")
+            .and_then(JsonValue::as_object)
+            .unwrap()
+            .contains_key("mid")
+    );
+}
+
+#[test]
+fn add_step_rejects_empty_manifest_schema_by_default() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    cargo_bin_cmd!("greentic-flow")
+        .arg("new")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--id")
+        .arg("main")
+        .arg("--type")
+        .arg("messaging")
+        .assert()
+        .success();
+
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+
+    fs::write(
+        dir.path().join("component.manifest.json"),
+        r#"{"id":"ai.greentic.empty","operations":[{"name":"run","input_schema":{}}]}"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--mode")
+        .arg("default")
+        .arg("--node-id")
+        .arg("empty")
+        .arg("--operation")
+        .arg("run")
+        .arg("--payload")
+        .arg("{}")
+        .arg("--routing-out")
+        .arg("--local-wasm")
+        .arg("comp.wasm")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("E_SCHEMA_EMPTY"));
+}
+
+#[test]
+fn add_step_warns_on_empty_manifest_schema_with_permissive_flag() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    cargo_bin_cmd!("greentic-flow")
+        .arg("new")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--id")
+        .arg("main")
+        .arg("--type")
+        .arg("messaging")
+        .assert()
+        .success();
+
+    let wasm_path = dir.path().join("comp.wasm");
+    fs::write(&wasm_path, b"wasm-bytes").unwrap();
+
+    fs::write(
+        dir.path().join("component.manifest.json"),
+        r#"{"id":"ai.greentic.empty","operations":[{"name":"run","input_schema":{}}]}"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("--permissive")
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--mode")
+        .arg("default")
+        .arg("--node-id")
+        .arg("empty")
+        .arg("--operation")
+        .arg("run")
+        .arg("--payload")
+        .arg("{}")
+        .arg("--routing-out")
+        .arg("--local-wasm")
+        .arg("comp.wasm")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("W_SCHEMA_EMPTY"));
+}
+
+#[test]
+fn answers_error_on_empty_question_graph_by_default() {
+    let dir = tempdir().unwrap();
+    let manifest_path = dir.path().join("component.manifest.json");
+    let manifest = json!({
+        "id": "ai.greentic.empty",
+        "dev_flows": {
+            "default": {
+                "graph": {
+                    "id": "cfg",
+                    "type": "component-config",
+                    "start": "ask",
+                    "nodes": {
+                        "ask": {
+                            "template": "{}"
+                        }
+                    }
+                }
+            }
+        }
+    });
+    fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("answers")
+        .arg("--component")
+        .arg(&manifest_path)
+        .arg("--operation")
+        .arg("default")
+        .arg("--name")
+        .arg("empty")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("E_SCHEMA_EMPTY"));
+}
+
+#[test]
+fn answers_warns_on_empty_question_graph_with_permissive() {
+    let dir = tempdir().unwrap();
+    let manifest_path = dir.path().join("component.manifest.json");
+    let manifest = json!({
+        "id": "ai.greentic.empty",
+        "dev_flows": {
+            "default": {
+                "graph": {
+                    "id": "cfg",
+                    "type": "component-config",
+                    "start": "ask",
+                    "nodes": {
+                        "ask": {
+                            "template": "{}"
+                        }
+                    }
+                }
+            }
+        }
+    });
+    fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("--permissive")
+        .arg("answers")
+        .arg("--component")
+        .arg(&manifest_path)
+        .arg("--operation")
+        .arg("default")
+        .arg("--name")
+        .arg("empty")
+        .arg("--out-dir")
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("W_SCHEMA_EMPTY"));
+}
+
+#[test]
+fn answers_prefers_operations_schema_when_dev_flow_questions_empty() {
+    let dir = tempdir().unwrap();
+    let manifest_path = dir.path().join("component.manifest.json");
+    let manifest = json!({
+        "id": "ai.greentic.empty-with-ops",
+        "operations": [
+            {
+                "name": "default",
+                "input_schema": {
+                    "type": "object",
+                    "properties": {
+                        "foo": {
+                            "type": "string"
+                        }
+                    }
+                }
+            }
+        ],
+        "dev_flows": {
+            "default": {
+                "graph": {
+                    "id": "cfg",
+                    "type": "component-config",
+                    "start": "ask",
+                    "nodes": {
+                        "ask": {
+                            "questions": {
+                                "fields": []
+                            },
+                            "routing": [
+                                { "to": "emit" }
+                            ]
+                        },
+                        "emit": {
+                            "template": "{}"
+                        }
+                    }
+                }
+            }
+        }
+    });
+    fs::write(&manifest_path, serde_json::to_string(&manifest).unwrap()).unwrap();
+
+    Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .arg("answers")
+        .arg("--component")
+        .arg(&manifest_path)
+        .arg("--operation")
+        .arg("default")
+        .arg("--name")
+        .arg("empty-with-ops")
+        .arg("--out-dir")
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("E_SCHEMA_EMPTY").not());
+}
+
+#[test]
+fn generate_chains_pipeline_steps_into_new_flow() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    let spec_path = dir.path().join("pipeline.yaml");
+    fs::write(
+        &spec_path,
+        r#"flow: flows/main.ygtc
+id: main
+type: messaging
+steps:
+  - node_id: first
+    operation: handle_message
+    payload: { text: "hi" }
+    local_wasm: comp.wasm
+  - node_id: second
+    operation: handle_message
+    payload: { text: "bye" }
+    local_wasm: comp.wasm
+"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("generate")
+        .arg("--spec")
+        .arg(&spec_path)
+        .assert()
+        .success()
+        .stdout(contains("Generated flow 'main' with 2 step(s)"));
+
+    let flow_path = dir.path().join("flows/main.ygtc");
+    let yaml = read_yaml(&flow_path);
+    let nodes = yaml.get("nodes").and_then(Value::as_mapping).unwrap();
+    let first = nodes.get(Value::from("first")).unwrap();
+    let routing = serde_json::to_value(first.get(Value::from("routing")).unwrap()).unwrap();
+    assert_eq!(routing, json!([{"to": "second"}]));
+    let sidecar: JsonValue = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("flows/main.ygtc.resolve.json")).unwrap(),
+    )
+    .unwrap();
+    assert!(sidecar["nodes"]["second"].is_object());
+
+    // With --format json, stdout holds the generate payload and nothing else.
+    let output = cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["--format", "json", "generate", "--force", "--spec"])
+        .arg(&spec_path)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let payload: JsonValue = serde_json::from_slice(&output).unwrap();
+    assert_eq!(payload["action"], "generate");
+    assert_eq!(payload["nodes"], json!(["first", "second"]));
+}
+
+#[test]
+fn generate_output_does_not_depend_on_jobs() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    let spec_path = dir.path().join("pipeline.yaml");
+    fs::write(
+        &spec_path,
+        r#"id: main
+type: messaging
+steps:
+  - node_id: first
+    operation: handle_message
+    payload: { text: "hi" }
+    local_wasm: comp.wasm
+  - node_id: second
+    operation: handle_message
+    payload: { text: "bye" }
+    local_wasm: comp.wasm
+"#,
+    )
+    .unwrap();
+    let mut outputs = Vec::new();
+    for jobs in ["1", "4"] {
+        let flow_path = dir.path().join(format!("jobs-{jobs}.ygtc"));
+        cargo_bin_cmd!("greentic-flow")
+            .current_dir(dir.path())
+            .args(["generate", "--jobs", jobs, "--spec"])
+            .arg(&spec_path)
+            .arg("--flow")
+            .arg(&flow_path)
+            .assert()
+            .success();
+        outputs.push(fs::read_to_string(&flow_path).unwrap());
+    }
+    assert_eq!(outputs[0], outputs[1]);
+
+    // Wizard steps whose prefetch fails still report the error from their own add-step.
+    fs::write(
+        &spec_path,
+        r#"flow: wizard.ygtc
+id: main
+type: messaging
+steps:
+  - node_id: first
+    local_wasm: comp.wasm
+    answers: { text: "hi" }
+  - node_id: second
+    local_wasm: comp.wasm
+    answers: { text: "bye" }
+"#,
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["generate", "--jobs", "4", "--spec"])
+        .arg(&spec_path)
+        .assert()
+        .failure()
+        .stderr(contains("generate step #0"));
+    assert!(!dir.path().join("wizard.ygtc").exists());
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["generate", "--jobs", "0", "--spec"])
+        .arg(&spec_path)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn generate_failure_leaves_no_partial_flow() {
+    let dir = tempdir().unwrap();
+    let spec_path = dir.path().join("pipeline.yaml");
+    fs::write(
+        &spec_path,
+        r#"flow: main.ygtc
+id: main
+type: messaging
+steps:
+  - node_id: first
+    operation: handle_message
+    local_wasm: missing.wasm
+"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("generate")
+        .arg("--spec")
+        .arg(&spec_path)
+        .assert()
+        .failure();
+    assert!(!dir.path().join("main.ygtc").exists());
+}
+
+#[test]
+fn generate_failure_restores_every_file_the_steps_wrote() {
+    fn tree(dir: &Path, out: &mut BTreeMap<PathBuf, Vec<u8>>) {
+        for entry in fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                tree(&path, out);
+            } else {
+                out.insert(path.clone(), fs::read(&path).unwrap());
+            }
+        }
+    }
+
+    let dir = tempdir().unwrap();
+    let fixture_dir = dir.path().join("fixtures");
+    write_widget_fixture(
+        &fixture_dir,
+        "oci://acme/widget:1",
+        Vec::new(),
+        json!({"channel": "general"}),
+    );
+    // The first step runs the widget's wizard, storing its config, answers and wizard state;
+    // the second cannot resolve its component.
+    let failing_spec = dir.path().join("pipeline.yaml");
+    fs::write(
+        &failing_spec,
+        format!(
+            r#"flow: flows/main.ygtc
+id: main
+type: messaging
+resolver: fixture://{}
+steps:
+  - node_id: widget
+    component: oci://acme/widget:1
+  - node_id: second
+    operation: handle_message
+    local_wasm: missing.wasm
+"#,
+            fixture_dir.display()
+        ),
+    )
+    .unwrap();
+    let mut before = BTreeMap::new();
+    tree(dir.path(), &mut before);
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["generate", "--spec"])
+        .arg(&failing_spec)
+        .assert()
+        .failure();
+
+    let mut after = BTreeMap::new();
+    tree(dir.path(), &mut after);
+    assert_eq!(
+        after.keys().collect::<Vec<_>>(),
+        before.keys().collect::<Vec<_>>()
+    );
+    for (path, bytes) in &before {
+        assert_eq!(&after[path], bytes, "{} changed", path.display());
+    }
+}
+
+#[test]
+fn add_step_defaults_from_seeds_answers_from_sibling() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  card0:
+    card:
+      msg: from sibling
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    let manifest = json!({
+        "id": "ai.greentic.card",
+        "dev_flows": {
+            "default": {
+                "graph": {
+                    "id": "cfg",
+                    "type": "component-config",
+                    "start": "ask",
+                    "nodes": {
+                        "ask": {
+                            "questions": {
+                                "fields": [
+                                    {
+                                        "id": "msg",
+                                        "prompt": "Message?",
+                                        "type": "string",
+                                        "writes_to": "msg"
+                                    }
+                                ]
+                            },
+                            "routing": "out"
+                        }
+                    }
+                }
+            }
+        }
+    });
+    let manifest_path = dir.path().join("component.manifest.json");
+    fs::write(&manifest_path, manifest.to_string()).unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--node-id")
+        .arg("card1")
+        .arg("--operation")
+        .arg("card")
+        .arg("--local-wasm")
+        .arg("comp.wasm")
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--defaults-from")
+        .arg("card0")
+        .assert()
+        .success()
+        .stdout(contains("Question (msg):").not());
+
+    let yaml = read_yaml(&flow_path);
+    let nodes = yaml.get("nodes").and_then(Value::as_mapping).unwrap();
+    let card = nodes
+        .get(Value::from("card1"))
+        .and_then(|node| node.get(Value::from("card")))
+        .unwrap();
+    assert_eq!(
+        card.get(Value::from("msg")).and_then(Value::as_str),
+        Some("from sibling")
+    );
+}
+
+#[test]
+fn preset_save_and_apply_on_add_step() {
+    let dir = tempdir().unwrap();
+    let flows_dir = dir.path().join("flows");
+    fs::create_dir_all(&flows_dir).unwrap();
+    let flow_path = flows_dir.join("main.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  card0:
+    card:
+      msg: from preset
+      channel: general
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("preset")
+        .arg("save")
+        .arg("slack-default")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--from-step")
+        .arg("card0")
+        .assert()
+        .success();
+    let preset: JsonValue = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("presets/slack-default.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(preset, json!({"msg": "from preset", "channel": "general"}));
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--node-id")
+        .arg("card1")
+        .arg("--operation")
+        .arg("card")
+        .arg("--local-wasm")
+        .arg("comp.wasm")
+        .arg("--preset")
+        .arg("slack-default")
+        .arg("--answers")
+        .arg(r#"{"channel":"alerts"}"#)
+        .assert()
+        .success();
+
+    let yaml = read_yaml(&flow_path);
+    let card = yaml
+        .get("nodes")
+        .and_then(|nodes| nodes.get(Value::from("card1")))
+        .and_then(|node| node.get(Value::from("card")))
+        .unwrap();
+    assert_eq!(
+        serde_json::to_value(card).unwrap(),
+        json!({"msg": "from preset", "channel": "alerts"})
+    );
+}
+
+fn write_widget_fixture(
+    fixture_dir: &Path,
+    reference: &str,
+    questions: Vec<greentic_types::schemas::component::v0_6_0::Question>,
+    config: JsonValue,
+) {
+    fs::create_dir_all(fixture_dir).unwrap();
+    let key = reference
+        .trim_start_matches("oci://")
+        .replace(['/', ':', '@'], "_");
+    let schema = SchemaIr::Object {
+        properties: BTreeMap::new(),
+        required: Vec::new(),
+        additional: AdditionalProperties::Allow,
+    };
+    let describe = ComponentDescribe {
+        info: ComponentInfo {
+            id: "acme.widget".to_string(),
+            version: "0.1.0".to_string(),
+            role: "tool".to_string(),
+            display_name: None,
+        },
+        provided_capabilities: Vec::new(),
+        required_capabilities: Vec::new(),
+        metadata: BTreeMap::new(),
+        operations: vec![ComponentOperation {
+            id: "run".to_string(),
+            display_name: None,
+            input: ComponentRunInput {
+                schema: schema.clone(),
+            },
+            output: ComponentRunOutput {
+                schema: schema.clone(),
+            },
+            defaults: BTreeMap::new(),
+            redactions: Vec::new(),
+            constraints: BTreeMap::new(),
+            schema_hash: schema_hash(&schema, &schema, &schema).unwrap(),
+        }],
+        config_schema: schema,
+    };
+    fs::write(
+        fixture_dir.join(format!("{key}.describe.cbor")),
+        canonical::to_canonical_cbor_allow_floats(&describe).unwrap(),
+    )
+    .unwrap();
+    let defaults = questions
+        .iter()
+        .filter_map(|q| q.default.clone().map(|value| (q.id.clone(), value)))
+        .collect();
+    let spec = ComponentQaSpec {
+        mode: QaMode::Default,
+        title: I18nText::new("title", Some("Fixture Wizard".to_string())),
+        description: None,
+        questions,
+        defaults,
+    };
+    fs::write(
+        fixture_dir.join(format!("{key}.qa-spec.cbor")),
+        canonical::to_canonical_cbor(&spec).unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        fixture_dir.join(format!("{key}.apply-answers.cbor")),
+        canonical::to_canonical_cbor(&config).unwrap(),
+    )
+    .unwrap();
+    fs::write(fixture_dir.join(format!("{key}.abi")), "0.6.0").unwrap();
+}
+
+#[test]
+fn add_step_wizard_records_answer_provenance() {
+    use greentic_types::schemas::component::v0_6_0::{Question as QaQuestion, QuestionKind};
+
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    cargo_bin_cmd!("greentic-flow")
+        .arg("new")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--id")
+        .arg("main")
+        .arg("--type")
+        .arg("messaging")
+        .assert()
+        .success();
+    let fixture_dir = dir.path().join("fixtures");
+    let question = |id: &str, required: bool, default: Option<ciborium::value::Value>| QaQuestion {
+        id: id.to_string(),
+        label: I18nText::new(id, Some(id.to_string())),
+        help: None,
+        error: None,
+        kind: QuestionKind::Text,
+        required,
+        default,
+    };
+    write_widget_fixture(
+        &fixture_dir,
+        "oci://acme/widget:1",
+        vec![
+            question("channel", false, Some("general".into())),
+            question("api_token", true, None),
+        ],
+        json!({"channel": "general"}),
+    );
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--node-id")
+        .arg("widget")
+        .arg("--component")
+        .arg("oci://acme/widget:1")
+        .arg("--wizard-mode")
+        .arg("default")
+        .arg("--routing-out")
+        .arg("--answers")
+        .arg(r#"{"api_token":"s3cr3t"}"#)
+        .arg("--resolver")
+        .arg(format!("fixture://{}", fixture_dir.display()))
+        .env("GREENTIC_FLOW_ANSWER_CHANNEL", "ops")
+        .assert()
+        .success();
+
+    let yaml = read_yaml(&flow_path);
+    assert!(
+        yaml.get("meta")
+            .and_then(|meta| meta.get(Value::from("greentic")))
+            .and_then(|greentic| greentic.get(Value::from("answers_provenance")))
+            .is_none()
+    );
+    let sidecar_path = dir.path().join("flow.ygtc.resolve.json");
+    let sidecar_raw = fs::read_to_string(&sidecar_path).unwrap();
+    let sidecar: serde_json::Value = serde_json::from_str(&sidecar_raw).unwrap();
+    let provenance = &sidecar["answers_provenance"]["nodes"]["widget"];
+    assert_eq!(
+        provenance["channel"],
+        json!({"source": "env", "value": "ops"})
+    );
+    assert_eq!(provenance["api_token"]["source"], json!("user"));
+    assert!(provenance["api_token"].get("value").is_none());
+    let hash = provenance["api_token"]["value_hash"].as_str().unwrap();
+    assert_ne!(hash, blake3::hash(br#""s3cr3t""#).to_hex().as_str());
+    assert!(sidecar["answers_provenance"]["salt"].is_string());
+    assert!(!sidecar_raw.contains("s3cr3t"));
+    assert!(!fs::read_to_string(&flow_path).unwrap().contains("s3cr3t"));
+    greentic_types::flow_resolve::read_flow_resolve(&sidecar_path).unwrap();
+}
+
+#[test]
+fn doctor_flags_config_drift_from_stored_wizard_config() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    cargo_bin_cmd!("greentic-flow")
+        .arg("new")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--id")
+        .arg("main")
+        .arg("--type")
+        .arg("messaging")
+        .assert()
+        .success();
+    let fixture_dir = dir.path().join("fixtures");
+    write_widget_fixture(
+        &fixture_dir,
+        "oci://acme/widget:1",
+        Vec::new(),
+        json!({"channel": "general"}),
+    );
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--node-id")
+        .arg("widget")
+        .arg("--component")
+        .arg("oci://acme/widget:1")
+        .arg("--wizard-mode")
+        .arg("default")
+        .arg("--routing-out")
+        .arg("--resolver")
+        .arg(format!("fixture://{}", fixture_dir.display()))
+        .assert()
+        .success();
+    let stored_path = dir.path().join("flow.ygtc.config/widget.cbor");
+    assert!(stored_path.exists());
+
+    let edited = fs::read_to_string(&flow_path)
+        .unwrap()
+        .replace("general", "random");
+    fs::write(&flow_path, edited).unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(contains("FLOW_CONFIG_DRIFT").and(contains("keys: channel")));
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--accept-drift")
+        .arg(&flow_path)
+        .assert()
+        .stderr(contains("FLOW_CONFIG_DRIFT_ACCEPTED"));
+    let stored: ciborium::value::Value =
+        ciborium::de::from_reader(fs::read(&stored_path).unwrap().as_slice()).unwrap();
+    assert_eq!(
+        stored,
+        ciborium::value::Value::Map(vec![("channel".into(), "random".into())])
+    );
+}
+
+#[test]
+fn component_setup_writes_config_without_a_flow() {
+    let dir = tempdir().unwrap();
+    let fixture_dir = dir.path().join("fixtures");
+    write_widget_fixture(
+        &fixture_dir,
+        "oci://acme/widget:1",
+        Vec::new(),
+        json!({"channel": "general"}),
+    );
+    let resolver = format!("fixture://{}", fixture_dir.display());
+    let cbor_out = dir.path().join("provisioned/widget.cbor");
+    cargo_bin_cmd!("greentic-flow")
+        .arg("component")
+        .arg("setup")
+        .arg("--component")
+        .arg("oci://acme/widget:1")
+        .arg("--resolver")
+        .arg(&resolver)
+        .arg("--out")
+        .arg(&cbor_out)
+        .assert()
+        .success()
+        .stdout(contains("Wrote oci://acme/widget:1 setup config"));
+    let stored: ciborium::value::Value =
+        ciborium::de::from_reader(fs::read(&cbor_out).unwrap().as_slice()).unwrap();
+    assert_eq!(
+        stored,
+        ciborium::value::Value::Map(vec![("channel".into(), "general".into())])
+    );
+
+    let json_out = dir.path().join("widget.json");
+    cargo_bin_cmd!("greentic-flow")
+        .arg("component")
+        .arg("setup")
+        .arg("--component")
+        .arg("oci://acme/widget:1")
+        .arg("--resolver")
+        .arg(&resolver)
+        .arg("--out")
+        .arg(&json_out)
+        .assert()
+        .success();
+    let written: JsonValue = serde_json::from_str(&fs::read_to_string(&json_out).unwrap()).unwrap();
+    assert_eq!(written, json!({"channel": "general"}));
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("component")
+        .arg("setup")
+        .arg("--component")
+        .arg("oci://acme/widget:1")
+        .arg("--resolver")
+        .arg(&resolver)
+        .arg("--out")
+        .arg(&json_out)
+        .assert()
+        .failure()
+        .stderr(contains("--force"));
+}
+
+#[test]
+fn delete_step_run_remove_reports_teardown_output() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    cargo_bin_cmd!("greentic-flow")
+        .arg("new")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--id")
+        .arg("main")
+        .arg("--type")
+        .arg("messaging")
+        .assert()
+        .success();
+    let fixture_dir = dir.path().join("fixtures");
+    write_widget_fixture(
+        &fixture_dir,
+        "oci://acme/widget:1",
+        Vec::new(),
+        json!({"channel": "general"}),
+    );
+    fs::write(
+        fixture_dir.join("acme_widget_1.apply-remove-config.cbor"),
+        canonical::to_canonical_cbor(&json!({"webhook_revoked": true})).unwrap(),
+    )
+    .unwrap();
+    let resolver = format!("fixture://{}", fixture_dir.display());
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--node-id")
+        .arg("widget")
+        .arg("--component")
+        .arg("oci://acme/widget:1")
+        .arg("--wizard-mode")
+        .arg("default")
+        .arg("--routing-out")
+        .arg("--resolver")
+        .arg(&resolver)
+        .assert()
+        .success();
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .arg("--format")
+        .arg("json")
+        .arg("delete-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("widget")
+        .arg("--run-remove")
+        .arg("--resolver")
+        .arg(&resolver)
+        .arg("--write")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["teardown"], json!({"webhook_revoked": true}));
+    let yaml = fs::read_to_string(&flow_path).unwrap();
+    assert!(!yaml.contains("widget"), "{yaml}");
+}
+
+#[test]
+fn update_step_rename_moves_the_step_with_its_records() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+start: greet
+nodes:
+  greet:
+    hop: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    let fixture_dir = dir.path().join("fixtures");
+    write_widget_fixture(
+        &fixture_dir,
+        "oci://acme/widget:1",
+        Vec::new(),
+        json!({"channel": "general"}),
+    );
+    let resolver = format!("fixture://{}", fixture_dir.display());
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["add-step", "--after", "greet", "--node-id", "widget"])
+        .args([
+            "--component",
+            "oci://acme/widget:1",
+            "--wizard-mode",
+            "default",
+        ])
+        .arg("--resolver")
+        .arg(&resolver)
+        .arg("--flow")
+        .arg(&flow_path)
+        .assert()
+        .success();
+
+    // An id that is taken fails before the wizard runs.
+    cargo_bin_cmd!("greentic-flow")
+        .args(["update-step", "--step", "widget", "--rename", "greet"])
+        .args(["--wizard-mode", "update", "--resolver"])
+        .arg(&resolver)
+        .arg("--flow")
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(contains("node 'greet' already exists"));
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "update-step", "--step", "widget"])
+        .args(["--rename", "gadget", "--wizard-mode", "update"])
+        .args(["--component", "oci://acme/widget:1"])
+        .arg("--resolver")
+        .arg(&resolver)
+        .arg("--flow")
+        .arg(&flow_path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["node_id"], "gadget");
+    assert_eq!(payload["renamed_from"], "widget");
+
+    let yaml = read_yaml(&flow_path);
+    assert!(yaml["nodes"].get("widget").is_none());
+    assert!(yaml["nodes"].get("gadget").is_some());
+    let routing = serde_json::to_value(&yaml["nodes"]["greet"]["routing"]).unwrap();
+    assert_eq!(routing, json!([{"to": "gadget"}]));
+    let sidecar: JsonValue = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("flow.ygtc.resolve.json")).unwrap(),
+    )
+    .unwrap();
+    let bound: Vec<&String> = sidecar["nodes"].as_object().unwrap().keys().collect();
+    assert_eq!(bound, ["gadget"]);
+    let config_dir = dir.path().join("flow.ygtc.config");
+    assert!(config_dir.join("gadget.cbor").is_file());
+    assert!(!config_dir.join("widget.cbor").exists());
+
+    // Payload-only updates rename too; the sidecar entry and stored config follow.
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    fs::write(
+        dir.path().join("flow.ygtc.resolve.json"),
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"gadget":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["update-step", "--step", "gadget", "--rename", "widget"])
+        .args(["--answers", r#"{"channel":"random"}"#])
+        .arg("--flow")
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains("Updated step 'gadget' and renamed it to 'widget'"));
+    let yaml = read_yaml(&flow_path);
+    let routing = serde_json::to_value(&yaml["nodes"]["greet"]["routing"]).unwrap();
+    assert_eq!(routing, json!([{"to": "widget"}]));
+    let sidecar: JsonValue = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("flow.ygtc.resolve.json")).unwrap(),
+    )
+    .unwrap();
+    let bound: Vec<&String> = sidecar["nodes"].as_object().unwrap().keys().collect();
+    assert_eq!(bound, ["widget"]);
+    assert!(config_dir.join("widget.cbor").is_file());
+}
+
+#[test]
+fn delete_step_removes_stored_wizard_config() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    cargo_bin_cmd!("greentic-flow")
+        .arg("new")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--id")
+        .arg("main")
+        .arg("--type")
+        .arg("messaging")
+        .assert()
+        .success();
+    let fixture_dir = dir.path().join("fixtures");
+    write_widget_fixture(
+        &fixture_dir,
+        "oci://acme/widget:1",
+        Vec::new(),
+        json!({"channel": "general"}),
+    );
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .arg("add-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--node-id")
+        .arg("widget")
+        .arg("--component")
+        .arg("oci://acme/widget:1")
+        .arg("--wizard-mode")
+        .arg("default")
+        .arg("--routing-out")
+        .arg("--resolver")
+        .arg(format!("fixture://{}", fixture_dir.display()))
+        .assert()
+        .success();
+    let config_dir = dir.path().join("flow.ygtc.config");
+    assert!(config_dir.join("widget.cbor").exists());
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("delete-step")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--step")
+        .arg("widget")
+        .arg("--write")
+        .assert()
+        .success();
+    assert!(!config_dir.exists());
+}
+
+#[test]
+fn minimize_writes_smallest_flow_that_still_fails_lint() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("big.ygtc");
+    let out_path = dir.path().join("repro.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: big
+type: messaging
+start: missing
+nodes:
+  a:
+    qa.process: {}
+    routing:
+      - to: b
+  b:
+    qa.process: {}
+  c:
+    qa.process: {}
+"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("minimize")
+        .arg("--flow")
+        .arg(&flow_path)
+        .arg("--check")
+        .arg("lint:start_node_exists")
+        .arg("--out")
+        .arg(&out_path)
+        .assert()
+        .success()
+        .stderr(contains("3 -> 0 node(s), 1 -> 0 route(s)"));
+
+    let doc = read_yaml(&out_path);
+    assert_eq!(doc["start"].as_str(), Some("missing"));
+    assert!(doc["nodes"].as_mapping().unwrap().is_empty());
+}
+
+#[test]
+fn generate_random_writes_reproducible_valid_flow() {
+    let dir = tempdir().unwrap();
+    let out_path = dir.path().join("random.ygtc");
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["generate-random", "--seed", "9", "--nodes", "12", "--out"])
+        .arg(&out_path)
+        .assert()
+        .success();
+    load_ygtc_from_path(&out_path).unwrap();
+
+    let stdout = cargo_bin_cmd!("greentic-flow")
+        .args(["generate-random", "--seed", "9", "--nodes", "12"])
+        .output()
+        .unwrap()
+        .stdout;
+    assert_eq!(
+        String::from_utf8(stdout).unwrap(),
+        fs::read_to_string(&out_path).unwrap()
+    );
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["generate-random", "--branchiness", "2"])
+        .assert()
+        .failure()
+        .stderr(contains("--branchiness"));
+}
+
+#[test]
+fn doctor_flags_nodes_whose_manifest_schema_hash_changed() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let manifest_path = dir.path().join("component.manifest.json");
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    let write_manifest = |schema_hash: &str| {
+        fs::write(
+            &manifest_path,
+            json!({
+                "id": "ai.greentic.pay",
+                "operations": [{
+                    "name": "charge",
+                    "schema_hash": schema_hash,
+                    "input_schema": {"type": "object", "properties": {"amount": {"type": "integer"}}}
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+    };
+    write_manifest("hash-v1");
+    cargo_bin_cmd!("greentic-flow")
+        .args(["new", "--flow"])
+        .arg(&flow_path)
+        .args(["--id", "main", "--type", "messaging"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["add-step", "--flow"])
+        .arg(&flow_path)
+        .args([
+            "--mode",
+            "default",
+            "--node-id",
+            "pay",
+            "--operation",
+            "charge",
+        ])
+        .args([
+            "--payload",
+            r#"{"amount":5}"#,
+            "--local-wasm",
+            "comp.wasm",
+            "--routing-out",
+        ])
+        .assert()
+        .success();
+    let yaml = read_yaml(&flow_path);
+    assert_eq!(
+        yaml["meta"]["greentic"]["manifest_schema_hashes"]["pay"].as_str(),
+        Some("hash-v1")
+    );
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stderr(contains("schema_hash_drift").not());
+
+    // The payload still validates, but the schema it was written against is gone.
+    write_manifest("hash-v2");
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stderr(contains("schema_hash_drift").and(contains("hash-v1")));
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["update-step", "--flow"])
+        .arg(&flow_path)
+        .args(["--step", "pay", "--non-interactive"])
+        .assert()
+        .success();
+    assert_eq!(
+        read_yaml(&flow_path)["meta"]["greentic"]["manifest_schema_hashes"]["pay"].as_str(),
+        Some("hash-v2")
+    );
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stderr(contains("schema_hash_drift").not());
+}
+
+#[test]
+fn dry_run_prints_unified_diffs_without_writing() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar_path = dir.path().join("flow.ygtc.resolve.json");
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    fs::write(
+        dir.path().join("component.manifest.json"),
+        json!({
+            "id": "ai.greentic.pay",
+            "operations": [{"name": "charge", "input_schema": {"type": "object", "properties": {"amount": {"type": "integer"}}}}]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["new", "--flow"])
+        .arg(&flow_path)
+        .args(["--id", "main", "--type", "messaging"])
+        .assert()
+        .success();
+    let snapshot = || {
+        (
+            fs::read_to_string(&flow_path).unwrap(),
+            fs::read_to_string(&sidecar_path).ok(),
+        )
+    };
+    let add_step = |dry_run: bool| {
+        let mut cmd = cargo_bin_cmd!("greentic-flow");
+        cmd.current_dir(dir.path())
+            .args(["add-step", "--flow"])
+            .arg(&flow_path)
+            .args([
+                "--mode",
+                "default",
+                "--node-id",
+                "pay",
+                "--operation",
+                "charge",
+            ])
+            .args(["--payload", r#"{"amount":5}"#, "--local-wasm", "comp.wasm"])
+            .arg("--routing-out");
+        if dry_run {
+            cmd.arg("--dry-run");
+        }
+        cmd.assert().success()
+    };
+
+    let before = snapshot();
+    let flow_label = flow_path.display().to_string();
+    add_step(true).stdout(
+        contains(format!("--- {flow_label}\n+++ {flow_label}\n"))
+            .and(contains("+  pay:\n"))
+            .and(contains("+++ ").count(2))
+            .and(contains(r#"+    "pay": {"#)),
+    );
+    assert_eq!(snapshot(), before, "add-step --dry-run must not write");
+
+    add_step(false);
+    let before = snapshot();
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["update-step", "--flow"])
+        .arg(&flow_path)
+        .args(["--step", "pay", "--non-interactive", "--dry-run"])
+        .args(["--answers", r#"{"amount":7}"#])
+        .assert()
+        .success()
+        .stdout(contains("-      amount: 5\n").and(contains("+      amount: 7\n")));
+    cargo_bin_cmd!("greentic-flow")
+        .args(["update", "--flow"])
+        .arg(&flow_path)
+        .args(["--name", "Checkout", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(contains("+title: Checkout\n"));
+    cargo_bin_cmd!("greentic-flow")
+        .args(["update", "--flow"])
+        .arg(&flow_path)
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout("no changes\n");
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "delete-step", "--flow"])
+        .arg(&flow_path)
+        .args(["--step", "pay", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["dry_run"], json!(true));
+    let diff = payload["diff"].as_str().unwrap();
+    assert!(diff.contains("-  pay:\n"), "{diff}");
+    assert!(diff.contains(r#"-    "pay": {"#), "{diff}");
+    assert_eq!(snapshot(), before, "dry runs must not write");
+}
+
+#[test]
+fn component_diff_schema_lists_nodes_that_break() {
+    let dir = tempdir().unwrap();
+    let flows = dir.path().join("flows");
+    fs::create_dir_all(&flows).unwrap();
+    fs::write(flows.join("comp.wasm"), b"wasm-bytes").unwrap();
+    let old = json!({
+        "id": "ai.greentic.pay",
+        "operations": [{"name": "charge", "input_schema": {"type": "object", "properties": {
+            "amount": {"type": "number"}, "currency": {"type": "string"}
+        }}}]
+    });
+    let new = json!({
+        "id": "ai.greentic.pay",
+        "operations": [{"name": "charge", "input_schema": {
+            "type": "object",
+            "required": ["currency"],
+            "properties": {"amount": {"type": "integer"}, "currency": {"type": "string"}}
+        }}]
+    });
+    fs::write(flows.join("component.manifest.json"), old.to_string()).unwrap();
+    let new_path = dir.path().join("new.manifest.json");
+    fs::write(&new_path, new.to_string()).unwrap();
+    fs::write(
+        flows.join("main.ygtc"),
+        "id: main\ntype: messaging\nschema_version: 2\nstart: ok\nnodes:\n  ok:\n    charge:\n      amount: 5\n      currency: EUR\n    routing:\n      - to: bad\n  bad:\n    charge:\n      amount: 2.5\n    routing: out\n",
+    )
+    .unwrap();
+    fs::write(
+        flows.join("main.ygtc.resolve.json"),
+        r#"{"schema_version":1,"flow":"main.ygtc","nodes":{"ok":{"source":{"kind":"local","path":"comp.wasm"}},"bad":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["component", "diff-schema", "--old"])
+        .arg(flows.join("component.manifest.json"))
+        .arg("--new")
+        .arg(&new_path)
+        .arg("--dir")
+        .arg(dir.path())
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stdout(
+            contains("CHANGE operation 'charge': /amount no longer accepts number")
+                .and(contains(
+                    "BREAK  flows/main.ygtc node 'bad': sets /amount to a number",
+                ))
+                .and(contains(
+                    "node 'bad': does not set newly required /currency",
+                ))
+                .and(contains("node 'ok'").not()),
+        );
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "component", "diff-schema", "--old"])
+        .arg(flows.join("component.manifest.json"))
+        .arg("--new")
+        .arg(&new_path)
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["changes"].as_array().unwrap().len(), 2);
+    assert_eq!(payload["breaks"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn graph_command_renders_each_format() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("main.ygtc");
+    fs::write(
+        &flow_path,
+        "id: main\ntype: messaging\nstart: a\nnodes:\n  a:\n    emit: {}\n    routing:\n      - status: ok\n        to: b\n  b:\n    emit: {}\n    routing: out\n",
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["graph", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains("flowchart LR").and(contains(r#"n0 -->|"status ok"| n1"#)));
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["graph", "--graph-format", "dot", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains(r#""a" -> "b" [label="status ok"];"#));
+
+    let svg_path = dir.path().join("main.svg");
+    cargo_bin_cmd!("greentic-flow")
+        .args(["graph", "--graph-format", "svg", "--flow"])
+        .arg(&flow_path)
+        .arg("--out")
+        .arg(&svg_path)
+        .assert()
+        .success();
+    assert!(
+        fs::read_to_string(&svg_path)
+            .unwrap()
+            .starts_with("<svg xmlns=")
+    );
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args([
+            "--format",
+            "json",
+            "graph",
+            "--graph-format",
+            "dot",
+            "--flow",
+        ])
+        .arg(&flow_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["format"], json!("dot"));
+    assert!(payload["diagram"].as_str().unwrap().starts_with("digraph"));
+}
+
+#[test]
+fn diff_command_prints_text_and_json() {
+    let dir = tempdir().unwrap();
+    let old_path = dir.path().join("old.ygtc");
+    let new_path = dir.path().join("new.ygtc");
+    fs::write(
+        &old_path,
+        "id: main\ntype: messaging\nstart: a\nnodes:\n  a:\n    emit:\n      text: hi\n    routing:\n      - to: b\n  b:\n    emit: {}\n    routing: out\n",
+    )
+    .unwrap();
+    fs::write(
+        &new_path,
+        "id: main\ntype: messaging\nstart: a\nnodes:\n  a:\n    emit:\n      text: hello\n    routing: out\n",
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("diff")
+        .arg(&old_path)
+        .arg(&new_path)
+        .assert()
+        .success()
+        .stdout(
+            contains("- node 'b' (emit) -> out")
+                .and(contains("~ node 'a'"))
+                .and(contains("    routing default: b => out"))
+                .and(contains(r#"    payload /text: "hi" -> "hello""#)),
+        );
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "diff"])
+        .arg(&old_path)
+        .arg(&old_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["identical"], json!(true));
+    assert_eq!(payload["diff"]["modified_nodes"], json!([]));
+}
+
+#[test]
+fn requirements_lists_node_needs_and_doctor_checks_env_file() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    fs::write(
+        dir.path().join("component.manifest.json"),
+        json!({
+            "id": "ai.greentic.pay",
+            "operations": [{
+                "name": "charge",
+                "input_schema": {"type": "object", "properties": {"amount": {"type": "integer"}}}
+            }],
+            "capabilities": {
+                "wasi": {"env": {"allow": ["PAY_REGION"]}, "clocks": true},
+                "host": {
+                    "secrets": {"required": [
+                        {"key": "pay/api-key"},
+                        {"key": "pay/webhook", "required": false}
+                    ]},
+                    "http": {"client": true}
+                }
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["new", "--flow"])
+        .arg(&flow_path)
+        .args(["--id", "main", "--type", "messaging"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["add-step", "--flow"])
+        .arg(&flow_path)
+        .args([
+            "--mode",
+            "default",
+            "--node-id",
+            "pay",
+            "--operation",
+            "charge",
+        ])
+        .args([
+            "--payload",
+            r#"{"amount":5}"#,
+            "--local-wasm",
+            "comp.wasm",
+            "--routing-out",
+        ])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["requirements", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(
+            contains("env: PAY_REGION")
+                .and(contains("secret: pay/api-key [PAY_API_KEY]"))
+                .and(contains("secret: pay/webhook [PAY_WEBHOOK] (optional)"))
+                .and(contains("capabilities: wasi:clocks, host:http")),
+        );
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "requirements", "--flow"])
+        .arg(&flow_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["nodes"][0]["node_id"], "pay");
+    assert_eq!(payload["nodes"][0]["env"], json!(["PAY_REGION"]));
+    assert_eq!(payload["nodes"][0]["secrets"][1]["required"], false);
+
+    let env_path = dir.path().join(".env");
+    fs::write(&env_path, "# staging\nPAY_REGION=eu\n").unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--env-file")
+        .arg(&env_path)
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(
+            contains("env_requirements")
+                .and(contains("'pay/api-key'"))
+                .and(contains("PAY_REGION").not()),
+        );
+
+    fs::write(&env_path, "export PAY_REGION=eu\nPAY_API_KEY=\"secret\"\n").unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg("--env-file")
+        .arg(&env_path)
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stderr(contains("WARN").and(contains("'pay/webhook'")));
+}
+
+#[test]
+fn fmt_rewrites_flows_canonically_and_check_fails_on_drift() {
+    let dir = tempdir().unwrap();
+    let flows = dir.path().join("flows");
+    fs::create_dir(&flows).unwrap();
+    let messy = flows.join("main.ygtc");
+    fs::write(
+        &messy,
+        "nodes:\n  a:\n    routing: [{out: true}]\n    op: {b: 1, a: 2}\ntype: messaging\nid: main\n",
+    )
+    .unwrap();
+    let commented = flows.join("commented.ygtc");
+    let commented_text = "id: other\ntype: messaging\nschema_version: 2\nnodes:\n  # keep me\n  a:\n    op: {}\n    routing: out\n";
+    fs::write(&commented, commented_text).unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["fmt", "--check"])
+        .arg(&flows)
+        .assert()
+        .failure()
+        .stdout(contains("would reformat").and(contains("main.ygtc")))
+        .stderr(contains("1 flow(s) are not canonically formatted"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .arg("fmt")
+        .arg(&flows)
+        .assert()
+        .success()
+        .stdout(contains("1 of 2 flow(s) reformatted"));
+    assert_eq!(
+        fs::read_to_string(&messy).unwrap(),
+        "id: main\ntype: messaging\nschema_version: 2\nnodes:\n  a:\n    op:\n      a: 2\n      b: 1\n    routing: out\n"
+    );
+    assert_eq!(fs::read_to_string(&commented).unwrap(), commented_text);
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["fmt", "--check"])
+        .arg(&flows)
+        .assert()
+        .success();
+
+    fs::write(
+        &commented,
+        "nodes:\n  a:\n    op: {} # inline\n    routing: out\ntype: messaging\nid: other\n",
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .arg("fmt")
+        .arg(&commented)
+        .assert()
+        .failure()
+        .stderr(contains("commented.ygtc:3 has comments"));
+    cargo_bin_cmd!("greentic-flow")
+        .args(["fmt", "--drop-comments"])
+        .arg(&commented)
+        .assert()
+        .success();
+    assert!(!fs::read_to_string(&commented).unwrap().contains('#'));
+}
+
+#[test]
+fn state_keys_reports_keys_shared_across_wizards() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("main.ygtc");
+    let manifest_path = dir.path().join("component.manifest.json");
+    let operations = json!([{
+        "name": "get",
+        "input_schema": {"type": "object", "properties": {"url": {"type": "string"}}}
+    }]);
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    fs::write(
+        &manifest_path,
+        json!({"id": "ai.greentic.http", "operations": operations}).to_string(),
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["new", "--flow"])
+        .arg(&flow_path)
+        .args(["--id", "main", "--type", "messaging"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["add-step", "--flow"])
+        .arg(&flow_path)
+        .args([
+            "--mode",
+            "default",
+            "--node-id",
+            "fetch",
+            "--operation",
+            "get",
+        ])
+        .args([
+            "--payload",
+            "{}",
+            "--local-wasm",
+            "comp.wasm",
+            "--routing-out",
+        ])
+        .assert()
+        .success();
+    fs::write(
+        &manifest_path,
+        json!({
+            "id": "ai.greentic.http",
+            "operations": operations,
+            "dev_flows": {"default": {"graph": {
+                "id": "http-default",
+                "type": "component-config",
+                "nodes": {"ask": {"questions": {"fields": [
+                    {"id": "region", "default": "eu"},
+                    {"id": "url", "writes_to": "http.base_url"}
+                ]}}}
+            }}}
+        })
+        .to_string(),
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("storage.config.ygtc"),
+        "id: storage-config\ntype: component-config\nnodes:\n  ask_storage:\n    questions:\n      fields:\n        - id: region\n          default: us\n",
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["state-keys", "--dir"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(
+            contains("region (shared)")
+                .and(contains("main.ygtc node 'fetch'"))
+                .and(contains("dev_flows.default: ask.region"))
+                .and(contains("storage.config.ygtc: ask_storage.region"))
+                .and(contains("http.base_url\n"))
+                .and(contains("2 state key(s), 1 shared")),
+        );
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "state-keys", "--dir"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["shared"], json!(["region"]));
+    assert_eq!(payload["keys"]["http.base_url"][0]["question"], "url");
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["state-keys", "--strict", "--dir"])
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains(
+            "1 state key(s) written by more than one wizard: region",
+        ));
+}
+
+#[test]
+fn rename_step_rewires_references_and_moves_sidecar_entry() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    let sidecar = flow_path.with_extension("ygtc.resolve.json");
+    fs::write(
+        &sidecar,
+        r#"{"schema_version":1,"flow":"flow.ygtc","nodes":{"mid":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    let original = r#"id: main
+type: messaging
+schema_version: 2
+start: a
+entrypoints:
+  webhook: mid
+nodes:
+  a:
+    hop: {}
+    routing:
+      - to: mid
+  mid:
+    op: {}
+    retry:
+      attempts: 2
+    routing: out
+"#;
+    fs::write(&flow_path, original).unwrap();
+    let config_dir = dir.path().join("flow.ygtc.config");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(config_dir.join("mid.cbor"), b"\xa0").unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args([
+            "rename-step",
+            "--from",
+            "mid",
+            "--to",
+            "charge",
+            "--dry-run",
+            "--flow",
+        ])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(
+            contains("-  mid:")
+                .and(contains("+  charge:"))
+                .and(contains("\"charge\"")),
+        );
+    assert_eq!(fs::read_to_string(&flow_path).unwrap(), original);
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["rename-step", "--from", "mid", "--to", "a", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(contains("node 'a' already exists"));
+    assert_eq!(fs::read_to_string(&flow_path).unwrap(), original);
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["rename-step", "--from", "mid", "--to", "charge", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains("Renamed step 'mid' to 'charge'"));
+
+    let flow = read_yaml(&flow_path);
+    assert_eq!(flow["nodes"]["a"]["routing"][0]["to"], "charge");
+    assert_eq!(flow["entrypoints"]["webhook"], "charge");
+    assert_eq!(flow["nodes"]["charge"]["retry"]["attempts"], 2);
+    assert!(flow["nodes"].get("mid").is_none());
+    let sidecar_json: JsonValue =
+        serde_json::from_str(&fs::read_to_string(&sidecar).unwrap()).unwrap();
+    let nodes = sidecar_json["nodes"].as_object().unwrap();
+    assert!(nodes.contains_key("charge") && !nodes.contains_key("mid"));
+    assert!(config_dir.join("charge.cbor").exists());
+    assert!(!config_dir.join("mid.cbor").exists());
+}
+
+#[test]
+fn ide_data_exports_node_ids_and_component_properties() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("main.ygtc");
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    fs::write(
+        dir.path().join("component.manifest.json"),
+        json!({
+            "id": "ai.greentic.http",
+            "operations": [{
+                "name": "get",
+                "input_schema": {
+                    "type": "object",
+                    "required": ["url"],
+                    "properties": {"url": {"type": "string", "description": "Endpoint"}}
+                }
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["new", "--flow"])
+        .arg(&flow_path)
+        .args(["--id", "main", "--type", "messaging"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["add-step", "--flow"])
+        .arg(&flow_path)
+        .args([
+            "--mode",
+            "default",
+            "--node-id",
+            "fetch",
+            "--operation",
+            "get",
+        ])
+        .args(["--payload", r#"{"url":"https://example.com"}"#])
+        .args(["--local-wasm", "comp.wasm", "--routing-out"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["ide-data", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains(
+            "Wrote completion data for 1 node(s) and 1 component(s)",
+        ));
+    let data: JsonValue =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("main.ygtc.ide.json")).unwrap())
+            .unwrap();
+    assert_eq!(data["flow"], "main");
+    assert_eq!(data["node_ids"], json!(["fetch"]));
+    assert!(
+        data["reserved_keys"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("routing"))
+    );
+    let component = data["nodes"][0]["component"].as_str().unwrap();
+    assert_eq!(data["nodes"][0]["operation"], "get");
+    let url = &data["components"][component]["get"][0];
+    assert_eq!(url["name"], "url");
+    assert_eq!(url["type"], "string");
+    assert_eq!(url["description"], "Endpoint");
+    assert_eq!(url["required"], true);
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["ide-data", "--out", "-", "--flow"])
+        .arg(&flow_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stdout, data);
+}
+
+#[test]
+fn add_step_before_inserts_guard_in_front_of_node() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+start: start
+nodes:
+  start:
+    op: {}
+    routing:
+      - to: charge
+  retry:
+    op: {}
+    routing:
+      - to: charge
+  charge:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["add-step", "--flow"])
+        .arg(&flow_path)
+        .args([
+            "--mode",
+            "default",
+            "--node-id",
+            "guard",
+            "--operation",
+            "check",
+        ])
+        .args([
+            "--payload",
+            "{}",
+            "--local-wasm",
+            "comp.wasm",
+            "--before",
+            "charge",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Inserted node before 'charge'"));
+
+    let flow = read_yaml(&flow_path);
+    assert_eq!(flow["nodes"]["start"]["routing"][0]["to"], "guard");
+    assert_eq!(flow["nodes"]["retry"]["routing"][0]["to"], "guard");
+    assert_eq!(flow["nodes"]["guard"]["routing"][0]["to"], "charge");
+    assert_eq!(flow["start"], "start");
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["add-step", "--flow"])
+        .arg(&flow_path)
+        .args(["--after", "start", "--before", "charge", "--operation", "x"])
+        .assert()
+        .failure()
+        .stderr(contains("cannot be used with"));
+}
+
+#[test]
+fn selftest_determinism_reports_each_check_and_rejects_bad_runs() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("main.ygtc");
+    fs::write(
+        &flow_path,
+        "id: main\ntype: messaging\nstart: hello\nnodes:\n  hello:\n    templating.handlebars:\n      text: hi\n    routing: out\n",
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["selftest", "determinism", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .success()
+        .stdout(contains("PASS parse-serialize"))
+        .stdout(contains("PASS add-step"))
+        .stdout(contains("PASS bundle-hash"));
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "selftest", "determinism", "--runs", "3"])
+        .arg("--flow")
+        .arg(&flow_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: JsonValue = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["ok"], true);
+    assert_eq!(payload["runs"], 3);
+    assert_eq!(payload["checks"].as_array().unwrap().len(), 3);
+    assert_eq!(payload["checks"][2]["digest"].as_str().unwrap().len(), 64);
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["selftest", "determinism", "--runs", "1", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn timeout_aborts_waiting_wizard_with_distinct_exit_code_and_cleans_staging() {
+    let dir = tempdir().unwrap();
+    let pack = dir.path().join("pack");
+    fs::create_dir_all(pack.join("flows")).unwrap();
+    let tmp = dir.path().join("tmp");
+    fs::create_dir_all(&tmp).unwrap();
+
+    // stdin stays open, so the wizard blocks on its first prompt until the timeout fires.
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"))
+        .env("TMPDIR", &tmp)
+        .args(["--timeout", "500ms", "wizard"])
+        .arg(&pack)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdin = child.stdin.take();
+    let output = child.wait_with_output().unwrap();
+    drop(stdin);
+
+    assert_eq!(output.status.code(), Some(124));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("timed out after 500ms"));
+    assert_eq!(
+        fs::read_dir(&tmp).unwrap().count(),
+        0,
+        "wizard staging directory removed"
+    );
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["--timeout", "soon", "doctor"])
+        .arg(&pack)
+        .assert()
+        .failure()
+        .stderr(contains("invalid timeout"));
 }
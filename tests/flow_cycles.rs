@@ -0,0 +1,90 @@
+use greentic_flow::{
+    flow_ir::{find_cycles, parse_flow_to_ir},
+    lint::{LintSeverity, RoutingCyclesRule},
+    loader::load_ygtc_from_str,
+};
+
+const FLOW: &str = r#"id: main
+type: messaging
+start: fetch
+nodes:
+  fetch:
+    http.get: {}
+    routing:
+      - status: ok
+        to: parse
+      - status: retry
+        to: fetch
+      - to: fail
+  parse:
+    json.parse: {}
+    routing:
+      - status: more
+        to: page
+      - to: store
+  page:
+    http.get: {}
+    routing:
+      - to: wait
+  wait:
+    timer.sleep: {}
+    routing:
+      - to: parse
+  store:
+    kv.put: {}
+    routing: out
+  fail:
+    log.error: {}
+    routing: out
+"#;
+
+#[test]
+fn find_cycles_reports_self_loops_and_multi_node_paths() {
+    let flow = parse_flow_to_ir(FLOW).unwrap();
+    assert_eq!(
+        find_cycles(&flow),
+        vec![
+            vec!["fetch".to_string()],
+            vec!["parse".to_string(), "page".to_string(), "wait".to_string()],
+        ]
+    );
+}
+
+#[test]
+fn find_cycles_is_empty_for_forward_only_flows() {
+    let flow = parse_flow_to_ir(
+        "id: main\ntype: messaging\nstart: a\nnodes:\n  a:\n    x.y: {}\n    routing:\n      - to: b\n  b:\n    x.y: {}\n    routing: out\n",
+    )
+    .unwrap();
+    assert!(find_cycles(&flow).is_empty());
+}
+
+#[test]
+fn routing_cycles_rule_reports_each_cycle_path_unless_allowed() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let diagnostics = RoutingCyclesRule::check(&doc);
+    assert_eq!(diagnostics.len(), 2, "{diagnostics:?}");
+    assert_eq!(diagnostics[0].severity, LintSeverity::Error);
+    assert_eq!(diagnostics[0].path.as_deref(), Some("/nodes/fetch"));
+    assert!(
+        diagnostics[0]
+            .message
+            .starts_with("routing cycle fetch -> fetch;"),
+        "{}",
+        diagnostics[0].message
+    );
+    assert!(
+        diagnostics[1]
+            .message
+            .starts_with("routing cycle parse -> page -> wait -> parse;"),
+        "{}",
+        diagnostics[1].message
+    );
+
+    let allowed = FLOW.replace(
+        "start: fetch\n",
+        "start: fetch\nmeta:\n  allow_cycles: true\n",
+    );
+    let doc = load_ygtc_from_str(&allowed).unwrap();
+    assert!(RoutingCyclesRule::check(&doc).is_empty());
+}
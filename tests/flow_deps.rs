@@ -0,0 +1,74 @@
+use greentic_flow::flow_deps::{dependency_order, required_flows};
+use indexmap::IndexMap;
+use serde_json::json;
+
+fn flows(edges: &[(&str, &[&str])]) -> IndexMap<String, Vec<String>> {
+    edges
+        .iter()
+        .map(|(id, requires)| {
+            (
+                id.to_string(),
+                requires.iter().map(|dep| dep.to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn required_flows_come_from_greentic_meta() {
+    assert!(required_flows(None).unwrap().is_empty());
+    assert!(
+        required_flows(Some(&json!({"other": 1})))
+            .unwrap()
+            .is_empty()
+    );
+    let meta = json!({"greentic": {"requires_flows": ["billing", "notify", "billing"]}});
+    assert_eq!(required_flows(Some(&meta)).unwrap(), ["billing", "notify"]);
+    let err = required_flows(Some(&json!({"greentic": {"requires_flows": "billing"}})))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        "meta.greentic.requires_flows must be a list of flow ids"
+    );
+}
+
+#[test]
+fn required_flows_come_first_and_the_rest_keep_their_order() {
+    let order = dependency_order(&flows(&[
+        ("main", &["billing", "notify"]),
+        ("audit", &[]),
+        ("notify", &[]),
+        ("billing", &["notify"]),
+    ]))
+    .unwrap();
+    assert_eq!(order, ["notify", "billing", "main", "audit"]);
+}
+
+#[test]
+fn cycles_and_missing_flows_are_reported() {
+    let err = dependency_order(&flows(&[
+        ("main", &["billing"]),
+        ("billing", &["notify"]),
+        ("notify", &["billing"]),
+    ]))
+    .unwrap_err()
+    .to_string();
+    assert_eq!(
+        err,
+        "circular flow dependency: billing -> notify -> billing"
+    );
+
+    let err = dependency_order(&flows(&[("main", &["main"])]))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(err, "circular flow dependency: main -> main");
+
+    let err = dependency_order(&flows(&[("main", &["billing"])]))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        "flow 'main' requires 'billing', which is not in the pack"
+    );
+}
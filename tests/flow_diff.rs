@@ -0,0 +1,129 @@
+use greentic_flow::{
+    flow_diff::{RouteChange, diff_flows},
+    flow_ir::parse_flow_to_ir,
+};
+use serde_json::json;
+
+const OLD: &str = r#"
+id: checkout
+type: messaging
+start: pay
+nodes:
+  pay:
+    charge:
+      amount: 5
+      currency: EUR
+    routing:
+      - status: e402
+        to: retry
+      - to: receipt
+  retry:
+    charge:
+      amount: 5
+    routing: out
+  receipt:
+    emit: {}
+    routing: out
+"#;
+
+const NEW: &str = r#"
+id: checkout
+type: messaging
+start: pay
+nodes:
+  pay:
+    charge:
+      amount: 7
+      currency: EUR
+      memo: gift
+    routing:
+      - status: e402
+        to: notify
+      - status: e500
+        to: notify
+      - to: receipt
+  notify:
+    emit:
+      text: payment failed
+    routing: out
+  receipt:
+    emit: {}
+    routing: out
+"#;
+
+#[test]
+fn diff_reports_nodes_routing_and_payload_changes() {
+    let diff = diff_flows(
+        &parse_flow_to_ir(OLD).unwrap(),
+        &parse_flow_to_ir(NEW).unwrap(),
+    );
+    assert!(diff.flow.is_empty());
+    assert_eq!(diff.removed_nodes.len(), 1);
+    assert_eq!(diff.removed_nodes[0].node_id, "retry");
+    assert_eq!(diff.added_nodes.len(), 1);
+    assert_eq!(diff.added_nodes[0].node_id, "notify");
+
+    assert_eq!(diff.modified_nodes.len(), 1);
+    let pay = &diff.modified_nodes[0];
+    assert_eq!(pay.node_id, "pay");
+    assert!(pay.operation.is_none());
+    assert_eq!(
+        pay.routing,
+        vec![
+            RouteChange {
+                status: Some("e402".to_string()),
+                old: Some("retry".to_string()),
+                new: Some("notify".to_string()),
+            },
+            RouteChange {
+                status: Some("e500".to_string()),
+                old: None,
+                new: Some("notify".to_string()),
+            },
+        ]
+    );
+    let payload: Vec<_> = pay
+        .payload
+        .iter()
+        .map(|change| (change.path.as_str(), change.old.clone(), change.new.clone()))
+        .collect();
+    assert_eq!(
+        payload,
+        vec![
+            ("/amount", Some(json!(5)), Some(json!(7))),
+            ("/memo", None, Some(json!("gift"))),
+        ]
+    );
+
+    let text = diff.render_text();
+    assert!(text.contains("- node 'retry' (charge) -> out"), "{text}");
+    assert!(text.contains("+ node 'notify' (emit) -> out"), "{text}");
+    assert!(
+        text.contains("    routing status e402: retry => notify"),
+        "{text}"
+    );
+    assert!(text.contains("    payload /amount: 5 -> 7"), "{text}");
+}
+
+#[test]
+fn identical_flows_and_equivalent_routing_produce_no_diff() {
+    let flow = parse_flow_to_ir(OLD).unwrap();
+    assert!(diff_flows(&flow, &flow).is_empty());
+
+    let terminal = |routing: &str| {
+        parse_flow_to_ir(&format!(
+            "id: f\ntype: messaging\nstart: a\nnodes:\n  a:\n    emit: {{}}\n    routing: {routing}\n"
+        ))
+        .unwrap()
+    };
+    assert!(diff_flows(&terminal("[]"), &terminal("out")).is_empty());
+    let diff = diff_flows(&terminal("out"), &terminal("reply"));
+    assert_eq!(
+        diff.modified_nodes[0].routing,
+        vec![RouteChange {
+            status: None,
+            old: Some("out".to_string()),
+            new: Some("reply".to_string()),
+        }]
+    );
+}
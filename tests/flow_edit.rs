@@ -0,0 +1,224 @@
+use greentic_flow::{
+    error::FlowError,
+    flow_edit::{DeleteStrategy, MultiplePredecessors},
+    flow_ir::{Route, parse_flow_to_ir},
+};
+use serde_json::json;
+
+const FLOW: &str = r#"id: main
+type: messaging
+start: start
+entrypoints:
+  alt: middle
+meta:
+  greentic:
+    components:
+      middle: { ref: "oci://example/middle:1" }
+nodes:
+  start:
+    handle_message:
+      text: "hi"
+    routing:
+      - to: middle
+  middle:
+    transform:
+      mode: upper
+    routing:
+      - to: end
+  end:
+    emit:
+      text: "bye"
+    routing: out
+"#;
+
+fn to(target: &str) -> Route {
+    Route {
+        to: Some(target.to_string()),
+        ..Route::default()
+    }
+}
+
+#[test]
+fn rename_node_rewrites_references_in_place() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    flow.rename_node("middle", "shout").unwrap();
+
+    let ids: Vec<&str> = flow.nodes.keys().map(String::as_str).collect();
+    assert_eq!(ids, ["start", "shout", "end"]);
+    assert_eq!(flow.nodes["shout"].id, "shout");
+    assert_eq!(flow.nodes["start"].routing, vec![to("shout")]);
+    assert_eq!(flow.entrypoints["alt"], "shout");
+    let components = &flow.meta.as_ref().unwrap()["greentic"]["components"];
+    assert!(components.get("middle").is_none());
+    assert_eq!(components["shout"]["ref"], "oci://example/middle:1");
+    parse_flow_to_ir(&serde_yaml_bw::to_string(&flow.to_doc().unwrap()).unwrap()).unwrap();
+}
+
+#[test]
+fn rename_node_rejects_bad_targets_without_editing() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    for (from, to) in [("missing", "x"), ("middle", "end"), ("middle", "9bad")] {
+        assert!(flow.rename_node(from, to).is_err(), "{from} -> {to}");
+    }
+    assert!(flow.nodes.contains_key("middle"));
+}
+
+#[test]
+fn remove_node_splices_routing_into_predecessors() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    let removed = flow
+        .remove_node(
+            "middle",
+            DeleteStrategy::Splice,
+            MultiplePredecessors::Error,
+        )
+        .unwrap();
+    assert_eq!(removed.operation, "transform");
+    assert_eq!(flow.nodes["start"].routing, vec![to("end")]);
+    assert_eq!(flow.entrypoints["alt"], "start");
+    let components = &flow.meta.as_ref().unwrap()["greentic"]["components"];
+    assert!(components.get("middle").is_none());
+}
+
+#[test]
+fn remove_node_refuses_multiple_predecessors_unless_asked() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    flow.add_route("start", to("end")).unwrap();
+    let err = flow
+        .remove_node("end", DeleteStrategy::Splice, MultiplePredecessors::Error)
+        .unwrap_err();
+    assert!(err.to_string().contains("start, middle"), "{err}");
+    assert!(flow.nodes.contains_key("end"));
+
+    flow.remove_node(
+        "end",
+        DeleteStrategy::Splice,
+        MultiplePredecessors::SpliceAll,
+    )
+    .unwrap();
+    assert_eq!(flow.nodes["start"].routing, vec![to("middle")]);
+    assert!(flow.nodes["middle"].routing.is_empty());
+}
+
+#[test]
+fn remove_node_reroutes_multiple_predecessors_to_another_step() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    flow.add_route("start", to("end")).unwrap();
+    let reroute: MultiplePredecessors = "reroute-to:start".parse().unwrap();
+    assert_eq!(
+        reroute,
+        MultiplePredecessors::RerouteTo("start".to_string())
+    );
+    assert_eq!(reroute.to_string(), "reroute-to:start");
+    assert!("reroute-to:".parse::<MultiplePredecessors>().is_err());
+    assert!("bogus".parse::<MultiplePredecessors>().is_err());
+    let from_plan: MultiplePredecessors = serde_json::from_str(r#""splice-all""#).unwrap();
+    assert_eq!(from_plan, MultiplePredecessors::SpliceAll);
+
+    for bad in ["end", "missing"] {
+        let err = flow
+            .remove_node(
+                "end",
+                DeleteStrategy::Splice,
+                MultiplePredecessors::RerouteTo(bad.to_string()),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot reroute"), "{err}");
+    }
+    flow.remove_node("end", DeleteStrategy::Splice, reroute)
+        .unwrap();
+    assert_eq!(flow.nodes["start"].routing, vec![to("middle"), to("start")]);
+    assert_eq!(flow.nodes["middle"].routing, vec![to("start")]);
+}
+
+#[test]
+fn remove_node_cascade_takes_steps_only_the_node_led_to() {
+    let mut flow = parse_flow_to_ir(
+        r#"id: main
+type: messaging
+start: start
+nodes:
+  start:
+    check: {}
+    routing:
+      - status: e402
+        to: retry
+      - to: done
+  retry:
+    wait: {}
+    routing:
+      - to: charge
+  charge:
+    pay: {}
+    routing:
+      - to: done
+  done:
+    emit: {}
+    routing: out
+  orphan:
+    emit: {}
+    routing:
+      - to: retry
+"#,
+    )
+    .unwrap();
+    assert_eq!(flow.cascade_removal("retry").unwrap(), ["retry", "charge"]);
+    assert_eq!(
+        flow.cascade_removal_all(&["done", "retry"]).unwrap(),
+        ["done", "retry", "charge"]
+    );
+
+    let removed = flow.remove_node_cascade("retry").unwrap();
+    let ids: Vec<&str> = removed.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(ids, ["retry", "charge"]);
+    // `done` is still reached directly; the already-unreachable `orphan` stays but loses its
+    // route into the removed branch.
+    let left: Vec<&String> = flow.nodes.keys().collect();
+    assert_eq!(left, ["start", "done", "orphan"]);
+    assert_eq!(flow.nodes["start"].routing, vec![to("done")]);
+    assert!(flow.nodes["orphan"].routing.is_empty());
+
+    let err = flow.remove_node_cascade("start").unwrap_err();
+    assert!(
+        err.to_string().contains("start node or an entrypoint"),
+        "{err}"
+    );
+    assert!(flow.nodes.contains_key("start"));
+}
+
+#[test]
+fn replace_node_payload_returns_previous_payload() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    let previous = flow
+        .replace_node_payload("middle", json!({ "mode": "lower" }))
+        .unwrap();
+    assert_eq!(previous, json!({ "mode": "upper" }));
+    assert_eq!(flow.nodes["middle"].payload, json!({ "mode": "lower" }));
+    assert!(flow.replace_node_payload("missing", json!({})).is_err());
+}
+
+#[test]
+fn add_route_validates_target_and_status_reachability() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    match flow.add_route("start", to("nowhere")).unwrap_err() {
+        FlowError::MissingNode { target, .. } => assert_eq!(target, "nowhere"),
+        other => panic!("expected missing node, got {other:?}"),
+    }
+
+    let status = |status: &str| Route {
+        status: Some(status.to_string()),
+        ..to("end")
+    };
+    flow.add_route("middle", status("*")).unwrap();
+    let err = flow.add_route("middle", status("e412")).unwrap_err();
+    assert!(matches!(err, FlowError::Routing { .. }), "{err:?}");
+    assert_eq!(flow.nodes["middle"].routing.len(), 2);
+}
+
+#[test]
+fn remove_route_by_index() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    assert_eq!(flow.remove_route("start", 0).unwrap(), to("middle"));
+    assert!(flow.nodes["start"].routing.is_empty());
+    assert!(flow.remove_route("start", 0).is_err());
+}
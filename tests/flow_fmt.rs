@@ -0,0 +1,113 @@
+use greentic_flow::flow_ir::{format_canonical, parse_flow_to_ir};
+
+const MESSY: &str = r#"nodes:
+  fetch:
+    routing:
+      - to: store
+        out: false
+    annotations:
+      owner: payments
+    http.get:
+      url: "https://api.example.com"
+      headers: {b: 1, a: 2}
+  store:
+    routing: [{out: true}]
+    kv.put: {key: k}
+  done:
+    routing:
+      - reply: true
+    retry:
+      attempts: 3
+    template: "ok"
+type: messaging
+tags: []
+parameters: {}
+id: main
+start: fetch
+"#;
+
+const CANONICAL: &str = r#"id: main
+type: messaging
+schema_version: 2
+start: fetch
+nodes:
+  fetch:
+    http.get:
+      headers:
+        a: 2
+        b: 1
+      url: https://api.example.com
+    annotations:
+      owner: payments
+    routing:
+    - to: store
+  store:
+    kv.put:
+      key: k
+    routing: out
+  done:
+    template: ok
+    retry:
+      attempts: 3
+    routing: reply
+"#;
+
+#[test]
+fn format_canonical_orders_keys_and_normalizes_routing() {
+    assert_eq!(format_canonical(MESSY).unwrap(), CANONICAL);
+}
+
+#[test]
+fn format_canonical_is_idempotent_and_keeps_meaning() {
+    let once = format_canonical(MESSY).unwrap();
+    assert_eq!(format_canonical(&once).unwrap(), once);
+
+    let before = parse_flow_to_ir(MESSY).unwrap();
+    let after = parse_flow_to_ir(&once).unwrap();
+    assert_eq!(before.start, after.start);
+    assert_eq!(
+        before.nodes.keys().collect::<Vec<_>>(),
+        after.nodes.keys().collect::<Vec<_>>()
+    );
+    for (id, node) in &before.nodes {
+        let other = &after.nodes[id];
+        assert_eq!(node.operation, other.operation, "{id}");
+        assert_eq!(node.payload, other.payload, "{id}");
+        assert_eq!(node.routing, other.routing, "{id}");
+    }
+}
+
+#[test]
+fn format_canonical_keeps_documented_routes_and_entrypoints_in_order() {
+    let flow = r#"id: main
+type: messaging
+entrypoints:
+  zeta: b
+  alpha: a
+nodes:
+  a:
+    op: {}
+    routing:
+      - out: true
+        label: done
+  b:
+    op: {}
+    routing:
+      - status: ok
+        to: a
+      - to: a
+"#;
+    let formatted = format_canonical(flow).unwrap();
+    assert!(
+        formatted.contains("entrypoints:\n  zeta: b\n  alpha: a\n"),
+        "{formatted}"
+    );
+    assert!(
+        formatted.contains("routing:\n    - label: done\n      out: true\n"),
+        "{formatted}"
+    );
+    assert!(
+        formatted.contains("- status: ok\n      to: a\n    - to: a\n"),
+        "{formatted}"
+    );
+}
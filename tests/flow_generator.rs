@@ -0,0 +1,69 @@
+use greentic_flow::{
+    compile_flow,
+    lint::{RuleContext, RuleRegistry},
+    loader::load_ygtc_from_str,
+    testing::{FlowGenerator, RoutingKind},
+};
+use greentic_types::Routing;
+
+fn assert_valid(generator: &FlowGenerator) -> greentic_types::Flow {
+    let yaml = generator.generate_yaml();
+    let doc = load_ygtc_from_str(&yaml)
+        .unwrap_or_else(|err| panic!("seed {} does not load: {err}\n{yaml}", generator.seed));
+    let flow = compile_flow(doc.clone())
+        .unwrap_or_else(|err| panic!("seed {} does not compile: {err}\n{yaml}", generator.seed));
+    let diagnostics = RuleRegistry::builtin().run(&flow, &RuleContext::default().with_doc(&doc));
+    assert!(
+        diagnostics.is_empty(),
+        "seed {} lints: {diagnostics:?}\n{yaml}",
+        generator.seed
+    );
+    flow
+}
+
+#[test]
+fn generated_flows_are_valid_across_seeds_and_shapes() {
+    for seed in 0..200 {
+        let mut generator = FlowGenerator::new(seed);
+        generator.nodes = 1 + (seed as usize % 40);
+        generator.branchiness = (seed % 5) as f64 / 4.0;
+        let flow = assert_valid(&generator);
+        assert_eq!(flow.nodes.len(), generator.nodes);
+    }
+}
+
+#[test]
+fn generated_flows_are_deterministic_per_seed() {
+    let a = FlowGenerator::new(42).generate_yaml();
+    assert_eq!(a, FlowGenerator::new(42).generate_yaml());
+    assert_ne!(a, FlowGenerator::new(43).generate_yaml());
+}
+
+#[test]
+fn routing_kinds_restrict_generated_routes() {
+    let mut generator = FlowGenerator::new(7);
+    generator.nodes = 30;
+    generator.routing_kinds = [RoutingKind::Next].into_iter().collect();
+    let flow = assert_valid(&generator);
+    let ends = flow
+        .nodes
+        .values()
+        .filter(|node| matches!(node.routing, Routing::End))
+        .count();
+    assert_eq!(ends, 1, "only the last node ends the flow");
+    assert!(
+        flow.nodes
+            .values()
+            .all(|node| matches!(node.routing, Routing::Next { .. } | Routing::End))
+    );
+
+    generator.routing_kinds = [RoutingKind::Branch].into_iter().collect();
+    generator.branchiness = 1.0;
+    let flow = assert_valid(&generator);
+    let branches = flow
+        .nodes
+        .values()
+        .filter(|node| matches!(node.routing, Routing::Branch { .. }))
+        .count();
+    assert_eq!(branches, 29);
+}
@@ -0,0 +1,171 @@
+use greentic_flow::{
+    flow_ir::{Route, format_canonical, parse_flow_to_ir, to_dot, to_mermaid},
+    loader::load_ygtc_from_str,
+    report::{FlowGraph, render_svg},
+};
+
+const FLOW: &str = r#"
+id: checkout
+type: messaging
+start: pay
+entrypoints:
+  refund: refund
+nodes:
+  pay:
+    charge:
+      amount: 5
+    routing:
+      - status: e402
+        label: declined
+        to: retry
+      - status: e500
+        reply: true
+      - to: receipt
+  retry:
+    charge: {}
+    routing: out
+  receipt:
+    emit: {}
+    routing: reply
+  refund:
+    refund: {}
+"#;
+
+#[test]
+fn mermaid_renders_nodes_edges_terminals_and_entrypoints() {
+    let flow = parse_flow_to_ir(FLOW).expect("parse");
+    let mermaid = to_mermaid(&flow);
+    assert!(mermaid.starts_with("flowchart LR\n"), "{mermaid}");
+    for line in [
+        r#"    entry0(["default"])"#,
+        r#"    entry1(["refund"])"#,
+        r#"    n0["pay<br/>charge"]"#,
+        "    entry0 --> n0",
+        "    entry1 --> n3",
+        r#"    n0 -->|"declined"| n1"#,
+        r#"    n0 -->|"status e500"| reply_"#,
+        "    n0 --> n2",
+        "    n1 --> out_",
+        "    n2 --> reply_",
+        "    n3 --> out_",
+        "    out_((out))",
+        "    reply_((reply))",
+    ] {
+        assert!(
+            mermaid.lines().any(|l| l == line),
+            "missing {line:?} in\n{mermaid}"
+        );
+    }
+}
+
+#[test]
+fn dot_renders_same_graph() {
+    let flow = parse_flow_to_ir(FLOW).expect("parse");
+    let dot = to_dot(&flow);
+    assert!(dot.starts_with("digraph \"checkout\" {\n"), "{dot}");
+    assert!(dot.trim_end().ends_with('}'));
+    for line in [
+        r#"    "__entry1" [label="refund", shape=plaintext];"#,
+        r#"    "pay" [label="pay\ncharge"];"#,
+        r#"    "__entry0" -> "pay";"#,
+        r#"    "pay" -> "retry" [label="declined"];"#,
+        r#"    "pay" -> "__reply" [label="status e500"];"#,
+        r#"    "retry" -> "__out";"#,
+        r#"    "__out" [label="out", shape=doublecircle];"#,
+    ] {
+        assert!(dot.lines().any(|l| l == line), "missing {line:?} in\n{dot}");
+    }
+}
+
+#[test]
+fn dangling_targets_are_drawn_as_missing_nodes() {
+    let mut flow =
+        parse_flow_to_ir("id: main\ntype: messaging\nstart: a\nnodes:\n  a:\n    emit: {}\n")
+            .expect("parse");
+    flow.nodes["a"].routing = vec![Route {
+        to: Some("ghost \"x\"".to_string()),
+        ..Route::default()
+    }];
+    let mermaid = to_mermaid(&flow);
+    assert!(
+        mermaid.contains(r#"    missing0["ghost #quot;x#quot; (missing)"]"#),
+        "{mermaid}"
+    );
+    assert!(mermaid.contains("    n0 --> missing0"), "{mermaid}");
+    let dot = to_dot(&flow);
+    assert!(
+        dot.contains(r#"    "ghost \"x\"" [label="ghost \"x\" (missing)", style=dashed];"#),
+        "{dot}"
+    );
+}
+
+#[test]
+fn svg_is_standalone_document() {
+    let flow = parse_flow_to_ir(FLOW).expect("parse");
+    let svg = render_svg(&FlowGraph::from_ir(&flow));
+    assert!(
+        svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""),
+        "{svg}"
+    );
+    assert!(svg.contains("<style>"));
+    assert!(
+        svg.contains(">pay</text>") || svg.contains(">pay "),
+        "{svg}"
+    );
+    assert!(svg.trim_end().ends_with("</svg>"));
+}
+
+#[test]
+fn node_docs_are_preserved_and_shown_in_graphs() {
+    let yaml = r#"
+id: weather
+type: messaging
+start: fetch
+nodes:
+  fetch:
+    weather.lookup: {}
+    doc: |
+      Looks up the forecast for the user's city.
+      Falls back to the last cached forecast.
+    routing: out
+"#;
+    let flow = parse_flow_to_ir(yaml).unwrap();
+    let fetch = &flow.nodes["fetch"];
+    assert_eq!(
+        fetch.doc_summary(),
+        Some("Looks up the forecast for the user's city.")
+    );
+    let round_trip = flow.to_doc().unwrap();
+    assert_eq!(
+        round_trip.nodes["fetch"].raw["doc"].as_str(),
+        fetch.doc.as_deref()
+    );
+    assert!(format_canonical(yaml).unwrap().contains("doc: |"));
+
+    let mermaid = to_mermaid(&flow);
+    assert!(
+        mermaid.contains(
+            r#"    n0["fetch<br/>weather.lookup<br/><i>Looks up the forecast for the user's city.</i>"]"#
+        ),
+        "{mermaid}"
+    );
+    let dot = to_dot(&flow);
+    assert!(
+        dot.contains(
+            "    \"fetch\" [label=\"fetch\\nweather.lookup\\nLooks up the forecast for the user's \
+             city.\", tooltip=\"Looks up the forecast for the user's city.\nFalls back to the last \
+             cached forecast.\"];"
+        ),
+        "{dot}"
+    );
+    let graph = FlowGraph::from_ir(&flow);
+    assert!(render_svg(&graph).contains(
+        "<title>weather.lookup (entrypoint: default)\nLooks up the forecast for the user&#39;s city.\nFalls back"
+    ));
+
+    let err = load_ygtc_from_str(
+        "id: weather\ntype: messaging\nnodes:\n  fetch:\n    weather.lookup: {}\n    doc: 42\n",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("doc must be a string"), "{err}");
+}
@@ -0,0 +1,174 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::history::{diff_sidecar_nodes, flow_at, is_timestamp};
+use predicates::str::contains;
+use serde_json::{Value, json};
+use std::{fs, path::Path, process::Command};
+use tempfile::tempdir;
+
+const FLOW: &str = r#"id: main
+type: messaging
+schema_version: 2
+start: greet
+nodes:
+  greet:
+    op:
+      text: hi
+    routing: out
+"#;
+
+fn sidecar(path: &str) -> String {
+    format!(
+        r#"{{"schema_version":1,"flow":"main.ygtc","nodes":{{"greet":{{"source":{{"kind":"local","path":"{path}"}}}}}}}}"#
+    )
+}
+
+fn git(dir: &Path, args: &[&str], date: &str) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=dev", "-c", "user.email=dev@example.com"])
+        .args(["-c", "commit.gpgsign=false"])
+        .args(args)
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?}");
+}
+
+/// A repo where `flows/main.ygtc` said "hi" on 2026-09-01 and "hello" from 2026-10-01 on, and
+/// the working tree says "hey" with a rebound component.
+fn repo() -> tempfile::TempDir {
+    let dir = tempdir().unwrap();
+    let flows = dir.path().join("flows");
+    fs::create_dir_all(&flows).unwrap();
+    git(dir.path(), &["init", "-q"], "2026-09-01T00:00:00Z");
+    fs::write(flows.join("main.ygtc"), FLOW).unwrap();
+    fs::write(flows.join("main.ygtc.resolve.json"), sidecar("v1.wasm")).unwrap();
+    git(dir.path(), &["add", "-A"], "2026-09-01T10:00:00Z");
+    git(
+        dir.path(),
+        &["commit", "-q", "-m", "Add greeting flow"],
+        "2026-09-01T10:00:00Z",
+    );
+    fs::write(
+        flows.join("main.ygtc"),
+        FLOW.replace("text: hi", "text: hello"),
+    )
+    .unwrap();
+    git(dir.path(), &["add", "-A"], "2026-10-01T10:00:00Z");
+    git(
+        dir.path(),
+        &["commit", "-q", "-m", "Say hello"],
+        "2026-10-01T10:00:00Z",
+    );
+    fs::write(
+        flows.join("main.ygtc"),
+        FLOW.replace("text: hi", "text: hey"),
+    )
+    .unwrap();
+    fs::write(flows.join("main.ygtc.resolve.json"), sidecar("v2.wasm")).unwrap();
+    dir
+}
+
+#[test]
+fn flow_at_reads_revisions_and_timestamps() {
+    assert!(is_timestamp("2026-10-01"));
+    assert!(is_timestamp("2026-10-01T14:30:00Z"));
+    assert!(!is_timestamp("HEAD~1"));
+    assert!(!is_timestamp("2026"));
+
+    let dir = repo();
+    let flow = dir.path().join("flows/main.ygtc");
+
+    let head = flow_at(&flow, "HEAD").unwrap();
+    assert!(head.yaml.contains("text: hello"));
+    assert_eq!(head.revision.summary, "Say hello");
+    assert_eq!(
+        head.revision.flow_commit.as_deref(),
+        Some(head.revision.commit.as_str())
+    );
+
+    let before = flow_at(&flow, "HEAD~1").unwrap();
+    assert!(before.yaml.contains("text: hi"));
+    assert_eq!(
+        before.sidecar.unwrap()["nodes"]["greet"]["source"]["path"],
+        "v1.wasm"
+    );
+
+    // A bare date covers the whole day; earlier times select the earlier commit.
+    assert_eq!(
+        flow_at(&flow, "2026-10-01").unwrap().revision.summary,
+        "Say hello"
+    );
+    assert_eq!(
+        flow_at(&flow, "2026-10-01T09:00:00Z")
+            .unwrap()
+            .revision
+            .summary,
+        "Add greeting flow"
+    );
+    let err = flow_at(&flow, "2026-08-01").unwrap_err().to_string();
+    assert_eq!(err, "no commit at or before 2026-08-01");
+    assert!(flow_at(&flow, "no-such-tag").is_err());
+}
+
+#[test]
+fn sidecar_changes_are_listed_by_node() {
+    let old = json!({"nodes": {"a": {"source": 1}, "b": {"source": 2}}});
+    let new = json!({"nodes": {"a": {"source": 1}, "b": {"source": 3}, "c": {"source": 4}}});
+    let changes: Vec<(String, Option<Value>, Option<Value>)> =
+        diff_sidecar_nodes(Some(&old), Some(&new))
+            .into_iter()
+            .map(|change| (change.node_id, change.before, change.after))
+            .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (
+                "b".to_string(),
+                Some(json!({"source": 2})),
+                Some(json!({"source": 3}))
+            ),
+            ("c".to_string(), None, Some(json!({"source": 4}))),
+        ]
+    );
+}
+
+#[test]
+fn show_prints_or_diffs_a_historical_flow() {
+    let dir = repo();
+    let flow = dir.path().join("flows/main.ygtc");
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["show", "--at", "2026-09-15", "--flow"])
+        .arg(&flow)
+        .assert()
+        .success()
+        .stdout(contains("(2026-09-01, \"Add greeting flow\")"))
+        .stdout(contains("text: hi"))
+        .stdout(contains("\"path\": \"v1.wasm\""));
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["show", "--diff", "--at", "HEAD", "--flow"])
+        .arg(&flow)
+        .assert()
+        .success()
+        .stdout(contains("-> working tree"))
+        .stdout(contains("~ greet"))
+        .stdout(contains("sidecar ~ greet:"))
+        .stdout(contains("v2.wasm"));
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args([
+            "--format", "json", "show", "--diff", "--at", "HEAD~1", "--flow",
+        ])
+        .arg(&flow)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["identical"], false);
+    assert_eq!(payload["revision"]["summary"], "Add greeting flow");
+    assert_eq!(payload["sidecar_changes"][0]["node_id"], "greet");
+}
@@ -1,4 +1,9 @@
-use greentic_flow::{compile_flow, flow_ir::parse_flow_to_ir, loader::load_ygtc_from_str};
+use greentic_flow::{
+    compile_flow,
+    flow_ir::{StatusMatcher, parse_flow_to_ir},
+    loader::load_ygtc_from_str,
+};
+use greentic_types::{NodeId, Routing};
 
 #[test]
 fn flow_ir_roundtrip_preserves_structure() {
@@ -15,3 +20,112 @@ fn flow_ir_roundtrip_preserves_structure() {
     // Ensure the round-tripped doc still compiles.
     compile_flow(doc).expect("compile");
 }
+
+#[test]
+fn route_labels_survive_roundtrip_and_stay_out_of_compiled_routing() {
+    let yaml = r#"
+id: checkout
+type: messaging
+schema_version: 2
+start: pay
+nodes:
+  pay:
+    payments.charge: {}
+    routing:
+      - status: e412
+        to: declined
+        label: payment declined path
+        description: Card issuer rejected the charge.
+      - to: done
+  declined:
+    finish: {}
+    routing:
+      - to: done
+        label: retry later
+      - out: true
+        label: give up
+  done:
+    finish: {}
+    routing: out
+"#;
+    let ir = parse_flow_to_ir(yaml).expect("parse ir");
+    assert_eq!(
+        ir.nodes["pay"].routing[0].label.as_deref(),
+        Some("payment declined path")
+    );
+
+    let doc = ir.to_doc().expect("to doc");
+    assert_eq!(
+        doc.nodes["pay"].routing[0]["description"],
+        "Card issuer rejected the charge."
+    );
+    assert_eq!(
+        doc.nodes["declined"].routing,
+        serde_json::json!([
+            {"to": "done", "label": "retry later"},
+            {"out": true, "label": "give up"}
+        ])
+    );
+    assert_eq!(doc.nodes["done"].routing, "out");
+
+    let flow = compile_flow(doc).expect("compile");
+    let compiled = serde_json::to_value(&flow.nodes).expect("serialize nodes");
+    assert!(!compiled.to_string().contains("give up"), "{compiled}");
+}
+
+#[test]
+fn wildcard_and_negated_statuses_roundtrip_and_compile() {
+    let yaml = r#"
+id: checkout
+type: messaging
+schema_version: 2
+start: pay
+nodes:
+  pay:
+    payments.charge: {}
+    routing:
+      - status: ok
+        to: done
+      - status: e412
+        to: declined
+      - status: "!ok"
+        to: declined
+  declined:
+    finish: {}
+    routing: out
+  done:
+    finish: {}
+    routing:
+      - status: ok
+        to: declined
+      - status: "*"
+        to: declined
+"#;
+    let ir = parse_flow_to_ir(yaml).expect("parse ir");
+    assert_eq!(
+        ir.nodes["pay"].routing[2].status_matcher(),
+        Some(StatusMatcher::Not("ok"))
+    );
+    assert!(StatusMatcher::Not("ok").matches("e500"));
+    assert!(!StatusMatcher::Not("ok").matches("ok"));
+
+    let doc = ir.to_doc().expect("to doc");
+    assert_eq!(doc.nodes["pay"].routing[2]["status"], "!ok");
+    assert_eq!(doc.nodes["done"].routing[1]["status"], "*");
+
+    let flow = compile_flow(doc).expect("compile");
+    let pay = &flow.nodes[&NodeId::new("pay").unwrap()];
+    assert!(
+        matches!(pay.routing, Routing::Custom(_)),
+        "{:?}",
+        pay.routing
+    );
+    let done = &flow.nodes[&NodeId::new("done").unwrap()];
+    match &done.routing {
+        Routing::Branch { on_status, default } => {
+            assert_eq!(on_status.keys().collect::<Vec<_>>(), vec!["ok"]);
+            assert_eq!(default.as_ref().map(|id| id.as_str()), Some("declined"));
+        }
+        other => panic!("expected branch routing, got {other:?}"),
+    }
+}
@@ -0,0 +1,138 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    flow_ir::{FlowIr, format_canonical},
+    health::{HealthCategory, HealthFinding, HealthScorecard, find_secret_literals, flow_owners},
+    loader::load_ygtc_from_str,
+};
+use serde_json::Value;
+use std::fs;
+use tempfile::tempdir;
+
+const OWNED: &str = r#"id: owned
+type: messaging
+schema_version: 2
+meta:
+  owners: [team-payments]
+nodes:
+  start:
+    send:
+      text: hi
+      auth:
+        api_key: "{{secrets.pay_key}}"
+    routing: out
+"#;
+
+const LEAKY: &str = r#"id: leaky
+type: messaging
+schema_version: 2
+nodes:
+  start:
+    send:
+      headers:
+        - name: x
+          token: sk-live-123
+      password: ""
+    routing: out
+"#;
+
+fn ir(yaml: &str) -> FlowIr {
+    FlowIr::from_doc(load_ygtc_from_str(yaml).unwrap()).unwrap()
+}
+
+#[test]
+fn finds_literal_secrets_but_not_templates() {
+    assert!(find_secret_literals(&ir(OWNED)).is_empty());
+    assert_eq!(
+        find_secret_literals(&ir(LEAKY)),
+        vec![(
+            "start".to_string(),
+            "/nodes/start/send/headers/0/token".to_string()
+        )]
+    );
+}
+
+#[test]
+fn reads_owners_from_meta() {
+    assert_eq!(flow_owners(&ir(OWNED)), vec!["team-payments".to_string()]);
+    assert!(flow_owners(&ir(LEAKY)).is_empty());
+}
+
+#[test]
+fn overall_score_averages_checked_categories() {
+    let card = HealthScorecard::new(
+        4,
+        vec![
+            HealthCategory::checked("lint", 3, 4, vec![HealthFinding::new("a.ygtc", "bad")]),
+            HealthCategory::checked("pins", 0, 0, Vec::new()),
+            HealthCategory::skipped("upgrades", "no catalog"),
+            HealthCategory::checked("owners", 1, 3, Vec::new()),
+        ],
+    );
+    let scores: Vec<Option<u32>> = card.categories.iter().map(|c| c.score).collect();
+    assert_eq!(scores, vec![Some(75), Some(100), None, Some(33)]);
+    assert_eq!(card.score, 69);
+}
+
+#[test]
+fn cli_scores_a_pack_and_gates_on_min_score() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("owned.ygtc"),
+        format_canonical(OWNED).unwrap(),
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("flows")).unwrap();
+    fs::write(dir.path().join("flows/leaky.ygtc"), LEAKY).unwrap();
+
+    let out = cargo_bin_cmd!("greentic-flow")
+        .args(["health", "--json", "--dir"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let report: Value = serde_json::from_slice(&out.stdout).unwrap();
+    let card = &report["scorecard"];
+    assert_eq!(card["flows"], 2);
+    let category = |name: &str| {
+        card["categories"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["name"] == name)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(category("lint")["passed"], 2);
+    assert_eq!(category("secrets")["passed"], 1);
+    assert_eq!(
+        category("secrets")["findings"][0]["flow"],
+        "flows/leaky.ygtc"
+    );
+    assert_eq!(category("owners")["score"], 50);
+    assert_eq!(category("format")["passed"], 1);
+    assert_eq!(category("upgrades")["score"], Value::Null);
+    // lint 100, pins 100, secrets 50, owners 50, format 50
+    assert_eq!(card["score"], 70);
+
+    let out = cargo_bin_cmd!("greentic-flow")
+        .args(["health", "--min-score", "80", "--dir"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(!out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("health 70/100"), "{stdout}");
+    assert!(
+        stdout.contains("flows/leaky.ygtc node 'start': literal value under a secret-looking key"),
+        "{stdout}"
+    );
+    assert!(
+        String::from_utf8_lossy(&out.stderr).contains("below --min-score 80"),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
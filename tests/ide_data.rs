@@ -0,0 +1,61 @@
+use greentic_flow::ide_data::{manifest_operation_properties, schema_properties};
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn schema_properties_carry_types_docs_and_nesting() {
+    let schema = json!({
+        "type": "object",
+        "required": ["url"],
+        "properties": {
+            "url": {"type": "string", "description": "Endpoint to call"},
+            "method": {"type": "string", "enum": ["GET", "POST"], "default": "GET"},
+            "timeout_ms": {"type": ["integer", "null"], "title": "Timeout"},
+            "headers": {
+                "type": "object",
+                "properties": {"accept": {"type": "string"}}
+            }
+        }
+    });
+    let properties = schema_properties(&schema);
+    let names: Vec<&str> = properties.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["headers", "method", "timeout_ms", "url"]);
+
+    let url = &properties[3];
+    assert!(url.required);
+    assert_eq!(url.kind.as_deref(), Some("string"));
+    assert_eq!(url.description.as_deref(), Some("Endpoint to call"));
+    let method = &properties[1];
+    assert!(!method.required);
+    assert_eq!(method.enum_values, vec![json!("GET"), json!("POST")]);
+    assert_eq!(method.default, Some(json!("GET")));
+    let timeout = &properties[2];
+    assert_eq!(timeout.kind.as_deref(), Some("integer|null"));
+    assert_eq!(timeout.description.as_deref(), Some("Timeout"));
+    assert_eq!(properties[0].properties[0].name, "accept");
+
+    assert!(schema_properties(&json!({"type": "string"})).is_empty());
+}
+
+#[test]
+fn manifest_operation_properties_covers_every_operation() {
+    let dir = tempdir().unwrap();
+    let manifest = dir.path().join("component.manifest.json");
+    fs::write(
+        &manifest,
+        json!({
+            "id": "ai.greentic.http",
+            "operations": [
+                {"name": "get", "input_schema": {"type": "object", "properties": {"url": {"type": "string"}}}},
+                {"name": "ping"}
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let operations = manifest_operation_properties(&manifest).unwrap();
+    assert_eq!(operations.keys().collect::<Vec<_>>(), vec!["get", "ping"]);
+    assert_eq!(operations["get"][0].name, "url");
+    assert!(operations["ping"].is_empty());
+}
@@ -0,0 +1,39 @@
+use greentic_flow::lint::{LintBaseline, LintDiagnostic};
+
+fn finding(message: &str) -> LintDiagnostic {
+    LintDiagnostic::error("entrypoint_targets", message).with_path("/entrypoints/telegram")
+}
+
+#[test]
+fn each_recorded_finding_suppresses_one_occurrence() {
+    let mut baseline = LintBaseline::default();
+    baseline.record("flows/main.ygtc", &[finding("dangling")]);
+
+    let (kept, suppressed) = baseline.suppress(
+        "flows/main.ygtc",
+        vec![finding("dangling"), finding("dangling"), finding("other")],
+    );
+    assert_eq!(suppressed, 1);
+    assert_eq!(kept, vec![finding("dangling"), finding("other")]);
+
+    let (kept, suppressed) = baseline.suppress("flows/other.ygtc", vec![finding("dangling")]);
+    assert_eq!(suppressed, 0);
+    assert_eq!(kept.len(), 1);
+}
+
+#[test]
+fn baseline_round_trips_and_rejects_unknown_versions() {
+    let mut baseline = LintBaseline::default();
+    baseline.record("b.ygtc", &[finding("second")]);
+    baseline.record("a.ygtc", &[finding("first")]);
+    let text = baseline.to_json_string();
+    let reloaded = LintBaseline::from_json_str(&text).unwrap();
+    assert_eq!(reloaded.findings[0].flow, "a.ygtc");
+    assert_eq!(reloaded.findings.len(), 2);
+
+    let err = LintBaseline::from_json_str(r#"{"version": 2, "findings": []}"#).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("unsupported lint baseline version 2")
+    );
+}
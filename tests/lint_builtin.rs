@@ -1,4 +1,13 @@
-use greentic_flow::{compile_flow, lint::lint_builtin_rules, loader::load_ygtc_from_str};
+use greentic_flow::{
+    compile_flow,
+    lint::{
+        LintSeverity, PlaceholderValuesRule, SecretLiteralsRule, TerminalPathsRule,
+        lint_builtin_diagnostics,
+    },
+    lint_to_stdout_json,
+    loader::load_ygtc_from_str,
+    source_map::{SourceMap, SourceSpan},
+};
 
 #[test]
 fn lint_flags_missing_start_node() {
@@ -12,11 +21,49 @@ nodes:
 "#;
     let doc = load_ygtc_from_str(yaml).unwrap();
     let flow = compile_flow(doc).unwrap();
-    let errors = lint_builtin_rules(&flow);
+    let errors = lint_builtin_diagnostics(&flow);
     assert!(
-        errors.iter().any(|e| e.contains("start node 'missing'")),
+        errors
+            .iter()
+            .any(|e| e.message.contains("start node 'missing'")),
         "expected missing start node lint, got {errors:?}"
     );
+    assert_eq!(errors[0].rule, "start_node_exists");
+    assert_eq!(errors[0].severity, LintSeverity::Error);
+    assert_eq!(errors[0].path.as_deref(), Some("/start"));
+    assert_eq!(
+        errors[0].to_string(),
+        "start_node_exists: start node 'missing' not found in nodes"
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn string_shim_and_json_output_keep_rule_prefixed_messages() {
+    let yaml = r#"
+id: demo
+type: messaging
+start: missing
+nodes:
+  entry:
+    qa.process: {}
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let flow = compile_flow(doc).unwrap();
+    assert_eq!(
+        greentic_flow::lint::lint_builtin_rules(&flow),
+        vec!["start_node_exists: start node 'missing' not found in nodes".to_string()]
+    );
+
+    let output: serde_json::Value = serde_json::from_str(&lint_to_stdout_json(yaml)).unwrap();
+    let error = &output["errors"][0];
+    assert_eq!(error["rule"], "start_node_exists");
+    assert_eq!(error["severity"], "error");
+    assert_eq!(error["json_pointer"], "/start");
+    assert_eq!(
+        error["message"],
+        "start_node_exists: start node 'missing' not found in nodes"
+    );
 }
 
 #[test]
@@ -31,6 +78,273 @@ nodes:
 "#;
     let doc = load_ygtc_from_str(yaml).unwrap();
     let flow = compile_flow(doc).unwrap();
-    let errors = lint_builtin_rules(&flow);
+    let errors = lint_builtin_diagnostics(&flow);
     assert!(errors.is_empty(), "unexpected lint errors: {errors:?}");
 }
+
+#[test]
+fn placeholder_rule_flags_default_patterns_with_pointer() {
+    let yaml = r#"
+id: demo
+type: messaging
+start: entry
+nodes:
+  entry:
+    qa.process:
+      prompt: "TODO"
+      headers:
+        - name: auth
+          value: "__API_KEY__"
+      note: "TODO: polish copy"
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let flow = compile_flow(doc).unwrap();
+    let errors = PlaceholderValuesRule::default().check(&flow);
+    assert_eq!(errors.len(), 2, "unexpected placeholder hits: {errors:?}");
+    assert!(
+        errors.iter().any(|e| e.message.contains("at /prompt")),
+        "{errors:?}"
+    );
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.message.contains("at /headers/0/value")
+                && e.message.contains("'__API_KEY__'")),
+        "{errors:?}"
+    );
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.path.as_deref() == Some("/nodes/entry/qa.process/headers/0/value")),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn placeholder_rule_uses_configured_patterns() {
+    let yaml = r#"
+id: demo
+type: messaging
+start: entry
+nodes:
+  entry:
+    qa.process:
+      prompt: "TODO"
+      region: "<region-here>"
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let flow = compile_flow(doc).unwrap();
+    let errors = PlaceholderValuesRule::new(["<*>".to_string()]).check(&flow);
+    assert_eq!(errors.len(), 1, "unexpected placeholder hits: {errors:?}");
+    assert!(errors[0].message.contains("at /region"), "{errors:?}");
+}
+
+#[test]
+fn unreachable_rule_walks_every_entrypoint_and_route_kind() {
+    let yaml = r#"
+id: demo
+type: messaging
+start: entry
+entrypoints:
+  webhook: hook
+nodes:
+  entry:
+    qa.process: {}
+    routing:
+      - status: ok
+        to: branch_ok
+      - status: "!ok"
+        to: negated
+      - out: true
+  branch_ok:
+    qa.process: {}
+    routing: out
+  negated:
+    qa.process: {}
+    routing: out
+  hook:
+    qa.process: {}
+    routing:
+      - to: from_hook
+  from_hook:
+    qa.process: {}
+    routing: out
+  orphan:
+    qa.process: {}
+    routing:
+      - to: orphan_child
+  orphan_child:
+    qa.process: {}
+    routing: out
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let flow = compile_flow(doc).unwrap();
+    let diagnostics = lint_builtin_diagnostics(&flow);
+    let unreachable: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| d.rule == "unreachable_node")
+        .collect();
+    assert_eq!(unreachable.len(), 2, "{diagnostics:?}");
+    assert_eq!(
+        unreachable[0].message,
+        "node 'orphan' is not reachable from any entrypoint"
+    );
+    assert_eq!(unreachable[0].severity, LintSeverity::Warning);
+    assert_eq!(unreachable[0].path.as_deref(), Some("/nodes/orphan"));
+    assert_eq!(unreachable[1].path.as_deref(), Some("/nodes/orphan_child"));
+
+    let output: serde_json::Value = serde_json::from_str(&lint_to_stdout_json(yaml)).unwrap();
+    assert_eq!(output["ok"], true, "unreachable nodes warn without failing");
+    assert_eq!(output["warnings"][0]["rule"], "unreachable_node");
+}
+
+#[test]
+fn unreachable_rule_stays_quiet_without_a_valid_entrypoint() {
+    let yaml = r#"
+id: demo
+type: messaging
+start: missing
+nodes:
+  entry:
+    qa.process: {}
+    routing:
+      - to: next
+  next:
+    qa.process: {}
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let flow = compile_flow(doc).unwrap();
+    let rules: Vec<_> = lint_builtin_diagnostics(&flow)
+        .into_iter()
+        .map(|d| d.rule)
+        .collect();
+    assert_eq!(rules, vec!["start_node_exists"]);
+}
+
+#[test]
+fn terminal_paths_rule_reports_nodes_that_never_end_the_flow() {
+    let yaml = r#"
+id: demo
+type: messaging
+start: entry
+nodes:
+  entry:
+    qa.process: {}
+    routing:
+      - status: ok
+        to: reply
+      - to: retry
+  reply:
+    qa.process: {}
+    routing: reply
+  retry:
+    qa.process: {}
+    routing:
+      - status: again
+        to: retry
+      - to: stuck
+  stuck:
+    qa.process: {}
+  orphan:
+    qa.process: {}
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let diagnostics = TerminalPathsRule::check(&doc);
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "no path from node 'retry' reaches an `out` or `reply` route",
+            "node 'stuck' dead-ends: it has no route to another node, `out` or `reply`",
+        ]
+    );
+    assert_eq!(diagnostics[0].rule, "terminal_paths");
+    assert_eq!(diagnostics[0].severity, LintSeverity::Warning);
+    assert_eq!(diagnostics[1].path.as_deref(), Some("/nodes/stuck"));
+
+    let fixed = load_ygtc_from_str(&yaml.replace(
+        "  stuck:\n    qa.process: {}\n",
+        "  stuck:\n    qa.process: {}\n    routing: out\n",
+    ))
+    .unwrap();
+    assert!(TerminalPathsRule::check(&fixed).is_empty());
+}
+
+#[test]
+fn secret_literals_flags_credentials_without_echoing_them() {
+    // Assembled at runtime so the fixtures themselves do not trip secret scanners.
+    let aws_key = format!("AKIA{}", "IOSFODNN7EXAMPLE");
+    let random = format!("{}{}", "q8Vt3kZr0LxW9", "mNc2Ye5HbPa7Gs");
+    let yaml = format!(
+        r#"
+id: demo
+type: messaging
+start: call
+nodes:
+  call:
+    http.request:
+      headers:
+        api_key: "literal-value"
+        x-token: "{{{{state.token}}}}"
+      aws: "{aws_key}"
+      session: "{random}"
+      commit: "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+      text: "Thanks for contacting support, we will reply shortly"
+    routing: out
+"#
+    );
+    let doc = load_ygtc_from_str(&yaml).unwrap();
+    let diagnostics = SecretLiteralsRule::check(&doc);
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "node 'call' has a literal value under a secret-looking key at \
+             /nodes/call/http.request/headers/api_key; reference a secret instead",
+            "node 'call' payload at /nodes/call/http.request/aws looks like an AWS access key id; \
+             reference a secret instead",
+            "node 'call' payload at /nodes/call/http.request/session holds a random-looking token \
+             that may be a credential",
+        ]
+    );
+    assert!(
+        diagnostics
+            .iter()
+            .all(|d| d.rule == "secret_literals" && !d.message.contains(&aws_key))
+    );
+    assert_eq!(diagnostics[1].severity, LintSeverity::Error);
+    assert_eq!(diagnostics[2].severity, LintSeverity::Warning);
+    assert_eq!(
+        diagnostics[2].path.as_deref(),
+        Some("/nodes/call/http.request/session")
+    );
+}
+
+#[test]
+fn json_output_locates_findings_in_source() {
+    let yaml = r#"
+id: demo
+type: messaging
+start: missing
+nodes:
+  entry:
+    qa.process: {}
+"#;
+    let payload: serde_json::Value = serde_json::from_str(&lint_to_stdout_json(yaml)).unwrap();
+    let error = &payload["errors"][0];
+    assert_eq!(error["json_pointer"], "/start");
+    assert_eq!(error["line"], 4);
+    assert_eq!(error["col"], 1);
+}
+
+#[test]
+fn source_map_locates_nested_values() {
+    let yaml = "id: demo\nnodes:\n  a/b:\n    qa.process:\n      prompts: [x, y]\n";
+    let spans = SourceMap::from_yaml(yaml);
+    let at = |line, col| Some(SourceSpan { line, col });
+    assert_eq!(spans.get("/id"), at(1, 1));
+    assert_eq!(spans.node("a/b"), at(3, 3));
+    assert_eq!(spans.get("/nodes/a~1b/qa.process/prompts/1"), at(5, 20));
+    assert_eq!(spans.locate("/nodes/a~1b/routing"), at(3, 3));
+    assert_eq!(spans.locate("/missing"), at(1, 1));
+}
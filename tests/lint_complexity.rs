@@ -0,0 +1,153 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    flow_ir::{FlowIr, analysis::FlowAnalysis},
+    lint::{ComplexityLimits, ComplexityRule, LINT_CONFIG_FILE, LintConfig},
+    loader::load_ygtc_from_str,
+};
+use std::fs;
+use tempfile::tempdir;
+
+/// A chain `n0 -> n1 -> ... -> n{len-1}` whose first node also fans out to every later node.
+fn chain(len: usize) -> String {
+    let mut yaml = "id: big\ntype: messaging\nschema_version: 2\nstart: n0\nnodes:\n".to_string();
+    for i in 0..len {
+        yaml.push_str(&format!("  n{i}:\n    op: {{}}\n    routing:\n"));
+        if i == 0 {
+            for j in 2..len {
+                yaml.push_str(&format!("      - status: s{j}\n        to: n{j}\n"));
+            }
+        }
+        if i + 1 < len {
+            yaml.push_str(&format!("      - to: n{}\n", i + 1));
+        } else {
+            yaml.push_str("      - out: true\n");
+        }
+    }
+    yaml
+}
+
+#[test]
+fn longest_path_counts_each_loop_once() {
+    let doc = load_ygtc_from_str(
+        r#"id: loop
+type: messaging
+schema_version: 2
+start: fetch
+nodes:
+  fetch:
+    op: {}
+    routing:
+      - status: retry
+        to: wait
+      - to: parse
+  wait:
+    op: {}
+    routing:
+      - to: fetch
+  parse:
+    op: {}
+    routing:
+      - to: reply
+  reply:
+    op: {}
+    routing: reply
+"#,
+    )
+    .unwrap();
+    let analysis = FlowAnalysis::new(&FlowIr::from_doc(doc).unwrap());
+    assert_eq!(analysis.longest_path(), vec!["fetch", "parse", "reply"]);
+}
+
+#[test]
+fn limits_cover_nodes_depth_and_fan_out() {
+    let doc = load_ygtc_from_str(&chain(10)).unwrap();
+    assert!(ComplexityRule::default().check(&doc).is_empty());
+
+    let rule = ComplexityRule::new(ComplexityLimits {
+        max_nodes: 8,
+        max_depth: 9,
+        max_fan_out: 4,
+    });
+    let findings: Vec<(String, String)> = rule
+        .check(&doc)
+        .into_iter()
+        .map(|d| (d.path.unwrap(), d.message))
+        .collect();
+    assert_eq!(
+        findings,
+        vec![
+            (
+                "/nodes".to_string(),
+                "flow has 10 nodes (limit 8); split it into smaller flows".to_string()
+            ),
+            (
+                "/nodes/n9".to_string(),
+                "longest route has 10 nodes (limit 9): n0 -> n1 -> n2 -> ... -> n7 -> n8 -> n9"
+                    .to_string()
+            ),
+            (
+                "/nodes/n0/routing".to_string(),
+                "node 'n0' routes to 9 nodes (limit 4)".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn config_overrides_only_the_listed_limits() {
+    let config = LintConfig::from_toml_str("[complexity]\nmax_nodes = 300\n").unwrap();
+    assert_eq!(
+        config.complexity,
+        Some(ComplexityLimits {
+            max_nodes: 300,
+            ..ComplexityLimits::default()
+        })
+    );
+    assert!(LintConfig::from_toml_str("[complexity]\nmax_edges = 3\n").is_err());
+}
+
+#[test]
+fn doctor_applies_project_complexity_limits() {
+    let dir = tempdir().unwrap();
+    let flow = dir.path().join("big.ygtc");
+    fs::write(&flow, chain(6)).unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    let nodes: Vec<String> = (0..6)
+        .map(|i| format!(r#""n{i}":{{"source":{{"kind":"local","path":"comp.wasm"}}}}"#))
+        .collect();
+    fs::write(
+        dir.path().join("big.ygtc.resolve.json"),
+        format!(
+            r#"{{"schema_version":1,"flow":"big.ygtc","nodes":{{{}}}}}"#,
+            nodes.join(",")
+        ),
+    )
+    .unwrap();
+
+    let doctor = || {
+        cargo_bin_cmd!("greentic-flow")
+            .arg("doctor")
+            .arg(&flow)
+            .output()
+            .unwrap()
+    };
+    let out = doctor();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    fs::write(
+        dir.path().join(LINT_CONFIG_FILE),
+        "[complexity]\nmax_nodes = 5\n",
+    )
+    .unwrap();
+    let out = doctor();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!out.status.success(), "{stderr}");
+    assert!(
+        stderr.contains("complexity: flow has 6 nodes (limit 5)"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,154 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::lint::{LINT_CONFIG_FILE, LintConfig, LintDiagnostic, LintSeverity, RuleLevel};
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+const CONFIG: &str = r#"
+[rules]
+unreachable_node = "off"
+routing_shorthand = "error"
+
+[[suppress]]
+rule = "placeholder_values"
+flow = "legacy/onboarding.ygtc"
+reason = "legacy copy"
+
+[[suppress]]
+rule = "routing_cycles"
+node = "loop"
+reason = "polls until done"
+"#;
+
+fn finding(rule: &str, path: &str) -> LintDiagnostic {
+    LintDiagnostic::error(rule, "finding").with_path(path)
+}
+
+/// A flow with a placeholder payload and an unreachable node, bound in its sidecar.
+fn write_flow(path: &Path) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path.with_file_name("comp.wasm"), b"wasm-bytes").unwrap();
+    fs::write(
+        path,
+        r#"id: onboarding
+type: messaging
+schema_version: 2
+start: ask
+nodes:
+  ask:
+    op:
+      prompt: TODO
+    routing: out
+  orphan:
+    op: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    let name = path.file_name().unwrap().to_string_lossy();
+    fs::write(
+        path.with_file_name(format!("{name}.resolve.json")),
+        format!(
+            r#"{{"schema_version":1,"flow":"{name}","nodes":{{"ask":{{"source":{{"kind":"local","path":"comp.wasm"}}}},"orphan":{{"source":{{"kind":"local","path":"comp.wasm"}}}}}}}}"#
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn parses_levels_and_requires_reasons() {
+    let config = LintConfig::from_toml_str(CONFIG).unwrap();
+    assert_eq!(config.rule_level("unreachable_node"), Some(RuleLevel::Off));
+    assert_eq!(
+        config.rule_level("Routing_Shorthand"),
+        Some(RuleLevel::Error)
+    );
+    assert_eq!(config.rule_level("routing_cycles"), None);
+    assert_eq!(
+        LintConfig::from_toml_str("[rules]\nx = \"warn\"\n")
+            .unwrap()
+            .rule_level("x"),
+        Some(RuleLevel::Warning)
+    );
+
+    let err = LintConfig::from_toml_str("[[suppress]]\nrule = \"x\"\nreason = \" \"\n")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("needs a reason"), "{err}");
+    assert!(LintConfig::from_toml_str("[rules]\nx = \"loud\"\n").is_err());
+}
+
+#[test]
+fn apply_drops_overrides_and_suppresses() {
+    let config = LintConfig::from_toml_str(CONFIG).unwrap();
+    let findings = vec![
+        finding("unreachable_node", "/nodes/orphan"),
+        finding("routing_shorthand", "/nodes/a/routing").with_severity(LintSeverity::Warning),
+        finding("placeholder_values", "/nodes/ask/op/prompt"),
+        finding("routing_cycles", "/nodes/loop/routing"),
+        finding("routing_cycles", "/nodes/loop2/routing"),
+    ];
+
+    let (kept, dropped) = config.apply(
+        Some(Path::new("/pack/legacy/onboarding.ygtc")),
+        findings.clone(),
+    );
+    assert_eq!(dropped, 3);
+    let kept: Vec<(&str, LintSeverity)> = kept
+        .iter()
+        .map(|d| (d.path.as_deref().unwrap(), d.severity))
+        .collect();
+    assert_eq!(
+        kept,
+        vec![
+            ("/nodes/a/routing", LintSeverity::Error),
+            ("/nodes/loop2/routing", LintSeverity::Error),
+        ]
+    );
+
+    // Flow-scoped suppressions need a matching path.
+    let (kept, _) = config.apply(Some(Path::new("/pack/onboarding.ygtc")), findings.clone());
+    assert!(kept.iter().any(|d| d.rule == "placeholder_values"));
+    let (kept, _) = config.apply(None, findings);
+    assert!(kept.iter().any(|d| d.rule == "placeholder_values"));
+}
+
+#[test]
+fn discover_walks_up_from_the_flow() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join(LINT_CONFIG_FILE), CONFIG).unwrap();
+    let nested = dir.path().join("legacy/deeper");
+    fs::create_dir_all(&nested).unwrap();
+    let (path, config) = LintConfig::discover(&nested).unwrap().unwrap();
+    assert_eq!(path, dir.path().join(LINT_CONFIG_FILE));
+    assert_eq!(config.suppress.len(), 2);
+}
+
+#[test]
+fn doctor_silences_a_rule_for_one_legacy_flow() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join(LINT_CONFIG_FILE), CONFIG).unwrap();
+    let legacy = dir.path().join("legacy/onboarding.ygtc");
+    let current = dir.path().join("flows/onboarding.ygtc");
+    write_flow(&legacy);
+    write_flow(&current);
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg(&legacy)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("silenced 2 finding(s)"), "{stderr}");
+    assert!(!stderr.contains("unreachable_node"), "{stderr}");
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .arg("doctor")
+        .arg(&current)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("placeholder_values"), "{stderr}");
+    assert!(!stderr.contains("unreachable_node"), "{stderr}");
+}
@@ -0,0 +1,292 @@
+use greentic_flow::{
+    flow_ir::FlowIr,
+    lint::{
+        ConflictingRoutesRule, EntrypointTargetsRule, FixApplicability, FixEdit, LintDiagnostic,
+        NodeNamingRule, RuleContext, RuleRegistry, apply_machine_applicable_fixes,
+    },
+    loader::load_ygtc_from_str,
+    model::FlowDoc,
+};
+use serde_json::json;
+
+const FLOW: &str = r#"
+id: main
+type: messaging
+schema_version: 2
+start: hello
+entrypoints:
+  telegram: Hello
+  web: nowhere-at-all
+nodes:
+  hello:
+    questions: {fields: []}
+    routing:
+      - to: out
+  bye:
+    questions: {fields: []}
+    routing:
+      - reply: true
+        label: answer the user
+"#;
+
+/// What the built-in rules report with a fix attached, as `doctor --fix` sees them.
+fn fixable_findings(doc: &FlowDoc) -> Vec<LintDiagnostic> {
+    let flow = greentic_flow::compile_flow(doc.clone()).unwrap();
+    RuleRegistry::builtin()
+        .run(&flow, &RuleContext::default().with_doc(doc))
+        .into_iter()
+        .filter(|diagnostic| diagnostic.fix.is_some())
+        .collect()
+}
+
+#[test]
+fn doc_rules_attach_structured_fixes() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let diagnostics = fixable_findings(&doc);
+    let summary: Vec<_> = diagnostics
+        .iter()
+        .map(|d| {
+            (
+                d.rule.as_str(),
+                d.path.as_deref().unwrap_or_default(),
+                d.fix.as_ref().map(|fix| fix.applicability),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "entrypoint_targets",
+                "/entrypoints/telegram",
+                Some(FixApplicability::MachineApplicable)
+            ),
+            (
+                "entrypoint_targets",
+                "/entrypoints/web",
+                Some(FixApplicability::MaybeIncorrect)
+            ),
+            (
+                "routing_shorthand",
+                "/nodes/hello/routing",
+                Some(FixApplicability::MachineApplicable)
+            ),
+        ]
+    );
+    assert_eq!(
+        diagnostics[0].fix.as_ref().unwrap().edits,
+        vec![FixEdit::RetargetEntrypoint {
+            name: "telegram".to_string(),
+            target: "hello".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn machine_applicable_fixes_edit_the_ir() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let diagnostics = fixable_findings(&doc);
+    let mut flow = FlowIr::from_doc(doc).unwrap();
+    let applied = apply_machine_applicable_fixes(&mut flow, &diagnostics).unwrap();
+    assert_eq!(applied.len(), 2);
+
+    let fixed = flow.to_doc().unwrap();
+    assert_eq!(fixed.entrypoints["telegram"], json!("hello"));
+    assert_eq!(fixed.entrypoints["web"], json!("nowhere-at-all"));
+    assert_eq!(fixed.nodes["hello"].routing, json!("out"));
+}
+
+#[test]
+fn only_case_mismatches_retarget_entrypoints_without_review() {
+    let doc = load_ygtc_from_str(
+        r#"
+id: main
+type: messaging
+start: begin
+entrypoints:
+  chat: go
+  web: BEGIN
+nodes:
+  begin:
+    questions: {fields: []}
+    routing:
+      - to: do
+  do:
+    questions: {fields: []}
+    routing: out
+"#,
+    )
+    .unwrap();
+    let fixes: Vec<_> = EntrypointTargetsRule::check(&doc)
+        .into_iter()
+        .map(|d| {
+            let fix = d.fix.unwrap();
+            (fix.description, fix.applicability)
+        })
+        .collect();
+    assert_eq!(
+        fixes,
+        vec![
+            (
+                "retarget entrypoint 'chat' to 'do'".to_string(),
+                FixApplicability::MaybeIncorrect
+            ),
+            (
+                "retarget entrypoint 'web' to 'begin'".to_string(),
+                FixApplicability::MachineApplicable
+            ),
+        ]
+    );
+}
+
+#[test]
+fn conflicting_routes_point_at_the_route_index() {
+    // The loader rejects unreachable status routes, so build this document without it.
+    let doc: FlowDoc = serde_yaml_bw::from_str(
+        r#"
+id: main
+type: messaging
+start: pay
+nodes:
+  pay:
+    questions: {fields: []}
+    routing:
+      - status: "!ok"
+        to: declined
+      - status: e412
+        to: declined
+      - to: done
+      - to: done
+        label: again
+  declined:
+    questions: {fields: []}
+    routing: out
+  done:
+    questions: {fields: []}
+    routing: out
+"#,
+    )
+    .unwrap();
+    let diagnostics = ConflictingRoutesRule::check(&doc);
+    let summary: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.path.as_deref().unwrap_or_default(), d.message.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "/nodes/pay/routing/1",
+                "node 'pay': route 1 status 'e412' is unreachable; earlier routes already match every status it matches"
+            ),
+            (
+                "/nodes/pay/routing/3",
+                "node 'pay': route 3 duplicates route 2 (same status and target)"
+            ),
+        ]
+    );
+    // Dropping the duplicate fan-out changes what runs, so the fix needs review.
+    let fix = diagnostics[0].fix.as_ref().unwrap();
+    assert_eq!(fix.applicability, FixApplicability::MaybeIncorrect);
+    assert_eq!(fix.description, "remove route 1, 3 of node 'pay'");
+
+    let shadowed_only: FlowDoc = serde_yaml_bw::from_str(
+        r#"
+id: main
+type: messaging
+nodes:
+  pay:
+    questions: {fields: []}
+    routing:
+      - status: "*"
+        out: true
+      - status: "*"
+        reply: true
+"#,
+    )
+    .unwrap();
+    let diagnostics = ConflictingRoutesRule::check(&shadowed_only);
+    assert_eq!(diagnostics.len(), 1);
+    let mut flow = FlowIr::from_doc(shadowed_only).unwrap();
+    apply_machine_applicable_fixes(&mut flow, &diagnostics).unwrap();
+    assert_eq!(flow.nodes["pay"].routing.len(), 1);
+    assert!(flow.nodes["pay"].routing[0].out);
+}
+
+#[test]
+fn node_naming_renames_to_snake_case_when_the_id_is_free() {
+    let doc = load_ygtc_from_str(
+        r#"
+id: main
+type: messaging
+schema_version: 2
+start: fetchUser
+entrypoints:
+  web: fetchUser
+nodes:
+  fetchUser:
+    questions: {fields: []}
+    routing:
+      - to: HTTPCall
+  HTTPCall:
+    questions: {fields: []}
+    routing:
+      - to: send-reply
+  send-reply:
+    questions: {fields: []}
+    routing:
+      - to: Send_Reply
+  Send_Reply:
+    questions: {fields: []}
+    routing: reply
+"#,
+    )
+    .unwrap();
+    let diagnostics = NodeNamingRule::check(&doc);
+    let summary: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.message.as_str(), d.fix.as_ref().map(|fix| &fix.edits)))
+        .collect();
+    let rename = |from: &str, to: &str| {
+        vec![FixEdit::RenameNode {
+            from: from.to_string(),
+            to: to.to_string(),
+        }]
+    };
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "node id 'fetchUser' is not snake_case (expected 'fetch_user')",
+                Some(&rename("fetchUser", "fetch_user"))
+            ),
+            (
+                "node id 'HTTPCall' is not snake_case (expected 'http_call')",
+                Some(&rename("HTTPCall", "http_call"))
+            ),
+            (
+                "node id 'send-reply' is not snake_case (expected 'send_reply')",
+                Some(&rename("send-reply", "send_reply"))
+            ),
+            // Another node already claimed the id, so this one is left to a human.
+            (
+                "node id 'Send_Reply' is not snake_case (expected 'send_reply')",
+                None
+            ),
+        ]
+    );
+
+    let mut flow = FlowIr::from_doc(doc).unwrap();
+    apply_machine_applicable_fixes(&mut flow, &diagnostics).unwrap();
+    let fixed = flow.to_doc().unwrap();
+    assert_eq!(fixed.start.as_deref(), Some("fetch_user"));
+    assert_eq!(fixed.entrypoints["web"], json!("fetch_user"));
+    assert_eq!(
+        fixed.nodes.keys().collect::<Vec<_>>(),
+        vec!["fetch_user", "http_call", "send_reply", "Send_Reply"]
+    );
+    assert_eq!(
+        fixed.nodes["http_call"].routing,
+        json!([{"to": "send_reply"}])
+    );
+}
@@ -95,3 +95,47 @@ nodes:
             .unwrap_or(false)
     }));
 }
+
+#[test]
+fn json_mode_exposes_fixes_and_warnings() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    std::fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+start: hello
+entrypoints:
+  telegram: Hello
+nodes:
+  hello:
+    questions: {fields: []}
+    routing:
+      - reply: true
+"#,
+    )
+    .unwrap();
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("greentic-flow"));
+    let assert = cmd
+        .arg("doctor")
+        .arg("--json")
+        .arg(&flow_path)
+        .assert()
+        .failure();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let payload: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        payload["errors"][0]["fix"],
+        serde_json::json!({
+            "description": "retarget entrypoint 'telegram' to 'hello'",
+            "applicability": "machine-applicable",
+            "edits": [{"op": "retarget_entrypoint", "name": "telegram", "target": "hello"}]
+        })
+    );
+    assert_eq!(payload["warnings"][0]["rule"], "routing_shorthand");
+    assert_eq!(
+        payload["warnings"][0]["fix"]["edits"][0],
+        serde_json::json!({"op": "set_routing", "node_id": "hello", "routing": [{"reply": true}]})
+    );
+}
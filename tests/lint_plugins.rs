@@ -0,0 +1,154 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    compile_flow,
+    lint::{LintRule, LintSeverity, RuleContext, RuleRegistry, WasmLintRule},
+    loader::load_ygtc_from_str,
+};
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+/// A plugin with rule id `forbidden_components` that warns when the flow IR mentions `legacy`.
+const PLUGIN_WAT: &str = r#"
+(component
+  (core module $m
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 4096))
+    (data (i32.const 0) "forbidden_components")
+    (data (i32.const 64) "\81\a3\67\6d\65\73\73\61\67\65\78\22\63\6f\6d\70\6f\6e\65\6e\74\20\61\63\6d\65\2e\6c\65\67\61\63\79\20\69\73\20\66\6f\72\62\69\64\64\65\6e\64\70\61\74\68\6d\2f\6e\6f\64\65\73\2f\6c\65\67\61\63\79\68\73\65\76\65\72\69\74\79\67\77\61\72\6e\69\6e\67")
+    (data (i32.const 160) "\80")
+    (data (i32.const 176) "legacy")
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $p i32)
+      (local.set $p
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get 2) (i32.const 1)))
+          (i32.xor (i32.sub (local.get 2) (i32.const 1)) (i32.const -1))))
+      (global.set $heap (i32.add (local.get $p) (local.get 3)))
+      (local.get $p))
+    (func (export "id") (result i32)
+      (i32.store (i32.const 200) (i32.const 0))
+      (i32.store (i32.const 204) (i32.const 20))
+      (i32.const 200))
+    (func (export "check") (param $ptr i32) (param $len i32) (result i32)
+      (local $i i32)
+      (local $end i32)
+      (i32.store (i32.const 208) (i32.const 160))
+      (i32.store (i32.const 212) (i32.const 1))
+      (local.set $end (i32.add (local.get $ptr) (i32.sub (local.get $len) (i32.const 6))))
+      (local.set $i (local.get $ptr))
+      (block $done
+        (loop $scan
+          (br_if $done (i32.gt_s (local.get $i) (local.get $end)))
+          (if (i32.and
+                (i32.eq (i32.load (local.get $i)) (i32.load (i32.const 176)))
+                (i32.eq
+                  (i32.load16_u (i32.add (local.get $i) (i32.const 4)))
+                  (i32.load16_u (i32.const 180))))
+            (then
+              (i32.store (i32.const 208) (i32.const 64))
+              (i32.store (i32.const 212) (i32.const 82))
+              (br $done)))
+          (local.set $i (i32.add (local.get $i) (i32.const 1)))
+          (br $scan)))
+      (i32.const 208)))
+  (core instance $i (instantiate $m))
+  (func $id (result string)
+    (canon lift (core func $i "id") (memory $i "memory") (realloc (func $i "realloc"))))
+  (func $check (param "flow" (list u8)) (result (list u8))
+    (canon lift (core func $i "check") (memory $i "memory") (realloc (func $i "realloc"))))
+  (instance $lint (export "id" (func $id)) (export "check" (func $check)))
+  (export "greentic:flow/lint-rule@0.1.0" (instance $lint)))
+"#;
+
+const FLOW: &str = r#"id: main
+type: messaging
+schema_version: 2
+start: legacy
+nodes:
+  legacy:
+    qa.process: {}
+    routing: out
+"#;
+
+fn write_plugin(dir: &Path) -> std::path::PathBuf {
+    let path = dir.join("forbidden.wasm");
+    fs::write(&path, wat::parse_str(PLUGIN_WAT).unwrap()).unwrap();
+    path
+}
+
+#[test]
+fn plugin_rule_receives_the_flow_and_reports_findings() {
+    let dir = tempdir().unwrap();
+    let rule = WasmLintRule::load(&write_plugin(dir.path())).unwrap();
+    assert_eq!(rule.id(), "forbidden_components");
+
+    let mut registry = RuleRegistry::new();
+    registry.register(rule);
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let flow = compile_flow(doc.clone()).unwrap();
+    let found = registry.run(&flow, &RuleContext::default().with_doc(&doc));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].rule, "forbidden_components");
+    assert_eq!(found[0].severity, LintSeverity::Warning);
+    assert_eq!(found[0].message, "component acme.legacy is forbidden");
+    assert_eq!(found[0].path.as_deref(), Some("/nodes/legacy"));
+
+    let clean = load_ygtc_from_str(&FLOW.replace("legacy", "greet")).unwrap();
+    let flow = compile_flow(clean.clone()).unwrap();
+    assert!(
+        registry
+            .run(&flow, &RuleContext::default().with_doc(&clean))
+            .is_empty()
+    );
+}
+
+#[test]
+fn load_rejects_components_without_the_interface() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("empty.wasm");
+    fs::write(&path, wat::parse_str("(component)").unwrap()).unwrap();
+    let err = WasmLintRule::load(&path).unwrap_err().to_string();
+    assert!(err.contains("missing exported"), "{err}");
+}
+
+#[test]
+fn doctor_runs_lint_plugins() {
+    let dir = tempdir().unwrap();
+    let plugin = write_plugin(dir.path());
+    let flow_path = dir.path().join("main.ygtc");
+    fs::write(&flow_path, FLOW).unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    fs::write(
+        dir.path().join("main.ygtc.resolve.json"),
+        r#"{"schema_version":1,"flow":"main.ygtc","nodes":{"legacy":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .arg("lint")
+        .arg("--lint-plugin")
+        .arg(&plugin)
+        .arg(&flow_path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(
+        stderr.contains("forbidden_components: component acme.legacy is forbidden"),
+        "{stderr}"
+    );
+
+    // A project config can make the plugin's rule fail the run.
+    fs::write(
+        dir.path().join(".greentic-lint.toml"),
+        "[rules]\nforbidden_components = \"error\"\n",
+    )
+    .unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .arg("lint")
+        .arg("--lint-plugin")
+        .arg(&plugin)
+        .arg(&flow_path)
+        .assert()
+        .failure();
+}
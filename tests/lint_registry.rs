@@ -0,0 +1,116 @@
+use greentic_flow::{
+    compile_flow,
+    lint::{
+        EntrypointTargetsRule, LintDiagnostic, LintRule, LintSeverity, RuleContext, RuleRegistry,
+        UnreachableNodesRule, lint_builtin_diagnostics,
+    },
+    loader::load_ygtc_from_str,
+};
+use greentic_types::Flow;
+
+const FLOW: &str = r#"
+id: demo
+type: messaging
+start: missing
+entrypoints:
+  telegram: entyr
+nodes:
+  entry:
+    qa.process: {}
+    routing: out
+  orphan:
+    qa.process: {}
+    routing: out
+"#;
+
+/// Flags flows with more nodes than a project allows.
+struct MaxNodes(usize);
+
+impl LintRule for MaxNodes {
+    fn id(&self) -> &str {
+        "max_nodes"
+    }
+
+    fn severity(&self) -> LintSeverity {
+        LintSeverity::Warning
+    }
+
+    fn check(&self, flow: &Flow, _ctx: &RuleContext<'_>) -> Vec<LintDiagnostic> {
+        if flow.nodes.len() <= self.0 {
+            return Vec::new();
+        }
+        vec![
+            LintDiagnostic::error(self.id(), format!("flow has {} nodes", flow.nodes.len()))
+                .with_severity(self.severity()),
+        ]
+    }
+}
+
+fn rules(diagnostics: &[LintDiagnostic]) -> Vec<&str> {
+    diagnostics.iter().map(|d| d.rule.as_str()).collect()
+}
+
+#[test]
+fn builtin_registry_matches_the_lint_functions() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let flow = compile_flow(doc.clone()).unwrap();
+    let registry = RuleRegistry::builtin();
+    assert_eq!(
+        registry.ids().collect::<Vec<_>>(),
+        vec![
+            "start_node_exists",
+            "unreachable_node",
+            "adapter_resolvable",
+            "entrypoint_targets",
+            "routing_shorthand",
+            "node_naming",
+            "routing_cycles",
+            "conflicting_routes",
+            "terminal_paths",
+            "output_mapping",
+            "state_references",
+            "secret_literals",
+            "undefined_feature",
+            "node_expiry",
+            "complexity",
+        ]
+    );
+
+    let mut expected = lint_builtin_diagnostics(&flow);
+    expected.extend(EntrypointTargetsRule::check(&doc));
+    let found = registry.run(&flow, &RuleContext::default().with_doc(&doc));
+    assert_eq!(found, expected);
+    assert_eq!(
+        rules(&found),
+        vec!["start_node_exists", "entrypoint_targets"]
+    );
+
+    // Without the authored document only the compiled-flow rules run.
+    let compiled_only = registry.run(&flow, &RuleContext::default());
+    assert_eq!(rules(&compiled_only), vec!["start_node_exists"]);
+}
+
+#[test]
+fn external_rules_register_and_replace_by_id() {
+    let doc = load_ygtc_from_str(FLOW).unwrap();
+    let flow = compile_flow(doc).unwrap();
+    let mut registry = RuleRegistry::new();
+    registry
+        .register(MaxNodes(5))
+        .register(UnreachableNodesRule);
+    assert!(registry.run(&flow, &RuleContext::default()).is_empty());
+
+    registry.register(MaxNodes(1));
+    assert_eq!(registry.len(), 2);
+    assert_eq!(
+        registry.get("max_nodes").unwrap().severity(),
+        LintSeverity::Warning
+    );
+    let found = registry.run(&flow, &RuleContext::default());
+    assert_eq!(rules(&found), vec!["max_nodes"]);
+    assert_eq!(found[0].message, "flow has 2 nodes");
+
+    assert!(registry.unregister("max_nodes").is_some());
+    assert!(registry.unregister("max_nodes").is_none());
+    assert_eq!(registry.ids().collect::<Vec<_>>(), vec!["unreachable_node"]);
+}
@@ -0,0 +1,93 @@
+use greentic_flow::lint::{
+    LintDiagnostic, apply_lint_suppressions, expired_suppression_warnings, parse_lint_suppressions,
+    utc_today,
+};
+
+const FLOW: &str = r#"id: main
+type: messaging
+nodes:
+  # greentic-lint: disable=status_routes,placeholder_values reason="migrating" until=2030-06-30
+  fetch:
+    http.get: {}
+    routing: out
+  "store.put": {}  # greentic-lint: disable=placeholder_values reason="filled by CI"
+
+  # greentic-lint: disable=placeholder_values reason="detached by the blank line"
+
+  reply:
+    questions: {fields: []}
+    # greentic-lint: disable=placeholder_values reason="above a payload key, not a node"
+    routing: out
+"#;
+
+fn finding(rule: &str, node: &str) -> LintDiagnostic {
+    LintDiagnostic::error(rule, "finding").with_path(format!("/nodes/{node}/payload"))
+}
+
+#[test]
+fn parses_comments_above_or_trailing_node_keys() {
+    let (suppressions, problems) = parse_lint_suppressions(FLOW);
+    assert!(problems.is_empty(), "{problems:?}");
+    assert_eq!(suppressions.len(), 2);
+    assert_eq!(suppressions[0].node_id, "fetch");
+    assert_eq!(
+        suppressions[0].rules,
+        vec!["status_routes", "placeholder_values"]
+    );
+    assert_eq!(suppressions[0].reason, "migrating");
+    assert_eq!(suppressions[0].until.as_deref(), Some("2030-06-30"));
+    assert_eq!(suppressions[0].line, 4);
+    assert_eq!(suppressions[1].node_id, "store.put");
+    assert_eq!(suppressions[1].reason, "filled by CI");
+}
+
+#[test]
+fn suppresses_matching_rules_on_the_node_until_expiry() {
+    let (mut suppressions, _) = parse_lint_suppressions(FLOW);
+    let kept = apply_lint_suppressions(
+        &mut suppressions,
+        vec![
+            finding("STATUS_ROUTES", "fetch"),
+            finding("status_routes", "reply"),
+            finding("component_config", "fetch"),
+        ],
+        "2030-06-30",
+    );
+    assert_eq!(kept.len(), 2);
+    assert_eq!(suppressions[0].suppressed, 1);
+    assert!(expired_suppression_warnings(&suppressions, "2030-06-30").is_empty());
+
+    let (mut suppressions, _) = parse_lint_suppressions(FLOW);
+    let kept = apply_lint_suppressions(
+        &mut suppressions,
+        vec![finding("status_routes", "fetch")],
+        "2030-07-01",
+    );
+    assert_eq!(kept.len(), 1);
+    let warnings = expired_suppression_warnings(&suppressions, "2030-07-01");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].rule, "lint_suppression");
+}
+
+#[test]
+fn malformed_comments_are_reported() {
+    let flow = "nodes:\n  a:  # greentic-lint: disable=x until=soon reason=\"r\"\n    routing: out\n  # greentic-lint: enable=x\n  b: {}\n";
+    let (suppressions, problems) = parse_lint_suppressions(flow);
+    assert!(suppressions.is_empty());
+    let messages: Vec<String> = problems.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "lint_suppression: node 'a': until must be a YYYY-MM-DD date, found 'soon'",
+            "lint_suppression: node 'b': unknown greentic-lint key 'enable'",
+        ]
+    );
+    assert_eq!(problems[0].span.map(|span| span.line), Some(2));
+}
+
+#[test]
+fn utc_today_is_an_iso_date() {
+    let today = utc_today();
+    assert_eq!(today.len(), 10);
+    assert!(today.as_str() > "2024-01-01");
+}
@@ -13,6 +13,19 @@ fn two_components_is_error() {
     assert!(msg.contains("must contain exactly one component key"));
 }
 
+#[test]
+fn shape_error_lists_conflicting_keys() {
+    let yaml = std::fs::read_to_string("fixtures/invalid_node_shape.ygtc").unwrap();
+    match load_ygtc_from_str(&yaml).unwrap_err() {
+        FlowError::NodeComponentShape { keys, .. } => {
+            assert_eq!(keys, vec!["mcp.exec", "qa.process"]);
+        }
+        other => panic!("expected node shape error, got {other:?}"),
+    }
+    let msg = load_ygtc_from_str(&yaml).unwrap_err().to_string();
+    assert!(msg.contains("found 'mcp.exec', 'qa.process'"), "{msg}");
+}
+
 #[test]
 fn location_includes_source_label() {
     let yaml = std::fs::read_to_string("fixtures/invalid_node_shape.ygtc").unwrap();
@@ -33,6 +46,53 @@ fn location_includes_source_label() {
     }
 }
 
+#[test]
+fn reserved_key_as_operation_is_reported() {
+    let yaml = r#"id: main
+type: messaging
+start: start
+nodes:
+  start:
+    retry:
+      attempts: 3
+    routing: out
+"#;
+    let err = load_ygtc_from_str(yaml).unwrap_err();
+    match &err {
+        FlowError::ReservedOperationKey { node_id, key, .. } => {
+            assert_eq!(node_id, "start");
+            assert_eq!(key, "retry");
+        }
+        other => panic!("expected reserved key error, got {other:?}"),
+    }
+    assert!(
+        err.to_string().contains("reserved node keys: routing"),
+        "{err}"
+    );
+}
+
+#[test]
+fn flow_ir_rejects_reserved_operation_names() {
+    let mut ir = greentic_flow::flow_ir::parse_flow_to_ir(
+        r#"id: main
+type: messaging
+start: start
+nodes:
+  start:
+    handle_message:
+      text: hi
+    routing: out
+"#,
+    )
+    .unwrap();
+    ir.nodes.get_mut("start").unwrap().operation = "timeout".to_string();
+    let err = ir.to_doc().unwrap_err();
+    assert!(
+        matches!(err, FlowError::ReservedOperationKey { ref key, .. } if key == "timeout"),
+        "{err:?}"
+    );
+}
+
 #[test]
 fn schema_error_exposes_details() {
     let yaml = "id: missing_type\nnodes: {}\n";
@@ -45,3 +105,79 @@ fn schema_error_exposes_details() {
         other => panic!("expected schema error, got {other:?}"),
     }
 }
+
+fn status_routes_flow(routes: &str) -> String {
+    format!(
+        "id: main\ntype: messaging\nschema_version: 2\nstart: pay\nnodes:\n  pay:\n    charge: {{}}\n    routing:\n{routes}  done:\n    finish: {{}}\n    routing: out\n"
+    )
+}
+
+#[test]
+fn unreachable_status_routes_are_rejected() {
+    let cases = [
+        (
+            "      - status: \"*\"\n        to: done\n      - status: \"*\"\n        to: done\n",
+            "route 1 is a second catch-all status '*' (first at route 0)",
+        ),
+        (
+            "      - status: \"*\"\n        to: done\n      - status: e412\n        to: done\n",
+            "route 1 status 'e412' is unreachable",
+        ),
+        (
+            "      - status: \"!ok\"\n        to: done\n      - status: e412\n        to: done\n",
+            "route 1 status 'e412' is unreachable",
+        ),
+        (
+            "      - status: \"!ok\"\n        to: done\n      - status: \"!e412\"\n        to: done\n      - status: \"!ok\"\n        to: done\n",
+            "route 2 status '!ok' is unreachable",
+        ),
+        (
+            "      - status: \"!\"\n        to: done\n",
+            "route 0 has an empty status negation '!'",
+        ),
+    ];
+    for (routes, expected) in cases {
+        match load_ygtc_from_str(&status_routes_flow(routes)).unwrap_err() {
+            FlowError::Routing {
+                node_id, message, ..
+            } => {
+                assert_eq!(node_id, "pay");
+                assert!(message.contains(expected), "{message}");
+            }
+            other => panic!("expected routing error, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn shape_error_points_at_node_line() {
+    let yaml = std::fs::read_to_string("fixtures/invalid_node_shape.ygtc").unwrap();
+    match load_ygtc_from_str(&yaml).unwrap_err() {
+        FlowError::NodeComponentShape { location, .. } => {
+            assert_eq!((location.line, location.col), (Some(4), Some(3)));
+            assert_eq!(location.json_pointer.as_deref(), Some("/nodes/x"));
+        }
+        other => panic!("expected node shape error, got {other:?}"),
+    }
+}
+
+#[test]
+fn missing_route_target_points_at_routing_line() {
+    let yaml = r#"id: main
+type: messaging
+start: start
+nodes:
+  start:
+    qa.process: {}
+    routing:
+      - to: nowhere
+"#;
+    match load_ygtc_from_str(yaml).unwrap_err() {
+        FlowError::MissingNode { location, .. } => {
+            assert_eq!((location.line, location.col), (Some(7), Some(5)));
+            let msg = location.describe().unwrap();
+            assert!(msg.ends_with(":7:5"), "{msg}");
+        }
+        other => panic!("expected missing node error, got {other:?}"),
+    }
+}
@@ -135,3 +135,41 @@ nodes:
         Some("templating.handlebars")
     );
 }
+
+#[test]
+fn reserved_keys_after_the_operation_do_not_replace_it() {
+    let yaml = r#"
+id: reserved_flow
+type: messaging
+schema_version: 2
+start: send
+nodes:
+  send:
+    messaging.telegram.sendMessage:
+      text: "hi"
+    output:
+      select: "$.id"
+    meta:
+      adapter_version: "^1"
+    routing:
+      - to: exec
+  exec:
+    component.exec:
+      component: "ai.greentic.echo"
+    operation: run
+    routing: out
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let flow = compile_flow(doc).unwrap();
+    let send = flow.nodes.get(&NodeId::new("send").unwrap()).unwrap();
+    assert_eq!(
+        send.component.operation.as_deref(),
+        Some("messaging.telegram.sendMessage")
+    );
+    assert_eq!(send.input.mapping, json!({"text": "hi"}));
+
+    let exec = flow.nodes.get(&NodeId::new("exec").unwrap()).unwrap();
+    assert_eq!(exec.component.id.as_str(), "component.exec");
+    assert_eq!(exec.component.operation.as_deref(), Some("run"));
+    assert_eq!(exec.input.mapping, json!({"component": "ai.greentic.echo"}));
+}
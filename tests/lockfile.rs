@@ -0,0 +1,191 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    component_resolver::{FixtureResolver, ResolverRegistry, fixture_key},
+    flow_bundle::ComponentSourceKind,
+    lockfile::{FlowLock, diff_lock, lock_flows, verify_flows},
+    resolve_summary::sha256_digest,
+};
+use greentic_types::flow_resolve::{
+    ComponentSourceRefV1, FlowResolveV1, NodeResolveV1, sidecar_path_for_flow, write_flow_resolve,
+};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tempfile::{TempDir, tempdir};
+
+const FLOW: &str = r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  start:
+    send:
+      text: hi
+    routing: out
+"#;
+
+const REMOTE: &str = "oci://ghcr.io/acme/charge:1";
+
+fn write_flow(path: &Path, nodes: Vec<(&str, ComponentSourceRefV1)>) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, FLOW).unwrap();
+    let sidecar = FlowResolveV1 {
+        schema_version: 1,
+        flow: path.file_name().unwrap().to_string_lossy().into_owned(),
+        nodes: nodes
+            .into_iter()
+            .map(|(id, source)| (id.to_string(), NodeResolveV1 { source, mode: None }))
+            .collect::<BTreeMap<_, _>>(),
+    };
+    write_flow_resolve(&sidecar_path_for_flow(path), &sidecar).unwrap();
+}
+
+fn local(path: &str) -> ComponentSourceRefV1 {
+    ComponentSourceRefV1::Local {
+        path: path.to_string(),
+        digest: None,
+    }
+}
+
+fn remote() -> ComponentSourceRefV1 {
+    ComponentSourceRefV1::Oci {
+        r#ref: REMOTE.to_string(),
+        digest: None,
+    }
+}
+
+/// A project with `flows/main.ygtc` (one local, one remote component) and `flows/other.ygtc`,
+/// plus a fixture directory serving the remote component.
+fn project() -> (TempDir, PathBuf, Vec<PathBuf>) {
+    let dir = tempdir().unwrap();
+    let fixtures = dir.path().join("fixtures");
+    fs::create_dir_all(&fixtures).unwrap();
+    fs::write(
+        fixtures.join(format!("{}.wasm", fixture_key(REMOTE))),
+        b"charge-v1",
+    )
+    .unwrap();
+    fs::create_dir_all(dir.path().join("flows")).unwrap();
+    fs::write(dir.path().join("flows/comp.wasm"), b"local").unwrap();
+    let main = dir.path().join("flows/main.ygtc");
+    let other = dir.path().join("flows/other.ygtc");
+    write_flow(
+        &main,
+        vec![("start", local("file://comp.wasm")), ("charge", remote())],
+    );
+    write_flow(&other, vec![("start", local("file://comp.wasm"))]);
+    (dir, fixtures, vec![main, other])
+}
+
+fn registry(fixtures: &Path) -> ResolverRegistry {
+    ResolverRegistry::builtin().with(Arc::new(FixtureResolver::new(fixtures)))
+}
+
+#[test]
+fn locks_every_bound_node_deterministically() {
+    let (dir, fixtures, flows) = project();
+    let lock_path = dir.path().join("flow.lock");
+    let lock = lock_flows(&registry(&fixtures), &lock_path, &flows).unwrap();
+
+    assert_eq!(lock.components(), 3);
+    let main = &lock.flows["flows/main.ygtc"];
+    assert_eq!(main["start"].kind, ComponentSourceKind::Local);
+    assert_eq!(main["start"].digest, sha256_digest(b"local"));
+    assert_eq!(main["charge"].reference, REMOTE);
+    assert_eq!(main["charge"].digest, sha256_digest(b"charge-v1"));
+
+    let text = lock.to_json_string();
+    let again = lock_flows(&registry(&fixtures), &lock_path, &flows).unwrap();
+    assert_eq!(again.to_json_string(), text);
+    assert_eq!(FlowLock::from_json_str(&text).unwrap(), lock);
+    assert!(FlowLock::from_json_str(r#"{"version": 9, "flows": {}}"#).is_err());
+}
+
+#[test]
+fn reports_drift_against_the_lockfile() {
+    let (dir, fixtures, flows) = project();
+    let lock_path = dir.path().join("flow.lock");
+    let lock = lock_flows(&registry(&fixtures), &lock_path, &flows).unwrap();
+    fs::write(&lock_path, lock.to_json_string()).unwrap();
+    assert!(
+        verify_flows(&registry(&fixtures), &lock_path, &flows)
+            .unwrap()
+            .is_empty()
+    );
+
+    fs::write(
+        fixtures.join(format!("{}.wasm", fixture_key(REMOTE))),
+        b"charge-v2",
+    )
+    .unwrap();
+    write_flow(&flows[1], vec![("extra", remote())]);
+
+    let drift = verify_flows(&registry(&fixtures), &lock_path, &flows[..1]).unwrap();
+    assert_eq!(drift.len(), 1);
+    assert_eq!(drift[0].node_id, "charge");
+    assert!(drift[0].to_string().contains("resolves to"));
+
+    let current = lock_flows(&registry(&fixtures), &lock_path, &flows).unwrap();
+    let lines: Vec<String> = diff_lock(&lock, &current, true)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(lines.len(), 3, "{lines:?}");
+    assert!(lines[1].starts_with("flows/other.ygtc node 'extra': not locked"));
+    assert!(lines[2].starts_with("flows/other.ygtc node 'start': locked but no longer bound"));
+}
+
+#[test]
+fn cli_writes_and_checks_the_lockfile() {
+    let (dir, fixtures, flows) = project();
+    let resolver = format!("fixture://{}", fixtures.display());
+    let lock_path = dir.path().join("flow.lock");
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["lock", "--dir"])
+        .arg(dir.path())
+        .args(["--resolver", &resolver])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Locked 3 component(s) across 2 flow(s)",
+        ));
+    let written = fs::read_to_string(&lock_path).unwrap();
+    assert!(written.contains("\"flows/main.ygtc\""));
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["lock", "--check", "--dir"])
+        .arg(dir.path())
+        .args(["--resolver", &resolver])
+        .assert()
+        .success();
+
+    write_flow(&flows[0], vec![("start", local("file://comp.wasm"))]);
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "lock", "--check", "--dir"])
+        .arg(dir.path())
+        .args(["--resolver", &resolver])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["ok"], false);
+    assert_eq!(payload["drift"][0]["node_id"], "charge");
+    assert!(payload["drift"][0]["current"].is_null());
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["lock", "--flow"])
+        .arg(&flows[0])
+        .args(["--dir"])
+        .arg(dir.path())
+        .args(["--resolver", &resolver])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Locked 2 component(s) across 2 flow(s)",
+        ));
+    assert!(fs::read_to_string(&lock_path).unwrap().len() < written.len());
+}
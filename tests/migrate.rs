@@ -0,0 +1,182 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    compile_flow,
+    loader::load_ygtc_from_str,
+    migrate::{CURRENT_SCHEMA_VERSION, migrate_flow},
+};
+use greentic_types::NodeId;
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::tempdir;
+
+const LEGACY: &str = r#"id: legacy
+type: messaging
+schema_version: 1
+nodes:
+  start:
+    component.exec:
+      component: ai.greentic.echo
+      text: hi
+    operation: run
+    pack_alias: echo-pack
+    routing: finish
+  finish:
+    component.exec:
+      operation: render
+      template: "{{start.text}}"
+    routing:
+      - out: true
+"#;
+
+#[test]
+fn migrates_legacy_nodes_and_routing_to_v2() {
+    let migrated = migrate_flow(LEGACY, 2).unwrap();
+    assert_eq!((migrated.from, migrated.to), (1, 2));
+    let rules: Vec<(&str, &str)> = migrated
+        .changes
+        .iter()
+        .map(|c| (c.location.as_str(), c.rule))
+        .collect();
+    assert_eq!(
+        rules,
+        vec![
+            ("nodes.start", "component-exec"),
+            ("nodes.start", "pack-alias"),
+            ("nodes.start.routing", "routing-string"),
+            ("nodes.finish", "component-exec"),
+            ("schema_version", "schema-version"),
+        ]
+    );
+
+    let doc = load_ygtc_from_str(&migrated.yaml).unwrap();
+    assert_eq!(doc.schema_version, Some(2));
+    let start = &doc.nodes["start"];
+    assert_eq!(
+        start.raw.keys().collect::<Vec<_>>(),
+        vec!["run", "meta"],
+        "{}",
+        migrated.yaml
+    );
+    assert_eq!(
+        start.raw["run"],
+        json!({"component": "ai.greentic.echo", "text": "hi"})
+    );
+    assert_eq!(start.raw["meta"], json!({"pack_alias": "echo-pack"}));
+    assert_eq!(start.routing, json!([{"to": "finish"}]));
+    assert_eq!(
+        doc.nodes["finish"].raw["render"],
+        json!({"template": "{{start.text}}"})
+    );
+
+    let flow = compile_flow(doc).unwrap();
+    let node = flow.nodes.get(&NodeId::new("start").unwrap()).unwrap();
+    assert_eq!(node.component.id.as_str(), "component.exec");
+    assert_eq!(node.component.operation.as_deref(), Some("run"));
+}
+
+#[test]
+fn wraps_a_single_route_mapping_and_adds_a_missing_version() {
+    let yaml = "id: f\ntype: messaging\nnodes:\n  a:\n    op: {}\n    routing:\n      to: b\n  b:\n    op: {}\n    routing:\n      - b_done\n  b_done:\n    op: {}\n    routing: out\n";
+    let migrated = migrate_flow(yaml, CURRENT_SCHEMA_VERSION).unwrap();
+    let messages: Vec<&str> = migrated
+        .changes
+        .iter()
+        .map(|c| c.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "single route mapping -> route list",
+            "route 'b_done' -> {to: b_done}",
+            "added schema_version 2",
+        ]
+    );
+    assert!(
+        migrated
+            .yaml
+            .starts_with("id: f\ntype: messaging\nschema_version: 2\n"),
+        "{}",
+        migrated.yaml
+    );
+    load_ygtc_from_str(&migrated.yaml).unwrap();
+}
+
+#[test]
+fn current_documents_are_left_alone() {
+    let yaml =
+        "id: f\ntype: messaging\nschema_version: 2\nnodes:\n  a:\n    op: {}\n    routing: out\n";
+    let migrated = migrate_flow(yaml, 2).unwrap();
+    assert!(migrated.changes.is_empty());
+    assert_eq!(migrated.yaml, yaml);
+}
+
+#[test]
+fn rejects_unsupported_targets_and_unmigratable_nodes() {
+    let err = migrate_flow(LEGACY, 3).unwrap_err().to_string();
+    assert!(err.contains("newest is 2"), "{err}");
+    let v2 = "id: f\ntype: messaging\nschema_version: 2\nnodes: {}\n";
+    let err = migrate_flow(v2, 1).unwrap_err().to_string();
+    assert!(err.contains("cannot downgrade"), "{err}");
+
+    let two_ops =
+        "id: f\ntype: messaging\nschema_version: 1\nnodes:\n  a:\n    first: {}\n    second: {}\n";
+    let err = migrate_flow(two_ops, 2).unwrap_err().to_string();
+    assert!(err.contains("'a'"), "{err}");
+    let no_op = "id: f\ntype: messaging\nschema_version: 1\nnodes:\n  a:\n    component.exec: {}\n";
+    let err = migrate_flow(no_op, 2).unwrap_err().to_string();
+    assert!(err.contains("without an operation"), "{err}");
+}
+
+#[test]
+fn cli_migrates_in_place_and_reports_changes() {
+    let dir = tempdir().unwrap();
+    let flow = dir.path().join("legacy.ygtc");
+    fs::write(&flow, LEGACY).unwrap();
+
+    let out = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "migrate", "--dry-run", "--flow"])
+        .arg(&flow)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let report: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(report["changes"].as_array().unwrap().len(), 5);
+    assert_eq!(fs::read_to_string(&flow).unwrap(), LEGACY);
+
+    let out = cargo_bin_cmd!("greentic-flow")
+        .args(["migrate", "--to", "2", "--flow"])
+        .arg(&flow)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains(
+            "nodes.start: component.exec with operation 'run' -> 'run:' (component-exec)"
+        ),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("from schema_version 1 to 2 (5 change(s))"),
+        "{stdout}"
+    );
+    let written = fs::read_to_string(&flow).unwrap();
+    assert!(written.contains("schema_version: 2"), "{written}");
+    load_ygtc_from_str(&written).unwrap();
+
+    let out = cargo_bin_cmd!("greentic-flow")
+        .args(["migrate", "--flow"])
+        .arg(&flow)
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("already at schema_version 2"));
+}
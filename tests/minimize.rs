@@ -0,0 +1,67 @@
+use greentic_flow::minimize::{MinimizeCheck, minimize_flow};
+
+const FLOW: &str = r#"
+id: demo
+type: messaging
+start: a
+nodes:
+  a:
+    qa.process: {}
+    routing:
+      - to: b
+      - to: c
+  b:
+    qa.process: {}
+    routing:
+      - to: c
+  c:
+    qa.process: {}
+    routing:
+      - to: ghost
+  d:
+    qa.process: {}
+"#;
+
+#[test]
+fn minimize_keeps_only_what_the_predicate_needs() {
+    let minimized = minimize_flow(FLOW, |yaml| yaml.contains("b:") && yaml.contains("c:")).unwrap();
+    let doc: serde_yaml_bw::Value = serde_yaml_bw::from_str(&minimized.yaml).unwrap();
+    let nodes = doc["nodes"].as_mapping().unwrap();
+    let ids: Vec<_> = nodes.keys().filter_map(|k| k.as_str()).collect();
+    assert_eq!(ids, vec!["b", "c"]);
+    assert!(doc.get("start").is_none());
+    assert_eq!(minimized.nodes_before, 4);
+    assert_eq!(minimized.nodes_after, 2);
+    assert_eq!(minimized.routes_before, 4);
+    assert_eq!(minimized.routes_after, 0);
+}
+
+#[test]
+fn minimize_is_deterministic() {
+    let check: MinimizeCheck = "error:ghost".parse().unwrap();
+    assert!(check.reproduces(FLOW));
+    let first = minimize_flow(FLOW, |yaml| check.reproduces(yaml)).unwrap();
+    let second = minimize_flow(FLOW, |yaml| check.reproduces(yaml)).unwrap();
+    assert_eq!(first.yaml, second.yaml);
+    assert_eq!(first.nodes_after, 1);
+    assert_eq!(first.routes_after, 1);
+    assert!(first.yaml.contains("ghost"));
+}
+
+#[test]
+fn minimize_rejects_checks_that_do_not_reproduce() {
+    let check: MinimizeCheck = "lint:no_such_rule".parse().unwrap();
+    let err = minimize_flow(FLOW, |yaml| check.reproduces(yaml)).unwrap_err();
+    assert!(err.to_string().contains("does not reproduce"), "{err}");
+}
+
+#[test]
+fn minimize_check_parses_kinds() {
+    assert_eq!(
+        "lint: start_node_exists".parse::<MinimizeCheck>(),
+        Ok(MinimizeCheck::LintRule("start_node_exists".to_string()))
+    );
+    assert!("bogus:x".parse::<MinimizeCheck>().is_err());
+    assert!("lint:".parse::<MinimizeCheck>().is_err());
+    assert!("lint".parse::<MinimizeCheck>().is_err());
+}
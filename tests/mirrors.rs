@@ -0,0 +1,104 @@
+use greentic_flow::{
+    component_resolver::{FixtureResolver, ResolutionProvenance, ResolverRegistry, fixture_key},
+    mirrors::{MirrorRule, MirrorRules},
+    resolve_summary::sha256_digest,
+};
+use std::{fs, sync::Arc};
+use tempfile::tempdir;
+
+const RULES: &str = r#"
+[[mirror]]
+from = "oci://ghcr.io/acme/*"
+to = "oci://registry.internal/acme/*"
+
+[[mirror]]
+from = "oci://ghcr.io/*"
+to = "oci://registry.internal/ghcr/*"
+
+[[mirror]]
+from = "repo://exact/comp"
+to = "oci://registry.internal/exact/comp:1"
+"#;
+
+#[test]
+fn first_matching_rule_rewrites_the_reference() {
+    let rules = MirrorRules::from_toml_str(RULES).unwrap();
+    assert_eq!(
+        rules.rewrite("oci://ghcr.io/acme/greet:1.0.0").as_deref(),
+        Some("oci://registry.internal/acme/greet:1.0.0")
+    );
+    assert_eq!(
+        rules
+            .rewrite("oci://ghcr.io/other/comp@sha256:abc")
+            .as_deref(),
+        Some("oci://registry.internal/ghcr/other/comp@sha256:abc")
+    );
+    assert_eq!(
+        rules.rewrite("repo://exact/comp").as_deref(),
+        Some("oci://registry.internal/exact/comp:1")
+    );
+    assert_eq!(rules.rewrite("repo://exact/comp2"), None);
+    assert_eq!(rules.rewrite("oci://docker.io/acme/greet:1"), None);
+}
+
+#[test]
+fn rejects_malformed_rules() {
+    for (from, to) in [
+        ("oci://ghcr.io/*/comp", "oci://mirror/*/comp"),
+        ("oci://ghcr.io/*", "oci://mirror/fixed"),
+        ("*", "oci://mirror/*"),
+    ] {
+        let text = format!("[[mirror]]\nfrom = \"{from}\"\nto = \"{to}\"\n");
+        assert!(MirrorRules::from_toml_str(&text).is_err(), "{from} -> {to}");
+    }
+    assert!(MirrorRules::from_toml_str("[[mirror]]\nfrom = \"a\"\n").is_err());
+    assert!(MirrorRules::from_toml_str("").unwrap().is_empty());
+}
+
+#[test]
+fn registry_resolves_through_the_mirror_and_records_provenance() {
+    let dir = tempdir().unwrap();
+    let mirrored = "oci://registry.internal/acme/greet:1";
+    fs::write(
+        dir.path().join(format!("{}.wasm", fixture_key(mirrored))),
+        b"mirrored",
+    )
+    .unwrap();
+    let registry = ResolverRegistry::builtin()
+        .with(Arc::new(FixtureResolver::new(dir.path())))
+        .with_mirrors(MirrorRules {
+            rules: vec![MirrorRule {
+                from: "oci://ghcr.io/acme/*".to_string(),
+                to: "oci://registry.internal/acme/*".to_string(),
+            }],
+        });
+
+    let resolved = registry
+        .resolve("oci://ghcr.io/acme/greet:1", None)
+        .unwrap();
+    assert_eq!(resolved.digest, sha256_digest(b"mirrored"));
+    assert_eq!(
+        resolved.provenance,
+        Some(ResolutionProvenance {
+            requested: "oci://ghcr.io/acme/greet:1".to_string(),
+            effective: mirrored.to_string(),
+        })
+    );
+    assert_eq!(
+        registry
+            .resolve_digest("oci://ghcr.io/acme/greet:1")
+            .unwrap(),
+        sha256_digest(b"mirrored")
+    );
+
+    let direct = registry.resolve(mirrored, None).unwrap();
+    assert_eq!(direct.provenance, None);
+    let err = registry
+        .resolve("oci://ghcr.io/acme/missing:1", None)
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("via mirror oci://registry.internal/acme/missing:1"),
+        "{err}"
+    );
+}
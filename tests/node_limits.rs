@@ -0,0 +1,142 @@
+use greentic_flow::{
+    builder::{FlowBuilder, NodeBuilder},
+    flow_bundle::load_and_validate_bundle,
+    flow_ir::{FlowIr, NodeLimits, format_canonical},
+    loader::load_ygtc_from_str,
+};
+use serde_json::json;
+
+const FLOW: &str = r#"id: limited
+type: messaging
+start: fetch
+nodes:
+  fetch:
+    http.get:
+      url: https://example.com
+    limits:
+      rps: 5
+      concurrency: 2
+    routing:
+      - to: charge
+  charge:
+    payments.charge: {}
+    limits:
+      rps: 0.5
+      concurrency: 1
+    routing:
+      - to: done
+  done:
+    template: "ok"
+    routing: out
+"#;
+
+fn with_limits(limits: &str) -> String {
+    format!(
+        "id: bad\ntype: messaging\nstart: a\nnodes:\n  a:\n    template: \"x\"\n    limits: {limits}\n    routing: out\n"
+    )
+}
+
+#[test]
+fn limits_parse_into_ir_and_survive_formatting() {
+    let ir = FlowIr::from_doc(load_ygtc_from_str(FLOW).unwrap()).unwrap();
+    assert_eq!(
+        ir.nodes["fetch"].limits,
+        Some(NodeLimits {
+            rps: Some(5.0),
+            concurrency: Some(2),
+        })
+    );
+    assert_eq!(ir.nodes["done"].limits, None);
+    assert_eq!(ir.nodes["fetch"].operation, "http.get");
+
+    let doc = ir.to_doc().unwrap();
+    assert_eq!(
+        doc.nodes["charge"].raw.get("limits"),
+        Some(&json!({"rps": 0.5, "concurrency": 1}))
+    );
+
+    let formatted = format_canonical(FLOW).unwrap();
+    let reparsed = FlowIr::from_doc(load_ygtc_from_str(&formatted).unwrap()).unwrap();
+    assert_eq!(reparsed.nodes["charge"].limits, ir.nodes["charge"].limits);
+}
+
+#[test]
+fn invalid_limits_fail_to_load() {
+    for (limits, expected) in [
+        ("{rps: 0}", "limits"),
+        ("{concurrency: 0}", "limits"),
+        ("{}", "limits"),
+        ("{rps: 1, burst: 3}", "limits"),
+    ] {
+        let err = load_ygtc_from_str(&with_limits(limits)).unwrap_err();
+        assert!(
+            err.to_string().contains(expected),
+            "{limits}: unexpected error {err}"
+        );
+    }
+    assert!(load_ygtc_from_str(&with_limits("{concurrency: 4}")).is_ok());
+}
+
+#[test]
+fn limits_validation_explains_the_problem() {
+    assert!(
+        NodeLimits::from_value(&json!({"rps": -1}))
+            .unwrap_err()
+            .contains("positive")
+    );
+    assert!(
+        NodeLimits::from_value(&json!({"concurrency": 0}))
+            .unwrap_err()
+            .contains("at least 1")
+    );
+    assert!(
+        NodeLimits::from_value(&json!({}))
+            .unwrap_err()
+            .contains("rps or concurrency")
+    );
+    assert!(NodeLimits::from_value(&json!({"rps": 2.5})).is_ok());
+}
+
+#[test]
+fn builder_sets_limits() {
+    let flow = FlowBuilder::new("built")
+        .kind("messaging")
+        .add_node(
+            NodeBuilder::new("a", "template")
+                .payload(json!("hi"))
+                .limits(NodeLimits {
+                    rps: None,
+                    concurrency: Some(3),
+                })
+                .out(),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(flow.nodes["a"].limits.unwrap().concurrency, Some(3));
+}
+
+#[test]
+fn bundle_aggregates_node_limits() {
+    let bundle = load_and_validate_bundle(FLOW, None).unwrap();
+    let fetch = bundle
+        .nodes
+        .iter()
+        .find(|node| node.node_id == "fetch")
+        .unwrap();
+    assert_eq!(fetch.limits.unwrap().rps, Some(5.0));
+    let done = bundle
+        .nodes
+        .iter()
+        .find(|node| node.node_id == "done")
+        .unwrap();
+    assert_eq!(done.limits, None);
+
+    let limits = bundle.limits.expect("flow limits");
+    assert_eq!(limits.nodes, vec!["fetch", "charge"]);
+    assert_eq!(limits.total_concurrency, Some(3));
+    assert_eq!(limits.min_rps, Some(0.5));
+
+    let plain = with_limits("{concurrency: 1}").replace("    limits: {concurrency: 1}\n", "");
+    let plain = load_and_validate_bundle(&plain, None).unwrap();
+    assert!(plain.limits.is_none());
+}
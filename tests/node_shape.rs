@@ -0,0 +1,86 @@
+use greentic_flow::node_shape::{
+    FixTarget, apply_multi_operation_fixes, find_multi_operation_nodes, suggest_multi_operation_fix,
+};
+use serde_yaml_bw::Value;
+
+const FLOW: &str = r#"id: main
+type: messaging
+start: send
+nodes:
+  send:
+    messaging.telegram.sendMessage:
+      text: "hi"
+    chat_id: 42
+    tags: [a]
+    routing: out
+  legacy:
+    component.exec: { component: "ai.greentic.echo" }
+    operation: run
+"#;
+
+#[test]
+fn suggests_moving_extra_keys_when_one_key_is_known() {
+    let nodes = find_multi_operation_nodes(FLOW).unwrap();
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(
+        nodes[0].keys,
+        vec!["messaging.telegram.sendMessage", "chat_id", "tags"]
+    );
+
+    let fix = suggest_multi_operation_fix(FLOW, &nodes[0], |key| {
+        key == "messaging.telegram.sendMessage"
+    })
+    .unwrap()
+    .expect("fix");
+    assert_eq!(
+        fix.moves,
+        vec![
+            ("chat_id".to_string(), FixTarget::Payload),
+            ("tags".to_string(), FixTarget::Payload),
+        ]
+    );
+    assert_eq!(
+        fix.to_string(),
+        "node 'send': keep 'messaging.telegram.sendMessage' as the operation; move 'chat_id' into the 'messaging.telegram.sendMessage' payload, move 'tags' into the 'messaging.telegram.sendMessage' payload"
+    );
+
+    let ambiguous = suggest_multi_operation_fix(FLOW, &nodes[0], |_| true).unwrap();
+    assert!(ambiguous.is_none());
+}
+
+#[test]
+fn apply_moves_keys_into_payload_or_annotations() {
+    let flow = r#"id: main
+type: messaging
+start: send
+nodes:
+  send:
+    messaging.telegram.sendMessage:
+      text: "hi"
+    text: "duplicate"
+    chat_id: 42
+    routing: out
+"#;
+    let nodes = find_multi_operation_nodes(flow).unwrap();
+    let fix = suggest_multi_operation_fix(flow, &nodes[0], |key| key.contains('.'))
+        .unwrap()
+        .expect("fix");
+    assert_eq!(
+        fix.moves,
+        vec![
+            ("text".to_string(), FixTarget::Annotations),
+            ("chat_id".to_string(), FixTarget::Payload),
+        ]
+    );
+
+    let repaired = apply_multi_operation_fixes(flow, &[fix]).unwrap();
+    assert!(find_multi_operation_nodes(&repaired).unwrap().is_empty());
+    let doc: Value = serde_yaml_bw::from_str(&repaired).unwrap();
+    let node = &doc["nodes"]["send"];
+    assert_eq!(
+        node["messaging.telegram.sendMessage"]["chat_id"],
+        Value::from(42)
+    );
+    assert_eq!(node["annotations"]["text"], Value::from("duplicate"));
+    assert_eq!(node["routing"], Value::from("out"));
+}
@@ -0,0 +1,101 @@
+#![cfg(any(feature = "s3", feature = "gcs"))]
+
+use greentic_flow::{
+    cas::ArtifactStore,
+    component_resolver::{ComponentResolver, ResolverRegistry},
+    object_store_resolver::ObjectStoreResolver,
+    resolve_summary::sha256_digest,
+};
+use object_store::{ObjectStore, PutPayload, memory::InMemory, path::Path as ObjectPath};
+use std::{fs, sync::Arc};
+use tempfile::tempdir;
+
+fn bucket_with(objects: &[(&str, &[u8])]) -> Arc<InMemory> {
+    let store = Arc::new(InMemory::new());
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    for (key, bytes) in objects {
+        rt.block_on(store.put(&ObjectPath::from(*key), PutPayload::from(bytes.to_vec())))
+            .unwrap();
+    }
+    store
+}
+
+fn resolver(bucket: Arc<InMemory>, cache: &std::path::Path) -> ObjectStoreResolver {
+    ObjectStoreResolver::with_connector("s3", move |name| {
+        assert_eq!(name, "artifacts");
+        Ok(bucket.clone())
+    })
+    .with_cache(ArtifactStore::new(cache))
+}
+
+#[test]
+fn downloads_into_the_artifact_store_with_the_manifest() {
+    let dir = tempdir().unwrap();
+    let bucket = bucket_with(&[
+        ("acme/greet/1.0.0/greet.wasm", b"wasm-bytes"),
+        (
+            "acme/greet/1.0.0/component.manifest.json",
+            br#"{"id":"acme.greet"}"#,
+        ),
+    ]);
+    let resolved = resolver(bucket, dir.path())
+        .resolve("s3://artifacts/acme/greet/1.0.0/greet.wasm", None)
+        .unwrap();
+    assert_eq!(resolved.digest, sha256_digest(b"wasm-bytes"));
+    assert_eq!(fs::read(&resolved.wasm_path).unwrap(), b"wasm-bytes");
+    assert!(resolved.wasm_path.starts_with(dir.path()));
+    let manifest = resolved.manifest_path.expect("manifest");
+    assert_eq!(
+        fs::read_to_string(manifest).unwrap(),
+        r#"{"id":"acme.greet"}"#
+    );
+}
+
+#[test]
+fn rejects_downloads_that_do_not_match_the_pinned_digest() {
+    let dir = tempdir().unwrap();
+    let bucket = bucket_with(&[("greet.wasm", b"tampered")]);
+    let pinned = sha256_digest(b"wasm-bytes");
+    let err = resolver(bucket, dir.path())
+        .resolve("s3://artifacts/greet.wasm", Some(&pinned))
+        .unwrap_err();
+    assert!(err.to_string().contains("digest mismatch"), "{err}");
+    assert!(ArtifactStore::new(dir.path()).usage().unwrap().artifacts == 0);
+}
+
+#[test]
+fn pinned_digest_already_stored_skips_the_download() {
+    let dir = tempdir().unwrap();
+    let digest = ArtifactStore::new(dir.path())
+        .insert_bytes(b"wasm-bytes", None)
+        .unwrap();
+    let resolved = resolver(bucket_with(&[]), dir.path())
+        .resolve("s3://artifacts/greet.wasm", Some(&digest))
+        .unwrap();
+    assert_eq!(resolved.digest, digest);
+}
+
+#[test]
+fn reports_missing_objects_and_malformed_references() {
+    let dir = tempdir().unwrap();
+    let resolver = resolver(bucket_with(&[]), dir.path());
+    let err = resolver
+        .resolve("s3://artifacts/missing.wasm", None)
+        .unwrap_err();
+    assert!(err.to_string().contains("does not exist"), "{err}");
+    let err = resolver.resolve("s3://artifacts", None).unwrap_err();
+    assert!(err.to_string().contains("s3://<bucket>/<key>"), "{err}");
+}
+
+#[test]
+fn builtin_registry_handles_enabled_schemes() {
+    let registry = ResolverRegistry::builtin();
+    assert_eq!(
+        registry.handles("s3://bucket/key.wasm"),
+        cfg!(feature = "s3")
+    );
+    assert_eq!(
+        registry.handles("gs://bucket/key.wasm"),
+        cfg!(feature = "gcs")
+    );
+}
@@ -0,0 +1,109 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::component_schema::{apply_operation_defaults, resolve_operation_defaults};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::tempdir;
+
+const MANIFEST: &str = r#"{
+  "id": "ai.greentic.http",
+  "operations": [
+    {
+      "name": "fetch",
+      "input_schema": {"type": "object", "required": ["url"], "properties": {"url": {"type": "string"}}},
+      "defaults": {"retries": 3, "http": {"method": "GET", "timeout_ms": 500}}
+    },
+    {"name": "ping"}
+  ]
+}"#;
+
+#[test]
+fn defaults_fill_only_missing_fields() {
+    let dir = tempdir().unwrap();
+    let manifest = dir.path().join("component.manifest.json");
+    fs::write(&manifest, MANIFEST).unwrap();
+
+    let defaults = resolve_operation_defaults(&manifest, "fetch")
+        .unwrap()
+        .unwrap();
+    assert!(
+        resolve_operation_defaults(&manifest, "ping")
+            .unwrap()
+            .is_none()
+    );
+
+    let mut payload =
+        json!({"url": "https://example.com", "http": {"method": "POST"}, "retries": null});
+    let filled = apply_operation_defaults(&mut payload, &defaults);
+    assert_eq!(
+        payload,
+        json!({"url": "https://example.com", "http": {"method": "POST", "timeout_ms": 500}, "retries": null})
+    );
+    assert_eq!(filled, vec![("http.timeout_ms".to_string(), json!(500))]);
+
+    let mut empty = Value::Null;
+    apply_operation_defaults(&mut empty, &defaults);
+    assert_eq!(empty["http"]["method"], "GET");
+
+    fs::write(
+        &manifest,
+        r#"{"id":"x","operations":[{"name":"fetch","defaults":[1]}]}"#,
+    )
+    .unwrap();
+    let err = resolve_operation_defaults(&manifest, "fetch")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("must be an object"), "{err}");
+}
+
+#[test]
+fn add_step_merges_defaults_under_the_payload() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(dir.path().join("comp.wasm"), b"wasm-bytes").unwrap();
+    fs::write(dir.path().join("component.manifest.json"), MANIFEST).unwrap();
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+nodes:
+  start:
+    qa.process: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .current_dir(dir.path())
+        .args(["add-step", "--flow"])
+        .arg(&flow_path)
+        .args([
+            "--mode",
+            "default",
+            "--node-id",
+            "fetch",
+            "--operation",
+            "fetch",
+        ])
+        .args(["--payload", r#"{"url":"https://example.com","retries":5}"#])
+        .args(["--local-wasm", "comp.wasm", "--after", "start", "--write"])
+        .assert()
+        .success();
+
+    let yaml: Value = serde_yaml_bw::from_str(&fs::read_to_string(&flow_path).unwrap()).unwrap();
+    assert_eq!(
+        yaml["nodes"]["fetch"]["fetch"],
+        json!({"url": "https://example.com", "retries": 5, "http": {"method": "GET", "timeout_ms": 500}})
+    );
+    let sidecar: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("flow.ygtc.resolve.json")).unwrap(),
+    )
+    .unwrap();
+    let provenance = &sidecar["answers_provenance"]["nodes"]["fetch"];
+    assert_eq!(
+        provenance["http"],
+        json!({"source": "operation_default", "value": {"method": "GET", "timeout_ms": 500}})
+    );
+    assert!(provenance.get("retries").is_none());
+}
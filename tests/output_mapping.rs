@@ -0,0 +1,158 @@
+use greentic_flow::{
+    lint::{LintSeverity, OutputMappingRule, StateReferencesRule},
+    loader::load_ygtc_from_str,
+    output_mapping::{apply_output_mapping, parse_output_mapping},
+    template::template_state_references,
+};
+use serde_json::{Map, json};
+
+#[test]
+fn output_mapping_selects_result_parts_into_nested_state() {
+    let (captures, problems) = parse_output_mapping(&json!({
+        "weather": "$",
+        "temperature": "$.current.temp",
+        "forecast.today": "$.days[0]",
+        "missing": "$.nope"
+    }));
+    assert!(problems.is_empty(), "{problems:?}");
+
+    let result = json!({"current": {"temp": 21}, "days": ["sunny", "rain"]});
+    let mut state = Map::new();
+    apply_output_mapping(&captures, &result, &mut state);
+    assert_eq!(
+        serde_json::Value::Object(state),
+        json!({
+            "weather": result,
+            "temperature": 21,
+            "forecast": {"today": "sunny"}
+        })
+    );
+}
+
+#[test]
+fn output_mapping_reports_bad_keys_selectors_and_overlaps() {
+    let (captures, problems) = parse_output_mapping(&json!({
+        "user": "$.user",
+        "user.name": "$.name",
+        "1st": "$",
+        "total": "$.items[x]",
+        "raw": "result",
+        "count": 3
+    }));
+    assert_eq!(
+        captures.iter().map(|c| c.key.as_str()).collect::<Vec<_>>(),
+        vec!["user"]
+    );
+    let messages: Vec<_> = problems.iter().map(|p| p.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "output key '1st' is not a valid state key (dot-separated identifiers)",
+            "output 'count' must be a selector string such as \"$.field\"",
+            "output 'raw': selector 'result' must start with '$'",
+            "output 'total': selector '$.items[x]' has an invalid index 'x'",
+            "output 'user.name' overlaps 'user'; the later key overwrites the earlier one",
+        ]
+    );
+    assert_eq!(
+        parse_output_mapping(&json!("$.x")).1[0].message,
+        "output must map state keys to selectors"
+    );
+}
+
+#[test]
+fn output_mapping_rule_flags_problems_and_downstream_overwrites() {
+    let yaml = r#"
+id: demo
+type: messaging
+start: fetch
+nodes:
+  fetch:
+    weather.lookup: {}
+    output:
+      forecast: "$.forecast"
+      bad key: "$"
+    routing:
+      - to: refresh
+  refresh:
+    weather.lookup: {}
+    output:
+      forecast.today: "$.today"
+    routing: out
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let diagnostics = OutputMappingRule::check(&doc);
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "node 'fetch': output key 'bad key' is not a valid state key (dot-separated identifiers)",
+            "state key 'forecast' captured by node 'fetch' is overwritten by 'forecast.today' of \
+             downstream node 'refresh'",
+        ]
+    );
+    assert_eq!(diagnostics[0].severity, LintSeverity::Error);
+    assert_eq!(
+        diagnostics[0].path.as_deref(),
+        Some("/nodes/fetch/output/bad key")
+    );
+    assert_eq!(diagnostics[1].severity, LintSeverity::Warning);
+    assert_eq!(
+        diagnostics[1].path.as_deref(),
+        Some("/nodes/refresh/output/forecast.today")
+    );
+}
+
+#[test]
+fn template_state_references_list_paths_inside_blocks() {
+    assert_eq!(
+        template_state_references(
+            "{{#if state.ready}}{{state.user.name}} {{json state.user}}{{/if}} state.outside \
+             {{params.state.x}}"
+        ),
+        vec!["ready", "user.name", "user"]
+    );
+}
+
+#[test]
+fn state_references_resolve_against_upstream_writers() {
+    let yaml = r#"
+id: demo
+type: messaging
+start: ask
+nodes:
+  ask:
+    questions:
+      fields:
+        - id: city
+          writes_to: location.city
+    routing:
+      - to: fetch
+  fetch:
+    weather.lookup:
+      city: "{{state.location.city}}"
+    output:
+      weather: "$"
+    routing:
+      - to: answer
+  answer:
+    templating.handlebars:
+      text: "{{state.weather.summary}} in {{state.location}}; {{state.mood}}"
+    routing: out
+"#;
+    let doc = load_ygtc_from_str(yaml).unwrap();
+    let diagnostics = StateReferencesRule::check(&doc);
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "node 'answer' reads state.mood, which no upstream node writes (via `output` or a \
+             questions field)"
+        ]
+    );
+    assert_eq!(diagnostics[0].severity, LintSeverity::Warning);
+    assert_eq!(
+        diagnostics[0].path.as_deref(),
+        Some("/nodes/answer/templating.handlebars")
+    );
+}
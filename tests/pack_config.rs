@@ -0,0 +1,45 @@
+use greentic_flow::pack_config::{DefaultRouting, PACK_CONFIG_FILE, PackConfig};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn kind_override_wins_over_pack_default() {
+    let config = PackConfig::from_toml_str(
+        r#"
+default_routing = "reply"
+
+[kinds.events]
+default_routing = "out"
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.default_routing_for("messaging"),
+        Some(DefaultRouting::Reply)
+    );
+    assert_eq!(
+        config.default_routing_for("events"),
+        Some(DefaultRouting::Out)
+    );
+    assert_eq!(PackConfig::default().default_routing_for("events"), None);
+
+    let err = PackConfig::from_toml_str(r#"default_routing = "next""#).unwrap_err();
+    assert!(err.to_string().contains(PACK_CONFIG_FILE), "{err}");
+}
+
+#[test]
+fn discover_walks_up_to_the_pack_root() {
+    let dir = tempdir().unwrap();
+    let flows = dir.path().join("flows").join("nested");
+    fs::create_dir_all(&flows).unwrap();
+    assert!(PackConfig::discover(&flows).unwrap().is_none());
+
+    fs::write(
+        dir.path().join(PACK_CONFIG_FILE),
+        "default_routing = \"out\"\n",
+    )
+    .unwrap();
+    let (path, config) = PackConfig::discover(&flows).unwrap().unwrap();
+    assert_eq!(path, dir.path().join(PACK_CONFIG_FILE));
+    assert_eq!(config.default_routing, Some(DefaultRouting::Out));
+}
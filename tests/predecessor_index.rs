@@ -0,0 +1,166 @@
+use greentic_flow::{
+    builder::FlowBuilder,
+    flow_edit::{DeleteStrategy, MultiplePredecessors},
+    flow_ir::{FlowIr, Route, analysis::FlowAnalysis, parse_flow_to_ir},
+    lint::{FixApplicability, FixEdit, LintFix},
+};
+use serde_json::json;
+
+const FLOW: &str = r#"id: checkout
+type: messaging
+start: quote
+nodes:
+  quote:
+    pricing.quote: {}
+    routing:
+      - status: declined
+        to: apologize
+      - to: charge
+  charge:
+    payments.charge: {}
+    routing:
+      - to: receipt
+  apologize:
+    template: "sorry"
+    routing:
+      - to: receipt
+  receipt:
+    template: "done"
+    routing: out
+"#;
+
+/// The index must agree with a from-scratch scan for every node.
+fn assert_index_matches_scan(flow: &mut FlowIr) {
+    let scan = FlowAnalysis::new(flow);
+    let expected: Vec<(String, Vec<String>)> = scan
+        .predecessor_map()
+        .iter()
+        .map(|(id, preds)| (id.clone(), preds.clone()))
+        .collect();
+    for (id, preds) in expected {
+        assert_eq!(flow.predecessors_of(&id), preds, "predecessors of {id}");
+    }
+}
+
+fn to(target: &str) -> Route {
+    Route {
+        to: Some(target.to_string()),
+        ..Route::default()
+    }
+}
+
+#[test]
+fn predecessors_come_back_in_flow_order() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    assert!(!flow.predecessor_index().is_built());
+    assert_eq!(flow.predecessors_of("receipt"), vec!["charge", "apologize"]);
+    assert!(flow.predecessor_index().is_built());
+    assert!(flow.predecessors_of("quote").is_empty());
+    assert!(flow.predecessors_of("missing").is_empty());
+}
+
+#[test]
+fn editing_methods_keep_the_index_current() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    assert_index_matches_scan(&mut flow);
+
+    flow.add_route("apologize", to("charge")).unwrap();
+    assert_index_matches_scan(&mut flow);
+
+    flow.rename_node("charge", "pay").unwrap();
+    assert_eq!(flow.predecessors_of("pay"), vec!["quote", "apologize"]);
+    assert_index_matches_scan(&mut flow);
+
+    flow.remove_route("apologize", 1).unwrap();
+    assert_index_matches_scan(&mut flow);
+
+    flow.set_routing("pay", vec![to("apologize")]).unwrap();
+    assert_index_matches_scan(&mut flow);
+
+    flow.remove_node(
+        "apologize",
+        DeleteStrategy::Splice,
+        MultiplePredecessors::SpliceAll,
+    )
+    .unwrap();
+    assert_index_matches_scan(&mut flow);
+
+    flow.splice_out_node("pay").unwrap();
+    assert!(flow.predecessor_index().is_built());
+    assert_eq!(
+        flow.nodes.keys().collect::<Vec<_>>(),
+        vec!["quote", "receipt"]
+    );
+    assert_index_matches_scan(&mut flow);
+}
+
+#[test]
+fn renaming_onto_dangling_routes_merges_them() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    assert_eq!(flow.predecessors_of("apologize"), vec!["quote"]);
+    flow.set_routing("receipt", vec![to("later")]).unwrap();
+    flow.rename_node("apologize", "later").unwrap();
+    assert_eq!(flow.predecessors_of("later"), vec!["quote", "receipt"]);
+    assert_index_matches_scan(&mut flow);
+}
+
+#[test]
+fn direct_writes_drop_the_index() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    assert_eq!(flow.predecessors_of("receipt").len(), 2);
+    assert_eq!(flow.nodes["quote"].routing.len(), 2);
+    assert!(flow.predecessor_index().is_built());
+
+    flow.nodes["quote"].routing.push(to("receipt"));
+    assert!(!flow.predecessor_index().is_built());
+    assert_eq!(
+        flow.predecessors_of("receipt"),
+        vec!["quote", "charge", "apologize"]
+    );
+
+    let receipt = flow.nodes.swap_remove("receipt").unwrap();
+    flow.nodes.insert("done".to_string(), receipt);
+    flow.nodes["apologize"].routing = vec![to("done")];
+    assert_eq!(flow.predecessors_of("done"), vec!["apologize"]);
+    assert_index_matches_scan(&mut flow);
+}
+
+#[test]
+fn lint_fixes_keep_the_index_current() {
+    let mut flow = parse_flow_to_ir(FLOW).unwrap();
+    assert_eq!(flow.predecessors_of("receipt").len(), 2);
+    let fix = LintFix::new(
+        "route charge to apologize",
+        FixApplicability::MachineApplicable,
+        vec![FixEdit::SetRouting {
+            node_id: "charge".to_string(),
+            routing: vec![to("apologize")],
+        }],
+    );
+    fix.apply(&mut flow).unwrap();
+    assert_eq!(flow.predecessors_of("apologize"), vec!["quote", "charge"]);
+    assert_index_matches_scan(&mut flow);
+}
+
+#[test]
+fn splicing_out_most_of_a_large_chain_keeps_order_and_routing() {
+    let count = 5_000;
+    let mut builder = FlowBuilder::new("chain").kind("messaging");
+    for index in 0..count {
+        builder = builder.node(format!("n{index}"), "emit", json!({}));
+        builder = if index + 1 < count {
+            builder.route_to(format!("n{}", index + 1))
+        } else {
+            builder.out()
+        };
+    }
+    let mut flow = builder.build().unwrap();
+    for index in (1..count - 1).filter(|index| index % 2 == 1) {
+        flow.splice_out_node(&format!("n{index}")).unwrap();
+    }
+    assert_eq!(flow.nodes.len(), 2_501);
+    assert_eq!(flow.nodes.get_index(1).unwrap().0, "n2");
+    assert_eq!(flow.nodes["n0"].routing, vec![to("n2")]);
+    assert_eq!(flow.predecessors_of("n4"), vec!["n2"]);
+    assert_index_matches_scan(&mut flow);
+}
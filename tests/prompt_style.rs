@@ -0,0 +1,167 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::prompt_style::{PromptConfig, PromptFlags, PromptStyle};
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn flags_then_no_color_then_config_then_terminal() {
+    let none = PromptFlags::default();
+    let unset = PromptConfig::default();
+    let fancy = PromptStyle::from_settings(none, false, unset, true);
+    assert!(fancy.color && !fancy.ascii && !fancy.screen_reader);
+    assert_eq!(
+        PromptStyle::from_settings(none, false, unset, false),
+        PromptStyle::PLAIN
+    );
+
+    let config = PromptConfig::from_toml_str("[prompts]\ncolor = false\nascii = true\n").unwrap();
+    let styled = PromptStyle::from_settings(none, false, config, true);
+    assert!(!styled.color && styled.ascii);
+    assert!(!PromptStyle::from_settings(none, true, unset, true).color);
+    let flags = PromptFlags {
+        no_color: true,
+        ascii: true,
+        screen_reader: false,
+    };
+    let forced = PromptConfig {
+        color: Some(true),
+        ascii: Some(false),
+        screen_reader: None,
+    };
+    assert_eq!(
+        PromptStyle::from_settings(flags, false, forced, true),
+        PromptStyle::PLAIN
+    );
+
+    let reader = PromptConfig::from_toml_str("[prompts]\nscreen_reader = true\n").unwrap();
+    let style = PromptStyle::from_settings(none, false, reader, true);
+    assert!(style.screen_reader && !style.color && style.ascii);
+
+    // Other tables of the user config are left alone; unknown prompt keys are not.
+    assert_eq!(
+        PromptConfig::from_toml_str("[other]\nkey = 1\n").unwrap(),
+        unset
+    );
+    assert!(PromptConfig::from_toml_str("[prompts]\ncolour = false\n").is_err());
+}
+
+#[test]
+fn styles_render_markers_colour_and_glyphs() {
+    let fancy = PromptStyle {
+        color: true,
+        ascii: false,
+        screen_reader: false,
+    };
+    assert_eq!(fancy.title("Name"), "\u{1b}[1mName\u{1b}[0m");
+    assert_eq!(fancy.input(None), "\u{203a} ");
+    assert_eq!(fancy.arrow(), "\u{2192}");
+    assert_eq!(fancy.question_marker("name", "string", true, None), None);
+
+    let plain = PromptStyle::PLAIN;
+    assert_eq!(plain.title("Name"), "Name");
+    assert_eq!(plain.input(None), "> ");
+    assert_eq!(plain.input(Some("Enter text")), "Enter text: ");
+    assert_eq!(plain.choice(2, "beta", ")"), "  2) beta");
+
+    let reader = PromptStyle {
+        color: false,
+        ascii: true,
+        screen_reader: true,
+    };
+    assert_eq!(
+        reader
+            .payload_question_marker(&json!({
+                "id": "channel",
+                "type": "enum",
+                "required": true,
+                "default": "support"
+            }))
+            .unwrap(),
+        r#"[question id=channel type=enum required=true default="support"]"#
+    );
+    assert_eq!(reader.choice(1, "support", "."), "[choice 1] support");
+    assert_eq!(reader.input(Some("Select")), "[input] Select: ");
+    assert_eq!(reader.input(None), "[input] ");
+}
+
+#[test]
+fn screen_reader_prompts_come_from_the_flag_or_user_config() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("flow.ygtc");
+    fs::write(
+        &flow_path,
+        r#"id: main
+type: messaging
+schema_version: 2
+start: a
+nodes:
+  a:
+    hop: {}
+    routing:
+      - to: mid
+  b:
+    hop: {}
+    routing:
+      - to: mid
+  mid:
+    op: {}
+    routing:
+      - to: end
+  end:
+    noop: {}
+    routing: out
+"#,
+    )
+    .unwrap();
+    let marker = r#"[question id=delete_step.multiple_predecessors type=enum required=true default="splice-all"]"#;
+    let config = dir.path().join("config.toml");
+
+    // No user config: plain prompts unless the flag asks otherwise.
+    cargo_bin_cmd!("greentic-flow")
+        .env("GREENTIC_FLOW_CONFIG", &config)
+        .args(["delete-step", "--interactive", "--step", "mid", "--flow"])
+        .arg(&flow_path)
+        .write_stdin("abort\n")
+        .assert()
+        .failure()
+        .stderr(contains(marker).not())
+        .stderr(contains("\n> "));
+
+    cargo_bin_cmd!("greentic-flow")
+        .env("GREENTIC_FLOW_CONFIG", &config)
+        .args([
+            "--screen-reader",
+            "delete-step",
+            "--interactive",
+            "--step",
+            "mid",
+        ])
+        .arg("--flow")
+        .arg(&flow_path)
+        .write_stdin("abort\n")
+        .assert()
+        .failure()
+        .stderr(contains(marker))
+        .stderr(contains("[input] "));
+
+    fs::write(&config, "[prompts]\nscreen_reader = true\n").unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .env("GREENTIC_FLOW_CONFIG", &config)
+        .args(["delete-step", "--interactive", "--step", "mid", "--flow"])
+        .arg(&flow_path)
+        .write_stdin("abort\n")
+        .assert()
+        .failure()
+        .stderr(contains(marker));
+
+    fs::write(&config, "[prompts]\nscreen-reader = true\n").unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .env("GREENTIC_FLOW_CONFIG", &config)
+        .args(["delete-step", "--step", "mid", "--flow"])
+        .arg(&flow_path)
+        .assert()
+        .failure()
+        .stderr(contains("invalid user config"));
+}
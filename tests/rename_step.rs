@@ -0,0 +1,73 @@
+use greentic_flow::{flow_ir::parse_flow_to_ir, rename::rename_node};
+
+const FLOW: &str = r#"id: main
+type: messaging
+start: fetch
+entrypoints:
+  webhook: fetch
+meta:
+  greentic:
+    components:
+      fetch:
+        component_id: ai.greentic.http
+    manifest_schema_hashes:
+      fetch: abc123
+nodes:
+  fetch:
+    http.get:
+      url: https://api.example.com
+    retry:
+      attempts: 3
+    routing:
+      - to: store
+        status: ok
+      - to: fetch
+        status: retry
+  store:
+    kv.put:
+      key: k
+    routing:
+      - to: fetch
+        status: again
+      - out: true
+"#;
+
+#[test]
+fn rename_rewrites_routes_start_entrypoints_and_meta() {
+    let renamed = rename_node(FLOW, "fetch", "load").unwrap();
+    let flow = parse_flow_to_ir(&renamed).unwrap();
+
+    assert_eq!(
+        flow.nodes.keys().collect::<Vec<_>>(),
+        vec!["load", "store"],
+        "renamed node keeps its position"
+    );
+    assert_eq!(flow.start.as_deref(), Some("load"));
+    assert_eq!(flow.entrypoints["webhook"], "load");
+    let load = &flow.nodes["load"];
+    assert_eq!(load.operation, "http.get");
+    assert_eq!(load.routing[1].to.as_deref(), Some("load"));
+    assert_eq!(flow.nodes["store"].routing[0].to.as_deref(), Some("load"));
+    let greentic = &flow.meta.as_ref().unwrap()["greentic"];
+    assert!(greentic["components"]["load"].is_object());
+    assert!(greentic["components"].get("fetch").is_none());
+    assert_eq!(greentic["manifest_schema_hashes"]["load"], "abc123");
+
+    assert!(renamed.contains("retry:\n      attempts: 3"));
+    assert!(!renamed.contains("fetch"));
+}
+
+#[test]
+fn rename_rejects_missing_source_existing_target_and_invalid_id() {
+    let err = rename_node(FLOW, "nope", "load").unwrap_err();
+    assert!(err.to_string().contains("node 'nope' not found"), "{err}");
+
+    let err = rename_node(FLOW, "fetch", "store").unwrap_err();
+    assert!(
+        err.to_string().contains("node 'store' already exists"),
+        "{err}"
+    );
+
+    let err = rename_node(FLOW, "fetch", "9 bad id").unwrap_err();
+    assert!(err.to_string().contains("invalid node id"), "{err}");
+}
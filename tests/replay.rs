@@ -0,0 +1,142 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    flow_ir::{FlowIr, parse_flow_to_ir},
+    replay::{NodeSchemas, PayloadDirection, parse_recordings, validate_recordings},
+};
+use predicates::str::contains;
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::tempdir;
+
+const FLOW: &str = r#"id: main
+type: messaging
+schema_version: 2
+start: pay
+nodes:
+  pay:
+    charge: {}
+    routing:
+      - to: notify
+  notify:
+    send: {}
+    routing: out
+"#;
+
+const MANIFEST: &str = r#"{"id":"ai.greentic.pay","operations":[{"name":"charge","input_schema":{"type":"object","required":["amount"],"properties":{"amount":{"type":"integer"}}},"output_schema":{"type":"object","properties":{"status":{"enum":["ok","e402"]}}}}]}"#;
+
+const CAPTURES: &str = r#"{"node_id": "pay", "input": {"amount": 5}, "output": {"status": "ok"}}
+{"node_id": "pay", "input": {"amount": "5"}, "output": {"status": "e999"}}
+{"node_id": "notify", "input": {"text": "hi"}}
+{"node_id": "ghost", "input": {}}
+{"node_id": "pay", "flow": "other", "input": {}}
+"#;
+
+fn flow() -> FlowIr {
+    parse_flow_to_ir(FLOW).unwrap()
+}
+
+fn pay_schemas() -> NodeSchemas {
+    let manifest: Value = serde_json::from_str(MANIFEST).unwrap();
+    let op = &manifest["operations"][0];
+    NodeSchemas {
+        input: Some(op["input_schema"].clone()),
+        output: Some(op["output_schema"].clone()),
+        manifest_path: None,
+    }
+}
+
+#[test]
+fn parses_arrays_wrapped_records_and_json_lines() {
+    assert_eq!(parse_recordings(CAPTURES).unwrap().len(), 5);
+    let array = json!([{"node_id": "pay", "input": {"amount": 1}}]).to_string();
+    assert_eq!(parse_recordings(&array).unwrap()[0].node_id, "pay");
+    let wrapped = json!({"records": [{"node_id": "a"}, {"node_id": "b"}]}).to_string();
+    assert_eq!(parse_recordings(&wrapped).unwrap().len(), 2);
+    assert!(parse_recordings("").is_err());
+    assert!(parse_recordings("{\"input\": {}}").is_err());
+}
+
+#[test]
+fn reports_payloads_that_no_longer_match() {
+    let records = parse_recordings(CAPTURES).unwrap();
+    let report = validate_recordings(&flow(), &records, |node_id| match node_id {
+        "pay" => Ok(pay_schemas()),
+        _ => Err("not bound in the resolve sidecar".to_string()),
+    })
+    .unwrap();
+
+    assert_eq!(report.records, 4);
+    assert_eq!(report.unknown_nodes, vec!["ghost".to_string()]);
+    assert_eq!(report.drifted_nodes(), vec!["pay"]);
+    let pay = &report.nodes[0];
+    assert_eq!((pay.records, pay.checked, pay.failed), (2, 4, 2));
+    assert_eq!(
+        report.nodes[1].skipped.as_deref(),
+        Some("not bound in the resolve sidecar")
+    );
+    let mismatches: Vec<(usize, PayloadDirection, &str)> = report
+        .mismatches
+        .iter()
+        .map(|m| (m.record, m.direction, m.pointer.as_str()))
+        .collect();
+    assert_eq!(
+        mismatches,
+        vec![
+            (1, PayloadDirection::Input, "/amount"),
+            (1, PayloadDirection::Output, "/status"),
+        ]
+    );
+}
+
+#[test]
+fn cli_replays_captures_against_component_manifests() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("main.ygtc");
+    let captures = dir.path().join("captures.jsonl");
+    fs::write(&flow_path, FLOW).unwrap();
+    fs::write(dir.path().join("comp.wasm"), b"wasm").unwrap();
+    fs::write(dir.path().join("component.manifest.json"), MANIFEST).unwrap();
+    fs::write(
+        flow_path.with_extension("ygtc.resolve.json"),
+        r#"{"schema_version":1,"flow":"main.ygtc","nodes":{"pay":{"source":{"kind":"local","path":"comp.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(&captures, CAPTURES).unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["replay", "--flow"])
+        .arg(&flow_path)
+        .arg("--captures")
+        .arg(&captures)
+        .assert()
+        .failure()
+        .stdout(contains("DRIFT node 'pay' input (record 1) at /amount:"))
+        .stdout(contains("FAIL  node 'pay': 4 payload(s) checked, 2 failed"))
+        .stdout(contains(
+            "SKIP  node 'notify': not bound in the resolve sidecar",
+        ))
+        .stdout(contains(
+            "WARN  captures record node 'ghost', which is not in the flow",
+        ))
+        .stderr(contains(
+            "recorded traffic no longer matches the schemas of node(s): pay",
+        ));
+
+    fs::write(
+        &captures,
+        json!([{"node_id": "pay", "input": {"amount": 7}, "output": {"status": "e402"}}])
+            .to_string(),
+    )
+    .unwrap();
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "replay", "--flow"])
+        .arg(&flow_path)
+        .arg("--captures")
+        .arg(&captures)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["ok"], true);
+    assert_eq!(payload["report"]["nodes"][0]["checked"], 2);
+}
@@ -0,0 +1,72 @@
+use greentic_flow::{
+    flow_ir::parse_flow_to_ir,
+    json_output::{JsonDiagnostic, LintJsonOutput},
+    lint::{LintDiagnostic, LintSeverity},
+    report::{FlowGraph, FlowReport, render_html_report},
+};
+
+const FLOW: &str = r#"id: main
+type: messaging
+schema_version: 2
+start: fetch
+nodes:
+  fetch:
+    http.get: {}
+    routing:
+      - status: ok
+        to: store
+      - status: "*"
+        to: fail
+  store:
+    store.put: {}
+    routing: out
+  fail:
+    questions: {fields: []}
+    routing: reply
+  orphan:
+    questions: {fields: []}
+    routing: out
+"#;
+
+#[test]
+fn graph_lays_out_nodes_by_distance_from_entrypoints() {
+    let ir = parse_flow_to_ir(FLOW).unwrap();
+    let graph = FlowGraph::from_ir(&ir);
+    let depth = |id: &str| graph.nodes.iter().find(|n| n.id == id).unwrap().depth;
+    assert_eq!(depth("fetch"), 0);
+    assert_eq!(depth("store"), 1);
+    assert_eq!(depth("fail"), 1);
+    assert_eq!(depth("orphan"), 2);
+    assert_eq!(graph.edges.len(), 2);
+    assert_eq!(graph.edges[0].label.as_deref(), Some("status ok"));
+    let fail = graph.nodes.iter().find(|n| n.id == "fail").unwrap();
+    assert_eq!(fail.terminals, vec!["reply"]);
+}
+
+#[test]
+fn html_report_escapes_findings_and_lists_components() {
+    let ir = parse_flow_to_ir(FLOW).unwrap();
+    let lint = LintJsonOutput::lint_diagnostics(
+        vec![
+            LintDiagnostic::error("placeholder_values", "payload holds '<script>'")
+                .with_path("/nodes/store/store.put/key"),
+        ],
+        Some("flows/main.ygtc".to_string()),
+    );
+    let mut report = FlowReport::new("flows/main.ygtc", lint).with_flow(&ir, |node| {
+        (node == "store").then(|| "oci://ghcr.io/acme/store:1".to_string())
+    });
+    let mut contract = JsonDiagnostic::from_message("schema drift".to_string(), None);
+    contract.severity = Some(LintSeverity::Warning);
+    report.push_finding(contract);
+
+    let html = render_html_report(&[report]);
+    assert!(html.contains("1 flow(s), 1 failing, 1 error(s), 1 warning(s)"));
+    assert!(html.contains("<td>payload holds &#39;&lt;script&gt;&#39;</td>"));
+    assert!(!html.contains("<td>placeholder_values: payload"));
+    assert!(html.contains("<td>oci://ghcr.io/acme/store:1</td><td>store.put</td>"));
+    assert!(html.contains("<svg class=\"graph\""));
+    let data_start = html.find("id=\"report-data\">").unwrap();
+    let data = &html[data_start..];
+    assert!(!data[..data.find("</script>").unwrap()].contains("</"));
+}
@@ -0,0 +1,70 @@
+use greentic_flow::{
+    lint::LintSeverity,
+    requirements::{EnvFile, NodeRequirements, check_env_requirements, secret_env_name},
+};
+use serde_json::json;
+
+fn node() -> NodeRequirements {
+    NodeRequirements::from_manifest(
+        "fetch",
+        "oci://ghcr.io/acme/weather:1.0.0",
+        &json!({
+            "capabilities": {
+                "wasi": {"env": {"allow": ["WEATHER_REGION"]}, "filesystem": {"mode": "none"}, "random": false},
+                "host": {
+                    "secrets": {"required": [
+                        {"key": "weather/api-key", "description": "API token"},
+                        {"key": "weather.backup-key", "required": false}
+                    ]},
+                    "http": {"client": true},
+                    "state": null
+                }
+            }
+        }),
+    )
+}
+
+#[test]
+fn manifest_capabilities_become_node_requirements() {
+    let node = node();
+    assert_eq!(node.env, vec!["WEATHER_REGION"]);
+    assert_eq!(node.secrets.len(), 2);
+    assert!(node.secrets[0].required);
+    assert_eq!(node.secrets[0].description.as_deref(), Some("API token"));
+    assert!(!node.secrets[1].required);
+    assert_eq!(node.capabilities, vec!["wasi:filesystem", "host:http"]);
+
+    let bare = NodeRequirements::from_manifest("n", "c", &json!({"id": "x"}));
+    assert!(bare.is_empty());
+}
+
+#[test]
+fn env_files_parse_dotenv_lines() {
+    let env =
+        EnvFile::parse("# comment\n\nexport A=1\nB = \"two words\"\nC='3'\nEMPTY=\n").unwrap();
+    assert_eq!(env.vars["A"], "1");
+    assert_eq!(env.vars["B"], "two words");
+    assert_eq!(env.vars["C"], "3");
+    assert!(!env.provides_var("EMPTY"));
+
+    let err = EnvFile::parse("A=1\nnot an assignment\n").unwrap_err();
+    assert!(err.to_string().contains("line 2"), "{err}");
+}
+
+#[test]
+fn env_check_reports_missing_vars_and_secrets() {
+    assert_eq!(secret_env_name("weather/api-key"), "WEATHER_API_KEY");
+
+    let env = EnvFile::parse("WEATHER_API_KEY=t\n").unwrap();
+    let diags = check_env_requirements(&[node()], &env);
+    assert_eq!(diags.len(), 2);
+    assert_eq!(diags[0].severity, LintSeverity::Error);
+    assert!(diags[0].message.contains("WEATHER_REGION"));
+    assert_eq!(diags[1].severity, LintSeverity::Warning);
+    assert!(diags[1].message.contains("weather.backup-key"));
+    assert_eq!(diags[1].path.as_deref(), Some("/nodes/fetch"));
+
+    let env =
+        EnvFile::parse("WEATHER_REGION=eu\nweather/api-key=t\nWEATHER_BACKUP_KEY=b\n").unwrap();
+    assert!(check_env_requirements(&[node()], &env).is_empty());
+}
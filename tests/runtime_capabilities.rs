@@ -0,0 +1,90 @@
+use greentic_flow::{
+    error::FlowError, load_and_validate_bundle_for_runtime,
+    runtime_capabilities::RuntimeCapabilities,
+};
+
+const FLOW: &str = r#"id: main
+type: messaging
+schema_version: 2
+start: fetch
+meta:
+  greentic:
+    components:
+      fetch:
+        world: "greentic:component@0.6.0"
+        schema_id: "greentic.weather.v1"
+      store:
+        world: "greentic:component@0.7.0"
+nodes:
+  fetch:
+    fetch:
+      city: "Paris"
+    routing:
+      - to: store
+  store:
+    store:
+      key: "k"
+    routing: out
+"#;
+
+fn runtime(worlds: &[&str], schemas: &[&str]) -> RuntimeCapabilities {
+    RuntimeCapabilities {
+        worlds: worlds.iter().map(|w| w.to_string()).collect(),
+        schemas: schemas.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+#[test]
+fn worlds_match_with_caret_semantics() {
+    let caps = runtime(&["greentic:component@0.6.2", "wasi:http@1.0.0"], &[]);
+    assert!(caps.provides_world("greentic:component@0.6.0"));
+    assert!(!caps.provides_world("greentic:component@0.7.0"));
+    assert!(!caps.provides_world("greentic:component@0.6.3"));
+    assert!(caps.provides_world("wasi:http@1.0.0"));
+    assert!(!caps.provides_world("acme:custom@1.0.0"));
+}
+
+#[test]
+fn bundle_validation_reports_each_unsupported_node() {
+    let err = load_and_validate_bundle_for_runtime(
+        FLOW,
+        None,
+        &runtime(&["greentic:component@0.6.2"], &[]),
+    )
+    .unwrap_err();
+    let FlowError::RuntimeIncompatible { details, .. } = &err else {
+        panic!("expected runtime incompatibility, got {err:?}");
+    };
+    let pointers: Vec<_> = details
+        .iter()
+        .map(|d| d.location.json_pointer.as_deref().unwrap())
+        .collect();
+    assert_eq!(pointers, vec!["/nodes/fetch", "/nodes/store"]);
+    assert!(details[0].message.contains("schema 'greentic.weather.v1'"));
+    assert!(
+        details[1]
+            .message
+            .contains("world 'greentic:component@0.7.0'")
+    );
+
+    let bundle = load_and_validate_bundle_for_runtime(
+        FLOW,
+        None,
+        &runtime(
+            &["greentic:component@0.7.1", "greentic:component@0.6.2"],
+            &["greentic.weather.v1"],
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        bundle.nodes[0].world.as_deref(),
+        Some("greentic:component@0.6.0")
+    );
+}
+
+#[test]
+fn manifest_parses_from_json() {
+    let caps = RuntimeCapabilities::from_json_str(r#"{"worlds": ["a:b@1.0.0"]}"#).unwrap();
+    assert_eq!(caps, runtime(&["a:b@1.0.0"], &[]));
+    assert!(RuntimeCapabilities::from_json_str(r#"{"worlds": "a:b@1.0.0"}"#).is_err());
+}
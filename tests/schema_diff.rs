@@ -0,0 +1,97 @@
+use greentic_flow::schema_diff::{SchemaChangeKind, diff_manifests, diff_schemas, payload_breaks};
+use serde_json::json;
+
+#[test]
+fn diff_reports_removed_narrowed_and_required_fields() {
+    let old = json!({
+        "type": "object",
+        "required": ["channel"],
+        "properties": {
+            "channel": {"type": "string"},
+            "retries": {"type": "number"},
+            "legacy": {"type": "boolean"},
+            "mode": {"enum": ["fast", "slow", "safe"]},
+            "target": {"type": "object", "properties": {"host": {"type": ["string", "null"]}}}
+        }
+    });
+    let new = json!({
+        "type": "object",
+        "required": ["channel", "region"],
+        "properties": {
+            "channel": {"type": "string"},
+            "retries": {"type": "integer"},
+            "mode": {"enum": ["fast", "safe"]},
+            "region": {"type": "string"},
+            "target": {"type": "object", "properties": {"host": {"type": "string"}}}
+        }
+    });
+
+    let changes: Vec<_> = diff_schemas(&old, &new)
+        .into_iter()
+        .map(|change| (change.pointer, change.kind))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("/legacy".to_string(), SchemaChangeKind::FieldRemoved),
+            ("/mode".to_string(), SchemaChangeKind::EnumNarrowed),
+            ("/retries".to_string(), SchemaChangeKind::TypeNarrowed),
+            ("/target/host".to_string(), SchemaChangeKind::TypeNarrowed),
+            ("/region".to_string(), SchemaChangeKind::FieldNowRequired),
+        ]
+    );
+}
+
+#[test]
+fn widening_changes_are_not_reported() {
+    let old = json!({"type": "object", "required": ["a"], "properties": {
+        "a": {"type": "integer", "enum": [1, 2]}
+    }});
+    let new = json!({"type": "object", "properties": {
+        "a": {"type": ["number", "string"], "enum": [1, 2, 3]},
+        "b": {"type": "string"}
+    }});
+    assert!(diff_schemas(&old, &new).is_empty());
+}
+
+#[test]
+fn payload_breaks_only_for_values_the_change_affects() {
+    let old = json!({
+        "id": "ai.greentic.pay",
+        "operations": [
+            {"name": "charge", "input_schema": {"type": "object", "properties": {
+                "amount": {"type": "number"}, "note": {"type": "string"}
+            }}},
+            {"name": "refund"}
+        ]
+    });
+    let new = json!({
+        "id": "ai.greentic.pay",
+        "operations": [
+            {"name": "charge", "input_schema": {"type": "object", "properties": {
+                "amount": {"type": "integer"}
+            }}}
+        ]
+    });
+    let changes = diff_manifests(&old, &new);
+    assert!(
+        changes
+            .iter()
+            .any(|change| change.kind == SchemaChangeKind::OperationRemoved
+                && change.operation.as_deref() == Some("refund"))
+    );
+
+    assert!(payload_breaks(&old, &changes, "charge", &json!({"amount": 5})).is_empty());
+    let breaks = payload_breaks(
+        &old,
+        &changes,
+        "charge",
+        &json!({"amount": 5.5, "note": "x"}),
+    );
+    assert_eq!(breaks.len(), 2, "{breaks:?}");
+    assert!(breaks.iter().any(|reason| reason.contains("/note")));
+    assert_eq!(
+        payload_breaks(&old, &changes, "refund", &json!({})),
+        vec!["operation 'refund' was removed".to_string()]
+    );
+}
@@ -0,0 +1,161 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::schema_import::json_schema_to_schema_ir;
+use greentic_flow::schema_render::schema_ir_to_json_schema;
+use greentic_types::cbor::canonical;
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::tempdir;
+
+fn mailer_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Send email",
+        "type": "object",
+        "required": ["to"],
+        "additionalProperties": false,
+        "properties": {
+            "to": {"type": "string", "format": "email", "minLength": 3},
+            "retries": {"type": "integer", "minimum": 0, "exclusiveMaximum": 5.5},
+            "priority": {"enum": ["low", "high"], "description": "queue"},
+            "attachment": {"type": "string", "contentEncoding": "base64"},
+            "cc": {"type": "array", "items": {"$ref": "#/$defs/address"}, "maxItems": 3},
+            "reply_to": {"type": ["string", "null"]}
+        },
+        "$defs": {
+            "address": {"type": "string", "pattern": "^.+@.+$"}
+        }
+    })
+}
+
+#[test]
+fn imports_the_shared_subset_without_diagnostics() {
+    let import = json_schema_to_schema_ir(&mailer_schema()).unwrap();
+    assert!(import.diagnostics.is_empty(), "{:?}", import.diagnostics);
+    let SchemaIr::Object {
+        properties,
+        required,
+        additional,
+    } = &import.schema
+    else {
+        panic!("expected an object, got {:?}", import.schema);
+    };
+    assert_eq!(required, &vec!["to".to_string()]);
+    assert_eq!(additional, &AdditionalProperties::Forbid);
+    assert_eq!(
+        properties["retries"],
+        SchemaIr::Int {
+            min: Some(0),
+            max: Some(5)
+        }
+    );
+    assert_eq!(properties["attachment"], SchemaIr::Bytes);
+    assert!(matches!(
+        &properties["reply_to"],
+        SchemaIr::OneOf { variants } if variants[1] == SchemaIr::Null
+    ));
+
+    // Everything but the annotations survives a trip back to JSON Schema.
+    let exported = schema_ir_to_json_schema(&import.schema);
+    assert_eq!(
+        exported["properties"]["cc"],
+        json!({"type": "array", "maxItems": 3, "items": {"type": "string", "pattern": "^.+@.+$"}})
+    );
+    assert_eq!(
+        exported["properties"]["to"],
+        mailer_schema()["properties"]["to"]
+    );
+    assert_eq!(
+        exported["properties"]["priority"],
+        json!({"enum": ["low", "high"]})
+    );
+}
+
+#[test]
+fn reports_features_schema_ir_cannot_express() {
+    let import = json_schema_to_schema_ir(&json!({
+        "type": "object",
+        "properties": {
+            "ratio": {"type": "number", "exclusiveMinimum": 0},
+            "tags": {"type": "array", "uniqueItems": true, "items": {"type": "string"}},
+            "tree": {"$ref": "#/definitions/tree"},
+            "anything": {}
+        },
+        "definitions": {
+            "tree": {"type": "object", "properties": {"children": {"type": "array", "items": {"$ref": "#/definitions/tree"}}}}
+        }
+    }))
+    .unwrap();
+    let pointers: Vec<(&str, &str)> = import
+        .diagnostics
+        .iter()
+        .map(|d| (d.pointer.as_str(), d.keyword.as_str()))
+        .collect();
+    assert_eq!(
+        pointers,
+        vec![
+            ("/properties/anything", ""),
+            ("/properties/ratio/exclusiveMinimum", "exclusiveMinimum"),
+            ("/properties/tags/uniqueItems", "uniqueItems"),
+            ("/definitions/tree/properties/children/items/$ref", "$ref"),
+        ]
+    );
+    assert!(
+        import.diagnostics[2]
+            .message
+            .contains("'uniqueItems' is not supported"),
+        "{:?}",
+        import.diagnostics
+    );
+
+    let err = json_schema_to_schema_ir(&json!({"properties": {"id": {"type": "uuid"}}}))
+        .unwrap_err()
+        .to_string();
+    assert_eq!(err, "/properties/id/type: unknown type 'uuid'");
+}
+
+#[test]
+fn schema_convert_writes_canonical_cbor() {
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("schema.json");
+    let out = dir.path().join("out/schema.cbor");
+    fs::write(&input, serde_json::to_string(&mailer_schema()).unwrap()).unwrap();
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["schema", "convert", "--in"])
+        .arg(&input)
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Wrote"));
+    let bytes = fs::read(&out).unwrap();
+    let decoded: SchemaIr = canonical::from_cbor(&bytes).unwrap();
+    assert_eq!(
+        decoded,
+        json_schema_to_schema_ir(&mailer_schema()).unwrap().schema
+    );
+
+    fs::write(&input, r#"{"type": "object", "not": {"required": ["x"]}}"#).unwrap();
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "schema", "convert", "--in"])
+        .arg(&input)
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["action"], "schema-convert");
+    assert_eq!(payload["diagnostics"][0]["pointer"], "/not");
+
+    fs::write(&input, r#"{"type": 7}"#).unwrap();
+    cargo_bin_cmd!("greentic-flow")
+        .args(["schema", "convert", "--in"])
+        .arg(&input)
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("/type: unknown type 7"));
+}
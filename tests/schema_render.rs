@@ -0,0 +1,162 @@
+use ciborium::value::Value as CborValue;
+use greentic_flow::schema_render::{
+    describe_schema_ir, render_schema_table, schema_ir_to_json_schema,
+};
+use greentic_flow::schema_validate::validate_value_against_schema;
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn string() -> SchemaIr {
+    SchemaIr::String {
+        min_len: None,
+        max_len: None,
+        regex: None,
+        format: None,
+    }
+}
+
+fn finding_schema() -> SchemaIr {
+    SchemaIr::Object {
+        properties: BTreeMap::from([
+            ("message".to_string(), string()),
+            (
+                "path".to_string(),
+                SchemaIr::String {
+                    min_len: Some(1),
+                    max_len: None,
+                    regex: None,
+                    format: None,
+                },
+            ),
+            (
+                "severity".to_string(),
+                SchemaIr::Enum {
+                    values: vec![
+                        CborValue::Text("error".to_string()),
+                        CborValue::Text("warning".to_string()),
+                    ],
+                },
+            ),
+            (
+                "fixes".to_string(),
+                SchemaIr::Array {
+                    items: Box::new(SchemaIr::Object {
+                        properties: BTreeMap::from([(
+                            "line".to_string(),
+                            SchemaIr::Int {
+                                min: Some(1),
+                                max: None,
+                            },
+                        )]),
+                        required: vec!["line".to_string()],
+                        additional: AdditionalProperties::Forbid,
+                    }),
+                    min_items: None,
+                    max_items: Some(3),
+                },
+            ),
+            (
+                "labels".to_string(),
+                SchemaIr::Object {
+                    properties: BTreeMap::new(),
+                    required: Vec::new(),
+                    additional: AdditionalProperties::Schema(Box::new(string())),
+                },
+            ),
+        ]),
+        required: vec!["message".to_string()],
+        additional: AdditionalProperties::Allow,
+    }
+}
+
+#[test]
+fn converts_to_json_schema() {
+    assert_eq!(
+        schema_ir_to_json_schema(&finding_schema()),
+        json!({
+            "type": "object",
+            "required": ["message"],
+            "properties": {
+                "message": {"type": "string"},
+                "path": {"type": "string", "minLength": 1},
+                "severity": {"enum": ["error", "warning"]},
+                "fixes": {
+                    "type": "array",
+                    "maxItems": 3,
+                    "items": {
+                        "type": "object",
+                        "properties": {"line": {"type": "integer", "minimum": 1}},
+                        "required": ["line"],
+                        "additionalProperties": false
+                    }
+                },
+                "labels": {
+                    "type": "object",
+                    "properties": {},
+                    "additionalProperties": {"type": "string"}
+                }
+            }
+        })
+    );
+    assert_eq!(
+        schema_ir_to_json_schema(&SchemaIr::OneOf {
+            variants: vec![
+                SchemaIr::Bytes,
+                SchemaIr::Ref {
+                    id: "x".to_string()
+                }
+            ],
+        }),
+        json!({"oneOf": [{"type": "string", "contentEncoding": "base64"}, {"$ref": "x"}]})
+    );
+}
+
+#[test]
+fn renders_a_field_table() {
+    assert_eq!(
+        render_schema_table(&finding_schema()),
+        "FIELD         TYPE                        REQUIRED  CONSTRAINTS\n\
+         fixes         array                       no        max 3 items\n\
+         fixes[]       object                      -         no other keys\n\
+         fixes[].line  integer                     yes       >= 1\n\
+         labels        object                      no\n\
+         labels.*      string                      -\n\
+         message       string                      yes\n\
+         path          string                      no        min length 1\n\
+         severity      one of \"error\" | \"warning\"  no\n"
+    );
+    assert_eq!(
+        render_schema_table(&SchemaIr::Bool),
+        "FIELD    TYPE     REQUIRED  CONSTRAINTS\n(value)  boolean  -\n"
+    );
+    assert_eq!(
+        render_schema_table(&SchemaIr::Object {
+            properties: BTreeMap::new(),
+            required: Vec::new(),
+            additional: AdditionalProperties::Allow,
+        }),
+        "(no fields)\n"
+    );
+}
+
+#[test]
+fn type_mismatches_describe_the_expected_shape() {
+    assert_eq!(
+        describe_schema_ir(&finding_schema()),
+        "object with fields: fixes (array of object), labels (object), message (string, required), \
+         path (string), severity (one of \"error\" | \"warning\")"
+    );
+
+    let diags = validate_value_against_schema(&finding_schema(), &CborValue::Text("x".into()));
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].code, "SCHEMA_TYPE_MISMATCH");
+    assert!(
+        diags[0]
+            .message
+            .starts_with("expected object with fields: fixes (array of object), "),
+        "{}",
+        diags[0].message
+    );
+    assert!(diags[0].message.ends_with(" at $"), "{}", diags[0].message);
+}
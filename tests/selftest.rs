@@ -0,0 +1,37 @@
+use greentic_flow::selftest::run_determinism_suite;
+
+const FLOW: &str = r#"id: main
+type: messaging
+start: fetch
+nodes:
+  fetch:
+    http.get:
+      url: https://api.example.com
+      headers:
+        b: 2
+        a: 1
+    routing:
+      - to: store
+  store:
+    kv.put:
+      key: k
+    routing: out
+"#;
+
+#[test]
+fn determinism_suite_passes_with_stable_digests() {
+    let checks = run_determinism_suite(FLOW, None, 3).unwrap();
+    let names: Vec<&str> = checks.iter().map(|check| check.name).collect();
+    assert_eq!(names, vec!["parse-serialize", "add-step", "bundle-hash"]);
+    assert!(checks.iter().all(|check| check.passed), "{checks:?}");
+    assert!(checks.iter().all(|check| check.detail.is_none()));
+
+    let again = run_determinism_suite(FLOW, None, 2).unwrap();
+    assert_eq!(checks, again, "digests are comparable across invocations");
+}
+
+#[test]
+fn determinism_suite_reports_invalid_flows_as_errors() {
+    let err = run_determinism_suite("id: main\nnodes: []\n", None, 2).unwrap_err();
+    assert!(!err.to_string().is_empty());
+}
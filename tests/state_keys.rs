@@ -0,0 +1,156 @@
+use greentic_flow::{
+    config_flow::run_config_flow,
+    questions::{Answers, Question, QuestionKind},
+    state_keys::{StateKeyRegistry, StateKeyReport, StateKeyUser, writes_to_collisions},
+};
+use serde_json::{Map, json};
+use std::path::Path;
+
+const TWO_WIZARDS: &str = r#"id: cfg
+type: component-config
+start: ask_http
+nodes:
+  ask_http:
+    questions:
+      fields:
+        - id: region
+          default: eu-west
+        - id: timeout
+          default: 30
+    routing:
+      - to: ask_storage
+  ask_storage:
+    questions:
+      fields:
+        - id: region
+          default: us-east
+        - id: timeout
+          default: 30
+    routing:
+      - to: emit
+  emit:
+    template: |
+      {
+        "node_id": "store",
+        "node": {
+          "store.put": { "region": "{{state.region}}" },
+          "routing": [ { "to": "NEXT_NODE_PLACEHOLDER" } ]
+        }
+      }
+"#;
+
+#[test]
+fn registry_reports_differing_values_only() {
+    let mut registry = StateKeyRegistry::new();
+    assert!(
+        registry
+            .record("region", "a.region", &json!("eu"))
+            .is_none()
+    );
+    assert!(
+        registry
+            .record("region", "b.region", &json!("eu"))
+            .is_none()
+    );
+    let collision = registry
+        .record("region", "c.region", &json!("us"))
+        .cloned()
+        .expect("collision");
+    assert_eq!(collision.first, "a.region");
+    assert_eq!(collision.second, "c.region");
+    assert_eq!(
+        collision.to_string(),
+        r#"state key 'region' written by a.region ("eu") and c.region ("us")"#
+    );
+    assert_eq!(registry.collisions().len(), 1);
+}
+
+#[test]
+fn config_flow_run_reports_colliding_question_defaults() {
+    let output = run_config_flow(
+        TWO_WIZARDS,
+        Path::new("schemas/ygtc.flow.schema.json"),
+        &Map::new(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(output.node["store.put"]["region"], json!("eu-west"));
+    assert_eq!(output.state_key_collisions.len(), 1);
+    let collision = &output.state_key_collisions[0];
+    assert_eq!(collision.key, "region");
+    assert_eq!(collision.first, "ask_http.region");
+    assert_eq!(collision.second, "ask_storage.region");
+
+    // An explicit answer applies to every node asking for the key, so nothing collides.
+    let mut answers = Map::new();
+    answers.insert("region".to_string(), json!("ap-south"));
+    let output = run_config_flow(
+        TWO_WIZARDS,
+        Path::new("schemas/ygtc.flow.schema.json"),
+        &answers,
+        None,
+    )
+    .unwrap();
+    assert!(output.state_key_collisions.is_empty());
+}
+
+#[test]
+fn writes_to_collisions_flag_questions_sharing_a_path() {
+    let question = |id: &str, writes_to: &str| Question {
+        id: id.to_string(),
+        prompt: id.to_string(),
+        kind: QuestionKind::String,
+        required: false,
+        default: None,
+        choices: Vec::new(),
+        show_if: None,
+        writes_to: Some(writes_to.to_string()),
+    };
+    let questions = vec![
+        question("primary_url", "http.url"),
+        question("fallback_url", "http.url"),
+        question("token", "auth.token"),
+    ];
+    let mut answers = Answers::new();
+    answers.insert("primary_url".to_string(), json!("https://a"));
+    answers.insert("fallback_url".to_string(), json!("https://b"));
+    answers.insert("token".to_string(), json!("t"));
+    let collisions = writes_to_collisions(&questions, &answers);
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].key, "http.url");
+
+    answers.insert("fallback_url".to_string(), json!("https://a"));
+    assert!(writes_to_collisions(&questions, &answers).is_empty());
+}
+
+#[test]
+fn report_marks_keys_shared_between_components() {
+    let graph = json!({"nodes": {"ask": {"questions": {"fields": [
+        {"id": "region"},
+        {"id": "url", "writes_to": "http.base_url"}
+    ]}}}});
+    let user = |flow: &str, node: &str, component: &str| StateKeyUser {
+        flow: flow.to_string(),
+        node_id: Some(node.to_string()),
+        component: Some(component.to_string()),
+        dev_flow: Some("default".to_string()),
+        questions_node: String::new(),
+        question: String::new(),
+    };
+    let mut report = StateKeyReport::default();
+    report.add_config_flow(&graph, &user("main.ygtc", "a", "oci://acme/http"));
+    report.add_config_flow(&graph, &user("main.ygtc", "b", "oci://acme/http"));
+    assert_eq!(
+        report.shared_keys().count(),
+        0,
+        "same component at two nodes"
+    );
+
+    report.add_config_flow(
+        &json!({"nodes": {"q": {"questions": {"fields": [{"id": "region"}]}}}}),
+        &user("other.ygtc", "s", "oci://acme/storage"),
+    );
+    assert_eq!(report.shared_keys().collect::<Vec<_>>(), vec!["region"]);
+    assert_eq!(report.keys["http.base_url"][0].question, "url");
+    assert_eq!(report.keys["region"][2].questions_node, "q");
+}
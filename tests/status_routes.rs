@@ -0,0 +1,84 @@
+use greentic_flow::{
+    flow_ir::Route,
+    lint::{DeclaredStatuses, StatusRoutesRule},
+};
+use serde_json::json;
+
+fn status_route(status: &str) -> Route {
+    Route {
+        to: Some("next".to_string()),
+        status: Some(status.to_string()),
+        ..Route::default()
+    }
+}
+
+#[test]
+fn declared_statuses_come_from_enum_or_one_of() {
+    let from_enum = DeclaredStatuses::from_output_schema(&json!({
+        "properties": {"status": {"enum": ["ok", "e412", "error_timeout", "404", "retry"]}}
+    }))
+    .unwrap();
+    assert_eq!(from_enum.errors, vec!["e412", "error_timeout", "404"]);
+
+    let from_one_of = DeclaredStatuses::from_output_schema(&json!({
+        "properties": {"status": {
+            "oneOf": [{"const": "ok"}, {"const": "declined"}],
+            "x-error-statuses": ["declined"]
+        }}
+    }))
+    .unwrap();
+    assert_eq!(from_one_of.all, vec!["ok", "declined"]);
+    assert_eq!(from_one_of.errors, vec!["declined"]);
+
+    assert!(
+        DeclaredStatuses::from_output_schema(
+            &json!({"properties": {"status": {"type": "string"}}})
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn rule_reports_per_route_and_missing_error_statuses() {
+    let declared = DeclaredStatuses {
+        all: vec!["ok".into(), "e402".into(), "e412".into()],
+        errors: vec!["e402".into(), "e412".into()],
+    };
+
+    let diagnostics = StatusRoutesRule::check(
+        "pay",
+        &[
+            status_route("ok"),
+            status_route("e41"),
+            status_route("e412"),
+        ],
+        &declared,
+    );
+    let paths: Vec<_> = diagnostics.iter().map(|d| d.path.as_deref()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            Some("/nodes/pay/routing/1/status"),
+            Some("/nodes/pay/routing")
+        ]
+    );
+    assert!(diagnostics[1].message.contains("e402"), "{diagnostics:?}");
+
+    let with_catch_all = [status_route("e412"), status_route("*")];
+    assert!(StatusRoutesRule::check("pay", &with_catch_all, &declared).is_empty());
+    let with_negation = [status_route("ok"), status_route("!ok")];
+    assert!(StatusRoutesRule::check("pay", &with_negation, &declared).is_empty());
+    let undeclared_negation = [status_route("!done")];
+    assert_eq!(
+        StatusRoutesRule::check("pay", &undeclared_negation, &declared)[0]
+            .path
+            .as_deref(),
+        Some("/nodes/pay/routing/0/status")
+    );
+
+    let no_status = [Route {
+        out: true,
+        ..Route::default()
+    }];
+    assert!(StatusRoutesRule::check("pay", &no_status, &declared).is_empty());
+}
@@ -0,0 +1,231 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::{
+    flow_ir::{FlowIr, parse_flow_to_ir, to_dot_highlighted, to_mermaid_highlighted},
+    trace::{TraceRoute, annotate_trace, parse_otlp_json, trace_ids},
+};
+use serde_json::{Value, json};
+use std::fs;
+use tempfile::tempdir;
+
+const FLOW: &str = r#"
+id: checkout
+type: messaging
+start: pay
+nodes:
+  pay:
+    charge:
+      amount: 5
+    routing:
+      - status: e402
+        label: declined
+        to: retry
+      - status: e500
+        reply: true
+      - to: receipt
+  retry:
+    charge: {}
+    telemetry:
+      span_name: checkout.retry
+    routing: out
+  receipt:
+    emit: {}
+    routing: reply
+"#;
+
+fn flow() -> FlowIr {
+    parse_flow_to_ir(FLOW).unwrap()
+}
+
+fn span(trace: &str, id: &str, name: &str, start: u64, attributes: Value) -> Value {
+    json!({
+        "traceId": trace,
+        "spanId": id,
+        "name": name,
+        "startTimeUnixNano": start.to_string(),
+        "endTimeUnixNano": (start + 2_000_000).to_string(),
+        "attributes": attributes,
+    })
+}
+
+/// Trace `a`: pay (declined) -> retry (failed, via span_name) plus an http child span of pay.
+/// Trace `b`, later: pay -> receipt.
+fn export() -> String {
+    let mut retry = span("a", "a3", "checkout.retry", 300, json!([]));
+    retry["status"] = json!({"code": 2, "message": "card expired"});
+    let mut child = span(
+        "a",
+        "a2",
+        "http.request",
+        150,
+        json!([{"key": "greentic.node.id", "value": {"stringValue": "pay"}}]),
+    );
+    child["parentSpanId"] = json!("a1");
+    json!({
+        "resourceSpans": [{
+            "scopeSpans": [{
+                "spans": [
+                    span("b", "b1", "node", 1_000, json!([
+                        {"key": "greentic.node.id", "value": {"stringValue": "pay"}},
+                        {"key": "greentic.flow.id", "value": {"stringValue": "checkout"}}
+                    ])),
+                    span("b", "b2", "receipt", 2_000, json!([])),
+                    span("a", "a1", "node", 100, json!([
+                        {"key": "greentic.node.id", "value": {"stringValue": "pay"}},
+                        {"key": "greentic.node.status", "value": {"stringValue": "e402"}}
+                    ])),
+                    child,
+                    retry,
+                    span("a", "a9", "db.query", 400, json!([
+                        {"key": "greentic.flow.id", "value": {"stringValue": "other"}}
+                    ])),
+                ]
+            }]
+        }]
+    })
+    .to_string()
+}
+
+#[test]
+fn maps_spans_to_steps_and_routes() {
+    let spans = parse_otlp_json(&export()).unwrap();
+    assert_eq!(spans.len(), 6);
+    assert_eq!(trace_ids(&spans), vec!["a", "b"]);
+
+    let annotation = annotate_trace(&flow(), &spans, None).unwrap();
+    assert_eq!(annotation.trace_id, "a");
+    let steps: Vec<(&str, Option<usize>, Option<&str>)> = annotation
+        .steps
+        .iter()
+        .map(|step| (step.node_id.as_str(), step.route, step.next.as_deref()))
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            ("pay", Some(0), Some("retry")),
+            ("retry", Some(0), Some("out"))
+        ]
+    );
+    assert!(annotation.steps[1].error);
+    assert_eq!(
+        annotation.steps[1].error_message.as_deref(),
+        Some("card expired")
+    );
+    assert_eq!(annotation.steps[0].duration_nanos, 2_000_000);
+    assert_eq!(
+        annotation.untaken,
+        vec![
+            TraceRoute {
+                from: "pay".to_string(),
+                index: Some(1),
+                to: "reply".to_string(),
+                label: Some("status e500".to_string()),
+            },
+            TraceRoute {
+                from: "pay".to_string(),
+                index: Some(2),
+                to: "receipt".to_string(),
+                label: None,
+            },
+        ]
+    );
+    assert!(annotation.unexpected.is_empty());
+    assert_eq!(annotation.unmatched_spans, 0);
+
+    let other = annotate_trace(&flow(), &spans, Some("b")).unwrap();
+    let path: Vec<&str> = other
+        .steps
+        .iter()
+        .filter_map(|s| s.next.as_deref())
+        .collect();
+    assert_eq!(path, vec!["receipt", "reply"]);
+    assert!(annotate_trace(&flow(), &spans, Some("zzz")).is_err());
+}
+
+#[test]
+fn reports_transitions_without_a_route() {
+    let text = json!({"resourceSpans": [{"scopeSpans": [{"spans": [
+        span("t", "1", "receipt", 1, json!([])),
+        span("t", "2", "pay", 2, json!([])),
+        span("t", "3", "unknown", 3, json!([])),
+    ]}]}]})
+    .to_string();
+    let annotation = annotate_trace(&flow(), &parse_otlp_json(&text).unwrap(), None).unwrap();
+    assert_eq!(annotation.unexpected.len(), 1);
+    assert_eq!(annotation.unexpected[0].from, "receipt");
+    assert_eq!(annotation.unexpected[0].to, "pay");
+    assert_eq!(annotation.unmatched_spans, 1);
+    assert!(parse_otlp_json("{}").is_err());
+}
+
+#[test]
+fn highlighted_graphs_mark_taken_routes() {
+    let spans = parse_otlp_json(&export()).unwrap();
+    let highlight = annotate_trace(&flow(), &spans, None).unwrap().highlight();
+    let mermaid = to_mermaid_highlighted(&flow(), &highlight);
+    for line in [
+        "    class n0 executed",
+        "    class n1 failed",
+        "    class n2 idle",
+        "    linkStyle 1 stroke:#2b8a3e,stroke-width:3px",
+        "    linkStyle 2 stroke:#adb5bd,stroke-dasharray:4 4",
+        "    linkStyle 4 stroke:#2b8a3e,stroke-width:3px",
+    ] {
+        assert!(
+            mermaid.lines().any(|l| l == line),
+            "missing {line:?} in\n{mermaid}"
+        );
+    }
+    let dot = to_dot_highlighted(&flow(), &highlight);
+    assert!(
+        dot.contains(r##""pay" -> "retry" [label="declined", color="#2b8a3e", penwidth=3];"##),
+        "{dot}"
+    );
+    assert!(dot.contains(r##""pay" -> "receipt" [color="#adb5bd", style=dashed];"##));
+}
+
+#[test]
+fn cli_annotates_a_trace() {
+    let dir = tempdir().unwrap();
+    let flow_path = dir.path().join("checkout.ygtc");
+    let trace_path = dir.path().join("trace.json");
+    fs::write(&flow_path, FLOW).unwrap();
+    fs::write(&trace_path, export()).unwrap();
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["trace", "annotate", "--flow"])
+        .arg(&flow_path)
+        .arg("--trace")
+        .arg(&trace_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for line in [
+        "trace a: 2 step(s) through checkout (1 other trace(s) in the export; pick one with --trace-id)",
+        "  1. pay 2.0ms status e402 -> retry",
+        "  2. retry 2.0ms ERROR (card expired) -> out",
+        "  pay -> reply [status e500]",
+        "  pay -> receipt",
+        "flowchart LR",
+    ] {
+        assert!(
+            stdout.lines().any(|l| l == line),
+            "missing {line:?} in\n{stdout}"
+        );
+    }
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "trace", "annotate", "--trace-id", "b"])
+        .args(["--graph-format", "dot", "--flow"])
+        .arg(&flow_path)
+        .arg("--trace")
+        .arg(&trace_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["traces"], json!(["a", "b"]));
+    assert_eq!(payload["annotation"]["trace_id"], "b");
+    assert_eq!(payload["annotation"]["steps"][1]["next"], "reply");
+    assert!(payload["diagram"].as_str().unwrap().starts_with("digraph"));
+}
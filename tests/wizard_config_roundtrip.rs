@@ -0,0 +1,74 @@
+use ciborium::value::Value as CValue;
+use greentic_flow::wizard_ops::{cbor_to_json, config_json_to_cbor};
+use greentic_types::cbor::canonical;
+use greentic_types::schemas::common::schema_ir::{AdditionalProperties, SchemaIr};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn config_schema() -> SchemaIr {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "retries".to_string(),
+        SchemaIr::Int {
+            min: None,
+            max: None,
+        },
+    );
+    properties.insert(
+        "ratio".to_string(),
+        SchemaIr::Float {
+            min: None,
+            max: None,
+        },
+    );
+    properties.insert("key".to_string(), SchemaIr::Bytes);
+    properties.insert(
+        "limits".to_string(),
+        SchemaIr::Array {
+            items: Box::new(SchemaIr::Int {
+                min: None,
+                max: None,
+            }),
+            min_items: None,
+            max_items: None,
+        },
+    );
+    SchemaIr::Object {
+        properties,
+        required: Vec::new(),
+        additional: AdditionalProperties::Allow,
+    }
+}
+
+#[test]
+fn payload_round_trips_to_original_config_cbor() {
+    let original = CValue::Map(vec![
+        ("key".into(), CValue::Bytes(vec![1, 2, 255])),
+        ("label".into(), "greeter".into()),
+        ("limits".into(), CValue::Array(vec![1.into(), 2.into()])),
+        ("ratio".into(), CValue::Float(2.0)),
+        ("retries".into(), CValue::Integer(u64::MAX.into())),
+    ]);
+    let original_cbor = canonical::to_canonical_cbor_allow_floats(&original).expect("encode");
+    let payload = cbor_to_json(&original_cbor).expect("json");
+
+    let rebuilt = config_json_to_cbor(&payload, Some(&config_schema())).expect("rebuild");
+    assert_eq!(rebuilt, original_cbor);
+}
+
+#[test]
+fn schema_coerces_integral_floats_and_leaves_unknown_keys() {
+    let rebuilt = config_json_to_cbor(
+        &json!({"retries": 3.0, "extra": 1.5}),
+        Some(&config_schema()),
+    )
+    .expect("rebuild");
+    let value: CValue = ciborium::de::from_reader(rebuilt.as_slice()).expect("decode");
+    assert_eq!(
+        value,
+        CValue::Map(vec![
+            ("extra".into(), CValue::Float(1.5)),
+            ("retries".into(), CValue::Integer(3.into())),
+        ])
+    );
+}
@@ -0,0 +1,160 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use greentic_flow::workspace_index::{IndexQuery, WorkspaceIndex, index_path};
+use predicates::str::contains;
+use serde_json::Value;
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+const BILLING: &str = r#"id: billing
+type: messaging
+schema_version: 2
+tags: [payments]
+start: charge
+nodes:
+  charge:
+    pay: {}
+    routing:
+      - to: notify
+  notify:
+    emit: {}
+    routing: out
+"#;
+
+const SUPPORT: &str = r#"id: support
+type: messaging
+schema_version: 2
+start: answer
+nodes:
+  answer:
+    emit: {}
+    routing: out
+"#;
+
+fn write_workspace(root: &Path) {
+    fs::create_dir_all(root.join("flows/support")).unwrap();
+    fs::write(root.join("flows/billing.ygtc"), BILLING).unwrap();
+    fs::write(
+        root.join("flows/billing.ygtc.resolve.json"),
+        r#"{"schema_version":1,"flow":"billing.ygtc","nodes":{"charge":{"source":{"kind":"oci","ref":"oci://ghcr.io/acme/charge:1.2.0"}},"notify":{"source":{"kind":"local","path":"notify.wasm"}}}}"#,
+    )
+    .unwrap();
+    fs::write(root.join("flows/support/main.ygtc"), SUPPORT).unwrap();
+}
+
+fn flows(root: &Path) -> Vec<std::path::PathBuf> {
+    vec![
+        root.join("flows/billing.ygtc"),
+        root.join("flows/support/main.ygtc"),
+    ]
+}
+
+#[test]
+fn refresh_reparses_only_stale_flows_and_queries_summaries() {
+    let dir = tempdir().unwrap();
+    write_workspace(dir.path());
+    let mut index = WorkspaceIndex::default();
+    let report = index.refresh(dir.path(), &flows(dir.path())).unwrap();
+    assert_eq!(
+        report.updated,
+        ["flows/billing.ygtc", "flows/support/main.ygtc"]
+    );
+    let billing = &index.flows["flows/billing.ygtc"];
+    assert_eq!(billing.tags, ["payments"]);
+    assert_eq!(
+        billing.nodes[0].component.as_deref(),
+        Some("oci://ghcr.io/acme/charge:1.2.0")
+    );
+
+    let by_component = index.query(&IndexQuery {
+        component: Some("acme/charge".to_string()),
+        ..IndexQuery::default()
+    });
+    assert_eq!(by_component.len(), 1);
+    assert_eq!(by_component[0].node_id, "charge");
+    let emitters = index.query(&IndexQuery {
+        operation: Some("emit".to_string()),
+        ..IndexQuery::default()
+    });
+    assert_eq!(emitters.len(), 2);
+    let tagged = index.query(&IndexQuery {
+        tag: Some("payments".to_string()),
+        operation: Some("emit".to_string()),
+        ..IndexQuery::default()
+    });
+    assert_eq!(tagged.len(), 1);
+    assert_eq!(tagged[0].flow_id, "billing");
+
+    // Round-trips through JSON, and an unchanged workspace needs no parsing.
+    let mut index = WorkspaceIndex::from_json_str(&index.to_json_string()).unwrap();
+    let report = index.refresh(dir.path(), &flows(dir.path())).unwrap();
+    assert!(!report.changed());
+    assert_eq!(report.unchanged, 2);
+
+    fs::write(
+        dir.path().join("flows/support/main.ygtc"),
+        SUPPORT.replace("id: support", "id: helpdesk"),
+    )
+    .unwrap();
+    fs::remove_file(dir.path().join("flows/billing.ygtc")).unwrap();
+    let report = index.refresh(dir.path(), &flows(dir.path())[1..]).unwrap();
+    assert_eq!(report.updated, ["flows/support/main.ygtc"]);
+    assert_eq!(report.removed, ["flows/billing.ygtc"]);
+    assert_eq!(index.flows["flows/support/main.ygtc"].id, "helpdesk");
+
+    let err = WorkspaceIndex::from_json_str(r#"{"version":9,"flows":{}}"#).unwrap_err();
+    assert!(err.to_string().contains("unsupported index version 9"));
+}
+
+#[test]
+fn index_cli_rebuilds_queries_and_follows_writes() {
+    let dir = tempdir().unwrap();
+    write_workspace(dir.path());
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["index", "query", "--dir"])
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stderr(contains("run `greentic-flow index rebuild"));
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["index", "rebuild", "--dir"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(contains("Indexed 2 flow(s)"));
+    assert!(index_path(dir.path()).is_file());
+
+    cargo_bin_cmd!("greentic-flow")
+        .args(["index", "query", "--component", "acme/charge", "--dir"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(contains(
+            "flows/billing.ygtc node 'charge': pay -> oci://ghcr.io/acme/charge:1.2.0",
+        ))
+        .stdout(contains("1 node(s) in 1 flow(s) (2 indexed, 0 refreshed)"));
+
+    // Writes through the CLI update the index in place.
+    cargo_bin_cmd!("greentic-flow")
+        .args(["delete-step", "--write", "--step", "charge", "--flow"])
+        .arg(dir.path().join("flows/billing.ygtc"))
+        .assert()
+        .success();
+    let index = WorkspaceIndex::load(&index_path(dir.path())).unwrap();
+    let billing = &index.flows["flows/billing.ygtc"];
+    assert_eq!(billing.nodes.len(), 1);
+    assert!(billing.nodes.iter().all(|node| node.id != "charge"));
+
+    let output = cargo_bin_cmd!("greentic-flow")
+        .args(["--format", "json", "index", "query", "--tag", "payments"])
+        .arg("--dir")
+        .arg(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let payload: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(payload["flows"], 1);
+    assert_eq!(payload["matches"][0]["node_id"], "notify");
+    assert_eq!(payload["refreshed"], serde_json::json!([]));
+}